@@ -0,0 +1,178 @@
+//! Implementations of [`Arbitrary`] for core DICOM types,
+//! so that they can be generated from unstructured bytes
+//! in fuzz targets and property tests.
+//!
+//! Generated values are always well-formed
+//! (dates, times and value representations are valid),
+//! but are otherwise unconstrained:
+//! the value representation in a header does not need to
+//! match its tag or length.
+use crate::header::{DataElementHeader, Length, Tag, VR};
+use crate::value::{DicomDate, DicomDateTime, DicomTime, PrimitiveValue, C};
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+/// All value representations, in alphabetical order.
+const ALL_VRS: &[VR] = &[
+    VR::AE,
+    VR::AS,
+    VR::AT,
+    VR::CS,
+    VR::DA,
+    VR::DS,
+    VR::DT,
+    VR::FL,
+    VR::FD,
+    VR::IS,
+    VR::LO,
+    VR::LT,
+    VR::OB,
+    VR::OD,
+    VR::OF,
+    VR::OL,
+    VR::OV,
+    VR::OW,
+    VR::PN,
+    VR::SH,
+    VR::SL,
+    VR::SQ,
+    VR::SS,
+    VR::ST,
+    VR::SV,
+    VR::TM,
+    VR::UC,
+    VR::UI,
+    VR::UL,
+    VR::UN,
+    VR::UR,
+    VR::US,
+    VR::UT,
+    VR::UV,
+];
+
+impl<'a> Arbitrary<'a> for Tag {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Tag(u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for VR {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(ALL_VRS).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for Length {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Length(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for DataElementHeader {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(DataElementHeader::new(
+            Tag::arbitrary(u)?,
+            VR::arbitrary(u)?,
+            Length::arbitrary(u)?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for DicomDate {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let year = u.int_in_range(0..=9999)?;
+        let date = match u.int_in_range(0..=2)? {
+            0 => DicomDate::from_y(year),
+            1 => DicomDate::from_ym(year, u.int_in_range(1..=12)?),
+            _ => DicomDate::from_ymd(year, u.int_in_range(1..=12)?, u.int_in_range(1..=31)?),
+        };
+        date.map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for DicomTime {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let hour = u.int_in_range(0..=23)?;
+        let time = match u.int_in_range(0..=3)? {
+            0 => DicomTime::from_h(hour),
+            1 => DicomTime::from_hm(hour, u.int_in_range(0..=59)?),
+            2 => DicomTime::from_hms(hour, u.int_in_range(0..=59)?, u.int_in_range(0..=59)?),
+            _ => DicomTime::from_hms_micro(
+                hour,
+                u.int_in_range(0..=59)?,
+                u.int_in_range(0..=59)?,
+                u.int_in_range(0..=999_999)?,
+            ),
+        };
+        time.map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for DicomDateTime {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let date = DicomDate::arbitrary(u)?;
+        // a time can only follow a complete date
+        if date.day().is_some() && u.arbitrary()? {
+            DicomDateTime::from_date_and_time(date, DicomTime::arbitrary(u)?)
+                .map_err(|_| Error::IncorrectFormat)
+        } else {
+            Ok(DicomDateTime::from_date(date))
+        }
+    }
+}
+
+/// Generate an arbitrary number of values.
+fn multi<'a, T>(u: &mut Unstructured<'a>) -> Result<C<T>>
+where
+    T: Arbitrary<'a>,
+{
+    Vec::arbitrary(u).map(C::from_vec)
+}
+
+impl<'a> Arbitrary<'a> for PrimitiveValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=15)? {
+            0 => PrimitiveValue::Empty,
+            1 => PrimitiveValue::Strs(multi(u)?),
+            2 => PrimitiveValue::Str(String::arbitrary(u)?),
+            3 => PrimitiveValue::Tags(multi(u)?),
+            4 => PrimitiveValue::U8(multi(u)?),
+            5 => PrimitiveValue::I16(multi(u)?),
+            6 => PrimitiveValue::U16(multi(u)?),
+            7 => PrimitiveValue::I32(multi(u)?),
+            8 => PrimitiveValue::U32(multi(u)?),
+            9 => PrimitiveValue::I64(multi(u)?),
+            10 => PrimitiveValue::U64(multi(u)?),
+            11 => PrimitiveValue::F32(multi(u)?),
+            12 => PrimitiveValue::F64(multi(u)?),
+            13 => PrimitiveValue::Date(multi(u)?),
+            14 => PrimitiveValue::DateTime(multi(u)?),
+            _ => PrimitiveValue::Time(multi(u)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_from_bytes() {
+        // the same bytes always produce the same values
+        let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let mut u1 = Unstructured::new(&data);
+        let mut u2 = Unstructured::new(&data);
+        for _ in 0..32 {
+            let header1 = DataElementHeader::arbitrary(&mut u1).unwrap();
+            let header2 = DataElementHeader::arbitrary(&mut u2).unwrap();
+            assert_eq!(header1, header2);
+            assert!(ALL_VRS.contains(&header1.vr));
+            let value1 = PrimitiveValue::arbitrary(&mut u1).unwrap();
+            let value2 = PrimitiveValue::arbitrary(&mut u2).unwrap();
+            assert_eq!(value1.to_bytes(), value2.to_bytes());
+        }
+
+        // running out of data is fine
+        let mut u = Unstructured::new(&[]);
+        assert!(PrimitiveValue::arbitrary(&mut u).is_ok());
+    }
+}
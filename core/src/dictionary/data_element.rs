@@ -205,7 +205,7 @@ impl VirtualVr {
     ///
     /// This method is ill-advised for uses where
     /// the corresponding attribute is important.
-    pub fn relaxed(self) -> VR {
+    pub const fn relaxed(self) -> VR {
         match self {
             VirtualVr::Exact(vr) => vr,
             VirtualVr::Xs => VR::US,
@@ -216,6 +216,57 @@ impl VirtualVr {
     }
 }
 
+/// The value multiplicity (VM) of an attribute:
+/// the number of values which its elements may have,
+/// as specified by the data dictionary.
+///
+/// Empty elements are not subject to the value multiplicity.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum ValueMultiplicity {
+    /// Exactly the given number of values, such as `1` or `6`
+    Exactly(u32),
+    /// Between the two given numbers of values, inclusive, such as `1-3`
+    Between(u32, u32),
+    /// At least the given number of values, such as `1-n` or `2-n`
+    AtLeast(u32),
+    /// A non-zero multiple of the given number of values, such as `2-2n`
+    MultipleOf(u32),
+}
+
+impl ValueMultiplicity {
+    /// Check whether an element with the given number of values
+    /// conforms to this value multiplicity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::dictionary::ValueMultiplicity;
+    /// assert!(ValueMultiplicity::Exactly(6).contains(6));
+    /// assert!(!ValueMultiplicity::Exactly(6).contains(5));
+    /// assert!(ValueMultiplicity::AtLeast(2).contains(7));
+    /// assert!(!ValueMultiplicity::MultipleOf(2).contains(3));
+    /// ```
+    pub const fn contains(self, count: u32) -> bool {
+        match self {
+            ValueMultiplicity::Exactly(n) => count == n,
+            ValueMultiplicity::Between(min, max) => count >= min && count <= max,
+            ValueMultiplicity::AtLeast(min) => count >= min,
+            ValueMultiplicity::MultipleOf(n) => count > 0 && count.is_multiple_of(n),
+        }
+    }
+}
+
+impl std::fmt::Display for ValueMultiplicity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueMultiplicity::Exactly(n) => write!(f, "{}", n),
+            ValueMultiplicity::Between(min, max) => write!(f, "{}-{}", min, max),
+            ValueMultiplicity::AtLeast(min) => write!(f, "{}-n", min),
+            ValueMultiplicity::MultipleOf(n) => write!(f, "{}-{}n", n, n),
+        }
+    }
+}
+
 /// An error during attribute selector parsing
 #[derive(Debug, Snafu)]
 pub struct ParseSelectorError(ParseSelectorErrorInner);
@@ -405,10 +456,21 @@ pub trait DataDictionaryEntry {
     /// in which the representation of a value
     /// depends on surrounding context.
     fn vr(&self) -> VirtualVr;
+
+    /// The value multiplicity of the attribute,
+    /// if known to the dictionary.
+    fn vm(&self) -> Option<ValueMultiplicity> {
+        None
+    }
 }
 
 /// A data type for a dictionary entry with full ownership.
+///
+/// This type is non-exhaustive,
+/// so that more attribute properties can be added in the future.
+/// Create new entries with [`DataDictionaryEntryBuf::new`].
 #[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
 pub struct DataDictionaryEntryBuf {
     /// The attribute tag range
     pub tag: TagRange,
@@ -416,6 +478,26 @@ pub struct DataDictionaryEntryBuf {
     pub alias: String,
     /// The _typical_  value representation of the attribute
     pub vr: VirtualVr,
+    /// The value multiplicity of the attribute
+    pub vm: ValueMultiplicity,
+}
+
+impl DataDictionaryEntryBuf {
+    /// Create a new dictionary entry
+    /// from the attribute's tag range, alias, VR and VM.
+    pub fn new(
+        tag: TagRange,
+        alias: impl Into<String>,
+        vr: VirtualVr,
+        vm: ValueMultiplicity,
+    ) -> Self {
+        DataDictionaryEntryBuf {
+            tag,
+            alias: alias.into(),
+            vr,
+            vm,
+        }
+    }
 }
 
 impl DataDictionaryEntry for DataDictionaryEntryBuf {
@@ -428,10 +510,19 @@ impl DataDictionaryEntry for DataDictionaryEntryBuf {
     fn vr(&self) -> VirtualVr {
         self.vr
     }
+    fn vm(&self) -> Option<ValueMultiplicity> {
+        Some(self.vm)
+    }
 }
 
 /// A data type for a dictionary entry with a string slice for its alias.
+///
+/// This type is non-exhaustive,
+/// so that more attribute properties can be added in the future.
+/// Create new entries with [`DataDictionaryEntryRef::new`],
+/// which can also be used in constant contexts.
 #[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
 pub struct DataDictionaryEntryRef<'a> {
     /// The attribute tag or tag range
     pub tag: TagRange,
@@ -439,6 +530,16 @@ pub struct DataDictionaryEntryRef<'a> {
     pub alias: &'a str,
     /// The extended value representation descriptor of the attribute
     pub vr: VirtualVr,
+    /// The value multiplicity of the attribute
+    pub vm: ValueMultiplicity,
+}
+
+impl<'a> DataDictionaryEntryRef<'a> {
+    /// Create a new dictionary entry
+    /// from the attribute's tag range, alias, VR and VM.
+    pub const fn new(tag: TagRange, alias: &'a str, vr: VirtualVr, vm: ValueMultiplicity) -> Self {
+        DataDictionaryEntryRef { tag, alias, vr, vm }
+    }
 }
 
 impl<'a> DataDictionaryEntry for DataDictionaryEntryRef<'a> {
@@ -451,6 +552,9 @@ impl<'a> DataDictionaryEntry for DataDictionaryEntryRef<'a> {
     fn vr(&self) -> VirtualVr {
         self.vr
     }
+    fn vm(&self) -> Option<ValueMultiplicity> {
+        Some(self.vm)
+    }
 }
 
 /// Utility data structure that resolves to a DICOM attribute tag
@@ -2,6 +2,7 @@
 //! It comprises a variety of basic data types, such as the DICOM attribute tag, the
 //! element header, and element composite types.
 
+use crate::dictionary::{DataDictionary, DataDictionaryEntry};
 use crate::value::{
     CastValueError, ConvertValueError, DataSetSequence, DicomDate, DicomDateTime, DicomTime,
     InMemFragment, PrimitiveValue, Value, C,
@@ -13,6 +14,9 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::str::{from_utf8, FromStr};
 
+#[cfg(feature = "serde")]
+pub use crate::serde_impls::tag_tuple;
+
 /// Error type for issues constructing a sequence item header.
 #[derive(Debug, Snafu)]
 #[non_exhaustive]
@@ -661,6 +665,7 @@ impl<I, P> DataElement<I, P> {
 /// A data structure for a data element header, containing
 /// a tag, value representation and specified length.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataElementHeader {
     /// DICOM tag
     pub tag: Tag,
@@ -708,6 +713,68 @@ impl DataElementHeader {
     pub fn is_non_primitive(&self) -> bool {
         self.vr == VR::SQ || self.length().is_undefined()
     }
+
+    /// Obtain a displayable form of this header
+    /// which includes the keyword of the attribute
+    /// as found in the given data dictionary,
+    /// such as `(0008,0060) CS Modality, len=2`.
+    ///
+    /// The keyword is left out if the tag is not in the dictionary.
+    /// The standard dictionary is available in the `dicom-dictionary-std` crate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElementHeader, Length, Tag, VR};
+    /// # use dicom_core::dictionary::stub::StubDataDictionary;
+    /// let header = DataElementHeader::new(Tag(0x0008, 0x0060), VR::CS, Length(2));
+    /// assert_eq!(
+    ///     header.display_with(&StubDataDictionary).to_string(),
+    ///     "(0008,0060) CS, len=2",
+    /// );
+    /// ```
+    pub fn display_with<'a, D>(&'a self, dict: &'a D) -> DisplayHeaderWith<'a, D>
+    where
+        D: DataDictionary,
+    {
+        DisplayHeaderWith { header: self, dict }
+    }
+}
+
+/// Displays the tag, VR and length of the header,
+/// as in `(0008,0060) CS, len=2`.
+/// Undefined lengths are shown as `U/L`.
+///
+/// See [`display_with`](DataElementHeader::display_with)
+/// to include the attribute keyword.
+impl fmt::Display for DataElementHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}, len={}", self.tag, self.vr, self.len)
+    }
+}
+
+/// A displayable data element header
+/// with the attribute keyword from a data dictionary.
+///
+/// Obtained via [`DataElementHeader::display_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayHeaderWith<'a, D> {
+    header: &'a DataElementHeader,
+    dict: &'a D,
+}
+
+impl<D> fmt::Display for DisplayHeaderWith<'_, D>
+where
+    D: DataDictionary,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let DataElementHeader { tag, vr, len } = *self.header;
+        write!(f, "{} {}", tag, vr)?;
+        if let Some(entry) = self.dict.by_tag(tag) {
+            write!(f, " {}", entry.alias())?;
+        }
+        write!(f, ", len={}", len)
+    }
 }
 
 impl From<SequenceItemHeader> for DataElementHeader {
@@ -724,6 +791,7 @@ impl From<SequenceItemHeader> for DataElementHeader {
 /// If the element represents an item, it will also contain
 /// the specified length.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SequenceItemHeader {
     /// The cursor contains an item.
     Item {
@@ -915,6 +983,40 @@ impl VR {
         let bytes = self.to_string().as_bytes();
         [bytes[0], bytes[1]]
     }
+
+    /// Retrieve the maximum length of a single value of this VR,
+    /// in characters,
+    /// as per [PS3.5 table 6.2-1](https://dicom.nema.org/medical/dicom/2023e/output/chtml/part05/sect_6.2.html).
+    ///
+    /// For PN, the maximum applies to each component group
+    /// (alphabetic, ideographic, and phonetic).
+    /// For DA and TM,
+    /// it applies to each side of a range as used in queries.
+    ///
+    /// Returns `None` for VRs which are not textual,
+    /// and for UC, UR, and UT,
+    /// which are only limited by the value length field.
+    pub fn max_value_length(self) -> Option<u32> {
+        use VR::*;
+        match self {
+            AE => Some(16),
+            AS => Some(4),
+            CS => Some(16),
+            DA => Some(8),
+            DS => Some(16),
+            DT => Some(26),
+            IS => Some(12),
+            LO => Some(64),
+            LT => Some(10240),
+            PN => Some(64),
+            SH => Some(16),
+            ST => Some(1024),
+            TM => Some(14),
+            UI => Some(64),
+            AT | FL | FD | OB | OD | OF | OL | OV | OW | SL | SQ | SS | SV | UC | UL | UN | UR
+            | US | UT | UV => None,
+        }
+    }
 }
 
 /// Obtain the value representation corresponding to the given string.
@@ -1017,6 +1119,20 @@ impl Tag {
     pub fn element(self) -> ElementNumber {
         self.1
     }
+
+    /// Check whether this tag belongs to a private group,
+    /// which is any odd group number other than
+    /// 0001, 0003, 0005, 0007 and FFFF.
+    #[inline]
+    pub fn is_private(self) -> bool {
+        self.0 % 2 == 1 && !matches!(self.0, 0x0001 | 0x0003 | 0x0005 | 0x0007 | 0xFFFF)
+    }
+
+    /// Check whether this tag is a group length tag `(gggg,0000)`.
+    #[inline]
+    pub fn is_group_length(self) -> bool {
+        self.1 == 0
+    }
 }
 
 impl fmt::Debug for Tag {
@@ -1373,6 +1489,17 @@ mod tests {
         assert_eq!(0x0020u16, t.element());
     }
 
+    #[test]
+    fn tag_is_private() {
+        assert!(Tag(0x0009, 0x0010).is_private());
+        assert!(Tag(0x7FE1, 0x1001).is_private());
+        assert!(!Tag(0x0010, 0x0010).is_private());
+        assert!(!Tag(0x0003, 0x0010).is_private());
+        assert!(!Tag(0xFFFE, 0xE000).is_private());
+        assert!(Tag(0x0028, 0x0000).is_group_length());
+        assert!(!Tag(0x0028, 0x0010).is_group_length());
+    }
+
     #[test]
     fn tag_from_u16_array() {
         let t = Tag::from([0x0010u16, 0x0020u16]);
@@ -1541,4 +1668,63 @@ mod tests {
         assert!(e.length().is_undefined());
         assert_eq!(e.fragments().map(|f| f.len()), Some(3));
     }
+
+    #[test]
+    fn display_header() {
+        use crate::dictionary::{DataDictionaryEntryRef, TagRange, ValueMultiplicity, VirtualVr};
+
+        /// A dictionary with just a couple of attributes.
+        struct TestDictionary;
+
+        static ENTRIES: [DataDictionaryEntryRef<'static>; 2] = [
+            DataDictionaryEntryRef {
+                tag: TagRange::Single(Tag(0x0008, 0x0060)),
+                alias: "Modality",
+                vr: VirtualVr::Exact(VR::CS),
+                vm: ValueMultiplicity::Exactly(1),
+            },
+            DataDictionaryEntryRef {
+                tag: TagRange::Single(Tag(0x0008, 0x1140)),
+                alias: "ReferencedImageSequence",
+                vr: VirtualVr::Exact(VR::SQ),
+                vm: ValueMultiplicity::Exactly(1),
+            },
+        ];
+
+        impl DataDictionary for TestDictionary {
+            type Entry = DataDictionaryEntryRef<'static>;
+
+            fn by_name(&self, name: &str) -> Option<&Self::Entry> {
+                ENTRIES.iter().find(|e| e.alias == name)
+            }
+
+            fn by_tag(&self, tag: Tag) -> Option<&Self::Entry> {
+                ENTRIES.iter().find(|e| e.tag == TagRange::Single(tag))
+            }
+        }
+
+        // known tag
+        let header = DataElementHeader::new(Tag(0x0008, 0x0060), VR::CS, Length(2));
+        assert_eq!(header.to_string(), "(0008,0060) CS, len=2");
+        assert_eq!(
+            header.display_with(&TestDictionary).to_string(),
+            "(0008,0060) CS Modality, len=2"
+        );
+
+        // private tag, no keyword
+        let header = DataElementHeader::new(Tag(0x0009, 0x1001), VR::LO, Length(12));
+        assert_eq!(header.to_string(), "(0009,1001) LO, len=12");
+        assert_eq!(
+            header.display_with(&TestDictionary).to_string(),
+            "(0009,1001) LO, len=12"
+        );
+
+        // sequence of undefined length
+        let header = DataElementHeader::new(Tag(0x0008, 0x1140), VR::SQ, Length::UNDEFINED);
+        assert_eq!(header.to_string(), "(0008,1140) SQ, len=U/L");
+        assert_eq!(
+            header.display_with(&TestDictionary).to_string(),
+            "(0008,1140) SQ ReferencedImageSequence, len=U/L"
+        );
+    }
 }
@@ -24,6 +24,16 @@
 //!   with the awareness of multiplicity, representation,
 //!   and the possible presence of sequences.
 //!
+//! With the `arbitrary` feature,
+//! tags, value representations, element headers and primitive values
+//! implement `arbitrary::Arbitrary`,
+//! for use in fuzzing and property testing.
+//!
+//! With the `serde` feature,
+//! the same types implement `serde::Serialize` and `serde::Deserialize`.
+//! Tags are written as strings of 8 hexadecimal digits
+//! and value representations as their two-letter code.
+//!
 
 pub mod dictionary;
 pub mod header;
@@ -31,6 +41,11 @@ pub mod ops;
 pub mod prelude;
 pub mod value;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
 pub use dictionary::DataDictionary;
 pub use header::{DataElement, DataElementHeader, Length, Tag, VR};
 pub use value::{PrimitiveValue, Value as DicomValue};
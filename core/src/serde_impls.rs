@@ -0,0 +1,350 @@
+//! Implementations of [`Serialize`] and [`Deserialize`]
+//! for core DICOM types.
+//!
+//! The representations chosen are meant to be compact
+//! and readable in self-describing formats:
+//!
+//! - [`Tag`] is a string of 8 hexadecimal digits (e.g. `"00080018"`).
+//!   Human readable formats also accept a `[group, element]` pair
+//!   when deserializing,
+//!   and the [`tag_tuple`] module can be used with `#[serde(with)]`
+//!   to always use the pair form.
+//! - [`VR`] is its two-letter code (e.g. `"UI"`).
+//! - [`Length`] is an optional 32-bit number,
+//!   absent when the length is undefined.
+//! - [`PrimitiveValue`] is an externally tagged enum
+//!   named after the variant (e.g. `{"U16": [1, 2]}`).
+//!   Bytes are base64 encoded in human readable formats,
+//!   and dates and times use their DICOM encoded strings.
+use crate::header::{Length, Tag, VR};
+use crate::value::deserialize::{parse_date_partial, parse_datetime_partial, parse_time_partial};
+use crate::value::{
+    DicomDate, DicomDateTime, DicomTime, PrimitiveValue, C,
+};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:04X}{:04X}", self.0, self.1))
+    }
+}
+
+struct TagVisitor;
+
+impl<'de> Visitor<'de> for TagVisitor {
+    type Value = Tag;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a DICOM tag as a string or a (group, element) pair")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Tag, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Tag, A::Error> {
+        let group = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let element = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Tag(group, element))
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(TagVisitor)
+        } else {
+            deserializer.deserialize_str(TagVisitor)
+        }
+    }
+}
+
+/// Serialization of a [`Tag`] as a `(group, element)` pair of numbers,
+/// for use with `#[serde(with = "dicom_core::header::tag_tuple")]`.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::Tag;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Entry {
+///     #[serde(with = "dicom_core::header::tag_tuple")]
+///     tag: Tag,
+/// }
+/// ```
+pub mod tag_tuple {
+    use crate::header::Tag;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serialize a tag as a pair of numbers.
+    pub fn serialize<S: Serializer>(tag: &Tag, serializer: S) -> Result<S::Ok, S::Error> {
+        (tag.0, tag.1).serialize(serializer)
+    }
+
+    /// Deserialize a tag from a pair of numbers.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Tag, D::Error> {
+        <(u16, u16)>::deserialize(deserializer).map(Tag::from)
+    }
+}
+
+impl Serialize for VR {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(VR::to_string(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for VR {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct VrVisitor;
+
+        impl<'de> Visitor<'de> for VrVisitor {
+            type Value = VR;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a two-letter value representation code")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<VR, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(VrVisitor)
+    }
+}
+
+impl Serialize for Length {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Option::<u32>::deserialize(deserializer)?.map_or(Length::UNDEFINED, Length))
+    }
+}
+
+/// Implement serialization through the DICOM encoded string.
+macro_rules! impl_serde_encoded {
+    ($ty:ty, $expecting:expr, $parse:expr) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.to_encoded())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct EncodedVisitor;
+
+                impl<'de> Visitor<'de> for EncodedVisitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str($expecting)
+                    }
+
+                    fn visit_str<E: de::Error>(self, v: &str) -> Result<$ty, E> {
+                        let parse: fn(&[u8]) -> Option<$ty> = $parse;
+                        parse(v.as_bytes())
+                            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &$expecting))
+                    }
+                }
+
+                deserializer.deserialize_str(EncodedVisitor)
+            }
+        }
+    };
+}
+
+impl_serde_encoded!(DicomDate, "a DICOM date string", |v| {
+    match parse_date_partial(v) {
+        Ok((date, [])) => Some(date),
+        _ => None,
+    }
+});
+
+impl_serde_encoded!(DicomTime, "a DICOM time string", |v| {
+    match parse_time_partial(v) {
+        Ok((time, [])) => Some(time),
+        _ => None,
+    }
+});
+
+impl_serde_encoded!(DicomDateTime, "a DICOM date-time string", |v| {
+    parse_datetime_partial(v).ok()
+});
+
+/// A sequence of borrowed strings.
+struct StrSeq<'a>(&'a [String]);
+
+impl Serialize for StrSeq<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for s in self.0 {
+            seq.serialize_element(s.as_str())?;
+        }
+        seq.end()
+    }
+}
+
+/// Borrowed bytes, base64 encoded in human readable formats.
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&BASE64.encode(self.0))
+        } else {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+}
+
+/// Owned bytes, base64 encoded in human readable formats.
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a base64 string or a byte array")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<ByteBuf, E> {
+                BASE64.decode(v).map(ByteBuf).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ByteBuf, A::Error> {
+                let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(b) = seq.next_element()? {
+                    out.push(b);
+                }
+                Ok(ByteBuf(out))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+}
+
+// The two enums below mirror `PrimitiveValue` for serialization.
+// Variants must be kept in the same order,
+// since binary formats identify them by index.
+
+#[derive(Serialize)]
+#[serde(rename = "PrimitiveValue")]
+enum PrimitiveValueRef<'a> {
+    Empty,
+    Strs(StrSeq<'a>),
+    Str(&'a str),
+    Tags(&'a [Tag]),
+    U8(Bytes<'a>),
+    I16(&'a [i16]),
+    U16(&'a [u16]),
+    I32(&'a [i32]),
+    U32(&'a [u32]),
+    I64(&'a [i64]),
+    U64(&'a [u64]),
+    F32(&'a [f32]),
+    F64(&'a [f64]),
+    Date(&'a [DicomDate]),
+    DateTime(&'a [DicomDateTime]),
+    Time(&'a [DicomTime]),
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "PrimitiveValue")]
+enum PrimitiveValueOwned {
+    Empty,
+    Strs(Vec<String>),
+    Str(String),
+    Tags(Vec<Tag>),
+    U8(ByteBuf),
+    I16(Vec<i16>),
+    U16(Vec<u16>),
+    I32(Vec<i32>),
+    U32(Vec<u32>),
+    I64(Vec<i64>),
+    U64(Vec<u64>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    Date(Vec<DicomDate>),
+    DateTime(Vec<DicomDateTime>),
+    Time(Vec<DicomTime>),
+}
+
+impl Serialize for PrimitiveValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match self {
+            PrimitiveValue::Empty => PrimitiveValueRef::Empty,
+            PrimitiveValue::Strs(v) => PrimitiveValueRef::Strs(StrSeq(v)),
+            PrimitiveValue::Str(v) => PrimitiveValueRef::Str(v),
+            PrimitiveValue::Tags(v) => PrimitiveValueRef::Tags(v),
+            PrimitiveValue::U8(v) => PrimitiveValueRef::U8(Bytes(v)),
+            PrimitiveValue::I16(v) => PrimitiveValueRef::I16(v),
+            PrimitiveValue::U16(v) => PrimitiveValueRef::U16(v),
+            PrimitiveValue::I32(v) => PrimitiveValueRef::I32(v),
+            PrimitiveValue::U32(v) => PrimitiveValueRef::U32(v),
+            PrimitiveValue::I64(v) => PrimitiveValueRef::I64(v),
+            PrimitiveValue::U64(v) => PrimitiveValueRef::U64(v),
+            PrimitiveValue::F32(v) => PrimitiveValueRef::F32(v),
+            PrimitiveValue::F64(v) => PrimitiveValueRef::F64(v),
+            PrimitiveValue::Date(v) => PrimitiveValueRef::Date(v),
+            PrimitiveValue::DateTime(v) => PrimitiveValueRef::DateTime(v),
+            PrimitiveValue::Time(v) => PrimitiveValueRef::Time(v),
+        };
+        value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PrimitiveValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match PrimitiveValueOwned::deserialize(deserializer)? {
+            PrimitiveValueOwned::Empty => PrimitiveValue::Empty,
+            PrimitiveValueOwned::Strs(v) => {
+                PrimitiveValue::Strs(v.into_iter().collect())
+            }
+            PrimitiveValueOwned::Str(v) => PrimitiveValue::Str(v),
+            PrimitiveValueOwned::Tags(v) => PrimitiveValue::Tags(C::from_vec(v)),
+            PrimitiveValueOwned::U8(v) => PrimitiveValue::U8(C::from_vec(v.0)),
+            PrimitiveValueOwned::I16(v) => PrimitiveValue::I16(C::from_vec(v)),
+            PrimitiveValueOwned::U16(v) => PrimitiveValue::U16(C::from_vec(v)),
+            PrimitiveValueOwned::I32(v) => PrimitiveValue::I32(C::from_vec(v)),
+            PrimitiveValueOwned::U32(v) => PrimitiveValue::U32(C::from_vec(v)),
+            PrimitiveValueOwned::I64(v) => PrimitiveValue::I64(C::from_vec(v)),
+            PrimitiveValueOwned::U64(v) => PrimitiveValue::U64(C::from_vec(v)),
+            PrimitiveValueOwned::F32(v) => PrimitiveValue::F32(C::from_vec(v)),
+            PrimitiveValueOwned::F64(v) => PrimitiveValue::F64(C::from_vec(v)),
+            PrimitiveValueOwned::Date(v) => PrimitiveValue::Date(C::from_vec(v)),
+            PrimitiveValueOwned::DateTime(v) => PrimitiveValue::DateTime(C::from_vec(v)),
+            PrimitiveValueOwned::Time(v) => PrimitiveValue::Time(C::from_vec(v)),
+        })
+    }
+}
@@ -381,6 +381,32 @@ pub fn parse_datetime(buf: &[u8], dt_utc_offset: FixedOffset) -> Result<DateTime
         .context(InvalidDateTimeZoneSnafu)
 }
 
+/// Decode an offset from UTC in the form `&ZZXX`
+/// (as in the suffix of a DT value
+/// or in _Timezone Offset From UTC_ (0008,0201))
+/// into a `chrono::FixedOffset`.
+pub fn parse_time_zone(buf: &[u8]) -> Result<FixedOffset> {
+    if buf.len() < 5 {
+        return UnexpectedEndOfElementSnafu.fail();
+    }
+    let tz_sign = buf[0];
+    let buf = &buf[1..];
+    let tz_h: u32 = read_number(&buf[0..2])?;
+    let tz_m: u32 = read_number(&buf[2..4])?;
+    let s = (tz_h * 60 + tz_m) * 60;
+    match tz_sign {
+        b'+' => {
+            check_component(DateComponent::UtcEast, &s).context(InvalidComponentSnafu)?;
+            FixedOffset::east_opt(s as i32).context(SecsOutOfBoundsSnafu { secs: s as i32 })
+        }
+        b'-' => {
+            check_component(DateComponent::UtcWest, &s).context(InvalidComponentSnafu)?;
+            FixedOffset::west_opt(s as i32).context(SecsOutOfBoundsSnafu { secs: s as i32 })
+        }
+        c => InvalidTimeZoneSignTokenSnafu { value: c }.fail(),
+    }
+}
+
 /// Decode the text from the byte slice into a [`DicomDateTime`] value,
 /// which allows for missing Date / Time components.
 ///
@@ -420,30 +446,7 @@ pub fn parse_datetime_partial(buf: &[u8]) -> Result<DicomDateTime> {
 
     let time_zone = match buf.len() {
         0 => None,
-        len if len > 4 => {
-            let tz_sign = buf[0];
-            let buf = &buf[1..];
-            let tz_h: u32 = read_number(&buf[0..2])?;
-            let tz_m: u32 = read_number(&buf[2..4])?;
-            let s = (tz_h * 60 + tz_m) * 60;
-            match tz_sign {
-                b'+' => {
-                    check_component(DateComponent::UtcEast, &s).context(InvalidComponentSnafu)?;
-                    Some(
-                        FixedOffset::east_opt(s as i32)
-                            .context(SecsOutOfBoundsSnafu { secs: s as i32 })?,
-                    )
-                }
-                b'-' => {
-                    check_component(DateComponent::UtcWest, &s).context(InvalidComponentSnafu)?;
-                    Some(
-                        FixedOffset::west_opt(s as i32)
-                            .context(SecsOutOfBoundsSnafu { secs: s as i32 })?,
-                    )
-                }
-                c => return InvalidTimeZoneSignTokenSnafu { value: c }.fail(),
-            }
-        }
+        len if len > 4 => Some(parse_time_zone(buf)?),
         _ => return UnexpectedEndOfElementSnafu.fail(),
     };
 
@@ -931,4 +934,32 @@ mod tests {
         assert!(parse_datetime_partial(b"20171130101010.204+01").is_err());
         assert!(parse_datetime_partial(b"20171130101010.204+011").is_err());
     }
+
+    #[test]
+    fn test_parse_time_zone() {
+        assert_eq!(
+            parse_time_zone(b"+0530").unwrap(),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap()
+        );
+        assert_eq!(
+            parse_time_zone(b"-1000").unwrap(),
+            FixedOffset::west_opt(10 * 3600).unwrap()
+        );
+        assert_eq!(
+            parse_time_zone(b"+0000").unwrap(),
+            FixedOffset::east_opt(0).unwrap()
+        );
+        assert!(matches!(
+            parse_time_zone(b"0530"),
+            Err(Error::UnexpectedEndOfElement { .. })
+        ));
+        assert!(matches!(
+            parse_time_zone(b"*0530"),
+            Err(Error::InvalidTimeZoneSignToken { .. })
+        ));
+        assert!(matches!(
+            parse_time_zone(b"-1300"),
+            Err(Error::InvalidComponent { .. })
+        ));
+    }
 }
@@ -0,0 +1,401 @@
+//! Safe display of data element values.
+//!
+//! See [`DisplayValue`].
+use crate::header::VR;
+use crate::value::PrimitiveValue;
+use std::fmt::{self, Display, Formatter, Write};
+use std::mem::size_of;
+
+/// The maximum number of elements shown in the preview of a binary value.
+pub const BINARY_PREVIEW_LEN: usize = 16;
+
+/// An adapter for displaying a value safely,
+/// such as in a terminal or in a log.
+///
+/// - Control characters in text are escaped (e.g. `\x1b` for ESC).
+/// - Byte sequences which are not valid UTF-8
+///   are shown as the replacement character `�`.
+/// - Values of binary VRs (OB, OW, UN, ...)
+///   are shown as a short hexadecimal preview
+///   of at most [`BINARY_PREVIEW_LEN`] elements.
+/// - Output longer than the [maximum width](DisplayValue::with_max_width)
+///   is cut with an ellipsis,
+///   followed by the number of bytes left out,
+///   as in `… (+1024 bytes)`.
+///
+/// Values are written piece by piece,
+/// so displaying a value does not allocate memory
+/// in proportion to its length.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{PrimitiveValue, VR};
+/// use dicom_core::value::DisplayValue;
+///
+/// let value = PrimitiveValue::from("Doe^John\u{1b}[31m");
+/// assert_eq!(
+///     DisplayValue::new(&value, VR::PN).to_string(),
+///     "Doe^John\\x1b[31m",
+/// );
+/// assert_eq!(
+///     DisplayValue::new(&value, VR::PN).with_max_width(4).to_string(),
+///     "Doe^… (+9 bytes)",
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DisplayValue<'a> {
+    source: Source<'a>,
+    max_width: Option<usize>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Source<'a> {
+    Value(&'a PrimitiveValue, VR),
+    Text(&'a str),
+    TextBytes(&'a [u8]),
+    Binary(&'a [u8]),
+}
+
+impl<'a> DisplayValue<'a> {
+    /// Display a primitive value of the given value representation.
+    pub fn new(value: &'a PrimitiveValue, vr: VR) -> Self {
+        DisplayValue {
+            source: Source::Value(value, vr),
+            max_width: None,
+        }
+    }
+
+    /// Display a piece of text.
+    pub fn text(text: &'a str) -> Self {
+        DisplayValue {
+            source: Source::Text(text),
+            max_width: None,
+        }
+    }
+
+    /// Display text bytes which might not be valid UTF-8,
+    /// such as those of a corrupted text element.
+    pub fn text_bytes(bytes: &'a [u8]) -> Self {
+        DisplayValue {
+            source: Source::TextBytes(bytes),
+            max_width: None,
+        }
+    }
+
+    /// Display raw binary data as a hexadecimal preview.
+    pub fn binary(bytes: &'a [u8]) -> Self {
+        DisplayValue {
+            source: Source::Binary(bytes),
+            max_width: None,
+        }
+    }
+
+    /// Set the maximum number of characters of the value to display,
+    /// before the ellipsis and the number of bytes left out.
+    ///
+    /// By default, text is not cut.
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+}
+
+impl Display for DisplayValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut out = Output {
+            f,
+            max_width: self.max_width,
+            width: 0,
+            bytes: 0,
+        };
+        let (complete, total) = match self.source {
+            Source::Text(text) => (write_text(&mut out, text)?, text.len()),
+            Source::TextBytes(bytes) => (write_text_bytes(&mut out, bytes)?, bytes.len()),
+            Source::Binary(bytes) => (
+                write_hex(&mut out, bytes, |b| Hex(*b as u64, 2))?,
+                bytes.len(),
+            ),
+            Source::Value(value, vr) => (
+                write_value(&mut out, value, vr)?,
+                value.calculate_byte_len(),
+            ),
+        };
+        if complete {
+            Ok(())
+        } else {
+            write!(out.f, "… (+{} bytes)", total.saturating_sub(out.bytes))
+        }
+    }
+}
+
+/// The state of the output of a value,
+/// written one piece at a time.
+struct Output<'a, 'b> {
+    f: &'a mut Formatter<'b>,
+    max_width: Option<usize>,
+    /// number of characters written
+    width: usize,
+    /// number of source bytes written
+    bytes: usize,
+}
+
+impl Output<'_, '_> {
+    /// Write a piece of output representing the given number of source bytes.
+    /// Returns `false` if the piece does not fit in the maximum width.
+    fn piece(&mut self, piece: impl Display, bytes: usize) -> Result<bool, fmt::Error> {
+        let width = char_count(&piece);
+        if let Some(max_width) = self.max_width {
+            if self.width + width > max_width {
+                return Ok(false);
+            }
+        }
+        write!(self.f, "{}", piece)?;
+        self.width += width;
+        self.bytes += bytes;
+        Ok(true)
+    }
+}
+
+/// Count the characters of a displayable piece without allocating.
+fn char_count(piece: &impl Display) -> usize {
+    struct Count(usize);
+    impl Write for Count {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.chars().count();
+            Ok(())
+        }
+    }
+    let mut count = Count(0);
+    let _ = write!(count, "{}", piece);
+    count.0
+}
+
+/// A character with control characters escaped.
+struct Escaped(char);
+
+impl Display for Escaped {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.is_control() {
+            write!(f, "\\x{:02x}", self.0 as u32)
+        } else {
+            f.write_char(self.0)
+        }
+    }
+}
+
+/// A number in hexadecimal with the given number of digits.
+struct Hex(u64, usize);
+
+impl Display for Hex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:01$X}", self.0, self.1)
+    }
+}
+
+fn write_text(out: &mut Output, text: &str) -> Result<bool, fmt::Error> {
+    for c in text.chars() {
+        if !out.piece(Escaped(c), c.len_utf8())? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn write_text_bytes(out: &mut Output, mut bytes: &[u8]) -> Result<bool, fmt::Error> {
+    // validate a bounded window at a time,
+    // so that the work done is not proportional to the whole value
+    const WINDOW: usize = 1024;
+    while !bytes.is_empty() {
+        let window = &bytes[..bytes.len().min(WINDOW)];
+        let (valid, invalid_len) = match std::str::from_utf8(window) {
+            Ok(text) => (text, 0),
+            Err(e) => {
+                let valid = std::str::from_utf8(&window[..e.valid_up_to()]).unwrap();
+                let invalid_len = match e.error_len() {
+                    Some(len) => len,
+                    // a character cut by the end of the window
+                    None if window.len() < bytes.len() && e.valid_up_to() > 0 => 0,
+                    None => window.len() - e.valid_up_to(),
+                };
+                (valid, invalid_len)
+            }
+        };
+        if !write_text(out, valid)? {
+            return Ok(false);
+        }
+        if invalid_len > 0 && !out.piece(char::REPLACEMENT_CHARACTER, invalid_len)? {
+            return Ok(false);
+        }
+        bytes = &bytes[valid.len() + invalid_len..];
+    }
+    Ok(true)
+}
+
+/// Write a preview of binary data,
+/// with each element in hexadecimal.
+fn write_hex<T>(
+    out: &mut Output,
+    values: &[T],
+    hex: impl Fn(&T) -> Hex,
+) -> Result<bool, fmt::Error> {
+    let size = size_of::<T>();
+    for (i, value) in values.iter().enumerate() {
+        if i == BINARY_PREVIEW_LEN {
+            return Ok(false);
+        }
+        if i > 0 && !out.piece(' ', 0)? {
+            return Ok(false);
+        }
+        if !out.piece(hex(value), size)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Write a backslash-delimited list of values,
+/// where the delimiter stands for the given number of source bytes.
+fn write_list<T>(
+    out: &mut Output,
+    values: &[T],
+    delimiter_bytes: usize,
+    mut write: impl FnMut(&mut Output, &T) -> Result<bool, fmt::Error>,
+) -> Result<bool, fmt::Error> {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 && !out.piece('\\', delimiter_bytes)? {
+            return Ok(false);
+        }
+        if !write(out, value)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn write_value(out: &mut Output, value: &PrimitiveValue, vr: VR) -> Result<bool, fmt::Error> {
+    use PrimitiveValue::*;
+
+    let binary = matches!(
+        vr,
+        VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN
+    );
+    let number = |out: &mut Output, n: &dyn Display, size: usize| out.piece(n, size);
+    match value {
+        Empty => Ok(true),
+        Str(text) => write_text(out, text),
+        Strs(texts) => write_list(out, texts, 1, |out, text| write_text(out, text)),
+        U8(values) if binary => write_hex(out, values, |n| Hex(*n as u64, 2)),
+        U16(values) if binary => write_hex(out, values, |n| Hex(*n as u64, 4)),
+        U32(values) if binary => write_hex(out, values, |n| Hex(*n as u64, 8)),
+        U64(values) if binary => write_hex(out, values, |n| Hex(*n, 16)),
+        U8(values) => write_list(out, values, 0, |out, n| number(out, n, 1)),
+        U16(values) => write_list(out, values, 0, |out, n| number(out, n, 2)),
+        I16(values) => write_list(out, values, 0, |out, n| number(out, n, 2)),
+        U32(values) => write_list(out, values, 0, |out, n| number(out, n, 4)),
+        I32(values) => write_list(out, values, 0, |out, n| number(out, n, 4)),
+        U64(values) => write_list(out, values, 0, |out, n| number(out, n, 8)),
+        I64(values) => write_list(out, values, 0, |out, n| number(out, n, 8)),
+        F32(values) if binary => write_preview(out, values, 4),
+        F64(values) if binary => write_preview(out, values, 8),
+        F32(values) => write_list(out, values, 0, |out, n| number(out, n, 4)),
+        F64(values) => write_list(out, values, 0, |out, n| number(out, n, 8)),
+        Tags(values) => write_list(out, values, 0, |out, tag| number(out, tag, 4)),
+        Date(values) => write_list(out, values, 1, |out, date| {
+            number(out, date, date.to_encoded().len())
+        }),
+        Time(values) => write_list(out, values, 1, |out, time| {
+            number(out, time, time.to_encoded().len())
+        }),
+        DateTime(values) => write_list(out, values, 1, |out, dt| {
+            number(out, dt, dt.to_encoded().len())
+        }),
+    }
+}
+
+/// Write a preview of the first few floating point numbers of a binary value.
+fn write_preview<T: Display>(
+    out: &mut Output,
+    values: &[T],
+    size: usize,
+) -> Result<bool, fmt::Error> {
+    let shown = &values[..values.len().min(BINARY_PREVIEW_LEN)];
+    Ok(write_list(out, shown, 0, |out, n| out.piece(n, size))? && shown.len() == values.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dicom_value;
+
+    #[test]
+    fn display_text_with_control_characters() {
+        let value = PrimitiveValue::from("\u{1b}[2J\u{1b}]0;pwned\u{7}Doe^John");
+        assert_eq!(
+            DisplayValue::new(&value, VR::PN).to_string(),
+            "\\x1b[2J\\x1b]0;pwned\\x07Doe^John"
+        );
+        // escapes count towards the width
+        assert_eq!(
+            DisplayValue::new(&value, VR::PN)
+                .with_max_width(9)
+                .to_string(),
+            "\\x1b[2J… (+18 bytes)"
+        );
+
+        let value = dicom_value!(Strs, ["ONE\n", "TWO"]);
+        assert_eq!(
+            DisplayValue::new(&value, VR::CS).to_string(),
+            "ONE\\x0a\\TWO"
+        );
+    }
+
+    #[test]
+    fn display_invalid_utf8() {
+        let bytes = b"Doe^J\xffohn\xe2\x82";
+        assert_eq!(DisplayValue::text_bytes(bytes).to_string(), "Doe^J�ohn�");
+        assert_eq!(
+            DisplayValue::text_bytes(bytes)
+                .with_max_width(6)
+                .to_string(),
+            "Doe^J�… (+5 bytes)"
+        );
+
+        // characters across validation windows are kept
+        let text = "é".repeat(1000);
+        assert_eq!(DisplayValue::text_bytes(text.as_bytes()).to_string(), text);
+    }
+
+    #[test]
+    fn display_numbers() {
+        let value = dicom_value!(U16, [512, 256]);
+        assert_eq!(DisplayValue::new(&value, VR::US).to_string(), "512\\256");
+        assert_eq!(
+            DisplayValue::new(&value, VR::US)
+                .with_max_width(5)
+                .to_string(),
+            "512\\… (+2 bytes)"
+        );
+        assert_eq!(DisplayValue::new(&value, VR::OW).to_string(), "0200 0100");
+    }
+
+    #[test]
+    fn display_large_binary_preview() {
+        let data = vec![0xAB_u8; 10 * 1024 * 1024];
+        let value = PrimitiveValue::from(data);
+
+        let preview = DisplayValue::new(&value, VR::OB).to_string();
+        assert_eq!(
+            preview,
+            "AB AB AB AB AB AB AB AB AB AB AB AB AB AB AB AB… (+10485744 bytes)"
+        );
+
+        let preview = DisplayValue::new(&value, VR::OB)
+            .with_max_width(8)
+            .to_string();
+        assert_eq!(preview, "AB AB AB… (+10485757 bytes)");
+
+        let bytes = [1_u8, 2, 3];
+        assert_eq!(DisplayValue::binary(&bytes).to_string(), "01 02 03");
+    }
+}
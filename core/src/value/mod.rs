@@ -6,6 +6,7 @@ use smallvec::SmallVec;
 use std::{borrow::Cow, str::FromStr};
 
 pub mod deserialize;
+pub mod display;
 pub mod fragments;
 pub mod partial;
 pub mod person_name;
@@ -14,8 +15,9 @@ pub mod range;
 pub mod serialize;
 
 pub use self::deserialize::Error as DeserializeError;
+pub use self::display::DisplayValue;
 pub use self::partial::{DicomDate, DicomDateTime, DicomTime, PreciseDateTime};
-pub use self::person_name::PersonName;
+pub use self::person_name::{PersonName, PersonNameGroups};
 pub use self::range::{AsRange, DateRange, DateTimeRange, TimeRange};
 
 pub use self::primitive::{
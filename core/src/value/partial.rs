@@ -692,6 +692,20 @@ impl DicomDateTime {
         self.time_zone.is_some()
     }
 
+    /// Returns this date-time value with the given time-zone
+    /// if it does not contain one already.
+    ///
+    /// This is how a default offset from UTC,
+    /// such as that of _Timezone Offset From UTC_ (0008,0201),
+    /// applies to date-time values without their own suffix.
+    /// A time-zone already in the value is kept as is.
+    pub fn with_default_time_zone(self, time_zone: FixedOffset) -> DicomDateTime {
+        DicomDateTime {
+            time_zone: Some(self.time_zone.unwrap_or(time_zone)),
+            ..self
+        }
+    }
+
     /** Retrieves a reference to the internal offset value */
     #[deprecated(since = "0.7.0", note = "Use `time_zone` instead")]
     pub fn offset(&self) {}
@@ -1424,4 +1438,35 @@ mod tests {
         .unwrap()
         .is_precise());
     }
+
+    #[test]
+    fn test_dicom_datetime_default_time_zone() {
+        let ist = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let date = DicomDate::from_ymd(2024, 3, 1).unwrap();
+        let time = DicomTime::from_hms_micro(10, 0, 0, 0).unwrap();
+
+        // applied to a value without a time-zone
+        let dt = DicomDateTime::from_date_and_time(date, time)
+            .unwrap()
+            .with_default_time_zone(ist);
+        assert_eq!(dt.time_zone(), Some(&ist));
+        assert_eq!(
+            dt.to_precise_datetime()
+                .unwrap()
+                .into_datetime()
+                .unwrap()
+                .naive_utc(),
+            NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+                NaiveTime::from_hms_opt(4, 30, 0).unwrap()
+            )
+        );
+
+        // a time-zone in the value is kept
+        let dt = DicomDateTime::from_date_and_time_with_time_zone(date, time, utc)
+            .unwrap()
+            .with_default_time_zone(ist);
+        assert_eq!(dt.time_zone(), Some(&utc));
+    }
 }
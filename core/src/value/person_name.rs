@@ -131,8 +131,13 @@ impl<'a> PersonName<'a> {
     /// into its respective components.
     /// When passing a text value to this function,
     /// ensure that it contains a single DICOM formatted name.
+    ///
+    /// Only the first component group (the alphabetic representation)
+    /// is considered.
+    /// See [`PersonNameGroups`] for access to the other component groups.
     pub fn from_text(slice: &'a str) -> PersonName<'a> {
-        let mut parts = slice.trim().split('^');
+        let alphabetic = slice.split('=').next().unwrap_or_default();
+        let mut parts = alphabetic.trim().split('^');
 
         macro_rules! get_component {
             () => {
@@ -165,6 +170,102 @@ impl<'a> PersonName<'a> {
     }
 }
 
+/// The component groups of a DICOM _Person Name_,
+/// delimited by `'='` in its DICOM string representation.
+///
+/// Each group holds the same name in a different representation:
+/// alphabetic (single-byte characters),
+/// ideographic (such as Kanji or Hanja),
+/// and phonetic (such as Hiragana or Hangul).
+/// When the data set declares multiple character sets,
+/// the ideographic and phonetic groups
+/// are typically the ones using code extensions.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::value::person_name::PersonNameGroups;
+/// let name = PersonNameGroups::from_text("Yamada^Tarou=山田^太郎=やまだ^たろう");
+/// assert_eq!(name.alphabetic().and_then(|n| n.given()), Some("Tarou"));
+/// assert_eq!(name.ideographic().and_then(|n| n.family()), Some("山田"));
+/// assert_eq!(name.phonetic().and_then(|n| n.family()), Some("やまだ"));
+/// assert_eq!(&name.to_dicom_string(), "Yamada^Tarou=山田^太郎=やまだ^たろう");
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct PersonNameGroups<'a> {
+    alphabetic: Option<PersonName<'a>>,
+    ideographic: Option<PersonName<'a>>,
+    phonetic: Option<PersonName<'a>>,
+}
+
+impl<'a> PersonNameGroups<'a> {
+    /// Create a person name from its component groups.
+    pub fn new(
+        alphabetic: Option<PersonName<'a>>,
+        ideographic: Option<PersonName<'a>>,
+        phonetic: Option<PersonName<'a>>,
+    ) -> Self {
+        PersonNameGroups {
+            alphabetic,
+            ideographic,
+            phonetic,
+        }
+    }
+
+    /// Obtain the component groups of a person name
+    /// by interpreting `slice` as a DICOM formatted string.
+    ///
+    /// Empty component groups are `None`.
+    pub fn from_text(slice: &'a str) -> Self {
+        let mut groups = slice.trim_end().split('=').map(|group| {
+            if group.trim().is_empty() {
+                None
+            } else {
+                Some(PersonName::from_text(group))
+            }
+        });
+        PersonNameGroups {
+            alphabetic: groups.next().flatten(),
+            ideographic: groups.next().flatten(),
+            phonetic: groups.next().flatten(),
+        }
+    }
+
+    /// Retrieve the alphabetic representation of the name
+    pub fn alphabetic(&self) -> Option<&PersonName<'a>> {
+        self.alphabetic.as_ref()
+    }
+
+    /// Retrieve the ideographic representation of the name
+    pub fn ideographic(&self) -> Option<&PersonName<'a>> {
+        self.ideographic.as_ref()
+    }
+
+    /// Retrieve the phonetic representation of the name
+    pub fn phonetic(&self) -> Option<&PersonName<'a>> {
+        self.phonetic.as_ref()
+    }
+
+    /// Convert the person name into a DICOM formatted string.
+    ///
+    /// Component groups are interspersed with a `'='` separator.
+    /// Leading empty groups produce a separator,
+    /// while trailing groups do not.
+    pub fn to_dicom_string(&self) -> String {
+        let groups = [self.alphabetic, self.ideographic, self.phonetic];
+        let len = groups
+            .iter()
+            .rposition(Option::is_some)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        groups[..len]
+            .iter()
+            .map(|group| group.map(|name| name.to_dicom_string()).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("=")
+    }
+}
+
 impl<'a> PersonNameBuilder<'a> {
     pub fn new() -> PersonNameBuilder<'a> {
         PersonNameBuilder {
@@ -404,4 +505,59 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn person_name_component_groups() {
+        // PS3.5 H.3.1: Example 1
+        let text = "Yamada^Tarou=山田^太郎=やまだ^たろう";
+        let name = PersonNameGroups::from_text(text);
+        assert_eq!(
+            name.alphabetic(),
+            Some(
+                &PersonName::builder()
+                    .with_family("Yamada")
+                    .with_given("Tarou")
+                    .build()
+            )
+        );
+        assert_eq!(
+            name.ideographic(),
+            Some(
+                &PersonName::builder()
+                    .with_family("山田")
+                    .with_given("太郎")
+                    .build()
+            )
+        );
+        assert_eq!(
+            name.phonetic(),
+            Some(
+                &PersonName::builder()
+                    .with_family("やまだ")
+                    .with_given("たろう")
+                    .build()
+            )
+        );
+        assert_eq!(name.to_dicom_string(), text);
+
+        // the other groups do not leak into the alphabetic one
+        assert_eq!(
+            PersonName::from_text(text),
+            PersonName::builder()
+                .with_family("Yamada")
+                .with_given("Tarou")
+                .build()
+        );
+
+        // empty groups
+        let name = PersonNameGroups::from_text("=山田^太郎");
+        assert_eq!(name.alphabetic(), None);
+        assert_eq!(name.ideographic().and_then(|n| n.given()), Some("太郎"));
+        assert_eq!(name.phonetic(), None);
+        assert_eq!(name.to_dicom_string(), "=山田^太郎");
+
+        let name = PersonNameGroups::from_text("Wang^XiaoDong=");
+        assert_eq!(name.ideographic(), None);
+        assert_eq!(name.to_dicom_string(), "Wang^XiaoDong");
+    }
 }
@@ -3,7 +3,7 @@
 //! See [`PrimitiveValue`](./enum.PrimitiveValue.html).
 
 use super::{AsRange, DicomValueType};
-use crate::header::{HasLength, Length, Tag};
+use crate::header::{HasLength, Length, Tag, VR};
 use crate::value::partial::{DateComponent, DicomDate, DicomDateTime, DicomTime};
 use crate::value::person_name::PersonName;
 use crate::value::range::{AmbiguousDtRangeParser, DateRange, DateTimeRange, TimeRange};
@@ -320,7 +320,7 @@ impl From<String> for PrimitiveValue {
 
 impl From<&str> for PrimitiveValue {
     fn from(value: &str) -> Self {
-        PrimitiveValue::Str(value.to_owned())
+        PrimitiveValue::Str(value.into())
     }
 }
 
@@ -410,6 +410,24 @@ impl PrimitiveValue {
         PrimitiveValue::I32(C::from_elem(value, 1))
     }
 
+    /// Create a textual value from a sequence of strings,
+    /// one per value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::PrimitiveValue;
+    /// let value = PrimitiveValue::new_strs(["ORIGINAL", "PRIMARY"]);
+    /// assert_eq!(value.multiplicity(), 2);
+    /// assert_eq!(value.to_str(), "ORIGINAL\\PRIMARY");
+    /// ```
+    pub fn new_strs<T>(strings: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<String>,
+    {
+        PrimitiveValue::Strs(strings.into_iter().map(T::into).collect())
+    }
+
     /// Obtain the number of individual elements. This number may not
     /// match the DICOM value multiplicity in some value representations.
     pub fn multiplicity(&self) -> u32 {
@@ -434,6 +452,49 @@ impl PrimitiveValue {
         }
     }
 
+    /// Obtain the number of values of an element
+    /// with this value and the given value representation,
+    /// as per the DICOM value multiplicity.
+    ///
+    /// Unlike [`multiplicity`](Self::multiplicity),
+    /// text is split into its backslash-separated values
+    /// (except in value representations which hold a single value,
+    /// such as _LT_),
+    /// and binary numbers held as raw bytes
+    /// are counted from the size of a number of the given VR.
+    /// Values of the other binary VRs (such as _OB_ or _OW_)
+    /// always count as a single value.
+    /// Empty values have no values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{dicom_value, PrimitiveValue, VR};
+    /// let value = PrimitiveValue::from("1\\0\\0\\0\\1\\0");
+    /// assert_eq!(value.multiplicity(), 1);
+    /// assert_eq!(value.multiplicity_as(VR::DS), 6);
+    ///
+    /// let value = dicom_value!(U8, [0, 2, 0, 2]);
+    /// assert_eq!(value.multiplicity_as(VR::US), 2);
+    /// assert_eq!(value.multiplicity_as(VR::OB), 1);
+    /// ```
+    pub fn multiplicity_as(&self, vr: VR) -> u32 {
+        use self::PrimitiveValue::*;
+        if self.multiplicity() == 0 {
+            return 0;
+        }
+        match (vr, self) {
+            (VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::SQ | VR::UN, _) => 1,
+            (VR::LT | VR::ST | VR::UT | VR::UR, _) => self.multiplicity(),
+            (_, Str(text)) => count_text_values(std::iter::once(&**text)),
+            (_, Strs(texts)) => count_text_values(texts.iter().map(|text| &**text)),
+            (VR::US | VR::SS, U8(bytes)) => bytes.len() as u32 / 2,
+            (VR::AT | VR::UL | VR::SL | VR::FL, U8(bytes)) => bytes.len() as u32 / 4,
+            (VR::FD | VR::SV | VR::UV, U8(bytes)) => bytes.len() as u32 / 8,
+            _ => self.multiplicity(),
+        }
+    }
+
     /// Determine the length of the DICOM value in its encoded form.
     ///
     /// In other words,
@@ -649,7 +710,7 @@ impl PrimitiveValue {
             PrimitiveValue::Str(values) => Cow::from(values.as_str()),
             PrimitiveValue::Strs(values) => {
                 if values.len() == 1 {
-                    Cow::from(&values[0])
+                    Cow::from(values[0].as_str())
                 } else {
                     Cow::from(values.iter().join("\\"))
                 }
@@ -4168,6 +4229,23 @@ impl Display for PrimitiveValue {
     }
 }
 
+/// Count the backslash-separated values in the given strings,
+/// where a single empty string has no values.
+fn count_text_values<'a>(texts: impl Iterator<Item = &'a str>) -> u32 {
+    let mut count = 0;
+    let mut empty = true;
+    for text in texts {
+        let text = text.trim_end_matches([' ', '\0']);
+        empty &= text.is_empty();
+        count += text.split('\\').count() as u32;
+    }
+    if empty && count == 1 {
+        0
+    } else {
+        count
+    }
+}
+
 impl HasLength for PrimitiveValue {
     fn length(&self) -> Length {
         Length::defined(self.calculate_byte_len() as u32)
@@ -5000,6 +5078,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn multiplicity_as_vr() {
+        use crate::VR;
+
+        // text values
+        assert_eq!(
+            PrimitiveValue::from("ORIGINAL\\PRIMARY").multiplicity_as(VR::CS),
+            2
+        );
+        assert_eq!(
+            dicom_value!(Strs, ["0.5\\0.5", "0.5"]).multiplicity_as(VR::DS),
+            3
+        );
+        assert_eq!(dicom_value!(Strs, ["A", ""]).multiplicity_as(VR::CS), 2);
+        assert_eq!(PrimitiveValue::from("A\\B ").multiplicity_as(VR::CS), 2);
+        assert_eq!(PrimitiveValue::from(" ").multiplicity_as(VR::CS), 0);
+        assert_eq!(PrimitiveValue::Empty.multiplicity_as(VR::CS), 0);
+        // single-valued text
+        assert_eq!(PrimitiveValue::from("A\\B").multiplicity_as(VR::LT), 1);
+        // binary numbers
+        assert_eq!(dicom_value!(F64, [1., 2., 3.]).multiplicity_as(VR::FD), 3);
+        assert_eq!(
+            PrimitiveValue::U8(smallvec![0; 12]).multiplicity_as(VR::FL),
+            3
+        );
+        assert_eq!(
+            PrimitiveValue::U8(smallvec![0; 12]).multiplicity_as(VR::FD),
+            1
+        );
+        assert_eq!(
+            PrimitiveValue::U16(smallvec![0; 12]).multiplicity_as(VR::OW),
+            1
+        );
+        assert_eq!(
+            PrimitiveValue::U8(smallvec![0; 12]).multiplicity_as(VR::OB),
+            1
+        );
+    }
+
     #[test]
     fn calculate_byte_len() {
         // single even string
@@ -0,0 +1,195 @@
+//! Test suite for the serialization of core types with Serde,
+//! kept apart from the unit tests
+//! so that the format crates do not affect their type inference.
+#![cfg(feature = "serde")]
+
+use chrono::FixedOffset;
+use dicom_core::dicom_value;
+use dicom_core::header::{
+    DataElementHeader, HasLength, Header, Length, SequenceItemHeader, Tag, VR,
+};
+use dicom_core::value::{DicomDate, DicomDateTime, DicomTime, PrimitiveValue};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+
+/// Serialize the value and read it back, through JSON and bincode.
+fn through_formats<T>(value: &T) -> [T; 2]
+where
+    T: Serialize + DeserializeOwned + Debug,
+{
+    let json = serde_json::to_string(value).unwrap();
+    let from_json = serde_json::from_str(&json)
+        .unwrap_or_else(|e| panic!("failed to read back {}: {}", json, e));
+
+    let bytes = bincode::serialize(value).unwrap();
+    let from_bincode = bincode::deserialize(&bytes)
+        .unwrap_or_else(|e| panic!("failed to read back {:?}: {}", value, e));
+    [from_json, from_bincode]
+}
+
+/// Check that the value survives a trip through JSON and bincode.
+fn roundtrip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    for back in through_formats(value) {
+        assert_eq!(&back, value);
+    }
+}
+
+#[test]
+fn tag_as_hex_string() {
+    let tag = Tag(0x0008, 0x0018);
+    assert_eq!(serde_json::to_string(&tag).unwrap(), r#""00080018""#);
+    assert_eq!(
+        serde_json::from_str::<Tag>(r#""7fe00010""#).unwrap(),
+        Tag(0x7FE0, 0x0010)
+    );
+    // pair form is also understood
+    assert_eq!(serde_json::from_str::<Tag>("[8, 24]").unwrap(), tag);
+    assert!(serde_json::from_str::<Tag>(r#""0008001""#).is_err());
+
+    roundtrip(&tag);
+    roundtrip(&Tag(0xFFFE, 0xE0DD));
+}
+
+#[test]
+fn tag_as_tuple() {
+    #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Entry {
+        #[serde(with = "dicom_core::header::tag_tuple")]
+        tag: Tag,
+    }
+
+    let entry = Entry {
+        tag: Tag(0x0010, 0x0020),
+    };
+    assert_eq!(serde_json::to_string(&entry).unwrap(), r#"{"tag":[16,32]}"#);
+    roundtrip(&entry);
+}
+
+#[test]
+fn vr_as_code() {
+    assert_eq!(serde_json::to_string(&VR::UI).unwrap(), r#""UI""#);
+    assert!(serde_json::from_str::<VR>(r#""ui""#).is_err());
+    for vr in [VR::AE, VR::OB, VR::SQ, VR::UN, VR::UV] {
+        roundtrip(&vr);
+    }
+}
+
+#[test]
+fn headers_roundtrip() {
+    let header = DataElementHeader::new(Tag(0x0010, 0x0010), VR::PN, Length(12));
+    assert_eq!(
+        serde_json::to_string(&header).unwrap(),
+        r#"{"tag":"00100010","vr":"PN","len":12}"#
+    );
+    roundtrip(&header);
+
+    let undefined = DataElementHeader::new(Tag(0x0008, 0x1115), VR::SQ, Length::UNDEFINED);
+    assert_eq!(
+        serde_json::to_string(&undefined).unwrap(),
+        r#"{"tag":"00081115","vr":"SQ","len":null}"#
+    );
+    // undefined lengths never compare equal
+    for back in through_formats(&undefined) {
+        assert_eq!(back.tag(), undefined.tag());
+        assert_eq!(back.vr(), VR::SQ);
+        assert!(back.length().is_undefined());
+    }
+    roundtrip(&DataElementHeader::new(
+        Tag(0x0010, 0x0020),
+        VR::LO,
+        Length(0),
+    ));
+
+    roundtrip(&SequenceItemHeader::Item { len: Length(24) });
+    for back in through_formats(&SequenceItemHeader::Item {
+        len: Length::UNDEFINED,
+    }) {
+        assert!(matches!(back, SequenceItemHeader::Item { len } if len.is_undefined()));
+    }
+    roundtrip(&SequenceItemHeader::ItemDelimiter);
+    roundtrip(&SequenceItemHeader::SequenceDelimiter);
+}
+
+#[test]
+fn primitive_values_roundtrip() {
+    let offset = FixedOffset::east_opt(3600).unwrap();
+    let date = DicomDate::from_ymd(2024, 2, 29).unwrap();
+    let time = DicomTime::from_hms_milli(10, 30, 45, 120).unwrap();
+    let values = [
+        PrimitiveValue::Empty,
+        dicom_value!(Strs, ["ORIGINAL", "PRIMARY", ""]),
+        PrimitiveValue::Strs(Default::default()),
+        dicom_value!(Str, "Doe^John"),
+        dicom_value!(Str, ""),
+        dicom_value!(Tags, [Tag(0x0010, 0x0010), Tag(0x7FE0, 0x0010)]),
+        dicom_value!(U8, [0, 1, 0xFE, 0xFF, 7]),
+        PrimitiveValue::U8(Default::default()),
+        dicom_value!(I16, [-32768, 0, 32767]),
+        dicom_value!(U16, [0, 512, 65535]),
+        PrimitiveValue::U16(Default::default()),
+        dicom_value!(I32, [i32::MIN, -1, i32::MAX]),
+        dicom_value!(U32, [u32::MAX]),
+        dicom_value!(I64, [i64::MIN, i64::MAX]),
+        dicom_value!(U64, [u64::MAX, 0]),
+        dicom_value!(F32, [1.5, -0.25, f32::MAX]),
+        dicom_value!(F64, [std::f64::consts::PI, f64::MIN_POSITIVE]),
+        dicom_value!(
+            Date,
+            [
+                date,
+                DicomDate::from_ym(1999, 12).unwrap(),
+                DicomDate::from_y(1970).unwrap(),
+            ]
+        ),
+        dicom_value!(
+            DateTime,
+            [
+                DicomDateTime::from_date(date),
+                DicomDateTime::from_date_and_time(date, time).unwrap(),
+                DicomDateTime::from_date_and_time_with_time_zone(date, time, offset).unwrap(),
+            ]
+        ),
+        dicom_value!(
+            Time,
+            [
+                time,
+                DicomTime::from_h(23).unwrap(),
+                DicomTime::from_hms(0, 0, 1).unwrap(),
+            ]
+        ),
+    ];
+
+    for value in &values {
+        roundtrip(value);
+    }
+}
+
+#[test]
+fn primitive_value_representation() {
+    assert_eq!(
+        serde_json::to_string(&PrimitiveValue::Empty).unwrap(),
+        r#""Empty""#
+    );
+    assert_eq!(
+        serde_json::to_string(&dicom_value!(U8, [1, 2, 3, 4])).unwrap(),
+        r#"{"U8":"AQIDBA=="}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&dicom_value!(Strs, ["A", "B"])).unwrap(),
+        r#"{"Strs":["A","B"]}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&dicom_value!(
+            Date,
+            DicomDate::from_ymd(2024, 1, 31).unwrap()
+        ))
+        .unwrap(),
+        r#"{"Date":["20240131"]}"#
+    );
+    assert!(serde_json::from_str::<PrimitiveValue>(r#"{"Date":["2024013"]}"#).is_err());
+    assert!(serde_json::from_str::<PrimitiveValue>(r#"{"U8":"not base64!"}"#).is_err());
+}
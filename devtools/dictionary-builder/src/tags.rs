@@ -187,6 +187,20 @@ struct Entry {
     tag_type: TagType,
 }
 
+/// Convert a value multiplicity from the dictionary (such as `1`, `1-3`,
+/// `2-n` or `2-2n`) into a `ValueMultiplicity` variant.
+fn vm_declaration(vm: &str) -> String {
+    match vm.split_once('-') {
+        None => format!("Exactly({})", vm),
+        Some((min, "n")) => format!("AtLeast({})", min),
+        Some((min, max)) => match max.strip_suffix('n') {
+            Some(step) if step == min => format!("MultipleOf({})", min),
+            Some(_) => panic!("unsupported value multiplicity: {}", vm),
+            None => format!("Between({}, {})", min, max),
+        },
+    }
+}
+
 /// Write the tag dictionary as Rust code.
 fn to_code_file<P>(
     dest_path: P,
@@ -215,7 +229,9 @@ where
 
     f.write_all(
         b"\n\
-    use dicom_core::dictionary::{DataDictionaryEntryRef, TagRange, TagRange::*, VirtualVr::*};\n\
+    use dicom_core::dictionary::{\n    \
+    DataDictionaryEntryRef, TagRange, TagRange::*, ValueMultiplicity::*, VirtualVr::*,\n\
+    };\n\
     use dicom_core::Tag;\n\
     use dicom_core::VR::*;\n\n",
     )?;
@@ -287,8 +303,14 @@ where
 
         writeln!(
             f,
-            "    E {{ tag: {}, alias: \"{}\", vr: {}{}{} }}, // {}",
-            tag_set, e.alias, vr1, vr2, vr3, e.obs
+            "    E::new({}, \"{}\", {}{}{}, {}), // {}",
+            tag_set,
+            e.alias,
+            vr1,
+            vr2,
+            vr3,
+            vm_declaration(&e.vm),
+            e.obs
         )?;
     }
     f.write_all(b"];\n")?;
@@ -1,7 +1,9 @@
 //! Data element dictionary implementation
 
 use crate::tags::ENTRIES;
-use dicom_core::dictionary::{DataDictionary, DataDictionaryEntryRef, TagRange::*, VirtualVr};
+use dicom_core::dictionary::{
+    DataDictionary, DataDictionaryEntryRef, TagRange::*, ValueMultiplicity, VirtualVr,
+};
 use dicom_core::header::Tag;
 use dicom_core::VR;
 use once_cell::sync::Lazy;
@@ -22,6 +24,54 @@ pub fn registry() -> &'static StandardDataDictionaryRegistry {
     &DICT
 }
 
+/// Look up a standard data dictionary entry by keyword
+/// in a constant context.
+///
+/// This performs a linear search over all entries,
+/// and is meant for resolving attribute keywords at compile time.
+/// Run-time look-ups should use [`StandardDataDictionary`] instead.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::dictionary::{TagRange, VirtualVr};
+/// # use dicom_core::VR;
+/// # use dicom_dictionary_std::{data_element::entry_by_name_const, tags};
+/// const ROWS: TagRange = match entry_by_name_const("Rows") {
+///     Some(entry) => entry.tag,
+///     None => panic!("no such attribute"),
+/// };
+/// assert_eq!(ROWS, TagRange::Single(tags::ROWS));
+/// assert!(entry_by_name_const("NotAnAttribute").is_none());
+/// ```
+pub const fn entry_by_name_const(name: &str) -> Option<&'static DataDictionaryEntryRef<'static>> {
+    let entries: &'static [DataDictionaryEntryRef<'static>] = ENTRIES;
+    let mut i = 0;
+    while i < entries.len() {
+        if const_str_eq(entries[i].alias, name) {
+            return Some(&entries[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// String equality usable in a constant context.
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
 /// The data struct actually containing the standard dictionary.
 ///
 /// This structure is made opaque via the unit type [`StandardDataDictionary`],
@@ -66,18 +116,20 @@ impl StandardDataDictionaryRegistry {
 }
 
 /// Generic Group Length dictionary entry.
-static GROUP_LENGTH_ENTRY: DataDictionaryEntryRef<'static> = DataDictionaryEntryRef {
-    tag: GroupLength,
-    alias: "GenericGroupLength",
-    vr: VirtualVr::Exact(VR::UL),
-};
+static GROUP_LENGTH_ENTRY: DataDictionaryEntryRef<'static> = DataDictionaryEntryRef::new(
+    GroupLength,
+    "GenericGroupLength",
+    VirtualVr::Exact(VR::UL),
+    ValueMultiplicity::Exactly(1),
+);
 
 /// Generic Private Creator dictionary entry.
-static PRIVATE_CREATOR_ENTRY: DataDictionaryEntryRef<'static> = DataDictionaryEntryRef {
-    tag: PrivateCreator,
-    alias: "PrivateCreator",
-    vr: VirtualVr::Exact(VR::LO),
-};
+static PRIVATE_CREATOR_ENTRY: DataDictionaryEntryRef<'static> = DataDictionaryEntryRef::new(
+    PrivateCreator,
+    "PrivateCreator",
+    VirtualVr::Exact(VR::LO),
+    ValueMultiplicity::Exactly(1),
+);
 
 /// A data element dictionary which consults
 /// the library's global DICOM attribute registry.
@@ -172,7 +224,9 @@ mod tests {
     use crate::tags;
 
     use super::StandardDataDictionary;
-    use dicom_core::dictionary::{DataDictionary, DataDictionaryEntryRef, TagRange::*, VirtualVr};
+    use dicom_core::dictionary::{
+        DataDictionary, DataDictionaryEntryRef, TagRange::*, ValueMultiplicity, VirtualVr,
+    };
     use dicom_core::header::{Tag, VR};
     use dicom_core::ops::AttributeSelector;
 
@@ -184,20 +238,22 @@ mod tests {
 
         assert_eq!(
             dict.by_name("PatientName"),
-            Some(&DataDictionaryEntryRef {
-                tag: Single(Tag(0x0010, 0x0010)),
-                alias: "PatientName",
-                vr: VR::PN.into(),
-            })
+            Some(&DataDictionaryEntryRef::new(
+                Single(Tag(0x0010, 0x0010)),
+                "PatientName",
+                VR::PN.into(),
+                ValueMultiplicity::Exactly(1)
+            ))
         );
 
         assert_eq!(
             dict.by_name("Modality"),
-            Some(&DataDictionaryEntryRef {
-                tag: Single(Tag(0x0008, 0x0060)),
-                alias: "Modality",
-                vr: VR::CS.into(),
-            })
+            Some(&DataDictionaryEntryRef::new(
+                Single(Tag(0x0008, 0x0060)),
+                "Modality",
+                VR::CS.into(),
+                ValueMultiplicity::Exactly(1)
+            ))
         );
 
         let pixel_data = dict
@@ -224,6 +280,35 @@ mod tests {
         assert!(overlay_data.vr == VirtualVr::Ox);
     }
 
+    #[test]
+    fn value_multiplicities() {
+        use dicom_core::dictionary::DataDictionaryEntry;
+
+        let dict = StandardDataDictionary;
+        let vm = |tag| dict.by_tag(tag).and_then(|e| e.vm());
+
+        assert_eq!(vm(tags::PATIENT_NAME), Some(ValueMultiplicity::Exactly(1)));
+        assert_eq!(
+            vm(tags::IMAGE_ORIENTATION_PATIENT),
+            Some(ValueMultiplicity::Exactly(6))
+        );
+        assert_eq!(vm(tags::IMAGE_TYPE), Some(ValueMultiplicity::AtLeast(2)));
+        assert_eq!(vm(tags::SCAN_OPTIONS), Some(ValueMultiplicity::AtLeast(1)));
+        assert_eq!(
+            vm(tags::REFERENCED_FRAME_NUMBER),
+            Some(ValueMultiplicity::AtLeast(1))
+        );
+        assert_eq!(
+            vm(tags::VERTICES_OF_THE_POLYGONAL_SHUTTER),
+            Some(ValueMultiplicity::MultipleOf(2))
+        );
+        assert_eq!(
+            vm(tags::PRIVATE_DATA_ELEMENT_VALUE_MULTIPLICITY),
+            Some(ValueMultiplicity::Between(1, 3))
+        );
+        assert_eq!(vm(Tag(0x6002, 0x0050)), Some(ValueMultiplicity::Exactly(2)));
+    }
+
     #[test]
     fn can_parse_tags() {
         let dict = StandardDataDictionary;
@@ -247,29 +332,32 @@ mod tests {
 
         assert_eq!(
             dict.by_expr("(0010,0010)"),
-            Some(&DataDictionaryEntryRef {
-                tag: Single(crate::tags::PATIENT_NAME),
-                alias: "PatientName",
-                vr: VR::PN.into(),
-            })
+            Some(&DataDictionaryEntryRef::new(
+                Single(crate::tags::PATIENT_NAME),
+                "PatientName",
+                VR::PN.into(),
+                ValueMultiplicity::Exactly(1)
+            ))
         );
 
         assert_eq!(
             dict.by_expr("0008,0060"),
-            Some(&DataDictionaryEntryRef {
-                tag: Single(crate::tags::MODALITY),
-                alias: "Modality",
-                vr: VR::CS.into(),
-            })
+            Some(&DataDictionaryEntryRef::new(
+                Single(crate::tags::MODALITY),
+                "Modality",
+                VR::CS.into(),
+                ValueMultiplicity::Exactly(1)
+            ))
         );
 
         assert_eq!(
             dict.by_expr("OperatorsName"),
-            Some(&DataDictionaryEntryRef {
-                tag: Single(crate::tags::OPERATORS_NAME),
-                alias: "OperatorsName",
-                vr: VR::PN.into(),
-            })
+            Some(&DataDictionaryEntryRef::new(
+                Single(crate::tags::OPERATORS_NAME),
+                "OperatorsName",
+                VR::PN.into(),
+                ValueMultiplicity::AtLeast(1)
+            ))
         );
 
         // can't handle these
@@ -288,40 +376,44 @@ mod tests {
 
         assert_eq!(
             dict.by_tag(FILE_META_INFORMATION_GROUP_LENGTH),
-            Some(&DataDictionaryEntryRef {
-                tag: Single(FILE_META_INFORMATION_GROUP_LENGTH),
-                alias: "FileMetaInformationGroupLength",
-                vr: VR::UL.into(),
-            }),
+            Some(&DataDictionaryEntryRef::new(
+                Single(FILE_META_INFORMATION_GROUP_LENGTH),
+                "FileMetaInformationGroupLength",
+                VR::UL.into(),
+                ValueMultiplicity::Exactly(1)
+            )),
         );
 
         assert_eq!(
             dict.by_tag(COMMAND_GROUP_LENGTH),
-            Some(&DataDictionaryEntryRef {
-                tag: Single(COMMAND_GROUP_LENGTH),
-                alias: "CommandGroupLength",
-                vr: VR::UL.into(),
-            }),
+            Some(&DataDictionaryEntryRef::new(
+                Single(COMMAND_GROUP_LENGTH),
+                "CommandGroupLength",
+                VR::UL.into(),
+                ValueMultiplicity::Exactly(1)
+            )),
         );
 
         // generic group length
 
         assert_eq!(
             dict.by_tag(Tag(0x7FE0, 0x0000)),
-            Some(&DataDictionaryEntryRef {
-                tag: GroupLength,
-                alias: "GenericGroupLength",
-                vr: VR::UL.into(),
-            }),
+            Some(&DataDictionaryEntryRef::new(
+                GroupLength,
+                "GenericGroupLength",
+                VR::UL.into(),
+                ValueMultiplicity::Exactly(1)
+            )),
         );
 
         assert_eq!(
             dict.by_name("GenericGroupLength"),
-            Some(&DataDictionaryEntryRef {
-                tag: GroupLength,
-                alias: "GenericGroupLength",
-                vr: VR::UL.into(),
-            }),
+            Some(&DataDictionaryEntryRef::new(
+                GroupLength,
+                "GenericGroupLength",
+                VR::UL.into(),
+                ValueMultiplicity::Exactly(1)
+            )),
         );
     }
 
@@ -329,11 +421,12 @@ mod tests {
     fn has_private_creator() {
         let dict = StandardDataDictionary::default();
 
-        let private_creator = DataDictionaryEntryRef {
-            tag: PrivateCreator,
-            alias: "PrivateCreator",
-            vr: VR::LO.into(),
-        };
+        let private_creator = DataDictionaryEntryRef::new(
+            PrivateCreator,
+            "PrivateCreator",
+            VR::LO.into(),
+            ValueMultiplicity::Exactly(1),
+        );
 
         assert_eq!(dict.by_tag(Tag(0x0009, 0x0010)), Some(&private_creator));
         assert_eq!(dict.by_tag(Tag(0x0009, 0x0011)), Some(&private_creator));
@@ -0,0 +1,172 @@
+//! Hexadecimal dumping of raw bytes.
+//!
+//! See [`hex_dump`].
+use std::fmt::Write;
+
+/// The number of bytes printed in each line of a hex dump.
+const BYTES_PER_LINE: usize = 16;
+
+/// Options for [`hex_dump`].
+///
+/// # Example
+///
+/// ```
+/// use dicom_dump::{hex_dump, HexDumpOptions};
+///
+/// let mut options = HexDumpOptions::new();
+/// options.base_offset(0x1F0).max_lines(1);
+/// assert_eq!(
+///     hex_dump(b"DICOM hex dump of a few bytes", options),
+///     "000001F0  44 49 43 4F 4D 20 68 65  78 20 64 75 6D 70 20 6F  |DICOM hex dump o|\n\
+///      … 13 more bytes\n",
+/// );
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct HexDumpOptions {
+    /// the offset of the first byte,
+    /// such as the position of the value in its file
+    pub base_offset: u64,
+    /// the maximum number of lines of bytes to print
+    pub max_lines: Option<usize>,
+}
+
+impl HexDumpOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the offset of the first byte.
+    ///
+    /// By default, offsets are relative to the start of the bytes.
+    pub fn base_offset(&mut self, base_offset: u64) -> &mut Self {
+        self.base_offset = base_offset;
+        self
+    }
+
+    /// Set the maximum number of lines of bytes to print.
+    ///
+    /// The remaining bytes are summarized in a final line.
+    pub fn max_lines(&mut self, max_lines: usize) -> &mut Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+}
+
+/// Produce a classic hex dump of the given bytes.
+///
+/// Each line has the offset of its first byte,
+/// up to 16 bytes in hexadecimal,
+/// and the same bytes as ASCII characters
+/// (with `.` in place of non-printable characters).
+/// When the maximum number of lines is exceeded,
+/// a last line states the number of bytes left out.
+///
+/// # Example
+///
+/// ```
+/// use dicom_dump::{hex_dump, HexDumpOptions};
+///
+/// assert_eq!(
+///     hex_dump(b"1.2.840\0", HexDumpOptions::new()),
+///     "00000000  31 2E 32 2E 38 34 30 00                           |1.2.840.|\n",
+/// );
+/// ```
+pub fn hex_dump(bytes: &[u8], options: HexDumpOptions) -> String {
+    let lines = bytes.len().div_ceil(BYTES_PER_LINE);
+    let shown_lines = options.max_lines.map_or(lines, |max| max.min(lines));
+
+    let mut out = String::new();
+    for (i, chunk) in bytes.chunks(BYTES_PER_LINE).take(shown_lines).enumerate() {
+        let offset = options.base_offset + (i * BYTES_PER_LINE) as u64;
+        write!(out, "{:08X} ", offset).unwrap();
+        for j in 0..BYTES_PER_LINE {
+            if j == BYTES_PER_LINE / 2 {
+                out.push(' ');
+            }
+            match chunk.get(j) {
+                Some(byte) => write!(out, " {:02X}", byte).unwrap(),
+                None => out.push_str("   "),
+            }
+        }
+        out.push_str("  |");
+        out.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+
+    let remaining = bytes.len().saturating_sub(shown_lines * BYTES_PER_LINE);
+    if remaining > 0 {
+        writeln!(
+            out,
+            "… {} more byte{}",
+            remaining,
+            if remaining == 1 { "" } else { "s" }
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hex_dump, HexDumpOptions};
+
+    #[test]
+    fn hex_dump_partial_lines() {
+        let bytes: Vec<u8> = (0x40..0x40 + 20).collect();
+        assert_eq!(
+            hex_dump(&bytes, HexDumpOptions::new()),
+            concat!(
+                "00000000  40 41 42 43 44 45 46 47  48 49 4A 4B 4C 4D 4E 4F  |@ABCDEFGHIJKLMNO|\n",
+                "00000010  50 51 52 53                                       |PQRS|\n",
+            )
+        );
+
+        // exactly one full line
+        assert_eq!(
+            hex_dump(&bytes[..16], HexDumpOptions::new()),
+            "00000000  40 41 42 43 44 45 46 47  48 49 4A 4B 4C 4D 4E 4F  |@ABCDEFGHIJKLMNO|\n",
+        );
+
+        // less than half a line, with non-printable bytes
+        assert_eq!(
+            hex_dump(&[0x00, 0x7F, b' ', 0xFF, b'a'], HexDumpOptions::new()),
+            "00000000  00 7F 20 FF 61                                    |.. .a|\n",
+        );
+
+        assert_eq!(hex_dump(&[], HexDumpOptions::new()), "");
+    }
+
+    #[test]
+    fn hex_dump_truncated() {
+        let bytes = [0xAB; 50];
+        let mut options = HexDumpOptions::new();
+        options.max_lines(2).base_offset(0x100);
+        assert_eq!(
+            hex_dump(&bytes, options),
+            concat!(
+                "00000100  AB AB AB AB AB AB AB AB  AB AB AB AB AB AB AB AB  |................|\n",
+                "00000110  AB AB AB AB AB AB AB AB  AB AB AB AB AB AB AB AB  |................|\n",
+                "… 18 more bytes\n",
+            )
+        );
+
+        // one byte past the last line
+        options.max_lines(3);
+        assert!(hex_dump(&bytes[..49], options).ends_with("|................|\n… 1 more byte\n"));
+
+        // the limit is not reached
+        options.max_lines(4);
+        assert!(!hex_dump(&bytes, options).contains('…'));
+
+        // no lines at all
+        options.max_lines(0);
+        assert_eq!(hex_dump(&bytes, options), "… 50 more bytes\n");
+    }
+}
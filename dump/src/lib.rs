@@ -32,17 +32,22 @@
 //! options.width(100).dump_file(&obj)?;
 //! # Result::<(), Box<dyn std::error::Error>>::Ok(())
 //! ```
+//!
+//! The data set can also be dumped into a string
+//! or any other [`fmt::Write`] destination via [`dump`],
+//! or formatted through the adapter returned by [`DumpOptions::display`].
+//!
+//! Selected attributes of many objects
+//! can also be tabulated as CSV with [`csv_export`].
 #[cfg(feature = "sop-class")]
 use dicom_core::dictionary::UidDictionary;
 use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
-use dicom_core::header::Header;
-use dicom_core::value::{PrimitiveValue, Value as DicomValue};
+use dicom_core::value::{DisplayValue, PrimitiveValue};
 use dicom_core::VR;
 #[cfg(feature = "sop-class")]
 use dicom_dictionary_std::StandardSopClassDictionary;
 use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
-use dicom_object::mem::{InMemDicomObject, InMemElement};
-use dicom_object::{FileDicomObject, FileMetaTable, StandardDataDictionary};
+use dicom_object::{DicomElement, DicomObject, FileMetaTable, StandardDataDictionary};
 use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
 use owo_colors::*;
 use std::borrow::Cow;
@@ -50,6 +55,12 @@ use std::fmt::{self, Display, Formatter};
 use std::io::{stdout, Result as IoResult, Write};
 use std::str::FromStr;
 
+mod csv;
+mod hex;
+
+pub use crate::csv::{csv_export, CsvError, CsvOptions, TagOrKeyword};
+pub use crate::hex::{hex_dump, HexDumpOptions};
+
 /// An enum of all supported output formats for dumping DICOM data.
 #[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
 #[non_exhaustive]
@@ -111,7 +122,7 @@ impl Default for DumpFormat {
 ///     .dump_file(&my_dicom_file)?;
 /// # Result::<(), Box<dyn std::error::Error>>::Ok(())
 /// ```
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 #[non_exhaustive]
 pub struct DumpOptions {
     /// the output format
@@ -120,10 +131,36 @@ pub struct DumpOptions {
     pub color: ColorMode,
     /// the console width to assume when trimming long values
     pub width: Option<u32>,
+    /// the maximum number of characters of each value,
+    /// instead of trimming values to fit the console width
+    pub value_width: Option<u32>,
     /// never trim out long text values
     pub no_text_limit: bool,
     /// never trim out any values (implies `no_text_limit`)
     pub no_limit: bool,
+    /// print the position of each element's value in its source,
+    /// when the object provides it
+    pub offsets: bool,
+    /// print the offset table and fragments of encapsulated pixel data
+    pub fragments: bool,
+    /// print a hex dump of OB and UN values with these options
+    pub hex_dump: Option<HexDumpOptions>,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        DumpOptions {
+            format: DumpFormat::default(),
+            color: ColorMode::default(),
+            width: None,
+            value_width: None,
+            no_text_limit: false,
+            no_limit: false,
+            offsets: false,
+            fragments: true,
+            hex_dump: None,
+        }
+    }
 }
 
 impl DumpOptions {
@@ -164,6 +201,16 @@ impl DumpOptions {
         self
     }
 
+    /// Set the maximum number of characters to print of each value.
+    ///
+    /// Unlike [`width`](DumpOptions::width),
+    /// this limit does not depend on the indentation of the element,
+    /// and also applies to [`dump`] and [`display`](DumpOptions::display).
+    pub fn value_width(&mut self, value_width: u32) -> &mut Self {
+        self.value_width = Some(value_width);
+        self
+    }
+
     /// Set whether to remove the maximum width restriction for text values.
     pub fn no_text_limit(&mut self, no_text_limit: bool) -> &mut Self {
         self.no_text_limit = no_text_limit;
@@ -183,34 +230,94 @@ impl DumpOptions {
         self
     }
 
+    /// Set whether to print the position of each element's value
+    /// in the source of the object.
+    ///
+    /// Positions are only known to objects which read values on demand,
+    /// such as [`LazyDicomObject`](dicom_object::LazyDicomObject).
+    /// A blank column is printed for the other elements.
+    pub fn offsets(&mut self, offsets: bool) -> &mut Self {
+        self.offsets = offsets;
+        self
+    }
+
+    /// Set whether to print the basic offset table and each fragment
+    /// of encapsulated pixel data.
+    ///
+    /// This is the default behavior.
+    /// When disabled, only the number of items in the pixel sequence is printed.
+    pub fn fragments(&mut self, fragments: bool) -> &mut Self {
+        self.fragments = fragments;
+        self
+    }
+
+    /// Print the bytes of OB and UN values in a hex dump
+    /// below each element, with the given options.
+    ///
+    /// The offsets in the hex dump are relative to the start of the value,
+    /// unless [offsets](DumpOptions::offsets) are enabled
+    /// and the element's position in its source is known,
+    /// in which case that position is used instead.
+    pub fn hex_dump(&mut self, options: HexDumpOptions) -> &mut Self {
+        self.hex_dump = Some(options);
+        self
+    }
+
+    /// Obtain a value which displays the data set of a DICOM object
+    /// with these options.
+    ///
+    /// See [`dump`] for the rules applied to the output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, PrimitiveValue, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// use dicom_dump::{ColorMode, DumpOptions};
+    ///
+    /// let obj = InMemDicomObject::from_element_iter([DataElement::new(
+    ///     tags::MODALITY,
+    ///     VR::CS,
+    ///     PrimitiveValue::from("OT"),
+    /// )]);
+    /// let mut options = DumpOptions::new();
+    /// options.color_mode(ColorMode::Never);
+    ///
+    /// let text = options.display(&obj).to_string();
+    /// assert!(text.starts_with("(0008,0060) Modality"));
+    /// assert!(text.ends_with(": \"OT\"\n"));
+    /// ```
+    pub fn display<O>(&self, obj: O) -> DumpDisplay<'_, O>
+    where
+        O: DicomObject,
+    {
+        DumpDisplay { obj, options: self }
+    }
+
     /// Dump the contents of an open DICOM file to standard output.
-    pub fn dump_file<D>(&self, obj: &FileDicomObject<InMemDicomObject<D>>) -> IoResult<()>
+    ///
+    /// The file meta table is included if the object provides one.
+    pub fn dump_file<O>(&self, obj: O) -> IoResult<()>
     where
-        D: DataDictionary,
+        O: DicomObject,
     {
         self.dump_file_impl(stdout(), obj, true)
     }
 
     /// Dump the contents of an open DICOM file to the given writer.
-    pub fn dump_file_to<D>(
-        &self,
-        to: impl Write,
-        obj: &FileDicomObject<InMemDicomObject<D>>,
-    ) -> IoResult<()>
+    ///
+    /// The file meta table is included if the object provides one.
+    pub fn dump_file_to<O>(&self, to: impl Write, obj: O) -> IoResult<()>
     where
-        D: DataDictionary,
+        O: DicomObject,
     {
         self.dump_file_impl(to, obj, false)
     }
 
-    fn dump_file_impl<D>(
-        &self,
-        mut to: impl Write,
-        obj: &FileDicomObject<InMemDicomObject<D>>,
-        to_stdout: bool,
-    ) -> IoResult<()>
+    fn dump_file_impl<O>(&self, mut to: impl Write, obj: O, to_stdout: bool) -> IoResult<()>
     where
-        D: DataDictionary,
+        O: DicomObject,
     {
         match self.color {
             ColorMode::Never => owo_colors::set_override(false),
@@ -218,70 +325,136 @@ impl DumpOptions {
             ColorMode::Auto => owo_colors::unset_override(),
         }
 
-        let meta = obj.meta();
-
-        let width = determine_width(self.width);
-
-        let (no_text_limit, no_limit) = if to_stdout {
-            (self.no_text_limit, self.no_limit)
-        } else {
-            (true, true)
-        };
-
-        meta_dump(&mut to, meta, if no_limit { u32::MAX } else { width })?;
-
-        writeln!(to, "{:-<58}", "")?;
+        let settings = self.io_settings(to_stdout);
+
+        if let Some(meta) = obj.meta() {
+            meta_dump(
+                &mut to,
+                meta,
+                if settings.no_limit {
+                    u32::MAX
+                } else {
+                    settings.width
+                },
+            )?;
 
-        dump(&mut to, obj, width, 0, no_text_limit, no_limit)?;
+            writeln!(to, "{:-<58}", "")?;
+        }
 
-        Ok(())
+        write_io(&mut to, |to| dump_elements(to, &obj, 0, &settings))
     }
 
     /// Dump the contents of a DICOM object to standard output.
     #[inline]
-    pub fn dump_object<D>(&self, obj: &InMemDicomObject<D>) -> IoResult<()>
+    pub fn dump_object<O>(&self, obj: O) -> IoResult<()>
     where
-        D: DataDictionary,
+        O: DicomObject,
     {
         self.dump_object_impl(stdout(), obj, true)
     }
 
     /// Dump the contents of a DICOM object to the given writer.
     #[inline]
-    pub fn dump_object_to<D>(&self, to: impl Write, obj: &InMemDicomObject<D>) -> IoResult<()>
+    pub fn dump_object_to<O>(&self, to: impl Write, obj: O) -> IoResult<()>
     where
-        D: DataDictionary,
+        O: DicomObject,
     {
         self.dump_object_impl(to, obj, false)
     }
 
-    fn dump_object_impl<D>(
-        &self,
-        mut to: impl Write,
-        obj: &InMemDicomObject<D>,
-        to_stdout: bool,
-    ) -> IoResult<()>
+    fn dump_object_impl<O>(&self, mut to: impl Write, obj: O, to_stdout: bool) -> IoResult<()>
     where
-        D: DataDictionary,
+        O: DicomObject,
     {
+        self.set_color_override(to_stdout);
+
+        let settings = self.io_settings(to_stdout);
+        write_io(&mut to, |to| dump_elements(to, &obj, 0, &settings))
+    }
+
+    fn set_color_override(&self, to_stdout: bool) {
         match (self.color, to_stdout) {
             (ColorMode::Never, _) => colored::control::set_override(false),
             (ColorMode::Always, _) => colored::control::set_override(true),
             (ColorMode::Auto, false) => colored::control::set_override(false),
             (ColorMode::Auto, true) => colored::control::unset_override(),
         }
+    }
 
-        let width = determine_width(self.width);
-
+    /// The element settings for dumping to a byte writer,
+    /// where values are only trimmed when printing to standard output.
+    fn io_settings(&self, to_stdout: bool) -> ElementSettings {
         let (no_text_limit, no_limit) = if to_stdout {
             (self.no_text_limit, self.no_limit)
         } else {
             (true, true)
         };
+        ElementSettings {
+            width: determine_width(self.width),
+            value_width: self.value_width,
+            no_text_limit,
+            no_limit,
+            offsets: self.offsets,
+            fragments: self.fragments,
+            hex_dump: self.hex_dump,
+        }
+    }
 
-        dump(&mut to, obj, width, 0, no_text_limit, no_limit)?;
+    /// The element settings for dumping to a text writer,
+    /// where values are only trimmed to the value width, if set.
+    fn text_settings(&self) -> ElementSettings {
+        ElementSettings {
+            width: u32::MAX,
+            value_width: self.value_width,
+            no_text_limit: self.no_text_limit,
+            no_limit: self.no_limit || self.value_width.is_none(),
+            offsets: self.offsets,
+            fragments: self.fragments,
+            hex_dump: self.hex_dump,
+        }
+    }
+}
 
-        Ok(())
+/// A displayable dump of the data set of a DICOM object.
+///
+/// Obtained via [`DumpOptions::display`].
+#[derive(Debug)]
+pub struct DumpDisplay<'a, O> {
+    obj: O,
+    options: &'a DumpOptions,
+}
+
+impl<O> Display for DumpDisplay<'_, O>
+where
+    O: DicomObject,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.options.set_color_override(false);
+        dump_elements(f, &self.obj, 0, &self.options.text_settings())
+    }
+}
+
+/// The settings applied to each element being dumped.
+#[derive(Debug, Copy, Clone)]
+struct ElementSettings {
+    /// the output width, from which the room for each value is derived
+    width: u32,
+    /// a fixed maximum number of characters per value
+    value_width: Option<u32>,
+    no_text_limit: bool,
+    no_limit: bool,
+    offsets: bool,
+    fragments: bool,
+    hex_dump: Option<HexDumpOptions>,
+}
+
+impl ElementSettings {
+    /// The maximum number of characters of a value
+    /// which follows `reserved` characters at the given depth.
+    fn max_characters(&self, reserved: u32, depth: u32) -> u32 {
+        let reserved = if self.offsets { reserved + 9 } else { reserved };
+        self.value_width
+            .unwrap_or_else(|| self.width.saturating_sub(reserved + depth * 2))
     }
 }
 
@@ -400,9 +573,9 @@ where
 /// Dump the contents of a DICOM file to stdout.
 ///
 /// Both file meta table and main data set are dumped.
-pub fn dump_file<D>(obj: &FileDicomObject<InMemDicomObject<D>>) -> IoResult<()>
+pub fn dump_file<O>(obj: O) -> IoResult<()>
 where
-    D: DataDictionary,
+    O: DicomObject,
 {
     DumpOptions::new().dump_file(obj)
 }
@@ -410,29 +583,109 @@ where
 /// Dump the contents of a DICOM file to the given writer.
 ///
 /// Both file meta table and main data set are dumped.
-pub fn dump_file_to<D>(to: impl Write, obj: &FileDicomObject<InMemDicomObject<D>>) -> IoResult<()>
+pub fn dump_file_to<O>(to: impl Write, obj: O) -> IoResult<()>
 where
-    D: DataDictionary,
+    O: DicomObject,
 {
     DumpOptions::new().dump_file_to(to, obj)
 }
 
 /// Dump the contents of a DICOM object to stdout.
-pub fn dump_object<D>(obj: &InMemDicomObject<D>) -> IoResult<()>
+pub fn dump_object<O>(obj: O) -> IoResult<()>
 where
-    D: DataDictionary,
+    O: DicomObject,
 {
     DumpOptions::new().dump_object(obj)
 }
 
 /// Dump the contents of a DICOM object to the given writer.
-pub fn dump_object_to<D>(to: impl Write, obj: &InMemDicomObject<D>) -> IoResult<()>
+pub fn dump_object_to<O>(to: impl Write, obj: O) -> IoResult<()>
 where
-    D: DataDictionary,
+    O: DicomObject,
 {
     DumpOptions::new().dump_object_to(to, obj)
 }
 
+/// Dump the data set of a DICOM object to the given text writer.
+///
+/// One line is written per element,
+/// with the element's tag, dictionary keyword, VR,
+/// value multiplicity, length, and value,
+/// indented by the depth of the element in nested sequences.
+/// The file meta table is not included.
+///
+/// Values are printed to the end,
+/// unless a [value width](DumpOptions::value_width) was set.
+/// The output is only colored if the color mode is [`ColorMode::Always`].
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElement, PrimitiveValue, VR};
+/// # use dicom_dictionary_std::tags;
+/// # use dicom_object::InMemDicomObject;
+/// use dicom_dump::{dump, ColorMode, DumpOptions};
+///
+/// let obj = InMemDicomObject::from_element_iter([DataElement::new(
+///     tags::PATIENT_NAME,
+///     VR::PN,
+///     PrimitiveValue::from("Doe^John"),
+/// )]);
+///
+/// let mut out = String::new();
+/// dump(&obj, &mut out, DumpOptions::new().color_mode(ColorMode::Never))?;
+/// assert_eq!(
+///     out,
+///     "(0010,0010) PatientName                  PN (1,  8 bytes): \"Doe^John\"\n",
+/// );
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+pub fn dump<O, W>(obj: O, to: &mut W, options: &DumpOptions) -> fmt::Result
+where
+    O: DicomObject,
+    W: ?Sized + fmt::Write,
+{
+    options.set_color_override(false);
+    dump_elements(to, &obj, 0, &options.text_settings())
+}
+
+/// Adapter for writing formatted text to a byte writer,
+/// which keeps the I/O error behind a formatting error.
+struct IoWriter<W> {
+    inner: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W> fmt::Write for IoWriter<W>
+where
+    W: Write,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+/// Run a text writing procedure against a byte writer.
+fn write_io<W, F>(to: &mut W, f: F) -> IoResult<()>
+where
+    W: ?Sized + Write,
+    F: FnOnce(&mut IoWriter<&mut W>) -> fmt::Result,
+{
+    let mut writer = IoWriter {
+        inner: to,
+        error: None,
+    };
+    f(&mut writer).map_err(|_| {
+        writer
+            .error
+            .take()
+            .unwrap_or_else(|| std::io::Error::other("formatter error"))
+    })
+}
+
 #[inline]
 fn whitespace_or_null(c: char) -> bool {
     c.is_whitespace() || c == '\0'
@@ -562,28 +815,21 @@ where
     Ok(())
 }
 
-fn dump<W, D>(
-    to: &mut W,
-    obj: &InMemDicomObject<D>,
-    width: u32,
-    depth: u32,
-    no_text_limit: bool,
-    no_limit: bool,
-) -> IoResult<()>
+fn dump_elements<W, O>(to: &mut W, obj: &O, depth: u32, settings: &ElementSettings) -> fmt::Result
 where
-    W: ?Sized + Write,
-    D: DataDictionary,
+    W: ?Sized + fmt::Write,
+    O: DicomObject,
 {
-    for elem in obj {
-        dump_element(&mut *to, elem, width, depth, no_text_limit, no_limit)?;
+    for elem in obj.elements() {
+        dump_element_impl(&mut *to, elem, depth, settings)?;
     }
 
     Ok(())
 }
 
-pub fn dump_element<W, D>(
+pub fn dump_element<W, E>(
     to: &mut W,
-    elem: &InMemElement<D>,
+    elem: E,
     width: u32,
     depth: u32,
     no_text_limit: bool,
@@ -591,42 +837,83 @@ pub fn dump_element<W, D>(
 ) -> IoResult<()>
 where
     W: ?Sized + Write,
-    D: DataDictionary,
+    E: DicomElement,
+{
+    let settings = ElementSettings {
+        width,
+        value_width: None,
+        no_text_limit,
+        no_limit,
+        offsets: false,
+        fragments: true,
+        hex_dump: None,
+    };
+    write_io(to, |to| dump_element_impl(to, elem, depth, &settings))
+}
+
+/// Write the start of a line:
+/// the offset column if enabled, followed by the indentation.
+fn line_start<W>(
+    to: &mut W,
+    offset: Option<u64>,
+    depth: u32,
+    settings: &ElementSettings,
+) -> fmt::Result
+where
+    W: ?Sized + fmt::Write,
+{
+    if settings.offsets {
+        match offset {
+            Some(offset) => write!(to, "{} ", DumpValue::TagNum(format!("{:08X}", offset)))?,
+            None => to.write_str("         ")?,
+        }
+    }
+    write!(to, "{:1$}", "", (depth * 2) as usize)
+}
+
+fn dump_element_impl<W, E>(
+    to: &mut W,
+    elem: E,
+    depth: u32,
+    settings: &ElementSettings,
+) -> fmt::Result
+where
+    W: ?Sized + fmt::Write,
+    E: DicomElement,
 {
-    let indent = vec![b' '; (depth * 2) as usize];
     let tag_alias = StandardDataDictionary
         .by_tag(elem.tag())
         .map(DataDictionaryEntry::alias)
         .unwrap_or("«Unknown Attribute»");
-    to.write_all(&indent)?;
-    let vm = match elem.vr() {
-        VR::OB | VR::OW | VR::UN => 1,
-        _ => elem.value().multiplicity(),
-    };
+    line_start(to, elem.offset(), depth, settings)?;
 
-    match elem.value() {
-        DicomValue::Sequence(seq) => {
-            writeln!(
-                to,
-                "{} {:28} {} ({} Item{})",
-                DumpValue::TagNum(elem.tag()),
-                DumpValue::Alias(tag_alias),
-                elem.vr(),
-                vm,
-                if vm == 1 { "" } else { "s" },
-            )?;
-            for item in seq.items() {
-                dump_item(&mut *to, item, width, depth + 2, no_text_limit, no_limit)?;
-            }
-            to.write_all(&indent)?;
-            writeln!(
-                to,
-                "{} {}",
-                DumpValue::TagNum("(FFFE,E0DD)"),
-                DumpValue::Alias("SequenceDelimitationItem"),
-            )?;
+    if let Some(items) = elem.items() {
+        let items: Vec<_> = items.collect();
+        let vm = items.len();
+        writeln!(
+            to,
+            "{} {:28} {} ({} Item{})",
+            DumpValue::TagNum(elem.tag()),
+            DumpValue::Alias(tag_alias),
+            elem.vr(),
+            vm,
+            if vm == 1 { "" } else { "s" },
+        )?;
+        for item in items {
+            dump_item(&mut *to, item, depth + 2, settings)?;
         }
-        DicomValue::PixelSequence(seq) => {
+        line_start(to, None, depth, settings)?;
+        writeln!(
+            to,
+            "{} {}",
+            DumpValue::TagNum("(FFFE,E0DD)"),
+            DumpValue::Alias("SequenceDelimitationItem"),
+        )?;
+        return Ok(());
+    }
+
+    match elem.fragments() {
+        Ok(Some(seq)) => {
             // write pixel sequence start line
             let vr = elem.vr();
             let num_items = 1 + seq.fragments().len();
@@ -634,21 +921,24 @@ where
                 to,
                 "{} {:28} {} (PixelSequence, {} Item{})",
                 DumpValue::TagNum(elem.tag()),
-                "PixelData".bold(),
+                DumpValue::Alias("PixelData"),
                 vr,
                 num_items,
                 if num_items == 1 { "" } else { "s" },
             )?;
 
+            if !settings.fragments {
+                return Ok(());
+            }
+
+            let max_characters =
+                Some(settings.max_characters(38, depth)).filter(|_| !settings.no_limit);
+
             // write offset table
             let offset_table = seq.offset_table();
             let byte_len = offset_table.len() * 4;
-            let summary = offset_table_summary(
-                offset_table,
-                Some(width)
-                    .filter(|_| !no_limit)
-                    .map(|w| w.saturating_sub(38 + depth * 2)),
-            );
+            let summary = offset_table_summary(offset_table, max_characters);
+            line_start(to, None, depth, settings)?;
             writeln!(
                 to,
                 "  {} offset table ({:>2}, {:>2} bytes): {}",
@@ -661,12 +951,8 @@ where
             // write compressed fragments
             for fragment in seq.fragments() {
                 let byte_len = fragment.len();
-                let summary = item_value_summary(
-                    fragment,
-                    Some(width)
-                        .filter(|_| !no_limit)
-                        .map(|w| w.saturating_sub(38 + depth * 2)),
-                );
+                let summary = item_value_summary(fragment, max_characters);
+                line_start(to, None, depth, settings)?;
                 writeln!(
                     to,
                     "  {} pi ({:>3} bytes): {}",
@@ -675,10 +961,31 @@ where
                     summary
                 )?;
             }
+            return Ok(());
         }
-        DicomValue::Primitive(value) => {
-            let vr = elem.vr();
-            let byte_len = elem.header().len.0;
+        Ok(None) => {}
+        Err(e) => {
+            writeln!(
+                to,
+                "{} {:28} {} (PixelSequence): {}",
+                DumpValue::TagNum(elem.tag()),
+                DumpValue::Alias("PixelData"),
+                elem.vr(),
+                DumpValue::Invalid(format!("«{}»", e)),
+            )?;
+            return Ok(());
+        }
+    }
+
+    let vr = elem.vr();
+    let byte_len = elem.length().0;
+    match elem.primitive_value() {
+        Ok(value) => {
+            let value = value.unwrap_or(Cow::Owned(PrimitiveValue::Empty));
+            let vm = match vr {
+                VR::OB | VR::OW | VR::UN => 1,
+                _ => value.multiplicity(),
+            };
             writeln!(
                 to,
                 "{} {:28} {} ({},{:>3} bytes): {}",
@@ -688,44 +995,55 @@ where
                 vm,
                 byte_len,
                 value_summary(
-                    value,
+                    &value,
                     vr,
-                    width.saturating_sub(63 + depth * 2),
-                    no_text_limit,
-                    no_limit,
+                    settings.max_characters(63, depth),
+                    settings.no_text_limit,
+                    settings.no_limit,
                 ),
             )?;
+
+            if let (Some(mut options), VR::OB | VR::UN) = (settings.hex_dump, vr) {
+                options.base_offset = elem.offset().filter(|_| settings.offsets).unwrap_or(0);
+                for line in hex_dump(&value.to_bytes(), options).lines() {
+                    line_start(to, None, depth + 1, settings)?;
+                    writeln!(to, "{}", line)?;
+                }
+            }
+        }
+        Err(e) => {
+            writeln!(
+                to,
+                "{} {:28} {} (?,{:>3} bytes): {}",
+                DumpValue::TagNum(elem.tag()),
+                DumpValue::Alias(tag_alias),
+                vr,
+                byte_len,
+                DumpValue::Invalid(format!("«{}»", e)),
+            )?;
         }
     }
 
     Ok(())
 }
 
-fn dump_item<W, D>(
-    to: &mut W,
-    item: &InMemDicomObject<D>,
-    width: u32,
-    depth: u32,
-    no_text_limit: bool,
-    no_limit: bool,
-) -> IoResult<()>
+fn dump_item<W, O>(to: &mut W, item: O, depth: u32, settings: &ElementSettings) -> fmt::Result
 where
-    W: ?Sized + Write,
-    D: DataDictionary,
+    W: ?Sized + fmt::Write,
+    O: DicomObject,
 {
-    let indent: String = "  ".repeat(depth as usize);
+    line_start(to, None, depth, settings)?;
     writeln!(
         to,
-        "{}{} na {}",
-        indent,
+        "{} na {}",
         DumpValue::TagNum("(FFFE,E000)"),
         DumpValue::Alias("Item"),
     )?;
-    dump(to, item, width, depth + 1, no_text_limit, no_limit)?;
+    dump_elements(to, &item, depth + 1, settings)?;
+    line_start(to, None, depth, settings)?;
     writeln!(
         to,
-        "{}{} {}",
-        indent,
+        "{} {}",
         DumpValue::TagNum("(FFFE,E00D)"),
         DumpValue::Alias("ItemDelimitationItem"),
     )?;
@@ -836,16 +1154,22 @@ fn value_summary(
             DumpValue::DateTime(format_value_list(values, max_characters, false))
         }
         (Str(value), _) => {
+            let value = value.trim_end_matches(whitespace_or_null);
+            // only the part of the text which can be shown is processed
+            let end = max_characters.and_then(|max| value.char_indices().nth(max as usize));
+            let value = match end {
+                Some((end, _)) => &value[..end],
+                None => value,
+            };
             let txt = format!(
                 "\"{}\"",
-                value
-                    .to_string()
-                    .trim_end_matches(whitespace_or_null)
-                    // sanitize input
-                    .replace('\n', "␊")
-                    .replace('\r', "␍")
-                    .replace('\0', "␀")
-                    .replace(|c: char| c.is_control(), "�")
+                // sanitize input
+                DisplayValue::text(
+                    &value
+                        .replace('\n', "␊")
+                        .replace('\r', "␍")
+                        .replace('\0', "␀")
+                )
             );
             if let Some(max) = max_characters {
                 DumpValue::Str(cut_str(&txt, max).to_string())
@@ -867,7 +1191,9 @@ fn item_value_summary(data: &[u8], max_characters: Option<u32>) -> DumpValue<Str
 
 fn offset_table_summary(data: &[u32], max_characters: Option<u32>) -> String {
     if data.is_empty() {
-        format!("{}", "(empty)".italic())
+        "(empty)"
+            .if_supports_color(Stream::Stdout, |v| v.italic())
+            .to_string()
     } else {
         format_value_list(
             data.iter().map(|n| format!("{:04X}", n)),
@@ -896,8 +1222,8 @@ where
         piece = piece
             .replace('\n', "␊")
             .replace('\r', "␍")
-            .replace('\0', "␀")
-            .replace(|c: char| c.is_control(), "�");
+            .replace('\0', "␀");
+        piece = DisplayValue::text(&piece).to_string();
 
         if acc_size > 0 {
             pieces.push_str(", ");
@@ -960,7 +1286,7 @@ mod tests {
     use dicom_object::{FileMetaTableBuilder, InMemDicomObject};
 
     use super::whitespace_or_null;
-    use crate::{ColorMode, DumpOptions};
+    use crate::{ColorMode, DumpOptions, HexDumpOptions};
 
     #[test]
     fn trims_all_whitespace() {
@@ -1096,4 +1422,68 @@ mod tests {
             assert_eq!(value, expected.3);
         }
     }
+
+    #[test]
+    fn dump_hex_of_binary_values() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(2_u16)),
+            DataElement::new(
+                tags::ICC_PROFILE,
+                VR::OB,
+                PrimitiveValue::from((0x30..0x30 + 40).collect::<Vec<u8>>()),
+            ),
+        ]);
+
+        let mut hex_options = HexDumpOptions::new();
+        hex_options.max_lines(2);
+        let mut options = DumpOptions::new();
+        options
+            .color_mode(ColorMode::Never)
+            .value_width(12)
+            .hex_dump(hex_options);
+        let out = options.display(&obj).to_string();
+
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 5, "{}", out);
+        assert!(lines[0].starts_with("(0028,0010) Rows"));
+        assert!(lines[1].starts_with("(0028,2000) ICCProfile"));
+        assert_eq!(
+            lines[2],
+            "  00000000  30 31 32 33 34 35 36 37  38 39 3A 3B 3C 3D 3E 3F  |0123456789:;<=>?|"
+        );
+        assert_eq!(
+            lines[3],
+            "  00000010  40 41 42 43 44 45 46 47  48 49 4A 4B 4C 4D 4E 4F  |@ABCDEFGHIJKLMNO|"
+        );
+        assert_eq!(lines[4], "  … 8 more bytes");
+    }
+
+    #[test]
+    fn dump_escapes_control_characters() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::STUDY_DESCRIPTION,
+                VR::LO,
+                PrimitiveValue::from("Brain\u{7}"),
+            ),
+            DataElement::new(
+                tags::PATIENT_NAME,
+                VR::PN,
+                PrimitiveValue::from("Doe^John\u{1b}[2J"),
+            ),
+        ]);
+
+        let mut out = Vec::new();
+        DumpOptions::new()
+            .color_mode(ColorMode::Never)
+            .dump_object_to(&mut out, &obj)
+            .unwrap();
+        let out = std::str::from_utf8(&out).unwrap();
+        assert!(!out.contains('\u{1b}'));
+        assert!(!out.contains('\u{7}'));
+
+        let lines: Vec<_> = out.lines().collect();
+        assert!(lines[0].ends_with("\"Brain\\x07\""), "{}", lines[0]);
+        assert!(lines[1].ends_with("\"Doe^John\\x1b[2J\""), "{}", lines[1]);
+    }
 }
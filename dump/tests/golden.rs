@@ -0,0 +1,157 @@
+//! Dumping of a nested data set,
+//! checked against expected text files.
+use std::io::Cursor;
+
+use dicom_core::value::{DataSetSequence, PixelFragmentSequence};
+use dicom_core::{dicom_value, DataElement, Length, PrimitiveValue, VR};
+use dicom_dictionary_std::{tags, uids};
+use dicom_dump::{dump, ColorMode, DumpOptions, HexDumpOptions};
+use dicom_object::{FileMetaTableBuilder, InMemDicomObject};
+
+fn expected(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn nested() -> InMemDicomObject {
+    InMemDicomObject::from_element_iter([
+        DataElement::new(
+            tags::SOP_CLASS_UID,
+            VR::UI,
+            PrimitiveValue::from(uids::CT_IMAGE_STORAGE),
+        ),
+        DataElement::new(
+            tags::STUDY_DESCRIPTION,
+            VR::LO,
+            PrimitiveValue::from("A rather long study description, for trimming"),
+        ),
+        DataElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::new(
+                vec![
+                    InMemDicomObject::from_element_iter([DataElement::new(
+                        tags::REFERENCED_SOP_INSTANCE_UID,
+                        VR::UI,
+                        PrimitiveValue::from("1.2.3.4.5"),
+                    )]),
+                    InMemDicomObject::from_element_iter([
+                        DataElement::new(
+                            tags::REFERENCED_SOP_INSTANCE_UID,
+                            VR::UI,
+                            PrimitiveValue::from("1.2.3.4.6"),
+                        ),
+                        DataElement::new(
+                            tags::PURPOSE_OF_REFERENCE_CODE_SEQUENCE,
+                            VR::SQ,
+                            DataSetSequence::new(
+                                vec![InMemDicomObject::from_element_iter([
+                                    DataElement::new(
+                                        tags::CODE_VALUE,
+                                        VR::SH,
+                                        PrimitiveValue::from("121311"),
+                                    ),
+                                    DataElement::new(
+                                        tags::CODING_SCHEME_DESIGNATOR,
+                                        VR::SH,
+                                        PrimitiveValue::from("DCM"),
+                                    ),
+                                ])],
+                                Length::UNDEFINED,
+                            ),
+                        ),
+                    ]),
+                ],
+                Length::UNDEFINED,
+            ),
+        ),
+        DataElement::new(
+            tags::PIXEL_SPACING,
+            VR::DS,
+            dicom_value!(Strs, ["0.5", "0.5"]),
+        ),
+        DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(2_u16)),
+        DataElement::new(tags::COLUMNS, VR::US, PrimitiveValue::from(2_u16)),
+        DataElement::new(
+            tags::ICC_PROFILE,
+            VR::OB,
+            PrimitiveValue::from(vec![0_u8, 1, 2, 3]),
+        ),
+        DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new(
+                vec![0],
+                vec![(0..24).collect::<Vec<u8>>(), vec![0xFF, 0xD9]],
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn dump_nested_object() {
+    let mut options = DumpOptions::new();
+    options.color_mode(ColorMode::Never).value_width(32);
+
+    let mut out = String::new();
+    dump(&nested(), &mut out, &options).unwrap();
+    pretty_assertions::assert_eq!(out, expected("nested.txt"));
+
+    // the display adapter produces the same output
+    assert_eq!(options.display(&nested()).to_string(), out);
+}
+
+#[test]
+fn dump_nested_object_without_fragments() {
+    let mut options = DumpOptions::new();
+    options.color_mode(ColorMode::Never).fragments(false);
+
+    let out = options.display(&nested()).to_string();
+    assert!(
+        out.ends_with("(7FE0,0010) PixelData                    OB (PixelSequence, 3 Items)\n"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn dump_lazy_object_with_offsets() {
+    let file = nested()
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .media_storage_sop_instance_uid("1.2.3.4")
+                .transfer_syntax(uids::JPEG_BASELINE8_BIT),
+        )
+        .unwrap();
+    let mut data = Vec::new();
+    file.write_all(&mut data).unwrap();
+    let obj = dicom_object::lazy::from_reader(Cursor::new(data)).unwrap();
+
+    let mut options = DumpOptions::new();
+    options
+        .color_mode(ColorMode::Never)
+        .value_width(32)
+        .offsets(true);
+
+    let mut out = String::new();
+    dump(&obj, &mut out, &options).unwrap();
+    pretty_assertions::assert_eq!(out, expected("nested_offsets.txt"));
+
+    // hex dumps are annotated with the position in the file
+    let icc_profile = obj.element(tags::ICC_PROFILE).unwrap();
+    let offset = icc_profile.offset().unwrap();
+    options.hex_dump(HexDumpOptions::new());
+    let out = options.display(&obj).to_string();
+    let hex_line = out
+        .lines()
+        .skip_while(|line| !line.contains("ICCProfile"))
+        .nth(1)
+        .unwrap();
+    assert_eq!(
+        hex_line,
+        format!(
+            "           {:08X}  00 01 02 03                                       |....|",
+            offset
+        )
+    );
+}
@@ -0,0 +1,150 @@
+//! Dumping and attribute retrieval on a third-party DICOM object backend.
+use std::borrow::Cow;
+use std::convert::Infallible;
+
+use dicom_core::header::{HasLength, Header, Length};
+use dicom_core::value::{InMemFragment, PixelFragmentSequence};
+use dicom_core::{DataElement, PrimitiveValue, Tag, VR};
+use dicom_dictionary_std::tags;
+use dicom_dump::{ColorMode, DumpOptions};
+use dicom_object::{
+    AccessByNameError, AccessError, DicomElement, DicomObject, InMemDicomObject,
+    StandardDataDictionary,
+};
+use snafu::Backtrace;
+
+/// A trivial data set backed by a list of primitive values.
+struct MockObject {
+    elements: Vec<MockElement>,
+}
+
+struct MockElement {
+    tag: Tag,
+    vr: VR,
+    value: PrimitiveValue,
+}
+
+impl HasLength for &MockElement {
+    fn length(&self) -> Length {
+        Length(self.value.calculate_byte_len() as u32)
+    }
+}
+
+impl Header for &MockElement {
+    fn tag(&self) -> Tag {
+        self.tag
+    }
+}
+
+impl<'a> DicomElement for &'a MockElement {
+    type Error = Infallible;
+    type Item = &'a MockObject;
+    type Items = std::iter::Empty<&'a MockObject>;
+
+    fn vr(&self) -> VR {
+        self.vr
+    }
+
+    fn primitive_value(&self) -> Result<Option<Cow<'_, PrimitiveValue>>, Infallible> {
+        Ok(Some(Cow::Borrowed(&self.value)))
+    }
+
+    fn items(&self) -> Option<Self::Items> {
+        None
+    }
+
+    fn fragments(
+        &self,
+    ) -> Result<Option<Cow<'_, PixelFragmentSequence<InMemFragment>>>, Infallible> {
+        Ok(None)
+    }
+}
+
+impl<'a> DicomObject for &'a MockObject {
+    type Element = &'a MockElement;
+    type Elements = std::slice::Iter<'a, MockElement>;
+
+    fn element(&self, tag: Tag) -> Result<Self::Element, AccessError> {
+        self.elements.iter().find(|e| e.tag == tag).ok_or_else(|| {
+            AccessError::NoSuchDataElementTag {
+                tag,
+                backtrace: Backtrace::capture(),
+            }
+        })
+    }
+
+    fn element_by_name(&self, name: &str) -> Result<Self::Element, AccessByNameError> {
+        use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
+        let tag = StandardDataDictionary
+            .by_name(name)
+            .ok_or_else(|| AccessByNameError::NoSuchAttributeName {
+                name: name.to_string(),
+                backtrace: Backtrace::capture(),
+            })?
+            .tag();
+        self.element(tag).map_err(|e| e.into_access_by_name(name))
+    }
+
+    fn elements(&self) -> Self::Elements {
+        self.elements.iter()
+    }
+}
+
+fn mock_object() -> MockObject {
+    MockObject {
+        elements: vec![
+            MockElement {
+                tag: tags::PATIENT_NAME,
+                vr: VR::PN,
+                value: "Doe^John".into(),
+            },
+            MockElement {
+                tag: tags::ROWS,
+                vr: VR::US,
+                value: PrimitiveValue::from(256_u16),
+            },
+            MockElement {
+                tag: tags::PIXEL_SPACING,
+                vr: VR::DS,
+                value: "0.5\\0.25".into(),
+            },
+        ],
+    }
+}
+
+#[test]
+fn getters_work_on_mock_object() {
+    let obj = mock_object();
+    let obj = &obj;
+
+    assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), "Doe^John");
+    assert_eq!(obj.u16(tags::ROWS).unwrap(), 256);
+    assert_eq!(obj.f64s(tags::PIXEL_SPACING).unwrap(), [0.5, 0.25]);
+    assert_eq!(obj.f64(tags::PIXEL_SPACING).unwrap(), 0.5);
+    assert!(obj.string(tags::MODALITY).is_err());
+    assert_eq!(obj.element_by_name("Rows").unwrap().tag(), tags::ROWS);
+}
+
+#[test]
+fn dump_works_on_mock_object() {
+    let obj = mock_object();
+    let in_mem = InMemDicomObject::from_element_iter(
+        obj.elements
+            .iter()
+            .map(|e| DataElement::new(e.tag, e.vr, e.value.clone())),
+    );
+
+    let mut options = DumpOptions::new();
+    options.color_mode(ColorMode::Never);
+
+    let mut out = Vec::new();
+    options.dump_object_to(&mut out, &obj).unwrap();
+    let mut expected = Vec::new();
+    options.dump_object_to(&mut expected, &in_mem).unwrap();
+
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(out, String::from_utf8(expected).unwrap());
+    assert_eq!(out.lines().count(), 3);
+    assert!(out.contains("PatientName"));
+    assert!(out.contains("\"Doe^John\""));
+}
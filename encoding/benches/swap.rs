@@ -0,0 +1,76 @@
+//! Benchmarks for converting the byte order of numeric data in bulk,
+//! as done when transcoding between little and big endian.
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use dicom_core::PrimitiveValue;
+use dicom_encoding::encode::basic::BigEndianBasicEncoder;
+use dicom_encoding::encode::BasicEncode;
+use dicom_encoding::swap::{swap_bytes_in_place_16, swap_bytes_in_place_32};
+
+/// The size of the buffers in the benchmarks
+const LEN: usize = 4 * 1024 * 1024;
+
+fn swap_bytes(c: &mut Criterion) {
+    let mut data: Vec<u8> = (0..LEN).map(|i| (i * 31) as u8).collect();
+
+    let mut group = c.benchmark_group("swap_bytes");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.bench_function("naive_16", |b| {
+        b.iter(|| {
+            for elem in data.chunks_exact_mut(2) {
+                elem.swap(0, 1);
+            }
+            black_box(&data);
+        })
+    });
+    group.bench_function("bulk_16", |b| {
+        b.iter(|| {
+            swap_bytes_in_place_16(&mut data);
+            black_box(&data);
+        })
+    });
+    group.bench_function("naive_32", |b| {
+        b.iter(|| {
+            for elem in data.chunks_exact_mut(4) {
+                elem.reverse();
+            }
+            black_box(&data);
+        })
+    });
+    group.bench_function("bulk_32", |b| {
+        b.iter(|| {
+            swap_bytes_in_place_32(&mut data);
+            black_box(&data);
+        })
+    });
+    group.finish();
+}
+
+fn encode_words(c: &mut Criterion) {
+    let words: Vec<u16> = (0..LEN / 2).map(|i| (i * 31) as u16).collect();
+    let value = PrimitiveValue::U16(words.clone().into());
+    let encoder = BigEndianBasicEncoder;
+    let mut out = Vec::with_capacity(LEN);
+
+    let mut group = c.benchmark_group("encode_be_words");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.bench_function("one_at_a_time", |b| {
+        b.iter(|| {
+            out.clear();
+            for v in &words {
+                encoder.encode_us(&mut out, *v).unwrap();
+            }
+            black_box(&out);
+        })
+    });
+    group.bench_function("bulk", |b| {
+        b.iter(|| {
+            out.clear();
+            encoder.encode_primitive(&mut out, &value).unwrap();
+            black_box(&out);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, swap_bytes, encode_words);
+criterion_main!(benches);
@@ -316,10 +316,15 @@ pub trait PixelDataWriter {
     ) -> EncodeResult<Vec<AttributeOp>> {
         let frames = src.number_of_frames().unwrap_or(1);
         let mut out = Vec::new();
+        // offset of the next frame's first item,
+        // relative to the first item after the offset table
+        let mut offset = 0;
         for frame in 0..frames {
             let mut frame_data = Vec::new();
             out = self.encode_frame(src, frame, options.clone(), &mut frame_data)?;
-            offset_table.push(frame_data.len() as u32 + 8 * (frame + 1));
+            offset_table.push(offset);
+            // fragments are padded to an even length when written
+            offset += ((frame_data.len() as u32 + 1) & !1) + 8;
             dst.push(frame_data);
         }
         Ok(out)
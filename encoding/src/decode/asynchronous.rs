@@ -0,0 +1,246 @@
+//! Asynchronous decoding of data element headers
+//! over [`tokio::io::AsyncRead`] sources.
+//!
+//! No decoding logic lives here:
+//! [`AsyncDecoder`] fetches the bytes of each header,
+//! then interprets them with a synchronous [`Decode`] implementation
+//! over an in-memory slice.
+//! Decoding results and errors are therefore the same
+//! as when reading the same bytes synchronously.
+//!
+//! This module requires the `async` feature.
+use crate::decode::{Decode, Error, ReadHeaderTagSnafu, ReadItemHeaderSnafu, ReadTagSnafu, Result};
+use dicom_core::header::{DataElementHeader, SequenceItemHeader};
+use dicom_core::Tag;
+use snafu::ResultExt;
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// The maximum length of a data element header in bytes.
+const MAX_HEADER_LENGTH: usize = 12;
+
+/// An asynchronous data element decoder,
+/// wrapping a synchronous one.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{Tag, VR};
+/// # use dicom_encoding::decode::asynchronous::AsyncDecoder;
+/// # use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let decoder = AsyncDecoder::new(ExplicitVRLittleEndianDecoder::default());
+/// let mut source: &[u8] = &[
+///     0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R',
+/// ];
+/// let (header, bytes_read) = decoder.decode_header(&mut source).await?;
+/// assert_eq!(header.tag, Tag(0x0008, 0x0060));
+/// assert_eq!(header.vr, VR::CS);
+/// assert_eq!(bytes_read, 8);
+///
+/// let mut value = Vec::new();
+/// decoder.read_value(&mut source, 2, &mut value).await?;
+/// assert_eq!(value, b"MR");
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct AsyncDecoder<D> {
+    decoder: D,
+}
+
+impl<D> AsyncDecoder<D> {
+    /// Create an asynchronous decoder from a synchronous one.
+    pub fn new(decoder: D) -> Self {
+        AsyncDecoder { decoder }
+    }
+
+    /// Retrieve the underlying synchronous decoder.
+    pub fn into_inner(self) -> D {
+        self.decoder
+    }
+}
+
+impl<D> AsyncDecoder<D>
+where
+    D: Decode,
+{
+    /// Fetch and decode the next data element header from the given source.
+    ///
+    /// See [`Decode::decode_header`].
+    pub async fn decode_header<S>(&self, source: &mut S) -> Result<(DataElementHeader, usize)>
+    where
+        S: ?Sized + AsyncRead + Unpin,
+    {
+        self.decode_header_with_unknown_vr(source)
+            .await
+            .map(|(header, bytes_read, _)| (header, bytes_read))
+    }
+
+    /// Fetch and decode the next data element header from the given source,
+    /// also retrieving the value representation code found in the header
+    /// if it is not known to this library and the element was read as `UN`.
+    ///
+    /// See [`Decode::decode_header_with_unknown_vr`].
+    pub async fn decode_header_with_unknown_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + AsyncRead + Unpin,
+    {
+        let mut buf = [0; MAX_HEADER_LENGTH];
+        let mut got = 0;
+        // the shortest header possible,
+        // extended once the decoder asks for more
+        let mut want = 8;
+        loop {
+            got += fill(source, &mut buf[got..want])
+                .await
+                .context(ReadHeaderTagSnafu)?;
+            match self.decoder.decode_header_with_unknown_vr(&mut &buf[..got]) {
+                Err(Error::TruncatedHeader { expected, .. })
+                    if got == want && expected > want && expected <= MAX_HEADER_LENGTH =>
+                {
+                    want = expected;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Fetch and decode the next sequence item head from the given source.
+    ///
+    /// See [`Decode::decode_item_header`].
+    pub async fn decode_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
+    where
+        S: ?Sized + AsyncRead + Unpin,
+    {
+        let mut buf = [0; 8];
+        let got = fill(source, &mut buf).await.context(ReadItemHeaderSnafu)?;
+        self.decoder.decode_item_header(&mut &buf[..got])
+    }
+
+    /// Decode a DICOM attribute tag from the given source.
+    ///
+    /// See [`Decode::decode_tag`].
+    pub async fn decode_tag<S>(&self, source: &mut S) -> Result<Tag>
+    where
+        S: ?Sized + AsyncRead + Unpin,
+    {
+        let mut buf = [0; 4];
+        let got = fill(source, &mut buf).await.context(ReadTagSnafu)?;
+        self.decoder.decode_tag(&mut &buf[..got])
+    }
+
+    /// Read the `len` bytes of a value from the given source,
+    /// appending them to `to`.
+    ///
+    /// The bytes are not interpreted in any way.
+    pub async fn read_value<S>(&self, source: &mut S, len: u32, to: &mut Vec<u8>) -> io::Result<()>
+    where
+        S: ?Sized + AsyncRead + Unpin,
+    {
+        let start = to.len();
+        to.resize(start + len as usize, 0);
+        source.read_exact(&mut to[start..]).await?;
+        Ok(())
+    }
+}
+
+/// Read from the source until the buffer is full or the source ends,
+/// returning the number of bytes read.
+async fn fill<S>(source: &mut S, buf: &mut [u8]) -> io::Result<usize>
+where
+    S: ?Sized + AsyncRead + Unpin,
+{
+    let mut got = 0;
+    while got < buf.len() {
+        match source.read(&mut buf[got..]).await {
+            Ok(0) => break,
+            Ok(n) => got += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(got)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncDecoder;
+    use crate::decode::explicit_le::ExplicitVRLittleEndianDecoder;
+    use crate::decode::implicit_le::ImplicitVRLittleEndianDecoder;
+    use crate::decode::{Decode, Error};
+    use dicom_core::header::{Length, SequenceItemHeader};
+    use dicom_core::{Tag, VR};
+    use tokio::io::AsyncWriteExt;
+
+    // (0008,0060) CS "MR", then an empty (0008,1140) SQ of undefined length
+    const RAW: &[u8] = &[
+        0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R', //
+        0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, //
+        0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[tokio::test]
+    async fn decode_headers_in_small_chunks() {
+        let decoder = AsyncDecoder::new(ExplicitVRLittleEndianDecoder::default());
+        let (mut client, mut server) = tokio::io::duplex(3);
+
+        let write = async move {
+            for chunk in RAW.chunks(3) {
+                server.write_all(chunk).await.unwrap();
+            }
+        };
+        let read = async {
+            let (header, len) = decoder.decode_header(&mut client).await.unwrap();
+            assert_eq!(
+                (header.tag, header.vr, header.len),
+                (Tag(0x0008, 0x0060), VR::CS, Length(2))
+            );
+            assert_eq!(len, 8);
+            let mut value = Vec::new();
+            decoder
+                .read_value(&mut client, 2, &mut value)
+                .await
+                .unwrap();
+            assert_eq!(value, b"MR");
+
+            let (header, len) = decoder.decode_header(&mut client).await.unwrap();
+            assert_eq!((header.tag, header.vr), (Tag(0x0008, 0x1140), VR::SQ));
+            assert!(header.len.is_undefined());
+            assert_eq!(len, 12);
+
+            let item = decoder.decode_item_header(&mut client).await.unwrap();
+            assert_eq!(item, SequenceItemHeader::SequenceDelimiter);
+
+            // the source has ended
+            match decoder.decode_header(&mut client).await {
+                Err(Error::ReadHeaderTag { source, .. }) => {
+                    assert_eq!(source.kind(), std::io::ErrorKind::UnexpectedEof)
+                }
+                other => panic!("unexpected result {:?}", other),
+            }
+        };
+        tokio::join!(write, read);
+    }
+
+    #[tokio::test]
+    async fn truncated_header_matches_sync_decoder() {
+        let decoder = AsyncDecoder::new(ImplicitVRLittleEndianDecoder::default());
+        let mut source: &[u8] = &[0x10, 0x00, 0x10, 0x00, 0x04, 0x00];
+
+        let sync_err = decoder.decoder.decode_header(&mut &*source).unwrap_err();
+        let async_err = decoder.decode_header(&mut source).await.unwrap_err();
+        assert!(matches!(
+            async_err,
+            Error::TruncatedHeader {
+                expected: 8,
+                got: 6,
+                ..
+            }
+        ));
+        assert_eq!(async_err.to_string(), sync_err.to_string());
+    }
+}
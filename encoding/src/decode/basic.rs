@@ -2,6 +2,7 @@
 //! may be in either Little Endian or Big Endian.
 
 use super::BasicDecode;
+use crate::swap::read_values_into;
 use byteordered::{ByteOrdered, Endianness};
 use std::io::Read;
 
@@ -27,9 +28,7 @@ impl BasicDecode for LittleEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::le(source)
-            .read_u16_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Little, u16::from_ne_bytes)
     }
 
     fn decode_ul<S>(&self, source: S) -> Result<u32>
@@ -43,9 +42,7 @@ impl BasicDecode for LittleEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::le(source)
-            .read_u32_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Little, u32::from_ne_bytes)
     }
 
     fn decode_uv<S>(&self, source: S) -> Result<u64>
@@ -59,9 +56,7 @@ impl BasicDecode for LittleEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::le(source)
-            .read_u64_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Little, u64::from_ne_bytes)
     }
 
     fn decode_ss<S>(&self, source: S) -> Result<i16>
@@ -75,9 +70,7 @@ impl BasicDecode for LittleEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::le(source)
-            .read_i16_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Little, i16::from_ne_bytes)
     }
 
     fn decode_sl<S>(&self, source: S) -> Result<i32>
@@ -91,9 +84,7 @@ impl BasicDecode for LittleEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::le(source)
-            .read_i32_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Little, i32::from_ne_bytes)
     }
 
     fn decode_sv<S>(&self, source: S) -> Result<i64>
@@ -107,9 +98,7 @@ impl BasicDecode for LittleEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::le(source)
-            .read_i64_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Little, i64::from_ne_bytes)
     }
 
     fn decode_fl<S>(&self, source: S) -> Result<f32>
@@ -123,9 +112,7 @@ impl BasicDecode for LittleEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::le(source)
-            .read_f32_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Little, f32::from_ne_bytes)
     }
 
     fn decode_fd<S>(&self, source: S) -> Result<f64>
@@ -139,9 +126,7 @@ impl BasicDecode for LittleEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::le(source)
-            .read_f64_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Little, f64::from_ne_bytes)
     }
 }
 
@@ -165,9 +150,7 @@ impl BasicDecode for BigEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::be(source)
-            .read_u16_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Big, u16::from_ne_bytes)
     }
 
     fn decode_ul<S>(&self, source: S) -> Result<u32>
@@ -181,9 +164,7 @@ impl BasicDecode for BigEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::be(source)
-            .read_u32_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Big, u32::from_ne_bytes)
     }
 
     fn decode_uv<S>(&self, source: S) -> Result<u64>
@@ -197,9 +178,7 @@ impl BasicDecode for BigEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::be(source)
-            .read_u64_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Big, u64::from_ne_bytes)
     }
 
     fn decode_ss<S>(&self, source: S) -> Result<i16>
@@ -213,9 +192,7 @@ impl BasicDecode for BigEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::be(source)
-            .read_i16_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Big, i16::from_ne_bytes)
     }
 
     fn decode_sl<S>(&self, source: S) -> Result<i32>
@@ -229,9 +206,7 @@ impl BasicDecode for BigEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::be(source)
-            .read_i32_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Big, i32::from_ne_bytes)
     }
 
     fn decode_sv<S>(&self, source: S) -> Result<i64>
@@ -245,9 +220,7 @@ impl BasicDecode for BigEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::be(source)
-            .read_i64_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Big, i64::from_ne_bytes)
     }
 
     fn decode_fl<S>(&self, source: S) -> Result<f32>
@@ -261,9 +234,7 @@ impl BasicDecode for BigEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::be(source)
-            .read_f32_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Big, f32::from_ne_bytes)
     }
 
     fn decode_fd<S>(&self, source: S) -> Result<f64>
@@ -277,9 +248,7 @@ impl BasicDecode for BigEndianBasicDecoder {
     where
         S: Read,
     {
-        ByteOrdered::be(source)
-            .read_f64_into(target)
-            .map_err(Into::into)
+        read_values_into(source, target, Endianness::Big, f64::from_ne_bytes)
     }
 }
 
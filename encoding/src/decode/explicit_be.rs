@@ -2,9 +2,9 @@
 
 use crate::decode::basic::BigEndianBasicDecoder;
 use crate::decode::{
-    BadSequenceHeaderSnafu, BasicDecode, Decode, DecodeFrom, ReadHeaderTagSnafu,
-    ReadItemHeaderSnafu, ReadItemLengthSnafu, ReadLengthSnafu, ReadReservedSnafu, ReadTagSnafu,
-    ReadVrSnafu, Result,
+    decode_vr, truncated_header, BadSequenceHeaderSnafu, BasicDecode, CountingRead, Decode,
+    DecodeFrom, ReadHeaderTagSnafu, ReadItemHeaderSnafu, ReadItemLengthSnafu, ReadLengthSnafu,
+    ReadReservedSnafu, ReadTagSnafu, ReadVrSnafu, Result,
 };
 use byteordered::byteorder::{BigEndian, ByteOrder};
 use dicom_core::header::{DataElementHeader, Length, SequenceItemHeader};
@@ -18,8 +18,11 @@ pub struct ExplicitVRBigEndianDecoder {
     basic: BigEndianBasicDecoder,
 }
 
-impl Decode for ExplicitVRBigEndianDecoder {
-    fn decode_header<S>(&self, mut source: &mut S) -> Result<(DataElementHeader, usize)>
+impl ExplicitVRBigEndianDecoder {
+    fn read_header<S>(
+        &self,
+        mut source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
     where
         S: ?Sized + Read,
     {
@@ -37,12 +40,19 @@ impl Decode for ExplicitVRBigEndianDecoder {
             return Ok((
                 DataElementHeader::new((group, element), VR::UN, Length(len)),
                 8, // tag + len
+                None,
             ));
         }
 
         // retrieve explicit VR
         source.read_exact(&mut buf[0..2]).context(ReadVrSnafu)?;
-        let vr = VR::from_binary([buf[0], buf[1]]).unwrap_or(VR::UN);
+        let vr_bytes = [buf[0], buf[1]];
+        let vr = decode_vr(vr_bytes)?;
+        let unknown_vr = if vr == VR::UN && &vr_bytes != b"UN" {
+            Some(vr_bytes)
+        } else {
+            None
+        };
 
         let bytes_read;
 
@@ -79,10 +89,11 @@ impl Decode for ExplicitVRBigEndianDecoder {
         Ok((
             DataElementHeader::new((group, element), vr, Length(len)),
             bytes_read,
+            unknown_vr,
         ))
     }
 
-    fn decode_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
+    fn read_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
     where
         S: ?Sized + Read,
     {
@@ -95,6 +106,37 @@ impl Decode for ExplicitVRBigEndianDecoder {
 
         SequenceItemHeader::new((group, element), Length(len)).context(BadSequenceHeaderSnafu)
     }
+}
+
+impl Decode for ExplicitVRBigEndianDecoder {
+    fn decode_header<S>(&self, source: &mut S) -> Result<(DataElementHeader, usize)>
+    where
+        S: ?Sized + Read,
+    {
+        Decode::decode_header_with_unknown_vr(self, source)
+            .map(|(header, bytes_read, _)| (header, bytes_read))
+    }
+
+    fn decode_header_with_unknown_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + Read,
+    {
+        let mut source = CountingRead::new(source);
+        self.read_header(&mut source)
+            .map_err(|e| truncated_header(e, source.count()))
+    }
+
+    fn decode_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
+    where
+        S: ?Sized + Read,
+    {
+        let mut source = CountingRead::new(source);
+        self.read_item_header(&mut source)
+            .map_err(|e| truncated_header(e, source.count()))
+    }
 
     fn decode_tag<S>(&self, source: &mut S) -> Result<Tag>
     where
@@ -118,6 +160,14 @@ where
         Decode::decode_header(self, source)
     }
 
+    #[inline]
+    fn decode_header_with_unknown_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        Decode::decode_header_with_unknown_vr(self, source)
+    }
+
     #[inline]
     fn decode_item_header(&self, source: &mut S) -> Result<SequenceItemHeader> {
         Decode::decode_item_header(self, source)
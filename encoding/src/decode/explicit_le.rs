@@ -2,9 +2,9 @@
 
 use crate::decode::basic::LittleEndianBasicDecoder;
 use crate::decode::{
-    BadSequenceHeaderSnafu, BasicDecode, Decode, DecodeFrom, ReadHeaderTagSnafu,
-    ReadItemHeaderSnafu, ReadItemLengthSnafu, ReadLengthSnafu, ReadReservedSnafu, ReadTagSnafu,
-    ReadVrSnafu, Result,
+    decode_vr, truncated_header, BadSequenceHeaderSnafu, BasicDecode, CountingRead, Decode,
+    DecodeFrom, ReadHeaderTagSnafu, ReadItemHeaderSnafu, ReadItemLengthSnafu, ReadLengthSnafu,
+    ReadReservedSnafu, ReadTagSnafu, ReadVrSnafu, Result,
 };
 use byteordered::byteorder::{ByteOrder, LittleEndian};
 use dicom_core::header::{DataElementHeader, Length, SequenceItemHeader};
@@ -18,8 +18,11 @@ pub struct ExplicitVRLittleEndianDecoder {
     basic: LittleEndianBasicDecoder,
 }
 
-impl Decode for ExplicitVRLittleEndianDecoder {
-    fn decode_header<S>(&self, mut source: &mut S) -> Result<(DataElementHeader, usize)>
+impl ExplicitVRLittleEndianDecoder {
+    fn read_header<S>(
+        &self,
+        mut source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
     where
         S: ?Sized + Read,
     {
@@ -37,12 +40,19 @@ impl Decode for ExplicitVRLittleEndianDecoder {
             return Ok((
                 DataElementHeader::new((group, element), VR::UN, Length(len)),
                 8, // tag + len
+                None,
             ));
         }
 
         // retrieve explicit VR
         source.read_exact(&mut buf[0..2]).context(ReadVrSnafu)?;
-        let vr = VR::from_binary([buf[0], buf[1]]).unwrap_or(VR::UN);
+        let vr_bytes = [buf[0], buf[1]];
+        let vr = decode_vr(vr_bytes)?;
+        let unknown_vr = if vr == VR::UN && &vr_bytes != b"UN" {
+            Some(vr_bytes)
+        } else {
+            None
+        };
         let bytes_read;
 
         // retrieve data length
@@ -76,10 +86,11 @@ impl Decode for ExplicitVRLittleEndianDecoder {
         Ok((
             DataElementHeader::new((group, element), vr, Length(len)),
             bytes_read,
+            unknown_vr,
         ))
     }
 
-    fn decode_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
+    fn read_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
     where
         S: ?Sized + Read,
     {
@@ -92,6 +103,37 @@ impl Decode for ExplicitVRLittleEndianDecoder {
 
         SequenceItemHeader::new((group, element), Length(len)).context(BadSequenceHeaderSnafu)
     }
+}
+
+impl Decode for ExplicitVRLittleEndianDecoder {
+    fn decode_header<S>(&self, source: &mut S) -> Result<(DataElementHeader, usize)>
+    where
+        S: ?Sized + Read,
+    {
+        Decode::decode_header_with_unknown_vr(self, source)
+            .map(|(header, bytes_read, _)| (header, bytes_read))
+    }
+
+    fn decode_header_with_unknown_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + Read,
+    {
+        let mut source = CountingRead::new(source);
+        self.read_header(&mut source)
+            .map_err(|e| truncated_header(e, source.count()))
+    }
+
+    fn decode_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
+    where
+        S: ?Sized + Read,
+    {
+        let mut source = CountingRead::new(source);
+        self.read_item_header(&mut source)
+            .map_err(|e| truncated_header(e, source.count()))
+    }
 
     fn decode_tag<S>(&self, source: &mut S) -> Result<Tag>
     where
@@ -115,6 +157,14 @@ where
         Decode::decode_header(self, source)
     }
 
+    #[inline]
+    fn decode_header_with_unknown_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        Decode::decode_header_with_unknown_vr(self, source)
+    }
+
     #[inline]
     fn decode_item_header(&self, source: &mut S) -> Result<SequenceItemHeader> {
         Decode::decode_item_header(self, source)
@@ -256,4 +306,125 @@ mod tests {
             assert_eq!(elem.length(), Length(0));
         }
     }
+
+    #[test]
+    fn decode_unknown_and_invalid_vr() {
+        let dec = ExplicitVRLittleEndianDecoder::default();
+
+        // well-formed but unknown VR: read as UN
+        #[rustfmt::skip]
+        let raw: &[u8] = &[
+            0x09, 0x00, 0x10, 0x00, b'Z', b'Z', 0x00, 0x00,
+            0x02, 0x00, 0x00, 0x00,
+        ];
+        let (elem, bytes_read) = dec
+            .decode_header(&mut Cursor::new(raw))
+            .expect("should read a header");
+        assert_eq!(elem.vr(), VR::UN);
+        assert_eq!(elem.length(), Length(2));
+        assert_eq!(bytes_read, 12);
+
+        // the unknown code can be retrieved
+        let (_, _, unknown_vr) = dec
+            .decode_header_with_unknown_vr(&mut Cursor::new(raw))
+            .expect("should read a header");
+        assert_eq!(unknown_vr, Some(*b"ZZ"));
+
+        // but a proper UN is not reported
+        #[rustfmt::skip]
+        let raw: &[u8] = &[
+            0x09, 0x00, 0x10, 0x00, b'U', b'N', 0x00, 0x00,
+            0x02, 0x00, 0x00, 0x00,
+        ];
+        let (elem, _, unknown_vr) = dec
+            .decode_header_with_unknown_vr(&mut Cursor::new(raw))
+            .expect("should read a header");
+        assert_eq!(elem.vr(), VR::UN);
+        assert_eq!(unknown_vr, None);
+
+        // not a VR at all
+        let raw: &[u8] = &[0x09, 0x00, 0x10, 0x00, 0x00, 0x04, 0x02, 0x00];
+        let err = dec
+            .decode_header(&mut Cursor::new(raw))
+            .expect_err("should not read a header");
+        assert!(matches!(
+            err,
+            crate::decode::Error::InvalidVr {
+                bytes: [0x00, 0x04],
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn decode_truncated_header() {
+        let dec = ExplicitVRLittleEndianDecoder::default();
+
+        // no data at all: not a truncation
+        let err = dec
+            .decode_header(&mut Cursor::new(&[][..]))
+            .expect_err("should not read a header");
+        assert!(matches!(err, crate::decode::Error::ReadHeaderTag { .. }));
+
+        // OB header cut short within the length field
+        let raw: &[u8] = &[0xE0, 0x7F, 0x10, 0x00, b'O', b'B', 0x00, 0x00, 0x10, 0x00];
+        let err = dec
+            .decode_header(&mut Cursor::new(raw))
+            .expect_err("should not read a header");
+        assert!(matches!(
+            err,
+            crate::decode::Error::TruncatedHeader {
+                expected: 12,
+                got: 10,
+                ..
+            }
+        ));
+
+        // item header cut short within the tag
+        let raw: &[u8] = &[0xFE, 0xFF, 0x00];
+        let err = dec
+            .decode_item_header(&mut Cursor::new(raw))
+            .expect_err("should not read an item header");
+        assert!(matches!(
+            err,
+            crate::decode::Error::TruncatedHeader {
+                expected: 8,
+                got: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn decode_headers_from_slice() {
+        let dec = ExplicitVRLittleEndianDecoder::default();
+
+        let (elem, len) = dec.decode_header_from_slice(RAW).unwrap();
+        assert_eq!(
+            (elem.tag(), elem.vr(), elem.length()),
+            (Tag(2, 2), VR::UI, Length(26))
+        );
+        assert_eq!(len, 8);
+        let (elem, len) = dec.decode_header_from_slice(&RAW[34..]).unwrap();
+        assert_eq!(
+            (elem.tag(), elem.vr(), elem.length()),
+            (Tag(2, 16), VR::UI, Length(20))
+        );
+        assert_eq!(len, 8);
+
+        let (elem, len) = dec.decode_header_from_slice(RAW_SEQUENCE_ITEMS).unwrap();
+        assert_eq!((elem.tag(), elem.vr()), (Tag(8, 0x103F), VR::SQ));
+        assert_eq!(len, 12);
+        let (item, len) = dec
+            .decode_item_header_from_slice(&RAW_SEQUENCE_ITEMS[12..])
+            .unwrap();
+        assert!(item.is_item());
+        assert_eq!(len, 8);
+
+        // not enough bytes for a header
+        assert!(dec.decode_header_from_slice(&RAW[..6]).is_err());
+        assert!(dec
+            .decode_item_header_from_slice(&RAW_SEQUENCE_ITEMS[12..16])
+            .is_err());
+    }
 }
@@ -2,8 +2,8 @@
 
 use crate::decode::basic::LittleEndianBasicDecoder;
 use crate::decode::{
-    BadSequenceHeaderSnafu, BasicDecode, DecodeFrom, ReadHeaderTagSnafu, ReadLengthSnafu,
-    ReadTagSnafu, Result,
+    truncated_header, BadSequenceHeaderSnafu, BasicDecode, CountingRead, DecodeFrom,
+    ReadHeaderTagSnafu, ReadLengthSnafu, ReadTagSnafu, Result,
 };
 use crate::Decode;
 use byteordered::byteorder::{ByteOrder, LittleEndian};
@@ -70,11 +70,11 @@ where
     }
 }
 
-impl<D> Decode for ImplicitVRLittleEndianDecoder<D>
+impl<D> ImplicitVRLittleEndianDecoder<D>
 where
     D: DataDictionary,
 {
-    fn decode_header<S>(&self, mut source: &mut S) -> Result<(DataElementHeader, usize)>
+    fn read_header<S>(&self, mut source: &mut S) -> Result<(DataElementHeader, usize)>
     where
         S: ?Sized + Read,
     {
@@ -104,7 +104,7 @@ where
         Ok((DataElementHeader::new(tag, vr, Length(len)), 8))
     }
 
-    fn decode_item_header<S>(&self, mut source: &mut S) -> Result<SequenceItemHeader>
+    fn read_item_header<S>(&self, mut source: &mut S) -> Result<SequenceItemHeader>
     where
         S: ?Sized + Read,
     {
@@ -120,6 +120,29 @@ where
         let len = LittleEndian::read_u32(&buf);
         SequenceItemHeader::new(tag, Length(len)).context(BadSequenceHeaderSnafu)
     }
+}
+
+impl<D> Decode for ImplicitVRLittleEndianDecoder<D>
+where
+    D: DataDictionary,
+{
+    fn decode_header<S>(&self, source: &mut S) -> Result<(DataElementHeader, usize)>
+    where
+        S: ?Sized + Read,
+    {
+        let mut source = CountingRead::new(source);
+        self.read_header(&mut source)
+            .map_err(|e| truncated_header(e, source.count()))
+    }
+
+    fn decode_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
+    where
+        S: ?Sized + Read,
+    {
+        let mut source = CountingRead::new(source);
+        self.read_item_header(&mut source)
+            .map_err(|e| truncated_header(e, source.count()))
+    }
 
     #[inline]
     fn decode_tag<S>(&self, source: &mut S) -> Result<Tag>
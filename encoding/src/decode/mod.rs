@@ -4,10 +4,12 @@ use self::explicit_le::ExplicitVRLittleEndianDecoder;
 use self::implicit_le::{ImplicitVRLittleEndianDecoder, StandardImplicitVRLittleEndianDecoder};
 use byteordered::Endianness;
 use dicom_core::header::{DataElementHeader, SequenceItemHeader};
-use dicom_core::Tag;
+use dicom_core::{Tag, VR};
 use snafu::{Backtrace, Snafu};
 use std::io::{self, Read};
 
+#[cfg(feature = "async")]
+pub mod asynchronous;
 pub mod basic;
 pub mod explicit_be;
 pub mod explicit_le;
@@ -57,10 +59,92 @@ pub enum Error {
     BadSequenceHeader {
         source: dicom_core::header::SequenceItemHeaderError,
     },
+    #[snafu(display("Invalid value representation bytes {:02X?}", bytes))]
+    InvalidVr {
+        bytes: [u8; 2],
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Header ended after {} of {} bytes", got, expected))]
+    TruncatedHeader {
+        /// the length of the header being read
+        expected: usize,
+        /// the number of header bytes available
+        got: usize,
+        backtrace: Backtrace,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Interpret the two bytes of an explicit value representation.
+///
+/// Well-formed codes which this library does not know about
+/// are read as `UN`, as required by the standard,
+/// whereas anything other than two upper case letters is rejected.
+pub(crate) fn decode_vr(bytes: [u8; 2]) -> Result<VR> {
+    match VR::from_binary(bytes) {
+        Some(vr) => Ok(vr),
+        None if bytes.iter().all(u8::is_ascii_uppercase) => Ok(VR::UN),
+        None => InvalidVrSnafu { bytes }.fail(),
+    }
+}
+
+/// A reader adapter which counts the bytes read through it.
+pub(crate) struct CountingRead<'a, S: ?Sized> {
+    source: &'a mut S,
+    count: usize,
+}
+
+impl<'a, S: ?Sized> CountingRead<'a, S> {
+    pub(crate) fn new(source: &'a mut S) -> Self {
+        CountingRead { source, count: 0 }
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl<S: ?Sized + Read> Read for CountingRead<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.source.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+}
+
+/// Turn an end of data in the middle of a header
+/// into a [`TruncatedHeader`](Error::TruncatedHeader) error,
+/// given the number of header bytes read before the failure.
+///
+/// When no bytes were read at all,
+/// the error is returned as is,
+/// since this is usually the regular end of the data set.
+/// The expected header length is derived from the field which failed:
+/// only the reserved bytes and 4-byte lengths of explicit VR headers
+/// imply a 12-byte header.
+pub(crate) fn truncated_header(e: Error, got: usize) -> Error {
+    let source = match &e {
+        Error::ReadHeaderTag { source, .. }
+        | Error::ReadItemHeader { source, .. }
+        | Error::ReadItemLength { source, .. }
+        | Error::ReadTag { source, .. }
+        | Error::ReadReserved { source, .. }
+        | Error::ReadLength { source, .. }
+        | Error::ReadVr { source, .. } => source,
+        _ => return e,
+    };
+    if got == 0 || source.kind() != io::ErrorKind::UnexpectedEof {
+        return e;
+    }
+    let expected: usize = match e {
+        Error::ReadReserved { .. } => 12,
+        Error::ReadLength { .. } if got >= 8 => 12,
+        _ => 8,
+    };
+    TruncatedHeaderSnafu { expected, got }.build()
+}
+
 /** Obtain the default data element decoder.
  * According to the standard, data elements are encoded in Implicit
  * VR Little Endian by default.
@@ -519,6 +603,23 @@ pub trait Decode {
     where
         S: ?Sized + Read;
 
+    /// Fetch and decode the next data element header from the given source,
+    /// like [`decode_header`](Decode::decode_header),
+    /// while also retrieving the value representation code found in the header
+    /// if it is not known to this library and the element was read as `UN`.
+    ///
+    /// The default implementation never reports an unknown code.
+    fn decode_header_with_unknown_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + Read,
+    {
+        self.decode_header(source)
+            .map(|(header, bytes_read)| (header, bytes_read, None))
+    }
+
     /** Fetch and decode the next sequence item head from the given source. It is a separate method
      * because value representation is always implicit when reading item headers and delimiters.
      * This method returns only the header of the item. At the end of this operation, the source
@@ -532,6 +633,24 @@ pub trait Decode {
     fn decode_tag<S>(&self, source: &mut S) -> Result<Tag>
     where
         S: ?Sized + Read;
+
+    /// Decode the data element header at the start of the given bytes.
+    ///
+    /// Returns the header and the number of bytes which it takes,
+    /// so that the element's value starts right after them.
+    fn decode_header_from_slice(&self, bytes: &[u8]) -> Result<(DataElementHeader, usize)> {
+        self.decode_header(&mut &*bytes)
+    }
+
+    /// Decode the sequence item header at the start of the given bytes.
+    ///
+    /// Returns the header and the number of bytes which it takes,
+    /// so that the item's data starts right after them.
+    fn decode_item_header_from_slice(&self, bytes: &[u8]) -> Result<(SequenceItemHeader, usize)> {
+        let mut source = bytes;
+        let header = self.decode_item_header(&mut source)?;
+        Ok((header, bytes.len() - source.len()))
+    }
 }
 
 impl<T: ?Sized> Decode for Box<T>
@@ -545,6 +664,16 @@ where
         (**self).decode_header(source)
     }
 
+    fn decode_header_with_unknown_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + Read,
+    {
+        (**self).decode_header_with_unknown_vr(source)
+    }
+
     fn decode_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
     where
         S: ?Sized + Read,
@@ -571,6 +700,16 @@ where
         (**self).decode_header(source)
     }
 
+    fn decode_header_with_unknown_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + Read,
+    {
+        (**self).decode_header_with_unknown_vr(source)
+    }
+
     fn decode_item_header<S>(&self, source: &mut S) -> Result<SequenceItemHeader>
     where
         S: ?Sized + Read,
@@ -604,6 +743,20 @@ pub trait DecodeFrom<S: ?Sized + Read> {
      */
     fn decode_header(&self, source: &mut S) -> Result<(DataElementHeader, usize)>;
 
+    /// Fetch and decode the next data element header from the given source,
+    /// like [`decode_header`](DecodeFrom::decode_header),
+    /// while also retrieving the value representation code found in the header
+    /// if it is not known to this library and the element was read as `UN`.
+    ///
+    /// The default implementation never reports an unknown code.
+    fn decode_header_with_unknown_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        self.decode_header(source)
+            .map(|(header, bytes_read)| (header, bytes_read, None))
+    }
+
     /** Fetch and decode the next sequence item head from the given source. It is a separate method
      * because value representation is always implicit when reading item headers and delimiters.
      * This method returns only the header of the item. At the end of this operation, the source
@@ -624,6 +777,13 @@ where
         (**self).decode_header(source)
     }
 
+    fn decode_header_with_unknown_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        (**self).decode_header_with_unknown_vr(source)
+    }
+
     fn decode_item_header(&self, source: &mut S) -> Result<SequenceItemHeader> {
         (**self).decode_item_header(source)
     }
@@ -642,6 +802,13 @@ where
         (**self).decode_header(source)
     }
 
+    fn decode_header_with_unknown_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        (**self).decode_header_with_unknown_vr(source)
+    }
+
     fn decode_item_header(&self, source: &mut S) -> Result<SequenceItemHeader> {
         (**self).decode_item_header(source)
     }
@@ -2,15 +2,16 @@
 
 use crate::encode::basic::BigEndianBasicEncoder;
 use crate::encode::{
-    BasicEncode, Encode, Result, WriteHeaderSnafu, WriteItemDelimiterSnafu, WriteItemHeaderSnafu,
-    WriteOffsetTableSnafu, WriteSequenceDelimiterSnafu, WriteTagSnafu,
+    BasicEncode, Encode, LengthTooLongSnafu, Result, WriteHeaderSnafu, WriteItemDelimiterSnafu,
+    WriteItemHeaderSnafu, WriteOffsetTableSnafu, WriteSequenceDelimiterSnafu, WriteTagSnafu,
 };
 
 use byteordered::byteorder::{BigEndian, ByteOrder};
 use byteordered::Endianness;
 use dicom_core::header::{DataElementHeader, HasLength, Header};
 use dicom_core::{PrimitiveValue, Tag, VR};
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
+use std::convert::TryFrom;
 use std::io::{self, Write};
 
 /// A concrete encoder for the transfer syntax ExplicitVRBigEndian
@@ -126,7 +127,15 @@ impl Encode for ExplicitVRBigEndianEncoder {
                 let vr_bytes = de.vr().to_bytes();
                 buf[4] = vr_bytes[0];
                 buf[5] = vr_bytes[1];
-                BigEndian::write_u16(&mut buf[6..], de.length().0 as u16);
+                // only 2 bytes are available for the length
+                let len = u16::try_from(de.length().0)
+                    .ok()
+                    .context(LengthTooLongSnafu {
+                        tag: de.tag(),
+                        vr: de.vr(),
+                        len: de.length().0,
+                    })?;
+                BigEndian::write_u16(&mut buf[6..], len);
                 to.write_all(&buf).context(WriteHeaderSnafu)?;
 
                 Ok(8)
@@ -2,14 +2,15 @@
 
 use crate::encode::basic::LittleEndianBasicEncoder;
 use crate::encode::{
-    BasicEncode, Encode, Result, WriteHeaderSnafu, WriteItemDelimiterSnafu, WriteItemHeaderSnafu,
-    WriteOffsetTableSnafu, WriteSequenceDelimiterSnafu, WriteTagSnafu,
+    BasicEncode, Encode, LengthTooLongSnafu, Result, WriteHeaderSnafu, WriteItemDelimiterSnafu,
+    WriteItemHeaderSnafu, WriteOffsetTableSnafu, WriteSequenceDelimiterSnafu, WriteTagSnafu,
 };
 use byteordered::byteorder::{ByteOrder, LittleEndian};
 use byteordered::Endianness;
 use dicom_core::header::{DataElementHeader, HasLength, Header};
 use dicom_core::{PrimitiveValue, Tag, VR};
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
+use std::convert::TryFrom;
 use std::io::{self, Write};
 
 /// A concrete encoder for the transfer syntax ExplicitVRLittleEndian
@@ -124,7 +125,15 @@ impl Encode for ExplicitVRLittleEndianEncoder {
                 let vr_bytes = de.vr().to_bytes();
                 buf[4] = vr_bytes[0];
                 buf[5] = vr_bytes[1];
-                LittleEndian::write_u16(&mut buf[6..], de.length().0 as u16);
+                // only 2 bytes are available for the length
+                let len = u16::try_from(de.length().0)
+                    .ok()
+                    .context(LengthTooLongSnafu {
+                        tag: de.tag(),
+                        vr: de.vr(),
+                        len: de.length().0,
+                    })?;
+                LittleEndian::write_u16(&mut buf[6..], len);
                 to.write_all(&buf).context(WriteHeaderSnafu)?;
                 Ok(8)
             }
@@ -295,4 +304,17 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn encode_length_too_long_for_short_header() {
+        let enc = ExplicitVRLittleEndianEncoder::default();
+        let mut out = Vec::new();
+
+        // LO has a 16-bit length field, so this must not be truncated
+        let de = DataElementHeader::new(Tag(0x0010, 0x0010), VR::LO, Length(70_000));
+        assert!(matches!(
+            enc.encode_element_header(&mut out, de),
+            Err(crate::encode::Error::LengthTooLong { len: 70_000, .. })
+        ));
+    }
 }
@@ -1,10 +1,11 @@
 //! This module contains all DICOM data element encoding logic.
+use crate::swap::write_values;
 use byteordered::Endianness;
 use dicom_core::value::serialize::{encode_date, encode_datetime, encode_time};
-use dicom_core::{DataElementHeader, PrimitiveValue, Tag};
+use dicom_core::{DataElementHeader, PrimitiveValue, Tag, VR};
 use snafu::{Backtrace, ResultExt, Snafu};
 use std::fmt;
-use std::io::{self, Write};
+use std::io::{self, IoSlice, Write};
 use std::marker::PhantomData;
 
 pub mod basic;
@@ -88,6 +89,18 @@ pub enum Error {
         backtrace: Backtrace,
         source: io::Error,
     },
+    #[snafu(display(
+        "Value length {} of element {} does not fit in a {} element header",
+        len,
+        tag,
+        vr
+    ))]
+    LengthTooLong {
+        tag: Tag,
+        vr: VR,
+        len: u32,
+        backtrace: Backtrace,
+    },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -190,59 +203,43 @@ pub trait BasicEncode {
             })
             .context(WriteStringSnafu),
             F32(values) => {
-                for v in values {
-                    self.encode_fl(&mut to, *v)
-                        .context(WriteBinarySnafu { typ: "F32" })?;
-                }
+                write_values(&mut to, values, self.endianness(), f32::to_ne_bytes)
+                    .context(WriteBinarySnafu { typ: "F32" })?;
                 Ok(values.len() * 4)
             }
             F64(values) => {
-                for v in values {
-                    self.encode_fd(&mut to, *v)
-                        .context(WriteBinarySnafu { typ: "F64" })?;
-                }
+                write_values(&mut to, values, self.endianness(), f64::to_ne_bytes)
+                    .context(WriteBinarySnafu { typ: "F64" })?;
                 Ok(values.len() * 8)
             }
             U64(values) => {
-                for v in values {
-                    self.encode_uv(&mut to, *v)
-                        .context(WriteBinarySnafu { typ: "U64" })?;
-                }
+                write_values(&mut to, values, self.endianness(), u64::to_ne_bytes)
+                    .context(WriteBinarySnafu { typ: "U64" })?;
                 Ok(values.len() * 8)
             }
             I64(values) => {
-                for v in values {
-                    self.encode_sv(&mut to, *v)
-                        .context(WriteBinarySnafu { typ: "I64" })?;
-                }
+                write_values(&mut to, values, self.endianness(), i64::to_ne_bytes)
+                    .context(WriteBinarySnafu { typ: "I64" })?;
                 Ok(values.len() * 8)
             }
             U32(values) => {
-                for v in values {
-                    self.encode_ul(&mut to, *v)
-                        .context(WriteBinarySnafu { typ: "U32" })?;
-                }
+                write_values(&mut to, values, self.endianness(), u32::to_ne_bytes)
+                    .context(WriteBinarySnafu { typ: "U32" })?;
                 Ok(values.len() * 4)
             }
             I32(values) => {
-                for v in values {
-                    self.encode_sl(&mut to, *v)
-                        .context(WriteBinarySnafu { typ: "I32" })?;
-                }
+                write_values(&mut to, values, self.endianness(), i32::to_ne_bytes)
+                    .context(WriteBinarySnafu { typ: "I32" })?;
                 Ok(values.len() * 4)
             }
             U16(values) => {
-                for v in values {
-                    self.encode_us(&mut to, *v)
-                        .context(WriteBinarySnafu { typ: "U16" })?;
-                }
+                write_values(&mut to, values, self.endianness(), u16::to_ne_bytes)
+                    .context(WriteBinarySnafu { typ: "U16" })?;
                 Ok(values.len() * 2)
             }
             I16(values) => {
-                for v in values {
-                    self.encode_ss(&mut to, *v)
-                        .context(WriteBinarySnafu { typ: "I16" })?;
-                }
+                write_values(&mut to, values, self.endianness(), i16::to_ne_bytes)
+                    .context(WriteBinarySnafu { typ: "I16" })?;
                 Ok(values.len() * 2)
             }
             U8(values) => {
@@ -281,6 +278,43 @@ where
     Ok(acc)
 }
 
+/// Write all of the given buffers to the writer,
+/// in as few calls to [`Write::write_vectored`] as the writer allows.
+///
+/// Unlike [`Write::write_all`] over each buffer,
+/// this lets writers which support vectored output
+/// receive a header and its value in a single call.
+/// Writers which only accept part of the buffers in each call
+/// (including those which only write the first non-empty buffer,
+/// as per the default implementation of `write_vectored`)
+/// are called again with the remaining bytes,
+/// so the bytes written are always the same.
+pub fn write_all_vectored<W>(to: &mut W, bufs: &[&[u8]]) -> io::Result<()>
+where
+    W: ?Sized + Write,
+{
+    let mut slices: Vec<IoSlice> = bufs
+        .iter()
+        .filter(|buf| !buf.is_empty())
+        .map(|buf| IoSlice::new(buf))
+        .collect();
+    let mut slices = &mut slices[..];
+    while !slices.is_empty() {
+        match to.write_vectored(slices) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ))
+            }
+            Ok(n) => IoSlice::advance_slices(&mut slices, n),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
 /// Type trait for a data element encoder.
 pub trait Encode {
     /// Encode and write an element tag.
@@ -294,6 +328,34 @@ pub trait Encode {
     where
         W: Write;
 
+    /// Encode a data element header
+    /// and write it to the given destination
+    /// along with the given value, already in its encoded form.
+    /// Returns the number of bytes effectively written on success,
+    /// including the value.
+    ///
+    /// The header and the value are passed to the writer
+    /// with [`write_all_vectored`],
+    /// so that they can reach the destination in a single call.
+    /// As in [`encode_element_header`](Encode::encode_element_header),
+    /// the header is encoded as is,
+    /// regardless of the length of the value.
+    fn encode_element_header_with_value<W>(
+        &self,
+        mut to: W,
+        de: DataElementHeader,
+        value: &[u8],
+    ) -> Result<usize>
+    where
+        W: Write,
+    {
+        // an element header is never longer than 12 bytes
+        let mut header = [0_u8; 12];
+        let header_len = self.encode_element_header(&mut header[..], de)?;
+        write_all_vectored(&mut to, &[&header[..header_len], value]).context(WriteBytesSnafu)?;
+        Ok(header_len + value.len())
+    }
+
     /// Encode and write a DICOM sequence item header to the given destination.
     /* Although item element headers are always a tag and length sequence regardless of TS,
     the encoding of the length is unknown at this level. So no default impl. */
@@ -357,6 +419,18 @@ where
         (**self).encode_element_header(to, de)
     }
 
+    fn encode_element_header_with_value<W>(
+        &self,
+        to: W,
+        de: DataElementHeader,
+        value: &[u8],
+    ) -> Result<usize>
+    where
+        W: Write,
+    {
+        (**self).encode_element_header_with_value(to, de, value)
+    }
+
     fn encode_item_header<W>(&self, to: W, len: u32) -> Result<()>
     where
         W: Write,
@@ -411,6 +485,18 @@ where
         (**self).encode_element_header(to, de)
     }
 
+    fn encode_element_header_with_value<W>(
+        &self,
+        to: W,
+        de: DataElementHeader,
+        value: &[u8],
+    ) -> Result<usize>
+    where
+        W: Write,
+    {
+        (**self).encode_element_header_with_value(to, de, value)
+    }
+
     fn encode_item_header<W>(&self, to: W, len: u32) -> Result<()>
     where
         W: Write,
@@ -464,6 +550,27 @@ pub trait EncodeTo<W: ?Sized> {
     where
         W: Write;
 
+    /// Encode a data element header
+    /// and write it to the given destination
+    /// along with the given value, already in its encoded form.
+    /// Returns the number of bytes effectively written on success,
+    /// including the value.
+    ///
+    /// See [`Encode::encode_element_header_with_value`].
+    fn encode_element_header_with_value(
+        &self,
+        to: &mut W,
+        de: DataElementHeader,
+        value: &[u8],
+    ) -> Result<usize>
+    where
+        W: Write,
+    {
+        let header_len = self.encode_element_header(to, de)?;
+        to.write_all(value).context(WriteBytesSnafu)?;
+        Ok(header_len + value.len())
+    }
+
     /// Encode and write a DICOM sequence item header to the given destination.
     /* Although item element headers are always a tag and length sequence regardless of TS,
     the encoding of the length is unknown at this level. So no default impl. */
@@ -516,6 +623,18 @@ where
         (**self).encode_element_header(to, de)
     }
 
+    fn encode_element_header_with_value(
+        &self,
+        to: &mut W,
+        de: DataElementHeader,
+        value: &[u8],
+    ) -> Result<usize>
+    where
+        W: Write,
+    {
+        (**self).encode_element_header_with_value(to, de, value)
+    }
+
     fn encode_item_header(&self, to: &mut W, len: u32) -> Result<()>
     where
         W: Write,
@@ -571,6 +690,18 @@ where
         (**self).encode_element_header(to, de)
     }
 
+    fn encode_element_header_with_value(
+        &self,
+        to: &mut W,
+        de: DataElementHeader,
+        value: &[u8],
+    ) -> Result<usize>
+    where
+        W: Write,
+    {
+        (**self).encode_element_header_with_value(to, de, value)
+    }
+
     fn encode_item_header(&self, to: &mut W, len: u32) -> Result<()>
     where
         W: Write,
@@ -726,6 +857,15 @@ where
         self.inner.encode_element_header(to, de)
     }
 
+    fn encode_element_header_with_value(
+        &self,
+        to: &mut W,
+        de: DataElementHeader,
+        value: &[u8],
+    ) -> Result<usize> {
+        self.inner.encode_element_header_with_value(to, de, value)
+    }
+
     fn encode_item_header(&self, to: &mut W, len: u32) -> Result<()> {
         self.inner.encode_item_header(to, len)
     }
@@ -766,4 +906,114 @@ mod tests {
         is_encode(&boxed);
         is_encode_to::<dyn Write, _>(&EncoderFor::new(boxed));
     }
+
+    /// A writer which records the size of each call
+    /// and accepts at most `max` bytes per call,
+    /// failing once with `Interrupted` before the first write.
+    #[derive(Debug, Default)]
+    struct ChunkedWriter {
+        out: Vec<u8>,
+        calls: Vec<usize>,
+        max: usize,
+        interrupted: bool,
+    }
+
+    impl Write for ChunkedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.write_vectored(&[IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+            if !self.interrupted {
+                self.interrupted = true;
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            let mut n = 0;
+            for buf in bufs {
+                let len = buf.len().min(self.max - n);
+                self.out.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+            self.calls.push(n);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_all_vectored_partial_writes() {
+        let bufs: [&[u8]; 4] = [b"HEADER", b"", b"some value", b"!"];
+        let expected = b"HEADERsome value!";
+        for max in [1, 3, 6, 7, 16, 100] {
+            let mut to = ChunkedWriter {
+                max,
+                ..Default::default()
+            };
+            write_all_vectored(&mut to, &bufs).unwrap();
+            assert_eq!(&to.out, expected, "max = {}", max);
+            assert_eq!(to.calls.len(), expected.len().div_ceil(max));
+        }
+
+        // writers without vectored output only take the first buffer
+        struct Unvectored(Vec<u8>);
+        impl Write for Unvectored {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.write(buf)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut to = Unvectored(Vec::new());
+        write_all_vectored(&mut to, &bufs).unwrap();
+        assert_eq!(&to.0, expected);
+    }
+
+    #[test]
+    fn write_all_vectored_write_zero() {
+        let mut to = ChunkedWriter {
+            max: 0,
+            ..Default::default()
+        };
+        let err = write_all_vectored(&mut to, &[b"data"]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn encode_element_header_with_value_in_one_call() {
+        let encoder = EncoderFor::new(explicit_le::ExplicitVRLittleEndianEncoder::default());
+        let de = DataElementHeader::new(Tag(0x0010, 0x0010), VR::PN, dicom_core::Length(4));
+
+        let mut to = ChunkedWriter {
+            max: usize::MAX,
+            ..Default::default()
+        };
+        let len = encoder
+            .encode_element_header_with_value(&mut to, de, b"Doe^")
+            .unwrap();
+        assert_eq!(len, 12);
+        assert_eq!(to.calls, vec![12]);
+
+        // same bytes as writing the header and the value separately
+        let mut expected = Vec::new();
+        explicit_le::ExplicitVRLittleEndianEncoder::default()
+            .encode_element_header(&mut expected, de)
+            .unwrap();
+        expected.extend_from_slice(b"Doe^");
+        assert_eq!(to.out, expected);
+
+        // and with a writer taking a few bytes at a time
+        let mut to = ChunkedWriter {
+            max: 5,
+            ..Default::default()
+        };
+        encoder
+            .encode_element_header_with_value(&mut to, de, b"Doe^")
+            .unwrap();
+        assert_eq!(to.out, expected);
+        assert_eq!(to.calls, vec![5, 5, 2]);
+    }
 }
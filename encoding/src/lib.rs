@@ -12,13 +12,16 @@
 //! of [transfer syntax specifier], which can be used to produce DICOM encoders
 //! and decoders at run-time.
 //!
-//! For the time being, all APIs are based on synchronous I/O.
+//! APIs are based on synchronous I/O,
+//! with the exception of the asynchronous header decoder
+//! in the `decode::asynchronous` module (requires the `async` feature).
 //!
 //! [transfer syntax specifier]: ./transfer_syntax/index.html
 
 pub mod adapters;
 pub mod decode;
 pub mod encode;
+pub mod swap;
 pub mod text;
 pub mod transfer_syntax;
 
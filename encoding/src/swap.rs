@@ -0,0 +1,278 @@
+//! Bulk byte order conversion of numeric data.
+//!
+//! The functions in this module reverse the byte order
+//! of every element of a given width in a byte buffer,
+//! such as the words of big endian pixel data
+//! which need to be transcoded to little endian.
+//! Data is processed in large blocks,
+//! so that the compiler can vectorize the conversion.
+//! With the `simd` feature,
+//! x86-64 processors supporting SSSE3 swap 16 bytes at a time
+//! through a byte shuffle instruction, detected at run-time.
+//!
+//! Bytes at the end of the buffer
+//! which do not make up a whole element
+//! are left untouched.
+use byteordered::Endianness;
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+/// The size of the blocks of data swapped in one go.
+const BLOCK_SIZE: usize = 64;
+
+/// The size of the intermediate buffer
+/// used when reading or writing values in bulk.
+const BUFFER_SIZE: usize = 8192;
+
+macro_rules! impl_swap {
+    ($name: ident, $typ: ty, $width: literal) => {
+        fn $name(data: &mut [u8]) {
+            let mut blocks = data.chunks_exact_mut(BLOCK_SIZE);
+            for block in &mut blocks {
+                let block: &mut [u8; BLOCK_SIZE] = block.try_into().unwrap();
+                for elem in block.chunks_exact_mut($width) {
+                    let v = <$typ>::from_ne_bytes(elem.try_into().unwrap());
+                    elem.copy_from_slice(&v.swap_bytes().to_ne_bytes());
+                }
+            }
+            for elem in blocks.into_remainder().chunks_exact_mut($width) {
+                let v = <$typ>::from_ne_bytes(elem.try_into().unwrap());
+                elem.copy_from_slice(&v.swap_bytes().to_ne_bytes());
+            }
+        }
+    };
+}
+
+impl_swap!(swap_portable_16, u16, 2);
+impl_swap!(swap_portable_32, u32, 4);
+impl_swap!(swap_portable_64, u64, 8);
+
+/// Reverse the byte order of each 16-bit element in the given data.
+pub fn swap_bytes_in_place_16(data: &mut [u8]) {
+    let done = simd::swap(data, 2);
+    swap_portable_16(&mut data[done..]);
+}
+
+/// Reverse the byte order of each 32-bit element in the given data.
+pub fn swap_bytes_in_place_32(data: &mut [u8]) {
+    let done = simd::swap(data, 4);
+    swap_portable_32(&mut data[done..]);
+}
+
+/// Reverse the byte order of each 64-bit element in the given data.
+pub fn swap_bytes_in_place_64(data: &mut [u8]) {
+    let done = simd::swap(data, 8);
+    swap_portable_64(&mut data[done..]);
+}
+
+/// Reverse the byte order of each element in the given data,
+/// each element being `width` bytes long.
+///
+/// # Panics
+///
+/// Panics if `width` is not 1, 2, 4, or 8.
+pub fn swap_bytes_in_place(data: &mut [u8], width: usize) {
+    match width {
+        1 => {}
+        2 => swap_bytes_in_place_16(data),
+        4 => swap_bytes_in_place_32(data),
+        8 => swap_bytes_in_place_64(data),
+        _ => panic!("unsupported element width {}", width),
+    }
+}
+
+/// Read values of `N` bytes each in the given byte order
+/// until the target slice is filled,
+/// converting them in bulk.
+pub(crate) fn read_values_into<R, T, const N: usize>(
+    mut from: R,
+    target: &mut [T],
+    endianness: Endianness,
+    from_ne_bytes: fn([u8; N]) -> T,
+) -> io::Result<()>
+where
+    R: Read,
+{
+    let mut buf = [0; BUFFER_SIZE];
+    for target in target.chunks_mut(BUFFER_SIZE / N) {
+        let buf = &mut buf[..target.len() * N];
+        from.read_exact(buf)?;
+        if endianness != Endianness::native() {
+            swap_bytes_in_place(buf, N);
+        }
+        for (v, bytes) in target.iter_mut().zip(buf.chunks_exact(N)) {
+            *v = from_ne_bytes(bytes.try_into().unwrap());
+        }
+    }
+    Ok(())
+}
+
+/// Write values of `N` bytes each in the given byte order,
+/// converting them in bulk.
+pub(crate) fn write_values<W, T, const N: usize>(
+    mut to: W,
+    values: &[T],
+    endianness: Endianness,
+    to_ne_bytes: fn(T) -> [u8; N],
+) -> io::Result<()>
+where
+    W: Write,
+    T: Copy,
+{
+    let mut buf = [0; BUFFER_SIZE];
+    for values in values.chunks(BUFFER_SIZE / N) {
+        let buf = &mut buf[..values.len() * N];
+        for (bytes, v) in buf.chunks_exact_mut(N).zip(values) {
+            bytes.copy_from_slice(&to_ne_bytes(*v));
+        }
+        if endianness != Endianness::native() {
+            swap_bytes_in_place(buf, N);
+        }
+        to.write_all(buf)?;
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[allow(unsafe_code)]
+mod simd {
+    use std::arch::x86_64::{
+        __m128i, _mm_loadu_si128, _mm_setr_epi8, _mm_shuffle_epi8, _mm_storeu_si128,
+    };
+
+    /// Swap the bytes of each element of the given width
+    /// in whole blocks of 16 bytes,
+    /// returning the number of bytes processed.
+    pub(super) fn swap(data: &mut [u8], width: usize) -> usize {
+        if is_x86_feature_detected!("ssse3") {
+            // Safety: the processor was checked to support SSSE3
+            unsafe { swap_ssse3(data, width) }
+        } else {
+            0
+        }
+    }
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn swap_ssse3(data: &mut [u8], width: usize) -> usize {
+        let mask = match width {
+            2 => _mm_setr_epi8(1, 0, 3, 2, 5, 4, 7, 6, 9, 8, 11, 10, 13, 12, 15, 14),
+            4 => _mm_setr_epi8(3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12),
+            8 => _mm_setr_epi8(7, 6, 5, 4, 3, 2, 1, 0, 15, 14, 13, 12, 11, 10, 9, 8),
+            _ => return 0,
+        };
+        let len = data.len();
+        let mut blocks = data.chunks_exact_mut(16);
+        for block in &mut blocks {
+            // unaligned loads and stores of exactly 16 bytes
+            let ptr = block.as_mut_ptr() as *mut __m128i;
+            _mm_storeu_si128(ptr, _mm_shuffle_epi8(_mm_loadu_si128(ptr), mask));
+        }
+        len - blocks.into_remainder().len()
+    }
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+mod simd {
+    /// No platform specific implementation is available,
+    /// so no bytes are processed.
+    #[inline]
+    pub(super) fn swap(_data: &mut [u8], _width: usize) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Swap the bytes of each element one at a time.
+    fn swap_naive(data: &mut [u8], width: usize) {
+        for elem in data.chunks_exact_mut(width) {
+            elem.reverse();
+        }
+    }
+
+    /// Generate pseudo-random bytes with a simple xorshift generator.
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn swap_matches_naive_implementation() {
+        for width in [1, 2, 4, 8] {
+            for len in [
+                0, 1, 2, 3, 7, 15, 16, 17, 63, 64, 65, 127, 1000, 4099, 65_541,
+            ] {
+                let data = random_bytes(len, 0x9E37_79B9_7F4A_7C15 ^ len as u64);
+                let mut expected = data.clone();
+                swap_naive(&mut expected, width);
+                let mut actual = data.clone();
+                swap_bytes_in_place(&mut actual, width);
+                assert_eq!(actual, expected, "width {}, length {}", width, len);
+
+                // swapping twice yields the original data
+                swap_bytes_in_place(&mut actual, width);
+                assert_eq!(actual, data, "width {}, length {}", width, len);
+            }
+        }
+    }
+
+    #[test]
+    fn swap_unaligned_data() {
+        let data = random_bytes(1031, 7);
+        for offset in 1..8 {
+            for width in [2, 4, 8] {
+                let mut expected = data[offset..].to_vec();
+                swap_naive(&mut expected, width);
+                let mut actual = data.clone();
+                swap_bytes_in_place(&mut actual[offset..], width);
+                assert_eq!(&actual[offset..], &expected[..]);
+                assert_eq!(&actual[..offset], &data[..offset]);
+            }
+        }
+    }
+
+    #[test]
+    fn read_and_write_values_in_bulk() {
+        let values: Vec<u16> = (0..5000).map(|i| (i * 7919) as u16).collect();
+
+        let mut be = Vec::new();
+        write_values(&mut be, &values, Endianness::Big, u16::to_ne_bytes).unwrap();
+        let naive: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+        assert_eq!(be, naive);
+
+        let mut le = Vec::new();
+        write_values(&mut le, &values, Endianness::Little, u16::to_ne_bytes).unwrap();
+        let naive: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(le, naive);
+
+        let mut read = vec![0_u16; values.len()];
+        read_values_into(&be[..], &mut read, Endianness::Big, u16::from_ne_bytes).unwrap();
+        assert_eq!(read, values);
+
+        let floats: Vec<f64> = (0..3000).map(|i| f64::from(i) * -0.25).collect();
+        let mut be = Vec::new();
+        write_values(&mut be, &floats, Endianness::Big, f64::to_ne_bytes).unwrap();
+        let mut read = vec![0.; floats.len()];
+        read_values_into(&be[..], &mut read, Endianness::Big, f64::from_ne_bytes).unwrap();
+        assert_eq!(read, floats);
+
+        // not enough data
+        let mut read = vec![0_u32; 4];
+        assert!(read_values_into(
+            &[0_u8; 15][..],
+            &mut read,
+            Endianness::Big,
+            u32::from_ne_bytes
+        )
+        .is_err());
+    }
+}
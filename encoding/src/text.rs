@@ -11,23 +11,48 @@
 //! | ISO-IR 109 (ISO-8859-3): Right-hand part of the Latin alphabet no. 3, the South Europe character set | ✓ | ✓ |
 //! | ISO-IR 110 (ISO-8859-4): Right-hand part of the Latin alphabet no. 4, the North Europe character set | ✓ | ✓ |
 //! | ISO-IR 144 (ISO-8859-5): The Latin/Cyrillic character set | ✓ | ✓ |
+//! | ISO-IR 127 (ISO-8859-6): The Latin/Arabic character set | ✓ | ✓ |
+//! | ISO-IR 126 (ISO-8859-7): The Latin/Greek character set | ✓ | ✓ |
+//! | ISO-IR 138 (ISO-8859-8): The Latin/Hebrew character set | ✓ | ✓ |
+//! | ISO-IR 148 (ISO-8859-9): Right-hand part of the Latin alphabet no. 5, the Turkish character set | ✓ | ✓ |
+//! | ISO-IR 203 (ISO-8859-15): Right-hand part of the Latin alphabet no. 9, the Western Europe character set with the Euro sign | ✓ | ✓ |
+//! | ISO-IR 166 (TIS 620-2533): The Thai character set | ✓ | ✓ |
 //! | ISO-IR 192: The Unicode character set based on the UTF-8 encoding | ✓ | ✓ |
 //! | GB18030: The Simplified Chinese character set | ✓ | ✓ |
-//! | JIS X 0201-1976: Code for Information Interchange | x | x |
-//! | JIS X 0208-1990: Code for the Japanese Graphic Character set for information interchange | x | x |
-//! | JIS X 0212-1990: Code of the supplementary Japanese Graphic Character set for information interchange | x | x |
-//! | KS X 1001 (registered as ISO-IR 149) for Korean Language | x | x |
-//! | TIS 620-2533 (1990) Thai Characters Code for Information Interchange | x | x |
-//! | GB2312: Simplified Chinese character set | x | x |
+//! | JIS X 0201-1976: Code for Information Interchange | ✓ | ✓ |
+//! | JIS X 0208-1990: Code for the Japanese Graphic Character set for information interchange | ✓ | ✓ |
+//! | JIS X 0212-1990: Code of the supplementary Japanese Graphic Character set for information interchange | ✓ | ✓ |
+//! | KS X 1001 (registered as ISO-IR 149) for Korean Language | ✓ | ✓ |
+//! | GBK: Simplified Chinese character set | ✓ | ✓ |
+//! | GB2312 (ISO-IR 58): Simplified Chinese character set | ✓ | ✓ |
+//!
+//! The Japanese and Korean character sets are supported
+//! through code extension techniques (ISO 2022),
+//! as declared by a multi-valued Specific Character Set
+//! such as `\ISO 2022 IR 87`.
 //!
 //! These capabilities are available through [`SpecificCharacterSet`].
+//! Data sets declaring a character set which is not supported
+//! can still be read,
+//! their text being decoded as ISO-IR 100 in a lossy manner
+//! (see [`SpecificCharacterSet::from_values_with`]).
 
-use encoding::all::{GB18030, ISO_8859_1, ISO_8859_2, ISO_8859_3, ISO_8859_4, ISO_8859_5, UTF_8};
+use encoding::all::{
+    GB18030, GBK, ISO_8859_1, ISO_8859_15, ISO_8859_2, ISO_8859_3, ISO_8859_4, ISO_8859_5,
+    ISO_8859_6, ISO_8859_7, ISO_8859_8, UTF_8, WINDOWS_1254, WINDOWS_874,
+};
 use encoding::{DecoderTrap, EncoderTrap, Encoding, RawDecoder, StringWriter};
 use snafu::{Backtrace, Snafu};
 use std::borrow::Cow;
 use std::fmt::Debug;
 
+mod iso2022;
+pub mod length;
+pub mod repertoire;
+pub mod uid;
+
+use iso2022::Iso2022;
+
 /// An error type for text encoding issues.
 #[derive(Debug, Snafu)]
 #[non_exhaustive]
@@ -84,6 +109,96 @@ pub trait TextCodec {
     /// feature multiple text values by using the backslash character ('\')
     /// as the value delimiter.
     fn encode(&self, text: &str) -> EncodeResult<Vec<u8>>;
+
+    /// Find the position of the first value delimiter
+    /// (the backslash character, '\') in the given encoded text.
+    ///
+    /// The default implementation looks for the first backslash byte.
+    /// Character sets in which that byte may be part of a multi-byte character
+    /// should override this method,
+    /// so that no character is cut when splitting the values apart.
+    fn find_value_delimiter(&self, text: &[u8]) -> Option<usize> {
+        text.iter().position(|b| *b == b'\\')
+    }
+
+    /// Encode a text value into a byte vector,
+    /// replacing characters which cannot be represented
+    /// with a question mark ('?').
+    ///
+    /// The default implementation falls back to encoding
+    /// each character separately if the text as a whole cannot be encoded.
+    fn encode_lossy(&self, text: &str) -> Vec<u8> {
+        self.encode(text)
+            .unwrap_or_else(|_| encode_each_char_lossy(self, text))
+    }
+
+    /// Decode the given byte buffer as a person name (PN) value,
+    /// of which each component group (delimited by `'='`)
+    /// may be in a different code element of the character set.
+    ///
+    /// The default implementation decodes the text as a whole,
+    /// as with [`decode`](TextCodec::decode).
+    fn decode_person_name(&self, text: &[u8]) -> DecodeResult<String> {
+        self.decode(text)
+    }
+
+    /// Encode a person name (PN) value into a byte vector,
+    /// using the code elements allowed for each component group.
+    ///
+    /// The default implementation encodes the text as a whole,
+    /// as with [`encode`](TextCodec::encode).
+    fn encode_person_name(&self, text: &str) -> EncodeResult<Vec<u8>> {
+        self.encode(text)
+    }
+
+    /// Encode a person name (PN) value into a byte vector,
+    /// replacing characters which cannot be represented in their component group
+    /// with a question mark ('?').
+    ///
+    /// The default implementation encodes the text as a whole,
+    /// as with [`encode_lossy`](TextCodec::encode_lossy).
+    fn encode_person_name_lossy(&self, text: &str) -> Vec<u8> {
+        self.encode_lossy(text)
+    }
+}
+
+/// Encode each character of the text separately,
+/// replacing those which cannot be encoded with a question mark.
+fn encode_each_char_lossy<T>(codec: &T, text: &str) -> Vec<u8>
+where
+    T: ?Sized + TextCodec,
+{
+    let mut out = Vec::with_capacity(text.len());
+    let mut buf = [0; 4];
+    for c in text.chars() {
+        match codec.encode(c.encode_utf8(&mut buf)) {
+            Ok(bytes) => out.extend(bytes),
+            Err(_) => out.push(b'?'),
+        }
+    }
+    out
+}
+
+/// The action to take when text to encode
+/// contains characters which cannot be represented
+/// in the character set of the data set.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum EncodeTextPolicy {
+    /// Fail with an error.
+    #[default]
+    Error,
+    /// Replace each of those characters with a question mark ('?').
+    Replace,
+    /// Change the Specific Character Set (0008, 0005) of the data set
+    /// to ISO IR 192 (UTF-8),
+    /// so that all text is encoded in UTF-8.
+    ///
+    /// This requires changing the data set before it is written,
+    /// which is done by the owner of the data set
+    /// (such as the DICOM object writing functions).
+    /// Encoders which cannot do that treat this like [`Error`](Self::Error).
+    UpgradeToUtf8,
 }
 
 impl<T: ?Sized> TextCodec for Box<T>
@@ -101,6 +216,26 @@ where
     fn encode(&self, text: &str) -> EncodeResult<Vec<u8>> {
         self.as_ref().encode(text)
     }
+
+    fn find_value_delimiter(&self, text: &[u8]) -> Option<usize> {
+        self.as_ref().find_value_delimiter(text)
+    }
+
+    fn encode_lossy(&self, text: &str) -> Vec<u8> {
+        self.as_ref().encode_lossy(text)
+    }
+
+    fn decode_person_name(&self, text: &[u8]) -> DecodeResult<String> {
+        self.as_ref().decode_person_name(text)
+    }
+
+    fn encode_person_name(&self, text: &str) -> EncodeResult<Vec<u8>> {
+        self.as_ref().encode_person_name(text)
+    }
+
+    fn encode_person_name_lossy(&self, text: &str) -> Vec<u8> {
+        self.as_ref().encode_person_name_lossy(text)
+    }
 }
 
 impl<'a, T: ?Sized> TextCodec for &'a T
@@ -118,6 +253,26 @@ where
     fn encode(&self, text: &str) -> EncodeResult<Vec<u8>> {
         (**self).encode(text)
     }
+
+    fn find_value_delimiter(&self, text: &[u8]) -> Option<usize> {
+        (**self).find_value_delimiter(text)
+    }
+
+    fn encode_lossy(&self, text: &str) -> Vec<u8> {
+        (**self).encode_lossy(text)
+    }
+
+    fn decode_person_name(&self, text: &[u8]) -> DecodeResult<String> {
+        (**self).decode_person_name(text)
+    }
+
+    fn encode_person_name(&self, text: &str) -> EncodeResult<Vec<u8>> {
+        (**self).encode_person_name(text)
+    }
+
+    fn encode_person_name_lossy(&self, text: &str) -> Vec<u8> {
+        (**self).encode_person_name_lossy(text)
+    }
 }
 
 /// A descriptor for a specific character set,
@@ -143,9 +298,12 @@ impl SpecificCharacterSet {
     /// ISO IR 6: The default character set, as defined by the DICOM standard.
     pub const ISO_IR_6: SpecificCharacterSet = SpecificCharacterSet(CharsetImpl::Default);
 
-    // ISO IR 100: ISO 8859-1, the Western Europe character set
+    /// ISO IR 100: ISO 8859-1, the Western Europe character set
     pub const ISO_IR_100: SpecificCharacterSet = SpecificCharacterSet(CharsetImpl::IsoIr100);
 
+    /// ISO IR 144: ISO 8859-5, the Latin/Cyrillic character set
+    pub const ISO_IR_144: SpecificCharacterSet = SpecificCharacterSet(CharsetImpl::IsoIr144);
+
     /// ISO IR 192: UTF-8 encoding
     pub const ISO_IR_192: SpecificCharacterSet = SpecificCharacterSet(CharsetImpl::IsoIr192);
 
@@ -165,6 +323,121 @@ impl SpecificCharacterSet {
     pub fn from_code(code: &str) -> Option<Self> {
         CharsetImpl::from_code(code).map(SpecificCharacterSet)
     }
+
+    /// Obtain the specific character set declared
+    /// by the values of a Specific Character Set (0008, 0005) element.
+    ///
+    /// The first value defines the character set of the data set.
+    /// An empty or missing value stands for the default character repertoire.
+    /// Unsupported character sets fall back to ISO IR 100,
+    /// so that text can still be decoded in a lossy manner.
+    /// Use [`from_values_with`](SpecificCharacterSet::from_values_with)
+    /// to be notified when this happens.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dicom_encoding::text::SpecificCharacterSet;
+    ///
+    /// let character_set = SpecificCharacterSet::from_values(["ISO_IR 192"]);
+    /// assert_eq!(character_set, SpecificCharacterSet::ISO_IR_192);
+    /// let character_set = SpecificCharacterSet::from_values([""]);
+    /// assert_eq!(character_set, SpecificCharacterSet::ISO_IR_6);
+    /// ```
+    pub fn from_values<I>(values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        Self::from_values_with(values, |_| {})
+    }
+
+    /// Obtain the specific character set declared
+    /// by the values of a Specific Character Set (0008, 0005) element,
+    /// calling `on_unsupported` with the defined term
+    /// if it does not refer to a supported character set.
+    ///
+    /// Other than that,
+    /// this behaves like [`from_values`](SpecificCharacterSet::from_values).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dicom_encoding::text::SpecificCharacterSet;
+    ///
+    /// let character_set = SpecificCharacterSet::from_values_with(["ISO_IR 999"], |code| {
+    ///     eprintln!("Unsupported character set `{}`, decoding as ISO_IR 100", code);
+    /// });
+    /// assert_eq!(character_set, SpecificCharacterSet::ISO_IR_100);
+    /// ```
+    pub fn from_values_with<I, F>(values: I, on_unsupported: F) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+        F: FnOnce(&str),
+    {
+        let values: Vec<_> = values.into_iter().collect();
+        let mut codes = values.iter().map(|code| code.as_ref().trim());
+        let first = codes.next().unwrap_or("");
+
+        // multiple values call for code extension techniques
+        match Iso2022::from_values(std::iter::once(first).chain(codes)) {
+            Ok(Some(charset)) => return SpecificCharacterSet(CharsetImpl::Iso2022(charset)),
+            Ok(None) => {}
+            Err(code) => {
+                // keep to the character set of the first value
+                on_unsupported(code);
+                return Self::from_code(first).unwrap_or(if first.is_empty() {
+                    SpecificCharacterSet::ISO_IR_6
+                } else {
+                    SpecificCharacterSet::ISO_IR_100
+                });
+            }
+        }
+
+        if first.is_empty() {
+            return SpecificCharacterSet::ISO_IR_6;
+        }
+        Self::from_code(first).unwrap_or_else(|| {
+            on_unsupported(first);
+            SpecificCharacterSet::ISO_IR_100
+        })
+    }
+
+    /// Decode the given byte buffer as a single string,
+    /// never failing.
+    ///
+    /// Text which is valid in this character set is decoded as usual,
+    /// borrowing from the buffer if it is already valid UTF-8 text
+    /// (either because the text is in ASCII
+    /// or because the character set is ISO IR 192).
+    /// Byte sequences which cannot be decoded are replaced,
+    /// so that the rest of the text is preserved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dicom_encoding::text::SpecificCharacterSet;
+    ///
+    /// let character_set = SpecificCharacterSet::ISO_IR_100;
+    /// assert_eq!(character_set.decode_lossy(b"M\xFCller^Hans"), "Müller^Hans");
+    /// ```
+    pub fn decode_lossy<'a>(&self, text: &'a [u8]) -> Cow<'a, str> {
+        if self.0 == CharsetImpl::IsoIr192 {
+            return String::from_utf8_lossy(text);
+        }
+        if text.is_ascii() {
+            // all supported character sets are ASCII-compatible
+            if let Ok(text) = std::str::from_utf8(text) {
+                return Cow::Borrowed(text);
+            }
+        }
+        match self.0.decode(text) {
+            Ok(text) => Cow::Owned(text),
+            // fall back to ISO IR 100, which maps every byte to a character
+            Err(_) => Cow::Owned(text.iter().copied().map(char::from).collect()),
+        }
+    }
 }
 
 impl TextCodec for SpecificCharacterSet {
@@ -179,6 +452,26 @@ impl TextCodec for SpecificCharacterSet {
     fn encode(&self, text: &str) -> EncodeResult<Vec<u8>> {
         self.0.encode(text)
     }
+
+    fn find_value_delimiter(&self, text: &[u8]) -> Option<usize> {
+        self.0.find_value_delimiter(text)
+    }
+
+    fn encode_lossy(&self, text: &str) -> Vec<u8> {
+        self.0.encode_lossy(text)
+    }
+
+    fn decode_person_name(&self, text: &[u8]) -> DecodeResult<String> {
+        self.0.decode_person_name(text)
+    }
+
+    fn encode_person_name(&self, text: &str) -> EncodeResult<Vec<u8>> {
+        self.0.encode_person_name(text)
+    }
+
+    fn encode_person_name_lossy(&self, text: &str) -> Vec<u8> {
+        self.0.encode_person_name_lossy(text)
+    }
 }
 
 /// An enum type for individual supported character sets.
@@ -202,10 +495,35 @@ enum CharsetImpl {
     IsoIr110,
     /// **ISO-IR 144** (ISO-8859-5): The Latin/Cyrillic character set.
     IsoIr144,
+    /// **ISO-IR 127** (ISO-8859-6): The Latin/Arabic character set.
+    IsoIr127,
+    /// **ISO-IR 126** (ISO-8859-7): The Latin/Greek character set.
+    IsoIr126,
+    /// **ISO-IR 138** (ISO-8859-8): The Latin/Hebrew character set.
+    IsoIr138,
+    /// **ISO-IR 148** (ISO-8859-9): Right-hand part of the Latin alphabet no. 5,
+    /// the Turkish character set.
+    IsoIr148,
+    /// **ISO-IR 203** (ISO-8859-15): Right-hand part of the Latin alphabet no. 9,
+    /// the Western Europe character set with the Euro sign.
+    IsoIr203,
+    /// **ISO-IR 166** (TIS 620-2533): The Thai character set.
+    IsoIr166,
     /// **ISO-IR 192**: The Unicode character set based on the UTF-8 encoding.
     IsoIr192,
     /// **GB18030**: The Simplified Chinese character set.
     Gb18030,
+    /// **GBK**: The Simplified Chinese character set,
+    /// a subset of GB18030.
+    Gbk,
+    /// **ISO-IR 58** (GB 2312): The Simplified Chinese character set,
+    /// a subset of GBK.
+    IsoIr58,
+    /// Code extension techniques (ISO 2022) over the code elements
+    /// **ISO 2022 IR 13** (JIS X 0201), **ISO 2022 IR 87** (JIS X 0208),
+    /// **ISO 2022 IR 159** (JIS X 0212) and **ISO 2022 IR 149** (KS X 1001),
+    /// for Japanese and Korean text.
+    Iso2022(Iso2022),
     // Support for more text encodings is tracked in issue #40.
 }
 
@@ -223,9 +541,20 @@ impl CharsetImpl {
             "ISO_IR_109" | "ISO_IR 109" | "ISO 2022 IR 109" => Some(IsoIr109),
             "ISO_IR_110" | "ISO_IR 110" | "ISO 2022 IR 110" => Some(IsoIr110),
             "ISO_IR_144" | "ISO_IR 144" | "ISO 2022 IR 144" => Some(IsoIr144),
+            "ISO_IR_127" | "ISO_IR 127" | "ISO 2022 IR 127" => Some(IsoIr127),
+            "ISO_IR_126" | "ISO_IR 126" | "ISO 2022 IR 126" => Some(IsoIr126),
+            "ISO_IR_138" | "ISO_IR 138" | "ISO 2022 IR 138" => Some(IsoIr138),
+            "ISO_IR_148" | "ISO_IR 148" | "ISO 2022 IR 148" => Some(IsoIr148),
+            "ISO_IR_203" | "ISO_IR 203" | "ISO 2022 IR 203" => Some(IsoIr203),
+            "ISO_IR_166" | "ISO_IR 166" | "ISO 2022 IR 166" => Some(IsoIr166),
             "ISO_IR_192" | "ISO_IR 192" => Some(IsoIr192),
             "GB18030" => Some(Gb18030),
-            _ => None,
+            "GBK" => Some(Gbk),
+            "ISO_IR_58" | "ISO_IR 58" => Some(IsoIr58),
+            code => iso2022::Iso2022::from_values([code])
+                .ok()
+                .flatten()
+                .map(Iso2022),
         }
     }
 }
@@ -233,14 +562,23 @@ impl CharsetImpl {
 impl TextCodec for CharsetImpl {
     fn name(&self) -> Cow<'static, str> {
         Cow::Borrowed(match self {
+            CharsetImpl::Iso2022(charset) => return charset.name(),
             CharsetImpl::Default => "ISO_IR 6",
             CharsetImpl::IsoIr100 => "ISO_IR 100",
             CharsetImpl::IsoIr101 => "ISO_IR 101",
             CharsetImpl::IsoIr109 => "ISO_IR 109",
             CharsetImpl::IsoIr110 => "ISO_IR 110",
             CharsetImpl::IsoIr144 => "ISO_IR 144",
+            CharsetImpl::IsoIr127 => "ISO_IR 127",
+            CharsetImpl::IsoIr126 => "ISO_IR 126",
+            CharsetImpl::IsoIr138 => "ISO_IR 138",
+            CharsetImpl::IsoIr148 => "ISO_IR 148",
+            CharsetImpl::IsoIr203 => "ISO_IR 203",
+            CharsetImpl::IsoIr166 => "ISO_IR 166",
             CharsetImpl::IsoIr192 => "ISO_IR 192",
             CharsetImpl::Gb18030 => "GB18030",
+            CharsetImpl::Gbk => "GBK",
+            CharsetImpl::IsoIr58 => "ISO_IR 58",
         })
     }
 
@@ -252,8 +590,17 @@ impl TextCodec for CharsetImpl {
             CharsetImpl::IsoIr109 => IsoIr109CharacterSetCodec.decode(text),
             CharsetImpl::IsoIr110 => IsoIr110CharacterSetCodec.decode(text),
             CharsetImpl::IsoIr144 => IsoIr144CharacterSetCodec.decode(text),
+            CharsetImpl::IsoIr127 => IsoIr127CharacterSetCodec.decode(text),
+            CharsetImpl::IsoIr126 => IsoIr126CharacterSetCodec.decode(text),
+            CharsetImpl::IsoIr138 => IsoIr138CharacterSetCodec.decode(text),
+            CharsetImpl::IsoIr148 => IsoIr148CharacterSetCodec.decode(text),
+            CharsetImpl::IsoIr203 => IsoIr203CharacterSetCodec.decode(text),
+            CharsetImpl::IsoIr166 => IsoIr166CharacterSetCodec.decode(text),
             CharsetImpl::IsoIr192 => Utf8CharacterSetCodec.decode(text),
             CharsetImpl::Gb18030 => Gb18030CharacterSetCodec.decode(text),
+            CharsetImpl::Gbk => GbkCharacterSetCodec.decode(text),
+            CharsetImpl::IsoIr58 => IsoIr58CharacterSetCodec.decode(text),
+            CharsetImpl::Iso2022(charset) => charset.decode(text),
         }
     }
 
@@ -265,8 +612,61 @@ impl TextCodec for CharsetImpl {
             CharsetImpl::IsoIr109 => IsoIr109CharacterSetCodec.encode(text),
             CharsetImpl::IsoIr110 => IsoIr110CharacterSetCodec.encode(text),
             CharsetImpl::IsoIr144 => IsoIr144CharacterSetCodec.encode(text),
+            CharsetImpl::IsoIr127 => IsoIr127CharacterSetCodec.encode(text),
+            CharsetImpl::IsoIr126 => IsoIr126CharacterSetCodec.encode(text),
+            CharsetImpl::IsoIr138 => IsoIr138CharacterSetCodec.encode(text),
+            CharsetImpl::IsoIr148 => IsoIr148CharacterSetCodec.encode(text),
+            CharsetImpl::IsoIr203 => IsoIr203CharacterSetCodec.encode(text),
+            CharsetImpl::IsoIr166 => IsoIr166CharacterSetCodec.encode(text),
             CharsetImpl::IsoIr192 => Utf8CharacterSetCodec.encode(text),
             CharsetImpl::Gb18030 => Gb18030CharacterSetCodec.encode(text),
+            CharsetImpl::Gbk => GbkCharacterSetCodec.encode(text),
+            CharsetImpl::IsoIr58 => IsoIr58CharacterSetCodec.encode(text),
+            CharsetImpl::Iso2022(charset) => charset.encode(text, false),
+        }
+    }
+
+    fn encode_lossy(&self, text: &str) -> Vec<u8> {
+        match self {
+            CharsetImpl::Iso2022(charset) => charset
+                .encode(text, true)
+                .expect("encoding with replacement should not fail"),
+            _ => self
+                .encode(text)
+                .unwrap_or_else(|_| encode_each_char_lossy(self, text)),
+        }
+    }
+
+    fn find_value_delimiter(&self, text: &[u8]) -> Option<usize> {
+        match self {
+            CharsetImpl::Gb18030 | CharsetImpl::Gbk | CharsetImpl::IsoIr58 => {
+                find_value_delimiter_gb(text)
+            }
+            CharsetImpl::Iso2022(charset) => charset.find_value_delimiter(text),
+            _ => text.iter().position(|b| *b == b'\\'),
+        }
+    }
+
+    fn decode_person_name(&self, text: &[u8]) -> DecodeResult<String> {
+        match self {
+            CharsetImpl::Iso2022(charset) => charset.decode_person_name(text),
+            _ => self.decode(text),
+        }
+    }
+
+    fn encode_person_name(&self, text: &str) -> EncodeResult<Vec<u8>> {
+        match self {
+            CharsetImpl::Iso2022(charset) => charset.encode_person_name(text, false),
+            _ => self.encode(text),
+        }
+    }
+
+    fn encode_person_name_lossy(&self, text: &str) -> Vec<u8> {
+        match self {
+            CharsetImpl::Iso2022(charset) => charset
+                .encode_person_name(text, true)
+                .expect("encoding with replacement should not fail"),
+            _ => self.encode_lossy(text),
         }
     }
 }
@@ -343,8 +743,71 @@ decl_character_set!(IsoIr101CharacterSetCodec, "ISO_IR 101", ISO_8859_2);
 decl_character_set!(IsoIr109CharacterSetCodec, "ISO_IR 109", ISO_8859_3);
 decl_character_set!(IsoIr110CharacterSetCodec, "ISO_IR 110", ISO_8859_4);
 decl_character_set!(IsoIr144CharacterSetCodec, "ISO_IR 144", ISO_8859_5);
+decl_character_set!(IsoIr127CharacterSetCodec, "ISO_IR 127", ISO_8859_6);
+decl_character_set!(IsoIr126CharacterSetCodec, "ISO_IR 126", ISO_8859_7);
+decl_character_set!(IsoIr138CharacterSetCodec, "ISO_IR 138", ISO_8859_8);
+// ISO 8859-9 only differs from Windows-1254 in the C1 control range,
+// which is not used in DICOM text
+decl_character_set!(IsoIr148CharacterSetCodec, "ISO_IR 148", WINDOWS_1254);
+decl_character_set!(IsoIr203CharacterSetCodec, "ISO_IR 203", ISO_8859_15);
+// likewise, Windows-874 is a superset of TIS 620-2533
+decl_character_set!(IsoIr166CharacterSetCodec, "ISO_IR 166", WINDOWS_874);
 decl_character_set!(Utf8CharacterSetCodec, "ISO_IR 192", UTF_8);
 decl_character_set!(Gb18030CharacterSetCodec, "GB18030", GB18030);
+decl_character_set!(GbkCharacterSetCodec, "GBK", GBK);
+// GB 2312 text is also valid GBK text
+decl_character_set!(IsoIr58CharacterSetCodec, "ISO_IR 58", GBK);
+
+/// Find the first value delimiter in GB18030 text,
+/// skipping over multi-byte characters,
+/// of which the trailing bytes may be a backslash.
+fn find_value_delimiter_gb(text: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    while i < text.len() {
+        match text[i] {
+            b'\\' => return Some(i),
+            0x00..=0x80 => i += 1,
+            // four-byte sequences have a digit as the second byte
+            _ if matches!(text.get(i + 1), Some(0x30..=0x39)) => i += 4,
+            _ => i += 2,
+        }
+    }
+    None
+}
+
+/// Iterate over the individual values of the given encoded text,
+/// as delimited by the backslash character ('\')
+/// according to the given text codec.
+///
+/// # Example
+///
+/// ```
+/// use dicom_encoding::text::{split_values, SpecificCharacterSet};
+///
+/// let character_set = SpecificCharacterSet::from_values(["GBK"]);
+/// // the second byte of the first character is a backslash
+/// let values: Vec<_> = split_values(&character_set, b"\x9c\\\\\xcd\xf5").collect();
+/// assert_eq!(values, [&b"\x9c\\"[..], b"\xcd\xf5"]);
+/// ```
+pub fn split_values<'a, T>(codec: &'a T, text: &'a [u8]) -> impl Iterator<Item = &'a [u8]> + 'a
+where
+    T: ?Sized + TextCodec,
+{
+    let mut rest = Some(text);
+    std::iter::from_fn(move || {
+        let text = rest?;
+        match codec.find_value_delimiter(text) {
+            Some(i) => {
+                rest = Some(&text[i + 1..]);
+                Some(&text[..i])
+            }
+            None => {
+                rest = None;
+                Some(text)
+            }
+        }
+    })
+}
 
 /// The result of a text validation procedure (please see [`validate_iso_8859`]).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -465,4 +928,270 @@ mod tests {
             b"\xb8\xd2\xd0\xdd\xda\xde\xd2^\xb0\xdd\xd4\xe0\xd5\xd9",
         );
     }
+
+    #[test]
+    fn iso_ir_126_baseline() {
+        let codec = SpecificCharacterSet(CharsetImpl::IsoIr126);
+        test_codec(codec, "Διονυσιος", b"\xc4\xe9\xef\xed\xf5\xf3\xe9\xef\xf2");
+    }
+
+    #[test]
+    fn from_values_latin1() {
+        let codec = SpecificCharacterSet::from_values(["ISO_IR 100"]);
+        assert_eq!(codec, SpecificCharacterSet::ISO_IR_100);
+        let name = codec.decode_lossy(b"M\xfcller^Hans");
+        assert!(matches!(name, Cow::Owned(_)));
+        assert_eq!(name, "Müller^Hans");
+    }
+
+    #[test]
+    fn from_values_cyrillic() {
+        let codec = SpecificCharacterSet::from_values(["ISO_IR 144 "]);
+        assert_eq!(codec, SpecificCharacterSet::ISO_IR_144);
+        assert_eq!(
+            codec.decode_lossy(b"\xb8\xd2\xd0\xdd\xda\xde\xd2^\xb0\xdd\xd4\xe0\xd5\xd9"),
+            "Иванков^Андрей",
+        );
+    }
+
+    #[test]
+    fn from_values_default() {
+        let no_values: [&str; 0] = [];
+        assert_eq!(
+            SpecificCharacterSet::from_values(no_values),
+            SpecificCharacterSet::ISO_IR_6
+        );
+        assert_eq!(
+            SpecificCharacterSet::from_values(["", "ISO 2022 IR 100"]),
+            SpecificCharacterSet::ISO_IR_6
+        );
+        let codec = SpecificCharacterSet::from_values(["ISO_IR 6"]);
+        assert!(matches!(
+            codec.decode_lossy(b"Smith^John"),
+            Cow::Borrowed("Smith^John")
+        ));
+    }
+
+    #[test]
+    fn utf8_passthrough() {
+        let codec = SpecificCharacterSet::from_values(["ISO_IR 192"]);
+        let text = "Simões^João=山田^太郎";
+        assert!(matches!(
+            codec.decode_lossy(text.as_bytes()),
+            Cow::Borrowed(t) if t == text
+        ));
+        // invalid UTF-8 is replaced instead of failing
+        assert_eq!(codec.decode_lossy(b"Sim\xf5es"), "Sim\u{FFFD}es");
+    }
+
+    #[test]
+    fn unsupported_charset_falls_back_to_latin1() {
+        let mut unsupported = None;
+        let codec = SpecificCharacterSet::from_values_with(["ISO_IR 999"], |code| {
+            unsupported = Some(code.to_string());
+        });
+        assert_eq!(unsupported.as_deref(), Some("ISO_IR 999"));
+        assert_eq!(codec, SpecificCharacterSet::ISO_IR_100);
+        assert_eq!(codec.decode_lossy(b"M\xfcller"), "Müller");
+
+        // supported character sets do not trigger the hook
+        let codec = SpecificCharacterSet::from_values_with(["ISO_IR 192"], |code| {
+            panic!("unexpected call for {}", code)
+        });
+        assert_eq!(codec, SpecificCharacterSet::ISO_IR_192);
+    }
+
+    #[test]
+    fn iso_2022_ir_87_yamada_tarou() {
+        // PS3.5 H.3.1: Example 1
+        let codec = SpecificCharacterSet::from_values(["", "ISO 2022 IR 87"]);
+        assert_eq!(codec.name(), "\\ISO 2022 IR 87");
+        test_codec(
+            codec,
+            "Yamada^Tarou=山田^太郎=やまだ^たろう",
+            b"Yamada^Tarou=\x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B=\x1b$B$d$^$@\x1b(B^\x1b$B$?$m$&\x1b(B",
+        );
+    }
+
+    #[test]
+    fn iso_2022_ir_13_ir_87_yamada_tarou() {
+        // PS3.5 H.3.2: Example 2
+        let codec = SpecificCharacterSet::from_values(["ISO 2022 IR 13", "ISO 2022 IR 87"]);
+        assert_eq!(codec.name(), "ISO 2022 IR 13\\ISO 2022 IR 87");
+        test_codec(
+            codec,
+            "ﾔﾏﾀﾞ^ﾀﾛｳ=山田^太郎=やまだ^たろう",
+            b"\xd4\xcf\xc0\xde^\xc0\xdb\xb3=\x1b$B;3ED\x1b(J^\x1b$BB@O:\x1b(J=\x1b$B$d$^$@\x1b(J^\x1b$B$?$m$&\x1b(J",
+        );
+    }
+
+    #[test]
+    fn iso_2022_ir_149_hong_gildong() {
+        // PS3.5 I.2: Example of a Korean person name
+        let codec = SpecificCharacterSet::from_values(["", "ISO 2022 IR 149"]);
+        test_codec(
+            codec,
+            "Hong^Gildong=洪^吉洞=홍^길동",
+            b"Hong^Gildong=\x1b$)C\xfb\xf3^\x1b$)C\xd1\xce\xd4\xd7=\x1b$)C\xc8\xab^\x1b$)C\xb1\xe6\xb5\xbf",
+        );
+    }
+
+    #[test]
+    fn iso_2022_escapes() {
+        let codec = SpecificCharacterSet::from_values(["ISO 2022 IR 6", "ISO 2022 IR 87"]);
+        // no escape sequences if not necessary
+        assert_eq!(codec.encode("Yamada^Tarou").unwrap(), b"Yamada^Tarou");
+        // a single escape for consecutive characters of the same code element
+        assert_eq!(codec.encode("山田太郎 ").unwrap(), b"\x1b$B;3EDB@O:\x1b(B ");
+        // characters outside of the declared code elements
+        assert!(codec.encode("ﾔﾏﾀﾞ").is_err());
+        assert!(codec.encode("홍").is_err());
+
+        // delimiters inside of double-byte characters are not special
+        assert_eq!(codec.decode(b"\x1b$B$^\x1b(B").unwrap(), "ま");
+        // the initial code elements are restored at delimiters
+        // even without escape sequences
+        assert_eq!(
+            codec.decode(b"\x1b(J~\x1b)I\xd4^~\xd4").unwrap(),
+            "\u{203E}ﾔ^~\u{FFFD}",
+        );
+
+        // JIS X 0212
+        let codec = SpecificCharacterSet::from_values(["", "ISO 2022 IR 87", "ISO 2022 IR 159"]);
+        test_codec(codec, "丂", b"\x1b$(D0!\x1b(B");
+    }
+
+    #[test]
+    fn iso_2022_unsupported_extension() {
+        let mut unsupported = None;
+        let codec = SpecificCharacterSet::from_values_with(["", "ISO 2022 IR 166"], |code| {
+            unsupported = Some(code.to_string());
+        });
+        assert_eq!(unsupported.as_deref(), Some("ISO 2022 IR 166"));
+        assert_eq!(codec, SpecificCharacterSet::ISO_IR_6);
+    }
+
+    #[test]
+    fn gb18030_baseline() {
+        // PS3.5 K.1: Example of a Chinese person name
+        let codec = SpecificCharacterSet::from_values(["GB18030"]);
+        test_codec(
+            &codec,
+            "Wang^XiaoDong=王^小东=",
+            b"Wang^XiaoDong=\xcd\xf5^\xd0\xa1\xb6\xab=",
+        );
+        // institution name
+        test_codec(
+            codec,
+            "北京协和医院",
+            b"\xb1\xb1\xbe\xa9\xd0\xad\xba\xcd\xd2\xbd\xd4\xba",
+        );
+    }
+
+    #[test]
+    fn gbk_baseline() {
+        let codec = SpecificCharacterSet::from_values(["GBK"]);
+        assert_eq!(codec.name(), "GBK");
+        test_codec(codec, "淺^王", b"\x9c\\^\xcd\xf5");
+    }
+
+    #[test]
+    fn iso_2022_ir_58_zhang_xiaodong() {
+        // PS3.5 K.2: Example of a Chinese person name with code extensions
+        let codec = SpecificCharacterSet::from_values(["", "ISO 2022 IR 58"]);
+        test_codec(
+            codec,
+            "Zhang^XiaoDong=张^小东=",
+            b"Zhang^XiaoDong=\x1b$)A\xd5\xc5^\x1b$)A\xd0\xa1\xb6\xab=",
+        );
+    }
+
+    #[test]
+    fn split_multi_byte_values() {
+        // the second byte of 淺 is a backslash
+        let codec = SpecificCharacterSet::from_values(["GB18030"]);
+        let text = b"\x9c\\\\\xcd\xf5\\Wang\\\x94\x39\xfc\x36";
+        let values: Vec<_> = split_values(&codec, text)
+            .map(|value| codec.decode(value).unwrap())
+            .collect();
+        assert_eq!(values, ["淺", "王", "Wang", "😀"]);
+
+        // the first byte of 椒 is a backslash
+        let codec = SpecificCharacterSet::from_values(["", "ISO 2022 IR 87"]);
+        let text = b"\x1b$B\\%\x1b(B\\\x1b$B:4\x1b(B";
+        let values: Vec<_> = split_values(&codec, text)
+            .map(|value| codec.decode(value).unwrap())
+            .collect();
+        assert_eq!(values, ["椒", "佐"]);
+
+        // other character sets split at every backslash
+        let codec = SpecificCharacterSet::ISO_IR_100;
+        let values: Vec<_> = split_values(&codec, b"A\\\\B").collect();
+        assert_eq!(values, [&b"A"[..], b"", b"B"]);
+    }
+
+    #[test]
+    fn encode_lossy_replaces_characters() {
+        let codec = SpecificCharacterSet::ISO_IR_100;
+        assert!(codec.encode("Żółć").is_err());
+        assert_eq!(codec.encode_lossy("Żółć"), b"?\xf3??");
+        assert_eq!(codec.encode_lossy("Müller"), b"M\xfcller");
+
+        let codec = SpecificCharacterSet::from_values(["", "ISO 2022 IR 87"]);
+        assert_eq!(
+            codec.encode_lossy("山홍田"),
+            b"\x1b$B;3\x1b(B?\x1b$BED\x1b(B"
+        );
+    }
+
+    #[test]
+    fn person_name_component_groups() {
+        // PS3.5 H.3.1: Example 1
+        let codec = SpecificCharacterSet::from_values(["", "ISO 2022 IR 87"]);
+        let bytes: &[u8] =
+            b"Yamada^Tarou=\x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B=\x1b$B$d$^$@\x1b(B^\x1b$B$?$m$&\x1b(B";
+        let name = "Yamada^Tarou=山田^太郎=やまだ^たろう";
+        assert_eq!(codec.decode_person_name(bytes).unwrap(), name);
+        assert_eq!(codec.encode_person_name(name).unwrap(), bytes);
+
+        // PS3.5 H.3.2: Example 2
+        let codec = SpecificCharacterSet::from_values(["ISO 2022 IR 13", "ISO 2022 IR 87"]);
+        let bytes: &[u8] = b"\xd4\xcf\xc0\xde^\xc0\xdb\xb3=\x1b$B;3ED\x1b(J^\x1b$BB@O:\x1b(J=\x1b$B$d$^$@\x1b(J^\x1b$B$?$m$&\x1b(J";
+        let name = "ﾔﾏﾀﾞ^ﾀﾛｳ=山田^太郎=やまだ^たろう";
+        assert_eq!(codec.decode_person_name(bytes).unwrap(), name);
+        assert_eq!(codec.encode_person_name(name).unwrap(), bytes);
+
+        // an equals sign byte within a double-byte character
+        // does not delimit a component group
+        let codec = SpecificCharacterSet::from_values(["", "ISO 2022 IR 87"]);
+        assert_eq!(codec.encode("そ").unwrap(), b"\x1b$B$=\x1b(B");
+        assert_eq!(
+            codec
+                .decode_person_name(b"Yamada=\x1b$B$=\x1b(B=\x1b$B$=\x1b(B")
+                .unwrap(),
+            "Yamada=そ=そ"
+        );
+
+        // the alphabetic group is restricted to the first character set
+        assert!(codec.encode_person_name("山田^太郎").is_err());
+        assert_eq!(
+            codec.encode_person_name_lossy("山田^Tarou=山田^太郎"),
+            b"??^Tarou=\x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B"
+        );
+        // while other text values may use any of them
+        assert!(codec.encode("山田^太郎").is_ok());
+
+        // multiple values
+        assert_eq!(
+            codec.encode_person_name("Yamada=山田\\Tarou=太郎").unwrap(),
+            b"Yamada=\x1b$B;3ED\x1b(B\\Tarou=\x1b$BB@O:\x1b(B"
+        );
+
+        // single-byte character sets have no such restriction
+        let codec = SpecificCharacterSet::ISO_IR_100;
+        assert_eq!(
+            codec.encode_person_name("Müller^Hans").unwrap(),
+            b"M\xfcller^Hans"
+        );
+    }
 }
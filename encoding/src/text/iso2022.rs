@@ -0,0 +1,504 @@
+//! Support for character sets with code extension techniques,
+//! as per [PS3.5 ch 6 6.1.2.5](https://dicom.nema.org/medical/dicom/2023e/output/chtml/part05/chapter_6.html#sect_6.1.2.5).
+//!
+//! Text in these character sets switches between code elements
+//! by means of designation escape sequences.
+//! The code elements supported here are
+//! ISO 2022 IR 6 (ASCII), ISO 2022 IR 13 (JIS X 0201),
+//! ISO 2022 IR 87 (JIS X 0208), ISO 2022 IR 159 (JIS X 0212),
+//! ISO 2022 IR 149 (KS X 1001), and ISO 2022 IR 58 (GB 2312).
+
+use super::{DecodeResult, EncodeCustomSnafu, EncodeResult};
+use encoding::all::{GBK, WINDOWS_949};
+use encoding::{DecoderTrap, EncoderTrap, EncodingRef};
+use encoding_index_japanese::{jis0208, jis0212};
+use std::borrow::Cow;
+
+const ESC: u8 = 0x1B;
+
+/// The number of code points in a 94x94 character set.
+const DOUBLE_BYTE_SIZE: u16 = 94 * 94;
+
+/// The code element designated to G0.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum G0 {
+    /// ISO 2022 IR 6: ASCII
+    Ascii,
+    /// ISO 2022 IR 14: JIS X 0201 Romaji
+    Romaji,
+    /// ISO 2022 IR 87: JIS X 0208 Kanji
+    Jis0208,
+    /// ISO 2022 IR 159: JIS X 0212 supplementary Kanji
+    Jis0212,
+}
+
+impl G0 {
+    fn escape(self) -> &'static [u8] {
+        match self {
+            G0::Ascii => b"\x1B(B",
+            G0::Romaji => b"\x1B(J",
+            G0::Jis0208 => b"\x1B$B",
+            G0::Jis0212 => b"\x1B$(D",
+        }
+    }
+}
+
+/// The code element designated to G1.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum G1 {
+    None,
+    /// ISO 2022 IR 13: JIS X 0201 Katakana
+    Katakana,
+    /// ISO 2022 IR 149: KS X 1001
+    KsX1001,
+    /// ISO 2022 IR 58: GB 2312
+    Gb2312,
+}
+
+impl G1 {
+    fn escape(self) -> &'static [u8] {
+        match self {
+            G1::None => b"",
+            G1::Katakana => b"\x1B)I",
+            G1::KsX1001 => b"\x1B$)C",
+            G1::Gb2312 => b"\x1B$)A",
+        }
+    }
+}
+
+/// A change of code element by an escape sequence.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Designation {
+    G0(G0),
+    G1(G1),
+}
+
+/// The supported escape sequences, without the leading ESC byte.
+const DESIGNATIONS: [(&[u8], Designation); 8] = [
+    (b"(B", Designation::G0(G0::Ascii)),
+    (b"(J", Designation::G0(G0::Romaji)),
+    (b"$B", Designation::G0(G0::Jis0208)),
+    (b"$@", Designation::G0(G0::Jis0208)),
+    (b"$(D", Designation::G0(G0::Jis0212)),
+    (b")I", Designation::G1(G1::Katakana)),
+    (b"$)C", Designation::G1(G1::KsX1001)),
+    (b"$)A", Designation::G1(G1::Gb2312)),
+];
+
+/// Recognize the escape sequence at the start of the given text,
+/// which follows an ESC byte,
+/// returning its length and the code element designated.
+fn designation(text: &[u8]) -> Option<(usize, Designation)> {
+    DESIGNATIONS
+        .iter()
+        .find(|(seq, _)| text.starts_with(seq))
+        .map(|&(seq, designation)| (seq.len(), designation))
+}
+
+/// A character set made of the code elements
+/// declared in a multi-valued Specific Character Set (0008, 0005).
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub(super) struct Iso2022 {
+    /// whether the first value is ISO 2022 IR 13,
+    /// making JIS X 0201 the initial character set
+    /// instead of the default character repertoire
+    ir13_initial: bool,
+    ir13: bool,
+    ir87: bool,
+    ir159: bool,
+    ir149: bool,
+    ir58: bool,
+}
+
+impl Iso2022 {
+    /// Build the character set from the values of a Specific Character Set,
+    /// if they declare any of the supported multi-byte or Katakana code elements.
+    ///
+    /// Returns `Ok(None)` if the values do not call for code extensions,
+    /// and `Err` with the offending value if one of them is not supported.
+    pub(super) fn from_values<'a>(
+        values: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Option<Self>, &'a str> {
+        let mut charset = Iso2022::default();
+        for (i, value) in values.into_iter().enumerate() {
+            match value {
+                "" | "ISO 2022 IR 6" => {}
+                "ISO 2022 IR 13" | "ISO_IR 13" => {
+                    charset.ir13 = true;
+                    charset.ir13_initial |= i == 0;
+                }
+                "ISO 2022 IR 87" => charset.ir87 = true,
+                "ISO 2022 IR 159" => charset.ir159 = true,
+                "ISO 2022 IR 149" => charset.ir149 = true,
+                "ISO 2022 IR 58" => charset.ir58 = true,
+                // not a code extension, left for the single-byte character sets
+                _ if i == 0 => return Ok(None),
+                other => return Err(other),
+            }
+        }
+        if charset.ir13 || charset.ir87 || charset.ir159 || charset.ir149 || charset.ir58 {
+            Ok(Some(charset))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The defined terms of the code elements, separated by backslashes.
+    pub(super) fn name(&self) -> Cow<'static, str> {
+        let mut name = String::from(if self.ir13_initial {
+            "ISO 2022 IR 13"
+        } else {
+            ""
+        });
+        if self.ir13 && !self.ir13_initial {
+            name.push_str("\\ISO 2022 IR 13");
+        }
+        for (declared, term) in [
+            (self.ir87, "\\ISO 2022 IR 87"),
+            (self.ir159, "\\ISO 2022 IR 159"),
+            (self.ir149, "\\ISO 2022 IR 149"),
+            (self.ir58, "\\ISO 2022 IR 58"),
+        ] {
+            if declared {
+                name.push_str(term);
+            }
+        }
+        Cow::Owned(name)
+    }
+
+    fn initial_g0(&self) -> G0 {
+        if self.ir13_initial {
+            G0::Romaji
+        } else {
+            G0::Ascii
+        }
+    }
+
+    fn initial_g1(&self) -> G1 {
+        if self.ir13_initial {
+            G1::Katakana
+        } else {
+            G1::None
+        }
+    }
+
+    /// Decode the given text,
+    /// following the designation escape sequences in it.
+    ///
+    /// The initial code elements are restored
+    /// after each component delimiter (`^`, `=`), value delimiter (`\`),
+    /// line break, or tab,
+    /// as long as these are found in a single-byte code element.
+    /// Unknown escape sequences and invalid characters
+    /// are replaced with U+FFFD.
+    pub(super) fn decode(&self, text: &[u8]) -> DecodeResult<String> {
+        let mut out = String::with_capacity(text.len());
+        let (mut g0, mut g1) = (self.initial_g0(), self.initial_g1());
+        let mut i = 0;
+        while i < text.len() {
+            let b = text[i];
+            if b == ESC {
+                match designation(&text[i + 1..]) {
+                    Some((len, Designation::G0(new_g0))) => {
+                        g0 = new_g0;
+                        i += 1 + len;
+                    }
+                    Some((len, Designation::G1(new_g1))) => {
+                        g1 = new_g1;
+                        i += 1 + len;
+                    }
+                    None => {
+                        out.push(char::REPLACEMENT_CHARACTER);
+                        i += 1;
+                    }
+                }
+                continue;
+            }
+
+            if b >= 0x80 {
+                // G1 code element
+                match g1 {
+                    G1::Katakana if (0xA1..=0xDF).contains(&b) => {
+                        out.push(char::from_u32(0xFF61 + u32::from(b - 0xA1)).unwrap());
+                        i += 1;
+                    }
+                    G1::KsX1001 | G1::Gb2312 if i + 1 < text.len() => {
+                        let encoding: EncodingRef =
+                            if g1 == G1::KsX1001 { WINDOWS_949 } else { GBK };
+                        let decoded = encoding
+                            .decode(&text[i..i + 2], DecoderTrap::Strict)
+                            .unwrap_or_else(|_| char::REPLACEMENT_CHARACTER.to_string());
+                        out.push_str(&decoded);
+                        i += 2;
+                    }
+                    _ => {
+                        out.push(char::REPLACEMENT_CHARACTER);
+                        i += 1;
+                    }
+                }
+                continue;
+            }
+
+            match g0 {
+                G0::Jis0208 | G0::Jis0212 if b > 0x20 && b < 0x7F => {
+                    let c = text
+                        .get(i + 1)
+                        .filter(|&&b2| b2 > 0x20 && b2 < 0x7F)
+                        .and_then(|&b2| {
+                            let pointer = u16::from(b - 0x21) * 94 + u16::from(b2 - 0x21);
+                            let code = if g0 == G0::Jis0208 {
+                                jis0208::forward(pointer)
+                            } else {
+                                jis0212::forward(pointer)
+                            };
+                            char::from_u32(code).filter(|_| code != 0xFFFF)
+                        });
+                    out.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+                    i += 2;
+                    continue;
+                }
+                G0::Romaji if b == b'~' => out.push('\u{203E}'),
+                _ => out.push(char::from(b)),
+            }
+            if matches!(b, b'^' | b'=' | b'\\' | b'\r' | b'\n' | b'\t') {
+                g0 = self.initial_g0();
+                g1 = self.initial_g1();
+            }
+            i += 1;
+        }
+        Ok(out)
+    }
+
+    /// Encode the given text,
+    /// only emitting the escape sequences necessary
+    /// to switch to the code elements of its characters.
+    ///
+    /// The initial code elements are restored
+    /// before each component delimiter (`^`, `=`), value delimiter (`\`),
+    /// line break, or tab,
+    /// and at the end of the text.
+    ///
+    /// If `replace` is true,
+    /// characters outside of the declared code elements
+    /// are replaced with a question mark instead of failing.
+    pub(super) fn encode(&self, text: &str, replace: bool) -> EncodeResult<Vec<u8>> {
+        let mut out = Vec::with_capacity(text.len());
+        let (mut g0, mut g1) = (self.initial_g0(), self.initial_g1());
+
+        for c in text.chars() {
+            if matches!(c, '^' | '=' | '\\' | '\r' | '\n' | '\t') {
+                if g0 != self.initial_g0() {
+                    g0 = self.initial_g0();
+                    out.extend_from_slice(g0.escape());
+                }
+                g1 = self.initial_g1();
+                out.push(c as u8);
+                continue;
+            }
+
+            if c.is_ascii() {
+                // JIS X 0201 Romaji only differs from ASCII in the overline
+                let target = [g0, self.initial_g0()]
+                    .iter()
+                    .copied()
+                    .find(|&g| g == G0::Ascii || (g == G0::Romaji && c != '~'))
+                    .unwrap_or(G0::Ascii);
+                if g0 != target {
+                    g0 = target;
+                    out.extend_from_slice(g0.escape());
+                }
+                out.push(c as u8);
+                continue;
+            }
+
+            if self.ir13 && c == '\u{203E}' {
+                if g0 != G0::Romaji {
+                    g0 = G0::Romaji;
+                    out.extend_from_slice(g0.escape());
+                }
+                out.push(b'~');
+                continue;
+            }
+
+            if self.ir13 && ('\u{FF61}'..='\u{FF9F}').contains(&c) {
+                if g1 != G1::Katakana {
+                    g1 = G1::Katakana;
+                    out.extend_from_slice(g1.escape());
+                }
+                out.push((c as u32 - 0xFF61) as u8 + 0xA1);
+                continue;
+            }
+
+            let double_byte = [
+                (self.ir87, G0::Jis0208, jis0208::backward(c as u32)),
+                (self.ir159, G0::Jis0212, jis0212::backward(c as u32)),
+            ]
+            .iter()
+            .find(|(declared, _, pointer)| *declared && *pointer < DOUBLE_BYTE_SIZE)
+            .map(|&(_, target, pointer)| (target, pointer));
+            if let Some((target, pointer)) = double_byte {
+                if g0 != target {
+                    g0 = target;
+                    out.extend_from_slice(g0.escape());
+                }
+                out.push((pointer / 94) as u8 + 0x21);
+                out.push((pointer % 94) as u8 + 0x21);
+                continue;
+            }
+
+            let double_byte = [
+                (self.ir149, G1::KsX1001, WINDOWS_949 as EncodingRef),
+                (self.ir58, G1::Gb2312, GBK),
+            ]
+            .iter()
+            .filter(|(declared, _, _)| *declared)
+            .find_map(|&(_, target, encoding)| {
+                let mut buf = [0; 4];
+                encoding
+                    .encode(c.encode_utf8(&mut buf), EncoderTrap::Strict)
+                    .ok()
+                    .filter(|bytes| {
+                        bytes.len() == 2 && bytes.iter().all(|b| (0xA1..=0xFE).contains(b))
+                    })
+                    .map(|bytes| (target, bytes))
+            });
+            if let Some((target, bytes)) = double_byte {
+                if g1 != target {
+                    g1 = target;
+                    out.extend_from_slice(g1.escape());
+                }
+                out.extend_from_slice(&bytes);
+                continue;
+            }
+
+            if replace {
+                if matches!(g0, G0::Jis0208 | G0::Jis0212) {
+                    g0 = self.initial_g0();
+                    out.extend_from_slice(g0.escape());
+                }
+                out.push(b'?');
+                continue;
+            }
+
+            return EncodeCustomSnafu {
+                message: format!(
+                    "character {:?} cannot be encoded in {}",
+                    c,
+                    self.name().trim_start_matches('\\')
+                ),
+            }
+            .fail();
+        }
+
+        if g0 != self.initial_g0() {
+            out.extend_from_slice(self.initial_g0().escape());
+        }
+        Ok(out)
+    }
+
+    /// The code elements available to the alphabetic component group
+    /// of a person name,
+    /// which is restricted to the first value of Specific Character Set.
+    fn alphabetic(&self) -> Self {
+        Iso2022 {
+            ir13_initial: self.ir13_initial,
+            ir13: self.ir13_initial,
+            ..Iso2022::default()
+        }
+    }
+
+    /// Decode the given person name text,
+    /// starting each component group (delimited by `=`)
+    /// with the initial code elements.
+    pub(super) fn decode_person_name(&self, text: &[u8]) -> DecodeResult<String> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        loop {
+            match self.find_delimiter(rest, b'=') {
+                Some(i) => {
+                    out.push_str(&self.decode(&rest[..i])?);
+                    out.push('=');
+                    rest = &rest[i + 1..];
+                }
+                None => {
+                    out.push_str(&self.decode(rest)?);
+                    return Ok(out);
+                }
+            }
+        }
+    }
+
+    /// Encode the given person name text,
+    /// so that the alphabetic component group of each value
+    /// only uses the code elements of the first value of Specific Character Set,
+    /// while the ideographic and phonetic groups
+    /// may switch to any of the declared code elements.
+    ///
+    /// If `replace` is true,
+    /// characters outside of the available code elements
+    /// are replaced with a question mark instead of failing.
+    pub(super) fn encode_person_name(&self, text: &str, replace: bool) -> EncodeResult<Vec<u8>> {
+        let alphabetic = self.alphabetic();
+        let mut out = Vec::with_capacity(text.len());
+        for (i, value) in text.split('\\').enumerate() {
+            if i > 0 {
+                out.push(b'\\');
+            }
+            for (j, group) in value.split('=').enumerate() {
+                if j > 0 {
+                    out.push(b'=');
+                    out.extend_from_slice(&self.encode(group, replace)?);
+                    continue;
+                }
+                match alphabetic.encode(group, replace) {
+                    Ok(bytes) => out.extend_from_slice(&bytes),
+                    Err(_) => {
+                        return EncodeCustomSnafu {
+                            message: format!(
+                                "alphabetic person name group {:?} cannot be encoded in {}",
+                                group,
+                                if self.ir13_initial {
+                                    "ISO 2022 IR 13"
+                                } else {
+                                    "ISO 2022 IR 6"
+                                }
+                            ),
+                        }
+                        .fail();
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Find the first value delimiter in the given text,
+    /// skipping over double-byte characters in G0,
+    /// of which either byte may be a backslash.
+    pub(super) fn find_value_delimiter(&self, text: &[u8]) -> Option<usize> {
+        self.find_delimiter(text, b'\\')
+    }
+
+    /// Find the first occurrence of the given single-byte delimiter in the text,
+    /// skipping over escape sequences and double-byte characters in G0.
+    fn find_delimiter(&self, text: &[u8], delimiter: u8) -> Option<usize> {
+        let mut double_byte = false;
+        let mut i = 0;
+        while i < text.len() {
+            match text[i] {
+                ESC => match designation(&text[i + 1..]) {
+                    Some((len, Designation::G0(g0))) => {
+                        double_byte = matches!(g0, G0::Jis0208 | G0::Jis0212);
+                        i += 1 + len;
+                    }
+                    Some((len, Designation::G1(_))) => i += 1 + len,
+                    None => i += 1,
+                },
+                b if b == delimiter && !double_byte => return Some(i),
+                0x21..=0x7E if double_byte => i += 2,
+                _ => i += 1,
+            }
+        }
+        None
+    }
+}
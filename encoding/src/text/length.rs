@@ -0,0 +1,387 @@
+//! Validation of text values against the maximum value length
+//! of their value representation
+//! (see [`VR::max_value_length`]).
+//!
+//! Lengths are checked on each value of a multi-valued element,
+//! and on each component group of a person name.
+//! Padding (trailing spaces, or a trailing null character for UI)
+//! does not count towards the length.
+
+use dicom_core::VR;
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::fmt;
+
+/// How text values are checked against the maximum value length of their VR
+/// when they are encoded.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum LengthValidation {
+    /// Fail to encode values which are too long.
+    Strict,
+    /// Log a warning for each value which is too long,
+    /// but encode it anyway.
+    #[default]
+    Warn,
+    /// Cut values which are too long to the maximum length,
+    /// logging a warning for each one.
+    ///
+    /// Encoders apply the maximum length to the encoded form of the value,
+    /// in bytes,
+    /// and cut it at the last whole character which fits.
+    ///
+    /// Values of the VRs AE, CS, DA, DS, DT, IS, TM and UI
+    /// are never cut (see [`is_truncatable`]),
+    /// and fail to encode as in [`Strict`](LengthValidation::Strict) mode.
+    Truncate,
+    /// Do not check values.
+    Off,
+}
+
+/// A text value which is longer than its VR allows.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct LengthViolation {
+    /// the index of the value in the element
+    pub index: usize,
+    /// the length of the value (or of the component group), in characters
+    pub length: usize,
+    /// the maximum length allowed, in characters
+    pub max: u32,
+}
+
+impl fmt::Display for LengthViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value #{} is {} characters long, the maximum is {}",
+            self.index, self.length, self.max
+        )
+    }
+}
+
+/// Check the values of a text element value
+/// against the maximum value length of the given VR.
+///
+/// For VRs which admit multiple values,
+/// the text is split into values by backslash,
+/// and each value is checked separately.
+///
+/// # Example
+///
+/// ```
+/// use dicom_core::VR;
+/// use dicom_encoding::text::length::check_value_length;
+///
+/// assert!(check_value_length(VR::CS, "ORIGINAL\\PRIMARY").is_ok());
+/// let violation = check_value_length(VR::CS, "ORIGINAL\\ABCDEFGHIJKLMNOPQ").unwrap_err();
+/// assert_eq!(violation.index, 1);
+/// assert_eq!(violation.length, 17);
+/// assert_eq!(violation.max, 16);
+/// ```
+pub fn check_value_length(vr: VR, text: &str) -> Result<(), LengthViolation> {
+    let max = match vr.max_value_length() {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+    for (index, value) in split_values(vr, text).enumerate() {
+        for part in split_parts(vr, value) {
+            let length = part.chars().count();
+            if length > max as usize {
+                return Err(LengthViolation { index, length, max });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether the values of the given VR may be cut
+/// to the maximum value length.
+///
+/// Cutting a number, a date or time, a code string,
+/// an application entity title or a UID
+/// would silently turn it into a different value,
+/// so values of AE, CS, DA, DS, DT, IS, TM and UI are never cut.
+///
+/// # Example
+///
+/// ```
+/// use dicom_core::VR;
+/// use dicom_encoding::text::length::is_truncatable;
+///
+/// assert!(is_truncatable(VR::LO));
+/// assert!(!is_truncatable(VR::IS));
+/// ```
+pub fn is_truncatable(vr: VR) -> bool {
+    !matches!(
+        vr,
+        VR::AE | VR::CS | VR::DA | VR::DS | VR::DT | VR::IS | VR::TM | VR::UI
+    )
+}
+
+/// Cut each value of a text element value
+/// to the maximum value length of the given VR,
+/// leaving values which fit untouched.
+///
+/// Values of a VR which is not [truncatable](is_truncatable)
+/// are always left untouched.
+///
+/// # Example
+///
+/// ```
+/// use dicom_core::VR;
+/// use dicom_encoding::text::length::truncate_value_length;
+///
+/// assert_eq!(
+///     truncate_value_length(VR::SH, "SHORT\\A VERY LONG SHORT STRING"),
+///     "SHORT\\A VERY LONG SHOR",
+/// );
+/// ```
+pub fn truncate_value_length(vr: VR, text: &str) -> Cow<'_, str> {
+    let max = match vr.max_value_length() {
+        Some(max) => max as usize,
+        None => return Cow::Borrowed(text),
+    };
+    let result: Result<_, Infallible> = truncate_values_with(vr, text, |_, part| {
+        Ok(part
+            .char_indices()
+            .nth(max)
+            .map(|(end, _)| part[..end].to_string()))
+    });
+    match result {
+        Ok(text) => text,
+        Err(e) => match e {},
+    }
+}
+
+/// Cut the values of a text element value with the given function,
+/// which receives the index of each value
+/// and each part of it to which the maximum length applies
+/// (see [`check_value_length`]),
+/// and returns the part to write in its place, if it is too long.
+///
+/// The text is returned untouched if no part needs cutting,
+/// or if the VR is not [truncatable](is_truncatable).
+/// This allows limits to be applied to the encoded form of the text,
+/// such as in bytes rather than in characters.
+pub fn truncate_values_with<'t, E>(
+    vr: VR,
+    text: &'t str,
+    mut truncate: impl FnMut(usize, &str) -> Result<Option<String>, E>,
+) -> Result<Cow<'t, str>, E> {
+    if vr.max_value_length().is_none() || !is_truncatable(vr) {
+        return Ok(Cow::Borrowed(text));
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut truncated = false;
+    for (i, value) in split_values(vr, text).enumerate() {
+        if i > 0 {
+            out.push('\\');
+        }
+        for (j, part) in split_parts(vr, value).enumerate() {
+            if let (true, Some(delimiter)) = (j > 0, part_delimiter(vr)) {
+                out.push(delimiter);
+            }
+            match truncate(i, part)? {
+                Some(part) => {
+                    out.push_str(&part);
+                    truncated = true;
+                }
+                None => out.push_str(part),
+            }
+        }
+    }
+    if truncated {
+        Ok(Cow::Owned(out))
+    } else {
+        Ok(Cow::Borrowed(text))
+    }
+}
+
+/// Split a text element value into its values,
+/// without padding.
+fn split_values(vr: VR, text: &str) -> impl Iterator<Item = &str> {
+    let text = match vr {
+        VR::UI => text.strip_suffix('\0').unwrap_or(text),
+        _ => text.trim_end_matches(' '),
+    };
+    match vr {
+        // these VRs are always single-valued, and may contain backslashes
+        VR::LT | VR::ST | VR::UT | VR::UR => split(text, None),
+        _ => split(text, Some('\\')),
+    }
+}
+
+/// The delimiter of the parts of a value
+/// to which the maximum length applies:
+/// the component groups of a person name,
+/// or the sides of a date or time range.
+fn part_delimiter(vr: VR) -> Option<char> {
+    match vr {
+        VR::PN => Some('='),
+        VR::DA | VR::TM => Some('-'),
+        _ => None,
+    }
+}
+
+/// Split a value into the parts to which the maximum length applies.
+fn split_parts(vr: VR, value: &str) -> impl Iterator<Item = &str> {
+    split(value, part_delimiter(vr))
+}
+
+/// Split the text by the delimiter, if any.
+fn split(text: &str, delimiter: Option<char>) -> impl Iterator<Item = &str> {
+    let (whole, parts) = match delimiter {
+        Some(delimiter) => (None, Some(text.split(delimiter))),
+        None => (Some(text), None),
+    };
+    whole.into_iter().chain(parts.into_iter().flatten())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// an over-limit value for each bounded VR
+    const OVERLONG: &[(VR, &str)] = &[
+        (VR::AE, "STORE-SCP-ARCHIV1"),
+        (VR::AS, "042Y1"),
+        (VR::CS, "DERIVED_SECONDARY"),
+        (VR::DA, "202301011"),
+        (VR::DS, "3.141592653589793"),
+        (VR::DT, "20230101123000.123456+01001"),
+        (VR::IS, "1234567890123"),
+        (VR::SH, "Seventeen Chars!!"),
+        (VR::TM, "123000.12345678"),
+        (
+            VR::UI,
+            "1.2.826.0.1.3680043.10.1234567890.1234567890.1234567890.123456789",
+        ),
+    ];
+
+    #[test]
+    fn max_value_lengths() {
+        assert_eq!(VR::AE.max_value_length(), Some(16));
+        assert_eq!(VR::IS.max_value_length(), Some(12));
+        assert_eq!(VR::LO.max_value_length(), Some(64));
+        assert_eq!(VR::PN.max_value_length(), Some(64));
+        for vr in [VR::UC, VR::UR, VR::UT, VR::OB, VR::US, VR::SQ] {
+            assert_eq!(vr.max_value_length(), None, "{}", vr);
+        }
+    }
+
+    #[test]
+    fn check_overlong_values() {
+        for &(vr, value) in OVERLONG {
+            let max = vr.max_value_length().unwrap();
+            assert_eq!(
+                check_value_length(vr, value),
+                Err(LengthViolation {
+                    index: 0,
+                    length: max as usize + 1,
+                    max
+                }),
+                "{} {:?}",
+                vr,
+                value
+            );
+            // cut down to size, unless the value would change meaning
+            let truncated = truncate_value_length(vr, value);
+            if is_truncatable(vr) {
+                assert_eq!(truncated.chars().count(), max as usize);
+                assert!(value.starts_with(&*truncated));
+                assert_eq!(check_value_length(vr, &truncated), Ok(()));
+            } else {
+                assert_eq!(truncated, *value, "{}", vr);
+            }
+        }
+
+        let lo = "L".repeat(65);
+        assert!(check_value_length(VR::LO, &lo).is_err());
+        assert!(check_value_length(VR::LO, &lo[..64]).is_ok());
+        let lt = "L".repeat(10241);
+        assert!(check_value_length(VR::LT, &lt).is_err());
+        assert!(check_value_length(VR::LT, &lt[..10240]).is_ok());
+        let st = "S".repeat(1025);
+        assert!(check_value_length(VR::ST, &st).is_err());
+        assert!(check_value_length(VR::ST, &st[..1024]).is_ok());
+    }
+
+    #[test]
+    fn unlimited_values() {
+        let long = "X".repeat(100_000);
+        for vr in [VR::UC, VR::UR, VR::UT] {
+            assert_eq!(check_value_length(vr, &long), Ok(()), "{}", vr);
+            assert_eq!(truncate_value_length(vr, &long), long);
+        }
+    }
+
+    #[test]
+    fn check_values_separately() {
+        // 3 values of 16 characters, 50 characters in total
+        let cs = "ABCDEFGHIJKLMNOP\\ABCDEFGHIJKLMNOP\\ABCDEFGHIJKLMNOP";
+        assert_eq!(check_value_length(VR::CS, cs), Ok(()));
+        assert_eq!(truncate_value_length(VR::CS, cs), cs);
+
+        let cs = "ORIGINAL\\ABCDEFGHIJKLMNOPQ\\AXIAL";
+        assert_eq!(
+            check_value_length(VR::CS, cs),
+            Err(LengthViolation {
+                index: 1,
+                length: 17,
+                max: 16
+            })
+        );
+        assert_eq!(truncate_value_length(VR::CS, cs), cs);
+        assert_eq!(
+            truncate_value_length(VR::SH, cs),
+            "ORIGINAL\\ABCDEFGHIJKLMNOP\\AXIAL"
+        );
+
+        // backslashes in single-valued VRs are part of the value
+        let st = format!("{}\\{}", "S".repeat(600), "T".repeat(400));
+        assert_eq!(check_value_length(VR::ST, &st), Ok(()));
+    }
+
+    #[test]
+    fn check_person_name_groups() {
+        let group = "G".repeat(64);
+        // each group within limits, 194 characters in total
+        let pn = format!("{}={}={}", group, group, group);
+        assert_eq!(check_value_length(VR::PN, &pn), Ok(()));
+
+        let pn = format!("Doe^John={}G", group);
+        assert_eq!(
+            check_value_length(VR::PN, &pn),
+            Err(LengthViolation {
+                index: 0,
+                length: 65,
+                max: 64
+            })
+        );
+        assert_eq!(
+            truncate_value_length(VR::PN, &pn),
+            format!("Doe^John={}", group)
+        );
+
+        // limits are in characters
+        let pn = "山".repeat(64);
+        assert_eq!(check_value_length(VR::PN, &pn), Ok(()));
+    }
+
+    #[test]
+    fn ignore_padding_and_ranges() {
+        assert_eq!(check_value_length(VR::CS, "ABCDEFGHIJKLMNOP "), Ok(()));
+        assert_eq!(
+            check_value_length(
+                VR::UI,
+                "1.2.826.0.1.3680043.10.1234567890.1234567890.1234567890.12345678\0"
+            ),
+            Ok(())
+        );
+        assert_eq!(check_value_length(VR::DA, "20230101-20231231"), Ok(()));
+        assert_eq!(check_value_length(VR::TM, "080000-173000.123"), Ok(()));
+        assert_eq!(
+            truncate_value_length(VR::DA, "20230101-202312311"),
+            "20230101-202312311"
+        );
+    }
+}
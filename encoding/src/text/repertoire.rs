@@ -0,0 +1,402 @@
+//! Validation of text values against the character repertoire
+//! and format of their value representation,
+//! as per [PS3.5 sect 6.2](https://dicom.nema.org/medical/dicom/2023e/output/chtml/part05/sect_6.2.html).
+//!
+//! These checks only cover value representations
+//! with a restricted repertoire:
+//! AE, AS, CS, DA, DS, DT, IS, TM, and UI
+//! (the latter through [`validate_uid_lenient`](super::uid::validate_uid_lenient)).
+//! Values of other VRs always pass.
+
+use crate::text::uid::{validate_uid_lenient, UidError};
+use dicom_core::VR;
+use std::fmt;
+
+/// How text values are checked against the repertoire of their VR
+/// when they are encoded.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum RepertoireValidation {
+    /// Fail to encode values which do not conform to their VR.
+    Strict,
+    /// Log a warning for each value which does not conform to its VR,
+    /// but encode it anyway.
+    #[default]
+    Warn,
+    /// Do not check values.
+    Off,
+}
+
+/// A violation of the repertoire or format of a value representation,
+/// pointing at the first offending character of a text value.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct RepertoireViolation {
+    /// the offending character
+    pub character: char,
+    /// the position of the character in the value, in characters
+    pub position: usize,
+    /// what the character breaks
+    pub reason: &'static str,
+}
+
+impl fmt::Display for RepertoireViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "character {:?} at position {}: {}",
+            self.character, self.position, self.reason
+        )
+    }
+}
+
+/// Check a single text value (without value delimiters)
+/// against the repertoire and format of the given VR.
+///
+/// Padding (trailing spaces, or a trailing null character for UI)
+/// is accepted.
+///
+/// # Example
+///
+/// ```
+/// use dicom_core::VR;
+/// use dicom_encoding::text::repertoire::check_repertoire;
+///
+/// assert!(check_repertoire(VR::CS, "ORIGINAL").is_ok());
+/// let violation = check_repertoire(VR::CS, "hello world!").unwrap_err();
+/// assert_eq!(violation.character, 'h');
+/// assert_eq!(violation.position, 0);
+/// ```
+pub fn check_repertoire(vr: VR, value: &str) -> Result<(), RepertoireViolation> {
+    match vr {
+        VR::AE => check_ae(value),
+        VR::AS => check_as(value),
+        VR::CS => check_cs(value),
+        VR::DA => check_range(value, check_da),
+        VR::DS => check_ds(value),
+        VR::DT => check_dt(value.trim_end_matches(' ')),
+        VR::IS => check_is(value),
+        VR::TM => check_range(value, check_tm),
+        VR::UI => check_ui(value),
+        _ => Ok(()),
+    }
+}
+
+fn violation<T>(
+    value: &str,
+    position: usize,
+    reason: &'static str,
+) -> Result<T, RepertoireViolation> {
+    // point at the last character if the value ended too soon
+    let (position, character) = value
+        .chars()
+        .enumerate()
+        .nth(position)
+        .or_else(|| value.chars().enumerate().last())
+        .unwrap_or((0, ' '));
+    Err(RepertoireViolation {
+        character,
+        position,
+        reason,
+    })
+}
+
+/// Check that the value has at most the given number of characters.
+fn check_max_len(value: &str, max: usize) -> Result<(), RepertoireViolation> {
+    if value.chars().count() > max {
+        return violation(value, max, "value is too long");
+    }
+    Ok(())
+}
+
+/// Check that every character is accepted by the predicate.
+fn check_chars(
+    value: &str,
+    offset: usize,
+    accept: impl Fn(char) -> bool,
+    reason: &'static str,
+) -> Result<(), RepertoireViolation> {
+    match value.chars().position(|c| !accept(c)) {
+        Some(i) => Err(RepertoireViolation {
+            character: value.chars().nth(i).unwrap(),
+            position: offset + i,
+            reason,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check that the value is a sequence of digits of one of the given lengths.
+fn check_digits(value: &str, lengths: &[usize]) -> Result<(), RepertoireViolation> {
+    check_chars(value, 0, |c| c.is_ascii_digit(), "expected a digit")?;
+    if !lengths.contains(&value.len()) {
+        return violation(value, value.len(), "unexpected number of digits");
+    }
+    Ok(())
+}
+
+fn check_ae(value: &str) -> Result<(), RepertoireViolation> {
+    check_chars(
+        value,
+        0,
+        |c| c.is_ascii() && !c.is_ascii_control() && c != '\\',
+        "not allowed in an application entity",
+    )?;
+    check_max_len(value, 16)
+}
+
+fn check_as(value: &str) -> Result<(), RepertoireViolation> {
+    if let Some(i) = value.chars().take(3).position(|c| !c.is_ascii_digit()) {
+        return violation(value, i, "expected a digit");
+    }
+    match value.chars().nth(3) {
+        Some('D' | 'W' | 'M' | 'Y') if value.chars().count() == 4 => Ok(()),
+        Some('D' | 'W' | 'M' | 'Y') => violation(value, 4, "value is too long"),
+        _ => violation(value, 3, "expected one of D, W, M, or Y"),
+    }
+}
+
+fn check_cs(value: &str) -> Result<(), RepertoireViolation> {
+    check_chars(
+        value,
+        0,
+        |c| matches!(c, 'A'..='Z' | '0'..='9' | ' ' | '_'),
+        "only uppercase letters, digits, space, and underscore are allowed",
+    )?;
+    check_max_len(value, 16)
+}
+
+fn check_ds(value: &str) -> Result<(), RepertoireViolation> {
+    check_chars(
+        value,
+        0,
+        |c| matches!(c, '0'..='9' | '+' | '-' | '.' | 'e' | 'E' | ' '),
+        "not allowed in a decimal string",
+    )?;
+    check_max_len(value, 16)?;
+    let trimmed = value.trim();
+    if !trimmed.is_empty() && trimmed.parse::<f64>().is_err() {
+        let start = value.len() - value.trim_start().len();
+        return violation(value, start, "not a decimal number");
+    }
+    Ok(())
+}
+
+fn check_is(value: &str) -> Result<(), RepertoireViolation> {
+    check_chars(
+        value,
+        0,
+        |c| matches!(c, '0'..='9' | '+' | '-' | ' '),
+        "not allowed in an integer string",
+    )?;
+    check_max_len(value, 12)?;
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Ok(());
+    }
+    let start = value.len() - value.trim_start().len();
+    match trimmed.parse::<i64>() {
+        Ok(n) if (i64::from(i32::MIN)..=i64::from(i32::MAX)).contains(&n) => Ok(()),
+        Ok(_) => violation(value, start, "integer out of range"),
+        Err(_) => violation(value, start, "not an integer number"),
+    }
+}
+
+/// Check a value which may be a range of two values delimited by `-`,
+/// as used in queries.
+fn check_range(
+    value: &str,
+    check: fn(&str, usize) -> Result<(), RepertoireViolation>,
+) -> Result<(), RepertoireViolation> {
+    let value = value.trim_end_matches(' ');
+    match value.find('-') {
+        Some(i) => {
+            if i > 0 {
+                check(&value[..i], 0)?;
+            }
+            if i + 1 < value.len() {
+                check(&value[i + 1..], i + 1)?;
+            }
+            Ok(())
+        }
+        None if value.is_empty() => Ok(()),
+        None => check(value, 0),
+    }
+}
+
+/// Adjust the position of a violation in part of a value.
+fn shifted(
+    result: Result<(), RepertoireViolation>,
+    offset: usize,
+) -> Result<(), RepertoireViolation> {
+    result.map_err(|violation| RepertoireViolation {
+        position: violation.position + offset,
+        ..violation
+    })
+}
+
+fn check_da(value: &str, offset: usize) -> Result<(), RepertoireViolation> {
+    shifted(check_digits(value, &[8]), offset)
+}
+
+fn check_tm(value: &str, offset: usize) -> Result<(), RepertoireViolation> {
+    let (hms, fraction) = match value.find('.') {
+        Some(i) => (&value[..i], Some(&value[i + 1..])),
+        None => (value, None),
+    };
+    shifted(check_digits(hms, &[2, 4, 6]), offset)?;
+    if let Some(fraction) = fraction {
+        if hms.len() != 6 {
+            return shifted(
+                violation(value, hms.len(), "fraction without seconds"),
+                offset,
+            );
+        }
+        shifted(check_digits(fraction, &[1, 2, 3, 4, 5, 6]), offset + 7)?;
+    }
+    Ok(())
+}
+
+fn check_dt(value: &str) -> Result<(), RepertoireViolation> {
+    if value.is_empty() {
+        return Ok(());
+    }
+    // an optional UTC offset at the end
+    let (datetime, utc_offset) = match value.rfind(['+', '-']) {
+        Some(i) if i > 0 => (&value[..i], Some(i)),
+        _ => (value, None),
+    };
+    let (digits, fraction) = match datetime.find('.') {
+        Some(i) => (&datetime[..i], Some(&datetime[i + 1..])),
+        None => (datetime, None),
+    };
+    check_digits(digits, &[4, 6, 8, 10, 12, 14])?;
+    if let Some(fraction) = fraction {
+        if digits.len() != 14 {
+            return violation(value, digits.len(), "fraction without seconds");
+        }
+        shifted(check_digits(fraction, &[1, 2, 3, 4, 5, 6]), 15)?;
+    }
+    if let Some(i) = utc_offset {
+        shifted(check_digits(&value[i + 1..], &[4]), i + 1)?;
+    }
+    Ok(())
+}
+
+fn check_ui(value: &str) -> Result<(), RepertoireViolation> {
+    let error = match validate_uid_lenient(value) {
+        // an empty value is not a UID, but it is a valid UI value
+        Ok(()) | Err(UidError::Empty) => return Ok(()),
+        Err(error) => error,
+    };
+    let reason = match error {
+        UidError::Empty | UidError::InvalidCharacter { .. } => "only digits and dots are allowed",
+        UidError::TooLong { .. } => "value is too long",
+        UidError::EmptyComponent { .. } => "empty UID component",
+        UidError::LeadingZero { .. } => "UID component with a leading zero",
+    };
+    violation(value, error.position(), reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn assert_violation(vr: VR, value: &str, character: char, position: usize) {
+        let violation = check_repertoire(vr, value).unwrap_err();
+        assert_eq!(
+            (violation.character, violation.position),
+            (character, position),
+            "{} {:?}: {}",
+            vr,
+            value,
+            violation
+        );
+    }
+
+    #[test]
+    fn check_code_strings() {
+        assert!(check_repertoire(VR::CS, "ORIGINAL").is_ok());
+        assert!(check_repertoire(VR::CS, "ISO_IR 100").is_ok());
+        assert!(check_repertoire(VR::CS, "").is_ok());
+        assert_violation(VR::CS, "hello world!", 'h', 0);
+        assert_violation(VR::CS, "DERIVED-1", '-', 7);
+        assert_violation(VR::CS, "ABCDEFGHIJKLMNOPQ", 'Q', 16);
+    }
+
+    #[test]
+    fn check_application_entities() {
+        assert!(check_repertoire(VR::AE, "STORESCP").is_ok());
+        assert!(check_repertoire(VR::AE, "any-title 1").is_ok());
+        assert_violation(VR::AE, "STORE\nSCP", '\n', 5);
+        assert_violation(VR::AE, "STORE\\SCP", '\\', 5);
+        assert_violation(VR::AE, "A_VERY_LONG_AE_TITLE", 'I', 16);
+    }
+
+    #[test]
+    fn check_unique_identifiers() {
+        assert!(check_repertoire(VR::UI, "1.2.840.10008.1.2.1").is_ok());
+        assert!(check_repertoire(VR::UI, "1.2.840.10008.1.2\0").is_ok());
+        assert!(check_repertoire(VR::UI, "2.25.0").is_ok());
+        assert_violation(VR::UI, "1.2.abc", 'a', 4);
+        assert_violation(VR::UI, "1.2..3", '.', 4);
+        assert_violation(VR::UI, "1.2.03", '0', 4);
+        assert_violation(VR::UI, "1.2.3.", '.', 5);
+        let long = format!("2.25.{}", "1".repeat(60));
+        assert_violation(VR::UI, &long, '1', 64);
+        // empty values and a single null padding character are accepted
+        assert!(check_repertoire(VR::UI, "").is_ok());
+        assert!(check_repertoire(VR::UI, "\0").is_ok());
+        let uid_64 = format!("2.25.{}", "1".repeat(59));
+        assert!(check_repertoire(VR::UI, &uid_64).is_ok());
+        assert!(check_repertoire(VR::UI, &format!("{}\0", uid_64)).is_ok());
+        assert_violation(VR::UI, "1.2.840.10008.1.2\0\0", '\0', 17);
+    }
+
+    #[test]
+    fn check_numeric_strings() {
+        assert!(check_repertoire(VR::DS, "-1.5e-3").is_ok());
+        assert!(check_repertoire(VR::DS, " 42 ").is_ok());
+        assert_violation(VR::DS, "1,5", ',', 1);
+        assert_violation(VR::DS, "1.2.3", '1', 0);
+        assert_violation(VR::DS, "0.12345678901234567", '5', 16);
+
+        assert!(check_repertoire(VR::IS, "-2147483648").is_ok());
+        assert!(check_repertoire(VR::IS, "+12 ").is_ok());
+        assert_violation(VR::IS, "1.0", '.', 1);
+        assert_violation(VR::IS, "2147483648", '2', 0);
+        assert_violation(VR::IS, "1-2", '1', 0);
+    }
+
+    #[test]
+    fn check_ages() {
+        assert!(check_repertoire(VR::AS, "042Y").is_ok());
+        assert!(check_repertoire(VR::AS, "003W").is_ok());
+        assert_violation(VR::AS, "42Y", 'Y', 2);
+        assert_violation(VR::AS, "042y", 'y', 3);
+        assert_violation(VR::AS, "042YY", 'Y', 4);
+    }
+
+    #[test]
+    fn check_dates_and_times() {
+        assert!(check_repertoire(VR::DA, "20240229").is_ok());
+        assert!(check_repertoire(VR::DA, "20240101-20241231").is_ok());
+        assert!(check_repertoire(VR::DA, "-20241231").is_ok());
+        assert_violation(VR::DA, "2024-02-29", '4', 3);
+        assert_violation(VR::DA, "2024/02/29", '/', 4);
+        assert_violation(VR::DA, "20240101-2024123", '3', 15);
+
+        assert!(check_repertoire(VR::TM, "070907.0705").is_ok());
+        assert!(check_repertoire(VR::TM, "1010").is_ok());
+        assert!(check_repertoire(VR::TM, "10-12 ").is_ok());
+        assert_violation(VR::TM, "07:09:07", ':', 2);
+        assert_violation(VR::TM, "0709.5", '.', 4);
+        assert_violation(VR::TM, "070907.1234567", '7', 13);
+
+        assert!(check_repertoire(VR::DT, "20240229101530.123456+0100").is_ok());
+        assert!(check_repertoire(VR::DT, "2024").is_ok());
+        assert!(check_repertoire(VR::DT, "202402-0500").is_ok());
+        assert_violation(VR::DT, "2024-02-29", '-', 4);
+        assert_violation(VR::DT, "20240229T1015", 'T', 8);
+        assert_violation(VR::DT, "202402291015+01", '1', 14);
+    }
+}
@@ -0,0 +1,221 @@
+//! Validation of unique identifiers (UIDs),
+//! as per [PS3.5 sect 9.1](https://dicom.nema.org/medical/dicom/2023e/output/chtml/part05/chapter_9.html#sect_9.1).
+//!
+//! A UID is a sequence of numeric components separated by dots,
+//! such as `1.2.840.10008.1.2.1`.
+//! Each component is a non-empty sequence of digits
+//! without leading zeros (except for the component `0` itself),
+//! and the whole UID is at most 64 characters long.
+
+use snafu::Snafu;
+
+/// The maximum length of a UID, in characters.
+pub const MAX_UID_LENGTH: usize = 64;
+
+/// An error describing why a string is not a valid UID.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Snafu)]
+#[non_exhaustive]
+pub enum UidError {
+    /// The UID is empty.
+    #[snafu(display("UID is empty"))]
+    Empty,
+    /// The UID is longer than 64 characters.
+    #[snafu(display("UID is {} characters long, the maximum is 64", length))]
+    TooLong { length: usize },
+    /// The UID contains a character other than a digit or a dot.
+    #[snafu(display(
+        "character {:?} at position {}: only digits and dots are allowed",
+        character,
+        position
+    ))]
+    InvalidCharacter { character: char, position: usize },
+    /// The UID has an empty component,
+    /// which includes leading, trailing, and repeated dots.
+    #[snafu(display("empty UID component at position {}", position))]
+    EmptyComponent { position: usize },
+    /// A component of the UID other than `0` starts with a zero.
+    #[snafu(display("UID component with a leading zero at position {}", position))]
+    LeadingZero { position: usize },
+}
+
+impl UidError {
+    /// The position of the offending character in the UID, in characters.
+    pub fn position(&self) -> usize {
+        match self {
+            UidError::Empty => 0,
+            UidError::TooLong { .. } => MAX_UID_LENGTH,
+            UidError::InvalidCharacter { position, .. }
+            | UidError::EmptyComponent { position }
+            | UidError::LeadingZero { position } => *position,
+        }
+    }
+}
+
+/// Check that the given string is a valid UID.
+///
+/// The string must not have any padding:
+/// see [`validate_uid_lenient`] for UIDs as found in data element values.
+///
+/// # Example
+///
+/// ```
+/// use dicom_encoding::text::uid::{validate_uid, UidError};
+///
+/// assert!(validate_uid("1.2.840.10008.1.2.1").is_ok());
+/// assert_eq!(
+///     validate_uid("1.2.840.010008"),
+///     Err(UidError::LeadingZero { position: 8 }),
+/// );
+/// ```
+pub fn validate_uid(uid: &str) -> Result<(), UidError> {
+    if uid.is_empty() {
+        return Err(UidError::Empty);
+    }
+    if let Some((position, character)) = uid
+        .chars()
+        .enumerate()
+        .find(|&(_, c)| !c.is_ascii_digit() && c != '.')
+    {
+        return Err(UidError::InvalidCharacter {
+            character,
+            position,
+        });
+    }
+    // only ASCII characters from here on
+    if uid.len() > MAX_UID_LENGTH {
+        return Err(UidError::TooLong { length: uid.len() });
+    }
+    let mut position = 0;
+    for component in uid.split('.') {
+        if component.is_empty() {
+            return Err(UidError::EmptyComponent { position });
+        }
+        if component.len() > 1 && component.starts_with('0') {
+            return Err(UidError::LeadingZero { position });
+        }
+        position += component.len() + 1;
+    }
+    Ok(())
+}
+
+/// Check that the given string is a valid UID,
+/// ignoring a single trailing null character
+/// used to pad UID values to an even length.
+///
+/// # Example
+///
+/// ```
+/// use dicom_encoding::text::uid::{validate_uid, validate_uid_lenient};
+///
+/// assert!(validate_uid_lenient("1.2.840.10008.1.2.1\0").is_ok());
+/// assert!(validate_uid("1.2.840.10008.1.2.1\0").is_err());
+/// ```
+pub fn validate_uid_lenient(uid: &str) -> Result<(), UidError> {
+    validate_uid(uid.strip_suffix('\0').unwrap_or(uid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a valid UID of exactly 64 characters
+    const UID_64: &str = "1.2.826.0.1.3680043.10.1234567890.1234567890.1234567890.12345678";
+
+    #[test]
+    fn valid_uids() {
+        assert_eq!(UID_64.len(), 64);
+        assert_eq!(validate_uid(UID_64), Ok(()));
+        assert_eq!(validate_uid("1.2.840.10008.1.2.1"), Ok(()));
+        assert_eq!(validate_uid("0"), Ok(()));
+        assert_eq!(validate_uid("1.0.2"), Ok(()));
+        assert_eq!(validate_uid("2.25.0"), Ok(()));
+    }
+
+    #[test]
+    fn uid_too_long() {
+        let uid = format!("{}9", UID_64);
+        assert_eq!(uid.len(), 65);
+        assert_eq!(validate_uid(&uid), Err(UidError::TooLong { length: 65 }));
+        assert_eq!(
+            validate_uid_lenient(&format!("{}\0", uid)),
+            Err(UidError::TooLong { length: 65 })
+        );
+    }
+
+    #[test]
+    fn uid_leading_zero() {
+        assert_eq!(
+            validate_uid("1.2.03.4"),
+            Err(UidError::LeadingZero { position: 4 })
+        );
+        assert_eq!(
+            validate_uid("01.2"),
+            Err(UidError::LeadingZero { position: 0 })
+        );
+        assert_eq!(
+            validate_uid("1.2.00"),
+            Err(UidError::LeadingZero { position: 4 })
+        );
+    }
+
+    #[test]
+    fn uid_bad_dots() {
+        assert_eq!(validate_uid(""), Err(UidError::Empty));
+        assert_eq!(
+            validate_uid(".1.2"),
+            Err(UidError::EmptyComponent { position: 0 })
+        );
+        assert_eq!(
+            validate_uid("1.2."),
+            Err(UidError::EmptyComponent { position: 4 })
+        );
+        assert_eq!(
+            validate_uid("1..2"),
+            Err(UidError::EmptyComponent { position: 2 })
+        );
+    }
+
+    #[test]
+    fn uid_bad_characters() {
+        assert_eq!(
+            validate_uid("1.2.a"),
+            Err(UidError::InvalidCharacter {
+                character: 'a',
+                position: 4
+            })
+        );
+        assert_eq!(
+            validate_uid("1.2 "),
+            Err(UidError::InvalidCharacter {
+                character: ' ',
+                position: 3
+            })
+        );
+    }
+
+    #[test]
+    fn uid_null_padding() {
+        let padded = "1.2.840.10008.1.2\0";
+        // strict: the padding is not part of the UID
+        assert_eq!(
+            validate_uid(padded),
+            Err(UidError::InvalidCharacter {
+                character: '\0',
+                position: 17
+            })
+        );
+        // lenient: one trailing null character is ignored
+        assert_eq!(validate_uid_lenient(padded), Ok(()));
+        assert_eq!(validate_uid_lenient(UID_64), Ok(()));
+        assert_eq!(validate_uid_lenient(&format!("{}\0", UID_64)), Ok(()));
+        // but only one
+        assert_eq!(
+            validate_uid_lenient("1.2.840.10008.1.2\0\0"),
+            Err(UidError::InvalidCharacter {
+                character: '\0',
+                position: 17
+            })
+        );
+        assert_eq!(validate_uid_lenient("\0"), Err(UidError::Empty));
+    }
+}
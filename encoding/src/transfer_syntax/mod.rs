@@ -503,6 +503,12 @@ impl<D, R, W> TransferSyntax<D, R, W> {
         self.byte_order
     }
 
+    /// Obtain whether this transfer syntax
+    /// encodes value representations explicitly.
+    pub const fn explicit_vr(&self) -> bool {
+        self.explicit_vr
+    }
+
     /// Obtain this transfer syntax' codec specification.
     pub fn codec(&self) -> &Codec<D, R, W> {
         &self.codec
@@ -0,0 +1,32 @@
+#![no_main]
+//! Read all tokens of a data set.
+//! The first byte selects the transfer syntax,
+//! the remaining bytes are the encoded data set.
+use dicom_parser::dataset::read::{DataSetReader, ReadOptions};
+use dicom_transfer_syntax_registry::entries::{
+    EXPLICIT_VR_BIG_ENDIAN, EXPLICIT_VR_LITTLE_ENDIAN, IMPLICIT_VR_LITTLE_ENDIAN,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let (ts, data) = match data.split_first() {
+        Some((0, data)) => (EXPLICIT_VR_LITTLE_ENDIAN.erased(), data),
+        Some((1, data)) => (EXPLICIT_VR_BIG_ENDIAN.erased(), data),
+        Some((_, data)) => (IMPLICIT_VR_LITTLE_ENDIAN.erased(), data),
+        None => return,
+    };
+    // keep allocations bounded by the input,
+    // as a bogus length would otherwise be taken at face value
+    let options = ReadOptions::new()
+        .max_value_length(data.len() as u32)
+        .max_depth(64);
+    let reader = match DataSetReader::new_with_ts_options(data, &ts, options) {
+        Ok(reader) => reader,
+        Err(_) => return,
+    };
+    for token in reader {
+        if token.is_err() {
+            break;
+        }
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+//! Decode a single sequence item header in each of the
+//! uncompressed transfer syntaxes.
+use dicom_encoding::decode::explicit_be::ExplicitVRBigEndianDecoder;
+use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
+use dicom_encoding::decode::implicit_le::ImplicitVRLittleEndianDecoder;
+use dicom_encoding::decode::Decode;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ExplicitVRLittleEndianDecoder::default().decode_item_header(&mut &data[..]);
+    let _ = ExplicitVRBigEndianDecoder::default().decode_item_header(&mut &data[..]);
+    let _ = ImplicitVRLittleEndianDecoder::default().decode_item_header(&mut &data[..]);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+//! Parse a file meta information group,
+//! starting at the `DICM` magic code.
+use dicom_object::meta::FileMetaTable;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = FileMetaTable::from_reader(data);
+});
@@ -4,9 +4,11 @@ use std::{marker::PhantomData, str::FromStr};
 
 use crate::DicomJson;
 use dicom_core::{
-    value::{InMemFragment, Value, C},
-    DataDictionary, DataElement, PrimitiveValue, Tag, VR,
+    ops::{AttributeSelector, AttributeSelectorStep},
+    value::{DataSetSequence, InMemFragment, Value, C},
+    DataDictionary, DataElement, Length, PrimitiveValue, Tag, VR,
 };
+use dicom_dictionary_std::StandardDataDictionary;
 use dicom_object::InMemDicomObject;
 use serde::de::{Deserialize, DeserializeOwned, Error as _, Visitor};
 
@@ -47,6 +49,106 @@ where
     serde_json::from_value::<DicomJson<T>>(value).map(DicomJson::into_inner)
 }
 
+/// A reference to the value of an attribute
+/// which is held elsewhere,
+/// as given by the `"BulkDataURI"` field in DICOM JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkDataReference {
+    /// The location of the attribute in the data set
+    pub selector: AttributeSelector,
+    /// The value representation of the attribute
+    pub vr: VR,
+    /// The URI from which the value can be retrieved
+    pub uri: String,
+}
+
+/// A DICOM object read from DICOM JSON,
+/// along with the references to bulk data
+/// which were not included in the document.
+///
+/// Each attribute with a bulk data reference
+/// is present in the object with an empty value,
+/// until it is filled in by [`resolve_bulk_data`](Self::resolve_bulk_data).
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::Tag;
+/// use dicom_json::DicomJsonObject;
+///
+/// let json = r#"{
+///     "7FE00010": {
+///         "vr": "OB",
+///         "BulkDataURI": "https://example.com/bulk/7FE00010"
+///     }
+/// }"#;
+/// let obj: DicomJsonObject = dicom_json::from_str(json)?;
+/// assert_eq!(obj.bulk_data()[0].uri, "https://example.com/bulk/7FE00010");
+///
+/// let obj = obj.resolve_bulk_data(|reference| {
+///     // fetch the data from `reference.uri`
+///     Ok::<_, std::io::Error>(vec![0; 16])
+/// })?;
+/// assert_eq!(obj.get(Tag(0x7FE0, 0x0010)).unwrap().value().to_bytes()?.len(), 16);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DicomJsonObject<D = StandardDataDictionary> {
+    object: InMemDicomObject<D>,
+    bulk_data: Vec<BulkDataReference>,
+}
+
+impl<D> DicomJsonObject<D> {
+    /// Obtain a reference to the DICOM object,
+    /// in which bulk data values are still empty.
+    pub fn object(&self) -> &InMemDicomObject<D> {
+        &self.object
+    }
+
+    /// Obtain the references to bulk data found in the document,
+    /// in document order.
+    pub fn bulk_data(&self) -> &[BulkDataReference] {
+        &self.bulk_data
+    }
+
+    /// Split this value into the DICOM object
+    /// and the unresolved bulk data references.
+    pub fn into_parts(self) -> (InMemDicomObject<D>, Vec<BulkDataReference>) {
+        (self.object, self.bulk_data)
+    }
+
+    /// Retrieve the value of each bulk data reference
+    /// using the given function,
+    /// and place it in the DICOM object.
+    ///
+    /// Stops at the first error returned by `fetch`.
+    pub fn resolve_bulk_data<F, E>(self, mut fetch: F) -> Result<InMemDicomObject<D>, E>
+    where
+        F: FnMut(&BulkDataReference) -> Result<Vec<u8>, E>,
+        D: Clone + DataDictionary,
+    {
+        let mut object = self.object;
+        for reference in &self.bulk_data {
+            let mut data = Some(fetch(reference)?);
+            object
+                .update_value_at(reference.selector.clone(), |value| {
+                    if let Some(data) = data.take() {
+                        *value = PrimitiveValue::from(data).into();
+                    }
+                })
+                // an empty attribute is put in place when reading
+                .expect("bulk data attribute should be present");
+        }
+        Ok(object)
+    }
+}
+
+impl<D> From<DicomJsonObject<D>> for DicomJson<DicomJsonObject<D>> {
+    fn from(value: DicomJsonObject<D>) -> Self {
+        Self(value, Default::default())
+    }
+}
+
 #[derive(Debug)]
 struct InMemDicomObjectVisitor<D>(PhantomData<D>);
 
@@ -60,7 +162,7 @@ impl<'de, D> Visitor<'de> for InMemDicomObjectVisitor<D>
 where
     D: Default + DataDictionary + Clone,
 {
-    type Value = InMemDicomObject<D>;
+    type Value = DicomJsonObject<D>;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         formatter.write_str("a DICOM data set map")
@@ -71,29 +173,48 @@ where
         A: serde::de::MapAccess<'de>,
     {
         let mut obj = InMemDicomObject::<D>::new_empty_with_dict(D::default());
+        let mut bulk_data = Vec::new();
         while let Some(e) = map.next_entry::<DicomJson<Tag>, JsonDataElement<D>>()? {
             let (
-                DicomJson(tag),
+                DicomJson(tag, _),
                 JsonDataElement {
                     vr,
                     value,
                     bulk_data_uri,
+                    nested_bulk_data,
                 },
             ) = e;
-            if bulk_data_uri.is_some() {
-                tracing::warn!(
-                    "bulk data URI is not supported for InMemDicomObject; skipping {}",
-                    tag
-                );
+
+            // locate bulk data in sequence items from this data set
+            for (item, reference) in nested_bulk_data {
+                let steps = std::iter::once(AttributeSelectorStep::Nested { tag, item })
+                    .chain(reference.selector.iter().copied());
+                bulk_data.push(BulkDataReference {
+                    selector: AttributeSelector::new(steps)
+                        .expect("selector should end with a tag"),
+                    ..reference
+                });
+            }
+
+            if let Some(BulkDataUri(uri)) = bulk_data_uri {
+                bulk_data.push(BulkDataReference {
+                    selector: tag.into(),
+                    vr,
+                    uri,
+                });
+                obj.put(DataElement::new(tag, vr, PrimitiveValue::Empty));
             } else {
                 obj.put(DataElement::new(tag, vr, value));
             }
         }
-        Ok(obj)
+        Ok(DicomJsonObject {
+            object: obj,
+            bulk_data,
+        })
     }
 }
 
-impl<'de, I> Deserialize<'de> for DicomJson<InMemDicomObject<I>>
+impl<'de, I> Deserialize<'de> for DicomJson<DicomJsonObject<I>>
 where
     I: Default + Clone + DataDictionary,
 {
@@ -104,7 +225,31 @@ where
         deserializer
             .deserialize_map(InMemDicomObjectVisitor::default())
             .map(DicomJson::from)
-            .map_err(From::from)
+    }
+}
+
+impl<'de, I> Deserialize<'de> for DicomJson<InMemDicomObject<I>>
+where
+    I: Default + Clone + DataDictionary,
+{
+    /// Deserializes a DICOM JSON data set.
+    ///
+    /// Attributes given by a bulk data URI are left empty.
+    /// Deserialize a [`DicomJsonObject`] instead
+    /// to retrieve their values.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let DicomJsonObject { object, bulk_data } =
+            deserializer.deserialize_map(InMemDicomObjectVisitor::default())?;
+        for reference in bulk_data {
+            tracing::warn!(
+                "bulk data URI is not supported for InMemDicomObject; leaving {} empty",
+                reference.selector
+            );
+        }
+        Ok(DicomJson::from(object))
     }
 }
 
@@ -112,10 +257,9 @@ where
 struct JsonDataElement<D> {
     vr: VR,
     value: Value<InMemDicomObject<D>, InMemFragment>,
-    // TODO(#470): we just ignore this when deserializing with
-    // DicomJson<InMemDicomObject>
-    // Handle this properly with a custom deserializer
     bulk_data_uri: Option<BulkDataUri>,
+    /// bulk data references in sequence items, by item index
+    nested_bulk_data: Vec<(u32, BulkDataReference)>,
 }
 
 #[derive(Debug)]
@@ -146,6 +290,7 @@ where
         let mut value: Option<serde_json::Value> = None;
         let mut inline_binary = None;
         let mut bulk_data_uri = None;
+        let mut nested_bulk_data = Vec::new();
 
         while let Some(key) = map.next_key::<String>()? {
             match &*key {
@@ -171,7 +316,7 @@ where
                     value = Some(map.next_value()?);
                 }
                 "InlineBinary" => {
-                    if values.is_some() {
+                    if value.is_some() {
                         return Err(A::Error::custom(
                             "\"InlineBinary\" conflicts with \"Value\"",
                         ));
@@ -187,7 +332,7 @@ where
                     inline_binary = Some(val);
                 }
                 "BulkDataURI" => {
-                    if values.is_some() {
+                    if value.is_some() {
                         return Err(A::Error::custom("\"BulkDataURI\" conflicts with \"Value\""));
                     }
 
@@ -218,10 +363,15 @@ where
             match vr {
                 // sequence
                 VR::SQ => {
-                    let items: Vec<DicomJson<InMemDicomObject<D>>> =
+                    let items: Vec<DicomJson<DicomJsonObject<D>>> =
                         serde_json::from_value(value).map_err(A::Error::custom)?;
-                    let items: Vec<_> = items.into_iter().map(DicomJson::into_inner).collect();
-                    values = Some(Value::Sequence(items.into()));
+                    let mut objects = Vec::with_capacity(items.len());
+                    for (i, item) in items.into_iter().enumerate() {
+                        let (object, bulk_data) = item.into_inner().into_parts();
+                        nested_bulk_data.extend(bulk_data.into_iter().map(|r| (i as u32, r)));
+                        objects.push(object);
+                    }
+                    values = Some(Value::Sequence(objects.into()));
                 }
                 // always text
                 VR::AE
@@ -240,9 +390,8 @@ where
                 | VR::UI => {
                     let items: Vec<Option<String>> =
                         serde_json::from_value(value).map_err(A::Error::custom)?;
-                    let items: Vec<String> =
-                        items.into_iter().map(|v| v.unwrap_or_default()).collect();
-                    values = Some(PrimitiveValue::Strs(items.into()).into());
+                    let items = items.into_iter().map(|v| v.unwrap_or_default());
+                    values = Some(PrimitiveValue::new_strs(items).into());
                 }
 
                 // should always be signed 16-bit integers
@@ -321,24 +470,22 @@ where
                 }
                 // sometimes numbers, sometimes text,
                 // but retain string form
-                VR::DS => {
-                    let items: Vec<NumberOrText<f64>> =
+                VR::DS | VR::IS => {
+                    let items: Vec<Option<NumberOrText<serde_json::Number>>> =
                         serde_json::from_value(value).map_err(A::Error::custom)?;
-                    let items: C<String> = items.into_iter().map(|v| v.to_string()).collect();
-                    values = Some(PrimitiveValue::Strs(items).into());
-                }
-                VR::IS => {
-                    let items: Vec<NumberOrText<f64>> =
-                        serde_json::from_value(value).map_err(A::Error::custom)?;
-                    let items: C<String> = items.into_iter().map(|v| v.to_string()).collect();
-                    values = Some(PrimitiveValue::Strs(items).into());
+                    let items = items
+                        .into_iter()
+                        .map(|v| v.map(|v| v.to_string()).unwrap_or_default());
+                    values = Some(PrimitiveValue::new_strs(items).into());
                 }
                 // person names
                 VR::PN => {
-                    let items: Vec<DicomJsonPerson> =
+                    let items: Vec<Option<DicomJsonPerson>> =
                         serde_json::from_value(value).map_err(A::Error::custom)?;
-                    let items: C<String> = items.into_iter().map(|v| v.to_string()).collect();
-                    values = Some(PrimitiveValue::Strs(items).into());
+                    let items = items
+                        .into_iter()
+                        .map(|v| v.map(|v| v.to_string()).unwrap_or_default());
+                    values = Some(PrimitiveValue::new_strs(items).into());
                 }
                 // tags
                 VR::AT => {
@@ -353,6 +500,10 @@ where
         }
 
         let value = match (values, inline_binary) {
+            // sequence with no items
+            (None, None) if vr == VR::SQ => {
+                Value::Sequence(DataSetSequence::new(Vec::new(), Length(0)))
+            }
             (None, None) => PrimitiveValue::Empty.into(),
             (None, Some(inline_binary)) => {
                 // decode from Base64
@@ -370,6 +521,7 @@ where
             vr,
             value,
             bulk_data_uri,
+            nested_bulk_data,
         })
     }
 }
@@ -413,7 +565,9 @@ impl<'de> Deserialize<'de> for DicomJson<Tag> {
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_str(TagVisitor).map(DicomJson)
+        deserializer
+            .deserialize_str(TagVisitor)
+            .map(DicomJson::from)
     }
 }
 
@@ -524,6 +678,27 @@ mod tests {
         )
     }
 
+    #[test]
+    fn can_parse_numeric_strings_in_either_form() {
+        let serialized = serde_json::json!({
+            "00200013": { "vr": "IS", "Value": [5] },
+            "00280030": { "vr": "DS", "Value": ["0.5", 0.25] },
+            "00281050": { "vr": "DS", "Value": [40, null] },
+            "00100010": {
+                "vr": "PN",
+                "Value": [{ "Ideographic": "山田^太郎" }, null]
+            }
+        });
+
+        let obj: InMemDicomObject = super::from_value(serialized).unwrap();
+
+        let value = |tag| obj.get(tag).unwrap().value().to_str().unwrap().into_owned();
+        assert_eq!(value(Tag(0x0020, 0x0013)), "5");
+        assert_eq!(value(Tag(0x0028, 0x0030)), "0.5\\0.25");
+        assert_eq!(value(Tag(0x0028, 0x1050)), "40\\");
+        assert_eq!(value(Tag(0x0010, 0x0010)), "=山田^太郎\\");
+    }
+
     #[test]
     fn can_resolve_bulk_data() {
         let serialized = serde_json::json!({
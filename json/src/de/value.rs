@@ -7,7 +7,7 @@ use serde::Deserialize;
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct DicomJsonPerson {
     #[serde(rename = "Alphabetic")]
-    alphabetic: String,
+    alphabetic: Option<String>,
     #[serde(rename = "Ideographic")]
     ideographic: Option<String>,
     #[serde(rename = "Phonetic")]
@@ -16,33 +16,26 @@ pub struct DicomJsonPerson {
 
 impl fmt::Display for DicomJsonPerson {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            DicomJsonPerson {
-                alphabetic,
-                ideographic: None,
-                phonetic: None,
-            } => write!(f, "{}", alphabetic),
-            DicomJsonPerson {
-                alphabetic,
-                ideographic: Some(ideographic),
-                phonetic: None,
-            } => write!(f, "{}={}", alphabetic, ideographic),
-            DicomJsonPerson {
-                alphabetic,
-                ideographic: None,
-                phonetic: Some(phonetic),
-            } => write!(f, "{}=={}", alphabetic, phonetic),
-            DicomJsonPerson {
-                alphabetic,
-                ideographic: Some(ideographic),
-                phonetic: Some(phonetic),
-            } => write!(f, "{}={}={}", alphabetic, ideographic, phonetic),
+        let groups = [&self.alphabetic, &self.ideographic, &self.phonetic];
+        // trailing empty component groups are left out
+        let len = groups
+            .iter()
+            .rposition(|g| g.is_some())
+            .map_or(0, |i| i + 1);
+        for (i, group) in groups[..len].iter().enumerate() {
+            if i > 0 {
+                f.write_str("=")?;
+            }
+            if let Some(group) = group {
+                f.write_str(group)?;
+            }
         }
+        Ok(())
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
-pub struct BulkDataUri(String);
+pub struct BulkDataUri(pub String);
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(untagged)]
@@ -80,8 +80,10 @@
 mod de;
 mod ser;
 
-pub use crate::de::{from_reader, from_slice, from_str, from_value};
-pub use crate::ser::{to_string, to_string_pretty, to_value, to_vec, to_writer};
+pub use crate::de::{
+    from_reader, from_slice, from_str, from_value, BulkDataReference, DicomJsonObject,
+};
+pub use crate::ser::{to_string, to_string_pretty, to_value, to_vec, to_writer, SerializeOptions};
 
 /// A wrapper type for DICOM JSON serialization using [Serde](serde).
 ///
@@ -115,6 +117,10 @@ pub use crate::ser::{to_string, to_string_pretty, to_value, to_vec, to_writer};
 ///   where `GGGG` and `EEEE` are the group/element parts
 ///   in uppercase hexadecimal.
 ///
+/// Serialization can be adjusted
+/// by attaching [`SerializeOptions`] with [`with_options`](DicomJson::with_options),
+/// such as to write large binary values as bulk data references.
+///
 /// [1]: dicom_object::InMemDicomObject
 /// [2]: dicom_object::mem::InMemElement
 /// [3]: dicom_object::DefaultDicomObject
@@ -161,6 +167,9 @@ pub use crate::ser::{to_string, to_string_pretty, to_value, to_vec, to_writer};
 /// `DicomJson` can deserialize:
 ///
 /// - [`InMemDicomObject`][1], expecting a JSON object indexed by tags;
+///   attributes with a bulk data URI are left empty;
+/// - [`DicomJsonObject`], like the above,
+///   but also keeping the bulk data references to be resolved later;
 /// - [`Tag`][5], a string formatted as a DICOM tag;
 /// - [`VR`][6], a 2-character string with one of the supported
 ///   value representation identifiers.
@@ -193,9 +202,19 @@ pub use crate::ser::{to_string, to_string_pretty, to_value, to_vec, to_writer};
 /// # Ok::<(), serde_json::Error>(())
 /// ```
 #[derive(Debug, Clone, PartialEq)]
-pub struct DicomJson<T>(T);
+pub struct DicomJson<T>(T, SerializeOptions);
 
 impl<T> DicomJson<T> {
+    /// Replace the options used when serializing the value.
+    pub fn with_options(self, options: SerializeOptions) -> Self {
+        DicomJson(self.0, options)
+    }
+
+    /// Obtain the options used when serializing the value.
+    pub fn options(&self) -> &SerializeOptions {
+        &self.1
+    }
+
     /// Unwrap the DICOM JSON wrapper,
     /// returning the underlying value.
     pub fn into_inner(self) -> T {
@@ -6,9 +6,12 @@ use crate::DicomJson;
 use dicom_core::{header::Header, DicomValue, PrimitiveValue, Tag, VR};
 use dicom_dictionary_std::StandardDataDictionary;
 use dicom_object::{mem::InMemElement, DefaultDicomObject, InMemDicomObject};
-use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde::{
+    ser::{Error as _, SerializeMap},
+    Serialize, Serializer,
+};
 
-use self::value::{AsNumbers, AsPersonNames, AsStrings, InlineBinary};
+use self::value::{AsNumbers, AsPersonNames, AsStrings, AsTags, InlineBinary, StringsAsNumbers};
 mod value;
 
 /// Serialize a piece of DICOM data as a string of JSON.
@@ -52,9 +55,296 @@ where
     serde_json::to_writer(writer, &DicomJson::from(data))
 }
 
+/// Options for serializing DICOM data to JSON.
+///
+/// The default options write every value in place,
+/// and keep decimal and integer strings (DS and IS) as JSON strings
+/// so that their exact textual form is preserved.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{PrimitiveValue, VR};
+/// # use dicom_object::mem::{InMemDicomObject, InMemElement};
+/// # use dicom_dictionary_std::tags;
+/// use dicom_json::{DicomJson, SerializeOptions};
+///
+/// let obj = InMemDicomObject::from_element_iter([
+///     InMemElement::new(tags::SLICE_THICKNESS, VR::DS, "2.5"),
+///     InMemElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(vec![0_u8; 4096])),
+/// ]);
+///
+/// let options = SerializeOptions::new()
+///     .numeric_strings_as_numbers(true)
+///     .bulk_data("https://example.com/bulk/1.2.3", 1024);
+/// let json = serde_json::to_value(DicomJson::from(&obj).with_options(options))?;
+///
+/// assert_eq!(
+///     json,
+///     serde_json::json!({
+///         "00180050": { "vr": "DS", "Value": [2.5] },
+///         "7FE00010": {
+///             "vr": "OB",
+///             "BulkDataURI": "https://example.com/bulk/1.2.3/7FE00010"
+///         }
+///     }),
+/// );
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SerializeOptions {
+    /// Whether to write the values of decimal strings (DS)
+    /// and integer strings (IS) as JSON numbers.
+    ///
+    /// Values which do not parse as numbers are still written as strings.
+    pub numeric_strings_as_numbers: bool,
+    /// The base URI of bulk data references.
+    ///
+    /// When set, binary values larger than the threshold
+    /// and encapsulated pixel data
+    /// are written as a `"BulkDataURI"`
+    /// instead of `"InlineBinary"`.
+    /// The URI of each value is the base URI
+    /// followed by the path to the attribute,
+    /// made of the tags of the enclosing sequences,
+    /// the index of the item in each sequence,
+    /// and the attribute's own tag,
+    /// separated by slashes
+    /// (e.g. `{base}/52009229/0/00281052`).
+    pub bulk_data_base_uri: Option<String>,
+    /// The maximum length in bytes of a binary value
+    /// to be written inline when bulk data references are enabled.
+    pub bulk_data_threshold: usize,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        SerializeOptions {
+            numeric_strings_as_numbers: false,
+            bulk_data_base_uri: None,
+            bulk_data_threshold: 1024,
+        }
+    }
+}
+
+impl SerializeOptions {
+    /// Create the default set of serialization options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether to write decimal and integer strings as JSON numbers.
+    pub fn numeric_strings_as_numbers(mut self, value: bool) -> Self {
+        self.numeric_strings_as_numbers = value;
+        self
+    }
+
+    /// Write binary values larger than `threshold` bytes
+    /// as references to bulk data under the given base URI.
+    pub fn bulk_data(mut self, base_uri: impl Into<String>, threshold: usize) -> Self {
+        self.bulk_data_base_uri = Some(base_uri.into());
+        self.bulk_data_threshold = threshold;
+        self
+    }
+
+    /// The bulk data URI of the attribute at the given path,
+    /// if bulk data references are enabled.
+    fn bulk_data_uri(&self, path: &str) -> Option<String> {
+        self.bulk_data_base_uri
+            .as_ref()
+            .map(|base| format!("{}/{}", base.trim_end_matches('/'), path))
+    }
+}
+
+/// Format a tag in the DICOM JSON form `GGGGEEEE`.
+fn tag_key(tag: Tag) -> String {
+    format!("{:04X}{:04X}", tag.group(), tag.element())
+}
+
+/// Whether a primitive value has no content,
+/// in which case no value is written.
+fn is_empty_value(value: &PrimitiveValue) -> bool {
+    match value {
+        PrimitiveValue::Str(_) | PrimitiveValue::Strs(_) => value.to_str().is_empty(),
+        _ => value.multiplicity() == 0,
+    }
+}
+
+/// A data set to serialize,
+/// located at the given path prefix in the root data set.
+struct JsonObject<'a, D> {
+    obj: &'a InMemDicomObject<D>,
+    options: &'a SerializeOptions,
+    prefix: &'a str,
+}
+
+impl<D> Serialize for JsonObject<'_, D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.obj.into_iter().map(|e| {
+            let element = JsonElement {
+                element: e,
+                options: self.options,
+                prefix: self.prefix,
+            };
+            (DicomJson::from(e.tag()), element)
+        }))
+    }
+}
+
+/// A sequence of data sets to serialize,
+/// located at the given path in the root data set.
+struct JsonItems<'a, D> {
+    items: &'a [InMemDicomObject<D>],
+    options: &'a SerializeOptions,
+    path: &'a str,
+}
+
+impl<D> Serialize for JsonItems<'_, D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let prefixes: Vec<String> = (0..self.items.len())
+            .map(|i| {
+                if self.path.is_empty() {
+                    format!("{}/", i)
+                } else {
+                    format!("{}/{}/", self.path, i)
+                }
+            })
+            .collect();
+        serializer.collect_seq(
+            self.items
+                .iter()
+                .zip(&prefixes)
+                .map(|(obj, prefix)| JsonObject {
+                    obj,
+                    options: self.options,
+                    prefix,
+                }),
+        )
+    }
+}
+
+/// A data element to serialize,
+/// located at the given path prefix in the root data set.
+struct JsonElement<'a, D> {
+    element: &'a InMemElement<D>,
+    options: &'a SerializeOptions,
+    prefix: &'a str,
+}
+
+impl<D> JsonElement<'_, D> {
+    fn path(&self) -> String {
+        format!("{}{}", self.prefix, tag_key(self.element.tag()))
+    }
+}
+
+impl<D> Serialize for JsonElement<'_, D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut serializer = serializer.serialize_map(None)?;
+        let vr = self.element.vr();
+        serializer.serialize_entry("vr", vr.to_string())?;
+
+        match self.element.value() {
+            DicomValue::Sequence(seq) if seq.items().is_empty() => {
+                // no items, no value
+            }
+            DicomValue::Sequence(seq) => {
+                let path = self.path();
+                serializer.serialize_entry(
+                    "Value",
+                    &JsonItems {
+                        items: seq.items(),
+                        options: self.options,
+                        path: &path,
+                    },
+                )?;
+            }
+            DicomValue::PixelSequence(_seq) => {
+                match self.options.bulk_data_uri(&self.path()) {
+                    Some(uri) => serializer.serialize_entry("BulkDataURI", &uri)?,
+                    None => return Err(S::Error::custom(
+                        "serialization of encapsulated pixel data requires bulk data references",
+                    )),
+                }
+            }
+            DicomValue::Primitive(v) if is_empty_value(v) => {
+                // attribute is present, but has no value
+            }
+            DicomValue::Primitive(v) => match vr {
+                VR::AE
+                | VR::AS
+                | VR::CS
+                | VR::DA
+                | VR::DT
+                | VR::LO
+                | VR::LT
+                | VR::SH
+                | VR::UC
+                | VR::UI
+                | VR::UR
+                | VR::TM
+                | VR::ST
+                | VR::UT => {
+                    serializer.serialize_entry("Value", &AsStrings::from(v))?;
+                }
+                VR::AT => {
+                    serializer.serialize_entry("Value", &AsTags::from(v))?;
+                }
+                VR::PN => {
+                    serializer.serialize_entry("Value", &AsPersonNames::from(v))?;
+                }
+                VR::IS | VR::DS
+                    if self.options.numeric_strings_as_numbers
+                        && matches!(v, PrimitiveValue::Str(_) | PrimitiveValue::Strs(_)) =>
+                {
+                    serializer.serialize_entry("Value", &StringsAsNumbers::from(v))?;
+                }
+                VR::FD
+                | VR::IS
+                | VR::FL
+                | VR::DS
+                | VR::SL
+                | VR::SS
+                | VR::SV
+                | VR::UL
+                | VR::US
+                | VR::UV => {
+                    serializer.serialize_entry("Value", &AsNumbers::from(v))?;
+                }
+                VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN => {
+                    let bulk_data_uri = if v.calculate_byte_len() > self.options.bulk_data_threshold
+                    {
+                        self.options.bulk_data_uri(&self.path())
+                    } else {
+                        None
+                    };
+                    match bulk_data_uri {
+                        Some(uri) => serializer.serialize_entry("BulkDataURI", &uri)?,
+                        None => {
+                            serializer.serialize_entry("InlineBinary", &InlineBinary::from(v))?
+                        }
+                    }
+                }
+                VR::SQ => unreachable!("unexpected VR SQ in primitive value"),
+            },
+        }
+
+        serializer.end()
+    }
+}
+
 impl<'a, D> From<&'a DefaultDicomObject<D>> for DicomJson<&'a DefaultDicomObject<D>> {
     fn from(value: &'a DefaultDicomObject<D>) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
@@ -81,13 +371,23 @@ where
                 continue;
             };
             let e = InMemElement::<StandardDataDictionary>::new(e.tag(), e.vr(), value.clone());
-            ser.serialize_entry(&DicomJson(tag), &DicomJson(&e))?;
+            let element = JsonElement {
+                element: &e,
+                options: &self.1,
+                prefix: "",
+            };
+            ser.serialize_entry(&DicomJson::from(tag), &element)?;
         }
 
         let inner: &InMemDicomObject<_> = &**self.0;
         for e in inner {
             let tag = e.tag();
-            ser.serialize_entry(&DicomJson(tag), &DicomJson(e))?;
+            let element = JsonElement {
+                element: e,
+                options: &self.1,
+                prefix: "",
+            };
+            ser.serialize_entry(&DicomJson::from(tag), &element)?;
         }
 
         ser.end()
@@ -96,7 +396,7 @@ where
 
 impl<D> From<DefaultDicomObject<D>> for DicomJson<DefaultDicomObject<D>> {
     fn from(value: DefaultDicomObject<D>) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
@@ -112,13 +412,13 @@ impl<D> Serialize for DicomJson<DefaultDicomObject<D>> {
     where
         S: Serializer,
     {
-        DicomJson(&self.0).serialize(serializer)
+        DicomJson(&self.0, self.1.clone()).serialize(serializer)
     }
 }
 
 impl<'a, D> From<&'a InMemDicomObject<D>> for DicomJson<&'a InMemDicomObject<D>> {
     fn from(value: &'a InMemDicomObject<D>) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
@@ -133,16 +433,18 @@ where
     where
         S: Serializer,
     {
-        serializer.collect_map(self.0.into_iter().map(|e| {
-            let tag = e.tag();
-            (DicomJson(tag), DicomJson(e))
-        }))
+        JsonObject {
+            obj: self.0,
+            options: &self.1,
+            prefix: "",
+        }
+        .serialize(serializer)
     }
 }
 
 impl<D> From<InMemDicomObject<D>> for DicomJson<InMemDicomObject<D>> {
     fn from(value: InMemDicomObject<D>) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
@@ -151,29 +453,41 @@ impl<D> Serialize for DicomJson<InMemDicomObject<D>> {
     where
         S: Serializer,
     {
-        DicomJson(&self.0).serialize(serializer)
+        JsonObject {
+            obj: &self.0,
+            options: &self.1,
+            prefix: "",
+        }
+        .serialize(serializer)
     }
 }
 
 impl<'a, D> From<&'a [InMemDicomObject<D>]> for DicomJson<&'a [InMemDicomObject<D>]> {
     fn from(value: &'a [InMemDicomObject<D>]) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
 impl<'a, D> Serialize for DicomJson<&'a [InMemDicomObject<D>]> {
     /// Serializes the sequence of DICOM objects into a JSON array.
+    ///
+    /// Bulk data URIs of each object start with its index in the array.
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.collect_seq(self.0.iter().map(DicomJson::from))
+        JsonItems {
+            items: self.0,
+            options: &self.1,
+            path: "",
+        }
+        .serialize(serializer)
     }
 }
 
 impl<D> From<Vec<InMemDicomObject<D>>> for DicomJson<Vec<InMemDicomObject<D>>> {
     fn from(value: Vec<InMemDicomObject<D>>) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
@@ -183,13 +497,18 @@ impl<D> Serialize for DicomJson<Vec<InMemDicomObject<D>>> {
     where
         S: Serializer,
     {
-        DicomJson(self.0.as_slice()).serialize(serializer)
+        JsonItems {
+            items: &self.0,
+            options: &self.1,
+            path: "",
+        }
+        .serialize(serializer)
     }
 }
 
 impl<'a, D> From<&'a InMemElement<D>> for DicomJson<&'a InMemElement<D>> {
     fn from(value: &'a InMemElement<D>) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
@@ -198,8 +517,9 @@ impl<D> Serialize for DicomJson<&'_ InMemElement<D>> {
     ///
     /// The fields present will be:
     /// - `"vr"`, containing the value representation;
-    /// - Either `"Value"` (as an array of values)
-    ///   or `"InlineBinary"` (binary data in base64),
+    /// - One of `"Value"` (as an array of values),
+    ///   `"InlineBinary"` (binary data in base64),
+    ///   or `"BulkDataURI"` (a reference to binary data),
     ///   if the value is not empty.
     ///
     /// The DICOM tag is not encoded,
@@ -208,67 +528,18 @@ impl<D> Serialize for DicomJson<&'_ InMemElement<D>> {
     where
         S: Serializer,
     {
-        let mut serializer = serializer.serialize_map(None)?;
-        let vr = self.0.vr();
-        serializer.serialize_entry("vr", vr.to_string())?;
-
-        match self.0.value() {
-            DicomValue::Sequence(seq) => {
-                serializer.serialize_entry("Value", &DicomJson(seq.items()))?;
-            }
-            DicomValue::PixelSequence(_seq) => {
-                panic!("serialization of encapsulated pixel data is not supported")
-            }
-            DicomValue::Primitive(PrimitiveValue::Empty) => {
-                // no-op
-            }
-            DicomValue::Primitive(v) => match vr {
-                VR::AE
-                | VR::AS
-                | VR::AT
-                | VR::CS
-                | VR::DA
-                | VR::DT
-                | VR::LO
-                | VR::LT
-                | VR::SH
-                | VR::UC
-                | VR::UI
-                | VR::UR
-                | VR::TM
-                | VR::ST
-                | VR::UT => {
-                    serializer.serialize_entry("Value", &AsStrings::from(v))?;
-                }
-                VR::PN => {
-                    serializer.serialize_entry("Value", &AsPersonNames::from(v))?;
-                }
-                VR::FD
-                | VR::IS
-                | VR::FL
-                | VR::DS
-                | VR::SL
-                | VR::SS
-                | VR::SV
-                | VR::UL
-                | VR::US
-                | VR::UV => {
-                    serializer.serialize_entry("Value", &AsNumbers::from(v))?;
-                }
-                VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN => {
-                    serializer.serialize_entry("InlineBinary", &InlineBinary::from(v))?;
-                }
-                VR::SQ => unreachable!("unexpected VR SQ in primitive value"),
-            },
+        JsonElement {
+            element: self.0,
+            options: &self.1,
+            prefix: "",
         }
-
-        serializer.end()
+        .serialize(serializer)
     }
 }
 
 impl<D> From<InMemElement<D>> for DicomJson<InMemElement<D>> {
     fn from(value: InMemElement<D>) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
@@ -277,13 +548,18 @@ impl<D> Serialize for DicomJson<InMemElement<D>> {
     where
         S: Serializer,
     {
-        DicomJson(&self.0).serialize(serializer)
+        JsonElement {
+            element: &self.0,
+            options: &self.1,
+            prefix: "",
+        }
+        .serialize(serializer)
     }
 }
 
 impl From<Tag> for DicomJson<Tag> {
     fn from(value: Tag) -> Self {
-        Self(value)
+        Self(value, SerializeOptions::default())
     }
 }
 
@@ -294,8 +570,7 @@ impl Serialize for DicomJson<Tag> {
     where
         S: Serializer,
     {
-        let Tag(g, e) = self.0;
-        serializer.serialize_str(&format!("{:04X}{:04X}", g, e))
+        serializer.serialize_str(&tag_key(self.0))
     }
 }
 
@@ -1,8 +1,10 @@
 //! DICOM value serialization
-use dicom_core::PrimitiveValue;
+use dicom_core::{PrimitiveValue, Tag};
 use serde::ser::SerializeSeq;
 use serde::Serialize;
 
+use crate::DicomJson;
+
 /// Wrapper type for [primitive values][1]
 /// which should always be encoded as strings.
 ///
@@ -27,7 +29,48 @@ impl<'a> Serialize for AsStrings<'a> {
         S: serde::Serializer,
     {
         let strings = self.0.to_multi_str();
-        serializer.collect_seq(&*strings)
+        // empty values in a multi-valued attribute are null
+        serializer.collect_seq(strings.iter().map(|s| Some(s).filter(|s| !s.is_empty())))
+    }
+}
+
+/// Wrapper type for [primitive values][1]
+/// of numeric strings which should be encoded as numbers.
+///
+/// Should be used for the value representations DS and IS
+/// when the value is held as text.
+/// Values which cannot be parsed as a number are kept as strings.
+///
+/// [1]: dicom_core::PrimitiveValue
+#[derive(Debug, Clone)]
+pub struct StringsAsNumbers<'a>(&'a PrimitiveValue);
+
+impl<'a> From<&'a PrimitiveValue> for StringsAsNumbers<'a> {
+    fn from(value: &'a PrimitiveValue) -> Self {
+        StringsAsNumbers(value)
+    }
+}
+
+impl<'a> Serialize for StringsAsNumbers<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let strings = self.0.to_multi_str();
+        let mut ser = serializer.serialize_seq(Some(strings.len()))?;
+        for s in strings.iter() {
+            let s = s.trim();
+            if s.is_empty() {
+                ser.serialize_element(&Option::<()>::None)?;
+            } else if let Ok(number) = s.parse::<i64>() {
+                ser.serialize_element(&number)?;
+            } else if let Some(number) = s.parse::<f64>().ok().filter(|n| n.is_finite()) {
+                ser.serialize_element(&number)?;
+            } else {
+                ser.serialize_element(s)?;
+            }
+        }
+        ser.end()
     }
 }
 
@@ -63,8 +106,12 @@ impl<'a> Serialize for AsNumbers<'a> {
             PrimitiveValue::Time(_) => panic!("wrong impl: cannot encode Time as numbers"),
             PrimitiveValue::Tags(_) => panic!("wrong impl: cannot encode Tags as numbers"),
             // strings
-            PrimitiveValue::Strs(strings) => serializer.collect_seq(strings),
-            PrimitiveValue::Str(string) => serializer.collect_seq([string]),
+            PrimitiveValue::Strs(strings) => serializer.collect_seq(
+                strings
+                    .iter()
+                    .map(|s| Some(s.trim()).filter(|s| !s.is_empty())),
+            ),
+            PrimitiveValue::Str(string) => serializer.collect_seq([string.trim()]),
             // no risk of precision loss
             PrimitiveValue::U8(numbers) => serializer.collect_seq(numbers),
             PrimitiveValue::I16(numbers) => serializer.collect_seq(numbers),
@@ -170,23 +217,78 @@ impl<'a> Serialize for AsPersonNames<'a> {
         S: serde::Serializer,
     {
         let strings = self.0.to_multi_str();
-        serializer.collect_seq(strings.iter().map(|p| PersonNameDef::from(p.as_str())))
+        serializer.collect_seq(strings.iter().map(|p| {
+            // empty values in a multi-valued attribute are null
+            Some(PersonNameDef::from(p.as_str())).filter(|p| !p.is_empty())
+        }))
     }
 }
 
 /// Wrapper type for a string
 /// to be interpreted as a person's name.
 ///
+/// Each of the alphabetic, ideographic, and phonetic
+/// component groups is written only if present.
+///
 /// Should only used for the value representation PN.
 #[derive(Debug, Clone, Serialize)]
 pub struct PersonNameDef<'a> {
-    #[serde(rename = "Alphabetic")]
-    alphabetic: &'a str,
+    #[serde(rename = "Alphabetic", skip_serializing_if = "Option::is_none")]
+    alphabetic: Option<&'a str>,
+    #[serde(rename = "Ideographic", skip_serializing_if = "Option::is_none")]
+    ideographic: Option<&'a str>,
+    #[serde(rename = "Phonetic", skip_serializing_if = "Option::is_none")]
+    phonetic: Option<&'a str>,
+}
+
+impl PersonNameDef<'_> {
+    /// Whether no component group is present.
+    fn is_empty(&self) -> bool {
+        self.alphabetic.is_none() && self.ideographic.is_none() && self.phonetic.is_none()
+    }
 }
 
 impl<'a> From<&'a str> for PersonNameDef<'a> {
     fn from(value: &'a str) -> Self {
-        PersonNameDef { alphabetic: value }
+        let mut groups = value
+            .splitn(3, '=')
+            .map(|group| Some(group).filter(|g| !g.is_empty()));
+        PersonNameDef {
+            alphabetic: groups.next().flatten(),
+            ideographic: groups.next().flatten(),
+            phonetic: groups.next().flatten(),
+        }
+    }
+}
+
+/// Wrapper type for [primitive values][1]
+/// which should be encoded as attribute tags,
+/// in the form `"GGGGEEEE"`.
+///
+/// Should only used for the value representation AT.
+///
+/// [1]: dicom_core::PrimitiveValue
+#[derive(Debug, Clone)]
+pub struct AsTags<'a>(&'a PrimitiveValue);
+
+impl<'a> From<&'a PrimitiveValue> for AsTags<'a> {
+    fn from(value: &'a PrimitiveValue) -> Self {
+        AsTags(value)
+    }
+}
+
+impl<'a> Serialize for AsTags<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.0 {
+            PrimitiveValue::Tags(tags) => {
+                serializer.collect_seq(tags.iter().map(|tag| DicomJson::<Tag>::from(*tag)))
+            }
+            // tags stored as text are written as is
+            value => AsStrings(value).serialize(serializer),
+        }
     }
 }
 
@@ -0,0 +1,174 @@
+//! Serialization of a data set,
+//! checked against reference DICOM JSON documents.
+use dicom_core::ops::AttributeSelector;
+use dicom_core::value::DataSetSequence;
+use dicom_core::{dicom_value, Length, PrimitiveValue, Tag, VR};
+use dicom_dictionary_std::{tags, uids};
+use dicom_json::{DicomJson, DicomJsonObject, SerializeOptions};
+use dicom_object::mem::{InMemDicomObject, InMemElement};
+use dicom_object::file::ReadPreamble;
+use dicom_object::{FileMetaTableBuilder, OpenFileOptions};
+use serde_json::Value;
+
+fn reference(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let data = std::fs::read(path).unwrap();
+    serde_json::from_slice(&data).unwrap()
+}
+
+/// Write the data set to a DICOM file in memory and read it back.
+fn through_file(obj: InMemDicomObject) -> InMemDicomObject {
+    let file = obj
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .media_storage_sop_class_uid(uids::CT_IMAGE_STORAGE)
+                .media_storage_sop_instance_uid("1.2.3.4.5.6.7.8.11")
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN),
+        )
+        .unwrap();
+    let mut data = Vec::new();
+    file.write_all(&mut data).unwrap();
+
+    let file = OpenFileOptions::new()
+        .read_preamble(ReadPreamble::Always)
+        .from_reader(&data[..])
+        .unwrap();
+    file.into_inner()
+}
+
+fn referenced_image(instance_uid: &str, extra: InMemElement) -> InMemDicomObject {
+    InMemDicomObject::from_element_iter([
+        InMemElement::new(
+            tags::REFERENCED_SOP_CLASS_UID,
+            VR::UI,
+            "1.2.840.10008.5.1.4.1.1.2\0",
+        ),
+        InMemElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, instance_uid),
+        extra,
+    ])
+}
+
+fn dataset() -> InMemDicomObject {
+    InMemDicomObject::from_element_iter([
+        InMemElement::new(tags::SPECIFIC_CHARACTER_SET, VR::CS, "ISO_IR 192"),
+        InMemElement::new(tags::STUDY_DATE, VR::DA, "20130409"),
+        InMemElement::new(tags::STUDY_TIME, VR::TM, PrimitiveValue::Empty),
+        InMemElement::new(
+            tags::MODALITIES_IN_STUDY,
+            VR::CS,
+            dicom_value!(Strs, ["CT", "PET"]),
+        ),
+        InMemElement::new(tags::REFERRING_PHYSICIAN_NAME, VR::PN, "^Bob^^Dr."),
+        InMemElement::new(
+            tags::REFERENCED_STUDY_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::new(vec![], Length(0)),
+        ),
+        InMemElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::new(
+                vec![
+                    referenced_image(
+                        "1.2.3.4.5.6.7.8.9\0",
+                        InMemElement::new(tags::RESCALE_INTERCEPT, VR::DS, "-1024 "),
+                    ),
+                    referenced_image(
+                        "1.2.3.4.5.6.7.8.10",
+                        InMemElement::new(
+                            tags::ICC_PROFILE,
+                            VR::OB,
+                            PrimitiveValue::from((0..16).collect::<Vec<u8>>()),
+                        ),
+                    ),
+                ],
+                Length::UNDEFINED,
+            ),
+        ),
+        InMemElement::new(
+            tags::PATIENT_NAME,
+            VR::PN,
+            "Yamada^Tarou=山田^太郎=やまだ^たろう",
+        ),
+        InMemElement::new(tags::PATIENT_ID, VR::LO, "12345"),
+        InMemElement::new(
+            tags::OTHER_PATIENT_NAMES,
+            VR::PN,
+            dicom_value!(Strs, ["Smith^Jane", "", "=山田^花子"]),
+        ),
+        InMemElement::new(tags::SLICE_THICKNESS, VR::DS, "2.5"),
+        InMemElement::new(tags::INSTANCE_NUMBER, VR::IS, "5 "),
+        InMemElement::new(tags::SLICE_LOCATION, VR::DS, "-12.75"),
+        InMemElement::new(
+            tags::DIMENSION_INDEX_POINTER,
+            VR::AT,
+            dicom_value!(Tags, [Tag(0x0020, 0x9056), Tag(0x0020, 0x9057)]),
+        ),
+        InMemElement::new(tags::ROWS, VR::US, PrimitiveValue::from(2_u16)),
+        InMemElement::new(tags::COLUMNS, VR::US, PrimitiveValue::from(2_u16)),
+        InMemElement::new(
+            tags::PIXEL_SPACING,
+            VR::DS,
+            dicom_value!(Strs, ["0.5", "0.5"]),
+        ),
+        InMemElement::new(tags::WINDOW_CENTER, VR::DS, dicom_value!(Strs, ["40", ""])),
+        InMemElement::new(tags::PIXEL_DATA, VR::OW, dicom_value!(U16, [0, 1, 2, 3])),
+    ])
+}
+
+#[test]
+fn serialize_to_reference_document() {
+    let json = dicom_json::to_value(&dataset()).unwrap();
+    pretty_assertions::assert_eq!(json, reference("dataset.json"));
+}
+
+#[test]
+fn serialize_with_bulk_data_and_numbers() {
+    let options = SerializeOptions::new()
+        .numeric_strings_as_numbers(true)
+        .bulk_data("https://example.com/instances/1.2.3/bulk/", 8);
+    let obj = dataset();
+    let json = serde_json::to_value(DicomJson::from(&obj).with_options(options)).unwrap();
+    pretty_assertions::assert_eq!(json, reference("dataset_bulk_data.json"));
+}
+
+#[test]
+fn reference_document_roundtrip() {
+    let reference = reference("dataset.json");
+    let obj: InMemDicomObject = dicom_json::from_value(reference.clone()).unwrap();
+    let obj = through_file(obj);
+    pretty_assertions::assert_eq!(dicom_json::to_value(&obj).unwrap(), reference);
+}
+
+#[test]
+fn resolve_bulk_data_references() {
+    let obj: DicomJsonObject = dicom_json::from_value(reference("dataset_bulk_data.json")).unwrap();
+
+    let selector = AttributeSelector::from((tags::REFERENCED_IMAGE_SEQUENCE, 1, tags::ICC_PROFILE));
+    let [bulk_data] = obj.bulk_data() else {
+        panic!(
+            "expected one bulk data reference, got {:?}",
+            obj.bulk_data()
+        );
+    };
+    assert_eq!(bulk_data.selector, selector);
+    assert_eq!(bulk_data.vr, VR::OB);
+    assert_eq!(
+        bulk_data.uri,
+        "https://example.com/instances/1.2.3/bulk/00081140/1/00282000"
+    );
+    // still present, but empty
+    assert_eq!(
+        obj.object().value_at(selector).unwrap().primitive(),
+        Some(&PrimitiveValue::Empty)
+    );
+
+    let obj = obj
+        .resolve_bulk_data(|_| Ok::<_, std::convert::Infallible>((0..16).collect()))
+        .unwrap();
+    let obj = through_file(obj);
+    pretty_assertions::assert_eq!(
+        dicom_json::to_value(&obj).unwrap(),
+        reference("dataset.json")
+    );
+}
@@ -0,0 +1,644 @@
+//! DICOM objects with values allocated from a single arena.
+//!
+//! An [`ArenaDicomObject`] is read into one contiguous buffer (the arena),
+//! which is owned by the object.
+//! The data set structure is kept in a compact index
+//! of element headers and value ranges within the arena,
+//! so that reading a data set with thousands of elements
+//! takes only a handful of allocations,
+//! instead of at least one per element value
+//! as in an [`InMemDicomObject`].
+//! Text values are retrieved from the arena without copying
+//! whenever their character set allows it.
+//!
+//! Elements are accessed through lightweight views
+//! ([`ArenaElement`] and [`ArenaDataSetRef`])
+//! which borrow the object,
+//! so arena-backed values cannot be used after the object is dropped.
+//! The object can be converted into an [`InMemDicomObject`]
+//! with [`to_in_mem`](ArenaDataSet::to_in_mem).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_dictionary_std::tags;
+//! use dicom_object::arena;
+//!
+//! let obj = arena::open_file("0001.dcm")?;
+//! let patient_name = obj.element(tags::PATIENT_NAME)?.to_str()?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::borrow::Cow;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
+use dicom_core::header::{DataElementHeader, HasLength, Header, Length};
+use dicom_core::value::PrimitiveValue;
+use dicom_core::{Tag, VR};
+use dicom_dictionary_std::StandardDataDictionary;
+use dicom_encoding::text::SpecificCharacterSet;
+use dicom_encoding::transfer_syntax::TransferSyntax;
+use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::borrowed::{BorrowedDataSet, BorrowedElement, BorrowedReadError, BorrowedValue};
+use crate::file::ReadPreamble;
+use crate::mem::InMemDicomObject;
+use crate::{
+    AccessByNameError, AccessError, FileDicomObject, NoSuchAttributeNameSnafu,
+    NoSuchDataElementTagSnafu,
+};
+
+/// A DICOM object with its values allocated from an arena owned by the object.
+///
+/// See the [module-level documentation](self) for more information.
+pub type ArenaDicomObject = FileDicomObject<ArenaDataSet>;
+
+/// An error which may occur when reading an arena-backed DICOM object.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum ArenaReadError {
+    #[snafu(display("Could not open file '{}'", filename.display()))]
+    OpenFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not read DICOM data into the arena
+    ReadData {
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not parse DICOM data
+    ParseData {
+        #[snafu(backtrace)]
+        source: BorrowedReadError,
+    },
+}
+
+pub type Result<T, E = ArenaReadError> = std::result::Result<T, E>;
+
+/// The value of an element in the arena index.
+#[derive(Debug, Clone)]
+enum NodeValue {
+    Primitive(Range<usize>),
+    Sequence(Vec<DataSetNode>),
+    PixelSequence {
+        offset_table: Range<usize>,
+        fragments: Vec<Range<usize>>,
+    },
+}
+
+/// An element in the arena index.
+#[derive(Debug, Clone)]
+struct ElementNode {
+    header: DataElementHeader,
+    value: NodeValue,
+    charset: Arc<SpecificCharacterSet>,
+}
+
+/// A data set in the arena index,
+/// with its elements sorted by tag.
+#[derive(Debug, Clone, Default)]
+struct DataSetNode {
+    elements: Vec<ElementNode>,
+}
+
+/// The range of the given slice within the arena.
+fn range_in(arena: &[u8], bytes: &[u8]) -> Range<usize> {
+    let start = bytes.as_ptr() as usize - arena.as_ptr() as usize;
+    start..start + bytes.len()
+}
+
+impl DataSetNode {
+    fn from_borrowed(arena: &[u8], data_set: &BorrowedDataSet<'_>) -> Self {
+        let elements = data_set
+            .iter()
+            .map(|elem| ElementNode {
+                header: *elem.header(),
+                value: match elem.value() {
+                    BorrowedValue::Primitive(bytes) => NodeValue::Primitive(range_in(arena, bytes)),
+                    BorrowedValue::Sequence(items) => NodeValue::Sequence(
+                        items
+                            .iter()
+                            .map(|item| DataSetNode::from_borrowed(arena, item))
+                            .collect(),
+                    ),
+                    BorrowedValue::PixelSequence {
+                        offset_table,
+                        fragments,
+                    } => NodeValue::PixelSequence {
+                        offset_table: range_in(arena, offset_table),
+                        fragments: fragments
+                            .iter()
+                            .map(|fragment| range_in(arena, fragment))
+                            .collect(),
+                    },
+                },
+                charset: Arc::clone(elem.charset()),
+            })
+            .collect();
+        DataSetNode { elements }
+    }
+
+    fn get(&self, tag: Tag) -> Option<&ElementNode> {
+        self.elements
+            .binary_search_by_key(&tag, |elem| elem.header.tag)
+            .ok()
+            .map(|i| &self.elements[i])
+    }
+}
+
+/// A DICOM data set with its values allocated from an arena.
+///
+/// This is the data set type of an [`ArenaDicomObject`].
+/// The items of its sequences are accessed through [`ArenaDataSetRef`].
+#[derive(Clone)]
+pub struct ArenaDataSet {
+    arena: Vec<u8>,
+    /// the position of the data set in the arena
+    start: usize,
+    root: DataSetNode,
+    ts: &'static TransferSyntax,
+}
+
+impl fmt::Debug for ArenaDataSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArenaDataSet")
+            .field("arena_len", &self.arena.len())
+            .field("root", &self.root)
+            .field("ts", &self.ts.uid())
+            .finish()
+    }
+}
+
+impl ArenaDataSet {
+    /// Obtain a view of the root data set.
+    pub fn root(&self) -> ArenaDataSetRef<'_> {
+        ArenaDataSetRef {
+            arena: &self.arena,
+            ts: self.ts,
+            node: &self.root,
+        }
+    }
+
+    /// Retrieve a particular DICOM element by its tag,
+    /// or `None` if it is not present.
+    pub fn get(&self, tag: Tag) -> Option<ArenaElement<'_>> {
+        self.root().get(tag)
+    }
+
+    /// Retrieve a particular DICOM element by its tag.
+    pub fn element(&self, tag: Tag) -> Result<ArenaElement<'_>, AccessError> {
+        self.root().element(tag)
+    }
+
+    /// Retrieve a particular DICOM element by its name.
+    pub fn element_by_name(&self, name: &str) -> Result<ArenaElement<'_>, AccessByNameError> {
+        self.root().element_by_name(name)
+    }
+
+    /// Obtain an iterator over the elements of this data set.
+    pub fn iter(&self) -> impl Iterator<Item = ArenaElement<'_>> + '_ {
+        self.root().iter()
+    }
+
+    /// Retrieve the number of elements in this data set.
+    pub fn len(&self) -> usize {
+        self.root.elements.len()
+    }
+
+    /// Check whether this data set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.elements.is_empty()
+    }
+
+    /// Retrieve the size of the arena in bytes.
+    pub fn arena_len(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Create an in-memory copy of this data set.
+    pub fn to_in_mem(&self) -> Result<InMemDicomObject, BorrowedReadError> {
+        BorrowedDataSet::from_slice_with_ts(&self.arena[self.start..], self.ts)?.to_owned()
+    }
+}
+
+impl ArenaDicomObject {
+    /// Create an in-memory copy of this DICOM object.
+    pub fn to_in_mem(&self) -> Result<FileDicomObject<InMemDicomObject>, BorrowedReadError> {
+        Ok(FileDicomObject {
+            meta: self.meta().clone(),
+            obj: (**self).to_in_mem()?,
+        })
+    }
+}
+
+/// A view of a data set in an arena,
+/// either the root data set or a sequence item.
+#[derive(Clone, Copy)]
+pub struct ArenaDataSetRef<'a> {
+    arena: &'a [u8],
+    ts: &'static TransferSyntax,
+    node: &'a DataSetNode,
+}
+
+impl fmt::Debug for ArenaDataSetRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> ArenaDataSetRef<'a> {
+    fn view(&self, node: &'a ElementNode) -> ArenaElement<'a> {
+        ArenaElement {
+            arena: self.arena,
+            ts: self.ts,
+            node,
+        }
+    }
+
+    /// Retrieve a particular DICOM element by its tag,
+    /// or `None` if it is not present.
+    pub fn get(&self, tag: Tag) -> Option<ArenaElement<'a>> {
+        self.node.get(tag).map(|node| self.view(node))
+    }
+
+    /// Retrieve a particular DICOM element by its tag.
+    pub fn element(&self, tag: Tag) -> Result<ArenaElement<'a>, AccessError> {
+        self.get(tag).context(NoSuchDataElementTagSnafu { tag })
+    }
+
+    /// Retrieve a particular DICOM element by its name.
+    pub fn element_by_name(&self, name: &str) -> Result<ArenaElement<'a>, AccessByNameError> {
+        let tag = StandardDataDictionary
+            .by_name(name)
+            .context(NoSuchAttributeNameSnafu { name })?
+            .tag();
+        self.element(tag).map_err(|e| e.into_access_by_name(name))
+    }
+
+    /// Obtain an iterator over the elements of this data set.
+    pub fn iter(&self) -> impl Iterator<Item = ArenaElement<'a>> + 'a {
+        let this = *self;
+        self.node.elements.iter().map(move |node| this.view(node))
+    }
+
+    /// Retrieve the number of elements in this data set.
+    pub fn len(&self) -> usize {
+        self.node.elements.len()
+    }
+
+    /// Check whether this data set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.node.elements.is_empty()
+    }
+}
+
+/// A view of a data element in an arena.
+#[derive(Clone, Copy)]
+pub struct ArenaElement<'a> {
+    arena: &'a [u8],
+    ts: &'static TransferSyntax,
+    node: &'a ElementNode,
+}
+
+impl fmt::Debug for ArenaElement<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArenaElement")
+            .field("header", &self.node.header)
+            .field("value", &self.node.value)
+            .finish()
+    }
+}
+
+impl HasLength for ArenaElement<'_> {
+    fn length(&self) -> Length {
+        self.node.header.len
+    }
+}
+
+impl Header for ArenaElement<'_> {
+    fn tag(&self) -> Tag {
+        self.node.header.tag
+    }
+}
+
+impl<'a> ArenaElement<'a> {
+    /// Retrieve the element header.
+    pub fn header(&self) -> &'a DataElementHeader {
+        &self.node.header
+    }
+
+    /// Retrieve the value representation of the element.
+    pub fn vr(&self) -> VR {
+        self.node.header.vr
+    }
+
+    /// Retrieve the bytes of the primitive value of this element,
+    /// as encoded in the arena.
+    ///
+    /// Returns `None` if the element is a data set sequence
+    /// or encapsulated pixel data.
+    pub fn bytes(&self) -> Option<&'a [u8]> {
+        match &self.node.value {
+            NodeValue::Primitive(range) => Some(&self.arena[range.clone()]),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the items of this element,
+    /// if it is a data set sequence.
+    pub fn items(&self) -> Option<impl Iterator<Item = ArenaDataSetRef<'a>> + 'a> {
+        match &self.node.value {
+            NodeValue::Sequence(items) => {
+                let (arena, ts) = (self.arena, self.ts);
+                Some(
+                    items
+                        .iter()
+                        .map(move |node| ArenaDataSetRef { arena, ts, node }),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Retrieve the bytes of each pixel data fragment of this element,
+    /// if it is encapsulated pixel data.
+    ///
+    /// The basic offset table is not included.
+    pub fn fragments(&self) -> Option<impl Iterator<Item = &'a [u8]> + 'a> {
+        match &self.node.value {
+            NodeValue::PixelSequence { fragments, .. } => {
+                let arena = self.arena;
+                Some(fragments.iter().map(move |range| &arena[range.clone()]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Retrieve the bytes of the basic offset table of this element,
+    /// if it is encapsulated pixel data.
+    pub fn offset_table_bytes(&self) -> Option<&'a [u8]> {
+        match &self.node.value {
+            NodeValue::PixelSequence { offset_table, .. } => {
+                Some(&self.arena[offset_table.clone()])
+            }
+            _ => None,
+        }
+    }
+
+    /// View the primitive value of this element as a borrowed element.
+    fn as_borrowed(&self) -> Result<BorrowedElement<'a>, BorrowedReadError> {
+        match &self.node.value {
+            NodeValue::Primitive(range) => Ok(BorrowedElement::new_primitive(
+                self.node.header,
+                &self.arena[range.clone()],
+                range.start as u64,
+                Arc::clone(&self.node.charset),
+                self.ts,
+            )),
+            _ => crate::borrowed::NotPrimitiveSnafu {
+                tag: self.node.header.tag,
+            }
+            .fail(),
+        }
+    }
+
+    /// Retrieve the text of this element as a single string,
+    /// with trailing padding removed.
+    ///
+    /// The text is borrowed from the arena
+    /// if it does not need to be decoded into UTF-8
+    /// (see [`BorrowedElement::to_str`]).
+    pub fn to_str(&self) -> Result<Cow<'a, str>, BorrowedReadError> {
+        self.as_borrowed()?.to_str()
+    }
+
+    /// Decode the primitive value of this element into memory,
+    /// in the same way as when reading an in-memory object.
+    pub fn to_primitive_value(&self) -> Result<PrimitiveValue, BorrowedReadError> {
+        self.as_borrowed()?.to_primitive_value()
+    }
+}
+
+/// Open a DICOM file into an arena.
+///
+/// The presence of the 128-byte preamble is detected automatically,
+/// and the preamble is assumed to be there if detection fails,
+/// as in [`open_file`](crate::open_file).
+pub fn open_file<P>(path: P) -> Result<ArenaDicomObject>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|_| OpenFileSnafu { filename: path })?;
+    read_arena(BufReader::new(file), ReadPreamble::Auto, true)
+}
+
+/// Read a DICOM object into an arena,
+/// from the current position of the given source to its end.
+///
+/// The presence of the 128-byte preamble is detected automatically,
+/// and the source is assumed to start at the file meta group
+/// if detection fails,
+/// as in [`from_reader`](crate::from_reader).
+pub fn from_reader<R>(src: R) -> Result<ArenaDicomObject>
+where
+    R: Read,
+{
+    from_reader_with_preamble(src, ReadPreamble::Auto)
+}
+
+/// Read a DICOM object into an arena,
+/// from the current position of the given source to its end,
+/// with the given option for reading the 128-byte preamble.
+pub fn from_reader_with_preamble<R>(src: R, read_preamble: ReadPreamble) -> Result<ArenaDicomObject>
+where
+    R: Read,
+{
+    read_arena(src, read_preamble, false)
+}
+
+/// Read a DICOM object into an arena,
+/// assuming the presence of the preamble as given by `preamble_by_default`
+/// if it cannot be detected.
+fn read_arena<R>(
+    mut src: R,
+    read_preamble: ReadPreamble,
+    preamble_by_default: bool,
+) -> Result<ArenaDicomObject>
+where
+    R: Read,
+{
+    let mut arena = Vec::new();
+    src.read_to_end(&mut arena).context(ReadDataSnafu)?;
+    let has_preamble = match read_preamble {
+        ReadPreamble::Always => true,
+        ReadPreamble::Never => false,
+        ReadPreamble::Auto => match (arena.get(128..132), arena.get(..4)) {
+            (Some(b"DICM"), _) => true,
+            (_, Some(b"DICM")) => false,
+            _ => preamble_by_default,
+        },
+    };
+    let position = if has_preamble { 128 } else { 0 };
+    let (meta, ts, start) =
+        crate::borrowed::read_meta_at(&arena, position).context(ParseDataSnafu)?;
+    let root = {
+        let data_set = crate::borrowed::read_data_set(&arena[start..], ts, start as u64)
+            .context(ParseDataSnafu)?;
+        DataSetNode::from_borrowed(&arena, &data_set)
+    };
+    Ok(FileDicomObject {
+        meta,
+        obj: ArenaDataSet {
+            arena,
+            start,
+            root,
+            ts,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_reader, from_reader_with_preamble, ArenaReadError};
+    use crate::file::ReadPreamble;
+    use crate::meta::FileMetaTableBuilder;
+    use crate::InMemDicomObject;
+    use crate::OpenFileOptions;
+    use dicom_core::{DataElement, PrimitiveValue, Tag, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use std::borrow::Cow;
+
+    fn fixture() -> (InMemDicomObject, Vec<u8>) {
+        let mut obj = crate::dicom_object! {
+            SpecificCharacterSet: "ISO_IR 100",
+            SOPClassUID: uids::SECONDARY_CAPTURE_IMAGE_STORAGE,
+            SOPInstanceUID: "2.25.175",
+            PatientName: "Doe^John",
+            InstitutionName: "Hôpital",
+            Rows: 2_u16,
+            Columns: 2_u16,
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "2.25.1", ReferencedFrameNumber: "1" },
+                { ReferencedSOPInstanceUID: "2.25.2" },
+            ],
+        };
+        for i in 0..500 {
+            obj.put(DataElement::new(
+                Tag(0x0011, 0x1000 + i),
+                VR::LO,
+                PrimitiveValue::from(format!("value #{}", i)),
+            ));
+        }
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PrimitiveValue::from(vec![1_u8, 2, 3, 4]),
+        ));
+        let file = obj
+            .clone()
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+            .unwrap();
+        let mut bytes = Vec::new();
+        file.write_all(&mut bytes).unwrap();
+        (obj, bytes)
+    }
+
+    #[test]
+    fn read_same_contents_as_in_mem() {
+        let (_, bytes) = fixture();
+        let in_mem = crate::from_reader(&bytes[..]).unwrap();
+        let obj = from_reader(&bytes[..]).unwrap();
+
+        assert_eq!(obj.meta(), in_mem.meta());
+        assert_eq!(obj.len(), in_mem.iter().count());
+        for (elem, expected) in obj.iter().zip(in_mem.iter()) {
+            assert_eq!(elem.header().tag, expected.header().tag);
+            assert_eq!(elem.vr(), expected.vr());
+            if let Some(value) = expected.value().primitive() {
+                assert_eq!(&elem.to_primitive_value().unwrap(), value);
+            }
+        }
+
+        // text is borrowed from the arena when possible
+        let patient_name = obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap();
+        assert!(matches!(patient_name, Cow::Borrowed("Doe^John")));
+        let institution = obj
+            .element(tags::INSTITUTION_NAME)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_eq!(institution, "Hôpital");
+        assert_eq!(
+            obj.element(Tag(0x0011, 0x11F3)).unwrap().to_str().unwrap(),
+            "value #499"
+        );
+
+        let items: Vec<_> = obj
+            .element(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap()
+            .collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[1]
+                .element(tags::REFERENCED_SOP_INSTANCE_UID)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "2.25.2"
+        );
+        assert_eq!(
+            obj.element(tags::PIXEL_DATA).unwrap().bytes().unwrap(),
+            &[1, 2, 3, 4]
+        );
+
+        // converting to an in-memory object yields the same object
+        let converted = obj.to_in_mem().unwrap();
+        assert_eq!(
+            format!("{:?}", *converted),
+            format!("{:?}", *in_mem),
+            "sequences of undefined length never compare equal"
+        );
+        let mut written = Vec::new();
+        converted.write_all(&mut written).unwrap();
+        assert_eq!(written, bytes);
+    }
+
+    #[test]
+    fn read_with_and_without_preamble() {
+        let (_, bytes) = fixture();
+        for (src, read_preamble) in [
+            (&bytes[..], ReadPreamble::Auto),
+            (&bytes[..], ReadPreamble::Always),
+            (&bytes[128..], ReadPreamble::Auto),
+            (&bytes[128..], ReadPreamble::Never),
+        ] {
+            let in_mem = OpenFileOptions::new()
+                .read_preamble(read_preamble)
+                .from_reader(src)
+                .unwrap();
+            let obj = from_reader_with_preamble(src, read_preamble).unwrap();
+            assert_eq!(obj.meta(), in_mem.meta());
+            assert_eq!(obj.len(), in_mem.iter().count());
+        }
+
+        // not skipping the preamble
+        assert!(from_reader_with_preamble(&bytes[..], ReadPreamble::Never).is_err());
+    }
+
+    #[test]
+    fn read_truncated_data() {
+        let (_, bytes) = fixture();
+        let err = from_reader(&bytes[..bytes.len() - 2]).unwrap_err();
+        assert!(matches!(err, ArenaReadError::ParseData { .. }), "{:?}", err);
+    }
+}
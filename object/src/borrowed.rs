@@ -0,0 +1,1280 @@
+//! DICOM objects borrowing their values from a byte slice.
+//!
+//! A [`BorrowedDicomObject`] is read from DICOM data which is
+//! already in memory as a whole,
+//! such as a file read into a buffer or mapped into memory.
+//! Instead of copying each value out of the data,
+//! primitive elements and pixel data fragments
+//! refer to their bytes in the original slice,
+//! and values are only interpreted when requested.
+//! Text which is already valid UTF-8
+//! (because it is in ASCII or the character set is ISO IR 192)
+//! is retrieved without copying as well.
+//!
+//! Both borrowed data sets and their elements
+//! can be turned into in-memory objects with `to_owned`,
+//! after which the original data is no longer needed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_dictionary_std::tags;
+//! use dicom_object::borrowed;
+//!
+//! let bytes = std::fs::read("0001.dcm")?;
+//! let obj = borrowed::from_slice(&bytes)?;
+//! let patient_name = obj.element(tags::PATIENT_NAME)?.to_str()?;
+//! let pixel_data: &[u8] = obj.element(tags::PIXEL_DATA)?.bytes().unwrap_or_default();
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
+use dicom_core::header::{DataElementHeader, HasLength, Header, Length, SequenceItemHeader};
+use dicom_core::value::{DataSetSequence, PixelFragmentSequence, PrimitiveValue, Value};
+use dicom_core::{DataElement, Tag, VR};
+use dicom_dictionary_std::StandardDataDictionary;
+use dicom_encoding::decode::explicit_be::ExplicitVRBigEndianDecoder;
+use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
+use dicom_encoding::decode::implicit_le::ImplicitVRLittleEndianDecoder;
+use dicom_encoding::decode::Decode;
+use dicom_encoding::text::{DecodeTextError, SpecificCharacterSet, TextCodec};
+use dicom_encoding::transfer_syntax::{Codec, Endianness, TransferSyntax, TransferSyntaxIndex};
+use dicom_parser::stateful::decode::{DynStatefulDecoder, StatefulDecode};
+use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::mem::{InMemDicomObject, InMemElement, InMemFragment};
+use crate::{
+    AccessByNameError, AccessError, DefaultDicomObject, DicomElement, DicomObject, FileDicomObject,
+    FileMetaTable, NoSuchAttributeNameSnafu, NoSuchDataElementTagSnafu,
+};
+
+/// A DICOM object whose values are borrowed from a byte slice.
+///
+/// See the [module-level documentation](self) for more information.
+pub type BorrowedDicomObject<'a> = FileDicomObject<BorrowedDataSet<'a>>;
+
+/// An error which may occur when reading a borrowed DICOM object
+/// or interpreting one of its values.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum BorrowedReadError {
+    /// Could not parse meta group data set
+    ParseMetaDataSet {
+        #[snafu(backtrace)]
+        source: crate::meta::Error,
+    },
+    /// Unsupported transfer syntax `{uid}`
+    ReadUnsupportedTransferSyntax { uid: String, backtrace: Backtrace },
+    /// Could not decode data element header at position {position}
+    DecodeHeader {
+        position: u64,
+        #[snafu(backtrace)]
+        source: dicom_encoding::decode::Error,
+    },
+    /// Could not decode item header at position {position}
+    DecodeItemHeader {
+        position: u64,
+        #[snafu(backtrace)]
+        source: dicom_encoding::decode::Error,
+    },
+    /// Value of {len} bytes at position {position} goes past the end of the data
+    TruncatedValue {
+        position: u64,
+        len: u32,
+        backtrace: Backtrace,
+    },
+    /// Unexpected item header {tag} at position {position}
+    UnexpectedItem {
+        tag: Tag,
+        position: u64,
+        backtrace: Backtrace,
+    },
+    /// Pixel data item at position {position} has an undefined length
+    UndefinedItemLength { position: u64, backtrace: Backtrace },
+    /// Sequence or item ending at position {end} was read up to position {position}
+    InconsistentEnd {
+        end: u64,
+        position: u64,
+        backtrace: Backtrace,
+    },
+    /// Could not create value decoder
+    CreateDecoder {
+        #[snafu(backtrace)]
+        source: dicom_parser::stateful::decode::Error,
+    },
+    /// Could not read value at position {position}
+    ReadValue {
+        position: u64,
+        #[snafu(backtrace)]
+        source: dicom_parser::stateful::decode::Error,
+    },
+    /// Could not decode text of element {tag}
+    DecodeText {
+        tag: Tag,
+        #[snafu(backtrace)]
+        source: DecodeTextError,
+    },
+    /// Element {tag} does not have a primitive value
+    #[snafu(visibility(pub(crate)))]
+    NotPrimitive { tag: Tag, backtrace: Backtrace },
+    /// Element {tag} of value representation {vr} does not hold text
+    NotText {
+        tag: Tag,
+        vr: VR,
+        backtrace: Backtrace,
+    },
+    /// Missing or invalid attribute `{name}`
+    MissingPixelAttribute {
+        name: &'static str,
+        backtrace: Backtrace,
+    },
+    /// Frame #{frame} is out of range ({number_of_frames} frames)
+    FrameOutOfRange {
+        frame: u32,
+        number_of_frames: u32,
+        backtrace: Backtrace,
+    },
+    /// Frames cannot be randomly accessed without an offset table ({number_of_frames} frames in {fragments} fragments)
+    FrameNotAddressable {
+        number_of_frames: u32,
+        fragments: usize,
+        backtrace: Backtrace,
+    },
+    /// Offset table does not match the pixel data fragments
+    InvalidOffsetTable { backtrace: Backtrace },
+    /// Frames of native pixel data do not start at a byte boundary
+    UnalignedFrame { backtrace: Backtrace },
+}
+
+pub type Result<T, E = BorrowedReadError> = std::result::Result<T, E>;
+
+/// The value of a borrowed DICOM element.
+#[derive(Debug, Clone)]
+pub enum BorrowedValue<'a> {
+    /// The bytes of a primitive value, as encoded in the source.
+    Primitive(&'a [u8]),
+    /// The items of a data set sequence.
+    Sequence(Vec<BorrowedDataSet<'a>>),
+    /// The bytes of the basic offset table
+    /// and of each fragment of encapsulated pixel data,
+    /// as encoded in the source.
+    PixelSequence {
+        offset_table: &'a [u8],
+        fragments: Vec<&'a [u8]>,
+    },
+}
+
+/// A data element of a [`BorrowedDataSet`].
+#[derive(Clone)]
+pub struct BorrowedElement<'a> {
+    header: DataElementHeader,
+    value: BorrowedValue<'a>,
+    /// the position of the value in the source
+    offset: u64,
+    /// the character set of the data set holding the element
+    charset: Arc<SpecificCharacterSet>,
+    ts: &'a TransferSyntax,
+}
+
+impl fmt::Debug for BorrowedElement<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowedElement")
+            .field("header", &self.header)
+            .field("value", &self.value)
+            .field("offset", &self.offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HasLength for BorrowedElement<'_> {
+    fn length(&self) -> Length {
+        self.header.len
+    }
+}
+
+impl Header for BorrowedElement<'_> {
+    fn tag(&self) -> Tag {
+        self.header.tag
+    }
+}
+
+impl HasLength for &BorrowedElement<'_> {
+    fn length(&self) -> Length {
+        self.header.len
+    }
+}
+
+impl Header for &BorrowedElement<'_> {
+    fn tag(&self) -> Tag {
+        self.header.tag
+    }
+}
+
+impl<'a> BorrowedElement<'a> {
+    /// Create an element with a primitive value.
+    pub(crate) fn new_primitive(
+        header: DataElementHeader,
+        bytes: &'a [u8],
+        offset: u64,
+        charset: Arc<SpecificCharacterSet>,
+        ts: &'a TransferSyntax,
+    ) -> Self {
+        BorrowedElement {
+            header,
+            value: BorrowedValue::Primitive(bytes),
+            offset,
+            charset,
+            ts,
+        }
+    }
+
+    /// Retrieve the character set of the data set holding the element.
+    pub(crate) fn charset(&self) -> &Arc<SpecificCharacterSet> {
+        &self.charset
+    }
+
+    /// Retrieve the element header,
+    /// as read from the source.
+    pub fn header(&self) -> &DataElementHeader {
+        &self.header
+    }
+
+    /// Retrieve the value representation of the element.
+    pub fn vr(&self) -> VR {
+        self.header.vr
+    }
+
+    /// Retrieve the absolute position of the element's value in the source.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Retrieve the value of the element.
+    pub fn value(&self) -> &BorrowedValue<'a> {
+        &self.value
+    }
+
+    /// Retrieve the bytes of the primitive value of this element,
+    /// as encoded in the source.
+    ///
+    /// Returns `None` if the element is a data set sequence
+    /// or encapsulated pixel data.
+    pub fn bytes(&self) -> Option<&'a [u8]> {
+        match self.value {
+            BorrowedValue::Primitive(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the items of this element,
+    /// if it is a data set sequence.
+    pub fn items(&self) -> Option<&[BorrowedDataSet<'a>]> {
+        match &self.value {
+            BorrowedValue::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the bytes of each pixel data fragment of this element,
+    /// if it is encapsulated pixel data.
+    ///
+    /// The basic offset table is not included.
+    pub fn fragments(&self) -> Option<&[&'a [u8]]> {
+        match &self.value {
+            BorrowedValue::PixelSequence { fragments, .. } => Some(fragments),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the text of this element as a single string,
+    /// with trailing padding removed.
+    ///
+    /// Multiple values remain separated by backslashes.
+    /// The text is borrowed from the source
+    /// if it does not need to be decoded into UTF-8,
+    /// which is the case for text in ASCII
+    /// and for any text in character set ISO IR 192.
+    ///
+    /// Fails if the element's value representation is not textual.
+    pub fn to_str(&self) -> Result<Cow<'a, str>> {
+        let bytes = self.bytes().context(NotPrimitiveSnafu {
+            tag: self.header.tag,
+        })?;
+        ensure!(
+            is_text(self.header.vr),
+            NotTextSnafu {
+                tag: self.header.tag,
+                vr: self.header.vr,
+            }
+        );
+        let text = decode_text(&self.charset, bytes).context(DecodeTextSnafu {
+            tag: self.header.tag,
+        })?;
+        Ok(match text {
+            Cow::Borrowed(text) => Cow::Borrowed(text.trim_end_matches([' ', '\0'])),
+            Cow::Owned(text) => Cow::Owned(text.trim_end_matches([' ', '\0']).to_string()),
+        })
+    }
+
+    /// Decode the primitive value of this element into memory,
+    /// in the same way as when reading an in-memory object.
+    ///
+    /// Fails if the element is a data set sequence
+    /// or encapsulated pixel data.
+    pub fn to_primitive_value(&self) -> Result<PrimitiveValue> {
+        let bytes = self.bytes().context(NotPrimitiveSnafu {
+            tag: self.header.tag,
+        })?;
+        let mut decoder =
+            DynStatefulDecoder::new_with(bytes, self.ts, (*self.charset).clone(), self.offset)
+                .context(CreateDecoderSnafu)?;
+        decoder
+            .read_value_preserved(&self.header)
+            .context(ReadValueSnafu {
+                position: self.offset,
+            })
+    }
+
+    /// Decode the basic offset table of this element,
+    /// if it is encapsulated pixel data.
+    pub fn offset_table(&self) -> Option<Vec<u32>> {
+        match self.value {
+            BorrowedValue::PixelSequence { offset_table, .. } => Some(
+                offset_table
+                    .chunks_exact(4)
+                    .map(|entry| {
+                        let entry = [entry[0], entry[1], entry[2], entry[3]];
+                        match self.ts.endianness() {
+                            Endianness::Little => u32::from_le_bytes(entry),
+                            Endianness::Big => u32::from_be_bytes(entry),
+                        }
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Copy the basic offset table and fragments of this element into memory,
+    /// if it is encapsulated pixel data.
+    pub fn to_fragment_sequence(&self) -> Option<PixelFragmentSequence<InMemFragment>> {
+        let offset_table = self.offset_table()?;
+        let fragments: Vec<InMemFragment> = self
+            .fragments()?
+            .iter()
+            .map(|fragment| fragment.to_vec())
+            .collect();
+        Some(PixelFragmentSequence::new(offset_table, fragments))
+    }
+
+    /// Create an in-memory copy of this element.
+    pub fn to_owned(&self) -> Result<InMemElement> {
+        let tag = self.header.tag;
+        if let Some(fragments) = self.to_fragment_sequence() {
+            return Ok(DataElement::new(
+                tag,
+                self.header.vr,
+                Value::from(fragments),
+            ));
+        }
+        Ok(match &self.value {
+            BorrowedValue::Primitive(_) | BorrowedValue::PixelSequence { .. } => {
+                DataElement::new_with_len(
+                    tag,
+                    self.header.vr,
+                    self.header.len,
+                    self.to_primitive_value()?,
+                )
+            }
+            BorrowedValue::Sequence(items) => DataElement::new(
+                tag,
+                VR::SQ,
+                DataSetSequence::new(
+                    items
+                        .iter()
+                        .map(BorrowedDataSet::to_owned)
+                        .collect::<Result<Vec<_>>>()?,
+                    Length::UNDEFINED,
+                ),
+            ),
+        })
+    }
+}
+
+impl<'s, 'a: 's> DicomElement for &'s BorrowedElement<'a> {
+    type Error = BorrowedReadError;
+    type Item = &'s BorrowedDataSet<'a>;
+    type Items = std::slice::Iter<'s, BorrowedDataSet<'a>>;
+
+    fn vr(&self) -> VR {
+        self.header.vr
+    }
+
+    fn primitive_value(&self) -> Result<Option<Cow<'_, PrimitiveValue>>> {
+        match self.value {
+            BorrowedValue::Primitive(_) => self.to_primitive_value().map(|v| Some(Cow::Owned(v))),
+            _ => Ok(None),
+        }
+    }
+
+    fn items(&self) -> Option<Self::Items> {
+        BorrowedElement::items(self).map(|items| items.iter())
+    }
+
+    fn fragments(&self) -> Result<Option<Cow<'_, PixelFragmentSequence<InMemFragment>>>> {
+        Ok(self.to_fragment_sequence().map(Cow::Owned))
+    }
+
+    fn offset(&self) -> Option<u64> {
+        Some(self.offset)
+    }
+}
+
+/// Whether values of the given representation are text.
+fn is_text(vr: VR) -> bool {
+    matches!(
+        vr,
+        VR::AE
+            | VR::AS
+            | VR::CS
+            | VR::DA
+            | VR::DS
+            | VR::DT
+            | VR::IS
+            | VR::LO
+            | VR::LT
+            | VR::PN
+            | VR::SH
+            | VR::ST
+            | VR::TM
+            | VR::UC
+            | VR::UI
+            | VR::UR
+            | VR::UT
+    )
+}
+
+/// Decode text in the given character set,
+/// borrowing it if it is already valid UTF-8.
+fn decode_text<'a>(
+    charset: &SpecificCharacterSet,
+    bytes: &'a [u8],
+) -> Result<Cow<'a, str>, DecodeTextError> {
+    // all supported character sets are ASCII-compatible,
+    // save for escape sequences switching to other character sets
+    let borrowable = (bytes.is_ascii() && !bytes.contains(&0x1B))
+        || *charset == SpecificCharacterSet::ISO_IR_192;
+    match std::str::from_utf8(bytes) {
+        Ok(text) if borrowable => Ok(Cow::Borrowed(text)),
+        _ => charset.decode(bytes).map(Cow::Owned),
+    }
+}
+
+/// A DICOM data set whose values are borrowed from a byte slice.
+///
+/// This is the data set type of a [`BorrowedDicomObject`],
+/// and of the items of its data set sequences.
+#[derive(Debug, Clone, Default)]
+pub struct BorrowedDataSet<'a> {
+    /// the elements of the data set, sorted by tag
+    entries: Vec<BorrowedElement<'a>>,
+}
+
+impl<'a> BorrowedDataSet<'a> {
+    /// Read a data set from the given bytes,
+    /// encoded in the given transfer syntax.
+    ///
+    /// The data set is expected to span the whole slice,
+    /// without a preamble or file meta group.
+    pub fn from_slice_with_ts(bytes: &'a [u8], ts: &'a TransferSyntax) -> Result<Self> {
+        read_data_set(bytes, ts, 0)
+    }
+
+    /// Retrieve a particular DICOM element by its tag,
+    /// or `None` if it is not present.
+    pub fn get(&self, tag: Tag) -> Option<&BorrowedElement<'a>> {
+        self.entries
+            .binary_search_by_key(&tag, |elem| elem.header.tag)
+            .ok()
+            .map(|i| &self.entries[i])
+    }
+
+    /// Retrieve a particular DICOM element by its tag.
+    pub fn element(&self, tag: Tag) -> Result<&BorrowedElement<'a>, AccessError> {
+        self.get(tag).context(NoSuchDataElementTagSnafu { tag })
+    }
+
+    /// Retrieve a particular DICOM element by its name.
+    pub fn element_by_name(&self, name: &str) -> Result<&BorrowedElement<'a>, AccessByNameError> {
+        let tag = StandardDataDictionary
+            .by_name(name)
+            .context(NoSuchAttributeNameSnafu { name })?
+            .tag();
+        self.element(tag).map_err(|e| e.into_access_by_name(name))
+    }
+
+    /// Obtain an iterator over the elements of this data set.
+    pub fn iter(&self) -> impl Iterator<Item = &BorrowedElement<'a>> + '_ {
+        self.entries.iter()
+    }
+
+    /// Obtain an iterator over the tags of the elements in this data set.
+    pub fn tags(&self) -> impl Iterator<Item = Tag> + '_ {
+        self.entries.iter().map(|elem| elem.header.tag)
+    }
+
+    /// Retrieve the number of elements in this data set.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether this data set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Retrieve the value of an integer attribute in this data set,
+    /// if present and valid.
+    fn int_attribute(&self, tag: Tag) -> Result<Option<u32>> {
+        match self.get(tag) {
+            Some(elem) => Ok(elem.to_primitive_value()?.to_int().ok()),
+            None => Ok(None),
+        }
+    }
+
+    /// Create an in-memory copy of this data set.
+    pub fn to_owned(&self) -> Result<InMemDicomObject> {
+        let elements = self
+            .entries
+            .iter()
+            .map(BorrowedElement::to_owned)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(InMemDicomObject::from_element_iter(elements))
+    }
+}
+
+impl<'s, 'a: 's> DicomObject for &'s BorrowedDataSet<'a> {
+    type Element = &'s BorrowedElement<'a>;
+    type Elements = std::slice::Iter<'s, BorrowedElement<'a>>;
+
+    fn element(&self, tag: Tag) -> Result<Self::Element, AccessError> {
+        BorrowedDataSet::element(self, tag)
+    }
+
+    fn element_by_name(&self, name: &str) -> Result<Self::Element, AccessByNameError> {
+        BorrowedDataSet::element_by_name(self, name)
+    }
+
+    fn elements(&self) -> Self::Elements {
+        self.entries.iter()
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s BorrowedDataSet<'a> {
+    type Item = &'s BorrowedElement<'a>;
+    type IntoIter = std::slice::Iter<'s, BorrowedElement<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl<'a> BorrowedDicomObject<'a> {
+    /// Create an in-memory copy of this DICOM object.
+    pub fn to_owned(&self) -> Result<DefaultDicomObject> {
+        Ok(FileDicomObject {
+            meta: self.meta().clone(),
+            obj: (**self).to_owned()?,
+        })
+    }
+
+    /// Retrieve the encoded bytes of a single frame of the pixel data.
+    ///
+    /// Frames of native pixel data
+    /// and frames made of a single fragment
+    /// are borrowed from the source.
+    /// The fragments of encapsulated pixel data are located
+    /// in the same way as in [`LazyDicomObject::read_frame_data`],
+    /// and concatenated if a frame spans more than one fragment.
+    ///
+    /// [`LazyDicomObject::read_frame_data`]: crate::lazy::LazyDicomObject::read_frame_data
+    pub fn frame_data(&self, frame: u32) -> Result<Cow<'a, [u8]>> {
+        let pixel_data = self
+            .get(Tag(0x7FE0, 0x0010))
+            .context(MissingPixelAttributeSnafu { name: "PixelData" })?;
+        let number_of_frames = self.int_attribute(Tag(0x0028, 0x0008))?.unwrap_or(1);
+
+        match pixel_data.value() {
+            BorrowedValue::PixelSequence { fragments, .. } => {
+                let offset_table: Vec<u64> = match self.get(Tag(0x7FE0, 0x0001)) {
+                    Some(elem) => elem
+                        .to_primitive_value()?
+                        .to_multi_int::<u64>()
+                        .ok()
+                        .context(MissingPixelAttributeSnafu {
+                            name: "ExtendedOffsetTable",
+                        })?,
+                    None => pixel_data
+                        .offset_table()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(u64::from)
+                        .collect(),
+                };
+                let fragment_lens: Vec<u32> = fragments.iter().map(|f| f.len() as u32).collect();
+                let range = crate::lazy::frame_fragment_range(
+                    &offset_table,
+                    &fragment_lens,
+                    frame,
+                    number_of_frames,
+                )
+                .map_err(|e| match e {
+                    crate::lazy::LazyReadError::FrameOutOfRange { .. } => FrameOutOfRangeSnafu {
+                        frame,
+                        number_of_frames,
+                    }
+                    .build(),
+                    crate::lazy::LazyReadError::FrameNotAddressable { fragments, .. } => {
+                        FrameNotAddressableSnafu {
+                            number_of_frames,
+                            fragments,
+                        }
+                        .build()
+                    }
+                    _ => InvalidOffsetTableSnafu.build(),
+                })?;
+                Ok(match &fragments[range] {
+                    [fragment] => Cow::Borrowed(*fragment),
+                    fragments => Cow::Owned(fragments.concat()),
+                })
+            }
+            BorrowedValue::Primitive(bytes) => {
+                let mut frame_bits = 1;
+                for (tag, name) in [
+                    (Tag(0x0028, 0x0010), "Rows"),
+                    (Tag(0x0028, 0x0011), "Columns"),
+                    (Tag(0x0028, 0x0002), "SamplesPerPixel"),
+                    (Tag(0x0028, 0x0100), "BitsAllocated"),
+                ] {
+                    let value = self
+                        .int_attribute(tag)?
+                        .context(MissingPixelAttributeSnafu { name })?;
+                    frame_bits *= u64::from(value);
+                }
+                ensure!(frame_bits % 8 == 0, UnalignedFrameSnafu);
+                ensure!(
+                    frame < number_of_frames,
+                    FrameOutOfRangeSnafu {
+                        frame,
+                        number_of_frames,
+                    }
+                );
+                let frame_size = frame_bits / 8;
+                let len = bytes.len() as u64;
+                let start = u64::from(frame) * frame_size;
+                ensure!(
+                    frame_size > 0 && start + frame_size <= len,
+                    FrameOutOfRangeSnafu {
+                        frame,
+                        number_of_frames: len.checked_div(frame_size).unwrap_or(0) as u32,
+                    }
+                );
+                Ok(Cow::Borrowed(
+                    &bytes[start as usize..(start + frame_size) as usize],
+                ))
+            }
+            BorrowedValue::Sequence(_) => MissingPixelAttributeSnafu { name: "PixelData" }.fail(),
+        }
+    }
+}
+
+/// Read a DICOM object from the bytes of a DICOM file in memory,
+/// borrowing its values from them.
+///
+/// The 128-byte preamble is skipped if present.
+/// The file meta group is copied into the object,
+/// whereas the values of the main data set are not.
+pub fn from_slice(bytes: &[u8]) -> Result<BorrowedDicomObject<'_>> {
+    let (meta, ts, start) = read_meta(bytes)?;
+    let obj = read_data_set(&bytes[start..], ts, start as u64)?;
+    Ok(FileDicomObject { meta, obj })
+}
+
+/// Read the file meta group from the bytes of a DICOM file in memory,
+/// skipping the preamble if present.
+///
+/// Returns the file meta table,
+/// the transfer syntax of the main data set,
+/// and the position where the main data set starts.
+pub(crate) fn read_meta(bytes: &[u8]) -> Result<(FileMetaTable, &'static TransferSyntax, usize)> {
+    let position = match bytes.get(128..132) {
+        Some(b"DICM") => 128,
+        _ => 0,
+    };
+    read_meta_at(bytes, position)
+}
+
+/// Read the file meta group from the bytes of a DICOM file in memory,
+/// starting at the given position.
+///
+/// See [`read_meta`].
+pub(crate) fn read_meta_at(
+    bytes: &[u8],
+    position: usize,
+) -> Result<(FileMetaTable, &'static TransferSyntax, usize)> {
+    let mut data = bytes.get(position..).unwrap_or_default();
+    let meta = FileMetaTable::from_reader(&mut data).context(ParseMetaDataSetSnafu)?;
+    let ts = TransferSyntaxRegistry.get(&meta.transfer_syntax).context(
+        ReadUnsupportedTransferSyntaxSnafu {
+            uid: meta.transfer_syntax.clone(),
+        },
+    )?;
+    let start = bytes.len() - data.len();
+    Ok((meta, ts, start))
+}
+
+/// Read a data set spanning the given bytes,
+/// which are at the given position of the source.
+pub(crate) fn read_data_set<'a>(
+    bytes: &'a [u8],
+    ts: &'a TransferSyntax,
+    base_offset: u64,
+) -> Result<BorrowedDataSet<'a>> {
+    // data sets in need of decompression cannot be borrowed from
+    ensure!(
+        !matches!(ts.codec(), Codec::Dataset(_)),
+        ReadUnsupportedTransferSyntaxSnafu { uid: ts.uid() }
+    );
+    let charset = Arc::new(SpecificCharacterSet::default());
+    match (ts.endianness(), ts.explicit_vr()) {
+        (Endianness::Little, false) => SliceParser::new(
+            bytes,
+            ts,
+            base_offset,
+            ImplicitVRLittleEndianDecoder::default(),
+        )
+        .read_data_set(DataSetEnd::Data, &charset),
+        (Endianness::Little, true) => SliceParser::new(
+            bytes,
+            ts,
+            base_offset,
+            ExplicitVRLittleEndianDecoder::default(),
+        )
+        .read_data_set(DataSetEnd::Data, &charset),
+        (Endianness::Big, true) => SliceParser::new(
+            bytes,
+            ts,
+            base_offset,
+            ExplicitVRBigEndianDecoder::default(),
+        )
+        .read_data_set(DataSetEnd::Data, &charset),
+        _ => ReadUnsupportedTransferSyntaxSnafu { uid: ts.uid() }.fail(),
+    }
+}
+
+/// Where a data set being read ends.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum DataSetEnd {
+    /// at the end of the data
+    Data,
+    /// at the given position in the data
+    Position(usize),
+    /// at an item delimiter
+    Delimiter,
+}
+
+/// A reader of data sets over a byte slice,
+/// keeping track of the current position.
+struct SliceParser<'a, D> {
+    data: &'a [u8],
+    ts: &'a TransferSyntax,
+    /// the position of the data in the source
+    base_offset: u64,
+    /// the position of the parser in the data
+    position: usize,
+    decoder: D,
+}
+
+impl<'a, D> SliceParser<'a, D>
+where
+    D: Decode,
+{
+    fn new(data: &'a [u8], ts: &'a TransferSyntax, base_offset: u64, decoder: D) -> Self {
+        SliceParser {
+            data,
+            ts,
+            base_offset,
+            position: 0,
+            decoder,
+        }
+    }
+
+    /// The absolute position of the parser in the source.
+    fn offset(&self) -> u64 {
+        self.base_offset + self.position as u64
+    }
+
+    /// The position in the data where a value of the given length
+    /// starting at the current position ends.
+    fn end_of(&self, len: u32) -> Result<usize> {
+        self.position
+            .checked_add(len as usize)
+            .filter(|&end| end <= self.data.len())
+            .context(TruncatedValueSnafu {
+                position: self.offset(),
+                len,
+            })
+    }
+
+    /// Take the following bytes of the data.
+    fn take(&mut self, len: u32) -> Result<&'a [u8]> {
+        let end = self.end_of(len)?;
+        let data: &'a [u8] = self.data;
+        let bytes = &data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Check whether the data set or sequence ending at the given position
+    /// was read up to its end.
+    fn at_end(&self, end: usize) -> Result<bool> {
+        ensure!(
+            self.position <= end,
+            InconsistentEndSnafu {
+                end: self.base_offset + end as u64,
+                position: self.offset(),
+            }
+        );
+        Ok(self.position == end)
+    }
+
+    fn read_item_header(&mut self) -> Result<SequenceItemHeader> {
+        let position = self.offset();
+        let (header, len) = self
+            .decoder
+            .decode_item_header_from_slice(&self.data[self.position..])
+            .context(DecodeItemHeaderSnafu { position })?;
+        self.position += len;
+        Ok(header)
+    }
+
+    fn read_data_set(
+        &mut self,
+        end: DataSetEnd,
+        charset: &Arc<SpecificCharacterSet>,
+    ) -> Result<BorrowedDataSet<'a>> {
+        let mut charset = Arc::clone(charset);
+        let mut entries: Vec<BorrowedElement<'a>> = Vec::new();
+        let mut sorted = true;
+        loop {
+            match end {
+                DataSetEnd::Data if self.position == self.data.len() => break,
+                DataSetEnd::Position(end) if self.at_end(end)? => break,
+                _ => {}
+            }
+            let position = self.offset();
+            let (header, len) = self
+                .decoder
+                .decode_header_from_slice(&self.data[self.position..])
+                .context(DecodeHeaderSnafu { position })?;
+            self.position += len;
+
+            let value = match header.tag {
+                Tag(0xFFFE, 0xE00D) if end == DataSetEnd::Delimiter => break,
+                tag @ Tag(0xFFFE, _) => return UnexpectedItemSnafu { tag, position }.fail(),
+                _ if header.is_encapsulated_pixeldata() => self.read_pixel_sequence()?,
+                _ if header.vr == VR::SQ || header.len.is_undefined() => {
+                    // other elements of undefined length
+                    // are read as data set sequences
+                    BorrowedValue::Sequence(self.read_items(header.len, &charset)?)
+                }
+                _ => BorrowedValue::Primitive(self.take(header.len.0)?),
+            };
+            let offset = self.offset() - u64::from(header.len.get().unwrap_or(0));
+            let element = BorrowedElement {
+                header,
+                value,
+                offset,
+                charset: Arc::clone(&charset),
+                ts: self.ts,
+            };
+            if header.tag == Tag(0x0008, 0x0005) {
+                // applies to the rest of the data set
+                if let Some(bytes) = element.bytes() {
+                    let codes = String::from_utf8_lossy(bytes);
+                    charset = Arc::new(SpecificCharacterSet::from_values_with(
+                        codes.split('\\'),
+                        |code| {
+                            tracing::warn!(
+                                "Unsupported character set `{}`, decoding text as ISO_IR 100",
+                                code
+                            );
+                        },
+                    ));
+                }
+            }
+            if let Some(last) = entries.last() {
+                sorted &= last.header.tag < header.tag;
+            }
+            entries.push(element);
+        }
+        if !sorted {
+            // elements out of order or repeated,
+            // keeping the last occurrence of each tag
+            entries.reverse();
+            entries.sort_by_key(|elem| elem.header.tag);
+            entries.dedup_by_key(|elem| elem.header.tag);
+        }
+        Ok(BorrowedDataSet { entries })
+    }
+
+    fn read_items(
+        &mut self,
+        len: Length,
+        charset: &Arc<SpecificCharacterSet>,
+    ) -> Result<Vec<BorrowedDataSet<'a>>> {
+        let end = len.get().map(|len| self.end_of(len)).transpose()?;
+        let mut items = Vec::new();
+        loop {
+            if let Some(end) = end {
+                if self.at_end(end)? {
+                    break;
+                }
+            }
+            let position = self.offset();
+            match self.read_item_header()? {
+                SequenceItemHeader::Item { len } => {
+                    let end = match len.get() {
+                        Some(len) => DataSetEnd::Position(self.end_of(len)?),
+                        None => DataSetEnd::Delimiter,
+                    };
+                    items.push(self.read_data_set(end, charset)?);
+                }
+                SequenceItemHeader::SequenceDelimiter if end.is_none() => break,
+                header => {
+                    return UnexpectedItemSnafu {
+                        tag: header.tag(),
+                        position,
+                    }
+                    .fail()
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    fn read_pixel_sequence(&mut self) -> Result<BorrowedValue<'a>> {
+        let mut items = Vec::new();
+        loop {
+            let position = self.offset();
+            match self.read_item_header()? {
+                SequenceItemHeader::Item { len } => {
+                    let len = len.get().context(UndefinedItemLengthSnafu { position })?;
+                    items.push(self.take(len)?);
+                }
+                SequenceItemHeader::SequenceDelimiter => break,
+                header => {
+                    return UnexpectedItemSnafu {
+                        tag: header.tag(),
+                        position,
+                    }
+                    .fail()
+                }
+            }
+        }
+        let (offset_table, fragments) = match items.split_first() {
+            Some((offset_table, fragments)) => (*offset_table, fragments.to_vec()),
+            None => (&[][..], Vec::new()),
+        };
+        Ok(BorrowedValue::PixelSequence {
+            offset_table,
+            fragments,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_slice, BorrowedDataSet, BorrowedReadError, BorrowedValue};
+    use crate::meta::FileMetaTableBuilder;
+    use crate::InMemDicomObject;
+    use dicom_core::value::PixelFragmentSequence;
+    use dicom_core::{DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_transfer_syntax_registry::entries;
+    use std::borrow::Cow;
+
+    /// Check that the given bytes are part of the given data.
+    fn assert_within(data: &[u8], bytes: &[u8]) {
+        let range = data.as_ptr_range();
+        let bytes = bytes.as_ptr_range();
+        assert!(
+            range.start <= bytes.start && bytes.end <= range.end,
+            "value at {:?} is not within the data at {:?}",
+            bytes,
+            range
+        );
+    }
+
+    fn fixture(ts: &str, pixel_data: DataElement<InMemDicomObject>) -> Vec<u8> {
+        let mut obj = crate::dicom_object! {
+            SpecificCharacterSet: "ISO_IR 100",
+            SOPClassUID: uids::SECONDARY_CAPTURE_IMAGE_STORAGE,
+            SOPInstanceUID: "2.25.172",
+            PatientName: "Doe^John",
+            InstitutionName: "Hôpital",
+            Rows: 2_u16,
+            Columns: 2_u16,
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "2.25.1", ReferencedFrameNumber: "1" },
+                { ReferencedSOPInstanceUID: "2.25.2" },
+            ],
+        };
+        obj.put(pixel_data);
+        let obj = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(ts))
+            .unwrap();
+        let mut bytes = Vec::new();
+        obj.write_all(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn borrow_values_from_slice() {
+        let pixels: Vec<u8> = (0..16).collect();
+        let bytes = fixture(
+            uids::EXPLICIT_VR_LITTLE_ENDIAN,
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PrimitiveValue::from(pixels.clone()),
+            ),
+        );
+
+        let obj = from_slice(&bytes).unwrap();
+        assert_eq!(obj.meta().media_storage_sop_instance_uid(), "2.25.172");
+
+        // text in ASCII is borrowed
+        let patient_name = obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap();
+        assert_eq!(patient_name, "Doe^John");
+        match patient_name {
+            Cow::Borrowed(name) => assert_within(&bytes, name.as_bytes()),
+            Cow::Owned(_) => panic!("patient name should be borrowed"),
+        }
+        // other text is decoded according to the character set
+        let institution = obj
+            .element(tags::INSTITUTION_NAME)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_eq!(institution, "Hôpital");
+        assert!(matches!(institution, Cow::Owned(_)));
+
+        // binary values are borrowed as is
+        let pixel_data = obj.element(tags::PIXEL_DATA).unwrap();
+        let data = pixel_data.bytes().unwrap();
+        assert_eq!(data, &pixels[..]);
+        assert_within(&bytes, data);
+        assert_eq!(
+            &bytes[pixel_data.offset() as usize..][..16],
+            &pixels[..],
+            "offset should point to the value in the source"
+        );
+        assert_eq!(
+            obj.element(tags::ROWS).unwrap().bytes().unwrap(),
+            &[0x02, 0x00]
+        );
+        assert_eq!(
+            obj.element(tags::ROWS)
+                .unwrap()
+                .to_primitive_value()
+                .unwrap(),
+            PrimitiveValue::from(2_u16)
+        );
+
+        // sequence items borrow their values too
+        let items = obj
+            .element(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(items.len(), 2);
+        let uid = items[1]
+            .element(tags::REFERENCED_SOP_INSTANCE_UID)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_eq!(uid, "2.25.2");
+        assert_within(&bytes, uid.as_bytes());
+
+        // not text
+        assert!(matches!(
+            obj.element(tags::ROWS).unwrap().to_str(),
+            Err(BorrowedReadError::NotText { vr: VR::US, .. })
+        ));
+    }
+
+    #[test]
+    fn borrow_pixel_fragments_from_slice() {
+        let bytes = fixture(
+            entries::JPEG_BASELINE.uid(),
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PixelFragmentSequence::new(vec![0], vec![vec![0xFF, 0xD8, 0xFF, 0xD9]]),
+            ),
+        );
+
+        let obj = from_slice(&bytes).unwrap();
+        let pixel_data = obj.element(tags::PIXEL_DATA).unwrap();
+        let fragments = pixel_data.fragments().unwrap();
+        assert_eq!(fragments, [&[0xFF, 0xD8, 0xFF, 0xD9][..]]);
+        assert_within(&bytes, fragments[0]);
+        match pixel_data.value() {
+            BorrowedValue::PixelSequence { offset_table, .. } => {
+                assert_eq!(*offset_table, [0, 0, 0, 0]);
+                assert_within(&bytes, offset_table);
+            }
+            value => panic!("unexpected value {:?}", value),
+        }
+    }
+
+    #[test]
+    fn upgrade_to_owned() {
+        for (ts, pixel_data) in [
+            (
+                uids::EXPLICIT_VR_LITTLE_ENDIAN,
+                DataElement::new(
+                    tags::PIXEL_DATA,
+                    VR::OW,
+                    PrimitiveValue::U16([1, 2, 3, 4].as_ref().into()),
+                ),
+            ),
+            (
+                uids::IMPLICIT_VR_LITTLE_ENDIAN,
+                DataElement::new(
+                    tags::PIXEL_DATA,
+                    VR::OW,
+                    PrimitiveValue::U16([1, 2, 3, 4].as_ref().into()),
+                ),
+            ),
+            (
+                entries::EXPLICIT_VR_BIG_ENDIAN.uid(),
+                DataElement::new(
+                    tags::PIXEL_DATA,
+                    VR::OW,
+                    PrimitiveValue::U16([1, 2, 3, 4].as_ref().into()),
+                ),
+            ),
+            (
+                entries::JPEG_BASELINE.uid(),
+                DataElement::new(
+                    tags::PIXEL_DATA,
+                    VR::OB,
+                    PixelFragmentSequence::new(vec![0], vec![vec![0xFF, 0xD8, 0xFF, 0xD9]]),
+                ),
+            ),
+        ] {
+            let bytes = fixture(ts, pixel_data);
+            let (meta, expected) = crate::from_slice(&bytes).unwrap();
+
+            let obj = from_slice(&bytes).unwrap();
+            let owned = obj.to_owned().unwrap();
+            assert_eq!(owned.meta(), &meta, "{}", ts);
+            // sequences of undefined length never compare equal,
+            // so compare their debug representation instead
+            assert_eq!(format!("{:?}", *owned), format!("{:?}", expected), "{}", ts);
+            // and writing the owned object back yields the same data
+            let mut written = Vec::new();
+            owned.write_all(&mut written).unwrap();
+            assert_eq!(written, bytes, "{}", ts);
+        }
+    }
+
+    #[test]
+    fn read_elements_out_of_order() {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            // (0010,0010) PN "Doe"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x04, 0x00, b'D', b'o', b'e', b' ',
+            // (0008,0060) CS "MR"
+            0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R',
+            // (0008,0060) CS "CT", repeated
+            0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'C', b'T',
+        ];
+        let ts = entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        let obj = BorrowedDataSet::from_slice_with_ts(bytes, &ts).unwrap();
+        assert_eq!(
+            obj.tags().collect::<Vec<_>>(),
+            [tags::MODALITY, tags::PATIENT_NAME]
+        );
+        // the last occurrence is kept
+        assert_eq!(obj.element(tags::MODALITY).unwrap().to_str().unwrap(), "CT");
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Doe"
+        );
+    }
+
+    #[test]
+    fn read_data_set_without_meta() {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            // (0008,0060) CS "MR"
+            0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R',
+            // (0008,1140) SQ, undefined length, one item of undefined length
+            0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            0x08, 0x00, 0x55, 0x11, b'U', b'I', 0x04, 0x00, b'1', b'.', b'2', 0x00,
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // (0010,0010) PN "Doe"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x04, 0x00, b'D', b'o', b'e', b' ',
+        ];
+        let ts = entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        let obj = BorrowedDataSet::from_slice_with_ts(bytes, &ts).unwrap();
+        assert_eq!(obj.len(), 3);
+        assert_eq!(obj.element(tags::MODALITY).unwrap().to_str().unwrap(), "MR");
+        assert_eq!(obj.element(tags::PATIENT_NAME).unwrap().offset(), 66);
+        let items = obj
+            .element(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(
+            items[0]
+                .element(tags::REFERENCED_SOP_INSTANCE_UID)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "1.2"
+        );
+
+        // the value of the last element is cut short
+        let err = BorrowedDataSet::from_slice_with_ts(&bytes[..bytes.len() - 1], &ts).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                BorrowedReadError::TruncatedValue {
+                    position: 66,
+                    len: 4,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+
+        // an item delimiter out of place
+        let err = BorrowedDataSet::from_slice_with_ts(&bytes[42..], &ts).unwrap_err();
+        assert!(
+            matches!(err, BorrowedReadError::UnexpectedItem { position: 0, .. }),
+            "{:?}",
+            err
+        );
+    }
+}
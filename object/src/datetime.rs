@@ -0,0 +1,122 @@
+//! Date-times combined from separate date and time attributes.
+//!
+//! Many information object definitions split a point in time
+//! into a date (DA) attribute and a time (TM) attribute,
+//! such as _Study Date_ and _Study Time_.
+//! See [`InMemDicomObject::datetime_of`](crate::InMemDicomObject::datetime_of).
+use crate::{AttributeError, CombineDateTimeSnafu, DicomObject};
+use dicom_core::value::range::Error as RangeError;
+use dicom_core::value::{AsRange, DicomDateTime, PreciseDateTime};
+use dicom_core::Tag;
+use snafu::ResultExt;
+use std::fmt;
+
+/// One of the two attributes combined into a date-time.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum DateTimePart {
+    /// The date (DA) attribute
+    Date,
+    /// The time (TM) attribute
+    Time,
+}
+
+impl fmt::Display for DateTimePart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateTimePart::Date => f.write_str("date"),
+            DateTimePart::Time => f.write_str("time"),
+        }
+    }
+}
+
+/// A date-time combined from a date attribute and a time attribute.
+///
+/// The time keeps the precision of the time attribute,
+/// and is missing if the object has no such attribute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CombinedDateTime {
+    datetime: DicomDateTime,
+}
+
+impl CombinedDateTime {
+    /// Retrieve the combined date-time value.
+    pub fn datetime(&self) -> &DicomDateTime {
+        &self.datetime
+    }
+
+    /// Move out the combined date-time value.
+    pub fn into_datetime(self) -> DicomDateTime {
+        self.datetime
+    }
+
+    /// Whether the object only had the date,
+    /// so that midnight is assumed
+    /// when converting to a [precise date-time](Self::to_precise_datetime).
+    pub fn is_date_only(&self) -> bool {
+        self.datetime.time().is_none()
+    }
+
+    /// Convert to a precise date-time.
+    ///
+    /// If the time is missing, midnight is assumed
+    /// (see [`is_date_only`](Self::is_date_only)).
+    /// Otherwise, the time must be precise up to the second,
+    /// and a missing second fraction is taken as zero.
+    pub fn to_precise_datetime(&self) -> Result<PreciseDateTime, RangeError> {
+        match self.datetime.time() {
+            None => self.datetime.earliest(),
+            Some(time) if time.second().is_some() => self.datetime.earliest(),
+            Some(_) => self.datetime.exact(),
+        }
+    }
+}
+
+impl fmt::Display for CombinedDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.datetime.fmt(f)
+    }
+}
+
+pub(crate) fn datetime_of<O>(
+    obj: &O,
+    date_tag: Tag,
+    time_tag: Tag,
+) -> Result<CombinedDateTime, AttributeError>
+where
+    O: ?Sized + DicomObject,
+{
+    let date = obj
+        .date(date_tag)
+        .map_err(Box::from)
+        .context(CombineDateTimeSnafu {
+            date_tag,
+            time_tag,
+            part: DateTimePart::Date,
+        })?;
+    let time = match obj.time(time_tag) {
+        Ok(time) => Some(time),
+        Err(AttributeError::MissingAttribute { .. } | AttributeError::EmptyValue { .. }) => None,
+        Err(e) => {
+            return Err(Box::from(e)).context(CombineDateTimeSnafu {
+                date_tag,
+                time_tag,
+                part: DateTimePart::Time,
+            })
+        }
+    };
+    let datetime = match time {
+        Some(time) => DicomDateTime::from_date_and_time(date, time)
+            .map_err(Box::from)
+            .context(CombineDateTimeSnafu {
+                date_tag,
+                time_tag,
+                part: DateTimePart::Date,
+            })?,
+        None => DicomDateTime::from_date(date),
+    };
+    let datetime = match obj.timezone_offset()? {
+        Some(offset) => datetime.with_default_time_zone(offset),
+        None => datetime,
+    };
+    Ok(CombinedDateTime { datetime })
+}
@@ -0,0 +1,1265 @@
+//! De-identification of DICOM objects.
+//!
+//! This module follows the Basic Application Level Confidentiality Profile
+//! of [PS3.15 Annex E][1] through a set of tables,
+//! designating an [`Action`] for the most common attributes
+//! which may identify the patient or other people and institutions
+//! ([`IDENTIFYING_ATTRIBUTES`]),
+//! and for the attributes known not to identify anyone
+//! ([`RETAINED_ATTRIBUTES`]).
+//! The tables do not cover every attribute of Table E.1-1 of the profile,
+//! so attributes absent from them are only kept
+//! if their value representation cannot hold
+//! names, free text, dates, times or UIDs
+//! (see [`Deidentifier::action`]),
+//! and are removed otherwise,
+//! which errs on the side of removing too much.
+//! A [`Deidentifier`] applies the action of each attribute in place,
+//! including in nested sequence items,
+//! removes private attributes,
+//! and records that the patient identity was removed.
+//!
+//! Dates and times ([`TEMPORAL_ATTRIBUTES`])
+//! and UIDs ([`UID_ATTRIBUTES`])
+//! can be retained through the [`DeidentOptions`],
+//! as per the _Retain Longitudinal Temporal Information with Full Dates Option_
+//! and the _Retain UIDs Option_ of the profile.
+//! Replaced UIDs are mapped consistently
+//! across all objects de-identified by the same [`Deidentifier`],
+//! so that the references between the objects of a study are preserved.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_object::deident::{DeidentOptions, Deidentifier};
+//! use dicom_object::open_file;
+//!
+//! let mut deidentifier = Deidentifier::new(DeidentOptions::new().retain_dates(true));
+//! for path in ["1.dcm", "2.dcm"] {
+//!     let mut obj = open_file(path)?;
+//!     deidentifier.deidentify_file(&mut obj);
+//!     obj.write_to_file(format!("anon_{}", path))?;
+//! }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [1]: https://dicom.nema.org/medical/dicom/current/output/chtml/part15/chapter_E.html
+use std::collections::HashMap;
+
+use dicom_core::dictionary::DataDictionaryEntry;
+use dicom_core::header::Header;
+use dicom_core::value::{DataSetSequence, PrimitiveValue};
+use dicom_core::{DataDictionary, DataElement, Tag, VR};
+use dicom_dictionary_std::{tags, StandardDataDictionary};
+
+use crate::mem::{InMemDicomObject, InMemElement};
+use crate::uid::{uuid_uid, UidGenerator};
+use crate::FileDicomObject;
+
+/// The value written to person names, identifiers, and other texts
+/// replaced with a dummy value.
+pub const DUMMY_TEXT: &str = "ANONYMIZED";
+
+/// An action applied to an attribute during de-identification.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Action {
+    /// `X`: remove the attribute.
+    Remove,
+    /// `Z`: replace the value with a zero-length value.
+    Empty,
+    /// `D`: replace the value with a dummy value of the same VR.
+    Dummy,
+    /// `K`: keep the attribute as is.
+    Keep,
+    /// `U`: replace each UID with another one,
+    /// consistently within the de-identifier.
+    ReplaceUid,
+}
+
+/// Attributes identifying the patient or other people and institutions,
+/// and their action in the Basic Profile.
+///
+/// Attributes of type 1 or 2 in common IODs are given a dummy
+/// or zero-length value so that the objects remain valid,
+/// while the others are removed.
+/// Attributes of the profile missing from this table
+/// are removed as well if they may hold
+/// names, free text, dates, times or UIDs,
+/// like any other attribute absent from [`RETAINED_ATTRIBUTES`].
+// includes retired attributes, still found in older objects
+#[allow(deprecated)]
+pub const IDENTIFYING_ATTRIBUTES: &[(Tag, Action)] = &[
+    (tags::ACCESSION_NUMBER, Action::Empty),
+    (tags::INSTITUTION_NAME, Action::Remove),
+    (tags::INSTITUTION_ADDRESS, Action::Remove),
+    (tags::INSTITUTION_CODE_SEQUENCE, Action::Remove),
+    (tags::REFERRING_PHYSICIAN_NAME, Action::Empty),
+    (tags::REFERRING_PHYSICIAN_ADDRESS, Action::Remove),
+    (tags::REFERRING_PHYSICIAN_TELEPHONE_NUMBERS, Action::Remove),
+    (
+        tags::REFERRING_PHYSICIAN_IDENTIFICATION_SEQUENCE,
+        Action::Remove,
+    ),
+    (tags::CONSULTING_PHYSICIAN_NAME, Action::Remove),
+    (tags::STATION_NAME, Action::Remove),
+    (tags::STUDY_DESCRIPTION, Action::Remove),
+    (tags::SERIES_DESCRIPTION, Action::Remove),
+    (tags::INSTITUTIONAL_DEPARTMENT_NAME, Action::Remove),
+    (tags::PHYSICIANS_OF_RECORD, Action::Remove),
+    (
+        tags::PHYSICIANS_OF_RECORD_IDENTIFICATION_SEQUENCE,
+        Action::Remove,
+    ),
+    (tags::PERFORMING_PHYSICIAN_NAME, Action::Remove),
+    (
+        tags::PERFORMING_PHYSICIAN_IDENTIFICATION_SEQUENCE,
+        Action::Remove,
+    ),
+    (tags::NAME_OF_PHYSICIANS_READING_STUDY, Action::Remove),
+    (
+        tags::PHYSICIANS_READING_STUDY_IDENTIFICATION_SEQUENCE,
+        Action::Remove,
+    ),
+    (tags::OPERATORS_NAME, Action::Remove),
+    (tags::OPERATOR_IDENTIFICATION_SEQUENCE, Action::Remove),
+    (tags::ADMITTING_DIAGNOSES_DESCRIPTION, Action::Remove),
+    (tags::REFERENCED_PATIENT_SEQUENCE, Action::Remove),
+    (tags::DERIVATION_DESCRIPTION, Action::Remove),
+    (tags::PATIENT_NAME, Action::Dummy),
+    (tags::PATIENT_ID, Action::Dummy),
+    (tags::ISSUER_OF_PATIENT_ID, Action::Remove),
+    (
+        tags::ISSUER_OF_PATIENT_ID_QUALIFIERS_SEQUENCE,
+        Action::Remove,
+    ),
+    (tags::PATIENT_INSURANCE_PLAN_CODE_SEQUENCE, Action::Remove),
+    (tags::PATIENT_PRIMARY_LANGUAGE_CODE_SEQUENCE, Action::Remove),
+    (tags::PATIENT_BIRTH_DATE, Action::Empty),
+    (tags::PATIENT_BIRTH_TIME, Action::Remove),
+    (tags::PATIENT_SEX, Action::Empty),
+    (tags::OTHER_PATIENT_I_DS, Action::Remove),
+    (tags::OTHER_PATIENT_I_DS_SEQUENCE, Action::Remove),
+    (tags::OTHER_PATIENT_NAMES, Action::Remove),
+    (tags::PATIENT_BIRTH_NAME, Action::Remove),
+    (tags::PATIENT_AGE, Action::Remove),
+    (tags::PATIENT_SIZE, Action::Remove),
+    (tags::PATIENT_WEIGHT, Action::Remove),
+    (tags::PATIENT_ADDRESS, Action::Remove),
+    (tags::INSURANCE_PLAN_IDENTIFICATION, Action::Remove),
+    (tags::PATIENT_MOTHER_BIRTH_NAME, Action::Remove),
+    (tags::MILITARY_RANK, Action::Remove),
+    (tags::BRANCH_OF_SERVICE, Action::Remove),
+    (tags::MEDICAL_RECORD_LOCATOR, Action::Remove),
+    (tags::MEDICAL_ALERTS, Action::Remove),
+    (tags::ALLERGIES, Action::Remove),
+    (tags::COUNTRY_OF_RESIDENCE, Action::Remove),
+    (tags::REGION_OF_RESIDENCE, Action::Remove),
+    (tags::PATIENT_TELEPHONE_NUMBERS, Action::Remove),
+    (tags::ETHNIC_GROUP, Action::Remove),
+    (tags::OCCUPATION, Action::Remove),
+    (tags::SMOKING_STATUS, Action::Remove),
+    (tags::ADDITIONAL_PATIENT_HISTORY, Action::Remove),
+    (tags::PREGNANCY_STATUS, Action::Remove),
+    (tags::LAST_MENSTRUAL_DATE, Action::Remove),
+    (tags::PATIENT_RELIGIOUS_PREFERENCE, Action::Remove),
+    (tags::PATIENT_SEX_NEUTERED, Action::Remove),
+    (tags::RESPONSIBLE_PERSON, Action::Remove),
+    (tags::RESPONSIBLE_ORGANIZATION, Action::Remove),
+    (tags::PATIENT_COMMENTS, Action::Remove),
+    (tags::DEVICE_SERIAL_NUMBER, Action::Remove),
+    (tags::PLATE_ID, Action::Remove),
+    (tags::PROTOCOL_NAME, Action::Remove),
+    (tags::GANTRY_ID, Action::Remove),
+    (
+        tags::ACQUISITION_DEVICE_PROCESSING_DESCRIPTION,
+        Action::Remove,
+    ),
+    (tags::DETECTOR_ID, Action::Remove),
+    (tags::CASSETTE_ID, Action::Remove),
+    (tags::ACQUISITION_COMMENTS, Action::Remove),
+    (tags::GENERATOR_ID, Action::Remove),
+    (tags::STUDY_ID, Action::Empty),
+    (tags::IMAGE_COMMENTS, Action::Remove),
+    (tags::FRAME_COMMENTS, Action::Remove),
+    (tags::REQUESTING_PHYSICIAN, Action::Remove),
+    (tags::REQUESTING_SERVICE, Action::Remove),
+    (tags::REQUESTED_PROCEDURE_DESCRIPTION, Action::Remove),
+    (tags::STUDY_COMMENTS, Action::Remove),
+    (tags::ADMISSION_ID, Action::Remove),
+    (tags::SPECIAL_NEEDS, Action::Remove),
+    (tags::SERVICE_EPISODE_ID, Action::Remove),
+    (tags::CURRENT_PATIENT_LOCATION, Action::Remove),
+    (tags::PATIENT_INSTITUTION_RESIDENCE, Action::Remove),
+    (tags::PATIENT_STATE, Action::Remove),
+    (tags::VISIT_COMMENTS, Action::Remove),
+    (tags::SCHEDULED_STATION_AE_TITLE, Action::Remove),
+    (tags::SCHEDULED_PERFORMING_PHYSICIAN_NAME, Action::Remove),
+    (tags::SCHEDULED_PROCEDURE_STEP_DESCRIPTION, Action::Remove),
+    (tags::SCHEDULED_PROCEDURE_STEP_ID, Action::Remove),
+    (tags::SCHEDULED_STATION_NAME, Action::Remove),
+    (tags::SCHEDULED_PROCEDURE_STEP_LOCATION, Action::Remove),
+    (tags::PERFORMED_STATION_AE_TITLE, Action::Remove),
+    (tags::PERFORMED_STATION_NAME, Action::Remove),
+    (tags::PERFORMED_LOCATION, Action::Remove),
+    (tags::PERFORMED_STATION_NAME_CODE_SEQUENCE, Action::Remove),
+    (tags::PERFORMED_PROCEDURE_STEP_ID, Action::Remove),
+    (tags::PERFORMED_PROCEDURE_STEP_DESCRIPTION, Action::Remove),
+    (tags::REQUEST_ATTRIBUTES_SEQUENCE, Action::Remove),
+    (tags::REQUESTED_PROCEDURE_ID, Action::Remove),
+    (tags::REASON_FOR_THE_REQUESTED_PROCEDURE, Action::Remove),
+    (tags::REQUESTED_PROCEDURE_LOCATION, Action::Remove),
+    (tags::REQUESTED_PROCEDURE_COMMENTS, Action::Remove),
+    (
+        tags::PLACER_ORDER_NUMBER_IMAGING_SERVICE_REQUEST,
+        Action::Remove,
+    ),
+    (
+        tags::FILLER_ORDER_NUMBER_IMAGING_SERVICE_REQUEST,
+        Action::Remove,
+    ),
+    (tags::ORDER_ENTERED_BY, Action::Remove),
+    (tags::ORDER_ENTERER_LOCATION, Action::Remove),
+    (tags::ORDER_CALLBACK_PHONE_NUMBER, Action::Remove),
+    (tags::IMAGING_SERVICE_REQUEST_COMMENTS, Action::Remove),
+    (tags::REFERENCED_PATIENT_ALIAS_SEQUENCE, Action::Remove),
+    (tags::ACQUISITION_CONTEXT_SEQUENCE, Action::Remove),
+    (tags::CONTENT_CREATOR_NAME, Action::Empty),
+    (tags::PERSON_NAME, Action::Dummy),
+    (tags::PERSON_ADDRESS, Action::Remove),
+    (tags::PERSON_TELEPHONE_NUMBERS, Action::Remove),
+    (tags::VERIFYING_ORGANIZATION, Action::Remove),
+    (tags::VERIFYING_OBSERVER_NAME, Action::Dummy),
+    (tags::TEXT_VALUE, Action::Remove),
+    (tags::CONTENT_SEQUENCE, Action::Remove),
+    (tags::MODIFIED_ATTRIBUTES_SEQUENCE, Action::Remove),
+    (tags::ORIGINAL_ATTRIBUTES_SEQUENCE, Action::Remove),
+    (tags::DIGITAL_SIGNATURES_SEQUENCE, Action::Remove),
+];
+
+/// Attributes describing the image and its acquisition
+/// without identifying anyone,
+/// which are kept as is.
+///
+/// Sequences in this table are kept with the items they contain,
+/// whose attributes are de-identified in turn.
+/// Other sequences are removed.
+pub const RETAINED_ATTRIBUTES: &[Tag] = &[
+    tags::SPECIFIC_CHARACTER_SET,
+    tags::IMAGE_TYPE,
+    tags::SOP_CLASS_UID,
+    tags::MODALITY,
+    tags::CONVERSION_TYPE,
+    tags::MANUFACTURER,
+    tags::MANUFACTURER_MODEL_NAME,
+    tags::REFERENCED_SERIES_SEQUENCE,
+    tags::REFERENCED_IMAGE_SEQUENCE,
+    tags::REFERENCED_SOP_CLASS_UID,
+    tags::REFERENCED_FRAME_NUMBER,
+    tags::SOURCE_IMAGE_SEQUENCE,
+    tags::DERIVATION_CODE_SEQUENCE,
+    tags::ANATOMIC_REGION_SEQUENCE,
+    tags::CODE_VALUE,
+    tags::CODING_SCHEME_DESIGNATOR,
+    tags::CODING_SCHEME_VERSION,
+    tags::CODE_MEANING,
+    tags::PATIENT_IDENTITY_REMOVED,
+    tags::DEIDENTIFICATION_METHOD,
+    tags::DEIDENTIFICATION_METHOD_CODE_SEQUENCE,
+    tags::LONGITUDINAL_TEMPORAL_INFORMATION_MODIFIED,
+    tags::BODY_PART_EXAMINED,
+    tags::SCANNING_SEQUENCE,
+    tags::SEQUENCE_VARIANT,
+    tags::SCAN_OPTIONS,
+    tags::MR_ACQUISITION_TYPE,
+    tags::SLICE_THICKNESS,
+    tags::KVP,
+    tags::REPETITION_TIME,
+    tags::ECHO_TIME,
+    tags::INVERSION_TIME,
+    tags::NUMBER_OF_AVERAGES,
+    tags::IMAGING_FREQUENCY,
+    tags::MAGNETIC_FIELD_STRENGTH,
+    tags::SPACING_BETWEEN_SLICES,
+    tags::ECHO_TRAIN_LENGTH,
+    tags::DATA_COLLECTION_DIAMETER,
+    tags::RECONSTRUCTION_DIAMETER,
+    tags::DISTANCE_SOURCE_TO_DETECTOR,
+    tags::DISTANCE_SOURCE_TO_PATIENT,
+    tags::GANTRY_DETECTOR_TILT,
+    tags::TABLE_HEIGHT,
+    tags::ROTATION_DIRECTION,
+    tags::EXPOSURE_TIME,
+    tags::X_RAY_TUBE_CURRENT,
+    tags::EXPOSURE,
+    tags::FILTER_TYPE,
+    tags::FOCAL_SPOTS,
+    tags::CONVOLUTION_KERNEL,
+    tags::FLIP_ANGLE,
+    tags::PATIENT_POSITION,
+    tags::VIEW_POSITION,
+    tags::SERIES_NUMBER,
+    tags::ACQUISITION_NUMBER,
+    tags::INSTANCE_NUMBER,
+    tags::PATIENT_ORIENTATION,
+    tags::IMAGE_POSITION_PATIENT,
+    tags::IMAGE_ORIENTATION_PATIENT,
+    tags::LATERALITY,
+    tags::SLICE_LOCATION,
+    tags::IMAGE_LATERALITY,
+    tags::SAMPLES_PER_PIXEL,
+    tags::PHOTOMETRIC_INTERPRETATION,
+    tags::PLANAR_CONFIGURATION,
+    tags::NUMBER_OF_FRAMES,
+    tags::FRAME_INCREMENT_POINTER,
+    tags::ROWS,
+    tags::COLUMNS,
+    tags::PIXEL_SPACING,
+    tags::IMAGER_PIXEL_SPACING,
+    tags::PIXEL_ASPECT_RATIO,
+    tags::BITS_ALLOCATED,
+    tags::BITS_STORED,
+    tags::HIGH_BIT,
+    tags::PIXEL_REPRESENTATION,
+    tags::SMALLEST_IMAGE_PIXEL_VALUE,
+    tags::LARGEST_IMAGE_PIXEL_VALUE,
+    tags::PIXEL_PADDING_VALUE,
+    tags::BURNED_IN_ANNOTATION,
+    tags::WINDOW_CENTER,
+    tags::WINDOW_WIDTH,
+    tags::RESCALE_INTERCEPT,
+    tags::RESCALE_SLOPE,
+    tags::RESCALE_TYPE,
+    tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+    tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+    tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+    tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+    tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+    tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+    tags::LOSSY_IMAGE_COMPRESSION,
+    tags::MODALITY_LUT_SEQUENCE,
+    tags::LUT_DESCRIPTOR,
+    tags::MODALITY_LUT_TYPE,
+    tags::LUT_DATA,
+    tags::VOILUT_SEQUENCE,
+    tags::LOSSY_IMAGE_COMPRESSION_RATIO,
+    tags::LOSSY_IMAGE_COMPRESSION_METHOD,
+    tags::PRESENTATION_LUT_SHAPE,
+    tags::FLOAT_PIXEL_DATA,
+    tags::DOUBLE_FLOAT_PIXEL_DATA,
+    tags::PIXEL_DATA,
+    tags::ACQUISITION_MATRIX,
+    tags::POSITION_REFERENCE_INDICATOR,
+    tags::SEQUENCE_OF_ULTRASOUND_REGIONS,
+    tags::PRESENTATION_INTENT_TYPE,
+    tags::VOILUT_FUNCTION,
+    tags::WINDOW_CENTER_WIDTH_EXPLANATION,
+    // multi-frame dimensions and functional groups
+    tags::DIMENSION_ORGANIZATION_SEQUENCE,
+    tags::DIMENSION_INDEX_SEQUENCE,
+    tags::DIMENSION_INDEX_POINTER,
+    tags::FUNCTIONAL_GROUP_POINTER,
+    tags::DIMENSION_DESCRIPTION_LABEL,
+    tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+    tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+    tags::PIXEL_MEASURES_SEQUENCE,
+    tags::PLANE_POSITION_SEQUENCE,
+    tags::PLANE_ORIENTATION_SEQUENCE,
+    tags::PLANE_POSITION_VOLUME_SEQUENCE,
+    tags::PLANE_ORIENTATION_VOLUME_SEQUENCE,
+    tags::FRAME_CONTENT_SEQUENCE,
+    tags::STACK_ID,
+    tags::TEMPORAL_POSITION_SEQUENCE,
+    tags::DERIVATION_IMAGE_SEQUENCE,
+    tags::FRAME_ANATOMY_SEQUENCE,
+    tags::FRAME_VOILUT_SEQUENCE,
+    tags::PIXEL_VALUE_TRANSFORMATION_SEQUENCE,
+    tags::PIXEL_INTENSITY_RELATIONSHIP_LUT_SEQUENCE,
+    tags::REAL_WORLD_VALUE_MAPPING_SEQUENCE,
+    tags::MEASUREMENT_UNITS_CODE_SEQUENCE,
+    tags::FRAME_PIXEL_SHIFT_SEQUENCE,
+    tags::CARDIAC_SYNCHRONIZATION_SEQUENCE,
+    tags::RESPIRATORY_SYNCHRONIZATION_SEQUENCE,
+    tags::IMAGE_DATA_TYPE_SEQUENCE,
+    tags::PURPOSE_OF_REFERENCE_CODE_SEQUENCE,
+    tags::CT_IMAGE_FRAME_TYPE_SEQUENCE,
+    tags::CT_ACQUISITION_TYPE_SEQUENCE,
+    tags::CT_ACQUISITION_DETAILS_SEQUENCE,
+    tags::CT_TABLE_DYNAMICS_SEQUENCE,
+    tags::CT_POSITION_SEQUENCE,
+    tags::CT_GEOMETRY_SEQUENCE,
+    tags::CT_RECONSTRUCTION_SEQUENCE,
+    tags::CT_EXPOSURE_SEQUENCE,
+    tags::CTX_RAY_DETAILS_SEQUENCE,
+    tags::MR_IMAGE_FRAME_TYPE_SEQUENCE,
+    tags::MR_TIMING_AND_RELATED_PARAMETERS_SEQUENCE,
+    tags::MRFOV_GEOMETRY_SEQUENCE,
+    tags::MR_ECHO_SEQUENCE,
+    tags::MR_MODIFIER_SEQUENCE,
+    tags::MR_IMAGING_MODIFIER_SEQUENCE,
+    tags::MR_RECEIVE_COIL_SEQUENCE,
+    tags::MR_TRANSMIT_COIL_SEQUENCE,
+    tags::MR_DIFFUSION_SEQUENCE,
+    tags::MR_AVERAGES_SEQUENCE,
+];
+
+/// Value representations of the attributes absent from the tables
+/// which are removed,
+/// as they may hold names, free text, dates, times or UIDs.
+///
+/// Attributes absent from the tables with any other value representation,
+/// such as numbers, code strings and binary data, are kept,
+/// except for sequences.
+pub const REMOVED_VRS: &[VR] = &[
+    VR::AE,
+    VR::AS,
+    VR::DA,
+    VR::DT,
+    VR::LO,
+    VR::LT,
+    VR::PN,
+    VR::SH,
+    VR::ST,
+    VR::TM,
+    VR::UC,
+    VR::UI,
+    VR::UN,
+    VR::UR,
+    VR::UT,
+];
+
+/// Attributes holding UIDs which may be traced back to the patient,
+/// replaced unless [UIDs are retained](DeidentOptions::retain_uids).
+// includes retired attributes, still found in older objects
+#[allow(deprecated)]
+pub const UID_ATTRIBUTES: &[Tag] = &[
+    tags::INSTANCE_CREATOR_UID,
+    tags::SOP_INSTANCE_UID,
+    tags::REFERENCED_SOP_INSTANCE_UID,
+    tags::STUDY_INSTANCE_UID,
+    tags::SERIES_INSTANCE_UID,
+    tags::FRAME_OF_REFERENCE_UID,
+    tags::SYNCHRONIZATION_FRAME_OF_REFERENCE_UID,
+    tags::REFERENCED_FRAME_OF_REFERENCE_UID,
+    tags::RELATED_FRAME_OF_REFERENCE_UID,
+    tags::CONCATENATION_UID,
+    tags::DIMENSION_ORGANIZATION_UID,
+    tags::IRRADIATION_EVENT_UID,
+    tags::STORAGE_MEDIA_FILE_SET_UID,
+    tags::TRANSACTION_UID,
+    tags::FIDUCIAL_UID,
+    tags::UID,
+];
+
+/// Dates and times of events,
+/// and their action in the Basic Profile
+/// unless [dates are retained](DeidentOptions::retain_dates).
+pub const TEMPORAL_ATTRIBUTES: &[(Tag, Action)] = &[
+    (tags::INSTANCE_CREATION_DATE, Action::Remove),
+    (tags::INSTANCE_CREATION_TIME, Action::Remove),
+    (tags::STUDY_DATE, Action::Empty),
+    (tags::SERIES_DATE, Action::Remove),
+    (tags::ACQUISITION_DATE, Action::Empty),
+    (tags::CONTENT_DATE, Action::Empty),
+    (tags::ACQUISITION_DATE_TIME, Action::Empty),
+    (tags::STUDY_TIME, Action::Empty),
+    (tags::SERIES_TIME, Action::Remove),
+    (tags::ACQUISITION_TIME, Action::Empty),
+    (tags::CONTENT_TIME, Action::Empty),
+    (tags::PERFORMED_PROCEDURE_STEP_START_DATE, Action::Remove),
+    (tags::PERFORMED_PROCEDURE_STEP_START_TIME, Action::Remove),
+    (tags::CONTRAST_BOLUS_START_TIME, Action::Remove),
+    (tags::CONTRAST_BOLUS_STOP_TIME, Action::Remove),
+    (tags::RADIOPHARMACEUTICAL_START_TIME, Action::Remove),
+    (tags::RADIOPHARMACEUTICAL_STOP_TIME, Action::Remove),
+    (tags::ADMITTING_DATE, Action::Remove),
+    (tags::ADMITTING_TIME, Action::Remove),
+    (tags::SCHEDULED_PROCEDURE_STEP_START_DATE, Action::Remove),
+    (tags::SCHEDULED_PROCEDURE_STEP_START_TIME, Action::Remove),
+    (tags::SCHEDULED_PROCEDURE_STEP_END_DATE, Action::Remove),
+    (tags::SCHEDULED_PROCEDURE_STEP_END_TIME, Action::Remove),
+    (tags::PERFORMED_PROCEDURE_STEP_END_DATE, Action::Remove),
+    (tags::PERFORMED_PROCEDURE_STEP_END_TIME, Action::Remove),
+    (tags::OBSERVATION_DATE_TIME, Action::Remove),
+    (tags::DATE_TIME, Action::Remove),
+    (tags::DATE, Action::Remove),
+    (tags::TIME, Action::Remove),
+    (tags::VERIFICATION_DATE_TIME, Action::Remove),
+];
+
+/// Options for the de-identification of DICOM objects.
+///
+/// By default, UIDs are replaced, dates and times are removed,
+/// and private attributes are removed.
+/// Attributes absent from the tables of this module
+/// are removed unless they only hold
+/// numbers, codes or binary data (see [`REMOVED_VRS`]).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct DeidentOptions {
+    /// Whether to keep the UIDs in [`UID_ATTRIBUTES`] (_Retain UIDs Option_).
+    pub retain_uids: bool,
+    /// Whether to keep the dates and times in [`TEMPORAL_ATTRIBUTES`]
+    /// (_Retain Longitudinal Temporal Information with Full Dates Option_).
+    pub retain_dates: bool,
+    /// Whether to remove all private attributes.
+    pub remove_private: bool,
+}
+
+impl Default for DeidentOptions {
+    fn default() -> Self {
+        DeidentOptions {
+            retain_uids: false,
+            retain_dates: false,
+            remove_private: true,
+        }
+    }
+}
+
+impl DeidentOptions {
+    /// Create the default options of the Basic Profile,
+    /// without any profile option.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether to keep the original UIDs.
+    pub fn retain_uids(mut self, retain_uids: bool) -> Self {
+        self.retain_uids = retain_uids;
+        self
+    }
+
+    /// Set whether to keep the original dates and times.
+    pub fn retain_dates(mut self, retain_dates: bool) -> Self {
+        self.retain_dates = retain_dates;
+        self
+    }
+
+    /// Set whether to remove all private attributes.
+    pub fn remove_private(mut self, remove_private: bool) -> Self {
+        self.remove_private = remove_private;
+        self
+    }
+}
+
+/// A de-identification engine
+/// applying the Basic Application Level Confidentiality Profile.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone)]
+pub struct Deidentifier {
+    options: DeidentOptions,
+    actions: HashMap<Tag, Action>,
+    uids: HashMap<String, String>,
+    generator: UidGenerator,
+}
+
+impl Deidentifier {
+    /// Create a de-identifier with the given options,
+    /// generating new UIDs under the default organization root.
+    pub fn new(options: DeidentOptions) -> Self {
+        let mut actions: HashMap<Tag, Action> = RETAINED_ATTRIBUTES
+            .iter()
+            .map(|&tag| (tag, Action::Keep))
+            .collect();
+        actions.extend(IDENTIFYING_ATTRIBUTES.iter().copied());
+        if options.retain_uids {
+            actions.extend(UID_ATTRIBUTES.iter().map(|&tag| (tag, Action::Keep)));
+        } else {
+            actions.extend(UID_ATTRIBUTES.iter().map(|&tag| (tag, Action::ReplaceUid)));
+        }
+        if options.retain_dates {
+            actions.extend(
+                TEMPORAL_ATTRIBUTES
+                    .iter()
+                    .map(|&(tag, _)| (tag, Action::Keep)),
+            );
+        } else {
+            actions.extend(TEMPORAL_ATTRIBUTES.iter().copied());
+        }
+        Deidentifier {
+            options,
+            actions,
+            uids: HashMap::new(),
+            generator: UidGenerator::new(),
+        }
+    }
+
+    /// Set the generator of the UIDs replacing the original ones.
+    pub fn with_uid_generator(mut self, generator: UidGenerator) -> Self {
+        self.generator = generator;
+        self
+    }
+
+    /// Set the action to apply to the given attribute,
+    /// overriding the profile.
+    pub fn with_action(mut self, tag: Tag, action: Action) -> Self {
+        self.actions.insert(tag, action);
+        self
+    }
+
+    /// The action applied to the given attribute.
+    ///
+    /// Attributes not in the tables are kept
+    /// unless they are sequences,
+    /// group lengths,
+    /// unknown to the standard data dictionary,
+    /// or of a value representation in [`REMOVED_VRS`],
+    /// in which case they are removed.
+    /// Private attributes not in the tables are kept
+    /// when they are not removed.
+    ///
+    /// Private attributes are removed regardless of their action
+    /// if [`remove_private`](DeidentOptions::remove_private) is set.
+    pub fn action(&self, tag: Tag) -> Action {
+        if let Some(&action) = self.actions.get(&tag) {
+            return action;
+        }
+        if tag.is_private() {
+            return if self.options.remove_private {
+                Action::Remove
+            } else {
+                Action::Keep
+            };
+        }
+        if tag.element() == 0x0000 {
+            return Action::Remove;
+        }
+        match StandardDataDictionary
+            .by_tag(tag)
+            .map(|entry| entry.vr().relaxed())
+        {
+            Some(VR::SQ) | None => Action::Remove,
+            Some(vr) if REMOVED_VRS.contains(&vr) => Action::Remove,
+            Some(_) => Action::Keep,
+        }
+    }
+
+    /// The UIDs replaced so far, mapped to their replacements.
+    pub fn uid_map(&self) -> &HashMap<String, String> {
+        &self.uids
+    }
+
+    /// De-identify the given object in place.
+    ///
+    /// The actions are applied to all attributes,
+    /// including those in nested sequence items.
+    /// _Patient Identity Removed_ (0012,0062) is then set to `YES`,
+    /// and the profile and options applied are recorded in
+    /// _De-identification Method_ (0012,0063)
+    /// and _De-identification Method Code Sequence_ (0012,0064).
+    pub fn deidentify<D>(&mut self, obj: &mut InMemDicomObject<D>)
+    where
+        D: DataDictionary + Clone,
+    {
+        self.apply(obj);
+        obj.remove_orphan_group_lengths();
+        self.mark(obj);
+    }
+
+    /// De-identify the given file object in place,
+    /// replacing _Media Storage SOP Instance UID_ in the file meta group
+    /// in the same way as _SOP Instance UID_.
+    ///
+    /// See [`deidentify`](Self::deidentify).
+    pub fn deidentify_file<D>(&mut self, obj: &mut FileDicomObject<InMemDicomObject<D>>)
+    where
+        D: DataDictionary + Clone,
+    {
+        self.deidentify(obj);
+        if self.action(tags::SOP_INSTANCE_UID) == Action::ReplaceUid {
+            let meta = obj.meta_mut();
+            let uid = trim_uid(&meta.media_storage_sop_instance_uid).to_string();
+            meta.media_storage_sop_instance_uid = self.replace_uid(&uid);
+            meta.update_information_group_length();
+        }
+    }
+
+    fn apply<D>(&mut self, obj: &mut InMemDicomObject<D>)
+    where
+        D: DataDictionary + Clone,
+    {
+        let remove_private = self.options.remove_private;
+        obj.retain(|e| {
+            !(remove_private && e.tag().is_private()) && self.action(e.tag()) != Action::Remove
+        });
+
+        for elem in obj.elements_mut() {
+            let (tag, vr) = (elem.tag(), elem.vr());
+            match self.action(tag) {
+                Action::Empty => *elem = DataElement::empty(tag, vr),
+                Action::Dummy => *elem = dummy(tag, vr),
+                Action::ReplaceUid => {
+                    if let Some(value) = elem.value().primitive() {
+                        let uids = PrimitiveValue::new_strs(
+                            value
+                                .to_multi_str()
+                                .iter()
+                                .map(|uid| self.replace_uid(trim_uid(uid))),
+                        );
+                        *elem = DataElement::new(tag, vr, uids);
+                    }
+                }
+                Action::Remove | Action::Keep => {}
+            }
+            if elem.items().is_some() {
+                for item in elem.items_mut().into_iter().flatten() {
+                    self.apply(item);
+                }
+            }
+        }
+    }
+
+    /// Record the de-identification in the object.
+    fn mark<D>(&self, obj: &mut InMemDicomObject<D>)
+    where
+        D: DataDictionary + Clone,
+    {
+        let mut codes = vec![("113100", "Basic Application Confidentiality Profile")];
+        if self.options.retain_uids {
+            codes.push(("113110", "Retain UIDs Option"));
+        }
+        if self.options.retain_dates {
+            codes.push((
+                "113106",
+                "Retain Longitudinal Temporal Information Full Dates Option",
+            ));
+        }
+
+        let method = codes
+            .iter()
+            .map(|(_, meaning)| *meaning)
+            .collect::<Vec<_>>()
+            .join("\\");
+        let items: Vec<InMemDicomObject<D>> = codes
+            .iter()
+            .map(|&(value, meaning)| {
+                InMemDicomObject::from_iter_with_dict(
+                    [
+                        DataElement::new(tags::CODE_VALUE, VR::SH, value),
+                        DataElement::new(tags::CODING_SCHEME_DESIGNATOR, VR::SH, "DCM"),
+                        DataElement::new(tags::CODE_MEANING, VR::LO, meaning),
+                    ],
+                    obj.dict.clone(),
+                )
+            })
+            .collect();
+
+        obj.put(DataElement::new(
+            tags::PATIENT_IDENTITY_REMOVED,
+            VR::CS,
+            "YES",
+        ));
+        obj.put(DataElement::new(
+            tags::DEIDENTIFICATION_METHOD,
+            VR::LO,
+            method,
+        ));
+        obj.put(DataElement::new(
+            tags::DEIDENTIFICATION_METHOD_CODE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(items),
+        ));
+        obj.put(DataElement::new(
+            tags::LONGITUDINAL_TEMPORAL_INFORMATION_MODIFIED,
+            VR::CS,
+            if self.options.retain_dates {
+                "UNMODIFIED"
+            } else {
+                "REMOVED"
+            },
+        ));
+    }
+
+    /// Obtain the replacement of the given UID,
+    /// generating it on first use,
+    /// in the `2.25` form if the generator is exhausted.
+    fn replace_uid(&mut self, uid: &str) -> String {
+        if uid.is_empty() {
+            return String::new();
+        }
+        let generator = &self.generator;
+        self.uids
+            .entry(uid.to_string())
+            .or_insert_with(|| generator.generate().unwrap_or_else(|_| uuid_uid()))
+            .clone()
+    }
+}
+
+fn trim_uid(uid: &str) -> &str {
+    uid.trim_end_matches(['\0', ' '])
+}
+
+/// Create an element with a dummy value suitable for the given VR.
+fn dummy<D>(tag: Tag, vr: VR) -> InMemElement<D> {
+    let value = match vr {
+        VR::AE | VR::CS | VR::LO | VR::LT | VR::PN | VR::SH | VR::ST | VR::UC | VR::UT => {
+            PrimitiveValue::from(DUMMY_TEXT)
+        }
+        VR::DA => PrimitiveValue::from("19000101"),
+        VR::TM => PrimitiveValue::from("000000"),
+        VR::DT => PrimitiveValue::from("19000101000000"),
+        VR::AS => PrimitiveValue::from("000Y"),
+        VR::DS | VR::IS => PrimitiveValue::from("0"),
+        VR::US => PrimitiveValue::from(0_u16),
+        VR::SS => PrimitiveValue::from(0_i16),
+        VR::UL => PrimitiveValue::from(0_u32),
+        VR::SL => PrimitiveValue::from(0_i32),
+        VR::FL => PrimitiveValue::from(0_f32),
+        VR::FD => PrimitiveValue::from(0_f64),
+        _ => return DataElement::empty(tag, vr),
+    };
+    DataElement::new(tag, vr, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileMetaTableBuilder;
+    use dicom_core::dicom_value;
+    use dicom_core::header::HasLength;
+    use dicom_dictionary_std::uids;
+
+    /// A CT image with demographics, dates, UIDs,
+    /// a private group, and a nested sequence.
+    fn test_object() -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::SOP_CLASS_UID,
+                VR::UI,
+                PrimitiveValue::from(uids::CT_IMAGE_STORAGE),
+            ),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "1.2.3.4.1\0"),
+            DataElement::new(tags::STUDY_DATE, VR::DA, "20240315"),
+            DataElement::new(tags::STUDY_TIME, VR::TM, "101500"),
+            DataElement::new(tags::SERIES_DATE, VR::DA, "20240315"),
+            DataElement::new(tags::ACCESSION_NUMBER, VR::SH, "A123456"),
+            DataElement::new(tags::MODALITY, VR::CS, "CT"),
+            DataElement::new(tags::INSTITUTION_NAME, VR::LO, "General Hospital"),
+            DataElement::new(tags::REFERRING_PHYSICIAN_NAME, VR::PN, "House^Gregory"),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::REFERENCED_SOP_CLASS_UID,
+                        VR::UI,
+                        PrimitiveValue::from(uids::CT_IMAGE_STORAGE),
+                    ),
+                    DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.3.4.1\0"),
+                    DataElement::new(tags::OPERATORS_NAME, VR::PN, "Doe^Jane"),
+                ])]),
+            ),
+            DataElement::new(Tag(0x0009, 0x0010), VR::LO, "ACME 1.0"),
+            DataElement::new(Tag(0x0009, 0x1001), VR::LO, "secret"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+            DataElement::new(tags::PATIENT_ID, VR::LO, "12345678"),
+            DataElement::new(tags::PATIENT_BIRTH_DATE, VR::DA, "19700101"),
+            DataElement::new(tags::PATIENT_SEX, VR::CS, "M"),
+            DataElement::new(tags::PATIENT_AGE, VR::AS, "054Y"),
+            DataElement::new(tags::PATIENT_ADDRESS, VR::LO, "1 Main Street"),
+            DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2"),
+            DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, "1.2.3\0"),
+            DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, "1.2.3.4\0"),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::PIXEL_DATA, VR::OB, dicom_value!(U8, [1, 2, 3, 4])),
+        ])
+    }
+
+    #[test]
+    fn deidentify_basic_profile() {
+        let original = test_object();
+        let mut obj = original.clone();
+        let mut deidentifier = Deidentifier::new(DeidentOptions::new());
+        deidentifier.deidentify(&mut obj);
+
+        // demographics
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), DUMMY_TEXT);
+        assert_eq!(obj.string(tags::PATIENT_ID).unwrap(), DUMMY_TEXT);
+        assert!(obj.get(tags::PATIENT_BIRTH_DATE).unwrap().is_empty());
+        assert!(obj.get(tags::PATIENT_SEX).unwrap().is_empty());
+        assert!(obj.get(tags::PATIENT_AGE).is_none());
+        assert!(obj.get(tags::PATIENT_ADDRESS).is_none());
+        assert!(obj.get(tags::INSTITUTION_NAME).is_none());
+        assert!(obj.get(tags::ACCESSION_NUMBER).unwrap().is_empty());
+        assert!(obj.get(tags::REFERRING_PHYSICIAN_NAME).unwrap().is_empty());
+
+        // dates
+        assert!(obj.get(tags::STUDY_DATE).unwrap().is_empty());
+        assert!(obj.get(tags::STUDY_TIME).unwrap().is_empty());
+        assert!(obj.get(tags::SERIES_DATE).is_none());
+
+        // private groups
+        assert!(obj.tags().all(|tag| !tag.is_private()));
+
+        // UIDs, replaced consistently
+        let sop_instance_uid = obj.string(tags::SOP_INSTANCE_UID).unwrap();
+        assert_ne!(sop_instance_uid, "1.2.3.4.1");
+        assert_eq!(
+            deidentifier.uid_map().get("1.2.3.4.1"),
+            Some(&sop_instance_uid)
+        );
+        assert_ne!(obj.string(tags::STUDY_INSTANCE_UID).unwrap(), "1.2.3");
+        assert_eq!(
+            obj.string(tags::SOP_CLASS_UID).unwrap(),
+            uids::CT_IMAGE_STORAGE
+        );
+
+        // nested sequence items
+        let item = &obj.items(tags::REFERENCED_IMAGE_SEQUENCE).unwrap()[0];
+        assert_eq!(
+            item.string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap(),
+            sop_instance_uid
+        );
+        assert!(item.get(tags::OPERATORS_NAME).is_none());
+
+        // technical attributes
+        for tag in [
+            tags::MODALITY,
+            tags::SLICE_THICKNESS,
+            tags::ROWS,
+            tags::COLUMNS,
+            tags::BITS_ALLOCATED,
+            tags::PIXEL_DATA,
+        ] {
+            assert_eq!(obj.get(tag), original.get(tag));
+        }
+
+        // markers
+        assert_eq!(obj.string(tags::PATIENT_IDENTITY_REMOVED).unwrap(), "YES");
+        assert_eq!(
+            obj.string(tags::DEIDENTIFICATION_METHOD).unwrap(),
+            "Basic Application Confidentiality Profile"
+        );
+        let codes = obj
+            .items(tags::DEIDENTIFICATION_METHOD_CODE_SEQUENCE)
+            .unwrap();
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].string(tags::CODE_VALUE).unwrap(), "113100");
+        assert_eq!(
+            obj.string(tags::LONGITUDINAL_TEMPORAL_INFORMATION_MODIFIED)
+                .unwrap(),
+            "REMOVED"
+        );
+
+        // the same UIDs are used for another object of the study
+        let mut other = test_object();
+        deidentifier.deidentify(&mut other);
+        assert_eq!(
+            other.string(tags::SOP_INSTANCE_UID).unwrap(),
+            sop_instance_uid
+        );
+    }
+
+    #[test]
+    fn deidentify_with_options() {
+        let original = test_object();
+        let mut obj = original.clone();
+        let options = DeidentOptions::new()
+            .retain_uids(true)
+            .retain_dates(true)
+            .remove_private(false);
+        Deidentifier::new(options)
+            .with_action(tags::PATIENT_AGE, Action::Keep)
+            .deidentify(&mut obj);
+
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), DUMMY_TEXT);
+        assert!(obj.get(tags::PATIENT_BIRTH_DATE).unwrap().is_empty());
+        for tag in [
+            tags::STUDY_DATE,
+            tags::STUDY_TIME,
+            tags::SERIES_DATE,
+            tags::SOP_INSTANCE_UID,
+            tags::STUDY_INSTANCE_UID,
+            tags::PATIENT_AGE,
+            Tag(0x0009, 0x1001),
+        ] {
+            assert_eq!(obj.get(tag), original.get(tag));
+        }
+
+        let codes: Vec<_> = obj
+            .items(tags::DEIDENTIFICATION_METHOD_CODE_SEQUENCE)
+            .unwrap()
+            .iter()
+            .map(|item| item.string(tags::CODE_VALUE).unwrap())
+            .collect();
+        assert_eq!(codes, ["113100", "113110", "113106"]);
+        assert_eq!(
+            obj.string(tags::LONGITUDINAL_TEMPORAL_INFORMATION_MODIFIED)
+                .unwrap(),
+            "UNMODIFIED"
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deidentify_removes_unlisted_attributes() {
+        let mut obj = test_object();
+        obj.put(DataElement::new(
+            tags::OTHER_PATIENT_I_DS,
+            VR::LO,
+            "87654321",
+        ));
+        obj.put(DataElement::new(
+            tags::ACQUISITION_COMMENTS,
+            VR::LT,
+            "Doe^John",
+        ));
+        obj.put(DataElement::new(tags::STUDY_COMMENTS, VR::LT, "Doe^John"));
+        obj.put(DataElement::new(tags::ADMISSION_ID, VR::LO, "ADM42"));
+        obj.put(DataElement::new(
+            tags::SCHEDULED_PROCEDURE_STEP_START_DATE,
+            VR::DA,
+            "20240315",
+        ));
+        // not in any table
+        obj.put(DataElement::new(
+            tags::ADMITTING_DIAGNOSES_CODE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                DataElement::new(tags::CODE_MEANING, VR::LO, "Doe^John"),
+            ])]),
+        ));
+        obj.put(DataElement::new(Tag(0x6002, 0x4000), VR::LT, "Doe^John"));
+
+        let mut deidentifier = Deidentifier::new(DeidentOptions::new());
+        for tag in [Tag(0x6002, 0x4000), tags::ADMITTING_DIAGNOSES_CODE_SEQUENCE] {
+            assert_eq!(deidentifier.action(tag), Action::Remove);
+        }
+        deidentifier.deidentify(&mut obj);
+
+        for tag in [
+            tags::OTHER_PATIENT_I_DS,
+            tags::ACQUISITION_COMMENTS,
+            tags::STUDY_COMMENTS,
+            tags::ADMISSION_ID,
+            tags::SCHEDULED_PROCEDURE_STEP_START_DATE,
+            tags::ADMITTING_DIAGNOSES_CODE_SEQUENCE,
+            Tag(0x6002, 0x4000),
+        ] {
+            assert!(obj.get(tag).is_none(), "{} should be removed", tag);
+        }
+        assert_eq!(obj.string(tags::MODALITY).unwrap(), "CT");
+
+        // private attributes are kept on request
+        let deidentifier = Deidentifier::new(DeidentOptions::new().remove_private(false));
+        assert_eq!(deidentifier.action(Tag(0x0009, 0x1001)), Action::Keep);
+        assert_eq!(deidentifier.action(Tag(0x6002, 0x4000)), Action::Remove);
+    }
+
+    #[test]
+    fn deidentify_keeps_multiframe_geometry() {
+        let frame = |position: &str, index: u32| {
+            InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::FRAME_CONTENT_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                        DataElement::new(
+                            tags::DIMENSION_INDEX_VALUES,
+                            VR::UL,
+                            dicom_value!(U32, [1, index]),
+                        ),
+                        DataElement::new(tags::STACK_ID, VR::SH, "1"),
+                        DataElement::new(
+                            tags::IN_STACK_POSITION_NUMBER,
+                            VR::UL,
+                            dicom_value!(U32, [index]),
+                        ),
+                        DataElement::new(
+                            tags::FRAME_ACQUISITION_DATE_TIME,
+                            VR::DT,
+                            "20240315101500",
+                        ),
+                    ])]),
+                ),
+                DataElement::new(
+                    tags::PLANE_POSITION_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                        DataElement::new(tags::IMAGE_POSITION_PATIENT, VR::DS, position),
+                    ])]),
+                ),
+            ])
+        };
+        let mut obj = test_object();
+        obj.put(DataElement::new(
+            tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::PIXEL_MEASURES_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                        DataElement::new(tags::PIXEL_SPACING, VR::DS, "0.5\\0.5"),
+                        DataElement::new(tags::SLICE_THICKNESS, VR::DS, "1"),
+                    ])]),
+                ),
+                DataElement::new(
+                    tags::PLANE_ORIENTATION_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                        DataElement::new(
+                            tags::IMAGE_ORIENTATION_PATIENT,
+                            VR::DS,
+                            "1\\0\\0\\0\\1\\0",
+                        ),
+                    ])]),
+                ),
+            ])]),
+        ));
+        obj.put(DataElement::new(
+            tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![frame("0\\0\\0", 1), frame("0\\0\\1", 2)]),
+        ));
+        obj.put(DataElement::new(
+            tags::DIMENSION_INDEX_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::DIMENSION_INDEX_POINTER,
+                    VR::AT,
+                    dicom_value!(Tags, [tags::IMAGE_POSITION_PATIENT]),
+                ),
+                DataElement::new(
+                    tags::FUNCTIONAL_GROUP_POINTER,
+                    VR::AT,
+                    dicom_value!(Tags, [tags::PLANE_POSITION_SEQUENCE]),
+                ),
+            ])]),
+        ));
+        obj.put(DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, "2"));
+        obj.put(DataElement::new(tags::VOILUT_FUNCTION, VR::CS, "LINEAR"));
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER_WIDTH_EXPLANATION,
+            VR::LO,
+            "BRAIN",
+        ));
+        obj.put(DataElement::new(
+            tags::ACQUISITION_MATRIX,
+            VR::US,
+            dicom_value!(U16, [0, 256, 256, 0]),
+        ));
+        obj.put(DataElement::new(
+            tags::PRESENTATION_INTENT_TYPE,
+            VR::CS,
+            "FOR PRESENTATION",
+        ));
+        // overlay plane, not in any table
+        obj.put(DataElement::new(
+            Tag(0x6000, 0x0010),
+            VR::US,
+            dicom_value!(U16, [2]),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x6000, 0x3000),
+            VR::OW,
+            dicom_value!(U8, [0, 1]),
+        ));
+        // names, texts and UIDs not in any table
+        obj.put(DataElement::new(
+            tags::NAMES_OF_INTENDED_RECIPIENTS_OF_RESULTS,
+            VR::PN,
+            "Doe^Jane",
+        ));
+        obj.put(DataElement::new(Tag(0x6000, 0x0022), VR::LO, "Doe^John"));
+        let original = obj.clone();
+
+        Deidentifier::new(DeidentOptions::new()).deidentify(&mut obj);
+
+        // compare the leaves, as undefined lengths never compare equal
+        let shared = |obj: &InMemDicomObject, sequence, tag| {
+            let group = &obj.items(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE).unwrap()[0];
+            group.items(sequence).unwrap()[0].get(tag).cloned()
+        };
+        for (sequence, tag) in [
+            (tags::PIXEL_MEASURES_SEQUENCE, tags::PIXEL_SPACING),
+            (tags::PIXEL_MEASURES_SEQUENCE, tags::SLICE_THICKNESS),
+            (
+                tags::PLANE_ORIENTATION_SEQUENCE,
+                tags::IMAGE_ORIENTATION_PATIENT,
+            ),
+        ] {
+            let value = shared(&obj, sequence, tag);
+            assert!(value.is_some(), "{} should be kept", tag);
+            assert_eq!(value, shared(&original, sequence, tag));
+        }
+        let dimension = &obj.items(tags::DIMENSION_INDEX_SEQUENCE).unwrap()[0];
+        for tag in [
+            tags::DIMENSION_INDEX_POINTER,
+            tags::FUNCTIONAL_GROUP_POINTER,
+        ] {
+            assert!(dimension.get(tag).is_some(), "{} should be kept", tag);
+        }
+        for tag in [
+            tags::NUMBER_OF_FRAMES,
+            tags::VOILUT_FUNCTION,
+            tags::WINDOW_CENTER_WIDTH_EXPLANATION,
+            tags::ACQUISITION_MATRIX,
+            tags::PRESENTATION_INTENT_TYPE,
+            Tag(0x6000, 0x0010),
+            Tag(0x6000, 0x3000),
+        ] {
+            assert_eq!(obj.get(tag), original.get(tag), "{} should be kept", tag);
+        }
+
+        // the frame geometry survives, but not the frame acquisition times
+        let frames = obj
+            .items(tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE)
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        for (frame, position) in frames.iter().zip(["0\\0\\0", "0\\0\\1"]) {
+            let content = &frame.items(tags::FRAME_CONTENT_SEQUENCE).unwrap()[0];
+            assert!(content.get(tags::DIMENSION_INDEX_VALUES).is_some());
+            assert!(content.get(tags::IN_STACK_POSITION_NUMBER).is_some());
+            assert_eq!(content.string(tags::STACK_ID).unwrap(), "1");
+            assert!(content.get(tags::FRAME_ACQUISITION_DATE_TIME).is_none());
+            let plane = &frame.items(tags::PLANE_POSITION_SEQUENCE).unwrap()[0];
+            let plane_position = plane.get(tags::IMAGE_POSITION_PATIENT).unwrap();
+            assert_eq!(plane_position.to_str().unwrap(), position);
+        }
+
+        for tag in [
+            tags::NAMES_OF_INTENDED_RECIPIENTS_OF_RESULTS,
+            Tag(0x6000, 0x0022),
+        ] {
+            assert!(obj.get(tag).is_none(), "{} should be removed", tag);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn tables_have_unique_tags() {
+        let tables = [
+            IDENTIFYING_ATTRIBUTES
+                .iter()
+                .map(|&(tag, _)| tag)
+                .collect::<Vec<_>>(),
+            RETAINED_ATTRIBUTES.to_vec(),
+            UID_ATTRIBUTES.to_vec(),
+            TEMPORAL_ATTRIBUTES.iter().map(|&(tag, _)| tag).collect(),
+        ];
+        let mut seen = std::collections::HashSet::new();
+        for tag in tables.iter().flatten() {
+            assert!(seen.insert(*tag), "{} appears more than once", tag);
+        }
+    }
+
+    #[test]
+    fn deidentify_file_meta() {
+        let mut obj = test_object()
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+            .unwrap();
+        assert_eq!(obj.meta().media_storage_sop_instance_uid(), "1.2.3.4.1");
+
+        Deidentifier::new(DeidentOptions::new()).deidentify_file(&mut obj);
+        let sop_instance_uid = obj.string(tags::SOP_INSTANCE_UID).unwrap();
+        assert_eq!(
+            obj.meta().media_storage_sop_instance_uid(),
+            sop_instance_uid
+        );
+    }
+}
@@ -0,0 +1,1687 @@
+//! Reading and building of DICOMDIR files.
+//!
+//! A DICOMDIR is the Media Storage Directory of a file-set,
+//! such as the contents of a media exchange disc.
+//! Its _Directory Record Sequence_ holds every directory record in a flat list,
+//! and the records are linked together by the file positions of one another:
+//! each record points to the next record of the same directory entity
+//! (_Offset of the Next Directory Record_)
+//! and to the first record of the entity below it
+//! (_Offset of Referenced Lower-Level Directory Entity_).
+//!
+//! [`DicomDir`] reads the directory with the [lazy reader](crate::lazy),
+//! which knows the position of each sequence item in the file,
+//! and resolves these links into a tree of [`DirectoryRecord`]s,
+//! typically patients, then studies, series, and images.
+//! Records which are no longer in use
+//! (with a _Record In-use Flag_ of zero)
+//! are left out of the tree, along with the records below them.
+//!
+//! [`DicomDirBuilder`] goes the other way,
+//! creating a DICOMDIR which lists a set of instances
+//! with their paths in the file-set.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_object::dicomdir::{self, RecordType};
+//!
+//! let dicomdir = dicomdir::open_file("DICOMDIR")?;
+//! for patient in dicomdir.records() {
+//!     println!("Patient {:?}", patient.patient_name());
+//!     for study in patient.children() {
+//!         println!("  Study {:?}", study.study_instance_uid());
+//!     }
+//! }
+//!
+//! // resolve the files of all images
+//! for image in dicomdir.iter() {
+//!     if image.record_type() == &RecordType::Image {
+//!         println!("{:?}", dicomdir.file_path(image));
+//!     }
+//! }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+
+use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
+use dicom_core::value::{DataSetSequence, PrimitiveValue};
+use dicom_core::{DataElement, Tag, VR};
+use dicom_dictionary_std::{tags, uids, StandardDataDictionary};
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::iod::Attribute;
+use crate::iod::AttributeType::*;
+use crate::lazy::{self, LazyDataSet, LazyDicomObject, LazyReadError};
+use crate::mem::InMemDicomObject;
+use crate::meta::{FileMetaTable, FileMetaTableBuilder};
+use crate::uid::uuid_uid;
+use crate::{FileDicomObject, OpenFileOptions, ReadError, WriteError};
+
+/// An error which may occur when reading a DICOMDIR.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum DicomDirError {
+    /// Could not read DICOMDIR
+    ReadDicomDir {
+        #[snafu(backtrace)]
+        source: LazyReadError,
+    },
+    /// Could not determine the start position of the DICOMDIR
+    ReadStartPosition {
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not read the value of {tag} in a directory record
+    ReadRecordValue {
+        tag: Tag,
+        #[snafu(backtrace)]
+        source: LazyReadError,
+    },
+    /// Missing Directory Record Sequence
+    MissingRecordSequence { backtrace: Backtrace },
+    /// No directory record at offset {offset}
+    DanglingOffset { offset: u64, backtrace: Backtrace },
+    /// Directory record at offset {offset} is referenced more than once
+    CyclicRecord { offset: u64, backtrace: Backtrace },
+}
+
+pub type Result<T, E = DicomDirError> = std::result::Result<T, E>;
+
+/// The type of a directory record,
+/// from its _Directory Record Type_ (0004,1430).
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum RecordType {
+    /// `PATIENT`
+    Patient,
+    /// `STUDY`
+    Study,
+    /// `SERIES`
+    Series,
+    /// `IMAGE`
+    Image,
+    /// Any other record type, such as `SR DOCUMENT` or `PRESENTATION`
+    Other(String),
+}
+
+impl RecordType {
+    /// Obtain the record type of the given code string,
+    /// ignoring padding.
+    pub fn from_code(code: &str) -> Self {
+        match code.trim_end_matches([' ', '\0']) {
+            "PATIENT" => RecordType::Patient,
+            "STUDY" => RecordType::Study,
+            "SERIES" => RecordType::Series,
+            "IMAGE" => RecordType::Image,
+            code => RecordType::Other(code.to_string()),
+        }
+    }
+
+    /// Obtain the code string of this record type.
+    pub fn as_str(&self) -> &str {
+        match self {
+            RecordType::Patient => "PATIENT",
+            RecordType::Study => "STUDY",
+            RecordType::Series => "SERIES",
+            RecordType::Image => "IMAGE",
+            RecordType::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for RecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A directory record in use,
+/// with the records of the directory entity below it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryRecord {
+    offset: u64,
+    record_type: RecordType,
+    attributes: InMemDicomObject,
+    children: Vec<DirectoryRecord>,
+}
+
+impl DirectoryRecord {
+    /// The offset of this record in the DICOMDIR file,
+    /// by which other records refer to it.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The type of this record.
+    pub fn record_type(&self) -> &RecordType {
+        &self.record_type
+    }
+
+    /// All attributes of this record,
+    /// including the offsets linking it to other records.
+    pub fn attributes(&self) -> &InMemDicomObject {
+        &self.attributes
+    }
+
+    /// The records of the lower-level directory entity,
+    /// such as the studies of a patient.
+    pub fn children(&self) -> &[DirectoryRecord] {
+        &self.children
+    }
+
+    /// The path components of the file referenced by this record
+    /// in _Referenced File ID_ (0004,1500),
+    /// relative to the directory of the DICOMDIR.
+    pub fn referenced_file_id(&self) -> Option<Vec<String>> {
+        let value = self.value(tags::REFERENCED_FILE_ID)?;
+        Some(
+            value
+                .to_multi_str()
+                .iter()
+                .map(|component| trim(component).to_string())
+                .collect(),
+        )
+    }
+
+    /// The relative path of the file referenced by this record,
+    /// built from its [_Referenced File ID_](DirectoryRecord::referenced_file_id).
+    pub fn referenced_file_path(&self) -> Option<PathBuf> {
+        self.referenced_file_id()
+            .map(|components| components.iter().collect())
+    }
+
+    /// _Referenced SOP Class UID in File_ (0004,1510)
+    pub fn referenced_sop_class_uid(&self) -> Option<String> {
+        self.string(tags::REFERENCED_SOP_CLASS_UID_IN_FILE)
+    }
+
+    /// _Referenced SOP Instance UID in File_ (0004,1511)
+    pub fn referenced_sop_instance_uid(&self) -> Option<String> {
+        self.string(tags::REFERENCED_SOP_INSTANCE_UID_IN_FILE)
+    }
+
+    /// _Referenced Transfer Syntax UID in File_ (0004,1512)
+    pub fn referenced_transfer_syntax_uid(&self) -> Option<String> {
+        self.string(tags::REFERENCED_TRANSFER_SYNTAX_UID_IN_FILE)
+    }
+
+    /// _Patient ID_ (0010,0020), in patient records
+    pub fn patient_id(&self) -> Option<String> {
+        self.string(tags::PATIENT_ID)
+    }
+
+    /// _Patient's Name_ (0010,0010), in patient records
+    pub fn patient_name(&self) -> Option<String> {
+        self.string(tags::PATIENT_NAME)
+    }
+
+    /// _Study Instance UID_ (0020,000D), in study records
+    pub fn study_instance_uid(&self) -> Option<String> {
+        self.string(tags::STUDY_INSTANCE_UID)
+    }
+
+    /// _Study Date_ (0008,0020), in study records
+    pub fn study_date(&self) -> Option<String> {
+        self.string(tags::STUDY_DATE)
+    }
+
+    /// _Study Description_ (0008,1030), in study records
+    pub fn study_description(&self) -> Option<String> {
+        self.string(tags::STUDY_DESCRIPTION)
+    }
+
+    /// _Accession Number_ (0008,0050), in study records
+    pub fn accession_number(&self) -> Option<String> {
+        self.string(tags::ACCESSION_NUMBER)
+    }
+
+    /// _Series Instance UID_ (0020,000E), in series records
+    pub fn series_instance_uid(&self) -> Option<String> {
+        self.string(tags::SERIES_INSTANCE_UID)
+    }
+
+    /// _Modality_ (0008,0060), in series records
+    pub fn modality(&self) -> Option<String> {
+        self.string(tags::MODALITY)
+    }
+
+    /// _Series Number_ (0020,0011), in series records
+    pub fn series_number(&self) -> Option<i32> {
+        self.value(tags::SERIES_NUMBER)?.to_int().ok()
+    }
+
+    /// _Instance Number_ (0020,0013), in image records
+    pub fn instance_number(&self) -> Option<i32> {
+        self.value(tags::INSTANCE_NUMBER)?.to_int().ok()
+    }
+
+    fn value(&self, tag: Tag) -> Option<&PrimitiveValue> {
+        self.attributes.get(tag)?.value().primitive()
+    }
+
+    /// The value of a text attribute, without padding.
+    fn string(&self, tag: Tag) -> Option<String> {
+        Some(trim(&self.value(tag)?.to_str()).to_string())
+    }
+}
+
+fn trim(text: &str) -> &str {
+    text.trim_end_matches([' ', '\0'])
+}
+
+/// A DICOMDIR, with its directory records arranged in a tree.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DicomDir {
+    meta: FileMetaTable,
+    file_set_id: Option<String>,
+    base: Option<PathBuf>,
+    records: Vec<DirectoryRecord>,
+}
+
+impl DicomDir {
+    /// The file meta table of the DICOMDIR.
+    pub fn meta(&self) -> &FileMetaTable {
+        &self.meta
+    }
+
+    /// _File-set ID_ (0004,1130), if present and not empty.
+    pub fn file_set_id(&self) -> Option<&str> {
+        self.file_set_id.as_deref()
+    }
+
+    /// The records of the root directory entity,
+    /// which are usually patient records.
+    pub fn records(&self) -> &[DirectoryRecord] {
+        &self.records
+    }
+
+    /// Iterate over all records in the tree, depth first,
+    /// each record followed by the records below it.
+    pub fn iter(&self) -> Records<'_> {
+        Records {
+            stack: vec![self.records.iter()],
+        }
+    }
+
+    /// Resolve the path of the file referenced by the given record.
+    ///
+    /// The path is relative to the directory of the DICOMDIR file,
+    /// or relative to an unknown directory
+    /// if the DICOMDIR was not read from a file
+    /// (see [`DirectoryRecord::referenced_file_path`]).
+    pub fn file_path(&self, record: &DirectoryRecord) -> Option<PathBuf> {
+        let path = record.referenced_file_path()?;
+        Some(match &self.base {
+            Some(base) => base.join(path),
+            None => path,
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a DicomDir {
+    type Item = &'a DirectoryRecord;
+    type IntoIter = Records<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the records of a [`DicomDir`], depth first.
+#[derive(Debug, Clone)]
+pub struct Records<'a> {
+    stack: Vec<std::slice::Iter<'a, DirectoryRecord>>,
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = &'a DirectoryRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(record) => {
+                    self.stack.push(record.children.iter());
+                    return Some(record);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Open and read a DICOMDIR file.
+///
+/// The paths of referenced files are resolved
+/// relative to the directory of the DICOMDIR.
+pub fn open_file<P>(path: P) -> Result<DicomDir>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let obj = lazy::open_file(path).context(ReadDicomDirSnafu)?;
+    let base = path.parent().map(Path::to_path_buf);
+    from_lazy(&obj, 0, base)
+}
+
+/// Read a DICOMDIR from a random access source,
+/// starting at its current position,
+/// which is expected to be the start of the file preamble.
+pub fn from_reader<S>(mut src: S) -> Result<DicomDir>
+where
+    S: Read + Seek,
+{
+    let start = src.stream_position().context(ReadStartPositionSnafu)?;
+    let obj = lazy::from_reader(src).context(ReadDicomDirSnafu)?;
+    from_lazy(&obj, start, None)
+}
+
+/// Arrange the directory records of a lazily read DICOMDIR into a tree,
+/// given the position of the start of the file in the source.
+fn from_lazy<S>(obj: &LazyDicomObject<S>, start: u64, base: Option<PathBuf>) -> Result<DicomDir>
+where
+    S: Read + Seek,
+{
+    let items = obj
+        .get(tags::DIRECTORY_RECORD_SEQUENCE)
+        .and_then(|e| e.items())
+        .context(MissingRecordSequenceSnafu)?;
+    let by_offset: HashMap<u64, usize> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| Some((item.offset()?.checked_sub(start)?, i)))
+        .collect();
+
+    let file_set_id = match obj.get(tags::FILE_SET_ID) {
+        Some(elem) => Some(
+            trim(
+                &elem
+                    .value()
+                    .context(ReadRecordValueSnafu {
+                        tag: tags::FILE_SET_ID,
+                    })?
+                    .to_str(),
+            )
+            .to_string(),
+        ),
+        None => None,
+    }
+    .filter(|id| !id.is_empty());
+
+    // tolerate a missing offset to the first record
+    let first = match read_offset(
+        obj,
+        tags::OFFSET_OF_THE_FIRST_DIRECTORY_RECORD_OF_THE_ROOT_DIRECTORY_ENTITY,
+    )? {
+        Some(offset) => offset,
+        None => items
+            .first()
+            .and_then(|item| item.offset())
+            .map(|offset| offset - start)
+            .unwrap_or(0),
+    };
+
+    let mut linker = Linker {
+        items,
+        by_offset,
+        visited: vec![false; items.len()],
+    };
+    let records = linker.read_entity(first)?;
+
+    Ok(DicomDir {
+        meta: obj.meta().clone(),
+        file_set_id,
+        base,
+        records,
+    })
+}
+
+/// Read an offset attribute of a data set,
+/// or `None` if it is absent or not an integer.
+fn read_offset<S>(dataset: &LazyDataSet<S>, tag: Tag) -> Result<Option<u64>>
+where
+    S: Read + Seek,
+{
+    match dataset.get(tag) {
+        Some(elem) => Ok(elem
+            .value()
+            .context(ReadRecordValueSnafu { tag })?
+            .to_int::<u32>()
+            .ok()
+            .map(u64::from)),
+        None => Ok(None),
+    }
+}
+
+/// The state of resolving the links between directory records.
+struct Linker<'a, S> {
+    items: &'a [LazyDataSet<S>],
+    by_offset: HashMap<u64, usize>,
+    /// whether each record was reached already
+    visited: Vec<bool>,
+}
+
+impl<S> Linker<'_, S>
+where
+    S: Read + Seek,
+{
+    /// Read the records of the directory entity starting at the given offset,
+    /// following the offsets to the next record,
+    /// and the records below each of them.
+    fn read_entity(&mut self, mut offset: u64) -> Result<Vec<DirectoryRecord>> {
+        let mut records = Vec::new();
+        // an offset of zero marks the end of the entity
+        while offset != 0 {
+            let index = *self
+                .by_offset
+                .get(&offset)
+                .context(DanglingOffsetSnafu { offset })?;
+            ensure!(!self.visited[index], CyclicRecordSnafu { offset });
+            self.visited[index] = true;
+
+            let item = &self.items[index];
+            let next = read_offset(item, tags::OFFSET_OF_THE_NEXT_DIRECTORY_RECORD)?;
+            let in_use = read_offset(item, tags::RECORD_IN_USE_FLAG)? != Some(0);
+            if in_use {
+                let lower = read_offset(
+                    item,
+                    tags::OFFSET_OF_REFERENCED_LOWER_LEVEL_DIRECTORY_ENTITY,
+                )?;
+                let children = self.read_entity(lower.unwrap_or(0))?;
+                let attributes = item.to_in_mem().context(ReadRecordValueSnafu {
+                    tag: tags::DIRECTORY_RECORD_SEQUENCE,
+                })?;
+                let record_type = attributes
+                    .get(tags::DIRECTORY_RECORD_TYPE)
+                    .and_then(|e| e.value().primitive())
+                    .map(|v| RecordType::from_code(&v.to_str()))
+                    .unwrap_or_else(|| RecordType::Other(String::new()));
+                records.push(DirectoryRecord {
+                    offset,
+                    record_type,
+                    attributes,
+                    children,
+                });
+            }
+            offset = next.unwrap_or(0);
+        }
+        Ok(records)
+    }
+}
+
+/// An error which may occur when building a DICOMDIR.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum BuildDicomDirError {
+    /// Could not read an instance file
+    #[snafu(display("Could not read instance file '{}'", path.display()))]
+    ReadInstance {
+        path: PathBuf,
+        #[snafu(backtrace, source(from(ReadError, Box::new)))]
+        source: Box<ReadError>,
+    },
+    /// The path of a file cannot be used as a Referenced File ID
+    #[snafu(display("Path '{}' is not a valid Referenced File ID", path.display()))]
+    InvalidFileId { path: PathBuf, backtrace: Backtrace },
+    /// Could not build the file meta table
+    BuildMeta {
+        #[snafu(backtrace)]
+        source: crate::meta::Error,
+    },
+    /// Could not encode the DICOMDIR
+    EncodeDicomDir {
+        #[snafu(backtrace, source(from(WriteError, Box::new)))]
+        source: Box<WriteError>,
+    },
+    /// Could not locate the directory records in the encoded DICOMDIR
+    LocateRecords {
+        #[snafu(backtrace)]
+        source: LazyReadError,
+    },
+}
+
+/// The maximum number of components of a Referenced File ID
+const MAX_FILE_ID_COMPONENTS: usize = 8;
+
+/// The maximum length of each component of a Referenced File ID
+const MAX_FILE_ID_COMPONENT_LENGTH: usize = 8;
+
+const SPECIFIC_CHARACTER_SET: Attribute =
+    Attribute::new(tags::SPECIFIC_CHARACTER_SET, "SpecificCharacterSet", Type1C);
+
+/// The keys of a PATIENT record (PS3.3 F.5.1)
+const PATIENT_KEYS: &[Attribute] = &[
+    SPECIFIC_CHARACTER_SET,
+    Attribute::new(tags::PATIENT_NAME, "PatientName", Type2),
+    Attribute::new(tags::PATIENT_ID, "PatientID", Type1),
+];
+
+/// The keys of a STUDY record (PS3.3 F.5.2)
+const STUDY_KEYS: &[Attribute] = &[
+    SPECIFIC_CHARACTER_SET,
+    Attribute::new(tags::STUDY_DATE, "StudyDate", Type1),
+    Attribute::new(tags::STUDY_TIME, "StudyTime", Type1),
+    Attribute::new(tags::STUDY_DESCRIPTION, "StudyDescription", Type2),
+    Attribute::new(tags::STUDY_INSTANCE_UID, "StudyInstanceUID", Type1C),
+    Attribute::new(tags::STUDY_ID, "StudyID", Type1),
+    Attribute::new(tags::ACCESSION_NUMBER, "AccessionNumber", Type2),
+];
+
+/// The keys of a SERIES record (PS3.3 F.5.3)
+const SERIES_KEYS: &[Attribute] = &[
+    SPECIFIC_CHARACTER_SET,
+    Attribute::new(tags::MODALITY, "Modality", Type1),
+    Attribute::new(tags::SERIES_INSTANCE_UID, "SeriesInstanceUID", Type1),
+    Attribute::new(tags::SERIES_NUMBER, "SeriesNumber", Type1),
+];
+
+/// The keys of an IMAGE record (PS3.3 F.5.18)
+const IMAGE_KEYS: &[Attribute] = &[
+    SPECIFIC_CHARACTER_SET,
+    Attribute::new(tags::INSTANCE_NUMBER, "InstanceNumber", Type1),
+];
+
+/// The keys of a WAVEFORM record (PS3.3 F.5.20)
+const WAVEFORM_KEYS: &[Attribute] = &[
+    SPECIFIC_CHARACTER_SET,
+    Attribute::new(tags::INSTANCE_NUMBER, "InstanceNumber", Type1),
+    Attribute::new(tags::CONTENT_DATE, "ContentDate", Type1),
+    Attribute::new(tags::CONTENT_TIME, "ContentTime", Type1),
+];
+
+/// The keys of an SR DOCUMENT record (PS3.3 F.5.21)
+const SR_DOCUMENT_KEYS: &[Attribute] = &[
+    SPECIFIC_CHARACTER_SET,
+    Attribute::new(tags::INSTANCE_NUMBER, "InstanceNumber", Type1),
+    Attribute::new(tags::COMPLETION_FLAG, "CompletionFlag", Type1),
+    Attribute::new(tags::VERIFICATION_FLAG, "VerificationFlag", Type1),
+    Attribute::new(tags::CONTENT_DATE, "ContentDate", Type1),
+    Attribute::new(tags::CONTENT_TIME, "ContentTime", Type1),
+    Attribute::new(tags::VERIFICATION_DATE_TIME, "VerificationDateTime", Type1C),
+    Attribute::new(
+        tags::CONCEPT_NAME_CODE_SEQUENCE,
+        "ConceptNameCodeSequence",
+        Type1,
+    ),
+];
+
+/// The keys of a PRESENTATION record (PS3.3 F.5.23)
+const PRESENTATION_KEYS: &[Attribute] = &[
+    SPECIFIC_CHARACTER_SET,
+    Attribute::new(
+        tags::PRESENTATION_CREATION_DATE,
+        "PresentationCreationDate",
+        Type1,
+    ),
+    Attribute::new(
+        tags::PRESENTATION_CREATION_TIME,
+        "PresentationCreationTime",
+        Type1,
+    ),
+    Attribute::new(tags::CONTENT_LABEL, "ContentLabel", Type1),
+    Attribute::new(tags::CONTENT_DESCRIPTION, "ContentDescription", Type2),
+    Attribute::new(tags::CONTENT_CREATOR_NAME, "ContentCreatorName", Type2),
+    Attribute::new(
+        tags::REFERENCED_SERIES_SEQUENCE,
+        "ReferencedSeriesSequence",
+        Type1C,
+    ),
+];
+
+/// The keys of an ENCAP DOC record (PS3.3 F.5.27)
+const ENCAP_DOC_KEYS: &[Attribute] = &[
+    SPECIFIC_CHARACTER_SET,
+    Attribute::new(tags::CONTENT_DATE, "ContentDate", Type2),
+    Attribute::new(tags::CONTENT_TIME, "ContentTime", Type2),
+    Attribute::new(tags::INSTANCE_NUMBER, "InstanceNumber", Type1),
+    Attribute::new(tags::DOCUMENT_TITLE, "DocumentTitle", Type2),
+    Attribute::new(
+        tags::CONCEPT_NAME_CODE_SEQUENCE,
+        "ConceptNameCodeSequence",
+        Type2,
+    ),
+    Attribute::new(
+        tags::MIME_TYPE_OF_ENCAPSULATED_DOCUMENT,
+        "MIMETypeOfEncapsulatedDocument",
+        Type1,
+    ),
+];
+
+/// The type and keys of the record of an instance
+/// of the given SOP class.
+fn instance_record(sop_class_uid: &str) -> (&'static str, &'static [Attribute]) {
+    if sop_class_uid.starts_with("1.2.840.10008.5.1.4.1.1.88.") {
+        ("SR DOCUMENT", SR_DOCUMENT_KEYS)
+    } else if sop_class_uid.starts_with("1.2.840.10008.5.1.4.1.1.11.") {
+        ("PRESENTATION", PRESENTATION_KEYS)
+    } else if sop_class_uid.starts_with("1.2.840.10008.5.1.4.1.1.9.") {
+        ("WAVEFORM", WAVEFORM_KEYS)
+    } else if sop_class_uid.starts_with("1.2.840.10008.5.1.4.1.1.104.") {
+        ("ENCAP DOC", ENCAP_DOC_KEYS)
+    } else {
+        ("IMAGE", IMAGE_KEYS)
+    }
+}
+
+/// An instance to be listed in a DICOMDIR.
+#[derive(Debug, Clone)]
+struct Instance {
+    /// the path of the file, relative to the DICOMDIR
+    path: PathBuf,
+    sop_class_uid: String,
+    sop_instance_uid: String,
+    transfer_syntax_uid: String,
+    /// the root attributes of the instance
+    attributes: InMemDicomObject,
+}
+
+impl Instance {
+    fn string(&self, tag: Tag) -> String {
+        self.attributes
+            .get(tag)
+            .and_then(|e| e.value().primitive())
+            .map(|v| trim(&v.to_str()).to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// A directory record to be encoded,
+/// with the records of the directory entity below it.
+struct RecordNode {
+    record: InMemDicomObject,
+    children: Vec<RecordNode>,
+}
+
+/// A directory record in the order of the Directory Record Sequence,
+/// with the indices of the records it links to.
+struct FlatRecord {
+    record: InMemDicomObject,
+    next: Option<usize>,
+    lower: Option<usize>,
+}
+
+/// A builder of DICOMDIR files,
+/// listing the given instances in a hierarchy of
+/// patient, study, series, and instance records.
+///
+/// Each record holds the key attributes of its directory record definition
+/// in PS3.3 Annex F.5,
+/// copied from the first instance which belongs to it.
+/// Instances are grouped by _Patient ID_,
+/// _Study Instance UID_ and _Series Instance UID_,
+/// in the order in which they were added.
+///
+/// The DICOMDIR is encoded in Explicit VR Little Endian,
+/// as required for media storage directories.
+///
+/// # Example
+///
+/// ```no_run
+/// use dicom_object::dicomdir::DicomDirBuilder;
+///
+/// let dicomdir = DicomDirBuilder::new()
+///     .file_set_id("STUDY_DISC")
+///     .add_file("/media/disc", "IMAGES/IM1")?
+///     .add_file("/media/disc", "IMAGES/IM2")?
+///     .build()?;
+/// dicomdir.write_to_file("/media/disc/DICOMDIR")?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DicomDirBuilder {
+    file_set_id: Option<String>,
+    sop_instance_uid: Option<String>,
+    instances: Vec<Instance>,
+}
+
+impl DicomDirBuilder {
+    /// Create a new builder without any instances.
+    pub fn new() -> Self {
+        DicomDirBuilder::default()
+    }
+
+    /// Define the _File-set ID_ (0004,1130).
+    ///
+    /// The file-set ID is left empty by default.
+    pub fn file_set_id(mut self, id: impl Into<String>) -> Self {
+        self.file_set_id = Some(id.into());
+        self
+    }
+
+    /// Define the media storage SOP instance UID of the DICOMDIR.
+    ///
+    /// A new UID is generated by default (see [`uuid_uid`]).
+    pub fn media_storage_sop_instance_uid(mut self, uid: impl Into<String>) -> Self {
+        self.sop_instance_uid = Some(uid.into());
+        self
+    }
+
+    /// Add an instance to the DICOMDIR,
+    /// stored at the given path relative to the DICOMDIR.
+    ///
+    /// The SOP class and instance UIDs and the transfer syntax of the file
+    /// are taken from the file meta table.
+    pub fn add<P>(mut self, path: P, obj: &FileDicomObject<InMemDicomObject>) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        let meta = obj.meta();
+        let keys = [
+            PATIENT_KEYS,
+            STUDY_KEYS,
+            SERIES_KEYS,
+            instance_record(meta.media_storage_sop_class_uid()).1,
+        ];
+        let attributes = InMemDicomObject::from_element_iter(
+            keys.iter()
+                .flat_map(|keys| keys.iter())
+                .filter_map(|key| obj.get(key.tag))
+                .cloned(),
+        );
+        self.instances.push(Instance {
+            path: path.into(),
+            sop_class_uid: meta.media_storage_sop_class_uid().to_string(),
+            sop_instance_uid: meta.media_storage_sop_instance_uid().to_string(),
+            transfer_syntax_uid: meta.transfer_syntax().to_string(),
+            attributes,
+        });
+        self
+    }
+
+    /// Add the instance in the file at the given path,
+    /// relative to the root directory of the file-set,
+    /// which is where the DICOMDIR is meant to be written.
+    ///
+    /// The file is read up to the pixel data.
+    pub fn add_file<R, P>(self, root: R, path: P) -> Result<Self, BuildDicomDirError>
+    where
+        R: AsRef<Path>,
+        P: AsRef<Path>,
+    {
+        let full_path = root.as_ref().join(path.as_ref());
+        let obj = OpenFileOptions::new()
+            .read_until(tags::PIXEL_DATA)
+            .open_file(&full_path)
+            .context(ReadInstanceSnafu { path: &full_path })?;
+        Ok(self.add(path.as_ref(), &obj))
+    }
+
+    /// Build the DICOMDIR object.
+    ///
+    /// The offsets linking the directory records
+    /// are positions in the encoded file,
+    /// so the DICOMDIR is encoded once to locate each record,
+    /// and the offsets are then filled in.
+    /// Since offsets have a fixed size,
+    /// this does not move the records.
+    /// The object should therefore be written as is,
+    /// with the default write options.
+    pub fn build(&self) -> Result<FileDicomObject<InMemDicomObject>, BuildDicomDirError> {
+        let mut records = Vec::new();
+        flatten(self.record_tree()?, &mut records);
+
+        let meta = FileMetaTableBuilder::new()
+            .media_storage_sop_class_uid(uids::MEDIA_STORAGE_DIRECTORY_STORAGE)
+            .media_storage_sop_instance_uid(self.sop_instance_uid.clone().unwrap_or_else(uuid_uid))
+            .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+            .build()
+            .context(BuildMetaSnafu)?;
+
+        // first pass: locate the records
+        let draft = self.assemble(meta.clone(), &records, &vec![0; records.len()]);
+        let mut data = Vec::new();
+        draft.write_all(&mut data).context(EncodeDicomDirSnafu)?;
+        let encoded = lazy::from_reader(std::io::Cursor::new(data)).context(LocateRecordsSnafu)?;
+        let offsets: Vec<u32> = encoded
+            .get(tags::DIRECTORY_RECORD_SEQUENCE)
+            .and_then(|e| e.items())
+            .unwrap_or_default()
+            .iter()
+            .map(|item| item.offset().unwrap_or(0) as u32)
+            .collect();
+        debug_assert_eq!(offsets.len(), records.len());
+
+        // second pass: link the records
+        Ok(self.assemble(meta, &records, &offsets))
+    }
+
+    /// Arrange the instances into a tree of directory records,
+    /// grouped by patient ID, then by study and series instance UID.
+    fn record_tree(&self) -> Result<Vec<RecordNode>, BuildDicomDirError> {
+        type Series<'a> = Vec<&'a Instance>;
+        type Study<'a> = Vec<(String, Series<'a>)>;
+        type Patient<'a> = Vec<(String, Study<'a>)>;
+
+        fn entry<T: Default>(entries: &mut Vec<(String, T)>, key: String) -> &mut T {
+            let index = match entries.iter().position(|(k, _)| *k == key) {
+                Some(index) => index,
+                None => {
+                    entries.push((key, T::default()));
+                    entries.len() - 1
+                }
+            };
+            &mut entries[index].1
+        }
+
+        let mut patients: Vec<(String, Patient)> = Vec::new();
+        for instance in &self.instances {
+            let studies = entry(&mut patients, instance.string(tags::PATIENT_ID));
+            let series = entry(studies, instance.string(tags::STUDY_INSTANCE_UID));
+            entry(series, instance.string(tags::SERIES_INSTANCE_UID)).push(instance);
+        }
+
+        // each record takes its keys from the first instance below it
+        let series_node = |instances: Series| -> Result<RecordNode, BuildDicomDirError> {
+            Ok(RecordNode {
+                record: record("SERIES", SERIES_KEYS, instances[0]),
+                children: instances
+                    .into_iter()
+                    .map(instance_node)
+                    .collect::<Result<_, _>>()?,
+            })
+        };
+        let study_node = |series: Study| -> Result<RecordNode, BuildDicomDirError> {
+            Ok(RecordNode {
+                record: record("STUDY", STUDY_KEYS, series[0].1[0]),
+                children: series
+                    .into_iter()
+                    .map(|(_, instances)| series_node(instances))
+                    .collect::<Result<_, _>>()?,
+            })
+        };
+        patients
+            .into_iter()
+            .map(|(_, studies)| {
+                Ok(RecordNode {
+                    record: record("PATIENT", PATIENT_KEYS, studies[0].1[0].1[0]),
+                    children: studies
+                        .into_iter()
+                        .map(|(_, series)| study_node(series))
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Assemble the DICOMDIR object,
+    /// with the records linked by the given offsets.
+    fn assemble(
+        &self,
+        meta: FileMetaTable,
+        records: &[FlatRecord],
+        offsets: &[u32],
+    ) -> FileDicomObject<InMemDicomObject> {
+        let offset_of = |index: Option<usize>| index.map(|i| offsets[i]).unwrap_or(0);
+        // the last record of the root directory entity
+        let mut last = records.first().map(|_| 0);
+        while let Some(next) = last.and_then(|i| records[i].next) {
+            last = Some(next);
+        }
+
+        let items: Vec<_> = records
+            .iter()
+            .map(|r| {
+                let mut item = r.record.clone();
+                item.put(DataElement::new(
+                    tags::OFFSET_OF_THE_NEXT_DIRECTORY_RECORD,
+                    VR::UL,
+                    PrimitiveValue::from(offset_of(r.next)),
+                ));
+                item.put(DataElement::new(
+                    tags::OFFSET_OF_REFERENCED_LOWER_LEVEL_DIRECTORY_ENTITY,
+                    VR::UL,
+                    PrimitiveValue::from(offset_of(r.lower)),
+                ));
+                item
+            })
+            .collect();
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::FILE_SET_ID,
+                VR::CS,
+                PrimitiveValue::from(self.file_set_id.clone().unwrap_or_default()),
+            ),
+            DataElement::new(
+                tags::OFFSET_OF_THE_FIRST_DIRECTORY_RECORD_OF_THE_ROOT_DIRECTORY_ENTITY,
+                VR::UL,
+                PrimitiveValue::from(offset_of(records.first().map(|_| 0))),
+            ),
+            DataElement::new(
+                tags::OFFSET_OF_THE_LAST_DIRECTORY_RECORD_OF_THE_ROOT_DIRECTORY_ENTITY,
+                VR::UL,
+                PrimitiveValue::from(offset_of(last)),
+            ),
+            DataElement::new(
+                tags::FILE_SET_CONSISTENCY_FLAG,
+                VR::US,
+                PrimitiveValue::from(0_u16),
+            ),
+            DataElement::new(
+                tags::DIRECTORY_RECORD_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(items),
+            ),
+        ]);
+        FileDicomObject { meta, obj }
+    }
+}
+
+/// Create the record of the given type for an instance,
+/// with the given keys copied from the instance.
+///
+/// Offsets are added when the records are assembled.
+fn record(record_type: &str, keys: &[Attribute], instance: &Instance) -> InMemDicomObject {
+    let mut record = InMemDicomObject::from_element_iter([
+        DataElement::new(
+            tags::RECORD_IN_USE_FLAG,
+            VR::US,
+            PrimitiveValue::from(0xFFFF_u16),
+        ),
+        DataElement::new(tags::DIRECTORY_RECORD_TYPE, VR::CS, record_type),
+    ]);
+    for key in keys {
+        match (instance.attributes.get(key.tag), key.typ) {
+            (Some(elem), _) => {
+                record.put(elem.clone());
+            }
+            (None, Type1 | Type2) => {
+                if key.typ == Type1 {
+                    tracing::warn!(
+                        "{} record of {} is missing {} {}",
+                        record_type,
+                        instance.path.display(),
+                        key.tag,
+                        key.keyword
+                    );
+                }
+                let vr = StandardDataDictionary
+                    .by_tag(key.tag)
+                    .map(|entry| entry.vr().relaxed())
+                    .unwrap_or(VR::UN);
+                record.put(DataElement::empty(key.tag, vr));
+            }
+            (None, _) => {}
+        }
+    }
+    record
+}
+
+/// Create the record node of an instance, referencing its file.
+fn instance_node(instance: &Instance) -> Result<RecordNode, BuildDicomDirError> {
+    let (record_type, keys) = instance_record(&instance.sop_class_uid);
+    let mut record = record(record_type, keys, instance);
+    let file_id = file_id(&instance.path)?;
+    record.put(DataElement::new(
+        tags::REFERENCED_FILE_ID,
+        VR::CS,
+        PrimitiveValue::new_strs(file_id),
+    ));
+    record.put(DataElement::new(
+        tags::REFERENCED_SOP_CLASS_UID_IN_FILE,
+        VR::UI,
+        instance.sop_class_uid.as_str(),
+    ));
+    record.put(DataElement::new(
+        tags::REFERENCED_SOP_INSTANCE_UID_IN_FILE,
+        VR::UI,
+        instance.sop_instance_uid.as_str(),
+    ));
+    record.put(DataElement::new(
+        tags::REFERENCED_TRANSFER_SYNTAX_UID_IN_FILE,
+        VR::UI,
+        instance.transfer_syntax_uid.as_str(),
+    ));
+    Ok(RecordNode {
+        record,
+        children: Vec::new(),
+    })
+}
+
+/// Convert a relative path into the components of a Referenced File ID:
+/// up to 8 components of up to 8 uppercase letters, digits or underscores.
+fn file_id(path: &Path) -> Result<Vec<String>, BuildDicomDirError> {
+    let components = path
+        .components()
+        .map(|component| match component {
+            Component::Normal(name) => name
+                .to_str()
+                .filter(|name| {
+                    !name.is_empty()
+                        && name.len() <= MAX_FILE_ID_COMPONENT_LENGTH
+                        && name
+                            .chars()
+                            .all(|c| matches!(c, 'A'..='Z' | '0'..='9' | '_'))
+                })
+                .map(String::from),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|components| !components.is_empty() && components.len() <= MAX_FILE_ID_COMPONENTS);
+    components.context(InvalidFileIdSnafu { path })
+}
+
+/// Lay out the records of a tree in the order of the Directory Record Sequence,
+/// each record followed by the records below it,
+/// returning the index of the first record of the given entity.
+fn flatten(nodes: Vec<RecordNode>, out: &mut Vec<FlatRecord>) -> Option<usize> {
+    let mut first = None;
+    let mut previous: Option<usize> = None;
+    for node in nodes {
+        let index = out.len();
+        out.push(FlatRecord {
+            record: node.record,
+            next: None,
+            lower: None,
+        });
+        out[index].lower = flatten(node.children, out);
+        match previous {
+            Some(previous) => out[previous].next = Some(index),
+            None => first = Some(index),
+        }
+        previous = Some(index);
+    }
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::InMemElement;
+    use crate::meta::FileMetaTableBuilder;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, VR};
+    use dicom_dictionary_std::uids;
+
+    /// the item tag, as encoded in explicit VR little endian
+    const ITEM_TAG: [u8; 4] = [0xFE, 0xFF, 0x00, 0xE0];
+
+    /// A directory record with links to other records by index,
+    /// to be replaced with their offsets.
+    struct TestRecord {
+        record_type: &'static str,
+        next: Option<usize>,
+        lower: Option<usize>,
+        in_use: bool,
+        attributes: Vec<InMemElement>,
+    }
+
+    fn record(
+        record_type: &'static str,
+        next: Option<usize>,
+        lower: Option<usize>,
+        attributes: Vec<InMemElement>,
+    ) -> TestRecord {
+        TestRecord {
+            record_type,
+            next,
+            lower,
+            in_use: true,
+            attributes,
+        }
+    }
+
+    fn image(next: Option<usize>, file_id: &[&str], instance_number: i32) -> TestRecord {
+        record(
+            "IMAGE",
+            next,
+            None,
+            vec![
+                DataElement::new(
+                    tags::REFERENCED_FILE_ID,
+                    VR::CS,
+                    PrimitiveValue::Strs(file_id.iter().map(|&c| c.into()).collect()),
+                ),
+                DataElement::new(
+                    tags::REFERENCED_SOP_CLASS_UID_IN_FILE,
+                    VR::UI,
+                    uids::CT_IMAGE_STORAGE,
+                ),
+                DataElement::new(
+                    tags::REFERENCED_SOP_INSTANCE_UID_IN_FILE,
+                    VR::UI,
+                    format!("2.25.{}", instance_number),
+                ),
+                DataElement::new(
+                    tags::REFERENCED_TRANSFER_SYNTAX_UID_IN_FILE,
+                    VR::UI,
+                    uids::EXPLICIT_VR_LITTLE_ENDIAN,
+                ),
+                DataElement::new(tags::INSTANCE_NUMBER, VR::IS, instance_number.to_string()),
+            ],
+        )
+    }
+
+    /// Two patients, with one study and one series each,
+    /// and three images in total,
+    /// plus an image record which is no longer in use.
+    fn test_records() -> Vec<TestRecord> {
+        let mut inactive = image(Some(5), &["IMAGES", "P1", "OLD"], 99);
+        inactive.in_use = false;
+        vec![
+            record(
+                "PATIENT",
+                Some(6),
+                Some(1),
+                vec![
+                    DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+                    DataElement::new(tags::PATIENT_ID, VR::LO, "P1"),
+                ],
+            ),
+            record(
+                "STUDY",
+                None,
+                Some(2),
+                vec![
+                    DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, "2.25.1"),
+                    DataElement::new(tags::STUDY_DATE, VR::DA, "20240102"),
+                ],
+            ),
+            record(
+                "SERIES",
+                None,
+                Some(3),
+                vec![
+                    DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, "2.25.1.1"),
+                    DataElement::new(tags::MODALITY, VR::CS, "CT"),
+                    DataElement::new(tags::SERIES_NUMBER, VR::IS, "1"),
+                ],
+            ),
+            image(Some(4), &["IMAGES", "P1", "IM1"], 1),
+            inactive,
+            image(None, &["IMAGES", "P1", "IM2"], 2),
+            record(
+                "PATIENT",
+                None,
+                Some(7),
+                vec![
+                    DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^Jane"),
+                    DataElement::new(tags::PATIENT_ID, VR::LO, "P2"),
+                ],
+            ),
+            record(
+                "STUDY",
+                None,
+                Some(8),
+                vec![DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, "2.25.2")],
+            ),
+            record(
+                "SERIES",
+                None,
+                Some(9),
+                vec![
+                    DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, "2.25.2.1"),
+                    DataElement::new(tags::MODALITY, VR::CS, "MR"),
+                ],
+            ),
+            image(None, &["IMAGES", "P2", "IM1"], 1),
+        ]
+    }
+
+    /// Encode a DICOMDIR with the given records,
+    /// linked by the given offsets of each record.
+    fn encode_dicomdir(records: &[TestRecord], offsets: &[u32]) -> Vec<u8> {
+        let offset_of = |index: Option<usize>| index.map(|i| offsets[i]).unwrap_or(0);
+        let items = records
+            .iter()
+            .map(|r| {
+                let mut item = InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::OFFSET_OF_THE_NEXT_DIRECTORY_RECORD,
+                        VR::UL,
+                        dicom_value!(U32, [offset_of(r.next)]),
+                    ),
+                    DataElement::new(
+                        tags::RECORD_IN_USE_FLAG,
+                        VR::US,
+                        dicom_value!(U16, [if r.in_use { 0xFFFF } else { 0 }]),
+                    ),
+                    DataElement::new(
+                        tags::OFFSET_OF_REFERENCED_LOWER_LEVEL_DIRECTORY_ENTITY,
+                        VR::UL,
+                        dicom_value!(U32, [offset_of(r.lower)]),
+                    ),
+                    DataElement::new(tags::DIRECTORY_RECORD_TYPE, VR::CS, r.record_type),
+                ]);
+                for elem in &r.attributes {
+                    item.put(elem.clone());
+                }
+                item
+            })
+            .collect::<Vec<_>>();
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::FILE_SET_ID, VR::CS, "TEST_SET"),
+            DataElement::new(
+                tags::OFFSET_OF_THE_FIRST_DIRECTORY_RECORD_OF_THE_ROOT_DIRECTORY_ENTITY,
+                VR::UL,
+                dicom_value!(U32, [offsets.first().copied().unwrap_or(0)]),
+            ),
+            DataElement::new(
+                tags::OFFSET_OF_THE_LAST_DIRECTORY_RECORD_OF_THE_ROOT_DIRECTORY_ENTITY,
+                VR::UL,
+                dicom_value!(U32, [offsets.get(6).copied().unwrap_or(0)]),
+            ),
+            DataElement::new(
+                tags::FILE_SET_CONSISTENCY_FLAG,
+                VR::US,
+                dicom_value!(U16, [0]),
+            ),
+            DataElement::new(
+                tags::DIRECTORY_RECORD_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(items),
+            ),
+        ])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .media_storage_sop_class_uid(uids::MEDIA_STORAGE_DIRECTORY_STORAGE)
+                .media_storage_sop_instance_uid("2.25.100")
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN),
+        )
+        .unwrap();
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+        data
+    }
+
+    /// Encode a DICOMDIR with the records correctly linked,
+    /// locating each record by its item tag in a first encoding.
+    fn test_dicomdir() -> Vec<u8> {
+        let records = test_records();
+        let draft = encode_dicomdir(&records, &vec![0; records.len()]);
+        let offsets: Vec<u32> = draft
+            .windows(4)
+            .enumerate()
+            .filter(|(_, w)| *w == ITEM_TAG)
+            .map(|(i, _)| i as u32)
+            .collect();
+        assert_eq!(offsets.len(), records.len());
+        let data = encode_dicomdir(&records, &offsets);
+        // offsets have a fixed size, so the records stay in place
+        assert_eq!(data.len(), draft.len());
+        data
+    }
+
+    #[test]
+    fn read_dicomdir_tree() {
+        let dicomdir = from_reader(std::io::Cursor::new(test_dicomdir())).unwrap();
+        assert_eq!(dicomdir.file_set_id(), Some("TEST_SET"));
+        assert_eq!(
+            dicomdir.meta().media_storage_sop_class_uid(),
+            uids::MEDIA_STORAGE_DIRECTORY_STORAGE
+        );
+
+        let patients = dicomdir.records();
+        assert_eq!(patients.len(), 2);
+        assert_eq!(patients[0].record_type(), &RecordType::Patient);
+        assert_eq!(patients[0].patient_name().as_deref(), Some("Doe^John"));
+        assert_eq!(patients[0].patient_id().as_deref(), Some("P1"));
+        assert_eq!(patients[1].patient_name().as_deref(), Some("Doe^Jane"));
+
+        let study = &patients[0].children()[0];
+        assert_eq!(study.record_type(), &RecordType::Study);
+        assert_eq!(study.study_instance_uid().as_deref(), Some("2.25.1"));
+        assert_eq!(study.study_date().as_deref(), Some("20240102"));
+        let series = &study.children()[0];
+        assert_eq!(series.record_type(), &RecordType::Series);
+        assert_eq!(series.modality().as_deref(), Some("CT"));
+        assert_eq!(series.series_number(), Some(1));
+
+        // the inactive record is skipped
+        let images = series.children();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].instance_number(), Some(1));
+        assert_eq!(images[1].instance_number(), Some(2));
+        assert_eq!(
+            images[1].referenced_file_id(),
+            Some(vec!["IMAGES".to_string(), "P1".into(), "IM2".into()])
+        );
+        assert_eq!(
+            images[1].referenced_sop_class_uid().as_deref(),
+            Some(uids::CT_IMAGE_STORAGE)
+        );
+        assert_eq!(
+            images[1].referenced_sop_instance_uid().as_deref(),
+            Some("2.25.2")
+        );
+        assert_eq!(
+            images[1].referenced_transfer_syntax_uid().as_deref(),
+            Some(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+        );
+        // not read from a file, so paths stay relative
+        assert_eq!(
+            dicomdir.file_path(&images[0]),
+            Some(["IMAGES", "P1", "IM1"].iter().collect())
+        );
+        assert_eq!(dicomdir.file_path(series), None);
+
+        let mr_series = &patients[1].children()[0].children()[0];
+        assert_eq!(mr_series.modality().as_deref(), Some("MR"));
+        assert_eq!(mr_series.children().len(), 1);
+
+        // depth first traversal
+        let types: Vec<_> = dicomdir
+            .iter()
+            .map(|r| r.record_type().as_str().to_string())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "PATIENT", "STUDY", "SERIES", "IMAGE", "IMAGE", "PATIENT", "STUDY", "SERIES",
+                "IMAGE"
+            ]
+        );
+    }
+
+    #[test]
+    fn open_dicomdir_file_and_resolve_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("DICOMDIR");
+        std::fs::write(&path, test_dicomdir()).unwrap();
+
+        let dicomdir = open_file(&path).unwrap();
+        let paths: Vec<_> = dicomdir
+            .iter()
+            .filter(|r| r.record_type() == &RecordType::Image)
+            .filter_map(|r| dicomdir.file_path(r))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                dir.path().join("IMAGES").join("P1").join("IM1"),
+                dir.path().join("IMAGES").join("P1").join("IM2"),
+                dir.path().join("IMAGES").join("P2").join("IM1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_dicomdir_with_broken_links() {
+        let mut records = test_records();
+        let draft = encode_dicomdir(&records, &vec![0; records.len()]);
+        let mut offsets: Vec<u32> = draft
+            .windows(4)
+            .enumerate()
+            .filter(|(_, w)| *w == ITEM_TAG)
+            .map(|(i, _)| i as u32)
+            .collect();
+
+        // a record linking back to an earlier record
+        records[5].next = Some(3);
+        let data = encode_dicomdir(&records, &offsets);
+        let err = from_reader(std::io::Cursor::new(data)).unwrap_err();
+        assert!(
+            matches!(err, DicomDirError::CyclicRecord { offset, .. } if offset == u64::from(offsets[3])),
+            "{:?}",
+            err
+        );
+
+        // a record linking to nowhere
+        records[5].next = None;
+        offsets[9] += 2;
+        let data = encode_dicomdir(&records, &offsets);
+        let err = from_reader(std::io::Cursor::new(data)).unwrap_err();
+        assert!(
+            matches!(err, DicomDirError::DanglingOffset { offset, .. } if offset == u64::from(offsets[9])),
+            "{:?}",
+            err
+        );
+    }
+
+    /// A CT or MR image of the given patient, study and series.
+    fn fixture(
+        patient: (&str, &str),
+        study_uid: &str,
+        series: (&str, &str),
+        sop_instance_uid: &str,
+        instance_number: i32,
+    ) -> FileDicomObject<InMemDicomObject> {
+        let sop_class_uid = match series.1 {
+            "MR" => uids::MR_IMAGE_STORAGE,
+            _ => uids::CT_IMAGE_STORAGE,
+        };
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, sop_class_uid),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, sop_instance_uid),
+            DataElement::new(tags::STUDY_DATE, VR::DA, "20240102"),
+            DataElement::new(tags::STUDY_TIME, VR::TM, "120000"),
+            DataElement::new(tags::ACCESSION_NUMBER, VR::SH, "A1"),
+            DataElement::new(tags::MODALITY, VR::CS, series.1),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, patient.1),
+            DataElement::new(tags::PATIENT_ID, VR::LO, patient.0),
+            DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, study_uid),
+            DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, series.0),
+            DataElement::new(tags::STUDY_ID, VR::SH, "1"),
+            DataElement::new(tags::SERIES_NUMBER, VR::IS, "1"),
+            DataElement::new(tags::INSTANCE_NUMBER, VR::IS, instance_number.to_string()),
+        ])
+        .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+        .unwrap()
+    }
+
+    /// Write three images of two patients in a file-set,
+    /// returning their paths relative to the file-set root.
+    fn write_fixtures(root: &Path) -> Vec<PathBuf> {
+        let fixtures = [
+            (
+                ["IMAGES", "P1", "IM1"],
+                fixture(
+                    ("P1", "Doe^John"),
+                    "2.25.1",
+                    ("2.25.1.1", "CT"),
+                    "2.25.1.1.1",
+                    1,
+                ),
+            ),
+            (
+                ["IMAGES", "P2", "IM1"],
+                fixture(
+                    ("P2", "Doe^Jane"),
+                    "2.25.2",
+                    ("2.25.2.1", "MR"),
+                    "2.25.2.1.1",
+                    1,
+                ),
+            ),
+            (
+                ["IMAGES", "P1", "IM2"],
+                fixture(
+                    ("P1", "Doe^John"),
+                    "2.25.1",
+                    ("2.25.1.1", "CT"),
+                    "2.25.1.1.2",
+                    2,
+                ),
+            ),
+        ];
+        fixtures
+            .iter()
+            .map(|(components, obj)| {
+                let path: PathBuf = components.iter().collect();
+                std::fs::create_dir_all(root.join(&path).parent().unwrap()).unwrap();
+                obj.write_to_file(root.join(&path)).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn build_dicomdir_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = write_fixtures(dir.path());
+        let mut builder = DicomDirBuilder::new()
+            .file_set_id("TEST_SET")
+            .media_storage_sop_instance_uid("2.25.100");
+        for path in &paths {
+            builder = builder.add_file(dir.path(), path).unwrap();
+        }
+        let obj = builder.build().unwrap();
+        assert_eq!(
+            obj.meta().media_storage_sop_class_uid(),
+            uids::MEDIA_STORAGE_DIRECTORY_STORAGE
+        );
+        assert_eq!(obj.meta().media_storage_sop_instance_uid(), "2.25.100");
+        assert_eq!(
+            obj.meta().transfer_syntax(),
+            uids::EXPLICIT_VR_LITTLE_ENDIAN
+        );
+        let dicomdir_path = dir.path().join("DICOMDIR");
+        obj.write_to_file(&dicomdir_path).unwrap();
+
+        // navigate with the reader
+        let dicomdir = open_file(&dicomdir_path).unwrap();
+        assert_eq!(dicomdir.file_set_id(), Some("TEST_SET"));
+        let patients = dicomdir.records();
+        assert_eq!(patients.len(), 2);
+        assert_eq!(patients[0].patient_id().as_deref(), Some("P1"));
+        assert_eq!(patients[0].patient_name().as_deref(), Some("Doe^John"));
+        assert_eq!(patients[1].patient_id().as_deref(), Some("P2"));
+
+        let study = &patients[0].children()[0];
+        assert_eq!(study.record_type(), &RecordType::Study);
+        assert_eq!(study.study_instance_uid().as_deref(), Some("2.25.1"));
+        assert_eq!(study.study_date().as_deref(), Some("20240102"));
+        assert_eq!(study.accession_number().as_deref(), Some("A1"));
+        // type 2 keys are present even if the instances lack them
+        assert_eq!(study.study_description().as_deref(), Some(""));
+        let series = &study.children()[0];
+        assert_eq!(series.modality().as_deref(), Some("CT"));
+        assert_eq!(series.series_number(), Some(1));
+
+        // both images of the first patient in the same series
+        let images = series.children();
+        assert_eq!(images.len(), 2);
+        for (image, (number, path)) in images.iter().zip([(1, &paths[0]), (2, &paths[2])]) {
+            assert_eq!(image.record_type(), &RecordType::Image);
+            assert_eq!(image.instance_number(), Some(number));
+            assert_eq!(dicomdir.file_path(image), Some(dir.path().join(path)));
+            assert_eq!(
+                image.referenced_sop_class_uid().as_deref(),
+                Some(uids::CT_IMAGE_STORAGE)
+            );
+            assert_eq!(
+                image.referenced_sop_instance_uid(),
+                Some(format!("2.25.1.1.{}", number))
+            );
+            assert_eq!(
+                image.referenced_transfer_syntax_uid().as_deref(),
+                Some(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+            );
+        }
+        let mr_image = &patients[1].children()[0].children()[0].children()[0];
+        assert_eq!(
+            dicomdir.file_path(mr_image),
+            Some(dir.path().join(&paths[1]))
+        );
+        assert_eq!(dicomdir.iter().count(), 9);
+        for image in dicomdir
+            .iter()
+            .filter(|r| r.record_type() == &RecordType::Image)
+        {
+            assert!(dicomdir.file_path(image).unwrap().is_file());
+        }
+    }
+
+    #[test]
+    fn built_dicomdir_offsets_match_positions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = DicomDirBuilder::new();
+        for path in write_fixtures(dir.path()) {
+            builder = builder.add_file(dir.path(), path).unwrap();
+        }
+        let mut data = Vec::new();
+        builder.build().unwrap().write_all(&mut data).unwrap();
+
+        let obj = lazy::from_reader(std::io::Cursor::new(&data)).unwrap();
+        let items = obj
+            .get(tags::DIRECTORY_RECORD_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        let positions: Vec<u64> = items.iter().map(|item| item.offset().unwrap()).collect();
+        // patient, study, series, 2 images, patient, study, series, image
+        assert_eq!(positions.len(), 9);
+        for &position in &positions {
+            assert_eq!(data[position as usize..position as usize + 4], ITEM_TAG);
+        }
+
+        let offset = |dataset: &LazyDataSet<_>, tag| read_offset(dataset, tag).unwrap().unwrap();
+        assert_eq!(
+            offset(
+                &obj,
+                tags::OFFSET_OF_THE_FIRST_DIRECTORY_RECORD_OF_THE_ROOT_DIRECTORY_ENTITY
+            ),
+            positions[0]
+        );
+        assert_eq!(
+            offset(
+                &obj,
+                tags::OFFSET_OF_THE_LAST_DIRECTORY_RECORD_OF_THE_ROOT_DIRECTORY_ENTITY
+            ),
+            positions[5]
+        );
+        let links = [
+            (5, 1),
+            (0, 2),
+            (0, 3),
+            (4, 0),
+            (0, 0),
+            (0, 6),
+            (0, 7),
+            (0, 8),
+            (0, 0),
+        ];
+        for (item, (next, lower)) in items.iter().zip(links) {
+            let position = |index| if index == 0 { 0 } else { positions[index] };
+            assert_eq!(
+                offset(item, tags::OFFSET_OF_THE_NEXT_DIRECTORY_RECORD),
+                position(next)
+            );
+            assert_eq!(
+                offset(
+                    item,
+                    tags::OFFSET_OF_REFERENCED_LOWER_LEVEL_DIRECTORY_ENTITY
+                ),
+                position(lower)
+            );
+        }
+    }
+
+    #[test]
+    fn build_dicomdir_with_invalid_file_ids() {
+        let obj = fixture(
+            ("P1", "Doe^John"),
+            "2.25.1",
+            ("2.25.1.1", "CT"),
+            "2.25.1.1.1",
+            1,
+        );
+        for path in [
+            "images/im1.dcm",
+            "IMAGES/IMAGE0001",
+            "../IM1",
+            "/IM1",
+            "A/B/C/D/E/F/G/H/IM1",
+        ] {
+            let err = DicomDirBuilder::new().add(path, &obj).build().unwrap_err();
+            assert!(
+                matches!(&err, BuildDicomDirError::InvalidFileId { path: p, .. } if p == Path::new(path)),
+                "{}: {:?}",
+                path,
+                err
+            );
+        }
+        DicomDirBuilder::new()
+            .add("A/B/C/D/E/F/G/IM_0001", &obj)
+            .build()
+            .unwrap();
+    }
+}
@@ -0,0 +1,440 @@
+//! Comparison of DICOM objects.
+//!
+//! The [`diff`] function compares two in-memory DICOM objects
+//! attribute by attribute,
+//! descending into data set sequences,
+//! and reports every attribute which was added, removed, or changed.
+//!
+//! # Example
+//!
+//! ```
+//! # use dicom_core::{DataElement, VR};
+//! # use dicom_dictionary_std::tags;
+//! # use dicom_object::InMemDicomObject;
+//! use dicom_object::diff::{diff, DiffKind};
+//!
+//! let a = InMemDicomObject::from_element_iter([
+//!     DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+//! ]);
+//! let mut b = a.clone();
+//! b.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^Jane"));
+//!
+//! let entries = diff(&a, &b);
+//! assert_eq!(entries.len(), 1);
+//! assert_eq!(entries[0].kind, DiffKind::Changed);
+//! assert_eq!(entries[0].path.to_string(), "(0010,0010)");
+//! assert_eq!(entries[0].to_string(), "~ (0010,0010) PN Doe^John -> PN Doe^Jane");
+//! ```
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use dicom_core::header::Header;
+use dicom_core::ops::{AttributeSelector, AttributeSelectorStep};
+use dicom_core::value::{PrimitiveValue, Value};
+use dicom_core::{DataDictionary, Tag, VR};
+use itertools::{EitherOrBoth, Itertools};
+
+use crate::mem::{InMemDicomObject, InMemElement};
+
+/// The maximum number of characters of a textual value
+/// shown in a difference entry.
+const MAX_TEXT_LEN: usize = 64;
+
+/// The kind of difference found for an attribute.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum DiffKind {
+    /// The attribute only exists in the second object
+    Added,
+    /// The attribute only exists in the first object
+    Removed,
+    /// The attribute exists in both objects,
+    /// but with a different value representation or value
+    Changed,
+}
+
+/// A compact description of an attribute's value
+/// in a difference entry.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DiffValue {
+    /// the value representation of the attribute
+    pub vr: VR,
+    /// a short textual summary of the value
+    ///
+    /// Binary values and data set sequences are summarized
+    /// by their size instead of being printed in full.
+    pub summary: String,
+}
+
+impl fmt::Display for DiffValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.vr, self.summary)
+    }
+}
+
+/// A single difference between two DICOM objects.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DiffEntry {
+    /// the path to the attribute,
+    /// including the sequence items traversed to reach it
+    pub path: AttributeSelector,
+    /// the kind of difference
+    pub kind: DiffKind,
+    /// the attribute in the first object, if present
+    pub left: Option<DiffValue>,
+    /// the attribute in the second object, if present
+    pub right: Option<DiffValue>,
+}
+
+impl fmt::Display for DiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => write!(f, "~ {} {} -> {}", self.path, left, right),
+            (Some(left), None) => write!(f, "- {} {}", self.path, left),
+            (None, Some(right)) => write!(f, "+ {} {}", self.path, right),
+            (None, None) => write!(f, "? {}", self.path),
+        }
+    }
+}
+
+/// Options for comparing DICOM objects.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct DiffOptions {
+    ignore_group_lengths: bool,
+    ignore_meta: bool,
+}
+
+impl DiffOptions {
+    /// Create a new set of options,
+    /// in which all attributes are compared.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether to ignore group length attributes `(gggg,0000)`.
+    pub fn ignore_group_lengths(mut self, ignore: bool) -> Self {
+        self.ignore_group_lengths = ignore;
+        self
+    }
+
+    /// Set whether to ignore file meta information attributes
+    /// (group `0002`) in the data set.
+    pub fn ignore_meta(mut self, ignore: bool) -> Self {
+        self.ignore_meta = ignore;
+        self
+    }
+
+    fn ignores(&self, tag: Tag) -> bool {
+        (self.ignore_group_lengths && tag.is_group_length())
+            || (self.ignore_meta && tag.group() == 0x0002)
+    }
+}
+
+/// Compare two DICOM objects,
+/// returning all differences found in document order.
+///
+/// Identical objects produce no entries.
+/// See [`diff_with_options`] to ignore some of the attributes.
+pub fn diff<D>(a: &InMemDicomObject<D>, b: &InMemDicomObject<D>) -> Vec<DiffEntry>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    diff_with_options(a, b, DiffOptions::default())
+}
+
+/// Compare two DICOM objects with the given options,
+/// returning all differences found in document order.
+pub fn diff_with_options<D>(
+    a: &InMemDicomObject<D>,
+    b: &InMemDicomObject<D>,
+    options: DiffOptions,
+) -> Vec<DiffEntry>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    let mut entries = Vec::new();
+    diff_impl(a, b, options, &mut Vec::new(), &mut entries);
+    entries
+}
+
+fn diff_impl<D>(
+    a: &InMemDicomObject<D>,
+    b: &InMemDicomObject<D>,
+    options: DiffOptions,
+    parent: &mut Vec<AttributeSelectorStep>,
+    out: &mut Vec<DiffEntry>,
+) where
+    D: DataDictionary,
+    D: Clone,
+{
+    let pairs = a
+        .iter()
+        .merge_join_by(b.iter(), |e1, e2| e1.tag().cmp(&e2.tag()));
+    for pair in pairs {
+        let tag = match &pair {
+            EitherOrBoth::Both(e, _) | EitherOrBoth::Left(e) | EitherOrBoth::Right(e) => e.tag(),
+        };
+        if options.ignores(tag) {
+            continue;
+        }
+        let path = || {
+            AttributeSelector::new(
+                parent
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(AttributeSelectorStep::Tag(tag))),
+            )
+            .expect("path should end with a tag step")
+        };
+
+        match pair {
+            EitherOrBoth::Left(e) => out.push(DiffEntry {
+                path: path(),
+                kind: DiffKind::Removed,
+                left: Some(summarize(e)),
+                right: None,
+            }),
+            EitherOrBoth::Right(e) => out.push(DiffEntry {
+                path: path(),
+                kind: DiffKind::Added,
+                left: None,
+                right: Some(summarize(e)),
+            }),
+            EitherOrBoth::Both(e1, e2) => {
+                if let (Some(items1), Some(items2)) = (e1.items(), e2.items()) {
+                    if items1.len() != items2.len() {
+                        out.push(DiffEntry {
+                            path: path(),
+                            kind: DiffKind::Changed,
+                            left: Some(summarize(e1)),
+                            right: Some(summarize(e2)),
+                        });
+                    }
+                    // compare the items in common
+                    for (i, (item1, item2)) in items1.iter().zip(items2).enumerate() {
+                        parent.push(AttributeSelectorStep::Nested {
+                            tag,
+                            item: i as u32,
+                        });
+                        diff_impl(item1, item2, options, parent, out);
+                        parent.pop();
+                    }
+                } else if e1.vr() != e2.vr() || e1.value() != e2.value() {
+                    out.push(DiffEntry {
+                        path: path(),
+                        kind: DiffKind::Changed,
+                        left: Some(summarize(e1)),
+                        right: Some(summarize(e2)),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Describe the value of an element compactly.
+fn summarize<D>(elem: &InMemElement<D>) -> DiffValue {
+    let summary = match elem.value() {
+        Value::Sequence(seq) => format!("<{} items>", seq.multiplicity()),
+        Value::PixelSequence(seq) => {
+            let len: usize = seq.fragments().iter().map(|f| f.len()).sum();
+            let mut hasher = DefaultHasher::new();
+            seq.fragments().hash(&mut hasher);
+            format!(
+                "<{} fragments, {} bytes, hash {:016x}>",
+                seq.fragments().len(),
+                len,
+                hasher.finish()
+            )
+        }
+        Value::Primitive(value) => summarize_primitive(elem.vr(), value),
+    };
+    DiffValue {
+        vr: elem.vr(),
+        summary,
+    }
+}
+
+fn summarize_primitive(vr: VR, value: &PrimitiveValue) -> String {
+    let is_binary = matches!(
+        vr,
+        VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN
+    );
+    if is_binary {
+        let bytes = value.to_bytes();
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        return format!("<{} bytes, hash {:016x}>", bytes.len(), hasher.finish());
+    }
+
+    let text = value.to_str();
+    let len = text.chars().count();
+    if len > MAX_TEXT_LEN {
+        format!(
+            "{}... ({} characters)",
+            text.chars().take(MAX_TEXT_LEN).collect::<String>(),
+            len
+        )
+    } else {
+        text.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement};
+    use dicom_dictionary_std::tags;
+
+    fn sample() -> InMemDicomObject {
+        crate::dicom_object! {
+            PatientName: "Doe^John",
+            (0x0009, 0x0010): "ACME",
+            (0x0009, 0x1001): "secret",
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "1.2.3.1" },
+                {
+                    ReferencedSOPClassUID: "1.2.840.10008.5.1.4.1.1.2",
+                    ReferencedSOPInstanceUID: "1.2.3.2",
+                },
+            ],
+            SliceThickness: "1.5",
+        }
+    }
+
+    #[test]
+    fn identical_objects_have_no_diff() {
+        let a = sample();
+        assert_eq!(diff(&a, &a.clone()), vec![]);
+        assert_eq!(diff(&a, &sample()), vec![]);
+    }
+
+    #[test]
+    fn diff_top_level_changes() {
+        let a = sample();
+        let mut b = sample();
+        b.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^Jane"));
+        b.remove_element(Tag(0x0009, 0x1001));
+        b.put(DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, 64)));
+
+        let entries = diff(&a, &b);
+        let changes: Vec<_> = entries.iter().map(|e| (e.path.clone(), e.kind)).collect();
+        assert_eq!(
+            changes,
+            vec![
+                (Tag(0x0009, 0x1001).into(), DiffKind::Removed),
+                (tags::PATIENT_NAME.into(), DiffKind::Changed),
+                (tags::ROWS.into(), DiffKind::Added),
+            ]
+        );
+        assert!(entries[0]
+            .to_string()
+            .starts_with("- (0009,1001) UN <6 bytes, hash "));
+        assert_eq!(
+            entries[1],
+            DiffEntry {
+                path: tags::PATIENT_NAME.into(),
+                kind: DiffKind::Changed,
+                left: Some(DiffValue {
+                    vr: VR::PN,
+                    summary: "Doe^John".to_string(),
+                }),
+                right: Some(DiffValue {
+                    vr: VR::PN,
+                    summary: "Doe^Jane".to_string(),
+                }),
+            }
+        );
+        assert_eq!(entries[2].to_string(), "+ (0028,0010) US 64");
+    }
+
+    #[test]
+    fn diff_inside_sequence_item() {
+        let a = sample();
+        let mut b = sample();
+        b.item_mut(tags::REFERENCED_IMAGE_SEQUENCE, 1)
+            .unwrap()
+            .put(DataElement::new(
+                tags::REFERENCED_SOP_INSTANCE_UID,
+                VR::UI,
+                "1.2.3.99",
+            ));
+
+        let entries = diff(&a, &b);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].path,
+            AttributeSelector::from((
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                1,
+                tags::REFERENCED_SOP_INSTANCE_UID
+            ))
+        );
+        assert_eq!(entries[0].kind, DiffKind::Changed);
+        assert_eq!(
+            entries[0].to_string(),
+            "~ (0008,1140)[1].(0008,1155) UI 1.2.3.2 -> UI 1.2.3.99"
+        );
+
+        // different number of items
+        b.remove_item(tags::REFERENCED_IMAGE_SEQUENCE, 0).unwrap();
+        let entries = diff(&a, &b);
+        assert_eq!(
+            entries[0].to_string(),
+            "~ (0008,1140) SQ <2 items> -> SQ <1 items>"
+        );
+    }
+
+    #[test]
+    fn diff_options_and_binary_values() {
+        let mut a = sample();
+        a.put(DataElement::new(
+            Tag(0x0008, 0x0000),
+            VR::UL,
+            dicom_value!(U32, 100),
+        ));
+        a.put(DataElement::new(
+            tags::TRANSFER_SYNTAX_UID,
+            VR::UI,
+            "1.2.840.10008.1.2.1",
+        ));
+        a.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PrimitiveValue::from(vec![0_u8; 1 << 20]),
+        ));
+        let mut b = sample();
+        b.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PrimitiveValue::from(vec![1_u8; 1 << 20]),
+        ));
+        b.put(DataElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(a.items(tags::REFERENCED_IMAGE_SEQUENCE).unwrap().to_vec()),
+        ));
+
+        let entries = diff(&a, &b);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].path, Tag(0x0002, 0x0010).into());
+        assert_eq!(entries[1].path, Tag(0x0008, 0x0000).into());
+        let pixel_data = &entries[2];
+        assert_eq!(pixel_data.kind, DiffKind::Changed);
+        let left = &pixel_data.left.as_ref().unwrap().summary;
+        let right = &pixel_data.right.as_ref().unwrap().summary;
+        assert!(left.starts_with("<1048576 bytes, hash "));
+        assert!(right.starts_with("<1048576 bytes, hash "));
+        assert_ne!(left, right);
+
+        let options = DiffOptions::new()
+            .ignore_group_lengths(true)
+            .ignore_meta(true);
+        let entries = diff_with_options(&a, &b, options);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, tags::PIXEL_DATA.into());
+    }
+}
@@ -0,0 +1,373 @@
+//! Extraction of encapsulated documents.
+//!
+//! Encapsulated PDF, CDA, and 3D model objects (STL, OBJ, and MTL)
+//! carry a document as is in _Encapsulated Document_ (0042,0011),
+//! with its media type in _MIME Type of Encapsulated Document_ (0042,0012).
+//! Since DICOM values have an even length,
+//! a document of odd length is followed by a padding byte,
+//! and its actual length may be recorded in
+//! _Encapsulated Document Length_ (0042,0015).
+//!
+//! [`InMemDicomObject::encapsulated_document`] checks that the object
+//! is of an encapsulated document storage SOP class
+//! and that the document content is consistent with its MIME type,
+//! and obtains the document without padding.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_object::open_file;
+//!
+//! let obj = open_file("report.dcm")?;
+//! let document = obj.encapsulated_document()?;
+//! if document.mime_type() == "application/pdf" {
+//!     document.write_to_file("report.pdf")?;
+//! }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`InMemDicomObject::encapsulated_document`]: crate::InMemDicomObject::encapsulated_document
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use dicom_core::{DataDictionary, Tag};
+use dicom_dictionary_std::{tags, uids};
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::mem::InMemDicomObject;
+
+/// The SOP classes of objects holding an encapsulated document.
+pub const ENCAPSULATED_DOCUMENT_SOP_CLASSES: &[&str] = &[
+    uids::ENCAPSULATED_PDF_STORAGE,
+    uids::ENCAPSULATED_CDA_STORAGE,
+    uids::ENCAPSULATED_STL_STORAGE,
+    uids::ENCAPSULATED_OBJ_STORAGE,
+    uids::ENCAPSULATED_MTL_STORAGE,
+];
+
+/// An error which may occur when extracting an encapsulated document.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum EncapsulatedDocumentError {
+    /// Missing attribute {tag}
+    MissingAttribute { tag: Tag, backtrace: Backtrace },
+    /// Invalid value of {tag}
+    InvalidValue { tag: Tag, backtrace: Backtrace },
+    /// SOP Class {sop_class_uid} is not an encapsulated document storage class
+    UnsupportedSopClass {
+        sop_class_uid: String,
+        backtrace: Backtrace,
+    },
+    /// Encapsulated document has {actual} bytes, fewer than its declared length of {expected}
+    DocumentLength {
+        expected: u32,
+        actual: usize,
+        backtrace: Backtrace,
+    },
+    /// Encapsulated document content does not match MIME type {mime_type}
+    MimeTypeMismatch {
+        mime_type: String,
+        backtrace: Backtrace,
+    },
+    /// Could not write encapsulated document
+    WriteDocument {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+}
+
+pub type Result<T, E = EncapsulatedDocumentError> = std::result::Result<T, E>;
+
+/// A document encapsulated in a DICOM object.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncapsulatedDocument<'a> {
+    mime_type: String,
+    title: Option<String>,
+    data: Cow<'a, [u8]>,
+}
+
+impl<'a> EncapsulatedDocument<'a> {
+    /// Obtain the encapsulated document of the given object.
+    pub(crate) fn from_object<D>(obj: &'a InMemDicomObject<D>) -> Result<Self>
+    where
+        D: DataDictionary + Clone,
+    {
+        let sop_class_uid = text(obj, tags::SOP_CLASS_UID)?.context(MissingAttributeSnafu {
+            tag: tags::SOP_CLASS_UID,
+        })?;
+        ensure!(
+            ENCAPSULATED_DOCUMENT_SOP_CLASSES.contains(&sop_class_uid.as_str()),
+            UnsupportedSopClassSnafu { sop_class_uid }
+        );
+
+        let mime_type = text(obj, tags::MIME_TYPE_OF_ENCAPSULATED_DOCUMENT)?.context(
+            MissingAttributeSnafu {
+                tag: tags::MIME_TYPE_OF_ENCAPSULATED_DOCUMENT,
+            },
+        )?;
+        let title = text(obj, tags::DOCUMENT_TITLE)?.filter(|title| !title.is_empty());
+
+        let tag = tags::ENCAPSULATED_DOCUMENT;
+        let mut data = obj
+            .get(tag)
+            .context(MissingAttributeSnafu { tag })?
+            .to_bytes()
+            .ok()
+            .context(InvalidValueSnafu { tag })?;
+
+        let tag = tags::ENCAPSULATED_DOCUMENT_LENGTH;
+        match obj.get(tag) {
+            Some(e) => {
+                let expected: u32 = e.to_int().ok().context(InvalidValueSnafu { tag })?;
+                ensure!(
+                    data.len() >= expected as usize,
+                    DocumentLengthSnafu {
+                        expected,
+                        actual: data.len(),
+                    }
+                );
+                truncate(&mut data, expected as usize);
+            }
+            None => {
+                // without a declared length,
+                // only remove padding which cannot be part of the document
+                if is_textual(&mime_type) && data.last() == Some(&0) {
+                    let len = data.len() - 1;
+                    truncate(&mut data, len);
+                }
+            }
+        }
+
+        ensure!(
+            magic_matches(&mime_type, &data),
+            MimeTypeMismatchSnafu { mime_type }
+        );
+
+        Ok(EncapsulatedDocument {
+            mime_type,
+            title,
+            data,
+        })
+    }
+
+    /// The MIME type of the document, such as `application/pdf`.
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    /// The title of the document, from _Document Title_ (0042,0010).
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The bytes of the document, without padding.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Take the bytes of the document, without padding.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data.into_owned()
+    }
+
+    /// Write the document to the given writer.
+    pub fn write_to<W: Write>(&self, mut to: W) -> Result<()> {
+        to.write_all(&self.data).context(WriteDocumentSnafu)
+    }
+
+    /// Write the document to a file at the given path,
+    /// replacing any existing file.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = File::create(path).context(WriteDocumentSnafu)?;
+        self.write_to(file)
+    }
+}
+
+/// Retrieve a textual attribute without padding,
+/// or `None` if it is absent.
+fn text<D>(obj: &InMemDicomObject<D>, tag: Tag) -> Result<Option<String>>
+where
+    D: DataDictionary + Clone,
+{
+    obj.get(tag)
+        .map(|e| {
+            e.to_str()
+                .map(|s| s.trim_end_matches([' ', '\0']).to_string())
+                .ok()
+                .context(InvalidValueSnafu { tag })
+        })
+        .transpose()
+}
+
+fn truncate(data: &mut Cow<'_, [u8]>, len: usize) {
+    match data {
+        Cow::Borrowed(bytes) => *bytes = &bytes[..len],
+        Cow::Owned(bytes) => bytes.truncate(len),
+    }
+}
+
+/// The media type without parameters, in lower case.
+fn essence(mime_type: &str) -> String {
+    mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Whether documents of the given MIME type are text,
+/// and so never end with a NUL byte.
+fn is_textual(mime_type: &str) -> bool {
+    let essence = essence(mime_type);
+    essence == "application/pdf"
+        || essence.starts_with("text/")
+        || essence.ends_with("/xml")
+        || essence.ends_with("+xml")
+}
+
+/// Check the leading bytes of the document against its MIME type.
+///
+/// PDF documents must start with `%PDF-`,
+/// and documents of any other type must not.
+fn magic_matches(mime_type: &str, data: &[u8]) -> bool {
+    const PDF_MAGIC: &[u8] = b"%PDF-";
+    (essence(mime_type) == "application/pdf") == data.starts_with(PDF_MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{dicom_value, DataElement, VR};
+
+    /// A PDF document of odd length.
+    const PDF: &[u8] = b"%PDF-1.4\n1 0 obj << >> endobj\ntrailer << /Root 1 0 R >>\n%%EOF";
+
+    fn pdf_object(document: &[u8]) -> InMemDicomObject {
+        let mut data = document.to_vec();
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::SOP_CLASS_UID,
+                VR::UI,
+                PrimitiveValue::from(uids::ENCAPSULATED_PDF_STORAGE),
+            ),
+            DataElement::new(tags::DOCUMENT_TITLE, VR::ST, "Report "),
+            DataElement::new(
+                tags::MIME_TYPE_OF_ENCAPSULATED_DOCUMENT,
+                VR::LO,
+                "application/pdf ",
+            ),
+            DataElement::new(
+                tags::ENCAPSULATED_DOCUMENT,
+                VR::OB,
+                PrimitiveValue::from(data),
+            ),
+            DataElement::new(
+                tags::ENCAPSULATED_DOCUMENT_LENGTH,
+                VR::UL,
+                dicom_value!(U32, [document.len() as u32]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn extract_odd_length_pdf() {
+        assert_eq!(PDF.len() % 2, 1);
+        let obj = pdf_object(PDF);
+        let document = obj.encapsulated_document().unwrap();
+        assert_eq!(document.mime_type(), "application/pdf");
+        assert_eq!(document.title(), Some("Report"));
+        assert_eq!(document.data(), PDF);
+
+        let mut out = Vec::new();
+        document.write_to(&mut out).unwrap();
+        assert_eq!(out, PDF);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.pdf");
+        document.write_to_file(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), PDF);
+
+        // the padding is also removed without a declared length
+        let mut obj = pdf_object(PDF);
+        obj.remove_element(tags::ENCAPSULATED_DOCUMENT_LENGTH);
+        let document = obj.encapsulated_document().unwrap();
+        assert_eq!(document.into_data(), PDF);
+
+        // an even length document is untouched
+        let even = &PDF[..PDF.len() - 1];
+        let obj = pdf_object(even);
+        assert_eq!(obj.encapsulated_document().unwrap().data(), even);
+
+        // a declared length past the end of the data is reported
+        let mut obj = pdf_object(PDF);
+        obj.put(DataElement::new(
+            tags::ENCAPSULATED_DOCUMENT_LENGTH,
+            VR::UL,
+            dicom_value!(U32, [1000]),
+        ));
+        assert!(matches!(
+            obj.encapsulated_document(),
+            Err(EncapsulatedDocumentError::DocumentLength { expected: 1000, .. })
+        ));
+    }
+
+    #[test]
+    fn mime_type_mismatch_is_reported() {
+        let cda = b"<?xml version=\"1.0\"?><ClinicalDocument/>";
+
+        // declared as PDF, but not a PDF
+        let obj = pdf_object(cda);
+        assert!(matches!(
+            obj.encapsulated_document(),
+            Err(EncapsulatedDocumentError::MimeTypeMismatch { mime_type, .. })
+                if mime_type == "application/pdf"
+        ));
+
+        // a PDF declared as something else
+        let mut obj = pdf_object(PDF);
+        obj.put(DataElement::new(
+            tags::MIME_TYPE_OF_ENCAPSULATED_DOCUMENT,
+            VR::LO,
+            "text/XML",
+        ));
+        assert!(matches!(
+            obj.encapsulated_document(),
+            Err(EncapsulatedDocumentError::MimeTypeMismatch { .. })
+        ));
+
+        // consistent CDA document
+        let mut obj = pdf_object(cda);
+        obj.put(DataElement::new(
+            tags::SOP_CLASS_UID,
+            VR::UI,
+            PrimitiveValue::from(uids::ENCAPSULATED_CDA_STORAGE),
+        ));
+        obj.put(DataElement::new(
+            tags::MIME_TYPE_OF_ENCAPSULATED_DOCUMENT,
+            VR::LO,
+            "text/XML",
+        ));
+        assert_eq!(obj.encapsulated_document().unwrap().data(), cda);
+
+        // not an encapsulated document
+        obj.put(DataElement::new(
+            tags::SOP_CLASS_UID,
+            VR::UI,
+            PrimitiveValue::from(uids::CT_IMAGE_STORAGE),
+        ));
+        assert!(matches!(
+            obj.encapsulated_document(),
+            Err(EncapsulatedDocumentError::UnsupportedSopClass { sop_class_uid, .. })
+                if sop_class_uid == uids::CT_IMAGE_STORAGE
+        ));
+    }
+}
@@ -1,9 +1,10 @@
 use dicom_core::{DataDictionary, Tag};
 use dicom_dictionary_std::StandardDataDictionary;
 use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
+use dicom_parser::dataset::read::ReadOptions;
 use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
 
-use crate::{DefaultDicomObject, ReadError};
+use crate::{DefaultDicomObject, FileDicomObject, FileMetaTable, InMemDicomObject, ReadError};
 use std::io::Read;
 use std::path::Path;
 
@@ -20,6 +21,29 @@ where
     OpenFileOptions::new().from_reader(file)
 }
 
+/// Create a DICOM object from the bytes of a DICOM file in memory,
+/// returning its file meta table and data set separately.
+///
+/// The 128-byte preamble is skipped if present.
+/// The file system is never accessed,
+/// which makes this function suitable for targets without one.
+///
+/// # Example
+///
+/// ```no_run
+/// # use dicom_dictionary_std::tags;
+/// # let bytes: Vec<u8> = Vec::new();
+/// let (meta, obj) = dicom_object::from_slice(&bytes)?;
+/// println!("Transfer syntax: {}", meta.transfer_syntax());
+/// let patient_name = obj.element(tags::PATIENT_NAME)?.to_str()?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn from_slice(bytes: &[u8]) -> Result<(FileMetaTable, InMemDicomObject)> {
+    OpenFileOptions::new()
+        .from_reader(bytes)
+        .map(FileDicomObject::into_parts)
+}
+
 /// Create a DICOM object by reading from a file.
 ///
 /// This function assumes the standard file encoding structure: 128-byte
@@ -58,6 +82,7 @@ pub struct OpenFileOptions<D = StandardDataDictionary, T = TransferSyntaxRegistr
     ts_index: T,
     read_until: Option<Tag>,
     read_preamble: ReadPreamble,
+    read_options: ReadOptions,
 }
 
 impl OpenFileOptions {
@@ -92,6 +117,16 @@ impl<D, T> OpenFileOptions<D, T> {
         self
     }
 
+    /// Set the options for reading the data set,
+    /// such as the strategy for odd value lengths
+    /// or the policy for duplicate data elements.
+    ///
+    /// The file meta group is always read with the default options.
+    pub fn read_options(mut self, options: ReadOptions) -> Self {
+        self.read_options = options;
+        self
+    }
+
     /// Set the transfer syntax index to use when reading the file.
     pub fn tranfer_syntax_index<Tr>(self, ts_index: Tr) -> OpenFileOptions<D, Tr>
     where
@@ -101,6 +136,7 @@ impl<D, T> OpenFileOptions<D, T> {
             data_dictionary: self.data_dictionary,
             read_until: self.read_until,
             read_preamble: self.read_preamble,
+            read_options: self.read_options,
             ts_index,
         }
     }
@@ -115,12 +151,30 @@ impl<D, T> OpenFileOptions<D, T> {
             data_dictionary: dict,
             read_until: self.read_until,
             read_preamble: self.read_preamble,
+            read_options: self.read_options,
             ts_index: self.ts_index,
         }
     }
 
     /// Open the file at the given path.
     pub fn open_file<P>(self, path: P) -> Result<DefaultDicomObject<D>>
+    where
+        P: AsRef<Path>,
+        D: DataDictionary,
+        D: Clone,
+        T: TransferSyntaxIndex,
+    {
+        self.open_file_partial(path)
+            .and_then(PartialObject::into_result)
+    }
+
+    /// Open the file at the given path,
+    /// keeping the data elements read before any failure
+    /// to read the data set.
+    ///
+    /// Failing to open the file or to read its file meta group
+    /// is still reported as an error.
+    pub fn open_file_partial<P>(self, path: P) -> Result<PartialObject<DefaultDicomObject<D>>>
     where
         P: AsRef<Path>,
         D: DataDictionary,
@@ -133,6 +187,7 @@ impl<D, T> OpenFileOptions<D, T> {
             self.ts_index,
             self.read_until,
             self.read_preamble,
+            self.read_options,
         )
     }
 
@@ -141,7 +196,32 @@ impl<D, T> OpenFileOptions<D, T> {
     /// This method assumes
     /// the standard file encoding structure without the preamble:
     /// file meta group, followed by the rest of the data set.
+    ///
+    /// The source is buffered and then handled as a trait object,
+    /// so using this method with several types of sources
+    /// does not duplicate the data set reader for each of them.
+    /// This only applies to the object reading entry points:
+    /// [`DataSetReader`](dicom_parser::DataSetReader)
+    /// and the stateful decoders used directly
+    /// are still generic over the type of source.
     pub fn from_reader<R>(self, from: R) -> Result<DefaultDicomObject<D>>
+    where
+        R: Read,
+        D: DataDictionary,
+        D: Clone,
+        T: TransferSyntaxIndex,
+    {
+        self.from_reader_partial(from)
+            .and_then(PartialObject::into_result)
+    }
+
+    /// Obtain a DICOM object by reading from a byte source,
+    /// keeping the data elements read before any failure
+    /// to read the data set.
+    ///
+    /// Failing to read the file meta group
+    /// is still reported as an error.
+    pub fn from_reader_partial<R>(self, from: R) -> Result<PartialObject<DefaultDicomObject<D>>>
     where
         R: Read,
         D: DataDictionary,
@@ -154,10 +234,78 @@ impl<D, T> OpenFileOptions<D, T> {
             self.ts_index,
             self.read_until,
             self.read_preamble,
+            self.read_options,
         )
     }
 }
 
+/// A DICOM object along with the error which interrupted its reading,
+/// if any.
+///
+/// When the data set could not be read to the end,
+/// such as when the file is truncated,
+/// the object contains the data elements read up to that point.
+/// Sequences and items cut short are kept with the items
+/// and data elements which were read completely.
+///
+/// # Example
+///
+/// ```no_run
+/// # use dicom_object::OpenFileOptions;
+/// let partial = OpenFileOptions::new().open_file_partial("path/to/file.dcm")?;
+/// if let Some(e) = partial.error() {
+///     eprintln!("file was only partially read: {}", e);
+/// }
+/// let obj = partial.into_object();
+/// # Result::<(), Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug)]
+pub struct PartialObject<O> {
+    object: O,
+    error: Option<ReadError>,
+}
+
+impl<O> PartialObject<O> {
+    pub(crate) fn new(object: O, error: Option<ReadError>) -> Self {
+        PartialObject { object, error }
+    }
+
+    /// Retrieve the object read.
+    pub fn object(&self) -> &O {
+        &self.object
+    }
+
+    /// Retrieve the error which interrupted reading,
+    /// or `None` if the data set was read in full.
+    pub fn error(&self) -> Option<&ReadError> {
+        self.error.as_ref()
+    }
+
+    /// Whether the data set was read in full.
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Move out the object read, discarding the error if any.
+    pub fn into_object(self) -> O {
+        self.object
+    }
+
+    /// Split into the object read and the error which interrupted reading.
+    pub fn into_parts(self) -> (O, Option<ReadError>) {
+        (self.object, self.error)
+    }
+
+    /// Convert into a result,
+    /// discarding the object if reading was interrupted.
+    pub fn into_result(self) -> Result<O> {
+        match self.error {
+            None => Ok(self.object),
+            Some(e) => Err(e),
+        }
+    }
+}
+
 /// An enumerate of supported options for
 /// whether to read the 128-byte DICOM file preamble.
 #[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
@@ -175,3 +323,122 @@ pub enum ReadPreamble {
     /// thus assuming that the original source always has it.
     Always,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::meta::FileMetaTableBuilder;
+    use crate::{from_slice, ReadError};
+    use dicom_core::{DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::{tags, uids};
+
+    fn fixture() -> Vec<u8> {
+        let mut obj = crate::InMemDicomObject::new_empty();
+        obj.put(DataElement::new(
+            tags::PATIENT_NAME,
+            VR::PN,
+            PrimitiveValue::from("Doe^John"),
+        ));
+        obj.put(DataElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::SQ,
+            dicom_core::value::DataSetSequence::from(vec![
+                crate::InMemDicomObject::from_element_iter([DataElement::new(
+                    tags::REFERENCED_SOP_INSTANCE_UID,
+                    VR::UI,
+                    PrimitiveValue::from("2.25.1"),
+                )]),
+            ]),
+        ));
+        let obj = obj
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)
+                    .media_storage_sop_instance_uid("2.25.170")
+                    .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN),
+            )
+            .unwrap();
+        let mut bytes = Vec::new();
+        obj.write_all(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn from_slice_with_preamble() {
+        let bytes = fixture();
+        assert_eq!(&bytes[128..132], b"DICM");
+
+        let (meta, obj) = from_slice(&bytes).unwrap();
+        assert_eq!(meta.media_storage_sop_instance_uid(), "2.25.170");
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Doe^John"
+        );
+    }
+
+    #[test]
+    fn from_slice_without_preamble() {
+        let bytes = fixture();
+
+        let (meta, obj) = from_slice(&bytes[128..]).unwrap();
+        assert_eq!(meta.transfer_syntax(), uids::EXPLICIT_VR_LITTLE_ENDIAN);
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Doe^John"
+        );
+    }
+
+    #[test]
+    fn from_slice_truncated_meta() {
+        let bytes = fixture();
+
+        let err = from_slice(&bytes[..160]).unwrap_err();
+        assert!(matches!(err, ReadError::ParseMetaDataSet { .. }));
+    }
+
+    /// Write the given object back, for comparison
+    fn to_bytes(obj: &crate::DefaultDicomObject) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        obj.write_all(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn same_object_from_any_source() {
+        use crate::{open_file, OpenFileOptions};
+        use std::io::{Cursor, Read};
+
+        let bytes = fixture();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.dcm");
+        std::fs::write(&path, &bytes).unwrap();
+
+        let from_file = open_file(&path).unwrap();
+        assert_eq!(to_bytes(&from_file), bytes);
+
+        let from_cursor = OpenFileOptions::new()
+            .from_reader(Cursor::new(&bytes[128..]))
+            .unwrap();
+        assert_eq!(to_bytes(&from_cursor), bytes);
+
+        let from_fs_file = OpenFileOptions::new()
+            .from_reader(std::fs::File::open(&path).unwrap())
+            .unwrap();
+        assert_eq!(to_bytes(&from_fs_file), bytes);
+
+        let boxed: Box<dyn Read> = Box::new(Cursor::new(bytes[128..].to_vec()));
+        let from_boxed = OpenFileOptions::new().from_reader(boxed).unwrap();
+        assert_eq!(to_bytes(&from_boxed), bytes);
+
+        // truncated data is reported the same way
+        let truncated = &bytes[128..bytes.len() - 3];
+        let boxed: Box<dyn Read> = Box::new(Cursor::new(truncated.to_vec()));
+        for partial in [
+            OpenFileOptions::new().from_reader_partial(Cursor::new(truncated)),
+            OpenFileOptions::new().from_reader_partial(boxed),
+        ] {
+            let partial = partial.unwrap();
+            assert!(partial.error().is_some());
+            assert_eq!(partial.object().meta(), from_file.meta());
+        }
+    }
+}
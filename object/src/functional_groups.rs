@@ -0,0 +1,398 @@
+//! Functional groups of enhanced multi-frame objects.
+//!
+//! Enhanced multi-frame objects, such as Enhanced CT and Enhanced MR images,
+//! describe their frames with functional group macros,
+//! each held in a sequence attribute of a single item,
+//! such as the _Plane Position Sequence_ (0020,9113).
+//! Macros which are the same for all frames are in the
+//! _Shared Functional Groups Sequence_ (5200,9229),
+//! while those which vary are in the item of each frame
+//! in the _Per-Frame Functional Groups Sequence_ (5200,9230).
+//!
+//! [`InMemDicomObject::functional_groups`] gathers the functional groups of a frame,
+//! taking each macro from the frame's item if present there,
+//! or from the shared item otherwise.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_object::open_file;
+//!
+//! let obj = open_file("enhanced_ct.dcm")?;
+//! let frame = obj.functional_groups(36)?;
+//! println!("Image Position (Patient): {:?}", frame.image_position_patient());
+//! println!("Pixel Spacing: {:?}", frame.pixel_spacing());
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`InMemDicomObject::functional_groups`]: crate::InMemDicomObject::functional_groups
+use std::convert::TryInto;
+
+use dicom_core::value::PrimitiveValue;
+use dicom_core::{DataDictionary, Tag};
+use dicom_dictionary_std::tags;
+use snafu::{Backtrace, Snafu};
+
+use crate::mem::InMemDicomObject;
+
+/// An error which may occur when accessing the functional groups of a frame.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+#[snafu(visibility(pub(crate)))]
+pub enum FunctionalGroupsError {
+    /// Missing Per-Frame Functional Groups Sequence
+    MissingPerFrameFunctionalGroups { backtrace: Backtrace },
+    /// Frame index {frame_index} is out of range for {number_of_frames} frames
+    FrameOutOfRange {
+        frame_index: u32,
+        number_of_frames: u32,
+        backtrace: Backtrace,
+    },
+}
+
+/// The functional groups of a frame in an enhanced multi-frame object,
+/// with the frame's own functional groups overlaid on the shared ones.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameFunctionalGroups<'a, D> {
+    frame_index: u32,
+    shared: Option<&'a InMemDicomObject<D>>,
+    per_frame: &'a InMemDicomObject<D>,
+}
+
+impl<'a, D> FrameFunctionalGroups<'a, D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    pub(crate) fn new(
+        frame_index: u32,
+        shared: Option<&'a InMemDicomObject<D>>,
+        per_frame: &'a InMemDicomObject<D>,
+    ) -> Self {
+        FrameFunctionalGroups {
+            frame_index,
+            shared,
+            per_frame,
+        }
+    }
+
+    /// The index of the frame, starting from 0.
+    pub fn frame_index(&self) -> u32 {
+        self.frame_index
+    }
+
+    /// The item of the functional group macro in the given sequence,
+    /// from the frame's functional groups if present there,
+    /// or from the shared functional groups otherwise.
+    pub fn group(&self, sequence: Tag) -> Option<&'a InMemDicomObject<D>> {
+        let first_item = |obj: &'a InMemDicomObject<D>| obj.items(sequence)?.first();
+        first_item(self.per_frame).or_else(|| first_item(self.shared?))
+    }
+
+    /// Whether the functional group macro in the given sequence
+    /// is specific to this frame.
+    pub fn is_per_frame(&self, sequence: Tag) -> bool {
+        self.per_frame.get(sequence).is_some()
+    }
+
+    /// _Pixel Spacing_ (0028,0030) in the _Pixel Measures Sequence_ (0028,9110):
+    /// the distance between the centers of adjacent rows and adjacent columns, in mm.
+    pub fn pixel_spacing(&self) -> Option<[f64; 2]> {
+        self.floats(tags::PIXEL_MEASURES_SEQUENCE, tags::PIXEL_SPACING)?
+            .try_into()
+            .ok()
+    }
+
+    /// _Slice Thickness_ (0018,0050) in the _Pixel Measures Sequence_ (0028,9110), in mm.
+    pub fn slice_thickness(&self) -> Option<f64> {
+        self.float(tags::PIXEL_MEASURES_SEQUENCE, tags::SLICE_THICKNESS)
+    }
+
+    /// _Spacing Between Slices_ (0018,0088)
+    /// in the _Pixel Measures Sequence_ (0028,9110), in mm.
+    pub fn spacing_between_slices(&self) -> Option<f64> {
+        self.float(tags::PIXEL_MEASURES_SEQUENCE, tags::SPACING_BETWEEN_SLICES)
+    }
+
+    /// _Image Position (Patient)_ (0020,0032)
+    /// in the _Plane Position Sequence_ (0020,9113):
+    /// the coordinates of the center of the first voxel of the frame, in mm.
+    pub fn image_position_patient(&self) -> Option<[f64; 3]> {
+        self.floats(tags::PLANE_POSITION_SEQUENCE, tags::IMAGE_POSITION_PATIENT)?
+            .try_into()
+            .ok()
+    }
+
+    /// _Image Orientation (Patient)_ (0020,0037)
+    /// in the _Plane Orientation Sequence_ (0020,9116):
+    /// the direction cosines of the first row and the first column of the frame.
+    pub fn image_orientation_patient(&self) -> Option<[f64; 6]> {
+        self.floats(
+            tags::PLANE_ORIENTATION_SEQUENCE,
+            tags::IMAGE_ORIENTATION_PATIENT,
+        )?
+        .try_into()
+        .ok()
+    }
+
+    /// _Window Center_ (0028,1050) in the _Frame VOI LUT Sequence_ (0028,9132).
+    ///
+    /// Only the first window is returned if several are defined.
+    pub fn window_center(&self) -> Option<f64> {
+        self.float(tags::FRAME_VOILUT_SEQUENCE, tags::WINDOW_CENTER)
+    }
+
+    /// _Window Width_ (0028,1051) in the _Frame VOI LUT Sequence_ (0028,9132).
+    ///
+    /// Only the first window is returned if several are defined.
+    pub fn window_width(&self) -> Option<f64> {
+        self.float(tags::FRAME_VOILUT_SEQUENCE, tags::WINDOW_WIDTH)
+    }
+
+    /// _Dimension Index Values_ (0020,9157)
+    /// in the _Frame Content Sequence_ (0020,9111):
+    /// the index of the frame along each dimension
+    /// of the _Dimension Index Sequence_ (0020,9222), starting from 1.
+    pub fn dimension_index_values(&self) -> Option<Vec<u32>> {
+        self.value(tags::FRAME_CONTENT_SEQUENCE, tags::DIMENSION_INDEX_VALUES)?
+            .to_multi_int()
+            .ok()
+    }
+
+    /// _Stack ID_ (0020,9056) in the _Frame Content Sequence_ (0020,9111).
+    pub fn stack_id(&self) -> Option<String> {
+        let value = self.value(tags::FRAME_CONTENT_SEQUENCE, tags::STACK_ID)?;
+        Some(value.to_str().trim_end_matches([' ', '\0']).to_string())
+    }
+
+    /// _In-Stack Position Number_ (0020,9057)
+    /// in the _Frame Content Sequence_ (0020,9111).
+    pub fn in_stack_position_number(&self) -> Option<u32> {
+        self.value(tags::FRAME_CONTENT_SEQUENCE, tags::IN_STACK_POSITION_NUMBER)?
+            .to_int()
+            .ok()
+    }
+
+    /// _Temporal Position Index_ (0020,9128)
+    /// in the _Frame Content Sequence_ (0020,9111).
+    pub fn temporal_position_index(&self) -> Option<u32> {
+        self.value(tags::FRAME_CONTENT_SEQUENCE, tags::TEMPORAL_POSITION_INDEX)?
+            .to_int()
+            .ok()
+    }
+
+    /// _Frame Acquisition Number_ (0020,9156)
+    /// in the _Frame Content Sequence_ (0020,9111).
+    pub fn frame_acquisition_number(&self) -> Option<u32> {
+        self.value(tags::FRAME_CONTENT_SEQUENCE, tags::FRAME_ACQUISITION_NUMBER)?
+            .to_int()
+            .ok()
+    }
+
+    fn value(&self, sequence: Tag, tag: Tag) -> Option<&'a PrimitiveValue> {
+        self.group(sequence)?.get(tag)?.value().primitive()
+    }
+
+    fn float(&self, sequence: Tag, tag: Tag) -> Option<f64> {
+        self.value(sequence, tag)?.to_float64().ok()
+    }
+
+    fn floats(&self, sequence: Tag, tag: Tag) -> Option<Vec<f64>> {
+        self.value(sequence, tag)?.to_multi_float64().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, VR};
+
+    fn macro_element(
+        sequence: Tag,
+        elements: Vec<DataElement<InMemDicomObject>>,
+    ) -> DataElement<InMemDicomObject> {
+        DataElement::new(
+            sequence,
+            VR::SQ,
+            DataSetSequence::from(vec![InMemDicomObject::from_element_iter(elements)]),
+        )
+    }
+
+    /// An enhanced object of 3 frames along one axis,
+    /// with a window defined for all frames but overridden in the last one.
+    fn test_object() -> InMemDicomObject {
+        let shared = InMemDicomObject::from_element_iter([
+            macro_element(
+                tags::PIXEL_MEASURES_SEQUENCE,
+                vec![
+                    DataElement::new(
+                        tags::PIXEL_SPACING,
+                        VR::DS,
+                        dicom_value!(Strs, ["0.5", "0.75"]),
+                    ),
+                    DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2"),
+                ],
+            ),
+            macro_element(
+                tags::PLANE_ORIENTATION_SEQUENCE,
+                vec![DataElement::new(
+                    tags::IMAGE_ORIENTATION_PATIENT,
+                    VR::DS,
+                    dicom_value!(Strs, ["1", "0", "0", "0", "1", "0"]),
+                )],
+            ),
+            macro_element(
+                tags::FRAME_VOILUT_SEQUENCE,
+                vec![
+                    DataElement::new(tags::WINDOW_CENTER, VR::DS, "40"),
+                    DataElement::new(tags::WINDOW_WIDTH, VR::DS, "400"),
+                ],
+            ),
+        ]);
+
+        let frames: Vec<_> = (0..3_u32)
+            .map(|i| {
+                let mut frame = InMemDicomObject::from_element_iter([
+                    macro_element(
+                        tags::PLANE_POSITION_SEQUENCE,
+                        vec![DataElement::new(
+                            tags::IMAGE_POSITION_PATIENT,
+                            VR::DS,
+                            dicom_value!(
+                                Strs,
+                                ["-100".to_string(), "-120".to_string(), (2 * i).to_string()]
+                            ),
+                        )],
+                    ),
+                    macro_element(
+                        tags::FRAME_CONTENT_SEQUENCE,
+                        vec![
+                            DataElement::new(tags::STACK_ID, VR::SH, "1 "),
+                            DataElement::new(
+                                tags::IN_STACK_POSITION_NUMBER,
+                                VR::UL,
+                                dicom_value!(U32, [i + 1]),
+                            ),
+                            DataElement::new(
+                                tags::DIMENSION_INDEX_VALUES,
+                                VR::UL,
+                                dicom_value!(U32, [1, i + 1]),
+                            ),
+                        ],
+                    ),
+                ]);
+                if i == 2 {
+                    frame.put(macro_element(
+                        tags::FRAME_VOILUT_SEQUENCE,
+                        vec![
+                            DataElement::new(
+                                tags::WINDOW_CENTER,
+                                VR::DS,
+                                dicom_value!(Strs, ["300", "40"]),
+                            ),
+                            DataElement::new(
+                                tags::WINDOW_WIDTH,
+                                VR::DS,
+                                dicom_value!(Strs, ["1500", "400"]),
+                            ),
+                        ],
+                    ));
+                }
+                frame
+            })
+            .collect();
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, "3"),
+            DataElement::new(
+                tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![shared]),
+            ),
+            DataElement::new(
+                tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(frames),
+            ),
+        ])
+    }
+
+    #[test]
+    fn overlay_per_frame_groups_on_shared_groups() {
+        let obj = test_object();
+
+        for i in 0..3 {
+            let frame = obj.functional_groups(i).unwrap();
+            assert_eq!(frame.frame_index(), i);
+            assert_eq!(
+                frame.image_position_patient(),
+                Some([-100., -120., 2. * i as f64])
+            );
+            assert_eq!(frame.pixel_spacing(), Some([0.5, 0.75]));
+            assert_eq!(frame.slice_thickness(), Some(2.));
+            assert_eq!(
+                frame.image_orientation_patient(),
+                Some([1., 0., 0., 0., 1., 0.])
+            );
+            assert_eq!(frame.stack_id().as_deref(), Some("1"));
+            assert_eq!(frame.in_stack_position_number(), Some(i + 1));
+            assert_eq!(frame.dimension_index_values(), Some(vec![1, i + 1]));
+            assert_eq!(frame.temporal_position_index(), None);
+            assert!(frame.is_per_frame(tags::PLANE_POSITION_SEQUENCE));
+            assert!(!frame.is_per_frame(tags::PIXEL_MEASURES_SEQUENCE));
+        }
+
+        let frame = obj.functional_groups(0).unwrap();
+        assert_eq!(frame.window_center(), Some(40.));
+        assert_eq!(frame.window_width(), Some(400.));
+        assert!(!frame.is_per_frame(tags::FRAME_VOILUT_SEQUENCE));
+
+        // the last frame has its own window
+        let frame = obj.functional_groups(2).unwrap();
+        assert_eq!(frame.window_center(), Some(300.));
+        assert_eq!(frame.window_width(), Some(1500.));
+        assert!(frame.is_per_frame(tags::FRAME_VOILUT_SEQUENCE));
+        let voi = frame.group(tags::FRAME_VOILUT_SEQUENCE).unwrap();
+        assert_eq!(
+            voi.get(tags::WINDOW_CENTER)
+                .unwrap()
+                .value()
+                .to_multi_float64()
+                .unwrap(),
+            [300., 40.]
+        );
+    }
+
+    #[test]
+    fn functional_groups_errors() {
+        let mut obj = test_object();
+        assert!(matches!(
+            obj.functional_groups(3),
+            Err(FunctionalGroupsError::FrameOutOfRange {
+                frame_index: 3,
+                number_of_frames: 3,
+                ..
+            })
+        ));
+
+        // shared functional groups are optional
+        obj.remove_element(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE);
+        let frame = obj.functional_groups(1).unwrap();
+        assert_eq!(frame.image_position_patient(), Some([-100., -120., 2.]));
+        assert_eq!(frame.pixel_spacing(), None);
+
+        obj.remove_element(tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE);
+        let err = obj.functional_groups(0).unwrap_err();
+        assert!(matches!(
+            err,
+            FunctionalGroupsError::MissingPerFrameFunctionalGroups { .. }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Missing Per-Frame Functional Groups Sequence"
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Geometry of images in the patient coordinate system.
+//!
+//! The position and orientation of an image plane are given by
+//! _Image Position (Patient)_ (0020,0032),
+//! the coordinates of the center of the first pixel,
+//! and _Image Orientation (Patient)_ (0020,0037),
+//! the direction cosines of the first row and of the first column,
+//! while _Pixel Spacing_ (0028,0030) gives the physical distance
+//! between the centers of adjacent pixels.
+//! See PS3.3 C.7.6.2.
+//!
+//! [`InMemDicomObject::geometry`] collects these attributes
+//! into an [`ImageGeometry`],
+//! from the functional groups of the first frame in enhanced multi-frame objects.
+//! [`FrameFunctionalGroups::geometry`] does the same for any frame.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_object::open_file;
+//!
+//! let obj = open_file("ct.dcm")?;
+//! let geometry = obj.geometry()?;
+//! geometry.validate(1e-4)?;
+//! println!("Slice normal: {:?}", geometry.normal());
+//! println!("Last pixel of the first row: {:?}", geometry.pixel_to_patient(0., 511.));
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`InMemDicomObject::geometry`]: crate::InMemDicomObject::geometry
+use std::convert::TryInto;
+
+use dicom_core::{DataDictionary, Tag};
+use dicom_dictionary_std::tags;
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::functional_groups::{FrameFunctionalGroups, FunctionalGroupsError};
+use crate::mem::InMemDicomObject;
+
+/// An error which may occur when reading or validating image geometry.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum GeometryError {
+    /// Missing attribute {tag}
+    MissingAttribute { tag: Tag, backtrace: Backtrace },
+    /// Invalid value of {tag}
+    InvalidValue { tag: Tag, backtrace: Backtrace },
+    /// Could not access the functional groups of the first frame
+    FunctionalGroups {
+        #[snafu(backtrace)]
+        source: FunctionalGroupsError,
+    },
+    /// Direction cosines of the {direction} have length {length}, expected 1
+    NonUnitDirection {
+        direction: &'static str,
+        length: f64,
+        backtrace: Backtrace,
+    },
+    /// Row and column direction cosines are not orthogonal (dot product {dot})
+    NonOrthogonalDirections { dot: f64, backtrace: Backtrace },
+}
+
+pub type Result<T, E = GeometryError> = std::result::Result<T, E>;
+
+/// The position, orientation, and spacing of an image plane
+/// in the patient coordinate system, in mm.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ImageGeometry {
+    /// The coordinates of the center of the first pixel,
+    /// from _Image Position (Patient)_.
+    pub position: [f64; 3],
+    /// The direction cosines of the first row,
+    /// along which the column index increases,
+    /// from the first three values of _Image Orientation (Patient)_.
+    pub row_direction: [f64; 3],
+    /// The direction cosines of the first column,
+    /// along which the row index increases,
+    /// from the last three values of _Image Orientation (Patient)_.
+    pub column_direction: [f64; 3],
+    /// The distance between the centers of adjacent rows
+    /// and between the centers of adjacent columns,
+    /// in this order, from _Pixel Spacing_.
+    pub pixel_spacing: [f64; 2],
+    /// The nominal slice thickness, from _Slice Thickness_ (0018,0050).
+    pub slice_thickness: Option<f64>,
+    /// The distance between the centers of adjacent slices,
+    /// from _Spacing Between Slices_ (0018,0088).
+    pub spacing_between_slices: Option<f64>,
+}
+
+impl ImageGeometry {
+    /// Create an image geometry from the values of
+    /// _Image Position (Patient)_, _Image Orientation (Patient)_,
+    /// and _Pixel Spacing_.
+    pub fn new(position: [f64; 3], orientation: [f64; 6], pixel_spacing: [f64; 2]) -> Self {
+        ImageGeometry {
+            position,
+            row_direction: [orientation[0], orientation[1], orientation[2]],
+            column_direction: [orientation[3], orientation[4], orientation[5]],
+            pixel_spacing,
+            slice_thickness: None,
+            spacing_between_slices: None,
+        }
+    }
+
+    /// Set the nominal slice thickness.
+    pub fn slice_thickness(mut self, slice_thickness: impl Into<Option<f64>>) -> Self {
+        self.slice_thickness = slice_thickness.into();
+        self
+    }
+
+    /// Set the distance between the centers of adjacent slices.
+    pub fn spacing_between_slices(mut self, spacing: impl Into<Option<f64>>) -> Self {
+        self.spacing_between_slices = spacing.into();
+        self
+    }
+
+    /// Read the geometry of a single-frame image
+    /// from the top level attributes of the object,
+    /// or of the first frame of an enhanced multi-frame object
+    /// from its functional groups.
+    pub(crate) fn from_object<D>(obj: &InMemDicomObject<D>) -> Result<Self>
+    where
+        D: DataDictionary + Clone,
+    {
+        if obj
+            .get(tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE)
+            .is_some()
+        {
+            return obj
+                .functional_groups(0)
+                .context(FunctionalGroupsSnafu)?
+                .geometry();
+        }
+        Ok(ImageGeometry::new(
+            floats(obj, tags::IMAGE_POSITION_PATIENT)?,
+            floats(obj, tags::IMAGE_ORIENTATION_PATIENT)?,
+            floats(obj, tags::PIXEL_SPACING)?,
+        )
+        .slice_thickness(float(obj, tags::SLICE_THICKNESS))
+        .spacing_between_slices(float(obj, tags::SPACING_BETWEEN_SLICES)))
+    }
+
+    /// Read the geometry of a frame from its functional groups.
+    pub(crate) fn from_functional_groups<D>(frame: &FrameFunctionalGroups<'_, D>) -> Result<Self>
+    where
+        D: DataDictionary + Clone,
+    {
+        Ok(ImageGeometry::new(
+            frame
+                .image_position_patient()
+                .context(MissingAttributeSnafu {
+                    tag: tags::IMAGE_POSITION_PATIENT,
+                })?,
+            frame
+                .image_orientation_patient()
+                .context(MissingAttributeSnafu {
+                    tag: tags::IMAGE_ORIENTATION_PATIENT,
+                })?,
+            frame.pixel_spacing().context(MissingAttributeSnafu {
+                tag: tags::PIXEL_SPACING,
+            })?,
+        )
+        .slice_thickness(frame.slice_thickness())
+        .spacing_between_slices(frame.spacing_between_slices()))
+    }
+
+    /// The unit normal of the image plane,
+    /// as the cross product of the row and column direction cosines.
+    ///
+    /// For axial images in the usual orientation,
+    /// this points towards the head of the patient.
+    pub fn normal(&self) -> [f64; 3] {
+        let [x, y, z] = cross(self.row_direction, self.column_direction);
+        let length = norm([x, y, z]);
+        if length > 0. {
+            [x / length, y / length, z / length]
+        } else {
+            [x, y, z]
+        }
+    }
+
+    /// Map the center of the pixel at the given row and column,
+    /// starting from 0,
+    /// to its coordinates in the patient coordinate system.
+    ///
+    /// Fractional indices address points between pixel centers.
+    pub fn pixel_to_patient(&self, row: f64, column: f64) -> [f64; 3] {
+        let [row_spacing, column_spacing] = self.pixel_spacing;
+        let mut out = self.position;
+        for (i, out) in out.iter_mut().enumerate() {
+            *out += self.row_direction[i] * column_spacing * column
+                + self.column_direction[i] * row_spacing * row;
+        }
+        out
+    }
+
+    /// Check that the row and column direction cosines
+    /// are unit vectors orthogonal to each other,
+    /// within the given tolerance.
+    pub fn validate(&self, tolerance: f64) -> Result<()> {
+        for (direction, cosines) in [
+            ("row", self.row_direction),
+            ("column", self.column_direction),
+        ] {
+            let length = norm(cosines);
+            ensure!(
+                (length - 1.).abs() <= tolerance,
+                NonUnitDirectionSnafu { direction, length }
+            );
+        }
+        let dot = dot(self.row_direction, self.column_direction);
+        ensure!(dot.abs() <= tolerance, NonOrthogonalDirectionsSnafu { dot });
+        Ok(())
+    }
+}
+
+impl<'a, D> FrameFunctionalGroups<'a, D>
+where
+    D: DataDictionary + Clone,
+{
+    /// Collect the geometry of this frame from the
+    /// _Plane Position Sequence_, _Plane Orientation Sequence_,
+    /// and _Pixel Measures Sequence_ functional groups.
+    pub fn geometry(&self) -> Result<ImageGeometry> {
+        ImageGeometry::from_functional_groups(self)
+    }
+}
+
+fn floats<D, const N: usize>(obj: &InMemDicomObject<D>, tag: Tag) -> Result<[f64; N]>
+where
+    D: DataDictionary + Clone,
+{
+    obj.get(tag)
+        .context(MissingAttributeSnafu { tag })?
+        .to_multi_float64()
+        .ok()
+        .and_then(|values| values.try_into().ok())
+        .context(InvalidValueSnafu { tag })
+}
+
+fn float<D>(obj: &InMemDicomObject<D>, tag: Tag) -> Option<f64>
+where
+    D: DataDictionary + Clone,
+{
+    obj.get(tag)?.to_float64().ok()
+}
+
+pub(crate) fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn norm(a: [f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, VR};
+
+    /// A 512x512 axial slice with anisotropic pixels.
+    fn axial_object() -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::IMAGE_POSITION_PATIENT,
+                VR::DS,
+                dicom_value!(Strs, ["-125", "-125", "50"]),
+            ),
+            DataElement::new(
+                tags::IMAGE_ORIENTATION_PATIENT,
+                VR::DS,
+                dicom_value!(Strs, ["1", "0", "0", "0", "1", "0"]),
+            ),
+            DataElement::new(
+                tags::PIXEL_SPACING,
+                VR::DS,
+                dicom_value!(Strs, ["0.5", "0.75"]),
+            ),
+            DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2"),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [512])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [512])),
+        ])
+    }
+
+    #[test]
+    fn axial_corners_to_patient() {
+        let geometry = axial_object().geometry().unwrap();
+        assert_eq!(geometry.position, [-125., -125., 50.]);
+        assert_eq!(geometry.row_direction, [1., 0., 0.]);
+        assert_eq!(geometry.column_direction, [0., 1., 0.]);
+        assert_eq!(geometry.pixel_spacing, [0.5, 0.75]);
+        assert_eq!(geometry.slice_thickness, Some(2.));
+        assert_eq!(geometry.spacing_between_slices, None);
+        assert_eq!(geometry.normal(), [0., 0., 1.]);
+        geometry.validate(1e-4).unwrap();
+
+        // columns are 0.75 mm apart along x, rows 0.5 mm apart along y
+        assert_eq!(geometry.pixel_to_patient(0., 0.), [-125., -125., 50.]);
+        assert_eq!(geometry.pixel_to_patient(0., 511.), [258.25, -125., 50.]);
+        assert_eq!(geometry.pixel_to_patient(511., 0.), [-125., 130.5, 50.]);
+        assert_eq!(geometry.pixel_to_patient(511., 511.), [258.25, 130.5, 50.]);
+    }
+
+    #[test]
+    fn invalid_orientation_is_flagged() {
+        // the column direction is tilted by 30 degrees towards the row direction
+        let geometry = ImageGeometry::new(
+            [0., 0., 0.],
+            [1., 0., 0., 0.5, 0.866_025_403_784_438_6, 0.],
+            [1., 1.],
+        );
+        let err = geometry.validate(1e-4).unwrap_err();
+        assert!(matches!(
+            err,
+            GeometryError::NonOrthogonalDirections { dot, .. } if (dot - 0.5).abs() < 1e-9
+        ));
+
+        let geometry = ImageGeometry::new([0., 0., 0.], [1., 0., 0., 0., 2., 0.], [1., 1.]);
+        let err = geometry.validate(1e-4).unwrap_err();
+        assert!(matches!(
+            err,
+            GeometryError::NonUnitDirection {
+                direction: "column",
+                ..
+            }
+        ));
+
+        // slightly off values are accepted within the tolerance
+        let geometry =
+            ImageGeometry::new([0., 0., 0.], [1., 0., 0., 0.000_01, 0.999_99, 0.], [1., 1.]);
+        geometry.validate(1e-4).unwrap();
+        assert!(geometry.validate(1e-6).is_err());
+
+        let mut obj = axial_object();
+        obj.remove_element(tags::PIXEL_SPACING);
+        assert!(matches!(
+            obj.geometry(),
+            Err(GeometryError::MissingAttribute { tag, .. }) if tag == tags::PIXEL_SPACING
+        ));
+        obj.put(DataElement::new(tags::PIXEL_SPACING, VR::DS, "0.5"));
+        assert!(matches!(
+            obj.geometry(),
+            Err(GeometryError::InvalidValue { tag, .. }) if tag == tags::PIXEL_SPACING
+        ));
+    }
+
+    #[test]
+    fn geometry_from_functional_groups() {
+        let group = |sequence: Tag, elements: Vec<DataElement<InMemDicomObject>>| {
+            DataElement::new(
+                sequence,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter(elements)]),
+            )
+        };
+        let shared = InMemDicomObject::from_element_iter([
+            group(
+                tags::PIXEL_MEASURES_SEQUENCE,
+                vec![
+                    DataElement::new(
+                        tags::PIXEL_SPACING,
+                        VR::DS,
+                        dicom_value!(Strs, ["0.5", "0.5"]),
+                    ),
+                    DataElement::new(tags::SPACING_BETWEEN_SLICES, VR::DS, "3"),
+                ],
+            ),
+            // coronal
+            group(
+                tags::PLANE_ORIENTATION_SEQUENCE,
+                vec![DataElement::new(
+                    tags::IMAGE_ORIENTATION_PATIENT,
+                    VR::DS,
+                    dicom_value!(Strs, ["1", "0", "0", "0", "0", "-1"]),
+                )],
+            ),
+        ]);
+        let frames: Vec<_> = (0..2)
+            .map(|i| {
+                InMemDicomObject::from_element_iter([group(
+                    tags::PLANE_POSITION_SEQUENCE,
+                    vec![DataElement::new(
+                        tags::IMAGE_POSITION_PATIENT,
+                        VR::DS,
+                        dicom_value!(
+                            Strs,
+                            ["-100".to_string(), (3 * i).to_string(), "100".to_string()]
+                        ),
+                    )],
+                )])
+            })
+            .collect();
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![shared]),
+            ),
+            DataElement::new(
+                tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(frames),
+            ),
+        ]);
+
+        let geometry = obj.geometry().unwrap();
+        assert_eq!(geometry.position, [-100., 0., 100.]);
+        assert_eq!(geometry.spacing_between_slices, Some(3.));
+        assert_eq!(geometry.normal(), [0., 1., 0.]);
+        assert_eq!(geometry.pixel_to_patient(10., 10.), [-95., 0., 95.]);
+
+        let geometry = obj.functional_groups(1).unwrap().geometry().unwrap();
+        assert_eq!(geometry.position, [-100., 3., 100.]);
+        assert_eq!(geometry.pixel_to_patient(10., 10.), [-95., 3., 95.]);
+    }
+}
@@ -0,0 +1,664 @@
+//! Persistent indexes of the element headers of DICOM files.
+//!
+//! Opening a file with [`lazy::open_file`](crate::lazy::open_file)
+//! still reads every element header in it,
+//! which adds up when the same large files are opened over and over,
+//! such as in a PACS cache or a research data set.
+//! A [`DicomIndex`] records where the value of each element lies in a file,
+//! along with its file meta group and transfer syntax,
+//! so that the file can be opened again lazily
+//! through [`LazyDicomObject::open_with_index`]
+//! without parsing any header.
+//!
+//! An index is tied to the file it was built from
+//! through a [`FileFingerprint`] of its size and modification time.
+//! Opening a file whose fingerprint does not match the index
+//! fails with [`IndexError::StaleIndex`],
+//! in which case the index should be built again.
+//!
+//! With the `index` feature,
+//! indexes can be saved and loaded in a compact binary format.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "index")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use dicom_dictionary_std::tags;
+//! use dicom_object::index::DicomIndex;
+//! use dicom_object::LazyDicomObject;
+//!
+//! let index = DicomIndex::build("0001.dcm")?;
+//! index.save_to_file("0001.dcm.idx")?;
+//!
+//! // later on
+//! let index = DicomIndex::load_from_file("0001.dcm.idx")?;
+//! let obj = LazyDicomObject::open_with_index("0001.dcm", &index)?;
+//! let modality = obj.element(tags::MODALITY)?.value()?.to_str();
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "index"))]
+//! # fn main() {}
+//! ```
+use std::fs::{File, Metadata};
+use std::io::BufReader;
+#[cfg(feature = "index")]
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::{Tag, VR};
+use dicom_dictionary_std::StandardDataDictionary;
+use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
+use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+#[cfg(feature = "index")]
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::lazy::{self, LazyDicomObject, LazyReadError};
+use crate::meta::FileMetaTable;
+
+/// The magic code at the start of a saved index.
+#[cfg(feature = "index")]
+const MAGIC_CODE: &[u8; 8] = b"DCMINDEX";
+
+/// The version of the saved index format.
+#[cfg(feature = "index")]
+const FORMAT_VERSION: u16 = 1;
+
+/// An error which may occur when building, saving, loading
+/// or using a DICOM file index.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum IndexError {
+    #[snafu(display("Could not open file '{}'", filename.display()))]
+    OpenFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not read metadata of file '{}'", filename.display()))]
+    ReadMetadata {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not read the DICOM file to index
+    ReadFile {
+        #[snafu(backtrace)]
+        source: LazyReadError,
+    },
+    /// Could not encode the file meta group
+    EncodeMetaDataSet {
+        #[snafu(backtrace)]
+        source: crate::meta::Error,
+    },
+    /// Could not decode the file meta group of the index
+    DecodeMetaDataSet {
+        #[snafu(backtrace)]
+        source: crate::meta::Error,
+    },
+    #[snafu(display("Index does not match the current contents of '{}'", filename.display()))]
+    StaleIndex {
+        filename: PathBuf,
+        backtrace: Backtrace,
+    },
+    /// Unsupported transfer syntax `{uid}`
+    UnsupportedTransferSyntax { uid: String, backtrace: Backtrace },
+    /// Index entry #{entry} is invalid
+    InvalidEntry { entry: usize, backtrace: Backtrace },
+    /// Could not write the index
+    #[cfg(feature = "index")]
+    WriteIndex {
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not serialize the index
+    #[cfg(feature = "index")]
+    SerializeIndex {
+        backtrace: Backtrace,
+        source: bincode::Error,
+    },
+    /// Could not read the index
+    #[cfg(feature = "index")]
+    ReadIndex {
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Not a DICOM file index
+    #[cfg(feature = "index")]
+    NotAnIndex { backtrace: Backtrace },
+    /// Unsupported index format version {version}
+    #[cfg(feature = "index")]
+    UnsupportedVersion { version: u16, backtrace: Backtrace },
+    /// Could not deserialize the index
+    #[cfg(feature = "index")]
+    DeserializeIndex {
+        backtrace: Backtrace,
+        source: bincode::Error,
+    },
+}
+
+pub type Result<T, E = IndexError> = std::result::Result<T, E>;
+
+/// The size and modification time of a file,
+/// used to tell whether it changed since it was indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "index", derive(Serialize, Deserialize))]
+pub struct FileFingerprint {
+    size: u64,
+    /// seconds and nanoseconds since the Unix epoch,
+    /// if available on the platform
+    modified: Option<(u64, u32)>,
+}
+
+impl FileFingerprint {
+    /// Take the fingerprint of the file at the given path.
+    pub fn of_file<P>(path: P) -> std::io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        std::fs::metadata(path).map(|metadata| Self::from_metadata(&metadata))
+    }
+
+    /// Take the fingerprint of a file from its metadata.
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|time| (time.as_secs(), time.subsec_nanos()));
+        FileFingerprint {
+            size: metadata.len(),
+            modified,
+        }
+    }
+
+    /// The size of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// The value of an indexed element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "index", derive(Serialize, Deserialize))]
+pub(crate) enum IndexValue {
+    /// a primitive value at the given position of the file
+    Primitive { offset: u64 },
+    /// a data set sequence with the given number of items,
+    /// the elements of which follow the sequence in the index
+    Sequence { items: u32 },
+    /// an encapsulated pixel data sequence,
+    /// with the position and length of each item,
+    /// starting with the basic offset table
+    PixelSequence { items: Vec<(u64, u32)> },
+}
+
+/// An element of a [`DicomIndex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "index", derive(Serialize, Deserialize))]
+pub struct IndexEntry {
+    /// the tag and item number of each enclosing sequence,
+    /// outermost first
+    pub(crate) path: Vec<(u16, u16, u32)>,
+    pub(crate) tag: (u16, u16),
+    pub(crate) vr: [u8; 2],
+    pub(crate) len: u32,
+    pub(crate) value: IndexValue,
+}
+
+impl IndexEntry {
+    /// Create an index entry
+    /// for an element at the given sequence item path.
+    pub(crate) fn new(path: &[(Tag, u32)], header: &DataElementHeader, value: IndexValue) -> Self {
+        IndexEntry {
+            path: path
+                .iter()
+                .map(|(tag, item)| (tag.group(), tag.element(), *item))
+                .collect(),
+            tag: (header.tag.group(), header.tag.element()),
+            vr: header.vr.to_bytes(),
+            len: header.len.0,
+            value,
+        }
+    }
+
+    /// The tag and item number of each sequence enclosing the element,
+    /// outermost first.
+    pub fn path(&self) -> impl Iterator<Item = (Tag, u32)> + '_ {
+        self.path
+            .iter()
+            .map(|&(group, element, item)| (Tag(group, element), item))
+    }
+
+    /// The tag of the element.
+    pub fn tag(&self) -> Tag {
+        Tag(self.tag.0, self.tag.1)
+    }
+
+    /// The value representation of the element,
+    /// or `None` if the index holds an unknown one.
+    pub fn vr(&self) -> Option<VR> {
+        VR::from_binary(self.vr)
+    }
+
+    /// The position of the element's value in the file,
+    /// or `None` if the element is a sequence.
+    pub fn value_offset(&self) -> Option<u64> {
+        match self.value {
+            IndexValue::Primitive { offset } => Some(offset),
+            _ => None,
+        }
+    }
+
+    /// The value length of the element, as declared in its header.
+    pub fn value_length(&self) -> Length {
+        Length(self.len)
+    }
+
+    /// The header of the indexed element,
+    /// or `None` if the index holds an unknown value representation.
+    pub(crate) fn header(&self) -> Option<DataElementHeader> {
+        Some(DataElementHeader::new(
+            self.tag(),
+            self.vr()?,
+            self.value_length(),
+        ))
+    }
+}
+
+/// An index of the elements of a DICOM file,
+/// for opening it lazily without parsing its element headers.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "index", derive(Serialize, Deserialize))]
+pub struct DicomIndex {
+    fingerprint: FileFingerprint,
+    transfer_syntax: String,
+    /// the encoded file meta group, including the magic code
+    meta: Vec<u8>,
+    entries: Vec<IndexEntry>,
+}
+
+impl DicomIndex {
+    /// Build an index of the DICOM file at the given path,
+    /// reading all of its element headers.
+    pub fn build<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).context(OpenFileSnafu { filename: path })?;
+        let metadata = file
+            .metadata()
+            .context(ReadMetadataSnafu { filename: path })?;
+        let obj = lazy::from_reader(BufReader::new(file)).context(ReadFileSnafu)?;
+
+        let mut meta = b"DICM".to_vec();
+        obj.meta()
+            .write(&mut meta)
+            .context(EncodeMetaDataSetSnafu)?;
+        let mut entries = Vec::new();
+        obj.collect_index_entries(&mut Vec::new(), &mut entries);
+
+        Ok(DicomIndex {
+            fingerprint: FileFingerprint::from_metadata(&metadata),
+            transfer_syntax: obj.meta().transfer_syntax().to_string(),
+            meta,
+            entries,
+        })
+    }
+
+    /// The fingerprint of the indexed file at the time it was indexed.
+    pub fn fingerprint(&self) -> &FileFingerprint {
+        &self.fingerprint
+    }
+
+    /// The transfer syntax UID of the indexed file.
+    pub fn transfer_syntax(&self) -> &str {
+        &self.transfer_syntax
+    }
+
+    /// The file meta group of the indexed file.
+    pub fn meta(&self) -> Result<FileMetaTable> {
+        FileMetaTable::from_reader(&self.meta[..]).context(DecodeMetaDataSetSnafu)
+    }
+
+    /// The indexed elements,
+    /// in the order in which they appear in the file.
+    ///
+    /// Each sequence is followed by the elements of its items.
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// Check whether the file at the given path
+    /// still matches this index.
+    pub fn is_fresh<P>(&self, path: P) -> Result<bool>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let fingerprint =
+            FileFingerprint::of_file(path).context(ReadMetadataSnafu { filename: path })?;
+        Ok(fingerprint == self.fingerprint)
+    }
+
+    /// Save this index to the given writer.
+    #[cfg(feature = "index")]
+    pub fn save<W>(&self, mut to: W) -> Result<()>
+    where
+        W: std::io::Write,
+    {
+        to.write_all(MAGIC_CODE).context(WriteIndexSnafu)?;
+        to.write_all(&FORMAT_VERSION.to_le_bytes())
+            .context(WriteIndexSnafu)?;
+        let bytes = bincode::serialize(self).context(SerializeIndexSnafu)?;
+        to.write_all(&bytes).context(WriteIndexSnafu)?;
+        to.flush().context(WriteIndexSnafu)
+    }
+
+    /// Save this index to a file at the given path.
+    #[cfg(feature = "index")]
+    pub fn save_to_file<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::create(path).context(OpenFileSnafu { filename: path })?;
+        self.save(std::io::BufWriter::new(file))
+    }
+
+    /// Load an index saved with [`save`](DicomIndex::save).
+    #[cfg(feature = "index")]
+    pub fn load<R>(mut from: R) -> Result<Self>
+    where
+        R: Read,
+    {
+        let mut magic = [0; 8];
+        from.read_exact(&mut magic).context(ReadIndexSnafu)?;
+        snafu::ensure!(&magic == MAGIC_CODE, NotAnIndexSnafu);
+        let mut version = [0; 2];
+        from.read_exact(&mut version).context(ReadIndexSnafu)?;
+        let version = u16::from_le_bytes(version);
+        snafu::ensure!(
+            version == FORMAT_VERSION,
+            UnsupportedVersionSnafu { version }
+        );
+        let mut bytes = Vec::new();
+        from.read_to_end(&mut bytes).context(ReadIndexSnafu)?;
+        bincode::deserialize(&bytes).context(DeserializeIndexSnafu)
+    }
+
+    /// Load an index from the file at the given path.
+    #[cfg(feature = "index")]
+    pub fn load_from_file<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).context(OpenFileSnafu { filename: path })?;
+        Self::load(BufReader::new(file))
+    }
+}
+
+impl LazyDicomObject<BufReader<File>> {
+    /// Open a DICOM file lazily with the given index,
+    /// without parsing any of its element headers.
+    ///
+    /// Fails with [`IndexError::StaleIndex`]
+    /// if the file changed since the index was built.
+    pub fn open_with_index<P>(path: P, index: &DicomIndex) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).context(OpenFileSnafu { filename: path })?;
+        let metadata = file
+            .metadata()
+            .context(ReadMetadataSnafu { filename: path })?;
+        snafu::ensure!(
+            FileFingerprint::from_metadata(&metadata) == index.fingerprint,
+            StaleIndexSnafu { filename: path }
+        );
+
+        let meta = index.meta()?;
+        let registry: &'static TransferSyntaxRegistry = &TransferSyntaxRegistry;
+        let ts = registry
+            .get(&index.transfer_syntax)
+            .context(UnsupportedTransferSyntaxSnafu {
+                uid: index.transfer_syntax.clone(),
+            })?;
+        lazy::from_index_entries(
+            BufReader::new(file),
+            ts,
+            meta,
+            &index.entries,
+            StandardDataDictionary,
+        )
+        .map_err(|entry| InvalidEntrySnafu { entry }.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DicomIndex, IndexError};
+    use crate::meta::FileMetaTableBuilder;
+    use crate::{lazy, LazyDicomObject};
+    use dicom_core::value::PixelFragmentSequence;
+    use dicom_core::{DataElement, Tag, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_transfer_syntax_registry::entries;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    fn write_fixture(path: &Path) {
+        let mut obj = crate::dicom_object! {
+            SpecificCharacterSet: "ISO_IR 100",
+            SOPClassUID: uids::SECONDARY_CAPTURE_IMAGE_STORAGE,
+            SOPInstanceUID: "2.25.178",
+            PatientName: "Müller^Jörg",
+            Modality: "OT",
+            NumberOfFrames: "2",
+            Rows: 2_u16,
+            Columns: 2_u16,
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "2.25.1" },
+                {},
+                {
+                    ReferencedSOPInstanceUID: "2.25.3",
+                    ReferencedFrameNumber: "1",
+                },
+            ],
+        };
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new(vec![0, 12], vec![vec![0x11; 4], vec![0x22; 6]]),
+        ));
+        obj.with_meta(FileMetaTableBuilder::new().transfer_syntax(entries::JPEG_BASELINE.uid()))
+            .unwrap()
+            .write_to_file(path)
+            .unwrap();
+    }
+
+    /// Check that an object opened with an index
+    /// reads the same as one parsed from scratch.
+    fn assert_same_as_parsed(
+        obj: &LazyDicomObject<impl std::io::Read + std::io::Seek>,
+        path: &Path,
+    ) {
+        let parsed = lazy::open_file(path).unwrap();
+        assert_eq!(obj.meta(), parsed.meta());
+        assert_eq!(
+            obj.tags().collect::<Vec<_>>(),
+            parsed.tags().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            format!("{:?}", obj.to_in_mem().unwrap()),
+            format!("{:?}", parsed.to_in_mem().unwrap())
+        );
+        for frame in 0..2 {
+            assert_eq!(
+                obj.read_frame_data(frame).unwrap(),
+                parsed.read_frame_data(frame).unwrap()
+            );
+        }
+        let items = obj
+            .element(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(items.len(), 3);
+        assert!(items[1].is_empty());
+        assert_eq!(
+            items[2]
+                .element(tags::REFERENCED_SOP_INSTANCE_UID)
+                .unwrap()
+                .value()
+                .unwrap()
+                .to_str(),
+            "2.25.3"
+        );
+    }
+
+    #[test]
+    fn open_with_fresh_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.dcm");
+        write_fixture(&path);
+
+        let index = DicomIndex::build(&path).unwrap();
+        assert!(index.is_fresh(&path).unwrap());
+        assert_eq!(index.transfer_syntax(), entries::JPEG_BASELINE.uid());
+        assert_eq!(
+            index.fingerprint().size(),
+            std::fs::metadata(&path).unwrap().len()
+        );
+        let name = index
+            .entries()
+            .iter()
+            .find(|e| e.tag() == tags::PATIENT_NAME)
+            .unwrap();
+        assert_eq!(name.vr(), Some(VR::PN));
+        assert_eq!(name.path().count(), 0);
+        let offset = name.value_offset().unwrap() as usize;
+        let len = name.value_length().0 as usize;
+        assert_eq!(
+            &std::fs::read(&path).unwrap()[offset..offset + len],
+            b"M\xFCller^J\xF6rg "
+        );
+        let nested: Vec<_> = index
+            .entries()
+            .iter()
+            .filter(|e| e.tag() == tags::REFERENCED_SOP_INSTANCE_UID)
+            .map(|e| e.path().collect::<Vec<_>>())
+            .collect();
+        assert_eq!(
+            nested,
+            vec![
+                vec![(tags::REFERENCED_IMAGE_SEQUENCE, 0)],
+                vec![(tags::REFERENCED_IMAGE_SEQUENCE, 2)],
+            ]
+        );
+
+        let obj = LazyDicomObject::open_with_index(&path, &index).unwrap();
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME)
+                .unwrap()
+                .value()
+                .unwrap()
+                .to_str(),
+            "Müller^Jörg"
+        );
+        assert_same_as_parsed(&obj, &path);
+    }
+
+    #[cfg(feature = "index")]
+    #[test]
+    fn save_and_load_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.dcm");
+        let index_path = dir.path().join("fixture.idx");
+        write_fixture(&path);
+
+        let index = DicomIndex::build(&path).unwrap();
+        index.save_to_file(&index_path).unwrap();
+        let loaded = DicomIndex::load_from_file(&index_path).unwrap();
+        assert_eq!(loaded, index);
+
+        let obj = LazyDicomObject::open_with_index(&path, &loaded).unwrap();
+        assert_same_as_parsed(&obj, &path);
+
+        // not an index
+        let err = DicomIndex::load(&b"DICM and some more bytes"[..]).unwrap_err();
+        assert!(matches!(err, IndexError::NotAnIndex { .. }), "{:?}", err);
+
+        // a future version of the format
+        let mut bytes = Vec::new();
+        index.save(&mut bytes).unwrap();
+        bytes[8] = 0xFF;
+        let err = DicomIndex::load(&bytes[..]).unwrap_err();
+        assert!(
+            matches!(err, IndexError::UnsupportedVersion { .. }),
+            "{:?}",
+            err
+        );
+
+        // truncated index
+        let mut bytes = Vec::new();
+        index.save(&mut bytes).unwrap();
+        let err = DicomIndex::load(&bytes[..bytes.len() - 5]).unwrap_err();
+        assert!(
+            matches!(err, IndexError::DeserializeIndex { .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn reject_stale_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.dcm");
+        write_fixture(&path);
+        let index = DicomIndex::build(&path).unwrap();
+
+        // same size, different modification time
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(3600))
+            .unwrap();
+        drop(file);
+        assert!(!index.is_fresh(&path).unwrap());
+        let err = LazyDicomObject::open_with_index(&path, &index).unwrap_err();
+        assert!(matches!(err, IndexError::StaleIndex { .. }), "{:?}", err);
+
+        // different contents of the same size
+        let mut bytes = std::fs::read(&path).unwrap();
+        let modality = index
+            .entries()
+            .iter()
+            .find(|e| e.tag() == tags::MODALITY)
+            .unwrap()
+            .value_offset()
+            .unwrap() as usize;
+        bytes[modality] = b'C';
+        std::fs::write(&path, bytes).unwrap();
+        let err = LazyDicomObject::open_with_index(&path, &index).unwrap_err();
+        assert!(matches!(err, IndexError::StaleIndex { .. }), "{:?}", err);
+
+        // a rebuilt index is accepted again
+        let index = DicomIndex::build(&path).unwrap();
+        let obj = LazyDicomObject::open_with_index(&path, &index).unwrap();
+        assert_eq!(
+            obj.element(Tag(0x0008, 0x0060))
+                .unwrap()
+                .value()
+                .unwrap()
+                .to_str(),
+            "CT"
+        );
+    }
+}
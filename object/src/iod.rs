@@ -0,0 +1,1017 @@
+//! Validation of DICOM objects
+//! against the modules of their information object definition (IOD).
+//!
+//! Each [`Iod`] lists the [`Module`]s which it requires,
+//! and each module lists its attributes with their [`AttributeType`].
+//! [`validate_iod`] looks up the IOD of an object by its SOP Class UID
+//! in the [standard table](IodTable::standard),
+//! and checks that the object has every Type 1 attribute with a value
+//! and every Type 2 attribute, even if empty.
+//! The file meta information is checked as well,
+//! and so is the number of values of each element of the data set
+//! against the value multiplicity in the data dictionary
+//! (see [`check_multiplicity`]).
+//!
+//! The standard table covers a starter set of IODs
+//! (CT Image, MR Image, and Secondary Capture Image),
+//! with the Type 1 and Type 2 attributes of their mandatory modules.
+//! Tables can be extended with more IODs and modules,
+//! defined in the same way:
+//!
+//! ```
+//! use dicom_dictionary_std::tags;
+//! use dicom_object::iod::{
+//!     Attribute, AttributeType, Iod, IodTable, Module, PATIENT_MODULE, SOP_COMMON_MODULE,
+//! };
+//!
+//! const ENCAPSULATED_DOCUMENT_MODULE: Module = Module::new(
+//!     "Encapsulated Document",
+//!     &[
+//!         Attribute::new(
+//!             tags::MIME_TYPE_OF_ENCAPSULATED_DOCUMENT,
+//!             "MIMETypeOfEncapsulatedDocument",
+//!             AttributeType::Type1,
+//!         ),
+//!         Attribute::new(
+//!             tags::ENCAPSULATED_DOCUMENT,
+//!             "EncapsulatedDocument",
+//!             AttributeType::Type1,
+//!         ),
+//!     ],
+//! );
+//!
+//! let table = IodTable::standard().with_iod(Iod::new(
+//!     "Encapsulated PDF",
+//!     &["1.2.840.10008.5.1.4.1.1.104.1"],
+//!     &[PATIENT_MODULE, ENCAPSULATED_DOCUMENT_MODULE, SOP_COMMON_MODULE],
+//! ));
+//! assert!(table.find("1.2.840.10008.5.1.4.1.1.104.1").is_some());
+//! ```
+//!
+//! Conditional attributes (Types 1C and 2C)
+//! are currently checked as their unconditional counterparts.
+use std::fmt;
+
+use dicom_core::dictionary::{DataDictionaryEntry, ValueMultiplicity};
+use dicom_core::header::Header;
+use dicom_core::value::Value;
+use dicom_core::{DataDictionary, Tag};
+use dicom_dictionary_std::{tags, uids};
+
+use crate::mem::{is_empty_value, InMemDicomObject};
+use crate::FileDicomObject;
+
+/// The requirement type of an attribute in a module.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum AttributeType {
+    /// Required, with a value.
+    Type1,
+    /// Required with a value under some condition.
+    ///
+    /// Checked as [`Type1`](AttributeType::Type1).
+    Type1C,
+    /// Required, but may be empty.
+    Type2,
+    /// Required under some condition, but may be empty.
+    ///
+    /// Checked as [`Type2`](AttributeType::Type2).
+    Type2C,
+    /// Optional.
+    Type3,
+}
+
+impl fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AttributeType::Type1 => "Type 1",
+            AttributeType::Type1C => "Type 1C",
+            AttributeType::Type2 => "Type 2",
+            AttributeType::Type2C => "Type 2C",
+            AttributeType::Type3 => "Type 3",
+        })
+    }
+}
+
+/// An attribute of a module, with its requirement type.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct Attribute {
+    /// the tag of the attribute
+    pub tag: Tag,
+    /// the keyword of the attribute
+    pub keyword: &'static str,
+    /// the requirement type of the attribute
+    pub typ: AttributeType,
+}
+
+impl Attribute {
+    /// Define an attribute of a module.
+    pub const fn new(tag: Tag, keyword: &'static str, typ: AttributeType) -> Self {
+        Attribute { tag, keyword, typ }
+    }
+}
+
+/// A module: a named group of attributes.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct Module {
+    /// the name of the module, as in PS3.3
+    pub name: &'static str,
+    /// the attributes of the module
+    pub attributes: &'static [Attribute],
+}
+
+impl Module {
+    /// Define a module with the given attributes.
+    pub const fn new(name: &'static str, attributes: &'static [Attribute]) -> Self {
+        Module { name, attributes }
+    }
+}
+
+/// An information object definition:
+/// the modules required of the objects of some SOP classes.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct Iod {
+    /// the name of the IOD
+    pub name: &'static str,
+    /// the SOP Class UIDs of the objects defined by the IOD
+    pub sop_class_uids: &'static [&'static str],
+    /// the modules required by the IOD
+    pub modules: &'static [Module],
+}
+
+impl Iod {
+    /// Define an IOD for the given SOP classes with the given modules.
+    pub const fn new(
+        name: &'static str,
+        sop_class_uids: &'static [&'static str],
+        modules: &'static [Module],
+    ) -> Self {
+        Iod {
+            name,
+            sop_class_uids,
+            modules,
+        }
+    }
+}
+
+use AttributeType::*;
+
+/// The File Meta Information, checked in every file object.
+pub const FILE_META_MODULE: Module = Module::new(
+    "File Meta Information",
+    &[
+        Attribute::new(
+            tags::FILE_META_INFORMATION_VERSION,
+            "FileMetaInformationVersion",
+            Type1,
+        ),
+        Attribute::new(
+            tags::MEDIA_STORAGE_SOP_CLASS_UID,
+            "MediaStorageSOPClassUID",
+            Type1,
+        ),
+        Attribute::new(
+            tags::MEDIA_STORAGE_SOP_INSTANCE_UID,
+            "MediaStorageSOPInstanceUID",
+            Type1,
+        ),
+        Attribute::new(tags::TRANSFER_SYNTAX_UID, "TransferSyntaxUID", Type1),
+        Attribute::new(
+            tags::IMPLEMENTATION_CLASS_UID,
+            "ImplementationClassUID",
+            Type1,
+        ),
+    ],
+);
+
+/// The Patient module.
+pub const PATIENT_MODULE: Module = Module::new(
+    "Patient",
+    &[
+        Attribute::new(tags::PATIENT_NAME, "PatientName", Type2),
+        Attribute::new(tags::PATIENT_ID, "PatientID", Type2),
+        Attribute::new(tags::PATIENT_BIRTH_DATE, "PatientBirthDate", Type2),
+        Attribute::new(tags::PATIENT_SEX, "PatientSex", Type2),
+    ],
+);
+
+/// The General Study module.
+pub const GENERAL_STUDY_MODULE: Module = Module::new(
+    "General Study",
+    &[
+        Attribute::new(tags::STUDY_INSTANCE_UID, "StudyInstanceUID", Type1),
+        Attribute::new(tags::STUDY_DATE, "StudyDate", Type2),
+        Attribute::new(tags::STUDY_TIME, "StudyTime", Type2),
+        Attribute::new(
+            tags::REFERRING_PHYSICIAN_NAME,
+            "ReferringPhysicianName",
+            Type2,
+        ),
+        Attribute::new(tags::STUDY_ID, "StudyID", Type2),
+        Attribute::new(tags::ACCESSION_NUMBER, "AccessionNumber", Type2),
+    ],
+);
+
+/// The General Series module.
+pub const GENERAL_SERIES_MODULE: Module = Module::new(
+    "General Series",
+    &[
+        Attribute::new(tags::MODALITY, "Modality", Type1),
+        Attribute::new(tags::SERIES_INSTANCE_UID, "SeriesInstanceUID", Type1),
+        Attribute::new(tags::SERIES_NUMBER, "SeriesNumber", Type2),
+    ],
+);
+
+/// The Frame of Reference module.
+pub const FRAME_OF_REFERENCE_MODULE: Module = Module::new(
+    "Frame of Reference",
+    &[
+        Attribute::new(tags::FRAME_OF_REFERENCE_UID, "FrameOfReferenceUID", Type1),
+        Attribute::new(
+            tags::POSITION_REFERENCE_INDICATOR,
+            "PositionReferenceIndicator",
+            Type2,
+        ),
+    ],
+);
+
+/// The General Equipment module.
+pub const GENERAL_EQUIPMENT_MODULE: Module = Module::new(
+    "General Equipment",
+    &[Attribute::new(tags::MANUFACTURER, "Manufacturer", Type2)],
+);
+
+/// The SC Equipment module.
+pub const SC_EQUIPMENT_MODULE: Module = Module::new(
+    "SC Equipment",
+    &[Attribute::new(
+        tags::CONVERSION_TYPE,
+        "ConversionType",
+        Type1,
+    )],
+);
+
+/// The General Image module.
+pub const GENERAL_IMAGE_MODULE: Module = Module::new(
+    "General Image",
+    &[Attribute::new(
+        tags::INSTANCE_NUMBER,
+        "InstanceNumber",
+        Type2,
+    )],
+);
+
+/// The Image Plane module.
+pub const IMAGE_PLANE_MODULE: Module = Module::new(
+    "Image Plane",
+    &[
+        Attribute::new(tags::PIXEL_SPACING, "PixelSpacing", Type1),
+        Attribute::new(
+            tags::IMAGE_ORIENTATION_PATIENT,
+            "ImageOrientationPatient",
+            Type1,
+        ),
+        Attribute::new(tags::IMAGE_POSITION_PATIENT, "ImagePositionPatient", Type1),
+        Attribute::new(tags::SLICE_THICKNESS, "SliceThickness", Type2),
+    ],
+);
+
+/// The Image Pixel module.
+pub const IMAGE_PIXEL_MODULE: Module = Module::new(
+    "Image Pixel",
+    &[
+        Attribute::new(tags::SAMPLES_PER_PIXEL, "SamplesPerPixel", Type1),
+        Attribute::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            "PhotometricInterpretation",
+            Type1,
+        ),
+        Attribute::new(tags::ROWS, "Rows", Type1),
+        Attribute::new(tags::COLUMNS, "Columns", Type1),
+        Attribute::new(tags::BITS_ALLOCATED, "BitsAllocated", Type1),
+        Attribute::new(tags::BITS_STORED, "BitsStored", Type1),
+        Attribute::new(tags::HIGH_BIT, "HighBit", Type1),
+        Attribute::new(tags::PIXEL_REPRESENTATION, "PixelRepresentation", Type1),
+        // unless the pixel data is provided by reference
+        Attribute::new(tags::PIXEL_DATA, "PixelData", Type1C),
+    ],
+);
+
+/// The CT Image module.
+pub const CT_IMAGE_MODULE: Module = Module::new(
+    "CT Image",
+    &[
+        Attribute::new(tags::IMAGE_TYPE, "ImageType", Type1),
+        Attribute::new(tags::KVP, "KVP", Type2),
+        Attribute::new(tags::ACQUISITION_NUMBER, "AcquisitionNumber", Type2),
+        Attribute::new(tags::RESCALE_INTERCEPT, "RescaleIntercept", Type1),
+        Attribute::new(tags::RESCALE_SLOPE, "RescaleSlope", Type1),
+    ],
+);
+
+/// The MR Image module.
+pub const MR_IMAGE_MODULE: Module = Module::new(
+    "MR Image",
+    &[
+        Attribute::new(tags::IMAGE_TYPE, "ImageType", Type1),
+        Attribute::new(tags::SCANNING_SEQUENCE, "ScanningSequence", Type1),
+        Attribute::new(tags::SEQUENCE_VARIANT, "SequenceVariant", Type1),
+        Attribute::new(tags::SCAN_OPTIONS, "ScanOptions", Type2),
+        Attribute::new(tags::MR_ACQUISITION_TYPE, "MRAcquisitionType", Type2),
+        Attribute::new(tags::ECHO_TIME, "EchoTime", Type2),
+        Attribute::new(tags::ECHO_TRAIN_LENGTH, "EchoTrainLength", Type2),
+    ],
+);
+
+/// The SOP Common module.
+pub const SOP_COMMON_MODULE: Module = Module::new(
+    "SOP Common",
+    &[
+        Attribute::new(tags::SOP_CLASS_UID, "SOPClassUID", Type1),
+        Attribute::new(tags::SOP_INSTANCE_UID, "SOPInstanceUID", Type1),
+    ],
+);
+
+/// The CT Image IOD.
+pub const CT_IMAGE_IOD: Iod = Iod::new(
+    "CT Image",
+    &[uids::CT_IMAGE_STORAGE],
+    &[
+        PATIENT_MODULE,
+        GENERAL_STUDY_MODULE,
+        GENERAL_SERIES_MODULE,
+        FRAME_OF_REFERENCE_MODULE,
+        GENERAL_EQUIPMENT_MODULE,
+        GENERAL_IMAGE_MODULE,
+        IMAGE_PLANE_MODULE,
+        IMAGE_PIXEL_MODULE,
+        CT_IMAGE_MODULE,
+        SOP_COMMON_MODULE,
+    ],
+);
+
+/// The MR Image IOD.
+pub const MR_IMAGE_IOD: Iod = Iod::new(
+    "MR Image",
+    &[uids::MR_IMAGE_STORAGE],
+    &[
+        PATIENT_MODULE,
+        GENERAL_STUDY_MODULE,
+        GENERAL_SERIES_MODULE,
+        FRAME_OF_REFERENCE_MODULE,
+        GENERAL_EQUIPMENT_MODULE,
+        GENERAL_IMAGE_MODULE,
+        IMAGE_PLANE_MODULE,
+        IMAGE_PIXEL_MODULE,
+        MR_IMAGE_MODULE,
+        SOP_COMMON_MODULE,
+    ],
+);
+
+/// The Secondary Capture Image IOD.
+pub const SECONDARY_CAPTURE_IMAGE_IOD: Iod = Iod::new(
+    "Secondary Capture Image",
+    &[uids::SECONDARY_CAPTURE_IMAGE_STORAGE],
+    &[
+        PATIENT_MODULE,
+        GENERAL_STUDY_MODULE,
+        GENERAL_SERIES_MODULE,
+        GENERAL_EQUIPMENT_MODULE,
+        SC_EQUIPMENT_MODULE,
+        GENERAL_IMAGE_MODULE,
+        IMAGE_PIXEL_MODULE,
+        SOP_COMMON_MODULE,
+    ],
+);
+
+/// What is wrong with an attribute.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum FindingKind {
+    /// The attribute is not in the object.
+    Missing,
+    /// The attribute is in the object,
+    /// but without a value.
+    Empty,
+}
+
+/// An attribute of a module which does not meet its requirement type.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct Finding {
+    /// the tag of the attribute
+    pub tag: Tag,
+    /// the keyword of the attribute
+    pub keyword: &'static str,
+    /// the name of the module requiring the attribute
+    pub module: &'static str,
+    /// the requirement type of the attribute in the module
+    pub typ: AttributeType,
+    /// what is wrong with the attribute
+    pub kind: FindingKind,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            FindingKind::Missing => "missing",
+            FindingKind::Empty => "empty",
+        };
+        write!(
+            f,
+            "{} {} {} ({} in {} module)",
+            self.tag, self.keyword, what, self.typ, self.module
+        )
+    }
+}
+
+/// The outcome of validating an object against its IOD.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ValidationReport {
+    /// the SOP Class UID of the object, if any
+    pub sop_class_uid: Option<String>,
+    /// the name of the IOD of the object,
+    /// or `None` if the table has no IOD for its SOP class
+    pub iod: Option<&'static str>,
+    /// the attributes which do not meet their requirement type,
+    /// in the order of the modules of the IOD
+    pub findings: Vec<Finding>,
+    /// the elements of the data set
+    /// whose number of values does not conform to their value multiplicity
+    /// (see [`check_multiplicity`])
+    pub multiplicity: Vec<MultiplicityFinding>,
+}
+
+impl ValidationReport {
+    /// Whether the IOD of the object is known,
+    /// all of its modules are complete,
+    /// and all elements have as many values as their attributes allow.
+    pub fn is_valid(&self) -> bool {
+        self.iod.is_some() && self.findings.is_empty() && self.multiplicity.is_empty()
+    }
+}
+
+/// An element whose number of values
+/// does not conform to the value multiplicity of its attribute.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct MultiplicityFinding {
+    /// the tag of the element
+    pub tag: Tag,
+    /// the keyword of the attribute
+    pub keyword: String,
+    /// the value multiplicity of the attribute
+    pub expected: ValueMultiplicity,
+    /// the number of values in the element
+    pub actual: u32,
+}
+
+impl fmt::Display for MultiplicityFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} has {} values (VM {})",
+            self.tag, self.keyword, self.actual, self.expected
+        )
+    }
+}
+
+/// A table of information object definitions,
+/// by which objects are validated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IodTable {
+    iods: Vec<Iod>,
+}
+
+impl Default for IodTable {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl IodTable {
+    /// Create a table without any IOD.
+    pub fn new() -> Self {
+        IodTable { iods: Vec::new() }
+    }
+
+    /// Create a table with the IODs defined in this module.
+    pub fn standard() -> Self {
+        IodTable {
+            iods: vec![CT_IMAGE_IOD, MR_IMAGE_IOD, SECONDARY_CAPTURE_IMAGE_IOD],
+        }
+    }
+
+    /// Add an IOD to the table,
+    /// taking precedence over any IOD already in the table
+    /// for the same SOP classes.
+    pub fn with_iod(mut self, iod: Iod) -> Self {
+        self.add(iod);
+        self
+    }
+
+    /// Add an IOD to the table,
+    /// taking precedence over any IOD already in the table
+    /// for the same SOP classes.
+    pub fn add(&mut self, iod: Iod) {
+        // later IODs take precedence
+        self.iods.insert(0, iod);
+    }
+
+    /// Find the IOD for the given SOP Class UID.
+    pub fn find(&self, sop_class_uid: &str) -> Option<&Iod> {
+        let sop_class_uid = sop_class_uid.trim_end_matches(['\0', ' ']);
+        self.iods
+            .iter()
+            .find(|iod| iod.sop_class_uids.contains(&sop_class_uid))
+    }
+
+    /// Validate a file object against its file meta information
+    /// and the IOD of its SOP class.
+    ///
+    /// The SOP class is taken from _SOP Class UID_,
+    /// or from the file meta information if the data set does not have one.
+    pub fn validate<D>(&self, obj: &FileDicomObject<InMemDicomObject<D>>) -> ValidationReport
+    where
+        D: DataDictionary + Clone,
+    {
+        let mut findings = Vec::new();
+        let meta: Vec<_> = obj.meta().to_element_iter().collect();
+        check_module(&FILE_META_MODULE, &mut findings, |tag| {
+            meta.iter()
+                .find(|e| e.header().tag == tag)
+                .map(|e| is_empty(e.value()))
+        });
+        let sop_class_uid = sop_class_uid_of(obj).or_else(|| {
+            Some(obj.meta().media_storage_sop_class_uid().to_string()).filter(|uid| !uid.is_empty())
+        });
+        self.validate_with(obj, sop_class_uid, findings)
+    }
+
+    /// Validate a data set against the IOD of its SOP class,
+    /// as declared in _SOP Class UID_.
+    pub fn validate_dataset<D>(&self, obj: &InMemDicomObject<D>) -> ValidationReport
+    where
+        D: DataDictionary + Clone,
+    {
+        self.validate_with(obj, sop_class_uid_of(obj), Vec::new())
+    }
+
+    fn validate_with<D>(
+        &self,
+        obj: &InMemDicomObject<D>,
+        sop_class_uid: Option<String>,
+        mut findings: Vec<Finding>,
+    ) -> ValidationReport
+    where
+        D: DataDictionary + Clone,
+    {
+        let iod = sop_class_uid.as_deref().and_then(|uid| self.find(uid));
+        if let Some(iod) = iod {
+            for module in iod.modules {
+                check_module(module, &mut findings, |tag| {
+                    obj.get(tag).map(|e| is_empty(e.value()))
+                });
+            }
+        }
+        ValidationReport {
+            sop_class_uid,
+            iod: iod.map(|iod| iod.name),
+            findings,
+            multiplicity: check_multiplicity(obj),
+        }
+    }
+}
+
+/// Check the number of values of each element in the root data set
+/// against the value multiplicity of its attribute in the data dictionary
+/// of the object.
+///
+/// The values are counted as in [`PrimitiveValue::multiplicity_as`],
+/// so that text is counted by its backslash-separated values.
+/// Empty elements, sequences,
+/// and elements of attributes without a value multiplicity in the dictionary
+/// are not checked.
+///
+/// [`PrimitiveValue::multiplicity_as`]: dicom_core::PrimitiveValue::multiplicity_as
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElement, PrimitiveValue, VR};
+/// # use dicom_core::dictionary::ValueMultiplicity;
+/// # use dicom_dictionary_std::tags;
+/// # use dicom_object::InMemDicomObject;
+/// use dicom_object::iod::check_multiplicity;
+///
+/// let obj = InMemDicomObject::from_element_iter([DataElement::new(
+///     tags::PIXEL_SPACING,
+///     VR::DS,
+///     PrimitiveValue::from("0.5\\0.5\\1.0"),
+/// )]);
+/// let findings = check_multiplicity(&obj);
+/// assert_eq!(findings[0].expected, ValueMultiplicity::Exactly(2));
+/// assert_eq!(findings[0].actual, 3);
+/// ```
+pub fn check_multiplicity<D>(obj: &InMemDicomObject<D>) -> Vec<MultiplicityFinding>
+where
+    D: DataDictionary + Clone,
+{
+    obj.iter()
+        .filter_map(|e| {
+            let value = e.value().primitive()?;
+            let entry = obj.dict.by_tag(e.tag())?;
+            let expected = entry.vm()?;
+            let actual = value.multiplicity_as(e.vr());
+            if actual == 0 || expected.contains(actual) {
+                return None;
+            }
+            Some(MultiplicityFinding {
+                tag: e.tag(),
+                keyword: entry.alias().to_string(),
+                expected,
+                actual,
+            })
+        })
+        .collect()
+}
+
+/// The SOP Class UID of a data set, without padding.
+fn sop_class_uid_of<D>(obj: &InMemDicomObject<D>) -> Option<String>
+where
+    D: DataDictionary + Clone,
+{
+    obj.get(tags::SOP_CLASS_UID)
+        .and_then(|e| e.to_str().ok())
+        .map(|uid| uid.trim_end_matches(['\0', ' ']).to_string())
+        .filter(|uid| !uid.is_empty())
+}
+
+/// Validate a file object against its file meta information
+/// and the IOD of its SOP class in the [standard table](IodTable::standard).
+///
+/// # Example
+///
+/// ```no_run
+/// use dicom_object::{iod::validate_iod, open_file};
+///
+/// let obj = open_file("0002.dcm")?;
+/// let report = validate_iod(&obj);
+/// for finding in &report.findings {
+///     println!("{}", finding);
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn validate_iod<D>(obj: &FileDicomObject<InMemDicomObject<D>>) -> ValidationReport
+where
+    D: DataDictionary + Clone,
+{
+    IodTable::standard().validate(obj)
+}
+
+/// Check the attributes of a module,
+/// given a function telling whether the attribute with a tag is empty,
+/// or `None` if it is missing.
+fn check_module(
+    module: &Module,
+    findings: &mut Vec<Finding>,
+    lookup: impl Fn(Tag) -> Option<bool>,
+) {
+    for attribute in module.attributes {
+        let kind = match (attribute.typ, lookup(attribute.tag)) {
+            (Type3, _) => continue,
+            (_, None) => FindingKind::Missing,
+            (Type1 | Type1C, Some(true)) => FindingKind::Empty,
+            _ => continue,
+        };
+        findings.push(Finding {
+            tag: attribute.tag,
+            keyword: attribute.keyword,
+            module: module.name,
+            typ: attribute.typ,
+            kind,
+        });
+    }
+}
+
+fn is_empty<I, P>(value: &Value<I, P>) -> bool {
+    match value {
+        Value::Primitive(value) => is_empty_value(value),
+        Value::Sequence(sequence) => sequence.items().is_empty(),
+        Value::PixelSequence(sequence) => sequence.fragments().is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::FileMetaTableBuilder;
+    use dicom_core::value::PixelFragmentSequence;
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::StandardDataDictionary;
+
+    /// a CT image with all Type 1 and Type 2 attributes of its IOD
+    fn ct_image() -> FileDicomObject<InMemDicomObject> {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::IMAGE_TYPE,
+                VR::CS,
+                dicom_value!(Strs, ["ORIGINAL", "PRIMARY", "AXIAL"]),
+            ),
+            DataElement::new(
+                tags::SOP_CLASS_UID,
+                VR::UI,
+                PrimitiveValue::from(uids::CT_IMAGE_STORAGE),
+            ),
+            DataElement::new(
+                tags::SOP_INSTANCE_UID,
+                VR::UI,
+                PrimitiveValue::from("2.25.1234"),
+            ),
+            DataElement::new(tags::STUDY_DATE, VR::DA, PrimitiveValue::from("20240101")),
+            DataElement::new(tags::STUDY_TIME, VR::TM, PrimitiveValue::from("120000")),
+            DataElement::new(tags::ACCESSION_NUMBER, VR::SH, PrimitiveValue::Empty),
+            DataElement::new(tags::MODALITY, VR::CS, PrimitiveValue::from("CT")),
+            DataElement::new(tags::MANUFACTURER, VR::LO, PrimitiveValue::from("ACME")),
+            DataElement::new(
+                tags::REFERRING_PHYSICIAN_NAME,
+                VR::PN,
+                PrimitiveValue::Empty,
+            ),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("Doe^John")),
+            DataElement::new(tags::PATIENT_ID, VR::LO, PrimitiveValue::from("0001")),
+            DataElement::new(tags::PATIENT_BIRTH_DATE, VR::DA, PrimitiveValue::Empty),
+            DataElement::new(tags::PATIENT_SEX, VR::CS, PrimitiveValue::from("O")),
+            DataElement::new(tags::SLICE_THICKNESS, VR::DS, PrimitiveValue::from("1.0")),
+            DataElement::new(tags::KVP, VR::DS, PrimitiveValue::from("120")),
+            DataElement::new(
+                tags::STUDY_INSTANCE_UID,
+                VR::UI,
+                PrimitiveValue::from("2.25.1"),
+            ),
+            DataElement::new(
+                tags::SERIES_INSTANCE_UID,
+                VR::UI,
+                PrimitiveValue::from("2.25.12"),
+            ),
+            DataElement::new(tags::STUDY_ID, VR::SH, PrimitiveValue::from("1")),
+            DataElement::new(tags::SERIES_NUMBER, VR::IS, PrimitiveValue::from("1")),
+            DataElement::new(tags::ACQUISITION_NUMBER, VR::IS, PrimitiveValue::Empty),
+            DataElement::new(tags::INSTANCE_NUMBER, VR::IS, PrimitiveValue::from("1")),
+            DataElement::new(
+                tags::IMAGE_POSITION_PATIENT,
+                VR::DS,
+                dicom_value!(Strs, ["0", "0", "0"]),
+            ),
+            DataElement::new(
+                tags::IMAGE_ORIENTATION_PATIENT,
+                VR::DS,
+                dicom_value!(Strs, ["1", "0", "0", "0", "1", "0"]),
+            ),
+            DataElement::new(
+                tags::FRAME_OF_REFERENCE_UID,
+                VR::UI,
+                PrimitiveValue::from("2.25.123"),
+            ),
+            DataElement::new(
+                tags::POSITION_REFERENCE_INDICATOR,
+                VR::LO,
+                PrimitiveValue::Empty,
+            ),
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, PrimitiveValue::from(1_u16)),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                PrimitiveValue::from("MONOCHROME2"),
+            ),
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(2_u16)),
+            DataElement::new(tags::COLUMNS, VR::US, PrimitiveValue::from(2_u16)),
+            DataElement::new(
+                tags::PIXEL_SPACING,
+                VR::DS,
+                dicom_value!(Strs, ["0.5", "0.5"]),
+            ),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, PrimitiveValue::from(16_u16)),
+            DataElement::new(tags::BITS_STORED, VR::US, PrimitiveValue::from(12_u16)),
+            DataElement::new(tags::HIGH_BIT, VR::US, PrimitiveValue::from(11_u16)),
+            DataElement::new(
+                tags::PIXEL_REPRESENTATION,
+                VR::US,
+                PrimitiveValue::from(0_u16),
+            ),
+            DataElement::new(
+                tags::RESCALE_INTERCEPT,
+                VR::DS,
+                PrimitiveValue::from("-1024"),
+            ),
+            DataElement::new(tags::RESCALE_SLOPE, VR::DS, PrimitiveValue::from("1")),
+            DataElement::new(tags::PIXEL_DATA, VR::OW, dicom_value!(U16, [0, 1, 2, 3])),
+        ]);
+        obj.with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+            .unwrap()
+    }
+
+    #[test]
+    fn validate_complete_ct_image() {
+        let obj = ct_image();
+        let report = validate_iod(&obj);
+        assert_eq!(
+            report.sop_class_uid.as_deref(),
+            Some(uids::CT_IMAGE_STORAGE)
+        );
+        assert_eq!(report.iod, Some("CT Image"));
+        assert_eq!(report.findings, vec![]);
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn validate_ct_image_without_required_attributes() {
+        let mut obj = ct_image();
+        obj.remove_element(tags::SOP_INSTANCE_UID);
+        obj.remove_element(tags::MODALITY);
+
+        let report = validate_iod(&obj);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.findings,
+            vec![
+                Finding {
+                    tag: tags::MODALITY,
+                    keyword: "Modality",
+                    module: "General Series",
+                    typ: AttributeType::Type1,
+                    kind: FindingKind::Missing,
+                },
+                Finding {
+                    tag: tags::SOP_INSTANCE_UID,
+                    keyword: "SOPInstanceUID",
+                    module: "SOP Common",
+                    typ: AttributeType::Type1,
+                    kind: FindingKind::Missing,
+                },
+            ]
+        );
+        assert_eq!(
+            report.findings[0].to_string(),
+            "(0008,0060) Modality missing (Type 1 in General Series module)"
+        );
+    }
+
+    #[test]
+    fn validate_empty_and_missing_attributes() {
+        let mut obj = ct_image();
+        // Type 1, empty
+        obj.put(DataElement::new(tags::ROWS, VR::US, PrimitiveValue::Empty));
+        // Type 2, missing
+        obj.remove_element(tags::PATIENT_NAME);
+        // Type 1C, empty
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(vec![]),
+        ));
+
+        let report = validate_iod(&obj);
+        let findings: Vec<_> = report
+            .findings
+            .iter()
+            .map(|f| (f.tag, f.typ, f.kind))
+            .collect();
+        assert_eq!(
+            findings,
+            vec![
+                (
+                    tags::PATIENT_NAME,
+                    AttributeType::Type2,
+                    FindingKind::Missing
+                ),
+                (tags::ROWS, AttributeType::Type1, FindingKind::Empty),
+                (tags::PIXEL_DATA, AttributeType::Type1C, FindingKind::Empty),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_multiplicity() {
+        let mut obj = ct_image();
+        obj.put(DataElement::new(
+            tags::IMAGE_ORIENTATION_PATIENT,
+            VR::DS,
+            dicom_value!(Strs, ["1", "0", "0", "0", "1"]),
+        ));
+        obj.put(DataElement::new(
+            tags::PIXEL_SPACING,
+            VR::DS,
+            PrimitiveValue::from("0.5\\0.5\\1.0"),
+        ));
+        // 1-n
+        obj.put(DataElement::new(
+            tags::CONVOLUTION_KERNEL,
+            VR::SH,
+            dicom_value!(Strs, ["A", "B", "C", "D", "E", "F", "G"]),
+        ));
+
+        let report = validate_iod(&obj);
+        assert_eq!(report.findings, vec![]);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.multiplicity,
+            vec![
+                MultiplicityFinding {
+                    tag: tags::IMAGE_ORIENTATION_PATIENT,
+                    keyword: "ImageOrientationPatient".to_string(),
+                    expected: ValueMultiplicity::Exactly(6),
+                    actual: 5,
+                },
+                MultiplicityFinding {
+                    tag: tags::PIXEL_SPACING,
+                    keyword: "PixelSpacing".to_string(),
+                    expected: ValueMultiplicity::Exactly(2),
+                    actual: 3,
+                },
+            ]
+        );
+        assert_eq!(
+            report.multiplicity[1].to_string(),
+            "(0028,0030) PixelSpacing has 3 values (VM 2)"
+        );
+
+        // 2-n
+        obj.put(DataElement::new(
+            tags::IMAGE_TYPE,
+            VR::CS,
+            PrimitiveValue::from("ORIGINAL"),
+        ));
+        let findings = check_multiplicity(&obj);
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].tag, tags::IMAGE_TYPE);
+        assert_eq!(findings[0].expected, ValueMultiplicity::AtLeast(2));
+        assert_eq!(findings[0].actual, 1);
+    }
+
+    #[test]
+    fn validate_by_media_storage_sop_class() {
+        let mut obj = ct_image();
+        obj.remove_element(tags::SOP_CLASS_UID);
+        let report = validate_iod(&obj);
+        assert_eq!(report.iod, Some("CT Image"));
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].tag, tags::SOP_CLASS_UID);
+
+        // the data set alone has no SOP class
+        let report = IodTable::standard().validate_dataset(&obj);
+        assert_eq!(report.sop_class_uid, None);
+        assert_eq!(report.iod, None);
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn validate_with_custom_iod() {
+        const CUSTOM_MODULE: Module = Module::new(
+            "Custom",
+            &[
+                Attribute::new(tags::PATIENT_NAME, "PatientName", AttributeType::Type1),
+                Attribute::new(tags::PATIENT_AGE, "PatientAge", AttributeType::Type2),
+                Attribute::new(tags::PATIENT_WEIGHT, "PatientWeight", AttributeType::Type3),
+            ],
+        );
+        let table = IodTable::new().with_iod(Iod::new(
+            "Custom CT",
+            &[uids::CT_IMAGE_STORAGE],
+            &[CUSTOM_MODULE],
+        ));
+        let report = table.validate(&ct_image());
+        assert_eq!(report.iod, Some("Custom CT"));
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].keyword, "PatientAge");
+        assert_eq!(report.findings[0].module, "Custom");
+
+        // the custom IOD takes precedence over the standard one
+        let table = IodTable::standard().with_iod(Iod::new(
+            "Custom CT",
+            &[uids::CT_IMAGE_STORAGE],
+            &[CUSTOM_MODULE],
+        ));
+        assert_eq!(
+            table.find(uids::CT_IMAGE_STORAGE).unwrap().name,
+            "Custom CT"
+        );
+        assert_eq!(table.find(uids::MR_IMAGE_STORAGE).unwrap().name, "MR Image");
+    }
+
+    #[test]
+    fn table_keywords_match_dictionary() {
+        let iods = IodTable::standard().iods;
+        let modules = iods
+            .iter()
+            .flat_map(|iod| iod.modules)
+            .chain([&FILE_META_MODULE]);
+        for module in modules {
+            for attribute in module.attributes {
+                let entry = StandardDataDictionary
+                    .by_tag(attribute.tag)
+                    .unwrap_or_else(|| panic!("{} not in dictionary", attribute.tag));
+                assert_eq!(entry.alias, attribute.keyword, "in {}", module.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,2014 @@
+//! Lazily loaded DICOM objects.
+//!
+//! A [`LazyDicomObject`] is read from a file or other random access data source
+//! without loading element values into memory.
+//! Instead, the data set structure is retained,
+//! with each primitive element holding its header
+//! and the position of its value in the source.
+//! The value is only fetched on first access,
+//! after which it is kept in memory.
+//! Sequences and their items are always read upfront,
+//! so that attributes at any depth can be reached right away.
+//!
+//! This is useful when only a few attributes of a large file are needed,
+//! such as a multi-frame image with a large pixel data element.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_dictionary_std::tags;
+//! use dicom_object::lazy;
+//!
+//! let obj = lazy::open_file("0001.dcm")?;
+//! // nothing was read beyond the element headers so far
+//! let spacing = obj.element(tags::PIXEL_SPACING)?.value()?.to_multi_float64()?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
+use dicom_core::header::{DataElementHeader, HasLength, Header, Length};
+use dicom_core::value::{DataSetSequence, PixelFragmentSequence, PrimitiveValue, Value};
+use dicom_core::{DataElement, Tag, VR};
+use dicom_dictionary_std::StandardDataDictionary;
+use dicom_encoding::adapters::{PixelDataObject, RawPixelData};
+use dicom_encoding::text::SpecificCharacterSet;
+use dicom_encoding::transfer_syntax::{Codec, TransferSyntax, TransferSyntaxIndex};
+use dicom_parser::dataset::lazy_read::LazyDataSetReader;
+use dicom_parser::dataset::{LazyDataToken, LazyDataTokenRepr};
+use dicom_parser::stateful::decode::{DynStatefulDecoder, StatefulDecode};
+use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+use smallvec::SmallVec;
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::index::{IndexEntry, IndexValue};
+use crate::mem::{InMemDicomObject, InMemElement, InMemFragment};
+use crate::{
+    AccessByNameError, AccessError, DicomElement, DicomObject, FileDicomObject, FileMetaTable,
+    NoSuchAttributeNameSnafu, NoSuchDataElementTagSnafu,
+};
+
+/// A DICOM object read from a file or other random access source,
+/// with element values loaded on demand.
+///
+/// See the [module-level documentation](self) for more information.
+pub type LazyDicomObject<S, D = StandardDataDictionary> = FileDicomObject<LazyDataSet<S, D>>;
+
+/// An error which may occur when reading a lazy DICOM object
+/// or loading one of its values.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+#[snafu(visibility(pub(crate)))]
+pub enum LazyReadError {
+    #[snafu(display("Could not open file '{}'", filename.display()))]
+    OpenFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not read preamble bytes
+    ReadPreambleBytes {
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not parse meta group data set
+    ParseMetaDataSet {
+        #[snafu(backtrace)]
+        source: crate::meta::Error,
+    },
+    /// Unsupported transfer syntax `{uid}`
+    ReadUnsupportedTransferSyntax { uid: String, backtrace: Backtrace },
+    /// Could not create data set parser
+    CreateParser {
+        #[snafu(backtrace)]
+        source: dicom_parser::dataset::lazy_read::Error,
+    },
+    /// Could not read data set token
+    ReadToken {
+        #[snafu(backtrace)]
+        source: dicom_parser::dataset::lazy_read::Error,
+    },
+    /// Could not skip element value
+    SkipValue {
+        #[snafu(backtrace)]
+        source: dicom_parser::stateful::decode::Error,
+    },
+    /// Unexpected token {token:?}
+    UnexpectedToken {
+        token: LazyDataTokenRepr,
+        backtrace: Backtrace,
+    },
+    /// Premature data set end
+    PrematureEnd { backtrace: Backtrace },
+    /// Could not seek to value at position {position}
+    SeekValue {
+        position: u64,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not create value decoder
+    CreateDecoder {
+        #[snafu(backtrace)]
+        source: dicom_parser::stateful::decode::Error,
+    },
+    /// Could not read value at position {position}
+    ReadValue {
+        position: u64,
+        #[snafu(backtrace)]
+        source: dicom_parser::stateful::decode::Error,
+    },
+    /// Could not read pixel data fragment at position {position}
+    ReadFragment {
+        position: u64,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Element {tag} does not have a primitive value
+    NotPrimitive { tag: Tag, backtrace: Backtrace },
+    /// Element {tag} is not an encapsulated pixel data sequence
+    NotPixelSequence { tag: Tag, backtrace: Backtrace },
+    /// Missing or invalid attribute `{name}`
+    MissingPixelAttribute {
+        name: &'static str,
+        backtrace: Backtrace,
+    },
+    /// Frame #{frame} is out of range ({number_of_frames} frames)
+    FrameOutOfRange {
+        frame: u32,
+        number_of_frames: u32,
+        backtrace: Backtrace,
+    },
+    /// Frames cannot be randomly accessed without an offset table ({number_of_frames} frames in {fragments} fragments)
+    FrameNotAddressable {
+        number_of_frames: u32,
+        fragments: usize,
+        backtrace: Backtrace,
+    },
+    /// Offset table does not match the pixel data fragments
+    InvalidOffsetTable { backtrace: Backtrace },
+    /// Frames of native pixel data do not start at a byte boundary
+    UnalignedFrame { backtrace: Backtrace },
+    /// Decoding pixel data of transfer syntax `{uid}` is not supported
+    UnsupportedPixelDecoding { uid: String, backtrace: Backtrace },
+    /// Could not decode frame #{frame}
+    DecodeFrame {
+        frame: u32,
+        source: dicom_encoding::adapters::DecodeError,
+    },
+}
+
+pub type Result<T, E = LazyReadError> = std::result::Result<T, E>;
+
+/// The data source shared by all elements of a lazy DICOM object.
+struct LazySource<S> {
+    reader: Mutex<S>,
+    ts: &'static TransferSyntax,
+}
+
+impl<S> fmt::Debug for LazySource<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazySource")
+            .field("ts", &self.ts.uid())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The scope of a Specific Character Set declaration:
+/// either the root data set or a sequence item,
+/// which inherits the character set of its parent
+/// unless it declares its own.
+#[derive(Debug, Default)]
+struct CharsetScope {
+    /// header and value position of the Specific Character Set element
+    /// in this data set
+    element: OnceLock<(DataElementHeader, u64)>,
+    /// the scope of the enclosing data set
+    parent: Option<Arc<CharsetScope>>,
+    /// the resolved character set of the data set
+    resolved: OnceLock<SpecificCharacterSet>,
+}
+
+impl CharsetScope {
+    fn nested(parent: &Arc<CharsetScope>) -> Self {
+        CharsetScope {
+            parent: Some(Arc::clone(parent)),
+            ..Default::default()
+        }
+    }
+}
+
+impl<S> LazySource<S>
+where
+    S: Read + Seek,
+{
+    fn reader(&self) -> std::sync::MutexGuard<'_, S> {
+        // the reader is always repositioned before use,
+        // so a panic while holding the lock does not leave it unusable
+        self.reader.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Resolve the character set of the data set in the given scope,
+    /// loading its Specific Character Set element on first use,
+    /// or falling back to that of the enclosing data set.
+    fn charset(&self, scope: &CharsetScope) -> Result<SpecificCharacterSet> {
+        if let Some(charset) = scope.resolved.get() {
+            return Ok(charset.clone());
+        }
+        let charset = match (scope.element.get(), &scope.parent) {
+            (Some((header, offset)), _) => {
+                let value = self.read_value(header, *offset, SpecificCharacterSet::default())?;
+                SpecificCharacterSet::from_values_with(value.to_multi_str().iter(), |code| {
+                    tracing::warn!(
+                        "Unsupported character set `{}`, decoding text as ISO_IR 100",
+                        code
+                    );
+                })
+            }
+            (None, Some(parent)) => self.charset(parent)?,
+            (None, None) => SpecificCharacterSet::default(),
+        };
+        let _ = scope.resolved.set(charset.clone());
+        Ok(charset)
+    }
+
+    /// Read a primitive value at the given position of the source.
+    fn read_value(
+        &self,
+        header: &DataElementHeader,
+        offset: u64,
+        charset: SpecificCharacterSet,
+    ) -> Result<PrimitiveValue> {
+        let mut reader = self.reader();
+        reader
+            .seek(SeekFrom::Start(offset))
+            .context(SeekValueSnafu { position: offset })?;
+        let mut decoder = DynStatefulDecoder::new_with(&mut *reader, self.ts, charset, offset)
+            .context(CreateDecoderSnafu)?;
+        decoder
+            .read_value_preserved(header)
+            .context(ReadValueSnafu { position: offset })
+    }
+
+    /// Read the given number of bytes at the given position of the source.
+    fn read_bytes(&self, offset: u64, len: u32) -> Result<Vec<u8>> {
+        let mut reader = self.reader();
+        reader
+            .seek(SeekFrom::Start(offset))
+            .context(SeekValueSnafu { position: offset })?;
+        let mut data = vec![0; len as usize];
+        reader
+            .read_exact(&mut data)
+            .context(ReadFragmentSnafu { position: offset })?;
+        Ok(data)
+    }
+}
+
+/// The position of an item in an encapsulated pixel data sequence.
+#[derive(Debug, Copy, Clone)]
+struct FragmentPosition {
+    offset: u64,
+    len: u32,
+}
+
+/// The value of a lazy DICOM element.
+#[derive(Debug)]
+enum LazyValue<S, D> {
+    /// a primitive value in the source, loaded on first access
+    Deferred {
+        source: Arc<LazySource<S>>,
+        /// the character set scope of the data set holding the element
+        scope: Arc<CharsetScope>,
+        offset: u64,
+        value: OnceLock<PrimitiveValue>,
+    },
+    /// an encapsulated pixel data sequence in the source,
+    /// loaded on first access
+    DeferredPixelSequence {
+        source: Arc<LazySource<S>>,
+        /// the basic offset table followed by the fragments
+        items: Vec<FragmentPosition>,
+        value: OnceLock<PixelFragmentSequence<InMemFragment>>,
+    },
+    /// a primitive value held in memory
+    Primitive(PrimitiveValue),
+    /// an encapsulated pixel data sequence held in memory
+    PixelSequence(PixelFragmentSequence<InMemFragment>),
+    /// a data set sequence
+    Sequence(Vec<LazyDataSet<S, D>>),
+}
+
+/// A data element of a [`LazyDataSet`].
+///
+/// The value of a primitive element is only read from the source
+/// when first requested via [`value`](LazyElement::value)
+/// (or [`fragments`](LazyElement::fragments) for encapsulated pixel data).
+/// Replacing the value of an element, or obtaining a mutable reference to it,
+/// turns it into an element held in memory.
+#[derive(Debug)]
+pub struct LazyElement<S, D = StandardDataDictionary> {
+    header: DataElementHeader,
+    value: LazyValue<S, D>,
+}
+
+impl<S, D> HasLength for LazyElement<S, D> {
+    fn length(&self) -> Length {
+        self.header.len
+    }
+}
+
+impl<S, D> Header for LazyElement<S, D> {
+    fn tag(&self) -> Tag {
+        self.header.tag
+    }
+}
+
+impl<S, D> HasLength for &LazyElement<S, D> {
+    fn length(&self) -> Length {
+        self.header.len
+    }
+}
+
+impl<S, D> Header for &LazyElement<S, D> {
+    fn tag(&self) -> Tag {
+        self.header.tag
+    }
+}
+
+impl<S, D> LazyElement<S, D> {
+    /// Retrieve the element header,
+    /// as read from the source.
+    ///
+    /// The length of elements with values in memory
+    /// is that of the value at the time it was last set,
+    /// or undefined if a mutable reference to the value was requested.
+    pub fn header(&self) -> &DataElementHeader {
+        &self.header
+    }
+
+    /// Retrieve the value representation of the element.
+    pub fn vr(&self) -> VR {
+        self.header.vr
+    }
+
+    /// Retrieve the absolute position of the element's value in the source,
+    /// if it was not replaced by an in-memory value.
+    ///
+    /// Data set sequences and encapsulated pixel data do not have one.
+    pub fn offset(&self) -> Option<u64> {
+        match &self.value {
+            LazyValue::Deferred { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Check whether the value of this element is in memory,
+    /// either because it was already loaded or because it was set.
+    ///
+    /// Data set sequences are always considered loaded,
+    /// regardless of the state of the elements in their items.
+    pub fn is_loaded(&self) -> bool {
+        match &self.value {
+            LazyValue::Deferred { value, .. } => value.get().is_some(),
+            LazyValue::DeferredPixelSequence { value, .. } => value.get().is_some(),
+            _ => true,
+        }
+    }
+
+    /// Check whether this element is an encapsulated pixel data sequence.
+    fn is_pixel_sequence(&self) -> bool {
+        matches!(
+            self.value,
+            LazyValue::DeferredPixelSequence { .. } | LazyValue::PixelSequence(_)
+        )
+    }
+
+    /// Retrieve the items of this element,
+    /// if it is a data set sequence.
+    pub fn items(&self) -> Option<&[LazyDataSet<S, D>]> {
+        match &self.value {
+            LazyValue::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Retrieve a mutable reference to the items of this element,
+    /// if it is a data set sequence.
+    ///
+    /// The length of the sequence is reset to undefined.
+    pub fn items_mut(&mut self) -> Option<&mut Vec<LazyDataSet<S, D>>> {
+        match &mut self.value {
+            LazyValue::Sequence(items) => {
+                self.header.len = Length::UNDEFINED;
+                Some(items)
+            }
+            _ => None,
+        }
+    }
+
+    /// Replace the value of this element with the given primitive value,
+    /// held in memory from then on.
+    pub fn set_value(&mut self, value: impl Into<PrimitiveValue>) {
+        let value = value.into();
+        self.header.len = Length(value.calculate_byte_len() as u32);
+        self.value = LazyValue::Primitive(value);
+    }
+}
+
+impl<S, D> LazyElement<S, D>
+where
+    S: Read + Seek,
+{
+    /// Retrieve the primitive value of this element,
+    /// reading it from the source if it was not loaded yet.
+    ///
+    /// Fails if the element is a data set sequence
+    /// or encapsulated pixel data.
+    pub fn value(&self) -> Result<&PrimitiveValue> {
+        match &self.value {
+            LazyValue::Deferred {
+                source,
+                scope,
+                offset,
+                value,
+            } => {
+                if let Some(value) = value.get() {
+                    return Ok(value);
+                }
+                let charset = if has_text(self.header.vr) {
+                    source.charset(scope)?
+                } else {
+                    SpecificCharacterSet::default()
+                };
+                let _ = value.set(source.read_value(&self.header, *offset, charset)?);
+                Ok(value.get().unwrap())
+            }
+            LazyValue::Primitive(value) => Ok(value),
+            _ => NotPrimitiveSnafu {
+                tag: self.header.tag,
+            }
+            .fail(),
+        }
+    }
+
+    /// Retrieve the basic offset table and fragments of this element,
+    /// reading them from the source if they were not loaded yet.
+    ///
+    /// Fails if the element is not an encapsulated pixel data sequence.
+    pub fn fragments(&self) -> Result<&PixelFragmentSequence<InMemFragment>> {
+        match &self.value {
+            LazyValue::DeferredPixelSequence {
+                source,
+                items,
+                value,
+            } => {
+                if let Some(value) = value.get() {
+                    return Ok(value);
+                }
+                let (offset_table, fragments) = match items.split_first() {
+                    Some((table, fragments)) => (*table, fragments),
+                    None => {
+                        let _ = value.set(PixelFragmentSequence::new_fragments(vec![]));
+                        return Ok(value.get().unwrap());
+                    }
+                };
+                let offset_table: Vec<u32> = source
+                    .read_bytes(offset_table.offset, offset_table.len)?
+                    .chunks_exact(4)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                let fragments = fragments
+                    .iter()
+                    .map(|fragment| source.read_bytes(fragment.offset, fragment.len))
+                    .collect::<Result<Vec<_>>>()?;
+                let _ = value.set(PixelFragmentSequence::new(offset_table, fragments));
+                Ok(value.get().unwrap())
+            }
+            LazyValue::PixelSequence(value) => Ok(value),
+            _ => NotPixelSequenceSnafu {
+                tag: self.header.tag,
+            }
+            .fail(),
+        }
+    }
+
+    /// Read the bytes of a single frame of native pixel data,
+    /// each frame having the given size in bytes.
+    fn read_native_frame(&self, frame: u32, frame_size: u64) -> Result<Vec<u8>> {
+        let len = match &self.value {
+            LazyValue::Deferred { value, .. } if value.get().is_none() => {
+                u64::from(self.header.len.0)
+            }
+            _ => self.value()?.calculate_byte_len() as u64,
+        };
+        let start = u64::from(frame) * frame_size;
+        ensure!(
+            frame_size > 0 && start + frame_size <= len,
+            FrameOutOfRangeSnafu {
+                frame,
+                number_of_frames: len.checked_div(frame_size).unwrap_or(0) as u32,
+            }
+        );
+        match &self.value {
+            LazyValue::Deferred {
+                source,
+                offset,
+                value,
+                ..
+            } if value.get().is_none() => source.read_bytes(offset + start, frame_size as u32),
+            _ => {
+                let bytes = self.value()?.to_bytes();
+                Ok(bytes[start as usize..(start + frame_size) as usize].to_vec())
+            }
+        }
+    }
+
+    /// Read the fragments of a single frame of encapsulated pixel data,
+    /// concatenated into one byte vector.
+    ///
+    /// The fragments are located with the given extended offset table,
+    /// or with the basic offset table if there is none.
+    /// Only the fragments of that frame
+    /// (and the basic offset table, if needed) are read from the source.
+    fn read_encapsulated_frame(
+        &self,
+        frame: u32,
+        number_of_frames: u32,
+        extended_offset_table: Option<&[u64]>,
+    ) -> Result<Vec<u8>> {
+        if let LazyValue::DeferredPixelSequence {
+            source,
+            items,
+            value,
+        } = &self.value
+        {
+            if value.get().is_none() {
+                let (basic_offset_table, fragments) = match items.split_first() {
+                    Some((table, fragments)) => (Some(table), fragments),
+                    None => (None, &[][..]),
+                };
+                let offset_table: Vec<u64> = match (extended_offset_table, basic_offset_table) {
+                    (Some(table), _) => table.to_vec(),
+                    (None, Some(table)) if table.len > 0 => source
+                        .read_bytes(table.offset, table.len)?
+                        .chunks_exact(4)
+                        .map(|b| u64::from(u32::from_le_bytes([b[0], b[1], b[2], b[3]])))
+                        .collect(),
+                    (None, _) => Vec::new(),
+                };
+                let fragment_lens: Vec<u32> = fragments.iter().map(|f| f.len).collect();
+                let range =
+                    frame_fragment_range(&offset_table, &fragment_lens, frame, number_of_frames)?;
+                let mut data = Vec::new();
+                for fragment in &fragments[range] {
+                    if fragment.len > 0 {
+                        data.extend(source.read_bytes(fragment.offset, fragment.len)?);
+                    }
+                }
+                return Ok(data);
+            }
+        }
+
+        let seq = self.fragments()?;
+        let offset_table: Vec<u64> = match extended_offset_table {
+            Some(table) => table.to_vec(),
+            None => seq.offset_table().iter().map(|&o| u64::from(o)).collect(),
+        };
+        let fragment_lens: Vec<u32> = seq.fragments().iter().map(|f| f.len() as u32).collect();
+        let range = frame_fragment_range(&offset_table, &fragment_lens, frame, number_of_frames)?;
+        Ok(seq.fragments()[range].concat())
+    }
+
+    /// Retrieve a mutable reference to the primitive value of this element,
+    /// reading it from the source if it was not loaded yet.
+    ///
+    /// The element is held in memory from then on,
+    /// and its length is reset to undefined.
+    pub fn value_mut(&mut self) -> Result<&mut PrimitiveValue> {
+        if let LazyValue::Deferred { .. } = self.value {
+            let value = self.value()?.clone();
+            self.value = LazyValue::Primitive(value);
+        }
+        match &mut self.value {
+            LazyValue::Primitive(value) => {
+                self.header.len = Length::UNDEFINED;
+                Ok(value)
+            }
+            _ => NotPrimitiveSnafu {
+                tag: self.header.tag,
+            }
+            .fail(),
+        }
+    }
+}
+
+impl<S, D> LazyElement<S, D>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Create an in-memory copy of this element,
+    /// loading all values which were not loaded yet.
+    pub fn to_in_mem(&self) -> Result<InMemElement<D>> {
+        let tag = self.header.tag;
+        let vr = self.header.vr;
+        Ok(match &self.value {
+            LazyValue::Deferred { .. } | LazyValue::Primitive(_) => {
+                DataElement::new(tag, vr, self.value()?.clone())
+            }
+            LazyValue::DeferredPixelSequence { .. } | LazyValue::PixelSequence(_) => {
+                DataElement::new(tag, vr, Value::from(self.fragments()?.clone()))
+            }
+            LazyValue::Sequence(items) => DataElement::new(
+                tag,
+                VR::SQ,
+                DataSetSequence::new(
+                    items
+                        .iter()
+                        .map(LazyDataSet::to_in_mem)
+                        .collect::<Result<Vec<_>>>()?,
+                    Length::UNDEFINED,
+                ),
+            ),
+        })
+    }
+}
+
+impl<S, D> LazyElement<S, D>
+where
+    D: Clone,
+{
+    /// Create a lazy element held in memory from an in-memory element.
+    fn from_in_mem(elem: InMemElement<D>, dict: &D) -> Self {
+        let (header, value) = elem.into_parts();
+        let value = match value {
+            Value::Primitive(value) => LazyValue::Primitive(value),
+            Value::PixelSequence(seq) => LazyValue::PixelSequence(seq),
+            Value::Sequence(seq) => LazyValue::Sequence(
+                seq.into_items()
+                    .into_iter()
+                    .map(|item| LazyDataSet::from_in_mem(item, dict.clone()))
+                    .collect(),
+            ),
+        };
+        LazyElement { header, value }
+    }
+}
+
+impl<'s, S: 's, D: 's> DicomElement for &'s LazyElement<S, D>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    type Error = LazyReadError;
+    type Item = &'s LazyDataSet<S, D>;
+    type Items = std::slice::Iter<'s, LazyDataSet<S, D>>;
+
+    fn vr(&self) -> VR {
+        self.header.vr
+    }
+
+    fn primitive_value(&self) -> Result<Option<Cow<'_, PrimitiveValue>>> {
+        match &self.value {
+            LazyValue::Deferred { .. } | LazyValue::Primitive(_) => {
+                self.value().map(|v| Some(Cow::Borrowed(v)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn items(&self) -> Option<Self::Items> {
+        LazyElement::items(*self).map(|items| items.iter())
+    }
+
+    fn fragments(&self) -> Result<Option<Cow<'_, PixelFragmentSequence<InMemFragment>>>> {
+        match &self.value {
+            LazyValue::DeferredPixelSequence { .. } | LazyValue::PixelSequence(_) => {
+                LazyElement::fragments(*self).map(|v| Some(Cow::Borrowed(v)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn offset(&self) -> Option<u64> {
+        LazyElement::offset(*self)
+    }
+}
+
+/// Determine the range of fragments which make up the given frame,
+/// from the frame offsets in an offset table (possibly empty)
+/// and the length of each fragment.
+pub(crate) fn frame_fragment_range(
+    offset_table: &[u64],
+    fragment_lens: &[u32],
+    frame: u32,
+    number_of_frames: u32,
+) -> Result<Range<usize>> {
+    ensure!(
+        frame < number_of_frames,
+        FrameOutOfRangeSnafu {
+            frame,
+            number_of_frames,
+        }
+    );
+    let frame = frame as usize;
+
+    if offset_table.is_empty() {
+        return if number_of_frames == 1 {
+            Ok(0..fragment_lens.len())
+        } else if number_of_frames as usize == fragment_lens.len() {
+            Ok(frame..frame + 1)
+        } else {
+            FrameNotAddressableSnafu {
+                number_of_frames,
+                fragments: fragment_lens.len(),
+            }
+            .fail()
+        };
+    }
+    ensure!(
+        offset_table.len() == number_of_frames as usize,
+        InvalidOffsetTableSnafu
+    );
+
+    // offsets are relative to the first byte of the first fragment's item,
+    // each item having an 8 byte header
+    let mut position = 0;
+    let positions: Vec<u64> = fragment_lens
+        .iter()
+        .map(|&len| {
+            let item_position = position;
+            position += 8 + u64::from(len);
+            item_position
+        })
+        .collect();
+    let fragment_at = |offset: &u64| {
+        positions
+            .binary_search(offset)
+            .ok()
+            .context(InvalidOffsetTableSnafu)
+    };
+    let start = fragment_at(&offset_table[frame])?;
+    let end = match offset_table.get(frame + 1) {
+        Some(offset) => fragment_at(offset)?,
+        None => fragment_lens.len(),
+    };
+    ensure!(start < end, InvalidOffsetTableSnafu);
+    Ok(start..end)
+}
+
+/// Whether values of the given representation
+/// depend on the specific character set of the data set.
+fn has_text(vr: VR) -> bool {
+    matches!(
+        vr,
+        VR::LO | VR::LT | VR::PN | VR::SH | VR::ST | VR::UC | VR::UT
+    )
+}
+
+/// A DICOM data set with element values loaded on demand.
+///
+/// This is the data set type of a [`LazyDicomObject`],
+/// and of the items of its data set sequences.
+#[derive(Debug)]
+pub struct LazyDataSet<S, D = StandardDataDictionary> {
+    entries: BTreeMap<Tag, LazyElement<S, D>>,
+    dict: D,
+    /// the position of the item header, for sequence items read from the source
+    offset: Option<u64>,
+}
+
+impl<S, D> LazyDataSet<S, D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Retrieve a particular DICOM element by its tag,
+    /// or `None` if it is not present.
+    pub fn get(&self, tag: Tag) -> Option<&LazyElement<S, D>> {
+        self.entries.get(&tag)
+    }
+
+    /// Retrieve a mutable reference to a particular DICOM element by its tag,
+    /// or `None` if it is not present.
+    pub fn get_mut(&mut self, tag: Tag) -> Option<&mut LazyElement<S, D>> {
+        self.entries.get_mut(&tag)
+    }
+
+    /// Retrieve a particular DICOM element by its tag.
+    pub fn element(&self, tag: Tag) -> Result<&LazyElement<S, D>, AccessError> {
+        self.entries
+            .get(&tag)
+            .context(NoSuchDataElementTagSnafu { tag })
+    }
+
+    /// Retrieve a particular DICOM element by its name.
+    pub fn element_by_name(&self, name: &str) -> Result<&LazyElement<S, D>, AccessByNameError> {
+        let tag = self
+            .dict
+            .by_name(name)
+            .context(NoSuchAttributeNameSnafu { name })?
+            .tag();
+        self.element(tag).map_err(|e| e.into_access_by_name(name))
+    }
+
+    /// Insert a data element held in memory,
+    /// replacing and returning any previous element of the same tag.
+    pub fn put(&mut self, elem: InMemElement<D>) -> Option<LazyElement<S, D>> {
+        self.entries
+            .insert(elem.tag(), LazyElement::from_in_mem(elem, &self.dict))
+    }
+
+    /// Remove a DICOM element by its tag,
+    /// reporting whether it was present.
+    pub fn remove_element(&mut self, tag: Tag) -> bool {
+        self.entries.remove(&tag).is_some()
+    }
+
+    /// Obtain an iterator over the elements of this data set.
+    pub fn iter(&self) -> impl Iterator<Item = &LazyElement<S, D>> + '_ {
+        self.entries.values()
+    }
+
+    /// Obtain an iterator over the tags of the elements in this data set.
+    pub fn tags(&self) -> impl Iterator<Item = Tag> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Retrieve the number of elements in this data set.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether this data set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Retrieve the position in the source
+    /// of the first byte of the item tag of this data set,
+    /// if it is a sequence item read from the source.
+    ///
+    /// Directory records of a DICOMDIR refer to each other by this position.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+}
+
+impl<'s, S: 's, D: 's> DicomObject for &'s LazyDataSet<S, D>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    type Element = &'s LazyElement<S, D>;
+    type Elements = std::collections::btree_map::Values<'s, Tag, LazyElement<S, D>>;
+
+    fn element(&self, tag: Tag) -> Result<Self::Element, AccessError> {
+        LazyDataSet::element(*self, tag)
+    }
+
+    fn element_by_name(&self, name: &str) -> Result<Self::Element, AccessByNameError> {
+        LazyDataSet::element_by_name(*self, name)
+    }
+
+    fn elements(&self) -> Self::Elements {
+        self.entries.values()
+    }
+}
+
+impl<S, D> LazyDataSet<S, D>
+where
+    D: Clone,
+{
+    /// Create a lazy data set held in memory from an in-memory object.
+    fn from_in_mem(obj: InMemDicomObject<D>, dict: D) -> Self {
+        LazyDataSet {
+            entries: obj
+                .into_iter()
+                .map(|e| (e.tag(), LazyElement::from_in_mem(e, &dict)))
+                .collect(),
+            dict,
+            offset: None,
+        }
+    }
+}
+
+impl<S, D> LazyDataSet<S, D>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Read an integer attribute needed for accessing the pixel data,
+    /// or `None` if it is absent or not a valid integer.
+    fn int_attribute(&self, tag: Tag) -> Result<Option<u32>> {
+        match self.get(tag) {
+            Some(elem) => Ok(elem.value()?.to_int().ok()),
+            None => Ok(None),
+        }
+    }
+
+    /// Create an in-memory copy of this data set,
+    /// loading all values which were not loaded yet.
+    pub fn to_in_mem(&self) -> Result<InMemDicomObject<D>> {
+        let elements = self
+            .entries
+            .values()
+            .map(LazyElement::to_in_mem)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(InMemDicomObject::from_iter_with_dict(
+            elements,
+            self.dict.clone(),
+        ))
+    }
+}
+
+impl<S, D> LazyDataSet<S, D> {
+    /// Collect the index entries of the elements in this data set
+    /// and in its sequence items, depth first,
+    /// given the path of the enclosing sequence items.
+    ///
+    /// Values already held in memory are not in the source,
+    /// so they are left out.
+    pub(crate) fn collect_index_entries(
+        &self,
+        path: &mut Vec<(Tag, u32)>,
+        out: &mut Vec<IndexEntry>,
+    ) {
+        for elem in self.entries.values() {
+            let value = match &elem.value {
+                LazyValue::Deferred { offset, .. } => IndexValue::Primitive { offset: *offset },
+                LazyValue::DeferredPixelSequence { items, .. } => IndexValue::PixelSequence {
+                    items: items.iter().map(|item| (item.offset, item.len)).collect(),
+                },
+                LazyValue::Sequence(items) => IndexValue::Sequence {
+                    items: items.len() as u32,
+                },
+                LazyValue::Primitive(_) | LazyValue::PixelSequence(_) => continue,
+            };
+            out.push(IndexEntry::new(path, &elem.header, value));
+            if let LazyValue::Sequence(items) = &elem.value {
+                for (i, item) in items.iter().enumerate() {
+                    path.push((elem.header.tag, i as u32));
+                    item.collect_index_entries(path, out);
+                    path.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, S, D> IntoIterator for &'a LazyDataSet<S, D> {
+    type Item = &'a LazyElement<S, D>;
+    type IntoIter = std::collections::btree_map::Values<'a, Tag, LazyElement<S, D>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.values()
+    }
+}
+
+/// Open a DICOM file lazily.
+///
+/// This function assumes the standard file encoding structure:
+/// first it automatically detects whether the 128-byte preamble is present,
+/// skipping it if found.
+/// Then it reads the file meta group,
+/// followed by the headers of the rest of the data set.
+/// The file is kept open for loading element values on demand.
+pub fn open_file<P>(path: P) -> Result<LazyDicomObject<BufReader<File>>>
+where
+    P: AsRef<Path>,
+{
+    open_file_with_dict(path, StandardDataDictionary)
+}
+
+/// Open a DICOM file lazily,
+/// using the given data dictionary.
+///
+/// See [`open_file`] for more details.
+pub fn open_file_with_dict<P, D>(path: P, dict: D) -> Result<LazyDicomObject<BufReader<File>, D>>
+where
+    P: AsRef<Path>,
+    D: DataDictionary,
+    D: Clone,
+{
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|_| OpenFileSnafu { filename: path })?;
+    from_reader_with_dict(BufReader::new(file), dict)
+}
+
+/// Read a DICOM object lazily from a random access source,
+/// starting at its current position.
+///
+/// The preamble is detected and skipped automatically.
+/// The source is kept for loading element values on demand.
+pub fn from_reader<S>(src: S) -> Result<LazyDicomObject<S>>
+where
+    S: Read + Seek,
+{
+    from_reader_with_dict(src, StandardDataDictionary)
+}
+
+/// Read a DICOM object lazily from a random access source,
+/// starting at its current position
+/// and using the given data dictionary.
+///
+/// See [`from_reader`] for more details.
+pub fn from_reader_with_dict<S, D>(mut src: S, dict: D) -> Result<LazyDicomObject<S, D>>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    // skip the preamble if present
+    let start = src.stream_position().context(ReadPreambleBytesSnafu)?;
+    let mut buf = [0u8; 132];
+    let has_preamble = match src.read_exact(&mut buf) {
+        Ok(()) => &buf[128..] == b"DICM",
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => false,
+        Err(e) => return Err(e).context(ReadPreambleBytesSnafu),
+    };
+    let meta_start = if has_preamble { start + 128 } else { start };
+    src.seek(SeekFrom::Start(meta_start))
+        .context(ReadPreambleBytesSnafu)?;
+
+    let meta = FileMetaTable::from_reader(&mut src).context(ParseMetaDataSetSnafu)?;
+    let registry: &'static TransferSyntaxRegistry = &TransferSyntaxRegistry;
+    let ts = registry
+        .get(&meta.transfer_syntax)
+        .context(ReadUnsupportedTransferSyntaxSnafu {
+            uid: meta.transfer_syntax.clone(),
+        })?;
+
+    let obj = dataset_from_reader_with_dict(src, ts, dict)?;
+    Ok(FileDicomObject { meta, obj })
+}
+
+/// Read a data set lazily from a random access source,
+/// starting at its current position
+/// and encoded in the given transfer syntax.
+///
+/// Unlike [`from_reader_with_dict`],
+/// the source is not expected to have a preamble or file meta group.
+pub(crate) fn dataset_from_reader_with_dict<S, D>(
+    src: S,
+    ts: &'static TransferSyntax,
+    dict: D,
+) -> Result<LazyDataSet<S, D>>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    let source = Arc::new(LazySource {
+        reader: Mutex::new(src),
+        ts,
+    });
+    let scope = Arc::new(CharsetScope::default());
+
+    let mut reader = source.reader();
+    let mut dataset =
+        LazyDataSetReader::new_with_ts_cs(&mut *reader, ts, SpecificCharacterSet::default())
+            .context(CreateParserSnafu)?;
+    scan_dataset(&mut dataset, &source, &scope, dict, None)
+}
+
+/// Assemble a lazy DICOM object from the entries of an index,
+/// without reading anything from the source.
+///
+/// Returns the position of the first invalid entry on failure.
+pub(crate) fn from_index_entries<S, D>(
+    src: S,
+    ts: &'static TransferSyntax,
+    meta: FileMetaTable,
+    entries: &[IndexEntry],
+    dict: D,
+) -> std::result::Result<LazyDicomObject<S, D>, usize>
+where
+    D: Clone,
+{
+    let source = Arc::new(LazySource {
+        reader: Mutex::new(src),
+        ts,
+    });
+    let scope = Arc::new(CharsetScope::default());
+    let mut iter = entries.iter().enumerate().peekable();
+    let obj = dataset_from_index(&mut iter, &[], &source, &scope, &dict)?;
+    match iter.next() {
+        Some((i, _)) => Err(i),
+        None => Ok(FileDicomObject { meta, obj }),
+    }
+}
+
+/// Assemble the data set at the given sequence item path
+/// from the index entries which belong to it,
+/// which are expected next.
+fn dataset_from_index<'a, S, D>(
+    entries: &mut std::iter::Peekable<impl Iterator<Item = (usize, &'a IndexEntry)>>,
+    path: &[(u16, u16, u32)],
+    source: &Arc<LazySource<S>>,
+    scope: &Arc<CharsetScope>,
+    dict: &D,
+) -> std::result::Result<LazyDataSet<S, D>, usize>
+where
+    D: Clone,
+{
+    let mut out = BTreeMap::new();
+    while let Some((i, entry)) = entries.next_if(|(_, entry)| entry.path == path) {
+        let header = entry.header().ok_or(i)?;
+        let value = match &entry.value {
+            IndexValue::Primitive { offset } => {
+                if header.tag == Tag(0x0008, 0x0005) {
+                    let _ = scope.element.set((header, *offset));
+                }
+                LazyValue::Deferred {
+                    source: Arc::clone(source),
+                    scope: Arc::clone(scope),
+                    offset: *offset,
+                    value: OnceLock::new(),
+                }
+            }
+            IndexValue::Sequence { items } => {
+                let mut item_path = path.to_vec();
+                item_path.push((entry.tag.0, entry.tag.1, 0));
+                let items = (0..*items)
+                    .map(|item| {
+                        // each item may declare its own character set
+                        let item_scope = Arc::new(CharsetScope::nested(scope));
+                        item_path.last_mut().unwrap().2 = item;
+                        dataset_from_index(entries, &item_path, source, &item_scope, dict)
+                    })
+                    .collect::<std::result::Result<_, _>>()?;
+                LazyValue::Sequence(items)
+            }
+            IndexValue::PixelSequence { items } => LazyValue::DeferredPixelSequence {
+                source: Arc::clone(source),
+                items: items
+                    .iter()
+                    .map(|&(offset, len)| FragmentPosition { offset, len })
+                    .collect(),
+                value: OnceLock::new(),
+            },
+        };
+        out.insert(header.tag, LazyElement { header, value });
+    }
+    Ok(LazyDataSet {
+        entries: out,
+        dict: dict.clone(),
+        offset: None,
+    })
+}
+
+impl<S, D> LazyDicomObject<S, D>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Create an in-memory copy of this DICOM object,
+    /// loading all values which were not loaded yet.
+    pub fn to_in_mem(&self) -> Result<FileDicomObject<InMemDicomObject<D>>> {
+        Ok(FileDicomObject {
+            meta: self.meta().clone(),
+            obj: (**self).to_in_mem()?,
+        })
+    }
+}
+
+impl<S, D> LazyDicomObject<S, D>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Read the encoded bytes of a single frame of the pixel data,
+    /// without loading the bytes of other frames.
+    ///
+    /// The fragments of encapsulated pixel data are located
+    /// with the _Extended Offset Table_ if present,
+    /// or with the basic offset table otherwise,
+    /// and returned concatenated.
+    /// Without an offset table,
+    /// frames can only be located when there is a single frame
+    /// or exactly one fragment per frame,
+    /// failing with [`FrameNotAddressable`](LazyReadError::FrameNotAddressable)
+    /// in any other case.
+    pub fn read_frame_data(&self, frame: u32) -> Result<Vec<u8>> {
+        let pixel_data = self
+            .get(Tag(0x7FE0, 0x0010))
+            .context(MissingPixelAttributeSnafu { name: "PixelData" })?;
+        let number_of_frames = self.int_attribute(Tag(0x0028, 0x0008))?.unwrap_or(1);
+
+        if pixel_data.is_pixel_sequence() {
+            let extended_offset_table = match self.get(Tag(0x7FE0, 0x0001)) {
+                Some(elem) => Some(elem.value()?.to_multi_int::<u64>().ok().context(
+                    MissingPixelAttributeSnafu {
+                        name: "ExtendedOffsetTable",
+                    },
+                )?),
+                None => None,
+            };
+            pixel_data.read_encapsulated_frame(
+                frame,
+                number_of_frames,
+                extended_offset_table.as_deref(),
+            )
+        } else {
+            let mut frame_bits = 1;
+            for (tag, name) in [
+                (Tag(0x0028, 0x0010), "Rows"),
+                (Tag(0x0028, 0x0011), "Columns"),
+                (Tag(0x0028, 0x0002), "SamplesPerPixel"),
+                (Tag(0x0028, 0x0100), "BitsAllocated"),
+            ] {
+                let value = self
+                    .int_attribute(tag)?
+                    .context(MissingPixelAttributeSnafu { name })?;
+                frame_bits *= u64::from(value);
+            }
+            ensure!(frame_bits % 8 == 0, UnalignedFrameSnafu);
+            ensure!(
+                frame < number_of_frames,
+                FrameOutOfRangeSnafu {
+                    frame,
+                    number_of_frames,
+                }
+            );
+            pixel_data.read_native_frame(frame, frame_bits / 8)
+        }
+    }
+
+    /// Decode a single frame of the pixel data
+    /// with the pixel data decoder of the object's transfer syntax,
+    /// only reading the bytes of that frame from the source
+    /// (see [`read_frame_data`](Self::read_frame_data)).
+    ///
+    /// The bytes of native pixel data are returned as is.
+    pub fn decode_frame(&self, frame: u32) -> Result<Vec<u8>> {
+        let uid = self.meta().transfer_syntax();
+        let ts = TransferSyntaxRegistry
+            .get(uid)
+            .context(ReadUnsupportedTransferSyntaxSnafu { uid })?;
+        let decoder = match ts.codec() {
+            Codec::EncapsulatedPixelData(Some(decoder), _) => decoder,
+            Codec::EncapsulatedPixelData(None, _) => {
+                return UnsupportedPixelDecodingSnafu { uid }.fail()
+            }
+            Codec::None | Codec::Dataset(_) => return self.read_frame_data(frame),
+        };
+
+        let u16_attribute = |tag| -> Result<Option<u16>> {
+            Ok(self.int_attribute(tag)?.and_then(|v| u16::try_from(v).ok()))
+        };
+        let photometric_interpretation = match self.get(Tag(0x0028, 0x0004)) {
+            Some(elem) => Some(elem.value()?.to_str().trim_end().to_string()),
+            None => None,
+        };
+        let frame_data = FramePixelData {
+            transfer_syntax_uid: uid,
+            rows: u16_attribute(Tag(0x0028, 0x0010))?,
+            cols: u16_attribute(Tag(0x0028, 0x0011))?,
+            samples_per_pixel: u16_attribute(Tag(0x0028, 0x0002))?,
+            bits_allocated: u16_attribute(Tag(0x0028, 0x0100))?,
+            bits_stored: u16_attribute(Tag(0x0028, 0x0101))?,
+            photometric_interpretation,
+            data: self.read_frame_data(frame)?,
+        };
+
+        let mut out = Vec::new();
+        decoder
+            .decode_frame(&frame_data, 0, &mut out)
+            .context(DecodeFrameSnafu { frame })?;
+        Ok(out)
+    }
+}
+
+/// The encoded bytes of a single frame,
+/// presented to a pixel data decoder as a single-frame image.
+struct FramePixelData<'a> {
+    transfer_syntax_uid: &'a str,
+    rows: Option<u16>,
+    cols: Option<u16>,
+    samples_per_pixel: Option<u16>,
+    bits_allocated: Option<u16>,
+    bits_stored: Option<u16>,
+    photometric_interpretation: Option<String>,
+    data: Vec<u8>,
+}
+
+impl PixelDataObject for FramePixelData<'_> {
+    fn transfer_syntax_uid(&self) -> &str {
+        self.transfer_syntax_uid
+    }
+
+    fn rows(&self) -> Option<u16> {
+        self.rows
+    }
+
+    fn cols(&self) -> Option<u16> {
+        self.cols
+    }
+
+    fn samples_per_pixel(&self) -> Option<u16> {
+        self.samples_per_pixel
+    }
+
+    fn bits_allocated(&self) -> Option<u16> {
+        self.bits_allocated
+    }
+
+    fn bits_stored(&self) -> Option<u16> {
+        self.bits_stored
+    }
+
+    fn photometric_interpretation(&self) -> Option<&str> {
+        self.photometric_interpretation.as_deref()
+    }
+
+    fn number_of_frames(&self) -> Option<u32> {
+        Some(1)
+    }
+
+    fn number_of_fragments(&self) -> Option<u32> {
+        Some(1)
+    }
+
+    fn fragment(&self, fragment: usize) -> Option<Cow<'_, [u8]>> {
+        (fragment == 0).then(|| Cow::Borrowed(&self.data[..]))
+    }
+
+    fn offset_table(&self) -> Option<Cow<'_, [u32]>> {
+        None
+    }
+
+    fn raw_pixel_data(&self) -> Option<RawPixelData> {
+        Some(RawPixelData {
+            fragments: smallvec::smallvec![self.data.clone()],
+            offset_table: SmallVec::new(),
+        })
+    }
+}
+
+type LazyReader<'s, S> = LazyDataSetReader<DynStatefulDecoder<&'s mut S>>;
+
+/// Read the structure of a data set,
+/// skipping over primitive values.
+///
+/// Sequence items are given the position of their item header.
+fn scan_dataset<S, D>(
+    dataset: &mut LazyReader<'_, S>,
+    source: &Arc<LazySource<S>>,
+    scope: &Arc<CharsetScope>,
+    dict: D,
+    item_offset: Option<u64>,
+) -> Result<LazyDataSet<S, D>>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    let mut entries = BTreeMap::new();
+    while let Some(token) = dataset.advance() {
+        let elem = match token.context(ReadTokenSnafu)? {
+            LazyDataToken::ElementHeader(_) => {
+                let (header, offset) = match dataset.advance() {
+                    Some(Ok(LazyDataToken::LazyValue { header, decoder })) => {
+                        let offset = decoder.position();
+                        decoder.seek_bytes(header.len.0).context(SkipValueSnafu)?;
+                        (header, offset)
+                    }
+                    Some(Ok(token)) => {
+                        return UnexpectedTokenSnafu {
+                            token: token.into_repr(),
+                        }
+                        .fail()
+                    }
+                    Some(Err(e)) => return Err(e).context(ReadTokenSnafu),
+                    None => return PrematureEndSnafu.fail(),
+                };
+                if header.tag == Tag(0x0008, 0x0005) {
+                    let _ = scope.element.set((header, offset));
+                }
+                LazyElement {
+                    header,
+                    value: LazyValue::Deferred {
+                        source: Arc::clone(source),
+                        scope: Arc::clone(scope),
+                        offset,
+                        value: OnceLock::new(),
+                    },
+                }
+            }
+            LazyDataToken::SequenceStart { tag, len } => {
+                let items = scan_sequence(dataset, source, scope, &dict)?;
+                LazyElement {
+                    header: DataElementHeader::new(tag, VR::SQ, len),
+                    value: LazyValue::Sequence(items),
+                }
+            }
+            LazyDataToken::PixelSequenceStart => {
+                let items = scan_pixel_sequence(dataset)?;
+                LazyElement {
+                    header: DataElementHeader::new(Tag(0x7FE0, 0x0010), VR::OB, Length::UNDEFINED),
+                    value: LazyValue::DeferredPixelSequence {
+                        source: Arc::clone(source),
+                        items,
+                        value: OnceLock::new(),
+                    },
+                }
+            }
+            LazyDataToken::ItemEnd if item_offset.is_some() => break,
+            token => {
+                return UnexpectedTokenSnafu {
+                    token: token.into_repr(),
+                }
+                .fail()
+            }
+        };
+        entries.insert(elem.header.tag, elem);
+    }
+
+    Ok(LazyDataSet {
+        entries,
+        dict,
+        offset: item_offset,
+    })
+}
+
+/// Read the structure of the items in a data set sequence.
+fn scan_sequence<S, D>(
+    dataset: &mut LazyReader<'_, S>,
+    source: &Arc<LazySource<S>>,
+    scope: &Arc<CharsetScope>,
+    dict: &D,
+) -> Result<Vec<LazyDataSet<S, D>>>
+where
+    S: Read + Seek,
+    D: DataDictionary,
+    D: Clone,
+{
+    let mut items = Vec::new();
+    loop {
+        match dataset.advance() {
+            Some(Ok(LazyDataToken::ItemStart { .. })) => {
+                // the item header was just read
+                let offset = dataset.position() - 8;
+                // each item may declare its own character set
+                let item_scope = Arc::new(CharsetScope::nested(scope));
+                items.push(scan_dataset(
+                    dataset,
+                    source,
+                    &item_scope,
+                    dict.clone(),
+                    Some(offset),
+                )?);
+            }
+            Some(Ok(LazyDataToken::SequenceEnd)) => return Ok(items),
+            Some(Ok(token)) => {
+                return UnexpectedTokenSnafu {
+                    token: token.into_repr(),
+                }
+                .fail()
+            }
+            Some(Err(e)) => return Err(e).context(ReadTokenSnafu),
+            None => return PrematureEndSnafu.fail(),
+        }
+    }
+}
+
+/// Read the positions of the items in an encapsulated pixel data sequence,
+/// skipping over their contents.
+fn scan_pixel_sequence<S>(dataset: &mut LazyReader<'_, S>) -> Result<Vec<FragmentPosition>>
+where
+    S: Read + Seek,
+{
+    let mut items = Vec::new();
+    loop {
+        match dataset.advance() {
+            Some(Ok(LazyDataToken::ItemStart { .. })) => {
+                // empty items are not followed by an item value
+                items.push(FragmentPosition { offset: 0, len: 0 });
+            }
+            Some(Ok(LazyDataToken::LazyItemValue { len, decoder })) => {
+                let offset = decoder.position();
+                decoder.seek_bytes(len).context(SkipValueSnafu)?;
+                if let Some(item) = items.last_mut() {
+                    *item = FragmentPosition { offset, len };
+                }
+            }
+            Some(Ok(LazyDataToken::ItemEnd)) => { /* no-op */ }
+            Some(Ok(LazyDataToken::SequenceEnd)) => return Ok(items),
+            Some(Ok(token)) => {
+                return UnexpectedTokenSnafu {
+                    token: token.into_repr(),
+                }
+                .fail()
+            }
+            Some(Err(e)) => return Err(e).context(ReadTokenSnafu),
+            None => return PrematureEndSnafu.fail(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dicom_object, AttributeError, FileMetaTableBuilder};
+    use dicom_dictionary_std::{tags, uids};
+    use std::io::Cursor;
+    use std::ops::Range;
+
+    /// The byte ranges read from a source.
+    type Reads = Arc<Mutex<Vec<Range<u64>>>>;
+
+    /// A reader which records the byte ranges read from it.
+    struct RecordingReader {
+        inner: Cursor<Vec<u8>>,
+        reads: Reads,
+    }
+
+    impl Read for RecordingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let start = self.inner.position();
+            let n = self.inner.read(buf)?;
+            if n > 0 {
+                self.reads.lock().unwrap().push(start..start + n as u64);
+            }
+            Ok(n)
+        }
+    }
+
+    impl Seek for RecordingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    fn pixel_data() -> Vec<u8> {
+        (0..64 * 64).map(|i| i as u8).collect()
+    }
+
+    fn write_file(obj: InMemDicomObject, ts: &str) -> Vec<u8> {
+        let obj = obj
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .transfer_syntax(ts)
+                    .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)
+                    .media_storage_sop_instance_uid("2.25.1"),
+            )
+            .unwrap();
+        let mut out = Vec::new();
+        obj.write_all(&mut out).unwrap();
+        out
+    }
+
+    fn open_recorded(data: Vec<u8>) -> (LazyDicomObject<RecordingReader>, Reads) {
+        let reads = Arc::new(Mutex::new(Vec::new()));
+        let reader = RecordingReader {
+            inner: Cursor::new(data),
+            reads: Arc::clone(&reads),
+        };
+        (from_reader(reader).unwrap(), reads)
+    }
+
+    /// Collect the value ranges of all primitive elements at any depth.
+    fn value_ranges<S, D>(dataset: &LazyDataSet<S, D>, out: &mut Vec<(Tag, Range<u64>)>) {
+        for elem in dataset {
+            if let Some(offset) = elem.offset() {
+                out.push((elem.tag(), offset..offset + u64::from(elem.length().0)));
+            }
+            for item in elem.items().unwrap_or_default() {
+                value_ranges(item, out);
+            }
+        }
+    }
+
+    fn overlaps(a: &Range<u64>, b: &Range<u64>) -> bool {
+        a.start < b.end && b.start < a.end
+    }
+
+    #[test]
+    fn lazy_object_loads_values_on_demand() {
+        let data = write_file(
+            dicom_object! {
+                SpecificCharacterSet: "ISO_IR 100",
+                SOPInstanceUID: "2.25.1",
+                PatientName: "Müller^Hans",
+                ReferencedImageSequence: [
+                    { ReferencedSOPInstanceUID: "2.25.10" },
+                ],
+                Rows: 64_u16,
+                Columns: 64_u16,
+                PixelSpacing: [0.5, 0.25],
+                (0x7FE0, 0x0010): pixel_data(),
+            },
+            uids::EXPLICIT_VR_LITTLE_ENDIAN,
+        );
+        let file_len = data.len() as u64;
+        let (obj, reads) = open_recorded(data);
+
+        let mut ranges = Vec::new();
+        value_ranges(&obj, &mut ranges);
+        assert_eq!(ranges.len(), 8);
+
+        // no value bytes were read while opening the object
+        for read in reads.lock().unwrap().iter() {
+            for (tag, range) in &ranges {
+                assert!(
+                    !overlaps(read, range),
+                    "read {:?} overlaps value of {}",
+                    read,
+                    tag
+                );
+            }
+        }
+        assert!(obj.iter().all(|e| e.is_loaded() == e.items().is_some()));
+
+        let pixel_data_elem = obj.element(tags::PIXEL_DATA).unwrap();
+        assert_eq!(
+            pixel_data_elem.offset().unwrap() + u64::from(pixel_data_elem.length().0),
+            file_len
+        );
+
+        // loading a value reads exactly its bytes
+        reads.lock().unwrap().clear();
+        let spacing = obj.element(tags::PIXEL_SPACING).unwrap();
+        assert_eq!(
+            spacing.value().unwrap().to_multi_float64().unwrap(),
+            vec![0.5, 0.25]
+        );
+        assert!(spacing.is_loaded());
+        let spacing_range = spacing.offset().unwrap()..spacing.offset().unwrap() + 8;
+        assert_eq!(*reads.lock().unwrap(), vec![spacing_range]);
+
+        // loaded values are cached
+        reads.lock().unwrap().clear();
+        spacing.value().unwrap();
+        assert!(reads.lock().unwrap().is_empty());
+
+        assert_eq!(
+            &*pixel_data_elem.value().unwrap().to_bytes(),
+            &pixel_data()[..]
+        );
+        assert!(!obj.element(tags::ROWS).unwrap().is_loaded());
+
+        // text is decoded with the data set's character set
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME)
+                .unwrap()
+                .value()
+                .unwrap()
+                .to_str(),
+            "Müller^Hans"
+        );
+
+        // values in items are loaded on demand too
+        let item = &obj
+            .element(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap()[0];
+        let uid = item.element(tags::REFERENCED_SOP_INSTANCE_UID).unwrap();
+        assert!(!uid.is_loaded());
+        assert_eq!(uid.value().unwrap().to_str(), "2.25.10");
+
+        // sequences do not have a primitive value
+        assert!(matches!(
+            obj.element(tags::REFERENCED_IMAGE_SEQUENCE)
+                .unwrap()
+                .value(),
+            Err(LazyReadError::NotPrimitive { .. })
+        ));
+    }
+
+    #[test]
+    fn lazy_object_typed_getters() {
+        let data = write_file(
+            dicom_object! {
+                SOPInstanceUID: "2.25.1",
+                PatientName: "Doe^John",
+                Rows: 64_u16,
+                PixelSpacing: [0.5, 0.25],
+            },
+            uids::EXPLICIT_VR_LITTLE_ENDIAN,
+        );
+        let (obj, _reads) = open_recorded(data);
+        let obj = &obj;
+
+        assert_eq!(
+            DicomObject::meta(&obj)
+                .unwrap()
+                .media_storage_sop_instance_uid(),
+            "2.25.1"
+        );
+        assert_eq!(obj.elements().count(), 4);
+        assert!(!obj.element(tags::ROWS).unwrap().is_loaded());
+        assert_eq!(obj.u16(tags::ROWS).unwrap(), 64);
+        assert!(obj.element(tags::ROWS).unwrap().is_loaded());
+        assert_eq!(obj.f64s(tags::PIXEL_SPACING).unwrap(), [0.5, 0.25]);
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), "Doe^John");
+        assert!(matches!(
+            obj.string(tags::MODALITY),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
+    }
+
+    #[test]
+    fn lazy_object_character_set_of_items() {
+        let data = write_file(
+            dicom_object! {
+                SpecificCharacterSet: "ISO_IR 100",
+                SOPInstanceUID: "2.25.1",
+                PatientName: "Müller^Hans",
+                OtherPatientIDsSequence: [
+                    { SpecificCharacterSet: "ISO_IR 192", PatientName: "Gößmann^Jörg" },
+                    { PatientName: "Bäcker^Jan" },
+                ],
+                Occupation: "Köhler",
+            },
+            uids::EXPLICIT_VR_LITTLE_ENDIAN,
+        );
+        let (obj, _reads) = open_recorded(data);
+
+        let items = obj
+            .element(tags::OTHER_PATIENT_I_DS_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        // read the nested values first,
+        // so that they do not rely on the root character set being resolved
+        let names: Vec<_> = items
+            .iter()
+            .map(|item| {
+                item.element(tags::PATIENT_NAME)
+                    .unwrap()
+                    .value()
+                    .unwrap()
+                    .to_str()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(names, ["Gößmann^Jörg", "Bäcker^Jan"]);
+        let obj = &obj;
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), "Müller^Hans");
+        assert_eq!(obj.string(tags::OCCUPATION).unwrap(), "Köhler");
+    }
+
+    #[test]
+    fn lazy_object_encapsulated_pixel_data() {
+        let fragments = vec![vec![0x11; 32], vec![0x22; 16]];
+        let mut source = dicom_object! {
+            SOPInstanceUID: "2.25.1",
+            Rows: 64_u16,
+            Columns: 64_u16,
+        };
+        source.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            Value::from(PixelFragmentSequence::new(vec![0], fragments.clone())),
+        ));
+        let (obj, reads) = open_recorded(write_file(source, uids::JPEG_BASELINE8_BIT));
+
+        let pixel_data = obj.element(tags::PIXEL_DATA).unwrap();
+        assert!(!pixel_data.is_loaded());
+        let read_before = reads.lock().unwrap().len();
+
+        let seq = pixel_data.fragments().unwrap();
+        assert_eq!(seq.offset_table(), &[0]);
+        assert_eq!(seq.fragments(), &fragments[..]);
+        assert!(pixel_data.is_loaded());
+        assert_eq!(reads.lock().unwrap().len(), read_before + 3);
+    }
+
+    /// Write a multi-frame image in _Encapsulated Uncompressed_,
+    /// with each frame split into the given number of fragments.
+    fn encapsulated_frames(
+        frames: u32,
+        fragments_per_frame: usize,
+        offset_table: bool,
+        extended_offset_table: bool,
+    ) -> (Vec<u8>, Vec<Vec<u8>>) {
+        let frame_data: Vec<Vec<u8>> = (0..frames)
+            .map(|i| (0..64 * 64).map(|j| (i * 7 + j) as u8).collect())
+            .collect();
+        let mut offsets = Vec::new();
+        let mut fragments = Vec::new();
+        let mut position = 0u64;
+        for data in &frame_data {
+            offsets.push(position);
+            for fragment in data.chunks(data.len() / fragments_per_frame) {
+                position += 8 + fragment.len() as u64;
+                fragments.push(fragment.to_vec());
+            }
+        }
+        let mut source = dicom_object! {
+            SOPInstanceUID: "2.25.1",
+            Rows: 64_u16,
+            Columns: 64_u16,
+            SamplesPerPixel: 1_u16,
+            BitsAllocated: 8_u16,
+            BitsStored: 8_u16,
+            PhotometricInterpretation: "MONOCHROME2",
+            NumberOfFrames: frames.to_string(),
+        };
+        let basic_offset_table = if offset_table && !extended_offset_table {
+            offsets.iter().map(|&o| o as u32).collect()
+        } else {
+            vec![]
+        };
+        if extended_offset_table {
+            source.put(DataElement::new(
+                tags::EXTENDED_OFFSET_TABLE,
+                VR::OV,
+                PrimitiveValue::U64(offsets.into()),
+            ));
+        }
+        source.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            Value::from(PixelFragmentSequence::new(basic_offset_table, fragments)),
+        ));
+        let data = write_file(
+            source,
+            uids::ENCAPSULATED_UNCOMPRESSED_EXPLICIT_VR_LITTLE_ENDIAN,
+        );
+        (data, frame_data)
+    }
+
+    #[test]
+    fn lazy_object_decode_single_frame() {
+        let (data, frames) = encapsulated_frames(50, 1, true, false);
+        let (obj, reads) = open_recorded(data);
+        let fragment_ranges: Vec<Range<u64>> = match &obj.element(tags::PIXEL_DATA).unwrap().value {
+            LazyValue::DeferredPixelSequence { items, .. } => items[1..]
+                .iter()
+                .map(|f| f.offset..f.offset + u64::from(f.len))
+                .collect(),
+            _ => unreachable!(),
+        };
+        assert_eq!(fragment_ranges.len(), 50);
+
+        reads.lock().unwrap().clear();
+        assert_eq!(obj.decode_frame(42).unwrap(), frames[42]);
+
+        let reads = reads.lock().unwrap();
+        let bytes_read: u64 = reads.iter().map(|r| r.end - r.start).sum();
+        assert!(bytes_read < 2 * 64 * 64, "read {} bytes", bytes_read);
+        // no bytes of other frames were read
+        for (i, range) in fragment_ranges.iter().enumerate() {
+            let overlapping = reads.iter().any(|read| overlaps(read, range));
+            assert_eq!(overlapping, i == 42, "fragment #{}", i);
+        }
+        assert!(!obj.element(tags::PIXEL_DATA).unwrap().is_loaded());
+    }
+
+    #[test]
+    fn lazy_object_frames_with_offset_tables() {
+        // frames spanning several fragments, located with either offset table
+        for extended in [false, true] {
+            let (data, frames) = encapsulated_frames(5, 4, true, extended);
+            let (obj, _reads) = open_recorded(data);
+            for i in [0, 3, 4] {
+                assert_eq!(obj.read_frame_data(i).unwrap(), frames[i as usize]);
+            }
+            assert!(matches!(
+                obj.decode_frame(5),
+                Err(LazyReadError::FrameOutOfRange {
+                    frame: 5,
+                    number_of_frames: 5,
+                    ..
+                })
+            ));
+
+            // the same frames are found once the fragments are loaded
+            obj.element(tags::PIXEL_DATA).unwrap().fragments().unwrap();
+            assert_eq!(obj.decode_frame(3).unwrap(), frames[3]);
+        }
+
+        // one fragment per frame does not need an offset table
+        let (data, frames) = encapsulated_frames(5, 1, false, false);
+        let (obj, _reads) = open_recorded(data);
+        assert_eq!(obj.decode_frame(2).unwrap(), frames[2]);
+
+        // but several fragments per frame do
+        let (data, _frames) = encapsulated_frames(5, 2, false, false);
+        let (obj, _reads) = open_recorded(data);
+        assert!(matches!(
+            obj.decode_frame(2),
+            Err(LazyReadError::FrameNotAddressable {
+                number_of_frames: 5,
+                fragments: 10,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn lazy_object_native_frame() {
+        let pixel_data: Vec<u8> = (0..3 * 64 * 64).map(|i| (i / 7) as u8).collect();
+        let data = write_file(
+            dicom_object! {
+                SOPInstanceUID: "2.25.1",
+                Rows: 64_u16,
+                Columns: 64_u16,
+                SamplesPerPixel: 1_u16,
+                BitsAllocated: 8_u16,
+                NumberOfFrames: "3",
+                (0x7FE0, 0x0010): pixel_data.clone(),
+            },
+            uids::EXPLICIT_VR_LITTLE_ENDIAN,
+        );
+        let (obj, reads) = open_recorded(data);
+        let offset = obj.element(tags::PIXEL_DATA).unwrap().offset().unwrap();
+
+        reads.lock().unwrap().clear();
+        assert_eq!(
+            obj.decode_frame(1).unwrap(),
+            &pixel_data[64 * 64..2 * 64 * 64]
+        );
+        // only the bytes of the frame were read from the pixel data
+        let pixel_data_range = offset..offset + 3 * 64 * 64;
+        let pixel_data_reads: Vec<_> = reads
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|read| overlaps(read, &pixel_data_range))
+            .cloned()
+            .collect();
+        assert_eq!(
+            pixel_data_reads,
+            vec![offset + 64 * 64..offset + 2 * 64 * 64]
+        );
+        assert!(matches!(
+            obj.decode_frame(3),
+            Err(LazyReadError::FrameOutOfRange { frame: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn lazy_object_mutation() {
+        let data = write_file(
+            dicom_object! {
+                SOPInstanceUID: "2.25.1",
+                PatientName: "Doe^John",
+                PatientID: "ID0001",
+                Rows: 64_u16,
+                ReferencedImageSequence: [
+                    { ReferencedSOPInstanceUID: "2.25.10" },
+                ],
+            },
+            uids::EXPLICIT_VR_LITTLE_ENDIAN,
+        );
+        let (mut obj, _reads) = open_recorded(data);
+
+        let name = obj.get_mut(tags::PATIENT_NAME).unwrap();
+        name.set_value("Doe^Jane");
+        assert!(name.is_loaded());
+        assert_eq!(name.offset(), None);
+        assert_eq!(name.length(), Length(8));
+
+        let rows = obj.get_mut(tags::ROWS).unwrap();
+        *rows.value_mut().unwrap() = PrimitiveValue::from(128_u16);
+        assert_eq!(rows.offset(), None);
+
+        obj.get_mut(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items_mut()
+            .unwrap()[0]
+            .get_mut(tags::REFERENCED_SOP_INSTANCE_UID)
+            .unwrap()
+            .set_value("2.25.20");
+
+        assert!(obj.remove_element(tags::PATIENT_ID));
+        obj.put(DataElement::new(
+            tags::COLUMNS,
+            VR::US,
+            PrimitiveValue::from(32_u16),
+        ));
+
+        let in_mem = obj.to_in_mem().unwrap();
+        assert_eq!(in_mem.meta().media_storage_sop_instance_uid(), "2.25.1");
+        assert_eq!(in_mem.string(tags::PATIENT_NAME).unwrap(), "Doe^Jane");
+        assert_eq!(in_mem.u16(tags::ROWS).unwrap(), 128);
+        assert_eq!(in_mem.u16(tags::COLUMNS).unwrap(), 32);
+        assert!(in_mem.get(tags::PATIENT_ID).is_none());
+        assert_eq!(
+            in_mem
+                .element_at_path("ReferencedImageSequence.ReferencedSOPInstanceUID")
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "2.25.20"
+        );
+    }
+}
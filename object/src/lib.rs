@@ -48,6 +48,30 @@
 //! # Result::<(), dicom_object::ReadError>::Ok(())
 //! ```
 //!
+//! Alternatively, [`lazy::open_file`] reads only the structure of the data set,
+//! loading element values from the file when they are first accessed.
+//! An [`index::DicomIndex`] of a file's element headers can be kept
+//! to open it lazily again without parsing them.
+//! For very large files which need to be written back,
+//! `spill::SpillOptions` reads the object
+//! while keeping large values in temporary files instead of memory
+//! (requires the `spill` feature, enabled by default).
+//!
+//! DICOM data already in memory can be read with [`from_slice`],
+//! which does not touch the file system.
+//! To avoid copying values out of that data,
+//! [`borrowed::from_slice`] reads an object
+//! whose element values are borrowed from the slice.
+//! Likewise, `open_file_mmap` maps a file into memory
+//! and reads an object borrowing its values from the map
+//! (requires the `mmap` feature).
+//! [`arena::open_file`] reads a file into a single buffer
+//! and indexes the elements in it,
+//! so that values are not allocated one by one.
+//! Along with disabling default features,
+//! this allows the crate to be used in targets without one,
+//! such as `wasm32-unknown-unknown`.
+//!
 //! Once a data set element is looked up,
 //! one will typically wish to inspect the value within.
 //! Methods are available for converting the element's DICOM value
@@ -133,33 +157,89 @@
 //! # }
 //! # run().unwrap();
 //! ```
+pub mod arena;
+pub mod borrowed;
+pub mod datetime;
+pub mod deident;
+pub mod dicomdir;
+pub mod diff;
+pub mod encapsulated_document;
 pub mod file;
+pub mod functional_groups;
+pub mod geometry;
+pub mod index;
+pub mod iod;
+pub mod lazy;
+mod macros;
 pub mod mem;
+pub mod merge;
 pub mod meta;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 pub mod ops;
+pub mod path;
+pub mod scan;
+pub mod sort;
+#[cfg(feature = "spill")]
+pub mod spill;
+pub mod sr;
 pub mod tokens;
-
-pub use crate::file::{from_reader, open_file, OpenFileOptions};
+pub mod uid;
+pub mod visit;
+pub mod waveform;
+pub mod write;
+
+pub use crate::datetime::{CombinedDateTime, DateTimePart};
+pub use crate::file::{from_reader, from_slice, open_file, OpenFileOptions, PartialObject};
+pub use crate::lazy::LazyDicomObject;
+#[cfg(feature = "mmap")]
+pub use crate::mmap::open_file_mmap;
+#[doc(hidden)]
+pub use crate::macros::__private;
 pub use crate::mem::InMemDicomObject;
 pub use crate::meta::{FileMetaTable, FileMetaTableBuilder};
+pub use crate::path::AtPathError;
+use crate::write::{
+    batch_writer, measure_group_lengths, sop_uids, text_encodable, ReplaceGroupLengths,
+    StripGroupLengths, UpgradeCharset,
+};
+pub use crate::write::{
+    EncodeTextPolicy, GroupLengthMode, LengthValidation, MetaValidation, OddLengthPolicy,
+    RepertoireValidation, WriteOptions,
+};
 use dicom_core::ops::AttributeSelector;
 use dicom_core::DataDictionary;
 pub use dicom_core::Tag;
 pub use dicom_dictionary_std::StandardDataDictionary;
+pub use dicom_parser::dataset::read::{
+    DuplicatePolicy, IssueCollector, OddLengthStrategy, ParseIssue, ReadOptions, ReadWarning,
+    Severity, StrayItemStrategy, TagOrderStrategy, WarningCollector, WarningKind,
+};
 
 /// The default implementation of a root DICOM object.
 pub type DefaultDicomObject<D = StandardDataDictionary> = FileDicomObject<mem::InMemDicomObject<D>>;
 
+use crate::mem::{all_tags, first_str, first_tag, is_empty_value, split_values};
+use dicom_core::chrono::FixedOffset;
 use dicom_core::header::{GroupNumber, Header};
+use dicom_core::value::deserialize::parse_time_zone;
+use dicom_core::value::{
+    AsRange, ConvertValueError, DicomDate, DicomDateTime, DicomTime, InMemFragment,
+    PixelFragmentSequence, PreciseDateTime, PrimitiveValue,
+};
+use dicom_core::VR;
+use dicom_dictionary_std::tags;
 use dicom_encoding::adapters::{PixelDataObject, RawPixelData};
-use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
-use dicom_parser::dataset::{DataSetWriter, IntoTokens};
+use dicom_encoding::transfer_syntax::{Codec, TransferSyntaxIndex};
+use dicom_encoding::TransferSyntax;
+use dicom_parser::dataset::{DataSetWriter, IntoTokens, IntoTokensOptions};
 use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+use itertools::Either;
 use smallvec::SmallVec;
 use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
 use std::borrow::Cow;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::Write;
 use std::path::Path;
 
 /// The current implementation class UID generically referring to DICOM-rs.
@@ -179,9 +259,22 @@ pub const IMPLEMENTATION_VERSION_NAME: &str = "DICOM-rs 0.6";
 /// manipulated as dictionary of entries indexed by tags, which in
 /// turn may contain a DICOM object.
 ///
+/// Functionality written against this trait,
+/// such as the typed attribute getters provided here,
+/// works for any object backend:
+/// the in-memory [`InMemDicomObject`],
+/// the lazily loaded [`LazyDicomObject`](lazy::LazyDicomObject),
+/// or third-party implementations.
+/// The trait is usually implemented for a reference to the data set type,
+/// so that elements can be borrowed from it.
+///
 /// This trait interface is experimental and prone to sudden changes.
 pub trait DicomObject {
-    type Element: Header;
+    /// The type of the data elements retrieved from this object.
+    type Element: DicomElement;
+
+    /// The type of iterator over all elements of this object.
+    type Elements: Iterator<Item = Self::Element>;
 
     /// Retrieve a particular DICOM element by its tag.
     fn element(&self, tag: Tag) -> Result<Self::Element, AccessError>;
@@ -189,6 +282,12 @@ pub trait DicomObject {
     /// Retrieve a particular DICOM element by its name.
     fn element_by_name(&self, name: &str) -> Result<Self::Element, AccessByNameError>;
 
+    /// Obtain an iterator over all elements of this object,
+    /// in ascending tag order.
+    ///
+    /// Elements of the file meta group are not included.
+    fn elements(&self) -> Self::Elements;
+
     /// Retrieve the processed meta information table, if available.
     ///
     /// This table will generally not be reachable from children objects
@@ -197,6 +296,185 @@ pub trait DicomObject {
     fn meta(&self) -> Option<&FileMetaTable> {
         None
     }
+
+    /// Retrieve the value of an attribute as a single string.
+    ///
+    /// See [`InMemDicomObject::string`] for the conversion rules.
+    fn string(&self, tag: Tag) -> Result<String, AttributeError> {
+        single_value(self, tag, "string", false, first_str)
+    }
+
+    /// Retrieve the value of an attribute as a sequence of strings.
+    ///
+    /// See [`InMemDicomObject::strings`] for the conversion rules.
+    fn strings(&self, tag: Tag) -> Result<Vec<String>, AttributeError> {
+        multi_value(self, tag, "strings", |v| Ok(v.to_multi_str().into_owned()))
+    }
+
+    /// Retrieve the value of an attribute as a single unsigned 16-bit integer.
+    fn u16(&self, tag: Tag) -> Result<u16, AttributeError> {
+        single_value(self, tag, "u16", false, |v| v.to_int())
+    }
+
+    /// Retrieve all values of an attribute as unsigned 16-bit integers.
+    fn u16s(&self, tag: Tag) -> Result<Vec<u16>, AttributeError> {
+        multi_value(self, tag, "u16s", |v| v.to_multi_int())
+    }
+
+    /// Retrieve the value of an attribute as a single signed 32-bit integer.
+    fn i32(&self, tag: Tag) -> Result<i32, AttributeError> {
+        single_value(self, tag, "i32", false, |v| v.to_int())
+    }
+
+    /// Retrieve all values of an attribute as signed 32-bit integers.
+    fn i32s(&self, tag: Tag) -> Result<Vec<i32>, AttributeError> {
+        multi_value(self, tag, "i32s", |v| v.to_multi_int())
+    }
+
+    /// Retrieve the value of an attribute
+    /// as a single 64-bit floating point number.
+    fn f64(&self, tag: Tag) -> Result<f64, AttributeError> {
+        single_value(self, tag, "f64", false, PrimitiveValue::to_float64)
+    }
+
+    /// Retrieve all values of an attribute
+    /// as 64-bit floating point numbers.
+    fn f64s(&self, tag: Tag) -> Result<Vec<f64>, AttributeError> {
+        multi_value(self, tag, "f64s", PrimitiveValue::to_multi_float64)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM tag.
+    fn tag_value(&self, tag: Tag) -> Result<Tag, AttributeError> {
+        single_value(self, tag, "tag", false, first_tag)
+    }
+
+    /// Retrieve all values of an attribute as DICOM tags.
+    fn tag_values(&self, tag: Tag) -> Result<Vec<Tag>, AttributeError> {
+        multi_value(self, tag, "tags", all_tags)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM date.
+    fn date(&self, tag: Tag) -> Result<DicomDate, AttributeError> {
+        single_value(self, tag, "date", false, PrimitiveValue::to_date)
+    }
+
+    /// Retrieve all values of an attribute as DICOM dates.
+    fn dates(&self, tag: Tag) -> Result<Vec<DicomDate>, AttributeError> {
+        multi_value(self, tag, "dates", PrimitiveValue::to_multi_date)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM time.
+    fn time(&self, tag: Tag) -> Result<DicomTime, AttributeError> {
+        single_value(self, tag, "time", false, PrimitiveValue::to_time)
+    }
+
+    /// Retrieve all values of an attribute as DICOM times.
+    fn times(&self, tag: Tag) -> Result<Vec<DicomTime>, AttributeError> {
+        multi_value(self, tag, "times", PrimitiveValue::to_multi_time)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM date-time.
+    fn datetime(&self, tag: Tag) -> Result<DicomDateTime, AttributeError> {
+        single_value(self, tag, "datetime", false, PrimitiveValue::to_datetime)
+    }
+
+    /// Retrieve all values of an attribute as DICOM date-times.
+    fn datetimes(&self, tag: Tag) -> Result<Vec<DicomDateTime>, AttributeError> {
+        multi_value(self, tag, "datetimes", PrimitiveValue::to_multi_datetime)
+    }
+
+    /// Retrieve the offset from UTC declared in this object
+    /// by _Timezone Offset From UTC_ (0008,0201).
+    ///
+    /// See [`InMemDicomObject::timezone_offset`].
+    fn timezone_offset(&self) -> Result<Option<FixedOffset>, AttributeError> {
+        timezone_offset(self)
+    }
+
+    /// Retrieve the value of a date-time attribute as a precise date-time,
+    /// applying the offset from UTC of this object if the value has none.
+    ///
+    /// See [`InMemDicomObject::precise_datetime`].
+    fn precise_datetime(
+        &self,
+        tag: Tag,
+        default_offset: Option<FixedOffset>,
+    ) -> Result<PreciseDateTime, AttributeError> {
+        precise_datetime(self, tag, default_offset)
+    }
+
+    /// Retrieve a date-time combined from a date attribute
+    /// and a time attribute.
+    ///
+    /// See [`InMemDicomObject::datetime_of`].
+    fn datetime_of(
+        &self,
+        date_tag: Tag,
+        time_tag: Tag,
+    ) -> Result<CombinedDateTime, AttributeError> {
+        datetime::datetime_of(self, date_tag, time_tag)
+    }
+
+    /// Retrieve the date-time of the study,
+    /// from _Study Date_ and _Study Time_.
+    fn study_datetime(&self) -> Result<CombinedDateTime, AttributeError> {
+        self.datetime_of(tags::STUDY_DATE, tags::STUDY_TIME)
+    }
+
+    /// Retrieve the date-time of the acquisition,
+    /// from _Acquisition Date_ and _Acquisition Time_.
+    fn acquisition_datetime(&self) -> Result<CombinedDateTime, AttributeError> {
+        self.datetime_of(tags::ACQUISITION_DATE, tags::ACQUISITION_TIME)
+    }
+
+    /// Retrieve the date-time at which the content was created,
+    /// from _Content Date_ and _Content Time_.
+    fn content_datetime(&self) -> Result<CombinedDateTime, AttributeError> {
+        self.datetime_of(tags::CONTENT_DATE, tags::CONTENT_TIME)
+    }
+}
+
+/// Trait type for a data element retrieved from a [`DicomObject`].
+///
+/// Values are obtained through fallible methods,
+/// so that backends may load them on demand.
+///
+/// This trait interface is experimental and prone to sudden changes.
+pub trait DicomElement: Header {
+    /// The type of error raised when the element's value cannot be retrieved.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// The type of the data set items in a sequence element.
+    type Item: DicomObject;
+
+    /// The type of iterator over the items of a sequence element.
+    type Items: Iterator<Item = Self::Item>;
+
+    /// Retrieve the value representation of this element.
+    fn vr(&self) -> VR;
+
+    /// Retrieve the primitive value of this element,
+    /// or `None` if it is a data set sequence or encapsulated pixel data.
+    fn primitive_value(&self) -> Result<Option<Cow<'_, PrimitiveValue>>, Self::Error>;
+
+    /// Obtain an iterator over the items of this element,
+    /// or `None` if it is not a data set sequence.
+    fn items(&self) -> Option<Self::Items>;
+
+    /// Retrieve the basic offset table and fragments of this element,
+    /// or `None` if it is not encapsulated pixel data.
+    fn fragments(
+        &self,
+    ) -> Result<Option<Cow<'_, PixelFragmentSequence<InMemFragment>>>, Self::Error>;
+
+    /// Retrieve the absolute position of this element's value in its source,
+    /// if known.
+    ///
+    /// Only backends which read values on demand are able to provide one.
+    /// The default implementation returns `None`.
+    fn offset(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// An error which may occur when loading a DICOM object
@@ -246,11 +524,14 @@ pub enum ReadError {
     },
     #[snafu(display("Premature data set end"))]
     PrematureEnd { backtrace: Backtrace },
+    #[snafu(display("Duplicate data element tagged {}", tag))]
+    DuplicateElement { tag: Tag, backtrace: Backtrace },
 }
 
 /// An error which may occur when writing a DICOM object
 #[derive(Debug, Snafu)]
 #[non_exhaustive]
+#[snafu(visibility(pub(crate)))]
 pub enum WriteError {
     #[snafu(display("Could not write to file '{}'", filename.display()))]
     WriteFile {
@@ -285,6 +566,53 @@ pub enum WriteError {
     },
     #[snafu(display("Unsupported transfer syntax `{}`", uid))]
     WriteUnsupportedTransferSyntax { uid: String, backtrace: Backtrace },
+    #[snafu(display(
+        "Cannot transcode from `{}` to `{}` without converting the pixel data",
+        from,
+        to
+    ))]
+    UnsupportedTranscoding {
+        from: String,
+        to: String,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Could not read spilled value from '{}'", filename.display()))]
+    ReadSpilledValue {
+        filename: std::path::PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display(
+        "File meta {} `{}` does not match {} `{}` of the data set",
+        meta_tag,
+        meta_value,
+        tag,
+        value
+    ))]
+    MetaMismatch {
+        meta_tag: Tag,
+        meta_value: String,
+        tag: Tag,
+        value: String,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Cannot write data set as `{}`, which contradicts transfer syntax `{}` of the file meta group",
+        used,
+        declared
+    ))]
+    EncodingMismatch {
+        declared: String,
+        used: String,
+        backtrace: Backtrace,
+    },
+    /// An identifier given to the write options
+    /// is not valid in the file meta group.
+    #[snafu(display("Invalid file meta group identifier"))]
+    InvalidMetaIdentifier {
+        #[snafu(backtrace)]
+        source: crate::meta::Error,
+    },
 }
 
 /// An error which may occur during private element look-up or insertion
@@ -357,6 +685,79 @@ pub enum AtAccessError {
     MissingLeafElement { selector: AttributeSelector },
 }
 
+/// An error which may occur when retrieving or setting the value of an attribute
+/// through one of the typed getters or setters of a DICOM object,
+/// such as [`string`](crate::InMemDicomObject::string)
+/// or [`put_typed`](crate::InMemDicomObject::put_typed).
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+#[snafu(visibility(pub(crate)))]
+pub enum AttributeError {
+    /// Missing attribute {tag}
+    MissingAttribute { tag: Tag, backtrace: Backtrace },
+    /// Attribute {tag} is not in the data dictionary
+    UnknownAttribute { tag: Tag, backtrace: Backtrace },
+    /// Attribute {tag} is empty
+    EmptyValue { tag: Tag, backtrace: Backtrace },
+    /// Attribute {tag} is not a data set sequence
+    NotASequenceAttribute { tag: Tag, backtrace: Backtrace },
+    /// Item index {index} is out of range for attribute {tag} with {len} items
+    ItemOutOfRange {
+        tag: Tag,
+        index: usize,
+        len: usize,
+        backtrace: Backtrace,
+    },
+    /// Attribute {tag} has {count} values where only one was expected
+    MultipleValues {
+        tag: Tag,
+        count: u32,
+        backtrace: Backtrace,
+    },
+    /// Attribute {tag} has {count} values, but its value multiplicity is {vm}
+    InvalidMultiplicity {
+        tag: Tag,
+        vm: dicom_core::dictionary::ValueMultiplicity,
+        count: u32,
+        backtrace: Backtrace,
+    },
+    /// Could not convert value of attribute {tag}
+    ConvertValue {
+        tag: Tag,
+        #[snafu(source(from(dicom_core::value::ConvertValueError, Box::from)))]
+        source: Box<dicom_core::value::ConvertValueError>,
+        backtrace: Backtrace,
+    },
+    /// Could not retrieve value of attribute {tag}
+    RetrieveValue {
+        tag: Tag,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+        backtrace: Backtrace,
+    },
+    /// Invalid Timezone Offset From UTC `{value}`
+    InvalidTimezoneOffset {
+        value: String,
+        #[snafu(source(from(dicom_core::value::DeserializeError, Box::from)))]
+        source: Box<dicom_core::value::DeserializeError>,
+        backtrace: Backtrace,
+    },
+    /// Could not convert value of attribute {tag} to a precise date-time
+    ConvertDateTime {
+        tag: Tag,
+        #[snafu(source(from(dicom_core::value::range::Error, Box::from)))]
+        source: Box<dicom_core::value::range::Error>,
+        backtrace: Backtrace,
+    },
+    /// Could not combine {date_tag} and {time_tag} into a date-time: invalid {part}
+    CombineDateTime {
+        date_tag: Tag,
+        time_tag: Tag,
+        part: DateTimePart,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+        backtrace: Backtrace,
+    },
+}
+
 /// An error which may occur when looking up a DICOM object's attributes
 /// by a keyword (or alias) instead of by tag.
 ///
@@ -419,6 +820,11 @@ impl<O> FileDicomObject<O> {
     pub fn into_inner(self) -> O {
         self.obj
     }
+
+    /// Split the object into its file meta table and inner object.
+    pub fn into_parts(self) -> (FileMetaTable, O) {
+        (self.meta, self.obj)
+    }
 }
 
 impl<O> FileDicomObject<O>
@@ -429,10 +835,27 @@ where
     /// into the given file path.
     /// Preamble, magic code, and file meta group will be included
     /// before the inner object.
+    ///
+    /// Group length elements are removed from the data set.
+    /// See [`write_to_file_with_options`](Self::write_to_file_with_options)
+    /// for other options.
     pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WriteError> {
+        self.write_to_file_with_options(path, &WriteOptions::default())
+    }
+
+    /// Write the entire object as a DICOM file
+    /// into the given file path,
+    /// according to the given options.
+    /// Preamble, magic code, and file meta group will be included
+    /// before the inner object.
+    pub fn write_to_file_with_options<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &WriteOptions,
+    ) -> Result<(), WriteError> {
         let path = path.as_ref();
         let file = File::create(path).context(WriteFileSnafu { filename: path })?;
-        let mut to = BufWriter::new(file);
+        let mut to = batch_writer(file, options);
 
         // write preamble
         to.write_all(&[0_u8; 128][..])
@@ -442,31 +865,45 @@ where
         to.write_all(b"DICM")
             .context(WriteFileSnafu { filename: path })?;
 
-        // write meta group
-        self.meta.write(&mut to).context(PrintMetaDataSetSnafu)?;
-
-        // prepare encoder
-        let ts = TransferSyntaxRegistry
-            .get(&self.meta.transfer_syntax)
-            .with_context(|| WriteUnsupportedTransferSyntaxSnafu {
-                uid: self.meta.transfer_syntax.clone(),
-            })?;
-        let mut dset_writer = DataSetWriter::with_ts(to, ts).context(CreatePrinterSnafu)?;
-
-        // We use the default options, because only the inner object knows if something needs to change
-        dset_writer
-            .write_sequence((&self.obj).into_tokens())
-            .context(PrintDataSetSnafu)?;
-
-        Ok(())
+        self.write_meta_and_dataset(&mut to, options)
     }
 
     /// Write the entire object as a DICOM file
     /// into the given writer.
     /// Preamble, magic code, and file meta group will be included
     /// before the inner object.
+    ///
+    /// Group length elements are removed from the data set.
+    /// See [`write_all_with_options`](Self::write_all_with_options)
+    /// for other options.
     pub fn write_all<W: Write>(&self, to: W) -> Result<(), WriteError> {
-        let mut to = BufWriter::new(to);
+        self.write_all_with_options(to, &WriteOptions::default())
+    }
+
+    /// Write the entire object as a DICOM file
+    /// into the given writer,
+    /// according to the given options.
+    /// Preamble, magic code, and file meta group will be included
+    /// before the inner object.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_object::{open_file, GroupLengthMode, WriteOptions};
+    /// let obj = open_file("0001.dcm")?;
+    /// let mut out = Vec::new();
+    /// obj.write_all_with_options(
+    ///     &mut out,
+    ///     &WriteOptions::new().group_length(GroupLengthMode::Recompute),
+    /// )?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn write_all_with_options<W: Write>(
+        &self,
+        to: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteError> {
+        let mut to = batch_writer(to, options);
 
         // write preamble
         to.write_all(&[0_u8; 128][..]).context(WritePreambleSnafu)?;
@@ -474,23 +911,25 @@ where
         // write magic sequence
         to.write_all(b"DICM").context(WriteMagicCodeSnafu)?;
 
-        // write meta group
-        self.meta.write(&mut to).context(PrintMetaDataSetSnafu)?;
-
-        // prepare encoder
-        let ts = TransferSyntaxRegistry
-            .get(&self.meta.transfer_syntax)
-            .with_context(|| WriteUnsupportedTransferSyntaxSnafu {
-                uid: self.meta.transfer_syntax.clone(),
-            })?;
-        let mut dset_writer = DataSetWriter::with_ts(to, ts).context(CreatePrinterSnafu)?;
-
-        // We use the default options, because only the inner object knows if something needs to change
-        dset_writer
-            .write_sequence((&self.obj).into_tokens())
-            .context(PrintDataSetSnafu)?;
+        self.write_meta_and_dataset(&mut to, options)
+    }
 
-        Ok(())
+    /// Write the entire object as a DICOM file
+    /// into the given writer,
+    /// re-encoded in the transfer syntax with the given UID.
+    ///
+    /// The file meta group is written with the new transfer syntax UID,
+    /// and all data set sequences are written with undefined length,
+    /// as any previously recorded lengths may no longer apply.
+    /// Values are re-encoded according to their in-memory representation,
+    /// so that typed binary values follow the byte order of the new encoding.
+    ///
+    /// Only transcoding between transfer syntaxes
+    /// which do not encapsulate the pixel data is supported.
+    /// If `ts_uid` is the object's current transfer syntax,
+    /// this is equivalent to [`write_all`](Self::write_all).
+    pub fn write_with_syntax<W: Write>(&self, to: W, ts_uid: &str) -> Result<(), WriteError> {
+        self.write_all_with_options(to, &WriteOptions::new().transfer_syntax(ts_uid))
     }
 
     /// Write the file meta group set into the given writer.
@@ -504,24 +943,278 @@ where
     /// without preamble, magic code, nor file meta group.
     ///
     /// The transfer syntax is selected from the file meta table.
+    /// Group length elements are removed from the data set.
     pub fn write_dataset<W: Write>(&self, to: W) -> Result<(), WriteError> {
-        let to = BufWriter::new(to);
+        self.write_dataset_with_options(to, &WriteOptions::default())
+    }
+
+    /// Write the inner data set into the given writer,
+    /// without preamble, magic code, nor file meta group,
+    /// according to the given options.
+    ///
+    /// Unless [meta validation](WriteOptions::meta_validation) is off,
+    /// the data set can only be written in a transfer syntax
+    /// with the same byte order and VR explicitness
+    /// as the one in the file meta table.
+    pub fn write_dataset_with_options<W: Write>(
+        &self,
+        to: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteError> {
+        let ts = self.target_transfer_syntax(options)?;
+        if options.meta_validation != MetaValidation::Off {
+            check_encoding(self.meta.transfer_syntax(), ts)?;
+        }
+        self.write_dataset_impl(&mut batch_writer(to, options), ts, options)
+    }
+
+    /// Resolve the transfer syntax for writing the data set,
+    /// checking that the data set can be transcoded to it.
+    fn target_transfer_syntax(
+        &self,
+        options: &WriteOptions,
+    ) -> Result<&'static TransferSyntax, WriteError> {
+        let registry: &'static TransferSyntaxRegistry = &TransferSyntaxRegistry;
+        let current_uid = self.meta.transfer_syntax();
+        let ts_uid = options.transfer_syntax.as_deref().unwrap_or(current_uid);
+        let ts = registry
+            .get(ts_uid)
+            .with_context(|| WriteUnsupportedTransferSyntaxSnafu { uid: ts_uid })?;
+        if current_uid == ts.uid() {
+            return Ok(ts);
+        }
+
+        let current_ts = registry
+            .get(current_uid)
+            .with_context(|| WriteUnsupportedTransferSyntaxSnafu { uid: current_uid })?;
+        snafu::ensure!(
+            !matches!(current_ts.codec(), Codec::EncapsulatedPixelData(..))
+                && !matches!(ts.codec(), Codec::EncapsulatedPixelData(..)),
+            UnsupportedTranscodingSnafu {
+                from: current_uid,
+                to: ts.uid(),
+            }
+        );
+        Ok(ts)
+    }
+
+    fn write_meta_and_dataset<W: Write>(
+        &self,
+        mut to: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteError> {
+        let ts = self.target_transfer_syntax(options)?;
+
+        // write meta group
+        let mut meta = Cow::Borrowed(&self.meta);
+        if ts.uid() != meta.transfer_syntax() {
+            meta.to_mut().set_transfer_syntax(ts);
+        }
+        apply_identifiers(&mut meta, options)?;
+        if options.meta_validation != MetaValidation::Off {
+            let (sop_class_uid, sop_instance_uid) = sop_uids(
+                self.obj
+                    .into_tokens_with_options(IntoTokensOptions::new(false)),
+            );
+            check_meta_uid(
+                &mut meta,
+                tags::MEDIA_STORAGE_SOP_CLASS_UID,
+                tags::SOP_CLASS_UID,
+                sop_class_uid,
+                options.meta_validation,
+            )?;
+            check_meta_uid(
+                &mut meta,
+                tags::MEDIA_STORAGE_SOP_INSTANCE_UID,
+                tags::SOP_INSTANCE_UID,
+                sop_instance_uid,
+                options.meta_validation,
+            )?;
+        }
+        meta.write(&mut to).context(PrintMetaDataSetSnafu)?;
+
+        self.write_dataset_impl(to, ts, options)
+    }
+
+    fn write_dataset_impl<W: Write>(
+        &self,
+        to: W,
+        ts: &TransferSyntax,
+        options: &WriteOptions,
+    ) -> Result<(), WriteError> {
+        // Only the inner object knows if something needs to change,
+        // unless the data set is transcoded,
+        // in which case sequence lengths in bytes depend on the encoding
+        let transcoding = ts.uid() != self.meta.transfer_syntax();
+        write_dataset_with_options(&self.obj, to, ts, options, transcoding)
+    }
+}
+
+/// Check a SOP class or instance UID of the file meta table
+/// against the corresponding UID of the data set,
+/// replacing it in the table if fixing.
+fn check_meta_uid(
+    meta: &mut Cow<FileMetaTable>,
+    meta_tag: Tag,
+    tag: Tag,
+    value: Option<String>,
+    validation: MetaValidation,
+) -> Result<(), WriteError> {
+    let value = match value {
+        Some(value) if !value.is_empty() => value,
+        _ => return Ok(()),
+    };
+    let meta_value = match meta_tag {
+        tags::MEDIA_STORAGE_SOP_CLASS_UID => meta.media_storage_sop_class_uid(),
+        _ => meta.media_storage_sop_instance_uid(),
+    };
+    if meta_value == value {
+        return Ok(());
+    }
+    match validation {
+        MetaValidation::Strict => {
+            return MetaMismatchSnafu {
+                meta_tag,
+                meta_value,
+                tag,
+                value,
+            }
+            .fail();
+        }
+        // missing values are filled in silently
+        MetaValidation::Fix if meta_value.is_empty() => {}
+        MetaValidation::Warn | MetaValidation::Fix => {
+            tracing::warn!(
+                "File meta {} `{}` does not match {} `{}` of the data set",
+                meta_tag,
+                meta_value,
+                tag,
+                value
+            );
+        }
+        _ => {}
+    }
+    if validation == MetaValidation::Fix {
+        let meta = meta.to_mut();
+        match meta_tag {
+            tags::MEDIA_STORAGE_SOP_CLASS_UID => meta.media_storage_sop_class_uid = value,
+            _ => meta.media_storage_sop_instance_uid = value,
+        }
+        meta.update_information_group_length();
+    }
+    Ok(())
+}
 
-        // prepare encoder
-        let ts = TransferSyntaxRegistry
-            .get(&self.meta.transfer_syntax)
-            .with_context(|| WriteUnsupportedTransferSyntaxSnafu {
-                uid: self.meta.transfer_syntax.clone(),
-            })?;
-        let mut dset_writer = DataSetWriter::with_ts(to, ts).context(CreatePrinterSnafu)?;
+/// Write the implementation identifiers and source title
+/// given to the write options into the file meta table,
+/// or the identifiers of DICOM-rs if the table has none.
+fn apply_identifiers(
+    meta: &mut Cow<FileMetaTable>,
+    options: &WriteOptions,
+) -> Result<(), WriteError> {
+    let validation = options.repertoire_validation;
+    let class_uid = options.implementation_class_uid.as_deref();
+    let version_name = options.implementation_version_name.as_deref();
+    if let Some(uid) = class_uid {
+        meta::validate_meta_uid(validation, "ImplementationClassUID", uid)
+            .context(InvalidMetaIdentifierSnafu)?;
+    }
+    let identifiers = match (class_uid, version_name) {
+        (Some(uid), name) => Some((uid.to_string(), name)),
+        (None, name) if meta.implementation_class_uid().is_empty() => Some((
+            IMPLEMENTATION_CLASS_UID.to_string(),
+            name.or(Some(IMPLEMENTATION_VERSION_NAME)),
+        )),
+        (None, Some(name)) => Some((meta.implementation_class_uid().to_string(), Some(name))),
+        (None, None) => None,
+    };
+    if let Some((uid, name)) = identifiers {
+        meta.to_mut()
+            .set_implementation(uid, name.map(String::from));
+    }
 
-        // write object
-        dset_writer
-            .write_sequence((&self.obj).into_tokens())
-            .context(PrintDataSetSnafu)?;
+    if let Some(ae_title) = &options.source_application_entity_title {
+        meta::validate_meta_ae_title(validation, "SourceApplicationEntityTitle", ae_title)
+            .context(InvalidMetaIdentifierSnafu)?;
+        meta.to_mut()
+            .set_source_application_entity_title(Some(ae_title.as_str()));
+    }
+    Ok(())
+}
+
+/// Check that the given transfer syntax has the same byte order
+/// and VR explicitness as the declared transfer syntax,
+/// if the latter is known.
+fn check_encoding(declared_uid: &str, ts: &TransferSyntax) -> Result<(), WriteError> {
+    let declared = match TransferSyntaxRegistry.get(declared_uid) {
+        Some(declared) => declared,
+        None => return Ok(()),
+    };
+    snafu::ensure!(
+        declared.endianness() == ts.endianness() && declared.explicit_vr() == ts.explicit_vr(),
+        EncodingMismatchSnafu {
+            declared: declared.uid(),
+            used: ts.uid(),
+        }
+    );
+    Ok(())
+}
 
-        Ok(())
+/// Write the data set of the given object
+/// in the given transfer syntax, according to the given options.
+///
+/// Recorded sequence lengths are only kept
+/// if `invalidate_lengths` is false
+/// and the object itself did not invalidate them.
+/// The transfer syntax and batch size in the options are not used,
+/// so `to` should already be buffered as needed.
+pub(crate) fn write_dataset_with_options<O, W>(
+    obj: &O,
+    to: W,
+    ts: &TransferSyntax,
+    options: &WriteOptions,
+    invalidate_lengths: bool,
+) -> Result<(), WriteError>
+where
+    for<'a> &'a O: IntoTokens,
+    W: Write,
+{
+    let tokens = |invalidate| obj.into_tokens_with_options(IntoTokensOptions::new(invalidate));
+    // changing the character set changes the length of the text
+    let upgrade =
+        options.text_policy == EncodeTextPolicy::UpgradeToUtf8 && !text_encodable(tokens(false));
+    let tokens = || {
+        let tokens = tokens(invalidate_lengths || upgrade);
+        if upgrade {
+            Either::Left(UpgradeCharset::new(tokens))
+        } else {
+            Either::Right(tokens)
+        }
+    };
+
+    let mut dset_writer = DataSetWriter::with_ts(to, ts)
+        .context(CreatePrinterSnafu)?
+        .with_text_policy(options.text_policy)
+        .with_repertoire_validation(options.repertoire_validation)
+        .with_length_validation(options.length_validation)
+        .with_odd_length_policy(options.odd_length);
+    if let Some(marker) = &options.truncation_marker {
+        dset_writer = dset_writer.with_truncation_marker(marker.clone());
+    }
+    if let Some(warnings) = &options.warnings {
+        dset_writer = dset_writer.with_warning_collector(warnings);
+    }
+    match options.group_length {
+        GroupLengthMode::Strip => dset_writer.write_sequence(StripGroupLengths::new(tokens())),
+        GroupLengthMode::Recompute => {
+            let lengths =
+                measure_group_lengths(tokens(), ts, options).context(PrintDataSetSnafu)?;
+            dset_writer.write_sequence(ReplaceGroupLengths::new(tokens(), lengths))
+        }
+        GroupLengthMode::Preserve => dset_writer.write_sequence(tokens()),
     }
+    .context(PrintDataSetSnafu)?;
+    dset_writer.flush().context(PrintDataSetSnafu)
 }
 
 impl<O> ::std::ops::Deref for FileDicomObject<O> {
@@ -543,6 +1236,7 @@ where
     O: DicomObject,
 {
     type Element = <O as DicomObject>::Element;
+    type Elements = <O as DicomObject>::Elements;
 
     fn element(&self, tag: Tag) -> Result<Self::Element, AccessError> {
         self.obj.element(tag)
@@ -552,6 +1246,10 @@ where
         self.obj.element_by_name(name)
     }
 
+    fn elements(&self) -> Self::Elements {
+        self.obj.elements()
+    }
+
     fn meta(&self) -> Option<&FileMetaTable> {
         Some(&self.meta)
     }
@@ -559,16 +1257,25 @@ where
 
 impl<'a, O: 'a> DicomObject for &'a FileDicomObject<O>
 where
-    O: DicomObject,
+    &'a O: DicomObject,
 {
-    type Element = <O as DicomObject>::Element;
+    type Element = <&'a O as DicomObject>::Element;
+    type Elements = <&'a O as DicomObject>::Elements;
 
     fn element(&self, tag: Tag) -> Result<Self::Element, AccessError> {
-        self.obj.element(tag)
+        (&self.obj).element(tag)
     }
 
     fn element_by_name(&self, name: &str) -> Result<Self::Element, AccessByNameError> {
-        self.obj.element_by_name(name)
+        (&self.obj).element_by_name(name)
+    }
+
+    fn elements(&self) -> Self::Elements {
+        (&self.obj).elements()
+    }
+
+    fn meta(&self) -> Option<&FileMetaTable> {
+        Some(&self.meta)
     }
 }
 
@@ -727,6 +1434,121 @@ where
     }
 }
 
+/// Retrieve the primitive value of an attribute for one of the typed getters
+/// and convert it,
+/// failing if it is absent, empty, or not primitive.
+/// Textual values are split into multiple strings
+/// according to the element's value representation.
+fn convert_value<O, T>(
+    obj: &O,
+    tag: Tag,
+    requested: &'static str,
+    convert: impl FnOnce(&PrimitiveValue) -> Result<T, AttributeError>,
+) -> Result<T, AttributeError>
+where
+    O: ?Sized + DicomObject,
+{
+    let elem = obj
+        .element(tag)
+        .ok()
+        .context(MissingAttributeSnafu { tag })?;
+    let value = elem
+        .primitive_value()
+        .map_err(|e| Box::new(e) as Box<_>)
+        .context(RetrieveValueSnafu { tag })?;
+    match value {
+        Some(v) => {
+            snafu::ensure!(!is_empty_value(&v), EmptyValueSnafu { tag });
+            convert(&split_values(elem.vr(), &v))
+        }
+        None => Err(ConvertValueError {
+            requested,
+            original: if elem.items().is_some() {
+                dicom_core::value::ValueType::DataSetSequence
+            } else {
+                dicom_core::value::ValueType::PixelSequence
+            },
+            cause: None,
+        })
+        .context(ConvertValueSnafu { tag }),
+    }
+}
+
+pub(crate) fn single_value<O, T>(
+    obj: &O,
+    tag: Tag,
+    requested: &'static str,
+    strict: bool,
+    convert: impl FnOnce(&PrimitiveValue) -> Result<T, ConvertValueError>,
+) -> Result<T, AttributeError>
+where
+    O: ?Sized + DicomObject,
+{
+    convert_value(obj, tag, requested, |value| {
+        if strict {
+            let count = value.multiplicity();
+            snafu::ensure!(count <= 1, MultipleValuesSnafu { tag, count });
+        }
+        convert(value).context(ConvertValueSnafu { tag })
+    })
+}
+
+pub(crate) fn timezone_offset<O>(obj: &O) -> Result<Option<FixedOffset>, AttributeError>
+where
+    O: ?Sized + DicomObject,
+{
+    let value = match obj.string(tags::TIMEZONE_OFFSET_FROM_UTC) {
+        Ok(value) => value,
+        Err(AttributeError::MissingAttribute { .. } | AttributeError::EmptyValue { .. }) => {
+            return Ok(None)
+        }
+        Err(e) => return Err(e),
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    parse_time_zone(value.as_bytes())
+        .map(Some)
+        .context(InvalidTimezoneOffsetSnafu { value })
+}
+
+pub(crate) fn precise_datetime<O>(
+    obj: &O,
+    tag: Tag,
+    default_offset: Option<FixedOffset>,
+) -> Result<PreciseDateTime, AttributeError>
+where
+    O: ?Sized + DicomObject,
+{
+    let datetime = obj.datetime(tag)?;
+    // only values without their own suffix are adjusted
+    let datetime = match obj.timezone_offset()?.or(default_offset) {
+        Some(offset) => datetime.with_default_time_zone(offset),
+        None => datetime,
+    };
+    // a missing second fraction is taken as zero
+    match datetime.time() {
+        Some(time) if time.second().is_some() => datetime.earliest(),
+        _ => datetime.exact(),
+    }
+    .context(ConvertDateTimeSnafu { tag })
+}
+
+pub(crate) fn multi_value<O, T>(
+    obj: &O,
+    tag: Tag,
+    requested: &'static str,
+    convert: impl FnOnce(&PrimitiveValue) -> Result<Vec<T>, ConvertValueError>,
+) -> Result<Vec<T>, AttributeError>
+where
+    O: ?Sized + DicomObject,
+{
+    convert_value(obj, tag, requested, |value| {
+        convert(value).context(ConvertValueSnafu { tag })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use dicom_core::{DataElement, PrimitiveValue, VR};
@@ -864,4 +1686,927 @@ mod tests {
         );
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn write_with_syntax_transcodes_native_syntaxes() {
+        use dicom_core::Tag;
+        use dicom_dictionary_std::{tags, uids};
+        use dicom_transfer_syntax_registry::entries;
+
+        let syntaxes = [
+            uids::IMPLICIT_VR_LITTLE_ENDIAN,
+            uids::EXPLICIT_VR_LITTLE_ENDIAN,
+            entries::EXPLICIT_VR_BIG_ENDIAN.uid(),
+        ];
+
+        let mut obj = crate::dicom_object! {
+            SOPClassUID: "1.2.840.10008.5.1.4.1.1.7",
+            SOPInstanceUID: "2.25.1",
+            PatientName: "Doe^John",
+            ImageType: ["DERIVED", "SECONDARY"],
+            Rows: 4_u16,
+            Columns: 4_u16,
+            BitsAllocated: 16_u16,
+            PixelRepresentation: 0_u16,
+            PixelSpacing: [0.5, 0.25],
+            DiffusionBValue: 1000.5,
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "2.25.2", ReferencedFrameNumber: "1" },
+                { ReferencedSOPInstanceUID: "2.25.3" },
+            ],
+            DimensionIndexSequence: [
+                { DimensionIndexPointer: [tags::STACK_ID] },
+            ],
+        };
+        // raw bytes of unknown meaning are passed through as is
+        obj.put(DataElement::new(
+            Tag(0x0009, 0x1001),
+            VR::UN,
+            PrimitiveValue::from(vec![1_u8, 2, 3, 4]),
+        ));
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OW,
+            PrimitiveValue::U16((0..16_u16).map(|x| x * 0x0101).collect()),
+        ));
+
+        // the original object is read from an explicit VR big endian file
+        let obj = obj
+            .with_meta(
+                FileMetaTableBuilder::new().transfer_syntax(entries::EXPLICIT_VR_BIG_ENDIAN.uid()),
+            )
+            .unwrap();
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+        let source = FileDicomObject::from_reader(&data[..]).unwrap();
+
+        for from in syntaxes {
+            let mut data = Vec::new();
+            source.write_with_syntax(&mut data, from).unwrap();
+            let intermediate = FileDicomObject::from_reader(&data[..]).unwrap();
+            assert_eq!(intermediate.meta().transfer_syntax(), from);
+            assert_eq!(
+                crate::diff::diff(&source, &intermediate),
+                vec![],
+                "{}",
+                from
+            );
+
+            for to in syntaxes {
+                let mut data = Vec::new();
+                intermediate.write_with_syntax(&mut data, to).unwrap();
+                let result = FileDicomObject::from_reader(&data[..]).unwrap();
+                assert_eq!(result.meta().transfer_syntax(), to);
+                assert_eq!(
+                    crate::diff::diff(&source, &result),
+                    vec![],
+                    "{} -> {}",
+                    from,
+                    to
+                );
+            }
+        }
+
+        // transcoding to an encapsulated transfer syntax is not supported
+        let err = source
+            .write_with_syntax(Vec::new(), uids::JPEG_BASELINE8_BIT)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::WriteError::UnsupportedTranscoding { .. }
+        ));
+    }
+
+    /// Check the value of every group length element in the data set
+    /// against the byte length of its group,
+    /// accounted independently from the writer
+    /// for an undefined length sequence encoding.
+    fn check_group_lengths(obj: &InMemDicomObject, explicit_vr: bool) -> usize {
+        use dicom_core::header::{HasLength, Header};
+
+        fn encoded_len(elem: &crate::mem::InMemElement, explicit_vr: bool) -> u64 {
+            let header_len = match elem.vr() {
+                VR::OB
+                | VR::OD
+                | VR::OF
+                | VR::OL
+                | VR::OV
+                | VR::OW
+                | VR::SQ
+                | VR::SV
+                | VR::UC
+                | VR::UN
+                | VR::UR
+                | VR::UT
+                | VR::UV
+                    if explicit_vr =>
+                {
+                    12
+                }
+                _ => 8,
+            };
+            match elem.items() {
+                Some(items) => {
+                    let items_len: u64 = items
+                        .iter()
+                        .map(|item| {
+                            8 + item
+                                .iter()
+                                .map(|e| encoded_len(e, explicit_vr))
+                                .sum::<u64>()
+                                + 8
+                        })
+                        .sum();
+                    header_len + items_len + 8
+                }
+                None => header_len + u64::from(elem.length().0),
+            }
+        }
+
+        let mut checked = 0;
+        for elem in obj {
+            if elem.tag().is_group_length() {
+                let group = elem.tag().group();
+                let expected: u64 = obj
+                    .iter()
+                    .filter(|e| e.tag().group() == group && !e.tag().is_group_length())
+                    .map(|e| encoded_len(e, explicit_vr))
+                    .sum();
+                assert_eq!(
+                    u64::from(elem.to_int::<u32>().unwrap()),
+                    expected,
+                    "length of group {:04X}",
+                    group
+                );
+                checked += 1;
+            }
+            for item in elem.items().into_iter().flatten() {
+                checked += check_group_lengths(item, explicit_vr);
+            }
+        }
+        checked
+    }
+
+    fn object_with_group_lengths() -> InMemDicomObject {
+        use dicom_core::value::DataSetSequence;
+        use dicom_core::Tag;
+        use dicom_dictionary_std::tags;
+
+        let group_length =
+            |group| DataElement::new(Tag(group, 0x0000), VR::UL, PrimitiveValue::from(0_u32));
+        let mut item = crate::dicom_object! {
+            ReferencedSOPInstanceUID: "2.25.2",
+        };
+        item.put(group_length(0x0008));
+        let mut obj = crate::dicom_object! {
+            SOPClassUID: "1.2.840.10008.5.1.4.1.1.7",
+            SOPInstanceUID: "2.25.1",
+            PatientName: "Doe^John",
+            PatientID: "ID0001",
+            StudyInstanceUID: "2.25.3",
+        };
+        obj.put(DataElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![item]),
+        ));
+        for group in [0x0008, 0x0010, 0x0020] {
+            obj.put(group_length(group));
+        }
+        obj
+    }
+
+    #[test]
+    fn write_recomputes_group_lengths() {
+        use crate::{GroupLengthMode, WriteOptions};
+        use dicom_core::Tag;
+        use dicom_dictionary_std::{tags, uids};
+
+        let options = WriteOptions::new().group_length(GroupLengthMode::Recompute);
+
+        let obj = object_with_group_lengths()
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+            .unwrap();
+        let mut data = Vec::new();
+        obj.write_all_with_options(&mut data, &options).unwrap();
+        let mut fixture = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(check_group_lengths(&fixture, true), 4);
+        let patient_group_len = fixture
+            .element(Tag(0x0010, 0x0000))
+            .unwrap()
+            .to_int::<u32>()
+            .unwrap();
+
+        fixture.put(DataElement::new(
+            tags::PATIENT_NAME,
+            VR::PN,
+            PrimitiveValue::from("Doe^John Jonathan^Jr."),
+        ));
+
+        for (ts, explicit_vr) in [
+            (uids::EXPLICIT_VR_LITTLE_ENDIAN, true),
+            (uids::IMPLICIT_VR_LITTLE_ENDIAN, false),
+        ] {
+            let mut data = Vec::new();
+            fixture
+                .write_all_with_options(&mut data, &options.clone().transfer_syntax(ts))
+                .unwrap();
+            let result = FileDicomObject::from_reader(&data[..]).unwrap();
+            assert_eq!(result.meta().transfer_syntax(), ts);
+            assert_eq!(check_group_lengths(&result, explicit_vr), 4, "{}", ts);
+            assert_ne!(
+                result
+                    .element(Tag(0x0010, 0x0000))
+                    .unwrap()
+                    .to_int::<u32>()
+                    .unwrap(),
+                patient_group_len,
+            );
+        }
+    }
+
+    #[test]
+    fn write_strips_group_lengths_by_default() {
+        use crate::{GroupLengthMode, WriteOptions};
+        use dicom_core::header::Header;
+        use dicom_core::Tag;
+        use dicom_dictionary_std::uids;
+
+        fn has_group_lengths(obj: &InMemDicomObject) -> bool {
+            obj.iter().any(|elem| {
+                elem.tag().is_group_length()
+                    || elem.items().into_iter().flatten().any(has_group_lengths)
+            })
+        }
+
+        let obj = object_with_group_lengths()
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+            .unwrap();
+
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert!(!has_group_lengths(&result));
+        assert!(result.meta().information_group_length > 0);
+        let mut expected = object_with_group_lengths();
+        expected.retain_deep(|elem| !elem.tag().is_group_length());
+        assert_eq!(crate::diff::diff(&expected, &result), vec![]);
+
+        // stored values are kept as is when preserved
+        let mut data = Vec::new();
+        obj.write_all_with_options(
+            &mut data,
+            &WriteOptions::new().group_length(GroupLengthMode::Preserve),
+        )
+        .unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert!(has_group_lengths(&result));
+        assert_eq!(
+            result
+                .element(Tag(0x0008, 0x0000))
+                .unwrap()
+                .to_int::<u32>()
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn write_with_text_policy() {
+        use crate::{EncodeTextPolicy, WriteOptions};
+        use dicom_core::value::DataSetSequence;
+        use dicom_core::Length;
+        use dicom_dictionary_std::{tags, uids};
+
+        // no Specific Character Set, so text is in the default repertoire
+        let item = InMemDicomObject::from_element_iter([DataElement::new(
+            tags::PATIENT_NAME,
+            VR::PN,
+            PrimitiveValue::from("Gößmann^Jörg"),
+        )]);
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Żółć^Jan"),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::new(vec![item], Length::UNDEFINED),
+            ),
+        ])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE),
+        )
+        .unwrap();
+
+        // fails by default
+        let mut data = Vec::new();
+        assert!(matches!(
+            obj.write_all(&mut data),
+            Err(crate::WriteError::PrintDataSet { .. })
+        ));
+
+        // replaced with question marks
+        let mut data = Vec::new();
+        obj.write_all_with_options(
+            &mut data,
+            &WriteOptions::new().text_policy(EncodeTextPolicy::Replace),
+        )
+        .unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(
+            result
+                .element(tags::PATIENT_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "?ó??^Jan"
+        );
+        assert!(result.element(tags::SPECIFIC_CHARACTER_SET).is_err());
+
+        // declared as UTF-8 and encoded consistently
+        let mut data = Vec::new();
+        obj.write_all_with_options(
+            &mut data,
+            &WriteOptions::new().text_policy(EncodeTextPolicy::UpgradeToUtf8),
+        )
+        .unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(
+            result
+                .element(tags::SPECIFIC_CHARACTER_SET)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "ISO_IR 192"
+        );
+        assert_eq!(
+            result
+                .element(tags::PATIENT_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "Żółć^Jan"
+        );
+        let items = result
+            .element(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(
+            items[0]
+                .element(tags::PATIENT_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "Gößmann^Jörg"
+        );
+        // the object itself was not changed
+        assert!(obj.element(tags::SPECIFIC_CHARACTER_SET).is_err());
+
+        // nothing changes if the text is already representable
+        let mut obj = obj;
+        obj.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"));
+        obj.remove_element(tags::REFERENCED_IMAGE_SEQUENCE);
+        let mut data = Vec::new();
+        obj.write_all_with_options(
+            &mut data,
+            &WriteOptions::new().text_policy(EncodeTextPolicy::UpgradeToUtf8),
+        )
+        .unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert!(result.element(tags::SPECIFIC_CHARACTER_SET).is_err());
+    }
+
+    #[test]
+    fn write_with_repertoire_validation() {
+        use crate::{RepertoireValidation, WriteOptions};
+        use dicom_dictionary_std::{tags, uids};
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(tags::MODALITY, VR::CS, "ct"),
+        ])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE),
+        )
+        .unwrap();
+
+        // written as is by default
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(
+            result.element(tags::MODALITY).unwrap().to_str().unwrap(),
+            "ct"
+        );
+
+        // rejected in strict mode
+        let mut data = Vec::new();
+        let err = obj
+            .write_all_with_options(
+                &mut data,
+                &WriteOptions::new().repertoire_validation(RepertoireValidation::Strict),
+            )
+            .unwrap_err();
+        assert!(matches!(err, crate::WriteError::PrintDataSet { .. }));
+    }
+
+    #[test]
+    fn write_with_length_validation() {
+        use crate::{GroupLengthMode, LengthValidation, Tag, WriteOptions};
+        use dicom_dictionary_std::{tags, uids};
+
+        let institution = "Institute of Radiology and Nuclear Medicine of the Northern District";
+        assert!(institution.len() > 64);
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(Tag(0x0008, 0x0000), VR::UL, PrimitiveValue::from(0_u32)),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(tags::INSTITUTION_NAME, VR::LO, institution),
+        ])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE),
+        )
+        .unwrap();
+
+        // written as is by default
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(
+            result
+                .element(tags::INSTITUTION_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            institution
+        );
+
+        // rejected in strict mode
+        let err = obj
+            .write_all_with_options(
+                Vec::new(),
+                &WriteOptions::new().length_validation(LengthValidation::Strict),
+            )
+            .unwrap_err();
+        assert!(matches!(err, crate::WriteError::PrintDataSet { .. }));
+
+        // truncated, with the group length to match
+        let mut data = Vec::new();
+        obj.write_all_with_options(
+            &mut data,
+            &WriteOptions::new()
+                .length_validation(LengthValidation::Truncate)
+                .group_length(GroupLengthMode::Recompute),
+        )
+        .unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(
+            result
+                .element(tags::INSTITUTION_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            &institution[..64]
+        );
+        // SOP Instance UID: 8 + 8, Institution Name: 8 + 64
+        assert_eq!(
+            result
+                .element(Tag(0x0008, 0x0000))
+                .unwrap()
+                .to_int::<u32>()
+                .unwrap(),
+            16 + 72
+        );
+
+        // with a marker, reporting what was cut
+        let collector = crate::WarningCollector::new();
+        let mut data = Vec::new();
+        obj.write_all_with_options(
+            &mut data,
+            &WriteOptions::new()
+                .length_validation(LengthValidation::Truncate)
+                .truncation_marker("...")
+                .collect_warnings(&collector),
+        )
+        .unwrap();
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(
+            result
+                .element(tags::INSTITUTION_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            format!("{}...", &institution[..61])
+        );
+        let warnings = collector.take();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].tag(), tags::INSTITUTION_NAME);
+    }
+
+    #[test]
+    fn write_puts_in_tag_order() {
+        use crate::{OpenFileOptions, ReadOptions, TagOrderStrategy};
+        use dicom_dictionary_std::{tags, uids};
+
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(DataElement::new(tags::PATIENT_ID, VR::LO, "ID"));
+        obj.put(DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"));
+        obj.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"));
+        obj.put(DataElement::new(
+            tags::SOP_CLASS_UID,
+            VR::UI,
+            uids::SECONDARY_CAPTURE_IMAGE_STORAGE,
+        ));
+        let obj = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+            .unwrap();
+
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+        let result = OpenFileOptions::new()
+            .read_options(ReadOptions::new().tag_order(TagOrderStrategy::Fail))
+            .from_reader(&data[128..])
+            .unwrap();
+        let tags: Vec<_> = result.iter().map(|e| e.header().tag).collect();
+        assert_eq!(
+            tags,
+            [
+                tags::SOP_CLASS_UID,
+                tags::SOP_INSTANCE_UID,
+                tags::PATIENT_NAME,
+                tags::PATIENT_ID
+            ]
+        );
+    }
+
+    #[test]
+    fn write_with_meta_validation() {
+        use crate::{MetaValidation, OpenFileOptions, WriteError, WriteOptions};
+        use dicom_dictionary_std::{tags, uids};
+
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, uids::CT_IMAGE_STORAGE),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+        ])
+        .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+        .unwrap();
+        // the data set no longer matches the file meta group
+        obj.put(DataElement::new(
+            tags::SOP_CLASS_UID,
+            VR::UI,
+            uids::MR_IMAGE_STORAGE,
+        ));
+        obj.meta.media_storage_sop_instance_uid = String::new();
+
+        let write = |validation| {
+            let mut data = Vec::new();
+            obj.write_all_with_options(&mut data, &WriteOptions::new().meta_validation(validation))
+                .map(|_| OpenFileOptions::new().from_reader(&data[128..]).unwrap())
+        };
+
+        let err = write(MetaValidation::Strict).unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                WriteError::MetaMismatch { meta_tag, meta_value, tag, value, .. }
+                    if *meta_tag == tags::MEDIA_STORAGE_SOP_CLASS_UID
+                        && meta_value == uids::CT_IMAGE_STORAGE
+                        && *tag == tags::SOP_CLASS_UID
+                        && value == uids::MR_IMAGE_STORAGE
+            ),
+            "{:?}",
+            err
+        );
+
+        // written as is
+        let written = write(MetaValidation::Warn).unwrap();
+        assert_eq!(
+            written.meta().media_storage_sop_class_uid(),
+            uids::CT_IMAGE_STORAGE
+        );
+        assert_eq!(written.meta().media_storage_sop_instance_uid(), "");
+
+        // written with the values of the data set
+        let written = write(MetaValidation::Fix).unwrap();
+        assert_eq!(
+            written.meta().media_storage_sop_class_uid(),
+            uids::MR_IMAGE_STORAGE
+        );
+        assert_eq!(written.meta().media_storage_sop_instance_uid(), "2.25.123");
+        let mut meta = written.meta().clone();
+        meta.update_information_group_length();
+        assert_eq!(
+            written.meta().information_group_length,
+            meta.information_group_length
+        );
+        // the object itself is left untouched
+        assert_eq!(
+            obj.meta().media_storage_sop_class_uid(),
+            uids::CT_IMAGE_STORAGE
+        );
+    }
+
+    #[test]
+    fn write_with_implementation_identifiers() {
+        use crate::{
+            OpenFileOptions, RepertoireValidation, WriteError, WriteOptions,
+            IMPLEMENTATION_CLASS_UID, IMPLEMENTATION_VERSION_NAME,
+        };
+        use dicom_dictionary_std::{tags, uids};
+
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, uids::CT_IMAGE_STORAGE),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+        ])
+        .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+        .unwrap();
+
+        let write = |obj: &FileDicomObject<InMemDicomObject>, options: &WriteOptions| {
+            let mut data = Vec::new();
+            obj.write_all_with_options(&mut data, options)
+                .map(|_| OpenFileOptions::new().from_reader(&data[128..]).unwrap())
+        };
+        let check_group_length = |written: &FileDicomObject<InMemDicomObject>| {
+            let mut meta = written.meta().clone();
+            meta.update_information_group_length();
+            assert_eq!(
+                written.meta().information_group_length,
+                meta.information_group_length
+            );
+        };
+
+        // the identifiers of DICOM-rs by default
+        let written = write(&obj, &WriteOptions::new()).unwrap();
+        assert_eq!(
+            written.meta().implementation_class_uid(),
+            IMPLEMENTATION_CLASS_UID
+        );
+        assert_eq!(
+            written.meta().implementation_version_name.as_deref(),
+            Some(IMPLEMENTATION_VERSION_NAME)
+        );
+        assert_eq!(written.meta().source_application_entity_title, None);
+
+        // also filled in when missing from the table
+        obj.meta.implementation_class_uid = String::new();
+        obj.meta.implementation_version_name = None;
+        obj.meta.update_information_group_length();
+        let written = write(&obj, &WriteOptions::new()).unwrap();
+        assert_eq!(
+            written.meta().implementation_class_uid(),
+            IMPLEMENTATION_CLASS_UID
+        );
+        assert_eq!(
+            written.meta().implementation_version_name.as_deref(),
+            Some(IMPLEMENTATION_VERSION_NAME)
+        );
+        check_group_length(&written);
+
+        // custom identifiers
+        let options = WriteOptions::new()
+            .implementation_class_uid("1.2.826.0.1.3680043.9.7133")
+            .implementation_version_name("MYAPP 1.0")
+            .source_application_entity_title("STORESCU");
+        let written = write(&obj, &options).unwrap();
+        assert_eq!(
+            written.meta().implementation_class_uid(),
+            "1.2.826.0.1.3680043.9.7133"
+        );
+        assert_eq!(
+            written.meta().implementation_version_name.as_deref(),
+            Some("MYAPP 1.0 ")
+        );
+        assert_eq!(
+            written.meta().source_application_entity_title.as_deref(),
+            Some("STORESCU")
+        );
+        check_group_length(&written);
+
+        // a custom class UID does not keep the version name of another
+        let written = write(
+            &obj,
+            &WriteOptions::new().implementation_class_uid("1.2.826.0.1.3680043.9.7133"),
+        )
+        .unwrap();
+        assert_eq!(written.meta().implementation_version_name, None);
+        check_group_length(&written);
+
+        // invalid identifiers
+        let options = WriteOptions::new()
+            .repertoire_validation(RepertoireValidation::Strict)
+            .source_application_entity_title("A-VERY-LONG-AE-TITLE");
+        assert!(matches!(
+            write(&obj, &options),
+            Err(WriteError::InvalidMetaIdentifier {
+                source: crate::meta::Error::InvalidAeTitle { .. }
+            })
+        ));
+        let options = WriteOptions::new()
+            .repertoire_validation(RepertoireValidation::Strict)
+            .implementation_class_uid("1.2.3.abc");
+        assert!(matches!(
+            write(&obj, &options),
+            Err(WriteError::InvalidMetaIdentifier {
+                source: crate::meta::Error::InvalidUid { .. }
+            })
+        ));
+        // written anyway when only warning
+        let options = WriteOptions::new().source_application_entity_title("A-VERY-LONG-AE-TITLE");
+        let written = write(&obj, &options).unwrap();
+        assert_eq!(
+            written.meta().source_application_entity_title.as_deref(),
+            Some("A-VERY-LONG-AE-TITLE")
+        );
+    }
+
+    #[test]
+    fn write_dataset_with_contradicting_encoding() {
+        use crate::{MetaValidation, WriteError, WriteOptions};
+        use dicom_dictionary_std::{tags, uids};
+        use dicom_transfer_syntax_registry::entries;
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, uids::CT_IMAGE_STORAGE),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+        ])
+        .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+        .unwrap();
+
+        for ts in [
+            entries::EXPLICIT_VR_BIG_ENDIAN.uid(),
+            entries::IMPLICIT_VR_LITTLE_ENDIAN.uid(),
+        ] {
+            let err = obj
+                .write_dataset_with_options(Vec::new(), &WriteOptions::new().transfer_syntax(ts))
+                .unwrap_err();
+            assert!(
+                matches!(
+                    &err,
+                    WriteError::EncodingMismatch { declared, used, .. }
+                        if declared == uids::EXPLICIT_VR_LITTLE_ENDIAN && used == ts
+                ),
+                "{:?}",
+                err
+            );
+            // whole files declare the new transfer syntax
+            obj.write_all_with_options(Vec::new(), &WriteOptions::new().transfer_syntax(ts))
+                .unwrap();
+            // and the check may be turned off
+            obj.write_dataset_with_options(
+                Vec::new(),
+                &WriteOptions::new()
+                    .transfer_syntax(ts)
+                    .meta_validation(MetaValidation::Off),
+            )
+            .unwrap();
+        }
+
+        // same encoding
+        obj.write_dataset_with_options(
+            Vec::new(),
+            &WriteOptions::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn write_with_odd_length_policy() {
+        use crate::{GroupLengthMode, OddLengthPolicy, Tag, WriteOptions};
+        use dicom_dictionary_std::{tags, uids};
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(Tag(0x0008, 0x0000), VR::UL, PrimitiveValue::from(0_u32)),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(tags::MODALITY, VR::CS, "SEG"),
+        ])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE),
+        )
+        .unwrap();
+        let write = |policy| {
+            let mut data = Vec::new();
+            obj.write_all_with_options(
+                &mut data,
+                &WriteOptions::new()
+                    .odd_length(policy)
+                    .group_length(GroupLengthMode::Recompute),
+            )
+            .map(|_| data)
+        };
+        let group_length = |obj: &FileDicomObject<InMemDicomObject>| {
+            obj.element(Tag(0x0008, 0x0000))
+                .unwrap()
+                .to_int::<u32>()
+                .unwrap()
+        };
+
+        // padded with a space by default
+        let data = write(OddLengthPolicy::Pad).unwrap();
+        assert!(data.ends_with(b"\x08\x00\x60\x00CS\x04\x00SEG "));
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(
+            result.element(tags::MODALITY).unwrap().to_str().unwrap(),
+            "SEG"
+        );
+        // SOP Instance UID: 8 + 8, Modality: 8 + 4
+        assert_eq!(group_length(&result), 16 + 12);
+
+        // written as is
+        let data = write(OddLengthPolicy::Preserve).unwrap();
+        assert!(data.ends_with(b"\x08\x00\x60\x00CS\x03\x00SEG"));
+        let result = FileDicomObject::from_reader(&data[..]).unwrap();
+        assert_eq!(
+            result.element(tags::MODALITY).unwrap().to_str().unwrap(),
+            "SEG"
+        );
+        assert_eq!(group_length(&result), 16 + 11);
+
+        // rejected
+        let err = write(OddLengthPolicy::Error).unwrap_err();
+        assert!(matches!(err, crate::WriteError::PrintDataSet { .. }));
+    }
+
+    #[test]
+    fn write_in_batches() {
+        use crate::WriteOptions;
+        use dicom_dictionary_std::{tags, uids};
+        use std::io::{IoSlice, Write};
+
+        /// Records the number of bytes passed in each call.
+        #[derive(Default)]
+        struct RecordingWriter {
+            out: Vec<u8>,
+            calls: Vec<usize>,
+        }
+
+        impl Write for RecordingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.write_vectored(&[IoSlice::new(buf)])
+            }
+
+            fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+                let n = bufs.iter().map(|buf| buf.len()).sum();
+                for buf in bufs {
+                    self.out.extend_from_slice(buf);
+                }
+                self.calls.push(n);
+                Ok(n)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+            DataElement::new(tags::MODALITY, VR::CS, "OT"),
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(64_u16)),
+            DataElement::new(tags::COLUMNS, VR::US, PrimitiveValue::from(64_u16)),
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PrimitiveValue::from(vec![0x42_u8; 64 * 64]),
+            ),
+        ])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)
+                .media_storage_sop_instance_uid("2.25.123"),
+        )
+        .unwrap();
+
+        let mut unbatched = RecordingWriter::default();
+        obj.write_all_with_options(&mut unbatched, &WriteOptions::new().batch_size(0))
+            .unwrap();
+        assert!(unbatched.calls.len() > 15, "{:?}", unbatched.calls);
+
+        // by default, the whole object is passed on at once,
+        // the last batch along with the pixel data
+        let mut batched = RecordingWriter::default();
+        obj.write_all(&mut batched).unwrap();
+        assert_eq!(batched.out, unbatched.out);
+        assert_eq!(batched.calls, vec![unbatched.out.len()]);
+
+        let mut batched = RecordingWriter::default();
+        obj.write_all_with_options(&mut batched, &WriteOptions::new().batch_size(128))
+            .unwrap();
+        assert_eq!(batched.out, unbatched.out);
+        assert!(batched.calls.len() < 5, "{:?}", batched.calls);
+
+        let mut batched = RecordingWriter::default();
+        obj.write_dataset(&mut batched).unwrap();
+        assert!(unbatched.out.ends_with(&batched.out));
+        assert_eq!(batched.calls.len(), 1);
+    }
 }
@@ -0,0 +1,628 @@
+//! Macros for building DICOM objects.
+
+/// Build an [in-memory DICOM object](crate::InMemDicomObject)
+/// from a list of attributes and their values.
+///
+/// Each entry is of the form `Key: value`,
+/// separated by commas,
+/// where `Key` is either a standard attribute keyword
+/// or a tag in the form `(0xGGGG, 0xEEEE)`.
+/// Keywords are resolved at compile time,
+/// so unknown keywords result in a compilation error.
+/// The value representation is obtained from the standard data dictionary.
+///
+/// Values can be:
+///
+/// - a string slice, a number, a tag, a date or time value,
+///   a byte vector, or a [`PrimitiveValue`].
+///   Numbers are converted to the type of the attribute's value representation,
+///   so `Rows: 512` is stored as an unsigned 16-bit integer;
+/// - a list of values in square brackets,
+///   for attributes with multiple values
+///   (e.g. `[0.5, 0.5]` or `["ORIGINAL", "PRIMARY"]`);
+/// - a list of data set items in square brackets,
+///   each item written in curly braces with the same syntax as this macro,
+///   for data set sequences;
+/// - `()`, for an empty value.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::value::PrimitiveValue;
+/// # use dicom_core::{Tag, VR};
+/// # use dicom_dictionary_std::tags;
+/// use dicom_object::dicom_object;
+///
+/// let obj = dicom_object! {
+///     PatientName: "Doe^John",
+///     (0x0010, 0x0020): "ID0001",
+///     Rows: 512,
+///     PixelSpacing: [0.5, 0.5],
+///     ImageType: ["ORIGINAL", "PRIMARY"],
+///     SharedFunctionalGroupsSequence: [
+///         {
+///             PixelMeasuresSequence: [
+///                 { SliceThickness: 1.25 },
+///             ],
+///         },
+///     ],
+/// };
+///
+/// assert_eq!(obj.string(tags::PATIENT_ID)?, "ID0001");
+/// assert_eq!(obj.u16(tags::ROWS)?, 512);
+/// assert_eq!(obj.f64s(tags::PIXEL_SPACING)?, vec![0.5, 0.5]);
+/// assert_eq!(obj.strings(tags::IMAGE_TYPE)?, vec!["ORIGINAL", "PRIMARY"]);
+/// assert_eq!(obj.get(tags::PIXEL_SPACING).unwrap().vr(), VR::DS);
+/// let thickness = obj.element_at_path(
+///     "SharedFunctionalGroupsSequence.PixelMeasuresSequence.SliceThickness",
+/// )?;
+/// assert_eq!(thickness.to_float64()?, 1.25);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// Values which do not match the value representation are rejected,
+/// at compile time when the attribute is given by keyword
+/// and with a panic when it is given by tag.
+/// Numbers out of range for the value representation also panic.
+///
+/// ```compile_fail
+/// # use dicom_object::dicom_object;
+/// let obj = dicom_object! {
+///     PatientName: 42,
+/// };
+/// ```
+///
+/// Unknown keywords are rejected at compile time.
+///
+/// ```compile_fail
+/// # use dicom_object::dicom_object;
+/// let obj = dicom_object! {
+///     PatientNam: "Doe^John",
+/// };
+/// ```
+///
+/// This includes keywords in nested items.
+///
+/// ```compile_fail
+/// # use dicom_object::dicom_object;
+/// let obj = dicom_object! {
+///     ReferencedImageSequence: [
+///         { ReferencedSOPInstanceUid: "1.2.3.4" },
+///     ],
+/// };
+/// ```
+///
+/// [`PrimitiveValue`]: dicom_core::value::PrimitiveValue
+#[macro_export]
+macro_rules! dicom_object {
+    ($($entries:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut obj = $crate::InMemDicomObject::new_empty();
+        $crate::__dicom_object_entries!(obj; $($entries)*);
+        obj
+    }};
+}
+
+/// Implementation detail of [`dicom_object!`]:
+/// munches one entry at a time and puts it in the object.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dicom_object_entries {
+    ($obj:ident;) => {};
+    // data set sequence
+    ($obj:ident; $key:tt : [ $({ $($item:tt)* }),* $(,)? ] $(, $($rest:tt)*)?) => {
+        $obj.put($crate::__private::DataElement::new(
+            $crate::__dicom_object_key!($key).0,
+            $crate::__private::VR::SQ,
+            $crate::__private::DataSetSequence::from(vec![
+                $($crate::dicom_object! { $($item)* }),*
+            ]),
+        ));
+        $crate::__dicom_object_entries!($obj; $($($rest)*)?);
+    };
+    // multiple values
+    ($obj:ident; $key:tt : [ $($value:expr),* $(,)? ] $(, $($rest:tt)*)?) => {
+        let (tag, vr) = $crate::__dicom_object_key!($key);
+        $obj.put($crate::__private::DataElement::new(
+            tag,
+            vr,
+            $crate::__dicom_object_value!($key, vr, [$($value),*]),
+        ));
+        $crate::__dicom_object_entries!($obj; $($($rest)*)?);
+    };
+    // single value
+    ($obj:ident; $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        let (tag, vr) = $crate::__dicom_object_key!($key);
+        $obj.put($crate::__private::DataElement::new(
+            tag,
+            vr,
+            $crate::__dicom_object_value!($key, vr, $value),
+        ));
+        $crate::__dicom_object_entries!($obj; $($($rest)*)?);
+    };
+}
+
+/// Implementation detail of [`dicom_object!`]:
+/// resolves an attribute key into a tag and value representation.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dicom_object_key {
+    (($group:expr, $element:expr)) => {
+        $crate::__private::tag_vr($crate::__private::Tag($group, $element))
+    };
+    ($keyword:ident) => {{
+        const KEY: ($crate::__private::Tag, $crate::__private::VR) =
+            $crate::__private::keyword_tag_vr(stringify!($keyword));
+        KEY
+    }};
+}
+
+/// Implementation detail of [`dicom_object!`]:
+/// converts a value into a primitive value for the given key and VR.
+/// Keyword keys have their VR checked against the value at compile time.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dicom_object_value {
+    (($group:expr, $element:expr), $vr:expr, $value:expr) => {
+        $crate::__private::value($value, $vr)
+    };
+    ($keyword:ident, $vr:expr, $value:expr) => {
+        $crate::__private::checked_value::<
+            _,
+            { $crate::__private::keyword_tag_vr(stringify!($keyword)).1 as u8 },
+        >($value, $vr)
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use dicom_core::value::DataSetSequence;
+    pub use dicom_core::{DataElement, PrimitiveValue, Tag, VR};
+
+    use dicom_core::dictionary::{DataDictionary, DataDictionaryEntryRef, TagRange};
+    use dicom_core::value::{DicomDate, DicomDateTime, DicomTime, PersonName, C};
+    use dicom_dictionary_std::data_element::entry_by_name_const;
+    use dicom_dictionary_std::StandardDataDictionary;
+    use std::convert::TryFrom;
+    use std::marker::PhantomData;
+
+    /// Resolve the tag and VR of a standard attribute keyword,
+    /// failing to compile if evaluated in a constant
+    /// and the keyword does not resolve to a single attribute.
+    pub const fn keyword_tag_vr(keyword: &str) -> (Tag, VR) {
+        match entry_by_name_const(keyword) {
+            Some(DataDictionaryEntryRef {
+                tag: TagRange::Single(tag),
+                vr,
+                ..
+            }) => (*tag, vr.relaxed()),
+            Some(_) => panic!("attribute keyword refers to a range of tags, use an explicit tag"),
+            None => panic!("unknown attribute keyword"),
+        }
+    }
+
+    /// Resolve the VR of an attribute by tag,
+    /// falling back to `UN` if it is not in the standard dictionary.
+    pub fn tag_vr(tag: Tag) -> (Tag, VR) {
+        let vr = StandardDataDictionary
+            .by_tag(tag)
+            .map(|e| e.vr.relaxed())
+            .unwrap_or(VR::UN);
+        (tag, vr)
+    }
+
+    /// The kind of Rust value given to the macro,
+    /// which determines the value representations it can be used with.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum Kind {
+        Int,
+        Float,
+        Text,
+        Bytes,
+        Tag,
+        Date,
+        Time,
+        DateTime,
+        /// Already a primitive value or empty, accepted for any VR.
+        Any,
+    }
+
+    impl Kind {
+        /// Whether a value of this kind can be used
+        /// with the VR of the given discriminant.
+        pub const fn accepts_code(self, vr: u8) -> bool {
+            let allowed: &[VR] = match self {
+                Kind::Int => &[
+                    VR::US,
+                    VR::SS,
+                    VR::UL,
+                    VR::SL,
+                    VR::UV,
+                    VR::SV,
+                    VR::IS,
+                    VR::DS,
+                    VR::FL,
+                    VR::FD,
+                    VR::OB,
+                    VR::OW,
+                    VR::OL,
+                    VR::OV,
+                    VR::OF,
+                    VR::OD,
+                    VR::UN,
+                ],
+                Kind::Float => &[VR::FL, VR::FD, VR::DS, VR::OF, VR::OD, VR::UN],
+                Kind::Text => &[
+                    VR::AE,
+                    VR::AS,
+                    VR::CS,
+                    VR::DA,
+                    VR::DS,
+                    VR::DT,
+                    VR::IS,
+                    VR::LO,
+                    VR::LT,
+                    VR::PN,
+                    VR::SH,
+                    VR::ST,
+                    VR::TM,
+                    VR::UC,
+                    VR::UI,
+                    VR::UR,
+                    VR::UT,
+                    VR::UN,
+                ],
+                Kind::Bytes => &[VR::OB, VR::OW, VR::UN],
+                Kind::Tag => &[VR::AT, VR::UN],
+                Kind::Date => &[VR::DA, VR::UN],
+                Kind::Time => &[VR::TM, VR::UN],
+                Kind::DateTime => &[VR::DT, VR::UN],
+                Kind::Any => return true,
+            };
+            let mut i = 0;
+            while i < allowed.len() {
+                if allowed[i] as u8 == vr {
+                    return true;
+                }
+                i += 1;
+            }
+            false
+        }
+
+        /// Whether a value of this kind can be used with the given VR.
+        pub const fn accepts(self, vr: VR) -> bool {
+            self.accepts_code(vr as u8)
+        }
+    }
+
+    /// Conversion of a value given to the macro into a primitive value,
+    /// choosing the primitive type from the attribute's VR.
+    pub trait IntoValue {
+        /// The kind of value, for checking it against the VR.
+        const KIND: Kind;
+
+        /// Convert into a primitive value for the given VR.
+        /// The VR is expected to be accepted by [`Self::KIND`].
+        fn into_value(self, vr: VR) -> PrimitiveValue;
+    }
+
+    /// Convert a value for an attribute with the given VR,
+    /// panicking if the value does not fit the VR.
+    pub fn value<T: IntoValue>(value: T, vr: VR) -> PrimitiveValue {
+        assert!(
+            T::KIND.accepts(vr),
+            "{:?} value does not match value representation {}",
+            T::KIND,
+            vr
+        );
+        value.into_value(vr)
+    }
+
+    /// Convert a value for an attribute with a VR known at compile time,
+    /// failing to compile if the value does not fit the VR.
+    pub fn checked_value<T: IntoValue, const VR_CODE: u8>(value: T, vr: VR) -> PrimitiveValue {
+        #[allow(clippy::let_unit_value)]
+        let () = Accepts::<T, VR_CODE>::OK;
+        self::value(value, vr)
+    }
+
+    struct Accepts<T, const VR_CODE: u8>(PhantomData<T>);
+
+    impl<T: IntoValue, const VR_CODE: u8> Accepts<T, VR_CODE> {
+        const OK: () = assert!(
+            T::KIND.accepts_code(VR_CODE),
+            "value type does not match the attribute's value representation"
+        );
+    }
+
+    /// Narrow an integer to the type of the given VR.
+    fn narrow<T: TryFrom<i128>>(value: i128, vr: VR) -> T {
+        T::try_from(value).unwrap_or_else(|_| panic!("value {} is out of range for {}", value, vr))
+    }
+
+    /// Convert integers to the primitive type of the given VR,
+    /// or `None` if the value should keep its own type.
+    fn int_value<const N: usize>(values: [i128; N], vr: VR) -> Option<PrimitiveValue> {
+        let values = IntoIterator::into_iter(values);
+        Some(match vr {
+            VR::US | VR::OW => PrimitiveValue::U16(values.map(|v| narrow(v, vr)).collect()),
+            VR::SS => PrimitiveValue::I16(values.map(|v| narrow(v, vr)).collect()),
+            VR::UL | VR::OL => PrimitiveValue::U32(values.map(|v| narrow(v, vr)).collect()),
+            VR::SL => PrimitiveValue::I32(values.map(|v| narrow(v, vr)).collect()),
+            VR::UV | VR::OV => PrimitiveValue::U64(values.map(|v| narrow(v, vr)).collect()),
+            VR::SV => PrimitiveValue::I64(values.map(|v| narrow(v, vr)).collect()),
+            VR::OB => PrimitiveValue::U8(values.map(|v| narrow(v, vr)).collect()),
+            VR::FL | VR::OF => PrimitiveValue::F32(values.map(|v| v as f32).collect()),
+            VR::FD | VR::OD => PrimitiveValue::F64(values.map(|v| v as f64).collect()),
+            _ => return None,
+        })
+    }
+
+    macro_rules! impl_into_value_int {
+        ($typ: ty, $variant: ident) => {
+            impl IntoValue for $typ {
+                const KIND: Kind = Kind::Int;
+
+                fn into_value(self, vr: VR) -> PrimitiveValue {
+                    [self].into_value(vr)
+                }
+            }
+
+            impl<const N: usize> IntoValue for [$typ; N] {
+                const KIND: Kind = Kind::Int;
+
+                fn into_value(self, vr: VR) -> PrimitiveValue {
+                    int_value(self.map(i128::from), vr).unwrap_or_else(|| {
+                        PrimitiveValue::$variant(IntoIterator::into_iter(self).collect::<C<_>>())
+                    })
+                }
+            }
+        };
+    }
+
+    impl_into_value_int!(u8, U8);
+    impl_into_value_int!(u16, U16);
+    impl_into_value_int!(i16, I16);
+    impl_into_value_int!(u32, U32);
+    impl_into_value_int!(i32, I32);
+    impl_into_value_int!(u64, U64);
+    impl_into_value_int!(i64, I64);
+
+    macro_rules! impl_into_value_float {
+        ($typ: ty, $variant: ident) => {
+            impl IntoValue for $typ {
+                const KIND: Kind = Kind::Float;
+
+                fn into_value(self, vr: VR) -> PrimitiveValue {
+                    [self].into_value(vr)
+                }
+            }
+
+            impl<const N: usize> IntoValue for [$typ; N] {
+                const KIND: Kind = Kind::Float;
+
+                fn into_value(self, vr: VR) -> PrimitiveValue {
+                    let values = IntoIterator::into_iter(self);
+                    match vr {
+                        VR::FL | VR::OF => PrimitiveValue::F32(values.map(|v| v as f32).collect()),
+                        VR::FD | VR::OD => PrimitiveValue::F64(values.map(|v| v as f64).collect()),
+                        _ => PrimitiveValue::$variant(values.collect()),
+                    }
+                }
+            }
+        };
+    }
+
+    impl_into_value_float!(f32, F32);
+    impl_into_value_float!(f64, F64);
+
+    /// Implement conversion for values which keep their own type.
+    macro_rules! impl_into_value_as_is {
+        ($typ: ty, $kind: ident, $variant: ident) => {
+            impl IntoValue for $typ {
+                const KIND: Kind = Kind::$kind;
+
+                fn into_value(self, _vr: VR) -> PrimitiveValue {
+                    PrimitiveValue::from(self)
+                }
+            }
+
+            impl<const N: usize> IntoValue for [$typ; N] {
+                const KIND: Kind = Kind::$kind;
+
+                fn into_value(self, _vr: VR) -> PrimitiveValue {
+                    PrimitiveValue::$variant(IntoIterator::into_iter(self).collect::<C<_>>())
+                }
+            }
+        };
+    }
+
+    impl_into_value_as_is!(Tag, Tag, Tags);
+    impl_into_value_as_is!(DicomDate, Date, Date);
+    impl_into_value_as_is!(DicomTime, Time, Time);
+    impl_into_value_as_is!(DicomDateTime, DateTime, DateTime);
+
+    impl IntoValue for &str {
+        const KIND: Kind = Kind::Text;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            PrimitiveValue::from(self)
+        }
+    }
+
+    impl IntoValue for String {
+        const KIND: Kind = Kind::Text;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            PrimitiveValue::from(self)
+        }
+    }
+
+    impl IntoValue for PersonName<'_> {
+        const KIND: Kind = Kind::Text;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            PrimitiveValue::from(self)
+        }
+    }
+
+    impl<const N: usize> IntoValue for [&str; N] {
+        const KIND: Kind = Kind::Text;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            PrimitiveValue::new_strs(self)
+        }
+    }
+
+    impl<const N: usize> IntoValue for [String; N] {
+        const KIND: Kind = Kind::Text;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            PrimitiveValue::new_strs(self)
+        }
+    }
+
+    impl IntoValue for Vec<u8> {
+        const KIND: Kind = Kind::Bytes;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            PrimitiveValue::from(self)
+        }
+    }
+
+    impl IntoValue for &[u8] {
+        const KIND: Kind = Kind::Bytes;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            PrimitiveValue::from(self)
+        }
+    }
+
+    impl IntoValue for PrimitiveValue {
+        const KIND: Kind = Kind::Any;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            self
+        }
+    }
+
+    impl IntoValue for () {
+        const KIND: Kind = Kind::Any;
+
+        fn into_value(self, _vr: VR) -> PrimitiveValue {
+            PrimitiveValue::Empty
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::__private::{keyword_tag_vr, Kind};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::VR;
+    use dicom_dictionary_std::tags;
+
+    #[test]
+    fn integer_literals_follow_vr() {
+        let obj = dicom_object! {
+            Rows: 512,
+            SimpleFrameList: [1, 2],
+            PixelIntensityRelationshipSign: -1,
+            ImagerPixelSpacing: [1, 2],
+            InstanceNumber: 7,
+            (0x0028, 0x0011): 256,
+        };
+
+        assert_eq!(
+            obj.get(tags::ROWS).unwrap().value().primitive(),
+            Some(&PrimitiveValue::from(512_u16))
+        );
+        assert_eq!(
+            obj.get(tags::SIMPLE_FRAME_LIST)
+                .unwrap()
+                .value()
+                .primitive(),
+            Some(&PrimitiveValue::from([1_u32, 2]))
+        );
+        assert_eq!(
+            obj.get(tags::PIXEL_INTENSITY_RELATIONSHIP_SIGN)
+                .unwrap()
+                .vr(),
+            VR::SS
+        );
+        assert_eq!(
+            obj.get(tags::PIXEL_INTENSITY_RELATIONSHIP_SIGN)
+                .unwrap()
+                .value()
+                .primitive(),
+            Some(&PrimitiveValue::from(-1_i16))
+        );
+        // DS and IS keep the given type, they are written as text
+        assert_eq!(obj.f64s(tags::IMAGER_PIXEL_SPACING).unwrap(), vec![1., 2.]);
+        assert_eq!(obj.i32(tags::INSTANCE_NUMBER).unwrap(), 7);
+        assert_eq!(
+            obj.get(tags::COLUMNS).unwrap().value().primitive(),
+            Some(&PrimitiveValue::from(256_u16))
+        );
+    }
+
+    #[test]
+    fn float_literals_follow_vr() {
+        let obj = dicom_object! {
+            RescaleSlope: 2.5,
+            (0x0018, 0x9087): 1000.5_f32,
+            (0x0054, 0x1300): 1.5,
+        };
+
+        assert_eq!(obj.get(tags::RESCALE_SLOPE).unwrap().vr(), VR::DS);
+        assert_eq!(obj.get(tags::DIFFUSION_B_VALUE).unwrap().vr(), VR::FD);
+        assert_eq!(
+            obj.get(tags::DIFFUSION_B_VALUE)
+                .unwrap()
+                .value()
+                .primitive(),
+            Some(&PrimitiveValue::from(1000.5_f64))
+        );
+        assert_eq!(
+            obj.get(tags::FRAME_REFERENCE_TIME)
+                .unwrap()
+                .value()
+                .primitive(),
+            Some(&PrimitiveValue::from(1.5_f64))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Int value does not match value representation PN")]
+    fn tag_value_mismatch_panics() {
+        dicom_object! {
+            (0x0010, 0x0010): 42,
+        };
+    }
+
+    #[test]
+    #[should_panic(expected = "value 70000 is out of range for US")]
+    fn integer_out_of_range_panics() {
+        dicom_object! {
+            Rows: 70000,
+        };
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown attribute keyword")]
+    fn unknown_keyword_panics() {
+        keyword_tag_vr("PatientNam");
+    }
+
+    #[test]
+    fn kinds_accept_vrs() {
+        assert!(Kind::Int.accepts(VR::US));
+        assert!(Kind::Int.accepts(VR::UN));
+        assert!(!Kind::Int.accepts(VR::PN));
+        assert!(!Kind::Float.accepts(VR::US));
+        assert!(Kind::Text.accepts(VR::DA));
+        assert!(!Kind::Text.accepts(VR::FD));
+        assert!(!Kind::Bytes.accepts(VR::LO));
+        assert!(Kind::Any.accepts(VR::SQ));
+    }
+}
@@ -43,36 +43,59 @@ use itertools::Itertools;
 use smallvec::SmallVec;
 use snafu::{ensure, OptionExt, ResultExt};
 use std::borrow::Cow;
+use std::collections::btree_map::Entry;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
+use std::ops::ControlFlow;
 use std::path::Path;
 use std::{collections::BTreeMap, io::Write};
 
-use crate::file::ReadPreamble;
+use crate::datetime::CombinedDateTime;
+use crate::encapsulated_document::{EncapsulatedDocument, EncapsulatedDocumentError};
+use crate::file::{PartialObject, ReadPreamble};
+use crate::functional_groups::{
+    FrameFunctionalGroups, FrameOutOfRangeSnafu, FunctionalGroupsError,
+    MissingPerFrameFunctionalGroupsSnafu,
+};
+use crate::geometry::{GeometryError, ImageGeometry};
+use crate::merge::{MergeError, MergePolicy};
 use crate::ops::{
     ApplyError, ApplyResult, IncompatibleTypesSnafu, ModifySnafu, UnsupportedActionSnafu,
 };
+use crate::path::{self, AtPathError, PathItem, UnexpectedWildcardSnafu};
+use crate::visit::{DataVisitor, DataVisitorMut, MapValues, PathSegment};
+use crate::write::{
+    measure_sequence_lengths, trim_text_padding, NormalizeVr, ReplaceSequenceLengths,
+    StripGroupLengths,
+};
 use crate::{meta::FileMetaTable, FileMetaTableBuilder};
 use crate::{
-    AccessByNameError, AccessError, AtAccessError, BuildMetaTableSnafu, CreateParserSnafu,
-    CreatePrinterSnafu, DicomObject, ElementNotFoundSnafu, FileDicomObject, InvalidGroupSnafu,
-    MissingElementValueSnafu, MissingLeafElementSnafu, NoSpaceSnafu, NoSuchAttributeNameSnafu,
-    NoSuchDataElementAliasSnafu, NoSuchDataElementTagSnafu, NotASequenceSnafu, OpenFileSnafu,
+    write_dataset_with_options, AccessByNameError, AccessError, AtAccessError, AttributeError,
+    BuildMetaTableSnafu, ConvertValueSnafu, CreateParserSnafu, CreatePrinterSnafu, DicomElement,
+    DicomObject, DuplicateElementSnafu, ElementNotFoundSnafu, FileDicomObject, InvalidGroupSnafu,
+    InvalidMultiplicitySnafu, ItemOutOfRangeSnafu, MissingAttributeSnafu, MissingElementValueSnafu,
+    MissingLeafElementSnafu, NoSpaceSnafu, NoSuchAttributeNameSnafu, NoSuchDataElementAliasSnafu,
+    NoSuchDataElementTagSnafu, NotASequenceAttributeSnafu, NotASequenceSnafu, OpenFileSnafu,
     ParseMetaDataSetSnafu, PrematureEndSnafu, PrepareMetaTableSnafu, PrintDataSetSnafu,
     PrivateCreatorNotFoundSnafu, PrivateElementError, ReadError, ReadFileSnafu,
     ReadPreambleBytesSnafu, ReadTokenSnafu, ReadUnsupportedTransferSyntaxSnafu,
-    UnexpectedTokenSnafu, WithMetaError, WriteError,
+    UnexpectedTokenSnafu, UnknownAttributeSnafu, WithMetaError, WriteError, WriteOptions,
 };
+use dicom_core::chrono::FixedOffset;
 use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
-use dicom_core::header::{GroupNumber, HasLength, Header};
-use dicom_core::value::{DataSetSequence, PixelFragmentSequence, Value, ValueType, C};
+use dicom_core::header::{DataElementHeader, GroupNumber, HasLength, Header};
+use dicom_core::value::{
+    ConvertValueError, DataSetSequence, DicomDate, DicomDateTime, DicomTime, DicomValueType,
+    PixelFragmentSequence, PreciseDateTime, Value, ValueType, C,
+};
 use dicom_core::{DataElement, Length, PrimitiveValue, Tag, VR};
 use dicom_dictionary_std::{tags, StandardDataDictionary};
 use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
 use dicom_encoding::{encode::EncodeTo, text::SpecificCharacterSet, TransferSyntax};
 use dicom_parser::dataset::{DataSetReader, DataToken, IntoTokensOptions};
 use dicom_parser::{
-    dataset::{read::Error as ParserError, DataSetWriter, IntoTokens},
+    dataset::read::{DuplicatePolicy, Error as ParserError, ReadOptions},
+    dataset::{DataSetWriter, IntoTokens},
     StatefulDecode,
 };
 use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
@@ -96,7 +119,7 @@ pub struct InMemDicomObject<D = StandardDataDictionary> {
     /// the element map
     entries: BTreeMap<Tag, InMemElement<D>>,
     /// the data dictionary
-    dict: D,
+    pub(crate) dict: D,
     /// The length of the DICOM object in bytes.
     /// It is usually undefined, unless it is part of an item
     /// in a sequence with a specified length in its item header.
@@ -126,6 +149,7 @@ where
     D: Clone,
 {
     type Element = &'s InMemElement<D>;
+    type Elements = std::collections::btree_map::Values<'s, Tag, InMemElement<D>>;
 
     fn element(&self, tag: Tag) -> Result<Self::Element> {
         self.entries
@@ -137,6 +161,44 @@ where
         let tag = self.lookup_name(name)?;
         self.element(tag).map_err(|e| e.into_access_by_name(name))
     }
+
+    fn elements(&self) -> Self::Elements {
+        self.entries.values()
+    }
+}
+
+impl<'s, D: 's> DicomElement for &'s InMemElement<D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    type Error = std::convert::Infallible;
+    type Item = &'s InMemDicomObject<D>;
+    type Items = std::slice::Iter<'s, InMemDicomObject<D>>;
+
+    fn vr(&self) -> VR {
+        DataElement::vr(self)
+    }
+
+    fn primitive_value(&self) -> Result<Option<Cow<'_, PrimitiveValue>>, Self::Error> {
+        Ok(match self.value() {
+            Value::Primitive(v) => Some(Cow::Borrowed(v)),
+            _ => None,
+        })
+    }
+
+    fn items(&self) -> Option<Self::Items> {
+        DataElement::items(*self).map(|items| items.iter())
+    }
+
+    fn fragments(
+        &self,
+    ) -> Result<Option<Cow<'_, PixelFragmentSequence<InMemFragment>>>, Self::Error> {
+        Ok(match self.value() {
+            Value::PixelSequence(seq) => Some(Cow::Borrowed(seq)),
+            _ => None,
+        })
+    }
 }
 
 impl FileDicomObject<InMemDicomObject<StandardDataDictionary>> {
@@ -259,6 +321,35 @@ impl InMemDicomObject<StandardDataDictionary> {
             SpecificCharacterSet::default(),
         )
     }
+
+    /// Read an object from the bytes of a data set in memory,
+    /// encoded in the given transfer syntax,
+    /// without preamble, magic code, nor file meta group.
+    ///
+    /// This is the reverse of [`to_vec`](InMemDicomObject::to_vec).
+    /// To read the bytes of a whole DICOM file,
+    /// see [`from_slice`](crate::from_slice).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::{InMemDicomObject, WriteOptions};
+    /// use dicom_transfer_syntax_registry::entries::IMPLICIT_VR_LITTLE_ENDIAN;
+    ///
+    /// let ts = IMPLICIT_VR_LITTLE_ENDIAN.erased();
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+    /// ]);
+    /// let bytes = obj.to_vec(&ts, &WriteOptions::default())?;
+    /// let obj2 = InMemDicomObject::from_slice(&bytes, &ts)?;
+    /// assert_eq!(obj, obj2);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_slice(bytes: &[u8], ts: &TransferSyntax) -> Result<Self, ReadError> {
+        Self::read_dataset_with_ts(bytes, ts)
+    }
 }
 
 impl<D> FileDicomObject<InMemDicomObject<D>>
@@ -266,6 +357,22 @@ where
     D: DataDictionary,
     D: Clone,
 {
+    /// Encode the entire object as a DICOM file into a new byte vector,
+    /// according to the given options.
+    /// Preamble, magic code, and file meta group are included
+    /// before the inner object.
+    ///
+    /// This is equivalent to [`write_all_with_options`](Self::write_all_with_options)
+    /// over a vector, except that the vector is allocated once.
+    /// The bytes can be read back with [`from_slice`](crate::from_slice).
+    pub fn to_file_vec(&self, options: &WriteOptions) -> Result<Vec<u8>, WriteError> {
+        let meta_len = 12 + self.meta.information_group_length as usize;
+        let mut out = Vec::with_capacity(128 + 4 + meta_len + self.obj.encoded_len_estimate());
+        // the vector gains nothing from batching
+        self.write_all_with_options(&mut out, &options.clone().batch_size(0))?;
+        Ok(out)
+    }
+
     /// Create a new empty object, using the given dictionary and
     /// file meta table.
     pub fn new_empty_with_dict_and_meta(dict: D, meta: FileMetaTable) -> Self {
@@ -313,7 +420,15 @@ where
         P: AsRef<Path>,
         R: TransferSyntaxIndex,
     {
-        Self::open_file_with_all_options(path, dict, ts_index, None, ReadPreamble::Auto)
+        Self::open_file_with_all_options(
+            path,
+            dict,
+            ts_index,
+            None,
+            ReadPreamble::Auto,
+            Default::default(),
+        )
+        .and_then(PartialObject::into_result)
     }
 
     // detect the presence of a preamble
@@ -347,7 +462,8 @@ where
         ts_index: R,
         read_until: Option<Tag>,
         mut read_preamble: ReadPreamble,
-    ) -> Result<Self, ReadError>
+        options: ReadOptions,
+    ) -> Result<PartialObject<Self>, ReadError>
     where
         P: AsRef<Path>,
         R: TransferSyntaxIndex,
@@ -368,29 +484,7 @@ where
                 .with_context(|_| ReadFileSnafu { filename: path })?;
         }
 
-        // read metadata header
-        let meta = FileMetaTable::from_reader(&mut file).context(ParseMetaDataSetSnafu)?;
-
-        // read rest of data according to metadata, feed it to object
-        if let Some(ts) = ts_index.get(&meta.transfer_syntax) {
-            let mut dataset = DataSetReader::new_with_ts(file, ts).context(CreateParserSnafu)?;
-
-            Ok(FileDicomObject {
-                meta,
-                obj: InMemDicomObject::build_object(
-                    &mut dataset,
-                    dict,
-                    false,
-                    Length::UNDEFINED,
-                    read_until,
-                )?,
-            })
-        } else {
-            ReadUnsupportedTransferSyntaxSnafu {
-                uid: meta.transfer_syntax,
-            }
-            .fail()
-        }
+        Self::read_meta_and_data_set(&mut file, dict, ts_index, read_until, options)
     }
 
     /// Create a DICOM object by reading from a byte source.
@@ -425,7 +519,15 @@ where
         S: Read,
         R: TransferSyntaxIndex,
     {
-        Self::from_reader_with_all_options(src, dict, ts_index, None, ReadPreamble::Auto)
+        Self::from_reader_with_all_options(
+            src,
+            dict,
+            ts_index,
+            None,
+            ReadPreamble::Auto,
+            Default::default(),
+        )
+        .and_then(PartialObject::into_result)
     }
 
     pub(crate) fn from_reader_with_all_options<'s, S: 's, R>(
@@ -434,7 +536,8 @@ where
         ts_index: R,
         read_until: Option<Tag>,
         mut read_preamble: ReadPreamble,
-    ) -> Result<Self, ReadError>
+        options: ReadOptions,
+    ) -> Result<PartialObject<Self>, ReadError>
     where
         S: Read,
         R: TransferSyntaxIndex,
@@ -452,20 +555,39 @@ where
             file.read_exact(&mut buf).context(ReadPreambleBytesSnafu)?;
         }
 
+        Self::read_meta_and_data_set(&mut file, dict, ts_index, read_until, options)
+    }
+
+    /// Read the file meta group and the data set which follows it.
+    ///
+    /// The source is taken as a trait object,
+    /// so that the data set reader and the object builder
+    /// are instantiated once per data dictionary
+    /// instead of once per type of source.
+    /// Since the source is buffered,
+    /// this only costs a dynamic call per read of an element header or value,
+    /// not per byte.
+    fn read_meta_and_data_set<R>(
+        file: &mut dyn BufRead,
+        dict: D,
+        ts_index: R,
+        read_until: Option<Tag>,
+        options: ReadOptions,
+    ) -> Result<PartialObject<Self>, ReadError>
+    where
+        R: TransferSyntaxIndex,
+    {
         // read metadata header
-        let meta = FileMetaTable::from_reader(&mut file).context(ParseMetaDataSetSnafu)?;
+        let meta = FileMetaTable::from_reader(&mut *file).context(ParseMetaDataSetSnafu)?;
 
         // read rest of data according to metadata, feed it to object
         if let Some(ts) = ts_index.get(&meta.transfer_syntax) {
-            let mut dataset = DataSetReader::new_with_ts(file, ts).context(CreateParserSnafu)?;
-            let obj = InMemDicomObject::build_object(
-                &mut dataset,
-                dict,
-                false,
-                Length::UNDEFINED,
-                read_until,
-            )?;
-            Ok(FileDicomObject { meta, obj })
+            let duplicates = options.duplicates;
+            let mut dataset =
+                DataSetReader::new_with_ts_options(file, ts, options).context(CreateParserSnafu)?;
+            let (obj, error) =
+                InMemDicomObject::build_partial_object(&mut dataset, dict, read_until, duplicates);
+            Ok(PartialObject::new(FileDicomObject { meta, obj }, error))
         } else {
             ReadUnsupportedTransferSyntaxSnafu {
                 uid: meta.transfer_syntax,
@@ -579,7 +701,14 @@ where
         D: DataDictionary,
     {
         let mut dataset = DataSetReader::new(decoder, Default::default());
-        InMemDicomObject::build_object(&mut dataset, dict, false, Length::UNDEFINED, None)
+        InMemDicomObject::build_object(
+            &mut dataset,
+            dict,
+            false,
+            Length::UNDEFINED,
+            None,
+            DuplicatePolicy::default(),
+        )
     }
 
     /// Read an object from a source,
@@ -616,7 +745,14 @@ where
     {
         let from = BufReader::new(from);
         let mut dataset = DataSetReader::new_with_ts_cs(from, ts, cs).context(CreateParserSnafu)?;
-        InMemDicomObject::build_object(&mut dataset, dict, false, Length::UNDEFINED, None)
+        InMemDicomObject::build_object(
+            &mut dataset,
+            dict,
+            false,
+            Length::UNDEFINED,
+            None,
+            DuplicatePolicy::default(),
+        )
     }
 
     // Standard methods follow. They are not placed as a trait implementation
@@ -674,6 +810,206 @@ where
         self.entries.get(&tag)
     }
 
+    /// Check whether an element with the given tag
+    /// is present in this object's root data set.
+    pub fn contains(&self, tag: Tag) -> bool {
+        self.entries.contains_key(&tag)
+    }
+
+    /// Obtain the number of data elements in this object's root data set.
+    ///
+    /// Elements nested in sequence items are not counted.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether this object has no data elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Retrieve the items of a data set sequence by its tag.
+    ///
+    /// Returns `None` if the element does not exist
+    /// or is not a data set sequence.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, VR, value::DataSetSequence};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let obj = InMemDicomObject::from_element_iter([DataElement::new(
+    ///     tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+    ///     VR::SQ,
+    ///     DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+    ///         DataElement::new(
+    ///             tags::PIXEL_MEASURES_SEQUENCE,
+    ///             VR::SQ,
+    ///             DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+    ///                 DataElement::new(tags::PIXEL_SPACING, VR::DS, "0.5\\0.5"),
+    ///             ])]),
+    ///         ),
+    ///     ])]),
+    /// )]);
+    ///
+    /// let items = obj.items(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE).unwrap();
+    /// assert_eq!(items.len(), 1);
+    /// let pixel_spacing = items[0]
+    ///     .item(tags::PIXEL_MEASURES_SEQUENCE, 0)
+    ///     .unwrap()
+    ///     .f64s(tags::PIXEL_SPACING)?;
+    /// assert_eq!(pixel_spacing, [0.5, 0.5]);
+    /// # Ok::<_, dicom_object::AttributeError>(())
+    /// ```
+    pub fn items(&self, tag: Tag) -> Option<&[InMemDicomObject<D>]> {
+        self.entries.get(&tag)?.items()
+    }
+
+    /// Retrieve a single item of a data set sequence
+    /// by the sequence's tag and the item's index.
+    ///
+    /// Returns `None` if the element does not exist,
+    /// is not a data set sequence,
+    /// or the index is out of bounds.
+    pub fn item(&self, tag: Tag, index: usize) -> Option<&InMemDicomObject<D>> {
+        self.items(tag)?.get(index)
+    }
+
+    /// Obtain a mutable reference to the items of a data set sequence
+    /// by its tag.
+    ///
+    /// The recorded lengths of this object and of the sequence
+    /// are reset to undefined,
+    /// so that the data set is consistent when written.
+    ///
+    /// Returns `None` if the element does not exist
+    /// or is not a data set sequence.
+    pub fn items_mut(&mut self, tag: Tag) -> Option<&mut C<InMemDicomObject<D>>> {
+        let items = self.entries.get_mut(&tag)?.items_mut()?;
+        self.len = Length::UNDEFINED;
+        Some(items)
+    }
+
+    /// Obtain a mutable reference to a single item of a data set sequence
+    /// by the sequence's tag and the item's index.
+    ///
+    /// The recorded lengths of this object and of the sequence
+    /// are reset to undefined,
+    /// so that the data set is consistent when written.
+    ///
+    /// Returns `None` if the element does not exist,
+    /// is not a data set sequence,
+    /// or the index is out of bounds.
+    pub fn item_mut(&mut self, tag: Tag, index: usize) -> Option<&mut InMemDicomObject<D>> {
+        self.items_mut(tag)?.get_mut(index)
+    }
+
+    /// Append an item to the end of a data set sequence,
+    /// creating the sequence if it does not exist yet.
+    ///
+    /// The recorded lengths of this object and of the sequence
+    /// are reset to undefined.
+    /// Fails if the element exists but is not a data set sequence.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let mut obj = InMemDicomObject::new_empty();
+    /// obj.append_item(
+    ///     tags::REFERENCED_IMAGE_SEQUENCE,
+    ///     InMemDicomObject::from_element_iter([
+    ///         DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.3.4"),
+    ///     ]),
+    /// )?;
+    /// assert_eq!(obj.items(tags::REFERENCED_IMAGE_SEQUENCE).map(|items| items.len()), Some(1));
+    /// # Ok::<_, dicom_object::AttributeError>(())
+    /// ```
+    pub fn append_item(
+        &mut self,
+        tag: Tag,
+        item: InMemDicomObject<D>,
+    ) -> Result<(), AttributeError> {
+        self.sequence_items_mut(tag, true)?.push(item);
+        Ok(())
+    }
+
+    /// Insert an item into a data set sequence at the given index,
+    /// shifting all items after it.
+    ///
+    /// A missing sequence is created if the index is 0.
+    /// The recorded lengths of this object and of the sequence
+    /// are reset to undefined.
+    /// Fails if the element is not a data set sequence
+    /// or the index is greater than the number of items.
+    pub fn insert_item(
+        &mut self,
+        tag: Tag,
+        index: usize,
+        item: InMemDicomObject<D>,
+    ) -> Result<(), AttributeError> {
+        let items = self.sequence_items_mut(tag, index == 0)?;
+        ensure!(
+            index <= items.len(),
+            ItemOutOfRangeSnafu {
+                tag,
+                index,
+                len: items.len()
+            }
+        );
+        items.insert(index, item);
+        Ok(())
+    }
+
+    /// Remove and return the item of a data set sequence at the given index,
+    /// shifting all items after it.
+    ///
+    /// The recorded lengths of this object and of the sequence
+    /// are reset to undefined.
+    /// Fails if the element does not exist, is not a data set sequence,
+    /// or the index is out of bounds.
+    pub fn remove_item(
+        &mut self,
+        tag: Tag,
+        index: usize,
+    ) -> Result<InMemDicomObject<D>, AttributeError> {
+        let items = self.sequence_items_mut(tag, false)?;
+        ensure!(
+            index < items.len(),
+            ItemOutOfRangeSnafu {
+                tag,
+                index,
+                len: items.len()
+            }
+        );
+        Ok(items.remove(index))
+    }
+
+    /// Obtain the items of a data set sequence for modification,
+    /// optionally creating an empty sequence if it does not exist.
+    fn sequence_items_mut(
+        &mut self,
+        tag: Tag,
+        create: bool,
+    ) -> Result<&mut C<InMemDicomObject<D>>, AttributeError> {
+        if create && !self.entries.contains_key(&tag) {
+            self.put_element(DataElement::new(
+                tag,
+                VR::SQ,
+                DataSetSequence::new(C::new(), Length::UNDEFINED),
+            ));
+        }
+        ensure!(
+            self.entries.contains_key(&tag),
+            MissingAttributeSnafu { tag }
+        );
+        self.items_mut(tag)
+            .context(NotASequenceAttributeSnafu { tag })
+    }
+
     // Get a mutable reference to a particular DICOM attribute from this object by tag.
     //
     // Should be private as it would allow a user to change the tag of an
@@ -772,1794 +1108,4476 @@ where
         })
     }
 
-    /// Insert a data element to the object, replacing (and returning) any
-    /// previous element of the same attribute.
-    /// This might invalidate all sequence and item lengths if the charset of the
-    /// element changes.
-    pub fn put(&mut self, elt: InMemElement<D>) -> Option<InMemElement<D>> {
-        self.put_element(elt)
-    }
-
-    /// Insert a data element to the object, replacing (and returning) any
-    /// previous element of the same attribute.
-    /// This might invalidate all sequence and item lengths if the charset of the
-    /// element changes.
-    pub fn put_element(&mut self, elt: InMemElement<D>) -> Option<InMemElement<D>> {
-        self.len = Length::UNDEFINED;
-        self.invalidate_if_charset_changed(elt.tag());
-        self.entries.insert(elt.tag(), elt)
+    /// Retrieve the value of an attribute as a single string.
+    ///
+    /// If the value has multiple strings, only the first one is returned.
+    /// Trailing spaces and null characters are removed.
+    /// Values which are not textual are converted to their
+    /// standard string representation.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or is not a primitive value.
+    /// See [`strict`](Self::strict) for getters
+    /// which also fail on multi-valued attributes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::MODALITY, VR::CS, "CT"),
+    /// ]);
+    /// assert_eq!(obj.string(tags::MODALITY)?, "CT");
+    /// # Ok::<_, dicom_object::AttributeError>(())
+    /// ```
+    pub fn string(&self, tag: Tag) -> Result<String, AttributeError> {
+        self.single_value(tag, "string", false, first_str)
     }
 
-    /// Insert a private element into the dataset, replacing (and returning) any
-    /// previous element of the same attribute.
+    /// Retrieve the value of an attribute as a sequence of strings.
     ///
-    /// This function will find the next available private element block in the given
-    /// group. If the creator already exists, the element will be added to the block
-    /// already reserved for that creator. If it does not exist, then a new block
-    /// will be reserved for the creator in the specified group.
-    /// An error is returned if there is no space left in the group.
+    /// Trailing spaces and null characters are removed from each string.
+    /// Textual values are split into multiple strings
+    /// by the backslash delimiter,
+    /// unless the value representation only admits a single value
+    /// (such as LT or UT).
+    /// Values which are not textual are converted to their
+    /// standard string representation.
     ///
-    /// For more info, see the [DICOM standard section on private elements][1].
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or is not a primitive value.
     ///
-    /// [1]: https://dicom.nema.org/medical/dicom/2024a/output/chtml/part05/sect_7.8.html
+    /// # Example
     ///
-    /// ## Example
     /// ```
-    /// # use dicom_core::{VR, PrimitiveValue, Tag, DataElement, header::Header};
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_dictionary_std::tags;
     /// # use dicom_object::InMemDicomObject;
-    /// # use std::error::Error;
-    /// let mut ds = InMemDicomObject::new_empty();
-    /// ds.put_private_element(
-    ///     0x0009,
-    ///     "CREATOR 1",
-    ///     0x02,
-    ///     VR::DS,
-    ///     PrimitiveValue::from("1.0"),
-    /// )?;
-    /// assert_eq!(
-    ///     ds.private_element(0x0009, "CREATOR 1", 0x02)?
-    ///         .value()
-    ///         .to_str()?,
-    ///     "1.0"
-    /// );
-    /// assert_eq!(
-    ///     ds.private_element(0x0009, "CREATOR 1", 0x02)?
-    ///         .header()
-    ///         .tag(),
-    ///     Tag(0x0009, 0x0102)
-    /// );
-    /// # Ok::<(), Box<dyn Error>>(())
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::IMAGE_TYPE, VR::CS, "ORIGINAL\\PRIMARY\\AXIAL"),
+    /// ]);
+    /// assert_eq!(obj.strings(tags::IMAGE_TYPE)?, ["ORIGINAL", "PRIMARY", "AXIAL"]);
+    /// # Ok::<_, dicom_object::AttributeError>(())
     /// ```
-    pub fn put_private_element(
-        &mut self,
-        group: GroupNumber,
-        creator: &str,
-        element: u8,
-        vr: VR,
-        value: PrimitiveValue,
-    ) -> Result<Option<InMemElement<D>>, PrivateElementError> {
-        ensure!(group % 2 == 1, InvalidGroupSnafu { group });
-        let private_creator = self.find_private_creator(group, creator);
-        if let Some(tag) = private_creator {
-            // Private creator already exists
-            let tag = Tag(group, tag.element() << 8 | (element as u16));
-            Ok(self.put_element(DataElement::new(tag, vr, value)))
-        } else {
-            // Find last reserved block of tags.
-            let range = Tag(group, 0)..Tag(group, 0xFF);
-            let last_entry = self.entries.range(range).next_back();
-            let next_available = match last_entry {
-                Some((tag, _)) => tag.element() + 1,
-                None => 0x01,
-            };
-            if next_available < 0xFF {
-                // Put private creator
-                let tag = Tag(group, next_available);
-                self.put_str(tag, VR::LO, creator);
+    pub fn strings(&self, tag: Tag) -> Result<Vec<String>, AttributeError> {
+        self.multi_value(tag, "strings", |v| Ok(v.to_multi_str().into_owned()))
+    }
 
-                // Put private element
-                let tag = Tag(group, next_available << 8 | (element as u16));
-                Ok(self.put_element(DataElement::new(tag, vr, value)))
-            } else {
-                NoSpaceSnafu { group }.fail()
-            }
-        }
+    /// Retrieve the value of an attribute as a single unsigned 16-bit integer.
+    ///
+    /// If the value has multiple numbers, only the first one is returned.
+    /// Textual values (such as those of IS attributes) are parsed.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn u16(&self, tag: Tag) -> Result<u16, AttributeError> {
+        self.single_value(tag, "u16", false, |v| v.to_int())
     }
 
-    /// Insert a new element with a string value to the object,
-    /// replacing (and returning) any previous element of the same attribute.
-    pub fn put_str(
-        &mut self,
-        tag: Tag,
-        vr: VR,
-        string: impl Into<String>,
-    ) -> Option<InMemElement<D>> {
-        self.put_element(DataElement::new(tag, vr, string.into()))
+    /// Retrieve all values of an attribute as unsigned 16-bit integers.
+    ///
+    /// Textual values (such as those of IS attributes) are parsed.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn u16s(&self, tag: Tag) -> Result<Vec<u16>, AttributeError> {
+        self.multi_value(tag, "u16s", |v| v.to_multi_int())
     }
 
-    /// Remove a DICOM element by its tag,
-    /// reporting whether it was present.
-    pub fn remove_element(&mut self, tag: Tag) -> bool {
-        if self.entries.remove(&tag).is_some() {
-            self.len = Length::UNDEFINED;
-            true
-        } else {
-            false
-        }
+    /// Retrieve the value of an attribute as a single signed 32-bit integer.
+    ///
+    /// If the value has multiple numbers, only the first one is returned.
+    /// Textual values (such as those of IS attributes) are parsed.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn i32(&self, tag: Tag) -> Result<i32, AttributeError> {
+        self.single_value(tag, "i32", false, |v| v.to_int())
     }
 
-    /// Remove a DICOM element by its keyword,
-    /// reporting whether it was present.
-    pub fn remove_element_by_name(&mut self, name: &str) -> Result<bool, AccessByNameError> {
-        let tag = self.lookup_name(name)?;
-        Ok(self.entries.remove(&tag).is_some()).map(|removed| {
-            if removed {
-                self.len = Length::UNDEFINED;
-            }
-            removed
-        })
+    /// Retrieve all values of an attribute as signed 32-bit integers.
+    ///
+    /// Textual values (such as those of IS attributes) are parsed.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn i32s(&self, tag: Tag) -> Result<Vec<i32>, AttributeError> {
+        self.multi_value(tag, "i32s", |v| v.to_multi_int())
     }
 
-    /// Remove and return a particular DICOM element by its tag.
-    pub fn take_element(&mut self, tag: Tag) -> Result<InMemElement<D>> {
-        self.entries
-            .remove(&tag)
-            .map(|e| {
-                self.len = Length::UNDEFINED;
-                e
-            })
-            .context(NoSuchDataElementTagSnafu { tag })
+    /// Retrieve the value of an attribute
+    /// as a single 64-bit floating point number.
+    ///
+    /// If the value has multiple numbers, only the first one is returned.
+    /// Textual values (such as those of DS attributes) are parsed.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn f64(&self, tag: Tag) -> Result<f64, AttributeError> {
+        self.single_value(tag, "f64", false, PrimitiveValue::to_float64)
     }
 
-    /// Remove and return a particular DICOM element by its tag,
-    /// if it is present,
-    /// returns `None` otherwise.
-    pub fn take(&mut self, tag: Tag) -> Option<InMemElement<D>> {
-        self.entries.remove(&tag).map(|e| {
-            self.len = Length::UNDEFINED;
-            e
-        })
+    /// Retrieve all values of an attribute
+    /// as 64-bit floating point numbers.
+    ///
+    /// Textual values (such as those of DS attributes) are parsed.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::PIXEL_SPACING, VR::DS, "0.5\\0.25"),
+    /// ]);
+    /// assert_eq!(obj.f64s(tags::PIXEL_SPACING)?, [0.5, 0.25]);
+    /// // only the first value
+    /// assert_eq!(obj.f64(tags::PIXEL_SPACING)?, 0.5);
+    /// # Ok::<_, dicom_object::AttributeError>(())
+    /// ```
+    pub fn f64s(&self, tag: Tag) -> Result<Vec<f64>, AttributeError> {
+        self.multi_value(tag, "f64s", PrimitiveValue::to_multi_float64)
     }
 
-    /// Remove and return a particular DICOM element by its name.
-    pub fn take_element_by_name(
-        &mut self,
-        name: &str,
-    ) -> Result<InMemElement<D>, AccessByNameError> {
-        let tag = self.lookup_name(name)?;
-        self.entries
-            .remove(&tag)
-            .map(|e| {
-                self.len = Length::UNDEFINED;
-                e
-            })
-            .with_context(|| NoSuchDataElementAliasSnafu {
-                tag,
-                alias: name.to_string(),
-            })
+    /// Retrieve the value of an attribute as a single DICOM tag,
+    /// as in the value of an AT attribute.
+    ///
+    /// If the value has multiple tags, only the first one is returned.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or does not hold tags.
+    pub fn tag_value(&self, tag: Tag) -> Result<Tag, AttributeError> {
+        self.single_value(tag, "tag", false, first_tag)
     }
 
-    /// Modify the object by
-    /// retaining only the DICOM data elements specified by the predicate.
+    /// Retrieve all values of an attribute as DICOM tags,
+    /// as in the value of an AT attribute.
     ///
-    /// The elements are visited in ascending tag order,
-    /// and those for which `f(&element)` returns `false` are removed.
-    pub fn retain(&mut self, mut f: impl FnMut(&InMemElement<D>) -> bool) {
-        self.entries.retain(|_, elem| f(elem));
-        self.len = Length::UNDEFINED;
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or does not hold tags.
+    pub fn tag_values(&self, tag: Tag) -> Result<Vec<Tag>, AttributeError> {
+        self.multi_value(tag, "tags", all_tags)
     }
 
-    /// Obtain a temporary mutable reference to a DICOM value by tag,
-    /// so that mutations can be applied within.
+    /// Retrieve the value of an attribute as a single DICOM date.
     ///
-    /// If found, this method resets all related lengths recorded
-    /// and returns `true`.
-    /// Returns `false` otherwise.
+    /// If the value has multiple dates, only the first one is returned.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn date(&self, tag: Tag) -> Result<DicomDate, AttributeError> {
+        self.single_value(tag, "date", false, PrimitiveValue::to_date)
+    }
+
+    /// Retrieve all values of an attribute as DICOM dates.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn dates(&self, tag: Tag) -> Result<Vec<DicomDate>, AttributeError> {
+        self.multi_value(tag, "dates", PrimitiveValue::to_multi_date)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM time.
+    ///
+    /// If the value has multiple times, only the first one is returned.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn time(&self, tag: Tag) -> Result<DicomTime, AttributeError> {
+        self.single_value(tag, "time", false, PrimitiveValue::to_time)
+    }
+
+    /// Retrieve all values of an attribute as DICOM times.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn times(&self, tag: Tag) -> Result<Vec<DicomTime>, AttributeError> {
+        self.multi_value(tag, "times", PrimitiveValue::to_multi_time)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM date-time.
+    ///
+    /// If the value has multiple date-times, only the first one is returned.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn datetime(&self, tag: Tag) -> Result<DicomDateTime, AttributeError> {
+        self.single_value(tag, "datetime", false, PrimitiveValue::to_datetime)
+    }
+
+    /// Retrieve all values of an attribute as DICOM date-times.
+    ///
+    /// An error is returned if the attribute does not exist,
+    /// is empty, or cannot be converted.
+    pub fn datetimes(&self, tag: Tag) -> Result<Vec<DicomDateTime>, AttributeError> {
+        self.multi_value(tag, "datetimes", PrimitiveValue::to_multi_datetime)
+    }
+
+    /// Retrieve the offset from UTC declared in this object
+    /// by _Timezone Offset From UTC_ (0008,0201),
+    /// which applies to the date and time values in the object
+    /// without their own offset.
+    ///
+    /// Returns `None` if the attribute is missing or empty.
+    /// An error is returned if the value is not in the form `&ZZXX`.
+    pub fn timezone_offset(&self) -> Result<Option<FixedOffset>, AttributeError> {
+        crate::timezone_offset(&self)
+    }
+
+    /// Retrieve the value of a date-time attribute
+    /// as a precise date-time (such as an absolute timestamp).
+    ///
+    /// If the value does not have its own offset from UTC,
+    /// the one of _Timezone Offset From UTC_ (0008,0201) is applied,
+    /// or else `default_offset`.
+    /// Without any offset, the result is time-zone naive.
+    /// The value must be precise up to the second;
+    /// a missing second fraction is taken as zero.
     ///
     /// # Example
     ///
     /// ```
-    /// # use dicom_core::{DataElement, VR, dicom_value};
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_core::chrono::FixedOffset;
     /// # use dicom_dictionary_std::tags;
     /// # use dicom_object::InMemDicomObject;
-    /// let mut obj = InMemDicomObject::from_element_iter([
-    ///     DataElement::new(tags::LOSSY_IMAGE_COMPRESSION_RATIO, VR::DS, dicom_value!(Strs, ["25"])),
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::TIMEZONE_OFFSET_FROM_UTC, VR::SH, "+0100"),
+    ///     DataElement::new(tags::ACQUISITION_DATE_TIME, VR::DT, "20240301100000"),
     /// ]);
-    ///
-    /// // update lossy image compression ratio
-    /// obj.update_value(tags::LOSSY_IMAGE_COMPRESSION_RATIO, |e| {
-    ///     e.primitive_mut().unwrap().extend_str(["2.56"]);
-    /// });
-    ///
+    /// let dt = obj.precise_datetime(tags::ACQUISITION_DATE_TIME, None)?;
     /// assert_eq!(
-    ///     obj.get(tags::LOSSY_IMAGE_COMPRESSION_RATIO).unwrap().value().to_str().unwrap(),
-    ///     "25\\2.56"
+    ///     dt.as_datetime().unwrap().to_rfc3339(),
+    ///     "2024-03-01T10:00:00+01:00",
     /// );
+    /// # Ok::<_, dicom_object::AttributeError>(())
     /// ```
-    pub fn update_value(
-        &mut self,
+    pub fn precise_datetime(
+        &self,
         tag: Tag,
-        f: impl FnMut(&mut Value<InMemDicomObject<D>, InMemFragment>),
-    ) -> bool {
-        self.invalidate_if_charset_changed(tag);
-        if let Some(e) = self.entries.get_mut(&tag) {
-            e.update_value(f);
-            self.len = Length::UNDEFINED;
-            true
-        } else {
-            false
-        }
+        default_offset: Option<FixedOffset>,
+    ) -> Result<PreciseDateTime, AttributeError> {
+        crate::precise_datetime(&self, tag, default_offset)
     }
 
-    /// Obtain a temporary mutable reference to a DICOM value by AttributeSelector,
-    /// so that mutations can be applied within.
+    /// Retrieve a date-time combined from a date (DA) attribute
+    /// and a time (TM) attribute,
+    /// such as _Study Date_ and _Study Time_.
     ///
-    /// If found, this method resets all related lengths recorded
-    /// and returns `true`.
-    /// Returns `false` otherwise.
-    ///
-    /// See the documentation of [`AttributeSelector`] for more information
-    /// on how to write attribute selectors.
+    /// The time keeps its own precision.
+    /// If the time attribute is missing or empty,
+    /// the result only has the date
+    /// (see [`CombinedDateTime::is_date_only`]).
+    /// The offset of _Timezone Offset From UTC_ (0008,0201) is applied
+    /// if present.
     ///
-    /// Note: Consider using [`apply`](ApplyOp::apply) when possible.
+    /// An error naming the offending [part](crate::DateTimePart)
+    /// is returned if the date is missing or invalid,
+    /// or if the time is invalid.
     ///
     /// # Example
     ///
     /// ```
-    /// # use dicom_core::{DataElement, VR, dicom_value, value::DataSetSequence};
+    /// # use dicom_core::{DataElement, VR};
     /// # use dicom_dictionary_std::tags;
     /// # use dicom_object::InMemDicomObject;
-    /// # use dicom_core::ops::{AttributeAction, AttributeOp, ApplyOp};
-    /// let mut dcm = InMemDicomObject::from_element_iter([
-    ///     DataElement::new(
-    ///         tags::OTHER_PATIENT_I_DS_SEQUENCE,
-    ///         VR::SQ,
-    ///         DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
-    ///             DataElement::new(
-    ///                 tags::PATIENT_ID,
-    ///                 VR::LO,
-    ///                 dicom_value!(Str, "1234")
-    ///             )])
-    ///         ])
-    ///     ),
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::STUDY_DATE, VR::DA, "20240301"),
+    ///     DataElement::new(tags::STUDY_TIME, VR::TM, "101530.25"),
     /// ]);
-    /// let selector = (
-    ///     tags::OTHER_PATIENT_I_DS_SEQUENCE,
-    ///     0,
-    ///     tags::PATIENT_ID
-    /// );
+    /// let dt = obj.datetime_of(tags::STUDY_DATE, tags::STUDY_TIME)?;
+    /// assert_eq!(dt.to_string(), "2024-03-01 10:15:30.25");
+    /// # Ok::<_, dicom_object::AttributeError>(())
+    /// ```
+    pub fn datetime_of(
+        &self,
+        date_tag: Tag,
+        time_tag: Tag,
+    ) -> Result<CombinedDateTime, AttributeError> {
+        crate::datetime::datetime_of(&self, date_tag, time_tag)
+    }
+
+    /// Retrieve the date-time of the study,
+    /// from _Study Date_ and _Study Time_.
     ///
-    /// // update referenced SOP instance UID for deidentification potentially
-    /// dcm.update_value_at(*&selector, |e| {
-    ///     let mut v = e.primitive_mut().unwrap();
-    ///     *v = dicom_value!(Str, "abcd");
-    /// });
+    /// See [`datetime_of`](Self::datetime_of).
+    pub fn study_datetime(&self) -> Result<CombinedDateTime, AttributeError> {
+        self.datetime_of(tags::STUDY_DATE, tags::STUDY_TIME)
+    }
+
+    /// Retrieve the date-time of the acquisition,
+    /// from _Acquisition Date_ and _Acquisition Time_.
     ///
-    /// assert_eq!(
-    ///     dcm.entry_at(*&selector).unwrap().value().to_str().unwrap(),
-    ///     "abcd"
-    /// );
-    /// ```
-    pub fn update_value_at(
-        &mut self,
-        selector: impl Into<AttributeSelector>,
-        f: impl FnMut(&mut Value<InMemDicomObject<D>, InMemFragment>),
-    ) -> Result<(), AtAccessError> {
-        self.entry_at_mut(selector)
-            .map(|e| e.update_value(f))
-            .map(|_| {
-                self.len = Length::UNDEFINED;
-            })
+    /// See [`datetime_of`](Self::datetime_of).
+    pub fn acquisition_datetime(&self) -> Result<CombinedDateTime, AttributeError> {
+        self.datetime_of(tags::ACQUISITION_DATE, tags::ACQUISITION_TIME)
     }
 
-    /// Obtain the DICOM value by finding the element
-    /// that matches the given selector.
+    /// Retrieve the date-time at which the content was created,
+    /// from _Content Date_ and _Content Time_.
     ///
-    /// Returns an error if the respective element or any of its parents
-    /// cannot be found.
+    /// See [`datetime_of`](Self::datetime_of).
+    pub fn content_datetime(&self) -> Result<CombinedDateTime, AttributeError> {
+        self.datetime_of(tags::CONTENT_DATE, tags::CONTENT_TIME)
+    }
+
+    /// Obtain a view of this object
+    /// in which single-value getters are strict about value multiplicity.
     ///
-    /// See the documentation of [`AttributeSelector`] for more information
-    /// on how to write attribute selectors.
+    /// By default, getters such as [`f64`](Self::f64)
+    /// return the first value of a multi-valued attribute.
+    /// The getters of the returned view
+    /// fail with [`AttributeError::MultipleValues`] instead.
     ///
     /// # Example
     ///
-    /// ```no_run
-    /// # use dicom_core::prelude::*;
-    /// # use dicom_core::ops::AttributeSelector;
+    /// ```
+    /// # use dicom_core::{DataElement, VR};
     /// # use dicom_dictionary_std::tags;
-    /// # use dicom_object::InMemDicomObject;
-    /// # let obj: InMemDicomObject = unimplemented!();
-    /// let referenced_sop_instance_iod = obj.value_at(
-    ///     (
-    ///         tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
-    ///         tags::REFERENCED_IMAGE_SEQUENCE,
-    ///         tags::REFERENCED_SOP_INSTANCE_UID,
-    ///     ))?
-    ///     .to_str()?;
-    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # use dicom_object::{AttributeError, InMemDicomObject};
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::WINDOW_CENTER, VR::DS, "40\\400"),
+    ///     DataElement::new(tags::ROWS, VR::US, dicom_core::PrimitiveValue::from(512_u16)),
+    /// ]);
+    /// assert_eq!(obj.f64(tags::WINDOW_CENTER)?, 40.);
+    /// assert!(matches!(
+    ///     obj.strict().f64(tags::WINDOW_CENTER),
+    ///     Err(AttributeError::MultipleValues { count: 2, .. }),
+    /// ));
+    /// assert_eq!(obj.strict().u16(tags::ROWS)?, 512);
+    /// # Ok::<_, AttributeError>(())
     /// ```
-    pub fn value_at(
+    pub fn strict(&self) -> Strict<'_, D> {
+        Strict { obj: self }
+    }
+
+    // The typed getters are shared with all other object backends,
+    // see `crate::single_value` and `crate::multi_value`.
+    fn single_value<T>(
         &self,
-        selector: impl Into<AttributeSelector>,
-    ) -> Result<&Value<InMemDicomObject<D>, InMemFragment>, AtAccessError> {
-        let selector: AttributeSelector = selector.into();
+        tag: Tag,
+        requested: &'static str,
+        strict: bool,
+        convert: impl FnOnce(&PrimitiveValue) -> Result<T, ConvertValueError>,
+    ) -> Result<T, AttributeError> {
+        crate::single_value(&self, tag, requested, strict, convert)
+    }
 
-        let mut obj = self;
-        for (i, step) in selector.iter().enumerate() {
-            match step {
-                // reached the leaf
-                AttributeSelectorStep::Tag(tag) => {
-                    return obj.get(*tag).map(|e| e.value()).with_context(|| {
-                        MissingLeafElementSnafu {
-                            selector: selector.clone(),
-                        }
-                    });
-                }
-                // navigate further down
-                AttributeSelectorStep::Nested { tag, item } => {
-                    let e = obj
-                        .entries
-                        .get(tag)
-                        .with_context(|| crate::MissingSequenceSnafu {
-                            selector: selector.clone(),
-                            step_index: i as u32,
-                        })?;
+    fn multi_value<T>(
+        &self,
+        tag: Tag,
+        requested: &'static str,
+        convert: impl FnOnce(&PrimitiveValue) -> Result<Vec<T>, ConvertValueError>,
+    ) -> Result<Vec<T>, AttributeError> {
+        crate::multi_value(&self, tag, requested, convert)
+    }
 
-                    // get items
-                    let items = e.items().with_context(|| NotASequenceSnafu {
-                        selector: selector.clone(),
-                        step_index: i as u32,
-                    })?;
-
-                    // if item.length == i and action is a constructive action, append new item
-                    obj =
-                        items
-                            .get(*item as usize)
-                            .with_context(|| crate::MissingSequenceSnafu {
-                                selector: selector.clone(),
-                                step_index: i as u32,
-                            })?;
-                }
-            }
-        }
-
-        unreachable!()
+    /// Insert a data element to the object, replacing (and returning) any
+    /// previous element of the same attribute.
+    /// This might invalidate all sequence and item lengths if the charset of the
+    /// element changes.
+    pub fn put(&mut self, elt: InMemElement<D>) -> Option<InMemElement<D>> {
+        self.put_element(elt)
     }
 
-    /// Change the 'specific_character_set' tag to ISO_IR 192, marking the dataset as UTF-8
-    pub fn convert_to_utf8(&mut self) {
-        self.put(DataElement::new(
-            tags::SPECIFIC_CHARACTER_SET,
-            VR::CS,
-            "ISO_IR 192",
-        ));
+    /// Insert a data element to the object, replacing (and returning) any
+    /// previous element of the same attribute.
+    /// This might invalidate all sequence and item lengths if the charset of the
+    /// element changes.
+    pub fn put_element(&mut self, elt: InMemElement<D>) -> Option<InMemElement<D>> {
+        self.len = Length::UNDEFINED;
+        self.invalidate_if_charset_changed(elt.tag());
+        self.entries.insert(elt.tag(), elt)
     }
 
-    /// Get a DataElement by AttributeSelector
+    /// Insert a private element into the dataset, replacing (and returning) any
+    /// previous element of the same attribute.
     ///
-    /// If the element or other intermediate elements do not exist, the method will return an error.
+    /// This function will find the next available private element block in the given
+    /// group. If the creator already exists, the element will be added to the block
+    /// already reserved for that creator. If it does not exist, then a new block
+    /// will be reserved for the creator in the specified group.
+    /// An error is returned if there is no space left in the group.
     ///
-    /// See the documentation of [`AttributeSelector`] for more information
-    /// on how to write attribute selectors.
+    /// For more info, see the [DICOM standard section on private elements][1].
     ///
-    /// If you only need the value, use [`value_at`](Self::value_at).
-    pub fn entry_at(
-        &self,
-        selector: impl Into<AttributeSelector>,
-    ) -> Result<&InMemElement<D>, AtAccessError> {
-        let selector: AttributeSelector = selector.into();
-
-        let mut obj = self;
-        for (i, step) in selector.iter().enumerate() {
-            match step {
-                // reached the leaf
-                AttributeSelectorStep::Tag(tag) => {
-                    return obj.get(*tag).with_context(|| MissingLeafElementSnafu {
-                        selector: selector.clone(),
-                    })
-                }
-                // navigate further down
-                AttributeSelectorStep::Nested { tag, item } => {
-                    let e = obj
-                        .entries
-                        .get(tag)
-                        .with_context(|| crate::MissingSequenceSnafu {
-                            selector: selector.clone(),
-                            step_index: i as u32,
-                        })?;
-
-                    // get items
-                    let items = e.items().with_context(|| NotASequenceSnafu {
-                        selector: selector.clone(),
-                        step_index: i as u32,
-                    })?;
+    /// [1]: https://dicom.nema.org/medical/dicom/2024a/output/chtml/part05/sect_7.8.html
+    ///
+    /// ## Example
+    /// ```
+    /// # use dicom_core::{VR, PrimitiveValue, Tag, DataElement, header::Header};
+    /// # use dicom_object::InMemDicomObject;
+    /// # use std::error::Error;
+    /// let mut ds = InMemDicomObject::new_empty();
+    /// ds.put_private_element(
+    ///     0x0009,
+    ///     "CREATOR 1",
+    ///     0x02,
+    ///     VR::DS,
+    ///     PrimitiveValue::from("1.0"),
+    /// )?;
+    /// assert_eq!(
+    ///     ds.private_element(0x0009, "CREATOR 1", 0x02)?
+    ///         .value()
+    ///         .to_str()?,
+    ///     "1.0"
+    /// );
+    /// assert_eq!(
+    ///     ds.private_element(0x0009, "CREATOR 1", 0x02)?
+    ///         .header()
+    ///         .tag(),
+    ///     Tag(0x0009, 0x0102)
+    /// );
+    /// # Ok::<(), Box<dyn Error>>(())
+    /// ```
+    pub fn put_private_element(
+        &mut self,
+        group: GroupNumber,
+        creator: &str,
+        element: u8,
+        vr: VR,
+        value: PrimitiveValue,
+    ) -> Result<Option<InMemElement<D>>, PrivateElementError> {
+        ensure!(group % 2 == 1, InvalidGroupSnafu { group });
+        let private_creator = self.find_private_creator(group, creator);
+        if let Some(tag) = private_creator {
+            // Private creator already exists
+            let tag = Tag(group, tag.element() << 8 | (element as u16));
+            Ok(self.put_element(DataElement::new(tag, vr, value)))
+        } else {
+            // Find last reserved block of tags.
+            let range = Tag(group, 0)..Tag(group, 0xFF);
+            let last_entry = self.entries.range(range).next_back();
+            let next_available = match last_entry {
+                Some((tag, _)) => tag.element() + 1,
+                None => 0x01,
+            };
+            if next_available < 0xFF {
+                // Put private creator
+                let tag = Tag(group, next_available);
+                self.put_str_as(tag, VR::LO, creator);
 
-                    // if item.length == i and action is a constructive action, append new item
-                    obj =
-                        items
-                            .get(*item as usize)
-                            .with_context(|| crate::MissingSequenceSnafu {
-                                selector: selector.clone(),
-                                step_index: i as u32,
-                            })?;
-                }
+                // Put private element
+                let tag = Tag(group, next_available << 8 | (element as u16));
+                Ok(self.put_element(DataElement::new(tag, vr, value)))
+            } else {
+                NoSpaceSnafu { group }.fail()
             }
         }
-
-        unreachable!()
     }
 
-    // Get a mutable reference to a particular entry by AttributeSelector
-    //
-    // Should be private for the same reason as `self.get_mut`
-    fn entry_at_mut(
+    /// Insert a new element with the given value to the object,
+    /// replacing (and returning) any previous element of the same attribute.
+    ///
+    /// The value representation is fetched from the object's data dictionary,
+    /// and the value is converted to the canonical form of that VR:
+    /// numbers are formatted as text for _DS_ and _IS_,
+    /// dates and times are encoded as text for _DA_, _TM_ and _DT_,
+    /// and binary numeric VRs receive numbers of the respective type
+    /// (parsing them from text if necessary).
+    /// Use [`put_typed_as`](Self::put_typed_as)
+    /// to specify the VR explicitly,
+    /// such as for private attributes.
+    ///
+    /// An error is returned if the attribute is not in the dictionary,
+    /// or if the value is not compatible with the VR.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{dicom_value, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let mut obj = InMemDicomObject::new_empty();
+    /// obj.put_typed(tags::SLICE_THICKNESS, 1.25)?;
+    /// obj.put_typed(tags::ROWS, 512_u16)?;
+    /// obj.put_typed(tags::COLUMNS, "512")?;
+    ///
+    /// let thickness = obj.get(tags::SLICE_THICKNESS).unwrap();
+    /// assert_eq!(thickness.vr(), VR::DS);
+    /// assert_eq!(thickness.to_str()?, "1.25");
+    /// assert_eq!(obj.get(tags::COLUMNS).unwrap().value(), &dicom_value!(U16, 512).into());
+    ///
+    /// // not a number
+    /// assert!(obj.put_typed(tags::SLICE_THICKNESS, "thin").is_err());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn put_typed(
         &mut self,
-        selector: impl Into<AttributeSelector>,
-    ) -> Result<&mut InMemElement<D>, AtAccessError> {
-        let selector: AttributeSelector = selector.into();
+        tag: Tag,
+        value: impl Into<PrimitiveValue>,
+    ) -> Result<Option<InMemElement<D>>, AttributeError> {
+        let vr = self
+            .dict
+            .by_tag(tag)
+            .map(|entry| entry.vr().relaxed())
+            .context(UnknownAttributeSnafu { tag })?;
+        self.put_typed_as(tag, vr, value)
+    }
 
-        let mut obj = self;
-        for (i, step) in selector.iter().enumerate() {
-            match step {
-                // reached the leaf
-                AttributeSelectorStep::Tag(tag) => {
-                    return obj.get_mut(*tag).with_context(|| MissingLeafElementSnafu {
-                        selector: selector.clone(),
-                    })
-                }
-                // navigate further down
-                AttributeSelectorStep::Nested { tag, item } => {
-                    let e =
-                        obj.entries
-                            .get_mut(tag)
-                            .with_context(|| crate::MissingSequenceSnafu {
-                                selector: selector.clone(),
-                                step_index: i as u32,
-                            })?;
+    /// Insert a new element with the given value to the object,
+    /// as in [`put_typed`](Self::put_typed),
+    /// checking that its number of values conforms to
+    /// the value multiplicity of the attribute in the data dictionary.
+    ///
+    /// Text is counted by its backslash-separated values
+    /// (see [`PrimitiveValue::multiplicity_as`]).
+    /// Empty values are accepted,
+    /// as are all values of attributes
+    /// without a value multiplicity in the dictionary.
+    /// The object is left unchanged if the check fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::{AttributeError, InMemDicomObject};
+    /// let mut obj = InMemDicomObject::new_empty();
+    /// obj.put_typed_checked(tags::PIXEL_SPACING, [0.5, 0.5])?;
+    ///
+    /// let result = obj.put_typed_checked(tags::PIXEL_SPACING, [0.5, 0.5, 1.0]);
+    /// assert!(matches!(
+    ///     result,
+    ///     Err(AttributeError::InvalidMultiplicity { count: 3, .. }),
+    /// ));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn put_typed_checked(
+        &mut self,
+        tag: Tag,
+        value: impl Into<PrimitiveValue>,
+    ) -> Result<Option<InMemElement<D>>, AttributeError> {
+        let (vr, vm) = self
+            .dict
+            .by_tag(tag)
+            .map(|entry| (entry.vr().relaxed(), entry.vm()))
+            .context(UnknownAttributeSnafu { tag })?;
+        let value = canonical_value(vr, value.into()).context(ConvertValueSnafu { tag })?;
+        if let Some(vm) = vm {
+            let count = value.multiplicity_as(vr);
+            ensure!(
+                count == 0 || vm.contains(count),
+                InvalidMultiplicitySnafu { tag, vm, count }
+            );
+        }
+        Ok(self.put_element(DataElement::new(tag, vr, value)))
+    }
 
-                    // get items
-                    let items = e.items_mut().with_context(|| NotASequenceSnafu {
-                        selector: selector.clone(),
-                        step_index: i as u32,
-                    })?;
+    /// Insert a new element with the given value representation and value
+    /// to the object,
+    /// replacing (and returning) any previous element of the same attribute.
+    ///
+    /// The value is converted to the canonical form of the given VR
+    /// as in [`put_typed`](Self::put_typed),
+    /// but the data dictionary is not consulted.
+    pub fn put_typed_as(
+        &mut self,
+        tag: Tag,
+        vr: VR,
+        value: impl Into<PrimitiveValue>,
+    ) -> Result<Option<InMemElement<D>>, AttributeError> {
+        let value = canonical_value(vr, value.into()).context(ConvertValueSnafu { tag })?;
+        Ok(self.put_element(DataElement::new(tag, vr, value)))
+    }
 
-                    // if item.length == i and action is a constructive action, append new item
-                    obj = items.get_mut(*item as usize).with_context(|| {
-                        crate::MissingSequenceSnafu {
-                            selector: selector.clone(),
-                            step_index: i as u32,
-                        }
-                    })?;
-                }
-            }
-        }
+    /// Insert a new element with a single unsigned 16-bit integer
+    /// to the object,
+    /// with the value representation from the data dictionary.
+    ///
+    /// See [`put_typed`](Self::put_typed) for more details.
+    pub fn put_u16(
+        &mut self,
+        tag: Tag,
+        value: u16,
+    ) -> Result<Option<InMemElement<D>>, AttributeError> {
+        self.put_typed(tag, value)
+    }
 
-        unreachable!()
+    /// Insert a new element with a single 64-bit floating point number
+    /// to the object,
+    /// with the value representation from the data dictionary.
+    ///
+    /// See [`put_typed`](Self::put_typed) for more details.
+    pub fn put_f64(
+        &mut self,
+        tag: Tag,
+        value: f64,
+    ) -> Result<Option<InMemElement<D>>, AttributeError> {
+        self.put_typed(tag, value)
     }
 
-    /// Apply the given attribute operation on this object.
+    /// Insert a new element with multiple 64-bit floating point numbers
+    /// to the object,
+    /// with the value representation from the data dictionary.
     ///
-    /// For more complex updates, see [`update_value_at`].
+    /// See [`put_typed`](Self::put_typed) for more details.
+    pub fn put_f64s(
+        &mut self,
+        tag: Tag,
+        values: &[f64],
+    ) -> Result<Option<InMemElement<D>>, AttributeError> {
+        self.put_typed(tag, PrimitiveValue::F64(values.into()))
+    }
+
+    /// Insert a new element with a string value to the object,
+    /// with the value representation from the data dictionary.
     ///
-    /// See the [`dicom_core::ops`] module
-    /// for more information.
+    /// Attributes not in the dictionary are given the VR `LO`
+    /// if they are private creators, and `UN` otherwise.
+    /// The string is converted to the canonical form of the VR
+    /// as in [`put_typed`](Self::put_typed).
+    /// See [`put_str_as`](Self::put_str_as)
+    /// for inserting a string with a specific VR.
     ///
-    /// # Examples
+    /// # Example
     ///
-    /// ```rust
-    /// # use dicom_core::header::{DataElement, VR};
-    /// # use dicom_core::value::PrimitiveValue;
+    /// ```
+    /// # use dicom_core::{Tag, VR};
     /// # use dicom_dictionary_std::tags;
-    /// # use dicom_object::mem::*;
-    /// # use dicom_object::ops::ApplyResult;
-    /// use dicom_core::ops::{ApplyOp, AttributeAction, AttributeOp};
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// // given an in-memory DICOM object
-    /// let mut obj = InMemDicomObject::from_element_iter([
-    ///     DataElement::new(
-    ///         tags::PATIENT_NAME,
-    ///         VR::PN,
-    ///         PrimitiveValue::from("Rosling^Hans")
-    ///     ),
-    /// ]);
-    ///
-    /// // apply patient name change
-    /// obj.apply(AttributeOp::new(
-    ///   tags::PATIENT_NAME,
-    ///   AttributeAction::SetStr("Patient^Anonymous".into()),
-    /// ))?;
+    /// # use dicom_object::InMemDicomObject;
+    /// let mut obj = InMemDicomObject::new_empty();
+    /// obj.put_str(tags::PATIENT_NAME, "Doe^John")?;
+    /// obj.put_str(Tag(0x0009, 0x0010), "ACME 1.0")?;
     ///
-    /// assert_eq!(
-    ///     obj.element(tags::PATIENT_NAME)?.to_str()?,
-    ///     "Patient^Anonymous",
-    /// );
-    /// # Ok(())
-    /// # }
+    /// assert_eq!(obj.get(tags::PATIENT_NAME).unwrap().vr(), VR::PN);
+    /// assert_eq!(obj.get(Tag(0x0009, 0x0010)).unwrap().vr(), VR::LO);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
     /// ```
-    fn apply(&mut self, op: AttributeOp) -> ApplyResult {
-        let AttributeOp { selector, action } = op;
-        let dict = self.dict.clone();
-
-        let mut obj = self;
-        for (i, step) in selector.iter().enumerate() {
-            match step {
-                // reached the leaf
-                AttributeSelectorStep::Tag(tag) => return obj.apply_leaf(*tag, action),
-                // navigate further down
-                AttributeSelectorStep::Nested { tag, item } => {
-                    let e =
-                        obj.entries
-                            .get_mut(tag)
-                            .ok_or_else(|| ApplyError::MissingSequence {
-                                selector: selector.clone(),
-                                step_index: i as u32,
-                            })?;
+    pub fn put_str(
+        &mut self,
+        tag: Tag,
+        string: impl Into<String>,
+    ) -> Result<Option<InMemElement<D>>, AttributeError> {
+        let vr = self
+            .dict
+            .by_tag(tag)
+            .map(|entry| entry.vr().relaxed())
+            .unwrap_or_else(|| {
+                if tag.is_private() && (0x0010..=0x00FF).contains(&tag.element()) {
+                    VR::LO
+                } else {
+                    VR::UN
+                }
+            });
+        self.put_typed_as(tag, vr, string.into())
+    }
 
-                    // get items
-                    let items = e.items_mut().ok_or_else(|| ApplyError::NotASequence {
-                        selector: selector.clone(),
-                        step_index: i as u32,
-                    })?;
+    /// Insert a new element with the given value representation
+    /// and string value to the object,
+    /// replacing (and returning) any previous element of the same attribute.
+    ///
+    /// The string is kept as is and the data dictionary is not consulted.
+    pub fn put_str_as(
+        &mut self,
+        tag: Tag,
+        vr: VR,
+        string: impl Into<String>,
+    ) -> Option<InMemElement<D>> {
+        self.put_element(DataElement::new(tag, vr, string.into()))
+    }
 
-                    // if item.length == i and action is a constructive action, append new item
-                    obj = if items.len() == *item as usize && action.is_constructive() {
-                        items.push(InMemDicomObject::new_empty_with_dict(dict.clone()));
-                        items.last_mut().unwrap()
-                    } else {
-                        items.get_mut(*item as usize).ok_or_else(|| {
-                            ApplyError::MissingSequence {
-                                selector: selector.clone(),
-                                step_index: i as u32,
-                            }
-                        })?
-                    };
-                }
-            }
+    /// Remove a DICOM element by its tag,
+    /// reporting whether it was present.
+    pub fn remove_element(&mut self, tag: Tag) -> bool {
+        if self.entries.remove(&tag).is_some() {
+            self.len = Length::UNDEFINED;
+            true
+        } else {
+            false
         }
-        unreachable!()
     }
 
-    fn apply_leaf(&mut self, tag: Tag, action: AttributeAction) -> ApplyResult {
-        self.invalidate_if_charset_changed(tag);
-        match action {
-            AttributeAction::Remove => {
-                self.remove_element(tag);
-                Ok(())
-            }
-            AttributeAction::Empty => {
-                if let Some(e) = self.entries.get_mut(&tag) {
-                    let vr = e.vr();
-                    // replace element
-                    *e = DataElement::empty(tag, vr);
-                    self.len = Length::UNDEFINED;
-                }
-                Ok(())
-            }
-            AttributeAction::SetVr(new_vr) => {
-                if let Some(e) = self.entries.remove(&tag) {
-                    let (header, value) = e.into_parts();
-                    let e = DataElement::new(header.tag, new_vr, value);
-                    self.put(e);
-                } else {
-                    self.put(DataElement::empty(tag, new_vr));
-                }
-                Ok(())
-            }
-            AttributeAction::Set(new_value) => {
-                self.apply_change_value_impl(tag, new_value);
-                Ok(())
-            }
-            AttributeAction::SetStr(string) => {
-                let new_value = PrimitiveValue::from(&*string);
-                self.apply_change_value_impl(tag, new_value);
-                Ok(())
-            }
-            AttributeAction::SetIfMissing(new_value) => {
-                if self.get(tag).is_none() {
-                    self.apply_change_value_impl(tag, new_value);
-                }
-                Ok(())
-            }
-            AttributeAction::SetStrIfMissing(string) => {
-                if self.get(tag).is_none() {
-                    let new_value = PrimitiveValue::from(&*string);
-                    self.apply_change_value_impl(tag, new_value);
-                }
-                Ok(())
-            }
-            AttributeAction::Replace(new_value) => {
-                if self.get(tag).is_some() {
-                    self.apply_change_value_impl(tag, new_value);
-                }
-                Ok(())
-            }
-            AttributeAction::ReplaceStr(string) => {
-                if self.get(tag).is_some() {
-                    let new_value = PrimitiveValue::from(&*string);
-                    self.apply_change_value_impl(tag, new_value);
-                }
-                Ok(())
-            }
-            AttributeAction::PushStr(string) => self.apply_push_str_impl(tag, string),
-            AttributeAction::PushI32(integer) => self.apply_push_i32_impl(tag, integer),
-            AttributeAction::PushU32(integer) => self.apply_push_u32_impl(tag, integer),
-            AttributeAction::PushI16(integer) => self.apply_push_i16_impl(tag, integer),
-            AttributeAction::PushU16(integer) => self.apply_push_u16_impl(tag, integer),
-            AttributeAction::PushF32(number) => self.apply_push_f32_impl(tag, number),
-            AttributeAction::PushF64(number) => self.apply_push_f64_impl(tag, number),
-            AttributeAction::Truncate(limit) => {
-                self.update_value(tag, |value| value.truncate(limit));
-                Ok(())
+    /// Remove a DICOM element by its keyword,
+    /// reporting whether it was present.
+    pub fn remove_element_by_name(&mut self, name: &str) -> Result<bool, AccessByNameError> {
+        let tag = self.lookup_name(name)?;
+        Ok(self.entries.remove(&tag).is_some()).map(|removed| {
+            if removed {
+                self.len = Length::UNDEFINED;
             }
-            _ => UnsupportedActionSnafu.fail(),
-        }
+            removed
+        })
     }
 
-    fn apply_change_value_impl(&mut self, tag: Tag, new_value: PrimitiveValue) {
-        self.invalidate_if_charset_changed(tag);
+    /// Remove and return a particular DICOM element by its tag.
+    pub fn take_element(&mut self, tag: Tag) -> Result<InMemElement<D>> {
+        self.entries
+            .remove(&tag)
+            .map(|e| {
+                self.len = Length::UNDEFINED;
+                e
+            })
+            .context(NoSuchDataElementTagSnafu { tag })
+    }
 
-        if let Some(e) = self.entries.get_mut(&tag) {
-            let vr = e.vr();
-            // handle edge case: if VR is SQ and suggested value is empty,
-            // then create an empty data set sequence
-            let new_value = if vr == VR::SQ && new_value.is_empty() {
-                DataSetSequence::empty().into()
-            } else {
-                Value::from(new_value)
-            };
-            *e = DataElement::new(tag, vr, new_value);
+    /// Remove and return a particular DICOM element by its tag,
+    /// if it is present,
+    /// returns `None` otherwise.
+    pub fn take(&mut self, tag: Tag) -> Option<InMemElement<D>> {
+        self.entries.remove(&tag).map(|e| {
             self.len = Length::UNDEFINED;
-        } else {
-            // infer VR from tag
-            let vr = dicom_dictionary_std::StandardDataDictionary
-                .by_tag(tag)
-                .and_then(|entry| entry.vr().exact())
-                .unwrap_or(VR::UN);
-            // insert element
+            e
+        })
+    }
 
-            // handle edge case: if VR is SQ and suggested value is empty,
-            // then create an empty data set sequence
-            let new_value = if vr == VR::SQ && new_value.is_empty() {
-                DataSetSequence::empty().into()
+    /// Remove and return a particular DICOM element by its name.
+    pub fn take_element_by_name(
+        &mut self,
+        name: &str,
+    ) -> Result<InMemElement<D>, AccessByNameError> {
+        let tag = self.lookup_name(name)?;
+        self.entries
+            .remove(&tag)
+            .map(|e| {
+                self.len = Length::UNDEFINED;
+                e
+            })
+            .with_context(|| NoSuchDataElementAliasSnafu {
+                tag,
+                alias: name.to_string(),
+            })
+    }
+
+    /// Modify the object by
+    /// retaining only the DICOM data elements specified by the predicate.
+    ///
+    /// The elements are visited in ascending tag order,
+    /// and those for which `f(&element)` returns `false` are removed.
+    pub fn retain(&mut self, mut f: impl FnMut(&InMemElement<D>) -> bool) {
+        self.entries.retain(|_, elem| f(elem));
+        self.len = Length::UNDEFINED;
+    }
+
+    /// Modify the object by
+    /// retaining only the DICOM data elements specified by the predicate,
+    /// including those in nested sequence items.
+    ///
+    /// The elements are visited in document order
+    /// (see [`iter_deep`](Self::iter_deep)),
+    /// and those for which `f(&element)` returns `false` are removed.
+    /// Sequences which are removed are not descended into.
+    /// Recorded lengths are only reset for the data sets
+    /// and sequences which were effectively modified.
+    ///
+    /// # Example
+    ///
+    /// Remove all private attributes and all values over 1 MiB:
+    ///
+    /// ```no_run
+    /// # use dicom_core::header::Header;
+    /// # use dicom_object::InMemDicomObject;
+    /// # let mut obj: InMemDicomObject = unimplemented!();
+    /// obj.retain_deep(|e| {
+    ///     !e.tag().is_private()
+    ///         && e.value().primitive().map_or(true, |v| v.calculate_byte_len() <= 1 << 20)
+    /// });
+    /// ```
+    pub fn retain_deep(&mut self, mut f: impl FnMut(&InMemElement<D>) -> bool) {
+        self.retain_deep_impl(&mut f);
+    }
+
+    /// Recursive implementation of [`retain_deep`](Self::retain_deep),
+    /// returning whether this data set was modified.
+    fn retain_deep_impl(&mut self, f: &mut dyn FnMut(&InMemElement<D>) -> bool) -> bool {
+        let mut changed = false;
+        // evaluate the predicate in document order,
+        // descending into sequences right after they are retained
+        let tags: Vec<Tag> = self.entries.keys().copied().collect();
+        for tag in tags {
+            let elem = self.entries.get_mut(&tag).unwrap();
+            if !f(elem) {
+                self.entries.remove(&tag);
+                changed = true;
+                continue;
+            }
+            if elem.items().is_none() {
+                continue;
+            }
+
+            let vr = elem.vr();
+            let (header, mut value) =
+                std::mem::replace(elem, DataElement::empty(tag, vr)).into_parts();
+            let mut items_changed = false;
+            for item in value.items_mut().into_iter().flatten() {
+                items_changed |= item.retain_deep_impl(f);
+            }
+            let len = if items_changed {
+                changed = true;
+                Length::UNDEFINED
             } else {
-                Value::from(new_value)
+                header.len
             };
+            *elem = DataElement::new_with_len(tag, vr, len, value);
+        }
 
-            self.put(DataElement::new(tag, vr, new_value));
+        if changed {
+            self.len = Length::UNDEFINED;
         }
+        changed
     }
 
-    fn invalidate_if_charset_changed(&mut self, tag: Tag) {
-        if tag == tags::SPECIFIC_CHARACTER_SET {
-            self.charset_changed = true;
-        }
+    /// Create a copy of this object
+    /// containing only the DICOM data elements specified by the predicate,
+    /// including those in nested sequence items.
+    ///
+    /// This is the non-destructive counterpart of
+    /// [`retain_deep`](Self::retain_deep).
+    /// Elements which are not retained are never cloned.
+    pub fn filtered(&self, mut f: impl FnMut(&InMemElement<D>) -> bool) -> Self {
+        self.filtered_impl(&mut f)
     }
 
-    fn apply_push_str_impl(&mut self, tag: Tag, string: Cow<'static, str>) -> ApplyResult {
-        if let Some(e) = self.entries.remove(&tag) {
-            let (header, value) = e.into_parts();
-            match value {
-                Value::Primitive(mut v) => {
-                    self.invalidate_if_charset_changed(tag);
-                    // extend value
-                    v.extend_str([string]).context(ModifySnafu)?;
-                    // reinsert element
-                    self.put(DataElement::new(tag, header.vr, v));
-                    Ok(())
-                }
-
-                Value::PixelSequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::PixelSequence,
-                }
-                .fail(),
-                Value::Sequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::DataSetSequence,
-                }
-                .fail(),
+    fn filtered_impl(&self, f: &mut dyn FnMut(&InMemElement<D>) -> bool) -> Self {
+        let mut entries = BTreeMap::new();
+        for (tag, elem) in &self.entries {
+            if !f(elem) {
+                continue;
             }
-        } else {
-            // infer VR from tag
-            let vr = dicom_dictionary_std::StandardDataDictionary
-                .by_tag(tag)
-                .and_then(|entry| entry.vr().exact())
-                .unwrap_or(VR::UN);
-            // insert element
-            self.put(DataElement::new(tag, vr, PrimitiveValue::from(&*string)));
-            Ok(())
+            let elem = match elem.items() {
+                Some(items) => {
+                    let items: C<_> = items.iter().map(|item| item.filtered_impl(f)).collect();
+                    DataElement::new(*tag, VR::SQ, DataSetSequence::new(items, Length::UNDEFINED))
+                }
+                None => elem.clone(),
+            };
+            entries.insert(*tag, elem);
+        }
+        InMemDicomObject {
+            entries,
+            dict: self.dict.clone(),
+            len: Length::UNDEFINED,
+            charset_changed: self.charset_changed,
         }
     }
 
-    fn apply_push_i32_impl(&mut self, tag: Tag, integer: i32) -> ApplyResult {
-        if let Some(e) = self.entries.remove(&tag) {
-            let (header, value) = e.into_parts();
-            match value {
-                Value::Primitive(mut v) => {
-                    // extend value
-                    v.extend_i32([integer]).context(ModifySnafu)?;
-                    // reinsert element
-                    self.put(DataElement::new(tag, header.vr, v));
-                    Ok(())
-                }
+    /// Merge the attributes of another DICOM object into this one.
+    ///
+    /// Attributes which only exist in `other` are added to this object,
+    /// and attributes which only exist in this object are left untouched.
+    /// The given policy defines what happens to attributes
+    /// which exist in both objects with different values,
+    /// whether data set sequences are merged item by item,
+    /// and whether file meta group attributes are merged
+    /// (they are not by default).
+    ///
+    /// With [`ConflictPolicy::Error`](crate::merge::ConflictPolicy::Error),
+    /// this object is not modified if the merge fails.
+    ///
+    /// # Example
+    ///
+    /// Apply corrected patient demographics to an existing instance:
+    ///
+    /// ```
+    /// # use dicom_dictionary_std::tags;
+    /// use dicom_object::dicom_object;
+    /// use dicom_object::merge::MergePolicy;
+    ///
+    /// let mut obj = dicom_object! {
+    ///     PatientName: "Doe^Jon",
+    ///     PatientID: "ID0001",
+    ///     Modality: "CT",
+    /// };
+    /// let patch = dicom_object! {
+    ///     PatientName: "Doe^John",
+    ///     PatientBirthDate: "19800101",
+    /// };
+    /// obj.merge(patch, MergePolicy::default())?;
+    ///
+    /// assert_eq!(obj.string(tags::PATIENT_NAME)?, "Doe^John");
+    /// assert_eq!(obj.string(tags::PATIENT_BIRTH_DATE)?, "19800101");
+    /// assert_eq!(obj.string(tags::MODALITY)?, "CT");
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn merge(
+        &mut self,
+        other: InMemDicomObject<D>,
+        policy: MergePolicy,
+    ) -> Result<(), MergeError> {
+        crate::merge::merge(self, other, &policy)
+    }
 
-                Value::PixelSequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::PixelSequence,
-                }
-                .fail(),
-                Value::Sequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::DataSetSequence,
-                }
-                .fail(),
-            }
-        } else {
-            // infer VR from tag
-            let vr = dicom_dictionary_std::StandardDataDictionary
-                .by_tag(tag)
-                .and_then(|entry| entry.vr().exact())
-                .unwrap_or(VR::SL);
-            // insert element
-            self.put(DataElement::new(tag, vr, PrimitiveValue::from(integer)));
-            Ok(())
-        }
+    /// Obtain the functional groups of a frame in an enhanced multi-frame object,
+    /// by its index starting from 0.
+    ///
+    /// The functional group macros in the frame's item
+    /// of the _Per-Frame Functional Groups Sequence_
+    /// take precedence over those in the
+    /// _Shared Functional Groups Sequence_,
+    /// which may be absent.
+    /// See the [`functional_groups`](crate::functional_groups) module
+    /// for more information.
+    pub fn functional_groups(
+        &self,
+        frame_index: u32,
+    ) -> Result<FrameFunctionalGroups<'_, D>, FunctionalGroupsError> {
+        let frames = self
+            .items(tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE)
+            .context(MissingPerFrameFunctionalGroupsSnafu)?;
+        let per_frame = frames
+            .get(frame_index as usize)
+            .context(FrameOutOfRangeSnafu {
+                frame_index,
+                number_of_frames: frames.len() as u32,
+            })?;
+        let shared = self
+            .items(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE)
+            .and_then(|items| items.first());
+        Ok(FrameFunctionalGroups::new(frame_index, shared, per_frame))
     }
 
-    fn apply_push_u32_impl(&mut self, tag: Tag, integer: u32) -> ApplyResult {
-        if let Some(e) = self.entries.remove(&tag) {
-            let (header, value) = e.into_parts();
-            match value {
-                Value::Primitive(mut v) => {
-                    // extend value
-                    v.extend_u32([integer]).context(ModifySnafu)?;
-                    // reinsert element
-                    self.put(DataElement::new(tag, header.vr, v));
-                    Ok(())
-                }
+    /// Obtain the position, orientation, and spacing of the image
+    /// in the patient coordinate system.
+    ///
+    /// For enhanced multi-frame objects,
+    /// this is the geometry of the first frame
+    /// according to its functional groups;
+    /// use [`FrameFunctionalGroups::geometry`] for the other frames.
+    /// See the [`geometry`](crate::geometry) module
+    /// for more information.
+    pub fn geometry(&self) -> Result<ImageGeometry, GeometryError> {
+        ImageGeometry::from_object(self)
+    }
 
-                Value::PixelSequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::PixelSequence,
-                }
-                .fail(),
-                Value::Sequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::DataSetSequence,
-                }
-                .fail(),
-            }
-        } else {
-            // infer VR from tag
-            let vr = dicom_dictionary_std::StandardDataDictionary
-                .by_tag(tag)
-                .and_then(|entry| entry.vr().exact())
-                .unwrap_or(VR::UL);
-            // insert element
-            self.put(DataElement::new(tag, vr, PrimitiveValue::from(integer)));
-            Ok(())
-        }
+    /// Obtain the document encapsulated in this object,
+    /// such as the PDF document of an _Encapsulated PDF_ object.
+    ///
+    /// Fails if the object is not of an encapsulated document SOP class,
+    /// or if the document content does not match its MIME type.
+    /// See the [`encapsulated_document`](crate::encapsulated_document) module
+    /// for more information.
+    pub fn encapsulated_document(
+        &self,
+    ) -> Result<EncapsulatedDocument<'_>, EncapsulatedDocumentError> {
+        EncapsulatedDocument::from_object(self)
     }
 
-    fn apply_push_i16_impl(&mut self, tag: Tag, integer: i16) -> ApplyResult {
-        if let Some(e) = self.entries.remove(&tag) {
-            let (header, value) = e.into_parts();
-            match value {
-                Value::Primitive(mut v) => {
-                    // extend value
-                    v.extend_i16([integer]).context(ModifySnafu)?;
-                    // reinsert element
-                    self.put(DataElement::new(tag, header.vr, v));
-                    Ok(())
-                }
+    /// Visit all elements of this object,
+    /// including those in nested data set sequence items,
+    /// in ascending tag order within each data set.
+    ///
+    /// Returns [`ControlFlow::Break`]
+    /// if the visitor stopped the traversal early.
+    /// See the [`visit`](crate::visit) module for an example.
+    pub fn walk<V>(&self, visitor: &mut V) -> ControlFlow<()>
+    where
+        V: ?Sized + DataVisitor<D>,
+    {
+        crate::visit::walk(self, &mut Vec::new(), visitor)
+    }
 
-                Value::PixelSequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::PixelSequence,
-                }
-                .fail(),
-                Value::Sequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::DataSetSequence,
-                }
-                .fail(),
-            }
-        } else {
-            // infer VR from tag
-            let vr = dicom_dictionary_std::StandardDataDictionary
-                .by_tag(tag)
-                .and_then(|entry| entry.vr().exact())
-                .unwrap_or(VR::SS);
-            // insert element
-            self.put(DataElement::new(tag, vr, PrimitiveValue::from(integer)));
-            Ok(())
-        }
+    /// Visit all elements of this object,
+    /// including those in nested data set sequence items,
+    /// with the possibility of modifying them in place.
+    ///
+    /// The lengths of all data sets and sequences visited
+    /// are reset to undefined.
+    /// Returns [`ControlFlow::Break`]
+    /// if the visitor stopped the traversal early.
+    pub fn walk_mut<V>(&mut self, visitor: &mut V) -> ControlFlow<()>
+    where
+        V: ?Sized + DataVisitorMut<D>,
+    {
+        crate::visit::walk_mut(self, &mut Vec::new(), visitor)
     }
 
-    fn apply_push_u16_impl(&mut self, tag: Tag, integer: u16) -> ApplyResult {
-        if let Some(e) = self.entries.remove(&tag) {
-            let (header, value) = e.into_parts();
-            match value {
-                Value::Primitive(mut v) => {
-                    // extend value
-                    v.extend_u16([integer]).context(ModifySnafu)?;
-                    // reinsert element
-                    self.put(DataElement::new(tag, header.vr, v));
-                    Ok(())
-                }
+    /// Replace primitive values anywhere in this object,
+    /// including in nested data set sequence items.
+    ///
+    /// The function is called with the path to the data set,
+    /// the element header, and the current value of each primitive element,
+    /// and returns the new value, or `None` to keep the current one.
+    /// Element lengths are updated accordingly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, VR, PrimitiveValue, value::DataSetSequence};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let mut obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "1.2.3"),
+    ///     DataElement::new(
+    ///         tags::REFERENCED_IMAGE_SEQUENCE,
+    ///         VR::SQ,
+    ///         DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+    ///             DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.4"),
+    ///         ])]),
+    ///     ),
+    /// ]);
+    ///
+    /// // replace all UIDs
+    /// obj.map_values(|_path, header, value| {
+    ///     (header.vr() == VR::UI)
+    ///         .then(|| PrimitiveValue::from(format!("2.25.{}", value.to_str().replace('.', ""))))
+    /// });
+    ///
+    /// assert_eq!(obj.string(tags::SOP_INSTANCE_UID)?, "2.25.123");
+    /// # Ok::<_, dicom_object::AttributeError>(())
+    /// ```
+    pub fn map_values<F>(&mut self, f: F)
+    where
+        F: FnMut(&[PathSegment], &DataElementHeader, &PrimitiveValue) -> Option<PrimitiveValue>,
+    {
+        let _ = self.walk_mut(&mut MapValues(f));
+    }
 
-                Value::PixelSequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::PixelSequence,
-                }
-                .fail(),
-                Value::Sequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::DataSetSequence,
-                }
-                .fail(),
-            }
-        } else {
-            // infer VR from tag
-            let vr = dicom_dictionary_std::StandardDataDictionary
-                .by_tag(tag)
-                .and_then(|entry| entry.vr().exact())
-                .unwrap_or(VR::US);
-            // insert element
-            self.put(DataElement::new(tag, vr, PrimitiveValue::from(integer)));
-            Ok(())
-        }
+    /// Obtain an iterator over mutable references to the elements
+    /// of this data set.
+    ///
+    /// The length of the data set is reset to undefined.
+    pub(crate) fn elements_mut(&mut self) -> impl Iterator<Item = &mut InMemElement<D>> + '_ {
+        self.len = Length::UNDEFINED;
+        self.entries.values_mut()
     }
 
-    fn apply_push_f32_impl(&mut self, tag: Tag, number: f32) -> ApplyResult {
-        if let Some(e) = self.entries.remove(&tag) {
-            let (header, value) = e.into_parts();
-            match value {
-                Value::Primitive(mut v) => {
-                    // extend value
-                    v.extend_f32([number]).context(ModifySnafu)?;
-                    // reinsert element
-                    self.put(DataElement::new(tag, header.vr, v));
-                    Ok(())
-                }
+    /// Remove all group length elements `(gggg,0000)`
+    /// of groups which have no other elements,
+    /// including those in nested sequence items.
+    ///
+    /// This is typically used after removing elements
+    /// with [`retain_deep`](Self::retain_deep).
+    pub fn remove_orphan_group_lengths(&mut self) {
+        let orphans: Vec<Tag> = self
+            .tags()
+            .filter(|tag| self.is_orphan_group_length(*tag))
+            .collect();
+        let mut changed = !orphans.is_empty();
+        for tag in orphans {
+            self.entries.remove(&tag);
+        }
 
-                Value::PixelSequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::PixelSequence,
-                }
-                .fail(),
-                Value::Sequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::DataSetSequence,
+        for elem in self.entries.values_mut() {
+            // only touch sequences which need to change
+            let needs_change = elem
+                .items()
+                .into_iter()
+                .flatten()
+                .any(|item| item.has_orphan_group_lengths());
+            if needs_change {
+                for item in elem.items_mut().into_iter().flatten() {
+                    item.remove_orphan_group_lengths();
                 }
-                .fail(),
+                changed = true;
             }
-        } else {
-            // infer VR from tag
-            let vr = dicom_dictionary_std::StandardDataDictionary
-                .by_tag(tag)
-                .and_then(|entry| entry.vr().exact())
-                .unwrap_or(VR::FL);
-            // insert element
-            self.put(DataElement::new(tag, vr, PrimitiveValue::from(number)));
-            Ok(())
+        }
+
+        if changed {
+            self.len = Length::UNDEFINED;
         }
     }
 
-    fn apply_push_f64_impl(&mut self, tag: Tag, number: f64) -> ApplyResult {
-        if let Some(e) = self.entries.remove(&tag) {
-            let (header, value) = e.into_parts();
-            match value {
-                Value::Primitive(mut v) => {
-                    // extend value
-                    v.extend_f64([number]).context(ModifySnafu)?;
-                    // reinsert element
-                    self.put(DataElement::new(tag, header.vr, v));
-                    Ok(())
-                }
+    fn is_orphan_group_length(&self, tag: Tag) -> bool {
+        tag.is_group_length()
+            && self
+                .entries
+                .range(Tag(tag.group(), 0x0001)..=Tag(tag.group(), 0xFFFF))
+                .next()
+                .is_none()
+    }
 
-                Value::PixelSequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::PixelSequence,
-                }
-                .fail(),
-                Value::Sequence(..) => IncompatibleTypesSnafu {
-                    kind: ValueType::DataSetSequence,
-                }
-                .fail(),
-            }
-        } else {
-            // infer VR from tag
-            let vr = dicom_dictionary_std::StandardDataDictionary
-                .by_tag(tag)
-                .and_then(|entry| entry.vr().exact())
-                .unwrap_or(VR::FD);
-            // insert element
-            self.put(DataElement::new(tag, vr, PrimitiveValue::from(number)));
-            Ok(())
-        }
+    fn has_orphan_group_lengths(&self) -> bool {
+        self.entries.iter().any(|(tag, elem)| {
+            self.is_orphan_group_length(*tag)
+                || elem
+                    .items()
+                    .into_iter()
+                    .flatten()
+                    .any(|item| item.has_orphan_group_lengths())
+        })
     }
 
-    /// Write this object's data set into the given writer,
-    /// with the given encoder specifications,
-    /// without preamble, magic code, nor file meta group.
+    /// Obtain a temporary mutable reference to a DICOM value by tag,
+    /// so that mutations can be applied within.
     ///
-    /// The text encoding to use will be the default character set
-    /// until _Specific Character Set_ is found in the data set,
-    /// in which then that character set will be used.
+    /// If found, this method resets all related lengths recorded
+    /// and returns `true`.
+    /// Returns `false` otherwise.
     ///
-    /// Note: [`write_dataset_with_ts`] and [`write_dataset_with_ts_cs`]
-    /// may be easier to use.
+    /// # Example
     ///
-    /// [`write_dataset_with_ts`]: #method.write_dataset_with_ts
-    /// [`write_dataset_with_ts_cs`]: #method.write_dataset_with_ts_cs
-    pub fn write_dataset<W, E>(&self, to: W, encoder: E) -> Result<(), WriteError>
-    where
-        W: Write,
-        E: EncodeTo<W>,
-    {
-        // prepare data set writer
-        let mut dset_writer = DataSetWriter::new(to, encoder);
-        let required_options = IntoTokensOptions::new(self.charset_changed);
-        // write object
-        dset_writer
-            .write_sequence(self.into_tokens_with_options(required_options))
-            .context(PrintDataSetSnafu)?;
-
-        Ok(())
-    }
-
-    /// Write this object's data set into the given printer,
-    /// with the specified transfer syntax and character set,
-    /// without preamble, magic code, nor file meta group.
+    /// ```
+    /// # use dicom_core::{DataElement, VR, dicom_value};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let mut obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::LOSSY_IMAGE_COMPRESSION_RATIO, VR::DS, dicom_value!(Strs, ["25"])),
+    /// ]);
     ///
-    /// If the attribute _Specific Character Set_ is found in the data set,
-    /// the last parameter is overridden accordingly.
-    /// See also [`write_dataset_with_ts`](Self::write_dataset_with_ts).
-    pub fn write_dataset_with_ts_cs<W>(
-        &self,
-        to: W,
-        ts: &TransferSyntax,
-        cs: SpecificCharacterSet,
-    ) -> Result<(), WriteError>
-    where
-        W: Write,
-    {
-        // prepare data set writer
-        let mut dset_writer = DataSetWriter::with_ts_cs(to, ts, cs).context(CreatePrinterSnafu)?;
-        let required_options = IntoTokensOptions::new(self.charset_changed);
-
-        // write object
-        dset_writer
-            .write_sequence(self.into_tokens_with_options(required_options))
-            .context(PrintDataSetSnafu)?;
-
-        Ok(())
-    }
-
-    /// Write this object's data set into the given writer,
-    /// with the specified transfer syntax,
-    /// without preamble, magic code, nor file meta group.
+    /// // update lossy image compression ratio
+    /// obj.update_value(tags::LOSSY_IMAGE_COMPRESSION_RATIO, |e| {
+    ///     e.primitive_mut().unwrap().extend_str(["2.56"]);
+    /// });
     ///
-    /// The default character set is assumed
-    /// until the _Specific Character Set_ is found in the data set,
-    /// after which the text encoder is overridden accordingly.
-    pub fn write_dataset_with_ts<W>(&self, to: W, ts: &TransferSyntax) -> Result<(), WriteError>
-    where
-        W: Write,
-    {
-        self.write_dataset_with_ts_cs(to, ts, SpecificCharacterSet::default())
+    /// assert_eq!(
+    ///     obj.get(tags::LOSSY_IMAGE_COMPRESSION_RATIO).unwrap().value().to_str().unwrap(),
+    ///     "25\\2.56"
+    /// );
+    /// ```
+    pub fn update_value(
+        &mut self,
+        tag: Tag,
+        f: impl FnMut(&mut Value<InMemDicomObject<D>, InMemFragment>),
+    ) -> bool {
+        self.invalidate_if_charset_changed(tag);
+        if let Some(e) = self.entries.get_mut(&tag) {
+            e.update_value(f);
+            self.len = Length::UNDEFINED;
+            true
+        } else {
+            false
+        }
     }
 
-    /// Encapsulate this object to contain a file meta group
-    /// as described exactly by the given table.
+    /// Obtain a temporary mutable reference to a DICOM value by AttributeSelector,
+    /// so that mutations can be applied within.
     ///
-    /// **Note:** this method will not adjust the file meta group
-    /// to be semantically valid for the object.
-    /// Namely, the _Media Storage SOP Instance UID_
-    /// and _Media Storage SOP Class UID_
-    /// are not updated based on the receiving data set.
-    pub fn with_exact_meta(self, meta: FileMetaTable) -> FileDicomObject<Self> {
-        FileDicomObject { meta, obj: self }
-    }
-
-    /// Encapsulate this object to contain a file meta group,
-    /// created through the given file meta table builder.
+    /// If found, this method resets all related lengths recorded
+    /// and returns `true`.
+    /// Returns `false` otherwise.
     ///
-    /// A complete file meta group should provide
-    /// the _Transfer Syntax UID_,
-    /// the _Media Storage SOP Instance UID_,
-    /// and the _Media Storage SOP Class UID_.
-    /// The last two will be filled with the values of
-    /// _SOP Instance UID_ and _SOP Class UID_
-    /// if they are present in this object.
+    /// See the documentation of [`AttributeSelector`] for more information
+    /// on how to write attribute selectors.
+    ///
+    /// Note: Consider using [`apply`](ApplyOp::apply) when possible.
     ///
     /// # Example
     ///
-    /// ```no_run
-    /// # use dicom_core::{DataElement, VR};
+    /// ```
+    /// # use dicom_core::{DataElement, VR, dicom_value, value::DataSetSequence};
     /// # use dicom_dictionary_std::tags;
-    /// # use dicom_dictionary_std::uids;
-    /// use dicom_object::{InMemDicomObject, meta::FileMetaTableBuilder};
-    ///
-    /// let obj = InMemDicomObject::from_element_iter([
-    ///     DataElement::new(tags::SOP_CLASS_UID, VR::UI, uids::COMPUTED_RADIOGRAPHY_IMAGE_STORAGE),
-    ///     DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.60156688944589400766024286894543900794"),
-    ///     // ...
+    /// # use dicom_object::InMemDicomObject;
+    /// # use dicom_core::ops::{AttributeAction, AttributeOp, ApplyOp};
+    /// let mut dcm = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(
+    ///         tags::OTHER_PATIENT_I_DS_SEQUENCE,
+    ///         VR::SQ,
+    ///         DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+    ///             DataElement::new(
+    ///                 tags::PATIENT_ID,
+    ///                 VR::LO,
+    ///                 dicom_value!(Str, "1234")
+    ///             )])
+    ///         ])
+    ///     ),
     /// ]);
+    /// let selector = (
+    ///     tags::OTHER_PATIENT_I_DS_SEQUENCE,
+    ///     0,
+    ///     tags::PATIENT_ID
+    /// );
     ///
-    /// let obj = obj.with_meta(FileMetaTableBuilder::new()
-    ///     .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))?;
+    /// // update referenced SOP instance UID for deidentification potentially
+    /// dcm.update_value_at(*&selector, |e| {
+    ///     let mut v = e.primitive_mut().unwrap();
+    ///     *v = dicom_value!(Str, "abcd");
+    /// });
     ///
-    /// // can now save everything to a file
-    /// let meta = obj.write_to_file("out.dcm")?;
-    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// assert_eq!(
+    ///     dcm.entry_at(*&selector).unwrap().value().to_str().unwrap(),
+    ///     "abcd"
+    /// );
     /// ```
-    pub fn with_meta(
-        self,
-        mut meta: FileMetaTableBuilder,
-    ) -> Result<FileDicomObject<Self>, WithMetaError> {
-        if let Some(elem) = self.get(tags::SOP_INSTANCE_UID) {
-            meta = meta.media_storage_sop_instance_uid(
-                elem.value().to_str().context(PrepareMetaTableSnafu)?,
-            );
-        }
-        if let Some(elem) = self.get(tags::SOP_CLASS_UID) {
-            meta = meta
-                .media_storage_sop_class_uid(elem.value().to_str().context(PrepareMetaTableSnafu)?);
-        }
-        Ok(FileDicomObject {
-            meta: meta.build().context(BuildMetaTableSnafu)?,
-            obj: self,
-        })
-    }
-
-    /// Obtain an iterator over the elements of this object.
-    pub fn iter(&self) -> impl Iterator<Item = &InMemElement<D>> + '_ {
-        self.into_iter()
-    }
-
-    /// Obtain an iteartor over the tags of the object's elements.
-    pub fn tags(&self) -> impl Iterator<Item = Tag> + '_ {
-        self.entries.keys().copied()
+    pub fn update_value_at(
+        &mut self,
+        selector: impl Into<AttributeSelector>,
+        f: impl FnMut(&mut Value<InMemDicomObject<D>, InMemFragment>),
+    ) -> Result<(), AtAccessError> {
+        self.entry_at_mut(selector)
+            .map(|e| e.update_value(f))
+            .map(|_| {
+                self.len = Length::UNDEFINED;
+            })
     }
 
-    // private methods
-
-    /// Build an object by consuming a data set parser.
-    fn build_object<I: ?Sized>(
-        dataset: &mut I,
-        dict: D,
-        in_item: bool,
-        len: Length,
-        read_until: Option<Tag>,
-    ) -> Result<Self, ReadError>
-    where
-        I: Iterator<Item = ParserResult<DataToken>>,
-    {
-        let mut entries: BTreeMap<Tag, InMemElement<D>> = BTreeMap::new();
-        // perform a structured parsing of incoming tokens
-        while let Some(token) = dataset.next() {
-            let elem = match token.context(ReadTokenSnafu)? {
-                DataToken::PixelSequenceStart => {
-                    // stop reading if reached `read_until` tag
-                    if read_until
-                        .map(|t| t <= Tag(0x7fe0, 0x0010))
-                        .unwrap_or(false)
-                    {
-                        break;
-                    }
-                    let value = InMemDicomObject::build_encapsulated_data(&mut *dataset)?;
-                    DataElement::new(Tag(0x7fe0, 0x0010), VR::OB, value)
-                }
-                DataToken::ElementHeader(header) => {
-                    // stop reading if reached `read_until` tag
-                    if read_until.map(|t| t <= header.tag).unwrap_or(false) {
-                        break;
-                    }
+    /// Obtain the DICOM value by finding the element
+    /// that matches the given selector.
+    ///
+    /// Returns an error if the respective element or any of its parents
+    /// cannot be found.
+    ///
+    /// See the documentation of [`AttributeSelector`] for more information
+    /// on how to write attribute selectors.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_core::prelude::*;
+    /// # use dicom_core::ops::AttributeSelector;
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// # let obj: InMemDicomObject = unimplemented!();
+    /// let referenced_sop_instance_iod = obj.value_at(
+    ///     (
+    ///         tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+    ///         tags::REFERENCED_IMAGE_SEQUENCE,
+    ///         tags::REFERENCED_SOP_INSTANCE_UID,
+    ///     ))?
+    ///     .to_str()?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn value_at(
+        &self,
+        selector: impl Into<AttributeSelector>,
+    ) -> Result<&Value<InMemDicomObject<D>, InMemFragment>, AtAccessError> {
+        let selector: AttributeSelector = selector.into();
 
-                    // fetch respective value, place it in the entries
-                    let next_token = dataset.next().context(MissingElementValueSnafu)?;
-                    match next_token.context(ReadTokenSnafu)? {
-                        DataToken::PrimitiveValue(v) => InMemElement::new_with_len(
-                            header.tag,
-                            header.vr,
-                            header.len,
-                            Value::Primitive(v),
-                        ),
-                        token => {
-                            return UnexpectedTokenSnafu { token }.fail();
+        let mut obj = self;
+        for (i, step) in selector.iter().enumerate() {
+            match step {
+                // reached the leaf
+                AttributeSelectorStep::Tag(tag) => {
+                    return obj.get(*tag).map(|e| e.value()).with_context(|| {
+                        MissingLeafElementSnafu {
+                            selector: selector.clone(),
                         }
-                    }
+                    });
                 }
-                DataToken::SequenceStart { tag, len } => {
-                    // stop reading if reached `read_until` tag
-                    if read_until.map(|t| t <= tag).unwrap_or(false) {
-                        break;
-                    }
+                // navigate further down
+                AttributeSelectorStep::Nested { tag, item } => {
+                    let e = obj
+                        .entries
+                        .get(tag)
+                        .with_context(|| crate::MissingSequenceSnafu {
+                            selector: selector.clone(),
+                            step_index: i as u32,
+                        })?;
 
-                    // delegate sequence building to another function
-                    let items = Self::build_sequence(tag, len, &mut *dataset, &dict)?;
-                    DataElement::new_with_len(
-                        tag,
-                        VR::SQ,
-                        len,
-                        Value::Sequence(DataSetSequence::new(items, len)),
-                    )
-                }
-                DataToken::ItemEnd if in_item => {
-                    // end of item, leave now
-                    return Ok(InMemDicomObject {
-                        entries,
-                        dict,
-                        len,
-                        charset_changed: false,
-                    });
+                    // get items
+                    let items = e.items().with_context(|| NotASequenceSnafu {
+                        selector: selector.clone(),
+                        step_index: i as u32,
+                    })?;
+
+                    // if item.length == i and action is a constructive action, append new item
+                    obj =
+                        items
+                            .get(*item as usize)
+                            .with_context(|| crate::MissingSequenceSnafu {
+                                selector: selector.clone(),
+                                step_index: i as u32,
+                            })?;
                 }
-                token => return UnexpectedTokenSnafu { token }.fail(),
-            };
-            entries.insert(elem.tag(), elem);
+            }
         }
 
-        Ok(InMemDicomObject {
-            entries,
-            dict,
-            len,
-            charset_changed: false,
-        })
+        unreachable!()
     }
 
-    /// Build an encapsulated pixel data by collecting all fragments into an
-    /// in-memory DICOM value.
-    fn build_encapsulated_data<I>(
-        dataset: I,
-    ) -> Result<Value<InMemDicomObject<D>, InMemFragment>, ReadError>
-    where
-        I: Iterator<Item = ParserResult<DataToken>>,
-    {
-        // continue fetching tokens to retrieve:
-        // - the offset table
-        // - the various compressed fragments
-        //
-        // Note: as there is still no standard way to represent this in memory,
-        // this code will currently flatten all compressed fragments into a
-        // single vector.
-
-        let mut offset_table = None;
+    /// Change the 'specific_character_set' tag to ISO_IR 192, marking the dataset as UTF-8
+    pub fn convert_to_utf8(&mut self) {
+        self.put(DataElement::new(
+            tags::SPECIFIC_CHARACTER_SET,
+            VR::CS,
+            "ISO_IR 192",
+        ));
+    }
 
-        let mut fragments = C::new();
+    /// Get a DataElement by AttributeSelector
+    ///
+    /// If the element or other intermediate elements do not exist, the method will return an error.
+    ///
+    /// See the documentation of [`AttributeSelector`] for more information
+    /// on how to write attribute selectors.
+    ///
+    /// If you only need the value, use [`value_at`](Self::value_at).
+    pub fn entry_at(
+        &self,
+        selector: impl Into<AttributeSelector>,
+    ) -> Result<&InMemElement<D>, AtAccessError> {
+        let selector: AttributeSelector = selector.into();
 
-        for token in dataset {
-            match token.context(ReadTokenSnafu)? {
-                DataToken::OffsetTable(table) => {
-                    offset_table = Some(table);
-                }
-                DataToken::ItemValue(data) => {
-                    fragments.push(data);
-                }
-                DataToken::ItemEnd => {
-                    // at the end of the first item ensure the presence of
-                    // an empty offset_table here, so that the next items
-                    // are seen as compressed fragments
-                    if offset_table.is_none() {
-                        offset_table = Some(Vec::new())
-                    }
-                }
-                DataToken::ItemStart { len: _ } => { /* no-op */ }
-                DataToken::SequenceEnd => {
-                    // end of pixel data
-                    break;
+        let mut obj = self;
+        for (i, step) in selector.iter().enumerate() {
+            match step {
+                // reached the leaf
+                AttributeSelectorStep::Tag(tag) => {
+                    return obj.get(*tag).with_context(|| MissingLeafElementSnafu {
+                        selector: selector.clone(),
+                    })
                 }
-                // the following variants are unexpected
-                token @ DataToken::ElementHeader(_)
-                | token @ DataToken::PixelSequenceStart
-                | token @ DataToken::SequenceStart { .. }
-                | token @ DataToken::PrimitiveValue(_) => {
-                    return UnexpectedTokenSnafu { token }.fail();
+                // navigate further down
+                AttributeSelectorStep::Nested { tag, item } => {
+                    let e = obj
+                        .entries
+                        .get(tag)
+                        .with_context(|| crate::MissingSequenceSnafu {
+                            selector: selector.clone(),
+                            step_index: i as u32,
+                        })?;
+
+                    // get items
+                    let items = e.items().with_context(|| NotASequenceSnafu {
+                        selector: selector.clone(),
+                        step_index: i as u32,
+                    })?;
+
+                    // if item.length == i and action is a constructive action, append new item
+                    obj =
+                        items
+                            .get(*item as usize)
+                            .with_context(|| crate::MissingSequenceSnafu {
+                                selector: selector.clone(),
+                                step_index: i as u32,
+                            })?;
                 }
             }
         }
 
-        Ok(Value::PixelSequence(PixelFragmentSequence::new(
-            offset_table.unwrap_or_default(),
-            fragments,
-        )))
+        unreachable!()
     }
 
-    /// Build a DICOM sequence by consuming a data set parser.
-    fn build_sequence<I: ?Sized>(
-        _tag: Tag,
-        _len: Length,
-        dataset: &mut I,
-        dict: &D,
-    ) -> Result<C<InMemDicomObject<D>>, ReadError>
-    where
-        I: Iterator<Item = ParserResult<DataToken>>,
-    {
-        let mut items: C<_> = SmallVec::new();
-        while let Some(token) = dataset.next() {
-            match token.context(ReadTokenSnafu)? {
-                DataToken::ItemStart { len } => {
-                    items.push(Self::build_object(
-                        &mut *dataset,
-                        dict.clone(),
-                        true,
-                        len,
-                        None,
-                    )?);
-                }
-                DataToken::SequenceEnd => {
-                    return Ok(items);
+    // Get a mutable reference to a particular entry by AttributeSelector
+    //
+    // Should be private for the same reason as `self.get_mut`
+    fn entry_at_mut(
+        &mut self,
+        selector: impl Into<AttributeSelector>,
+    ) -> Result<&mut InMemElement<D>, AtAccessError> {
+        let selector: AttributeSelector = selector.into();
+
+        let mut obj = self;
+        for (i, step) in selector.iter().enumerate() {
+            match step {
+                // reached the leaf
+                AttributeSelectorStep::Tag(tag) => {
+                    return obj.get_mut(*tag).with_context(|| MissingLeafElementSnafu {
+                        selector: selector.clone(),
+                    })
                 }
-                token => return UnexpectedTokenSnafu { token }.fail(),
-            };
-        }
+                // navigate further down
+                AttributeSelectorStep::Nested { tag, item } => {
+                    let e =
+                        obj.entries
+                            .get_mut(tag)
+                            .with_context(|| crate::MissingSequenceSnafu {
+                                selector: selector.clone(),
+                                step_index: i as u32,
+                            })?;
 
-        // iterator fully consumed without a sequence delimiter
-        PrematureEndSnafu.fail()
+                    // get items
+                    let items = e.items_mut().with_context(|| NotASequenceSnafu {
+                        selector: selector.clone(),
+                        step_index: i as u32,
+                    })?;
+
+                    // if item.length == i and action is a constructive action, append new item
+                    obj = items.get_mut(*item as usize).with_context(|| {
+                        crate::MissingSequenceSnafu {
+                            selector: selector.clone(),
+                            step_index: i as u32,
+                        }
+                    })?;
+                }
+            }
+        }
+
+        unreachable!()
+    }
+
+    /// Get a data element by a textual attribute path.
+    ///
+    /// Each segment of the path is an attribute keyword or tag,
+    /// and all segments but the last one
+    /// may select a sequence item with a bracketed index
+    /// (the first item is selected if omitted).
+    /// See the [`path`](crate::path) module for more information.
+    ///
+    /// Unlike [`entry_at`](Self::entry_at),
+    /// the error returned identifies the path segment which failed.
+    /// The item wildcard `[*]` is not accepted here,
+    /// use [`elements_at_path`](Self::elements_at_path) instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_object::InMemDicomObject;
+    /// # let obj: InMemDicomObject = unimplemented!();
+    /// let position = obj.element_at_path(
+    ///     "PerFrameFunctionalGroupsSequence[3].PlanePositionSequence[0].ImagePositionPatient",
+    /// )?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn element_at_path(&self, path: &str) -> Result<&InMemElement<D>, AtPathError> {
+        let segments = path::parse_path(&self.dict, path)?;
+        if let Some((segment_index, segment)) = segments
+            .iter()
+            .enumerate()
+            .find(|(_, s)| s.item == Some(PathItem::All))
+        {
+            return UnexpectedWildcardSnafu {
+                segment_index,
+                segment: segment.text,
+            }
+            .fail();
+        }
+
+        let mut out = Vec::with_capacity(1);
+        path::collect_at_path(self, &segments, 0, false, &mut out)?;
+        debug_assert_eq!(out.len(), 1);
+        Ok(out[0])
+    }
+
+    /// Get all data elements matching a textual attribute path,
+    /// in item order.
+    ///
+    /// The path follows the same syntax as in
+    /// [`element_at_path`](Self::element_at_path),
+    /// but sequence segments may also use the item wildcard `[*]`
+    /// to visit all items of the sequence.
+    /// Items under a wildcard which do not contain the rest of the path
+    /// are skipped.
+    pub fn elements_at_path(
+        &self,
+        path: &str,
+    ) -> Result<impl Iterator<Item = &InMemElement<D>>, AtPathError> {
+        let segments = path::parse_path(&self.dict, path)?;
+        let mut out = Vec::new();
+        path::collect_at_path(self, &segments, 0, false, &mut out)?;
+        Ok(out.into_iter())
+    }
+
+    /// Apply the given attribute operation on this object.
+    ///
+    /// For more complex updates, see [`update_value_at`].
+    ///
+    /// See the [`dicom_core::ops`] module
+    /// for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dicom_core::header::{DataElement, VR};
+    /// # use dicom_core::value::PrimitiveValue;
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::mem::*;
+    /// # use dicom_object::ops::ApplyResult;
+    /// use dicom_core::ops::{ApplyOp, AttributeAction, AttributeOp};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // given an in-memory DICOM object
+    /// let mut obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(
+    ///         tags::PATIENT_NAME,
+    ///         VR::PN,
+    ///         PrimitiveValue::from("Rosling^Hans")
+    ///     ),
+    /// ]);
+    ///
+    /// // apply patient name change
+    /// obj.apply(AttributeOp::new(
+    ///   tags::PATIENT_NAME,
+    ///   AttributeAction::SetStr("Patient^Anonymous".into()),
+    /// ))?;
+    ///
+    /// assert_eq!(
+    ///     obj.element(tags::PATIENT_NAME)?.to_str()?,
+    ///     "Patient^Anonymous",
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn apply(&mut self, op: AttributeOp) -> ApplyResult {
+        let AttributeOp { selector, action } = op;
+        let dict = self.dict.clone();
+
+        let mut obj = self;
+        for (i, step) in selector.iter().enumerate() {
+            match step {
+                // reached the leaf
+                AttributeSelectorStep::Tag(tag) => return obj.apply_leaf(*tag, action),
+                // navigate further down
+                AttributeSelectorStep::Nested { tag, item } => {
+                    let e =
+                        obj.entries
+                            .get_mut(tag)
+                            .ok_or_else(|| ApplyError::MissingSequence {
+                                selector: selector.clone(),
+                                step_index: i as u32,
+                            })?;
+
+                    // get items
+                    let items = e.items_mut().ok_or_else(|| ApplyError::NotASequence {
+                        selector: selector.clone(),
+                        step_index: i as u32,
+                    })?;
+
+                    // if item.length == i and action is a constructive action, append new item
+                    obj = if items.len() == *item as usize && action.is_constructive() {
+                        items.push(InMemDicomObject::new_empty_with_dict(dict.clone()));
+                        items.last_mut().unwrap()
+                    } else {
+                        items.get_mut(*item as usize).ok_or_else(|| {
+                            ApplyError::MissingSequence {
+                                selector: selector.clone(),
+                                step_index: i as u32,
+                            }
+                        })?
+                    };
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    fn apply_leaf(&mut self, tag: Tag, action: AttributeAction) -> ApplyResult {
+        self.invalidate_if_charset_changed(tag);
+        match action {
+            AttributeAction::Remove => {
+                self.remove_element(tag);
+                Ok(())
+            }
+            AttributeAction::Empty => {
+                if let Some(e) = self.entries.get_mut(&tag) {
+                    let vr = e.vr();
+                    // replace element
+                    *e = DataElement::empty(tag, vr);
+                    self.len = Length::UNDEFINED;
+                }
+                Ok(())
+            }
+            AttributeAction::SetVr(new_vr) => {
+                if let Some(e) = self.entries.remove(&tag) {
+                    let (header, value) = e.into_parts();
+                    let e = DataElement::new(header.tag, new_vr, value);
+                    self.put(e);
+                } else {
+                    self.put(DataElement::empty(tag, new_vr));
+                }
+                Ok(())
+            }
+            AttributeAction::Set(new_value) => {
+                self.apply_change_value_impl(tag, new_value);
+                Ok(())
+            }
+            AttributeAction::SetStr(string) => {
+                let new_value = PrimitiveValue::from(&*string);
+                self.apply_change_value_impl(tag, new_value);
+                Ok(())
+            }
+            AttributeAction::SetIfMissing(new_value) => {
+                if self.get(tag).is_none() {
+                    self.apply_change_value_impl(tag, new_value);
+                }
+                Ok(())
+            }
+            AttributeAction::SetStrIfMissing(string) => {
+                if self.get(tag).is_none() {
+                    let new_value = PrimitiveValue::from(&*string);
+                    self.apply_change_value_impl(tag, new_value);
+                }
+                Ok(())
+            }
+            AttributeAction::Replace(new_value) => {
+                if self.get(tag).is_some() {
+                    self.apply_change_value_impl(tag, new_value);
+                }
+                Ok(())
+            }
+            AttributeAction::ReplaceStr(string) => {
+                if self.get(tag).is_some() {
+                    let new_value = PrimitiveValue::from(&*string);
+                    self.apply_change_value_impl(tag, new_value);
+                }
+                Ok(())
+            }
+            AttributeAction::PushStr(string) => self.apply_push_str_impl(tag, string),
+            AttributeAction::PushI32(integer) => self.apply_push_i32_impl(tag, integer),
+            AttributeAction::PushU32(integer) => self.apply_push_u32_impl(tag, integer),
+            AttributeAction::PushI16(integer) => self.apply_push_i16_impl(tag, integer),
+            AttributeAction::PushU16(integer) => self.apply_push_u16_impl(tag, integer),
+            AttributeAction::PushF32(number) => self.apply_push_f32_impl(tag, number),
+            AttributeAction::PushF64(number) => self.apply_push_f64_impl(tag, number),
+            AttributeAction::Truncate(limit) => {
+                self.update_value(tag, |value| value.truncate(limit));
+                Ok(())
+            }
+            _ => UnsupportedActionSnafu.fail(),
+        }
+    }
+
+    fn apply_change_value_impl(&mut self, tag: Tag, new_value: PrimitiveValue) {
+        self.invalidate_if_charset_changed(tag);
+
+        if let Some(e) = self.entries.get_mut(&tag) {
+            let vr = e.vr();
+            // handle edge case: if VR is SQ and suggested value is empty,
+            // then create an empty data set sequence
+            let new_value = if vr == VR::SQ && new_value.is_empty() {
+                DataSetSequence::empty().into()
+            } else {
+                Value::from(new_value)
+            };
+            *e = DataElement::new(tag, vr, new_value);
+            self.len = Length::UNDEFINED;
+        } else {
+            // infer VR from tag
+            let vr = dicom_dictionary_std::StandardDataDictionary
+                .by_tag(tag)
+                .and_then(|entry| entry.vr().exact())
+                .unwrap_or(VR::UN);
+            // insert element
+
+            // handle edge case: if VR is SQ and suggested value is empty,
+            // then create an empty data set sequence
+            let new_value = if vr == VR::SQ && new_value.is_empty() {
+                DataSetSequence::empty().into()
+            } else {
+                Value::from(new_value)
+            };
+
+            self.put(DataElement::new(tag, vr, new_value));
+        }
+    }
+
+    fn invalidate_if_charset_changed(&mut self, tag: Tag) {
+        if tag == tags::SPECIFIC_CHARACTER_SET {
+            self.charset_changed = true;
+        }
+    }
+
+    fn apply_push_str_impl(&mut self, tag: Tag, string: Cow<'static, str>) -> ApplyResult {
+        if let Some(e) = self.entries.remove(&tag) {
+            let (header, value) = e.into_parts();
+            match value {
+                Value::Primitive(mut v) => {
+                    self.invalidate_if_charset_changed(tag);
+                    // extend value
+                    v.extend_str([string]).context(ModifySnafu)?;
+                    // reinsert element
+                    self.put(DataElement::new(tag, header.vr, v));
+                    Ok(())
+                }
+
+                Value::PixelSequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::PixelSequence,
+                }
+                .fail(),
+                Value::Sequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::DataSetSequence,
+                }
+                .fail(),
+            }
+        } else {
+            // infer VR from tag
+            let vr = dicom_dictionary_std::StandardDataDictionary
+                .by_tag(tag)
+                .and_then(|entry| entry.vr().exact())
+                .unwrap_or(VR::UN);
+            // insert element
+            self.put(DataElement::new(tag, vr, PrimitiveValue::from(&*string)));
+            Ok(())
+        }
+    }
+
+    fn apply_push_i32_impl(&mut self, tag: Tag, integer: i32) -> ApplyResult {
+        if let Some(e) = self.entries.remove(&tag) {
+            let (header, value) = e.into_parts();
+            match value {
+                Value::Primitive(mut v) => {
+                    // extend value
+                    v.extend_i32([integer]).context(ModifySnafu)?;
+                    // reinsert element
+                    self.put(DataElement::new(tag, header.vr, v));
+                    Ok(())
+                }
+
+                Value::PixelSequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::PixelSequence,
+                }
+                .fail(),
+                Value::Sequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::DataSetSequence,
+                }
+                .fail(),
+            }
+        } else {
+            // infer VR from tag
+            let vr = dicom_dictionary_std::StandardDataDictionary
+                .by_tag(tag)
+                .and_then(|entry| entry.vr().exact())
+                .unwrap_or(VR::SL);
+            // insert element
+            self.put(DataElement::new(tag, vr, PrimitiveValue::from(integer)));
+            Ok(())
+        }
+    }
+
+    fn apply_push_u32_impl(&mut self, tag: Tag, integer: u32) -> ApplyResult {
+        if let Some(e) = self.entries.remove(&tag) {
+            let (header, value) = e.into_parts();
+            match value {
+                Value::Primitive(mut v) => {
+                    // extend value
+                    v.extend_u32([integer]).context(ModifySnafu)?;
+                    // reinsert element
+                    self.put(DataElement::new(tag, header.vr, v));
+                    Ok(())
+                }
+
+                Value::PixelSequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::PixelSequence,
+                }
+                .fail(),
+                Value::Sequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::DataSetSequence,
+                }
+                .fail(),
+            }
+        } else {
+            // infer VR from tag
+            let vr = dicom_dictionary_std::StandardDataDictionary
+                .by_tag(tag)
+                .and_then(|entry| entry.vr().exact())
+                .unwrap_or(VR::UL);
+            // insert element
+            self.put(DataElement::new(tag, vr, PrimitiveValue::from(integer)));
+            Ok(())
+        }
+    }
+
+    fn apply_push_i16_impl(&mut self, tag: Tag, integer: i16) -> ApplyResult {
+        if let Some(e) = self.entries.remove(&tag) {
+            let (header, value) = e.into_parts();
+            match value {
+                Value::Primitive(mut v) => {
+                    // extend value
+                    v.extend_i16([integer]).context(ModifySnafu)?;
+                    // reinsert element
+                    self.put(DataElement::new(tag, header.vr, v));
+                    Ok(())
+                }
+
+                Value::PixelSequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::PixelSequence,
+                }
+                .fail(),
+                Value::Sequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::DataSetSequence,
+                }
+                .fail(),
+            }
+        } else {
+            // infer VR from tag
+            let vr = dicom_dictionary_std::StandardDataDictionary
+                .by_tag(tag)
+                .and_then(|entry| entry.vr().exact())
+                .unwrap_or(VR::SS);
+            // insert element
+            self.put(DataElement::new(tag, vr, PrimitiveValue::from(integer)));
+            Ok(())
+        }
+    }
+
+    fn apply_push_u16_impl(&mut self, tag: Tag, integer: u16) -> ApplyResult {
+        if let Some(e) = self.entries.remove(&tag) {
+            let (header, value) = e.into_parts();
+            match value {
+                Value::Primitive(mut v) => {
+                    // extend value
+                    v.extend_u16([integer]).context(ModifySnafu)?;
+                    // reinsert element
+                    self.put(DataElement::new(tag, header.vr, v));
+                    Ok(())
+                }
+
+                Value::PixelSequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::PixelSequence,
+                }
+                .fail(),
+                Value::Sequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::DataSetSequence,
+                }
+                .fail(),
+            }
+        } else {
+            // infer VR from tag
+            let vr = dicom_dictionary_std::StandardDataDictionary
+                .by_tag(tag)
+                .and_then(|entry| entry.vr().exact())
+                .unwrap_or(VR::US);
+            // insert element
+            self.put(DataElement::new(tag, vr, PrimitiveValue::from(integer)));
+            Ok(())
+        }
+    }
+
+    fn apply_push_f32_impl(&mut self, tag: Tag, number: f32) -> ApplyResult {
+        if let Some(e) = self.entries.remove(&tag) {
+            let (header, value) = e.into_parts();
+            match value {
+                Value::Primitive(mut v) => {
+                    // extend value
+                    v.extend_f32([number]).context(ModifySnafu)?;
+                    // reinsert element
+                    self.put(DataElement::new(tag, header.vr, v));
+                    Ok(())
+                }
+
+                Value::PixelSequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::PixelSequence,
+                }
+                .fail(),
+                Value::Sequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::DataSetSequence,
+                }
+                .fail(),
+            }
+        } else {
+            // infer VR from tag
+            let vr = dicom_dictionary_std::StandardDataDictionary
+                .by_tag(tag)
+                .and_then(|entry| entry.vr().exact())
+                .unwrap_or(VR::FL);
+            // insert element
+            self.put(DataElement::new(tag, vr, PrimitiveValue::from(number)));
+            Ok(())
+        }
+    }
+
+    fn apply_push_f64_impl(&mut self, tag: Tag, number: f64) -> ApplyResult {
+        if let Some(e) = self.entries.remove(&tag) {
+            let (header, value) = e.into_parts();
+            match value {
+                Value::Primitive(mut v) => {
+                    // extend value
+                    v.extend_f64([number]).context(ModifySnafu)?;
+                    // reinsert element
+                    self.put(DataElement::new(tag, header.vr, v));
+                    Ok(())
+                }
+
+                Value::PixelSequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::PixelSequence,
+                }
+                .fail(),
+                Value::Sequence(..) => IncompatibleTypesSnafu {
+                    kind: ValueType::DataSetSequence,
+                }
+                .fail(),
+            }
+        } else {
+            // infer VR from tag
+            let vr = dicom_dictionary_std::StandardDataDictionary
+                .by_tag(tag)
+                .and_then(|entry| entry.vr().exact())
+                .unwrap_or(VR::FD);
+            // insert element
+            self.put(DataElement::new(tag, vr, PrimitiveValue::from(number)));
+            Ok(())
+        }
+    }
+
+    /// Write this object's data set into the given writer,
+    /// with the given encoder specifications,
+    /// without preamble, magic code, nor file meta group.
+    ///
+    /// The text encoding to use will be the default character set
+    /// until _Specific Character Set_ is found in the data set,
+    /// in which then that character set will be used.
+    ///
+    /// Note: [`write_dataset_with_ts`] and [`write_dataset_with_ts_cs`]
+    /// may be easier to use.
+    ///
+    /// [`write_dataset_with_ts`]: #method.write_dataset_with_ts
+    /// [`write_dataset_with_ts_cs`]: #method.write_dataset_with_ts_cs
+    pub fn write_dataset<W, E>(&self, to: W, encoder: E) -> Result<(), WriteError>
+    where
+        W: Write,
+        E: EncodeTo<W>,
+    {
+        // prepare data set writer
+        let mut dset_writer = DataSetWriter::new(to, encoder);
+        let required_options = IntoTokensOptions::new(self.charset_changed);
+        // write object
+        dset_writer
+            .write_sequence(self.into_tokens_with_options(required_options))
+            .context(PrintDataSetSnafu)?;
+
+        Ok(())
+    }
+
+    /// Write this object's data set into the given printer,
+    /// with the specified transfer syntax and character set,
+    /// without preamble, magic code, nor file meta group.
+    ///
+    /// If the attribute _Specific Character Set_ is found in the data set,
+    /// the last parameter is overridden accordingly.
+    /// See also [`write_dataset_with_ts`](Self::write_dataset_with_ts).
+    pub fn write_dataset_with_ts_cs<W>(
+        &self,
+        to: W,
+        ts: &TransferSyntax,
+        cs: SpecificCharacterSet,
+    ) -> Result<(), WriteError>
+    where
+        W: Write,
+    {
+        // prepare data set writer
+        let mut dset_writer = DataSetWriter::with_ts_cs(to, ts, cs).context(CreatePrinterSnafu)?;
+        let required_options = IntoTokensOptions::new(self.charset_changed);
+
+        // write object
+        dset_writer
+            .write_sequence(self.into_tokens_with_options(required_options))
+            .context(PrintDataSetSnafu)?;
+
+        Ok(())
+    }
+
+    /// Write this object's data set into the given writer,
+    /// with the specified transfer syntax,
+    /// without preamble, magic code, nor file meta group.
+    ///
+    /// The default character set is assumed
+    /// until the _Specific Character Set_ is found in the data set,
+    /// after which the text encoder is overridden accordingly.
+    pub fn write_dataset_with_ts<W>(&self, to: W, ts: &TransferSyntax) -> Result<(), WriteError>
+    where
+        W: Write,
+    {
+        self.write_dataset_with_ts_cs(to, ts, SpecificCharacterSet::default())
+    }
+
+    /// Encode this object's data set into a new byte vector,
+    /// in the given transfer syntax and according to the given options,
+    /// without preamble, magic code, nor file meta group.
+    ///
+    /// As in [`write_dataset_with_ts`](Self::write_dataset_with_ts),
+    /// the default character set is assumed
+    /// until _Specific Character Set_ is found in the data set.
+    /// The transfer syntax and batch size in the options are not used.
+    /// The bytes can be read back with [`from_slice`](InMemDicomObject::from_slice).
+    pub fn to_vec(
+        &self,
+        ts: &TransferSyntax,
+        options: &WriteOptions,
+    ) -> Result<Vec<u8>, WriteError> {
+        let mut out = Vec::with_capacity(self.encoded_len_estimate());
+        write_dataset_with_options(self, &mut out, ts, options, false)?;
+        Ok(out)
+    }
+
+    /// Estimate the number of bytes of this object's data set once encoded,
+    /// so that output buffers can be allocated once.
+    ///
+    /// This assumes that all headers take 12 bytes
+    /// and that sequences and items are delimited,
+    /// so the estimate is usually slightly above the actual length.
+    /// It may fall short for text which takes more bytes
+    /// in the character set of the data set than in UTF-8,
+    /// or for binary values written as decimal strings.
+    pub(crate) fn encoded_len_estimate(&self) -> usize {
+        self.entries
+            .values()
+            .map(|elem| {
+                12 + match elem.value() {
+                    Value::Primitive(value) => (value.calculate_byte_len() + 1) & !1,
+                    Value::Sequence(seq) => {
+                        8 + seq
+                            .items()
+                            .iter()
+                            .map(|item| 16 + item.encoded_len_estimate())
+                            .sum::<usize>()
+                    }
+                    Value::PixelSequence(seq) => {
+                        16 + seq.offset_table().len() * 4
+                            + seq
+                                .fragments()
+                                .iter()
+                                .map(|fragment| 8 + ((fragment.len() + 1) & !1))
+                                .sum::<usize>()
+                    }
+                }
+            })
+            .sum()
+    }
+
+    /// Encode this object's data set in a canonical form,
+    /// suitable for comparing the contents of DICOM objects
+    /// regardless of how they were stored.
+    ///
+    /// The canonical form is the data set in _Explicit VR Little Endian_,
+    /// with explicit lengths for all sequences and items,
+    /// no group length elements,
+    /// textual values stripped of their trailing padding
+    /// before being padded again,
+    /// and the VR of each element resolved through the data dictionary
+    /// regardless of the VR it was decoded with.
+    /// Elements unknown to the dictionary, such as private elements,
+    /// are encoded with the VR `UN`,
+    /// and _Pixel Data_ is encoded as `OB` or `OW`
+    /// depending on _Bits Allocated_.
+    /// The file meta group is never included,
+    /// so objects which only differ in transfer syntax or meta information
+    /// have the same canonical form.
+    pub fn to_canonical_bytes(&self) -> Result<Vec<u8>, WriteError> {
+        let ts = dicom_transfer_syntax_registry::entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        let options = IntoTokensOptions::new(self.charset_changed);
+        let tokens: Vec<_> = NormalizeVr::new(
+            StripGroupLengths::new(self.into_tokens_with_options(options)),
+            self.dict.clone(),
+        )
+        .map(trim_text_padding)
+        .collect();
+        let lengths =
+            measure_sequence_lengths(tokens.iter().cloned(), &ts).context(PrintDataSetSnafu)?;
+
+        let mut out = Vec::new();
+        let mut dset_writer = DataSetWriter::with_ts(&mut out, &ts).context(CreatePrinterSnafu)?;
+        dset_writer
+            .write_sequence(ReplaceSequenceLengths::new(tokens, lengths))
+            .context(PrintDataSetSnafu)?;
+        drop(dset_writer);
+        Ok(out)
+    }
+
+    /// Calculate the SHA-256 hash of this object's data set
+    /// in its [canonical form](Self::to_canonical_bytes).
+    ///
+    /// Objects which only differ in transfer syntax or meta information
+    /// have the same content hash.
+    #[cfg(feature = "content-hash")]
+    pub fn content_hash(&self) -> Result<[u8; 32], WriteError> {
+        use sha2::{Digest, Sha256};
+
+        Ok(Sha256::digest(self.to_canonical_bytes()?).into())
+    }
+
+    /// Encapsulate this object to contain a file meta group
+    /// as described exactly by the given table.
+    ///
+    /// **Note:** this method will not adjust the file meta group
+    /// to be semantically valid for the object.
+    /// Namely, the _Media Storage SOP Instance UID_
+    /// and _Media Storage SOP Class UID_
+    /// are not updated based on the receiving data set.
+    pub fn with_exact_meta(self, meta: FileMetaTable) -> FileDicomObject<Self> {
+        FileDicomObject { meta, obj: self }
+    }
+
+    /// Encapsulate this object to contain a file meta group,
+    /// created through the given file meta table builder.
+    ///
+    /// A complete file meta group should provide
+    /// the _Transfer Syntax UID_,
+    /// the _Media Storage SOP Instance UID_,
+    /// and the _Media Storage SOP Class UID_.
+    /// The last two will be filled with the values of
+    /// _SOP Instance UID_ and _SOP Class UID_
+    /// if they are present in this object.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_dictionary_std::uids;
+    /// use dicom_object::{InMemDicomObject, meta::FileMetaTableBuilder};
+    ///
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(tags::SOP_CLASS_UID, VR::UI, uids::COMPUTED_RADIOGRAPHY_IMAGE_STORAGE),
+    ///     DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.60156688944589400766024286894543900794"),
+    ///     // ...
+    /// ]);
+    ///
+    /// let obj = obj.with_meta(FileMetaTableBuilder::new()
+    ///     .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))?;
+    ///
+    /// // can now save everything to a file
+    /// let meta = obj.write_to_file("out.dcm")?;
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn with_meta(
+        self,
+        mut meta: FileMetaTableBuilder,
+    ) -> Result<FileDicomObject<Self>, WithMetaError> {
+        if let Some(elem) = self.get(tags::SOP_INSTANCE_UID) {
+            meta = meta.media_storage_sop_instance_uid(
+                elem.value().to_str().context(PrepareMetaTableSnafu)?,
+            );
+        }
+        if let Some(elem) = self.get(tags::SOP_CLASS_UID) {
+            meta = meta
+                .media_storage_sop_class_uid(elem.value().to_str().context(PrepareMetaTableSnafu)?);
+        }
+        Ok(FileDicomObject {
+            meta: meta.build().context(BuildMetaTableSnafu)?,
+            obj: self,
+        })
+    }
+
+    /// Obtain an iterator over the elements of this object.
+    pub fn iter(&self) -> impl Iterator<Item = &InMemElement<D>> + '_ {
+        self.into_iter()
+    }
+
+    /// Obtain an iteartor over the tags of the object's elements.
+    pub fn tags(&self) -> impl Iterator<Item = Tag> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Obtain an iterator over the elements of this object
+    /// and of all nested sequence items, in document order.
+    ///
+    /// Each element is yielded as a tuple `(depth, item_index, element)`,
+    /// where `depth` is the nesting level of the data set
+    /// containing the element (0 for this object)
+    /// and `item_index` is the index of that data set
+    /// within its parent sequence (always 0 for this object).
+    /// The items of a sequence are visited
+    /// right after the sequence element itself,
+    /// before the following sibling element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, VR, value::DataSetSequence};
+    /// # use dicom_core::header::Header;
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(
+    ///         tags::REFERENCED_IMAGE_SEQUENCE,
+    ///         VR::SQ,
+    ///         DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+    ///             DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.3"),
+    ///         ])]),
+    ///     ),
+    ///     DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+    /// ]);
+    ///
+    /// let visited: Vec<_> = obj
+    ///     .iter_deep()
+    ///     .map(|(depth, item_index, e)| (depth, item_index, e.tag()))
+    ///     .collect();
+    /// assert_eq!(
+    ///     visited,
+    ///     vec![
+    ///         (0, 0, tags::REFERENCED_IMAGE_SEQUENCE),
+    ///         (1, 0, tags::REFERENCED_SOP_INSTANCE_UID),
+    ///         (0, 0, tags::PATIENT_NAME),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter_deep(&self) -> DeepIter<'_, D> {
+        DeepIter {
+            stack: vec![DeepIterFrame::Elements {
+                depth: 0,
+                item_index: 0,
+                elements: self.entries.values(),
+            }],
+        }
+    }
+
+    // private methods
+
+    /// Build an object by consuming a data set parser,
+    /// resolving repeated data elements with the given policy.
+    fn build_object<I: ?Sized>(
+        dataset: &mut I,
+        dict: D,
+        in_item: bool,
+        len: Length,
+        read_until: Option<Tag>,
+        duplicates: DuplicatePolicy,
+    ) -> Result<Self, ReadError>
+    where
+        I: Iterator<Item = ParserResult<DataToken>>,
+    {
+        let mut entries = BTreeMap::new();
+        Self::read_entries(
+            dataset,
+            &dict,
+            in_item,
+            read_until,
+            duplicates,
+            &mut entries,
+        )?;
+        Ok(InMemDicomObject {
+            entries,
+            dict,
+            len,
+            charset_changed: false,
+        })
+    }
+
+    /// Build an object by consuming a data set parser,
+    /// keeping the data elements read before any failure.
+    fn build_partial_object<I>(
+        dataset: &mut I,
+        dict: D,
+        read_until: Option<Tag>,
+        duplicates: DuplicatePolicy,
+    ) -> (Self, Option<ReadError>)
+    where
+        I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
+    {
+        let mut entries = BTreeMap::new();
+        let result =
+            Self::read_entries(dataset, &dict, false, read_until, duplicates, &mut entries);
+        let obj = InMemDicomObject {
+            entries,
+            dict,
+            len: Length::UNDEFINED,
+            charset_changed: false,
+        };
+        (obj, result.err())
+    }
+
+    /// Read data elements from a data set parser into `entries`,
+    /// until the end of the data set or of the current item.
+    ///
+    /// On failure, `entries` retains the data elements read so far,
+    /// including any sequence cut short.
+    fn read_entries<I>(
+        dataset: &mut I,
+        dict: &D,
+        in_item: bool,
+        read_until: Option<Tag>,
+        duplicates: DuplicatePolicy,
+        entries: &mut BTreeMap<Tag, InMemElement<D>>,
+    ) -> Result<(), ReadError>
+    where
+        I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
+    {
+        // perform a structured parsing of incoming tokens
+        while let Some(token) = dataset.next() {
+            let elem = match token.context(ReadTokenSnafu)? {
+                DataToken::PixelSequenceStart => {
+                    // stop reading if reached `read_until` tag
+                    if read_until
+                        .map(|t| t <= Tag(0x7fe0, 0x0010))
+                        .unwrap_or(false)
+                    {
+                        break;
+                    }
+                    let value = InMemDicomObject::build_encapsulated_data(&mut *dataset)?;
+                    DataElement::new(Tag(0x7fe0, 0x0010), VR::OB, value)
+                }
+                DataToken::ElementHeader(header) => {
+                    // stop reading if reached `read_until` tag
+                    if read_until.map(|t| t <= header.tag).unwrap_or(false) {
+                        break;
+                    }
+
+                    // fetch respective value, place it in the entries
+                    let next_token = dataset.next().context(MissingElementValueSnafu)?;
+                    match next_token.context(ReadTokenSnafu)? {
+                        DataToken::PrimitiveValue(v) => InMemElement::new_with_len(
+                            header.tag,
+                            header.vr,
+                            header.len,
+                            Value::Primitive(v),
+                        ),
+                        token => {
+                            return UnexpectedTokenSnafu { token }.fail();
+                        }
+                    }
+                }
+                DataToken::SequenceStart { tag, len } => {
+                    // stop reading if reached `read_until` tag
+                    if read_until.map(|t| t <= tag).unwrap_or(false) {
+                        break;
+                    }
+
+                    // delegate sequence building to another function
+                    let mut items: C<_> = SmallVec::new();
+                    let result = Self::read_items(&mut *dataset, dict, duplicates, &mut items);
+                    let elem = DataElement::new_with_len(
+                        tag,
+                        VR::SQ,
+                        len,
+                        Value::Sequence(DataSetSequence::new(items, len)),
+                    );
+                    if let Err(e) = result {
+                        // keep what was read of the sequence
+                        entries.insert(tag, elem);
+                        return Err(e);
+                    }
+                    elem
+                }
+                DataToken::ItemEnd if in_item => {
+                    // end of item, leave now
+                    return Ok(());
+                }
+                token => return UnexpectedTokenSnafu { token }.fail(),
+            };
+            match entries.entry(elem.tag()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(elem);
+                }
+                Entry::Occupied(mut entry) => match duplicates {
+                    DuplicatePolicy::KeepLast => {
+                        entry.insert(elem);
+                    }
+                    DuplicatePolicy::KeepFirst => {}
+                    DuplicatePolicy::Fail => {
+                        return DuplicateElementSnafu { tag: elem.tag() }.fail();
+                    }
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build an encapsulated pixel data by collecting all fragments into an
+    /// in-memory DICOM value.
+    fn build_encapsulated_data<I>(
+        dataset: I,
+    ) -> Result<Value<InMemDicomObject<D>, InMemFragment>, ReadError>
+    where
+        I: Iterator<Item = ParserResult<DataToken>>,
+    {
+        // continue fetching tokens to retrieve:
+        // - the offset table
+        // - the various compressed fragments
+        //
+        // Note: as there is still no standard way to represent this in memory,
+        // this code will currently flatten all compressed fragments into a
+        // single vector.
+
+        let mut offset_table = None;
+
+        let mut fragments = C::new();
+
+        for token in dataset {
+            match token.context(ReadTokenSnafu)? {
+                DataToken::OffsetTable(table) => {
+                    offset_table = Some(table);
+                }
+                DataToken::ItemValue(data) => {
+                    fragments.push(data);
+                }
+                DataToken::ItemEnd => {
+                    // at the end of the first item ensure the presence of
+                    // an empty offset_table here, so that the next items
+                    // are seen as compressed fragments
+                    if offset_table.is_none() {
+                        offset_table = Some(Vec::new())
+                    }
+                }
+                DataToken::ItemStart { len: _ } => { /* no-op */ }
+                DataToken::SequenceEnd => {
+                    // end of pixel data
+                    break;
+                }
+                // the following variants are unexpected
+                token @ DataToken::ElementHeader(_)
+                | token @ DataToken::PixelSequenceStart
+                | token @ DataToken::SequenceStart { .. }
+                | token @ DataToken::PrimitiveValue(_)
+                | token @ DataToken::ValueChunk(_) => {
+                    return UnexpectedTokenSnafu { token }.fail();
+                }
+            }
+        }
+
+        Ok(Value::PixelSequence(PixelFragmentSequence::new(
+            offset_table.unwrap_or_default(),
+            fragments,
+        )))
+    }
+
+    /// Read the items of a DICOM sequence from a data set parser into `items`,
+    /// until the end of the sequence.
+    ///
+    /// On failure, `items` retains the items read so far,
+    /// including any item cut short.
+    fn read_items<I>(
+        dataset: &mut I,
+        dict: &D,
+        duplicates: DuplicatePolicy,
+        items: &mut C<InMemDicomObject<D>>,
+    ) -> Result<(), ReadError>
+    where
+        I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
+    {
+        while let Some(token) = dataset.next() {
+            match token.context(ReadTokenSnafu)? {
+                DataToken::ItemStart { len } => {
+                    let mut entries = BTreeMap::new();
+                    let result = Self::read_entries(
+                        &mut *dataset,
+                        dict,
+                        true,
+                        None,
+                        duplicates,
+                        &mut entries,
+                    );
+                    items.push(InMemDicomObject {
+                        entries,
+                        dict: dict.clone(),
+                        len,
+                        charset_changed: false,
+                    });
+                    result?;
+                }
+                DataToken::SequenceEnd => {
+                    return Ok(());
+                }
+                token => return UnexpectedTokenSnafu { token }.fail(),
+            };
+        }
+
+        // iterator fully consumed without a sequence delimiter
+        PrematureEndSnafu.fail()
+    }
+
+    fn lookup_name(&self, name: &str) -> Result<Tag, AccessByNameError> {
+        self.dict
+            .by_name(name)
+            .context(NoSuchAttributeNameSnafu { name })
+            .map(|e| e.tag())
+    }
+}
+
+impl<D> ApplyOp for InMemDicomObject<D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    type Err = ApplyError;
+
+    #[inline]
+    fn apply(&mut self, op: AttributeOp) -> ApplyResult {
+        self.apply(op)
+    }
+}
+
+impl<'a, D> IntoIterator for &'a InMemDicomObject<D> {
+    type Item = &'a InMemElement<D>;
+    type IntoIter = ::std::collections::btree_map::Values<'a, Tag, InMemElement<D>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.values()
+    }
+}
+
+impl<D> IntoIterator for InMemDicomObject<D> {
+    type Item = InMemElement<D>;
+    type IntoIter = Iter<D>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter {
+            inner: self.entries.into_iter(),
+        }
+    }
+}
+
+/// Base iterator type for an in-memory DICOM object.
+#[derive(Debug)]
+pub struct Iter<D> {
+    inner: ::std::collections::btree_map::IntoIter<Tag, InMemElement<D>>,
+}
+
+impl<D> Iterator for Iter<D> {
+    type Item = InMemElement<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|x| x.1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.inner.count()
+    }
+}
+
+/// Depth-first iterator over the elements of an in-memory DICOM object
+/// and of its nested sequence items.
+///
+/// See [`InMemDicomObject::iter_deep`] for more details.
+#[derive(Debug)]
+pub struct DeepIter<'a, D> {
+    stack: Vec<DeepIterFrame<'a, D>>,
+}
+
+#[derive(Debug)]
+enum DeepIterFrame<'a, D> {
+    /// iterating over the elements of a data set
+    Elements {
+        depth: usize,
+        item_index: usize,
+        elements: ::std::collections::btree_map::Values<'a, Tag, InMemElement<D>>,
+    },
+    /// iterating over the items of a sequence
+    Items {
+        depth: usize,
+        items: ::std::iter::Enumerate<::std::slice::Iter<'a, InMemDicomObject<D>>>,
+    },
+}
+
+impl<'a, D> Iterator for DeepIter<'a, D> {
+    type Item = (usize, usize, &'a InMemElement<D>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()? {
+                DeepIterFrame::Elements {
+                    depth,
+                    item_index,
+                    elements,
+                } => {
+                    let (depth, item_index) = (*depth, *item_index);
+                    match elements.next() {
+                        Some(elem) => {
+                            if let Some(items) = elem.items() {
+                                self.stack.push(DeepIterFrame::Items {
+                                    depth: depth + 1,
+                                    items: items.iter().enumerate(),
+                                });
+                            }
+                            return Some((depth, item_index, elem));
+                        }
+                        None => {
+                            self.stack.pop();
+                        }
+                    }
+                }
+                DeepIterFrame::Items { depth, items } => {
+                    let depth = *depth;
+                    match items.next() {
+                        Some((item_index, item)) => {
+                            self.stack.push(DeepIterFrame::Elements {
+                                depth,
+                                item_index,
+                                elements: item.entries.values(),
+                            });
+                        }
+                        None => {
+                            self.stack.pop();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A view over an in-memory DICOM object
+/// in which single-value getters fail
+/// when the attribute has more than one value.
+///
+/// See [`InMemDicomObject::strict`] for more details.
+#[derive(Debug)]
+pub struct Strict<'a, D> {
+    obj: &'a InMemDicomObject<D>,
+}
+
+impl<'a, D> Strict<'a, D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Retrieve the value of an attribute as a single string.
+    ///
+    /// See [`InMemDicomObject::string`].
+    pub fn string(&self, tag: Tag) -> Result<String, AttributeError> {
+        self.obj.single_value(tag, "string", true, first_str)
+    }
+
+    /// Retrieve the value of an attribute as a single unsigned 16-bit integer.
+    ///
+    /// See [`InMemDicomObject::u16`].
+    pub fn u16(&self, tag: Tag) -> Result<u16, AttributeError> {
+        self.obj.single_value(tag, "u16", true, |v| v.to_int())
+    }
+
+    /// Retrieve the value of an attribute as a single signed 32-bit integer.
+    ///
+    /// See [`InMemDicomObject::i32`].
+    pub fn i32(&self, tag: Tag) -> Result<i32, AttributeError> {
+        self.obj.single_value(tag, "i32", true, |v| v.to_int())
+    }
+
+    /// Retrieve the value of an attribute
+    /// as a single 64-bit floating point number.
+    ///
+    /// See [`InMemDicomObject::f64`].
+    pub fn f64(&self, tag: Tag) -> Result<f64, AttributeError> {
+        self.obj
+            .single_value(tag, "f64", true, PrimitiveValue::to_float64)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM tag.
+    ///
+    /// See [`InMemDicomObject::tag_value`].
+    pub fn tag_value(&self, tag: Tag) -> Result<Tag, AttributeError> {
+        self.obj.single_value(tag, "tag", true, first_tag)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM date.
+    ///
+    /// See [`InMemDicomObject::date`].
+    pub fn date(&self, tag: Tag) -> Result<DicomDate, AttributeError> {
+        self.obj
+            .single_value(tag, "date", true, PrimitiveValue::to_date)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM time.
+    ///
+    /// See [`InMemDicomObject::time`].
+    pub fn time(&self, tag: Tag) -> Result<DicomTime, AttributeError> {
+        self.obj
+            .single_value(tag, "time", true, PrimitiveValue::to_time)
+    }
+
+    /// Retrieve the value of an attribute as a single DICOM date-time.
+    ///
+    /// See [`InMemDicomObject::datetime`].
+    pub fn datetime(&self, tag: Tag) -> Result<DicomDateTime, AttributeError> {
+        self.obj
+            .single_value(tag, "datetime", true, PrimitiveValue::to_datetime)
+    }
+}
+
+impl<D> Extend<InMemElement<D>> for InMemDicomObject<D> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = InMemElement<D>>,
+    {
+        self.len = Length::UNDEFINED;
+        self.entries.extend(iter.into_iter().map(|e| (e.tag(), e)))
+    }
+}
+
+fn even_len(l: u32) -> u32 {
+    (l + 1) & !1
+}
+
+/// Obtain the first string of a primitive value.
+pub(crate) fn first_str(value: &PrimitiveValue) -> Result<String, ConvertValueError> {
+    Ok(value.to_multi_str().first().cloned().unwrap_or_default())
+}
+
+/// Obtain the first tag of a primitive value.
+pub(crate) fn first_tag(value: &PrimitiveValue) -> Result<Tag, ConvertValueError> {
+    value.tag().map_err(|e| ConvertValueError {
+        requested: "tag",
+        original: e.got,
+        cause: None,
+    })
+}
+
+/// Obtain all tags of a primitive value.
+pub(crate) fn all_tags(value: &PrimitiveValue) -> Result<Vec<Tag>, ConvertValueError> {
+    value
+        .tags()
+        .map(|tags| tags.to_vec())
+        .map_err(|e| ConvertValueError {
+            requested: "tags",
+            original: e.got,
+            cause: None,
+        })
+}
+
+/// Split textual values delimited by backslashes into multiple strings,
+/// unless the value representation only admits a single value.
+pub(crate) fn split_values(vr: VR, value: &PrimitiveValue) -> Cow<'_, PrimitiveValue> {
+    let strings = match value {
+        PrimitiveValue::Str(s) => std::slice::from_ref(s),
+        PrimitiveValue::Strs(s) => &s[..],
+        _ => return Cow::Borrowed(value),
+    };
+    if matches!(vr, VR::LT | VR::ST | VR::UT | VR::UR) || !strings.iter().any(|s| s.contains('\\'))
+    {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(PrimitiveValue::Strs(
+        strings
+            .iter()
+            .flat_map(|s| s.split('\\'))
+            .map(String::from)
+            .collect(),
+    ))
+}
+
+/// Convert a primitive value to the canonical form
+/// of the given value representation.
+fn canonical_value(vr: VR, value: PrimitiveValue) -> Result<PrimitiveValue, ConvertValueError> {
+    fn strs(values: impl IntoIterator<Item = String>) -> PrimitiveValue {
+        PrimitiveValue::new_strs(values)
+    }
+
+    if value.multiplicity() == 0 {
+        return Ok(PrimitiveValue::Empty);
+    }
+    let value = split_values(vr, &value);
+
+    Ok(match vr {
+        VR::DS => strs(
+            value
+                .to_multi_float64()?
+                .into_iter()
+                .map(|v| format_ds(v).ok_or_else(|| incompatible_value(vr, &value)))
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        VR::IS => strs(
+            value
+                .to_multi_int::<i64>()?
+                .into_iter()
+                .map(|v| v.to_string()),
+        ),
+        VR::DA => strs(value.to_multi_date()?.iter().map(DicomDate::to_encoded)),
+        VR::TM => strs(value.to_multi_time()?.iter().map(DicomTime::to_encoded)),
+        VR::DT => strs(
+            value
+                .to_multi_datetime()?
+                .iter()
+                .map(DicomDateTime::to_encoded),
+        ),
+        VR::OB => PrimitiveValue::U8(value.to_multi_int::<u8>()?.into()),
+        VR::US | VR::OW => PrimitiveValue::U16(value.to_multi_int::<u16>()?.into()),
+        VR::SS => PrimitiveValue::I16(value.to_multi_int::<i16>()?.into()),
+        VR::UL | VR::OL => PrimitiveValue::U32(value.to_multi_int::<u32>()?.into()),
+        VR::SL => PrimitiveValue::I32(value.to_multi_int::<i32>()?.into()),
+        VR::UV | VR::OV => PrimitiveValue::U64(value.to_multi_int::<u64>()?.into()),
+        VR::SV => PrimitiveValue::I64(value.to_multi_int::<i64>()?.into()),
+        VR::FL | VR::OF => PrimitiveValue::F32(value.to_multi_float32()?.into()),
+        VR::FD | VR::OD => PrimitiveValue::F64(value.to_multi_float64()?.into()),
+        VR::UN => value.into_owned(),
+        VR::AT => match value.into_owned() {
+            value @ PrimitiveValue::Tags(_) => value,
+            value => return Err(incompatible_value(vr, &value)),
+        },
+        VR::SQ => return Err(incompatible_value(vr, &value)),
+        // other textual VRs
+        _ => match value.into_owned() {
+            value @ (PrimitiveValue::Str(_) | PrimitiveValue::Strs(_)) => value,
+            value => return Err(incompatible_value(vr, &value)),
+        },
+    })
+}
+
+fn incompatible_value(vr: VR, value: &PrimitiveValue) -> ConvertValueError {
+    ConvertValueError {
+        requested: vr.to_string(),
+        original: value.value_type(),
+        cause: None,
+    }
+}
+
+/// Format a number as a decimal string (DS),
+/// which cannot be longer than 16 characters.
+///
+/// Numbers which do not fit are rounded
+/// to the closest representation in fixed or exponent notation.
+/// Returns `None` if the number is not finite,
+/// as infinities and NaN cannot be represented in a DS.
+fn format_ds(value: f64) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+    let s = value.to_string();
+    if s.len() <= 16 {
+        return Some(s);
+    }
+    let error = |s: &String| (s.parse::<f64>().unwrap_or(f64::INFINITY) - value).abs();
+    (0..16)
+        .flat_map(|precision| {
+            let fixed = format!("{:.*}", precision, value);
+            let fixed = if fixed.contains('.') {
+                fixed
+                    .trim_end_matches('0')
+                    .trim_end_matches('.')
+                    .to_string()
+            } else {
+                fixed
+            };
+            [fixed, format!("{:.*e}", precision, value)]
+        })
+        .filter(|s| s.len() <= 16)
+        .min_by(|a, b| error(a).total_cmp(&error(b)).then(a.len().cmp(&b.len())))
+}
+
+/// Check whether a primitive value holds no actual values,
+/// which includes text made only of padding characters.
+pub(crate) fn is_empty_value(value: &PrimitiveValue) -> bool {
+    match value {
+        PrimitiveValue::Str(_) | PrimitiveValue::Strs(_) => value.to_str().is_empty(),
+        _ => value.multiplicity() == 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::open_file;
+    use byteordered::Endianness;
+    use dicom_core::chrono::FixedOffset;
+    use dicom_core::value::{DicomDate, DicomDateTime, DicomTime};
+    use dicom_core::{dicom_value, header::DataElementHeader};
+    use dicom_encoding::{
+        decode::{basic::BasicDecoder, implicit_le::ImplicitVRLittleEndianDecoder},
+        encode::{implicit_le::ImplicitVRLittleEndianEncoder, EncoderFor},
+    };
+    use dicom_parser::StatefulDecoder;
+
+    fn assert_obj_eq<D>(obj1: &InMemDicomObject<D>, obj2: &InMemDicomObject<D>)
+    where
+        D: std::fmt::Debug,
+    {
+        // debug representation because it makes a stricter comparison and
+        // assumes that Undefined lengths are equal.
+        assert_eq!(format!("{:?}", obj1), format!("{:?}", obj2))
+    }
+
+    #[test]
+    fn inmem_object_compare() {
+        let mut obj1 = InMemDicomObject::new_empty();
+        let mut obj2 = InMemDicomObject::new_empty();
+        assert_eq!(obj1, obj2);
+        let empty_patient_name = DataElement::empty(Tag(0x0010, 0x0010), VR::PN);
+        obj1.put(empty_patient_name.clone());
+        assert_ne!(obj1, obj2);
+        obj2.put(empty_patient_name.clone());
+        assert_obj_eq(&obj1, &obj2);
+    }
+
+    #[test]
+    fn inmem_object_read_dataset() {
+        let data_in = [
+            0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
+            0x08, 0x00, 0x00, 0x00, // Length: 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+        ];
+
+        let decoder = ImplicitVRLittleEndianDecoder::default();
+        let text = SpecificCharacterSet::default();
+        let mut cursor = &data_in[..];
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            decoder,
+            BasicDecoder::new(Endianness::Little),
+            text,
+        );
+
+        let obj = InMemDicomObject::read_dataset(parser).unwrap();
+
+        let mut gt = InMemDicomObject::new_empty();
+
+        let patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            dicom_value!(Strs, ["Doe^John"]),
+        );
+        gt.put(patient_name);
+
+        assert_eq!(obj, gt);
+    }
+
+    #[test]
+    fn inmem_object_read_dataset_with_ts_cs() {
+        let data_in = [
+            0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
+            0x08, 0x00, 0x00, 0x00, // Length: 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+        ];
+
+        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2").unwrap();
+        let cs = SpecificCharacterSet::default();
+        let mut cursor = &data_in[..];
+
+        let obj = InMemDicomObject::read_dataset_with_dict_ts_cs(
+            &mut cursor,
+            StandardDataDictionary,
+            &ts,
+            cs,
+        )
+        .unwrap();
+
+        let mut gt = InMemDicomObject::new_empty();
+
+        let patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            dicom_value!(Strs, ["Doe^John"]),
+        );
+        gt.put(patient_name);
+
+        assert_eq!(obj, gt);
+    }
+
+    /// Reading a data set
+    /// saves the original length of a text element.
+    #[test]
+    fn inmem_object_read_dataset_saves_len() {
+        let data_in = [
+            // SpecificCharacterSet (0008,0005)
+            0x08, 0x00, 0x05, 0x00, //
+            // Length: 10
+            0x0a, 0x00, 0x00, 0x00, //
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'0', b'0',
+            // ReferringPhysicianName (0008,0090)
+            0x08, 0x00, 0x90, 0x00, //
+            // Length: 12
+            0x0c, 0x00, 0x00, 0x00, b'S', b'i', b'm', 0xF5, b'e', b's', b'^', b'J', b'o', 0xE3,
+            b'o', b' ',
+        ];
+
+        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2").unwrap();
+        let mut cursor = &data_in[..];
+
+        let obj =
+            InMemDicomObject::read_dataset_with_dict_ts(&mut cursor, StandardDataDictionary, &ts)
+                .unwrap();
+
+        let physician_name = obj.element(Tag(0x0008, 0x0090)).unwrap();
+        assert_eq!(physician_name.header().len, Length(12));
+        assert_eq!(physician_name.value().to_str().unwrap(), "Simões^João");
+    }
+
+    #[test]
+    fn inmem_object_write_dataset() {
+        let mut obj = InMemDicomObject::new_empty();
+
+        let patient_name =
+            DataElement::new(Tag(0x0010, 0x0010), VR::PN, dicom_value!(Str, "Doe^John"));
+        obj.put(patient_name);
+
+        let mut out = Vec::new();
+
+        let printer = EncoderFor::new(ImplicitVRLittleEndianEncoder::default());
+
+        obj.write_dataset(&mut out, printer).unwrap();
+
+        assert_eq!(
+            out,
+            &[
+                0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
+                0x08, 0x00, 0x00, 0x00, // Length: 8
+                b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            ][..],
+        );
+    }
+
+    #[test]
+    fn inmem_object_write_dataset_with_ts() {
+        let mut obj = InMemDicomObject::new_empty();
+
+        let patient_name =
+            DataElement::new(Tag(0x0010, 0x0010), VR::PN, dicom_value!(Str, "Doe^John"));
+        obj.put(patient_name);
+
+        let mut out = Vec::new();
+
+        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2.1").unwrap();
+
+        obj.write_dataset_with_ts(&mut out, &ts).unwrap();
+
+        assert_eq!(
+            out,
+            &[
+                0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
+                b'P', b'N', // VR: PN
+                0x08, 0x00, // Length: 8
+                b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            ][..],
+        );
+    }
+
+    #[test]
+    fn inmem_object_write_dataset_with_ts_cs() {
+        let mut obj = InMemDicomObject::new_empty();
+
+        let patient_name =
+            DataElement::new(Tag(0x0010, 0x0010), VR::PN, dicom_value!(Str, "Doe^John"));
+        obj.put(patient_name);
+
+        let mut out = Vec::new();
+
+        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2").unwrap();
+        let cs = SpecificCharacterSet::default();
+
+        obj.write_dataset_with_ts_cs(&mut out, &ts, cs).unwrap();
+
+        assert_eq!(
+            out,
+            &[
+                0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
+                0x08, 0x00, 0x00, 0x00, // Length: 8
+                b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            ][..],
+        );
+    }
+
+    /// Specific Character Set declared in a sequence item
+    /// only applies to that item,
+    /// both when reading and when writing.
+    #[test]
+    fn inmem_object_character_set_of_items() {
+        #[rustfmt::skip]
+        let data_in: &[u8] = &[
+            // SpecificCharacterSet (0008,0005)
+            0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0A, 0x00,
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'0', b'0',
+            // PatientName (0010,0010): "Müller"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x06, 0x00,
+            b'M', 0xFC, b'l', b'l', b'e', b'r',
+            // OtherPatientIDsSequence (0010,1002)
+            0x10, 0x00, 0x02, 0x10, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // item
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // SpecificCharacterSet (0008,0005)
+            0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0A, 0x00,
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'9', b'2',
+            // PatientName (0010,0010): "Gößmann"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x0A, 0x00,
+            b'G', 0xC3, 0xB6, 0xC3, 0x9F, b'm', b'a', b'n', b'n', b' ',
+            // item delimiter
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // item inheriting the character set
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // PatientName (0010,0010): "Bäcker"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x06, 0x00,
+            b'B', 0xE4, b'c', b'k', b'e', b'r',
+            // item delimiter
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // Occupation (0010,2180): "Köhler"
+            0x10, 0x00, 0x80, 0x21, b'S', b'H', 0x06, 0x00,
+            b'K', 0xF6, b'h', b'l', b'e', b'r',
+        ];
+
+        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2.1").unwrap();
+        let obj = InMemDicomObject::read_dataset_with_ts(data_in, ts).unwrap();
+
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Müller"
+        );
+        assert_eq!(
+            obj.element(tags::OCCUPATION).unwrap().to_str().unwrap(),
+            "Köhler"
+        );
+        let items = obj
+            .element(tags::OTHER_PATIENT_I_DS_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(
+            items[0]
+                .element(tags::PATIENT_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "Gößmann"
+        );
+        assert_eq!(
+            items[1]
+                .element(tags::PATIENT_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "Bäcker"
+        );
+
+        // each level is encoded back in its own character set
+        let mut out = Vec::new();
+        obj.write_dataset_with_ts(&mut out, ts).unwrap();
+        assert_eq!(out, data_in);
+    }
+
+    /// writing a DICOM date time into an object
+    /// should include value padding
+    #[test]
+    fn inmem_object_write_datetime_odd() {
+        let mut obj = InMemDicomObject::new_empty();
+
+        // add a number that will be encoded in text
+        let instance_number =
+            DataElement::new(Tag(0x0020, 0x0013), VR::IS, PrimitiveValue::from(1_i32));
+        obj.put(instance_number);
+
+        // add a date time
+        let dt = DicomDateTime::from_date_and_time_with_time_zone(
+            DicomDate::from_ymd(2022, 11, 22).unwrap(),
+            DicomTime::from_hms(18, 09, 35).unwrap(),
+            FixedOffset::east_opt(3600).unwrap(),
+        )
+        .unwrap();
+        let instance_coercion_date_time =
+            DataElement::new(Tag(0x0008, 0x0015), VR::DT, dicom_value!(DateTime, dt));
+        obj.put(instance_coercion_date_time);
+
+        // explicit VR Little Endian
+        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2.1").unwrap();
+
+        let mut out = Vec::new();
+        obj.write_dataset_with_ts(&mut out, &ts)
+            .expect("should write DICOM data without errors");
+
+        assert_eq!(
+            out,
+            &[
+                // instance coercion date time
+                0x08, 0x00, 0x15, 0x00, // Tag(0x0008, 0x0015)
+                b'D', b'T', // VR: DT
+                0x14, 0x00, // Length: 20 bytes
+                b'2', b'0', b'2', b'2', b'1', b'1', b'2', b'2', // date
+                b'1', b'8', b'0', b'9', b'3', b'5', // time
+                b'+', b'0', b'1', b'0', b'0', // offset
+                b' ', // padding to even length
+                // instance number
+                0x20, 0x00, 0x13, 0x00, // Tag(0x0020, 0x0013)
+                b'I', b'S', // VR: IS
+                0x02, 0x00, // Length: 2 bytes
+                b'1', b' ' // 1, with padding
+            ][..],
+        );
+    }
+
+    /// Writes a file from scratch
+    /// and opens it to check that the data is equivalent.
+    #[test]
+    fn inmem_write_to_file_with_meta() {
+        let sop_uid = "1.4.645.212121";
+        let mut obj = InMemDicomObject::new_empty();
+
+        obj.put(DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            dicom_value!(Strs, ["Doe^John"]),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x0008, 0x0060),
+            VR::CS,
+            dicom_value!(Strs, ["CR"]),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x0008, 0x0018),
+            VR::UI,
+            dicom_value!(Strs, [sop_uid]),
+        ));
+
+        let file_object = obj
+            .with_meta(
+                FileMetaTableBuilder::default()
+                    // Explicit VR Little Endian
+                    .transfer_syntax("1.2.840.10008.1.2.1")
+                    // Computed Radiography image storage
+                    .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1")
+                    .media_storage_sop_instance_uid(sop_uid),
+            )
+            .unwrap();
+
+        // create temporary file path and write object to that file
+        let dir = tempfile::tempdir().unwrap();
+        let mut file_path = dir.into_path();
+        file_path.push(format!("{}.dcm", sop_uid));
+
+        file_object.write_to_file(&file_path).unwrap();
+
+        // read the file back to validate the outcome
+        let saved_object = open_file(file_path).unwrap();
+        assert_eq!(file_object, saved_object);
+    }
+
+    /// Creating a file DICOM object from an in-mem DICOM object
+    /// infers the SOP instance UID.
+    #[test]
+    fn inmem_with_meta_infers_sop_instance_uid() {
+        let sop_uid = "1.4.645.252521";
+        let mut obj = InMemDicomObject::new_empty();
+
+        obj.put(DataElement::new(
+            tags::SOP_INSTANCE_UID,
+            VR::UI,
+            PrimitiveValue::from(sop_uid),
+        ));
+
+        let file_object = obj
+            .with_meta(
+                // Media Storage SOP Instance UID deliberately not set
+                FileMetaTableBuilder::default()
+                    // Explicit VR Little Endian
+                    .transfer_syntax("1.2.840.10008.1.2.1")
+                    // Computed Radiography image storage
+                    .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1"),
+            )
+            .unwrap();
+
+        let meta = file_object.meta();
+
+        assert_eq!(
+            meta.media_storage_sop_instance_uid
+                .trim_end_matches(|c| c == '\0'),
+            sop_uid.trim_end_matches(|c| c == '\0'),
+        );
     }
 
-    fn lookup_name(&self, name: &str) -> Result<Tag, AccessByNameError> {
-        self.dict
-            .by_name(name)
-            .context(NoSuchAttributeNameSnafu { name })
-            .map(|e| e.tag())
-    }
-}
+    /// Write a file from scratch, with exact file meta table.
+    #[test]
+    fn inmem_write_to_file_with_exact_meta() {
+        let sop_uid = "1.4.645.212121";
+        let mut obj = InMemDicomObject::new_empty();
+
+        obj.put(DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            dicom_value!(Strs, ["Doe^John"]),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x0008, 0x0060),
+            VR::CS,
+            dicom_value!(Strs, ["CR"]),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x0008, 0x0018),
+            VR::UI,
+            dicom_value!(Strs, [sop_uid]),
+        ));
+
+        let file_object = obj.with_exact_meta(
+            FileMetaTableBuilder::default()
+                // Explicit VR Little Endian
+                .transfer_syntax("1.2.840.10008.1.2.1")
+                // Computed Radiography image storage
+                .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1")
+                .media_storage_sop_instance_uid(sop_uid)
+                .build()
+                .unwrap(),
+        );
+
+        // create temporary file path and write object to that file
+        let dir = tempfile::tempdir().unwrap();
+        let mut file_path = dir.into_path();
+        file_path.push(format!("{}.dcm", sop_uid));
 
-impl<D> ApplyOp for InMemDicomObject<D>
-where
-    D: DataDictionary,
-    D: Clone,
-{
-    type Err = ApplyError;
+        file_object.write_to_file(&file_path).unwrap();
 
-    #[inline]
-    fn apply(&mut self, op: AttributeOp) -> ApplyResult {
-        self.apply(op)
+        // read the file back to validate the outcome
+        let saved_object = open_file(file_path).unwrap();
+        assert_eq!(file_object, saved_object);
     }
-}
-
-impl<'a, D> IntoIterator for &'a InMemDicomObject<D> {
-    type Item = &'a InMemElement<D>;
-    type IntoIter = ::std::collections::btree_map::Values<'a, Tag, InMemElement<D>>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.entries.values()
+    #[test]
+    fn inmem_object_get() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        let elem1 = (&obj).element(Tag(0x0010, 0x0010)).unwrap();
+        assert_eq!(elem1, &another_patient_name);
     }
-}
 
-impl<D> IntoIterator for InMemDicomObject<D> {
-    type Item = InMemElement<D>;
-    type IntoIter = Iter<D>;
+    #[test]
+    fn inmem_object_get_opt() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        let elem1 = obj.element_opt(Tag(0x0010, 0x0010)).unwrap();
+        assert_eq!(elem1, Some(&another_patient_name));
 
-    fn into_iter(self) -> Self::IntoIter {
-        Iter {
-            inner: self.entries.into_iter(),
-        }
+        // try a missing element, should return None
+        assert_eq!(obj.element_opt(Tag(0x0010, 0x0020)).unwrap(), None);
     }
-}
 
-/// Base iterator type for an in-memory DICOM object.
-#[derive(Debug)]
-pub struct Iter<D> {
-    inner: ::std::collections::btree_map::IntoIter<Tag, InMemElement<D>>,
-}
+    #[test]
+    fn inmem_object_get_by_name() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        let elem1 = (&obj).element_by_name("PatientName").unwrap();
+        assert_eq!(elem1, &another_patient_name);
+    }
 
-impl<D> Iterator for Iter<D> {
-    type Item = InMemElement<D>;
+    #[test]
+    fn inmem_object_get_by_name_opt() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        let elem1 = obj.element_by_name_opt("PatientName").unwrap();
+        assert_eq!(elem1, Some(&another_patient_name));
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|x| x.1)
+        // try a missing element, should return None
+        assert_eq!(obj.element_by_name_opt("PatientID").unwrap(), None);
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.inner.size_hint()
+    #[test]
+    fn inmem_object_take_element() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        let elem1 = obj.take_element(Tag(0x0010, 0x0010)).unwrap();
+        assert_eq!(elem1, another_patient_name);
+        assert!(matches!(
+            obj.take_element(Tag(0x0010, 0x0010)),
+            Err(AccessError::NoSuchDataElementTag {
+                tag: Tag(0x0010, 0x0010),
+                ..
+            })
+        ));
     }
 
-    fn count(self) -> usize {
-        self.inner.count()
+    #[test]
+    fn inmem_object_take_element_by_name() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        let elem1 = obj.take_element_by_name("PatientName").unwrap();
+        assert_eq!(elem1, another_patient_name);
+        assert!(matches!(
+            obj.take_element_by_name("PatientName"),
+            Err(AccessByNameError::NoSuchDataElementAlias {
+                tag: Tag(0x0010, 0x0010),
+                alias,
+                ..
+            }) if alias == "PatientName"));
     }
-}
 
-impl<D> Extend<InMemElement<D>> for InMemDicomObject<D> {
-    fn extend<I>(&mut self, iter: I)
-    where
-        I: IntoIterator<Item = InMemElement<D>>,
-    {
-        self.len = Length::UNDEFINED;
-        self.entries.extend(iter.into_iter().map(|e| (e.tag(), e)))
+    #[test]
+    fn inmem_object_remove_element() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        assert!(obj.remove_element(Tag(0x0010, 0x0010)));
+        assert_eq!(obj.remove_element(Tag(0x0010, 0x0010)), false);
     }
-}
-
-fn even_len(l: u32) -> u32 {
-    (l + 1) & !1
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::open_file;
-    use byteordered::Endianness;
-    use dicom_core::chrono::FixedOffset;
-    use dicom_core::value::{DicomDate, DicomDateTime, DicomTime};
-    use dicom_core::{dicom_value, header::DataElementHeader};
-    use dicom_encoding::{
-        decode::{basic::BasicDecoder, implicit_le::ImplicitVRLittleEndianDecoder},
-        encode::{implicit_le::ImplicitVRLittleEndianEncoder, EncoderFor},
-    };
-    use dicom_parser::StatefulDecoder;
 
-    fn assert_obj_eq<D>(obj1: &InMemDicomObject<D>, obj2: &InMemDicomObject<D>)
-    where
-        D: std::fmt::Debug,
-    {
-        // debug representation because it makes a stricter comparison and
-        // assumes that Undefined lengths are equal.
-        assert_eq!(format!("{:?}", obj1), format!("{:?}", obj2))
+    #[test]
+    fn inmem_object_remove_element_by_name() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        assert!(obj.remove_element_by_name("PatientName").unwrap());
+        assert_eq!(obj.remove_element_by_name("PatientName").unwrap(), false);
     }
 
     #[test]
-    fn inmem_object_compare() {
-        let mut obj1 = InMemDicomObject::new_empty();
-        let mut obj2 = InMemDicomObject::new_empty();
-        assert_eq!(obj1, obj2);
-        let empty_patient_name = DataElement::empty(Tag(0x0010, 0x0010), VR::PN);
-        obj1.put(empty_patient_name.clone());
-        assert_ne!(obj1, obj2);
-        obj2.put(empty_patient_name.clone());
-        assert_obj_eq(&obj1, &obj2);
+    fn inmem_object_len_and_contains() {
+        let mut obj = InMemDicomObject::new_empty();
+        assert!(obj.is_empty());
+        assert_eq!(obj.len(), 0);
+        assert!(!obj.contains(tags::PATIENT_NAME));
+
+        obj.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"));
+        obj.put(DataElement::new(tags::PATIENT_ID, VR::LO, "12345"));
+        assert!(!obj.is_empty());
+        assert_eq!(obj.len(), 2);
+        assert!(obj.contains(tags::PATIENT_NAME));
+
+        // replacing an element does not change the length
+        let old = obj.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^Jane"));
+        assert_eq!(old.unwrap().to_str().unwrap(), "Doe^John");
+        assert_eq!(obj.len(), 2);
+
+        obj.remove_element(tags::PATIENT_NAME);
+        assert_eq!(obj.len(), 1);
+        assert!(!obj.contains(tags::PATIENT_NAME));
     }
 
     #[test]
-    fn inmem_object_read_dataset() {
-        let data_in = [
-            0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
-            0x08, 0x00, 0x00, 0x00, // Length: 8
-            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
-        ];
+    fn inmem_object_typed_getters() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::MODALITY, VR::CS, "CT "),
+            DataElement::new(
+                tags::IMAGE_TYPE,
+                VR::CS,
+                dicom_value!(Strs, ["ORIGINAL", "PRIMARY"]),
+            ),
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(512_u16)),
+            DataElement::new(tags::SERIES_NUMBER, VR::IS, "12 "),
+            DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2.5"),
+            DataElement::new(
+                tags::FRAME_INCREMENT_POINTER,
+                VR::AT,
+                PrimitiveValue::from(tags::FRAME_TIME),
+            ),
+            DataElement::new(tags::STUDY_DATE, VR::DA, "20240229"),
+            DataElement::new(tags::STUDY_TIME, VR::TM, "101530"),
+            DataElement::new(tags::ACQUISITION_DATE_TIME, VR::DT, "20240229101530"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::Empty),
+            DataElement::new(tags::PATIENT_ID, VR::LO, "  "),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::new_empty()]),
+            ),
+        ]);
 
-        let decoder = ImplicitVRLittleEndianDecoder::default();
-        let text = SpecificCharacterSet::default();
-        let mut cursor = &data_in[..];
-        let parser = StatefulDecoder::new(
-            &mut cursor,
-            decoder,
-            BasicDecoder::new(Endianness::Little),
-            text,
+        // present
+        assert_eq!(obj.string(tags::MODALITY).unwrap(), "CT");
+        assert_eq!(obj.string(tags::IMAGE_TYPE).unwrap(), "ORIGINAL");
+        assert_eq!(
+            obj.strings(tags::IMAGE_TYPE).unwrap(),
+            vec!["ORIGINAL".to_string(), "PRIMARY".to_string()]
+        );
+        assert_eq!(obj.u16(tags::ROWS).unwrap(), 512);
+        assert_eq!(obj.i32(tags::SERIES_NUMBER).unwrap(), 12);
+        assert_eq!(obj.f64(tags::SLICE_THICKNESS).unwrap(), 2.5);
+        assert_eq!(
+            obj.tag_value(tags::FRAME_INCREMENT_POINTER).unwrap(),
+            tags::FRAME_TIME
+        );
+        assert_eq!(
+            obj.date(tags::STUDY_DATE).unwrap(),
+            DicomDate::from_ymd(2024, 2, 29).unwrap()
+        );
+        assert_eq!(
+            obj.time(tags::STUDY_TIME).unwrap(),
+            DicomTime::from_hms(10, 15, 30).unwrap()
+        );
+        assert_eq!(
+            obj.datetime(tags::ACQUISITION_DATE_TIME)
+                .unwrap()
+                .to_encoded(),
+            "20240229101530"
         );
 
-        let obj = InMemDicomObject::read_dataset(parser).unwrap();
-
-        let mut gt = InMemDicomObject::new_empty();
+        // absent
+        assert!(matches!(
+            obj.string(tags::SERIES_DESCRIPTION),
+            Err(AttributeError::MissingAttribute { tag, .. }) if tag == tags::SERIES_DESCRIPTION
+        ));
+        assert!(matches!(
+            obj.strings(tags::SERIES_DESCRIPTION),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
+        assert!(matches!(
+            obj.u16(tags::COLUMNS),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
+        assert!(matches!(
+            obj.i32(tags::INSTANCE_NUMBER),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
+        assert!(matches!(
+            obj.f64(tags::PIXEL_SPACING),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
+        assert!(matches!(
+            obj.tag_value(tags::DIMENSION_INDEX_POINTER),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
+        assert!(matches!(
+            obj.date(tags::SERIES_DATE),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
+        assert!(matches!(
+            obj.time(tags::SERIES_TIME),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
+        assert!(matches!(
+            obj.datetime(tags::FRAME_ACQUISITION_DATE_TIME),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
 
-        let patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            dicom_value!(Strs, ["Doe^John"]),
-        );
-        gt.put(patient_name);
+        // empty
+        assert!(matches!(
+            obj.string(tags::PATIENT_NAME),
+            Err(AttributeError::EmptyValue { tag, .. }) if tag == tags::PATIENT_NAME
+        ));
+        assert!(matches!(
+            obj.strings(tags::PATIENT_ID),
+            Err(AttributeError::EmptyValue { .. })
+        ));
+        assert!(matches!(
+            obj.u16(tags::PATIENT_NAME),
+            Err(AttributeError::EmptyValue { .. })
+        ));
+        assert!(matches!(
+            obj.i32(tags::PATIENT_ID),
+            Err(AttributeError::EmptyValue { .. })
+        ));
+        assert!(matches!(
+            obj.f64(tags::PATIENT_NAME),
+            Err(AttributeError::EmptyValue { .. })
+        ));
+        assert!(matches!(
+            obj.tag_value(tags::PATIENT_NAME),
+            Err(AttributeError::EmptyValue { .. })
+        ));
+        assert!(matches!(
+            obj.date(tags::PATIENT_ID),
+            Err(AttributeError::EmptyValue { .. })
+        ));
+        assert!(matches!(
+            obj.time(tags::PATIENT_NAME),
+            Err(AttributeError::EmptyValue { .. })
+        ));
+        assert!(matches!(
+            obj.datetime(tags::PATIENT_ID),
+            Err(AttributeError::EmptyValue { .. })
+        ));
 
-        assert_eq!(obj, gt);
+        // wrong type
+        assert!(matches!(
+            obj.string(tags::REFERENCED_IMAGE_SEQUENCE),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.strings(tags::REFERENCED_IMAGE_SEQUENCE),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.u16(tags::MODALITY),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.i32(tags::MODALITY),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.f64(tags::MODALITY),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.tag_value(tags::MODALITY),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.date(tags::MODALITY),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.time(tags::MODALITY),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.datetime(tags::ROWS),
+            Err(AttributeError::ConvertValue { .. })
+        ));
     }
 
     #[test]
-    fn inmem_object_read_dataset_with_ts_cs() {
-        let data_in = [
-            0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
-            0x08, 0x00, 0x00, 0x00, // Length: 8
-            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
-        ];
-
-        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2").unwrap();
-        let cs = SpecificCharacterSet::default();
-        let mut cursor = &data_in[..];
+    fn inmem_object_precise_datetime_with_timezone_offset() {
+        use dicom_core::chrono::{NaiveDate, NaiveDateTime};
 
-        let obj = InMemDicomObject::read_dataset_with_dict_ts_cs(
-            &mut cursor,
-            StandardDataDictionary,
-            &ts,
-            cs,
-        )
-        .unwrap();
+        let utc = |y, mo, d, h, mi, s| {
+            NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(y, mo, d).unwrap(),
+                dicom_core::chrono::NaiveTime::from_hms_opt(h, mi, s).unwrap(),
+            )
+        };
 
-        let mut gt = InMemDicomObject::new_empty();
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::TIMEZONE_OFFSET_FROM_UTC, VR::SH, "+0530"),
+            DataElement::new(tags::ACQUISITION_DATE_TIME, VR::DT, "20240301100000"),
+            DataElement::new(tags::CONTENT_DATE, VR::DA, "20240301"),
+            DataElement::new(
+                tags::INSTANCE_COERCION_DATE_TIME,
+                VR::DT,
+                "20240301100000.250000-0100",
+            ),
+        ]);
+        assert_eq!(
+            obj.timezone_offset().unwrap(),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60)
+        );
 
-        let patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            dicom_value!(Strs, ["Doe^John"]),
+        // shifted by the offset of the data set
+        let dt = obj
+            .precise_datetime(tags::ACQUISITION_DATE_TIME, None)
+            .unwrap();
+        assert_eq!(
+            dt.as_datetime().unwrap().naive_utc(),
+            utc(2024, 3, 1, 4, 30, 0)
         );
-        gt.put(patient_name);
 
-        assert_eq!(obj, gt);
-    }
+        // a value with its own offset is not adjusted again
+        let dt = obj
+            .precise_datetime(
+                tags::INSTANCE_COERCION_DATE_TIME,
+                FixedOffset::east_opt(3600),
+            )
+            .unwrap();
+        let dt = dt.as_datetime().unwrap();
+        assert_eq!(dt.offset(), &FixedOffset::west_opt(3600).unwrap());
+        assert_eq!(
+            dt.naive_utc(),
+            utc(2024, 3, 1, 11, 0, 0) + dicom_core::chrono::Duration::milliseconds(250)
+        );
 
-    /// Reading a data set
-    /// saves the original length of a text element.
-    #[test]
-    fn inmem_object_read_dataset_saves_len() {
-        let data_in = [
-            // SpecificCharacterSet (0008,0005)
-            0x08, 0x00, 0x05, 0x00, //
-            // Length: 10
-            0x0a, 0x00, 0x00, 0x00, //
-            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'0', b'0',
-            // ReferringPhysicianName (0008,0090)
-            0x08, 0x00, 0x90, 0x00, //
-            // Length: 12
-            0x0c, 0x00, 0x00, 0x00, b'S', b'i', b'm', 0xF5, b'e', b's', b'^', b'J', b'o', 0xE3,
-            b'o', b' ',
-        ];
+        // date-times without time are not precise
+        assert!(matches!(
+            obj.precise_datetime(tags::CONTENT_DATE, None),
+            Err(AttributeError::ConvertDateTime { .. })
+        ));
 
-        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2").unwrap();
-        let mut cursor = &data_in[..];
+        // without the attribute, the given default applies
+        obj.remove_element(tags::TIMEZONE_OFFSET_FROM_UTC);
+        assert_eq!(obj.timezone_offset().unwrap(), None);
+        let dt = obj
+            .precise_datetime(tags::ACQUISITION_DATE_TIME, FixedOffset::west_opt(3600))
+            .unwrap();
+        assert_eq!(
+            dt.as_datetime().unwrap().naive_utc(),
+            utc(2024, 3, 1, 11, 0, 0)
+        );
 
-        let obj =
-            InMemDicomObject::read_dataset_with_dict_ts(&mut cursor, StandardDataDictionary, &ts)
-                .unwrap();
+        // or else the result is naive
+        let dt = obj
+            .precise_datetime(tags::ACQUISITION_DATE_TIME, None)
+            .unwrap();
+        assert_eq!(dt.as_naive_datetime().unwrap(), &utc(2024, 3, 1, 10, 0, 0));
 
-        let physician_name = obj.element(Tag(0x0008, 0x0090)).unwrap();
-        assert_eq!(physician_name.header().len, Length(12));
-        assert_eq!(physician_name.value().to_str().unwrap(), "Simões^João");
+        // malformed offsets are reported
+        obj.put(DataElement::new(
+            tags::TIMEZONE_OFFSET_FROM_UTC,
+            VR::SH,
+            "0530",
+        ));
+        assert!(matches!(
+            obj.precise_datetime(tags::ACQUISITION_DATE_TIME, None),
+            Err(AttributeError::InvalidTimezoneOffset { .. })
+        ));
     }
 
     #[test]
-    fn inmem_object_write_dataset() {
-        let mut obj = InMemDicomObject::new_empty();
+    fn inmem_object_datetime_of_date_and_time() {
+        use crate::DateTimePart;
+        use dicom_core::chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+        // full pair, with the fraction of seconds preserved
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::STUDY_DATE, VR::DA, "20240301"),
+            DataElement::new(tags::STUDY_TIME, VR::TM, "101530.123456"),
+            DataElement::new(tags::ACQUISITION_DATE, VR::DA, "20240301"),
+            DataElement::new(tags::ACQUISITION_TIME, VR::TM, "1015"),
+            DataElement::new(tags::CONTENT_DATE, VR::DA, "20240302"),
+        ]);
+        let dt = obj.study_datetime().unwrap();
+        assert!(!dt.is_date_only());
+        assert_eq!(
+            dt.datetime().time(),
+            Some(&DicomTime::from_hms_micro(10, 15, 30, 123_456).unwrap())
+        );
+        assert_eq!(
+            dt.to_precise_datetime().unwrap(),
+            PreciseDateTime::Naive(NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+                NaiveTime::from_hms_micro_opt(10, 15, 30, 123_456).unwrap(),
+            ))
+        );
 
-        let patient_name =
-            DataElement::new(Tag(0x0010, 0x0010), VR::PN, dicom_value!(Str, "Doe^John"));
-        obj.put(patient_name);
+        // partial time precision is kept
+        let dt = obj.acquisition_datetime().unwrap();
+        assert_eq!(
+            dt.datetime().time(),
+            Some(&DicomTime::from_hm(10, 15).unwrap())
+        );
+        assert!(dt.to_precise_datetime().is_err());
 
-        let mut out = Vec::new();
+        // date only, midnight is assumed
+        let dt = obj.content_datetime().unwrap();
+        assert!(dt.is_date_only());
+        assert_eq!(
+            dt.to_precise_datetime().unwrap(),
+            PreciseDateTime::Naive(NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2024, 3, 2).unwrap(),
+                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            ))
+        );
 
-        let printer = EncoderFor::new(ImplicitVRLittleEndianEncoder::default());
+        // the offset from UTC of the data set applies
+        let mut obj = obj;
+        obj.put(DataElement::new(
+            tags::TIMEZONE_OFFSET_FROM_UTC,
+            VR::SH,
+            "+0530",
+        ));
+        let dt = obj.study_datetime().unwrap();
+        assert_eq!(
+            dt.datetime().time_zone(),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60).as_ref()
+        );
 
-        obj.write_dataset(&mut out, printer).unwrap();
+        // missing date
+        assert!(matches!(
+            obj.datetime_of(tags::SERIES_DATE, tags::STUDY_TIME),
+            Err(AttributeError::CombineDateTime {
+                part: DateTimePart::Date,
+                ..
+            })
+        ));
 
+        // malformed time
+        obj.put(DataElement::new(tags::STUDY_TIME, VR::TM, "noon"));
+        let err = obj.study_datetime().unwrap_err();
+        assert!(matches!(
+            err,
+            AttributeError::CombineDateTime {
+                part: DateTimePart::Time,
+                ..
+            }
+        ));
         assert_eq!(
-            out,
-            &[
-                0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
-                0x08, 0x00, 0x00, 0x00, // Length: 8
-                b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
-            ][..],
+            err.to_string(),
+            "Could not combine (0008,0020) and (0008,0030) into a date-time: invalid time"
         );
     }
 
     #[test]
-    fn inmem_object_write_dataset_with_ts() {
-        let mut obj = InMemDicomObject::new_empty();
-
-        let patient_name =
-            DataElement::new(Tag(0x0010, 0x0010), VR::PN, dicom_value!(Str, "Doe^John"));
-        obj.put(patient_name);
-
-        let mut out = Vec::new();
+    fn inmem_object_multi_valued_getters() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::IMAGE_ORIENTATION_PATIENT,
+                VR::DS,
+                dicom_value!(Strs, ["1", "0", "0", "0", "1", "0"]),
+            ),
+            // multiple values in a single string
+            DataElement::new(tags::WINDOW_CENTER, VR::DS, "40\\400 "),
+            DataElement::new(
+                tags::WINDOW_WIDTH,
+                VR::DS,
+                dicom_value!(Strs, ["400", "2000"]),
+            ),
+            DataElement::new(tags::IMAGE_TYPE, VR::CS, "DERIVED\\SECONDARY"),
+            DataElement::new(tags::IMAGE_COMMENTS, VR::LT, "not\\split"),
+            DataElement::new(
+                tags::ACQUISITION_MATRIX,
+                VR::US,
+                PrimitiveValue::from([0_u16, 256, 256, 0]),
+            ),
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(512_u16)),
+            DataElement::new(tags::INSTANCE_NUMBER, VR::IS, "7"),
+        ]);
 
-        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2.1").unwrap();
+        assert_eq!(
+            obj.f64s(tags::IMAGE_ORIENTATION_PATIENT).unwrap(),
+            vec![1., 0., 0., 0., 1., 0.]
+        );
+        assert_eq!(
+            obj.strings(tags::IMAGE_ORIENTATION_PATIENT).unwrap().len(),
+            6
+        );
+        assert_eq!(obj.f64s(tags::WINDOW_CENTER).unwrap(), vec![40., 400.]);
+        assert_eq!(obj.f64s(tags::WINDOW_WIDTH).unwrap(), vec![400., 2000.]);
+        assert_eq!(obj.i32s(tags::WINDOW_WIDTH).unwrap(), vec![400, 2000]);
+        assert_eq!(
+            obj.strings(tags::IMAGE_TYPE).unwrap(),
+            vec!["DERIVED".to_string(), "SECONDARY".to_string()]
+        );
+        assert_eq!(
+            obj.strings(tags::IMAGE_COMMENTS).unwrap(),
+            vec!["not\\split".to_string()]
+        );
+        assert_eq!(
+            obj.u16s(tags::ACQUISITION_MATRIX).unwrap(),
+            vec![0, 256, 256, 0]
+        );
+        assert_eq!(obj.u16s(tags::ROWS).unwrap(), vec![512]);
 
-        obj.write_dataset_with_ts(&mut out, &ts).unwrap();
+        // single-value getters return the first value by default
+        assert_eq!(obj.f64(tags::IMAGE_ORIENTATION_PATIENT).unwrap(), 1.);
+        assert_eq!(obj.f64(tags::WINDOW_CENTER).unwrap(), 40.);
+        assert_eq!(obj.f64(tags::WINDOW_WIDTH).unwrap(), 400.);
+        assert_eq!(obj.string(tags::IMAGE_TYPE).unwrap(), "DERIVED");
+        assert_eq!(obj.u16(tags::ACQUISITION_MATRIX).unwrap(), 0);
 
+        // but fail in strict mode
+        assert!(matches!(
+            obj.strict().f64(tags::IMAGE_ORIENTATION_PATIENT),
+            Err(AttributeError::MultipleValues { count: 6, .. })
+        ));
+        assert!(matches!(
+            obj.strict().f64(tags::WINDOW_CENTER),
+            Err(AttributeError::MultipleValues { count: 2, .. })
+        ));
+        assert!(matches!(
+            obj.strict().i32(tags::WINDOW_WIDTH),
+            Err(AttributeError::MultipleValues { count: 2, .. })
+        ));
+        assert!(matches!(
+            obj.strict().string(tags::IMAGE_TYPE),
+            Err(AttributeError::MultipleValues { count: 2, .. })
+        ));
+        assert!(matches!(
+            obj.strict().u16(tags::ACQUISITION_MATRIX),
+            Err(AttributeError::MultipleValues { count: 4, .. })
+        ));
+        // single values are fine in strict mode
         assert_eq!(
-            out,
-            &[
-                0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
-                b'P', b'N', // VR: PN
-                0x08, 0x00, // Length: 8
-                b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
-            ][..],
+            obj.strict().string(tags::IMAGE_COMMENTS).unwrap(),
+            "not\\split"
         );
+        assert_eq!(obj.strict().u16(tags::ROWS).unwrap(), 512);
+        assert_eq!(obj.strict().i32(tags::INSTANCE_NUMBER).unwrap(), 7);
+        assert!(matches!(
+            obj.strict().f64(tags::SLICE_THICKNESS),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
     }
 
     #[test]
-    fn inmem_object_write_dataset_with_ts_cs() {
-        let mut obj = InMemDicomObject::new_empty();
-
-        let patient_name =
-            DataElement::new(Tag(0x0010, 0x0010), VR::PN, dicom_value!(Str, "Doe^John"));
-        obj.put(patient_name);
-
-        let mut out = Vec::new();
+    fn inmem_object_nested_items() {
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::PIXEL_MEASURES_SEQUENCE,
+                        VR::SQ,
+                        DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                            DataElement::new(tags::PIXEL_SPACING, VR::DS, "0.8\\0.6"),
+                        ])]),
+                    ),
+                ])]),
+            ),
+            DataElement::new(
+                tags::REFERENCED_SERIES_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::<InMemDicomObject>::empty(),
+            ),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+        ]);
 
-        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2").unwrap();
-        let cs = SpecificCharacterSet::default();
+        let shared = obj.items(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE).unwrap();
+        assert_eq!(shared.len(), 1);
+        let pixel_measures = shared[0].items(tags::PIXEL_MEASURES_SEQUENCE).unwrap();
+        assert_eq!(pixel_measures.len(), 1);
+        assert_eq!(
+            pixel_measures[0].f64s(tags::PIXEL_SPACING).unwrap(),
+            vec![0.8, 0.6]
+        );
+        assert_eq!(
+            obj.item(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE, 0)
+                .and_then(|item| item.item(tags::PIXEL_MEASURES_SEQUENCE, 0))
+                .map(|item| item.contains(tags::PIXEL_SPACING)),
+            Some(true)
+        );
 
-        obj.write_dataset_with_ts_cs(&mut out, &ts, cs).unwrap();
+        // out of bounds, empty sequence, not a sequence, missing
+        assert!(obj
+            .item(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE, 1)
+            .is_none());
+        assert_eq!(obj.items(tags::REFERENCED_SERIES_SEQUENCE), Some(&[][..]));
+        assert!(obj.item(tags::REFERENCED_SERIES_SEQUENCE, 0).is_none());
+        assert!(obj.items(tags::PATIENT_NAME).is_none());
+        assert!(obj.items(tags::REFERENCED_IMAGE_SEQUENCE).is_none());
+
+        // mutate an attribute two levels down
+        obj.item_mut(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE, 0)
+            .unwrap()
+            .item_mut(tags::PIXEL_MEASURES_SEQUENCE, 0)
+            .unwrap()
+            .put(DataElement::new(tags::SLICE_THICKNESS, VR::DS, "1.5"));
+        assert_eq!(
+            obj.entry_at((
+                tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+                0,
+                tags::PIXEL_MEASURES_SEQUENCE,
+                0,
+                tags::SLICE_THICKNESS
+            ))
+            .unwrap()
+            .to_float64()
+            .unwrap(),
+            1.5
+        );
 
+        // add an item to the empty sequence
+        obj.items_mut(tags::REFERENCED_SERIES_SEQUENCE)
+            .unwrap()
+            .push(InMemDicomObject::new_empty());
         assert_eq!(
-            out,
-            &[
-                0x10, 0x00, 0x10, 0x00, // Tag(0x0010, 0x0010)
-                0x08, 0x00, 0x00, 0x00, // Length: 8
-                b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
-            ][..],
+            obj.items(tags::REFERENCED_SERIES_SEQUENCE).unwrap().len(),
+            1
         );
+        assert!(obj.items_mut(tags::PATIENT_NAME).is_none());
     }
 
-    /// writing a DICOM date time into an object
-    /// should include value padding
+    /// Sequences and items with explicit lengths,
+    /// including empty sequences and zero-length items,
+    /// are encoded back the same way.
     #[test]
-    fn inmem_object_write_datetime_odd() {
-        let mut obj = InMemDicomObject::new_empty();
-
-        // add a number that will be encoded in text
-        let instance_number =
-            DataElement::new(Tag(0x0020, 0x0013), VR::IS, PrimitiveValue::from(1_i32));
-        obj.put(instance_number);
-
-        // add a date time
-        let dt = DicomDateTime::from_date_and_time_with_time_zone(
-            DicomDate::from_ymd(2022, 11, 22).unwrap(),
-            DicomTime::from_hms(18, 09, 35).unwrap(),
-            FixedOffset::east_opt(3600).unwrap(),
-        )
-        .unwrap();
-        let instance_coercion_date_time =
-            DataElement::new(Tag(0x0008, 0x0015), VR::DT, dicom_value!(DateTime, dt));
-        obj.put(instance_coercion_date_time);
+    fn inmem_object_preserves_item_lengths() {
+        #[rustfmt::skip]
+        let data_in: &[u8] = &[
+            // ReferencedSeriesSequence (0008,1115), empty
+            0x08, 0x00, 0x15, 0x11, b'S', b'Q', 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            // ReferencedImageSequence (0008,1140), length 8
+            0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00,
+            0x08, 0x00, 0x00, 0x00,
+            // item, length 0
+            0xFE, 0xFF, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ];
 
-        // explicit VR Little Endian
         let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2.1").unwrap();
+        let obj = InMemDicomObject::read_dataset_with_ts(data_in, ts).unwrap();
 
-        let mut out = Vec::new();
-        obj.write_dataset_with_ts(&mut out, &ts)
-            .expect("should write DICOM data without errors");
+        assert_eq!(obj.items(tags::REFERENCED_SERIES_SEQUENCE), Some(&[][..]));
+        let items = obj.items(tags::REFERENCED_IMAGE_SEQUENCE).unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_empty());
+        assert_eq!(items[0].length(), Length(0));
 
-        assert_eq!(
-            out,
-            &[
-                // instance coercion date time
-                0x08, 0x00, 0x15, 0x00, // Tag(0x0008, 0x0015)
-                b'D', b'T', // VR: DT
-                0x14, 0x00, // Length: 20 bytes
-                b'2', b'0', b'2', b'2', b'1', b'1', b'2', b'2', // date
-                b'1', b'8', b'0', b'9', b'3', b'5', // time
-                b'+', b'0', b'1', b'0', b'0', // offset
-                b' ', // padding to even length
-                // instance number
-                0x20, 0x00, 0x13, 0x00, // Tag(0x0020, 0x0013)
-                b'I', b'S', // VR: IS
-                0x02, 0x00, // Length: 2 bytes
-                b'1', b' ' // 1, with padding
-            ][..],
-        );
+        let mut data_out = Vec::new();
+        obj.write_dataset_with_ts(&mut data_out, ts).unwrap();
+        assert_eq!(data_out, data_in);
     }
 
-    /// Writes a file from scratch
-    /// and opens it to check that the data is equivalent.
     #[test]
-    fn inmem_write_to_file_with_meta() {
-        let sop_uid = "1.4.645.212121";
+    fn inmem_object_put_typed() {
         let mut obj = InMemDicomObject::new_empty();
 
-        obj.put(DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            dicom_value!(Strs, ["Doe^John"]),
-        ));
-        obj.put(DataElement::new(
-            Tag(0x0008, 0x0060),
-            VR::CS,
-            dicom_value!(Strs, ["CR"]),
-        ));
-        obj.put(DataElement::new(
-            Tag(0x0008, 0x0018),
-            VR::UI,
-            dicom_value!(Strs, [sop_uid]),
-        ));
+        // DS and IS
+        obj.put_typed(tags::SLICE_THICKNESS, 0.1 + 0.2).unwrap();
+        let e = obj.get(tags::SLICE_THICKNESS).unwrap();
+        assert_eq!(e.vr(), VR::DS);
+        assert_eq!(e.to_str().unwrap(), "0.3");
+        obj.put_f64s(tags::PIXEL_SPACING, &[0.5, 1. / 3.]).unwrap();
+        let e = obj.get(tags::PIXEL_SPACING).unwrap();
+        assert_eq!(e.vr(), VR::DS);
+        assert_eq!(e.to_str().unwrap(), "0.5\\0.33333333333333");
+        obj.put_typed(tags::SERIES_NUMBER, 3_u16).unwrap();
+        let e = obj.get(tags::SERIES_NUMBER).unwrap();
+        assert_eq!(e.vr(), VR::IS);
+        assert_eq!(e.to_str().unwrap(), "3");
+        assert_eq!(format_ds(-1.5e-20).unwrap(), "-1.5e-20");
+        assert_eq!(format_ds(123456789012345678.).unwrap(), "1.23456789012e17");
+        assert_eq!(format_ds(3.33e-21).unwrap(), "3.33e-21");
+        assert_eq!(format_ds(1. / 3e20).unwrap(), "3.3333333333e-21");
+        assert_eq!(format_ds(f64::NAN), None);
+        assert_eq!(format_ds(f64::INFINITY), None);
+        assert!(obj.put_typed(tags::SLICE_THICKNESS, f64::NAN).is_err());
+
+        // binary numbers
+        obj.put_u16(tags::ROWS, 512).unwrap();
+        obj.put_typed(tags::COLUMNS, 256_i32).unwrap();
+        assert_eq!(obj.u16(tags::COLUMNS).unwrap(), 256);
+        assert_eq!(
+            obj.get(tags::COLUMNS).unwrap().value(),
+            &PrimitiveValue::from(256_u16).into()
+        );
+        obj.put_typed(tags::BITS_ALLOCATED, "16").unwrap();
+        assert_eq!(obj.u16(tags::BITS_ALLOCATED).unwrap(), 16);
 
-        let file_object = obj
-            .with_meta(
-                FileMetaTableBuilder::default()
-                    // Explicit VR Little Endian
-                    .transfer_syntax("1.2.840.10008.1.2.1")
-                    // Computed Radiography image storage
-                    .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1")
-                    .media_storage_sop_instance_uid(sop_uid),
-            )
+        // dates and times
+        obj.put_typed(tags::STUDY_DATE, DicomDate::from_ymd(2024, 2, 29).unwrap())
             .unwrap();
+        let e = obj.get(tags::STUDY_DATE).unwrap();
+        assert_eq!(e.vr(), VR::DA);
+        assert_eq!(e.to_str().unwrap(), "20240229");
+        obj.put_typed(tags::STUDY_TIME, DicomTime::from_hms(9, 30, 0).unwrap())
+            .unwrap();
+        assert_eq!(obj.string(tags::STUDY_TIME).unwrap(), "093000");
+        obj.put_typed(tags::SERIES_DATE, "20240301").unwrap();
+        assert_eq!(
+            obj.date(tags::SERIES_DATE).unwrap(),
+            DicomDate::from_ymd(2024, 3, 1).unwrap()
+        );
 
-        // create temporary file path and write object to that file
-        let dir = tempfile::tempdir().unwrap();
-        let mut file_path = dir.into_path();
-        file_path.push(format!("{}.dcm", sop_uid));
-
-        file_object.write_to_file(&file_path).unwrap();
+        // incompatible values
+        assert!(matches!(
+            obj.put_typed(tags::SLICE_THICKNESS, "thick"),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.put_typed(tags::ROWS, 70_000_u32),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.put_typed(tags::ROWS, 1.5),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.put_typed(tags::PATIENT_NAME, 5_u16),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        assert!(matches!(
+            obj.put_typed(tags::STUDY_DATE, DicomTime::from_hm(9, 30).unwrap()),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+        // previous values were kept
+        assert_eq!(obj.u16(tags::ROWS).unwrap(), 512);
+        assert_eq!(obj.string(tags::STUDY_DATE).unwrap(), "20240229");
 
-        // read the file back to validate the outcome
-        let saved_object = open_file(file_path).unwrap();
-        assert_eq!(file_object, saved_object);
+        // private attribute with explicit VR
+        let private_tag = Tag(0x0009, 0x1010);
+        assert!(matches!(
+            obj.put_typed(private_tag, 2.5),
+            Err(AttributeError::UnknownAttribute { .. })
+        ));
+        obj.put_typed_as(private_tag, VR::FD, 2.5_f32).unwrap();
+        let e = obj.get(private_tag).unwrap();
+        assert_eq!(e.vr(), VR::FD);
+        assert_eq!(e.value(), &PrimitiveValue::from(2.5_f64).into());
     }
 
-    /// Creating a file DICOM object from an in-mem DICOM object
-    /// infers the SOP instance UID.
     #[test]
-    fn inmem_with_meta_infers_sop_instance_uid() {
-        let sop_uid = "1.4.645.252521";
+    fn inmem_object_put_str() {
         let mut obj = InMemDicomObject::new_empty();
 
-        obj.put(DataElement::new(
-            tags::SOP_INSTANCE_UID,
-            VR::UI,
-            PrimitiveValue::from(sop_uid),
-        ));
-
-        let file_object = obj
-            .with_meta(
-                // Media Storage SOP Instance UID deliberately not set
-                FileMetaTableBuilder::default()
-                    // Explicit VR Little Endian
-                    .transfer_syntax("1.2.840.10008.1.2.1")
-                    // Computed Radiography image storage
-                    .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1"),
-            )
-            .unwrap();
-
-        let meta = file_object.meta();
-
+        // VR from the dictionary
+        obj.put_str(tags::PATIENT_NAME, "Doe^John").unwrap();
+        assert_eq!(obj.get(tags::PATIENT_NAME).unwrap().vr(), VR::PN);
+        obj.put_str(tags::SLICE_THICKNESS, "1.250").unwrap();
+        let e = obj.get(tags::SLICE_THICKNESS).unwrap();
+        assert_eq!(e.vr(), VR::DS);
+        assert_eq!(e.to_str().unwrap(), "1.25");
+        obj.put_str(tags::ROWS, "512").unwrap();
         assert_eq!(
-            meta.media_storage_sop_instance_uid
-                .trim_end_matches(|c| c == '\0'),
-            sop_uid.trim_end_matches(|c| c == '\0'),
+            obj.get(tags::ROWS).unwrap().value(),
+            &PrimitiveValue::from(512_u16).into()
         );
+        assert!(matches!(
+            obj.put_str(tags::ROWS, "many"),
+            Err(AttributeError::ConvertValue { .. })
+        ));
+
+        // fallback for attributes not in the dictionary
+        let creator_tag = Tag(0x0009, 0x0010);
+        obj.put_str(creator_tag, "ACME 1.0").unwrap();
+        let e = obj.get(creator_tag).unwrap();
+        assert_eq!(e.vr(), VR::LO);
+        assert_eq!(e.to_str().unwrap(), "ACME 1.0");
+        let private_tag = Tag(0x0009, 0x1010);
+        obj.put_str(private_tag, "value").unwrap();
+        assert_eq!(obj.get(private_tag).unwrap().vr(), VR::UN);
+
+        // explicit VR
+        obj.put_str_as(private_tag, VR::LT, "some text");
+        let e = obj.get(private_tag).unwrap();
+        assert_eq!(e.vr(), VR::LT);
+        assert_eq!(e.to_str().unwrap(), "some text");
     }
 
-    /// Write a file from scratch, with exact file meta table.
     #[test]
-    fn inmem_write_to_file_with_exact_meta() {
-        let sop_uid = "1.4.645.212121";
+    fn inmem_object_put_typed_checked() {
+        use dicom_core::dictionary::ValueMultiplicity;
+
         let mut obj = InMemDicomObject::new_empty();
+        obj.put_typed_checked(tags::IMAGE_ORIENTATION_PATIENT, [1., 0., 0., 0., 1., 0.])
+            .unwrap();
+        obj.put_typed_checked(tags::PIXEL_SPACING, "0.5\\0.5")
+            .unwrap();
+        obj.put_typed_checked(tags::IMAGE_TYPE, "ORIGINAL\\PRIMARY\\AXIAL")
+            .unwrap();
+        // 1-n
+        obj.put_typed_checked(tags::CONVOLUTION_KERNEL, "A\\B\\C\\D\\E\\F\\G")
+            .unwrap();
+        // empty values are not checked
+        obj.put_typed_checked(tags::SLICE_THICKNESS, PrimitiveValue::Empty)
+            .unwrap();
 
-        obj.put(DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            dicom_value!(Strs, ["Doe^John"]),
+        assert!(matches!(
+            obj.put_typed_checked(tags::IMAGE_ORIENTATION_PATIENT, [1., 0., 0., 0., 1.]),
+            Err(AttributeError::InvalidMultiplicity {
+                tag: tags::IMAGE_ORIENTATION_PATIENT,
+                vm: ValueMultiplicity::Exactly(6),
+                count: 5,
+                ..
+            })
         ));
-        obj.put(DataElement::new(
-            Tag(0x0008, 0x0060),
-            VR::CS,
-            dicom_value!(Strs, ["CR"]),
+        assert!(matches!(
+            obj.put_typed_checked(tags::PIXEL_SPACING, "0.5\\0.5\\1"),
+            Err(AttributeError::InvalidMultiplicity {
+                vm: ValueMultiplicity::Exactly(2),
+                count: 3,
+                ..
+            })
         ));
-        obj.put(DataElement::new(
-            Tag(0x0008, 0x0018),
-            VR::UI,
-            dicom_value!(Strs, [sop_uid]),
+        assert!(matches!(
+            obj.put_typed_checked(tags::IMAGE_TYPE, "ORIGINAL"),
+            Err(AttributeError::InvalidMultiplicity {
+                vm: ValueMultiplicity::AtLeast(2),
+                count: 1,
+                ..
+            })
         ));
-
-        let file_object = obj.with_exact_meta(
-            FileMetaTableBuilder::default()
-                // Explicit VR Little Endian
-                .transfer_syntax("1.2.840.10008.1.2.1")
-                // Computed Radiography image storage
-                .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1")
-                .media_storage_sop_instance_uid(sop_uid)
-                .build()
-                .unwrap(),
+        // previous values were kept
+        assert_eq!(
+            obj.get(tags::IMAGE_ORIENTATION_PATIENT)
+                .unwrap()
+                .to_multi_float64()
+                .unwrap()
+                .len(),
+            6
+        );
+        assert_eq!(
+            obj.get(tags::PIXEL_SPACING).unwrap().to_str().unwrap(),
+            "0.5\\0.5"
         );
 
-        // create temporary file path and write object to that file
-        let dir = tempfile::tempdir().unwrap();
-        let mut file_path = dir.into_path();
-        file_path.push(format!("{}.dcm", sop_uid));
-
-        file_object.write_to_file(&file_path).unwrap();
-
-        // read the file back to validate the outcome
-        let saved_object = open_file(file_path).unwrap();
-        assert_eq!(file_object, saved_object);
+        // put_typed does not check
+        obj.put_typed(tags::PIXEL_SPACING, "0.5\\0.5\\1").unwrap();
     }
 
     #[test]
-    fn inmem_object_get() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
-        );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        let elem1 = (&obj).element(Tag(0x0010, 0x0010)).unwrap();
-        assert_eq!(elem1, &another_patient_name);
-    }
+    fn inmem_object_element_at_path() {
+        let obj = crate::dicom_object! {
+            PatientName: "Doe^John",
+            PerFrameFunctionalGroupsSequence: [
+                { PlanePositionSequence: [{ ImagePositionPatient: [0., 0., 0.] }] },
+                { PlanePositionSequence: [{ ImagePositionPatient: [0., 0., 2.5] }] },
+                { PlanePositionSequence: [{ ImagePositionPatient: [0., 0., 5.] }] },
+            ],
+        };
 
-    #[test]
-    fn inmem_object_get_opt() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
-        );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        let elem1 = obj.element_opt(Tag(0x0010, 0x0010)).unwrap();
-        assert_eq!(elem1, Some(&another_patient_name));
+        // deep path with keywords
+        let e = obj
+            .element_at_path(
+                "PerFrameFunctionalGroupsSequence[2].PlanePositionSequence[0].ImagePositionPatient",
+            )
+            .unwrap();
+        assert_eq!(e.to_multi_float64().unwrap(), vec![0., 0., 5.]);
 
-        // try a missing element, should return None
-        assert_eq!(obj.element_opt(Tag(0x0010, 0x0020)).unwrap(), None);
-    }
+        // keywords and tags mixed, implicit first item
+        let e = obj
+            .element_at_path("(5200,9230)[1].PlanePositionSequence.00200032")
+            .unwrap();
+        assert_eq!(e.to_multi_float64().unwrap(), vec![0., 0., 2.5]);
 
-    #[test]
-    fn inmem_object_get_by_name() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
-        );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        let elem1 = (&obj).element_by_name("PatientName").unwrap();
-        assert_eq!(elem1, &another_patient_name);
+        // wildcard
+        let z: Vec<_> = obj
+            .elements_at_path(
+                "PerFrameFunctionalGroupsSequence[*].PlanePositionSequence[0].ImagePositionPatient",
+            )
+            .unwrap()
+            .map(|e| e.to_multi_float64().unwrap()[2])
+            .collect();
+        assert_eq!(z, vec![0., 2.5, 5.]);
+        assert!(matches!(
+            obj.element_at_path(
+                "PerFrameFunctionalGroupsSequence[*].PlanePositionSequence.ImagePositionPatient"
+            ),
+            Err(AtPathError::UnexpectedWildcard {
+                segment_index: 0,
+                ..
+            })
+        ));
+
+        // bad index
+        let err = obj
+            .element_at_path(
+                "PerFrameFunctionalGroupsSequence[3].PlanePositionSequence.ImagePositionPatient",
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AtPathError::ItemOutOfRange {
+                segment_index: 0,
+                index: 3,
+                len: 3,
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .contains("PerFrameFunctionalGroupsSequence[3]"));
+
+        // traversing through a non-sequence
+        assert!(matches!(
+            obj.element_at_path("PatientName[0].PatientID"),
+            Err(AtPathError::NotASequence {
+                segment_index: 0,
+                ..
+            })
+        ));
+
+        // unknown keyword
+        let err = obj
+            .element_at_path("PerFrameFunctionalGroupsSequence.NotAKeyword")
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AtPathError::UnknownKey { segment_index: 1, ref segment, .. } if segment == "NotAKeyword"
+        ));
+
+        // missing element
+        assert!(matches!(
+            obj.element_at_path(
+                "PerFrameFunctionalGroupsSequence.PixelMeasuresSequence.PixelSpacing"
+            ),
+            Err(AtPathError::MissingElement {
+                segment_index: 1,
+                ..
+            })
+        ));
+
+        // bad syntax
+        assert!(matches!(
+            obj.element_at_path("PerFrameFunctionalGroupsSequence[x].PlanePositionSequence"),
+            Err(AtPathError::InvalidSegment {
+                segment_index: 0,
+                ..
+            })
+        ));
+        assert!(matches!(
+            obj.element_at_path("PerFrameFunctionalGroupsSequence[0]"),
+            Err(AtPathError::InvalidSegment {
+                segment_index: 0,
+                ..
+            })
+        ));
     }
 
+    /// Deep iteration visits elements in tag order
+    /// and descends into sequence items
+    /// before moving on to the next sibling element.
     #[test]
-    fn inmem_object_get_by_name_opt() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
+    fn inmem_iter_deep() {
+        // written out of order, with interleaved groups
+        let obj = crate::dicom_object! {
+            PatientName: "Doe^John",
+            Rows: 64_u16,
+            ReferencedImageSequence: [
+                {
+                    ReferencedSOPInstanceUID: "1.2.3.1",
+                    ReferencedSOPClassUID: "1.2.840.10008.5.1.4.1.1.2",
+                },
+                {
+                    ReferencedSOPInstanceUID: "1.2.3.2",
+                    ReferencedSOPClassUID: "1.2.840.10008.5.1.4.1.1.2",
+                },
+            ],
+            Modality: "CT",
+            StudyInstanceUID: "1.2.3",
+        };
+
+        let tags: Vec<_> = obj.iter().map(|e| e.tag()).collect();
+        assert_eq!(
+            tags,
+            vec![
+                tags::MODALITY,
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                tags::PATIENT_NAME,
+                tags::STUDY_INSTANCE_UID,
+                tags::ROWS,
+            ]
         );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        let elem1 = obj.element_by_name_opt("PatientName").unwrap();
-        assert_eq!(elem1, Some(&another_patient_name));
 
-        // try a missing element, should return None
-        assert_eq!(obj.element_by_name_opt("PatientID").unwrap(), None);
+        let visited: Vec<_> = obj
+            .iter_deep()
+            .map(|(depth, item_index, e)| (depth, item_index, e.tag()))
+            .collect();
+        assert_eq!(
+            visited,
+            vec![
+                (0, 0, tags::MODALITY),
+                (0, 0, tags::REFERENCED_IMAGE_SEQUENCE),
+                (1, 0, tags::REFERENCED_SOP_CLASS_UID),
+                (1, 0, tags::REFERENCED_SOP_INSTANCE_UID),
+                (1, 1, tags::REFERENCED_SOP_CLASS_UID),
+                (1, 1, tags::REFERENCED_SOP_INSTANCE_UID),
+                (0, 0, tags::PATIENT_NAME),
+                (0, 0, tags::STUDY_INSTANCE_UID),
+                (0, 0, tags::ROWS),
+            ]
+        );
+
+        // elements are borrowed from the object
+        let (_, _, e) = obj.iter_deep().nth(3).unwrap();
+        assert!(std::ptr::eq(
+            e,
+            obj.item(tags::REFERENCED_IMAGE_SEQUENCE, 0)
+                .unwrap()
+                .get(tags::REFERENCED_SOP_INSTANCE_UID)
+                .unwrap()
+        ));
+
+        // owned decomposition into elements
+        let elements: Vec<InMemElement> = obj.into_iter().collect();
+        assert_eq!(elements.len(), 5);
+        assert_eq!(elements[0].tag(), tags::MODALITY);
     }
 
+    /// Sequence items can be added, removed and modified,
+    /// even when the sequence and items were read with explicit lengths.
     #[test]
-    fn inmem_object_take_element() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
+    fn inmem_object_edit_items() {
+        #[rustfmt::skip]
+        let data_in: &[u8] = &[
+            // ReferencedImageSequence (0008,1140), length 60
+            0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00,
+            0x3C, 0x00, 0x00, 0x00,
+            // item, length 12
+            0xFE, 0xFF, 0x00, 0xE0, 0x0C, 0x00, 0x00, 0x00,
+            // ReferencedSOPInstanceUID (0008,1155)
+            0x08, 0x00, 0x55, 0x11, b'U', b'I', 0x04, 0x00,
+            b'1', b'.', b'1', 0x00,
+            // item, length 12
+            0xFE, 0xFF, 0x00, 0xE0, 0x0C, 0x00, 0x00, 0x00,
+            0x08, 0x00, 0x55, 0x11, b'U', b'I', 0x04, 0x00,
+            b'1', b'.', b'2', 0x00,
+            // item, length 12
+            0xFE, 0xFF, 0x00, 0xE0, 0x0C, 0x00, 0x00, 0x00,
+            0x08, 0x00, 0x55, 0x11, b'U', b'I', 0x04, 0x00,
+            b'1', b'.', b'3', 0x00,
+        ];
+
+        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2.1").unwrap();
+        let mut obj = InMemDicomObject::read_dataset_with_ts(data_in, ts).unwrap();
+
+        // append to a missing sequence
+        obj.append_item(
+            tags::REFERENCED_SERIES_SEQUENCE,
+            crate::dicom_object! { SeriesInstanceUID: "1.2.3.4" },
+        )
+        .unwrap();
+
+        // remove the middle item
+        let removed = obj.remove_item(tags::REFERENCED_IMAGE_SEQUENCE, 1).unwrap();
+        assert_eq!(
+            removed.string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap(),
+            "1.2"
         );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        let elem1 = obj.take_element(Tag(0x0010, 0x0010)).unwrap();
-        assert_eq!(elem1, another_patient_name);
         assert!(matches!(
-            obj.take_element(Tag(0x0010, 0x0010)),
-            Err(AccessError::NoSuchDataElementTag {
-                tag: Tag(0x0010, 0x0010),
+            obj.remove_item(tags::REFERENCED_IMAGE_SEQUENCE, 2),
+            Err(AttributeError::ItemOutOfRange {
+                index: 2,
+                len: 2,
                 ..
             })
         ));
-    }
 
-    #[test]
-    fn inmem_object_take_element_by_name() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
-        );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        let elem1 = obj.take_element_by_name("PatientName").unwrap();
-        assert_eq!(elem1, another_patient_name);
+        // mutate an attribute inside an item
+        obj.item_mut(tags::REFERENCED_IMAGE_SEQUENCE, 0)
+            .unwrap()
+            .put_str_as(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.345");
+        obj.insert_item(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            2,
+            crate::dicom_object! { ReferencedSOPInstanceUID: "1.4" },
+        )
+        .unwrap();
+
+        obj.put_str(tags::PATIENT_NAME, "Doe^John").unwrap();
         assert!(matches!(
-            obj.take_element_by_name("PatientName"),
-            Err(AccessByNameError::NoSuchDataElementAlias {
-                tag: Tag(0x0010, 0x0010),
-                alias,
-                ..
-            }) if alias == "PatientName"));
-    }
+            obj.append_item(tags::PATIENT_NAME, InMemDicomObject::new_empty()),
+            Err(AttributeError::NotASequenceAttribute { .. })
+        ));
+        assert!(matches!(
+            obj.remove_item(tags::REFERENCED_INSTANCE_SEQUENCE, 0),
+            Err(AttributeError::MissingAttribute { .. })
+        ));
 
-    #[test]
-    fn inmem_object_remove_element() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
+        // write and read back
+        let mut data_out = Vec::new();
+        obj.write_dataset_with_ts(&mut data_out, ts).unwrap();
+        let obj = InMemDicomObject::read_dataset_with_ts(&data_out[..], ts).unwrap();
+
+        let uids: Vec<_> = obj
+            .items(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .iter()
+            .map(|item| item.string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap())
+            .collect();
+        assert_eq!(uids, vec!["1.2.345", "1.3", "1.4"]);
+        assert_eq!(
+            obj.item(tags::REFERENCED_SERIES_SEQUENCE, 0)
+                .unwrap()
+                .string(tags::SERIES_INSTANCE_UID)
+                .unwrap(),
+            "1.2.3.4"
         );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        assert!(obj.remove_element(Tag(0x0010, 0x0010)));
-        assert_eq!(obj.remove_element(Tag(0x0010, 0x0010)), false);
     }
 
+    /// Private attributes can be stripped from the whole data set,
+    /// and the result can be encoded and decoded back.
     #[test]
-    fn inmem_object_remove_element_by_name() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
+    fn inmem_object_retain_deep() {
+        let mut obj = crate::dicom_object! {
+            PatientName: "Doe^John",
+            (0x0009, 0x0010): "ACME",
+            (0x0009, 0x1001): "secret",
+            ReferencedImageSequence: [
+                {
+                    ReferencedSOPInstanceUID: "1.2.3",
+                    (0x0011, 0x0010): "ACME",
+                    (0x0011, 0x1001): "secret",
+                },
+                { ReferencedSOPInstanceUID: "1.2.4" },
+            ],
+            SliceThickness: "1.5",
+        };
+        obj.put(DataElement::new(
+            Tag(0x0018, 0x0000),
+            VR::UL,
+            PrimitiveValue::from(10_u32),
+        ));
+        // (note: undefined lengths never compare equal,
+        // so objects are compared by their elements)
+        let elements = |obj: &InMemDicomObject| -> Vec<_> {
+            obj.iter_deep()
+                .map(|(depth, item, e)| (depth, item, e.tag(), e.value().primitive().cloned()))
+                .collect()
+        };
+        let original = elements(&obj);
+
+        let is_public = |e: &InMemElement| !e.tag().is_private();
+        let filtered = obj.filtered(is_public);
+        assert_eq!(elements(&obj), original);
+
+        obj.retain_deep(is_public);
+        assert_eq!(elements(&obj), elements(&filtered));
+        let tags: Vec<_> = obj.iter_deep().map(|(_, _, e)| e.tag()).collect();
+        assert_eq!(
+            tags,
+            vec![
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                tags::REFERENCED_SOP_INSTANCE_UID,
+                tags::REFERENCED_SOP_INSTANCE_UID,
+                tags::PATIENT_NAME,
+                Tag(0x0018, 0x0000),
+                tags::SLICE_THICKNESS,
+            ]
         );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        assert!(obj.remove_element_by_name("PatientName").unwrap());
-        assert_eq!(obj.remove_element_by_name("PatientName").unwrap(), false);
+
+        // group length remains until the group is emptied
+        obj.remove_orphan_group_lengths();
+        assert!(obj.contains(Tag(0x0018, 0x0000)));
+        obj.retain_deep(|e| e.tag() != tags::SLICE_THICKNESS);
+        obj.remove_orphan_group_lengths();
+        assert!(!obj.contains(Tag(0x0018, 0x0000)));
+
+        // re-encode and decode
+        let ts = TransferSyntaxRegistry.get("1.2.840.10008.1.2.1").unwrap();
+        let mut data = Vec::new();
+        obj.write_dataset_with_ts(&mut data, ts).unwrap();
+        let obj2 = InMemDicomObject::read_dataset_with_ts(&data[..], ts).unwrap();
+        assert_eq!(obj2.len(), 2);
+        assert_eq!(
+            obj2.items(tags::REFERENCED_IMAGE_SEQUENCE).unwrap().len(),
+            2
+        );
+        assert!(obj2.iter_deep().all(|(_, _, e)| !e.tag().is_private()));
     }
 
     /// Elements are traversed in tag order.
@@ -2682,6 +5700,7 @@ mod tests {
             false,
             Length::UNDEFINED,
             None,
+            DuplicatePolicy::default(),
         )
         .unwrap();
 
@@ -2798,6 +5817,7 @@ mod tests {
             false,
             Length::UNDEFINED,
             None,
+            DuplicatePolicy::default(),
         )
         .unwrap();
 
@@ -2906,6 +5926,7 @@ mod tests {
             false,
             Length::UNDEFINED,
             None,
+            DuplicatePolicy::default(),
         )
         .unwrap();
 
@@ -3857,4 +6878,418 @@ mod tests {
             "No space available in group 0x0009"
         );
     }
+
+    #[test]
+    fn read_file_with_duplicate_policy() {
+        use crate::{DuplicatePolicy, OpenFileOptions, ReadOptions};
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, "1.2.840.10008.5.1.4.1.1.7"),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+        ]);
+        let file = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax("1.2.840.10008.1.2.1"))
+            .unwrap();
+        let mut data = Vec::new();
+        file.write_all(&mut data).unwrap();
+        // a second Patient Name at the end of the data set
+        #[rustfmt::skip]
+        data.extend_from_slice(&[
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00,
+            b'R', b'o', b'e', b'^', b'J', b'a', b'n', b'e',
+        ]);
+
+        let options = |duplicates| {
+            OpenFileOptions::new().read_options(ReadOptions::new().duplicates(duplicates))
+        };
+
+        let obj = options(DuplicatePolicy::KeepLast)
+            .from_reader(&data[..])
+            .unwrap();
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Roe^Jane"
+        );
+
+        let obj = options(DuplicatePolicy::KeepFirst)
+            .from_reader(&data[..])
+            .unwrap();
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Doe^John"
+        );
+
+        let err = options(DuplicatePolicy::Fail)
+            .from_reader(&data[..])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ReadError::DuplicateElement {
+                tag: tags::PATIENT_NAME,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn read_file_collecting_issues() {
+        use crate::{IssueCollector, OpenFileOptions, ReadOptions, Severity};
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, "1.2.840.10008.5.1.4.1.1.7"),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+        ]);
+        let file = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax("1.2.840.10008.1.2.1"))
+            .unwrap();
+        let mut data = Vec::new();
+        file.write_all(&mut data).unwrap();
+        // (0009,1010) OB of 128 bytes, longer than the maximum of 64
+        #[rustfmt::skip]
+        data.extend_from_slice(&[
+            0x09, 0x00, 0x10, 0x10, b'O', b'B', 0x00, 0x00,
+            0x80, 0x00, 0x00, 0x00,
+        ]);
+        data.extend_from_slice(&[0x55; 128]);
+        #[rustfmt::skip]
+        data.extend_from_slice(&[
+            // (0011,0010) with an unknown VR "ZZ"
+            0x11, 0x00, 0x10, 0x00, b'Z', b'Z', 0x00, 0x00,
+            0x02, 0x00, 0x00, 0x00,
+            b'A', b'B',
+            // (0040,A730) ContentSequence, undefined length
+            0x40, 0x00, 0x30, 0xA7, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // item of 8 bytes, but with 12 bytes of content
+            0xFE, 0xFF, 0x00, 0xE0, 0x08, 0x00, 0x00, 0x00,
+            // (0040,A040) ValueType: "TEXT"
+            0x40, 0x00, 0x40, 0xA0, b'C', b'S', 0x04, 0x00,
+            b'T', b'E', b'X', b'T',
+            // sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ]);
+
+        let options = ReadOptions::new().max_value_length(64);
+        assert!(OpenFileOptions::new()
+            .read_options(options.clone())
+            .from_reader(&data[..])
+            .is_err());
+
+        let collector = IssueCollector::new();
+        let obj = OpenFileOptions::new()
+            .read_options(options.collect_issues(&collector))
+            .from_reader(&data[..])
+            .unwrap();
+
+        let issues = collector.take();
+        assert_eq!(
+            issues.iter().map(|i| i.severity()).collect::<Vec<_>>(),
+            vec![Severity::Error, Severity::Warning, Severity::Error],
+        );
+        assert_eq!(issues[0].tag(), Some(Tag(0x0009, 0x1010)));
+        assert_eq!(issues[1].tag(), Some(Tag(0x0011, 0x0010)));
+        assert_eq!(issues[2].path()[0].sequence, tags::CONTENT_SEQUENCE);
+
+        // the rest of the data set is available
+        assert_eq!(
+            obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Doe^John"
+        );
+        assert!(obj.element_opt(Tag(0x0009, 0x1010)).unwrap().is_none());
+        assert_eq!(
+            obj.element(Tag(0x0011, 0x0010))
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+            &b"AB"[..]
+        );
+        let items = obj
+            .element(tags::CONTENT_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0]
+                .element(tags::VALUE_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "TEXT"
+        );
+    }
+
+    #[test]
+    fn read_truncated_file_partially() {
+        use crate::OpenFileOptions;
+        use dicom_parser::dataset::read::Error as DataSetReadError;
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, "1.2.840.10008.5.1.4.1.1.7"),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "2.25.4"),
+                ])]),
+            ),
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(2_u16)),
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OW,
+                PrimitiveValue::U16(vec![1, 2, 3, 4, 5, 6, 7, 8].into()),
+            ),
+        ]);
+        let file = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax("1.2.840.10008.1.2.1"))
+            .unwrap();
+        let mut data = Vec::new();
+        file.write_all(&mut data).unwrap();
+
+        // cut off 10 of the 16 bytes of pixel data
+        let truncated = &data[..data.len() - 10];
+        assert!(OpenFileOptions::new().from_reader(truncated).is_err());
+        let partial = OpenFileOptions::new()
+            .from_reader_partial(truncated)
+            .unwrap();
+        assert!(!partial.is_complete());
+        match partial.error() {
+            Some(ReadError::ReadToken { source }) => assert!(
+                matches!(
+                    source.without_context(),
+                    DataSetReadError::PrematureEnd {
+                        tag: Some(tags::PIXEL_DATA),
+                        expected: 16,
+                        got: 6,
+                        ..
+                    }
+                ),
+                "{:?}",
+                source
+            ),
+            e => panic!("unexpected error {:?}", e),
+        }
+        let obj = partial.into_object();
+        assert_eq!(obj.element(tags::ROWS).unwrap().to_int::<u16>().unwrap(), 2);
+        assert!(obj.element(tags::REFERENCED_IMAGE_SEQUENCE).is_ok());
+        assert!(obj.element(tags::PIXEL_DATA).is_err());
+
+        // cut off within the sequence item
+        let pos = data
+            .windows(6)
+            .position(|w| w == b"2.25.4")
+            .expect("referenced SOP instance UID should be in the data");
+        let partial = OpenFileOptions::new()
+            .from_reader_partial(&data[..pos + 2])
+            .unwrap();
+        assert!(!partial.is_complete());
+        let obj = partial.into_object();
+        assert_eq!(
+            obj.element(tags::SOP_INSTANCE_UID)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "2.25.123"
+        );
+        let items = obj
+            .element(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].element(tags::REFERENCED_SOP_INSTANCE_UID).is_err());
+
+        // cut off right after an element: nothing is missing
+        let pos = data.len() - 28;
+        let partial = OpenFileOptions::new()
+            .from_reader_partial(&data[..pos])
+            .unwrap();
+        assert!(partial.is_complete());
+        assert!(partial.object().element(tags::ROWS).is_ok());
+    }
+
+    fn canonical_fixture() -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(Tag(0x0008, 0x0000), VR::UL, PrimitiveValue::from(0_u32)),
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, "1.2.840.10008.5.1.4.1.1.7"),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.123"),
+            DataElement::new(tags::MODALITY, VR::CS, "OT"),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "2.25.4"),
+                ])]),
+            ),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(64_u16)),
+        ])
+    }
+
+    /// Write the object to a file in the given transfer syntax
+    /// and read it back.
+    fn stored_as(obj: InMemDicomObject, ts: &str) -> crate::DefaultDicomObject {
+        let file = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(ts))
+            .unwrap();
+        let mut data = Vec::new();
+        file.write_all(&mut data).unwrap();
+        FileDicomObject::from_reader(&data[..]).unwrap()
+    }
+
+    #[test]
+    fn to_vec_allocates_once() {
+        use dicom_transfer_syntax_registry::entries::{
+            EXPLICIT_VR_BIG_ENDIAN, EXPLICIT_VR_LITTLE_ENDIAN, IMPLICIT_VR_LITTLE_ENDIAN,
+        };
+
+        let mut obj = canonical_fixture();
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new(vec![], vec![vec![0x55; 31], vec![0xAA; 8]]),
+        ));
+        let estimate = obj.encoded_len_estimate();
+        for ts in [
+            IMPLICIT_VR_LITTLE_ENDIAN.erased(),
+            EXPLICIT_VR_LITTLE_ENDIAN.erased(),
+            EXPLICIT_VR_BIG_ENDIAN.erased(),
+        ] {
+            let bytes = obj.to_vec(&ts, &WriteOptions::default()).unwrap();
+            assert!(bytes.len() <= estimate, "{} > {}", bytes.len(), estimate);
+            assert_eq!(bytes.capacity(), estimate);
+
+            // group lengths are stripped by default
+            let read_back = InMemDicomObject::from_slice(&bytes, &ts).unwrap();
+            assert!(read_back.get(Tag(0x0008, 0x0000)).is_none());
+            assert_eq!(
+                read_back.get(tags::MODALITY).unwrap().to_str().unwrap(),
+                "OT"
+            );
+        }
+
+        let file = canonical_fixture()
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .transfer_syntax("1.2.840.10008.1.2.1")
+                    .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7"),
+            )
+            .unwrap();
+        let bytes = file.to_file_vec(&WriteOptions::default()).unwrap();
+        assert_eq!(&bytes[128..132], b"DICM");
+        let (meta, read_back) = crate::from_slice(&bytes).unwrap();
+        assert_eq!(meta.transfer_syntax(), "1.2.840.10008.1.2.1");
+        assert_eq!(
+            read_back.get(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Doe^John"
+        );
+    }
+
+    #[test]
+    fn canonical_bytes_ignore_storage() {
+        let implicit_le = stored_as(canonical_fixture(), "1.2.840.10008.1.2");
+        let explicit_be = stored_as(canonical_fixture(), "1.2.840.10008.1.2.2");
+        assert_ne!(implicit_le.meta(), explicit_be.meta());
+
+        let canonical = implicit_le.to_canonical_bytes().unwrap();
+        assert_eq!(canonical, explicit_be.to_canonical_bytes().unwrap());
+        assert_eq!(canonical, canonical_fixture().to_canonical_bytes().unwrap());
+
+        // the canonical form is a valid data set with explicit lengths
+        // and without group lengths
+        let ts = dicom_transfer_syntax_registry::entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        let read_back = InMemDicomObject::read_dataset_with_ts(&canonical[..], &ts).unwrap();
+        assert!(read_back.get(Tag(0x0008, 0x0000)).is_none());
+        assert_eq!(
+            read_back
+                .get(tags::SOP_INSTANCE_UID)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "2.25.123"
+        );
+        let seq = read_back.get(tags::REFERENCED_IMAGE_SEQUENCE).unwrap();
+        assert!(seq.length().is_defined());
+        assert!(seq.items().unwrap()[0].length().is_defined());
+        assert!(!canonical
+            .windows(4)
+            .any(|w| w == [0xFE, 0xFF, 0xDD, 0xE0] || w == [0xFE, 0xFF, 0x0D, 0xE0]));
+
+        let mut changed = canonical_fixture();
+        changed.put(DataElement::new(tags::MODALITY, VR::CS, "CT"));
+        assert_ne!(canonical, changed.to_canonical_bytes().unwrap());
+    }
+
+    #[test]
+    fn canonical_bytes_normalize_vr() {
+        let mut obj = canonical_fixture();
+        obj.put(DataElement::new(Tag(0x0009, 0x0010), VR::LO, "ACME 1.0"));
+        obj.put(DataElement::new(Tag(0x0009, 0x1001), VR::LO, "secret"));
+        obj.put(DataElement::new(
+            tags::COLUMNS,
+            VR::US,
+            PrimitiveValue::from(2_u16),
+        ));
+        obj.put(DataElement::new(
+            tags::BITS_ALLOCATED,
+            VR::US,
+            PrimitiveValue::from(8_u16),
+        ));
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            dicom_value!(U8, [1, 2, 3, 4]),
+        ));
+
+        // decoded as OW and UN
+        let implicit_le = stored_as(obj.clone(), "1.2.840.10008.1.2");
+        assert_eq!(implicit_le.get(tags::PIXEL_DATA).unwrap().vr(), VR::OW);
+        assert_eq!(implicit_le.get(Tag(0x0009, 0x1001)).unwrap().vr(), VR::UN);
+        // decoded as OB and LO
+        let explicit_le = stored_as(obj.clone(), "1.2.840.10008.1.2.1");
+        assert_eq!(explicit_le.get(tags::PIXEL_DATA).unwrap().vr(), VR::OB);
+        assert_eq!(explicit_le.get(Tag(0x0009, 0x1001)).unwrap().vr(), VR::LO);
+
+        let canonical = implicit_le.to_canonical_bytes().unwrap();
+        assert_eq!(canonical, explicit_le.to_canonical_bytes().unwrap());
+        assert_eq!(canonical, obj.to_canonical_bytes().unwrap());
+
+        let ts = dicom_transfer_syntax_registry::entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        let read_back = InMemDicomObject::read_dataset_with_ts(&canonical[..], &ts).unwrap();
+        assert_eq!(read_back.get(tags::PIXEL_DATA).unwrap().vr(), VR::OB);
+        assert_eq!(read_back.get(Tag(0x0009, 0x1001)).unwrap().vr(), VR::UN);
+
+        // 16-bit pixel data is always OW
+        obj.put(DataElement::new(
+            tags::BITS_ALLOCATED,
+            VR::US,
+            PrimitiveValue::from(16_u16),
+        ));
+        let explicit_le = stored_as(obj.clone(), "1.2.840.10008.1.2.1");
+        let canonical = explicit_le.to_canonical_bytes().unwrap();
+        assert_eq!(
+            canonical,
+            stored_as(obj, "1.2.840.10008.1.2")
+                .to_canonical_bytes()
+                .unwrap()
+        );
+        let read_back = InMemDicomObject::read_dataset_with_ts(&canonical[..], &ts).unwrap();
+        assert_eq!(read_back.get(tags::PIXEL_DATA).unwrap().vr(), VR::OW);
+    }
+
+    #[cfg(feature = "content-hash")]
+    #[test]
+    fn content_hash_ignores_storage() {
+        let implicit_le = stored_as(canonical_fixture(), "1.2.840.10008.1.2");
+        let explicit_be = stored_as(canonical_fixture(), "1.2.840.10008.1.2.2");
+        let hash = implicit_le.content_hash().unwrap();
+        assert_eq!(hash, explicit_be.content_hash().unwrap());
+
+        let mut changed = canonical_fixture();
+        changed.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^Jane"));
+        assert_ne!(hash, changed.content_hash().unwrap());
+    }
 }
@@ -0,0 +1,402 @@
+//! Merging of DICOM objects.
+//!
+//! See [`InMemDicomObject::merge`] for more information.
+//!
+//! [`InMemDicomObject::merge`]: crate::InMemDicomObject::merge
+use dicom_core::header::Header;
+use dicom_core::ops::{AttributeSelector, AttributeSelectorStep};
+use dicom_core::{DataDictionary, Tag};
+use snafu::{Backtrace, Snafu};
+
+use crate::mem::{InMemDicomObject, InMemElement};
+
+/// An error which may occur when merging DICOM objects.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+#[snafu(visibility(pub(crate)))]
+pub enum MergeError {
+    /// Conflicting values for attribute {path}
+    Conflict {
+        path: AttributeSelector,
+        backtrace: Backtrace,
+    },
+}
+
+/// What to do when an attribute exists in both objects
+/// with a different value.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum ConflictPolicy {
+    /// Keep the attribute of the object being merged into
+    PreferSelf,
+    /// Replace the attribute with the one from the other object
+    #[default]
+    PreferOther,
+    /// Fail the merge without modifying the object
+    Error,
+}
+
+/// How to merge data set sequences present in both objects.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum SequencePolicy {
+    /// Merge items with the same index recursively,
+    /// appending any additional items of the other object
+    #[default]
+    ItemWise,
+    /// Treat sequences as any other attribute,
+    /// so that conflicting sequences are replaced or kept as a whole
+    /// according to the [conflict policy](ConflictPolicy)
+    Replace,
+}
+
+/// The policy for merging one DICOM object into another.
+///
+/// By default,
+/// attributes of the other object take precedence,
+/// sequences are merged item by item,
+/// and file meta group attributes (group `0002`) are not merged.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_object::merge::{ConflictPolicy, MergePolicy, SequencePolicy};
+/// let policy = MergePolicy::new(ConflictPolicy::PreferSelf)
+///     .sequences(SequencePolicy::Replace);
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct MergePolicy {
+    conflicts: ConflictPolicy,
+    sequences: SequencePolicy,
+    include_meta: bool,
+}
+
+impl MergePolicy {
+    /// Create a merge policy with the given conflict policy,
+    /// and defaults for everything else.
+    pub fn new(conflicts: ConflictPolicy) -> Self {
+        MergePolicy {
+            conflicts,
+            ..Default::default()
+        }
+    }
+
+    /// Set the policy for merging sequences present in both objects.
+    pub fn sequences(mut self, sequences: SequencePolicy) -> Self {
+        self.sequences = sequences;
+        self
+    }
+
+    /// Set whether to merge file meta group attributes (group `0002`).
+    pub fn include_meta(mut self, include_meta: bool) -> Self {
+        self.include_meta = include_meta;
+        self
+    }
+
+    fn excludes(&self, tag: Tag) -> bool {
+        !self.include_meta && tag.group() == 0x0002
+    }
+
+    fn merges_items<D>(&self, e1: &InMemElement<D>, e2: &InMemElement<D>) -> bool {
+        self.sequences == SequencePolicy::ItemWise && e1.items().is_some() && e2.items().is_some()
+    }
+}
+
+/// Merge `other` into `target` according to the given policy.
+pub(crate) fn merge<D>(
+    target: &mut InMemDicomObject<D>,
+    other: InMemDicomObject<D>,
+    policy: &MergePolicy,
+) -> Result<(), MergeError>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    if policy.conflicts == ConflictPolicy::Error {
+        // look for conflicts first so that the object is left untouched
+        if let Some(path) = find_conflict(target, &other, policy, &mut Vec::new()) {
+            return ConflictSnafu { path }.fail();
+        }
+    }
+    merge_into(target, other, policy);
+    Ok(())
+}
+
+/// Find the first attribute which would conflict
+/// when merging `other` into `target`.
+fn find_conflict<D>(
+    target: &InMemDicomObject<D>,
+    other: &InMemDicomObject<D>,
+    policy: &MergePolicy,
+    parent: &mut Vec<AttributeSelectorStep>,
+) -> Option<AttributeSelector>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    for e2 in other {
+        let tag = e2.tag();
+        if policy.excludes(tag) {
+            continue;
+        }
+        let e1 = match target.get(tag) {
+            Some(e1) => e1,
+            None => continue,
+        };
+
+        if policy.merges_items(e1, e2) {
+            let items = e1.items().unwrap().iter().zip(e2.items().unwrap());
+            for (i, (item1, item2)) in items.enumerate() {
+                parent.push(AttributeSelectorStep::Nested {
+                    tag,
+                    item: i as u32,
+                });
+                let conflict = find_conflict(item1, item2, policy, parent);
+                parent.pop();
+                if conflict.is_some() {
+                    return conflict;
+                }
+            }
+        } else if !same_element(e1, e2) {
+            return AttributeSelector::new(
+                parent
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(AttributeSelectorStep::Tag(tag))),
+            );
+        }
+    }
+    None
+}
+
+/// Merge `other` into `target`,
+/// assuming that conflicts do not have to be reported.
+fn merge_into<D>(target: &mut InMemDicomObject<D>, other: InMemDicomObject<D>, policy: &MergePolicy)
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    for e2 in other {
+        let tag = e2.tag();
+        if policy.excludes(tag) {
+            continue;
+        }
+        let e1 = match target.get(tag) {
+            Some(e1) => e1,
+            None => {
+                target.put(e2);
+                continue;
+            }
+        };
+
+        if policy.merges_items(e1, &e2) {
+            let (_, value) = e2.into_parts();
+            let items2 = value.into_items().unwrap_or_default();
+            let items1 = target.items_mut(tag).unwrap();
+            for (i, item2) in items2.into_iter().enumerate() {
+                match items1.get_mut(i) {
+                    Some(item1) => merge_into(item1, item2, policy),
+                    None => items1.push(item2),
+                }
+            }
+        } else if !same_element(e1, &e2) && policy.conflicts != ConflictPolicy::PreferSelf {
+            target.put(e2);
+        }
+    }
+}
+
+fn same_element<D>(e1: &InMemElement<D>, e2: &InMemElement<D>) -> bool {
+    e1.vr() == e2.vr() && e1.value() == e2.value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dicom_object;
+    use dicom_dictionary_std::tags;
+
+    fn full_dataset() -> InMemDicomObject {
+        dicom_object! {
+            (0x0002, 0x0010): "1.2.840.10008.1.2.1",
+            SOPInstanceUID: "2.25.1",
+            PatientName: "Doe^John",
+            PatientID: "ID0001",
+            PatientSex: "M",
+            Rows: 512_u16,
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "2.25.10", ReferencedFrameNumber: "1" },
+                { ReferencedSOPInstanceUID: "2.25.11" },
+            ],
+        }
+    }
+
+    fn demographics_patch() -> InMemDicomObject {
+        dicom_object! {
+            (0x0002, 0x0010): "1.2.840.10008.1.2",
+            PatientName: "Doe^Jane",
+            PatientSex: "M",
+            PatientBirthDate: "19700101",
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "2.25.20" },
+                {},
+                { ReferencedSOPInstanceUID: "2.25.22" },
+            ],
+        }
+    }
+
+    fn assert_untouched(obj: &InMemDicomObject) {
+        assert_eq!(obj.string(tags::SOP_INSTANCE_UID).unwrap(), "2.25.1");
+        assert_eq!(obj.string(tags::PATIENT_ID).unwrap(), "ID0001");
+        assert_eq!(obj.string(tags::PATIENT_SEX).unwrap(), "M");
+        assert_eq!(obj.u16(tags::ROWS).unwrap(), 512);
+        assert_eq!(
+            obj.string(Tag(0x0002, 0x0010)).unwrap(),
+            "1.2.840.10008.1.2.1"
+        );
+    }
+
+    #[test]
+    fn merge_prefer_other() {
+        let mut obj = full_dataset();
+        obj.merge(
+            demographics_patch(),
+            MergePolicy::new(ConflictPolicy::PreferOther),
+        )
+        .unwrap();
+
+        assert_untouched(&obj);
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), "Doe^Jane");
+        assert_eq!(obj.string(tags::PATIENT_BIRTH_DATE).unwrap(), "19700101");
+
+        let items = obj
+            .get(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(
+            items[0].string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap(),
+            "2.25.20"
+        );
+        // attributes absent from the patch item are kept
+        assert_eq!(items[0].string(tags::REFERENCED_FRAME_NUMBER).unwrap(), "1");
+        assert_eq!(
+            items[1].string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap(),
+            "2.25.11"
+        );
+        assert_eq!(
+            items[2].string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap(),
+            "2.25.22"
+        );
+    }
+
+    #[test]
+    fn merge_prefer_self() {
+        let mut obj = full_dataset();
+        obj.merge(
+            demographics_patch(),
+            MergePolicy::new(ConflictPolicy::PreferSelf),
+        )
+        .unwrap();
+
+        assert_untouched(&obj);
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), "Doe^John");
+        // new attributes are still added
+        assert_eq!(obj.string(tags::PATIENT_BIRTH_DATE).unwrap(), "19700101");
+
+        let items = obj
+            .get(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(
+            items[0].string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap(),
+            "2.25.10"
+        );
+        assert_eq!(
+            items[2].string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap(),
+            "2.25.22"
+        );
+    }
+
+    #[test]
+    fn merge_error_on_conflict() {
+        let mut obj = full_dataset();
+        let err = obj
+            .merge(
+                demographics_patch(),
+                MergePolicy::new(ConflictPolicy::Error),
+            )
+            .unwrap_err();
+        // conflicts inside sequence items are reported with their full path
+        let MergeError::Conflict { path, .. } = err;
+        assert_eq!(
+            path,
+            AttributeSelector::from((
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                0,
+                tags::REFERENCED_SOP_INSTANCE_UID
+            ))
+        );
+
+        // nothing was merged
+        assert_untouched(&obj);
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), "Doe^John");
+        assert!(obj.get(tags::PATIENT_BIRTH_DATE).is_none());
+        assert_eq!(
+            obj.get(tags::REFERENCED_IMAGE_SEQUENCE)
+                .unwrap()
+                .items()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let mut patch = demographics_patch();
+        patch.remove_element(tags::REFERENCED_IMAGE_SEQUENCE);
+        let err = obj
+            .merge(patch, MergePolicy::new(ConflictPolicy::Error))
+            .unwrap_err();
+        let MergeError::Conflict { path, .. } = err;
+        assert_eq!(path, AttributeSelector::from(tags::PATIENT_NAME));
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), "Doe^John");
+
+        // merging a patch without conflicts succeeds
+        let patch = dicom_object! {
+            PatientName: "Doe^John",
+            PatientBirthDate: "19700101",
+        };
+        obj.merge(patch, MergePolicy::new(ConflictPolicy::Error))
+            .unwrap();
+        assert_untouched(&obj);
+        assert_eq!(obj.string(tags::PATIENT_BIRTH_DATE).unwrap(), "19700101");
+    }
+
+    #[test]
+    fn merge_replace_sequences() {
+        let mut obj = full_dataset();
+        let policy =
+            MergePolicy::new(ConflictPolicy::PreferOther).sequences(SequencePolicy::Replace);
+        obj.merge(demographics_patch(), policy).unwrap();
+
+        assert_untouched(&obj);
+        let items = obj
+            .get(tags::REFERENCED_IMAGE_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(items.len(), 3);
+        assert!(items[0].get(tags::REFERENCED_FRAME_NUMBER).is_none());
+        assert!(items[1].get(tags::REFERENCED_SOP_INSTANCE_UID).is_none());
+    }
+
+    #[test]
+    fn merge_include_meta() {
+        let mut obj = full_dataset();
+        let policy = MergePolicy::new(ConflictPolicy::PreferOther).include_meta(true);
+        obj.merge(demographics_patch(), policy).unwrap();
+        assert_eq!(
+            obj.string(Tag(0x0002, 0x0010)).unwrap(),
+            "1.2.840.10008.1.2"
+        );
+    }
+}
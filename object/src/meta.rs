@@ -9,6 +9,10 @@ use dicom_dictionary_std::tags;
 use dicom_encoding::decode::{self, DecodeFrom};
 use dicom_encoding::encode::explicit_le::ExplicitVRLittleEndianEncoder;
 use dicom_encoding::encode::EncoderFor;
+use dicom_encoding::text::repertoire::{
+    check_repertoire, RepertoireValidation, RepertoireViolation,
+};
+use dicom_encoding::text::uid::{validate_uid_lenient, UidError};
 use dicom_encoding::text::{self, TextCodec};
 use dicom_encoding::TransferSyntax;
 use dicom_parser::dataset::{DataSetWriter, IntoTokens};
@@ -104,6 +108,23 @@ pub enum Error {
         #[snafu(backtrace)]
         source: dicom_parser::dataset::write::Error,
     },
+
+    /// A UID in the file meta group is not a valid UID.
+    #[snafu(display("Invalid UID in `{}`", alias))]
+    InvalidUid {
+        alias: &'static str,
+        source: UidError,
+        backtrace: Backtrace,
+    },
+
+    /// An application entity title in the file meta group
+    /// is too long or contains characters which are not allowed.
+    #[snafu(display("Invalid application entity title in `{}`: {}", alias, violation))]
+    InvalidAeTitle {
+        alias: &'static str,
+        violation: RepertoireViolation,
+        backtrace: Backtrace,
+    },
 }
 
 type Result<T> = std::result::Result<T, Error>;
@@ -164,17 +185,33 @@ pub struct FileMetaTable {
     */
 }
 
+/// Utility function for reading the body of a DICOM element.
+///
+/// The buffer only grows as data is actually read,
+/// so that a bogus element length cannot trigger
+/// a large allocation on its own.
+fn read_body<S>(source: &mut S, len: u32) -> Result<Vec<u8>>
+where
+    S: Read,
+{
+    let mut v = Vec::new();
+    source
+        .take(u64::from(len))
+        .read_to_end(&mut v)
+        .context(ReadValueDataSnafu)?;
+    if v.len() < len as usize {
+        return Err(std::io::ErrorKind::UnexpectedEof.into()).context(ReadValueDataSnafu);
+    }
+    Ok(v)
+}
+
 /// Utility function for reading the body of the DICOM element as a UID.
 fn read_str_body<'s, S: 's, T>(source: &'s mut S, text: &T, len: u32) -> Result<String>
 where
     S: Read,
     T: TextCodec,
 {
-    let mut v = Vec::new();
-    v.try_reserve_exact(len as usize)
-        .context(AllocationSizeSnafu)?;
-    v.resize(len as usize, 0);
-    source.read_exact(&mut v).context(ReadValueDataSnafu)?;
+    let v = read_body(source, len)?;
 
     text.decode(&v)
         .context(DecodeTextSnafu { name: text.name() })
@@ -183,8 +220,8 @@ where
 impl FileMetaTable {
     /// Construct a file meta group table
     /// by parsing a DICOM data set from a reader.
-    pub fn from_reader<R: Read>(file: R) -> Result<Self> {
-        FileMetaTable::read_from(file)
+    pub fn from_reader<R: Read>(mut file: R) -> Result<Self> {
+        FileMetaTable::read_from(&mut file)
     }
 
     /// Getter for the transfer syntax UID,
@@ -237,6 +274,32 @@ impl FileMetaTable {
         self.update_information_group_length();
     }
 
+    /// Set the implementation class UID and version name of the table,
+    /// identifying the application which writes the file.
+    ///
+    /// The values are padded to even length.
+    /// The information group length field is automatically recalculated.
+    pub fn set_implementation<T>(&mut self, class_uid: T, version_name: Option<T>)
+    where
+        T: Into<String>,
+    {
+        self.implementation_class_uid = ui_padded(class_uid);
+        self.implementation_version_name = version_name.map(txt_padded);
+        self.update_information_group_length();
+    }
+
+    /// Set the source application entity title of the table.
+    ///
+    /// The value is padded to even length.
+    /// The information group length field is automatically recalculated.
+    pub fn set_source_application_entity_title<T>(&mut self, ae_title: Option<T>)
+    where
+        T: Into<String>,
+    {
+        self.source_application_entity_title = ae_title.map(txt_padded);
+        self.update_information_group_length();
+    }
+
     /// Calculate the expected file meta group length
     /// according to the file meta attributes currently set,
     /// and assign it to the field `information_group_length`.
@@ -476,7 +539,9 @@ impl FileMetaTable {
                 .unwrap_or(0)
     }
 
-    fn read_from<S: Read>(mut file: S) -> Result<Self> {
+    /// Read the file meta group from the given source,
+    /// which is erased so that this is only instantiated once.
+    fn read_from(mut file: &mut dyn Read) -> Result<Self> {
         let mut buff: [u8; 4] = [0; 4];
         {
             // check magic code
@@ -555,11 +620,7 @@ impl FileMetaTable {
                 }
                 Tag(0x0002, 0x0013) => {
                     // Implementation Version Name
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+                    let v = read_body(&mut file, elem_len)?;
 
                     builder.implementation_version_name(
                         text.decode(&v)
@@ -568,11 +629,7 @@ impl FileMetaTable {
                 }
                 Tag(0x0002, 0x0016) => {
                     // Source Application Entity Title
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+                    let v = read_body(&mut file, elem_len)?;
 
                     builder.source_application_entity_title(
                         text.decode(&v)
@@ -581,11 +638,7 @@ impl FileMetaTable {
                 }
                 Tag(0x0002, 0x0017) => {
                     // Sending Application Entity Title
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+                    let v = read_body(&mut file, elem_len)?;
 
                     builder.sending_application_entity_title(
                         text.decode(&v)
@@ -594,11 +647,7 @@ impl FileMetaTable {
                 }
                 Tag(0x0002, 0x0018) => {
                     // Receiving Application Entity Title
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+                    let v = read_body(&mut file, elem_len)?;
 
                     builder.receiving_application_entity_title(
                         text.decode(&v)
@@ -607,11 +656,7 @@ impl FileMetaTable {
                 }
                 Tag(0x0002, 0x0100) => {
                     // Private Information Creator UID
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+                    let v = read_body(&mut file, elem_len)?;
 
                     builder.private_information_creator_uid(
                         text.decode(&v)
@@ -620,11 +665,7 @@ impl FileMetaTable {
                 }
                 Tag(0x0002, 0x0102) => {
                     // Private Information
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+                    let v = read_body(&mut file, elem_len)?;
 
                     builder.private_information(v)
                 }
@@ -826,6 +867,11 @@ pub struct FileMetaTableBuilder {
     private_information_creator_uid: Option<String>,
     /// Private Information (OB)
     private_information: Option<Vec<u8>>,
+
+    /// How the UIDs are validated on build
+    uid_validation: RepertoireValidation,
+    /// How the application entity titles are validated on build
+    ae_title_validation: RepertoireValidation,
 }
 
 /// Ensure that the string is even lengthed, by adding a trailing character
@@ -966,7 +1012,40 @@ impl FileMetaTableBuilder {
         self
     }
 
+    /// Define how the UIDs in the table are validated
+    /// when the table is built (see [`validate_uid_lenient`]).
+    ///
+    /// The default is [`RepertoireValidation::Warn`],
+    /// which logs a warning for each invalid UID.
+    /// With [`RepertoireValidation::Strict`],
+    /// building a table with an invalid UID fails.
+    pub fn uid_validation(mut self, validation: RepertoireValidation) -> FileMetaTableBuilder {
+        self.uid_validation = validation;
+        self
+    }
+
+    /// Define how the application entity titles in the table
+    /// are validated when the table is built.
+    ///
+    /// A title must have at most 16 characters
+    /// from the default character repertoire,
+    /// excluding backslash and control characters,
+    /// and must not consist of spaces only.
+    ///
+    /// The default is [`RepertoireValidation::Warn`],
+    /// which logs a warning for each invalid title.
+    /// With [`RepertoireValidation::Strict`],
+    /// building a table with an invalid title fails.
+    pub fn ae_title_validation(mut self, validation: RepertoireValidation) -> FileMetaTableBuilder {
+        self.ae_title_validation = validation;
+        self
+    }
+
     /// Build the table.
+    ///
+    /// If no implementation class UID is defined,
+    /// the table identifies DICOM-rs as the implementation,
+    /// with [`IMPLEMENTATION_CLASS_UID`] and [`IMPLEMENTATION_VERSION_NAME`].
     pub fn build(self) -> Result<FileMetaTable> {
         let information_version = self.information_version.unwrap_or(
             // Missing information version, will assume (00H, 01H). See #28
@@ -993,6 +1072,41 @@ impl FileMetaTableBuilder {
             IMPLEMENTATION_CLASS_UID.to_string()
         });
 
+        let uids = [
+            ("MediaStorageSOPClassUID", &media_storage_sop_class_uid),
+            (
+                "MediaStorageSOPInstanceUID",
+                &media_storage_sop_instance_uid,
+            ),
+            ("TransferSyntax", &transfer_syntax),
+            ("ImplementationClassUID", &implementation_class_uid),
+        ];
+        for (alias, uid) in uids {
+            validate_meta_uid(self.uid_validation, alias, uid)?;
+        }
+        if let Some(uid) = &self.private_information_creator_uid {
+            validate_meta_uid(self.uid_validation, "PrivateInformationCreatorUID", uid)?;
+        }
+        let ae_titles = [
+            (
+                "SourceApplicationEntityTitle",
+                &self.source_application_entity_title,
+            ),
+            (
+                "SendingApplicationEntityTitle",
+                &self.sending_application_entity_title,
+            ),
+            (
+                "ReceivingApplicationEntityTitle",
+                &self.receiving_application_entity_title,
+            ),
+        ];
+        for (alias, ae_title) in ae_titles {
+            if let Some(ae_title) = ae_title {
+                validate_meta_ae_title(self.ae_title_validation, alias, ae_title)?;
+            }
+        }
+
         let mut table = FileMetaTable {
             // placeholder value which will be replaced on update
             information_group_length: 0x00,
@@ -1014,6 +1128,62 @@ impl FileMetaTableBuilder {
     }
 }
 
+/// Check a UID of the table as configured by the validation mode.
+pub(crate) fn validate_meta_uid(
+    validation: RepertoireValidation,
+    alias: &'static str,
+    uid: &str,
+) -> Result<()> {
+    if validation == RepertoireValidation::Off {
+        return Ok(());
+    }
+    match validate_uid_lenient(uid) {
+        Ok(()) => Ok(()),
+        Err(e) if validation == RepertoireValidation::Strict => {
+            Err(e).context(InvalidUidSnafu { alias })
+        }
+        Err(e) => {
+            tracing::warn!("Invalid UID in `{}` {:?}: {}", alias, uid, e);
+            Ok(())
+        }
+    }
+}
+
+/// Check an application entity title of the table
+/// as configured by the validation mode.
+pub(crate) fn validate_meta_ae_title(
+    validation: RepertoireValidation,
+    alias: &'static str,
+    ae_title: &str,
+) -> Result<()> {
+    if validation == RepertoireValidation::Off {
+        return Ok(());
+    }
+    let checked = match check_repertoire(VR::AE, ae_title) {
+        Ok(()) if ae_title.trim_matches(' ').is_empty() => Err(RepertoireViolation {
+            character: ' ',
+            position: 0,
+            reason: "application entity title is blank",
+        }),
+        checked => checked,
+    };
+    match checked {
+        Ok(()) => Ok(()),
+        Err(violation) if validation == RepertoireValidation::Strict => {
+            InvalidAeTitleSnafu { alias, violation }.fail()
+        }
+        Err(violation) => {
+            tracing::warn!(
+                "Invalid application entity title in `{}` {:?}: {}",
+                alias,
+                ae_title,
+                violation
+            );
+            Ok(())
+        }
+    }
+}
+
 fn dicom_len<T: AsRef<str>>(x: T) -> u32 {
     (x.as_ref().len() as u32 + 1) & !1
 }
@@ -1022,11 +1192,13 @@ fn dicom_len<T: AsRef<str>>(x: T) -> u32 {
 mod tests {
     use crate::{IMPLEMENTATION_CLASS_UID, IMPLEMENTATION_VERSION_NAME};
 
-    use super::{dicom_len, FileMetaTable, FileMetaTableBuilder};
+    use super::{dicom_len, Error, FileMetaTable, FileMetaTableBuilder};
     use dicom_core::ops::{AttributeAction, AttributeOp};
     use dicom_core::value::Value;
     use dicom_core::{dicom_value, DataElement, PrimitiveValue, Tag, VR};
     use dicom_dictionary_std::tags;
+    use dicom_encoding::text::repertoire::RepertoireValidation;
+    use dicom_encoding::text::uid::UidError;
 
     const TEST_META_1: &'static [u8] = &[
         // magic code
@@ -1107,6 +1279,29 @@ mod tests {
         assert_eq!(table, gt);
     }
 
+    #[test]
+    fn read_meta_table_with_bogus_length() {
+        #[rustfmt::skip]
+        let mut source: &[u8] = &[
+            // magic code
+            b'D', b'I', b'C', b'M',
+            // File Meta Information Group Length: (0000,0002) ; UL ; 4 ; 24
+            0x02, 0x00, 0x00, 0x00, b'U', b'L', 0x04, 0x00, 0x18, 0x00, 0x00, 0x00,
+            // Private Information (0002,0102) ; OB ; 0xFFFF_FFF0 ; only 4 bytes follow
+            0x02, 0x00, 0x02, 0x01, b'O', b'B', 0x00, 0x00, 0xF0, 0xFF, 0xFF, 0xFF,
+            0x01, 0x02, 0x03, 0x04,
+        ];
+
+        // fails without trying to allocate the full declared length
+        let err = FileMetaTable::from_reader(&mut source).unwrap_err();
+        assert!(
+            matches!(err, super::Error::ReadValueData { ref source, .. }
+                if source.kind() == std::io::ErrorKind::UnexpectedEof),
+            "{:?}",
+            err
+        );
+    }
+
     #[test]
     fn create_meta_table_with_builder() {
         let table = FileMetaTableBuilder::new()
@@ -1382,4 +1577,123 @@ mod tests {
             "1.2.840.10008.5.1.4.1.1.7",
         );
     }
+
+    /// a valid UID of exactly 64 characters
+    const UID_64: &str = "1.2.826.0.1.3680043.10.1234567890.1234567890.1234567890.12345678";
+
+    fn builder_with_instance_uid(uid: &str) -> FileMetaTableBuilder {
+        FileMetaTableBuilder::new()
+            .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+            .media_storage_sop_instance_uid(uid)
+            .transfer_syntax("1.2.840.10008.1.2.1")
+    }
+
+    #[test]
+    fn build_meta_table_with_uid_validation() {
+        // valid UIDs pass in strict mode, with or without padding
+        let table = builder_with_instance_uid(UID_64)
+            .uid_validation(RepertoireValidation::Strict)
+            .build()
+            .unwrap();
+        assert_eq!(table.media_storage_sop_instance_uid(), UID_64);
+        builder_with_instance_uid("1.2.3.4.5\0")
+            .uid_validation(RepertoireValidation::Strict)
+            .build()
+            .unwrap();
+
+        // invalid UIDs are rejected in strict mode
+        let uid_65 = format!("{}9", UID_64);
+        for uid in ["1.2.03.4", &uid_65, "1.2.3.4.5\0\0"] {
+            let err = builder_with_instance_uid(uid)
+                .uid_validation(RepertoireValidation::Strict)
+                .build()
+                .unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    Error::InvalidUid {
+                        alias: "MediaStorageSOPInstanceUID",
+                        ..
+                    }
+                ),
+                "unexpected error for {:?}: {:?}",
+                uid,
+                err
+            );
+        }
+        let err = builder_with_instance_uid("1.2.03.4")
+            .uid_validation(RepertoireValidation::Strict)
+            .build()
+            .unwrap_err();
+        match err {
+            Error::InvalidUid { source, .. } => {
+                assert_eq!(source, UidError::LeadingZero { position: 4 })
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+
+        // but accepted otherwise
+        for validation in [RepertoireValidation::Warn, RepertoireValidation::Off] {
+            let table = builder_with_instance_uid(&uid_65)
+                .uid_validation(validation)
+                .build()
+                .unwrap();
+            assert_eq!(table.media_storage_sop_instance_uid(), uid_65);
+        }
+    }
+
+    #[test]
+    fn build_meta_table_with_ae_title_validation() {
+        // 16 characters at most, padded to even length
+        let table = builder_with_instance_uid("1.2.3.4")
+            .source_application_entity_title("STORE-SCP")
+            .sending_application_entity_title("ARCHIVE-GATEWAY1")
+            .ae_title_validation(RepertoireValidation::Strict)
+            .build()
+            .unwrap();
+        assert_eq!(
+            table.source_application_entity_title.as_deref(),
+            Some("STORE-SCP ")
+        );
+        assert_eq!(
+            table.sending_application_entity_title.as_deref(),
+            Some("ARCHIVE-GATEWAY1")
+        );
+        let mut expected = table.clone();
+        expected.update_information_group_length();
+        assert_eq!(
+            table.information_group_length,
+            expected.information_group_length
+        );
+
+        for (ae_title, position) in [
+            ("ARCHIVE-GATEWAY12", 16),
+            ("STORE\\SCP", 5),
+            ("STORE\tSCP", 5),
+            ("    ", 0),
+        ] {
+            let err = builder_with_instance_uid("1.2.3.4")
+                .receiving_application_entity_title(ae_title)
+                .ae_title_validation(RepertoireValidation::Strict)
+                .build()
+                .unwrap_err();
+            match err {
+                Error::InvalidAeTitle {
+                    alias: "ReceivingApplicationEntityTitle",
+                    violation,
+                    ..
+                } => assert_eq!(violation.position, position, "{:?}", ae_title),
+                e => panic!("unexpected error for {:?}: {:?}", ae_title, e),
+            }
+
+            // accepted otherwise
+            for validation in [RepertoireValidation::Warn, RepertoireValidation::Off] {
+                builder_with_instance_uid("1.2.3.4")
+                    .receiving_application_entity_title(ae_title)
+                    .ae_title_validation(validation)
+                    .build()
+                    .unwrap();
+            }
+        }
+    }
 }
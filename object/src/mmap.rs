@@ -0,0 +1,357 @@
+//! Memory-mapped DICOM files.
+//!
+//! A [`MappedFile`] maps a DICOM file into memory,
+//! so that a [`BorrowedDicomObject`] can be read from it
+//! with its values borrowed from the map.
+//! Only the pages of the file which are actually accessed
+//! are read by the operating system,
+//! which makes opening a very large file
+//! and reading a handful of attributes from it cheap.
+//! Use [`BorrowedDicomObject::to_owned`] to copy the object into memory
+//! if it needs to outlive the map.
+//!
+//! # Safety
+//!
+//! The contents of a memory-mapped file may change
+//! if the file is modified by this or another process while it is mapped,
+//! and accessing a part of the map beyond the end of a file
+//! which was truncated in the meantime
+//! makes the process receive a bus error on most platforms.
+//! Mapping a file is therefore only advisable
+//! when the file is not expected to be modified while it is in use.
+//! Otherwise, [`MmapOptions::buffered`] reads the whole file into memory instead,
+//! with the same API.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_dictionary_std::tags;
+//! use dicom_object::open_file_mmap;
+//!
+//! let file = open_file_mmap("wsi.dcm")?;
+//! let obj = file.object()?;
+//! let patient_name = obj.element(tags::PATIENT_NAME)?.to_str()?;
+//! let frame: &[u8] = &obj.frame_data(0)?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use memmap2::Mmap;
+use snafu::{Backtrace, ResultExt, Snafu};
+
+use crate::borrowed::{BorrowedDicomObject, BorrowedReadError};
+
+/// An error which may occur when opening a memory-mapped file.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum MmapError {
+    #[snafu(display("Could not open file '{}'", filename.display()))]
+    OpenFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not map file '{}' into memory", filename.display()))]
+    MapFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not read file '{}'", filename.display()))]
+    ReadFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+}
+
+pub type Result<T, E = MmapError> = std::result::Result<T, E>;
+
+/// Options for opening a DICOM file mapped into memory.
+///
+/// # Example
+///
+/// ```no_run
+/// use dicom_object::mmap::MmapOptions;
+///
+/// // read the file into memory if it cannot be mapped
+/// let file = MmapOptions::new()
+///     .fallback_to_buffered(true)
+///     .open_file("path/to/file.dcm")?;
+/// # Result::<(), Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct MmapOptions {
+    buffered: bool,
+    fallback_to_buffered: bool,
+}
+
+impl MmapOptions {
+    pub fn new() -> Self {
+        MmapOptions::default()
+    }
+
+    /// Set whether to read the whole file into memory
+    /// through a buffered reader instead of mapping it.
+    ///
+    /// This is not affected by changes to the file after it was opened,
+    /// at the cost of reading all of it upfront.
+    pub fn buffered(mut self, buffered: bool) -> Self {
+        self.buffered = buffered;
+        self
+    }
+
+    /// Set whether to read the whole file into memory
+    /// if it cannot be mapped,
+    /// instead of failing.
+    pub fn fallback_to_buffered(mut self, fallback: bool) -> Self {
+        self.fallback_to_buffered = fallback;
+        self
+    }
+
+    /// Open the file at the given path.
+    pub fn open_file<P>(self, path: P) -> Result<MappedFile>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).context(OpenFileSnafu { filename: path })?;
+        if !self.buffered {
+            // Safety: see the module-level documentation
+            match unsafe { Mmap::map(&file) } {
+                Ok(map) => {
+                    return Ok(MappedFile {
+                        data: MappedData::Map(map),
+                    })
+                }
+                Err(e) if self.fallback_to_buffered => {
+                    tracing::warn!(
+                        "Could not map file '{}' into memory, reading it instead: {}",
+                        path.display(),
+                        e
+                    );
+                }
+                Err(e) => return Err(e).context(MapFileSnafu { filename: path }),
+            }
+        }
+        let mut data = Vec::new();
+        BufReader::new(file)
+            .read_to_end(&mut data)
+            .context(ReadFileSnafu { filename: path })?;
+        Ok(MappedFile {
+            data: MappedData::Buffer(data),
+        })
+    }
+}
+
+#[derive(Debug)]
+enum MappedData {
+    Map(Mmap),
+    Buffer(Vec<u8>),
+}
+
+/// A DICOM file mapped into memory
+/// (or read into memory as a fallback).
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+pub struct MappedFile {
+    data: MappedData,
+}
+
+impl MappedFile {
+    /// Retrieve all bytes of the file.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.data {
+            MappedData::Map(map) => map,
+            MappedData::Buffer(data) => data,
+        }
+    }
+
+    /// Check whether the file is mapped into memory,
+    /// as opposed to having been read into memory.
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.data, MappedData::Map(_))
+    }
+
+    /// Read the DICOM object in the file,
+    /// borrowing its values from the map.
+    ///
+    /// Only the file meta group and the element headers are read,
+    /// so this does not touch the pages of large values.
+    /// The object can be kept for as long as the file,
+    /// and copied into memory with [`BorrowedDicomObject::to_owned`].
+    pub fn object(&self) -> Result<BorrowedDicomObject<'_>, BorrowedReadError> {
+        crate::borrowed::from_slice(self.as_bytes())
+    }
+}
+
+/// Open a DICOM file mapped into memory,
+/// from which a [`BorrowedDicomObject`] can be read.
+///
+/// See [`MmapOptions`] for more options.
+pub fn open_file_mmap<P>(path: P) -> Result<MappedFile>
+where
+    P: AsRef<Path>,
+{
+    MmapOptions::new().open_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open_file_mmap, MmapError, MmapOptions};
+    use crate::meta::FileMetaTableBuilder;
+    use crate::{lazy, InMemDicomObject};
+    use dicom_core::value::PixelFragmentSequence;
+    use dicom_core::{DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_transfer_syntax_registry::entries;
+    use std::path::Path;
+
+    fn write_fixture(
+        path: &Path,
+        ts: &str,
+        frames: u32,
+        pixel_data: DataElement<InMemDicomObject>,
+    ) {
+        let mut obj = crate::dicom_object! {
+            SOPClassUID: uids::SECONDARY_CAPTURE_IMAGE_STORAGE,
+            SOPInstanceUID: "2.25.173",
+            PatientName: "Doe^John",
+            NumberOfFrames: frames.to_string(),
+            Rows: 2_u16,
+            Columns: 3_u16,
+            SamplesPerPixel: 1_u16,
+            BitsAllocated: 8_u16,
+            BitsStored: 8_u16,
+            HighBit: 7_u16,
+            PixelRepresentation: 0_u16,
+            PhotometricInterpretation: "MONOCHROME2",
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "2.25.1" },
+            ],
+        };
+        obj.put(pixel_data);
+        obj.with_meta(FileMetaTableBuilder::new().transfer_syntax(ts))
+            .unwrap()
+            .write_to_file(path)
+            .unwrap();
+    }
+
+    /// Check that attributes and frames of the mapped file
+    /// match those read by the lazy reader.
+    fn assert_same_as_buffered(path: &Path, frames: u32) {
+        let buffered = lazy::open_file(path).unwrap();
+
+        for file in [
+            open_file_mmap(path).unwrap(),
+            MmapOptions::new().buffered(true).open_file(path).unwrap(),
+        ] {
+            let obj = file.object().unwrap();
+            assert_eq!(obj.meta(), buffered.meta());
+            for tag in [
+                tags::SOP_INSTANCE_UID,
+                tags::PATIENT_NAME,
+                tags::NUMBER_OF_FRAMES,
+                tags::ROWS,
+            ] {
+                assert_eq!(
+                    &obj.element(tag).unwrap().to_primitive_value().unwrap(),
+                    buffered.element(tag).unwrap().value().unwrap(),
+                    "{}",
+                    tag
+                );
+            }
+            let uid = obj
+                .element(tags::REFERENCED_IMAGE_SEQUENCE)
+                .unwrap()
+                .items()
+                .unwrap()[0]
+                .element(tags::REFERENCED_SOP_INSTANCE_UID)
+                .unwrap()
+                .to_str()
+                .unwrap();
+            assert_eq!(uid, "2.25.1");
+
+            for frame in 0..frames {
+                assert_eq!(
+                    &*obj.frame_data(frame).unwrap(),
+                    &buffered.read_frame_data(frame).unwrap()[..],
+                    "frame #{}",
+                    frame
+                );
+            }
+            assert!(obj.frame_data(frames).is_err());
+
+            let owned = obj.to_owned().unwrap();
+            drop(file);
+            assert_eq!(
+                owned.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+                "Doe^John"
+            );
+        }
+    }
+
+    #[test]
+    fn map_native_multiframe_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("native.dcm");
+        let pixels: Vec<u8> = (0..18).collect();
+        write_fixture(
+            &path,
+            uids::EXPLICIT_VR_LITTLE_ENDIAN,
+            3,
+            DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(pixels)),
+        );
+
+        let file = open_file_mmap(&path).unwrap();
+        assert!(file.is_mapped());
+        assert_eq!(file.as_bytes(), &std::fs::read(&path).unwrap()[..]);
+        let obj = file.object().unwrap();
+        assert_eq!(&*obj.frame_data(1).unwrap(), &[6, 7, 8, 9, 10, 11]);
+
+        let buffered = MmapOptions::new().buffered(true).open_file(&path).unwrap();
+        assert!(!buffered.is_mapped());
+
+        assert_same_as_buffered(&path, 3);
+    }
+
+    #[test]
+    fn map_encapsulated_multiframe_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("encapsulated.dcm");
+        write_fixture(
+            &path,
+            entries::JPEG_BASELINE.uid(),
+            2,
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PixelFragmentSequence::new(
+                    vec![0, 24],
+                    vec![vec![0x11; 4], vec![0x22; 4], vec![0x33; 6]],
+                ),
+            ),
+        );
+
+        let file = open_file_mmap(&path).unwrap();
+        let obj = file.object().unwrap();
+        assert_eq!(
+            &*obj.frame_data(0).unwrap(),
+            &[0x11, 0x11, 0x11, 0x11, 0x22, 0x22, 0x22, 0x22]
+        );
+        assert_eq!(&*obj.frame_data(1).unwrap(), &[0x33; 6]);
+
+        assert_same_as_buffered(&path, 2);
+    }
+
+    #[test]
+    fn open_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = open_file_mmap(dir.path().join("missing.dcm")).unwrap_err();
+        assert!(matches!(err, MmapError::OpenFile { .. }), "{:?}", err);
+    }
+}
@@ -0,0 +1,237 @@
+//! Textual attribute paths into nested data sets.
+//!
+//! A path is a sequence of segments separated by dots,
+//! where each segment identifies an attribute
+//! either by keyword or by tag (such as `PixelSpacing` or `(0028,0030)`).
+//! All segments but the last one must refer to data set sequences,
+//! and may be followed by an item index in square brackets.
+//! When omitted, the first item is selected.
+//! The wildcard index `[*]` selects all items of the sequence.
+//!
+//! This is the same syntax as that of
+//! [attribute selectors](dicom_core::ops::AttributeSelector),
+//! extended with the item wildcard.
+//! See [`InMemDicomObject::element_at_path`]
+//! and [`InMemDicomObject::elements_at_path`].
+//!
+//! # Example
+//!
+//! ```
+//! # use dicom_core::{DataElement, VR, value::DataSetSequence};
+//! # use dicom_dictionary_std::tags;
+//! # use dicom_object::InMemDicomObject;
+//! let obj = InMemDicomObject::from_element_iter([DataElement::new(
+//!     tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+//!     VR::SQ,
+//!     DataSetSequence::from(vec![
+//!         InMemDicomObject::from_element_iter([DataElement::new(
+//!             tags::FRAME_CONTENT_SEQUENCE,
+//!             VR::SQ,
+//!             DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+//!                 DataElement::new(tags::STACK_ID, VR::SH, "1"),
+//!             ])]),
+//!         )]),
+//!         InMemDicomObject::from_element_iter([DataElement::new(
+//!             tags::FRAME_CONTENT_SEQUENCE,
+//!             VR::SQ,
+//!             DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+//!                 DataElement::new(tags::STACK_ID, VR::SH, "2"),
+//!             ])]),
+//!         )]),
+//!     ]),
+//! )]);
+//!
+//! let stack_id = obj.element_at_path(
+//!     "PerFrameFunctionalGroupsSequence[1].FrameContentSequence[0].StackID"
+//! )?;
+//! assert_eq!(stack_id.to_str()?, "2");
+//!
+//! let stack_ids: Vec<_> = obj
+//!     .elements_at_path("PerFrameFunctionalGroupsSequence[*].(0020,9111).StackID")?
+//!     .map(|e| e.to_str().unwrap().to_string())
+//!     .collect();
+//! assert_eq!(stack_ids, ["1", "2"]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`InMemDicomObject::element_at_path`]: crate::InMemDicomObject::element_at_path
+//! [`InMemDicomObject::elements_at_path`]: crate::InMemDicomObject::elements_at_path
+use crate::mem::{InMemDicomObject, InMemElement};
+use dicom_core::{DataDictionary, Tag};
+use snafu::{ensure, Backtrace, OptionExt, Snafu};
+
+/// An error which may occur when resolving a textual attribute path
+/// in a DICOM object.
+///
+/// All variants identify the path segment which failed,
+/// by its position in the path (starting at 0) and its text.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+#[snafu(visibility(pub(crate)))]
+pub enum AtPathError {
+    /// Invalid syntax in path segment #{segment_index} `{segment}`
+    InvalidSegment {
+        segment_index: usize,
+        segment: String,
+        backtrace: Backtrace,
+    },
+    /// Unknown attribute in path segment #{segment_index} `{segment}`
+    UnknownKey {
+        segment_index: usize,
+        segment: String,
+        backtrace: Backtrace,
+    },
+    /// Missing element at path segment #{segment_index} `{segment}`
+    MissingElement {
+        segment_index: usize,
+        segment: String,
+        backtrace: Backtrace,
+    },
+    /// Path segment #{segment_index} `{segment}` is not a data set sequence
+    NotASequence {
+        segment_index: usize,
+        segment: String,
+        backtrace: Backtrace,
+    },
+    /// Item index {index} out of range at path segment #{segment_index} `{segment}` (sequence has {len} items)
+    ItemOutOfRange {
+        segment_index: usize,
+        segment: String,
+        index: u32,
+        len: usize,
+        backtrace: Backtrace,
+    },
+    /// Unexpected item wildcard at path segment #{segment_index} `{segment}`
+    UnexpectedWildcard {
+        segment_index: usize,
+        segment: String,
+        backtrace: Backtrace,
+    },
+}
+
+/// The item selection of an intermediate path segment.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum PathItem {
+    /// A single item by index
+    Index(u32),
+    /// All items in the sequence
+    All,
+}
+
+/// A single parsed segment of an attribute path.
+#[derive(Debug)]
+pub(crate) struct PathSegment<'p> {
+    /// the original text of the segment
+    pub text: &'p str,
+    /// the attribute tag
+    pub tag: Tag,
+    /// the item selected, if it is an intermediate segment
+    pub item: Option<PathItem>,
+}
+
+/// Parse a textual attribute path,
+/// resolving keywords with the given data dictionary.
+pub(crate) fn parse_path<'p, D>(
+    dict: &D,
+    path: &'p str,
+) -> Result<Vec<PathSegment<'p>>, AtPathError>
+where
+    D: DataDictionary,
+{
+    let parts: Vec<&str> = path.split('.').collect();
+    let mut segments = Vec::with_capacity(parts.len());
+    for (segment_index, &text) in parts.iter().enumerate() {
+        let (key, item) = if let Some(stripped) = text.strip_suffix(']') {
+            let split_i = stripped.find('[').context(InvalidSegmentSnafu {
+                segment_index,
+                segment: text,
+            })?;
+            let item = match &stripped[split_i + 1..] {
+                "*" => PathItem::All,
+                index => PathItem::Index(index.parse().ok().context(InvalidSegmentSnafu {
+                    segment_index,
+                    segment: text,
+                })?),
+            };
+            // the last segment cannot select an item
+            ensure!(
+                segment_index + 1 < parts.len(),
+                InvalidSegmentSnafu {
+                    segment_index,
+                    segment: text,
+                }
+            );
+            (&stripped[..split_i], Some(item))
+        } else {
+            (text, None)
+        };
+
+        let tag = dict.parse_tag(key).context(UnknownKeySnafu {
+            segment_index,
+            segment: text,
+        })?;
+        segments.push(PathSegment { text, tag, item });
+    }
+    Ok(segments)
+}
+
+/// Collect the elements reachable through the given path segments,
+/// starting at the segment with the given index.
+///
+/// Within items selected by a wildcard,
+/// missing elements and items are not considered an error,
+/// and are skipped instead.
+pub(crate) fn collect_at_path<'a, D>(
+    obj: &'a InMemDicomObject<D>,
+    segments: &[PathSegment<'_>],
+    segment_index: usize,
+    in_wildcard: bool,
+    out: &mut Vec<&'a InMemElement<D>>,
+) -> Result<(), AtPathError>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    let segment = &segments[segment_index];
+    let elem = match obj.get(segment.tag) {
+        Some(elem) => elem,
+        None if in_wildcard => return Ok(()),
+        None => {
+            return MissingElementSnafu {
+                segment_index,
+                segment: segment.text,
+            }
+            .fail()
+        }
+    };
+
+    if segment_index + 1 == segments.len() {
+        out.push(elem);
+        return Ok(());
+    }
+
+    let items = elem.items().context(NotASequenceSnafu {
+        segment_index,
+        segment: segment.text,
+    })?;
+
+    match segment.item.unwrap_or(PathItem::Index(0)) {
+        PathItem::Index(index) => match items.get(index as usize) {
+            Some(item) => collect_at_path(item, segments, segment_index + 1, in_wildcard, out),
+            None if in_wildcard => Ok(()),
+            None => ItemOutOfRangeSnafu {
+                segment_index,
+                segment: segment.text,
+                index,
+                len: items.len(),
+            }
+            .fail(),
+        },
+        PathItem::All => {
+            for item in items {
+                collect_at_path(item, segments, segment_index + 1, true, out)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,668 @@
+//! Scanning of directory trees for DICOM files.
+//!
+//! [`scan_dir`] walks a directory tree
+//! and yields a [`ScanEntry`] for each file found,
+//! telling whether the file is DICOM
+//! and holding a few of its key attributes,
+//! such as the SOP Instance UID and the Study Instance UID.
+//! The files are read with the [lazy reader](crate::lazy),
+//! so only the values of the requested attributes are loaded.
+//!
+//! Files which are not DICOM are reported as such,
+//! and errors on individual files or directories
+//! do not stop the scan.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_dictionary_std::tags;
+//! use dicom_object::scan::{scan_dir, ScanOptions};
+//!
+//! for entry in scan_dir("studies", ScanOptions::new().max_depth(2)) {
+//!     let entry = entry?;
+//!     if entry.is_dicom() {
+//!         println!("{}: {:?}", entry.path().display(), entry.string(tags::SOP_INSTANCE_UID));
+//!     }
+//! }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use dicom_core::{Tag, VR};
+use dicom_dictionary_std::{tags, uids, StandardDataDictionary};
+use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
+use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+use snafu::{Backtrace, ResultExt, Snafu};
+
+use crate::lazy::{self, LazyDataSet, LazyReadError};
+use crate::mem::InMemDicomObject;
+use crate::meta::FileMetaTable;
+
+/// An error which may occur while scanning a directory tree.
+///
+/// These are yielded by the scan in place of the affected entries,
+/// after which the scan carries on.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum ScanError {
+    #[snafu(display("Could not read directory '{}'", path.display()))]
+    ReadDir {
+        path: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not read an entry of directory '{}'", path.display()))]
+    ReadDirEntry {
+        path: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not read metadata of '{}'", path.display()))]
+    ReadMetadata {
+        path: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not read file '{}'", path.display()))]
+    ReadFile {
+        path: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not read the attributes of DICOM file '{}'", path.display()))]
+    ReadAttributes {
+        path: PathBuf,
+        #[snafu(backtrace, source(from(LazyReadError, Box::new)))]
+        source: Box<LazyReadError>,
+    },
+}
+
+pub type Result<T, E = ScanError> = std::result::Result<T, E>;
+
+/// The attributes collected by default:
+/// _SOP Class UID_, _SOP Instance UID_,
+/// _Study Instance UID_, _Series Instance UID_,
+/// _Modality_ and _Instance Number_.
+pub const DEFAULT_TAGS: [Tag; 6] = [
+    tags::SOP_CLASS_UID,
+    tags::SOP_INSTANCE_UID,
+    tags::STUDY_INSTANCE_UID,
+    tags::SERIES_INSTANCE_UID,
+    tags::MODALITY,
+    tags::INSTANCE_NUMBER,
+];
+
+/// Options for scanning a directory tree.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ScanOptions {
+    /// The attributes to collect from each DICOM file
+    pub tags: Vec<Tag>,
+    /// Whether to follow symbolic links
+    pub follow_symlinks: bool,
+    /// The maximum depth of subdirectories to descend into
+    pub max_depth: Option<usize>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            tags: DEFAULT_TAGS.to_vec(),
+            follow_symlinks: false,
+            max_depth: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Create the default scan options,
+    /// collecting the [default attributes](DEFAULT_TAGS),
+    /// skipping symbolic links
+    /// and descending into all subdirectories.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the attributes to collect from each DICOM file,
+    /// replacing the default ones.
+    pub fn tags(mut self, tags: impl IntoIterator<Item = Tag>) -> Self {
+        self.tags = tags.into_iter().collect();
+        self
+    }
+
+    /// Set whether to follow symbolic links to files and directories.
+    ///
+    /// Links are skipped by default.
+    /// When they are followed,
+    /// a link to a directory which is already being scanned is skipped,
+    /// so that link cycles do not make the scan endless.
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Set the maximum depth of subdirectories to descend into.
+    ///
+    /// With a depth of 0, only the files directly in the root directory are scanned.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+}
+
+/// The kind of a scanned file.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum FileKind {
+    /// A DICOM file with the 128-byte preamble and the `DICM` code
+    Dicom,
+    /// A DICOM file starting with the `DICM` code, without a preamble
+    DicomWithoutPreamble,
+    /// A data set without the file meta group,
+    /// in little endian and with explicit or implicit VR,
+    /// as guessed from its first element
+    DataSet {
+        /// whether the data set was found to have explicit VR
+        explicit_vr: bool,
+    },
+    /// Not a DICOM file
+    NotDicom,
+}
+
+impl FileKind {
+    /// Whether the file was recognized as DICOM data.
+    pub fn is_dicom(self) -> bool {
+        self != FileKind::NotDicom
+    }
+
+    /// Recognize the kind of file from its first bytes.
+    ///
+    /// Files with neither the preamble nor the `DICM` code
+    /// are only taken for a data set
+    /// if their first element belongs to group 0008,
+    /// as is common for composite instances.
+    fn detect(head: &[u8]) -> Self {
+        if head.len() >= 132 && &head[128..132] == b"DICM" {
+            return FileKind::Dicom;
+        }
+        if head.starts_with(b"DICM") {
+            return FileKind::DicomWithoutPreamble;
+        }
+        if head.len() >= 8 && head[0..2] == [0x08, 0x00] {
+            let explicit_vr = VR::from_binary([head[4], head[5]]).is_some();
+            return FileKind::DataSet { explicit_vr };
+        }
+        FileKind::NotDicom
+    }
+}
+
+/// A file found while scanning a directory tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanEntry {
+    path: PathBuf,
+    kind: FileKind,
+    meta: Option<FileMetaTable>,
+    attributes: InMemDicomObject,
+}
+
+impl ScanEntry {
+    /// The path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The kind of file found.
+    pub fn kind(&self) -> FileKind {
+        self.kind
+    }
+
+    /// Whether the file was recognized as DICOM data.
+    pub fn is_dicom(&self) -> bool {
+        self.kind.is_dicom()
+    }
+
+    /// The file meta group,
+    /// if the file has one.
+    pub fn meta(&self) -> Option<&FileMetaTable> {
+        self.meta.as_ref()
+    }
+
+    /// The collected attributes found in the file.
+    ///
+    /// This is empty for files which are not DICOM.
+    pub fn attributes(&self) -> &InMemDicomObject {
+        &self.attributes
+    }
+
+    /// Take the collected attributes found in the file.
+    pub fn into_attributes(self) -> InMemDicomObject {
+        self.attributes
+    }
+
+    /// The value of the given collected attribute as a string,
+    /// without trailing padding.
+    pub fn string(&self, tag: Tag) -> Option<String> {
+        let value = self.attributes.get(tag)?.to_str().ok()?;
+        Some(value.trim_end_matches([' ', '\0']).to_string())
+    }
+}
+
+/// Scan the directory tree at the given path,
+/// yielding an entry for each file found.
+///
+/// The entries of each directory are visited in order of file name,
+/// descending into each subdirectory at its position in that order,
+/// so that the order of the scan is deterministic.
+/// If the path is a file rather than a directory,
+/// only that file is scanned.
+///
+/// Failures to read a file or directory are yielded as errors,
+/// after which the scan goes on with the next entry.
+pub fn scan_dir<P>(path: P, options: ScanOptions) -> ScanDir
+where
+    P: AsRef<Path>,
+{
+    ScanDir {
+        options,
+        root: Some(path.as_ref().to_path_buf()),
+        stack: Vec::new(),
+    }
+}
+
+/// An iterator over the files of a directory tree,
+/// created by [`scan_dir`].
+#[derive(Debug)]
+pub struct ScanDir {
+    options: ScanOptions,
+    /// the root path, until it is visited
+    root: Option<PathBuf>,
+    /// the directories being scanned, innermost last
+    stack: Vec<OpenDir>,
+}
+
+/// A directory being scanned.
+#[derive(Debug)]
+struct OpenDir {
+    /// the canonical path of the directory,
+    /// for detecting link cycles
+    canonical: Option<PathBuf>,
+    /// the entries not visited yet, in reverse order
+    entries: Vec<Result<PathBuf>>,
+}
+
+impl ScanDir {
+    /// Visit the given path,
+    /// entering it if it is a directory.
+    ///
+    /// Returns an entry if the path is a file.
+    fn visit(&mut self, path: PathBuf, depth: usize) -> Option<Result<ScanEntry>> {
+        let metadata = match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                if !self.options.follow_symlinks {
+                    return None;
+                }
+                match fs::metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(e) => return Some(Err(e).context(ReadMetadataSnafu { path })),
+                }
+            }
+            Ok(metadata) => metadata,
+            Err(e) => return Some(Err(e).context(ReadMetadataSnafu { path })),
+        };
+
+        if metadata.is_file() {
+            return Some(scan_file(path, &self.options.tags));
+        }
+        if !metadata.is_dir() || self.options.max_depth.is_some_and(|max| depth > max) {
+            return None;
+        }
+
+        let canonical = if self.options.follow_symlinks {
+            match fs::canonicalize(&path) {
+                Ok(canonical) => Some(canonical),
+                Err(e) => return Some(Err(e).context(ReadMetadataSnafu { path })),
+            }
+        } else {
+            None
+        };
+        if canonical.is_some() && self.stack.iter().any(|dir| dir.canonical == canonical) {
+            // a link back to a directory being scanned
+            return None;
+        }
+
+        let mut entries = match fs::read_dir(&path) {
+            Ok(read_dir) => read_dir
+                .map(|entry| {
+                    entry
+                        .map(|entry| entry.path())
+                        .context(ReadDirEntrySnafu { path: &path })
+                })
+                .collect::<Vec<_>>(),
+            Err(e) => return Some(Err(e).context(ReadDirSnafu { path })),
+        };
+        // errors first, then by file name
+        entries.sort_by(|a, b| {
+            let name = |entry: &Result<PathBuf>| {
+                entry
+                    .as_ref()
+                    .ok()
+                    .and_then(|path| path.file_name().map(ToOwned::to_owned))
+            };
+            name(a).cmp(&name(b))
+        });
+        entries.reverse();
+        self.stack.push(OpenDir { canonical, entries });
+        None
+    }
+}
+
+impl Iterator for ScanDir {
+    type Item = Result<ScanEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            if let Some(entry) = self.visit(root, 0) {
+                return Some(entry);
+            }
+        }
+
+        loop {
+            let dir = self.stack.last_mut()?;
+            let Some(entry) = dir.entries.pop() else {
+                self.stack.pop();
+                continue;
+            };
+            // the root directory is at depth 0
+            let depth = self.stack.len();
+            match entry {
+                Ok(path) => {
+                    if let Some(entry) = self.visit(path, depth) {
+                        return Some(entry);
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Recognize and read the file at the given path.
+fn scan_file(path: PathBuf, tags: &[Tag]) -> Result<ScanEntry> {
+    let mut file = match File::open(&path) {
+        Ok(file) => BufReader::new(file),
+        Err(e) => return Err(e).context(ReadFileSnafu { path }),
+    };
+    let mut head = Vec::with_capacity(132);
+    let kind = match (&mut file)
+        .take(132)
+        .read_to_end(&mut head)
+        .and_then(|_| file.seek(SeekFrom::Start(0)))
+    {
+        Ok(_) => FileKind::detect(&head),
+        Err(e) => return Err(e).context(ReadFileSnafu { path }),
+    };
+
+    let (meta, attributes) = match kind {
+        FileKind::Dicom | FileKind::DicomWithoutPreamble => {
+            lazy::from_reader(file).and_then(|obj| {
+                let attributes = collect(&obj, tags)?;
+                Ok((Some(obj.meta), attributes))
+            })
+        }
+        FileKind::DataSet { explicit_vr } => {
+            let uid = if explicit_vr {
+                uids::EXPLICIT_VR_LITTLE_ENDIAN
+            } else {
+                uids::IMPLICIT_VR_LITTLE_ENDIAN
+            };
+            let ts = TransferSyntaxRegistry
+                .get(uid)
+                .expect("little endian transfer syntaxes should be registered");
+            lazy::dataset_from_reader_with_dict(file, ts, StandardDataDictionary)
+                .and_then(|obj| Ok((None, collect(&obj, tags)?)))
+        }
+        FileKind::NotDicom => Ok((None, InMemDicomObject::new_empty())),
+    }
+    .context(ReadAttributesSnafu { path: &path })?;
+
+    Ok(ScanEntry {
+        path,
+        kind,
+        meta,
+        attributes,
+    })
+}
+
+/// Copy the given attributes of a lazily read data set into memory.
+fn collect<S>(
+    obj: &LazyDataSet<S>,
+    tags: &[Tag],
+) -> std::result::Result<InMemDicomObject, LazyReadError>
+where
+    S: Read + Seek,
+{
+    let elements = tags
+        .iter()
+        .filter_map(|&tag| obj.get(tag))
+        .map(|elem| elem.to_in_mem())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(InMemDicomObject::from_element_iter(elements))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::FileMetaTableBuilder;
+    use dicom_core::{dicom_value, DataElement};
+
+    fn test_object(sop_instance_uid: &str, modality: &str) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, uids::CT_IMAGE_STORAGE),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, sop_instance_uid),
+            DataElement::new(tags::MODALITY, VR::CS, modality),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+            DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, "2.25.1"),
+            DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, "2.25.2"),
+            DataElement::new(tags::INSTANCE_NUMBER, VR::IS, dicom_value!(Strs, ["7"])),
+        ])
+    }
+
+    fn write_test_file(path: &Path, sop_instance_uid: &str, modality: &str) {
+        test_object(sop_instance_uid, modality)
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .media_storage_sop_class_uid(uids::CT_IMAGE_STORAGE)
+                    .media_storage_sop_instance_uid(sop_instance_uid)
+                    .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN),
+            )
+            .unwrap()
+            .write_to_file(path)
+            .unwrap();
+    }
+
+    /// Create a directory tree with two DICOM files,
+    /// one of them in a subdirectory, and a text file.
+    fn test_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(&dir.path().join("b.dcm"), "2.25.10", "CT");
+        std::fs::write(dir.path().join("notes.txt"), "not a DICOM file\n").unwrap();
+        std::fs::create_dir(dir.path().join("a")).unwrap();
+        write_test_file(&dir.path().join("a").join("c.dcm"), "2.25.11", "MR");
+        dir
+    }
+
+    fn scanned(dir: &Path, options: ScanOptions) -> Vec<ScanEntry> {
+        scan_dir(dir, options).collect::<Result<Vec<_>>>().unwrap()
+    }
+
+    #[test]
+    fn scan_directory_tree() {
+        let dir = test_tree();
+        let entries = scanned(dir.path(), ScanOptions::new());
+
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_path_buf()).collect();
+        assert_eq!(
+            paths,
+            [
+                dir.path().join("a").join("c.dcm"),
+                dir.path().join("b.dcm"),
+                dir.path().join("notes.txt"),
+            ]
+        );
+        assert_eq!(entries[0].kind(), FileKind::Dicom);
+        assert_eq!(
+            entries[0].string(tags::SOP_INSTANCE_UID).as_deref(),
+            Some("2.25.11")
+        );
+        assert_eq!(entries[0].string(tags::MODALITY).as_deref(), Some("MR"));
+        assert_eq!(
+            entries[0].meta().map(|meta| meta.transfer_syntax()),
+            Some(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+        );
+
+        let entry = &entries[1];
+        assert!(entry.is_dicom());
+        assert_eq!(
+            entry.string(tags::SOP_CLASS_UID).as_deref(),
+            Some(uids::CT_IMAGE_STORAGE)
+        );
+        assert_eq!(
+            entry.string(tags::SOP_INSTANCE_UID).as_deref(),
+            Some("2.25.10")
+        );
+        assert_eq!(
+            entry.string(tags::STUDY_INSTANCE_UID).as_deref(),
+            Some("2.25.1")
+        );
+        assert_eq!(
+            entry.string(tags::SERIES_INSTANCE_UID).as_deref(),
+            Some("2.25.2")
+        );
+        assert_eq!(entry.string(tags::MODALITY).as_deref(), Some("CT"));
+        assert_eq!(entry.string(tags::INSTANCE_NUMBER).as_deref(), Some("7"));
+        // not in the default attributes
+        assert_eq!(entry.string(tags::PATIENT_NAME), None);
+
+        assert_eq!(entries[2].kind(), FileKind::NotDicom);
+        assert!(entries[2].attributes().is_empty());
+        assert!(entries[2].meta().is_none());
+    }
+
+    #[test]
+    fn scan_with_tags_and_max_depth() {
+        let dir = test_tree();
+        let options = ScanOptions::new().tags([tags::PATIENT_NAME]).max_depth(0);
+        let entries = scanned(dir.path(), options);
+
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_path_buf()).collect();
+        assert_eq!(
+            paths,
+            [dir.path().join("b.dcm"), dir.path().join("notes.txt")]
+        );
+        assert_eq!(
+            entries[0].string(tags::PATIENT_NAME).as_deref(),
+            Some("Doe^John")
+        );
+        assert_eq!(entries[0].string(tags::SOP_INSTANCE_UID), None);
+
+        // a single file is scanned on its own
+        let entries = scanned(&dir.path().join("b.dcm"), ScanOptions::new());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind(), FileKind::Dicom);
+    }
+
+    #[test]
+    fn scan_files_without_preamble_or_meta() {
+        let dir = tempfile::tempdir().unwrap();
+        let obj = test_object("2.25.20", "US");
+
+        // file meta group without the preamble
+        let mut data = Vec::new();
+        obj.clone()
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .media_storage_sop_class_uid(uids::CT_IMAGE_STORAGE)
+                    .media_storage_sop_instance_uid("2.25.20")
+                    .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN),
+            )
+            .unwrap()
+            .write_all(&mut data)
+            .unwrap();
+        std::fs::write(dir.path().join("1"), &data[128..]).unwrap();
+
+        for (name, uid) in [
+            ("2", uids::EXPLICIT_VR_LITTLE_ENDIAN),
+            ("3", uids::IMPLICIT_VR_LITTLE_ENDIAN),
+        ] {
+            let ts = TransferSyntaxRegistry.get(uid).unwrap();
+            let mut data = Vec::new();
+            obj.write_dataset_with_ts(&mut data, ts).unwrap();
+            std::fs::write(dir.path().join(name), data).unwrap();
+        }
+
+        let entries = scanned(dir.path(), ScanOptions::new());
+        let kinds: Vec<_> = entries.iter().map(ScanEntry::kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                FileKind::DicomWithoutPreamble,
+                FileKind::DataSet { explicit_vr: true },
+                FileKind::DataSet { explicit_vr: false },
+            ]
+        );
+        for entry in &entries {
+            assert_eq!(
+                entry.string(tags::SOP_INSTANCE_UID).as_deref(),
+                Some("2.25.20")
+            );
+            assert_eq!(entry.string(tags::MODALITY).as_deref(), Some("US"));
+        }
+        assert!(entries[0].meta().is_some());
+        assert!(entries[1].meta().is_none());
+    }
+
+    #[test]
+    fn scan_continues_after_broken_file() {
+        let dir = test_tree();
+        // the DICM code, followed by a truncated file meta group
+        let mut data = vec![0; 128];
+        data.extend_from_slice(b"DICM\x02\x00\x00\x00UL");
+        std::fs::write(dir.path().join("a0.dcm"), data).unwrap();
+
+        let entries: Vec<_> = scan_dir(dir.path(), ScanOptions::new()).collect();
+        assert_eq!(entries.len(), 4);
+        assert!(matches!(
+            &entries[1],
+            Err(ScanError::ReadAttributes { path, .. }) if path == &dir.path().join("a0.dcm")
+        ));
+        assert!(entries[0].is_ok());
+        assert!(entries[2].is_ok());
+        assert!(entries[3].is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scan_with_symlinks() {
+        let dir = test_tree();
+        std::os::unix::fs::symlink(dir.path().join("a"), dir.path().join("link")).unwrap();
+        // a cycle back to the root directory
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("a").join("up")).unwrap();
+
+        let entries = scanned(dir.path(), ScanOptions::new());
+        assert_eq!(entries.len(), 3);
+
+        let entries = scanned(dir.path(), ScanOptions::new().follow_symlinks(true));
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_path_buf()).collect();
+        assert_eq!(
+            paths,
+            [
+                dir.path().join("a").join("c.dcm"),
+                dir.path().join("b.dcm"),
+                dir.path().join("link").join("c.dcm"),
+                dir.path().join("notes.txt"),
+            ]
+        );
+    }
+}
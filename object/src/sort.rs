@@ -0,0 +1,401 @@
+//! Spatial sorting of the instances of a series.
+//!
+//! The slices of a volume are best ordered by their position
+//! along the normal of the image plane,
+//! since _Instance Number_ (0020,0013) does not reliably follow it.
+//! [`sort_by_position`] projects _Image Position (Patient)_ (0020,0032)
+//! onto the normal derived from _Image Orientation (Patient)_ (0020,0037),
+//! sorts the objects along it,
+//! and reports on the consistency of the resulting stack in a [`SortReport`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_object::open_file;
+//! use dicom_object::sort::sort_by_position;
+//!
+//! let files = vec![open_file("1.dcm")?, open_file("2.dcm")?, open_file("3.dcm")?];
+//! let mut slices: Vec<_> = files.iter().collect();
+//! let report = sort_by_position(&mut slices)?;
+//! if !report.duplicates.is_empty() {
+//!     eprintln!("Slices at the same position: {:?}", report.duplicates);
+//! }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::convert::TryInto;
+
+use dicom_dictionary_std::tags;
+use snafu::{Backtrace, OptionExt, Snafu};
+
+use crate::geometry::{cross, dot, norm};
+use crate::DicomObject;
+
+/// The maximum distance in mm
+/// between the positions of two slices considered to be the same.
+pub const POSITION_TOLERANCE: f64 = 1e-3;
+
+/// The maximum difference between direction cosines
+/// of two orientations considered to be the same.
+pub const ORIENTATION_TOLERANCE: f64 = 1e-4;
+
+/// How much larger than the median spacing
+/// the spacing between two slices must be to be reported as a gap.
+pub const GAP_FACTOR: f64 = 1.5;
+
+/// An error which may occur when sorting objects.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum SortError {
+    /// Object #{index} has neither a valid position nor an Instance Number
+    MissingSortKey { index: usize, backtrace: Backtrace },
+}
+
+pub type Result<T, E = SortError> = std::result::Result<T, E>;
+
+/// The criterion by which objects were sorted.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum SortMethod {
+    /// By position along the normal of the image plane.
+    Position,
+    /// By _Instance Number_,
+    /// because not all objects have a valid position and orientation.
+    InstanceNumber,
+}
+
+/// Statistics about the distance between consecutive slices,
+/// excluding slices at the same position.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SliceSpacing {
+    /// The smallest distance between consecutive slices, in mm.
+    pub min: f64,
+    /// The largest distance between consecutive slices, in mm.
+    pub max: f64,
+    /// The mean distance between consecutive slices, in mm.
+    pub mean: f64,
+    /// The indices of the slices followed by a gap,
+    /// where the distance to the next slice is more than
+    /// [`GAP_FACTOR`] times the median distance.
+    pub gaps: Vec<usize>,
+}
+
+/// The outcome of [`sort_by_position`].
+///
+/// All indices refer to the positions of the objects after sorting.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SortReport {
+    /// The criterion by which the objects were sorted.
+    pub method: SortMethod,
+    /// The unit normal of the image plane along which the objects were sorted,
+    /// derived from the orientation of the first object,
+    /// or `None` if sorted by _Instance Number_.
+    pub normal: Option<[f64; 3]>,
+    /// The position of each object along the normal, in mm,
+    /// or empty if sorted by _Instance Number_.
+    pub positions: Vec<f64>,
+    /// The indices of the objects
+    /// at the same position as the object before them.
+    pub duplicates: Vec<usize>,
+    /// The indices of the objects whose orientation
+    /// differs from the one used to derive the normal.
+    pub inconsistent_orientations: Vec<usize>,
+    /// Statistics about the distance between consecutive slices,
+    /// or `None` if there are fewer than two distinct positions.
+    pub spacing: Option<SliceSpacing>,
+}
+
+/// Sort the given objects of a series in place
+/// by their position along the normal of the image plane,
+/// in ascending order.
+///
+/// Objects at the same position keep their relative order.
+/// If any object lacks a valid _Image Position (Patient)_
+/// or _Image Orientation (Patient)_,
+/// the objects are sorted by _Instance Number_ instead,
+/// which fails if some object has no Instance Number either.
+/// See the [module-level documentation](self) for more information.
+pub fn sort_by_position<O: DicomObject>(objects: &mut [O]) -> Result<SortReport> {
+    let geometry: Option<Vec<([f64; 3], [f64; 6])>> = objects
+        .iter()
+        .map(|obj| {
+            let position = obj
+                .f64s(tags::IMAGE_POSITION_PATIENT)
+                .ok()?
+                .try_into()
+                .ok()?;
+            let orientation = obj
+                .f64s(tags::IMAGE_ORIENTATION_PATIENT)
+                .ok()?
+                .try_into()
+                .ok()?;
+            Some((position, orientation))
+        })
+        .collect();
+    let normal = geometry
+        .as_ref()
+        .and_then(|geometry| geometry.first())
+        .and_then(|(_, orientation)| plane_normal(orientation));
+
+    let (geometry, normal) = match (geometry, normal) {
+        (Some(geometry), Some(normal)) => (geometry, normal),
+        _ => return sort_by_instance_number(objects),
+    };
+
+    let keys: Vec<f64> = geometry
+        .iter()
+        .map(|(position, _)| dot(*position, normal))
+        .collect();
+    let order = stable_order(&keys);
+    apply_order(objects, &order);
+
+    let first_orientation = geometry[0].1;
+    let positions: Vec<f64> = order.iter().map(|&i| keys[i]).collect();
+    let inconsistent_orientations = order
+        .iter()
+        .enumerate()
+        .filter(|(_, &i)| {
+            geometry[i]
+                .1
+                .iter()
+                .zip(&first_orientation)
+                .any(|(a, b)| (a - b).abs() > ORIENTATION_TOLERANCE)
+        })
+        .map(|(sorted, _)| sorted)
+        .collect();
+    let duplicates = (1..positions.len())
+        .filter(|&i| positions[i] - positions[i - 1] <= POSITION_TOLERANCE)
+        .collect();
+
+    Ok(SortReport {
+        method: SortMethod::Position,
+        normal: Some(normal),
+        spacing: slice_spacing(&positions),
+        positions,
+        duplicates,
+        inconsistent_orientations,
+    })
+}
+
+fn sort_by_instance_number<O: DicomObject>(objects: &mut [O]) -> Result<SortReport> {
+    let keys = objects
+        .iter()
+        .enumerate()
+        .map(|(index, obj)| {
+            obj.i32(tags::INSTANCE_NUMBER)
+                .ok()
+                .map(f64::from)
+                .context(MissingSortKeySnafu { index })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let order = stable_order(&keys);
+    apply_order(objects, &order);
+    Ok(SortReport {
+        method: SortMethod::InstanceNumber,
+        normal: None,
+        positions: Vec::new(),
+        duplicates: Vec::new(),
+        inconsistent_orientations: Vec::new(),
+        spacing: None,
+    })
+}
+
+/// The unit normal of the plane with the given orientation,
+/// or `None` if the direction cosines are parallel or null.
+fn plane_normal(orientation: &[f64; 6]) -> Option<[f64; 3]> {
+    let row = [orientation[0], orientation[1], orientation[2]];
+    let column = [orientation[3], orientation[4], orientation[5]];
+    let [x, y, z] = cross(row, column);
+    let length = norm([x, y, z]);
+    if length > ORIENTATION_TOLERANCE {
+        Some([x / length, y / length, z / length])
+    } else {
+        None
+    }
+}
+
+/// Statistics of the distances between consecutive sorted positions,
+/// excluding duplicate positions.
+fn slice_spacing(positions: &[f64]) -> Option<SliceSpacing> {
+    let distances: Vec<(usize, f64)> = positions
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .enumerate()
+        .filter(|&(_, distance)| distance > POSITION_TOLERANCE)
+        .collect();
+    if distances.is_empty() {
+        return None;
+    }
+
+    let mut sorted: Vec<f64> = distances.iter().map(|&(_, d)| d).collect();
+    sorted.sort_by(f64::total_cmp);
+    let median = sorted[sorted.len() / 2];
+    let gaps = distances
+        .iter()
+        .filter(|&&(_, distance)| distance > median * GAP_FACTOR)
+        .map(|&(i, _)| i)
+        .collect();
+
+    Some(SliceSpacing {
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+        mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        gaps,
+    })
+}
+
+/// The indices of the given keys in ascending order,
+/// keeping equal keys in their original order.
+fn stable_order(keys: &[f64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    order.sort_by(|&a, &b| keys[a].total_cmp(&keys[b]));
+    order
+}
+
+/// Rearrange the items so that the item at position `i`
+/// is the one which was at position `order[i]`.
+fn apply_order<T>(items: &mut [T], order: &[usize]) {
+    for i in 0..items.len() {
+        // follow the swaps done so far
+        // to find where the item now lies
+        let mut j = order[i];
+        while j < i {
+            j = order[j];
+        }
+        items.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::InMemDicomObject;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{dicom_value, DataElement, VR};
+
+    fn slice(uid: &str, z: f64, instance_number: i32) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from(uid)),
+            DataElement::new(
+                tags::INSTANCE_NUMBER,
+                VR::IS,
+                PrimitiveValue::from(instance_number.to_string()),
+            ),
+            DataElement::new(
+                tags::IMAGE_POSITION_PATIENT,
+                VR::DS,
+                dicom_value!(
+                    Strs,
+                    ["-100".to_string(), "-100".to_string(), z.to_string()]
+                ),
+            ),
+            DataElement::new(
+                tags::IMAGE_ORIENTATION_PATIENT,
+                VR::DS,
+                dicom_value!(Strs, ["1", "0", "0", "0", "1", "0"]),
+            ),
+        ])
+    }
+
+    fn uids<O: DicomObject>(objects: &[O]) -> Vec<String> {
+        objects
+            .iter()
+            .map(|obj| obj.string(tags::SOP_INSTANCE_UID).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn apply_order_permutes() {
+        let mut items = ['a', 'b', 'c', 'd', 'e'];
+        apply_order(&mut items, &[3, 0, 4, 1, 2]);
+        assert_eq!(items, ['d', 'a', 'e', 'b', 'c']);
+    }
+
+    #[test]
+    fn sort_shuffled_slices() {
+        // slices every 2 mm from 0 to 8, a gap up to 14,
+        // and a second slice at 4 (labeled 4b),
+        // with instance numbers which do not follow the positions
+        let objects = [
+            slice("8", 8., 1),
+            slice("4", 4., 2),
+            slice("0", 0., 7),
+            slice("14", 14., 3),
+            slice("2", 2., 6),
+            slice("4b", 4., 4),
+            slice("6", 6., 5),
+        ];
+        let mut slices: Vec<_> = objects.iter().collect();
+        let report = sort_by_position(&mut slices).unwrap();
+
+        assert_eq!(uids(&slices), ["0", "2", "4", "4b", "6", "8", "14"]);
+        assert_eq!(report.method, SortMethod::Position);
+        assert_eq!(report.normal, Some([0., 0., 1.]));
+        assert_eq!(report.positions, [0., 2., 4., 4., 6., 8., 14.]);
+        assert_eq!(report.duplicates, [3]);
+        assert!(report.inconsistent_orientations.is_empty());
+
+        let spacing = report.spacing.unwrap();
+        assert_eq!(spacing.min, 2.);
+        assert_eq!(spacing.max, 6.);
+        assert_eq!(spacing.mean, 2.8);
+        assert_eq!(spacing.gaps, [5]);
+    }
+
+    #[test]
+    fn sort_along_oblique_normal() {
+        // sagittal slices, with the normal along -x
+        let mut objects: Vec<_> = [("a", 10.), ("b", -10.), ("c", 0.)]
+            .iter()
+            .map(|&(uid, x)| {
+                let mut obj = slice(uid, 0., 1);
+                obj.put(DataElement::new(
+                    tags::IMAGE_POSITION_PATIENT,
+                    VR::DS,
+                    dicom_value!(Strs, [x.to_string(), "0".to_string(), "0".to_string()]),
+                ));
+                obj.put(DataElement::new(
+                    tags::IMAGE_ORIENTATION_PATIENT,
+                    VR::DS,
+                    dicom_value!(Strs, ["0", "1", "0", "0", "0", "-1"]),
+                ));
+                obj
+            })
+            .collect();
+        // a tilted slice
+        objects[2].put(DataElement::new(
+            tags::IMAGE_ORIENTATION_PATIENT,
+            VR::DS,
+            dicom_value!(Strs, ["0", "0.9", "0.1", "0", "0", "-1"]),
+        ));
+
+        let mut slices: Vec<_> = objects.iter().collect();
+        let report = sort_by_position(&mut slices).unwrap();
+        assert_eq!(uids(&slices), ["a", "c", "b"]);
+        assert_eq!(report.normal, Some([-1., 0., 0.]));
+        assert_eq!(report.positions, [-10., 0., 10.]);
+        assert_eq!(report.inconsistent_orientations, [1]);
+    }
+
+    #[test]
+    fn fall_back_to_instance_number() {
+        let mut objects = [slice("a", 0., 3), slice("b", 2., 1), slice("c", 4., 2)];
+        objects[1].remove_element(tags::IMAGE_POSITION_PATIENT);
+
+        let mut slices: Vec<_> = objects.iter().collect();
+        let report = sort_by_position(&mut slices).unwrap();
+        assert_eq!(uids(&slices), ["b", "c", "a"]);
+        assert_eq!(report.method, SortMethod::InstanceNumber);
+        assert_eq!(report.normal, None);
+        assert!(report.positions.is_empty());
+        assert_eq!(report.spacing, None);
+
+        objects[0].remove_element(tags::INSTANCE_NUMBER);
+        let mut slices: Vec<_> = objects.iter().collect();
+        assert!(matches!(
+            sort_by_position(&mut slices),
+            Err(SortError::MissingSortKey { index: 0, .. })
+        ));
+    }
+}
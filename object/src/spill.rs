@@ -0,0 +1,1265 @@
+//! DICOM objects with large values spilled to temporary files.
+//!
+//! A [`SpillDicomObject`] is read like an in-memory object,
+//! except that primitive values and pixel data fragments
+//! longer than a configurable threshold
+//! are streamed into temporary files instead of being held in memory.
+//! When writing the object back,
+//! these values are streamed out of their files again.
+//! Temporary files are removed once the values using them are dropped.
+//!
+//! This is useful for processing very large files,
+//! such as whole slide images or long multi-frame series,
+//! where only the smaller attributes need to be inspected or modified.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_dictionary_std::tags;
+//! use dicom_object::spill::SpillOptions;
+//!
+//! let mut obj = SpillOptions::new()
+//!     .threshold(64 * 1024 * 1024)
+//!     .directory("/var/spool/dicom")
+//!     .open_file("wsi.dcm")?;
+//! obj.remove_element(tags::PATIENT_NAME);
+//! obj.write_to_file("wsi-anon.dcm")?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
+use dicom_core::header::{DataElementHeader, HasLength, Header, Length};
+use dicom_core::value::{DataSetSequence, PixelFragmentSequence, PrimitiveValue, Value, C};
+use dicom_core::{DataElement, Tag, VR};
+use dicom_dictionary_std::StandardDataDictionary;
+use dicom_encoding::text::SpecificCharacterSet;
+use dicom_encoding::transfer_syntax::{DynEncoder, TransferSyntaxIndex};
+use dicom_parser::dataset::lazy_read::LazyDataSetReader;
+use dicom_parser::dataset::{
+    BatchWriter, DataSetWriter, DataToken, LazyDataToken, LazyDataTokenRepr,
+};
+use dicom_parser::stateful::decode::DynStatefulDecoder;
+use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::mem::{InMemDicomObject, InMemElement};
+use crate::{
+    AccessByNameError, AccessError, CreatePrinterSnafu, FileDicomObject, FileMetaTable,
+    NoSuchAttributeNameSnafu, NoSuchDataElementTagSnafu, PrintDataSetSnafu, PrintMetaDataSetSnafu,
+    ReadSpilledValueSnafu, WriteError, WriteFileSnafu, WriteMagicCodeSnafu, WritePreambleSnafu,
+    WriteUnsupportedTransferSyntaxSnafu,
+};
+
+/// A DICOM object read from a file or other data source,
+/// with large values spilled to temporary files.
+///
+/// See the [module-level documentation](self) for more information.
+pub type SpillDicomObject<D = StandardDataDictionary> = FileDicomObject<SpillDataSet<D>>;
+
+/// An error which may occur when reading a DICOM object with spilled values
+/// or loading one of its spilled values.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum SpillError {
+    #[snafu(display("Could not open file '{}'", filename.display()))]
+    OpenFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not read preamble bytes
+    ReadPreambleBytes {
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not parse meta group data set
+    ParseMetaDataSet {
+        #[snafu(backtrace)]
+        source: crate::meta::Error,
+    },
+    /// Unsupported transfer syntax `{uid}`
+    ReadUnsupportedTransferSyntax { uid: String, backtrace: Backtrace },
+    /// Could not create data set parser
+    CreateParser {
+        #[snafu(backtrace)]
+        source: dicom_parser::stateful::decode::Error,
+    },
+    /// Could not read data set token
+    ReadToken {
+        #[snafu(backtrace)]
+        source: dicom_parser::dataset::lazy_read::Error,
+    },
+    /// Unexpected token {token:?}
+    UnexpectedToken {
+        token: LazyDataTokenRepr,
+        backtrace: Backtrace,
+    },
+    /// Premature data set end
+    PrematureEnd { backtrace: Backtrace },
+    /// Could not read element value
+    ReadValue {
+        #[snafu(backtrace)]
+        source: dicom_parser::dataset::Error,
+    },
+    #[snafu(display("Could not create spill file in '{}'", directory.display()))]
+    CreateSpillFile {
+        directory: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    /// Could not stream value into spill file
+    SpillValue {
+        #[snafu(backtrace)]
+        source: dicom_parser::dataset::Error,
+    },
+    #[snafu(display("Could not write spill file '{}'", filename.display()))]
+    WriteSpillFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not read spill file '{}'", filename.display()))]
+    ReadSpillFile {
+        filename: PathBuf,
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
+}
+
+pub type Result<T, E = SpillError> = std::result::Result<T, E>;
+
+/// Options for reading a DICOM object with large values spilled to temporary files.
+#[derive(Debug, Clone)]
+pub struct SpillOptions<D = StandardDataDictionary> {
+    threshold: u32,
+    directory: Option<PathBuf>,
+    dict: D,
+}
+
+impl Default for SpillOptions {
+    fn default() -> Self {
+        SpillOptions {
+            threshold: 16 * 1024 * 1024,
+            directory: None,
+            dict: StandardDataDictionary,
+        }
+    }
+}
+
+impl SpillOptions {
+    pub fn new() -> Self {
+        SpillOptions::default()
+    }
+}
+
+impl<D> SpillOptions<D> {
+    /// Set the length in bytes above which values are spilled.
+    ///
+    /// The default is 16 MiB.
+    pub fn threshold(mut self, threshold: u32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set the directory in which to create spill files.
+    ///
+    /// By default, the system's temporary directory is used.
+    pub fn directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Set the data element dictionary to use when reading the object.
+    pub fn dictionary<Di>(self, dict: Di) -> SpillOptions<Di>
+    where
+        Di: DataDictionary,
+        Di: Clone,
+    {
+        SpillOptions {
+            threshold: self.threshold,
+            directory: self.directory,
+            dict,
+        }
+    }
+
+    /// Open the DICOM file at the given path.
+    ///
+    /// Whether the 128-byte preamble is present is detected automatically.
+    pub fn open_file<P>(self, path: P) -> Result<SpillDicomObject<D>>
+    where
+        P: AsRef<Path>,
+        D: DataDictionary,
+        D: Clone,
+    {
+        let path = path.as_ref();
+        let mut file =
+            BufReader::new(File::open(path).with_context(|_| OpenFileSnafu { filename: path })?);
+
+        // skip the preamble if present
+        let mut buf = [0u8; 132];
+        let has_preamble = match file.read_exact(&mut buf) {
+            Ok(()) => &buf[128..] == b"DICM",
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => false,
+            Err(e) => return Err(e).context(ReadPreambleBytesSnafu),
+        };
+        let meta_start = if has_preamble { 128 } else { 0 };
+        file.seek(SeekFrom::Start(meta_start))
+            .context(ReadPreambleBytesSnafu)?;
+
+        self.from_reader(file)
+    }
+
+    /// Read a DICOM object from a byte source.
+    ///
+    /// This method assumes
+    /// the standard file encoding structure without the preamble:
+    /// file meta group, followed by the rest of the data set.
+    /// The source does not need to support seeking.
+    pub fn from_reader<R>(self, mut from: R) -> Result<SpillDicomObject<D>>
+    where
+        R: Read,
+        D: DataDictionary,
+        D: Clone,
+    {
+        let meta = FileMetaTable::from_reader(&mut from).context(ParseMetaDataSetSnafu)?;
+        let ts = TransferSyntaxRegistry.get(&meta.transfer_syntax).context(
+            ReadUnsupportedTransferSyntaxSnafu {
+                uid: meta.transfer_syntax.clone(),
+            },
+        )?;
+        let spooler = Spooler {
+            threshold: self.threshold,
+            directory: self.directory.unwrap_or_else(std::env::temp_dir),
+        };
+
+        let decoder =
+            DynStatefulDecoder::new_with(&mut from, ts, SpecificCharacterSet::default(), 0)
+                .context(CreateParserSnafu)?;
+        let mut dataset = LazyDataSetReader::new(decoder);
+        let obj = read_dataset(&mut dataset, &spooler, self.dict, Length::UNDEFINED, false)?;
+
+        Ok(FileDicomObject { meta, obj })
+    }
+}
+
+/// A temporary file which is removed when dropped.
+#[derive(Debug)]
+struct SpillFile {
+    path: PathBuf,
+}
+
+impl SpillFile {
+    /// Create a new empty file with a unique name in the given directory.
+    fn create(directory: &Path) -> std::io::Result<(Self, File)> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        loop {
+            let path = directory.join(format!(
+                "dicom-spill-{}-{}.tmp",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok((SpillFile { path }, file)),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// The policy for spilling values while reading.
+struct Spooler {
+    threshold: u32,
+    directory: PathBuf,
+}
+
+impl Spooler {
+    /// Stream a value of the given length into a new spill file.
+    fn spill<F>(&self, len: u32, read_into: F) -> Result<SpilledValue>
+    where
+        F: FnOnce(&mut BufWriter<File>) -> dicom_parser::dataset::Result<()>,
+    {
+        let (file, out) = SpillFile::create(&self.directory).context(CreateSpillFileSnafu {
+            directory: &self.directory,
+        })?;
+        let mut out = BufWriter::new(out);
+        read_into(&mut out).context(SpillValueSnafu)?;
+        out.flush().context(WriteSpillFileSnafu {
+            filename: &file.path,
+        })?;
+        Ok(SpilledValue {
+            file: Arc::new(file),
+            len,
+        })
+    }
+}
+
+/// A value held in a temporary file.
+///
+/// The value is kept exactly as encoded in the source,
+/// in the transfer syntax of the object it was read from.
+/// Clones share the same file,
+/// which is removed once the last one is dropped.
+#[derive(Debug, Clone)]
+pub struct SpilledValue {
+    file: Arc<SpillFile>,
+    len: u32,
+}
+
+impl SpilledValue {
+    /// Retrieve the length of the value in bytes.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Check whether the value is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Retrieve the path of the file holding the value.
+    pub fn path(&self) -> &Path {
+        &self.file.path
+    }
+
+    /// Open the file holding the value for reading.
+    pub fn open(&self) -> Result<File> {
+        File::open(self.path()).context(ReadSpillFileSnafu {
+            filename: self.path(),
+        })
+    }
+
+    /// Read the whole value into memory.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(self.len as usize);
+        self.open()?
+            .read_to_end(&mut bytes)
+            .context(ReadSpillFileSnafu {
+                filename: self.path(),
+            })?;
+        Ok(bytes)
+    }
+}
+
+/// The storage of a primitive value or pixel data fragment,
+/// either in memory or spilled to a temporary file.
+#[derive(Debug, Clone)]
+pub enum StoredValue {
+    /// A value held in memory.
+    Memory(PrimitiveValue),
+    /// A value held in a temporary file.
+    Spilled(SpilledValue),
+}
+
+impl From<PrimitiveValue> for StoredValue {
+    fn from(value: PrimitiveValue) -> Self {
+        StoredValue::Memory(value)
+    }
+}
+
+impl StoredValue {
+    /// Check whether the value was spilled to a temporary file.
+    pub fn is_spilled(&self) -> bool {
+        matches!(self, StoredValue::Spilled(_))
+    }
+
+    /// Retrieve the value held in memory,
+    /// or `None` if it was spilled.
+    pub fn as_primitive(&self) -> Option<&PrimitiveValue> {
+        match self {
+            StoredValue::Memory(value) => Some(value),
+            StoredValue::Spilled(_) => None,
+        }
+    }
+
+    /// Retrieve the length of the value in bytes.
+    pub fn byte_len(&self) -> u32 {
+        match self {
+            StoredValue::Memory(value) => value.calculate_byte_len() as u32,
+            StoredValue::Spilled(value) => value.len(),
+        }
+    }
+
+    /// Obtain the value as a primitive value,
+    /// reading it into memory if it was spilled.
+    ///
+    /// Spilled values are retrieved as raw bytes
+    /// in the encoding of the source.
+    pub fn load(&self) -> Result<Cow<'_, PrimitiveValue>> {
+        match self {
+            StoredValue::Memory(value) => Ok(Cow::Borrowed(value)),
+            StoredValue::Spilled(value) => {
+                Ok(Cow::Owned(PrimitiveValue::U8(value.to_bytes()?.into())))
+            }
+        }
+    }
+
+    /// Obtain the bytes of the value,
+    /// reading them into memory if the value was spilled.
+    pub fn to_bytes(&self) -> Result<Cow<'_, [u8]>> {
+        match self {
+            StoredValue::Memory(value) => Ok(value.to_bytes()),
+            StoredValue::Spilled(value) => Ok(Cow::Owned(value.to_bytes()?)),
+        }
+    }
+}
+
+/// The value of a data element with possibly spilled values.
+#[derive(Debug, Clone)]
+enum SpillValue<D> {
+    Primitive(StoredValue),
+    PixelSequence {
+        offset_table: C<u32>,
+        fragments: Vec<StoredValue>,
+    },
+    Sequence(Vec<SpillDataSet<D>>),
+}
+
+/// A data element of a [`SpillDataSet`].
+#[derive(Debug, Clone)]
+pub struct SpillElement<D = StandardDataDictionary> {
+    header: DataElementHeader,
+    value: SpillValue<D>,
+}
+
+impl<D> HasLength for SpillElement<D> {
+    fn length(&self) -> Length {
+        self.header.len
+    }
+}
+
+impl<D> Header for SpillElement<D> {
+    fn tag(&self) -> Tag {
+        self.header.tag
+    }
+}
+
+impl<D> SpillElement<D> {
+    /// Retrieve the element header.
+    pub fn header(&self) -> &DataElementHeader {
+        &self.header
+    }
+
+    /// Retrieve the value representation of the element.
+    pub fn vr(&self) -> VR {
+        self.header.vr
+    }
+
+    /// Check whether the value of this element,
+    /// or any of its pixel data fragments,
+    /// was spilled to a temporary file.
+    ///
+    /// Values in the items of data set sequences are not considered.
+    pub fn is_spilled(&self) -> bool {
+        match &self.value {
+            SpillValue::Primitive(value) => value.is_spilled(),
+            SpillValue::PixelSequence { fragments, .. } => {
+                fragments.iter().any(StoredValue::is_spilled)
+            }
+            SpillValue::Sequence(_) => false,
+        }
+    }
+
+    /// Retrieve the primitive value of this element,
+    /// if it is not a sequence.
+    pub fn value(&self) -> Option<&StoredValue> {
+        match &self.value {
+            SpillValue::Primitive(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the basic offset table of this element,
+    /// if it is an encapsulated pixel data sequence.
+    pub fn offset_table(&self) -> Option<&[u32]> {
+        match &self.value {
+            SpillValue::PixelSequence { offset_table, .. } => Some(offset_table),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the fragments of this element,
+    /// if it is an encapsulated pixel data sequence.
+    pub fn fragments(&self) -> Option<&[StoredValue]> {
+        match &self.value {
+            SpillValue::PixelSequence { fragments, .. } => Some(fragments),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the items of this element,
+    /// if it is a data set sequence.
+    pub fn items(&self) -> Option<&[SpillDataSet<D>]> {
+        match &self.value {
+            SpillValue::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Retrieve a mutable reference to the items of this element,
+    /// if it is a data set sequence.
+    ///
+    /// The length of the sequence is reset to undefined.
+    pub fn items_mut(&mut self) -> Option<&mut Vec<SpillDataSet<D>>> {
+        match &mut self.value {
+            SpillValue::Sequence(items) => {
+                self.header.len = Length::UNDEFINED;
+                Some(items)
+            }
+            _ => None,
+        }
+    }
+
+    /// Replace the value of this element with the given primitive value,
+    /// held in memory from then on.
+    pub fn set_value(&mut self, value: impl Into<PrimitiveValue>) {
+        let value = value.into();
+        self.header.len = Length(value.calculate_byte_len() as u32);
+        self.value = SpillValue::Primitive(StoredValue::Memory(value));
+    }
+}
+
+impl<D> SpillElement<D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Create an in-memory copy of this element,
+    /// reading all spilled values into memory.
+    ///
+    /// Spilled values are retrieved as raw bytes
+    /// in the encoding of the source.
+    pub fn to_in_mem(&self) -> Result<InMemElement<D>> {
+        let tag = self.header.tag;
+        let vr = self.header.vr;
+        Ok(match &self.value {
+            SpillValue::Primitive(value) => DataElement::new(tag, vr, value.load()?.into_owned()),
+            SpillValue::PixelSequence {
+                offset_table,
+                fragments,
+            } => DataElement::new(
+                tag,
+                vr,
+                Value::from(PixelFragmentSequence::new(
+                    offset_table.clone(),
+                    fragments
+                        .iter()
+                        .map(|fragment| Ok(fragment.to_bytes()?.into_owned()))
+                        .collect::<Result<Vec<_>>>()?,
+                )),
+            ),
+            SpillValue::Sequence(items) => DataElement::new(
+                tag,
+                VR::SQ,
+                DataSetSequence::new(
+                    items
+                        .iter()
+                        .map(SpillDataSet::to_in_mem)
+                        .collect::<Result<Vec<_>>>()?,
+                    Length::UNDEFINED,
+                ),
+            ),
+        })
+    }
+}
+
+impl<D> SpillElement<D>
+where
+    D: Clone,
+{
+    /// Create an element held in memory from an in-memory element.
+    fn from_in_mem(elem: InMemElement<D>, dict: &D) -> Self {
+        let (header, value) = elem.into_parts();
+        let value = match value {
+            Value::Primitive(value) => SpillValue::Primitive(StoredValue::Memory(value)),
+            Value::PixelSequence(seq) => {
+                let (offset_table, fragments) = seq.into_parts();
+                SpillValue::PixelSequence {
+                    offset_table,
+                    fragments: fragments
+                        .into_iter()
+                        .map(|fragment| StoredValue::Memory(PrimitiveValue::from(fragment)))
+                        .collect(),
+                }
+            }
+            Value::Sequence(seq) => SpillValue::Sequence(
+                seq.into_items()
+                    .into_iter()
+                    .map(|item| SpillDataSet::from_in_mem(item, dict.clone()))
+                    .collect(),
+            ),
+        };
+        SpillElement { header, value }
+    }
+}
+
+/// A DICOM data set with large values spilled to temporary files.
+///
+/// This is the data set type of a [`SpillDicomObject`],
+/// and of the items of its data set sequences.
+#[derive(Debug, Clone)]
+pub struct SpillDataSet<D = StandardDataDictionary> {
+    entries: BTreeMap<Tag, SpillElement<D>>,
+    dict: D,
+    /// the length of the data set as an item, usually undefined
+    len: Length,
+}
+
+impl<D> SpillDataSet<D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Retrieve a particular DICOM element by its tag,
+    /// or `None` if it is not present.
+    pub fn get(&self, tag: Tag) -> Option<&SpillElement<D>> {
+        self.entries.get(&tag)
+    }
+
+    /// Retrieve a mutable reference to a particular DICOM element by its tag,
+    /// or `None` if it is not present.
+    pub fn get_mut(&mut self, tag: Tag) -> Option<&mut SpillElement<D>> {
+        self.len = Length::UNDEFINED;
+        self.entries.get_mut(&tag)
+    }
+
+    /// Retrieve a particular DICOM element by its tag.
+    pub fn element(&self, tag: Tag) -> Result<&SpillElement<D>, AccessError> {
+        self.entries
+            .get(&tag)
+            .context(NoSuchDataElementTagSnafu { tag })
+    }
+
+    /// Retrieve a particular DICOM element by its name.
+    pub fn element_by_name(&self, name: &str) -> Result<&SpillElement<D>, AccessByNameError> {
+        let tag = self
+            .dict
+            .by_name(name)
+            .context(NoSuchAttributeNameSnafu { name })?
+            .tag();
+        self.element(tag).map_err(|e| e.into_access_by_name(name))
+    }
+
+    /// Insert a data element held in memory,
+    /// replacing and returning any previous element of the same tag.
+    pub fn put(&mut self, elem: InMemElement<D>) -> Option<SpillElement<D>> {
+        self.len = Length::UNDEFINED;
+        self.entries
+            .insert(elem.tag(), SpillElement::from_in_mem(elem, &self.dict))
+    }
+
+    /// Remove a DICOM element by its tag,
+    /// reporting whether it was present.
+    ///
+    /// Any temporary files used only by the element are removed.
+    pub fn remove_element(&mut self, tag: Tag) -> bool {
+        let removed = self.entries.remove(&tag).is_some();
+        if removed {
+            self.len = Length::UNDEFINED;
+        }
+        removed
+    }
+
+    /// Obtain an iterator over the elements of this data set.
+    pub fn iter(&self) -> impl Iterator<Item = &SpillElement<D>> + '_ {
+        self.entries.values()
+    }
+
+    /// Obtain an iterator over the tags of the elements in this data set.
+    pub fn tags(&self) -> impl Iterator<Item = Tag> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Retrieve the number of elements in this data set.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether this data set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Create an in-memory copy of this data set,
+    /// reading all spilled values into memory.
+    ///
+    /// Spilled values are retrieved as raw bytes
+    /// in the encoding of the source.
+    pub fn to_in_mem(&self) -> Result<InMemDicomObject<D>> {
+        let elements = self
+            .entries
+            .values()
+            .map(SpillElement::to_in_mem)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(InMemDicomObject::from_iter_with_dict(
+            elements,
+            self.dict.clone(),
+        ))
+    }
+}
+
+impl<D> SpillDataSet<D>
+where
+    D: Clone,
+{
+    /// Create a data set held in memory from an in-memory object.
+    fn from_in_mem(obj: InMemDicomObject<D>, dict: D) -> Self {
+        SpillDataSet {
+            entries: obj
+                .into_iter()
+                .map(|e| (e.tag(), SpillElement::from_in_mem(e, &dict)))
+                .collect(),
+            dict,
+            len: Length::UNDEFINED,
+        }
+    }
+}
+
+impl<'a, D> IntoIterator for &'a SpillDataSet<D> {
+    type Item = &'a SpillElement<D>;
+    type IntoIter = std::collections::btree_map::Values<'a, Tag, SpillElement<D>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.values()
+    }
+}
+
+impl<D> SpillDicomObject<D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Write the entire object as a DICOM file
+    /// into the given file path.
+    /// Preamble, magic code, and file meta group will be included
+    /// before the inner object.
+    ///
+    /// Spilled values are streamed from their temporary files.
+    /// The data set is written in the transfer syntax of the file meta group,
+    /// which must be the one that the object was read with.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WriteError> {
+        let path = path.as_ref();
+        let file = File::create(path).context(WriteFileSnafu { filename: path })?;
+        let mut to = BatchWriter::new(file);
+
+        // write preamble
+        to.write_all(&[0_u8; 128][..])
+            .context(WriteFileSnafu { filename: path })?;
+
+        // write magic sequence
+        to.write_all(b"DICM")
+            .context(WriteFileSnafu { filename: path })?;
+
+        self.write_meta_and_dataset(to)
+    }
+
+    /// Write the entire object as a DICOM file
+    /// into the given writer.
+    /// Preamble, magic code, and file meta group will be included
+    /// before the inner object.
+    ///
+    /// Spilled values are streamed from their temporary files.
+    /// The data set is written in the transfer syntax of the file meta group,
+    /// which must be the one that the object was read with.
+    pub fn write_all<W: Write>(&self, to: W) -> Result<(), WriteError> {
+        let mut to = BatchWriter::new(to);
+
+        // write preamble
+        to.write_all(&[0_u8; 128][..]).context(WritePreambleSnafu)?;
+
+        // write magic sequence
+        to.write_all(b"DICM").context(WriteMagicCodeSnafu)?;
+
+        self.write_meta_and_dataset(to)
+    }
+
+    fn write_meta_and_dataset<W: Write>(&self, mut to: W) -> Result<(), WriteError> {
+        self.meta().write(&mut to).context(PrintMetaDataSetSnafu)?;
+
+        let ts = TransferSyntaxRegistry
+            .get(self.meta().transfer_syntax())
+            .with_context(|| WriteUnsupportedTransferSyntaxSnafu {
+                uid: self.meta().transfer_syntax(),
+            })?;
+        let mut dset_writer = DataSetWriter::with_ts(to, ts).context(CreatePrinterSnafu)?;
+        write_dataset(&mut dset_writer, self)?;
+        dset_writer.flush().context(PrintDataSetSnafu)
+    }
+
+    /// Create an in-memory copy of this DICOM object,
+    /// reading all spilled values into memory.
+    pub fn to_in_mem(&self) -> Result<FileDicomObject<InMemDicomObject<D>>> {
+        Ok(FileDicomObject {
+            meta: self.meta().clone(),
+            obj: (**self).to_in_mem()?,
+        })
+    }
+}
+
+type SpillReader<'s, R> = LazyDataSetReader<DynStatefulDecoder<&'s mut R>>;
+
+/// Read a data set, spilling large values.
+fn read_dataset<R, D>(
+    dataset: &mut SpillReader<'_, R>,
+    spooler: &Spooler,
+    dict: D,
+    len: Length,
+    in_item: bool,
+) -> Result<SpillDataSet<D>>
+where
+    R: Read,
+    D: DataDictionary,
+    D: Clone,
+{
+    let mut entries = BTreeMap::new();
+    while let Some(token) = dataset.advance() {
+        let elem = match token.context(ReadTokenSnafu)? {
+            LazyDataToken::ElementHeader(header) => {
+                let token = match dataset.advance() {
+                    Some(Ok(token @ LazyDataToken::LazyValue { .. })) => token,
+                    Some(Ok(token)) => {
+                        return UnexpectedTokenSnafu {
+                            token: token.into_repr(),
+                        }
+                        .fail()
+                    }
+                    Some(Err(e)) => return Err(e).context(ReadTokenSnafu),
+                    None => return PrematureEndSnafu.fail(),
+                };
+                let value = match header.len.get() {
+                    Some(len) if len > spooler.threshold => {
+                        StoredValue::Spilled(spooler.spill(len, |out| token.read_value_into(out))?)
+                    }
+                    _ => StoredValue::Memory(token.into_value().context(ReadValueSnafu)?),
+                };
+                SpillElement {
+                    header,
+                    value: SpillValue::Primitive(value),
+                }
+            }
+            LazyDataToken::SequenceStart { tag, len } => {
+                let items = read_sequence(dataset, spooler, &dict)?;
+                SpillElement {
+                    header: DataElementHeader::new(tag, VR::SQ, len),
+                    value: SpillValue::Sequence(items),
+                }
+            }
+            LazyDataToken::PixelSequenceStart => {
+                let (offset_table, fragments) = read_pixel_sequence(dataset, spooler)?;
+                SpillElement {
+                    header: DataElementHeader::new(Tag(0x7FE0, 0x0010), VR::OB, Length::UNDEFINED),
+                    value: SpillValue::PixelSequence {
+                        offset_table,
+                        fragments,
+                    },
+                }
+            }
+            LazyDataToken::ItemEnd if in_item => break,
+            token => {
+                return UnexpectedTokenSnafu {
+                    token: token.into_repr(),
+                }
+                .fail()
+            }
+        };
+        entries.insert(elem.header.tag, elem);
+    }
+
+    Ok(SpillDataSet { entries, dict, len })
+}
+
+/// Read the items of a data set sequence, spilling large values.
+fn read_sequence<R, D>(
+    dataset: &mut SpillReader<'_, R>,
+    spooler: &Spooler,
+    dict: &D,
+) -> Result<Vec<SpillDataSet<D>>>
+where
+    R: Read,
+    D: DataDictionary,
+    D: Clone,
+{
+    let mut items = Vec::new();
+    loop {
+        match dataset.advance() {
+            Some(Ok(LazyDataToken::ItemStart { len })) => {
+                items.push(read_dataset(dataset, spooler, dict.clone(), len, true)?);
+            }
+            Some(Ok(LazyDataToken::SequenceEnd)) => return Ok(items),
+            Some(Ok(token)) => {
+                return UnexpectedTokenSnafu {
+                    token: token.into_repr(),
+                }
+                .fail()
+            }
+            Some(Err(e)) => return Err(e).context(ReadTokenSnafu),
+            None => return PrematureEndSnafu.fail(),
+        }
+    }
+}
+
+/// Read the basic offset table and fragments
+/// of an encapsulated pixel data sequence,
+/// spilling large fragments.
+fn read_pixel_sequence<R>(
+    dataset: &mut SpillReader<'_, R>,
+    spooler: &Spooler,
+) -> Result<(C<u32>, Vec<StoredValue>)>
+where
+    R: Read,
+{
+    let mut offset_table = C::new();
+    let mut fragments = Vec::new();
+    let mut items = 0;
+    let mut item_len = 0;
+    loop {
+        match dataset.advance() {
+            Some(Ok(LazyDataToken::ItemStart { len })) => {
+                // empty items are not followed by an item value
+                item_len = len.0;
+                if items > 0 {
+                    fragments.push(StoredValue::Memory(PrimitiveValue::U8(C::new())));
+                }
+                items += 1;
+            }
+            Some(Ok(token @ LazyDataToken::LazyItemValue { .. })) if items == 1 => {
+                let mut bytes = Vec::new();
+                token.read_value_into(&mut bytes).context(ReadValueSnafu)?;
+                offset_table = bytes
+                    .chunks_exact(4)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+            }
+            Some(Ok(token @ LazyDataToken::LazyItemValue { .. })) => {
+                let fragment = if item_len > spooler.threshold {
+                    StoredValue::Spilled(spooler.spill(item_len, |out| token.read_value_into(out))?)
+                } else {
+                    let mut bytes = Vec::new();
+                    token.read_value_into(&mut bytes).context(ReadValueSnafu)?;
+                    StoredValue::Memory(PrimitiveValue::U8(bytes.into()))
+                };
+                if let Some(last) = fragments.last_mut() {
+                    *last = fragment;
+                }
+            }
+            Some(Ok(LazyDataToken::ItemEnd)) => { /* no-op */ }
+            Some(Ok(LazyDataToken::SequenceEnd)) => return Ok((offset_table, fragments)),
+            Some(Ok(token)) => {
+                return UnexpectedTokenSnafu {
+                    token: token.into_repr(),
+                }
+                .fail()
+            }
+            Some(Err(e)) => return Err(e).context(ReadTokenSnafu),
+            None => return PrematureEndSnafu.fail(),
+        }
+    }
+}
+
+type SpillWriter<'w, W> = DataSetWriter<W, DynEncoder<'w, W>>;
+
+/// Write a data set, streaming spilled values from their files.
+fn write_dataset<W, D>(
+    writer: &mut SpillWriter<'_, W>,
+    dataset: &SpillDataSet<D>,
+) -> Result<(), WriteError>
+where
+    W: Write,
+{
+    for elem in dataset {
+        match &elem.value {
+            SpillValue::Primitive(value) => write_value(writer, elem.header, value)?,
+            SpillValue::PixelSequence {
+                offset_table,
+                fragments,
+            } => {
+                writer
+                    .write(DataToken::PixelSequenceStart)
+                    .context(PrintDataSetSnafu)?;
+                writer
+                    .write(DataToken::ItemStart {
+                        len: Length(offset_table.len() as u32 * 4),
+                    })
+                    .context(PrintDataSetSnafu)?;
+                if !offset_table.is_empty() {
+                    writer
+                        .write(DataToken::OffsetTable(offset_table.to_vec()))
+                        .context(PrintDataSetSnafu)?;
+                }
+                writer
+                    .write(DataToken::ItemEnd)
+                    .context(PrintDataSetSnafu)?;
+                for fragment in fragments {
+                    writer
+                        .write(DataToken::ItemStart {
+                            len: Length(fragment.byte_len()),
+                        })
+                        .context(PrintDataSetSnafu)?;
+                    match fragment {
+                        StoredValue::Memory(value) => writer
+                            .write(DataToken::ItemValue(value.to_bytes().into_owned()))
+                            .context(PrintDataSetSnafu)?,
+                        StoredValue::Spilled(value) => write_spilled(writer, value)?,
+                    }
+                    writer
+                        .write(DataToken::ItemEnd)
+                        .context(PrintDataSetSnafu)?;
+                }
+                writer
+                    .write(DataToken::SequenceEnd)
+                    .context(PrintDataSetSnafu)?;
+            }
+            SpillValue::Sequence(items) => {
+                writer
+                    .write(DataToken::SequenceStart {
+                        tag: elem.header.tag,
+                        len: elem.header.len,
+                    })
+                    .context(PrintDataSetSnafu)?;
+                for item in items {
+                    writer
+                        .write(DataToken::ItemStart { len: item.len })
+                        .context(PrintDataSetSnafu)?;
+                    write_dataset(writer, item)?;
+                    writer
+                        .write(DataToken::ItemEnd)
+                        .context(PrintDataSetSnafu)?;
+                }
+                writer
+                    .write(DataToken::SequenceEnd)
+                    .context(PrintDataSetSnafu)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_value<W: Write>(
+    writer: &mut SpillWriter<'_, W>,
+    header: DataElementHeader,
+    value: &StoredValue,
+) -> Result<(), WriteError> {
+    match value {
+        StoredValue::Memory(value) => {
+            writer
+                .write(DataToken::ElementHeader(header))
+                .context(PrintDataSetSnafu)?;
+            writer
+                .write(DataToken::PrimitiveValue(value.clone()))
+                .context(PrintDataSetSnafu)
+        }
+        StoredValue::Spilled(value) => {
+            writer
+                .write(DataToken::ElementHeader(DataElementHeader::new(
+                    header.tag,
+                    header.vr,
+                    Length(value.len()),
+                )))
+                .context(PrintDataSetSnafu)?;
+            write_spilled(writer, value)
+        }
+    }
+}
+
+fn write_spilled<W: Write>(
+    writer: &mut SpillWriter<'_, W>,
+    value: &SpilledValue,
+) -> Result<(), WriteError> {
+    let file = File::open(value.path()).context(ReadSpilledValueSnafu {
+        filename: value.path(),
+    })?;
+    writer
+        .write_value_from(BufReader::new(file))
+        .context(PrintDataSetSnafu)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dicom_object, FileMetaTableBuilder};
+    use dicom_dictionary_std::{tags, uids};
+
+    fn file_count(dir: &Path) -> usize {
+        std::fs::read_dir(dir).unwrap().count()
+    }
+
+    #[test]
+    fn spills_large_values_and_writes_them_back() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut obj = dicom_object! {
+            SOPClassUID: "1.2.840.10008.5.1.4.1.1.7",
+            SOPInstanceUID: "2.25.1",
+            PatientName: "Doe^John",
+            Rows: 32_u16,
+            Columns: 32_u16,
+            ReferencedImageSequence: [
+                { ReferencedSOPInstanceUID: "2.25.2" },
+            ],
+        };
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OW,
+            PrimitiveValue::U16((0..1024_u16).collect()),
+        ));
+        let obj = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+            .unwrap();
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+
+        let spilled = SpillOptions::new()
+            .threshold(1024)
+            .directory(dir.path())
+            .from_reader(&data[128..])
+            .unwrap();
+
+        let pixel_data = spilled.element(tags::PIXEL_DATA).unwrap();
+        assert!(pixel_data.is_spilled());
+        assert_eq!(pixel_data.value().unwrap().byte_len(), 2048);
+        let patient_name = spilled.element(tags::PATIENT_NAME).unwrap();
+        assert!(!patient_name.is_spilled());
+        assert_eq!(
+            patient_name
+                .value()
+                .unwrap()
+                .as_primitive()
+                .unwrap()
+                .to_str(),
+            "Doe^John"
+        );
+        assert_eq!(file_count(dir.path()), 1);
+
+        let mut out = Vec::new();
+        spilled.write_all(&mut out).unwrap();
+        assert_eq!(out, data);
+
+        let in_mem = spilled.to_in_mem().unwrap();
+        assert_eq!(
+            &*in_mem
+                .element(tags::PIXEL_DATA)
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+            &*obj.element(tags::PIXEL_DATA).unwrap().to_bytes().unwrap(),
+        );
+
+        drop(spilled);
+        assert_eq!(file_count(dir.path()), 0);
+    }
+
+    #[test]
+    fn spills_large_fragments() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut obj = dicom_object! {
+            SOPClassUID: "1.2.840.10008.5.1.4.1.1.7",
+            SOPInstanceUID: "2.25.1",
+            NumberOfFrames: "2",
+        };
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new(vec![0, 2056], vec![vec![0x55; 2048], vec![0xAA; 16]]),
+        ));
+        let obj = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::JPEG_BASELINE8_BIT))
+            .unwrap();
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+
+        let spilled = SpillOptions::new()
+            .threshold(1024)
+            .directory(dir.path())
+            .from_reader(&data[128..])
+            .unwrap();
+        let pixel_data = spilled.element(tags::PIXEL_DATA).unwrap();
+        assert_eq!(pixel_data.offset_table(), Some(&[0, 2056][..]));
+        let fragments = pixel_data.fragments().unwrap();
+        assert_eq!(fragments.len(), 2);
+        assert!(fragments[0].is_spilled());
+        assert!(!fragments[1].is_spilled());
+        assert_eq!(&*fragments[0].to_bytes().unwrap(), &[0x55; 2048][..]);
+        assert_eq!(file_count(dir.path()), 1);
+
+        let mut out = Vec::new();
+        spilled.write_all(&mut out).unwrap();
+        assert_eq!(out, data);
+
+        drop(spilled);
+        assert_eq!(file_count(dir.path()), 0);
+    }
+
+    #[test]
+    fn modified_object_keeps_spilled_values() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut obj = dicom_object! {
+            SOPClassUID: "1.2.840.10008.5.1.4.1.1.7",
+            SOPInstanceUID: "2.25.1",
+            PatientName: "Doe^John",
+        };
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PrimitiveValue::from(vec![0x12_u8; 1500]),
+        ));
+        let obj = obj
+            .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::IMPLICIT_VR_LITTLE_ENDIAN))
+            .unwrap();
+        let mut data = Vec::new();
+        obj.write_all(&mut data).unwrap();
+
+        let mut spilled = SpillOptions::new()
+            .threshold(1024)
+            .directory(dir.path())
+            .from_reader(&data[128..])
+            .unwrap();
+        spilled.put(DataElement::new(
+            tags::PATIENT_NAME,
+            VR::PN,
+            PrimitiveValue::from("Anonymous"),
+        ));
+        assert!(spilled.remove_element(tags::SOP_INSTANCE_UID));
+
+        let mut out = Vec::new();
+        spilled.write_all(&mut out).unwrap();
+        let result = crate::from_reader(&out[128..]).unwrap();
+        assert_eq!(
+            result
+                .element(tags::PATIENT_NAME)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "Anonymous"
+        );
+        assert!(result.element(tags::SOP_INSTANCE_UID).is_err());
+        assert_eq!(
+            &*result
+                .element(tags::PIXEL_DATA)
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+            &[0x12; 1500][..]
+        );
+
+        // removing the element removes its spill file
+        assert_eq!(file_count(dir.path()), 1);
+        spilled.remove_element(tags::PIXEL_DATA);
+        assert_eq!(file_count(dir.path()), 0);
+    }
+}
@@ -0,0 +1,756 @@
+//! Navigation of Structured Report documents.
+//!
+//! The content of an SR document is a tree of content items.
+//! The document itself is the root item,
+//! and the items below each item are in its _Content Sequence_ (0040,A730).
+//! Each item has a _Value Type_ (0040,A040) telling how its value is encoded,
+//! a concept name coded in _Concept Name Code Sequence_ (0040,A043),
+//! and, apart from the root,
+//! a _Relationship Type_ (0040,A010) with its parent.
+//!
+//! [`ContentItem`] wraps each item with typed accessors
+//! for the concept name and the common kinds of values,
+//! and resolves items which are included by reference
+//! (with a _Referenced Content Item Identifier_ (0040,DB73))
+//! to the items they refer to.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_object::open_file;
+//! use dicom_object::sr::{CodedEntry, ContentItem};
+//!
+//! let obj = open_file("report.dcm")?;
+//! let root = ContentItem::root(&obj);
+//! let length = CodedEntry::new("410668003", "SCT", "Length");
+//! if let Some(item) = root.find_by_concept(&length) {
+//!     if let Some((value, unit)) = item.measurement() {
+//!         println!("Length: {} {}", value, unit.meaning);
+//!     }
+//! }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::fmt;
+
+use dicom_core::value::PrimitiveValue;
+use dicom_core::Tag;
+use dicom_dictionary_std::tags;
+
+use crate::mem::InMemDicomObject;
+
+/// The value type of a content item,
+/// from its _Value Type_ (0040,A040).
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ValueType {
+    /// `CONTAINER`
+    Container,
+    /// `TEXT`
+    Text,
+    /// `NUM`
+    Num,
+    /// `CODE`
+    Code,
+    /// `DATETIME`
+    DateTime,
+    /// `DATE`
+    Date,
+    /// `TIME`
+    Time,
+    /// `UIDREF`
+    UidRef,
+    /// `PNAME`
+    PName,
+    /// `COMPOSITE`
+    Composite,
+    /// `IMAGE`
+    Image,
+    /// `WAVEFORM`
+    Waveform,
+    /// `SCOORD`
+    SCoord,
+    /// `SCOORD3D`
+    SCoord3D,
+    /// `TCOORD`
+    TCoord,
+    /// `TABLE`
+    Table,
+    /// Any other value type
+    Other(String),
+}
+
+impl ValueType {
+    /// Obtain the value type of the given code string,
+    /// ignoring padding.
+    pub fn from_code(code: &str) -> Self {
+        match trim(code) {
+            "CONTAINER" => ValueType::Container,
+            "TEXT" => ValueType::Text,
+            "NUM" => ValueType::Num,
+            "CODE" => ValueType::Code,
+            "DATETIME" => ValueType::DateTime,
+            "DATE" => ValueType::Date,
+            "TIME" => ValueType::Time,
+            "UIDREF" => ValueType::UidRef,
+            "PNAME" => ValueType::PName,
+            "COMPOSITE" => ValueType::Composite,
+            "IMAGE" => ValueType::Image,
+            "WAVEFORM" => ValueType::Waveform,
+            "SCOORD" => ValueType::SCoord,
+            "SCOORD3D" => ValueType::SCoord3D,
+            "TCOORD" => ValueType::TCoord,
+            "TABLE" => ValueType::Table,
+            code => ValueType::Other(code.to_string()),
+        }
+    }
+
+    /// Obtain the code string of this value type.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ValueType::Container => "CONTAINER",
+            ValueType::Text => "TEXT",
+            ValueType::Num => "NUM",
+            ValueType::Code => "CODE",
+            ValueType::DateTime => "DATETIME",
+            ValueType::Date => "DATE",
+            ValueType::Time => "TIME",
+            ValueType::UidRef => "UIDREF",
+            ValueType::PName => "PNAME",
+            ValueType::Composite => "COMPOSITE",
+            ValueType::Image => "IMAGE",
+            ValueType::Waveform => "WAVEFORM",
+            ValueType::SCoord => "SCOORD",
+            ValueType::SCoord3D => "SCOORD3D",
+            ValueType::TCoord => "TCOORD",
+            ValueType::Table => "TABLE",
+            ValueType::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The relationship of a content item with its parent,
+/// from its _Relationship Type_ (0040,A010).
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum RelationshipType {
+    /// `CONTAINS`
+    Contains,
+    /// `HAS PROPERTIES`
+    HasProperties,
+    /// `HAS OBS CONTEXT`
+    HasObsContext,
+    /// `HAS ACQ CONTEXT`
+    HasAcqContext,
+    /// `HAS CONCEPT MOD`
+    HasConceptMod,
+    /// `INFERRED FROM`
+    InferredFrom,
+    /// `SELECTED FROM`
+    SelectedFrom,
+    /// Any other relationship type
+    Other(String),
+}
+
+impl RelationshipType {
+    /// Obtain the relationship type of the given code string,
+    /// ignoring padding.
+    pub fn from_code(code: &str) -> Self {
+        match trim(code) {
+            "CONTAINS" => RelationshipType::Contains,
+            "HAS PROPERTIES" => RelationshipType::HasProperties,
+            "HAS OBS CONTEXT" => RelationshipType::HasObsContext,
+            "HAS ACQ CONTEXT" => RelationshipType::HasAcqContext,
+            "HAS CONCEPT MOD" => RelationshipType::HasConceptMod,
+            "INFERRED FROM" => RelationshipType::InferredFrom,
+            "SELECTED FROM" => RelationshipType::SelectedFrom,
+            code => RelationshipType::Other(code.to_string()),
+        }
+    }
+
+    /// Obtain the code string of this relationship type.
+    pub fn as_str(&self) -> &str {
+        match self {
+            RelationshipType::Contains => "CONTAINS",
+            RelationshipType::HasProperties => "HAS PROPERTIES",
+            RelationshipType::HasObsContext => "HAS OBS CONTEXT",
+            RelationshipType::HasAcqContext => "HAS ACQ CONTEXT",
+            RelationshipType::HasConceptMod => "HAS CONCEPT MOD",
+            RelationshipType::InferredFrom => "INFERRED FROM",
+            RelationshipType::SelectedFrom => "SELECTED FROM",
+            RelationshipType::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for RelationshipType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A coded concept,
+/// as found in an item of a code sequence
+/// such as _Concept Name Code Sequence_ (0040,A043).
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct CodedEntry {
+    /// _Code Value_ (0008,0100),
+    /// or the _Long Code Value_ (0008,0119)
+    /// or _URN Code Value_ (0008,0120) in its absence
+    pub value: String,
+    /// _Coding Scheme Designator_ (0008,0102)
+    pub scheme: String,
+    /// _Code Meaning_ (0008,0104)
+    pub meaning: String,
+    /// _Coding Scheme Version_ (0008,0103)
+    pub scheme_version: Option<String>,
+}
+
+impl CodedEntry {
+    /// Create a coded entry.
+    pub fn new(
+        value: impl Into<String>,
+        scheme: impl Into<String>,
+        meaning: impl Into<String>,
+    ) -> Self {
+        CodedEntry {
+            value: value.into(),
+            scheme: scheme.into(),
+            meaning: meaning.into(),
+            scheme_version: None,
+        }
+    }
+
+    /// Read a coded entry from an item of a code sequence.
+    ///
+    /// Returns `None` if the item has no code value.
+    pub fn from_item(item: &InMemDicomObject) -> Option<Self> {
+        let value = [
+            tags::CODE_VALUE,
+            tags::LONG_CODE_VALUE,
+            tags::URN_CODE_VALUE,
+        ]
+        .iter()
+        .filter_map(|&tag| string(item, tag))
+        .find(|value| !value.is_empty())?;
+        Some(CodedEntry {
+            value,
+            scheme: string(item, tags::CODING_SCHEME_DESIGNATOR).unwrap_or_default(),
+            meaning: string(item, tags::CODE_MEANING).unwrap_or_default(),
+            scheme_version: string(item, tags::CODING_SCHEME_VERSION),
+        })
+    }
+
+    /// Read the coded entry in the first item
+    /// of the given code sequence in a data set.
+    pub fn from_sequence(obj: &InMemDicomObject, tag: Tag) -> Option<Self> {
+        Self::from_item(obj.items(tag)?.first()?)
+    }
+
+    /// Whether this entry has the same code value and coding scheme
+    /// as the other one.
+    ///
+    /// The code meaning and the scheme version are not compared.
+    pub fn is_same_concept(&self, other: &CodedEntry) -> bool {
+        self.value == other.value && self.scheme == other.scheme
+    }
+}
+
+impl fmt::Display for CodedEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, \"{}\")", self.value, self.scheme, self.meaning)
+    }
+}
+
+/// A content item of a Structured Report document,
+/// with access to the items below it.
+///
+/// Items included by reference in the content sequence of their parent
+/// are resolved to the items they refer to,
+/// while keeping the relationship type given by the parent.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentItem<'a> {
+    /// the root of the document, for resolving references
+    root: &'a InMemDicomObject,
+    /// the data set of this item
+    item: &'a InMemDicomObject,
+    /// the item in the parent's content sequence
+    /// which refers to this item, if included by reference
+    reference: Option<&'a InMemDicomObject>,
+    /// the position of this item in the tree
+    identifier: Vec<u32>,
+}
+
+impl<'a> ContentItem<'a> {
+    /// Wrap the root content item of an SR document,
+    /// which is the document's data set.
+    pub fn root(obj: &'a InMemDicomObject) -> Self {
+        ContentItem {
+            root: obj,
+            item: obj,
+            reference: None,
+            identifier: vec![1],
+        }
+    }
+
+    /// The data set of this item.
+    ///
+    /// For items included by reference,
+    /// this is the data set of the referenced item.
+    pub fn attributes(&self) -> &'a InMemDicomObject {
+        self.item
+    }
+
+    /// The position of this item in the content tree,
+    /// as used in _Referenced Content Item Identifier_ (0040,DB73):
+    /// 1 for the root,
+    /// followed by the one-based index of the item
+    /// in the content sequence at each level below.
+    pub fn identifier(&self) -> &[u32] {
+        &self.identifier
+    }
+
+    /// Whether this item was included by reference
+    /// in the content sequence of its parent.
+    pub fn is_by_reference(&self) -> bool {
+        self.reference.is_some()
+    }
+
+    /// _Value Type_ (0040,A040)
+    pub fn value_type(&self) -> Option<ValueType> {
+        string(self.item, tags::VALUE_TYPE).map(|code| ValueType::from_code(&code))
+    }
+
+    /// _Relationship Type_ (0040,A010) with the parent item,
+    /// which is absent in the root.
+    pub fn relationship_type(&self) -> Option<RelationshipType> {
+        string(self.reference.unwrap_or(self.item), tags::RELATIONSHIP_TYPE)
+            .map(|code| RelationshipType::from_code(&code))
+    }
+
+    /// The concept name of this item,
+    /// in _Concept Name Code Sequence_ (0040,A043).
+    pub fn concept_name(&self) -> Option<CodedEntry> {
+        CodedEntry::from_sequence(self.item, tags::CONCEPT_NAME_CODE_SEQUENCE)
+    }
+
+    /// Whether the concept name of this item is the given concept.
+    pub fn has_concept(&self, concept: &CodedEntry) -> bool {
+        self.concept_name()
+            .is_some_and(|name| name.is_same_concept(concept))
+    }
+
+    /// The value of a `TEXT` item,
+    /// in _Text Value_ (0040,A160).
+    pub fn text(&self) -> Option<String> {
+        string(self.item, tags::TEXT_VALUE)
+    }
+
+    /// The value of a `CODE` item,
+    /// in _Concept Code Sequence_ (0040,A168).
+    pub fn code(&self) -> Option<CodedEntry> {
+        CodedEntry::from_sequence(self.item, tags::CONCEPT_CODE_SEQUENCE)
+    }
+
+    /// The value of a `DATETIME` item,
+    /// in _DateTime_ (0040,A120).
+    pub fn datetime(&self) -> Option<String> {
+        string(self.item, tags::DATE_TIME)
+    }
+
+    /// The value of a `UIDREF` item,
+    /// in _UID_ (0040,A124).
+    pub fn uid(&self) -> Option<String> {
+        string(self.item, tags::UID)
+    }
+
+    /// The value of a `PNAME` item,
+    /// in _Person Name_ (0040,A123).
+    pub fn person_name(&self) -> Option<String> {
+        string(self.item, tags::PERSON_NAME)
+    }
+
+    /// The measured value of a `NUM` item and its unit,
+    /// in the first item of _Measured Value Sequence_ (0040,A300).
+    ///
+    /// The value is taken from _Floating Point Value_ (0040,A161) if present,
+    /// as it is more precise than the decimal string
+    /// in _Numeric Value_ (0040,A30A).
+    pub fn measurement(&self) -> Option<(f64, CodedEntry)> {
+        let measured = self.item.items(tags::MEASURED_VALUE_SEQUENCE)?.first()?;
+        let value = primitive(measured, tags::FLOATING_POINT_VALUE)
+            .or_else(|| primitive(measured, tags::NUMERIC_VALUE))?
+            .to_float64()
+            .ok()?;
+        let unit = CodedEntry::from_sequence(measured, tags::MEASUREMENT_UNITS_CODE_SEQUENCE)?;
+        Some((value, unit))
+    }
+
+    /// Iterate over the items directly below this one,
+    /// in the order of its content sequence.
+    ///
+    /// Items included by reference are resolved to the items they refer to.
+    /// References which do not resolve to an item are skipped.
+    pub fn children(&self) -> impl Iterator<Item = ContentItem<'a>> + '_ {
+        self.item
+            .items(tags::CONTENT_SEQUENCE)
+            .unwrap_or_default()
+            .iter()
+            .zip(1..)
+            .filter_map(move |(item, index)| {
+                match primitive(item, tags::REFERENCED_CONTENT_ITEM_IDENTIFIER) {
+                    None => {
+                        let mut identifier = self.identifier.clone();
+                        identifier.push(index);
+                        Some(ContentItem {
+                            root: self.root,
+                            item,
+                            reference: None,
+                            identifier,
+                        })
+                    }
+                    Some(value) => {
+                        let identifier = value.to_multi_int::<u32>().ok()?;
+                        let target = resolve(self.root, &identifier)?;
+                        Some(ContentItem {
+                            root: self.root,
+                            item: target,
+                            reference: Some(item),
+                            identifier,
+                        })
+                    }
+                }
+            })
+    }
+
+    /// Iterate over all items below this one, depth first,
+    /// each item followed by the items below it.
+    ///
+    /// Items included by reference are visited,
+    /// but not the items below them,
+    /// as those are already visited at the position of the referenced item.
+    pub fn descendants(&self) -> Descendants<'a> {
+        let mut stack: Vec<_> = self.children().collect();
+        stack.reverse();
+        Descendants { stack }
+    }
+
+    /// Look for the first item below this one, depth first,
+    /// with the given concept name.
+    pub fn find_by_concept(&self, concept: &CodedEntry) -> Option<ContentItem<'a>> {
+        self.descendants().find(|item| item.has_concept(concept))
+    }
+
+    /// Look for all items below this one, depth first,
+    /// with the given concept name.
+    pub fn find_all_by_concept<'c>(
+        &self,
+        concept: &'c CodedEntry,
+    ) -> impl Iterator<Item = ContentItem<'a>> + 'c
+    where
+        'a: 'c,
+    {
+        self.descendants()
+            .filter(move |item| item.has_concept(concept))
+    }
+}
+
+/// An iterator over the items below a [`ContentItem`], depth first.
+#[derive(Debug, Clone)]
+pub struct Descendants<'a> {
+    stack: Vec<ContentItem<'a>>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = ContentItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.stack.pop()?;
+        if !item.is_by_reference() {
+            let start = self.stack.len();
+            self.stack.extend(item.children());
+            self.stack[start..].reverse();
+        }
+        Some(item)
+    }
+}
+
+/// Find the item at the given position in the content tree.
+fn resolve<'a>(root: &'a InMemDicomObject, identifier: &[u32]) -> Option<&'a InMemDicomObject> {
+    let (&first, path) = identifier.split_first()?;
+    if first != 1 {
+        return None;
+    }
+    path.iter().try_fold(root, |item, &index| {
+        let index = index.checked_sub(1)? as usize;
+        item.items(tags::CONTENT_SEQUENCE)?.get(index)
+    })
+}
+
+fn primitive(obj: &InMemDicomObject, tag: Tag) -> Option<&PrimitiveValue> {
+    obj.get(tag)?.value().primitive()
+}
+
+/// The value of a text attribute, without padding.
+fn string(obj: &InMemDicomObject, tag: Tag) -> Option<String> {
+    Some(trim(&primitive(obj, tag)?.to_str()).to_string())
+}
+
+fn trim(text: &str) -> &str {
+    text.trim_end_matches([' ', '\0'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, VR};
+
+    fn code_item(value: &str, scheme: &str, meaning: &str) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::CODE_VALUE, VR::SH, value),
+            DataElement::new(tags::CODING_SCHEME_DESIGNATOR, VR::SH, scheme),
+            DataElement::new(tags::CODE_MEANING, VR::LO, meaning),
+        ])
+    }
+
+    fn content_item(
+        relationship: &str,
+        value_type: &str,
+        concept: (&str, &str, &str),
+    ) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::RELATIONSHIP_TYPE, VR::CS, relationship),
+            DataElement::new(tags::VALUE_TYPE, VR::CS, value_type),
+            DataElement::new(
+                tags::CONCEPT_NAME_CODE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![code_item(concept.0, concept.1, concept.2)]),
+            ),
+        ])
+    }
+
+    fn with_content(
+        mut item: InMemDicomObject,
+        children: Vec<InMemDicomObject>,
+    ) -> InMemDicomObject {
+        item.put(DataElement::new(
+            tags::CONTENT_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(children),
+        ));
+        item
+    }
+
+    /// A small measurement report:
+    ///
+    /// ```text
+    /// 1      CONTAINER Imaging Measurement Report
+    /// 1.1      HAS OBS CONTEXT PNAME Person Observer Name
+    /// 1.2      CONTAINS CONTAINER Imaging Measurements
+    /// 1.2.1      CONTAINS NUM Length = 12.5 mm
+    /// 1.2.2      CONTAINS NUM Width = 4 mm
+    /// 1.2.3      CONTAINS CODE Finding Site = Liver
+    /// 1.3      CONTAINS TEXT Impression
+    /// 1.3.1      INFERRED FROM (by reference to 1.2.1)
+    /// ```
+    fn test_report() -> InMemDicomObject {
+        let mut observer = content_item(
+            "HAS OBS CONTEXT",
+            "PNAME",
+            ("121008", "DCM", "Person Observer Name"),
+        );
+        observer.put(DataElement::new(tags::PERSON_NAME, VR::PN, "Doe^Jane"));
+
+        let num = |concept, value: &str, float: Option<f64>| {
+            let mut measured = InMemDicomObject::from_element_iter([
+                DataElement::new(tags::NUMERIC_VALUE, VR::DS, value),
+                DataElement::new(
+                    tags::MEASUREMENT_UNITS_CODE_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![code_item("mm", "UCUM", "millimeter")]),
+                ),
+            ]);
+            if let Some(float) = float {
+                measured.put(DataElement::new(
+                    tags::FLOATING_POINT_VALUE,
+                    VR::FD,
+                    dicom_value!(F64, [float]),
+                ));
+            }
+            let mut item = content_item("CONTAINS", "NUM", concept);
+            item.put(DataElement::new(
+                tags::MEASURED_VALUE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![measured]),
+            ));
+            item
+        };
+        let mut site = content_item("CONTAINS", "CODE", ("363698007", "SCT", "Finding Site"));
+        site.put(DataElement::new(
+            tags::CONCEPT_CODE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![code_item("10200004", "SCT", "Liver")]),
+        ));
+        let measurements = with_content(
+            content_item(
+                "CONTAINS",
+                "CONTAINER",
+                ("126010", "DCM", "Imaging Measurements"),
+            ),
+            vec![
+                num(("410668003", "SCT", "Length"), "12.5", None),
+                num(("103355008", "SCT", "Width"), "4 ", Some(4.0625)),
+                site,
+            ],
+        );
+
+        let mut impression = content_item("CONTAINS", "TEXT", ("121073", "DCM", "Impression"));
+        impression.put(DataElement::new(tags::TEXT_VALUE, VR::UT, "Small lesion"));
+        let reference = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::RELATIONSHIP_TYPE, VR::CS, "INFERRED FROM"),
+            DataElement::new(
+                tags::REFERENCED_CONTENT_ITEM_IDENTIFIER,
+                VR::UL,
+                dicom_value!(U32, [1, 2, 1]),
+            ),
+        ]);
+        let impression = with_content(impression, vec![reference]);
+
+        let mut root = with_content(
+            content_item(
+                "",
+                "CONTAINER",
+                ("126000", "DCM", "Imaging Measurement Report"),
+            ),
+            vec![observer, measurements, impression],
+        );
+        root.remove_element(tags::RELATIONSHIP_TYPE);
+        root
+    }
+
+    #[test]
+    fn find_measurement_by_concept() {
+        let report = test_report();
+        let root = ContentItem::root(&report);
+        assert_eq!(root.value_type(), Some(ValueType::Container));
+        assert_eq!(root.relationship_type(), None);
+        assert_eq!(
+            root.concept_name(),
+            Some(CodedEntry::new(
+                "126000",
+                "DCM",
+                "Imaging Measurement Report"
+            ))
+        );
+
+        // the code meaning does not need to match
+        let length = root
+            .find_by_concept(&CodedEntry::new("410668003", "SCT", "length"))
+            .unwrap();
+        assert_eq!(length.value_type(), Some(ValueType::Num));
+        assert_eq!(length.relationship_type(), Some(RelationshipType::Contains));
+        assert_eq!(length.identifier(), &[1, 2, 1]);
+        let (value, unit) = length.measurement().unwrap();
+        assert_eq!(value, 12.5);
+        assert_eq!(unit, CodedEntry::new("mm", "UCUM", "millimeter"));
+
+        // the floating point value is preferred
+        let width = root
+            .find_by_concept(&CodedEntry::new("103355008", "SCT", "Width"))
+            .unwrap();
+        assert_eq!(width.measurement().map(|(value, _)| value), Some(4.0625));
+
+        let site = root
+            .find_by_concept(&CodedEntry::new("363698007", "SCT", "Finding Site"))
+            .unwrap();
+        assert_eq!(site.code().map(|code| code.meaning), Some("Liver".into()));
+        assert_eq!(site.measurement(), None);
+
+        assert!(root
+            .find_by_concept(&CodedEntry::new("410668003", "DCM", "Length"))
+            .is_none());
+    }
+
+    #[test]
+    fn navigate_content_tree() {
+        let report = test_report();
+        let root = ContentItem::root(&report);
+
+        let children: Vec<_> = root.children().collect();
+        assert_eq!(children.len(), 3);
+        assert_eq!(
+            children[0].relationship_type(),
+            Some(RelationshipType::HasObsContext)
+        );
+        assert_eq!(children[0].person_name().as_deref(), Some("Doe^Jane"));
+        assert_eq!(children[1].children().count(), 3);
+        assert_eq!(children[2].text().as_deref(), Some("Small lesion"));
+
+        // the item included by reference resolves to the length measurement
+        let inferred: Vec<_> = children[2].children().collect();
+        assert_eq!(inferred.len(), 1);
+        assert!(inferred[0].is_by_reference());
+        assert_eq!(
+            inferred[0].relationship_type(),
+            Some(RelationshipType::InferredFrom)
+        );
+        assert_eq!(inferred[0].value_type(), Some(ValueType::Num));
+        assert_eq!(inferred[0].identifier(), &[1, 2, 1]);
+        assert_eq!(
+            inferred[0].measurement().map(|(value, _)| value),
+            Some(12.5)
+        );
+
+        let identifiers: Vec<_> = root
+            .descendants()
+            .map(|item| item.identifier().to_vec())
+            .collect();
+        assert_eq!(
+            identifiers,
+            [
+                vec![1, 1],
+                vec![1, 2],
+                vec![1, 2, 1],
+                vec![1, 2, 2],
+                vec![1, 2, 3],
+                vec![1, 3],
+                vec![1, 2, 1],
+            ]
+        );
+
+        let length = CodedEntry::new("410668003", "SCT", "Length");
+        assert_eq!(root.find_all_by_concept(&length).count(), 2);
+    }
+
+    #[test]
+    fn skip_dangling_references() {
+        let reference = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::RELATIONSHIP_TYPE, VR::CS, "INFERRED FROM"),
+            DataElement::new(
+                tags::REFERENCED_CONTENT_ITEM_IDENTIFIER,
+                VR::UL,
+                dicom_value!(U32, [1, 7]),
+            ),
+        ]);
+        let report = with_content(
+            content_item(
+                "",
+                "CONTAINER",
+                ("126000", "DCM", "Imaging Measurement Report"),
+            ),
+            vec![reference],
+        );
+        let root = ContentItem::root(&report);
+        assert_eq!(root.children().count(), 0);
+        assert_eq!(root.descendants().count(), 0);
+    }
+}
@@ -0,0 +1,438 @@
+//! Generation of unique identifiers (UIDs)
+//! for new and derived DICOM instances.
+//!
+//! A [`UidGenerator`] produces UIDs under an organization root,
+//! by appending a component derived from a random UUID
+//! and a counter which is unique within the process.
+//! The resulting UIDs are well formed and at most 64 characters long
+//! (see [`validate_uid`]).
+//!
+//! UIDs in the `2.25` form,
+//! derived from a UUID as per [PS3.5 sect B.2][1],
+//! are generated with [`uuid_uid`].
+//!
+//! Random numbers are drawn from the operating system.
+//! On `wasm32-unknown-unknown`, which has no source of randomness,
+//! a seed must be provided with [`seed_random`]
+//! before any UID is generated.
+//!
+//! [1]: https://dicom.nema.org/medical/dicom/2023e/output/chtml/part05/sect_B.2.html
+//!
+//! # Example
+//!
+//! ```
+//! use dicom_object::uid::UidGenerator;
+//!
+//! let generator = UidGenerator::with_root("1.2.826.0.1.3680043.10")?;
+//! let study_uid = generator.generate()?;
+//! let series_uid = generator.generate()?;
+//! assert!(study_uid.starts_with("1.2.826.0.1.3680043.10."));
+//! assert_ne!(study_uid, series_uid);
+//! # Ok::<_, dicom_object::uid::UidGeneratorError>(())
+//! ```
+use std::collections::hash_map::DefaultHasher;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::collections::hash_map::RandomState;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::hash::BuildHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::SystemTime;
+
+use dicom_encoding::text::uid::{validate_uid, UidError, MAX_UID_LENGTH};
+use snafu::{ensure, ResultExt, Snafu};
+
+use crate::IMPLEMENTATION_CLASS_UID;
+
+/// The organization root used by [`UidGenerator::new`],
+/// which is the implementation class UID of DICOM-rs.
+pub const DEFAULT_UID_ROOT: &str = IMPLEMENTATION_CLASS_UID;
+
+/// The minimum number of characters
+/// which an organization root must leave for generated components,
+/// including the separating dots.
+pub const MIN_GENERATED_LENGTH: usize = 20;
+
+/// The minimum number of random digits in a generated UID.
+pub const MIN_RANDOM_LENGTH: usize = 10;
+
+/// The random seed provided with [`seed_random`].
+static SEED: OnceLock<[u8; 16]> = OnceLock::new();
+
+/// The number of random numbers drawn from the seed so far.
+static DRAWS: AtomicU64 = AtomicU64::new(0);
+
+/// The random component of the UIDs of [`UidGenerator`],
+/// drawn once per process.
+static RANDOM: OnceLock<String> = OnceLock::new();
+
+/// An error creating a UID generator or generating UIDs.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum UidGeneratorError {
+    /// The organization root is not a valid UID.
+    #[snafu(display("Invalid organization root `{}`", root))]
+    InvalidRoot { root: String, source: UidError },
+    /// The organization root leaves too little room for generated components.
+    #[snafu(display(
+        "Organization root `{}` is {} characters long, the maximum is {}",
+        root,
+        root.len(),
+        MAX_UID_LENGTH - MIN_GENERATED_LENGTH
+    ))]
+    RootTooLong { root: String },
+    /// The counter of generated UIDs grew too long
+    /// to leave [`MIN_RANDOM_LENGTH`] random digits under the organization root.
+    #[snafu(display("No more UIDs can be generated under `{}` in this process", root))]
+    Exhausted { root: String },
+    /// A random seed was provided after random numbers were drawn,
+    /// or more than once.
+    #[snafu(display("Random numbers were already drawn or seeded"))]
+    AlreadySeeded,
+}
+
+/// A generator of unique identifiers under an organization root.
+///
+/// Each UID has the form `<root>.<random>.<counter>`,
+/// where `<random>` is the decimal form of a random UUID
+/// drawn once per process,
+/// truncated to fit in 64 characters,
+/// and `<counter>` is incremented on every UID generated in the process.
+/// As such, UIDs are unique within the process,
+/// even when generated from multiple threads or generators,
+/// and unique across processes with high probability,
+/// as `<random>` always has at least [`MIN_RANDOM_LENGTH`] digits.
+/// Since the root leaves at least [`MIN_GENERATED_LENGTH`] characters,
+/// the first 10<sup>8</sup> UIDs of the process can always be generated,
+/// after which [`generate`](Self::generate) may fail
+/// for the longest roots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UidGenerator {
+    root: String,
+}
+
+impl Default for UidGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UidGenerator {
+    /// Create a UID generator under the default organization root
+    /// ([`DEFAULT_UID_ROOT`]).
+    pub fn new() -> Self {
+        UidGenerator {
+            root: DEFAULT_UID_ROOT.to_string(),
+        }
+    }
+
+    /// Create a UID generator under the given organization root.
+    ///
+    /// Fails if the root is not a valid UID,
+    /// or if it does not leave at least [`MIN_GENERATED_LENGTH`] characters
+    /// for the generated components.
+    pub fn with_root(root: impl Into<String>) -> Result<Self, UidGeneratorError> {
+        let root = root.into();
+        if let Err(source) = validate_uid(&root) {
+            return Err(source).context(InvalidRootSnafu { root });
+        }
+        ensure!(
+            root.len() + MIN_GENERATED_LENGTH <= MAX_UID_LENGTH,
+            RootTooLongSnafu { root }
+        );
+        Ok(UidGenerator { root })
+    }
+
+    /// The organization root of the generated UIDs.
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Generate a new UID.
+    ///
+    /// Fails if the counter of the process leaves less than
+    /// [`MIN_RANDOM_LENGTH`] random digits under this root.
+    ///
+    /// # Panics
+    ///
+    /// On `wasm32-unknown-unknown`,
+    /// this panics if no seed was provided with [`seed_random`].
+    pub fn generate(&self) -> Result<String, UidGeneratorError> {
+        static COUNTER: AtomicU64 = AtomicU64::new(1);
+
+        let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let random = RANDOM.get_or_init(|| random_uuid().to_string());
+        self.compose(random, counter)
+    }
+
+    /// Build a UID from the random component and the counter.
+    fn compose(&self, random: &str, counter: u64) -> Result<String, UidGeneratorError> {
+        let counter = counter.to_string();
+        // room for the random component, between the two dots
+        let len = MAX_UID_LENGTH
+            .saturating_sub(self.root.len() + counter.len() + 2)
+            .min(random.len());
+        ensure!(
+            len >= MIN_RANDOM_LENGTH,
+            ExhaustedSnafu {
+                root: self.root.clone()
+            }
+        );
+        // the random component has no leading zero,
+        // and the counter is distinct in each UID,
+        // so that any prefix of the random component will do
+        Ok(format!("{}.{}.{}", self.root, &random[..len], counter))
+    }
+}
+
+/// Provide a random seed from which all random numbers
+/// behind generated UIDs are drawn in this process,
+/// instead of the operating system.
+///
+/// This is required on `wasm32-unknown-unknown`,
+/// which has no source of randomness of its own,
+/// and should be called there with bytes from a secure source
+/// such as `crypto.getRandomValues`,
+/// before any UID is generated.
+/// Distinct seeds must be used in distinct sessions.
+///
+/// Fails if a seed was already provided
+/// or if UIDs were already generated.
+pub fn seed_random(seed: [u8; 16]) -> Result<(), UidGeneratorError> {
+    ensure!(
+        DRAWS.load(Ordering::SeqCst) == 0 && RANDOM.get().is_none(),
+        AlreadySeededSnafu
+    );
+    SEED.set(seed).map_err(|_| UidGeneratorError::AlreadySeeded)
+}
+
+/// Generate a new UID in the `2.25` form,
+/// from the decimal form of a random (version 4) UUID.
+///
+/// # Panics
+///
+/// On `wasm32-unknown-unknown`,
+/// this panics if no seed was provided with [`seed_random`].
+///
+/// # Example
+///
+/// ```
+/// use dicom_object::uid::uuid_uid;
+///
+/// let uid = uuid_uid();
+/// assert!(uid.starts_with("2.25."));
+/// assert!(uid.len() <= 44);
+/// ```
+pub fn uuid_uid() -> String {
+    format!("2.25.{}", random_uuid())
+}
+
+/// Create a random (version 4) UUID as a 128-bit number.
+///
+/// # Panics
+///
+/// On `wasm32-unknown-unknown`,
+/// this panics if no seed was provided with [`seed_random`].
+fn random_uuid() -> u128 {
+    let bits = u128::from_be_bytes(random_bytes());
+    // version 4 in bits 76 to 79, variant 0b10 in bits 62 and 63
+    (bits & !(0xF << 76) & !(0b11 << 62)) | (0x4 << 76) | (0b10 << 62)
+}
+
+/// Random bytes from the seed if one was provided,
+/// from the operating system otherwise.
+fn random_bytes() -> [u8; 16] {
+    let draw = DRAWS.fetch_add(1, Ordering::SeqCst);
+    match SEED.get() {
+        Some(seed) => seeded_random_bytes(seed, draw),
+        None => system_random_bytes(),
+    }
+}
+
+/// Random bytes from the operating system,
+/// or from the fallback if these are not available.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn system_random_bytes() -> [u8; 16] {
+    let mut bytes = [0; 16];
+    if let Err(e) = getrandom::getrandom(&mut bytes) {
+        tracing::warn!("Could not obtain random bytes ({}), using a fallback", e);
+        bytes = fallback_random_bytes();
+    }
+    bytes
+}
+
+/// There is no source of randomness on this target.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn system_random_bytes() -> [u8; 16] {
+    panic!("No source of randomness on this target, a seed must be provided with `seed_random`")
+}
+
+/// Random bytes from the randomly keyed hasher of the standard library,
+/// mixed with the current time.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn fallback_random_bytes() -> [u8; 16] {
+    let state = RandomState::new();
+    let mut bytes = [0; 16];
+    for (i, chunk) in bytes.chunks_mut(8).enumerate() {
+        let mut hasher = state.build_hasher();
+        i.hash(&mut hasher);
+        SystemTime::now().hash(&mut hasher);
+        chunk.copy_from_slice(&hasher.finish().to_be_bytes());
+    }
+    bytes
+}
+
+/// Random bytes derived from the seed and the index of the draw,
+/// so that each draw is different.
+fn seeded_random_bytes(seed: &[u8; 16], draw: u64) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    for (i, chunk) in bytes.chunks_mut(8).enumerate() {
+        let mut hasher = DefaultHasher::new();
+        (seed, draw, i).hash(&mut hasher);
+        chunk.copy_from_slice(&hasher.finish().to_be_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn generate_unique_valid_uids() {
+        let generator = UidGenerator::new();
+        let uids: HashSet<_> = (0..10_000).map(|_| generator.generate().unwrap()).collect();
+        assert_eq!(uids.len(), 10_000);
+        for uid in &uids {
+            assert!(uid.starts_with(DEFAULT_UID_ROOT), "{}", uid);
+            assert_eq!(validate_uid(uid), Ok(()), "{}", uid);
+        }
+    }
+
+    #[test]
+    fn generate_unique_uids_across_threads() {
+        let generator = UidGenerator::with_root("1.2.3").unwrap();
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let generator = generator.clone();
+                std::thread::spawn(move || {
+                    (0..2_500)
+                        .map(|_| generator.generate().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let uids: HashSet<_> = threads
+            .into_iter()
+            .flat_map(|t| t.join().unwrap())
+            .collect();
+        assert_eq!(uids.len(), 10_000);
+        for uid in &uids {
+            assert_eq!(validate_uid(uid), Ok(()), "{}", uid);
+        }
+    }
+
+    #[test]
+    fn generate_with_long_root() {
+        // the longest root allowed
+        let root = format!(
+            "1.2.{}",
+            "3".repeat(MAX_UID_LENGTH - MIN_GENERATED_LENGTH - 4)
+        );
+        assert_eq!(root.len(), 44);
+        let generator = UidGenerator::with_root(&*root).unwrap();
+        let uids: HashSet<_> = (0..10_000).map(|_| generator.generate().unwrap()).collect();
+        assert_eq!(uids.len(), 10_000);
+        for uid in &uids {
+            assert!(uid.starts_with(&format!("{}.", root)), "{}", uid);
+            assert!(uid.len() <= MAX_UID_LENGTH, "{}", uid);
+            assert_eq!(validate_uid(uid), Ok(()), "{}", uid);
+        }
+
+        // one character too many
+        let root = format!("{}3", root);
+        assert!(matches!(
+            UidGenerator::with_root(&*root),
+            Err(UidGeneratorError::RootTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn generate_keeps_random_digits() {
+        let random = random_uuid().to_string();
+        let root = format!(
+            "1.2.{}",
+            "3".repeat(MAX_UID_LENGTH - MIN_GENERATED_LENGTH - 4)
+        );
+        let generator = UidGenerator::with_root(&*root).unwrap();
+
+        // the largest counter which fits with the longest root
+        let uid = generator.compose(&random, 99_999_999).unwrap();
+        assert_eq!(uid.len(), MAX_UID_LENGTH);
+        assert_eq!(
+            uid,
+            format!("{}.{}.99999999", root, &random[..MIN_RANDOM_LENGTH])
+        );
+        assert!(matches!(
+            generator.compose(&random, 100_000_000),
+            Err(UidGeneratorError::Exhausted { .. })
+        ));
+
+        // shorter roots leave room for more
+        let generator = UidGenerator::with_root("1.2.3").unwrap();
+        assert!(generator.compose(&random, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn seeded_random_bytes_differ_per_draw() {
+        let seed = [7; 16];
+        assert_eq!(seeded_random_bytes(&seed, 0), seeded_random_bytes(&seed, 0));
+        assert_ne!(seeded_random_bytes(&seed, 0), seeded_random_bytes(&seed, 1));
+        assert_ne!(
+            seeded_random_bytes(&seed, 0),
+            seeded_random_bytes(&[8; 16], 0)
+        );
+
+        // too late to seed once random numbers were drawn
+        random_uuid();
+        assert!(matches!(
+            seed_random(seed),
+            Err(UidGeneratorError::AlreadySeeded)
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_root() {
+        assert!(matches!(
+            UidGenerator::with_root("1.2.03"),
+            Err(UidGeneratorError::InvalidRoot {
+                source: UidError::LeadingZero { position: 4 },
+                ..
+            })
+        ));
+        assert!(matches!(
+            UidGenerator::with_root("1.2."),
+            Err(UidGeneratorError::InvalidRoot { .. })
+        ));
+    }
+
+    #[test]
+    fn generate_uuid_uids() {
+        let uids: HashSet<_> = (0..10_000).map(|_| uuid_uid()).collect();
+        assert_eq!(uids.len(), 10_000);
+        for uid in &uids {
+            assert!(uid.starts_with("2.25."), "{}", uid);
+            assert_eq!(validate_uid(uid), Ok(()), "{}", uid);
+        }
+    }
+
+    #[test]
+    fn uuid_has_version_and_variant() {
+        let uuid = random_uuid();
+        assert_eq!((uuid >> 76) & 0xF, 4);
+        assert_eq!((uuid >> 62) & 0b11, 0b10);
+    }
+}
@@ -0,0 +1,398 @@
+//! Traversal of DICOM objects and their nested data sets.
+//!
+//! A [`DataVisitor`] is called back for every element of an object,
+//! at any depth,
+//! as well as when entering and leaving each data set sequence item.
+//! Each callback receives the [path](PathSegment) to the current item,
+//! and may stop the traversal early by returning [`ControlFlow::Break`].
+//! See [`InMemDicomObject::walk`] and [`InMemDicomObject::walk_mut`].
+//!
+//! # Example
+//!
+//! ```
+//! # use std::ops::ControlFlow;
+//! # use dicom_core::{DataElement, VR, value::DataSetSequence};
+//! # use dicom_dictionary_std::tags;
+//! use dicom_object::InMemDicomObject;
+//! use dicom_object::mem::InMemElement;
+//! use dicom_object::visit::{DataVisitor, PathSegment};
+//!
+//! /// Count the number of elements at each depth
+//! #[derive(Default)]
+//! struct DepthCounter(Vec<usize>);
+//!
+//! impl DataVisitor for DepthCounter {
+//!     fn element(&mut self, path: &[PathSegment], _elem: &InMemElement) -> ControlFlow<()> {
+//!         if self.0.len() <= path.len() {
+//!             self.0.resize(path.len() + 1, 0);
+//!         }
+//!         self.0[path.len()] += 1;
+//!         ControlFlow::Continue(())
+//!     }
+//! }
+//!
+//! let obj = InMemDicomObject::from_element_iter([
+//!     DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+//!     DataElement::new(
+//!         tags::REFERENCED_IMAGE_SEQUENCE,
+//!         VR::SQ,
+//!         DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+//!             DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "2.25.1"),
+//!         ])]),
+//!     ),
+//! ]);
+//!
+//! let mut counter = DepthCounter::default();
+//! obj.walk(&mut counter);
+//! assert_eq!(counter.0, [2, 1]);
+//! ```
+//!
+//! [`InMemDicomObject::walk`]: crate::InMemDicomObject::walk
+//! [`InMemDicomObject::walk_mut`]: crate::InMemDicomObject::walk_mut
+use std::ops::ControlFlow;
+
+use dicom_core::dictionary::DataDictionary;
+use dicom_core::header::{DataElementHeader, Header};
+use dicom_core::ops::{AttributeSelector, AttributeSelectorStep};
+use dicom_core::value::Value;
+use dicom_core::{PrimitiveValue, Tag};
+use dicom_dictionary_std::StandardDataDictionary;
+
+use crate::mem::{InMemDicomObject, InMemElement};
+
+/// A step into a data set sequence item:
+/// the tag of the sequence and the index of the item.
+///
+/// A path to an item in a nested data set
+/// is given as a slice of these segments,
+/// from the root of the object.
+/// The root data set has an empty path.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct PathSegment {
+    /// the tag of the data set sequence
+    pub tag: Tag,
+    /// the index of the item in the sequence, starting at 0
+    pub item: u32,
+}
+
+impl From<PathSegment> for AttributeSelectorStep {
+    fn from(segment: PathSegment) -> Self {
+        AttributeSelectorStep::Nested {
+            tag: segment.tag,
+            item: segment.item,
+        }
+    }
+}
+
+/// Build the attribute selector of the element with the given tag
+/// in the data set at the given path.
+pub fn selector(path: &[PathSegment], tag: Tag) -> AttributeSelector {
+    AttributeSelector::new(
+        path.iter()
+            .copied()
+            .map(AttributeSelectorStep::from)
+            .chain(std::iter::once(AttributeSelectorStep::Tag(tag))),
+    )
+    .expect("selector should end with a tag step")
+}
+
+/// A visitor of the elements of a DICOM object.
+///
+/// All methods do nothing and continue the traversal by default.
+/// Returning [`ControlFlow::Break`] from any of them
+/// stops the traversal immediately.
+pub trait DataVisitor<D = StandardDataDictionary> {
+    /// Visit a data element
+    /// in the data set at the given path.
+    ///
+    /// Data set sequences are visited before their items.
+    fn element(&mut self, path: &[PathSegment], elem: &InMemElement<D>) -> ControlFlow<()> {
+        let _ = (path, elem);
+        ControlFlow::Continue(())
+    }
+
+    /// Enter the data set sequence item at the given path,
+    /// whose last segment identifies the item.
+    fn enter_item(&mut self, path: &[PathSegment]) -> ControlFlow<()> {
+        let _ = path;
+        ControlFlow::Continue(())
+    }
+
+    /// Leave the data set sequence item at the given path,
+    /// after all of its elements were visited.
+    fn leave_item(&mut self, path: &[PathSegment]) -> ControlFlow<()> {
+        let _ = path;
+        ControlFlow::Continue(())
+    }
+}
+
+/// A visitor of the elements of a DICOM object
+/// which may modify them in place.
+///
+/// This is the mutable counterpart of [`DataVisitor`].
+/// Sequences are descended into after the visitor is called,
+/// so the items seen are those of the element as modified by the visitor.
+pub trait DataVisitorMut<D = StandardDataDictionary> {
+    /// Visit a data element
+    /// in the data set at the given path.
+    fn element(&mut self, path: &[PathSegment], elem: &mut InMemElement<D>) -> ControlFlow<()> {
+        let _ = (path, elem);
+        ControlFlow::Continue(())
+    }
+
+    /// Enter the data set sequence item at the given path,
+    /// whose last segment identifies the item.
+    fn enter_item(&mut self, path: &[PathSegment]) -> ControlFlow<()> {
+        let _ = path;
+        ControlFlow::Continue(())
+    }
+
+    /// Leave the data set sequence item at the given path,
+    /// after all of its elements were visited.
+    fn leave_item(&mut self, path: &[PathSegment]) -> ControlFlow<()> {
+        let _ = path;
+        ControlFlow::Continue(())
+    }
+}
+
+pub(crate) fn walk<D, V>(
+    obj: &InMemDicomObject<D>,
+    path: &mut Vec<PathSegment>,
+    visitor: &mut V,
+) -> ControlFlow<()>
+where
+    D: DataDictionary + Clone,
+    V: ?Sized + DataVisitor<D>,
+{
+    for elem in obj {
+        visitor.element(path, elem)?;
+        for (i, item) in elem.items().unwrap_or_default().iter().enumerate() {
+            path.push(PathSegment {
+                tag: elem.tag(),
+                item: i as u32,
+            });
+            visitor.enter_item(path)?;
+            walk(item, path, visitor)?;
+            visitor.leave_item(path)?;
+            path.pop();
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+pub(crate) fn walk_mut<D, V>(
+    obj: &mut InMemDicomObject<D>,
+    path: &mut Vec<PathSegment>,
+    visitor: &mut V,
+) -> ControlFlow<()>
+where
+    D: DataDictionary + Clone,
+    V: ?Sized + DataVisitorMut<D>,
+{
+    for elem in obj.elements_mut() {
+        visitor.element(path, elem)?;
+        let tag = elem.tag();
+        if let Some(items) = elem.items_mut() {
+            for (i, item) in items.iter_mut().enumerate() {
+                path.push(PathSegment {
+                    tag,
+                    item: i as u32,
+                });
+                visitor.enter_item(path)?;
+                walk_mut(item, path, visitor)?;
+                visitor.leave_item(path)?;
+                path.pop();
+            }
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// A mutable visitor which replaces primitive values
+/// through the given function.
+pub(crate) struct MapValues<F>(pub(crate) F);
+
+impl<D, F> DataVisitorMut<D> for MapValues<F>
+where
+    F: FnMut(&[PathSegment], &DataElementHeader, &PrimitiveValue) -> Option<PrimitiveValue>,
+{
+    fn element(&mut self, path: &[PathSegment], elem: &mut InMemElement<D>) -> ControlFlow<()> {
+        let new_value = match elem.value() {
+            Value::Primitive(value) => (self.0)(path, elem.header(), value),
+            _ => None,
+        };
+        if let Some(new_value) = new_value {
+            let mut new_value = Some(new_value);
+            elem.update_value(|value| {
+                if let Some(new_value) = new_value.take() {
+                    *value = Value::Primitive(new_value);
+                }
+            });
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{DataElement, VR};
+    use dicom_dictionary_std::tags;
+    use dicom_transfer_syntax_registry::entries::EXPLICIT_VR_LITTLE_ENDIAN;
+
+    /// A multi-frame object with UIDs at several depths
+    fn nested_fixture() -> InMemDicomObject {
+        let source_image = |uid: &str| {
+            InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::REFERENCED_SOP_CLASS_UID,
+                    VR::UI,
+                    "1.2.840.10008.5.1.4.1.1.4",
+                ),
+                DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, uid),
+            ])
+        };
+        let frame = |uid: &str| {
+            InMemDicomObject::from_element_iter([DataElement::new(
+                tags::DERIVATION_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::SOURCE_IMAGE_SEQUENCE,
+                        VR::SQ,
+                        DataSetSequence::from(vec![source_image(uid)]),
+                    ),
+                ])]),
+            )])
+        };
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "1.2.3.4"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+            DataElement::new(
+                tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![frame("1.2.3.4.1"), frame("1.2.3.4.2")]),
+            ),
+        ])
+    }
+
+    #[derive(Default)]
+    struct DepthCounter {
+        elements: Vec<usize>,
+        open_items: Vec<Vec<PathSegment>>,
+        items: usize,
+    }
+
+    impl DataVisitor for DepthCounter {
+        fn element(&mut self, path: &[PathSegment], _elem: &InMemElement) -> ControlFlow<()> {
+            if self.elements.len() <= path.len() {
+                self.elements.resize(path.len() + 1, 0);
+            }
+            self.elements[path.len()] += 1;
+            ControlFlow::Continue(())
+        }
+
+        fn enter_item(&mut self, path: &[PathSegment]) -> ControlFlow<()> {
+            self.open_items.push(path.to_vec());
+            self.items += 1;
+            ControlFlow::Continue(())
+        }
+
+        fn leave_item(&mut self, path: &[PathSegment]) -> ControlFlow<()> {
+            assert_eq!(self.open_items.pop().as_deref(), Some(path));
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn count_elements_per_depth() {
+        let obj = nested_fixture();
+        let mut counter = DepthCounter::default();
+        assert_eq!(obj.walk(&mut counter), ControlFlow::Continue(()));
+        assert_eq!(counter.elements, [3, 2, 2, 4]);
+        assert_eq!(counter.items, 6);
+        assert!(counter.open_items.is_empty());
+    }
+
+    #[test]
+    fn walk_stops_on_break() {
+        struct FindUid(Vec<AttributeSelector>);
+
+        impl DataVisitor for FindUid {
+            fn element(&mut self, path: &[PathSegment], elem: &InMemElement) -> ControlFlow<()> {
+                if elem.tag() == tags::REFERENCED_SOP_INSTANCE_UID {
+                    self.0.push(selector(path, elem.tag()));
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            }
+        }
+
+        let obj = nested_fixture();
+        let mut visitor = FindUid(Vec::new());
+        assert_eq!(obj.walk(&mut visitor), ControlFlow::Break(()));
+        assert_eq!(visitor.0.len(), 1);
+        assert_eq!(
+            obj.value_at(visitor.0[0].clone())
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "1.2.3.4.1"
+        );
+    }
+
+    #[test]
+    fn rewrite_all_uids_with_walk_mut() {
+        let mut obj = nested_fixture();
+        let mut count = 0;
+        obj.map_values(|_path, header, value| {
+            if header.vr() != VR::UI {
+                return None;
+            }
+            count += 1;
+            Some(PrimitiveValue::from(format!(
+                "2.25.{}",
+                value.to_str().replace('.', "")
+            )))
+        });
+        assert_eq!(count, 5);
+
+        // write and read back
+        let mut buf = Vec::new();
+        obj.write_dataset_with_ts(&mut buf, &EXPLICIT_VR_LITTLE_ENDIAN.erased())
+            .unwrap();
+        let obj =
+            InMemDicomObject::read_dataset_with_ts(&buf[..], &EXPLICIT_VR_LITTLE_ENDIAN.erased())
+                .unwrap();
+
+        assert_eq!(obj.string(tags::SOP_INSTANCE_UID).unwrap(), "2.25.1234");
+        assert_eq!(obj.string(tags::PATIENT_NAME).unwrap(), "Doe^John");
+        let frames = obj
+            .element(tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        for (frame, expected) in frames.iter().zip(["2.25.12341", "2.25.12342"]) {
+            let source = &frame
+                .element(tags::DERIVATION_IMAGE_SEQUENCE)
+                .unwrap()
+                .items()
+                .unwrap()[0]
+                .element(tags::SOURCE_IMAGE_SEQUENCE)
+                .unwrap()
+                .items()
+                .unwrap()[0];
+            assert_eq!(
+                source.string(tags::REFERENCED_SOP_INSTANCE_UID).unwrap(),
+                expected
+            );
+            assert_eq!(
+                source.string(tags::REFERENCED_SOP_CLASS_UID).unwrap(),
+                "2.25.1284010008514114"
+            );
+        }
+    }
+}
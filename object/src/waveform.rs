@@ -0,0 +1,567 @@
+//! Decoding of waveform data.
+//!
+//! Waveform objects, such as electrocardiograms
+//! and hemodynamic recordings,
+//! hold their signals in the _Waveform Sequence_ (5400,0100).
+//! Each item of the sequence is a multiplex group:
+//! a set of channels sampled at the same frequency,
+//! with the samples of all channels interleaved in _Waveform Data_ (5400,1010),
+//! and the definition of each channel
+//! in _Channel Definition Sequence_ (003A,0200).
+//!
+//! [`multiplex_groups`] reads all multiplex groups of an object
+//! into [`MultiplexGroup`]s,
+//! holding the samples of each channel separately
+//! and calibrated in the units of the channel.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dicom_object::open_file;
+//! use dicom_object::waveform::multiplex_groups;
+//!
+//! let obj = open_file("ecg.dcm")?;
+//! for group in multiplex_groups(&obj)? {
+//!     for channel in &group.channels {
+//!         println!(
+//!             "{:?}: {} samples in {:?}",
+//!             channel.label,
+//!             channel.samples.len(),
+//!             channel.units.as_ref().map(|units| &units.value),
+//!         );
+//!     }
+//! }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::convert::TryFrom;
+
+use dicom_core::value::PrimitiveValue;
+use dicom_core::Tag;
+use dicom_dictionary_std::tags;
+use snafu::{ensure, Backtrace, OptionExt, Snafu};
+
+use crate::mem::InMemDicomObject;
+use crate::sr::CodedEntry;
+
+/// An error which may occur when decoding waveform data.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum WaveformError {
+    /// Missing Waveform Sequence
+    MissingWaveformSequence { backtrace: Backtrace },
+    /// Missing attribute {tag} in multiplex group
+    MissingAttribute { tag: Tag, backtrace: Backtrace },
+    /// Invalid value of {tag} in multiplex group
+    InvalidValue { tag: Tag, backtrace: Backtrace },
+    /// Unsupported waveform sample interpretation {interpretation} with {bits_allocated} bits allocated
+    UnsupportedInterpretation {
+        interpretation: String,
+        bits_allocated: u16,
+        backtrace: Backtrace,
+    },
+    /// Waveform data holds {actual} samples, but {expected} were expected
+    DataLength {
+        expected: usize,
+        actual: usize,
+        backtrace: Backtrace,
+    },
+}
+
+pub type Result<T, E = WaveformError> = std::result::Result<T, E>;
+
+/// The encoding of waveform samples,
+/// from _Waveform Sample Interpretation_ (5400,1006)
+/// and _Waveform Bits Allocated_ (5400,1004).
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum SampleInterpretation {
+    /// `SB`: signed 8 bit linear
+    Signed8,
+    /// `UB`: unsigned 8 bit linear
+    Unsigned8,
+    /// `MB`: 8 bit mu-law (in accordance with ITU-T Recommendation G.711)
+    MuLaw8,
+    /// `AB`: 8 bit A-law (in accordance with ITU-T Recommendation G.711)
+    ALaw8,
+    /// `SS`: signed 16 bit linear
+    Signed16,
+    /// `US`: unsigned 16 bit linear
+    Unsigned16,
+}
+
+impl SampleInterpretation {
+    /// Obtain the sample interpretation of the given code string
+    /// and number of bits allocated per sample,
+    /// or `None` if they are not supported or do not match.
+    pub fn from_code(code: &str, bits_allocated: u16) -> Option<Self> {
+        let interpretation = match code.trim_end_matches([' ', '\0']) {
+            "SB" => SampleInterpretation::Signed8,
+            "UB" => SampleInterpretation::Unsigned8,
+            "MB" => SampleInterpretation::MuLaw8,
+            "AB" => SampleInterpretation::ALaw8,
+            "SS" => SampleInterpretation::Signed16,
+            "US" => SampleInterpretation::Unsigned16,
+            _ => return None,
+        };
+        if interpretation.bits_allocated() == bits_allocated {
+            Some(interpretation)
+        } else {
+            None
+        }
+    }
+
+    /// Obtain the code string of this sample interpretation.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SampleInterpretation::Signed8 => "SB",
+            SampleInterpretation::Unsigned8 => "UB",
+            SampleInterpretation::MuLaw8 => "MB",
+            SampleInterpretation::ALaw8 => "AB",
+            SampleInterpretation::Signed16 => "SS",
+            SampleInterpretation::Unsigned16 => "US",
+        }
+    }
+
+    /// The number of bits allocated per sample.
+    pub fn bits_allocated(self) -> u16 {
+        match self {
+            SampleInterpretation::Signed8
+            | SampleInterpretation::Unsigned8
+            | SampleInterpretation::MuLaw8
+            | SampleInterpretation::ALaw8 => 8,
+            SampleInterpretation::Signed16 | SampleInterpretation::Unsigned16 => 16,
+        }
+    }
+
+    /// Decode the samples in the given waveform data.
+    ///
+    /// Waveform data is in little endian,
+    /// whether it is held as bytes or as 16-bit words.
+    /// Companded samples are expanded to 16-bit linear values.
+    fn decode(self, data: &PrimitiveValue) -> Vec<i32> {
+        match self.bits_allocated() {
+            8 => {
+                let bytes: Vec<u8> = match data {
+                    PrimitiveValue::U16(words) => {
+                        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+                    }
+                    PrimitiveValue::I16(words) => {
+                        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+                    }
+                    data => data.to_bytes().into_owned(),
+                };
+                bytes
+                    .into_iter()
+                    .map(|b| match self {
+                        SampleInterpretation::Signed8 => i32::from(b as i8),
+                        SampleInterpretation::MuLaw8 => mu_law_to_linear(b),
+                        SampleInterpretation::ALaw8 => a_law_to_linear(b),
+                        _ => i32::from(b),
+                    })
+                    .collect()
+            }
+            _ => {
+                let words: Vec<u16> = match data {
+                    PrimitiveValue::U16(words) => words.to_vec(),
+                    PrimitiveValue::I16(words) => words.iter().map(|&w| w as u16).collect(),
+                    data => data
+                        .to_bytes()
+                        .chunks_exact(2)
+                        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                        .collect(),
+                };
+                words
+                    .into_iter()
+                    .map(|w| match self {
+                        SampleInterpretation::Signed16 => i32::from(w as i16),
+                        _ => i32::from(w),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Expand a G.711 mu-law sample.
+fn mu_law_to_linear(sample: u8) -> i32 {
+    let sample = !sample;
+    let exponent = (sample >> 4) & 0x07;
+    let mantissa = i32::from(sample & 0x0F);
+    let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+    if sample & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Expand a G.711 A-law sample.
+fn a_law_to_linear(sample: u8) -> i32 {
+    let sample = sample ^ 0x55;
+    let exponent = (sample >> 4) & 0x07;
+    let mantissa = i32::from(sample & 0x0F);
+    let magnitude = match exponent {
+        0 => (mantissa << 4) + 8,
+        _ => ((mantissa << 4) + 0x108) << (exponent - 1),
+    };
+    if sample & 0x80 != 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+/// A multiplex group of a waveform,
+/// from an item of the _Waveform Sequence_ (5400,0100).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct MultiplexGroup {
+    /// _Multiplex Group Label_ (003A,0020)
+    pub label: Option<String>,
+    /// _Sampling Frequency_ (003A,001A), in Hz
+    pub sampling_frequency: Option<f64>,
+    /// The encoding of the samples
+    pub interpretation: SampleInterpretation,
+    /// _Number of Waveform Samples_ (003A,0010) in each channel
+    pub number_of_samples: usize,
+    /// The channels of the group, in the order of their definitions
+    pub channels: Vec<WaveformChannel>,
+}
+
+/// A channel of a multiplex group, with its calibrated samples.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct WaveformChannel {
+    /// _Channel Label_ (003A,0203)
+    pub label: Option<String>,
+    /// The source of the channel,
+    /// in _Channel Source Sequence_ (003A,0208)
+    pub source: Option<CodedEntry>,
+    /// _Channel Sensitivity_ (003A,0210):
+    /// the value of one unit of a sample,
+    /// in the units of the channel
+    pub sensitivity: Option<f64>,
+    /// The units of the channel,
+    /// in _Channel Sensitivity Units Sequence_ (003A,0211)
+    pub units: Option<CodedEntry>,
+    /// The calibrated samples of the channel
+    pub samples: Vec<f64>,
+}
+
+/// Read all multiplex groups in the _Waveform Sequence_ of an object.
+pub fn multiplex_groups(obj: &InMemDicomObject) -> Result<Vec<MultiplexGroup>> {
+    obj.items(tags::WAVEFORM_SEQUENCE)
+        .context(MissingWaveformSequenceSnafu)?
+        .iter()
+        .map(MultiplexGroup::from_item)
+        .collect()
+}
+
+impl MultiplexGroup {
+    /// Read a multiplex group from an item of the _Waveform Sequence_.
+    ///
+    /// The samples of each channel are calibrated
+    /// with the attributes of its channel definition:
+    ///
+    /// _value_ = _sample_ × _Channel Sensitivity_ × _Channel Sensitivity Correction Factor_
+    /// \+ _Channel Baseline_
+    ///
+    /// where missing attributes are taken as a sensitivity
+    /// and correction factor of 1 and a baseline of 0,
+    /// leaving the samples as stored.
+    pub fn from_item(item: &InMemDicomObject) -> Result<Self> {
+        let number_of_channels = required_int(item, tags::NUMBER_OF_WAVEFORM_CHANNELS)? as usize;
+        let number_of_samples = required_int(item, tags::NUMBER_OF_WAVEFORM_SAMPLES)? as usize;
+        let bits_allocated = u16::try_from(required_int(item, tags::WAVEFORM_BITS_ALLOCATED)?)
+            .ok()
+            .context(InvalidValueSnafu {
+                tag: tags::WAVEFORM_BITS_ALLOCATED,
+            })?;
+        let code = required(item, tags::WAVEFORM_SAMPLE_INTERPRETATION)?.to_str();
+        let interpretation = SampleInterpretation::from_code(&code, bits_allocated).context(
+            UnsupportedInterpretationSnafu {
+                interpretation: code.trim_end_matches([' ', '\0']),
+                bits_allocated,
+            },
+        )?;
+
+        let samples = interpretation.decode(required(item, tags::WAVEFORM_DATA)?);
+        // the data may have a trailing padding byte
+        let expected = number_of_channels * number_of_samples;
+        ensure!(
+            samples.len() >= expected,
+            DataLengthSnafu {
+                expected,
+                actual: samples.len(),
+            }
+        );
+
+        let definitions = item
+            .items(tags::CHANNEL_DEFINITION_SEQUENCE)
+            .unwrap_or_default();
+        let channels = (0..number_of_channels)
+            .map(|index| {
+                let definition = definitions.get(index);
+                let number = |tag| {
+                    definition
+                        .and_then(|def| primitive(def, tag))
+                        .and_then(|value| value.to_float64().ok())
+                };
+                let sensitivity = number(tags::CHANNEL_SENSITIVITY);
+                let scale = sensitivity.unwrap_or(1.)
+                    * number(tags::CHANNEL_SENSITIVITY_CORRECTION_FACTOR).unwrap_or(1.);
+                let baseline = number(tags::CHANNEL_BASELINE).unwrap_or(0.);
+
+                WaveformChannel {
+                    label: definition.and_then(|def| string(def, tags::CHANNEL_LABEL)),
+                    source: definition.and_then(|def| {
+                        CodedEntry::from_sequence(def, tags::CHANNEL_SOURCE_SEQUENCE)
+                    }),
+                    sensitivity,
+                    units: definition.and_then(|def| {
+                        CodedEntry::from_sequence(def, tags::CHANNEL_SENSITIVITY_UNITS_SEQUENCE)
+                    }),
+                    samples: samples[..expected]
+                        .iter()
+                        .skip(index)
+                        .step_by(number_of_channels)
+                        .map(|&sample| f64::from(sample) * scale + baseline)
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Ok(MultiplexGroup {
+            label: string(item, tags::MULTIPLEX_GROUP_LABEL),
+            sampling_frequency: primitive(item, tags::SAMPLING_FREQUENCY)
+                .and_then(|value| value.to_float64().ok()),
+            interpretation,
+            number_of_samples,
+            channels,
+        })
+    }
+}
+
+fn primitive(obj: &InMemDicomObject, tag: Tag) -> Option<&PrimitiveValue> {
+    obj.get(tag)?.value().primitive()
+}
+
+fn required(obj: &InMemDicomObject, tag: Tag) -> Result<&PrimitiveValue> {
+    primitive(obj, tag).context(MissingAttributeSnafu { tag })
+}
+
+fn required_int(obj: &InMemDicomObject, tag: Tag) -> Result<u32> {
+    required(obj, tag)?
+        .to_int()
+        .ok()
+        .context(InvalidValueSnafu { tag })
+}
+
+/// The value of a text attribute, without padding.
+fn string(obj: &InMemDicomObject, tag: Tag) -> Option<String> {
+    Some(
+        primitive(obj, tag)?
+            .to_str()
+            .trim_end_matches([' ', '\0'])
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, VR};
+
+    fn code_item(value: &str, scheme: &str, meaning: &str) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::CODE_VALUE, VR::SH, value),
+            DataElement::new(tags::CODING_SCHEME_DESIGNATOR, VR::SH, scheme),
+            DataElement::new(tags::CODE_MEANING, VR::LO, meaning),
+        ])
+    }
+
+    fn channel_definition(
+        label: &str,
+        sensitivity: &str,
+        correction: &str,
+        baseline: &str,
+    ) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::CHANNEL_LABEL, VR::SH, label),
+            DataElement::new(
+                tags::CHANNEL_SOURCE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![code_item("5.6.3-9-1", "SCPECG", label)]),
+            ),
+            DataElement::new(tags::CHANNEL_SENSITIVITY, VR::DS, sensitivity),
+            DataElement::new(
+                tags::CHANNEL_SENSITIVITY_UNITS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![code_item("uV", "UCUM", "microvolt")]),
+            ),
+            DataElement::new(
+                tags::CHANNEL_SENSITIVITY_CORRECTION_FACTOR,
+                VR::DS,
+                correction,
+            ),
+            DataElement::new(tags::CHANNEL_BASELINE, VR::DS, baseline),
+        ])
+    }
+
+    fn multiplex_group(
+        channels: u16,
+        samples: u32,
+        bits_allocated: u16,
+        interpretation: &str,
+        data: PrimitiveValue,
+    ) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::MULTIPLEX_GROUP_LABEL, VR::SH, "RHYTHM"),
+            DataElement::new(
+                tags::NUMBER_OF_WAVEFORM_CHANNELS,
+                VR::US,
+                dicom_value!(U16, [channels]),
+            ),
+            DataElement::new(
+                tags::NUMBER_OF_WAVEFORM_SAMPLES,
+                VR::UL,
+                dicom_value!(U32, [samples]),
+            ),
+            DataElement::new(tags::SAMPLING_FREQUENCY, VR::DS, "500"),
+            DataElement::new(
+                tags::WAVEFORM_BITS_ALLOCATED,
+                VR::US,
+                dicom_value!(U16, [bits_allocated]),
+            ),
+            DataElement::new(tags::WAVEFORM_SAMPLE_INTERPRETATION, VR::CS, interpretation),
+            DataElement::new(tags::WAVEFORM_DATA, VR::OW, data),
+        ])
+    }
+
+    /// A waveform with two 16-bit signed channels of three samples each.
+    fn test_waveform(data: PrimitiveValue) -> InMemDicomObject {
+        let mut group = multiplex_group(2, 3, 16, "SS", data);
+        group.put(DataElement::new(
+            tags::CHANNEL_DEFINITION_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![
+                channel_definition("Lead I", "2.5", "1", "0"),
+                channel_definition("Lead II", "0.5", "2", "10"),
+            ]),
+        ));
+        InMemDicomObject::from_element_iter([DataElement::new(
+            tags::WAVEFORM_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![group]),
+        )])
+    }
+
+    /// interleaved samples: (100, -1), (-200, 0), (300, 32767)
+    const SAMPLES: [i16; 6] = [100, -1, -200, 0, 300, 32767];
+
+    #[test]
+    fn decode_16_bit_channels() {
+        let words: Vec<u16> = SAMPLES.iter().map(|&s| s as u16).collect();
+        let obj = test_waveform(PrimitiveValue::U16(words.into()));
+
+        let groups = multiplex_groups(&obj).unwrap();
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.label.as_deref(), Some("RHYTHM"));
+        assert_eq!(group.sampling_frequency, Some(500.));
+        assert_eq!(group.interpretation, SampleInterpretation::Signed16);
+        assert_eq!(group.number_of_samples, 3);
+        assert_eq!(group.channels.len(), 2);
+
+        let lead_1 = &group.channels[0];
+        assert_eq!(lead_1.label.as_deref(), Some("Lead I"));
+        assert_eq!(
+            lead_1.source,
+            Some(CodedEntry::new("5.6.3-9-1", "SCPECG", "Lead I"))
+        );
+        assert_eq!(lead_1.sensitivity, Some(2.5));
+        assert_eq!(
+            lead_1.units,
+            Some(CodedEntry::new("uV", "UCUM", "microvolt"))
+        );
+        assert_eq!(lead_1.samples, [250., -500., 750.]);
+
+        let lead_2 = &group.channels[1];
+        assert_eq!(lead_2.label.as_deref(), Some("Lead II"));
+        assert_eq!(lead_2.samples, [9., 10., 32777.]);
+
+        // the same samples held as bytes
+        let bytes: Vec<u8> = SAMPLES.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let obj = test_waveform(PrimitiveValue::from(bytes));
+        assert_eq!(multiplex_groups(&obj).unwrap(), groups);
+    }
+
+    #[test]
+    fn decode_8_bit_interpretations() {
+        let data = [0x00_u8, 0x7F, 0x80, 0xFF];
+        let decode = |interpretation| {
+            let obj = InMemDicomObject::from_element_iter([DataElement::new(
+                tags::WAVEFORM_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![multiplex_group(
+                    1,
+                    4,
+                    8,
+                    interpretation,
+                    PrimitiveValue::from(data.to_vec()),
+                )]),
+            )]);
+            multiplex_groups(&obj)
+                .unwrap()
+                .remove(0)
+                .channels
+                .remove(0)
+                .samples
+        };
+
+        assert_eq!(decode("SB"), [0., 127., -128., -1.]);
+        assert_eq!(decode("UB"), [0., 127., 128., 255.]);
+        assert_eq!(decode("MB"), [-32124., 0., 32124., 0.]);
+        assert_eq!(decode("AB"), [-5504., -848., 5504., 848.]);
+    }
+
+    #[test]
+    fn reject_invalid_waveforms() {
+        let words: Vec<u16> = SAMPLES.iter().map(|&s| s as u16).collect();
+
+        // 8 bits allocated do not match a 16-bit interpretation
+        let group = multiplex_group(2, 3, 8, "SS", PrimitiveValue::U16(words.clone().into()));
+        assert!(matches!(
+            MultiplexGroup::from_item(&group),
+            Err(WaveformError::UnsupportedInterpretation {
+                bits_allocated: 8,
+                ..
+            })
+        ));
+
+        // fewer samples than declared
+        let group = multiplex_group(2, 4, 16, "SS", PrimitiveValue::U16(words.into()));
+        assert!(matches!(
+            MultiplexGroup::from_item(&group),
+            Err(WaveformError::DataLength {
+                expected: 8,
+                actual: 6,
+                ..
+            })
+        ));
+
+        let mut group = multiplex_group(1, 1, 16, "US", PrimitiveValue::U16(vec![0_u16].into()));
+        group.remove_element(tags::NUMBER_OF_WAVEFORM_CHANNELS);
+        assert!(matches!(
+            MultiplexGroup::from_item(&group),
+            Err(WaveformError::MissingAttribute { tag, .. })
+                if tag == tags::NUMBER_OF_WAVEFORM_CHANNELS
+        ));
+
+        assert!(matches!(
+            multiplex_groups(&InMemDicomObject::new_empty()),
+            Err(WaveformError::MissingWaveformSequence { .. })
+        ));
+    }
+}
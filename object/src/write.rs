@@ -0,0 +1,1044 @@
+//! Options for writing DICOM objects.
+//!
+//! See [`WriteOptions`] and
+//! [`FileDicomObject::write_all_with_options`](crate::FileDicomObject::write_all_with_options).
+use dicom_core::dictionary::{DataDictionaryEntry, VirtualVr};
+use dicom_core::header::Length;
+use dicom_core::{DataDictionary, DataElementHeader, PrimitiveValue, Tag, VR};
+pub use dicom_encoding::text::length::LengthValidation;
+pub use dicom_encoding::text::repertoire::RepertoireValidation;
+pub use dicom_encoding::text::EncodeTextPolicy;
+use dicom_encoding::text::{SpecificCharacterSet, TextCodec};
+use dicom_encoding::TransferSyntax;
+use dicom_parser::dataset::read::WarningCollector;
+pub use dicom_parser::dataset::write::OddLengthPolicy;
+use dicom_parser::dataset::write::Result as WriterResult;
+use dicom_parser::dataset::{BatchWriter, DataSetWriter, DataToken};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::io::Write;
+
+/// How group length elements (gggg,0000) in the data set
+/// are handled when writing an object.
+///
+/// The file meta group length (0002,0000) is always written,
+/// regardless of this mode.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum GroupLengthMode {
+    /// Remove all group length elements from the data set,
+    /// including those in nested items.
+    ///
+    /// Group lengths are retired in the standard,
+    /// so this is the default.
+    #[default]
+    Strip,
+    /// Recalculate the value of each existing group length element
+    /// from the byte length of the rest of its group,
+    /// as encoded in the transfer syntax of the output.
+    Recompute,
+    /// Write the group length elements as they are stored.
+    ///
+    /// The values may no longer match the groups
+    /// if the object was modified or is being transcoded.
+    Preserve,
+}
+
+/// How the file meta group is checked against the data set
+/// when writing a file.
+///
+/// The _Media Storage SOP Class UID_ (0002,0002)
+/// and _Media Storage SOP Instance UID_ (0002,0003)
+/// should be equal to the _SOP Class UID_ (0008,0016)
+/// and _SOP Instance UID_ (0008,0018) of the data set.
+/// In all modes but [`Off`](MetaValidation::Off),
+/// writing a data set in an encoding (byte order and VR explicitness)
+/// other than that of the _Transfer Syntax UID_ (0002,0010)
+/// of its file meta group fails.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum MetaValidation {
+    /// Fail to write files whose file meta group
+    /// does not match the data set.
+    Strict,
+    /// Log a warning for each attribute of the file meta group
+    /// which does not match the data set,
+    /// but write it anyway.
+    #[default]
+    Warn,
+    /// Write the values of the data set in the file meta group
+    /// in place of those which are missing or do not match,
+    /// logging a warning for each one which did not match.
+    ///
+    /// The object itself is not modified.
+    Fix,
+    /// Do not check the file meta group.
+    Off,
+}
+
+/// Options for writing a DICOM object.
+#[derive(Debug, Default, Clone, Eq, Hash, PartialEq)]
+pub struct WriteOptions {
+    pub(crate) group_length: GroupLengthMode,
+    pub(crate) transfer_syntax: Option<String>,
+    pub(crate) text_policy: EncodeTextPolicy,
+    pub(crate) repertoire_validation: RepertoireValidation,
+    pub(crate) length_validation: LengthValidation,
+    pub(crate) truncation_marker: Option<String>,
+    pub(crate) warnings: Option<WarningCollector>,
+    pub(crate) odd_length: OddLengthPolicy,
+    pub(crate) batch_size: Option<usize>,
+    pub(crate) meta_validation: MetaValidation,
+    pub(crate) implementation_class_uid: Option<String>,
+    pub(crate) implementation_version_name: Option<String>,
+    pub(crate) source_application_entity_title: Option<String>,
+}
+
+impl WriteOptions {
+    pub fn new() -> Self {
+        WriteOptions::default()
+    }
+
+    /// Set how group length elements are handled.
+    ///
+    /// The default is [`GroupLengthMode::Strip`].
+    pub fn group_length(mut self, mode: GroupLengthMode) -> Self {
+        self.group_length = mode;
+        self
+    }
+
+    /// Set the UID of the transfer syntax to write the data set with.
+    ///
+    /// By default, the transfer syntax declared in the file meta group is used.
+    pub fn transfer_syntax(mut self, uid: impl Into<String>) -> Self {
+        self.transfer_syntax = Some(uid.into());
+        self
+    }
+
+    /// Set how text which cannot be represented
+    /// in the character set of the data set is handled.
+    ///
+    /// The default is [`EncodeTextPolicy::Error`].
+    /// With [`EncodeTextPolicy::UpgradeToUtf8`],
+    /// _Specific Character Set_ (0008,0005) is written as `ISO_IR 192`
+    /// (or inserted if missing)
+    /// whenever some text would not be encodable otherwise,
+    /// and all text is then encoded in UTF-8.
+    /// The object itself is not modified.
+    pub fn text_policy(mut self, policy: EncodeTextPolicy) -> Self {
+        self.text_policy = policy;
+        self
+    }
+
+    /// Set how text values are checked against
+    /// the character repertoire and format of their value representation
+    /// (for example, that code strings only contain
+    /// uppercase letters, digits, spaces and underscores,
+    /// or that UIDs are well formed).
+    ///
+    /// The default is [`RepertoireValidation::Warn`],
+    /// which logs each offending value and writes it as is.
+    /// With [`RepertoireValidation::Strict`],
+    /// writing fails at the first offending value.
+    pub fn repertoire_validation(mut self, validation: RepertoireValidation) -> Self {
+        self.repertoire_validation = validation;
+        self
+    }
+
+    /// Set how text values are checked against
+    /// the maximum length of a value of their value representation
+    /// (for example, 16 characters for a code string,
+    /// or 64 characters for each component group of a person name).
+    ///
+    /// The default is [`LengthValidation::Warn`],
+    /// which logs each offending value and writes it as is.
+    /// With [`LengthValidation::Strict`],
+    /// writing fails at the first offending value,
+    /// and with [`LengthValidation::Truncate`],
+    /// values are cut to the maximum length in bytes
+    /// once encoded in the character set of the data set,
+    /// at the last whole character which fits.
+    /// Values of a VR which may not be truncated,
+    /// such as numbers, dates and UIDs,
+    /// still fail as in strict mode
+    /// (see [`is_truncatable`](dicom_encoding::text::length::is_truncatable)).
+    pub fn length_validation(mut self, validation: LengthValidation) -> Self {
+        self.length_validation = validation;
+        self
+    }
+
+    /// Set a marker to append to the values cut
+    /// under [`LengthValidation::Truncate`], such as `"..."`.
+    ///
+    /// The marker counts towards the maximum length.
+    /// No marker is appended by default.
+    pub fn truncation_marker(mut self, marker: impl Into<String>) -> Self {
+        self.truncation_marker = Some(marker.into());
+        self
+    }
+
+    /// Gather the values cut under [`LengthValidation::Truncate`]
+    /// into the given collector, in addition to logging them.
+    pub fn collect_warnings(mut self, collector: &WarningCollector) -> Self {
+        self.warnings = Some(collector.clone());
+        self
+    }
+
+    /// Set what to do with values of odd length.
+    ///
+    /// The default is [`OddLengthPolicy::Pad`],
+    /// which appends the padding byte of the value representation.
+    /// With [`OddLengthPolicy::Preserve`],
+    /// such values are written with their odd length,
+    /// as in the source of a byte-exact copy,
+    /// and with [`OddLengthPolicy::Error`],
+    /// writing fails at the first one.
+    pub fn odd_length(mut self, policy: OddLengthPolicy) -> Self {
+        self.odd_length = policy;
+        self
+    }
+
+    /// Set the number of bytes of small data elements
+    /// to gather before passing them on to the writer
+    /// (see [`BatchWriter`](dicom_parser::dataset::BatchWriter)).
+    ///
+    /// The default is 8 KiB.
+    /// With a batch size of zero,
+    /// the writer receives each element header and value as it is encoded.
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = Some(size);
+        self
+    }
+
+    /// Set how the file meta group is checked against the data set.
+    ///
+    /// The default is [`MetaValidation::Warn`],
+    /// which logs each SOP class or instance UID
+    /// which differs between the file meta group and the data set.
+    /// With [`MetaValidation::Strict`],
+    /// writing fails at the first one,
+    /// and with [`MetaValidation::Fix`],
+    /// the UIDs of the data set are written in the file meta group.
+    pub fn meta_validation(mut self, validation: MetaValidation) -> Self {
+        self.meta_validation = validation;
+        self
+    }
+
+    /// Set the implementation class UID
+    /// written in the file meta group,
+    /// identifying the application writing the file.
+    ///
+    /// By default, the implementation identifiers of the file meta table
+    /// are written as they are,
+    /// and files without an implementation class UID
+    /// are written with [`IMPLEMENTATION_CLASS_UID`](crate::IMPLEMENTATION_CLASS_UID)
+    /// and [`IMPLEMENTATION_VERSION_NAME`](crate::IMPLEMENTATION_VERSION_NAME).
+    /// Setting a class UID replaces both identifiers,
+    /// leaving out the version name unless one is also set.
+    pub fn implementation_class_uid(mut self, uid: impl Into<String>) -> Self {
+        self.implementation_class_uid = Some(uid.into());
+        self
+    }
+
+    /// Set the implementation version name
+    /// written in the file meta group.
+    pub fn implementation_version_name(mut self, name: impl Into<String>) -> Self {
+        self.implementation_version_name = Some(name.into());
+        self
+    }
+
+    /// Set the source application entity title
+    /// written in the file meta group.
+    ///
+    /// The UID and title given to these options
+    /// are validated as configured by
+    /// [`repertoire_validation`](WriteOptions::repertoire_validation).
+    pub fn source_application_entity_title(mut self, ae_title: impl Into<String>) -> Self {
+        self.source_application_entity_title = Some(ae_title.into());
+        self
+    }
+}
+
+/// Find the values of _SOP Class UID_ and _SOP Instance UID_
+/// in the root data set of the given token stream, without padding.
+///
+/// The stream is only consumed up to these elements.
+pub(crate) fn sop_uids<I>(tokens: I) -> (Option<String>, Option<String>)
+where
+    I: IntoIterator<Item = DataToken>,
+{
+    let mut sop_class_uid = None;
+    let mut sop_instance_uid = None;
+    let mut depth = 0;
+    let mut last_tag = None;
+    for token in tokens {
+        match token {
+            DataToken::SequenceStart { .. } | DataToken::PixelSequenceStart => depth += 1,
+            DataToken::SequenceEnd => depth -= 1,
+            DataToken::ElementHeader(header) if depth == 0 => {
+                if header.tag > Tag(0x0008, 0x0018) {
+                    break;
+                }
+                last_tag = Some(header.tag);
+            }
+            DataToken::PrimitiveValue(value) if depth == 0 => {
+                let uid = || {
+                    value
+                        .to_str()
+                        .trim_end_matches(|c: char| c.is_whitespace() || c == '\0')
+                        .to_string()
+                };
+                match last_tag.take() {
+                    Some(Tag(0x0008, 0x0016)) => sop_class_uid = Some(uid()),
+                    Some(Tag(0x0008, 0x0018)) => sop_instance_uid = Some(uid()),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    (sop_class_uid, sop_instance_uid)
+}
+
+/// Wrap the given writer so that small data elements
+/// are passed on in batches of the size in the options.
+pub(crate) fn batch_writer<W: Write>(to: W, options: &WriteOptions) -> BatchWriter<W> {
+    match options.batch_size {
+        Some(size) => BatchWriter::with_batch_size(to, size),
+        None => BatchWriter::new(to),
+    }
+}
+
+/// Whether the tag is of a group length element outside the file meta group.
+fn is_dataset_group_length(tag: Tag) -> bool {
+    tag.is_group_length() && tag.group() != 0x0002
+}
+
+/// Token stream adapter which drops all data set group length elements.
+///
+/// Sequences in which an element was removed
+/// are converted to undefined length,
+/// along with their items.
+pub(crate) struct StripGroupLengths<I> {
+    tokens: I,
+    pending: VecDeque<DataToken>,
+}
+
+impl<I> StripGroupLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    pub(crate) fn new(tokens: impl IntoIterator<IntoIter = I, Item = DataToken>) -> Self {
+        StripGroupLengths {
+            tokens: tokens.into_iter(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Consume the remaining tokens of a sequence
+    /// which started with the given token,
+    /// removing group lengths from its items.
+    fn buffer_sequence(&mut self, start: DataToken) {
+        let mut buffer = vec![start];
+        // whether each open sequence is a pixel data sequence
+        let mut stack = vec![false];
+        let mut stripped = false;
+        while let Some(token) = self.tokens.next() {
+            match &token {
+                DataToken::SequenceStart { .. } => stack.push(false),
+                DataToken::PixelSequenceStart => stack.push(true),
+                DataToken::SequenceEnd => {
+                    stack.pop();
+                }
+                DataToken::ElementHeader(header) if is_dataset_group_length(header.tag) => {
+                    // skip the value too
+                    self.tokens.next();
+                    stripped = true;
+                    continue;
+                }
+                _ => {}
+            }
+            buffer.push(token);
+            if stack.is_empty() {
+                break;
+            }
+        }
+
+        if stripped {
+            let mut stack = vec![];
+            for token in &mut buffer {
+                match token {
+                    DataToken::SequenceStart { len, .. } => {
+                        *len = Length::UNDEFINED;
+                        stack.push(false);
+                    }
+                    DataToken::PixelSequenceStart => stack.push(true),
+                    DataToken::SequenceEnd => {
+                        stack.pop();
+                    }
+                    DataToken::ItemStart { len } if stack.last() == Some(&false) => {
+                        *len = Length::UNDEFINED;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.pending.extend(buffer);
+    }
+}
+
+impl<I> Iterator for StripGroupLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<DataToken> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Some(token);
+            }
+            match self.tokens.next()? {
+                DataToken::ElementHeader(header) if is_dataset_group_length(header.tag) => {
+                    // skip the value too
+                    self.tokens.next();
+                }
+                token @ DataToken::SequenceStart { .. } => self.buffer_sequence(token),
+                token => return Some(token),
+            }
+        }
+    }
+}
+
+/// Check whether all text in the given tokens
+/// can be encoded in the character set which applies to it,
+/// following the declarations of _Specific Character Set_ in the stream,
+/// which only apply to the data set (or sequence item) declaring them.
+///
+/// Values of VRs restricted to the default character repertoire
+/// are not considered,
+/// since changing the character set would not help them.
+pub(crate) fn text_encodable<I>(tokens: I) -> bool
+where
+    I: IntoIterator<Item = DataToken>,
+{
+    let mut charset = SpecificCharacterSet::default();
+    // the character sets of the enclosing data sets
+    let mut scopes = Vec::new();
+    let mut last_header = None;
+    for token in tokens {
+        match token {
+            DataToken::ElementHeader(header) => last_header = Some(header),
+            DataToken::ItemStart { .. } => scopes.push(charset.clone()),
+            DataToken::ItemEnd => {
+                if let Some(parent) = scopes.pop() {
+                    charset = parent;
+                }
+            }
+            DataToken::PrimitiveValue(value) => {
+                let header = match last_header.take() {
+                    Some(header) => header,
+                    None => continue,
+                };
+                if header.tag == Tag(0x0008, 0x0005) {
+                    charset = SpecificCharacterSet::from_values(value.to_multi_str().iter());
+                    continue;
+                }
+                let restricted = matches!(
+                    header.vr,
+                    VR::AE | VR::AS | VR::CS | VR::DA | VR::DS | VR::DT | VR::IS | VR::TM | VR::UI
+                );
+                let encodable = match &value {
+                    _ if restricted => true,
+                    PrimitiveValue::Str(text) => charset.encode(text).is_ok(),
+                    PrimitiveValue::Strs(texts) => {
+                        texts.iter().all(|text| charset.encode(text).is_ok())
+                    }
+                    _ => true,
+                };
+                if !encodable {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Token stream adapter which declares the data set as encoded in UTF-8,
+/// by replacing the value of every _Specific Character Set_ element
+/// with `ISO_IR 192`,
+/// or inserting the element in the root data set if it is missing.
+///
+/// Sequence lengths are not adjusted,
+/// so the tokens should have sequences of undefined length.
+pub(crate) struct UpgradeCharset<I> {
+    tokens: I,
+    pending: VecDeque<DataToken>,
+    /// the nesting depth of sequences and items
+    depth: u32,
+    /// whether the root data set already has the element
+    declared: bool,
+}
+
+impl<I> UpgradeCharset<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    pub(crate) fn new(tokens: impl IntoIterator<IntoIter = I, Item = DataToken>) -> Self {
+        UpgradeCharset {
+            tokens: tokens.into_iter(),
+            pending: VecDeque::new(),
+            depth: 0,
+            declared: false,
+        }
+    }
+
+    fn declaration() -> [DataToken; 2] {
+        let value = PrimitiveValue::from("ISO_IR 192");
+        [
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0005),
+                VR::CS,
+                Length(value.calculate_byte_len() as u32),
+            )),
+            DataToken::PrimitiveValue(value),
+        ]
+    }
+}
+
+impl<I> Iterator for UpgradeCharset<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<DataToken> {
+        if let Some(token) = self.pending.pop_front() {
+            return Some(token);
+        }
+        let token = match self.tokens.next() {
+            Some(token) => token,
+            None if !self.declared => {
+                self.declared = true;
+                let [header, value] = Self::declaration();
+                self.pending.push_back(value);
+                return Some(header);
+            }
+            None => return None,
+        };
+
+        let element_tag = match &token {
+            DataToken::ElementHeader(header) => Some(header.tag),
+            DataToken::SequenceStart { tag, .. } => Some(*tag),
+            DataToken::PixelSequenceStart => Some(Tag(0x7FE0, 0x0010)),
+            _ => None,
+        };
+        let root = self.depth == 0;
+        match &token {
+            DataToken::SequenceStart { .. }
+            | DataToken::PixelSequenceStart
+            | DataToken::ItemStart { .. } => self.depth += 1,
+            DataToken::SequenceEnd | DataToken::ItemEnd => {
+                self.depth = self.depth.saturating_sub(1)
+            }
+            _ => {}
+        }
+
+        match element_tag {
+            Some(Tag(0x0008, 0x0005)) => {
+                // replace the original value with the declaration
+                self.declared |= root;
+                self.tokens.next();
+                let [header, value] = Self::declaration();
+                self.pending.push_back(value);
+                Some(header)
+            }
+            Some(tag) if root && !self.declared && tag > Tag(0x0008, 0x0005) => {
+                self.declared = true;
+                let [header, value] = Self::declaration();
+                self.pending.push_back(value);
+                self.pending.push_back(token);
+                Some(header)
+            }
+            _ => Some(token),
+        }
+    }
+}
+
+/// A writer which discards all bytes,
+/// only keeping track of how many were written.
+struct CountingWriter<'a> {
+    count: &'a Cell<u64>,
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.count.set(self.count.get() + buf.len() as u64);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A group whose byte length is being measured.
+struct OpenGroup {
+    group: u16,
+    /// bytes written when the group length element ended
+    start: u64,
+    /// index of the group length in the output
+    index: usize,
+}
+
+/// The nesting context while measuring group lengths.
+enum Context {
+    /// a data set (the root one or an item)
+    DataSet(Option<OpenGroup>),
+    Sequence,
+    PixelSequence,
+}
+
+/// Measure the byte length of each group which has a group length element,
+/// as the given tokens are encoded in the given transfer syntax
+/// with the text policy, length validation and odd length policy
+/// of the given options.
+///
+/// The lengths are returned in the order
+/// in which group length elements appear in the token stream.
+pub(crate) fn measure_group_lengths<I>(
+    tokens: I,
+    ts: &TransferSyntax,
+    options: &WriteOptions,
+) -> WriterResult<Vec<u32>>
+where
+    I: IntoIterator<Item = DataToken>,
+{
+    fn close(context: &mut [Context], lengths: &mut [u32], position: u64) {
+        if let Some(Context::DataSet(open)) = context.last_mut() {
+            if let Some(open) = open.take() {
+                lengths[open.index] = u32::try_from(position - open.start).unwrap_or(u32::MAX);
+            }
+        }
+    }
+
+    let count = Cell::new(0);
+    // values are validated when actually written
+    let mut writer = DataSetWriter::with_ts(CountingWriter { count: &count }, ts)?
+        .with_text_policy(options.text_policy)
+        .with_repertoire_validation(RepertoireValidation::Off)
+        .with_odd_length_policy(options.odd_length)
+        // but truncation changes their length
+        .with_length_validation(match options.length_validation {
+            LengthValidation::Truncate => LengthValidation::Truncate,
+            _ => LengthValidation::Off,
+        });
+    if let Some(marker) = &options.truncation_marker {
+        writer = writer.with_truncation_marker(marker.clone());
+    }
+    let mut lengths = Vec::new();
+    let mut context = vec![Context::DataSet(None)];
+    // the group of a group length element awaiting its value
+    let mut group_length_of = None;
+
+    for token in tokens {
+        let element_tag = match &token {
+            DataToken::ElementHeader(header) => Some(header.tag),
+            DataToken::SequenceStart { tag, .. } => Some(*tag),
+            DataToken::PixelSequenceStart => Some(Tag(0x7FE0, 0x0010)),
+            _ => None,
+        };
+        if let Some(tag) = element_tag {
+            if let Some(Context::DataSet(Some(open))) = context.last() {
+                if open.group != tag.group() {
+                    close(&mut context, &mut lengths, count.get());
+                }
+            }
+            if is_dataset_group_length(tag) {
+                group_length_of = Some(tag.group());
+            }
+        }
+
+        match &token {
+            DataToken::SequenceStart { .. } => context.push(Context::Sequence),
+            DataToken::PixelSequenceStart => context.push(Context::PixelSequence),
+            DataToken::ItemStart { .. } => {
+                if let Some(Context::Sequence) = context.last() {
+                    context.push(Context::DataSet(None));
+                }
+            }
+            DataToken::ItemEnd => {
+                if let Some(Context::DataSet(_)) = context.last() {
+                    close(&mut context, &mut lengths, count.get());
+                    context.pop();
+                }
+            }
+            DataToken::SequenceEnd => {
+                context.pop();
+            }
+            _ => {}
+        }
+
+        let is_value = matches!(token, DataToken::PrimitiveValue(_));
+        writer.write(token)?;
+
+        if is_value {
+            if let Some(group) = group_length_of.take() {
+                if let Some(Context::DataSet(open)) = context.last_mut() {
+                    *open = Some(OpenGroup {
+                        group,
+                        start: count.get(),
+                        index: lengths.len(),
+                    });
+                    lengths.push(0);
+                }
+            }
+        }
+    }
+    close(&mut context, &mut lengths, count.get());
+
+    Ok(lengths)
+}
+
+/// Token stream adapter which replaces the values of data set group length elements
+/// with the given lengths, in order.
+pub(crate) struct ReplaceGroupLengths<I> {
+    tokens: I,
+    lengths: std::vec::IntoIter<u32>,
+    replace_next: bool,
+}
+
+impl<I> ReplaceGroupLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    pub(crate) fn new(
+        tokens: impl IntoIterator<IntoIter = I, Item = DataToken>,
+        lengths: Vec<u32>,
+    ) -> Self {
+        ReplaceGroupLengths {
+            tokens: tokens.into_iter(),
+            lengths: lengths.into_iter(),
+            replace_next: false,
+        }
+    }
+}
+
+impl<I> Iterator for ReplaceGroupLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<DataToken> {
+        match self.tokens.next()? {
+            DataToken::ElementHeader(header) if is_dataset_group_length(header.tag) => {
+                self.replace_next = true;
+                Some(DataToken::ElementHeader(DataElementHeader::new(
+                    header.tag,
+                    VR::UL,
+                    Length(4),
+                )))
+            }
+            DataToken::PrimitiveValue(value) if self.replace_next => {
+                self.replace_next = false;
+                match self.lengths.next() {
+                    Some(length) => Some(DataToken::PrimitiveValue(PrimitiveValue::from(length))),
+                    None => Some(DataToken::PrimitiveValue(value)),
+                }
+            }
+            token => Some(token),
+        }
+    }
+}
+
+/// Remove the trailing padding (spaces and null characters)
+/// of textual values in the token,
+/// so that values which only differ in padding are encoded alike.
+pub(crate) fn trim_text_padding(token: DataToken) -> DataToken {
+    fn trim(s: &str) -> String {
+        s.trim_end_matches([' ', '\0']).to_string()
+    }
+
+    match token {
+        DataToken::PrimitiveValue(PrimitiveValue::Str(s)) => {
+            DataToken::PrimitiveValue(PrimitiveValue::Str(trim(&s)))
+        }
+        DataToken::PrimitiveValue(PrimitiveValue::Strs(values)) => DataToken::PrimitiveValue(
+            PrimitiveValue::Strs(values.iter().map(|s| trim(s)).collect()),
+        ),
+        token => token,
+    }
+}
+
+/// Token stream adapter which replaces the VR of each element header
+/// with the one of the data dictionary,
+/// so that elements decoded from implicit and explicit VR transfer syntaxes
+/// are encoded alike.
+///
+/// Elements absent from the dictionary, such as private elements,
+/// are given the VR `UN`.
+/// Pixel Data is given the VR `OB`
+/// if _Bits Allocated_ in the same data set is 8 or less,
+/// and `OW` otherwise.
+/// Other ambiguous VRs are resolved as in _Implicit VR Little Endian_.
+pub(crate) struct NormalizeVr<I, D> {
+    tokens: I,
+    dict: D,
+    /// the value of _Bits Allocated_ in each open data set
+    bits_allocated: Vec<Option<u16>>,
+    /// whether the next value token is the value of _Bits Allocated_
+    bits_allocated_next: bool,
+}
+
+impl<I, D> NormalizeVr<I, D>
+where
+    I: Iterator<Item = DataToken>,
+    D: DataDictionary,
+{
+    pub(crate) fn new(tokens: impl IntoIterator<IntoIter = I, Item = DataToken>, dict: D) -> Self {
+        NormalizeVr {
+            tokens: tokens.into_iter(),
+            dict,
+            bits_allocated: vec![None],
+            bits_allocated_next: false,
+        }
+    }
+
+    fn normalized_vr(&self, header: &DataElementHeader) -> VR {
+        let vr = match self.dict.by_tag(header.tag).map(|entry| entry.vr()) {
+            Some(VirtualVr::Px) => match self.bits_allocated.last() {
+                Some(Some(bits)) if *bits <= 8 => VR::OB,
+                _ => VR::OW,
+            },
+            Some(vr) => vr.relaxed(),
+            None => VR::UN,
+        };
+        // sequences never reach this point as element headers
+        if vr == VR::SQ {
+            header.vr
+        } else {
+            vr
+        }
+    }
+}
+
+impl<I, D> Iterator for NormalizeVr<I, D>
+where
+    I: Iterator<Item = DataToken>,
+    D: DataDictionary,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<DataToken> {
+        let token = self.tokens.next()?;
+        match &token {
+            DataToken::ElementHeader(header) => {
+                self.bits_allocated_next = header.tag == Tag(0x0028, 0x0100);
+                let vr = self.normalized_vr(header);
+                return Some(DataToken::ElementHeader(DataElementHeader {
+                    vr,
+                    ..*header
+                }));
+            }
+            DataToken::PrimitiveValue(value) if self.bits_allocated_next => {
+                self.bits_allocated_next = false;
+                if let Some(bits) = self.bits_allocated.last_mut() {
+                    *bits = value.to_int::<u16>().ok();
+                }
+            }
+            DataToken::ItemStart { .. } => self.bits_allocated.push(None),
+            DataToken::ItemEnd => {
+                self.bits_allocated.pop();
+            }
+            _ => {}
+        }
+        Some(token)
+    }
+}
+
+/// Measure the byte length of each data set sequence and sequence item,
+/// as the given tokens are encoded in the given transfer syntax
+/// with explicit lengths.
+///
+/// The lengths are returned in the order
+/// in which the sequences and items start in the token stream.
+pub(crate) fn measure_sequence_lengths<I>(tokens: I, ts: &TransferSyntax) -> WriterResult<Vec<u32>>
+where
+    I: IntoIterator<Item = DataToken>,
+{
+    let count = Cell::new(0);
+    let mut writer = DataSetWriter::with_ts(CountingWriter { count: &count }, ts)?;
+    let mut lengths = Vec::new();
+    // for each open sequence or item,
+    // its index in the output and the bytes written when it started
+    // (or `None` for pixel data sequences and their items)
+    let mut open: Vec<Option<(usize, u64)>> = Vec::new();
+
+    for token in tokens {
+        match token {
+            DataToken::SequenceStart { tag, .. } => {
+                // a defined length keeps delimiters out of the count
+                writer.write(DataToken::SequenceStart {
+                    tag,
+                    len: Length(0),
+                })?;
+                open.push(Some((lengths.len(), count.get())));
+                lengths.push(0);
+            }
+            DataToken::ItemStart { .. } if matches!(open.last(), Some(Some(_))) => {
+                writer.write(DataToken::ItemStart { len: Length(0) })?;
+                open.push(Some((lengths.len(), count.get())));
+                lengths.push(0);
+            }
+            token @ DataToken::PixelSequenceStart | token @ DataToken::ItemStart { .. } => {
+                writer.write(token)?;
+                open.push(None);
+            }
+            token @ DataToken::SequenceEnd | token @ DataToken::ItemEnd => {
+                if let Some(Some((index, start))) = open.pop() {
+                    lengths[index] = u32::try_from(count.get() - start).unwrap_or(u32::MAX);
+                }
+                writer.write(token)?;
+            }
+            token => writer.write(token)?,
+        }
+    }
+
+    Ok(lengths)
+}
+
+/// Token stream adapter which replaces the lengths
+/// of data set sequences and their items with the given lengths, in order.
+pub(crate) struct ReplaceSequenceLengths<I> {
+    tokens: I,
+    lengths: std::vec::IntoIter<u32>,
+    /// whether each open sequence or item is part of a data set sequence
+    open: Vec<bool>,
+}
+
+impl<I> ReplaceSequenceLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    pub(crate) fn new(
+        tokens: impl IntoIterator<IntoIter = I, Item = DataToken>,
+        lengths: Vec<u32>,
+    ) -> Self {
+        ReplaceSequenceLengths {
+            tokens: tokens.into_iter(),
+            lengths: lengths.into_iter(),
+            open: Vec::new(),
+        }
+    }
+
+    fn next_length(&mut self, len: Length) -> Length {
+        self.lengths.next().map(Length).unwrap_or(len)
+    }
+}
+
+impl<I> Iterator for ReplaceSequenceLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<DataToken> {
+        match self.tokens.next()? {
+            DataToken::SequenceStart { tag, len } => {
+                self.open.push(true);
+                let len = self.next_length(len);
+                Some(DataToken::SequenceStart { tag, len })
+            }
+            DataToken::ItemStart { len } if self.open.last() == Some(&true) => {
+                self.open.push(true);
+                let len = self.next_length(len);
+                Some(DataToken::ItemStart { len })
+            }
+            token @ DataToken::PixelSequenceStart | token @ DataToken::ItemStart { .. } => {
+                self.open.push(false);
+                Some(token)
+            }
+            token @ DataToken::SequenceEnd | token @ DataToken::ItemEnd => {
+                self.open.pop();
+                Some(token)
+            }
+            token => Some(token),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::DataElement;
+    use dicom_dictionary_std::tags;
+    use dicom_parser::dataset::IntoTokens;
+    use dicom_transfer_syntax_registry::entries::EXPLICIT_VR_LITTLE_ENDIAN;
+
+    use crate::InMemDicomObject;
+
+    #[test]
+    fn strip_group_lengths_in_items() {
+        let item = InMemDicomObject::from_element_iter([
+            DataElement::new(Tag(0x0008, 0x0000), VR::UL, PrimitiveValue::from(26_u32)),
+            DataElement::new(tags::REFERENCED_SOP_CLASS_UID, VR::UI, "1.2.3.4"),
+        ]);
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(Tag(0x0008, 0x0000), VR::UL, PrimitiveValue::from(0_u32)),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::new(vec![item], Length(34)),
+            ),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+        ]);
+
+        let tokens: Vec<_> = StripGroupLengths::new((&obj).into_tokens()).collect();
+        assert!(!tokens.iter().any(|token| matches!(
+            token,
+            DataToken::ElementHeader(header) if header.tag.is_group_length()
+        )));
+        // the lengths of the sequence and its items were invalidated
+        assert!(tokens.iter().any(|token| matches!(
+            token,
+            DataToken::SequenceStart { len, .. } if len.is_undefined()
+        )));
+        assert!(tokens.iter().all(|token| !matches!(
+            token,
+            DataToken::ItemStart { len } if len.is_defined()
+        )));
+    }
+
+    #[test]
+    fn measure_group_lengths_at_each_level() {
+        let item = InMemDicomObject::from_element_iter([
+            DataElement::new(Tag(0x0008, 0x0000), VR::UL, PrimitiveValue::from(0_u32)),
+            DataElement::new(tags::REFERENCED_SOP_CLASS_UID, VR::UI, "1.2.3.4"),
+        ]);
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(Tag(0x0008, 0x0000), VR::UL, PrimitiveValue::from(0_u32)),
+            DataElement::new(tags::MODALITY, VR::CS, "CT"),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![item]),
+            ),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+        ]);
+
+        let lengths = measure_group_lengths(
+            (&obj).into_tokens(),
+            &EXPLICIT_VR_LITTLE_ENDIAN.erased(),
+            &WriteOptions::default(),
+        )
+        .unwrap();
+        // Modality: 8 + 2;
+        // sequence: 12 + item header (8) + group length (8 + 4) + UID (8 + 8)
+        // + item delimiter (8) + sequence delimiter (8)
+        // item: UID (8 + 8)
+        assert_eq!(lengths, vec![10 + 12 + 8 + 12 + 16 + 8 + 8, 16]);
+    }
+}
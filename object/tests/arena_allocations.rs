@@ -0,0 +1,95 @@
+//! Checks that reading an arena-backed object
+//! takes far fewer allocations than an in-memory object.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dicom_core::{DataElement, PrimitiveValue, Tag, VR};
+use dicom_dictionary_std::uids;
+use dicom_object::{arena, FileMetaTableBuilder, InMemDicomObject};
+
+/// A global allocator counting the number of allocations made.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Count the allocations made by the given function.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let out = f();
+    (out, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+// only one test in this binary,
+// so that allocations are not shared with other tests
+#[test]
+fn arena_object_takes_fewer_allocations() {
+    let mut obj = InMemDicomObject::new_empty();
+    obj.put(DataElement::new(
+        Tag(0x0008, 0x0016),
+        VR::UI,
+        uids::SECONDARY_CAPTURE_IMAGE_STORAGE,
+    ));
+    obj.put(DataElement::new(Tag(0x0008, 0x0018), VR::UI, "2.25.175"));
+    for i in 0..5_000 {
+        obj.put(DataElement::new(
+            Tag(0x0011, 0x1000 + i),
+            VR::LO,
+            PrimitiveValue::from(format!("value #{}", i)),
+        ));
+    }
+    let file = obj
+        .with_meta(FileMetaTableBuilder::new().transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN))
+        .unwrap();
+    let mut bytes = Vec::new();
+    file.write_all(&mut bytes).unwrap();
+
+    let (in_mem, in_mem_allocations) =
+        count_allocations(|| dicom_object::from_reader(&bytes[..]).unwrap());
+    let (arena_obj, arena_allocations) =
+        count_allocations(|| arena::from_reader(&bytes[..]).unwrap());
+
+    assert_eq!(arena_obj.len(), 5_002);
+    assert_eq!(
+        arena_obj
+            .element(Tag(0x0011, 0x1000 + 4_999))
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        in_mem
+            .element(Tag(0x0011, 0x1000 + 4_999))
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+    // values and element headers are not allocated one by one
+    assert!(
+        arena_allocations < 100,
+        "arena object took {} allocations",
+        arena_allocations
+    );
+    assert!(
+        arena_allocations * 10 < in_mem_allocations,
+        "arena object took {} allocations, in-memory object took {}",
+        arena_allocations,
+        in_mem_allocations
+    );
+}
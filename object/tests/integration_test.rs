@@ -4,6 +4,7 @@ use std::{
 };
 
 use dicom_core::value::Value;
+use dicom_core::{DataElement, PrimitiveValue, VR};
 use dicom_dictionary_std::tags;
 use dicom_encoding::text::SpecificCharacterSet;
 use dicom_object::{
@@ -153,3 +154,62 @@ fn test_expl_vr_le_no_meta() {
         "1.2.333.4444.5.6.7.8.9",
     );
 }
+
+#[test]
+fn test_modify_write_and_read_back() {
+    let path =
+        dicom_test_files::path("pydicom/CT_small.dcm").expect("test DICOM file should exist");
+    let mut object = open_file(&path).unwrap();
+    let original_len = object.len();
+    assert!(object.contains(tags::PATIENT_ID));
+
+    // replace an existing attribute
+    let old = object.put(DataElement::new(
+        tags::PATIENT_NAME,
+        VR::PN,
+        PrimitiveValue::from("Doe^John"),
+    ));
+    assert!(old.is_some());
+    // add a new one
+    let old = object.put(DataElement::new(
+        tags::PATIENT_COMMENTS,
+        VR::LT,
+        PrimitiveValue::from("Round trip test"),
+    ));
+    assert!(old.is_none());
+    // and remove another
+    assert!(object.remove_element(tags::PATIENT_ID));
+    assert!(!object.contains(tags::PATIENT_ID));
+    assert_eq!(object.len(), original_len);
+
+    let mut data = Vec::new();
+    object.write_all(&mut data).unwrap();
+
+    let object2 = OpenFileOptions::new()
+        .read_preamble(ReadPreamble::Always)
+        .from_reader(&data[..])
+        .unwrap();
+
+    assert_eq!(object2.len(), object.len());
+    assert_eq!(
+        object2
+            .element(tags::PATIENT_NAME)
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        "Doe^John"
+    );
+    assert_eq!(
+        object2
+            .element(tags::PATIENT_COMMENTS)
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        "Round trip test"
+    );
+    assert!(object2.get(tags::PATIENT_ID).is_none());
+    for (e1, e2) in object.iter().zip(object2.iter()) {
+        assert_eq!(e1.header().tag, e2.header().tag);
+        assert_eq!(e1.value(), e2.value());
+    }
+}
@@ -0,0 +1,139 @@
+//! Checks that misuses of the `dicom_object!` macro
+//! fail to compile with the intended diagnostic.
+//!
+//! Each case is compiled as a binary of a scratch crate
+//! depending on this one,
+//! and the compiler output is checked for the expected message.
+use std::path::Path;
+use std::process::Command;
+
+/// Cases of name, source, and expected error message.
+const CASES: &[(&str, &str, &str)] = &[
+    (
+        "unknown_keyword",
+        r#"
+fn main() {
+    let _ = dicom_object::dicom_object! {
+        PatientNam: "Doe^John",
+    };
+}
+"#,
+        "unknown attribute keyword",
+    ),
+    (
+        "unknown_nested_keyword",
+        r#"
+fn main() {
+    let _ = dicom_object::dicom_object! {
+        ReferencedImageSequence: [
+            { ReferencedSOPInstanceUid: "1.2.3.4" },
+        ],
+    };
+}
+"#,
+        "unknown attribute keyword",
+    ),
+    (
+        "integer_for_text",
+        r#"
+fn main() {
+    let _ = dicom_object::dicom_object! {
+        PatientName: 42,
+    };
+}
+"#,
+        "value type does not match the attribute's value representation",
+    ),
+    (
+        "text_for_integer",
+        r#"
+fn main() {
+    let _ = dicom_object::dicom_object! {
+        Rows: "512",
+    };
+}
+"#,
+        "value type does not match the attribute's value representation",
+    ),
+    (
+        "float_for_integer",
+        r#"
+fn main() {
+    let _ = dicom_object::dicom_object! {
+        Columns: [0.5, 0.5],
+    };
+}
+"#,
+        "value type does not match the attribute's value representation",
+    ),
+];
+
+/// Write the scratch crate with one binary per case.
+fn write_crate(dir: &Path) {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    std::fs::create_dir_all(dir.join("src/bin")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "dicom-object-macro-errors"
+version = "0.0.0"
+edition = "2018"
+publish = false
+
+[dependencies]
+dicom-object = {{ path = {:?}, default-features = false }}
+
+[workspace]
+"#,
+            manifest_dir
+        ),
+    )
+    .unwrap();
+    // keep dependency versions the same as in the workspace
+    let lock = manifest_dir.join("../Cargo.lock");
+    if lock.exists() {
+        std::fs::copy(lock, dir.join("Cargo.lock")).unwrap();
+    }
+    for (name, source, _) in CASES {
+        std::fs::write(dir.join("src/bin").join(format!("{}.rs", name)), source).unwrap();
+    }
+}
+
+#[test]
+fn macro_misuse_fails_with_intended_error() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("macro_errors");
+    write_crate(&dir);
+
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    for (name, _, expected) in CASES {
+        let output = Command::new(&cargo)
+            .arg("build")
+            .arg("--quiet")
+            .arg("--bin")
+            .arg(name)
+            .current_dir(&dir)
+            .env("CARGO_TARGET_DIR", dir.join("target"))
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            !output.status.success(),
+            "case `{}` should not compile",
+            name
+        );
+        assert!(
+            stderr.contains(expected),
+            "case `{}` should fail with `{}`, got:\n{}",
+            name,
+            expected,
+            stderr
+        );
+        assert!(
+            stderr.contains(&format!("src/bin/{}.rs", name)),
+            "case `{}` should point to its source, got:\n{}",
+            name,
+            stderr
+        );
+    }
+}
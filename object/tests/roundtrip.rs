@@ -0,0 +1,202 @@
+//! Property tests checking that in-memory DICOM objects
+//! survive an encoding and decoding round trip
+//! in each of the uncompressed transfer syntaxes.
+use dicom_core::value::DataSetSequence;
+use dicom_core::{DataDictionary, DataElement, Length, PrimitiveValue, VR};
+use dicom_dictionary_std::{tags, uids, StandardDataDictionary};
+use dicom_encoding::TransferSyntax;
+use dicom_object::diff::diff;
+use dicom_object::mem::InMemElement;
+use dicom_object::{FileMetaTableBuilder, InMemDicomObject, WriteOptions};
+use dicom_transfer_syntax_registry::entries::{
+    EXPLICIT_VR_BIG_ENDIAN, EXPLICIT_VR_LITTLE_ENDIAN, IMPLICIT_VR_LITTLE_ENDIAN,
+};
+use proptest::prelude::*;
+
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const ALNUM: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Strategy for a string of 1 to `max_len` characters of the given alphabet.
+fn word(alphabet: &'static str, max_len: usize) -> impl Strategy<Value = String> {
+    let chars: Vec<char> = alphabet.chars().collect();
+    prop::collection::vec(prop::sample::select(chars), 1..max_len + 1)
+        .prop_map(|chars| chars.into_iter().collect())
+}
+
+/// Strategy for a single textual value of the given VR,
+/// free of padding characters so that it reads back the same.
+fn text(vr: VR) -> BoxedStrategy<String> {
+    match vr {
+        VR::CS => word(UPPER, 16).boxed(),
+        VR::UI => prop::collection::vec(1..100_000_u32, 2..7)
+            .prop_map(|components| {
+                components
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join(".")
+            })
+            .boxed(),
+        VR::PN => (word(UPPER, 10), word(UPPER, 10))
+            .prop_map(|(family, given)| format!("{}^{}", family, given))
+            .boxed(),
+        _ => prop::collection::vec(word(ALNUM, 12), 1..4)
+            .prop_map(|words| words.join(" "))
+            .boxed(),
+    }
+}
+
+/// Strategy for a textual value of the given VR,
+/// with up to three values unless the VR only admits one.
+fn text_value(vr: VR) -> BoxedStrategy<PrimitiveValue> {
+    if vr == VR::LT {
+        text(vr).prop_map(PrimitiveValue::from).boxed()
+    } else {
+        prop::collection::vec(text(vr), 1..4)
+            .prop_map(PrimitiveValue::new_strs)
+            .boxed()
+    }
+}
+
+/// Strategy for a primitive data element of one of several tags,
+/// with a value matching the tag's VR in the standard dictionary.
+fn primitive_element() -> impl Strategy<Value = InMemElement> {
+    prop_oneof![
+        text_value(VR::PN).prop_map(|v| (tags::PATIENT_NAME, VR::PN, v)),
+        text_value(VR::LO).prop_map(|v| (tags::PATIENT_ID, VR::LO, v)),
+        text_value(VR::CS).prop_map(|v| (tags::MODALITY, VR::CS, v)),
+        text_value(VR::UI).prop_map(|v| (tags::STUDY_INSTANCE_UID, VR::UI, v)),
+        text_value(VR::LT).prop_map(|v| (tags::IMAGE_COMMENTS, VR::LT, v)),
+        prop::collection::vec(any::<u16>(), 1..4).prop_map(|v| (
+            tags::ROWS,
+            VR::US,
+            PrimitiveValue::U16(v.into())
+        )),
+        prop::collection::vec(any::<u32>(), 1..4).prop_map(|v| (
+            tags::SIMPLE_FRAME_LIST,
+            VR::UL,
+            PrimitiveValue::U32(v.into())
+        )),
+        any::<i32>().prop_map(|v| (tags::REFERENCE_PIXEL_X0, VR::SL, PrimitiveValue::from(v))),
+        (-1e6_f32..1e6).prop_map(|v| {
+            (
+                tags::RECOMMENDED_DISPLAY_FRAME_RATE_IN_FLOAT,
+                VR::FL,
+                PrimitiveValue::from(v),
+            )
+        }),
+        prop::collection::vec(-1e9_f64..1e9, 1..4).prop_map(|v| (
+            tags::DIFFUSION_B_VALUE,
+            VR::FD,
+            PrimitiveValue::F64(v.into())
+        )),
+        prop::collection::vec(any::<[u8; 2]>(), 1..64).prop_map(|v| {
+            let bytes: Vec<u8> = v.into_iter().flatten().collect();
+            (
+                tags::ENCAPSULATED_DOCUMENT,
+                VR::OB,
+                PrimitiveValue::from(bytes),
+            )
+        }),
+    ]
+    .prop_map(|(tag, vr, value)| DataElement::new(tag, vr, value))
+}
+
+/// Strategy for a data set
+/// with primitive elements and nested sequences.
+fn object() -> impl Strategy<Value = InMemDicomObject> {
+    let leaf = prop::collection::vec(primitive_element(), 0..8)
+        .prop_map(InMemDicomObject::from_element_iter);
+    leaf.prop_recursive(2, 32, 4, |inner| {
+        (
+            prop::collection::vec(primitive_element(), 0..8),
+            prop::collection::vec(inner, 1..3),
+        )
+            .prop_map(|(elements, items)| {
+                let mut obj = InMemDicomObject::from_element_iter(elements);
+                obj.put(DataElement::new(
+                    tags::REFERENCED_IMAGE_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::new(items, Length::UNDEFINED),
+                ));
+                obj
+            })
+    })
+}
+
+fn uncompressed_syntaxes() -> [TransferSyntax; 3] {
+    [
+        IMPLICIT_VR_LITTLE_ENDIAN.erased(),
+        EXPLICIT_VR_LITTLE_ENDIAN.erased(),
+        EXPLICIT_VR_BIG_ENDIAN.erased(),
+    ]
+}
+
+#[test]
+fn generated_tags_match_dictionary() {
+    for (tag, vr) in [
+        (tags::PATIENT_NAME, VR::PN),
+        (tags::PATIENT_ID, VR::LO),
+        (tags::MODALITY, VR::CS),
+        (tags::STUDY_INSTANCE_UID, VR::UI),
+        (tags::IMAGE_COMMENTS, VR::LT),
+        (tags::ROWS, VR::US),
+        (tags::SIMPLE_FRAME_LIST, VR::UL),
+        (tags::REFERENCE_PIXEL_X0, VR::SL),
+        (tags::RECOMMENDED_DISPLAY_FRAME_RATE_IN_FLOAT, VR::FL),
+        (tags::DIFFUSION_B_VALUE, VR::FD),
+        (tags::ENCAPSULATED_DOCUMENT, VR::OB),
+        (tags::REFERENCED_IMAGE_SEQUENCE, VR::SQ),
+    ] {
+        let entry = StandardDataDictionary.by_tag(tag).unwrap();
+        assert_eq!(entry.vr.relaxed(), vr, "{}", tag);
+    }
+}
+
+proptest! {
+    #[test]
+    fn dataset_roundtrip(obj in object()) {
+        for ts in uncompressed_syntaxes() {
+            let bytes = obj
+                .to_vec(&ts, &WriteOptions::default())
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            let decoded = InMemDicomObject::from_slice(&bytes, &ts)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            prop_assert_eq!(diff(&obj, &decoded), vec![], "in {}", ts.name());
+
+            // encoding the decoded object gives the same bytes
+            let bytes2 = decoded
+                .to_vec(&ts, &WriteOptions::default())
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            prop_assert_eq!(bytes2, bytes);
+        }
+    }
+
+    #[test]
+    fn file_roundtrip(obj in object()) {
+        for ts in uncompressed_syntaxes() {
+            let file = obj
+                .clone()
+                .with_meta(
+                    FileMetaTableBuilder::new()
+                        .transfer_syntax(ts.uid())
+                        .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)
+                        .media_storage_sop_instance_uid("2.25.1"),
+                )
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            let bytes = file
+                .to_file_vec(&WriteOptions::default())
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            // same bytes as written through any other writer
+            let mut expected = Vec::new();
+            file.write_all(&mut expected).map_err(|e| TestCaseError::fail(e.to_string()))?;
+            prop_assert_eq!(&bytes, &expected);
+
+            let (meta, decoded) = dicom_object::from_slice(&bytes)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            prop_assert_eq!(meta.transfer_syntax(), ts.uid());
+            prop_assert_eq!(diff(&obj, &decoded), vec![], "in {}", ts.name());
+        }
+    }
+}
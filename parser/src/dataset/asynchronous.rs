@@ -0,0 +1,457 @@
+//! Asynchronous reading of DICOM data sets as streams of tokens,
+//! over [`tokio::io::AsyncRead`] sources.
+//!
+//! The [`AsyncDataSetReader`] does not reimplement data set parsing:
+//! it fetches from the source the bytes which the next token needs,
+//! then reads the token with a regular [`DataSetReader`]
+//! over those bytes.
+//! Tokens, warnings and errors are therefore the same
+//! as when reading the same data synchronously.
+//!
+//! This module requires the `async` feature.
+use super::read::{CreateDecoderSnafu, DataSetReader, FetchDataSnafu, ReadOptions, Result};
+use super::DataToken;
+use crate::stateful::decode::{send_decoder_for, SendDecoder, SendStatefulDecoder, StatefulDecode};
+use dicom_encoding::transfer_syntax::TransferSyntax;
+use snafu::ResultExt;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// The minimum number of bytes requested from the source at once.
+const MIN_FETCH_LENGTH: usize = 8 * 1024;
+
+/// The maximum number of bytes requested from the source at once.
+const MAX_FETCH_LENGTH: usize = 1024 * 1024;
+
+/// The bytes fetched from the asynchronous source
+/// and not yet consumed by the data set reader.
+#[derive(Debug, Default)]
+struct ChunkBuffer {
+    bytes: Vec<u8>,
+    /// the index of the first byte not consumed
+    start: usize,
+}
+
+impl ChunkBuffer {
+    fn available(&self) -> &[u8] {
+        &self.bytes[self.start..]
+    }
+
+    /// Fetch more bytes from the source,
+    /// returning the number of bytes added
+    /// (0 if the source has ended).
+    async fn fetch<R>(&mut self, source: &mut R, len: usize) -> io::Result<usize>
+    where
+        R: ?Sized + AsyncRead + Unpin,
+    {
+        // discard the bytes already consumed
+        self.bytes.drain(..self.start);
+        self.start = 0;
+
+        let filled = self.bytes.len();
+        self.bytes.resize(filled + len, 0);
+        let result = source.read(&mut self.bytes[filled..]).await;
+        let n = *result.as_ref().unwrap_or(&0);
+        self.bytes.truncate(filled + n);
+        result
+    }
+}
+
+impl Read for ChunkBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = Read::read(&mut self.available(), buf)?;
+        self.start += n;
+        Ok(n)
+    }
+}
+
+/// An asynchronous reader for retrieving structure
+/// in a DICOM data set from an asynchronous byte source.
+///
+/// This is the asynchronous counterpart of [`DataSetReader`]:
+/// tokens are retrieved with the [`next`](AsyncDataSetReader::next) method
+/// instead of by iteration.
+/// No more data than necessary for each token is awaited,
+/// so a token is available as soon as its bytes arrive.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{Tag, VR};
+/// # use dicom_core::header::{DataElementHeader, Length};
+/// # use dicom_parser::dataset::DataToken;
+/// use dicom_parser::dataset::asynchronous::AsyncDataSetReader;
+/// # use dicom_encoding::{Codec, Endianness, TransferSyntax};
+/// # let explicit_vr_le = TransferSyntax::new(
+/// #     "1.2.840.10008.1.2.1",
+/// #     "Explicit VR Little Endian",
+/// #     Endianness::Little,
+/// #     true,
+/// #     Codec::None,
+/// # );
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let source: &[u8] = &[
+///     // (0008,0060) CS "MR"
+///     0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R',
+/// ];
+/// let mut reader =
+///     AsyncDataSetReader::new_with_ts(source, &explicit_vr_le)?;
+///
+/// let mut tokens = Vec::new();
+/// while let Some(token) = reader.next().await {
+///     tokens.push(token?);
+/// }
+/// assert_eq!(
+///     tokens,
+///     vec![
+///         DataToken::ElementHeader(DataElementHeader::new(
+///             Tag(0x0008, 0x0060),
+///             VR::CS,
+///             Length(2),
+///         )),
+///         DataToken::PrimitiveValue("MR".into()),
+///     ]
+/// );
+/// # Ok::<_, dicom_parser::dataset::read::Error>(())
+/// # }).unwrap();
+/// ```
+pub struct AsyncDataSetReader<R> {
+    /// the asynchronous byte source
+    source: R,
+    /// the synchronous reader over the bytes fetched
+    reader: DataSetReader<SendStatefulDecoder<ChunkBuffer>>,
+    /// the decoder of headers to look ahead of the reader
+    lookahead: SendDecoder<ChunkBuffer>,
+    /// whether the source has ended
+    source_ended: bool,
+    /// whether fetching data from the source failed
+    fetch_failed: bool,
+}
+
+impl<R> fmt::Debug for AsyncDataSetReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncDataSetReader")
+            .field("position", &self.reader.decoder().position())
+            .field("source_ended", &self.source_ended)
+            .field("fetch_failed", &self.fetch_failed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R> AsyncDataSetReader<R> {
+    /// Create a new asynchronous data set token reader
+    /// with the given byte source,
+    /// while considering the given transfer syntax specifier.
+    pub fn new_with_ts(source: R, ts: &TransferSyntax) -> Result<Self> {
+        Self::new_with_ts_options(source, ts, Default::default())
+    }
+
+    /// Create a new asynchronous data set token reader
+    /// with the given byte source,
+    /// while considering the given transfer syntax specifier
+    /// and reading options.
+    pub fn new_with_ts_options(
+        source: R,
+        ts: &TransferSyntax,
+        options: ReadOptions,
+    ) -> Result<Self> {
+        let parser =
+            SendStatefulDecoder::new_send(ChunkBuffer::default(), ts, options.charset.clone(), 0)
+                .context(CreateDecoderSnafu)?;
+        let lookahead = send_decoder_for(ts).context(CreateDecoderSnafu)?;
+
+        Ok(AsyncDataSetReader {
+            source,
+            reader: DataSetReader::new(parser, options),
+            lookahead,
+            source_ended: false,
+            fetch_failed: false,
+        })
+    }
+
+    /// Retrieve the inner byte source.
+    ///
+    /// Bytes fetched from the source but not read into tokens yet
+    /// are discarded.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+}
+
+impl<R> AsyncDataSetReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    /// Retrieve the next token of the data set,
+    /// waiting for the data needed from the source.
+    ///
+    /// Returns `None` once the data set has been read to the end
+    /// or after an error.
+    pub async fn next(&mut self) -> Option<Result<DataToken>> {
+        if self.fetch_failed {
+            return None;
+        }
+        if let Err(e) = self.fetch_next_token().await {
+            self.fetch_failed = true;
+            return Some(Err(e));
+        }
+        self.reader.next()
+    }
+
+    /// Fetch from the source the bytes needed for the next token,
+    /// or until the source ends.
+    async fn fetch_next_token(&mut self) -> Result<()> {
+        while !self.source_ended {
+            let buffer = self.reader.decoder().source();
+            let available = buffer.available();
+            let lookahead = &self.lookahead;
+            let needed = self.reader.bytes_needed(available, |bytes| {
+                let mut header_bytes = ChunkBuffer {
+                    bytes: bytes[..bytes.len().min(12)].to_vec(),
+                    start: 0,
+                };
+                lookahead.decode_header(&mut header_bytes).ok()
+            });
+            let missing = match usize::try_from(needed) {
+                Ok(needed) if needed <= available.len() => return Ok(()),
+                Ok(needed) => needed - available.len(),
+                Err(_) => MAX_FETCH_LENGTH,
+            };
+
+            let position = self.reader.decoder().position();
+            let fetched = self
+                .reader
+                .decoder_mut()
+                .source_mut()
+                .fetch(
+                    &mut self.source,
+                    missing.clamp(MIN_FETCH_LENGTH, MAX_FETCH_LENGTH),
+                )
+                .await
+                .context(FetchDataSnafu { offset: position })?;
+            if fetched == 0 {
+                self.source_ended = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncDataSetReader;
+    use crate::dataset::read::{DataSetReader, ReadOptions, StrayItemStrategy};
+    use crate::dataset::DataToken;
+    use dicom_encoding::{Codec, Endianness, TransferSyntax};
+    use tokio::io::AsyncWriteExt;
+
+    const IMPLICIT_VR_LE: TransferSyntax = TransferSyntax::new(
+        "1.2.840.10008.1.2",
+        "Implicit VR Little Endian",
+        Endianness::Little,
+        false,
+        Codec::None,
+    );
+
+    const EXPLICIT_VR_LE: TransferSyntax = TransferSyntax::new(
+        "1.2.840.10008.1.2.1",
+        "Explicit VR Little Endian",
+        Endianness::Little,
+        true,
+        Codec::None,
+    );
+
+    const EXPLICIT_VR_BE: TransferSyntax = TransferSyntax::new(
+        "1.2.840.10008.1.2.2",
+        "Explicit VR Big Endian",
+        Endianness::Big,
+        true,
+        Codec::None,
+    );
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    /// Read the data set synchronously, collecting all tokens and the error.
+    fn read_sync(data: &[u8], ts: &TransferSyntax, options: ReadOptions) -> Vec<String> {
+        DataSetReader::new_with_ts_options(data, ts, options)
+            .unwrap()
+            .map(|token| format!("{:?}", token.map_err(|e| e.to_string())))
+            .collect()
+    }
+
+    /// Read the data set asynchronously through a duplex stream,
+    /// writing it in chunks of the given size.
+    async fn read_async(
+        data: &[u8],
+        ts: &TransferSyntax,
+        options: ReadOptions,
+        chunk_size: usize,
+    ) -> Vec<String> {
+        let (client, mut server) = tokio::io::duplex(chunk_size);
+        let write = async move {
+            for chunk in data.chunks(chunk_size) {
+                // the reader may stop early on errors
+                if server.write_all(chunk).await.is_err() {
+                    break;
+                }
+            }
+        };
+        let read = async {
+            let mut reader = AsyncDataSetReader::new_with_ts_options(client, ts, options).unwrap();
+            assert_send(&reader);
+            let mut tokens = Vec::new();
+            while let Some(token) = reader.next().await {
+                tokens.push(format!("{:?}", token.map_err(|e| e.to_string())));
+            }
+            tokens
+        };
+        tokio::join!(write, read).1
+    }
+
+    async fn check_same_tokens(data: &[u8], ts: &TransferSyntax, options: ReadOptions) {
+        let expected = read_sync(data, ts, options.clone());
+        for chunk_size in [1, 3, 7, 64, 1024] {
+            let tokens = read_async(data, ts, options.clone(), chunk_size).await;
+            assert_eq!(tokens, expected, "chunks of {} bytes", chunk_size);
+        }
+    }
+
+    // Explicit VR Little Endian data set with
+    // a sequence of undefined length with an item of undefined length,
+    // a sequence of defined length with an item of defined length,
+    // and encapsulated pixel data with an offset table and two fragments
+    #[rustfmt::skip]
+    const EXPLICIT_LE: &[u8] = &[
+        // (0008,0005) CS "ISO_IR 100"
+        0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0A, 0x00,
+        b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'0', b'0',
+        // (0008,1140) SQ, undefined length
+        0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+        // item, undefined length
+        0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+        // (0008,1155) UI "1.2.3.4"
+        0x08, 0x00, 0x55, 0x11, b'U', b'I', 0x08, 0x00,
+        b'1', b'.', b'2', b'.', b'3', b'.', b'4', 0x00,
+        // item delimiter
+        0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        // sequence delimiter
+        0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        // (0010,0010) PN "Müller^Hans"
+        0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x0C, 0x00,
+        b'M', 0xFC, b'l', b'l', b'e', b'r', b'^', b'H', b'a', b'n', b's', b' ',
+        // (0040,0275) SQ, 22 bytes
+        0x40, 0x00, 0x75, 0x02, b'S', b'Q', 0x00, 0x00, 0x16, 0x00, 0x00, 0x00,
+        // item, 14 bytes
+        0xFE, 0xFF, 0x00, 0xE0, 0x0E, 0x00, 0x00, 0x00,
+        // (0040,0009) SH "SPS-1"
+        0x40, 0x00, 0x09, 0x00, b'S', b'H', 0x06, 0x00,
+        b'S', b'P', b'S', b'-', b'1', b' ',
+        // (0028,0010) US 2, (0028,0011) US 2
+        0x28, 0x00, 0x10, 0x00, b'U', b'S', 0x02, 0x00, 0x02, 0x00,
+        0x28, 0x00, 0x11, 0x00, b'U', b'S', 0x02, 0x00, 0x02, 0x00,
+        // (7FE0,0010) OB, undefined length
+        0xE0, 0x7F, 0x10, 0x00, b'O', b'B', 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+        // offset table with one entry
+        0xFE, 0xFF, 0x00, 0xE0, 0x04, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        // fragments
+        0xFE, 0xFF, 0x00, 0xE0, 0x04, 0x00, 0x00, 0x00,
+        0x01, 0x02, 0x03, 0x04,
+        0xFE, 0xFF, 0x00, 0xE0, 0x02, 0x00, 0x00, 0x00,
+        0xFF, 0xD9,
+        // sequence delimiter
+        0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[tokio::test]
+    async fn read_in_small_chunks_like_sync_reader() {
+        let ts = EXPLICIT_VR_LE;
+        check_same_tokens(EXPLICIT_LE, &ts, ReadOptions::new()).await;
+
+        // make sure that the tokens are the expected ones
+        let tokens = read_async(EXPLICIT_LE, &ts, ReadOptions::new(), 5).await;
+        assert_eq!(tokens.len(), 31);
+        assert_eq!(tokens[9], "Ok(PrimitiveValue(Strs([\"Müller^Hans \"])))");
+        assert_eq!(tokens[22], "Ok(OffsetTable([0]))");
+        assert_eq!(tokens[28], "Ok(ItemValue([255, 217]))");
+    }
+
+    #[tokio::test]
+    async fn read_tokens_before_the_source_ends() {
+        let (client, mut server) = tokio::io::duplex(64);
+        // only the first data element is available for now
+        server.write_all(&EXPLICIT_LE[..18]).await.unwrap();
+
+        let mut reader = AsyncDataSetReader::new_with_ts(client, &EXPLICIT_VR_LE).unwrap();
+        let token = reader.next().await.unwrap().unwrap();
+        assert!(matches!(token, DataToken::ElementHeader(_)));
+        let token = reader.next().await.unwrap().unwrap();
+        assert_eq!(token, DataToken::PrimitiveValue("ISO_IR 100".into()));
+
+        // the data set ends gracefully with the source
+        drop(server);
+        assert!(reader.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn read_other_transfer_syntaxes_like_sync_reader() {
+        #[rustfmt::skip]
+        let implicit_le: &[u8] = &[
+            // (0008,0060) "MR"
+            0x08, 0x00, 0x60, 0x00, 0x02, 0x00, 0x00, 0x00, b'M', b'R',
+            // (0008,1140) undefined length, one empty item
+            0x08, 0x00, 0x40, 0x11, 0xFF, 0xFF, 0xFF, 0xFF,
+            0xFE, 0xFF, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // (0028,0010) 2
+            0x28, 0x00, 0x10, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x00,
+        ];
+        check_same_tokens(implicit_le, &IMPLICIT_VR_LE, ReadOptions::new()).await;
+
+        #[rustfmt::skip]
+        let explicit_be: &[u8] = &[
+            // (0028,0010) US 2, (0028,0011) US 3
+            0x00, 0x28, 0x00, 0x10, b'U', b'S', 0x00, 0x02, 0x00, 0x02,
+            0x00, 0x28, 0x00, 0x11, b'U', b'S', 0x00, 0x02, 0x00, 0x03,
+        ];
+        check_same_tokens(explicit_be, &EXPLICIT_VR_BE, ReadOptions::new()).await;
+    }
+
+    #[tokio::test]
+    async fn read_skipped_and_truncated_data_like_sync_reader() {
+        let ts = EXPLICIT_VR_LE;
+
+        // a stray item header is skipped,
+        // or fails the reader
+        #[rustfmt::skip]
+        let stray_item: &[u8] = &[
+            0xFE, 0xFF, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R',
+        ];
+        check_same_tokens(stray_item, &ts, ReadOptions::new()).await;
+        check_same_tokens(
+            stray_item,
+            &ts,
+            ReadOptions::new().stray_items(StrayItemStrategy::Fail),
+        )
+        .await;
+
+        // the source ends in the middle of a header, then of a value
+        check_same_tokens(&EXPLICIT_LE[..22], &ts, ReadOptions::new()).await;
+        check_same_tokens(&EXPLICIT_LE[..14], &ts, ReadOptions::new()).await;
+    }
+
+    #[tokio::test]
+    async fn read_values_in_chunks_like_sync_reader() {
+        let ts = EXPLICIT_VR_LE;
+        let options = ReadOptions::new().chunk_values(3);
+        check_same_tokens(EXPLICIT_LE, &ts, options.clone()).await;
+        check_same_tokens(&EXPLICIT_LE[..13], &ts, options).await;
+
+        let tokens = read_async(EXPLICIT_LE, &ts, ReadOptions::new().chunk_values(3), 5).await;
+        assert_eq!(tokens[1], "Ok(ValueChunk([73, 83, 79]))");
+    }
+}
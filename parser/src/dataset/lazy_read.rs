@@ -180,7 +180,7 @@ where
                                 token = LazyDataToken::ItemEnd;
                             }
                         }
-                        self.seq_delimiters.pop();
+                        self.pop_sequence_token();
                         return Ok(Some(token));
                     }
                     Ordering::Less => {
@@ -200,6 +200,10 @@ where
 
     #[inline]
     fn push_sequence_token(&mut self, typ: SeqTokenType, len: Length, pixel_data: bool) {
+        if typ == SeqTokenType::Item && !pixel_data {
+            // data set items have their own character set scope
+            self.parser.begin_item();
+        }
         self.seq_delimiters.push(SeqToken {
             typ,
             pixel_data,
@@ -208,11 +212,29 @@ where
         })
     }
 
+    #[inline]
+    fn pop_sequence_token(&mut self) {
+        if let Some(SeqToken {
+            typ: SeqTokenType::Item,
+            pixel_data: false,
+            ..
+        }) = self.seq_delimiters.pop()
+        {
+            self.parser.end_item();
+        }
+    }
+
     /// Retrieve the inner stateful decoder from this data set reader.
     pub fn into_decoder(self) -> S {
         self.parser
     }
 
+    /// Retrieve the current reading position of the inner decoder,
+    /// in bytes.
+    pub fn position(&self) -> u64 {
+        self.parser.position()
+    }
+
     /// Advance and retrieve the next DICOM data token.
     ///
     /// **Note:** For the data set to be successfully parsed,
@@ -260,7 +282,7 @@ where
                         }
                         SequenceItemHeader::ItemDelimiter => {
                             // closed an item
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = true;
                             // sequences can end after an item delimiter
                             self.delimiter_check_pending = true;
@@ -268,7 +290,7 @@ where
                         }
                         SequenceItemHeader::SequenceDelimiter => {
                             // closed a sequence
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = false;
                             // items can end after a nested sequence ends
                             self.delimiter_check_pending = true;
@@ -324,7 +346,7 @@ where
                         }
                         SequenceItemHeader::SequenceDelimiter => {
                             // empty pixel data
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = false;
                             Some(Ok(LazyDataToken::SequenceEnd))
                         }
@@ -374,7 +396,7 @@ where
                 }) => {
                     self.in_sequence = true;
                     // pop item delimiter
-                    self.seq_delimiters.pop();
+                    self.pop_sequence_token();
                     // sequences can end after this token
                     self.delimiter_check_pending = true;
                     Some(Ok(LazyDataToken::ItemEnd))
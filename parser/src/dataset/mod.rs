@@ -7,13 +7,15 @@ use snafu::{OptionExt, ResultExt, Snafu};
 use std::default::Default;
 use std::fmt;
 
+#[cfg(feature = "async")]
+pub mod asynchronous;
 pub mod lazy_read;
 pub mod read;
 pub mod write;
 
 pub use self::read::DataSetReader;
 use self::read::ValueReadStrategy;
-pub use self::write::DataSetWriter;
+pub use self::write::{BatchWriter, DataSetWriter};
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -64,12 +66,22 @@ pub enum DataToken {
     /// for each frame in the sequence of items,
     /// as per PS 3.5, Section A.4.
     OffsetTable(Vec<u32>),
+    /// A piece of the raw value of a primitive data element
+    /// or of a pixel data fragment,
+    /// emitted by data set readers which
+    /// [read values in chunks](read::ReadOptions::chunk_values).
+    ///
+    /// The bytes are kept as found in the source, without any decoding.
+    /// Chunks follow each other until the length declared
+    /// by the preceding element header or item start is reached.
+    ValueChunk(Vec<u8>),
 }
 
 impl fmt::Display for DataToken {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             DataToken::PrimitiveValue(ref v) => write!(f, "PrimitiveValue({:?})", v.value_type()),
+            DataToken::ValueChunk(ref chunk) => write!(f, "ValueChunk({} bytes)", chunk.len()),
             other => write!(f, "{:?}", other),
         }
     }
@@ -104,7 +116,7 @@ impl PartialEq<Self> for DataToken {
             ) => tag1 == tag2 && len1.inner_eq(*len2),
             (ItemStart { len: len1 }, ItemStart { len: len2 }) => len1.inner_eq(*len2),
             (PrimitiveValue(v1), PrimitiveValue(v2)) => v1 == v2,
-            (ItemValue(v1), ItemValue(v2)) => v1 == v2,
+            (ItemValue(v1), ItemValue(v2)) | (ValueChunk(v1), ValueChunk(v2)) => v1 == v2,
             (OffsetTable(v1), OffsetTable(v2)) => v1 == v2,
             (ItemEnd, ItemEnd)
             | (SequenceEnd, SequenceEnd)
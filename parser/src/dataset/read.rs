@@ -9,9 +9,12 @@ use dicom_core::header::{DataElementHeader, Header, Length, SequenceItemHeader};
 use dicom_core::{PrimitiveValue, Tag, VR};
 use dicom_encoding::text::SpecificCharacterSet;
 use dicom_encoding::transfer_syntax::TransferSyntax;
-use snafu::{Backtrace, ResultExt, Snafu};
+use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
 use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::io::Read;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use super::{DataToken, SeqTokenType};
 
@@ -25,6 +28,7 @@ where
 #[non_exhaustive]
 pub enum Error {
     #[snafu(display("Could not create decoder"))]
+    #[snafu(visibility(pub(crate)))]
     CreateDecoder {
         #[snafu(backtrace)]
         source: DecoderError,
@@ -43,6 +47,7 @@ pub enum Error {
     ReadValue {
         len: u32,
         tag: Tag,
+        vr: VR,
         #[snafu(backtrace)]
         source: DecoderError,
     },
@@ -52,6 +57,12 @@ pub enum Error {
         #[snafu(backtrace)]
         source: DecoderError,
     },
+    #[snafu(display("Could not skip the {} remaining bytes of item", len))]
+    SkipItemRemainder {
+        len: u32,
+        #[snafu(backtrace)]
+        source: DecoderError,
+    },
     #[snafu(display(
         "Inconsistent sequence end: expected end at {} bytes but read {}",
         end_of_sequence,
@@ -64,12 +75,553 @@ pub enum Error {
     },
     #[snafu(display("Unexpected item tag {} while reading element header", tag))]
     UnexpectedItemTag { tag: Tag, backtrace: Backtrace },
+    #[snafu(display("Data element tagged {} out of order after {}", tag, previous))]
+    TagOutOfOrder {
+        tag: Tag,
+        previous: Tag,
+        backtrace: Backtrace,
+    },
     /// Undefined pixel item length
     UndefinedItemLength,
+    #[snafu(display("Odd value length {} of {} element tagged {}", len, vr, tag))]
+    OddLength {
+        tag: Tag,
+        vr: VR,
+        len: u32,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Value length {} of element tagged {} exceeds the maximum of {}",
+        len,
+        tag,
+        max
+    ))]
+    ValueTooLong {
+        tag: Tag,
+        len: u32,
+        max: u32,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Sequence tagged {} exceeds the maximum nesting depth of {}",
+        tag,
+        max_depth
+    ))]
+    NestingTooDeep {
+        tag: Tag,
+        max_depth: u32,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Length {} of sequence or item in {} at byte offset {} overflows the byte position",
+        len,
+        tag,
+        offset
+    ))]
+    LengthOverflow {
+        tag: Tag,
+        len: u32,
+        offset: u64,
+        backtrace: Backtrace,
+    },
+    /// The source ended in the middle of a header or value,
+    /// as opposed to at the end of a data element
+    #[snafu(display(
+        "Premature end of data at byte offset {}: got {} of {} bytes",
+        offset,
+        got,
+        expected
+    ))]
+    PrematureEnd {
+        /// the tag of the element whose value was cut short,
+        /// or `None` if the source ended within a header
+        tag: Option<Tag>,
+        /// the number of bytes of the header or value
+        expected: u64,
+        /// the number of bytes available before the end
+        got: u64,
+        /// the byte offset at which the header or value started
+        offset: u64,
+        backtrace: Backtrace,
+    },
+    /// The asynchronous source failed to provide more data
+    #[cfg(feature = "async")]
+    #[snafu(display("Could not fetch data at byte offset {}", offset))]
+    #[snafu(visibility(pub(crate)))]
+    FetchData {
+        /// the position of the data set reader
+        offset: u64,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+    /// A lower-level failure,
+    /// annotated with where it happened in the data set
+    #[snafu(display("Could not read data set at {}", context))]
+    WithContext {
+        context: ErrorContext,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Obtain the location in the data set where the error occurred,
+    /// if it was recorded.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Obtain the underlying error,
+    /// stripping out the data set location if present.
+    pub fn without_context(&self) -> &Error {
+        match self {
+            Error::WithContext { source, .. } => source.without_context(),
+            e => e,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// One step in the path from the root data set to a nested data element:
+/// the sequence containing it and, if already inside one,
+/// the index of the item (starting at 0).
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct PathSegment {
+    /// the tag of the sequence element
+    pub sequence: Tag,
+    /// the index of the item within the sequence,
+    /// or `None` if between items
+    pub item: Option<u32>,
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.sequence)?;
+        if let Some(item) = self.item {
+            write!(f, "[{}]", item)?;
+        }
+        Ok(())
+    }
+}
+
+/// The location in a data set at which a reading error occurred.
+///
+/// Displayed as the sequence path followed by the element tag,
+/// such as `(0040,A730)[1].(0040,A160), byte offset 294`.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct ErrorContext {
+    tag: Option<Tag>,
+    path: Vec<PathSegment>,
+    offset: u64,
+}
+
+impl ErrorContext {
+    /// The tag of the data element being read, if known.
+    pub fn tag(&self) -> Option<Tag> {
+        self.tag
+    }
+
+    /// The enclosing sequences and items, from the outermost to the innermost.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// The byte offset of the reader at the point of failure,
+    /// as counted by the stateful decoder.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut steps = self
+            .path
+            .iter()
+            .map(|segment| segment as &dyn fmt::Display)
+            .chain(self.tag.as_ref().map(|tag| tag as &dyn fmt::Display))
+            .peekable();
+        if steps.peek().is_some() {
+            for (i, step) in steps.enumerate() {
+                if i > 0 {
+                    f.write_str(".")?;
+                }
+                step.fmt(f)?;
+            }
+            f.write_str(", ")?;
+        }
+        write!(f, "byte offset {}", self.offset)
+    }
+}
+
+/// A kind of anomaly in the data set
+/// which the reader recovered from.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// A value representation code unknown to this library
+    /// was read as `UN`.
+    UnknownVr {
+        /// the two bytes of the value representation code
+        bytes: [u8; 2],
+    },
+    /// A data element with an odd value length was read anyway,
+    /// as per the [odd length strategy](OddLengthStrategy).
+    OddLength {
+        /// the value length declared
+        len: u32,
+    },
+    /// An item or delimiter header found out of place was skipped
+    /// to re-synchronize with the data set.
+    StrayItem,
+    /// An unsupported character set was declared,
+    /// so text is decoded as ISO IR 100 instead.
+    CharsetFallback {
+        /// the defined term of the character set
+        name: String,
+    },
+    /// The length in a group length element
+    /// does not match the number of bytes in the group.
+    GroupLengthMismatch {
+        /// the group length declared
+        expected: u32,
+        /// the actual length of the group
+        found: u64,
+    },
+    /// An item was delimited before reaching its declared length,
+    /// so its remaining bytes were skipped.
+    ItemUnderRead {
+        /// the number of bytes skipped
+        skipped: u32,
+    },
+    /// A data element was read after one with a greater tag
+    /// in the same data set,
+    /// as per the [tag order strategy](TagOrderStrategy).
+    TagOutOfOrder {
+        /// the tag of the data element read before
+        previous: Tag,
+    },
+    /// A text value too long for its value representation
+    /// was cut by a data set writer
+    /// (see [`LengthValidation::Truncate`]).
+    ///
+    /// [`LengthValidation::Truncate`]: dicom_encoding::text::length::LengthValidation::Truncate
+    ValueTruncated {
+        /// the index of the value in the element
+        index: u32,
+        /// the encoded length of the value
+        /// (or of the person name component group), in bytes
+        length: u32,
+        /// the maximum length of the value, in bytes
+        max: u32,
+    },
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningKind::UnknownVr { bytes } => write!(
+                f,
+                "unknown value representation {:?} read as UN",
+                String::from_utf8_lossy(bytes)
+            ),
+            WarningKind::OddLength { len } => write!(f, "odd value length {} accepted", len),
+            WarningKind::StrayItem => f.write_str("stray item header skipped"),
+            WarningKind::CharsetFallback { name } => write!(
+                f,
+                "unsupported character set `{}`, decoding text as ISO_IR 100",
+                name
+            ),
+            WarningKind::GroupLengthMismatch { expected, found } => write!(
+                f,
+                "group length {} does not match the {} bytes in the group",
+                expected, found
+            ),
+            WarningKind::ItemUnderRead { skipped } => {
+                write!(f, "item ended early, {} remaining bytes skipped", skipped)
+            }
+            WarningKind::TagOutOfOrder { previous } => {
+                write!(f, "data element out of order after {}", previous)
+            }
+            WarningKind::ValueTruncated { index, length, max } => write!(
+                f,
+                "value #{} of {} bytes truncated to {} bytes",
+                index, length, max
+            ),
+        }
+    }
+}
+
+/// A recoverable anomaly found by the data set reader.
+///
+/// Warnings are logged through `tracing`,
+/// and can also be gathered by the application
+/// via [`ReadOptions::on_warning`] or [`ReadOptions::collect_warnings`].
+///
+/// Data set writers report the values they truncate with the same type
+/// (see [`DataSetWriter::with_warning_collector`]),
+/// in which case the offset is counted by the stateful encoder.
+///
+/// [`DataSetWriter::with_warning_collector`]: crate::dataset::DataSetWriter::with_warning_collector
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct ReadWarning {
+    kind: WarningKind,
+    tag: Tag,
+    offset: u64,
+}
+
+impl ReadWarning {
+    pub(crate) fn new(kind: WarningKind, tag: Tag, offset: u64) -> Self {
+        ReadWarning { kind, tag, offset }
+    }
+
+    /// The kind of anomaly found.
+    pub fn kind(&self) -> &WarningKind {
+        &self.kind
+    }
+
+    /// The tag of the data element concerned.
+    ///
+    /// For group length mismatches, this is the tag of the group length element.
+    /// For items ending early, this is the tag of the sequence.
+    pub fn tag(&self) -> Tag {
+        self.tag
+    }
+
+    /// The byte offset of the anomaly, as counted by the stateful decoder.
+    ///
+    /// This is the start of the header of the element concerned,
+    /// except for group length mismatches,
+    /// where it is the offset at which the group actually ends,
+    /// and items ending early,
+    /// where it is the offset of the first byte skipped.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl fmt::Display for ReadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}, byte offset {}",
+            self.kind, self.tag, self.offset
+        )
+    }
+}
+
+/// A function receiving the warnings of a data set reader.
+#[derive(Clone)]
+pub struct WarningHandler(Arc<dyn Fn(&ReadWarning) + Send + Sync>);
+
+impl WarningHandler {
+    /// Create a warning handler from the given function.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&ReadWarning) + Send + Sync + 'static,
+    {
+        WarningHandler(Arc::new(f))
+    }
+
+    fn call(&self, warning: &ReadWarning) {
+        (self.0)(warning)
+    }
+}
+
+impl fmt::Debug for WarningHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WarningHandler(..)")
+    }
+}
+
+impl PartialEq for WarningHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A shared list of the warnings of data set readers and writers,
+/// for applications without a logger.
+///
+/// Clones of a collector share the same list,
+/// and compare equal to each other.
+///
+/// ```
+/// # use dicom_parser::dataset::read::{ReadOptions, WarningCollector};
+/// let collector = WarningCollector::new();
+/// let options = ReadOptions::new().collect_warnings(&collector);
+/// // ... read a data set with these options ...
+/// for warning in collector.take() {
+///     eprintln!("{}", warning);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WarningCollector {
+    warnings: Arc<Mutex<Vec<ReadWarning>>>,
+}
+
+impl WarningCollector {
+    /// Create a new collector with no warnings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieve a copy of the warnings collected so far.
+    pub fn warnings(&self) -> Vec<ReadWarning> {
+        self.lock().clone()
+    }
+
+    /// Move out the warnings collected so far,
+    /// leaving the collector empty.
+    pub fn take(&self) -> Vec<ReadWarning> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub(crate) fn push(&self, warning: ReadWarning) {
+        self.lock().push(warning);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<ReadWarning>> {
+        // a panic while holding the lock cannot leave the list inconsistent
+        self.warnings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl PartialEq for WarningCollector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.warnings, &other.warnings)
+    }
+}
+
+impl Eq for WarningCollector {}
+
+impl Hash for WarningCollector {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.warnings).hash(state);
+    }
+}
+
+/// How serious a problem found by the data set reader is.
+#[derive(Debug, Copy, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// An anomaly which the reader recovered from
+    /// without discarding any content.
+    Warning,
+    /// An error which the reader recovered from
+    /// by discarding or closing part of the data set.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A problem found by the data set reader
+/// while [collecting issues](ReadOptions::collect_issues).
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct ParseIssue {
+    severity: Severity,
+    context: ErrorContext,
+    message: String,
+}
+
+impl ParseIssue {
+    /// How serious the problem is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// The tag of the data element concerned, if known.
+    pub fn tag(&self) -> Option<Tag> {
+        self.context.tag()
+    }
+
+    /// The enclosing sequences and items, from the outermost to the innermost.
+    pub fn path(&self) -> &[PathSegment] {
+        self.context.path()
+    }
+
+    /// The byte offset of the problem, as counted by the stateful decoder.
+    pub fn offset(&self) -> u64 {
+        self.context.offset()
+    }
+
+    /// A description of the problem and of what the reader did about it.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} at {}", self.severity, self.message, self.context)
+    }
+}
+
+/// A shared list of the problems found by data set readers
+/// which recover from errors instead of failing.
+///
+/// Clones of a collector share the same list.
+///
+/// ```
+/// # use dicom_parser::dataset::read::{IssueCollector, ReadOptions};
+/// let collector = IssueCollector::new();
+/// let options = ReadOptions::new().collect_issues(&collector);
+/// // ... read a data set with these options ...
+/// for issue in collector.take() {
+///     eprintln!("{}", issue);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IssueCollector {
+    issues: Arc<Mutex<Vec<ParseIssue>>>,
+}
+
+impl IssueCollector {
+    /// Create a new collector with no issues.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieve a copy of the issues collected so far.
+    pub fn issues(&self) -> Vec<ParseIssue> {
+        self.lock().clone()
+    }
+
+    /// Move out the issues collected so far,
+    /// leaving the collector empty.
+    pub fn take(&self) -> Vec<ParseIssue> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn push(&self, issue: ParseIssue) {
+        self.lock().push(issue);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<ParseIssue>> {
+        // a panic while holding the lock cannot leave the list inconsistent
+        self.issues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl PartialEq for IssueCollector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.issues, &other.issues)
+    }
+}
+
 /// A reader-specific token representing a sequence or item start.
 #[derive(Debug, Copy, Clone, PartialEq)]
 struct SeqToken {
@@ -80,9 +632,19 @@ struct SeqToken {
     len: Length,
     /// Whether this sequence token is part of an encapsulated pixel data.
     pixel_data: bool,
+    /// The tag of the sequence element
+    /// (or of the enclosing sequence element in the case of an item).
+    tag: Tag,
+    /// For sequences, the number of items started so far.
+    /// For items, the index of the item in its sequence.
+    index: u32,
     /// The number of bytes the parser has read until it reached the
     /// beginning of the sequence or item value data.
     base_offset: u64,
+    /// For items, the tag of the last data element read
+    /// in the enclosing data set,
+    /// to resume checking the tag order after the item.
+    last_tag: Option<Tag>,
 }
 
 /// The value reading strategy for the data set reader.
@@ -122,17 +684,133 @@ pub enum ValueReadStrategy {
     Raw,
 }
 
-/// The set of options for the data set reader.
+/// What to do with data elements declaring an odd value length,
+/// which is forbidden by the standard.
 #[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum OddLengthStrategy {
+    /// Read the value with the length declared.
+    ///
+    /// This is the default strategy.
+    #[default]
+    Accept,
+    /// Read one more byte than declared,
+    /// assuming that the value was padded to even length
+    /// but the length was left uncorrected.
+    NextEven,
+    /// Fail with an error.
+    Fail,
+}
+
+/// What to do with item or delimitation item headers
+/// found where a data element header was expected,
+/// such as an item header at the root of the data set.
+///
+/// Such headers are never read as data elements.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum StrayItemStrategy {
+    /// Skip the 8-byte header with a warning
+    /// and continue reading the data set.
+    ///
+    /// This is the default strategy.
+    #[default]
+    Skip,
+    /// Fail with an error.
+    Fail,
+}
+
+/// What to do with data elements which are not in ascending tag order
+/// within their data set or item,
+/// as required by the standard.
+///
+/// Repeated data elements are not out of order:
+/// see [`DuplicatePolicy`] for those.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum TagOrderStrategy {
+    /// Read the data element without checking.
+    ///
+    /// This is the default strategy.
+    #[default]
+    Accept,
+    /// Read the data element with a warning.
+    Warn,
+    /// Fail with an error.
+    Fail,
+}
+
+/// What to do with a data element whose tag was already seen
+/// in the same data set.
+///
+/// The data set reader itself does not keep track of the elements read,
+/// so this policy is applied by consumers
+/// which gather tokens into data sets,
+/// such as the in-memory objects of `dicom-object`.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum DuplicatePolicy {
+    /// Keep the element read last, replacing the previous one.
+    ///
+    /// This is the default policy.
+    #[default]
+    KeepLast,
+    /// Keep the element read first, discarding the next ones.
+    KeepFirst,
+    /// Fail with an error.
+    Fail,
+}
+
+/// The set of options for reading DICOM data sets.
+///
+/// Options are built from the defaults
+/// by chaining the builder methods.
+///
+/// ```
+/// # use dicom_parser::dataset::read::{OddLengthStrategy, ReadOptions};
+/// let options = ReadOptions::new()
+///     .odd_length(OddLengthStrategy::Fail)
+///     .max_value_length(64 * 1024 * 1024)
+///     .max_depth(16);
+/// # assert_eq!(options.max_depth, Some(16));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
 #[non_exhaustive]
-pub struct DataSetReaderOptions {
+pub struct ReadOptions {
     /// the value reading strategy
     pub value_read: ValueReadStrategy,
     /// the position of the reader as received at building time
     pub base_offset: u64,
+    /// the character set to assume
+    /// until the data set declares one via _Specific Character Set_
+    pub charset: SpecificCharacterSet,
+    /// what to do with odd value lengths
+    pub odd_length: OddLengthStrategy,
+    /// what to do with item headers out of place
+    pub stray_items: StrayItemStrategy,
+    /// the maximum value length admitted for a single element or fragment,
+    /// so that no more memory than this is allocated at once for a value
+    pub max_value_length: Option<u32>,
+    /// the maximum number of sequences nested into each other
+    pub max_depth: Option<u32>,
+    /// the maximum length of the value chunks emitted,
+    /// if longer values should be read in chunks
+    pub value_chunk_size: Option<u32>,
+    /// what to do with repeated data elements
+    pub duplicates: DuplicatePolicy,
+    /// what to do with data elements out of tag order
+    pub tag_order: TagOrderStrategy,
+    /// the function receiving the warnings of the reader, if any
+    pub on_warning: Option<WarningHandler>,
+    /// the collector of problems found,
+    /// if the reader should recover from errors where possible
+    pub issues: Option<IssueCollector>,
 }
 
-impl DataSetReaderOptions {
+/// The set of options for the data set reader.
+pub type DataSetReaderOptions = ReadOptions;
+
+impl ReadOptions {
+    /// Create a new set of options with the defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
     /// Replace the value reading strategy of the options.
     pub fn value_read(mut self, value_read: ValueReadStrategy) -> Self {
         self.value_read = value_read;
@@ -143,6 +821,106 @@ impl DataSetReaderOptions {
         self.base_offset = base_offset;
         self
     }
+    /// Replace the character set to assume by default.
+    ///
+    /// The default is ISO IR 6 (the basic character repertoire).
+    pub fn charset(mut self, charset: SpecificCharacterSet) -> Self {
+        self.charset = charset;
+        self
+    }
+    /// Replace the strategy for elements with an odd value length.
+    pub fn odd_length(mut self, odd_length: OddLengthStrategy) -> Self {
+        self.odd_length = odd_length;
+        self
+    }
+    /// Set the maximum value length admitted for a single element or fragment.
+    ///
+    /// Elements declaring a longer value make reading fail
+    /// before any memory is allocated for them.
+    /// There is no limit by default.
+    pub fn max_value_length(mut self, max_value_length: u32) -> Self {
+        self.max_value_length = Some(max_value_length);
+        self
+    }
+    /// Set the maximum number of sequences nested into each other.
+    ///
+    /// A value of 1 admits sequences in the root data set,
+    /// but no sequences inside their items.
+    /// There is no limit by default.
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+    /// Read values longer than the given size in chunks.
+    ///
+    /// The values of primitive data elements
+    /// and the fragments of encapsulated pixel data
+    /// which are longer than `chunk_size` bytes
+    /// are emitted as a series of [`DataToken::ValueChunk`] tokens
+    /// of at most `chunk_size` bytes each,
+    /// instead of a single value token.
+    /// This keeps the memory needed to read large values
+    /// bounded by the chunk size.
+    /// Values in chunks are not decoded,
+    /// so the chunk size should exceed the length
+    /// of any value which affects how the rest of the data set is read,
+    /// such as _Specific Character Set_.
+    ///
+    /// Values are always read whole by default.
+    pub fn chunk_values(mut self, chunk_size: u32) -> Self {
+        self.value_chunk_size = Some(chunk_size);
+        self
+    }
+    /// Replace the strategy for item headers out of place.
+    pub fn stray_items(mut self, stray_items: StrayItemStrategy) -> Self {
+        self.stray_items = stray_items;
+        self
+    }
+    /// Replace the policy for repeated data elements.
+    pub fn duplicates(mut self, duplicates: DuplicatePolicy) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+    /// Replace the strategy for data elements out of tag order.
+    pub fn tag_order(mut self, tag_order: TagOrderStrategy) -> Self {
+        self.tag_order = tag_order;
+        self
+    }
+    /// Set a function to receive the warnings of the reader,
+    /// in addition to logging them.
+    ///
+    /// This replaces any previous handler or collector.
+    pub fn on_warning<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&ReadWarning) + Send + Sync + 'static,
+    {
+        self.on_warning = Some(WarningHandler::new(f));
+        self
+    }
+    /// Gather the warnings of the reader into the given collector,
+    /// in addition to logging them.
+    ///
+    /// This replaces any previous handler or collector.
+    pub fn collect_warnings(self, collector: &WarningCollector) -> Self {
+        let collector = collector.clone();
+        self.on_warning(move |warning| collector.push(warning.clone()))
+    }
+    /// Recover from errors where possible instead of failing,
+    /// gathering all problems found into the given collector.
+    ///
+    /// Warnings are collected as well,
+    /// and errors which the reader recovers from
+    /// are reported here instead of being returned:
+    /// values which cannot be interpreted are read as empty,
+    /// values longer than the [maximum value length](Self::max_value_length)
+    /// are skipped,
+    /// and sequences or items running past their declared length
+    /// are closed where they end.
+    /// Any other error still stops the reader.
+    pub fn collect_issues(mut self, collector: &IssueCollector) -> Self {
+        self.issues = Some(collector.clone());
+        self
+    }
 }
 
 /// A higher-level reader for retrieving structure in a DICOM data set from an
@@ -152,7 +930,7 @@ pub struct DataSetReader<S> {
     /// the stateful decoder
     parser: S,
     /// the options of this reader
-    options: DataSetReaderOptions,
+    options: ReadOptions,
     /// whether the reader is expecting an item header next (or a sequence delimiter)
     in_sequence: bool,
     /// whether the reader is expecting the first item value of a pixel sequence next
@@ -164,10 +942,32 @@ pub struct DataSetReader<S> {
     seq_delimiters: Vec<SeqToken>,
     /// fuse the iteration process if true
     hard_break: bool,
+    /// the number of bytes left of the value being read in chunks, if any
+    chunk_remaining: Option<u32>,
     /// last decoded header
     last_header: Option<DataElementHeader>,
     /// if a peek was taken, this holds the token peeked
     peek: Option<DataToken>,
+    /// the offset of the last data element header decoded
+    header_offset: u64,
+    /// the group length element in effect, if any
+    group_length: Option<GroupLength>,
+    /// the tag of the last data element read in the current data set
+    last_tag: Option<Tag>,
+}
+
+/// A group length element read,
+/// to be checked against the actual length of the group.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct GroupLength {
+    /// the tag of the group length element
+    tag: Tag,
+    /// the group length declared
+    len: u32,
+    /// the offset right after the group length element
+    start: u64,
+    /// the number of sequences and items enclosing the group
+    depth: usize,
 }
 
 impl<R> DataSetReader<DynStatefulDecoder<R>> {
@@ -178,7 +978,7 @@ impl<R> DataSetReader<DynStatefulDecoder<R>> {
     where
         R: Read,
     {
-        Self::new_with_ts_cs_options(source, ts, Default::default(), Default::default())
+        Self::new_with_ts_options(source, ts, Default::default())
     }
 
     /// Create a new data set token reader with the given byte source,
@@ -193,40 +993,50 @@ impl<R> DataSetReader<DynStatefulDecoder<R>> {
     where
         R: Read,
     {
-        Self::new_with_ts_cs_options(source, ts, cs, Default::default())
+        Self::new_with_ts_options(source, ts, ReadOptions::new().charset(cs))
     }
 
-    /// Create a new iterator with the given stateful decoder and options.
+    /// Create a new data set token reader with the given byte source,
+    /// while considering the given transfer syntax specifier
+    /// and reading options.
+    pub fn new_with_ts_options(source: R, ts: &TransferSyntax, options: ReadOptions) -> Result<Self>
+    where
+        R: Read,
+    {
+        let parser = DynStatefulDecoder::new_with(source, ts, options.charset.clone(), 0)
+            .context(CreateDecoderSnafu)?;
+
+        is_stateful_decode(&parser);
+
+        Ok(DataSetReader::new(parser, options))
+    }
+
+    /// Create a new data set token reader with the given byte source,
+    /// while considering the given transfer syntax specifier,
+    /// the specific character set to assume by default,
+    /// and reading options.
+    ///
+    /// The character set given takes precedence
+    /// over the one in the options.
     pub fn new_with_ts_cs_options(
         source: R,
         ts: &TransferSyntax,
         cs: SpecificCharacterSet,
-        options: DataSetReaderOptions,
+        options: ReadOptions,
     ) -> Result<Self>
     where
         R: Read,
     {
-        let parser = DynStatefulDecoder::new_with(source, ts, cs, 0).context(CreateDecoderSnafu)?;
-
-        is_stateful_decode(&parser);
-
-        Ok(DataSetReader {
-            parser,
-            options,
-            seq_delimiters: Vec::new(),
-            delimiter_check_pending: false,
-            offset_table_next: false,
-            in_sequence: false,
-            hard_break: false,
-            last_header: None,
-            peek: None,
-        })
+        Self::new_with_ts_options(source, ts, options.charset(cs))
     }
 }
 
 impl<S> DataSetReader<S> {
     /// Create a new iterator with the given stateful decoder and options.
-    pub fn new(decoder: S, options: DataSetReaderOptions) -> Self {
+    ///
+    /// The character set in the options is not used,
+    /// as the decoder already has one.
+    pub fn new(decoder: S, options: ReadOptions) -> Self {
         DataSetReader {
             parser: decoder,
             options,
@@ -235,10 +1045,26 @@ impl<S> DataSetReader<S> {
             offset_table_next: false,
             in_sequence: false,
             hard_break: false,
+            chunk_remaining: None,
             last_header: None,
             peek: None,
+            header_offset: 0,
+            group_length: None,
+            last_tag: None,
         }
     }
+
+    /// Retrieve the underlying stateful decoder.
+    #[cfg(feature = "async")]
+    pub(crate) fn decoder(&self) -> &S {
+        &self.parser
+    }
+
+    /// Retrieve the underlying stateful decoder for modification.
+    #[cfg(feature = "async")]
+    pub(crate) fn decoder_mut(&mut self) -> &mut S {
+        &mut self.parser
+    }
 }
 
 impl<S> Iterator for DataSetReader<S>
@@ -256,6 +1082,27 @@ where
             return Some(Ok(token));
         }
 
+        // the element whose value is about to be read, if any
+        let tag = self
+            .last_header
+            .filter(|header| !header.is_encapsulated_pixeldata())
+            .map(|header| header.tag);
+
+        match self.read_token()? {
+            Ok(token) => Some(Ok(token)),
+            Err(e) => Some(Err(Error::WithContext {
+                context: self.error_context(tag),
+                source: Box::new(self.check_premature_end(e)),
+            })),
+        }
+    }
+}
+
+impl<S> DataSetReader<S>
+where
+    S: StatefulDecode,
+{
+    fn read_token(&mut self) -> Option<Result<DataToken>> {
         // item or sequence delimitation logic for explicit lengths
         if self.delimiter_check_pending {
             match self.update_seq_delimiters() {
@@ -277,12 +1124,14 @@ where
                         SequenceItemHeader::Item { len } => {
                             // entered a new item
                             self.in_sequence = false;
-                            self.push_sequence_token(
-                                SeqTokenType::Item,
-                                len,
-                                self.seq_delimiters.last()
-                                    .expect("item header should be read only inside an existing sequence")
-                                    .pixel_data);
+                            let pixel_data = self
+                                .seq_delimiters
+                                .last()
+                                .expect(
+                                    "item header should be read only inside an existing sequence",
+                                )
+                                .pixel_data;
+                            self.push_item_token(len, pixel_data);
                             // items can be empty
                             if len == Length(0) {
                                 self.delimiter_check_pending = true;
@@ -291,7 +1140,7 @@ where
                         }
                         SequenceItemHeader::ItemDelimiter => {
                             // closed an item
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = true;
                             // sequences can end after an item delimiter
                             self.delimiter_check_pending = true;
@@ -299,7 +1148,7 @@ where
                         }
                         SequenceItemHeader::SequenceDelimiter => {
                             // closed a sequence
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = false;
                             // items can end after a nested sequence ends
                             self.delimiter_check_pending = true;
@@ -320,41 +1169,51 @@ where
         }) = self.seq_delimiters.last()
         {
             let len = match len.get() {
-                Some(len) => len as usize,
+                Some(len) => len,
                 None => return Some(UndefinedItemLengthSnafu.fail()),
             };
-
+            if let Err(e) = self.check_max_value_length(Tag(0xFFFE, 0xE000), len) {
+                self.hard_break = true;
+                return Some(Err(e));
+            }
             if self.offset_table_next {
                 // offset table
-                let mut offset_table = Vec::with_capacity(len);
+                let mut offset_table = Vec::new();
 
                 self.offset_table_next = false;
 
                 // need to pop item delimiter on the next iteration
                 self.delimiter_check_pending = true;
 
-                Some(
-                    match self.parser.read_u32_to_vec(len as u32, &mut offset_table) {
-                        Ok(()) => Ok(DataToken::OffsetTable(offset_table)),
-                        Err(e) => Err(e).context(ReadItemValueSnafu { len: len as u32 }),
-                    },
-                )
+                Some(match self.parser.read_u32_to_vec(len, &mut offset_table) {
+                    Ok(()) => Ok(DataToken::OffsetTable(offset_table)),
+                    Err(e) => Err(e).context(ReadItemValueSnafu { len }),
+                })
+            } else if let Some(remaining) = self.chunk_remaining(len) {
+                // the next chunk of the fragment
+                Some(match self.read_chunk(remaining) {
+                    Ok(chunk) => Ok(chunk),
+                    Err(e) => {
+                        self.hard_break = true;
+                        Err(e).context(ReadItemValueSnafu { len })
+                    }
+                })
             } else {
                 // item value
-                let mut value = Vec::with_capacity(len);
+                let mut value = Vec::new();
 
                 // need to pop item delimiter on the next iteration
                 self.delimiter_check_pending = true;
                 Some(
                     self.parser
-                        .read_to_vec(len as u32, &mut value)
+                        .read_to_vec(len, &mut value)
                         .map(|_| Ok(DataToken::ItemValue(value)))
-                        .unwrap_or_else(|e| Err(e).context(ReadItemValueSnafu { len: len as u32 })),
+                        .unwrap_or_else(|e| Err(e).context(ReadItemValueSnafu { len })),
                 )
             }
         } else if let Some(header) = self.last_header {
             if header.is_encapsulated_pixeldata() {
-                self.push_sequence_token(SeqTokenType::Sequence, Length::UNDEFINED, true);
+                self.push_sequence_token(header.tag, Length::UNDEFINED, true);
                 self.last_header = None;
 
                 // encapsulated pixel data, expecting offset table
@@ -363,7 +1222,7 @@ where
                         SequenceItemHeader::Item { len } => {
                             // entered a new item
                             self.in_sequence = false;
-                            self.push_sequence_token(SeqTokenType::Item, len, true);
+                            self.push_item_token(len, true);
                             // items can be empty
                             if len == Length(0) {
                                 self.delimiter_check_pending = true;
@@ -374,7 +1233,7 @@ where
                         }
                         SequenceItemHeader::SequenceDelimiter => {
                             // empty pixel data
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = false;
                             Some(Ok(DataToken::SequenceEnd))
                         }
@@ -388,16 +1247,48 @@ where
                         Some(Err(e).context(ReadItemHeaderSnafu))
                     }
                 }
+            } else if let Some(remaining) =
+                header.len.get().and_then(|len| self.chunk_remaining(len))
+            {
+                // the next chunk of the value
+                match self.read_chunk(remaining) {
+                    Ok(chunk) => {
+                        if self.chunk_remaining.is_none() {
+                            self.last_header = None;
+                        }
+                        Some(Ok(chunk))
+                    }
+                    Err(e) => {
+                        self.hard_break = true;
+                        self.last_header = None;
+                        Some(Err(e).context(ReadValueSnafu {
+                            len: header.len.0,
+                            tag: header.tag,
+                            vr: header.vr,
+                        }))
+                    }
+                }
             } else {
                 // a plain element header was read, so a value is expected
                 let value = match self.read_value(&header) {
                     Ok(v) => v,
+                    Err(Error::ReadValue { source, .. })
+                        if self.options.issues.is_some() && is_uninterpretable(&source) =>
+                    {
+                        self.recovered(
+                            Some(header.tag),
+                            self.header_offset,
+                            format!("{}, value read as empty", source),
+                        );
+                        PrimitiveValue::Empty
+                    }
                     Err(e) => {
                         self.hard_break = true;
                         self.last_header = None;
                         return Some(Err(e));
                     }
                 };
+                self.inspect_value(&header, &value);
 
                 self.last_header = None;
 
@@ -408,14 +1299,32 @@ where
             }
         } else {
             // a data element header or item delimiter is expected
-            match self.parser.decode_header() {
+            let offset = self.parser.position();
+            let header = self
+                .parser
+                .decode_header_with_unknown_vr()
+                .map(|(header, unknown_vr)| {
+                    self.inspect_header(&header, unknown_vr, offset);
+                    header
+                });
+            if let Ok(header) = &header {
+                if let Err(e) = self.check_tag_order(header.tag) {
+                    self.hard_break = true;
+                    return Some(Err(e));
+                }
+            }
+            match header {
                 Ok(DataElementHeader {
                     tag,
                     vr: VR::SQ,
                     len,
                 }) => {
+                    if let Err(e) = self.check_depth(tag) {
+                        self.hard_break = true;
+                        return Some(Err(e));
+                    }
                     self.in_sequence = true;
-                    self.push_sequence_token(SeqTokenType::Sequence, len, false);
+                    self.push_sequence_token(tag, len, false);
 
                     // sequences can end right after they start
                     if len == Length(0) {
@@ -427,26 +1336,43 @@ where
                 Ok(DataElementHeader {
                     tag: Tag(0xFFFE, 0xE00D),
                     ..
-                }) if self.seq_delimiters.is_empty() => {
-                    // ignore delimiter, we are not in a sequence
-                    tracing::warn!(
-                        "Item delimitation item outside of a sequence in position {}",
-                        self.parser.position()
-                    );
-                    // return a new token by calling the method again
-                    self.next()
-                }
-                Ok(DataElementHeader {
-                    tag: Tag(0xFFFE, 0xE00D),
-                    ..
-                }) => {
+                }) if matches!(
+                    self.seq_delimiters.last(),
+                    Some(SeqToken {
+                        typ: SeqTokenType::Item,
+                        ..
+                    })
+                ) =>
+                {
+                    if let Err(e) = self.skip_item_remainder() {
+                        self.hard_break = true;
+                        return Some(Err(e));
+                    }
                     self.in_sequence = true;
                     // pop item delimiter
-                    self.seq_delimiters.pop();
+                    self.pop_sequence_token();
                     // sequences can end after this token
                     self.delimiter_check_pending = true;
                     Some(Ok(DataToken::ItemEnd))
                 }
+                Ok(DataElementHeader {
+                    tag: tag @ Tag(0xFFFE, _),
+                    ..
+                }) => {
+                    // items and delimiters cannot appear here,
+                    // and neither can data elements in group FFFE
+                    match self.options.stray_items {
+                        StrayItemStrategy::Skip => {
+                            self.warn(WarningKind::StrayItem, tag, offset);
+                            // return a new token by calling the method again
+                            self.read_token()
+                        }
+                        StrayItemStrategy::Fail => {
+                            self.hard_break = true;
+                            Some(UnexpectedItemTagSnafu { tag }.fail())
+                        }
+                    }
+                }
                 Ok(header) if header.is_encapsulated_pixeldata() => {
                     // encapsulated pixel data conditions:
                     // expect a sequence of pixel data fragments
@@ -459,14 +1385,48 @@ where
                     // treat other undefined length elements
                     // as data set sequences,
                     // discarding the VR in the process
+                    let DataElementHeader { tag, len, .. } = header;
+                    if let Err(e) = self.check_depth(tag) {
+                        self.hard_break = true;
+                        return Some(Err(e));
+                    }
                     self.in_sequence = true;
 
-                    let DataElementHeader { tag, len, .. } = header;
-                    self.push_sequence_token(SeqTokenType::Sequence, len, false);
+                    self.push_sequence_token(tag, len, false);
 
                     Some(Ok(DataToken::SequenceStart { tag, len }))
                 }
-                Ok(header) => {
+                Ok(mut header) => {
+                    match self.check_value_length(&mut header) {
+                        Ok(()) => {}
+                        Err(Error::ValueTooLong { len, max, .. })
+                            if self.options.issues.is_some() =>
+                        {
+                            if let Err(e) = self.parser.skip_bytes(len).context(ReadValueSnafu {
+                                len,
+                                tag: header.tag,
+                                vr: header.vr,
+                            }) {
+                                self.hard_break = true;
+                                return Some(Err(e));
+                            }
+                            self.recovered(
+                                Some(header.tag),
+                                offset,
+                                format!(
+                                    "value length {} exceeds the maximum of {}, element skipped",
+                                    len, max
+                                ),
+                            );
+                            // sequences can end after the skipped element
+                            self.delimiter_check_pending = true;
+                            return self.read_token();
+                        }
+                        Err(e) => {
+                            self.hard_break = true;
+                            return Some(Err(e));
+                        }
+                    }
                     // save it for the next step
                     self.last_header = Some(header);
                     Some(Ok(DataToken::ElementHeader(header)))
@@ -481,6 +1441,7 @@ where
                     // This approach is unlikely to consume trailing bytes,
                     // but may ignore the current depth of the data set tree.
                     self.hard_break = true;
+                    self.end_group(None, offset);
                     None
                 }
                 Err(e) => {
@@ -515,13 +1476,159 @@ where
         Ok(self.peek.as_ref())
     }
 
+    /// Determine how many bytes from the current position of the source
+    /// the next token is going to read,
+    /// given the bytes already available
+    /// and a function decoding a data element header
+    /// at the start of the given bytes
+    /// (returning `None` if there are not enough bytes for it).
+    ///
+    /// The number returned may exceed the bytes available,
+    /// in which case the caller should fetch more data before reading.
+    /// This allows the reader to run over data which arrives in chunks.
+    #[cfg(feature = "async")]
+    pub(crate) fn bytes_needed<F>(&self, available: &[u8], decode_header: F) -> u64
+    where
+        F: Fn(&[u8]) -> Option<(DataElementHeader, usize)>,
+    {
+        if self.hard_break || self.peek.is_some() {
+            return 0;
+        }
+        let position = self.parser.position();
+        if self.delimiter_check_pending {
+            if let Some(sd) = self.seq_delimiters.last() {
+                let end = sd
+                    .len
+                    .get()
+                    .and_then(|len| sd.base_offset.checked_add(u64::from(len)));
+                if matches!(end, Some(end) if end <= position) {
+                    // the sequence or item ends here
+                    return 0;
+                }
+            }
+        }
+
+        if self.in_sequence {
+            // item header or sequence delimiter
+            return 8;
+        }
+        if let Some(SeqToken {
+            typ: SeqTokenType::Item,
+            pixel_data: true,
+            len,
+            ..
+        }) = self.seq_delimiters.last()
+        {
+            // fragment or offset table
+            return match len.get() {
+                Some(len)
+                    if self
+                        .check_max_value_length(Tag(0xFFFE, 0xE000), len)
+                        .is_ok() =>
+                {
+                    if self.offset_table_next {
+                        u64::from(len)
+                    } else {
+                        u64::from(self.next_read_len(len))
+                    }
+                }
+                _ => 0,
+            };
+        }
+        if let Some(header) = self.last_header {
+            if header.is_encapsulated_pixeldata() {
+                // the first item header of the pixel sequence
+                return 8;
+            }
+            return u64::from(header.len.get().map_or(0, |len| self.next_read_len(len)));
+        }
+
+        // a data element header,
+        // plus anything which the reader would skip after it
+        let mut needed = 0_usize;
+        loop {
+            let (header, header_len) = match available.get(needed..).and_then(&decode_header) {
+                Some(header) => header,
+                // at least one more byte is needed to tell
+                None => return available.len().max(needed) as u64 + 1,
+            };
+            needed += header_len;
+            match header {
+                DataElementHeader {
+                    tag: Tag(0xFFFE, 0xE00D),
+                    ..
+                } if matches!(
+                    self.seq_delimiters.last(),
+                    Some(SeqToken {
+                        typ: SeqTokenType::Item,
+                        ..
+                    })
+                ) =>
+                {
+                    // the remainder of the item is skipped
+                    let remainder = self.seq_delimiters.last().and_then(|item| {
+                        let end = item.base_offset + u64::from(item.len.get()?);
+                        end.checked_sub(position + needed as u64)
+                    });
+                    return needed as u64 + remainder.unwrap_or(0);
+                }
+                DataElementHeader {
+                    tag: Tag(0xFFFE, _),
+                    ..
+                } if self.options.stray_items == StrayItemStrategy::Skip => {
+                    // stray item header, skipped
+                }
+                DataElementHeader { len, vr, .. }
+                    if vr != VR::SQ
+                        && self.options.issues.is_some()
+                        && matches!(
+                            (len.get(), self.options.max_value_length),
+                            (Some(len), Some(max)) if len > max
+                        ) =>
+                {
+                    // value too long, skipped
+                    needed += len.0 as usize;
+                }
+                _ => return needed as u64,
+            }
+        }
+    }
+
     fn update_seq_delimiters(&mut self) -> Result<Option<DataToken>> {
         if let Some(sd) = self.seq_delimiters.last() {
             if let Some(len) = sd.len.get() {
-                let end_of_sequence = sd.base_offset + len as u64;
+                let end_of_sequence =
+                    sd.base_offset
+                        .checked_add(u64::from(len))
+                        .context(LengthOverflowSnafu {
+                            tag: sd.tag,
+                            len,
+                            offset: sd.base_offset,
+                        })?;
                 let bytes_read = self.parser.position();
                 match end_of_sequence.cmp(&bytes_read) {
-                    Ordering::Equal => {
+                    Ordering::Less if self.options.issues.is_none() => {
+                        return InconsistentSequenceEndSnafu {
+                            end_of_sequence,
+                            bytes_read,
+                        }
+                        .fail();
+                    }
+                    Ordering::Equal | Ordering::Less => {
+                        if bytes_read > end_of_sequence {
+                            let what = match sd.typ {
+                                SeqTokenType::Sequence => "sequence",
+                                SeqTokenType::Item => "item",
+                            };
+                            self.recovered(
+                                None,
+                                bytes_read,
+                                format!(
+                                    "{} runs past its declared end at byte offset {}, closed here",
+                                    what, end_of_sequence
+                                ),
+                            );
+                        }
                         // end of delimiter, as indicated by the element's length
                         let token;
                         match sd.typ {
@@ -534,32 +1641,450 @@ where
                                 token = DataToken::ItemEnd;
                             }
                         }
-                        self.seq_delimiters.pop();
+                        self.pop_sequence_token();
                         return Ok(Some(token));
                     }
-                    Ordering::Less => {
-                        return InconsistentSequenceEndSnafu {
-                            end_of_sequence,
-                            bytes_read,
-                        }
-                        .fail();
-                    }
                     Ordering::Greater => {} // continue normally
                 }
             }
         }
-        self.delimiter_check_pending = false;
-        Ok(None)
+        self.delimiter_check_pending = false;
+        Ok(None)
+    }
+
+    /// Turn an error caused by the source ending
+    /// in the middle of a header or value
+    /// into a [`PrematureEnd`](Error::PrematureEnd) error.
+    fn check_premature_end(&self, e: Error) -> Error {
+        use dicom_encoding::decode::Error::TruncatedHeader;
+        match e {
+            Error::ReadValue {
+                tag,
+                source:
+                    DecoderError::TruncatedValue {
+                        position,
+                        expected,
+                        got,
+                        ..
+                    },
+                ..
+            } => PrematureEndSnafu {
+                tag: Some(tag),
+                expected,
+                got,
+                offset: position,
+            }
+            .build(),
+            Error::ReadItemValue {
+                source:
+                    DecoderError::TruncatedValue {
+                        position,
+                        expected,
+                        got,
+                        ..
+                    },
+                ..
+            }
+            | Error::SkipItemRemainder {
+                source:
+                    DecoderError::TruncatedValue {
+                        position,
+                        expected,
+                        got,
+                        ..
+                    },
+                ..
+            } => PrematureEndSnafu {
+                // fragments belong to the pixel data element,
+                // items to their sequence
+                tag: self
+                    .seq_delimiters
+                    .iter()
+                    .rev()
+                    .find(|token| token.typ == SeqTokenType::Sequence)
+                    .map(|token| token.tag),
+                expected,
+                got,
+                offset: position,
+            }
+            .build(),
+            Error::ReadHeader {
+                source:
+                    DecoderError::DecodeElementHeader {
+                        position,
+                        source: TruncatedHeader { expected, got, .. },
+                    },
+            }
+            | Error::ReadItemHeader {
+                source:
+                    DecoderError::DecodeItemHeader {
+                        position,
+                        source: TruncatedHeader { expected, got, .. },
+                    },
+            } => PrematureEndSnafu {
+                tag: None,
+                expected: expected as u64,
+                got: got as u64,
+                offset: position,
+            }
+            .build(),
+            e => e,
+        }
+    }
+
+    #[inline]
+    fn push_sequence_token(&mut self, tag: Tag, len: Length, pixel_data: bool) {
+        self.seq_delimiters.push(SeqToken {
+            typ: SeqTokenType::Sequence,
+            pixel_data,
+            len,
+            base_offset: self.parser.position(),
+            tag,
+            index: 0,
+            last_tag: None,
+        })
+    }
+
+    #[inline]
+    fn push_item_token(&mut self, len: Length, pixel_data: bool) {
+        if !pixel_data {
+            // data set items have their own character set scope
+            self.parser.begin_item();
+        }
+        let sequence = self
+            .seq_delimiters
+            .last_mut()
+            .expect("item header should be read only inside an existing sequence");
+        let (tag, index) = (sequence.tag, sequence.index);
+        sequence.index += 1;
+        self.seq_delimiters.push(SeqToken {
+            typ: SeqTokenType::Item,
+            pixel_data,
+            len,
+            base_offset: self.parser.position(),
+            tag,
+            index,
+            last_tag: self.last_tag.take(),
+        })
+    }
+
+    /// Apply the odd length strategy and the maximum value length
+    /// to the header of a primitive data element.
+    fn check_value_length(&self, header: &mut DataElementHeader) -> Result<()> {
+        let len = match header.len.get() {
+            Some(len) => len,
+            None => return Ok(()),
+        };
+        if len % 2 == 1 {
+            match self.options.odd_length {
+                OddLengthStrategy::Accept => {
+                    self.warn(
+                        WarningKind::OddLength { len },
+                        header.tag,
+                        self.header_offset,
+                    );
+                }
+                OddLengthStrategy::NextEven => {
+                    self.warn(
+                        WarningKind::OddLength { len },
+                        header.tag,
+                        self.header_offset,
+                    );
+                    header.len = Length(len + 1);
+                }
+                OddLengthStrategy::Fail => {
+                    return OddLengthSnafu {
+                        tag: header.tag,
+                        vr: header.vr,
+                        len,
+                    }
+                    .fail()
+                }
+            }
+        }
+        self.check_max_value_length(header.tag, header.len.0)
+    }
+
+    fn check_max_value_length(&self, tag: Tag, len: u32) -> Result<()> {
+        match self.options.max_value_length {
+            Some(max) if len > max => ValueTooLongSnafu { tag, len, max }.fail(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Apply the tag order strategy to the tag of a header just read,
+    /// keeping track of the last data element in the current data set.
+    fn check_tag_order(&mut self, tag: Tag) -> Result<()> {
+        // items and delimiters are not data elements
+        if tag.group() == 0xFFFE {
+            return Ok(());
+        }
+        if let Some(previous) = self.last_tag.replace(tag) {
+            if tag < previous {
+                match self.options.tag_order {
+                    TagOrderStrategy::Accept => {}
+                    TagOrderStrategy::Warn => {
+                        self.warn(
+                            WarningKind::TagOutOfOrder { previous },
+                            tag,
+                            self.header_offset,
+                        );
+                    }
+                    TagOrderStrategy::Fail => {
+                        return TagOutOfOrderSnafu { tag, previous }.fail();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a new sequence would not exceed the maximum nesting depth.
+    fn check_depth(&self, tag: Tag) -> Result<()> {
+        let max_depth = match self.options.max_depth {
+            Some(max_depth) => max_depth,
+            None => return Ok(()),
+        };
+        let depth = self
+            .seq_delimiters
+            .iter()
+            .filter(|token| token.typ == SeqTokenType::Sequence)
+            .count();
+        if depth >= max_depth as usize {
+            NestingTooDeepSnafu { tag, max_depth }.fail()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Describe the current location in the data set,
+    /// for the purpose of error reporting.
+    fn error_context(&self, tag: Option<Tag>) -> ErrorContext {
+        let mut path: Vec<PathSegment> = Vec::new();
+        for token in &self.seq_delimiters {
+            match token.typ {
+                SeqTokenType::Sequence => path.push(PathSegment {
+                    sequence: token.tag,
+                    item: None,
+                }),
+                SeqTokenType::Item => {
+                    if let Some(segment) = path.last_mut() {
+                        segment.item = Some(token.index);
+                    }
+                }
+            }
+        }
+        ErrorContext {
+            tag,
+            path,
+            offset: self.parser.position(),
+        }
+    }
+
+    #[inline]
+    fn pop_sequence_token(&mut self) {
+        if let Some(SeqToken {
+            typ: SeqTokenType::Item,
+            pixel_data,
+            last_tag,
+            ..
+        }) = self.seq_delimiters.pop()
+        {
+            if !pixel_data {
+                self.parser.end_item();
+            }
+            // back to the enclosing data set
+            self.last_tag = last_tag;
+        }
+        // a group cannot outlive its data set
+        if matches!(self.group_length, Some(gl) if gl.depth > self.seq_delimiters.len()) {
+            self.group_length = None;
+        }
+    }
+
+    /// Report a recoverable anomaly
+    /// to the log and to the warning handler.
+    fn warn(&self, kind: WarningKind, tag: Tag, offset: u64) {
+        let warning = ReadWarning { kind, tag, offset };
+        tracing::warn!("{}", warning);
+        if let Some(handler) = &self.options.on_warning {
+            handler.call(&warning);
+        }
+        if let Some(issues) = &self.options.issues {
+            issues.push(self.issue(
+                Severity::Warning,
+                Some(tag),
+                offset,
+                warning.kind.to_string(),
+            ));
+        }
+    }
+
+    /// Report an error which the reader recovered from
+    /// to the log and to the issue collector.
+    fn recovered(&self, tag: Option<Tag>, offset: u64, message: String) {
+        let issue = self.issue(Severity::Error, tag, offset, message);
+        tracing::warn!("{}", issue);
+        if let Some(issues) = &self.options.issues {
+            issues.push(issue);
+        }
+    }
+
+    /// Describe a problem found at the given offset
+    /// within the current location in the data set.
+    fn issue(
+        &self,
+        severity: Severity,
+        tag: Option<Tag>,
+        offset: u64,
+        message: String,
+    ) -> ParseIssue {
+        let mut context = self.error_context(tag);
+        context.offset = offset;
+        ParseIssue {
+            severity,
+            context,
+            message,
+        }
+    }
+
+    /// Look for anomalies in a data element header just decoded,
+    /// which started at the given offset.
+    fn inspect_header(
+        &mut self,
+        header: &DataElementHeader,
+        unknown_vr: Option<[u8; 2]>,
+        offset: u64,
+    ) {
+        self.header_offset = offset;
+        if let Some(bytes) = unknown_vr {
+            self.warn(WarningKind::UnknownVr { bytes }, header.tag, offset);
+        }
+        // item delimiters end the group, other item headers are stray
+        if header.tag.group() != 0xFFFE || header.tag == Tag(0xFFFE, 0xE00D) {
+            self.end_group(Some(header.tag), offset);
+        }
+    }
+
+    /// Look for anomalies in a primitive value just read,
+    /// and keep track of group lengths.
+    fn inspect_value(&mut self, header: &DataElementHeader, value: &PrimitiveValue) {
+        match (header.tag, value) {
+            (Tag(0x0008, 0x0005), PrimitiveValue::Strs(names)) => {
+                // same fallback as in the stateful decoder
+                SpecificCharacterSet::from_values_with(names.iter(), |name| {
+                    self.warn(
+                        WarningKind::CharsetFallback {
+                            name: name.to_string(),
+                        },
+                        header.tag,
+                        self.header_offset,
+                    );
+                });
+            }
+            (Tag(group, 0x0000), PrimitiveValue::U32(len)) if group != 0xFFFE && len.len() == 1 => {
+                self.group_length = Some(GroupLength {
+                    tag: header.tag,
+                    len: len[0],
+                    start: self.parser.position(),
+                    depth: self.seq_delimiters.len(),
+                });
+            }
+            _ => {}
+        }
     }
 
-    #[inline]
-    fn push_sequence_token(&mut self, typ: SeqTokenType, len: Length, pixel_data: bool) {
-        self.seq_delimiters.push(SeqToken {
-            typ,
-            pixel_data,
-            len,
-            base_offset: self.parser.position(),
-        })
+    /// Check the group length in effect against the actual group length
+    /// if the group ends at the given offset,
+    /// either because an element of another group follows
+    /// or because the data set ends (`next` is `None`).
+    fn end_group(&mut self, next: Option<Tag>, offset: u64) {
+        let gl = match self.group_length {
+            Some(gl) if gl.depth == self.seq_delimiters.len() => gl,
+            _ => return,
+        };
+        if matches!(next, Some(tag) if tag.group() == gl.tag.group()) {
+            return;
+        }
+        self.group_length = None;
+        let found = offset.saturating_sub(gl.start);
+        if found != u64::from(gl.len) {
+            self.warn(
+                WarningKind::GroupLengthMismatch {
+                    expected: gl.len,
+                    found,
+                },
+                gl.tag,
+                offset,
+            );
+        }
+    }
+
+    /// Skip the bytes left in the current item of defined length
+    /// when it is delimited before reaching its declared end.
+    fn skip_item_remainder(&mut self) -> Result<()> {
+        let (tag, end) = match self.seq_delimiters.last() {
+            Some(SeqToken {
+                typ: SeqTokenType::Item,
+                len,
+                base_offset,
+                tag,
+                ..
+            }) => match len.get() {
+                Some(len) => (*tag, base_offset.saturating_add(u64::from(len))),
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        let offset = self.parser.position();
+        if end > offset {
+            // cannot be longer than the item itself
+            let skipped = (end - offset) as u32;
+            self.warn(WarningKind::ItemUnderRead { skipped }, tag, offset);
+            self.parser
+                .skip_bytes(skipped)
+                .context(SkipItemRemainderSnafu { len: skipped })?;
+        }
+        Ok(())
+    }
+
+    /// The number of bytes left to read of a value of the given length
+    /// if it is to be read in chunks,
+    /// or `None` if it is to be read whole.
+    fn chunk_remaining(&self, len: u32) -> Option<u32> {
+        match (self.chunk_remaining, self.options.value_chunk_size) {
+            (Some(remaining), _) => Some(remaining),
+            (None, Some(chunk_size)) if len > chunk_size => Some(len),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes of a value of the given length
+    /// to be read by the next token.
+    #[cfg(feature = "async")]
+    fn next_read_len(&self, len: u32) -> u32 {
+        match (self.chunk_remaining(len), self.options.value_chunk_size) {
+            (Some(remaining), Some(chunk_size)) => remaining.min(chunk_size.max(1)),
+            _ => len,
+        }
+    }
+
+    /// Read the next chunk of the value being read in chunks,
+    /// given the number of bytes left.
+    fn read_chunk(&mut self, remaining: u32) -> std::result::Result<DataToken, DecoderError> {
+        let chunk_size = self.options.value_chunk_size.unwrap_or(remaining).max(1);
+        let len = remaining.min(chunk_size);
+        let mut chunk = Vec::new();
+        if let Err(e) = self.parser.read_to_vec(len, &mut chunk) {
+            self.chunk_remaining = None;
+            return Err(e);
+        }
+        self.chunk_remaining = Some(remaining - len).filter(|&remaining| remaining > 0);
+        if self.chunk_remaining.is_none() {
+            // sequences and items can end after the last chunk
+            self.delimiter_check_pending = true;
+        }
+        Ok(DataToken::ValueChunk(chunk))
     }
 
     fn read_value(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
@@ -571,13 +2096,33 @@ where
         .context(ReadValueSnafu {
             len: header.len.0,
             tag: header.tag,
+            vr: header.vr,
         })
     }
 }
 
+/// Whether the decoder consumed a whole value
+/// but could not interpret its contents.
+fn is_uninterpretable(e: &DecoderError) -> bool {
+    matches!(
+        e,
+        DecoderError::DecodeText { .. }
+            | DecoderError::DeserializeValue { .. }
+            | DecoderError::ReadInt { .. }
+            | DecoderError::ReadFloat { .. }
+            | DecoderError::InvalidDateValue { .. }
+            | DecoderError::InvalidTimeValue { .. }
+            | DecoderError::InvalidDateTimeValue { .. }
+    )
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{DataSetReader, DataToken, StatefulDecode};
+    use super::{
+        DataSetReader, DataToken, Error, IssueCollector, OddLengthStrategy, PathSegment,
+        ReadOptions, Severity, StatefulDecode, StrayItemStrategy, TagOrderStrategy,
+        ValueReadStrategy, WarningCollector, WarningKind,
+    };
     use crate::stateful::decode::StatefulDecoder;
     use dicom_core::header::{DataElementHeader, Length};
     use dicom_core::value::PrimitiveValue;
@@ -587,6 +2132,8 @@ mod tests {
         explicit_le::ExplicitVRLittleEndianDecoder, implicit_le::ImplicitVRLittleEndianDecoder,
     };
     use dicom_encoding::text::SpecificCharacterSet;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    use std::sync::Arc;
 
     fn validate_dataset_reader_implicit_vr<I>(data: &[u8], ground_truth: I)
     where
@@ -1224,4 +2771,928 @@ mod tests {
         // finished reading, peek should return None
         assert!(iter.peek().unwrap().is_none());
     }
+
+    #[test]
+    fn read_character_set_of_items() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (0008,0005) SpecificCharacterSet
+            0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0A, 0x00,
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'0', b'0',
+            // (0010,0010) PatientName: "Müller"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x06, 0x00,
+            b'M', 0xFC, b'l', b'l', b'e', b'r',
+            // (0010,1002) OtherPatientIDsSequence
+            0x10, 0x00, 0x02, 0x10, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // item with undefined length
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // (0008,0005) SpecificCharacterSet
+            0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0A, 0x00,
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'9', b'2',
+            // (0010,0010) PatientName: "Gößmann"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x0A, 0x00,
+            b'G', 0xC3, 0xB6, 0xC3, 0x9F, b'm', b'a', b'n', b'n', b' ',
+            // item delimiter
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // item with defined length, inheriting the character set
+            0xFE, 0xFF, 0x00, 0xE0, 0x0E, 0x00, 0x00, 0x00,
+            // (0010,0010) PatientName: "Bäcker"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x06, 0x00,
+            b'B', 0xE4, b'c', b'k', b'e', b'r',
+            // sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // (0010,2180) Occupation: "Köhler"
+            0x10, 0x00, 0x80, 0x21, b'S', b'H', 0x06, 0x00,
+            b'K', 0xF6, b'h', b'l', b'e', b'r',
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let values: Vec<_> = DataSetReader::new(parser, Default::default())
+            .filter_map(|token| match token.unwrap() {
+                DataToken::PrimitiveValue(value) => Some(value.to_str().trim_end().to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            values,
+            [
+                "ISO_IR 100",
+                "Müller",
+                "ISO_IR 192",
+                "Gößmann",
+                "Bäcker",
+                "Köhler"
+            ]
+        );
+    }
+
+    #[test]
+    fn read_error_with_nested_context() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (0040,A730) ContentSequence
+            0x40, 0x00, 0x30, 0xA7, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // item #0 with undefined length
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // (0008,0100) CodeValue: "ABC "
+            0x08, 0x00, 0x00, 0x01, b'S', b'H', 0x04, 0x00,
+            b'A', b'B', b'C', b' ',
+            // item delimiter
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // item #1 with undefined length
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // (0040,A043) ConceptNameCodeSequence
+            0x40, 0x00, 0x43, 0xA0, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // item #0 with undefined length
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // (0008,0100) CodeValue: 16 bytes announced, 2 available
+            0x08, 0x00, 0x00, 0x01, b'S', b'H', 0x10, 0x00,
+            b'1', b'2',
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let err = DataSetReader::new(parser, Default::default())
+            .find_map(|token| token.err())
+            .expect("reading should fail");
+
+        let context = err.context().expect("error should have context");
+        assert_eq!(context.tag(), Some(Tag(0x0008, 0x0100)));
+        assert_eq!(
+            context.path(),
+            &[
+                PathSegment {
+                    sequence: Tag(0x0040, 0xA730),
+                    item: Some(1),
+                },
+                PathSegment {
+                    sequence: Tag(0x0040, 0xA043),
+                    item: Some(0),
+                },
+            ]
+        );
+        assert_eq!(context.offset(), 76);
+        assert!(matches!(
+            err.without_context(),
+            Error::PrematureEnd {
+                expected: 16,
+                got: 2,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Could not read data set at (0040,A730)[1].(0040,A043)[0].(0008,0100), byte offset 76"
+        );
+    }
+
+    #[test]
+    fn read_truncated_pixel_data() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (0028,0010) Rows: 2
+            0x28, 0x00, 0x10, 0x00, b'U', b'S', 0x02, 0x00, 0x02, 0x00,
+            // (0028,0011) Columns: 4
+            0x28, 0x00, 0x11, 0x00, b'U', b'S', 0x02, 0x00, 0x04, 0x00,
+            // (7FE0,0010) PixelData: 16 bytes announced, 6 available
+            0xE0, 0x7F, 0x10, 0x00, b'O', b'W', 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00,
+            0x01, 0x00, 0x02, 0x00, 0x03, 0x00,
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let mut reader = DataSetReader::new(parser, Default::default());
+        let tokens: Vec<_> = reader.by_ref().take(5).collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            tokens.last(),
+            Some(&DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x7FE0, 0x0010),
+                vr: VR::OW,
+                len: Length(16),
+            }))
+        );
+        let err = reader
+            .next()
+            .expect("should have a result")
+            .expect_err("reading should fail");
+        assert!(
+            matches!(
+                err.without_context(),
+                Error::PrematureEnd {
+                    tag: Some(Tag(0x7FE0, 0x0010)),
+                    expected: 16,
+                    got: 6,
+                    offset: 32,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+        assert!(reader.next().is_none());
+
+        // truncated right after an element: a clean end
+        let tokens = read_tokens_with(&DATA[..20], ReadOptions::new()).unwrap();
+        assert_eq!(tokens.len(), 4);
+    }
+
+    fn read_tokens_with(data: &[u8], options: ReadOptions) -> Result<Vec<DataToken>, Error> {
+        let mut cursor = data;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            options.charset.clone(),
+        );
+        DataSetReader::new(parser, options).collect()
+    }
+
+    #[test]
+    fn read_sequence_length_overflow() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (0008,1140) ReferencedImageSequence, length 0xFFFF_FFF0
+            0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00,
+            0xF0, 0xFF, 0xFF, 0xFF,
+            // empty item
+            0xFE, 0xFF, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        // the reader's position is about to overflow
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new_with_position(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+            u64::MAX - 20,
+        );
+        let mut reader = DataSetReader::new(parser, Default::default());
+        let tokens: Vec<_> = reader.by_ref().take(3).collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::SequenceStart {
+                    tag: Tag(0x0008, 0x1140),
+                    len: Length(0xFFFF_FFF0),
+                },
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+            ]
+        );
+        let err = reader
+            .next()
+            .expect("should have a result")
+            .expect_err("reading should fail");
+        assert!(
+            matches!(
+                err.without_context(),
+                Error::LengthOverflow {
+                    tag: Tag(0x0008, 0x1140),
+                    len: 0xFFFF_FFF0,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn read_with_stray_items() {
+        fn data_with(stray: [u8; 4]) -> Vec<u8> {
+            #[rustfmt::skip]
+            let data = [
+                // (0008,0060) Modality: "OT"
+                &[0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'O', b'T'][..],
+                // the stray header, with a zero length
+                &stray[..],
+                &[0x00, 0x00, 0x00, 0x00],
+                // (0010,0040) PatientSex: "O "
+                &[0x10, 0x00, 0x40, 0x00, b'C', b'S', 0x02, 0x00, b'O', b' '],
+            ];
+            data.concat()
+        }
+
+        let expected = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0060),
+                VR::CS,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::Str("OT".into())),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0040),
+                VR::CS,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::Str("O ".into())),
+        ];
+
+        // a stray item header, then a stray sequence delimiter
+        for (stray, tag) in [
+            ([0xFE, 0xFF, 0x00, 0xE0], Tag(0xFFFE, 0xE000)),
+            ([0xFE, 0xFF, 0xDD, 0xE0], Tag(0xFFFE, 0xE0DD)),
+        ] {
+            let data = data_with(stray);
+
+            // skipped by default
+            let tokens = read_tokens_with(&data, ReadOptions::new()).unwrap();
+            assert_eq!(tokens, expected);
+
+            // rejected in strict mode
+            let err = read_tokens_with(
+                &data,
+                ReadOptions::new().stray_items(StrayItemStrategy::Fail),
+            )
+            .unwrap_err();
+            assert!(
+                matches!(err.without_context(), Error::UnexpectedItemTag { tag: t, .. } if *t == tag),
+                "{:?}",
+                err
+            );
+            assert_eq!(err.context().map(|c| c.offset()), Some(18));
+        }
+    }
+
+    #[test]
+    fn read_with_warnings() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // 0: (0008,0000) group length: 100, but the group has 29 bytes
+            0x08, 0x00, 0x00, 0x00, b'U', b'L', 0x04, 0x00,
+            0x64, 0x00, 0x00, 0x00,
+            // 12: (0008,0005) SpecificCharacterSet: "ISO_IR 999"
+            0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0A, 0x00,
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'9', b'9', b'9',
+            // 30: (0008,0070) Manufacturer: "ABC", with an odd length
+            0x08, 0x00, 0x70, 0x00, b'L', b'O', 0x03, 0x00,
+            b'A', b'B', b'C',
+            // 41: (0009,0010) with an unknown VR "ZZ"
+            0x09, 0x00, 0x10, 0x00, b'Z', b'Z', 0x00, 0x00,
+            0x02, 0x00, 0x00, 0x00,
+            b'A', b'B',
+            // 55: stray sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // 63: (0040,A730) ContentSequence, undefined length
+            0x40, 0x00, 0x30, 0xA7, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // 75: item of 24 bytes
+            0xFE, 0xFF, 0x00, 0xE0, 0x18, 0x00, 0x00, 0x00,
+            // 83: (0040,A040) ValueType: "TEXT"
+            0x40, 0x00, 0x40, 0xA0, b'C', b'S', 0x04, 0x00,
+            b'T', b'E', b'X', b'T',
+            // 95: item delimiter before the end of the item
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // 103: remaining item bytes
+            0x00, 0x00, 0x00, 0x00,
+            // 107: sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let collector = WarningCollector::new();
+        let tokens =
+            read_tokens_with(DATA, ReadOptions::new().collect_warnings(&collector)).unwrap();
+        assert_eq!(tokens.len(), 14);
+        assert_eq!(
+            &tokens[9..],
+            &[
+                DataToken::ItemStart { len: Length(24) },
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0040, 0xA040),
+                    VR::CS,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from("TEXT")),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+            ]
+        );
+
+        let warnings: Vec<_> = collector
+            .take()
+            .into_iter()
+            .map(|w| (w.kind().clone(), w.tag(), w.offset()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (
+                    WarningKind::CharsetFallback {
+                        name: "ISO_IR 999".to_string()
+                    },
+                    Tag(0x0008, 0x0005),
+                    12
+                ),
+                (WarningKind::OddLength { len: 3 }, Tag(0x0008, 0x0070), 30),
+                (
+                    WarningKind::UnknownVr { bytes: *b"ZZ" },
+                    Tag(0x0009, 0x0010),
+                    41
+                ),
+                (
+                    WarningKind::GroupLengthMismatch {
+                        expected: 100,
+                        found: 29
+                    },
+                    Tag(0x0008, 0x0000),
+                    41
+                ),
+                (WarningKind::StrayItem, Tag(0xFFFE, 0xE0DD), 55),
+                (
+                    WarningKind::ItemUnderRead { skipped: 4 },
+                    Tag(0x0040, 0xA730),
+                    103
+                ),
+            ]
+        );
+        assert!(collector.warnings().is_empty());
+
+        // callbacks receive the same warnings
+        let count = Arc::new(AtomicUsize::new(0));
+        let options = ReadOptions::new().on_warning({
+            let count = Arc::clone(&count);
+            move |_| {
+                count.fetch_add(1, AtomicOrdering::SeqCst);
+            }
+        });
+        read_tokens_with(DATA, options).unwrap();
+        assert_eq!(count.load(AtomicOrdering::SeqCst), 6);
+    }
+
+    #[test]
+    fn read_with_tag_order_strategy() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // 0: (0010,0020) PatientID: "ID"
+            0x10, 0x00, 0x20, 0x00, b'L', b'O', 0x02, 0x00,
+            b'I', b'D',
+            // 10: (0010,0010) PatientName: "DOE^", out of order
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x04, 0x00,
+            b'D', b'O', b'E', b'^',
+            // 22: (0040,A730) ContentSequence, undefined length
+            0x40, 0x00, 0x30, 0xA7, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // 34: item, undefined length
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // 42: (0008,0104) CodeMeaning: "AB",
+            // which may come before the sequence
+            0x08, 0x00, 0x04, 0x01, b'L', b'O', 0x02, 0x00,
+            b'A', b'B',
+            // 52: (0008,0100) CodeValue: "1234", out of order in the item
+            0x08, 0x00, 0x00, 0x01, b'S', b'H', 0x04, 0x00,
+            b'1', b'2', b'3', b'4',
+            // 64: item delimiter
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // 72: sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // 80: (0088,0130) StorageMediaFileSetID: "DISK"
+            0x88, 0x00, 0x30, 0x01, b'S', b'H', 0x04, 0x00,
+            b'D', b'I', b'S', b'K',
+        ];
+
+        // not checked by default
+        let collector = WarningCollector::new();
+        let tokens =
+            read_tokens_with(DATA, ReadOptions::new().collect_warnings(&collector)).unwrap();
+        assert_eq!(tokens.len(), 14);
+        assert!(collector.warnings().is_empty());
+
+        let options = ReadOptions::new()
+            .tag_order(TagOrderStrategy::Warn)
+            .collect_warnings(&collector);
+        assert_eq!(read_tokens_with(DATA, options).unwrap(), tokens);
+        let warnings: Vec<_> = collector
+            .take()
+            .into_iter()
+            .map(|w| (w.kind().clone(), w.tag(), w.offset()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (
+                    WarningKind::TagOutOfOrder {
+                        previous: Tag(0x0010, 0x0020)
+                    },
+                    Tag(0x0010, 0x0010),
+                    10
+                ),
+                (
+                    WarningKind::TagOutOfOrder {
+                        previous: Tag(0x0008, 0x0104)
+                    },
+                    Tag(0x0008, 0x0100),
+                    52
+                ),
+            ]
+        );
+
+        let err = read_tokens_with(DATA, ReadOptions::new().tag_order(TagOrderStrategy::Fail))
+            .unwrap_err();
+        assert!(
+            matches!(
+                err.without_context(),
+                Error::TagOutOfOrder { tag, previous, .. }
+                    if *tag == Tag(0x0010, 0x0010) && *previous == Tag(0x0010, 0x0020)
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn read_with_issues() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // 0: (0008,0020) StudyDate: "2023XX01", not a valid date
+            0x08, 0x00, 0x20, 0x00, b'D', b'A', 0x08, 0x00,
+            b'2', b'0', b'2', b'3', b'X', b'X', b'0', b'1',
+            // 16: (0009,1010) OB of 16 bytes, longer than the maximum of 8
+            0x09, 0x00, 0x10, 0x10, b'O', b'B', 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07,
+            0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+            // 44: (0040,A730) ContentSequence, undefined length
+            0x40, 0x00, 0x30, 0xA7, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // 56: item of 8 bytes, but with 12 bytes of content
+            0xFE, 0xFF, 0x00, 0xE0, 0x08, 0x00, 0x00, 0x00,
+            // 64: (0040,A040) ValueType: "TEXT"
+            0x40, 0x00, 0x40, 0xA0, b'C', b'S', 0x04, 0x00,
+            b'T', b'E', b'X', b'T',
+            // 76: sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // 84: (0010,0010) PatientName: "DOE^J "
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x06, 0x00,
+            b'D', b'O', b'E', b'^', b'J', b' ',
+        ];
+
+        let options = ReadOptions::new()
+            .value_read(ValueReadStrategy::Interpreted)
+            .max_value_length(8);
+
+        // each of the problems is an error by default
+        assert!(read_tokens_with(DATA, options.clone()).is_err());
+
+        let collector = IssueCollector::new();
+        let tokens = read_tokens_with(DATA, options.collect_issues(&collector)).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0008, 0x0020),
+                    VR::DA,
+                    Length(8),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Empty),
+                DataToken::SequenceStart {
+                    tag: Tag(0x0040, 0xA730),
+                    len: Length::UNDEFINED,
+                },
+                DataToken::ItemStart { len: Length(8) },
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0040, 0xA040),
+                    VR::CS,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from("TEXT")),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0010),
+                    VR::PN,
+                    Length(6),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from("DOE^J")),
+            ]
+        );
+
+        let issues = collector.take();
+        assert_eq!(issues.len(), 3);
+        let summary: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.severity(), issue.tag(), issue.path(), issue.offset()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Error, Some(Tag(0x0008, 0x0020)), &[][..], 0),
+                (Severity::Error, Some(Tag(0x0009, 0x1010)), &[][..], 16),
+                (
+                    Severity::Error,
+                    None,
+                    &[PathSegment {
+                        sequence: Tag(0x0040, 0xA730),
+                        item: Some(0),
+                    }][..],
+                    76
+                ),
+            ]
+        );
+        assert!(issues[1]
+            .message()
+            .contains("value length 16 exceeds the maximum of 8"));
+        assert_eq!(
+            issues[2].to_string(),
+            "error: item runs past its declared end at byte offset 72, closed here \
+             at (0040,A730)[0], byte offset 76"
+        );
+    }
+
+    #[test]
+    fn read_with_odd_length_strategy() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (0010,0020) PatientID: "ABC", odd length but padded
+            0x10, 0x00, 0x20, 0x00, b'L', b'O', 0x03, 0x00,
+            b'A', b'B', b'C', b' ',
+            // (0010,0040) PatientSex: "O "
+            0x10, 0x00, 0x40, 0x00, b'C', b'S', 0x02, 0x00,
+            b'O', b' ',
+        ];
+
+        // accepted as is: the padding byte is taken as the next header
+        assert!(read_tokens_with(DATA, ReadOptions::new()).is_err());
+
+        // read up to the next even length
+        let tokens = read_tokens_with(
+            DATA,
+            ReadOptions::new().odd_length(OddLengthStrategy::NextEven),
+        )
+        .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0020),
+                    VR::LO,
+                    Length(4)
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Str("ABC ".into())),
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0040),
+                    VR::CS,
+                    Length(2)
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Str("O ".into())),
+            ]
+        );
+
+        // rejected
+        let err = read_tokens_with(DATA, ReadOptions::new().odd_length(OddLengthStrategy::Fail))
+            .unwrap_err();
+        assert!(matches!(
+            err.without_context(),
+            Error::OddLength {
+                tag: Tag(0x0010, 0x0020),
+                len: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn read_with_max_value_length() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (7FE0,0010) PixelData, OW, 16 bytes
+            0xE0, 0x7F, 0x10, 0x00, b'O', b'W', 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00,
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+        ];
+
+        assert!(read_tokens_with(DATA, ReadOptions::new().max_value_length(16)).is_ok());
+
+        let err = read_tokens_with(DATA, ReadOptions::new().max_value_length(8)).unwrap_err();
+        assert!(matches!(
+            err.without_context(),
+            Error::ValueTooLong {
+                tag: Tag(0x7FE0, 0x0010),
+                len: 16,
+                max: 8,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn read_with_max_depth() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (0040,A730) ContentSequence
+            0x40, 0x00, 0x30, 0xA7, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // item
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // (0040,A730) ContentSequence
+            0x40, 0x00, 0x30, 0xA7, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // item delimiter
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        assert_eq!(
+            read_tokens_with(DATA, ReadOptions::new().max_depth(2))
+                .unwrap()
+                .len(),
+            6
+        );
+
+        let err = read_tokens_with(DATA, ReadOptions::new().max_depth(1)).unwrap_err();
+        assert!(matches!(
+            err.without_context(),
+            Error::NestingTooDeep { max_depth: 1, .. }
+        ));
+        assert_eq!(err.context().unwrap().offset(), 32);
+    }
+
+    #[test]
+    fn read_with_default_charset() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (0010,0010) PatientName: "Müller" in ISO 8859-1
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x06, 0x00,
+            b'M', 0xFC, b'l', b'l', b'e', b'r',
+        ];
+
+        let tokens = read_tokens_with(
+            DATA,
+            ReadOptions::new().charset(SpecificCharacterSet::ISO_IR_100),
+        )
+        .unwrap();
+        assert_eq!(
+            tokens[1],
+            DataToken::PrimitiveValue(PrimitiveValue::Str("Müller".into()))
+        );
+    }
+
+    /// Compute the FNV-1a hash of the given bytes incrementally,
+    /// starting from `hash`.
+    fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+        bytes.iter().fold(hash, |hash, &b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+    #[test]
+    fn read_native_value_in_chunks() {
+        const LEN: u32 = 3 * 1024 * 1024 + 2;
+        const CHUNK_SIZE: u32 = 64 * 1024;
+
+        let value: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+        // (0008,0060) CS "MR", (7FE0,0010) OW, (FFFA,FFFA) OB of 4 bytes
+        let mut data = vec![0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R'];
+        data.extend_from_slice(&[0xE0, 0x7F, 0x10, 0x00, b'O', b'W', 0x00, 0x00]);
+        data.extend_from_slice(&LEN.to_le_bytes());
+        data.extend_from_slice(&value);
+        data.extend_from_slice(&[0xFA, 0xFF, 0xFA, 0xFF, b'O', b'B', 0x00, 0x00]);
+        data.extend_from_slice(&[0x04, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04]);
+
+        let mut reader = DataSetReader::new(
+            StatefulDecoder::new(
+                &data[..],
+                ExplicitVRLittleEndianDecoder::default(),
+                LittleEndianBasicDecoder,
+                SpecificCharacterSet::default(),
+            ),
+            ReadOptions::new().chunk_values(CHUNK_SIZE),
+        );
+
+        // values up to the chunk size are read whole
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0060),
+                VR::CS,
+                Length(2)
+            ))
+        );
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            DataToken::PrimitiveValue(PrimitiveValue::from("MR"))
+        );
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x7FE0, 0x0010),
+                VR::OW,
+                Length(LEN)
+            ))
+        );
+
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut got = 0;
+        let mut chunks = 0;
+        while got < LEN as usize {
+            match reader.next().unwrap().unwrap() {
+                DataToken::ValueChunk(chunk) => {
+                    assert!(chunk.len() <= CHUNK_SIZE as usize);
+                    hash = fnv1a(hash, &chunk);
+                    got += chunk.len();
+                    chunks += 1;
+                }
+                token => panic!("unexpected token {:?}", token),
+            }
+        }
+        assert_eq!(got, LEN as usize);
+        assert_eq!(chunks, 49);
+        assert_eq!(hash, fnv1a(FNV_OFFSET_BASIS, &value));
+
+        let tokens: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0xFFFA, 0xFFFA),
+                    VR::OB,
+                    Length(4)
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from(vec![1_u8, 2, 3, 4])),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_fragments_in_chunks() {
+        const LEN: u32 = 200_000;
+        const CHUNK_SIZE: u32 = 64 * 1024;
+
+        let fragment: Vec<u8> = (0..LEN).map(|i| (i % 241) as u8).collect();
+        // (7FE0,0010) OB of undefined length, with an empty offset table,
+        // a large fragment and a small one
+        let mut data = vec![
+            0xE0, 0x7F, 0x10, 0x00, b'O', b'B', 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, //
+            0xFE, 0xFF, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00, //
+            0xFE, 0xFF, 0x00, 0xE0,
+        ];
+        data.extend_from_slice(&LEN.to_le_bytes());
+        data.extend_from_slice(&fragment);
+        data.extend_from_slice(&[
+            0xFE, 0xFF, 0x00, 0xE0, 0x02, 0x00, 0x00, 0x00, 0xFF, 0xD9, //
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ]);
+
+        let reader = DataSetReader::new(
+            StatefulDecoder::new(
+                &data[..],
+                ExplicitVRLittleEndianDecoder::default(),
+                LittleEndianBasicDecoder,
+                SpecificCharacterSet::default(),
+            ),
+            ReadOptions::new().chunk_values(CHUNK_SIZE),
+        );
+        let tokens: Vec<_> = reader.map(Result::unwrap).collect();
+
+        let chunks = &tokens[4..8];
+        let mut hash = FNV_OFFSET_BASIS;
+        for token in chunks {
+            match token {
+                DataToken::ValueChunk(chunk) => hash = fnv1a(hash, chunk),
+                token => panic!("unexpected token {:?}", token),
+            }
+        }
+        assert_eq!(hash, fnv1a(FNV_OFFSET_BASIS, &fragment));
+
+        assert_eq!(
+            tokens[..4],
+            [
+                DataToken::PixelSequenceStart,
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+                DataToken::ItemStart { len: Length(LEN) },
+            ]
+        );
+        assert_eq!(
+            tokens[4],
+            DataToken::ValueChunk(fragment[..CHUNK_SIZE as usize].to_vec())
+        );
+        assert_eq!(
+            tokens[8..],
+            [
+                DataToken::ItemEnd,
+                DataToken::ItemStart { len: Length(2) },
+                DataToken::ItemValue(vec![0xFF, 0xD9]),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+            ]
+        );
+    }
+
+    #[test]
+    fn read_values_in_chunks_in_items_of_defined_length() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            // (0008,1140) SQ, 26 bytes
+            0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00, 0x1A, 0x00, 0x00, 0x00,
+            // item, 18 bytes
+            0xFE, 0xFF, 0x00, 0xE0, 0x12, 0x00, 0x00, 0x00,
+            // (0009,1010) OB, 6 bytes
+            0x09, 0x00, 0x10, 0x10, b'O', b'B', 0x00, 0x00, 0x06, 0x00, 0x00, 0x00,
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+            // (0010,0010) PN "Doe"
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x04, 0x00, b'D', b'o', b'e', b' ',
+        ];
+
+        let reader = DataSetReader::new(
+            StatefulDecoder::new(
+                DATA,
+                ExplicitVRLittleEndianDecoder::default(),
+                LittleEndianBasicDecoder,
+                SpecificCharacterSet::default(),
+            ),
+            ReadOptions::new().chunk_values(4),
+        );
+        let tokens: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::SequenceStart {
+                    tag: Tag(0x0008, 0x1140),
+                    len: Length(26),
+                },
+                DataToken::ItemStart { len: Length(18) },
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0009, 0x1010),
+                    VR::OB,
+                    Length(6)
+                )),
+                DataToken::ValueChunk(vec![1, 2, 3, 4]),
+                DataToken::ValueChunk(vec![5, 6]),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0010),
+                    VR::PN,
+                    Length(4)
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from("Doe")),
+            ]
+        );
+    }
 }
@@ -6,15 +6,23 @@
 //! to a writer.
 //! In this process, the writer will also adapt values
 //! to the necessary DICOM encoding rules.
+//! The [`BatchWriter`] can be placed between the two
+//! to gather small data elements in fewer calls to the writer.
+use crate::dataset::read::WarningCollector;
 use crate::dataset::{DataToken, SeqTokenType};
+pub use crate::stateful::encode::OddLengthPolicy;
 use crate::stateful::encode::StatefulEncoder;
 use dicom_core::{DataElementHeader, Length, Tag, VR};
-use dicom_encoding::encode::EncodeTo;
-use dicom_encoding::text::SpecificCharacterSet;
+use dicom_encoding::encode::{write_all_vectored, EncodeTo};
+use dicom_encoding::text::{
+    length::LengthValidation, repertoire::RepertoireValidation, EncodeTextPolicy,
+    SpecificCharacterSet,
+};
 use dicom_encoding::transfer_syntax::DynEncoder;
 use dicom_encoding::TransferSyntax;
 use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
-use std::io::Write;
+use std::io::{self, IoSlice, Read, Write};
+use std::iter::Peekable;
 
 #[derive(Debug, Snafu)]
 #[non_exhaustive]
@@ -38,6 +46,14 @@ pub enum Error {
         token: DataToken,
         backtrace: Backtrace,
     },
+    /// A data element was given after one with a greater or equal tag
+    /// in the same data set
+    #[snafu(display("Data element tagged {} out of order after {}", tag, previous))]
+    TagOutOfOrder {
+        tag: Tag,
+        previous: Tag,
+        backtrace: Backtrace,
+    },
     #[snafu(display("Could not write element header tagged {}", tag))]
     WriteHeader {
         tag: Tag,
@@ -67,10 +83,167 @@ pub enum Error {
         #[snafu(backtrace)]
         source: crate::stateful::encode::Error,
     },
+
+    #[snafu(display("Could not read the element value to hold for sorting"))]
+    ReadValueSource {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not flush the data set output"))]
+    Flush {
+        #[snafu(backtrace)]
+        source: crate::stateful::encode::Error,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The default number of bytes gathered by a [`BatchWriter`]
+/// before they are passed on to the inner writer.
+pub const DEFAULT_BATCH_SIZE: usize = 8 * 1024;
+
+/// A writer adapter which gathers small writes in an internal buffer,
+/// passing them on to the inner writer once the batch is full.
+///
+/// This serves the same purpose as [`std::io::BufWriter`],
+/// but writes which do not fit in the batch
+/// are passed on together with the pending bytes
+/// in a single call to [`Write::write_vectored`],
+/// instead of being copied or written separately.
+/// As such, a data set written through a batch writer
+/// reaches the inner writer in few calls,
+/// while large values (such as pixel data) are never copied.
+///
+/// A batch size of zero disables batching,
+/// so that every write is passed on to the inner writer as is.
+///
+/// Pending bytes are written when the batch writer is dropped,
+/// but any errors in doing so are ignored.
+/// Call [`flush`](Write::flush) to ensure that everything was written.
+#[derive(Debug)]
+pub struct BatchWriter<W: Write> {
+    inner: W,
+    pending: Vec<u8>,
+    batch_size: usize,
+}
+
+impl<W: Write> BatchWriter<W> {
+    /// Create a new batch writer
+    /// with the [default batch size](DEFAULT_BATCH_SIZE).
+    pub fn new(inner: W) -> Self {
+        Self::with_batch_size(inner, DEFAULT_BATCH_SIZE)
+    }
+
+    /// Create a new batch writer
+    /// which passes on the pending bytes
+    /// once there are more than `batch_size` of them.
+    pub fn with_batch_size(inner: W, batch_size: usize) -> Self {
+        BatchWriter {
+            inner,
+            pending: Vec::with_capacity(batch_size),
+            batch_size,
+        }
+    }
+
+    /// Retrieve the maximum number of bytes held before writing them.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Retrieve the bytes which were not passed on to the inner writer yet.
+    pub fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+
+    /// Obtain a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Obtain a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly bypasses any pending bytes.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Write the pending bytes followed by the given buffers,
+    /// emptying the batch.
+    fn write_through(&mut self, bufs: &[&[u8]]) -> io::Result<()> {
+        let result = if self.pending.is_empty() {
+            write_all_vectored(&mut self.inner, bufs)
+        } else {
+            let mut all = Vec::with_capacity(bufs.len() + 1);
+            all.push(&self.pending[..]);
+            all.extend_from_slice(bufs);
+            write_all_vectored(&mut self.inner, &all)
+        };
+        // like `write_all`, the number of bytes written on error is unspecified,
+        // so the pending bytes are not written again
+        self.pending.clear();
+        result
+    }
+}
+
+impl<W: Write> Write for BatchWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.pending.len() + buf.len() <= self.batch_size {
+            self.pending.extend_from_slice(buf);
+        } else if self.pending.is_empty() {
+            return self.inner.write(buf);
+        } else {
+            self.write_through(&[buf])?;
+        }
+        Ok(buf.len())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        if self.pending.len() + len <= self.batch_size {
+            for buf in bufs {
+                self.pending.extend_from_slice(buf);
+            }
+        } else if self.pending.is_empty() {
+            return self.inner.write_vectored(bufs);
+        } else {
+            let bufs: Vec<&[u8]> = bufs.iter().map(|buf| &buf[..]).collect();
+            self.write_through(&bufs)?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            self.write_through(&[])?;
+        }
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for BatchWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// What to do with data elements which are not given
+/// in ascending tag order within their data set or item,
+/// as required by the standard.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum TagOrderPolicy {
+    /// Fail to write the data element out of order,
+    /// or repeated.
+    #[default]
+    Error,
+    /// Hold all tokens until the writer is [flushed](DataSetWriter::flush),
+    /// then write the data elements of each data set and item
+    /// in ascending tag order.
+    ///
+    /// Repeated data elements still make writing fail.
+    Sort,
+}
+
 /// A writer-specific token representing a sequence or item start.
 #[derive(Debug)]
 struct SeqToken {
@@ -79,6 +252,9 @@ struct SeqToken {
     /// The length of the value, as indicated by the starting element,
     /// can be unknown.
     len: Length,
+    /// For items, the tag of the last data element written
+    /// in the enclosing data set.
+    last_tag: Option<Tag>,
 }
 
 /// A stateful device for printing a DICOM data set in sequential order.
@@ -89,6 +265,11 @@ pub struct DataSetWriter<W, E, T = SpecificCharacterSet> {
     printer: StatefulEncoder<W, E, T>,
     seq_tokens: Vec<SeqToken>,
     last_de: Option<DataElementHeader>,
+    tag_order: TagOrderPolicy,
+    /// the tag of the last data element written in the current data set
+    last_tag: Option<Tag>,
+    /// tokens held for sorting
+    pending: Vec<DataToken>,
 }
 
 impl<'w, W: 'w> DataSetWriter<W, DynEncoder<'w, W>>
@@ -131,6 +312,9 @@ impl<W, E> DataSetWriter<W, E> {
             printer: StatefulEncoder::new(to, encoder, SpecificCharacterSet::default()),
             seq_tokens: Vec::new(),
             last_de: None,
+            tag_order: TagOrderPolicy::default(),
+            last_tag: None,
+            pending: Vec::new(),
         }
     }
 }
@@ -141,8 +325,77 @@ impl<W, E, T> DataSetWriter<W, E, T> {
             printer: StatefulEncoder::new(to, encoder, text),
             seq_tokens: Vec::new(),
             last_de: None,
+            tag_order: TagOrderPolicy::default(),
+            last_tag: None,
+            pending: Vec::new(),
         }
     }
+
+    /// Set what to do with characters in text values
+    /// which cannot be represented in the character set of the data set.
+    ///
+    /// The default is [`EncodeTextPolicy::Error`].
+    /// See [`StatefulEncoder::with_text_policy`] for more details.
+    pub fn with_text_policy(mut self, policy: EncodeTextPolicy) -> Self {
+        self.printer = self.printer.with_text_policy(policy);
+        self
+    }
+
+    /// Set how text values are checked against
+    /// the character repertoire and format of their value representation.
+    ///
+    /// The default is [`RepertoireValidation::Warn`].
+    /// See [`StatefulEncoder::with_repertoire_validation`] for more details.
+    pub fn with_repertoire_validation(mut self, validation: RepertoireValidation) -> Self {
+        self.printer = self.printer.with_repertoire_validation(validation);
+        self
+    }
+
+    /// Set how text values are checked against
+    /// the maximum value length of their value representation.
+    ///
+    /// The default is [`LengthValidation::Warn`].
+    /// See [`StatefulEncoder::with_length_validation`] for more details.
+    pub fn with_length_validation(mut self, validation: LengthValidation) -> Self {
+        self.printer = self.printer.with_length_validation(validation);
+        self
+    }
+
+    /// Set a marker to append to the values cut
+    /// under [`LengthValidation::Truncate`].
+    ///
+    /// See [`StatefulEncoder::with_truncation_marker`] for more details.
+    pub fn with_truncation_marker(mut self, marker: impl Into<String>) -> Self {
+        self.printer = self.printer.with_truncation_marker(marker);
+        self
+    }
+
+    /// Gather the values cut under [`LengthValidation::Truncate`]
+    /// into the given collector, in addition to logging them.
+    pub fn with_warning_collector(mut self, collector: &WarningCollector) -> Self {
+        self.printer = self.printer.with_warning_collector(collector);
+        self
+    }
+
+    /// Set what to do with values of odd length.
+    ///
+    /// The default is [`OddLengthPolicy::Pad`].
+    /// See [`StatefulEncoder::with_odd_length_policy`] for more details.
+    pub fn with_odd_length_policy(mut self, policy: OddLengthPolicy) -> Self {
+        self.printer = self.printer.with_odd_length_policy(policy);
+        self
+    }
+
+    /// Set what to do with data elements out of tag order.
+    ///
+    /// The default is [`TagOrderPolicy::Error`].
+    /// With [`TagOrderPolicy::Sort`],
+    /// nothing is written until the writer is [flushed](Self::flush),
+    /// which should then only happen at the end of the data set.
+    pub fn with_tag_order_policy(mut self, policy: TagOrderPolicy) -> Self {
+        self.tag_order = policy;
+        self
+    }
 }
 
 impl<W, E> DataSetWriter<W, E>
@@ -165,15 +418,25 @@ where
 
     /// Feed the given data set token for writing the data set.
     pub fn write(&mut self, token: DataToken) -> Result<()> {
+        if self.tag_order == TagOrderPolicy::Sort {
+            self.pending.push(token);
+            return Ok(());
+        }
+        self.write_token(token)
+    }
+
+    fn write_token(&mut self, token: DataToken) -> Result<()> {
         // adjust the logic of sequence printing:
         // explicit length sequences or items should not print
         // the respective delimiter
 
         match token {
-            DataToken::SequenceStart { len, .. } => {
+            DataToken::SequenceStart { tag, len } => {
+                self.check_tag_order(tag)?;
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Sequence,
                     len,
+                    last_tag: None,
                 });
                 self.write_impl(&token)?;
                 Ok(())
@@ -182,15 +445,23 @@ where
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Item,
                     len,
+                    last_tag: self.last_tag.take(),
                 });
                 self.write_impl(&token)?;
+                // items have their own character set scope
+                self.printer.begin_item();
                 Ok(())
             }
             DataToken::ItemEnd => {
+                self.printer.end_item();
                 // only write if it's an unknown length item
                 if let Some(seq_start) = self.seq_tokens.pop() {
-                    if seq_start.typ == SeqTokenType::Item && seq_start.len.is_undefined() {
-                        self.write_impl(&token)?;
+                    if seq_start.typ == SeqTokenType::Item {
+                        // back to the enclosing data set
+                        self.last_tag = seq_start.last_tag;
+                        if seq_start.len.is_undefined() {
+                            self.write_impl(&token)?;
+                        }
                     }
                 }
                 Ok(())
@@ -205,6 +476,7 @@ where
                 Ok(())
             }
             DataToken::ElementHeader(de) => {
+                self.check_tag_order(de.tag)?;
                 // save the header for later
                 self.last_de = Some(de);
 
@@ -212,18 +484,81 @@ where
                 Ok(())
             }
             token @ DataToken::PixelSequenceStart => {
+                self.check_tag_order(Tag(0x7FE0, 0x0010))?;
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Sequence,
                     len: Length::UNDEFINED,
+                    last_tag: None,
                 });
                 self.write_impl(&token)
             }
+            token @ DataToken::ValueChunk(_) => {
+                // the pending header is written before the first chunk
+                if let Some(header) = self.last_de.take() {
+                    self.printer
+                        .encode_element_header(header)
+                        .context(WriteHeaderSnafu { tag: header.tag })?;
+                }
+                self.write_impl(&token)
+            }
             token @ DataToken::ItemValue(_)
             | token @ DataToken::PrimitiveValue(_)
             | token @ DataToken::OffsetTable(_) => self.write_impl(&token),
         }
     }
 
+    /// Flush the underlying writer,
+    /// so that all data written so far reaches its destination.
+    ///
+    /// This should be called at the end of the data set
+    /// when writing to a [`BatchWriter`],
+    /// so that the last batch is written and any errors are reported.
+    ///
+    /// With [`TagOrderPolicy::Sort`],
+    /// this is when the tokens held are sorted and written.
+    pub fn flush(&mut self) -> Result<()> {
+        if !self.pending.is_empty() {
+            let tokens = sort_data_set(std::mem::take(&mut self.pending));
+            for token in tokens {
+                self.write_token(token)?;
+            }
+        }
+        self.printer.flush().context(FlushSnafu)
+    }
+
+    /// Feed the value of the pending element header or pixel data item
+    /// by copying its already encoded bytes from the given reader,
+    /// in place of a value token.
+    ///
+    /// This allows large values to be written
+    /// without holding them in memory.
+    /// The length of the pending element header must match
+    /// the number of bytes copied.
+    pub fn write_value_from<R: Read>(&mut self, mut from: R) -> Result<()> {
+        if self.tag_order == TagOrderPolicy::Sort {
+            // the value is needed until the data set is sorted
+            let mut data = Vec::new();
+            from.read_to_end(&mut data).context(ReadValueSourceSnafu)?;
+            self.pending.push(DataToken::ValueChunk(data));
+            return Ok(());
+        }
+        if let Some(header) = self.last_de.take() {
+            self.printer
+                .encode_element_header(header)
+                .context(WriteHeaderSnafu { tag: header.tag })?;
+        }
+        self.printer.write_bytes_from(from).context(WriteValueSnafu)
+    }
+
+    /// Check that the given data element comes after the last one
+    /// in the current data set.
+    fn check_tag_order(&mut self, tag: Tag) -> Result<()> {
+        match self.last_tag.replace(tag) {
+            Some(previous) if tag <= previous => TagOutOfOrderSnafu { tag, previous }.fail(),
+            _ => Ok(()),
+        }
+    }
+
     fn write_impl(&mut self, token: &DataToken) -> Result<()> {
         match token {
             DataToken::ElementHeader(header) => {
@@ -272,7 +607,7 @@ where
                     .encode_offset_table(table)
                     .context(WriteValueSnafu)?;
             }
-            DataToken::ItemValue(data) => {
+            DataToken::ItemValue(data) | DataToken::ValueChunk(data) => {
                 self.printer.write_bytes(data).context(WriteValueSnafu)?;
             }
         }
@@ -280,16 +615,104 @@ where
     }
 }
 
+/// Reorder the data elements of each data set and item in the given tokens
+/// by ascending tag,
+/// keeping repeated data elements in the order given.
+fn sort_data_set(tokens: Vec<DataToken>) -> Vec<DataToken> {
+    let mut tokens = tokens.into_iter().peekable();
+    let mut out = Vec::new();
+    sort_level(&mut tokens, &mut out);
+    // anything after an unbalanced item end is left as is
+    out.extend(tokens);
+    out
+}
+
+/// Move the data elements of the current data set level
+/// to `out` by ascending tag,
+/// stopping before the end of the item, if in one.
+fn sort_level<I>(tokens: &mut Peekable<I>, out: &mut Vec<DataToken>)
+where
+    I: Iterator<Item = DataToken>,
+{
+    let mut elements: Vec<(Tag, Vec<DataToken>)> = Vec::new();
+    while let Some(token) = tokens.peek() {
+        let tag = match token {
+            DataToken::ElementHeader(header) => header.tag,
+            DataToken::SequenceStart { tag, .. } => *tag,
+            DataToken::PixelSequenceStart => Tag(0x7FE0, 0x0010),
+            DataToken::ItemEnd => break,
+            _ => {
+                // not the start of a data element,
+                // kept with the previous one for the writer to reject
+                let token = tokens.next().unwrap();
+                match elements.last_mut() {
+                    Some((_, element)) => element.push(token),
+                    None => out.push(token),
+                }
+                continue;
+            }
+        };
+        let first = tokens.next().unwrap();
+        let mut element = Vec::new();
+        match first {
+            DataToken::ElementHeader(_) => {
+                element.push(first);
+                while let Some(token) = tokens.next_if(|token| {
+                    matches!(
+                        token,
+                        DataToken::PrimitiveValue(_) | DataToken::ValueChunk(_)
+                    )
+                }) {
+                    element.push(token);
+                }
+            }
+            DataToken::SequenceStart { .. } => {
+                element.push(first);
+                while let Some(token) = tokens.next() {
+                    match token {
+                        DataToken::ItemStart { .. } => {
+                            element.push(token);
+                            sort_level(tokens, &mut element);
+                            element.extend(tokens.next_if_eq(&DataToken::ItemEnd));
+                        }
+                        DataToken::SequenceEnd => {
+                            element.push(token);
+                            break;
+                        }
+                        token => element.push(token),
+                    }
+                }
+            }
+            _ => {
+                // pixel data fragments are kept in order
+                element.push(first);
+                for token in tokens.by_ref() {
+                    let end = token == DataToken::SequenceEnd;
+                    element.push(token);
+                    if end {
+                        break;
+                    }
+                }
+            }
+        }
+        elements.push((tag, element));
+    }
+    // stable, so that repeated data elements remain in order
+    elements.sort_by_key(|(tag, _)| *tag);
+    out.extend(elements.into_iter().flat_map(|(_, element)| element));
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::DataToken;
-    use super::DataSetWriter;
+    use super::{BatchWriter, DataSetWriter, Error, TagOrderPolicy};
     use dicom_core::{
         header::{DataElementHeader, Length},
         value::PrimitiveValue,
         Tag, VR,
     };
     use dicom_encoding::encode::{explicit_le::ExplicitVRLittleEndianEncoder, EncoderFor};
+    use std::io::{IoSlice, Write};
 
     fn validate_dataset_writer<I>(tokens: I, ground_truth: &[u8])
     where
@@ -368,6 +791,107 @@ mod tests {
         validate_dataset_writer(tokens, GROUND_TRUTH);
     }
 
+    /// a data element with the element number of its tag as its value
+    fn element(tag: Tag) -> [DataToken; 2] {
+        [
+            DataToken::ElementHeader(DataElementHeader::new(tag, VR::SH, Length(4))),
+            DataToken::PrimitiveValue(format!("{:04X}", tag.element()).into()),
+        ]
+    }
+
+    fn item_in_order(first: Tag, second: Tag) -> Vec<DataToken> {
+        let mut tokens = vec![DataToken::ItemStart {
+            len: Length::UNDEFINED,
+        }];
+        tokens.extend(element(first));
+        tokens.extend(element(second));
+        tokens.push(DataToken::ItemEnd);
+        tokens
+    }
+
+    /// a data set with a sequence followed by two elements,
+    /// with those and the elements of the item in the given order
+    fn tokens_in_order(root: [Tag; 2], item: [Tag; 2]) -> Vec<DataToken> {
+        let mut tokens = vec![DataToken::SequenceStart {
+            tag: Tag(0x0008, 0x1115),
+            len: Length::UNDEFINED,
+        }];
+        // elements of the item come before the sequence by tag,
+        // which has no bearing on their order
+        tokens.extend(item_in_order(item[0], item[1]));
+        tokens.push(DataToken::SequenceEnd);
+        tokens.extend(element(root[0]));
+        tokens.extend(element(root[1]));
+        tokens
+    }
+
+    fn write_with_tag_order(
+        tokens: Vec<DataToken>,
+        policy: TagOrderPolicy,
+    ) -> Result<Vec<u8>, Error> {
+        let mut raw_out: Vec<u8> = vec![];
+        let encoder = EncoderFor::new(ExplicitVRLittleEndianEncoder::default());
+        let mut dset_writer =
+            DataSetWriter::new(&mut raw_out, encoder).with_tag_order_policy(policy);
+        dset_writer.write_sequence(tokens)?;
+        dset_writer.flush()?;
+        Ok(raw_out)
+    }
+
+    #[test]
+    fn write_with_tag_order_policy() {
+        let (patient_name, patient_id) = (Tag(0x0010, 0x0010), Tag(0x0010, 0x0020));
+        let (code_value, code_meaning) = (Tag(0x0008, 0x0100), Tag(0x0008, 0x0104));
+        let sorted = tokens_in_order([patient_name, patient_id], [code_value, code_meaning]);
+        let expected = write_with_tag_order(sorted.clone(), TagOrderPolicy::Error).unwrap();
+        assert_eq!(
+            write_with_tag_order(sorted, TagOrderPolicy::Sort).unwrap(),
+            expected
+        );
+
+        // swapped at the root
+        let tokens = tokens_in_order([patient_id, patient_name], [code_value, code_meaning]);
+        let err = write_with_tag_order(tokens.clone(), TagOrderPolicy::Error).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::TagOutOfOrder { tag, previous, .. }
+                    if tag == patient_name && previous == patient_id
+            ),
+            "{:?}",
+            err
+        );
+        assert_eq!(
+            write_with_tag_order(tokens, TagOrderPolicy::Sort).unwrap(),
+            expected
+        );
+
+        // swapped in the item only
+        let tokens = tokens_in_order([patient_name, patient_id], [code_meaning, code_value]);
+        let err = write_with_tag_order(tokens.clone(), TagOrderPolicy::Error).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::TagOutOfOrder { tag, previous, .. }
+                    if tag == code_value && previous == code_meaning
+            ),
+            "{:?}",
+            err
+        );
+        assert_eq!(
+            write_with_tag_order(tokens, TagOrderPolicy::Sort).unwrap(),
+            expected
+        );
+
+        // repeated elements cannot be sorted out
+        let mut tokens = element(patient_name).to_vec();
+        tokens.extend(element(patient_name));
+        for policy in [TagOrderPolicy::Error, TagOrderPolicy::Sort] {
+            let err = write_with_tag_order(tokens.clone(), policy).unwrap_err();
+            assert!(matches!(err, Error::TagOutOfOrder { .. }), "{:?}", err);
+        }
+    }
+
     #[test]
     fn write_element_overrides_len() {
         let tokens = vec![
@@ -600,4 +1124,252 @@ mod tests {
 
         validate_dataset_writer(tokens, GROUND_TRUTH);
     }
+
+    #[test]
+    fn write_values_from_readers() {
+        let encoder = EncoderFor::new(ExplicitVRLittleEndianEncoder::default());
+        let mut raw_out: Vec<u8> = vec![];
+        let mut dset_writer = DataSetWriter::new(&mut raw_out, encoder);
+
+        dset_writer
+            .write(DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1001),
+                VR::OB,
+                Length(4),
+            )))
+            .unwrap();
+        dset_writer.write_value_from(&[1_u8, 2, 3, 4][..]).unwrap();
+        dset_writer.write(DataToken::PixelSequenceStart).unwrap();
+        dset_writer
+            .write(DataToken::ItemStart { len: Length(0) })
+            .unwrap();
+        dset_writer.write(DataToken::ItemEnd).unwrap();
+        dset_writer
+            .write(DataToken::ItemStart { len: Length(2) })
+            .unwrap();
+        dset_writer.write_value_from(&[0x99_u8; 2][..]).unwrap();
+        dset_writer.write(DataToken::ItemEnd).unwrap();
+        dset_writer.write(DataToken::SequenceEnd).unwrap();
+
+        #[rustfmt::skip]
+        let ground_truth: &[u8] = &[
+            0x09, 0x00, 0x01, 0x10, // (0009, 1001)
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0x04, 0x00, 0x00, 0x00, // length: 4
+            0x01, 0x02, 0x03, 0x04,
+            0xe0, 0x7f, 0x10, 0x00, // (7FE0, 0010) PixelData
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x00, 0x00, 0x00, 0x00, // item length: 0
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x02, 0x00, 0x00, 0x00, // item length: 2
+            0x99, 0x99,
+            0xfe, 0xff, 0xdd, 0xe0, // sequence end tag
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(raw_out, ground_truth);
+    }
+
+    #[test]
+    fn write_value_chunks() {
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0009, 0x1001),
+                vr: VR::OB,
+                len: Length(6),
+            }),
+            DataToken::ValueChunk(vec![0x01, 0x02, 0x03, 0x04]),
+            DataToken::ValueChunk(vec![0x05, 0x06]),
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(4) },
+            DataToken::ValueChunk(vec![0x99, 0x98]),
+            DataToken::ValueChunk(vec![0x97, 0x96]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0x09, 0x00, 0x01, 0x10, // (0009, 1001)
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0x06, 0x00, 0x00, 0x00, // length: 6
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+            0xe0, 0x7f, 0x10, 0x00, // (7FE0, 0010) PixelData
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x00, 0x00, 0x00, 0x00, // item length: 0
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x04, 0x00, 0x00, 0x00, // item length: 4
+            0x99, 0x98, 0x97, 0x96,
+            0xfe, 0xff, 0xdd, 0xe0, // sequence end tag
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        validate_dataset_writer(tokens, GROUND_TRUTH);
+    }
+
+    /// A writer which records the number of bytes in each call,
+    /// optionally accepting at most `max` bytes per call.
+    #[derive(Debug, Default)]
+    struct RecordingWriter {
+        out: Vec<u8>,
+        calls: Vec<usize>,
+        max: Option<usize>,
+    }
+
+    impl Write for RecordingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.write_vectored(&[IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+            let max = self.max.unwrap_or(usize::MAX);
+            let mut n = 0;
+            for buf in bufs {
+                let len = buf.len().min(max - n);
+                self.out.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+            self.calls.push(n);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A small data set with a large pixel data value.
+    fn batch_test_tokens() -> Vec<DataToken> {
+        let mut tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0005),
+                VR::CS,
+                Length(10),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("ISO_IR 100")),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0060),
+                VR::CS,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("MR")),
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1140),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x1150),
+                VR::UI,
+                Length(7),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.3.4")),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+        for (i, element) in [0x0010_u16, 0x0011].iter().copied().enumerate() {
+            tokens.push(DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0028, element),
+                VR::US,
+                Length(2),
+            )));
+            tokens.push(DataToken::PrimitiveValue(PrimitiveValue::from(
+                64 + i as u16,
+            )));
+        }
+        tokens.push(DataToken::ElementHeader(DataElementHeader::new(
+            Tag(0x0028, 0x1050),
+            VR::DS,
+            Length(4),
+        )));
+        tokens.push(DataToken::PrimitiveValue(PrimitiveValue::from(40.5_f64)));
+        tokens.push(DataToken::ElementHeader(DataElementHeader::new(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            Length(64 * 65),
+        )));
+        tokens.push(DataToken::ValueChunk(vec![0x55; 64 * 65]));
+        tokens
+    }
+
+    fn write_batch_test<W: Write>(to: W) {
+        let encoder = EncoderFor::new(ExplicitVRLittleEndianEncoder::default());
+        let mut dset_writer = DataSetWriter::new(to, encoder);
+        dset_writer.write_sequence(batch_test_tokens()).unwrap();
+        dset_writer.flush().unwrap();
+    }
+
+    #[test]
+    fn write_in_batches() {
+        // unbatched
+        let mut unbatched = RecordingWriter::default();
+        write_batch_test(&mut unbatched);
+        let small_writes = unbatched.calls.iter().filter(|&&n| n < 64).count();
+        assert!(small_writes > 10, "{:?}", unbatched.calls);
+
+        // text elements are written with their header in a single call
+        assert_eq!(&unbatched.calls[..2], &[18, 10]);
+
+        // batched, with a batch smaller than the pixel data
+        let mut to = RecordingWriter::default();
+        write_batch_test(BatchWriter::with_batch_size(&mut to, 1024));
+        assert_eq!(to.out, unbatched.out);
+        // all small elements are gathered,
+        // then passed on with the pixel data header and value
+        assert_eq!(to.calls, vec![unbatched.out.len()]);
+
+        // with a batch larger than the whole data set
+        let mut to = RecordingWriter::default();
+        let mut batch = BatchWriter::with_batch_size(&mut to, 1 << 16);
+        write_batch_test(&mut batch);
+        assert_eq!(batch.pending().len(), 0);
+        drop(batch);
+        assert_eq!(to.out, unbatched.out);
+        assert_eq!(to.calls, vec![unbatched.out.len()]);
+
+        // a batch size of zero writes everything as is
+        let mut to = RecordingWriter::default();
+        write_batch_test(BatchWriter::with_batch_size(&mut to, 0));
+        assert_eq!(to.out, unbatched.out);
+        assert_eq!(to.calls, unbatched.calls);
+
+        // a tiny batch still gathers the headers with their values
+        let mut to = RecordingWriter::default();
+        write_batch_test(BatchWriter::with_batch_size(&mut to, 16));
+        assert_eq!(to.out, unbatched.out);
+        assert!(to.calls.len() < unbatched.calls.len());
+    }
+
+    #[test]
+    fn write_in_batches_with_partial_writes() {
+        let mut unbatched = RecordingWriter::default();
+        write_batch_test(&mut unbatched);
+
+        for &max in &[1, 5, 12, 100, 1000] {
+            for &batch_size in &[0, 16, 1024] {
+                let mut to = RecordingWriter {
+                    max: Some(max),
+                    ..Default::default()
+                };
+                write_batch_test(BatchWriter::with_batch_size(&mut to, batch_size));
+                assert_eq!(
+                    to.out, unbatched.out,
+                    "max = {}, batch size = {}",
+                    max, batch_size
+                );
+                assert!(to.calls.iter().all(|&n| n <= max));
+            }
+        }
+    }
 }
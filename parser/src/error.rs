@@ -0,0 +1,759 @@
+//! Crate-level error type, classifying failures by their kind.
+//!
+//! Each module of this crate reports failures through its own error type,
+//! which keeps track of the operation that failed and where.
+//! All of them can be converted into the [`Error`] type of this module,
+//! so that callers can match on the kind of failure
+//! without having to inspect the individual module errors,
+//! and so that `?` can be used on any of them
+//! in a function returning [`Result`].
+//!
+//! ```no_run
+//! # use dicom_encoding::TransferSyntax;
+//! use dicom_parser::error::{Error, Result};
+//! use dicom_parser::DataSetReader;
+//!
+//! fn count_tokens(data: &[u8], ts: &TransferSyntax) -> Result<usize> {
+//!     let mut count = 0;
+//!     for token in DataSetReader::new_with_ts(data, ts)? {
+//!         token?;
+//!         count += 1;
+//!     }
+//!     Ok(count)
+//! }
+//! # let ts = dicom_encoding::TransferSyntax::new(
+//! #     "1.2.840.10008.1.2",
+//! #     "Implicit VR Little Endian",
+//! #     dicom_encoding::Endianness::Little,
+//! #     false,
+//! #     dicom_encoding::Codec::None,
+//! # );
+//! match count_tokens(&[0x08, 0x00, 0x05], &ts) {
+//!     Err(Error::PrematureEnd { offset, .. }) => {
+//!         eprintln!("data set is truncated at byte offset {}", offset)
+//!     }
+//!     Err(e) => eprintln!("{}", e),
+//!     Ok(count) => println!("{} tokens", count),
+//! }
+//! ```
+//!
+//! The conversion retains the kind of failure and its essential details,
+//! but not the location where it occurred.
+//! When reading a data set, that location is available via
+//! [`dataset::read::Error::context`](crate::dataset::read::Error::context)
+//! before converting the error.
+//! The exception are failures of the source itself,
+//! which keep their location in [`Error::Io`],
+//! since nothing else would tell where reading stopped.
+use crate::dataset::read::ErrorContext;
+use crate::dataset::{lazy_read, read, write};
+use crate::stateful::{decode as stateful_decode, encode as stateful_encode};
+use dicom_core::header::SequenceItemHeaderError;
+use dicom_core::{Tag, VR};
+use dicom_encoding::text::SpecificCharacterSet;
+use dicom_encoding::{decode, encode};
+use std::fmt;
+use std::io;
+
+/// A failure to parse or print DICOM data, classified by its kind.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The data source ended in the middle of a data set construct.
+    UnexpectedEndOfStream,
+    /// The data source ended in the middle of a header or value
+    /// at a known position.
+    PrematureEnd {
+        /// the tag of the element whose value was cut short,
+        /// if known
+        tag: Option<Tag>,
+        /// the number of bytes of the header or value
+        expected: u64,
+        /// the number of bytes available before the end
+        got: u64,
+        /// the byte offset at which the header or value started
+        offset: u64,
+    },
+    /// The bytes in place of an explicit value representation
+    /// are not a value representation.
+    InvalidVr {
+        /// the offending bytes
+        bytes: [u8; 2],
+    },
+    /// An element tag appeared where it is not admissible,
+    /// such as a data element header in place of an item header.
+    InvalidTag {
+        /// the offending tag
+        tag: Tag,
+    },
+    /// The transfer syntax is known,
+    /// but data cannot be decoded or encoded in it.
+    UnsupportedTransferSyntax {
+        /// the transfer syntax UID
+        uid: String,
+    },
+    /// The character set is known, but text cannot be decoded or encoded in it.
+    UnsupportedCharacterSet {
+        /// the specific character set
+        charset: SpecificCharacterSet,
+    },
+    /// A value did not end where its length said it would.
+    ValueLengthMismatch {
+        /// the byte position at which the value should have ended
+        expected: u64,
+        /// the byte position reached
+        actual: u64,
+    },
+    /// A value is not valid for its value representation.
+    InvalidValue {
+        /// the value representation of the value,
+        /// or `UN` if unknown
+        vr: VR,
+        /// a description of the problem
+        reason: String,
+    },
+    /// The nesting of sequences, items and values is not well formed.
+    SequenceStructure(Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// A limit set in the reading options was exceeded,
+    /// or a length or position does not fit the platform's integer types.
+    LimitExceeded(Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// Any other failure to read from the source or write to the destination.
+    Io {
+        /// the original I/O error
+        source: io::Error,
+        /// the location in the data set at which reading failed,
+        /// if the failure happened in the data set reader
+        context: Option<ErrorContext>,
+    },
+}
+
+/// Type alias for a result with the crate-level [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnexpectedEndOfStream => f.write_str("unexpected end of stream"),
+            Error::PrematureEnd {
+                tag: Some(tag),
+                expected,
+                got,
+                offset,
+            } => write!(
+                f,
+                "premature end of element {} at byte offset {}: got {} of {} bytes",
+                tag, offset, got, expected
+            ),
+            Error::PrematureEnd {
+                tag: None,
+                expected,
+                got,
+                offset,
+            } => write!(
+                f,
+                "premature end of data at byte offset {}: got {} of {} bytes",
+                offset, got, expected
+            ),
+            Error::InvalidVr { bytes } => {
+                write!(f, "invalid value representation bytes {:02X?}", bytes)
+            }
+            Error::InvalidTag { tag } => write!(f, "unexpected tag {}", tag),
+            Error::UnsupportedTransferSyntax { uid } => {
+                write!(f, "unsupported transfer syntax {}", uid)
+            }
+            Error::UnsupportedCharacterSet { charset } => {
+                write!(f, "unsupported character set {:?}", charset)
+            }
+            Error::ValueLengthMismatch { expected, actual } => write!(
+                f,
+                "value length mismatch: expected end at {} bytes but reached {}",
+                expected, actual
+            ),
+            Error::InvalidValue { vr, reason } => write!(f, "invalid {} value: {}", vr, reason),
+            Error::SequenceStructure(_) => f.write_str("malformed sequence structure"),
+            Error::LimitExceeded(_) => f.write_str("reading limit exceeded"),
+            Error::Io {
+                context: Some(context),
+                ..
+            } => write!(f, "I/O error at {}", context),
+            Error::Io { context: None, .. } => f.write_str("I/O error"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::SequenceStructure(e) | Error::LimitExceeded(e) => Some(e.as_ref()),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Classify a value decoding error,
+    /// using the given value representation if the error does not imply one.
+    fn from_value_decode(e: stateful_decode::Error, vr: VR) -> Self {
+        use stateful_decode::Error::*;
+        let invalid_value = |vr, reason: &dyn fmt::Display| Error::InvalidValue {
+            vr,
+            reason: reason.to_string(),
+        };
+        match e {
+            UnsupportedTransferSyntax { ts, .. } => Error::UnsupportedTransferSyntax {
+                uid: ts.to_string(),
+            },
+            UnsupportedCharacterSet { charset, .. } => Error::UnsupportedCharacterSet { charset },
+            e @ NonPrimitiveType { .. } | e @ UndefinedValueLength { .. } => {
+                Error::SequenceStructure(Box::new(e))
+            }
+            e @ LengthOverflow { .. } => Error::LimitExceeded(Box::new(e)),
+            DecodeElementHeader { source, .. } | DecodeItemHeader { source, .. } => source.into(),
+            DecodeText { source, .. } => invalid_value(vr, &source),
+            ReadValueData { source, .. }
+            | SeekReader { source, .. }
+            | ConsumeValueChunk { source, .. } => source.into(),
+            TruncatedValue {
+                position,
+                expected,
+                got,
+                ..
+            } => Error::PrematureEnd {
+                tag: None,
+                expected,
+                got,
+                offset: position,
+            },
+            DeserializeValue { source, .. } => invalid_value(vr, &source),
+            ReadInt { source, .. } => invalid_value(VR::IS, &source),
+            ReadFloat { source, .. } => invalid_value(VR::DS, &source),
+            InvalidDateValue { string, .. } => invalid_value(VR::DA, &string),
+            InvalidTimeValue { string, .. } => invalid_value(VR::TM, &string),
+            InvalidDateTimeValue { string, .. } => invalid_value(VR::DT, &string),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    /// Classify an I/O error,
+    /// recognizing a premature end of the source.
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            Error::UnexpectedEndOfStream
+        } else {
+            Error::Io {
+                source: e,
+                context: None,
+            }
+        }
+    }
+}
+
+impl From<decode::Error> for Error {
+    fn from(e: decode::Error) -> Self {
+        use decode::Error::*;
+        match e {
+            ReadHeaderTag { source, .. }
+            | ReadItemHeader { source, .. }
+            | ReadItemLength { source, .. }
+            | ReadTag { source, .. }
+            | ReadReserved { source, .. }
+            | ReadLength { source, .. }
+            | ReadVr { source, .. } => source.into(),
+            BadSequenceHeader {
+                source: SequenceItemHeaderError::UnexpectedTag { tag, .. },
+            } => Error::InvalidTag { tag },
+            InvalidVr { bytes, .. } => Error::InvalidVr { bytes },
+            e => Error::SequenceStructure(Box::new(e)),
+        }
+    }
+}
+
+impl From<encode::Error> for Error {
+    fn from(e: encode::Error) -> Self {
+        use encode::Error::*;
+        match e {
+            WriteDate { source, .. }
+            | WriteTime { source, .. }
+            | WriteDateTime { source, .. }
+            | WriteTag { source, .. }
+            | WriteTagGroup { source, .. }
+            | WriteTagElement { source, .. }
+            | WriteItemHeader { source, .. }
+            | WriteHeader { source, .. }
+            | WriteItemDelimiter { source, .. }
+            | WriteSequenceDelimiter { source, .. }
+            | WriteBinary { source, .. }
+            | WriteString { source, .. }
+            | WriteBytes { source, .. }
+            | WriteOffsetTable { source, .. } => source.into(),
+            e @ LengthTooLong { vr, .. } => Error::InvalidValue {
+                vr,
+                reason: e.to_string(),
+            },
+            e => io::Error::other(e).into(),
+        }
+    }
+}
+
+impl From<stateful_decode::Error> for Error {
+    fn from(e: stateful_decode::Error) -> Self {
+        Error::from_value_decode(e, VR::UN)
+    }
+}
+
+impl From<stateful_encode::Error> for Error {
+    fn from(e: stateful_encode::Error) -> Self {
+        use stateful_encode::Error::*;
+        match e {
+            UnsupportedTransferSyntax { ts, .. } => Error::UnsupportedTransferSyntax {
+                uid: ts.to_string(),
+            },
+            UnsupportedCharacterSet { charset, .. } => Error::UnsupportedCharacterSet { charset },
+            EncodeData { source, .. } => source.into(),
+            EncodeText { source, .. } => Error::InvalidValue {
+                vr: VR::UN,
+                reason: source.to_string(),
+            },
+            InvalidValue { vr, violation, .. } => Error::InvalidValue {
+                vr,
+                reason: violation.to_string(),
+            },
+            ValueTooLong { vr, violation, .. } => Error::InvalidValue {
+                vr,
+                reason: violation.to_string(),
+            },
+            e @ OddLength { .. } => Error::InvalidValue {
+                vr: VR::UN,
+                reason: e.to_string(),
+            },
+            WriteValueData { source, .. } | FlushOutput { source, .. } => source.into(),
+        }
+    }
+}
+
+impl From<read::Error> for Error {
+    fn from(e: read::Error) -> Self {
+        use read::Error::*;
+        match e {
+            CreateDecoder { source }
+            | ReadItemHeader { source }
+            | ReadHeader { source }
+            | ReadItemValue { source, .. }
+            | SkipItemRemainder { source, .. } => source.into(),
+            ReadValue { vr, source, .. } => Error::from_value_decode(source, vr),
+            InconsistentSequenceEnd {
+                end_of_sequence,
+                bytes_read,
+                ..
+            } => Error::ValueLengthMismatch {
+                expected: end_of_sequence,
+                actual: bytes_read,
+            },
+            UnexpectedItemTag { tag, .. } | TagOutOfOrder { tag, .. } => Error::InvalidTag { tag },
+            e @ UndefinedItemLength => Error::SequenceStructure(Box::new(e)),
+            e @ OddLength { vr, .. } => Error::InvalidValue {
+                vr,
+                reason: e.to_string(),
+            },
+            e @ ValueTooLong { .. } | e @ NestingTooDeep { .. } | e @ LengthOverflow { .. } => {
+                Error::LimitExceeded(Box::new(e))
+            }
+            PrematureEnd {
+                tag,
+                expected,
+                got,
+                offset,
+                ..
+            } => Error::PrematureEnd {
+                tag,
+                expected,
+                got,
+                offset,
+            },
+            #[cfg(feature = "async")]
+            FetchData { source, .. } => source.into(),
+            WithContext { context, source } => match (*source).into() {
+                Error::Io {
+                    source,
+                    context: None,
+                } => Error::Io {
+                    source,
+                    context: Some(context),
+                },
+                e => e,
+            },
+        }
+    }
+}
+
+impl From<lazy_read::Error> for Error {
+    fn from(e: lazy_read::Error) -> Self {
+        use lazy_read::Error::*;
+        match e {
+            CreateDecoder { source }
+            | ReadItemHeader { source, .. }
+            | ReadHeader { source, .. }
+            | ReadValue { source } => source.into(),
+            GetPosition { source, .. } => source.into(),
+            InconsistentSequenceEnd {
+                end_of_sequence,
+                bytes_read,
+                ..
+            } => Error::ValueLengthMismatch {
+                expected: end_of_sequence,
+                actual: bytes_read,
+            },
+            e @ UnexpectedItemDelimiter { .. } | e @ UndefinedLength { .. } => {
+                Error::SequenceStructure(Box::new(e))
+            }
+        }
+    }
+}
+
+impl From<write::Error> for Error {
+    fn from(e: write::Error) -> Self {
+        use write::Error::*;
+        match e {
+            UnsupportedTransferSyntax { ts_uid, .. } => Error::UnsupportedTransferSyntax {
+                uid: ts_uid.to_string(),
+            },
+            UnsupportedCharacterSet { charset, .. } => Error::UnsupportedCharacterSet { charset },
+            e @ UnexpectedToken { .. } => Error::SequenceStructure(Box::new(e)),
+            TagOutOfOrder { tag, .. } => Error::InvalidTag { tag },
+            ReadValueSource { source, .. } => source.into(),
+            WriteHeader { source, .. }
+            | WriteItemHeader { source }
+            | WriteSequenceDelimiter { source }
+            | WriteItemDelimiter { source }
+            | WriteValue { source }
+            | Flush { source } => source.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Result};
+    use crate::dataset::read::{DataSetReaderOptions, ValueReadStrategy};
+    use crate::dataset::{DataSetReader, DataSetWriter, DataToken};
+    use crate::stateful::decode::StatefulDecoder;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::{dicom_value, Tag, VR};
+    use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
+    use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
+    use dicom_encoding::text::repertoire::RepertoireValidation;
+    use dicom_encoding::text::SpecificCharacterSet;
+    use dicom_encoding::{Codec, Endianness, TransferSyntax};
+
+    const EXPLICIT_VR_LE: TransferSyntax = TransferSyntax::new(
+        "1.2.840.10008.1.2.1",
+        "Explicit VR Little Endian",
+        Endianness::Little,
+        true,
+        Codec::None,
+    );
+
+    /// Read all tokens in the given explicit VR little endian data set.
+    fn read_all(data: &[u8], value_read: ValueReadStrategy) -> Result<Vec<DataToken>> {
+        let mut cursor = data;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let options = DataSetReaderOptions::default().value_read(value_read);
+        let mut tokens = Vec::new();
+        for token in DataSetReader::new(parser, options) {
+            tokens.push(token?);
+        }
+        Ok(tokens)
+    }
+
+    #[test]
+    fn truncated_header_is_premature_end() {
+        // (0010,0010) PatientName, cut in the VR
+        let data: &[u8] = &[0x10, 0x00, 0x10, 0x00, b'P'];
+        let err = read_all(data, ValueReadStrategy::Preserved).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::PrematureEnd {
+                    tag: None,
+                    expected: 8,
+                    got: 5,
+                    offset: 0,
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn truncated_value_is_premature_end() {
+        // (0010,0010) PatientName, 8 bytes announced but 3 available
+        let data: &[u8] = &[
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, b'D', b'o', b'e',
+        ];
+        let err = read_all(data, ValueReadStrategy::Preserved).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::PrematureEnd {
+                    tag: Some(Tag(0x0010, 0x0010)),
+                    expected: 8,
+                    got: 3,
+                    offset: 8,
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn garbage_vr_is_invalid_vr() {
+        let data: &[u8] = &[0x10, 0x00, 0x10, 0x00, 0x01, 0x02, 0x00, 0x00];
+        let err = read_all(data, ValueReadStrategy::Preserved).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::InvalidVr {
+                    bytes: [0x01, 0x02]
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn element_in_place_of_item_is_invalid_tag() {
+        #[rustfmt::skip]
+        let data: &[u8] = &[
+            // (0008,1115) ReferencedSeriesSequence
+            0x08, 0x00, 0x15, 0x11, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // (0010,0010) PatientName instead of an item
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x04, 0x00,
+            b'D', b'o', b'e', b' ',
+        ];
+        let err = read_all(data, ValueReadStrategy::Preserved).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::InvalidTag {
+                    tag: Tag(0x0010, 0x0010)
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn overrun_sequence_is_value_length_mismatch() {
+        #[rustfmt::skip]
+        let data: &[u8] = &[
+            // (0008,1115) ReferencedSeriesSequence, 10 bytes
+            0x08, 0x00, 0x15, 0x11, b'S', b'Q', 0x00, 0x00,
+            0x0A, 0x00, 0x00, 0x00,
+            // item, 12 bytes
+            0xFE, 0xFF, 0x00, 0xE0, 0x0C, 0x00, 0x00, 0x00,
+            // (0020,000E) SeriesInstanceUID
+            0x20, 0x00, 0x0E, 0x00, b'U', b'I', 0x04, 0x00,
+            b'1', b'.', b'2', 0x00,
+        ];
+        let err = read_all(data, ValueReadStrategy::Preserved).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::ValueLengthMismatch {
+                    expected: 22,
+                    actual: 32
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    /// A source which is interrupted before every read
+    /// and fails for good at the given byte.
+    struct FlakyReader<'a> {
+        data: &'a [u8],
+        position: usize,
+        fail_at: usize,
+        interrupt: bool,
+        interruptions: usize,
+    }
+
+    impl std::io::Read for FlakyReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                self.interruptions += 1;
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+            if self.position == self.fail_at {
+                return Err(std::io::ErrorKind::ConnectionReset.into());
+            }
+            // at most 3 bytes at a time, never past the failure point
+            let end = (self.position + 3).min(self.fail_at).min(self.data.len());
+            let n = (end - self.position).min(buf.len());
+            buf[..n].copy_from_slice(&self.data[self.position..self.position + n]);
+            self.position += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn source_failure_keeps_context() {
+        #[rustfmt::skip]
+        let data: &[u8] = &[
+            // 0: (0008,1140) ReferencedImageSequence, undefined length
+            0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00,
+            0xFF, 0xFF, 0xFF, 0xFF,
+            // 12: item, undefined length
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // 20: (0008,1155) ReferencedSOPInstanceUID
+            0x08, 0x00, 0x55, 0x11, b'U', b'I', 0x08, 0x00,
+            // 28: value, the source fails at byte 30
+            b'2', b'.', b'2', b'5', b'.', b'1', b'2', 0x00,
+        ];
+        let mut source = FlakyReader {
+            data,
+            position: 0,
+            fail_at: 30,
+            interrupt: false,
+            interruptions: 0,
+        };
+        let parser = StatefulDecoder::new(
+            &mut source,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let mut reader = DataSetReader::new(parser, DataSetReaderOptions::default());
+
+        // interruptions are retried
+        for _ in 0..3 {
+            reader.next().unwrap().unwrap();
+        }
+        let err: Error = reader.next().unwrap().unwrap_err().into();
+        assert!(source.interruptions > 10);
+
+        let context = match &err {
+            Error::Io {
+                source,
+                context: Some(context),
+            } => {
+                assert_eq!(source.kind(), std::io::ErrorKind::ConnectionReset);
+                context
+            }
+            _ => panic!("unexpected error {:?}", err),
+        };
+        assert_eq!(context.tag(), Some(Tag(0x0008, 0x1155)));
+        assert_eq!(context.offset(), 28);
+        assert_eq!(context.path().len(), 1);
+        assert_eq!(context.path()[0].sequence, Tag(0x0008, 0x1140));
+        assert_eq!(context.path()[0].item, Some(0));
+        assert_eq!(
+            err.to_string(),
+            "I/O error at (0008,1140)[0].(0008,1155), byte offset 28"
+        );
+
+        // the original error is the source
+        let source = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<std::io::Error>())
+            .unwrap();
+        assert_eq!(source.kind(), std::io::ErrorKind::ConnectionReset);
+    }
+
+    #[test]
+    fn bad_date_is_invalid_value() {
+        // (0008,0020) StudyDate: "2024AB01"
+        #[rustfmt::skip]
+        let data: &[u8] = &[
+            0x08, 0x00, 0x20, 0x00, b'D', b'A', 0x08, 0x00,
+            b'2', b'0', b'2', b'4', b'A', b'B', b'0', b'1',
+        ];
+        let err = read_all(data, ValueReadStrategy::Interpreted).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidValue { vr: VR::DA, .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn unsupported_transfer_syntax() {
+        let ts = TransferSyntax::new(
+            "1.2.3.4.5.6",
+            "Implicit VR Big Endian",
+            Endianness::Big,
+            false,
+            Codec::None,
+        );
+        let err: Error = match DataSetReader::new_with_ts(&[][..], &ts) {
+            Ok(_) => panic!("transfer syntax should not be supported"),
+            Err(e) => e.into(),
+        };
+        match err {
+            Error::UnsupportedTransferSyntax { uid } => assert_eq!(uid, "1.2.3.4.5.6"),
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn invalid_repertoire_is_invalid_value() {
+        let mut out = Vec::new();
+        let mut writer = DataSetWriter::with_ts(&mut out, &EXPLICIT_VR_LE)
+            .unwrap()
+            .with_repertoire_validation(RepertoireValidation::Strict);
+        let err: Error = writer
+            .write_sequence(vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0008, 0x0060),
+                    VR::CS,
+                    Length(2),
+                )),
+                DataToken::PrimitiveValue(dicom_value!(Str, "mr")),
+            ])
+            .unwrap_err()
+            .into();
+        assert!(
+            matches!(err, Error::InvalidValue { vr: VR::CS, .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn interop_with_anyhow() {
+        fn read_with_anyhow(data: &[u8]) -> anyhow::Result<usize> {
+            let tokens = read_all(data, ValueReadStrategy::Preserved)?;
+            Ok(tokens.len())
+        }
+
+        let data: &[u8] = &[0x10, 0x00, 0x10, 0x00, b'P'];
+        let err = read_with_anyhow(data).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::PrematureEnd { .. })
+        ));
+        assert_eq!(
+            err.to_string(),
+            "premature end of data at byte offset 0: got 5 of 8 bytes"
+        );
+
+        // the I/O error is preserved as the source
+        let err: Error = std::io::Error::other("disk on fire").into();
+        let err = anyhow::Error::from(err);
+        assert_eq!(err.chain().count(), 2);
+        assert_eq!(err.root_cause().to_string(), "disk on fire");
+    }
+}
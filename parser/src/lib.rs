@@ -4,11 +4,15 @@
 //! ultimately enables the user to perceive the DICOM object as a sequence of
 //! tokens.
 //!
-//! For the time being, all APIs are based on synchronous I/O.
+//! APIs are based on synchronous I/O,
+//! with the exception of the asynchronous data set reader
+//! in the `dataset::asynchronous` module (requires the `async` feature).
 //!
 //! For a more intuitive, object-oriented API, please see the `dicom-object`
 //! crate.
 pub mod dataset;
+pub mod error;
+pub mod pixel_sequence;
 pub mod stateful;
 
 mod util;
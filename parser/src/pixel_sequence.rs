@@ -0,0 +1,355 @@
+//! Parsing of encapsulated pixel data.
+//!
+//! In encapsulated transfer syntaxes,
+//! the _Pixel Data_ element has an undefined length
+//! and contains a sequence of items:
+//! the first item is the Basic Offset Table,
+//! which may be empty,
+//! and every other item is a fragment of the encoded pixel data.
+//! The sequence ends with a sequence delimitation item.
+//!
+//! The functions in this module read this structure
+//! with a [`StatefulDecode`] positioned
+//! right after the header of the pixel data element.
+//! [`read_pixel_sequence`] loads all fragments into memory,
+//! whereas [`scan_pixel_sequence`] only records
+//! where each fragment lies in a seekable source,
+//! so that they can be loaded later with [`FragmentRef::read_from`].
+use crate::stateful::decode::{self, StatefulDecode, MAX_VALUE_RESERVE};
+use dicom_core::header::SequenceItemHeader;
+use dicom_core::value::PixelFragmentSequence;
+use snafu::{Backtrace, ResultExt, Snafu};
+use std::io::{Read, Seek, SeekFrom};
+
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum Error {
+    #[snafu(display("Could not read pixel data item header at position {}", position))]
+    ReadItemHeader {
+        position: u64,
+        #[snafu(backtrace)]
+        source: decode::Error,
+    },
+
+    #[snafu(display("Could not read pixel data item value at position {}", position))]
+    ReadItemValue {
+        position: u64,
+        #[snafu(backtrace)]
+        source: decode::Error,
+    },
+
+    #[snafu(display("Could not skip pixel data item value at position {}", position))]
+    SkipItemValue {
+        position: u64,
+        #[snafu(backtrace)]
+        source: decode::Error,
+    },
+
+    #[snafu(display("Missing basic offset table item at position {}", position))]
+    MissingOffsetTable { position: u64, backtrace: Backtrace },
+
+    #[snafu(display("Undefined length of pixel data item at position {}", position))]
+    UndefinedItemLength { position: u64, backtrace: Backtrace },
+
+    #[snafu(display("Unexpected item delimiter in pixel data at position {}", position))]
+    UnexpectedItemDelimiter { position: u64, backtrace: Backtrace },
+
+    #[snafu(display(
+        "Pixel data ended at position {} without a sequence delimiter",
+        position
+    ))]
+    MissingSequenceDelimiter { position: u64, backtrace: Backtrace },
+
+    #[snafu(display("Could not read pixel data fragment at position {}", position))]
+    ReadFragment {
+        position: u64,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The location of a pixel data fragment in a data source.
+///
+/// The fragment's data is not loaded,
+/// see [`read_from`](FragmentRef::read_from).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FragmentRef {
+    /// the position of the fragment data in the source
+    pub offset: u64,
+    /// the length of the fragment data in bytes
+    pub len: u32,
+}
+
+impl FragmentRef {
+    /// Read the fragment's data from the given source,
+    /// which must be the same source that it was scanned from.
+    pub fn read_from<S>(&self, source: &mut S) -> Result<Vec<u8>>
+    where
+        S: ?Sized + Read + Seek,
+    {
+        source
+            .seek(SeekFrom::Start(self.offset))
+            .context(ReadFragmentSnafu {
+                position: self.offset,
+            })?;
+        // the length may be bogus, so the buffer grows as data arrives
+        let len = self.len as usize;
+        let mut data = Vec::with_capacity(len.min(MAX_VALUE_RESERVE));
+        (&mut *source)
+            .take(u64::from(self.len))
+            .read_to_end(&mut data)
+            .and_then(|got| {
+                if got < len {
+                    Err(std::io::ErrorKind::UnexpectedEof.into())
+                } else {
+                    Ok(())
+                }
+            })
+            .context(ReadFragmentSnafu {
+                position: self.offset,
+            })?;
+        Ok(data)
+    }
+}
+
+/// Read an encapsulated pixel data sequence,
+/// loading the basic offset table and all fragments into memory.
+///
+/// The decoder must be positioned
+/// right after the header of the pixel data element.
+/// On success,
+/// the decoder is positioned right after the sequence delimiter.
+pub fn read_pixel_sequence<D>(decoder: &mut D) -> Result<PixelFragmentSequence<Vec<u8>>>
+where
+    D: StatefulDecode,
+{
+    let offset_table = read_offset_table(decoder)?;
+    let mut fragments = Vec::new();
+    while let Some(len) = next_fragment(decoder)? {
+        let position = decoder.position();
+        let mut data = Vec::new();
+        decoder
+            .read_to_vec(len, &mut data)
+            .context(ReadItemValueSnafu { position })?;
+        fragments.push(data);
+    }
+    Ok(PixelFragmentSequence::new(offset_table, fragments))
+}
+
+/// Scan an encapsulated pixel data sequence,
+/// loading the basic offset table
+/// and recording the location of each fragment
+/// without reading its data.
+///
+/// The decoder must be positioned
+/// right after the header of the pixel data element.
+/// On success,
+/// the decoder is positioned right after the sequence delimiter.
+pub fn scan_pixel_sequence<D>(decoder: &mut D) -> Result<PixelFragmentSequence<FragmentRef>>
+where
+    D: StatefulDecode,
+    D::Reader: Seek,
+{
+    let offset_table = read_offset_table(decoder)?;
+    let mut fragments = Vec::new();
+    while let Some(len) = next_fragment(decoder)? {
+        let offset = decoder.position();
+        decoder
+            .seek_bytes(len)
+            .context(SkipItemValueSnafu { position: offset })?;
+        fragments.push(FragmentRef { offset, len });
+    }
+    Ok(PixelFragmentSequence::new(offset_table, fragments))
+}
+
+/// Read the first item of the pixel sequence as the basic offset table.
+fn read_offset_table<D>(decoder: &mut D) -> Result<Vec<u32>>
+where
+    D: StatefulDecode,
+{
+    let position = decoder.position();
+    match read_item_header(decoder)? {
+        SequenceItemHeader::Item { len } => {
+            let len = len
+                .get()
+                .ok_or_else(|| UndefinedItemLengthSnafu { position }.build())?;
+            let mut offset_table = Vec::new();
+            decoder
+                .read_u32_to_vec(len, &mut offset_table)
+                .context(ReadItemValueSnafu {
+                    position: decoder.position(),
+                })?;
+            Ok(offset_table)
+        }
+        _ => MissingOffsetTableSnafu { position }.fail(),
+    }
+}
+
+/// Read the next item header of the pixel sequence,
+/// returning the length of the fragment that follows,
+/// or `None` at the end of the sequence.
+fn next_fragment<D>(decoder: &mut D) -> Result<Option<u32>>
+where
+    D: StatefulDecode,
+{
+    let position = decoder.position();
+    match read_item_header(decoder)? {
+        SequenceItemHeader::Item { len } => len
+            .get()
+            .map(Some)
+            .ok_or_else(|| UndefinedItemLengthSnafu { position }.build()),
+        SequenceItemHeader::ItemDelimiter => UnexpectedItemDelimiterSnafu { position }.fail(),
+        SequenceItemHeader::SequenceDelimiter => Ok(None),
+    }
+}
+
+fn read_item_header<D>(decoder: &mut D) -> Result<SequenceItemHeader>
+where
+    D: StatefulDecode,
+{
+    let position = decoder.position();
+    decoder.decode_item_header().map_err(|e| match &e {
+        // the source ended before the sequence delimiter
+        decode::Error::DecodeItemHeader {
+            source: dicom_encoding::decode::Error::ReadItemHeader { source, .. },
+            ..
+        } if source.kind() == std::io::ErrorKind::UnexpectedEof => {
+            MissingSequenceDelimiterSnafu { position }.build()
+        }
+        _ => Error::ReadItemHeader {
+            position,
+            source: e,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stateful::decode::StatefulDecoder;
+    use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
+    use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
+    use dicom_encoding::text::SpecificCharacterSet;
+    use std::io::Cursor;
+
+    fn decoder_for(
+        data: &[u8],
+    ) -> StatefulDecoder<ExplicitVRLittleEndianDecoder, Cursor<&[u8]>, LittleEndianBasicDecoder>
+    {
+        StatefulDecoder::new(
+            Cursor::new(data),
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        )
+    }
+
+    // Pixel data items of a single frame JPEG image
+    //  Item: length 0 (empty basic offset table)
+    //  Item: length 8, JPEG SOI ... EOI
+    //  Sequence Delimitation Item
+    const SINGLE_FRAME: &[u8] = &[
+        0xFE, 0xFF, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00, // BOT
+        0xFE, 0xFF, 0x00, 0xE0, 0x08, 0x00, 0x00, 0x00, // fragment
+        0xFF, 0xD8, 0xFF, 0xDB, 0x00, 0x00, 0xFF, 0xD9, //
+        0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00, // delimiter
+    ];
+
+    // Pixel data items of a 2-frame image
+    //  Item: length 8, offsets 0 and 20
+    //  Item: length 4 (frame 0, fragment 0)
+    //  Item: length 4 (frame 0, fragment 1)
+    //  Item: length 2 (frame 1)
+    //  Sequence Delimitation Item
+    const MULTI_FRAME: &[u8] = &[
+        0xFE, 0xFF, 0x00, 0xE0, 0x08, 0x00, 0x00, 0x00, // BOT
+        0x00, 0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00, //
+        0xFE, 0xFF, 0x00, 0xE0, 0x04, 0x00, 0x00, 0x00, // fragment
+        0x01, 0x02, 0x03, 0x04, //
+        0xFE, 0xFF, 0x00, 0xE0, 0x04, 0x00, 0x00, 0x00, // fragment
+        0x05, 0x06, 0x07, 0x08, //
+        0xFE, 0xFF, 0x00, 0xE0, 0x02, 0x00, 0x00, 0x00, // fragment
+        0x09, 0x0A, //
+        0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00, // delimiter
+    ];
+
+    #[test]
+    fn read_with_empty_offset_table() {
+        let mut decoder = decoder_for(SINGLE_FRAME);
+        let seq = read_pixel_sequence(&mut decoder).unwrap();
+        assert!(seq.offset_table().is_empty());
+        assert_eq!(
+            seq.fragments(),
+            &[vec![0xFF, 0xD8, 0xFF, 0xDB, 0x00, 0x00, 0xFF, 0xD9]]
+        );
+        assert_eq!(decoder.position(), SINGLE_FRAME.len() as u64);
+    }
+
+    #[test]
+    fn read_with_offset_table() {
+        let mut decoder = decoder_for(MULTI_FRAME);
+        let seq = read_pixel_sequence(&mut decoder).unwrap();
+        assert_eq!(seq.offset_table(), &[0, 24]);
+        assert_eq!(
+            seq.fragments(),
+            &[vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10]]
+        );
+        assert_eq!(decoder.position(), MULTI_FRAME.len() as u64);
+    }
+
+    #[test]
+    fn scan_and_load_lazily() {
+        let mut decoder = decoder_for(MULTI_FRAME);
+        let seq = scan_pixel_sequence(&mut decoder).unwrap();
+        assert_eq!(seq.offset_table(), &[0, 24]);
+        assert_eq!(
+            seq.fragments(),
+            &[
+                FragmentRef { offset: 24, len: 4 },
+                FragmentRef { offset: 36, len: 4 },
+                FragmentRef { offset: 48, len: 2 },
+            ]
+        );
+        assert_eq!(decoder.position(), MULTI_FRAME.len() as u64);
+
+        let mut source = Cursor::new(MULTI_FRAME);
+        assert_eq!(
+            seq.fragments()[2].read_from(&mut source).unwrap(),
+            vec![9, 10]
+        );
+        assert_eq!(
+            seq.fragments()[0].read_from(&mut source).unwrap(),
+            vec![1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn missing_sequence_delimiter() {
+        let data = &MULTI_FRAME[..MULTI_FRAME.len() - 8];
+        let mut decoder = decoder_for(data);
+        let err = read_pixel_sequence(&mut decoder).unwrap_err();
+        assert!(
+            matches!(err, Error::MissingSequenceDelimiter { position: 50, .. }),
+            "unexpected error {:?}",
+            err
+        );
+
+        let mut decoder = decoder_for(data);
+        let err = scan_pixel_sequence(&mut decoder).unwrap_err();
+        assert!(
+            matches!(err, Error::MissingSequenceDelimiter { position: 50, .. }),
+            "unexpected error {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn missing_offset_table() {
+        let data = &SINGLE_FRAME[SINGLE_FRAME.len() - 8..];
+        let mut decoder = decoder_for(data);
+        let err = read_pixel_sequence(&mut decoder).unwrap_err();
+        assert!(matches!(err, Error::MissingOffsetTable { position: 0, .. }));
+    }
+}
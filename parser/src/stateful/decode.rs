@@ -1,7 +1,7 @@
 //! Module holding a stateful DICOM data decoding abstraction,
 //! which also supports text decoding.
 
-use crate::util::n_times;
+use crate::util::{n_times, CountingRead};
 use dicom_core::dictionary::VirtualVr;
 use dicom_core::header::{DataElementHeader, HasLength, Length, SequenceItemHeader, Tag, VR};
 use dicom_core::value::deserialize::{
@@ -13,12 +13,12 @@ use dicom_encoding::decode::basic::{BasicDecoder, LittleEndianBasicDecoder};
 use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
 use dicom_encoding::decode::{BasicDecode, DecodeFrom};
 use dicom_encoding::text::{
-    validate_da, validate_dt, validate_tm, DefaultCharacterSetCodec, SpecificCharacterSet,
-    TextCodec, TextValidationOutcome,
+    split_values, validate_da, validate_dt, validate_tm, DefaultCharacterSetCodec,
+    SpecificCharacterSet, TextCodec, TextValidationOutcome,
 };
 use dicom_encoding::transfer_syntax::{DynDecoder, TransferSyntax};
-use smallvec::smallvec;
-use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+use std::convert::TryFrom;
 use std::io::Read;
 use std::{fmt::Debug, io::Seek, io::SeekFrom};
 
@@ -51,6 +51,19 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display(
+        "Value length {} of element tagged {} at position {} is too large for this platform",
+        len,
+        tag,
+        position
+    ))]
+    LengthOverflow {
+        tag: Tag,
+        len: u32,
+        position: u64,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Could not decode element header at position {}", position))]
     DecodeElementHeader {
         position: u64,
@@ -79,6 +92,26 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Could not consume value chunk read at position {}", position))]
+    ConsumeValueChunk {
+        position: u64,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Value at position {} ended after {} of {} bytes",
+        position,
+        got,
+        expected
+    ))]
+    TruncatedValue {
+        position: u64,
+        expected: u64,
+        got: u64,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display(
         "Could not move source cursor from position {} to {}",
         position,
@@ -139,6 +172,15 @@ pub trait StatefulDecode {
     /// Same as `Decode::decode_header` over the bound source.
     fn decode_header(&mut self) -> Result<DataElementHeader>;
 
+    /// Same as `Decode::decode_header_with_unknown_vr` over the bound source,
+    /// also retrieving the value representation code of the header
+    /// if it is not known to this library and the element was read as `UN`.
+    ///
+    /// The default implementation never reports an unknown code.
+    fn decode_header_with_unknown_vr(&mut self) -> Result<(DataElementHeader, Option<[u8; 2]>)> {
+        self.decode_header().map(|header| (header, None))
+    }
+
     /// Same as `Decode::decode_item_header` over the bound source.
     fn decode_item_header(&mut self) -> Result<SequenceItemHeader>;
 
@@ -176,6 +218,56 @@ pub trait StatefulDecode {
     /// sequence, which in that case this method should not be used.
     fn read_value_bytes(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue>;
 
+    /// Read the value of the given data element header as raw bytes,
+    /// passing them to `consume` in chunks of at most `chunk_size` bytes.
+    ///
+    /// Unlike the other value reading methods,
+    /// no more than `chunk_size` bytes are held in memory at once,
+    /// so that large values such as native pixel data
+    /// can be hashed or forwarded elsewhere as they are read.
+    /// The bytes are not interpreted in any way.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O problems,
+    /// if `consume` fails,
+    /// or if the header describes a sequence or a value of undefined length.
+    /// The fragments of encapsulated pixel data
+    /// are read in chunks through a data set reader instead.
+    fn read_value_chunked<F>(
+        &mut self,
+        header: &DataElementHeader,
+        chunk_size: u32,
+        mut consume: F,
+    ) -> Result<()>
+    where
+        Self: Sized,
+        F: FnMut(&[u8]) -> std::io::Result<()>,
+    {
+        ensure!(
+            header.vr() != VR::SQ,
+            NonPrimitiveTypeSnafu {
+                position: self.position(),
+            }
+        );
+        let mut remaining = header.length().get().context(UndefinedValueLengthSnafu {
+            tag: header.tag,
+            position: self.position(),
+        })?;
+        let chunk_size = chunk_size.max(1);
+        let mut chunk = Vec::new();
+        while remaining > 0 {
+            let len = remaining.min(chunk_size);
+            chunk.clear();
+            self.read_to_vec(len, &mut chunk)?;
+            consume(&chunk).context(ConsumeValueChunkSnafu {
+                position: self.position() - u64::from(len),
+            })?;
+            remaining -= len;
+        }
+        Ok(())
+    }
+
     /// Read the following number of bytes into a vector.
     fn read_to_vec(&mut self, length: u32, vec: &mut Vec<u8>) -> Result<()>;
 
@@ -194,6 +286,19 @@ pub trait StatefulDecode {
     /// counting them as if they were read.
     fn skip_bytes(&mut self, length: u32) -> Result<()>;
 
+    /// Skip the following bytes by repositioning the reader,
+    /// counting them as if they were read.
+    ///
+    /// Unlike [`skip_bytes`](StatefulDecode::skip_bytes),
+    /// the skipped bytes are not fetched from the source.
+    /// The default implementation falls back to `skip_bytes`.
+    fn seek_bytes(&mut self, length: u32) -> Result<()>
+    where
+        Self::Reader: Seek,
+    {
+        self.skip_bytes(length)
+    }
+
     /// Reposition the reader so that it starts reading
     /// at the reader's given position.
     ///
@@ -206,6 +311,19 @@ pub trait StatefulDecode {
     /// If the stateful decoder was constructed at the beginning of the reader,
     /// this equals to the number of bytes read so far.
     fn position(&self) -> u64;
+
+    /// Enter a data set sequence item.
+    ///
+    /// A _Specific Character Set_ found inside the item
+    /// only applies to the text in that item (and the items nested in it),
+    /// until the matching call to [`end_item`](StatefulDecode::end_item).
+    /// The default implementation does nothing.
+    fn begin_item(&mut self) {}
+
+    /// Leave a data set sequence item,
+    /// restoring the character set of the enclosing data set.
+    /// The default implementation does nothing.
+    fn end_item(&mut self) {}
 }
 
 /// Alias for a dynamically resolved DICOM stateful decoder. Although the data
@@ -216,6 +334,11 @@ pub type DynStatefulDecoder<S> = StatefulDecoder<DynDecoder<S>, S>;
 /// The initial capacity of the `DicomParser` buffer.
 const PARSER_BUFFER_CAPACITY: usize = 2048;
 
+/// The maximum number of bytes reserved for a value before it is read.
+/// Value buffers grow as data arrives beyond this,
+/// so that a bogus value length does not cause a huge allocation.
+pub(crate) const MAX_VALUE_RESERVE: usize = 64 * 1024;
+
 /// A stateful abstraction for the full DICOM content reading process.
 /// This type encapsulates the necessary codecs in order
 /// to be as autonomous as possible in the DICOM content reading
@@ -230,6 +353,9 @@ pub struct StatefulDecoder<D, S, BD = BasicDecoder, TC = SpecificCharacterSet> {
     decoder: D,
     basic: BD,
     text: TC,
+    /// the character sets of the enclosing data sets,
+    /// restored when leaving each sequence item
+    text_scopes: Vec<TC>,
     buffer: Vec<u8>,
     /// the assumed position of the reader source
     position: u64,
@@ -251,7 +377,7 @@ impl<S> StatefulDecoder<DynDecoder<S>, S> {
         let basic = ts.basic_decoder();
         let decoder = ts
             .decoder_for::<S>()
-            .context(UnsupportedTransferSyntaxSnafu { ts: ts.name() })?;
+            .context(UnsupportedTransferSyntaxSnafu { ts: ts.uid() })?;
 
         Ok(StatefulDecoder::new_with_position(
             from, decoder, basic, charset, position,
@@ -271,6 +397,60 @@ impl<S> StatefulDecoder<DynDecoder<S>, S> {
     }
 }
 
+/// A dynamically resolved data element decoder
+/// which can be sent across threads.
+#[cfg(feature = "async")]
+pub(crate) type SendDecoder<S> = Box<dyn DecodeFrom<S> + Send + Sync>;
+
+/// Alias for a DICOM stateful decoder
+/// with a dynamically resolved decoder
+/// which can be sent across threads.
+#[cfg(feature = "async")]
+pub(crate) type SendStatefulDecoder<S> = StatefulDecoder<SendDecoder<S>, S>;
+
+/// Retrieve the data element decoder for the given transfer syntax,
+/// like [`TransferSyntax::decoder_for`],
+/// as a value which can be sent across threads.
+#[cfg(feature = "async")]
+pub(crate) fn send_decoder_for<S>(ts: &TransferSyntax) -> Result<SendDecoder<S>>
+where
+    S: ?Sized + Read,
+{
+    use dicom_encoding::decode::explicit_be::ExplicitVRBigEndianDecoder;
+    use dicom_encoding::decode::implicit_le::ImplicitVRLittleEndianDecoder;
+    use dicom_encoding::Endianness;
+
+    match (ts.endianness(), ts.explicit_vr()) {
+        (Endianness::Little, false) => Ok(Box::<ImplicitVRLittleEndianDecoder<_>>::default()),
+        (Endianness::Little, true) => Ok(Box::<ExplicitVRLittleEndianDecoder>::default()),
+        (Endianness::Big, true) => Ok(Box::<ExplicitVRBigEndianDecoder>::default()),
+        _ => UnsupportedTransferSyntaxSnafu { ts: ts.uid() }.fail(),
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S> SendStatefulDecoder<S>
+where
+    S: Read,
+{
+    /// Create a new DICOM parser for the given transfer syntax, character set,
+    /// and assumed position of the reader source.
+    pub(crate) fn new_send(
+        from: S,
+        ts: &TransferSyntax,
+        charset: SpecificCharacterSet,
+        position: u64,
+    ) -> Result<Self> {
+        Ok(StatefulDecoder::new_with_position(
+            from,
+            send_decoder_for(ts)?,
+            ts.basic_decoder(),
+            charset,
+            position,
+        ))
+    }
+}
+
 /// Type alias for the DICOM parser of a file's Meta group.
 pub type FileHeaderParser<S> = StatefulDecoder<
     ExplicitVRLittleEndianDecoder,
@@ -294,6 +474,7 @@ where
             buffer: Vec::with_capacity(PARSER_BUFFER_CAPACITY),
             position: 0,
             signed_pixeldata: None,
+            text_scopes: Vec::new(),
         }
     }
 }
@@ -325,8 +506,21 @@ where
             buffer: Vec::with_capacity(PARSER_BUFFER_CAPACITY),
             position,
             signed_pixeldata: None,
+            text_scopes: Vec::new(),
         }
     }
+
+    /// Retrieve the source of data.
+    #[cfg(feature = "async")]
+    pub(crate) fn source(&self) -> &S {
+        &self.from
+    }
+
+    /// Retrieve the source of data for modification.
+    #[cfg(feature = "async")]
+    pub(crate) fn source_mut(&mut self) -> &mut S {
+        &mut self.from
+    }
 }
 
 impl<D, S, BD, TC> StatefulDecoder<D, S, BD, TC>
@@ -360,14 +554,15 @@ where
     // ---------------- private methods ---------------------
 
     fn require_known_length(&self, header: &DataElementHeader) -> Result<usize> {
-        header
-            .length()
-            .get()
-            .map(|len| len as usize)
-            .context(UndefinedValueLengthSnafu {
-                position: self.position,
-                tag: header.tag,
-            })
+        let len = header.length().get().context(UndefinedValueLengthSnafu {
+            position: self.position,
+            tag: header.tag,
+        })?;
+        checked_value_len(len, isize::MAX as usize).context(LengthOverflowSnafu {
+            tag: header.tag,
+            len,
+            position: self.position,
+        })
     }
 
     fn read_value_tag(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
@@ -375,17 +570,16 @@ where
 
         // tags
         let ntags = len >> 2;
-        let parts: Result<_> = n_times(ntags)
-            .map(|_| {
-                self.basic
-                    .decode_tag(&mut self.from)
-                    .context(ReadValueDataSnafu {
-                        position: self.position,
-                    })
-            })
-            .collect();
+        let basic = &self.basic;
+        let mut parts = Vec::new();
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            for _ in n_times(ntags) {
+                parts.push(basic.decode_tag(&mut *from)?);
+            }
+            Ok(())
+        })?;
         self.position += len as u64;
-        Ok(PrimitiveValue::Tags(parts?))
+        Ok(PrimitiveValue::Tags(parts.into()))
     }
 
     fn read_value_ob(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
@@ -394,23 +588,19 @@ where
         let len = self.require_known_length(header)?;
 
         // sequence of 8-bit integers (or arbitrary byte data)
-        let mut buf = smallvec![0u8; len];
-        self.from.read_exact(&mut buf).context(ReadValueDataSnafu {
-            position: self.position,
-        })?;
+        let mut buf = Vec::new();
+        read_value_to_vec(&mut self.from, len, &mut buf, self.position)?;
         self.position += len as u64;
-        Ok(PrimitiveValue::U8(buf))
+        Ok(PrimitiveValue::U8(buf.into()))
     }
 
     fn read_value_strs(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
         let len = self.require_known_length(header)?;
         // sequence of strings
-        self.buffer.resize_with(len, Default::default);
-        self.from
-            .read_exact(&mut self.buffer)
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let position = self.position;
+        read_value_to_vec(&mut self.from, len, &mut self.buffer, position)?;
+        // the value is consumed even if it cannot be decoded
+        self.position += len as u64;
 
         let parts: Result<_> = match header.vr() {
             VR::AE | VR::CS | VR::AS => self
@@ -419,23 +609,27 @@ where
                 .map(|slice| {
                     DefaultCharacterSetCodec
                         .decode(slice)
-                        .context(DecodeTextSnafu {
-                            position: self.position,
-                        })
+                        .context(DecodeTextSnafu { position })
                 })
                 .collect(),
-            _ => self
-                .buffer
-                .split(|v| *v == b'\\')
+            // component groups of person names may be in different code elements
+            VR::PN => split_values(&self.text, &self.buffer)
+                .map(|slice| {
+                    self.text
+                        .decode_person_name(slice)
+                        .context(DecodeTextSnafu { position })
+                })
+                .collect(),
+            // multi-byte characters may contain backslash bytes
+            _ => split_values(&self.text, &self.buffer)
                 .map(|slice| {
-                    self.text.decode(slice).context(DecodeTextSnafu {
-                        position: self.position,
-                    })
+                    self.text
+                        .decode(slice)
+                        .context(DecodeTextSnafu { position })
                 })
                 .collect(),
         };
 
-        self.position += len as u64;
         Ok(PrimitiveValue::Strs(parts?))
     }
 
@@ -443,12 +637,7 @@ where
         let len = self.require_known_length(header)?;
 
         // a single string
-        self.buffer.resize_with(len, Default::default);
-        self.from
-            .read_exact(&mut self.buffer)
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        read_value_to_vec(&mut self.from, len, &mut self.buffer, self.position)?;
         self.position += len as u64;
         Ok(PrimitiveValue::Str(
             self.text
@@ -464,41 +653,40 @@ where
         let len = self.require_known_length(header)?;
 
         let n = len >> 1;
-        let mut vec = smallvec![0; n];
-        self.basic
-            .decode_ss_into(&mut self.from, &mut vec[..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let mut vec = Vec::new();
+        let basic = &self.basic;
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            decode_values_into(from, n, &mut vec, |from, out| {
+                basic.decode_ss_into(from, out)
+            })
+        })?;
 
         self.position += len as u64;
-        Ok(PrimitiveValue::I16(vec))
+        Ok(PrimitiveValue::I16(vec.into()))
     }
 
     fn read_value_fl(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
         let len = self.require_known_length(header)?;
         // sequence of 32-bit floats
         let n = len >> 2;
-        let mut vec = smallvec![0.; n];
-        self.basic
-            .decode_fl_into(&mut self.from, &mut vec[..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let mut vec = Vec::new();
+        let basic = &self.basic;
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            decode_values_into(from, n, &mut vec, |from, out| {
+                basic.decode_fl_into(from, out)
+            })
+        })?;
         self.position += len as u64;
-        Ok(PrimitiveValue::F32(vec))
+        Ok(PrimitiveValue::F32(vec.into()))
     }
 
     fn read_value_da(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
         let len = self.require_known_length(header)?;
         // sequence of dates
 
-        self.buffer.resize_with(len, Default::default);
-        self.from
-            .read_exact(&mut self.buffer)
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let position = self.position;
+        read_value_to_vec(&mut self.from, len, &mut self.buffer, position)?;
+        self.position += len as u64;
         let buf = trim_trail_empty_bytes(&self.buffer);
         if buf.is_empty() {
             return Ok(PrimitiveValue::Empty);
@@ -509,7 +697,7 @@ where
                 .decode(buf)
                 .unwrap_or_else(|_| "[byte stream]".to_string());
             return InvalidDateValueSnafu {
-                position: self.position,
+                position,
                 string: lossy_str,
             }
             .fail();
@@ -519,12 +707,9 @@ where
             .map(|part| {
                 parse_date_partial(part)
                     .map(|t| t.0)
-                    .context(DeserializeValueSnafu {
-                        position: self.position,
-                    })
+                    .context(DeserializeValueSnafu { position })
             })
             .collect();
-        self.position += len as u64;
         Ok(PrimitiveValue::Date(vec?))
     }
 
@@ -532,12 +717,9 @@ where
         let len = self.require_known_length(header)?;
         // sequence of doubles in text form
 
-        self.buffer.resize_with(len, Default::default);
-        self.from
-            .read_exact(&mut self.buffer)
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let position = self.position;
+        read_value_to_vec(&mut self.from, len, &mut self.buffer, position)?;
+        self.position += len as u64;
         let buf = trim_trail_empty_bytes(&self.buffer);
         if buf.is_empty() {
             return Ok(PrimitiveValue::Empty);
@@ -547,16 +729,11 @@ where
             .split(|b| *b == b'\\')
             .map(|slice| {
                 let codec = DefaultCharacterSetCodec;
-                let txt = codec.decode(slice).context(DecodeTextSnafu {
-                    position: self.position,
-                })?;
+                let txt = codec.decode(slice).context(DecodeTextSnafu { position })?;
                 let txt = txt.trim();
-                txt.parse::<f64>().context(ReadFloatSnafu {
-                    position: self.position,
-                })
+                txt.parse::<f64>().context(ReadFloatSnafu { position })
             })
             .collect();
-        self.position += len as u64;
         Ok(PrimitiveValue::F64(parts?))
     }
 
@@ -564,12 +741,9 @@ where
         let len = self.require_known_length(header)?;
         // sequence of datetimes
 
-        self.buffer.resize_with(len, Default::default);
-        self.from
-            .read_exact(&mut self.buffer)
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let position = self.position;
+        read_value_to_vec(&mut self.from, len, &mut self.buffer, position)?;
+        self.position += len as u64;
         let buf = trim_trail_empty_bytes(&self.buffer);
         if buf.is_empty() {
             return Ok(PrimitiveValue::Empty);
@@ -580,33 +754,25 @@ where
                 .decode(buf)
                 .unwrap_or_else(|_| "[byte stream]".to_string());
             return InvalidDateTimeValueSnafu {
-                position: self.position,
+                position,
                 string: lossy_str,
             }
             .fail();
         }
         let vec: Result<_> = buf
             .split(|b| *b == b'\\')
-            .map(|part| {
-                parse_datetime_partial(part).context(DeserializeValueSnafu {
-                    position: self.position,
-                })
-            })
+            .map(|part| parse_datetime_partial(part).context(DeserializeValueSnafu { position }))
             .collect();
 
-        self.position += len as u64;
         Ok(PrimitiveValue::DateTime(vec?))
     }
 
     fn read_value_is(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
         let len = self.require_known_length(header)?;
         // sequence of signed integers in text form
-        self.buffer.resize_with(len, Default::default);
-        self.from
-            .read_exact(&mut self.buffer)
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let position = self.position;
+        read_value_to_vec(&mut self.from, len, &mut self.buffer, position)?;
+        self.position += len as u64;
         let buf = trim_trail_empty_bytes(&self.buffer);
         if buf.is_empty() {
             return Ok(PrimitiveValue::Empty);
@@ -616,16 +782,11 @@ where
             .split(|v| *v == b'\\')
             .map(|slice| {
                 let codec = DefaultCharacterSetCodec;
-                let txt = codec.decode(slice).context(DecodeTextSnafu {
-                    position: self.position,
-                })?;
+                let txt = codec.decode(slice).context(DecodeTextSnafu { position })?;
                 let txt = txt.trim();
-                txt.parse::<i32>().context(ReadIntSnafu {
-                    position: self.position,
-                })
+                txt.parse::<i32>().context(ReadIntSnafu { position })
             })
             .collect();
-        self.position += len as u64;
         Ok(PrimitiveValue::I32(parts?))
     }
 
@@ -633,12 +794,9 @@ where
         let len = self.require_known_length(header)?;
         // sequence of time instances
 
-        self.buffer.resize_with(len, Default::default);
-        self.from
-            .read_exact(&mut self.buffer)
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let position = self.position;
+        read_value_to_vec(&mut self.from, len, &mut self.buffer, position)?;
+        self.position += len as u64;
         let buf = trim_trail_empty_bytes(&self.buffer);
         if buf.is_empty() {
             return Ok(PrimitiveValue::Empty);
@@ -649,7 +807,7 @@ where
                 .decode(buf)
                 .unwrap_or_else(|_| "[byte stream]".to_string());
             return InvalidTimeValueSnafu {
-                position: self.position,
+                position,
                 string: lossy_str,
             }
             .fail();
@@ -659,12 +817,9 @@ where
             .map(|part| {
                 parse_time_partial(part)
                     .map(|t| t.0)
-                    .context(DeserializeValueSnafu {
-                        position: self.position,
-                    })
+                    .context(DeserializeValueSnafu { position })
             })
             .collect();
-        self.position += len as u64;
         Ok(PrimitiveValue::Time(vec?))
     }
 
@@ -672,14 +827,15 @@ where
         let len = self.require_known_length(header)?;
         // sequence of 64-bit floats
         let n = len >> 3;
-        let mut vec = smallvec![0.; n];
-        self.basic
-            .decode_fd_into(&mut self.from, &mut vec[..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let mut vec = Vec::new();
+        let basic = &self.basic;
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            decode_values_into(from, n, &mut vec, |from, out| {
+                basic.decode_fd_into(from, out)
+            })
+        })?;
         self.position += len as u64;
-        Ok(PrimitiveValue::F64(vec))
+        Ok(PrimitiveValue::F64(vec.into()))
     }
 
     fn read_value_ul(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
@@ -687,25 +843,22 @@ where
         // sequence of 32-bit unsigned integers
 
         let n = len >> 2;
-        let mut vec = smallvec![0u32; n];
-        self.basic
-            .decode_ul_into(&mut self.from, &mut vec[..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let mut vec = Vec::new();
+        let basic = &self.basic;
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            decode_values_into(from, n, &mut vec, |from, out| {
+                basic.decode_ul_into(from, out)
+            })
+        })?;
         self.position += len as u64;
-        Ok(PrimitiveValue::U32(vec))
+        Ok(PrimitiveValue::U32(vec.into()))
     }
 
     fn read_u32(&mut self, n: usize, vec: &mut Vec<u32>) -> Result<()> {
-        let base = vec.len();
-        vec.resize(base + n, 0);
-
-        self.basic
-            .decode_ul_into(&mut self.from, &mut vec[base..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let basic = &self.basic;
+        read_value_with(&mut self.from, n as u64 * 4, self.position, |from| {
+            decode_values_into(from, n, vec, |from, out| basic.decode_ul_into(from, out))
+        })?;
         self.position += n as u64 * 4;
         Ok(())
     }
@@ -715,21 +868,22 @@ where
         // sequence of 16-bit unsigned integers
 
         let n = len >> 1;
-        let mut vec = smallvec![0; n];
-        self.basic
-            .decode_us_into(&mut self.from, &mut vec[..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let mut vec = Vec::new();
+        let basic = &self.basic;
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            decode_values_into(from, n, &mut vec, |from, out| {
+                basic.decode_us_into(from, out)
+            })
+        })?;
 
         self.position += len as u64;
 
         if header.tag == Tag(0x0028, 0x0103) {
             //Pixel Representation is not 0, so 2s complement (signed)
-            self.signed_pixeldata = Some(vec[0] != 0);
+            self.signed_pixeldata = vec.first().map(|v| *v != 0);
         }
 
-        Ok(PrimitiveValue::U16(vec))
+        Ok(PrimitiveValue::U16(vec.into()))
     }
 
     fn read_value_uv(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
@@ -737,14 +891,15 @@ where
         // sequence of 64-bit unsigned integers
 
         let n = len >> 3;
-        let mut vec = smallvec![0; n];
-        self.basic
-            .decode_uv_into(&mut self.from, &mut vec[..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let mut vec = Vec::new();
+        let basic = &self.basic;
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            decode_values_into(from, n, &mut vec, |from, out| {
+                basic.decode_uv_into(from, out)
+            })
+        })?;
         self.position += len as u64;
-        Ok(PrimitiveValue::U64(vec))
+        Ok(PrimitiveValue::U64(vec.into()))
     }
 
     fn read_value_sl(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
@@ -752,14 +907,15 @@ where
         // sequence of 32-bit signed integers
 
         let n = len >> 2;
-        let mut vec = smallvec![0; n];
-        self.basic
-            .decode_sl_into(&mut self.from, &mut vec[..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let mut vec = Vec::new();
+        let basic = &self.basic;
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            decode_values_into(from, n, &mut vec, |from, out| {
+                basic.decode_sl_into(from, out)
+            })
+        })?;
         self.position += len as u64;
-        Ok(PrimitiveValue::I32(vec))
+        Ok(PrimitiveValue::I32(vec.into()))
     }
 
     fn read_value_sv(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
@@ -767,14 +923,15 @@ where
         // sequence of 64-bit signed integers
 
         let n = len >> 3;
-        let mut vec = smallvec![0; n];
-        self.basic
-            .decode_sv_into(&mut self.from, &mut vec[..])
-            .context(ReadValueDataSnafu {
-                position: self.position,
-            })?;
+        let mut vec = Vec::new();
+        let basic = &self.basic;
+        read_value_with(&mut self.from, len as u64, self.position, |from| {
+            decode_values_into(from, n, &mut vec, |from, out| {
+                basic.decode_sv_into(from, out)
+            })
+        })?;
         self.position += len as u64;
-        Ok(PrimitiveValue::I64(vec))
+        Ok(PrimitiveValue::I64(vec.into()))
     }
 }
 
@@ -802,17 +959,11 @@ where
 
         // if it's a Specific Character Set, update the decoder immediately.
         if header.tag == Tag(0x0008, 0x0005) {
-            // Edge case handling strategies for
-            // unsupported specific character sets should probably be considered
-            // in the future. See #40 for discussion.
-            if let Some(charset) = parts.first().map(|x| x.as_ref()).and_then(|name| {
-                SpecificCharacterSet::from_code(name).or_else(|| {
-                    tracing::warn!("Unsupported character set `{}`, ignoring", name);
-                    None
-                })
-            }) {
-                self.set_character_set(charset)?;
-            }
+            // text in unsupported character sets is decoded as ISO IR 100,
+            // so that reading can carry on
+            // (the data set reader reports the fallback)
+            let charset = SpecificCharacterSet::from_values(parts.iter());
+            self.set_character_set(charset)?;
         }
 
         Ok(out)
@@ -829,6 +980,10 @@ where
         (**self).decode_header()
     }
 
+    fn decode_header_with_unknown_vr(&mut self) -> Result<(DataElementHeader, Option<[u8; 2]>)> {
+        (**self).decode_header_with_unknown_vr()
+    }
+
     fn decode_item_header(&mut self) -> Result<SequenceItemHeader> {
         (**self).decode_item_header()
     }
@@ -865,6 +1020,13 @@ where
         (**self).skip_bytes(length)
     }
 
+    fn seek_bytes(&mut self, length: u32) -> Result<()>
+    where
+        Self::Reader: Seek,
+    {
+        (**self).seek_bytes(length)
+    }
+
     fn position(&self) -> u64 {
         (**self).position()
     }
@@ -875,6 +1037,14 @@ where
     {
         (**self).seek(position)
     }
+
+    fn begin_item(&mut self) {
+        (**self).begin_item()
+    }
+
+    fn end_item(&mut self) {
+        (**self).end_item()
+    }
 }
 
 impl<D, S, BD> StatefulDecode for StatefulDecoder<D, S, BD>
@@ -886,17 +1056,21 @@ where
     type Reader = S;
 
     fn decode_header(&mut self) -> Result<DataElementHeader> {
-        let mut header = self
+        self.decode_header_with_unknown_vr()
+            .map(|(header, _)| header)
+    }
+
+    fn decode_header_with_unknown_vr(&mut self) -> Result<(DataElementHeader, Option<[u8; 2]>)> {
+        let (mut header, unknown_vr) = self
             .decoder
-            .decode_header(&mut self.from)
+            .decode_header_with_unknown_vr(&mut self.from)
             .context(DecodeElementHeaderSnafu {
                 position: self.position,
             })
-            .map(|(header, bytes_read)| {
+            .map(|(header, bytes_read, unknown_vr)| {
                 self.position += bytes_read as u64;
-                header
-            })
-            .map_err(From::from)?;
+                (header, unknown_vr)
+            })?;
 
         //If we are decoding the PixelPadding element, make sure the VR is the same as the pixel
         //representation (US by default, SS for signed data).
@@ -904,7 +1078,7 @@ where
             header.vr = vr;
         }
 
-        Ok(header)
+        Ok((header, unknown_vr))
     }
 
     fn decode_item_header(&mut self) -> Result<SequenceItemHeader> {
@@ -1018,12 +1192,33 @@ where
         self.position
     }
 
+    fn begin_item(&mut self) {
+        self.text_scopes.push(self.text.clone());
+    }
+
+    fn end_item(&mut self) {
+        if let Some(text) = self.text_scopes.pop() {
+            self.text = text;
+        }
+    }
+
     fn read_to_vec(&mut self, length: u32, vec: &mut Vec<u8>) -> Result<()> {
+        let len = checked_value_len(length, isize::MAX as usize).context(LengthOverflowSnafu {
+            tag: Tag(0xFFFE, 0xE000),
+            len: length,
+            position: self.position,
+        })?;
+        vec.reserve(len.min(MAX_VALUE_RESERVE));
         self.read_to(length, vec)
     }
 
     fn read_u32_to_vec(&mut self, length: u32, vec: &mut Vec<u32>) -> Result<()> {
-        self.read_u32((length >> 2) as usize, vec)
+        let len = checked_value_len(length, isize::MAX as usize).context(LengthOverflowSnafu {
+            tag: Tag(0xFFFE, 0xE000),
+            len: length,
+            position: self.position,
+        })?;
+        self.read_u32(len >> 2, vec)
     }
 
     fn read_to<W>(&mut self, length: u32, mut out: W) -> Result<()>
@@ -1032,25 +1227,54 @@ where
         W: std::io::Write,
     {
         let length = u64::from(length);
-        std::io::copy(&mut self.from.by_ref().take(length), &mut out).context(
+        let got = std::io::copy(&mut self.from.by_ref().take(length), &mut out).context(
             ReadValueDataSnafu {
                 position: self.position,
             },
         )?;
+        ensure!(
+            got == length,
+            TruncatedValueSnafu {
+                position: self.position,
+                expected: length,
+                got,
+            }
+        );
         self.position += length;
         Ok(())
     }
 
     fn skip_bytes(&mut self, length: u32) -> Result<()> {
-        std::io::copy(
-            &mut self.from.by_ref().take(u64::from(length)),
-            &mut std::io::sink(),
-        )
-        .context(ReadValueDataSnafu {
-            position: self.position,
-        })?;
+        let length = u64::from(length);
+        let got = std::io::copy(&mut self.from.by_ref().take(length), &mut std::io::sink())
+            .context(ReadValueDataSnafu {
+                position: self.position,
+            })?;
+        ensure!(
+            got == length,
+            TruncatedValueSnafu {
+                position: self.position,
+                expected: length,
+                got,
+            }
+        );
 
-        self.position += u64::from(length);
+        self.position += length;
+        Ok(())
+    }
+
+    fn seek_bytes(&mut self, length: u32) -> Result<()>
+    where
+        Self::Reader: Seek,
+    {
+        let new_position = self.position + u64::from(length);
+        self.from
+            .seek(SeekFrom::Current(i64::from(length)))
+            .context(SeekReaderSnafu {
+                position: self.position,
+                new_position,
+            })?;
+        self.position = new_position;
         Ok(())
     }
 
@@ -1090,6 +1314,101 @@ where
     }
 }
 
+/// Convert a value length into the size of an in-memory buffer,
+/// provided that no allocation may exceed `max_alloc` bytes.
+///
+/// The limit is a parameter so that the behavior on targets
+/// with a narrower `usize` can be verified on any target.
+fn checked_value_len(len: u32, max_alloc: usize) -> Option<usize> {
+    usize::try_from(len).ok().filter(|&len| len <= max_alloc)
+}
+
+/// Read a value of `len` bytes from the source with the given function,
+/// reporting a truncated value if the source ends early.
+///
+/// Any bytes which the function did not consume
+/// (such as the trailing bytes of a binary value
+/// whose length is not a multiple of its element size)
+/// are skipped, so that the source remains aligned
+/// with the next data element.
+fn read_value_with<S, T, F>(from: &mut S, len: u64, position: u64, f: F) -> Result<T>
+where
+    S: ?Sized + Read,
+    F: FnOnce(&mut CountingRead<S>) -> std::io::Result<T>,
+{
+    let mut from = CountingRead::new(from);
+    let value = f(&mut from).and_then(|value| {
+        let remaining = len.saturating_sub(from.count());
+        let skipped = std::io::copy(&mut (&mut from).take(remaining), &mut std::io::sink())?;
+        if skipped < remaining {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(value)
+    });
+    match value {
+        Ok(value) => Ok(value),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => TruncatedValueSnafu {
+            position,
+            expected: len,
+            got: from.count(),
+        }
+        .fail(),
+        Err(e) => Err(e).context(ReadValueDataSnafu { position }),
+    }
+}
+
+/// Read a value of `len` bytes into `buf`, replacing its contents,
+/// reporting a truncated value if the source ends early.
+///
+/// No more than [`MAX_VALUE_RESERVE`] bytes are reserved up front,
+/// the buffer grows as data arrives.
+fn read_value_to_vec<S>(from: &mut S, len: usize, buf: &mut Vec<u8>, position: u64) -> Result<()>
+where
+    S: ?Sized + Read,
+{
+    buf.clear();
+    buf.reserve(len.min(MAX_VALUE_RESERVE));
+    let got = (&mut *from)
+        .take(len as u64)
+        .read_to_end(buf)
+        .context(ReadValueDataSnafu { position })?;
+    ensure!(
+        got == len,
+        TruncatedValueSnafu {
+            position,
+            expected: len as u64,
+            got: got as u64,
+        }
+    );
+    Ok(())
+}
+
+/// Decode `n` more values into `values` with the given function,
+/// which fills a slice of values from the source.
+///
+/// The values are decoded in chunks of up to [`MAX_VALUE_RESERVE`] bytes,
+/// so that memory grows as data arrives.
+fn decode_values_into<S, T, F>(
+    from: &mut S,
+    n: usize,
+    values: &mut Vec<T>,
+    mut decode: F,
+) -> std::io::Result<()>
+where
+    S: ?Sized,
+    T: Clone + Default,
+    F: FnMut(&mut S, &mut [T]) -> std::io::Result<()>,
+{
+    let chunk = (MAX_VALUE_RESERVE / std::mem::size_of::<T>()).max(1);
+    let end = values.len() + n;
+    while values.len() < end {
+        let start = values.len();
+        values.resize(end.min(start + chunk), T::default());
+        decode(from, &mut values[start..])?;
+    }
+    Ok(())
+}
+
 /// Remove trailing spaces and null characters.
 fn trim_trail_empty_bytes(mut x: &[u8]) -> &[u8] {
     while x.last() == Some(&b' ') || x.last() == Some(&b'\0') {
@@ -1100,9 +1419,9 @@ fn trim_trail_empty_bytes(mut x: &[u8]) -> &[u8] {
 
 #[cfg(test)]
 mod tests {
-    use super::{StatefulDecode, StatefulDecoder};
+    use super::{checked_value_len, StatefulDecode, StatefulDecoder};
     use dicom_core::header::{DataElementHeader, HasLength, Header, Length, SequenceItemHeader};
-    use dicom_core::{Tag, VR};
+    use dicom_core::{PrimitiveValue, Tag, VR};
     use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
     use dicom_encoding::decode::{
         explicit_le::ExplicitVRLittleEndianDecoder, implicit_le::ImplicitVRLittleEndianDecoder,
@@ -1197,6 +1516,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn seek_bytes_skips_value() {
+        let mut cursor = Cursor::new(&RAW[..]);
+        let mut decoder = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+
+        let elem = decoder.decode_header().expect("should find an element");
+        assert_eq!(elem.tag(), Tag(2, 2));
+
+        // skip the value without reading it
+        decoder.seek_bytes(26).unwrap();
+        assert_eq!(decoder.position(), 8 + 26);
+
+        let elem = decoder.decode_header().expect("should find an element");
+        assert_eq!(elem.tag(), Tag(2, 16));
+        assert_eq!(decoder.position(), 8 + 26 + 8);
+        drop(decoder);
+        assert_eq!(cursor.stream_position().unwrap(), 8 + 26 + 8);
+    }
+
     /// Test that the stateful decoder updates
     /// the active character set after reaching a Specific Character Set element
     /// with a supported text encoding.
@@ -1240,6 +1583,119 @@ mod tests {
         assert_eq!(decoder.text.name(), "ISO_IR 192",);
     }
 
+    /// Text values are read the same way
+    /// whether or not they go through the text codec.
+    #[test]
+    fn decode_ascii_and_non_ascii_text_values() {
+        let mut raw = vec![
+            // Tag: (0008,0008) Image Type, VR: CS, Length: 16
+            0x08, 0x00, 0x08, 0x00, b'C', b'S', 0x10, 0x00,
+        ];
+        raw.extend_from_slice(b"ORIGINAL\\PRIMARY");
+        // Tag: (0008,0018) SOP Instance UID, VR: UI, Length: 26
+        raw.extend_from_slice(&[0x08, 0x00, 0x18, 0x00, b'U', b'I', 0x1a, 0x00]);
+        raw.extend_from_slice(b"1.2.840.10008.5.1.4.1.1.1\0");
+        // Tag: (0010,0010) Patient Name, VR: PN, Length: 10
+        raw.extend_from_slice(&[0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x0a, 0x00]);
+        raw.extend_from_slice("Simões^Jo".as_bytes());
+
+        let mut cursor = &raw[..];
+        let mut decoder = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::ISO_IR_192,
+        );
+
+        let header = decoder.decode_header().unwrap();
+        let value = decoder.read_value(&header).unwrap();
+        match &value {
+            PrimitiveValue::Strs(strs) => {
+                assert!(!strs.spilled());
+                assert_eq!(&strs[..], ["ORIGINAL", "PRIMARY"]);
+            }
+            value => panic!("unexpected value {:?}", value),
+        }
+
+        let header = decoder.decode_header().unwrap();
+        let value = decoder.read_value(&header).unwrap();
+        let strs = value.strings().unwrap();
+        assert_eq!(strs, ["1.2.840.10008.5.1.4.1.1.1\0"]);
+
+        // non-ASCII text decoded through the character set
+        let header = decoder.decode_header().unwrap();
+        let value = decoder.read_value(&header).unwrap();
+        let strs = value.strings().unwrap();
+        assert_eq!(strs, ["Simões^Jo"]);
+    }
+
+    /// Build an explicit VR little endian data set
+    /// with a Specific Character Set and a Patient Name.
+    fn charset_and_name(charset: &[u8], name: &[u8]) -> Vec<u8> {
+        let mut raw = vec![
+            0x08,
+            0x00,
+            0x05,
+            0x00,
+            b'C',
+            b'S',
+            charset.len() as u8,
+            0x00,
+        ];
+        raw.extend_from_slice(charset);
+        raw.extend_from_slice(&[0x10, 0x00, 0x10, 0x00, b'P', b'N', name.len() as u8, 0x00]);
+        raw.extend_from_slice(name);
+        raw
+    }
+
+    fn read_patient_name(raw: &[u8]) -> String {
+        let mut cursor = raw;
+        let mut decoder = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let header = decoder.decode_header().unwrap();
+        decoder.read_value(&header).unwrap();
+        let header = decoder.decode_header().unwrap();
+        assert_eq!(header.tag, Tag(0x0010, 0x0010));
+        decoder.read_value(&header).unwrap().to_str().to_string()
+    }
+
+    #[test]
+    fn decode_text_in_specific_character_set() {
+        let raw = charset_and_name(b"ISO_IR 100", b"M\xfcller^Hans ");
+        assert_eq!(read_patient_name(&raw), "Müller^Hans");
+
+        let raw = charset_and_name(
+            b"ISO_IR 144",
+            b"\xb8\xd2\xd0\xdd\xda\xde\xd2^\xb0\xdd\xd4\xe0\xd5\xd9",
+        );
+        assert_eq!(read_patient_name(&raw), "Иванков^Андрей");
+
+        let raw = charset_and_name(b"ISO_IR 192", "Simões^João ".as_bytes());
+        assert_eq!(read_patient_name(&raw), "Simões^João");
+
+        // code extensions in a multi-valued character set
+        let raw = charset_and_name(
+            b"\\ISO 2022 IR 87 ",
+            b"Yamada^Tarou=\x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B=\x1b$B$d$^$@\x1b(B^\x1b$B$?$m$&\x1b(B",
+        );
+        assert_eq!(
+            read_patient_name(&raw),
+            "Yamada^Tarou=山田^太郎=やまだ^たろう"
+        );
+
+        // backslash bytes inside of multi-byte characters
+        let raw = charset_and_name(b"GB18030 ", b"\x9c\\\\\xcd\xf5");
+        assert_eq!(read_patient_name(&raw), "淺\\王");
+
+        // unsupported character sets do not fail the read
+        let raw = charset_and_name(b"ISO_IR 999", b"M\xfcller^Hans ");
+        assert_eq!(read_patient_name(&raw), "Müller^Hans");
+    }
+
     #[test]
     fn decode_data_elements_with_position() {
         let data = {
@@ -1566,4 +2022,219 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn decode_odd_length_binary_values() {
+        #[rustfmt::skip]
+        const RAW: &[u8] = &[
+            // (0028,0103) PixelRepresentation, US, length 1
+            0x28, 0x00, 0x03, 0x01, b'U', b'S', 0x01, 0x00,
+            0x01,
+            // (0028,0010) Rows, US, length 3
+            0x28, 0x00, 0x10, 0x00, b'U', b'S', 0x03, 0x00,
+            0x00, 0x02, 0xFF,
+            // (0028,0011) Columns, US, length 2
+            0x28, 0x00, 0x11, 0x00, b'U', b'S', 0x02, 0x00,
+            0x00, 0x01,
+        ];
+
+        let mut cursor = RAW;
+        let mut decoder = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+
+        // a value too short to hold a single number yields no numbers
+        let header = decoder.decode_header().unwrap();
+        assert_eq!(header.length(), Length(1));
+        let value = decoder.read_value(&header).unwrap();
+        assert_eq!(value.multiplicity(), 0);
+        assert_eq!(decoder.position(), 9);
+
+        // trailing bytes are skipped
+        let header = decoder.decode_header().unwrap();
+        assert_eq!(header.tag(), Tag(0x0028, 0x0010));
+        let value = decoder.read_value(&header).unwrap();
+        assert_eq!(value, PrimitiveValue::from(0x0200_u16));
+        assert_eq!(decoder.position(), 20);
+
+        // and the next element is read in full
+        let header = decoder.decode_header().unwrap();
+        assert_eq!(header.tag(), Tag(0x0028, 0x0011));
+        let value = decoder.read_value(&header).unwrap();
+        assert_eq!(value, PrimitiveValue::from(0x0100_u16));
+        assert_eq!(decoder.position(), 30);
+    }
+
+    #[test]
+    fn checked_value_len_near_overflow() {
+        // allocations on 32-bit targets are limited to `i32::MAX` bytes
+        let max_alloc_32 = i32::MAX as usize;
+        assert_eq!(checked_value_len(0xFFFF_FFF0, max_alloc_32), None);
+        assert_eq!(checked_value_len(0x8000_0000, max_alloc_32), None);
+        assert_eq!(
+            checked_value_len(0x7FFF_FFFE, max_alloc_32),
+            Some(0x7FFF_FFFE)
+        );
+        assert_eq!(
+            checked_value_len(0xFFFF_FFF0, u32::MAX as usize),
+            Some(0xFFFF_FFF0)
+        );
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn read_value_length_overflow() {
+        // (0009,0010) OB with a length of 0xFFFF_FFF0, but only 4 bytes of data
+        #[rustfmt::skip]
+        let raw: &[u8] = &[
+            0x09, 0x00, 0x10, 0x00, b'O', b'B', 0x00, 0x00,
+            0xF0, 0xFF, 0xFF, 0xFF,
+            0x01, 0x02, 0x03, 0x04,
+        ];
+        let mut cursor = raw;
+        let mut decoder = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let header = decoder.decode_header().unwrap();
+        assert_eq!(header.length(), Length(0xFFFF_FFF0));
+        let err = decoder.read_value(&header).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                super::Error::LengthOverflow {
+                    len: 0xFFFF_FFF0,
+                    position: 12,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    /// Compute the FNV-1a hash of the given bytes incrementally,
+    /// starting from `hash`.
+    fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+        bytes.iter().fold(hash, |hash, &b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+    #[test]
+    fn read_value_chunked_large_value() {
+        const LEN: u32 = 5 * 1024 * 1024 + 6;
+        const CHUNK_SIZE: u32 = 64 * 1024;
+
+        let value: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+        // (7FE0,0010) OB, followed by (0008,0060) CS "MR"
+        let mut raw = vec![0xE0, 0x7F, 0x10, 0x00, b'O', b'B', 0x00, 0x00];
+        raw.extend_from_slice(&LEN.to_le_bytes());
+        raw.extend_from_slice(&value);
+        raw.extend_from_slice(&[0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R']);
+
+        let mut cursor = &raw[..];
+        let mut decoder = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let header = decoder.decode_header().unwrap();
+        assert_eq!(header.length(), Length(LEN));
+
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut chunks = 0;
+        decoder
+            .read_value_chunked(&header, CHUNK_SIZE, |chunk| {
+                assert!(chunk.len() <= CHUNK_SIZE as usize);
+                hash = fnv1a(hash, chunk);
+                chunks += 1;
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(hash, fnv1a(FNV_OFFSET_BASIS, &value));
+        assert_eq!(chunks, 81);
+        assert_eq!(decoder.position(), 12 + u64::from(LEN));
+
+        // the source is left at the next element
+        let header = decoder.decode_header().unwrap();
+        assert_eq!(header.tag, Tag(0x0008, 0x0060));
+    }
+
+    #[test]
+    fn read_value_chunked_errors() {
+        // (0009,1010) OB of length 100, but only 10 bytes of data
+        #[rustfmt::skip]
+        let raw: &[u8] = &[
+            0x09, 0x00, 0x10, 0x10, b'O', b'B', 0x00, 0x00,
+            0x64, 0x00, 0x00, 0x00,
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+        ];
+        let open = |source: &'static [u8]| {
+            let mut decoder = StatefulDecoder::new(
+                source,
+                ExplicitVRLittleEndianDecoder::default(),
+                LittleEndianBasicDecoder,
+                SpecificCharacterSet::default(),
+            );
+            let header = decoder.decode_header().unwrap();
+            (decoder, header)
+        };
+
+        // truncated value
+        let (mut decoder, header) = open(raw);
+        let mut got = Vec::new();
+        let err = decoder
+            .read_value_chunked(&header, 4, |chunk| {
+                got.extend_from_slice(chunk);
+                Ok(())
+            })
+            .unwrap_err();
+        assert_eq!(got, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert!(
+            matches!(
+                err,
+                super::Error::TruncatedValue {
+                    position: 20,
+                    expected: 4,
+                    got: 2,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+
+        // the consumer fails
+        let (mut decoder, header) = open(raw);
+        let err = decoder
+            .read_value_chunked(&header, 4, |_| Err(std::io::Error::other("stop")))
+            .unwrap_err();
+        assert!(
+            matches!(err, super::Error::ConsumeValueChunk { position: 12, .. }),
+            "{:?}",
+            err
+        );
+
+        // undefined length
+        let header = DataElementHeader::new(Tag(0x0009, 0x1010), VR::OB, Length::UNDEFINED);
+        let (mut decoder, _) = open(raw);
+        let err = decoder
+            .read_value_chunked(&header, 4, |_| Ok(()))
+            .unwrap_err();
+        assert!(
+            matches!(err, super::Error::UndefinedValueLength { .. }),
+            "{:?}",
+            err
+        );
+    }
 }
@@ -3,15 +3,25 @@
 //! The [`StatefulEncoder`] supports encoding of binary data and text
 //! while applying the necessary padding to conform to DICOM encoding rules.
 
+use crate::dataset::read::{ReadWarning, WarningCollector, WarningKind};
 use dicom_core::{value::PrimitiveValue, DataElementHeader, Length, Tag, VR};
 use dicom_encoding::transfer_syntax::DynEncoder;
 use dicom_encoding::{
-    encode::EncodeTo,
-    text::{DefaultCharacterSetCodec, SpecificCharacterSet, TextCodec},
+    encode::{write_all_vectored, EncodeTo},
+    text::{
+        length::{
+            check_value_length, is_truncatable, truncate_values_with, LengthValidation,
+            LengthViolation,
+        },
+        repertoire::{check_repertoire, RepertoireValidation, RepertoireViolation},
+        DefaultCharacterSetCodec, EncodeTextPolicy, SpecificCharacterSet, TextCodec,
+    },
     TransferSyntax,
 };
 use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
-use std::io::Write;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
 
 #[derive(Debug, Snafu)]
 #[non_exhaustive]
@@ -40,16 +50,102 @@ pub enum Error {
         source: dicom_encoding::text::EncodeTextError,
     },
 
+    #[snafu(display("Invalid value for {} {}: {}", tag, vr, violation))]
+    InvalidValue {
+        tag: Tag,
+        vr: VR,
+        violation: RepertoireViolation,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Value too long for {} {}: {}", tag, vr, violation))]
+    ValueTooLong {
+        tag: Tag,
+        vr: VR,
+        violation: LengthViolation,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Odd value length {} for {}", length, tag))]
+    OddLength {
+        tag: Tag,
+        length: u32,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Could not write value data at position {}", position))]
     WriteValueData {
         position: u64,
         source: std::io::Error,
         backtrace: Backtrace,
     },
+
+    #[snafu(display("Could not flush the output at position {}", position))]
+    FlushOutput {
+        position: u64,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// What to do with values of odd length when encoding them.
+///
+/// DICOM requires all values to have an even length.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum OddLengthPolicy {
+    /// Add a padding byte appropriate to the value representation:
+    /// a space for text,
+    /// a null character for unique identifiers,
+    /// and a zero byte otherwise.
+    #[default]
+    Pad,
+    /// Write the value and its length as they are.
+    ///
+    /// This produces non-conformant data,
+    /// but it allows reproducing a source file byte for byte,
+    /// odd lengths included.
+    Preserve,
+    /// Fail to encode the value.
+    Error,
+}
+
+/// Options for writing encapsulated pixel data
+/// with [`StatefulEncoder::encode_encapsulated_pixel_data`].
+#[derive(Debug, Default, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct EncapsulationOptions {
+    /// The maximum length of each fragment in bytes,
+    /// so that larger frames are split into several fragments.
+    /// If not specified, each frame is written in a single fragment.
+    pub fragment_size: Option<u32>,
+    /// Whether to write an _Extended Offset Table_
+    /// (along with the _Extended Offset Table Lengths_)
+    /// when the offset of a frame does not fit in the basic offset table.
+    /// Otherwise, the basic offset table is left empty in that case.
+    pub extended_offset_table: bool,
+}
+
+impl EncapsulationOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum length of each fragment.
+    pub fn with_fragment_size(mut self, fragment_size: u32) -> Self {
+        self.fragment_size = Some(fragment_size);
+        self
+    }
+
+    /// Set whether to write an extended offset table
+    /// when frame offsets do not fit in the basic offset table.
+    pub fn with_extended_offset_table(mut self, extended_offset_table: bool) -> Self {
+        self.extended_offset_table = extended_offset_table;
+        self
+    }
+}
+
 /// Also called a printer, this encoder type provides a stateful mid-level
 /// abstraction for writing DICOM content. Unlike `Encode`,
 /// the stateful encoder knows how to write text values and keeps track
@@ -60,6 +156,15 @@ pub struct StatefulEncoder<W, E, T = SpecificCharacterSet> {
     to: W,
     encoder: E,
     text: T,
+    /// the character sets of the enclosing data sets,
+    /// restored when leaving each sequence item
+    text_scopes: Vec<T>,
+    text_policy: EncodeTextPolicy,
+    repertoire_validation: RepertoireValidation,
+    length_validation: LengthValidation,
+    truncation_marker: Option<String>,
+    warnings: Option<WarningCollector>,
+    odd_length: OddLengthPolicy,
     bytes_written: u64,
     buffer: Vec<u8>,
 }
@@ -72,10 +177,105 @@ impl<W, E, T> StatefulEncoder<W, E, T> {
             to,
             encoder,
             text,
+            text_scopes: Vec::new(),
+            text_policy: EncodeTextPolicy::default(),
+            repertoire_validation: RepertoireValidation::default(),
+            length_validation: LengthValidation::default(),
+            truncation_marker: None,
+            warnings: None,
+            odd_length: OddLengthPolicy::default(),
             bytes_written: 0,
             buffer: Vec::with_capacity(128),
         }
     }
+
+    /// Set what to do with characters in text values
+    /// which cannot be represented in the current character set.
+    ///
+    /// The default is [`EncodeTextPolicy::Error`].
+    /// Since the encoder cannot change the character set of the data set,
+    /// [`EncodeTextPolicy::UpgradeToUtf8`] also results in an error.
+    pub fn with_text_policy(mut self, policy: EncodeTextPolicy) -> Self {
+        self.text_policy = policy;
+        self
+    }
+
+    /// Set how text values are checked against
+    /// the character repertoire and format of their value representation
+    /// (see [`check_repertoire`]).
+    ///
+    /// The default is [`RepertoireValidation::Warn`].
+    pub fn with_repertoire_validation(mut self, validation: RepertoireValidation) -> Self {
+        self.repertoire_validation = validation;
+        self
+    }
+
+    /// Set how text values are checked against
+    /// the maximum value length of their value representation
+    /// (see [`check_value_length`]).
+    ///
+    /// The default is [`LengthValidation::Warn`].
+    ///
+    /// With [`LengthValidation::Truncate`],
+    /// values are cut to the maximum length in bytes once encoded
+    /// in the character set of the data set,
+    /// at the last whole character which fits,
+    /// and each cut is reported as a [`WarningKind::ValueTruncated`]
+    /// to the [warning collector](Self::with_warning_collector).
+    pub fn with_length_validation(mut self, validation: LengthValidation) -> Self {
+        self.length_validation = validation;
+        self
+    }
+
+    /// Set a marker to append to the values cut
+    /// under [`LengthValidation::Truncate`],
+    /// such as `"..."`.
+    ///
+    /// The marker counts towards the maximum length,
+    /// and is left out if it does not fit on its own.
+    /// It should conform to the value representations concerned.
+    pub fn with_truncation_marker(mut self, marker: impl Into<String>) -> Self {
+        self.truncation_marker = Some(marker.into());
+        self
+    }
+
+    /// Gather the values cut under [`LengthValidation::Truncate`]
+    /// into the given collector, in addition to logging them.
+    pub fn with_warning_collector(mut self, collector: &WarningCollector) -> Self {
+        self.warnings = Some(collector.clone());
+        self
+    }
+
+    /// Set what to do with values of odd length,
+    /// including the lengths in element and item headers.
+    ///
+    /// The default is [`OddLengthPolicy::Pad`].
+    pub fn with_odd_length_policy(mut self, policy: OddLengthPolicy) -> Self {
+        self.odd_length = policy;
+        self
+    }
+}
+
+impl<W, E, T> StatefulEncoder<W, E, T>
+where
+    T: Clone,
+{
+    /// Enter a data set sequence item.
+    ///
+    /// A _Specific Character Set_ encoded inside the item
+    /// only applies to the text in that item (and the items nested in it),
+    /// until the matching call to [`end_item`](StatefulEncoder::end_item).
+    pub fn begin_item(&mut self) {
+        self.text_scopes.push(self.text.clone());
+    }
+
+    /// Leave a data set sequence item,
+    /// restoring the character set of the enclosing data set.
+    pub fn end_item(&mut self) {
+        if let Some(text) = self.text_scopes.pop() {
+            self.text = text;
+        }
+    }
 }
 
 impl<'s> DynStatefulEncoder<'s> {
@@ -97,9 +297,13 @@ where
     E: EncodeTo<W>,
 {
     /// Encode and write a data element header.
+    ///
+    /// An odd length is handled according to the
+    /// [odd length policy](StatefulEncoder::with_odd_length_policy),
+    /// in anticipation of the value.
     pub fn encode_element_header(&mut self, mut de: DataElementHeader) -> Result<()> {
         if let Some(len) = de.len.get() {
-            de.len = Length(even_len(len))
+            de.len = Length(self.even_header_len(de.tag, len)?);
         }
         let bytes = self
             .encoder
@@ -118,7 +322,7 @@ where
         let len = if len == 0xFFFF_FFFF {
             len
         } else {
-            even_len(len)
+            self.even_header_len(Tag(0xFFFE, 0xE000), len)?
         };
         self.encoder
             .encode_item_header(&mut self.to, len)
@@ -169,14 +373,35 @@ where
     ///
     /// This method will perform the necessary padding
     /// (always with zeros)
-    /// to ensure that the encoded value has an even number of bytes.
+    /// to ensure that the encoded value has an even number of bytes,
+    /// unless odd lengths are [preserved](OddLengthPolicy::Preserve).
     pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
         debug_assert!(bytes.len() < u32::max_value() as usize);
-        self.to.write_all(bytes).context(WriteValueDataSnafu {
+        let padding: &[u8] = if self.pads_bytes(bytes.len() as u64) {
+            &[0]
+        } else {
+            &[]
+        };
+        write_all_vectored(&mut self.to, &[bytes, padding]).context(WriteValueDataSnafu {
             position: self.bytes_written,
         })?;
-        self.bytes_written += bytes.len() as u64;
-        if bytes.len() % 2 != 0 {
+        self.bytes_written += (bytes.len() + padding.len()) as u64;
+        Ok(())
+    }
+
+    /// Write a primitive DICOM value
+    /// by copying all bytes from the given reader
+    /// directly to the inner writer.
+    ///
+    /// Like [`write_bytes`](StatefulEncoder::write_bytes),
+    /// this method will pad the value with a zero
+    /// if an odd number of bytes was copied.
+    pub fn write_bytes_from<R: Read>(&mut self, mut from: R) -> Result<()> {
+        let bytes = std::io::copy(&mut from, &mut self.to).context(WriteValueDataSnafu {
+            position: self.bytes_written,
+        })?;
+        self.bytes_written += bytes;
+        if self.pads_bytes(bytes) {
             self.to.write_all(&[0]).context(WriteValueDataSnafu {
                 position: self.bytes_written,
             })?;
@@ -190,6 +415,19 @@ where
         self.bytes_written
     }
 
+    /// Flush the inner writer,
+    /// so that all bytes written so far reach their destination.
+    ///
+    /// This is needed when writing to a [`BatchWriter`],
+    /// which holds on to small elements until a batch is complete.
+    ///
+    /// [`BatchWriter`]: crate::dataset::BatchWriter
+    pub fn flush(&mut self) -> Result<()> {
+        self.to.flush().context(FlushOutputSnafu {
+            position: self.bytes_written,
+        })
+    }
+
     /// Encode and write the values of a pixel data offset table.
     pub fn encode_offset_table(&mut self, table: &[u32]) -> Result<()> {
         self.encoder
@@ -202,6 +440,93 @@ where
         Ok(())
     }
 
+    /// Encode and write an encapsulated _Pixel Data_ element
+    /// containing the given frames of encoded pixel data,
+    /// preceded by the extended offset table elements if they are needed.
+    ///
+    /// Each frame is written in one or more fragments
+    /// (see [`EncapsulationOptions::fragment_size`]),
+    /// always padded to an even length as the standard requires,
+    /// regardless of the [odd length policy](OddLengthPolicy).
+    /// The basic offset table records the position of each frame
+    /// if all of them fit in 32 bits.
+    /// Otherwise,
+    /// the frame positions and lengths are written to the
+    /// _Extended Offset Table_ and _Extended Offset Table Lengths_ elements
+    /// if so requested in the options,
+    /// and the basic offset table is left empty.
+    ///
+    /// Since these elements must appear in tag order,
+    /// this should be called when the data set writing reaches _Pixel Data_.
+    pub fn encode_encapsulated_pixel_data<'a>(
+        &mut self,
+        frames: impl Iterator<Item = &'a [u8]>,
+        options: &EncapsulationOptions,
+    ) -> Result<()> {
+        let fragment_size = options
+            .fragment_size
+            .map(|size| (size & !1).max(2) as usize)
+            .unwrap_or((u32::MAX - 1) as usize);
+        let frames: Vec<&[u8]> = frames.collect();
+
+        // offsets are relative to the first byte of the first fragment's item,
+        // each item having an 8 byte header
+        let mut offsets = Vec::with_capacity(frames.len());
+        let mut position = 0_u64;
+        for frame in &frames {
+            offsets.push(position);
+            position += frame_fragments(frame, fragment_size)
+                .map(|fragment| 8 + u64::from(even_len(fragment.len() as u32)))
+                .sum::<u64>();
+        }
+
+        let basic_offset_table = basic_offset_table(&offsets);
+        if basic_offset_table.is_none() && options.extended_offset_table {
+            let lengths: Vec<u64> = frames.iter().map(|frame| frame.len() as u64).collect();
+            self.encode_primitive_element(
+                &DataElementHeader::new(Tag(0x7FE0, 0x0001), VR::OV, Length::UNDEFINED),
+                &PrimitiveValue::U64(offsets.iter().copied().collect()),
+            )?;
+            self.encode_primitive_element(
+                &DataElementHeader::new(Tag(0x7FE0, 0x0002), VR::OV, Length::UNDEFINED),
+                &PrimitiveValue::U64(lengths.into()),
+            )?;
+        }
+        let offset_table = basic_offset_table.unwrap_or_default();
+
+        self.encode_element_header(DataElementHeader::new(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            Length::UNDEFINED,
+        ))?;
+        self.encode_item_header(offset_table.len() as u32 * 4)?;
+        self.encode_offset_table(&offset_table)?;
+        for frame in frames {
+            for fragment in frame_fragments(frame, fragment_size) {
+                self.encode_fragment(fragment)?;
+            }
+        }
+        self.encode_sequence_delimiter()
+    }
+
+    /// Encode and write an item with a fragment of encapsulated pixel data,
+    /// padded with a zero byte if its length is odd.
+    fn encode_fragment(&mut self, fragment: &[u8]) -> Result<()> {
+        let len = fragment.len() as u32;
+        self.encoder
+            .encode_item_header(&mut self.to, even_len(len))
+            .context(EncodeDataSnafu {
+                position: self.bytes_written,
+            })?;
+        self.bytes_written += 8;
+        let padding: &[u8] = if len.is_multiple_of(2) { &[] } else { &[0] };
+        write_all_vectored(&mut self.to, &[fragment, padding]).context(WriteValueDataSnafu {
+            position: self.bytes_written,
+        })?;
+        self.bytes_written += u64::from(even_len(len));
+        Ok(())
+    }
+
     /// Encode and write a data element with a primitive value.
     ///
     /// This method will perform the necessary padding to ensure that the
@@ -246,7 +571,7 @@ where
                 )?;
 
                 self.bytes_written += bytes as u64;
-                if bytes % 2 != 0 {
+                if self.pads_bytes(bytes as u64) {
                     let padding = match de.vr {
                         VR::DA | VR::DT | VR::TM => b' ',
                         _ => 0,
@@ -262,40 +587,57 @@ where
         }
     }
 
-    fn try_new_codec(&mut self, name: &str) {
-        if let Some(codec) = SpecificCharacterSet::from_code(name) {
-            self.text = codec;
-        } else {
-            tracing::warn!("Unsupported character set `{}`, ignoring", name);
-        }
+    /// Encode and write a data element header
+    /// followed by its value, already encoded and padded to even length
+    /// (see [`even_value`](Self::even_value)),
+    /// letting the writer receive both in a single call.
+    fn encode_element_with_value(&mut self, de: DataElementHeader, value: &[u8]) -> Result<()> {
+        debug_assert!(
+            value.len().is_multiple_of(2) || self.odd_length == OddLengthPolicy::Preserve
+        );
+        let de = DataElementHeader {
+            len: Length(value.len() as u32),
+            ..de
+        };
+        let bytes = self
+            .encoder
+            .encode_element_header_with_value(&mut self.to, de, value)
+            .context(EncodeDataSnafu {
+                position: self.bytes_written,
+            })?;
+        self.bytes_written += bytes as u64;
+        Ok(())
+    }
+
+    fn try_new_codec<I>(&mut self, names: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.text = SpecificCharacterSet::from_values_with(names, |name| {
+            tracing::warn!(
+                "Unsupported character set `{}`, encoding text as ISO_IR 100",
+                name
+            );
+        });
     }
 
     fn encode_text_element(&mut self, text: &str, de: DataElementHeader) -> Result<()> {
+        let text = self.validate_length(text, de, 0)?;
+        let text = &*text;
+        self.validate_repertoire(&[text], de)?;
         // encode it in memory first so that we know the real length
         let mut encoded_value = self.convert_text_untrailed(text, de.vr)?;
-        // pad to even length
-        if encoded_value.len() % 2 == 1 {
-            let pad = if de.vr == VR::UI { b'\0' } else { b' ' };
-            encoded_value.push(pad);
-        }
+        let pad = if de.vr == VR::UI { b'\0' } else { b' ' };
+        self.even_value(&mut encoded_value, pad, de.tag)?;
 
         // now we can write the header with the correct length
-        self.encode_element_header(DataElementHeader {
-            tag: de.tag,
-            vr: de.vr,
-            len: Length(encoded_value.len() as u32),
-        })?;
-        self.to
-            .write_all(&encoded_value)
-            .context(WriteValueDataSnafu {
-                position: self.bytes_written,
-            })?;
-        self.bytes_written += encoded_value.len() as u64;
+        self.encode_element_with_value(de, &encoded_value)?;
 
         // if element is Specific Character Set,
         // update the text codec
         if de.tag == Tag(0x0008, 0x0005) {
-            self.try_new_codec(text);
+            self.try_new_codec(text.split('\\'));
         }
 
         Ok(())
@@ -305,6 +647,12 @@ where
     where
         S: AsRef<str>,
     {
+        let texts = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| self.validate_length(text.as_ref(), de, i))
+            .collect::<Result<Vec<_>>>()?;
+        self.validate_repertoire(&texts, de)?;
         self.buffer.clear();
         for (i, t) in texts.iter().enumerate() {
             self.buffer
@@ -313,48 +661,223 @@ where
                 self.buffer.push(b'\\');
             }
         }
-        // pad to even length
-        if self.buffer.len() % 2 == 1 {
-            let pad = if de.vr == VR::UI { b'\0' } else { b' ' };
-            self.buffer.push(pad);
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let pad = if de.vr == VR::UI { b'\0' } else { b' ' };
+        if let Err(e) = self.even_value(&mut buffer, pad, de.tag) {
+            self.buffer = buffer;
+            return Err(e);
         }
 
         // now we can write the header with the correct length
-        self.encode_element_header(DataElementHeader {
-            tag: de.tag,
-            vr: de.vr,
-            len: Length(self.buffer.len() as u32),
-        })?;
-
-        self.to
-            .write_all(&self.buffer)
-            .context(WriteValueDataSnafu {
-                position: self.bytes_written,
-            })?;
-        self.bytes_written += self.buffer.len() as u64;
+        let written = self.encode_element_with_value(de, &buffer);
+        self.buffer = buffer;
+        written?;
 
         // if element is Specific Character Set,
         // update the text codec
         if de.tag == Tag(0x0008, 0x0005) {
-            if let Some(charset_name) = texts.first() {
-                self.try_new_codec(charset_name.as_ref());
+            self.try_new_codec(texts.iter().map(|text| text.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    /// Check the values of a text element against its VR,
+    /// as configured by the repertoire validation mode.
+    fn validate_repertoire<S>(&self, texts: &[S], de: DataElementHeader) -> Result<()>
+    where
+        S: AsRef<str>,
+    {
+        if self.repertoire_validation == RepertoireValidation::Off {
+            return Ok(());
+        }
+        // position of the current value in the whole element value
+        let mut offset = 0;
+        for text in texts {
+            let text = text.as_ref();
+            if let Err(violation) = check_repertoire(de.vr, text) {
+                let violation = RepertoireViolation {
+                    position: offset + violation.position,
+                    ..violation
+                };
+                if self.repertoire_validation == RepertoireValidation::Strict {
+                    return InvalidValueSnafu {
+                        tag: de.tag,
+                        vr: de.vr,
+                        violation,
+                    }
+                    .fail();
+                }
+                tracing::warn!("Invalid value for {} {}: {}", de.tag, de.vr, violation);
+                return Ok(());
             }
+            // skip the value and its delimiter
+            offset += text.chars().count() + 1;
         }
+        Ok(())
+    }
+
+    /// Check the values of a text element against the maximum length of its VR,
+    /// as configured by the length validation mode,
+    /// truncating them if requested.
+    /// Values which may not be truncated
+    /// are rejected in truncation mode instead.
+    ///
+    /// `index` is the index of the first value of the text in the element.
+    fn validate_length<'t>(
+        &self,
+        text: &'t str,
+        de: DataElementHeader,
+        index: usize,
+    ) -> Result<Cow<'t, str>> {
+        match self.length_validation {
+            LengthValidation::Off => return Ok(Cow::Borrowed(text)),
+            LengthValidation::Truncate if is_truncatable(de.vr) => {
+                return self.truncate_encoded(text, de, index)
+            }
+            LengthValidation::Strict | LengthValidation::Warn | LengthValidation::Truncate => {}
+        }
+        let violation = match check_value_length(de.vr, text) {
+            Ok(()) => return Ok(Cow::Borrowed(text)),
+            Err(violation) => LengthViolation {
+                index: index + violation.index,
+                ..violation
+            },
+        };
+        if self.length_validation != LengthValidation::Warn {
+            return ValueTooLongSnafu {
+                tag: de.tag,
+                vr: de.vr,
+                violation,
+            }
+            .fail();
+        }
+        tracing::warn!("Value too long for {} {}: {}", de.tag, de.vr, violation);
+        Ok(Cow::Borrowed(text))
+    }
 
+    /// Cut the values of a text element
+    /// whose encoded form is longer than the maximum length of its VR,
+    /// at the last whole character which fits along with the truncation marker,
+    /// so that no partial multi-byte sequence is ever written.
+    fn truncate_encoded<'t>(
+        &self,
+        text: &'t str,
+        de: DataElementHeader,
+        index: usize,
+    ) -> Result<Cow<'t, str>> {
+        let max = match de.vr.max_value_length() {
+            Some(max) => max,
+            None => return Ok(Cow::Borrowed(text)),
+        };
+        let fits = |text: &str| -> Result<bool> {
+            Ok(self.convert_text_untrailed(text, de.vr)?.len() <= max as usize)
+        };
+        let marker = match self.truncation_marker.as_deref() {
+            Some(marker) if fits(marker)? => marker,
+            _ => "",
+        };
+        truncate_values_with(de.vr, text, |i, part| {
+            let length = self.convert_text_untrailed(part, de.vr)?.len();
+            if length <= max as usize {
+                return Ok(None);
+            }
+            // the encoded length grows with the number of characters,
+            // so search for the longest prefix which fits
+            let boundaries: Vec<usize> = part.char_indices().map(|(i, _)| i).collect();
+            let (mut lo, mut hi) = (0, boundaries.len());
+            while lo < hi {
+                let mid = (lo + hi).div_ceil(2);
+                let end = boundaries.get(mid).copied().unwrap_or(part.len());
+                if fits(&format!("{}{}", &part[..end], marker))? {
+                    lo = mid;
+                } else {
+                    hi = mid - 1;
+                }
+            }
+            let end = boundaries.get(lo).copied().unwrap_or(part.len());
+
+            let warning = ReadWarning::new(
+                WarningKind::ValueTruncated {
+                    index: (index + i) as u32,
+                    length: length as u32,
+                    max,
+                },
+                de.tag,
+                self.bytes_written,
+            );
+            tracing::warn!("{}", warning);
+            if let Some(warnings) = &self.warnings {
+                warnings.push(warning);
+            }
+            Ok(Some(format!("{}{}", &part[..end], marker)))
+        })
+    }
+
+    /// Bring the length in an element or item header to an even number,
+    /// as configured by the odd length policy.
+    fn even_header_len(&self, tag: Tag, len: u32) -> Result<u32> {
+        if len.is_multiple_of(2) {
+            return Ok(len);
+        }
+        match self.odd_length {
+            OddLengthPolicy::Pad => Ok(even_len(len)),
+            OddLengthPolicy::Preserve => Ok(len),
+            OddLengthPolicy::Error => OddLengthSnafu { tag, length: len }.fail(),
+        }
+    }
+
+    /// Bring an encoded value to an even length
+    /// with the given padding byte,
+    /// as configured by the odd length policy.
+    fn even_value(&self, value: &mut Vec<u8>, pad: u8, tag: Tag) -> Result<()> {
+        if value.len().is_multiple_of(2) {
+            return Ok(());
+        }
+        match self.odd_length {
+            OddLengthPolicy::Pad => value.push(pad),
+            OddLengthPolicy::Preserve => {}
+            OddLengthPolicy::Error => {
+                return OddLengthSnafu {
+                    tag,
+                    length: value.len() as u32,
+                }
+                .fail()
+            }
+        }
         Ok(())
     }
 
+    /// Whether a value of this many bytes, written after its header,
+    /// needs a padding byte.
+    ///
+    /// With the error policy,
+    /// the header of an odd value was already rejected,
+    /// so anything else is padded as declared.
+    fn pads_bytes(&self, len: u64) -> bool {
+        !len.is_multiple_of(2) && self.odd_length != OddLengthPolicy::Preserve
+    }
+
     fn convert_text_untrailed(&self, text: &str, vr: VR) -> Result<Vec<u8>> {
         match vr {
             VR::AE | VR::AS | VR::CS | VR::DA | VR::DS | VR::DT | VR::IS | VR::TM | VR::UI => {
                 // these VRs always use the default character repertoire
-                DefaultCharacterSetCodec
-                    .encode(text)
-                    .context(EncodeTextSnafu {
-                        position: self.bytes_written,
-                    })
+                self.encode_text_with_policy(&DefaultCharacterSetCodec, text)
             }
-            _ => self.text.encode(text).context(EncodeTextSnafu {
+            VR::PN => match self.text_policy {
+                EncodeTextPolicy::Replace => Ok(self.text.encode_person_name_lossy(text)),
+                _ => self.text.encode_person_name(text).context(EncodeTextSnafu {
+                    position: self.bytes_written,
+                }),
+            },
+            _ => self.encode_text_with_policy(&self.text, text),
+        }
+    }
+
+    fn encode_text_with_policy(&self, codec: &impl TextCodec, text: &str) -> Result<Vec<u8>> {
+        match self.text_policy {
+            EncodeTextPolicy::Replace => Ok(codec.encode_lossy(text)),
+            _ => codec.encode(text).context(EncodeTextSnafu {
                 position: self.bytes_written,
             }),
         }
@@ -386,25 +909,12 @@ where
             | PrimitiveValue::F32(_)
             | PrimitiveValue::F64(_) => {
                 let textual_value = value.to_str();
-                self.encode_element_header(DataElementHeader {
-                    tag: de.tag,
-                    vr: de.vr,
-                    len: Length(even_len(textual_value.len() as u32)),
-                })?;
-
-                write!(self.to, "{}", textual_value).context(WriteValueDataSnafu {
-                    position: self.bytes_written,
-                })?;
-                let len = if textual_value.len() % 2 == 1 {
-                    self.to.write_all(&[b' ']).context(WriteValueDataSnafu {
-                        position: self.bytes_written,
-                    })?;
-                    textual_value.len() as u64 + 1
-                } else {
-                    textual_value.len() as u64
-                };
-                self.bytes_written += len;
-                Ok(())
+                let mut textual_value = self
+                    .validate_length(&textual_value, *de, 0)?
+                    .into_owned()
+                    .into_bytes();
+                self.even_value(&mut textual_value, b' ', de.tag)?;
+                self.encode_element_with_value(*de, &textual_value)
             }
             PrimitiveValue::Date(_)
             | PrimitiveValue::DateTime(_)
@@ -421,18 +931,43 @@ fn even_len(l: u32) -> u32 {
     (l + 1) & !1
 }
 
+/// Convert frame offsets into the entries of a basic offset table,
+/// or `None` if an offset lies beyond 4 GiB,
+/// in which case only the extended offset table can record them.
+fn basic_offset_table(offsets: &[u64]) -> Option<Vec<u32>> {
+    offsets
+        .iter()
+        .map(|&offset| u32::try_from(offset).ok())
+        .collect()
+}
+
+/// Split a frame into fragments of at most `size` bytes,
+/// with an empty frame still occupying one (empty) fragment.
+fn frame_fragments(frame: &[u8], size: usize) -> impl Iterator<Item = &[u8]> {
+    let empty = if frame.is_empty() { Some(frame) } else { None };
+    frame.chunks(size).chain(empty)
+}
+
 #[cfg(test)]
 mod tests {
+    use dicom_core::header::SequenceItemHeader;
     use dicom_core::{
         dicom_value, value::DicomTime, DataElement, DataElementHeader, DicomValue, Length,
         PrimitiveValue, Tag, VR,
     };
     use dicom_encoding::{
+        decode::{basic::LittleEndianBasicDecoder, explicit_le::ExplicitVRLittleEndianDecoder},
         encode::{explicit_le::ExplicitVRLittleEndianEncoder, EncoderFor},
-        text::{SpecificCharacterSet, TextCodec},
+        text::{
+            length::LengthValidation, repertoire::RepertoireValidation, EncodeTextPolicy,
+            SpecificCharacterSet, TextCodec,
+        },
     };
+    use std::io::Cursor;
 
-    use super::StatefulEncoder;
+    use super::{EncapsulationOptions, Error, OddLengthPolicy, StatefulEncoder};
+    use crate::pixel_sequence::read_pixel_sequence;
+    use crate::stateful::decode::{StatefulDecode, StatefulDecoder};
 
     /// Odd lengthed values convert to tokens with even padding (PN)
     #[test]
@@ -605,6 +1140,290 @@ mod tests {
         )
     }
 
+    /// Odd lengthed values are padded, preserved or rejected
+    /// according to the odd length policy
+    #[test]
+    fn encode_with_odd_length_policy() {
+        let cs = DataElementHeader::new(Tag(0x0008, 0x0060), VR::CS, Length(3));
+        let ob = DataElementHeader::new(Tag(0x0009, 0x0010), VR::OB, Length(3));
+        let encode = |policy, header: &DataElementHeader, value: &PrimitiveValue| {
+            let mut sink = Vec::new();
+            let mut encoder = StatefulEncoder::new(
+                &mut sink,
+                EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                SpecificCharacterSet::default(),
+            )
+            .with_odd_length_policy(policy);
+            encoder.encode_primitive_element(header, value)?;
+            encoder.encode_item_header(3)?;
+            encoder.write_bytes(&[5; 3])?;
+            Ok::<_, Error>(sink)
+        };
+        let text = PrimitiveValue::from("ABC");
+        let bytes = PrimitiveValue::from(vec![1_u8, 2, 3]);
+
+        assert_eq!(
+            encode(OddLengthPolicy::Pad, &cs, &text).unwrap(),
+            &[
+                0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x04, 0x00, // header
+                b'A', b'B', b'C', b' ', // value
+                0xFE, 0xFF, 0x00, 0xE0, 0x04, 0x00, 0x00, 0x00, // item header
+                5, 5, 5, 0, // item value
+            ][..],
+        );
+        assert_eq!(
+            encode(OddLengthPolicy::Pad, &ob, &bytes).unwrap()[..16],
+            [
+                0x09, 0x00, 0x10, 0x00, b'O', b'B', 0x00, 0x00, 0x04, 0x00, 0x00,
+                0x00, // header
+                1, 2, 3, 0, // value
+            ],
+        );
+
+        assert_eq!(
+            encode(OddLengthPolicy::Preserve, &cs, &text).unwrap(),
+            &[
+                0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x03, 0x00, // header
+                b'A', b'B', b'C', // value
+                0xFE, 0xFF, 0x00, 0xE0, 0x03, 0x00, 0x00, 0x00, // item header
+                5, 5, 5, // item value
+            ][..],
+        );
+        assert_eq!(
+            encode(OddLengthPolicy::Preserve, &ob, &bytes).unwrap()[..15],
+            [
+                0x09, 0x00, 0x10, 0x00, b'O', b'B', 0x00, 0x00, 0x03, 0x00, 0x00,
+                0x00, // header
+                1, 2, 3, // value
+            ],
+        );
+
+        for (header, value) in [(&cs, &text), (&ob, &bytes)] {
+            let err = encode(OddLengthPolicy::Error, header, value).unwrap_err();
+            assert!(
+                matches!(err, Error::OddLength { tag, length: 3, .. } if tag == header.tag),
+                "{:?}",
+                err
+            );
+        }
+        // even values are unaffected
+        let even = PrimitiveValue::from("CT");
+        let mut sink = Vec::new();
+        StatefulEncoder::new(
+            &mut sink,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::default(),
+        )
+        .with_odd_length_policy(OddLengthPolicy::Error)
+        .encode_primitive_element(&cs, &even)
+        .unwrap();
+        assert_eq!(sink, b"\x08\x00\x60\x00CS\x02\x00CT");
+    }
+
+    fn pixel_data_decoder(
+        data: &[u8],
+    ) -> StatefulDecoder<ExplicitVRLittleEndianDecoder, Cursor<&[u8]>, LittleEndianBasicDecoder>
+    {
+        StatefulDecoder::new(
+            Cursor::new(data),
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        )
+    }
+
+    /// Encapsulated pixel data can be read back with the fragment parser
+    #[test]
+    fn encode_encapsulated_pixel_data_round_trip() {
+        let frames: Vec<Vec<u8>> = vec![
+            (0..10).collect(),
+            (10..17).collect(),
+            vec![],
+            (17..42).collect(),
+        ];
+        let mut out: Vec<_> = Vec::new();
+        {
+            let mut encoder = StatefulEncoder::new(
+                &mut out,
+                EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                SpecificCharacterSet::default(),
+            );
+            encoder
+                .encode_encapsulated_pixel_data(
+                    frames.iter().map(|f| &f[..]),
+                    &EncapsulationOptions::new().with_fragment_size(8),
+                )
+                .unwrap();
+            assert_eq!(encoder.bytes_written(), out.len() as u64);
+        }
+
+        let mut decoder = pixel_data_decoder(&out);
+        let header = decoder.decode_header().unwrap();
+        assert_eq!(header.tag, Tag(0x7FE0, 0x0010));
+        assert_eq!(header.vr, VR::OB);
+        assert!(header.len.is_undefined());
+        let seq = read_pixel_sequence(&mut decoder).unwrap();
+        assert_eq!(decoder.position(), out.len() as u64);
+
+        // fragments of at most 8 bytes, padded to an even length
+        let lengths: Vec<_> = seq.fragments().iter().map(|f| f.len()).collect();
+        assert_eq!(lengths, vec![8, 2, 8, 0, 8, 8, 8, 2]);
+        assert_eq!(seq.offset_table(), &[0, 26, 42, 50]);
+
+        // gather the fragments of each frame with the offset table
+        let mut positions = Vec::new();
+        let mut position = 0;
+        for fragment in seq.fragments() {
+            positions.push(position);
+            position += 8 + fragment.len() as u32;
+        }
+        for (i, frame) in frames.iter().enumerate() {
+            let start = positions.binary_search(&seq.offset_table()[i]).unwrap();
+            let end = match seq.offset_table().get(i + 1) {
+                Some(offset) => positions.binary_search(offset).unwrap(),
+                None => seq.fragments().len(),
+            };
+            let mut expected = frame.clone();
+            if expected.len() % 2 != 0 {
+                expected.push(0);
+            }
+            assert_eq!(seq.fragments()[start..end].concat(), expected);
+        }
+    }
+
+    /// Fragments of encapsulated pixel data are padded to an even length
+    /// whatever the odd length policy,
+    /// so that the offset table matches the item headers.
+    #[test]
+    fn encode_encapsulated_pixel_data_odd_length_policies() {
+        let frames: Vec<Vec<u8>> = vec![(0..7).collect(), (7..11).collect()];
+        let encode = |policy| {
+            let mut out: Vec<u8> = Vec::new();
+            let mut encoder = StatefulEncoder::new(
+                &mut out,
+                EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                SpecificCharacterSet::default(),
+            )
+            .with_odd_length_policy(policy);
+            encoder
+                .encode_encapsulated_pixel_data(
+                    frames.iter().map(|f| &f[..]),
+                    &EncapsulationOptions::new(),
+                )
+                .unwrap();
+            assert_eq!(encoder.bytes_written(), out.len() as u64);
+            out
+        };
+
+        let padded = encode(OddLengthPolicy::Pad);
+        for policy in [OddLengthPolicy::Preserve, OddLengthPolicy::Error] {
+            assert_eq!(encode(policy), padded, "{:?}", policy);
+        }
+
+        let mut decoder = pixel_data_decoder(&padded);
+        decoder.decode_header().unwrap();
+        let seq = read_pixel_sequence(&mut decoder).unwrap();
+        assert_eq!(decoder.position(), padded.len() as u64);
+        assert_eq!(
+            seq.fragments(),
+            &[vec![0, 1, 2, 3, 4, 5, 6, 0], vec![7, 8, 9, 10]]
+        );
+        assert_eq!(seq.offset_table(), &[0, 16]);
+    }
+
+    /// A writer which keeps the first bytes written to it
+    /// and only counts the rest.
+    struct PrefixWriter {
+        prefix: Vec<u8>,
+        max_len: usize,
+    }
+
+    impl std::io::Write for PrefixWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.max_len - self.prefix.len());
+            self.prefix.extend_from_slice(&buf[..n]);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Frame offsets beyond 32 bits are written to an extended offset table
+    #[test]
+    fn encode_encapsulated_pixel_data_extended_offset_table() {
+        // 65 frames of 64 MiB, the last one starting beyond 4 GiB
+        let frame = vec![0x55_u8; 64 << 20];
+        let frame_item_len = 8 + frame.len() as u64;
+
+        for extended in [false, true] {
+            let mut out = PrefixWriter {
+                prefix: Vec::new(),
+                max_len: 2048,
+            };
+            let mut encoder = StatefulEncoder::new(
+                &mut out,
+                EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                SpecificCharacterSet::default(),
+            );
+            encoder
+                .encode_encapsulated_pixel_data(
+                    std::iter::repeat_n(&frame[..], 65),
+                    &EncapsulationOptions::new().with_extended_offset_table(extended),
+                )
+                .unwrap();
+            let total_len = encoder.bytes_written();
+
+            let mut decoder = pixel_data_decoder(&out.prefix);
+            let mut header = decoder.decode_header().unwrap();
+            if extended {
+                assert_eq!(header.tag, Tag(0x7FE0, 0x0001));
+                assert_eq!(header.vr, VR::OV);
+                let offsets = decoder.read_value(&header).unwrap();
+                let expected: Vec<u64> = (0..65).map(|i| i * frame_item_len).collect();
+                assert_eq!(offsets.to_multi_int::<u64>().unwrap(), expected);
+
+                header = decoder.decode_header().unwrap();
+                assert_eq!(header.tag, Tag(0x7FE0, 0x0002));
+                let lengths = decoder.read_value(&header).unwrap();
+                assert_eq!(
+                    lengths.to_multi_int::<u64>().unwrap(),
+                    vec![frame.len() as u64; 65]
+                );
+                header = decoder.decode_header().unwrap();
+            }
+            assert_eq!(header.tag, Tag(0x7FE0, 0x0010));
+            let header_len = decoder.position();
+
+            // the basic offset table is left empty
+            assert_eq!(
+                decoder.decode_item_header().unwrap(),
+                SequenceItemHeader::Item { len: Length(0) }
+            );
+            assert_eq!(
+                decoder.decode_item_header().unwrap(),
+                SequenceItemHeader::Item {
+                    len: Length(frame.len() as u32)
+                }
+            );
+            assert_eq!(total_len, header_len + 8 + 65 * frame_item_len + 8);
+        }
+    }
+
+    #[test]
+    fn basic_offset_table_near_overflow() {
+        use super::basic_offset_table;
+
+        assert_eq!(
+            basic_offset_table(&[0, 0x7FFF_FFF8, 0xFFFF_FFFF]),
+            Some(vec![0, 0x7FFF_FFF8, 0xFFFF_FFFF])
+        );
+        // the last frame starts beyond 4 GiB
+        assert_eq!(basic_offset_table(&[0, 0xFFFF_FFF8, 0x1_0000_0000]), None);
+        assert_eq!(basic_offset_table(&[]), Some(vec![]));
+    }
+
     #[test]
     fn test_even_len() {
         use super::even_len;
@@ -671,4 +1490,397 @@ mod tests {
         // test all output against ground truth
         assert_eq!(&sink, GT);
     }
+
+    /// Test that characters which cannot be represented
+    /// in the character set are handled according to the text policy.
+    #[test]
+    fn encode_unrepresentable_text() {
+        let pn = DataElementHeader {
+            tag: Tag(0x0010, 0x0010),
+            vr: VR::PN,
+            len: Length(8),
+        };
+        let pn_value = PrimitiveValue::from("Żółć^Jan");
+
+        // fails by default
+        let mut sink = Vec::new();
+        let mut encoder = StatefulEncoder::new(
+            &mut sink,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::ISO_IR_100,
+        );
+        let err = encoder
+            .encode_primitive_element(&pn, &pn_value)
+            .unwrap_err();
+        assert!(matches!(err, Error::EncodeText { position: 0, .. }));
+
+        // the data set cannot be changed here, so upgrading fails too
+        let mut encoder = StatefulEncoder::new(
+            &mut sink,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::ISO_IR_100,
+        )
+        .with_text_policy(EncodeTextPolicy::UpgradeToUtf8);
+        assert!(encoder.encode_primitive_element(&pn, &pn_value).is_err());
+        assert!(sink.is_empty());
+
+        let mut encoder = StatefulEncoder::new(
+            &mut sink,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::ISO_IR_100,
+        )
+        .with_text_policy(EncodeTextPolicy::Replace);
+        encoder.encode_primitive_element(&pn, &pn_value).unwrap();
+        assert_eq!(
+            &sink,
+            &[
+                0x10, 0x00, 0x10, 0x00, // tag
+                b'P', b'N', // VR
+                0x08, 0x00, // length
+                // ---------- value ----------
+                b'?', 0xf3, b'?', b'?', b'^', b'J', b'a', b'n',
+            ],
+        );
+    }
+
+    /// Test that values which do not conform to their VR
+    /// are handled according to the repertoire validation mode.
+    #[test]
+    fn encode_with_repertoire_validation() {
+        let cs = DataElementHeader {
+            tag: Tag(0x0008, 0x0008),
+            vr: VR::CS,
+            len: Length::UNDEFINED,
+        };
+        let cs_value = dicom_value!(Strs, ["ORIGINAL", "primary"]);
+
+        // rejected in strict mode
+        let mut sink = Vec::new();
+        let mut encoder = StatefulEncoder::new(
+            &mut sink,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::default(),
+        )
+        .with_repertoire_validation(RepertoireValidation::Strict);
+        let err = encoder
+            .encode_primitive_element(&cs, &cs_value)
+            .unwrap_err();
+        match &err {
+            Error::InvalidValue {
+                tag, vr, violation, ..
+            } => {
+                assert_eq!(*tag, Tag(0x0008, 0x0008));
+                assert_eq!(*vr, VR::CS);
+                assert_eq!(violation.character, 'p');
+                // after "ORIGINAL\"
+                assert_eq!(violation.position, 9);
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+        assert!(err.to_string().contains("(0008,0008) CS"));
+        assert!(sink.is_empty());
+
+        // conforming values pass in strict mode
+        let mut encoder = StatefulEncoder::new(
+            &mut sink,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::default(),
+        )
+        .with_repertoire_validation(RepertoireValidation::Strict);
+        encoder
+            .encode_primitive_element(&cs, &dicom_value!(Strs, ["ORIGINAL", "PRIMARY"]))
+            .unwrap();
+        assert_eq!(sink.len(), 8 + 16);
+
+        // written as is otherwise
+        for validation in [RepertoireValidation::Warn, RepertoireValidation::Off] {
+            let mut sink = Vec::new();
+            let mut encoder = StatefulEncoder::new(
+                &mut sink,
+                EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                SpecificCharacterSet::default(),
+            )
+            .with_repertoire_validation(validation);
+            encoder.encode_primitive_element(&cs, &cs_value).unwrap();
+            assert_eq!(&sink[8..], b"ORIGINAL\\primary");
+        }
+    }
+
+    /// Test that UIDs are validated when encoding UI elements.
+    #[test]
+    fn encode_with_uid_validation() {
+        let ui = DataElementHeader {
+            tag: Tag(0x0008, 0x0018),
+            vr: VR::UI,
+            len: Length::UNDEFINED,
+        };
+        let uid_64 = "1.2.826.0.1.3680043.10.1234567890.1234567890.1234567890.12345678";
+        let uid_65 = format!("{}9", uid_64);
+
+        let encode = |uid: &str, validation| {
+            let mut sink = Vec::new();
+            StatefulEncoder::new(
+                &mut sink,
+                EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                SpecificCharacterSet::default(),
+            )
+            .with_repertoire_validation(validation)
+            .encode_primitive_element(&ui, &PrimitiveValue::from(uid))
+            .map(|_| sink)
+        };
+
+        // valid, also with null padding
+        let sink = encode(uid_64, RepertoireValidation::Strict).unwrap();
+        assert_eq!(&sink[8..], uid_64.as_bytes());
+        let sink = encode("1.2.3.4.5\0", RepertoireValidation::Strict).unwrap();
+        assert_eq!(&sink[8..], b"1.2.3.4.5\0");
+
+        // rejected in strict mode
+        for uid in [&uid_65, "1.2.03.4", "1.2.3.4\0\0"] {
+            match encode(uid, RepertoireValidation::Strict) {
+                Err(Error::InvalidValue { tag, vr, .. }) => {
+                    assert_eq!((tag, vr), (Tag(0x0008, 0x0018), VR::UI));
+                }
+                r => panic!("unexpected result for {:?}: {:?}", uid, r),
+            }
+        }
+
+        // written as is otherwise
+        for validation in [RepertoireValidation::Warn, RepertoireValidation::Off] {
+            let sink = encode(&uid_65, validation).unwrap();
+            assert_eq!(&sink[8..8 + 65], uid_65.as_bytes());
+        }
+    }
+
+    /// Test that values longer than their VR allows
+    /// are handled according to the length validation mode.
+    #[test]
+    fn encode_with_length_validation() {
+        // an over-limit value for each bounded VR,
+        // and its truncated form if the VR may be truncated
+        let long_lo = "L".repeat(65);
+        let long_pn = format!("Doe^John={}", "G".repeat(65));
+        let long_lt = "T".repeat(10241);
+        let long_st = "S".repeat(1025);
+        let cases: Vec<(VR, &str, Option<&str>)> = vec![
+            (VR::AE, "STORE-SCP-ARCHIV1", None),
+            (VR::AS, "042Y1", Some("042Y")),
+            (VR::CS, "DERIVED_SECONDARY", None),
+            (VR::DA, "202301011", None),
+            (VR::DS, "3.141592653589793", None),
+            (VR::DT, "20230101123000.123456+01001", None),
+            (VR::IS, "1234567890123", None),
+            (VR::LO, &long_lo, Some(&long_lo[..64])),
+            (VR::LT, &long_lt, Some(&long_lt[..10240])),
+            (VR::PN, &long_pn, Some(&long_pn[..73])),
+            (VR::SH, "Seventeen Chars!!", Some("Seventeen Chars!")),
+            (VR::ST, &long_st, Some(&long_st[..1024])),
+            (VR::TM, "123000.12345678", None),
+            (
+                VR::UI,
+                "1.2.826.0.1.3680043.10.1234567890.1234567890.1234567890.123456789",
+                None,
+            ),
+        ];
+
+        let encode = |vr, value: &PrimitiveValue, validation| {
+            let header = DataElementHeader::new(Tag(0x0009, 0x1010), vr, Length::UNDEFINED);
+            let mut sink = Vec::new();
+            StatefulEncoder::new(
+                &mut sink,
+                EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                SpecificCharacterSet::default(),
+            )
+            .with_repertoire_validation(RepertoireValidation::Off)
+            .with_length_validation(validation)
+            .encode_primitive_element(&header, value)
+            .map(|_| sink)
+        };
+        // the value in the encoded element, without padding
+        let written_value = |sink: &[u8]| {
+            String::from_utf8(sink[8..].to_vec())
+                .unwrap()
+                .trim_end_matches([' ', '\0'])
+                .to_string()
+        };
+
+        for (vr, value, truncated) in cases {
+            let value = PrimitiveValue::from(value);
+
+            match encode(vr, &value, LengthValidation::Strict) {
+                Err(Error::ValueTooLong {
+                    vr: err_vr,
+                    violation,
+                    ..
+                }) => {
+                    assert_eq!(err_vr, vr);
+                    assert_eq!(violation.index, 0);
+                    assert_eq!(violation.length, violation.max as usize + 1);
+                }
+                r => panic!("unexpected result for {}: {:?}", vr, r.map(|_| ())),
+            }
+
+            for validation in [LengthValidation::Warn, LengthValidation::Off] {
+                let sink = encode(vr, &value, validation).unwrap();
+                assert_eq!(written_value(&sink), value.to_str(), "{}", vr);
+            }
+
+            match (encode(vr, &value, LengthValidation::Truncate), truncated) {
+                (Ok(sink), Some(truncated)) => {
+                    assert_eq!(written_value(&sink), truncated, "{}", vr)
+                }
+                (Err(Error::ValueTooLong { vr: err_vr, .. }), None) => assert_eq!(err_vr, vr),
+                (r, _) => panic!("unexpected result for {}: {:?}", vr, r.map(|_| ())),
+            }
+        }
+
+        // multiple values are checked one by one, not in aggregate
+        let cs = dicom_value!(
+            Strs,
+            ["ABCDEFGHIJKLMNOP", "ABCDEFGHIJKLMNOP", "ABCDEFGHIJKLMNOP"]
+        );
+        let sink = encode(VR::CS, &cs, LengthValidation::Strict).unwrap();
+        assert_eq!(sink.len(), 8 + 50);
+        let cs = dicom_value!(Strs, ["ORIGINAL", "ABCDEFGHIJKLMNOPQ", "AXIAL"]);
+        match encode(VR::CS, &cs, LengthValidation::Strict) {
+            Err(Error::ValueTooLong { violation, .. }) => {
+                assert_eq!(violation.index, 1);
+                assert_eq!(violation.length, 17);
+            }
+            r => panic!("unexpected result: {:?}", r.map(|_| ())),
+        }
+        assert!(encode(VR::CS, &cs, LengthValidation::Truncate).is_err());
+        let sink = encode(VR::SH, &cs, LengthValidation::Truncate).unwrap();
+        assert_eq!(&sink[8..], b"ORIGINAL\\ABCDEFGHIJKLMNOP\\AXIAL ");
+        // also when the values are in a single string
+        let cs = PrimitiveValue::from("ORIGINAL\\ABCDEFGHIJKLMNOPQ\\AXIAL");
+        match encode(VR::CS, &cs, LengthValidation::Strict) {
+            Err(Error::ValueTooLong { violation, .. }) => assert_eq!(violation.index, 1),
+            r => panic!("unexpected result: {:?}", r.map(|_| ())),
+        }
+
+        // unlimited VRs are never too long
+        let long = PrimitiveValue::from("X".repeat(70_000));
+        for vr in [VR::UC, VR::UR, VR::UT] {
+            encode(vr, &long, LengthValidation::Strict).unwrap();
+        }
+
+        // numbers written as text are checked as well
+        let ds = PrimitiveValue::from(0.1_f64 + 0.2_f64);
+        assert!(encode(VR::DS, &ds, LengthValidation::Strict).is_err());
+        // and never truncated
+        assert!(encode(VR::DS, &ds, LengthValidation::Truncate).is_err());
+    }
+
+    /// Test that values are truncated to the maximum length in bytes
+    /// once encoded, without splitting multi-byte characters.
+    #[test]
+    fn truncate_encoded_values() {
+        use crate::dataset::read::{WarningCollector, WarningKind};
+
+        let lo = DataElementHeader::new(Tag(0x0008, 0x0080), VR::LO, Length::UNDEFINED);
+        let encode = |text: &str, charset, marker: Option<&str>, collector| {
+            let mut sink = Vec::new();
+            let mut encoder = StatefulEncoder::new(
+                &mut sink,
+                EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                charset,
+            )
+            .with_length_validation(LengthValidation::Truncate)
+            .with_warning_collector(collector);
+            if let Some(marker) = marker {
+                encoder = encoder.with_truncation_marker(marker);
+            }
+            encoder
+                .encode_primitive_element(&lo, &PrimitiveValue::from(text))
+                .unwrap();
+            sink
+        };
+        let collector = WarningCollector::new();
+
+        // 81 bytes in UTF-8, the 64th byte being in the middle of a character
+        let text = format!("a{}", "é".repeat(40));
+        let sink = encode(&text, SpecificCharacterSet::ISO_IR_192, None, &collector);
+        let value = std::str::from_utf8(&sink[8..]).expect("invalid UTF-8 written");
+        assert_eq!(value, format!("a{} ", "é".repeat(31)));
+        assert_eq!(sink.len(), 8 + 64);
+        let warnings = collector.take();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].tag(), Tag(0x0008, 0x0080));
+        assert_eq!(
+            warnings[0].kind(),
+            &WarningKind::ValueTruncated {
+                index: 0,
+                length: 81,
+                max: 64
+            }
+        );
+
+        // 3-byte characters
+        let text = "山".repeat(30);
+        let sink = encode(&text, SpecificCharacterSet::ISO_IR_192, None, &collector);
+        let value = std::str::from_utf8(&sink[8..]).expect("invalid UTF-8 written");
+        assert_eq!(value, format!("{} ", "山".repeat(21)));
+
+        // with a marker in the remaining room
+        let text = format!("a{}", "é".repeat(40));
+        let sink = encode(
+            &text,
+            SpecificCharacterSet::ISO_IR_192,
+            Some("..."),
+            &collector,
+        );
+        let value = std::str::from_utf8(&sink[8..]).expect("invalid UTF-8 written");
+        assert_eq!(value, format!("a{}...", "é".repeat(30)));
+
+        // one byte per character in Latin-1
+        let text = "é".repeat(70);
+        let sink = encode(&text, SpecificCharacterSet::ISO_IR_100, None, &collector);
+        assert_eq!(sink.len(), 8 + 64);
+        assert!(sink[8..].iter().all(|&b| b == 0xE9));
+
+        // values which fit in bytes are left alone
+        let text = "é".repeat(32);
+        let sink = encode(&text, SpecificCharacterSet::ISO_IR_192, None, &collector);
+        assert_eq!(&sink[8..], text.as_bytes());
+        assert_eq!(collector.take().len(), 3);
+    }
+
+    /// Test that person names are encoded by component group,
+    /// as in PS3.5 H.3.1 (Example 1).
+    #[test]
+    fn encode_person_name_component_groups() {
+        let mut sink = Vec::new();
+        let mut encoder = StatefulEncoder::new(
+            &mut sink,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::from_values(["", "ISO 2022 IR 87"]),
+        );
+        let pn = DataElementHeader {
+            tag: Tag(0x0010, 0x0010),
+            vr: VR::PN,
+            len: Length::UNDEFINED,
+        };
+        encoder
+            .encode_primitive_element(
+                &pn,
+                &PrimitiveValue::from("Yamada^Tarou=山田^太郎=やまだ^たろう"),
+            )
+            .unwrap();
+        let value: &[u8] =
+            b"Yamada^Tarou=\x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B=\x1b$B$d$^$@\x1b(B^\x1b$B$?$m$&\x1b(B";
+        assert_eq!(&sink[8..], value);
+        assert_eq!(&sink[6..8], &(value.len() as u16).to_le_bytes());
+
+        // the alphabetic group only takes the first character set
+        let mut encoder = StatefulEncoder::new(
+            &mut sink,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::from_values(["", "ISO 2022 IR 87"]),
+        );
+        let err = encoder
+            .encode_primitive_element(&pn, &PrimitiveValue::from("山田^太郎"))
+            .unwrap_err();
+        assert!(matches!(err, Error::EncodeText { .. }));
+    }
 }
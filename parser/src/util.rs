@@ -1,4 +1,4 @@
-use std::io::{Read, Seek};
+use std::io::{self, Read, Seek};
 
 pub trait ReadSeek: Read + Seek {}
 impl<T: ?Sized> ReadSeek for T where T: Read + Seek {}
@@ -37,6 +37,31 @@ impl ExactSizeIterator for VoidRepeatN {
     }
 }
 
+/// A reader adapter which counts the bytes read through it.
+pub(crate) struct CountingRead<'a, S: ?Sized> {
+    source: &'a mut S,
+    count: u64,
+}
+
+impl<'a, S: ?Sized> CountingRead<'a, S> {
+    pub(crate) fn new(source: &'a mut S) -> Self {
+        CountingRead { source, count: 0 }
+    }
+
+    /// The number of bytes read so far.
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<S: ?Sized + Read> Read for CountingRead<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.source.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::n_times;
@@ -0,0 +1,143 @@
+//! Property tests checking that data element headers and values
+//! survive an encoding and decoding round trip.
+use arbitrary::{Arbitrary, Unstructured};
+use dicom_core::header::{DataElementHeader, HasLength, Header, Length};
+use dicom_core::{PrimitiveValue, Tag, VR};
+use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
+use dicom_encoding::decode::explicit_be::ExplicitVRBigEndianDecoder;
+use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
+use dicom_encoding::decode::Decode;
+use dicom_encoding::encode::explicit_be::ExplicitVRBigEndianEncoder;
+use dicom_encoding::encode::explicit_le::ExplicitVRLittleEndianEncoder;
+use dicom_encoding::encode::{Encode, EncoderFor};
+use dicom_encoding::text::SpecificCharacterSet;
+use dicom_parser::stateful::decode::{StatefulDecode, StatefulDecoder};
+use dicom_parser::stateful::encode::StatefulEncoder;
+use proptest::prelude::*;
+
+/// Strategy for values of any type implementing [`Arbitrary`],
+/// built from random unstructured bytes.
+fn arb<T>() -> impl Strategy<Value = T>
+where
+    T: for<'a> Arbitrary<'a> + std::fmt::Debug,
+{
+    any::<Vec<u8>>().prop_filter_map("not enough data", |bytes| {
+        T::arbitrary(&mut Unstructured::new(&bytes)).ok()
+    })
+}
+
+/// Whether the explicit VR header of this VR has a 16-bit length field.
+fn has_short_length(vr: VR) -> bool {
+    !matches!(
+        vr,
+        VR::OB | VR::OD | VR::OF | VR::OL | VR::OW | VR::SQ | VR::UC | VR::UR | VR::UT | VR::UN
+    )
+}
+
+/// A value representation which can hold the given binary value
+/// without conversion.
+fn binary_vr(value: &PrimitiveValue) -> Option<VR> {
+    match value {
+        PrimitiveValue::U8(_) => Some(VR::OB),
+        PrimitiveValue::I16(_) => Some(VR::SS),
+        PrimitiveValue::U16(_) => Some(VR::US),
+        PrimitiveValue::I32(_) => Some(VR::SL),
+        PrimitiveValue::U32(_) => Some(VR::UL),
+        PrimitiveValue::I64(_) => Some(VR::SV),
+        PrimitiveValue::U64(_) => Some(VR::UV),
+        // NaN would never compare equal to itself
+        PrimitiveValue::F32(v) if v.iter().all(|x| !x.is_nan()) => Some(VR::FL),
+        PrimitiveValue::F64(v) if v.iter().all(|x| !x.is_nan()) => Some(VR::FD),
+        PrimitiveValue::Tags(_) => Some(VR::AT),
+        _ => None,
+    }
+}
+
+fn header_roundtrip<E, D>(
+    encoder: E,
+    decoder: D,
+    header: DataElementHeader,
+) -> Result<(), TestCaseError>
+where
+    E: Encode,
+    D: Decode,
+{
+    let mut out = Vec::new();
+    let result = encoder.encode_element_header(&mut out, header);
+
+    if has_short_length(header.vr()) && header.length().0 > 0xFFFF {
+        prop_assert!(result.is_err(), "length should not fit in {:?}", header);
+        return Ok(());
+    }
+    let written = result.map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(written, out.len());
+
+    let (decoded, read) = decoder
+        .decode_header(&mut &out[..])
+        .map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(read, written);
+    prop_assert_eq!(decoded, header);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn explicit_vr_le_header_roundtrip(header in arb::<DataElementHeader>()) {
+        // item and delimiter headers are encoded differently
+        prop_assume!(header.tag().group() != 0xFFFE);
+        header_roundtrip(
+            ExplicitVRLittleEndianEncoder::default(),
+            ExplicitVRLittleEndianDecoder::default(),
+            header,
+        )?;
+    }
+
+    #[test]
+    fn explicit_vr_be_header_roundtrip(header in arb::<DataElementHeader>()) {
+        prop_assume!(header.tag().group() != 0xFFFE);
+        header_roundtrip(
+            ExplicitVRBigEndianEncoder::default(),
+            ExplicitVRBigEndianDecoder::default(),
+            header,
+        )?;
+    }
+
+    #[test]
+    fn binary_value_roundtrip(tag in arb::<Tag>(), value in arb::<PrimitiveValue>()) {
+        prop_assume!(tag.group() != 0xFFFE);
+        let vr = match binary_vr(&value) {
+            Some(vr) => vr,
+            None => return Err(TestCaseError::reject("not a binary value")),
+        };
+        // empty values are always decoded as `Empty`
+        prop_assume!(value.multiplicity() > 0);
+        // odd length byte values are padded
+        prop_assume!(value.calculate_byte_len() % 2 == 0);
+
+        let header = DataElementHeader::new(tag, vr, Length::UNDEFINED);
+        let mut out = Vec::new();
+        StatefulEncoder::new(
+            &mut out,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            SpecificCharacterSet::default(),
+        )
+        .encode_primitive_element(&header, &value)
+        .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+        let mut source = &out[..];
+        let mut decoder = StatefulDecoder::new(
+            &mut source,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+        let decoded_header = decoder.decode_header().map_err(|e| TestCaseError::fail(e.to_string()))?;
+        prop_assert_eq!(decoded_header.tag(), tag);
+        prop_assert_eq!(decoded_header.vr(), vr);
+        let decoded = decoder
+            .read_value(&decoded_header)
+            .map_err(|e| TestCaseError::fail(e.to_string()))?;
+        prop_assert_eq!(decoded, value);
+        prop_assert_eq!(decoder.position(), out.len() as u64);
+    }
+}
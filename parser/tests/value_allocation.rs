@@ -0,0 +1,166 @@
+//! Checks that reading a value with a huge declared length
+//! but a short body fails without allocating for the declared length.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::{Tag, VR};
+use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
+use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
+use dicom_encoding::text::SpecificCharacterSet;
+use dicom_parser::pixel_sequence::{self, FragmentRef};
+use dicom_parser::stateful::decode::Error;
+use dicom_parser::{StatefulDecode, StatefulDecoder};
+
+/// A global allocator recording the largest allocation made.
+struct MaxSizeAllocator;
+
+static MAX_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for MaxSizeAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        MAX_SIZE.fetch_max(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        MAX_SIZE.fetch_max(layout.size(), Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        MAX_SIZE.fetch_max(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: MaxSizeAllocator = MaxSizeAllocator;
+
+/// The declared length of the values read.
+const HUGE_LEN: u32 = 0xFFFF_FFF0;
+
+/// The largest allocation tolerated while reading a short body.
+const MAX_ALLOCATION: usize = 1 << 20;
+
+/// Obtain the largest allocation made by the given function.
+fn max_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    MAX_SIZE.store(0, Ordering::Relaxed);
+    let out = f();
+    (out, MAX_SIZE.load(Ordering::Relaxed))
+}
+
+fn decoder_for(
+    data: &[u8],
+) -> StatefulDecoder<ExplicitVRLittleEndianDecoder, &[u8], LittleEndianBasicDecoder> {
+    StatefulDecoder::new(
+        data,
+        ExplicitVRLittleEndianDecoder::default(),
+        LittleEndianBasicDecoder,
+        SpecificCharacterSet::default(),
+    )
+}
+
+// only one test in this binary,
+// so that allocations are not shared with other tests
+#[test]
+fn huge_declared_length_does_not_allocate_it() {
+    let body = [b'1'; 16];
+
+    for vr in [
+        VR::AE,
+        VR::AT,
+        VR::CS,
+        VR::DA,
+        VR::DS,
+        VR::DT,
+        VR::FD,
+        VR::FL,
+        VR::IS,
+        VR::LO,
+        VR::OB,
+        VR::OD,
+        VR::OF,
+        VR::OL,
+        VR::OV,
+        VR::OW,
+        VR::PN,
+        VR::SL,
+        VR::SS,
+        VR::SV,
+        VR::TM,
+        VR::UL,
+        VR::UN,
+        VR::US,
+        VR::UT,
+        VR::UV,
+    ] {
+        let header = DataElementHeader::new(Tag(0x0009, 0x1010), vr, Length(HUGE_LEN));
+        for preserved in [false, true] {
+            let mut decoder = decoder_for(&body);
+            let (result, max) = max_allocation(|| {
+                if preserved {
+                    decoder.read_value_preserved(&header)
+                } else {
+                    decoder.read_value(&header)
+                }
+            });
+            assert!(
+                matches!(
+                    result,
+                    Err(Error::TruncatedValue {
+                        expected: 0xFFFF_FFF0,
+                        ..
+                    })
+                ),
+                "unexpected result for {}: {:?}",
+                vr,
+                result
+            );
+            assert!(max < MAX_ALLOCATION, "{} allocated {} bytes", vr, max);
+        }
+    }
+
+    // item values
+    let mut decoder = decoder_for(&body);
+    let mut data = Vec::new();
+    let (result, max) = max_allocation(|| decoder.read_to_vec(HUGE_LEN, &mut data));
+    assert!(matches!(result, Err(Error::TruncatedValue { .. })));
+    assert!(max < MAX_ALLOCATION, "item value allocated {} bytes", max);
+
+    let mut decoder = decoder_for(&body);
+    let mut offsets = Vec::new();
+    let (result, max) = max_allocation(|| decoder.read_u32_to_vec(HUGE_LEN, &mut offsets));
+    assert!(matches!(result, Err(Error::TruncatedValue { .. })));
+    assert!(max < MAX_ALLOCATION, "offset table allocated {} bytes", max);
+
+    // encapsulated pixel data with a huge fragment
+    let mut data = vec![
+        0xFE, 0xFF, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00, // empty offset table
+        0xFE, 0xFF, 0x00, 0xE0, 0xF0, 0xFF, 0xFF, 0xFF, // fragment
+    ];
+    data.extend_from_slice(&body);
+    let mut decoder = decoder_for(&data);
+    let (result, max) = max_allocation(|| pixel_sequence::read_pixel_sequence(&mut decoder));
+    assert!(matches!(
+        result,
+        Err(pixel_sequence::Error::ReadItemValue { .. })
+    ));
+    assert!(max < MAX_ALLOCATION, "fragment allocated {} bytes", max);
+
+    let fragment = FragmentRef {
+        offset: 16,
+        len: HUGE_LEN,
+    };
+    let (result, max) = max_allocation(|| fragment.read_from(&mut Cursor::new(&data)));
+    assert!(matches!(
+        result,
+        Err(pixel_sequence::Error::ReadFragment { .. })
+    ));
+    assert!(max < MAX_ALLOCATION, "fragment allocated {} bytes", max);
+}
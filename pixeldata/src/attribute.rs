@@ -1,6 +1,6 @@
 //! Utility module for fetching key attributes from a DICOM object.
 
-use dicom_core::{DataDictionary, Tag};
+use dicom_core::{DataDictionary, DicomValue, PrimitiveValue, Tag};
 use dicom_dictionary_std::tags;
 use dicom_object::{mem::InMemElement, FileDicomObject, InMemDicomObject};
 use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
@@ -30,12 +30,38 @@ pub enum AttributeName {
     VoiLutFunction,
     WindowCenter,
     WindowWidth,
+    RedPaletteColorLookupTableDescriptor,
+    GreenPaletteColorLookupTableDescriptor,
+    BluePaletteColorLookupTableDescriptor,
+    RedPaletteColorLookupTableData,
+    GreenPaletteColorLookupTableData,
+    BluePaletteColorLookupTableData,
+    ModalityLutSequence,
+    VoiLutSequence,
+    LutDescriptor,
+    LutExplanation,
+    LutData,
+    OverlayRows,
+    OverlayColumns,
+    NumberOfFramesInOverlay,
+    OverlayDescription,
+    OverlayType,
+    OverlayOrigin,
+    ImageFrameOrigin,
+    OverlayBitsAllocated,
+    OverlayBitPosition,
+    OverlayData,
 }
 
 impl std::fmt::Display for AttributeName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AttributeName::VoiLutFunction => f.write_str("VOILUTFunction"),
+            AttributeName::ModalityLutSequence => f.write_str("ModalityLUTSequence"),
+            AttributeName::VoiLutSequence => f.write_str("VOILUTSequence"),
+            AttributeName::LutDescriptor => f.write_str("LUTDescriptor"),
+            AttributeName::LutExplanation => f.write_str("LUTExplanation"),
+            AttributeName::LutData => f.write_str("LUTData"),
             _ => std::fmt::Debug::fmt(self, f),
         }
     }
@@ -336,6 +362,348 @@ pub fn window_width<D: DataDictionary + Clone>(
     ww
 }
 
+/// Get the Red, Green, and Blue Palette Color Lookup Table Descriptors
+/// from the DICOM object,
+/// each as the raw 16-bit words of its three values.
+///
+/// Whether the descriptor was encoded as US or SS,
+/// the words are returned without reinterpretation.
+pub fn palette_color_lut_descriptors<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Result<[[u16; 3]; 3]> {
+    Ok([
+        retrieve_lut_descriptor(
+            obj,
+            tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+            AttributeName::RedPaletteColorLookupTableDescriptor,
+        )?,
+        retrieve_lut_descriptor(
+            obj,
+            tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+            AttributeName::GreenPaletteColorLookupTableDescriptor,
+        )?,
+        retrieve_lut_descriptor(
+            obj,
+            tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+            AttributeName::BluePaletteColorLookupTableDescriptor,
+        )?,
+    ])
+}
+
+/// Get the Red, Green, and Blue Palette Color Lookup Table Data
+/// from the DICOM object, as sequences of 16-bit words.
+///
+/// Data encoded as bytes is read as little endian words.
+pub fn palette_color_lut_data<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Result<[Vec<u16>; 3]> {
+    Ok([
+        retrieve_lut_data(
+            obj,
+            tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            AttributeName::RedPaletteColorLookupTableData,
+        )?,
+        retrieve_lut_data(
+            obj,
+            tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            AttributeName::GreenPaletteColorLookupTableData,
+        )?,
+        retrieve_lut_data(
+            obj,
+            tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            AttributeName::BluePaletteColorLookupTableData,
+        )?,
+    ])
+}
+
+/// The attributes of a lookup table in an item of
+/// the _Modality LUT Sequence_ or of the _VOI LUT Sequence_.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LutAttributes {
+    /// the raw words of the _LUT Descriptor_
+    pub descriptor: [u16; 3],
+    /// the _LUT Explanation_, if present
+    pub explanation: Option<String>,
+    /// the _LUT Data_, as 16-bit words
+    pub data: Vec<u16>,
+}
+
+/// Get the lookup table in the _Modality LUT Sequence_
+/// of the DICOM object, if present.
+pub fn modality_lut<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Result<Option<LutAttributes>> {
+    retrieve_lut_sequence(
+        obj,
+        tags::MODALITY_LUT_SEQUENCE,
+        AttributeName::ModalityLutSequence,
+    )
+}
+
+/// Get the first lookup table in the _VOI LUT Sequence_
+/// of the DICOM object, if present.
+pub fn voi_lut<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Result<Option<LutAttributes>> {
+    retrieve_lut_sequence(obj, tags::VOILUT_SEQUENCE, AttributeName::VoiLutSequence)
+}
+
+fn retrieve_lut_sequence<D>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    tag: Tag,
+    name: AttributeName,
+) -> Result<Option<LutAttributes>>
+where
+    D: DataDictionary + Clone,
+{
+    let item = match obj
+        .element_opt(tag)
+        .context(RetrieveSnafu { name })?
+        .and_then(|e| e.items())
+        .and_then(|items| items.first())
+    {
+        Some(item) => item,
+        None => return Ok(None),
+    };
+    let explanation = item
+        .element_opt(tags::LUT_EXPLANATION)
+        .context(RetrieveSnafu {
+            name: AttributeName::LutExplanation,
+        })?
+        .map(|e| e.to_str())
+        .transpose()
+        .context(ConvertValueSnafu {
+            name: AttributeName::LutExplanation,
+        })?
+        .map(|s| s.trim_end().to_string());
+    Ok(Some(LutAttributes {
+        descriptor: retrieve_lut_descriptor(
+            item,
+            tags::LUT_DESCRIPTOR,
+            AttributeName::LutDescriptor,
+        )?,
+        explanation,
+        data: retrieve_lut_data(item, tags::LUT_DATA, AttributeName::LutData)?,
+    }))
+}
+
+fn retrieve_lut_descriptor<D>(
+    obj: &InMemDicomObject<D>,
+    tag: Tag,
+    name: AttributeName,
+) -> Result<[u16; 3]>
+where
+    D: DataDictionary + Clone,
+{
+    let values = obj
+        .element_opt(tag)
+        .context(RetrieveSnafu { name })?
+        .context(MissingRequiredSnafu { name })?
+        .to_multi_int::<i32>()
+        .context(ConvertValueSnafu { name })?;
+    match values[..] {
+        [entries, first_mapped, bits] => Ok([entries as u16, first_mapped as u16, bits as u16]),
+        _ => InvalidValueSnafu {
+            name,
+            value: format!("{:?}", values),
+        }
+        .fail(),
+    }
+}
+
+fn retrieve_lut_data<D>(
+    obj: &InMemDicomObject<D>,
+    tag: Tag,
+    name: AttributeName,
+) -> Result<Vec<u16>>
+where
+    D: DataDictionary + Clone,
+{
+    let elem = obj
+        .element_opt(tag)
+        .context(RetrieveSnafu { name })?
+        .context(MissingRequiredSnafu { name })?;
+    match elem.value() {
+        DicomValue::Primitive(PrimitiveValue::U8(bytes)) => Ok(bytes
+            .chunks(2)
+            .map(|b| u16::from_le_bytes([b[0], b.get(1).copied().unwrap_or(0)]))
+            .collect()),
+        _ => elem
+            .to_multi_int::<u16>()
+            .context(ConvertValueSnafu { name }),
+    }
+}
+
+/// The attributes of an overlay plane in a repeating group `60xx`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlayAttributes {
+    pub rows: u16,
+    pub columns: u16,
+    pub number_of_frames: u32,
+    pub description: Option<String>,
+    pub overlay_type: String,
+    pub origin: [i16; 2],
+    pub image_frame_origin: u16,
+    pub bits_allocated: u16,
+    pub bit_position: u16,
+    /// the packed overlay data, if present
+    pub data: Option<Vec<u8>>,
+}
+
+/// Get the groups `60xx` of the overlay planes in the DICOM object,
+/// identified by the presence of _Overlay Rows_.
+pub fn overlay_groups<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Vec<u16> {
+    (0x6000..=0x601E)
+        .step_by(2)
+        .filter(|&group| {
+            matches!(
+                obj.element_opt(Tag(group, tags::OVERLAY_ROWS.inner().element())),
+                Ok(Some(_))
+            )
+        })
+        .collect()
+}
+
+/// Get the attributes of the overlay plane in the given group
+/// from the DICOM object.
+///
+/// Missing optional attributes take their default values:
+/// 1 frame starting at the first image frame,
+/// 1 bit allocated at bit position 0,
+/// and an origin at the first row and column.
+/// A missing _Overlay Type_ is taken as graphics (`G`).
+pub fn overlay<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    group: u16,
+) -> Result<OverlayAttributes> {
+    let tag = |range: dicom_core::dictionary::TagRange| Tag(group, range.inner().element());
+
+    let name = AttributeName::NumberOfFramesInOverlay;
+    let number_of_frames = match obj
+        .element_opt(tag(tags::NUMBER_OF_FRAMES_IN_OVERLAY))
+        .context(RetrieveSnafu { name })?
+    {
+        Some(elem) => {
+            let integer = elem.to_int::<i32>().context(ConvertValueSnafu { name })?;
+            ensure!(
+                integer > 0,
+                InvalidValueSnafu {
+                    name,
+                    value: integer.to_string(),
+                }
+            );
+            integer as u32
+        }
+        None => 1,
+    };
+
+    let name = AttributeName::OverlayOrigin;
+    let origin = match obj
+        .element_opt(tag(tags::OVERLAY_ORIGIN))
+        .context(RetrieveSnafu { name })?
+    {
+        Some(elem) => {
+            let values = elem
+                .to_multi_int::<i16>()
+                .context(ConvertValueSnafu { name })?;
+            match values[..] {
+                [row, column] => [row, column],
+                _ => {
+                    return InvalidValueSnafu {
+                        name,
+                        value: format!("{:?}", values),
+                    }
+                    .fail()
+                }
+            }
+        }
+        None => [1, 1],
+    };
+
+    let name = AttributeName::OverlayData;
+    let data = obj
+        .element_opt(tag(tags::OVERLAY_DATA))
+        .context(RetrieveSnafu { name })?
+        .map(|elem| elem.to_bytes().map(|bytes| bytes.into_owned()))
+        .transpose()
+        .context(ConvertValueSnafu { name })?;
+
+    Ok(OverlayAttributes {
+        rows: retrieve_required_u16(obj, tag(tags::OVERLAY_ROWS), AttributeName::OverlayRows)?,
+        columns: retrieve_required_u16(
+            obj,
+            tag(tags::OVERLAY_COLUMNS),
+            AttributeName::OverlayColumns,
+        )?,
+        number_of_frames,
+        description: retrieve_optional_str(
+            obj,
+            tag(tags::OVERLAY_DESCRIPTION),
+            AttributeName::OverlayDescription,
+        )?,
+        overlay_type: retrieve_optional_str(
+            obj,
+            tag(tags::OVERLAY_TYPE),
+            AttributeName::OverlayType,
+        )?
+        .unwrap_or_else(|| "G".to_string()),
+        origin,
+        image_frame_origin: retrieve_optional_u16(
+            obj,
+            tag(tags::IMAGE_FRAME_ORIGIN),
+            AttributeName::ImageFrameOrigin,
+        )?
+        .unwrap_or(1),
+        bits_allocated: retrieve_optional_u16(
+            obj,
+            tag(tags::OVERLAY_BITS_ALLOCATED),
+            AttributeName::OverlayBitsAllocated,
+        )?
+        .unwrap_or(1),
+        bit_position: retrieve_optional_u16(
+            obj,
+            tag(tags::OVERLAY_BIT_POSITION),
+            AttributeName::OverlayBitPosition,
+        )?
+        .unwrap_or(0),
+        data,
+    })
+}
+
+fn retrieve_optional_u16<D>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    tag: Tag,
+    name: AttributeName,
+) -> Result<Option<u16>>
+where
+    D: DataDictionary + Clone,
+{
+    obj.element_opt(tag)
+        .context(RetrieveSnafu { name })?
+        .map(|elem| elem.uint16().context(CastValueSnafu { name }))
+        .transpose()
+}
+
+fn retrieve_optional_str<D>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    tag: Tag,
+    name: AttributeName,
+) -> Result<Option<String>>
+where
+    D: DataDictionary + Clone,
+{
+    obj.element_opt(tag)
+        .context(RetrieveSnafu { name })?
+        .map(|elem| {
+            elem.to_str()
+                .context(ConvertValueSnafu { name })
+                .map(|v| v.trim().to_string())
+        })
+        .transpose()
+}
+
 #[inline]
 fn retrieve_required_u16<D>(
     obj: &FileDicomObject<InMemDicomObject<D>>,
@@ -403,7 +771,6 @@ impl fmt::Display for PlanarConfiguration {
 
 /// Get the PlanarConfiguration from the DICOM object,
 /// returning the standard planar configuration by default
-#[cfg(not(feature = "gdcm"))]
 pub fn planar_configuration<D: DataDictionary + Clone>(
     obj: &FileDicomObject<InMemDicomObject<D>>,
 ) -> Result<PlanarConfiguration> {
@@ -0,0 +1,314 @@
+//! Color space conversion between `RGB` and `YBR_FULL`,
+//! and chroma upsampling of `YBR_FULL_422` samples.
+//!
+//! The conversions follow the equations in PS3.3 C.7.6.3.1.2,
+//! computed in 16-bit fixed point arithmetic
+//! and rounded to the nearest integer, with ties rounded up,
+//! so that results are reproducible across platforms.
+//! Samples of `bits` bits are expected,
+//! with chroma samples centered at `2^(bits-1)`.
+
+use crate::attribute::{PhotometricInterpretation, PlanarConfiguration};
+use crate::frame::DecodedFrame;
+use crate::{Result, UnsupportedOtherSnafu};
+use std::borrow::Cow;
+
+/// Fixed point scale of the conversion coefficients.
+const SHIFT: u32 = 16;
+/// Half of the fixed point scale, for rounding.
+const HALF: i64 = 1 << (SHIFT - 1);
+
+/// Convert a `YBR_FULL` pixel into `RGB`.
+///
+/// Output samples are clamped to the range of `bits` bits.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_pixeldata::color::ybr_full_to_rgb;
+/// assert_eq!(ybr_full_to_rgb([76, 85, 255], 8), [254, 0, 0]);
+/// ```
+pub fn ybr_full_to_rgb(ybr: [u16; 3], bits: u16) -> [u16; 3] {
+    let offset = 1_i64 << (bits - 1);
+    let y = i64::from(ybr[0]) << SHIFT;
+    let cb = i64::from(ybr[1]) - offset;
+    let cr = i64::from(ybr[2]) - offset;
+    // R = Y + 1.402 CR'
+    // G = Y - 0.344136 CB' - 0.714136 CR'
+    // B = Y + 1.772 CB'
+    let r = y + 91_881 * cr;
+    let g = y - 22_554 * cb - 46_802 * cr;
+    let b = y + 116_130 * cb;
+    [round(r, bits), round(g, bits), round(b, bits)]
+}
+
+/// Convert an `RGB` pixel into `YBR_FULL`.
+///
+/// Output samples are clamped to the range of `bits` bits.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_pixeldata::color::rgb_to_ybr_full;
+/// assert_eq!(rgb_to_ybr_full([255, 0, 0], 8), [76, 85, 255]);
+/// ```
+pub fn rgb_to_ybr_full(rgb: [u16; 3], bits: u16) -> [u16; 3] {
+    let offset = 1_i64 << (u32::from(bits) - 1 + SHIFT);
+    let [r, g, b] = rgb.map(i64::from);
+    // Y  = +0.2990 R + 0.5870 G + 0.1140 B
+    // CB = -0.1687 R - 0.3313 G + 0.5000 B + offset
+    // CR = +0.5000 R - 0.4187 G - 0.0813 B + offset
+    let y = 19_595 * r + 38_470 * g + 7_471 * b;
+    let cb = -11_059 * r - 21_709 * g + 32_768 * b + offset;
+    let cr = 32_768 * r - 27_439 * g - 5_329 * b + offset;
+    [round(y, bits), round(cb, bits), round(cr, bits)]
+}
+
+/// Round a fixed point value to the nearest integer
+/// and clamp it to the range of `bits` bits.
+fn round(value: i64, bits: u16) -> u16 {
+    let max = (1_i64 << bits) - 1;
+    ((value + HALF) >> SHIFT).clamp(0, max) as u16
+}
+
+/// Upsample `YBR_FULL_422` samples into `YBR_FULL`.
+///
+/// The input is a sequence of pairs of horizontally adjacent pixels,
+/// each as four samples `Y1 Y2 CB CR`,
+/// which become the six samples `Y1 CB CR Y2 CB CR`.
+/// A trailing incomplete group of samples is ignored.
+pub fn upsample_ybr_422<T: Copy>(samples: &[T]) -> Vec<T> {
+    let mut out = Vec::with_capacity(samples.len() / 4 * 6);
+    for group in samples.chunks_exact(4) {
+        let (y1, y2, cb, cr) = (group[0], group[1], group[2], group[3]);
+        out.extend_from_slice(&[y1, cb, cr, y2, cb, cr]);
+    }
+    out
+}
+
+/// Convert a decoded frame of `pixels` pixels
+/// in the `YBR_FULL` or `YBR_FULL_422` color space into `RGB`,
+/// keeping its planar configuration.
+///
+/// Chroma subsampled samples, as found in native `YBR_FULL_422` pixel data,
+/// are upsampled first.
+pub(crate) fn ybr_frame_to_rgb<'a>(
+    frame: DecodedFrame<'a>,
+    pixels: usize,
+    bits_stored: u16,
+) -> Result<DecodedFrame<'a>> {
+    let mut samples: Vec<u16> = match frame.bits_allocated {
+        8 => frame.bytes.iter().copied().map(u16::from).collect(),
+        16 => frame
+            .bytes
+            .chunks_exact(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .collect(),
+        bits_allocated => {
+            return UnsupportedOtherSnafu {
+                name: "BitsAllocated",
+                value: bits_allocated.to_string(),
+            }
+            .fail()?
+        }
+    };
+    if frame.photometric_interpretation == PhotometricInterpretation::YbrFull422
+        && samples.len() == pixels * 2
+    {
+        samples = upsample_ybr_422(&samples);
+    }
+    let bits = bits_stored.clamp(1, frame.bits_allocated);
+
+    match frame.planar_configuration {
+        PlanarConfiguration::Standard => {
+            for pixel in samples.chunks_exact_mut(3) {
+                let rgb = ybr_full_to_rgb([pixel[0], pixel[1], pixel[2]], bits);
+                pixel.copy_from_slice(&rgb);
+            }
+        }
+        PlanarConfiguration::PixelFirst => {
+            let n = samples.len() / 3;
+            for i in 0..n {
+                let rgb = ybr_full_to_rgb([samples[i], samples[n + i], samples[2 * n + i]], bits);
+                samples[i] = rgb[0];
+                samples[n + i] = rgb[1];
+                samples[2 * n + i] = rgb[2];
+            }
+        }
+    }
+
+    let bytes = if frame.bits_allocated == 8 {
+        samples.into_iter().map(|sample| sample as u8).collect()
+    } else {
+        samples.into_iter().flat_map(u16::to_ne_bytes).collect()
+    };
+    Ok(DecodedFrame {
+        bytes: Cow::Owned(bytes),
+        converted_from: Some(frame.photometric_interpretation.clone()),
+        photometric_interpretation: PhotometricInterpretation::Rgb,
+        ..frame
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::tests::dummy_image;
+    use crate::{FrameDecodeOptions, PixelDataAccess};
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+
+    fn with_pi(
+        mut obj: dicom_object::DefaultDicomObject,
+        pi: &str,
+    ) -> dicom_object::DefaultDicomObject {
+        obj.put(DataElement::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            VR::CS,
+            PrimitiveValue::from(pi),
+        ));
+        obj
+    }
+
+    #[test]
+    fn ybr_full_to_rgb_known_values() {
+        // gray levels have no chroma
+        assert_eq!(ybr_full_to_rgb([0, 128, 128], 8), [0, 0, 0]);
+        assert_eq!(ybr_full_to_rgb([128, 128, 128], 8), [128, 128, 128]);
+        assert_eq!(ybr_full_to_rgb([255, 128, 128], 8), [255, 255, 255]);
+        // primary colors:
+        // R = 76 + 1.402 * 127 = 254.054
+        assert_eq!(ybr_full_to_rgb([76, 85, 255], 8), [254, 0, 0]);
+        // G = 150 - 0.344136 * -107 - 0.714136 * -107 = 263.4, clamped
+        assert_eq!(ybr_full_to_rgb([150, 21, 21], 8), [0, 255, 0]);
+        // B = 29 + 1.772 * 127 = 254.044
+        assert_eq!(ybr_full_to_rgb([29, 255, 107], 8), [0, 0, 254]);
+        // 16-bit samples are centered at 32768
+        assert_eq!(
+            ybr_full_to_rgb([1000, 32768, 32768], 16),
+            [1000, 1000, 1000]
+        );
+        assert_eq!(ybr_full_to_rgb([1000, 32768, 33768], 16), [2402, 286, 1000]);
+    }
+
+    #[test]
+    fn rgb_to_ybr_full_known_values() {
+        assert_eq!(rgb_to_ybr_full([0, 0, 0], 8), [0, 128, 128]);
+        assert_eq!(rgb_to_ybr_full([255, 255, 255], 8), [255, 128, 128]);
+        // Y = 0.299 * 255 = 76.245
+        // CB = -0.1687 * 255 + 128 = 84.98
+        // CR = 0.5 * 255 + 128 = 255.5, clamped
+        assert_eq!(rgb_to_ybr_full([255, 0, 0], 8), [76, 85, 255]);
+        assert_eq!(rgb_to_ybr_full([0, 255, 0], 8), [150, 44, 21]);
+        assert_eq!(rgb_to_ybr_full([0, 0, 255], 8), [29, 255, 107]);
+    }
+
+    #[test]
+    fn rgb_ybr_round_trip() {
+        for rgb in [[12, 200, 99], [100, 100, 100], [230, 30, 130]] {
+            let back = ybr_full_to_rgb(rgb_to_ybr_full(rgb, 8), 8);
+            for (a, b) in rgb.iter().zip(back) {
+                assert!((i32::from(*a) - i32::from(b)).abs() <= 1, "{:?}", rgb);
+            }
+        }
+    }
+
+    #[test]
+    fn upsample_422() {
+        assert_eq!(
+            upsample_ybr_422(&[10_u8, 20, 128, 129, 30, 40, 100, 200, 99]),
+            vec![10, 128, 129, 20, 128, 129, 30, 100, 200, 40, 100, 200]
+        );
+    }
+
+    #[test]
+    fn decode_ybr_full_frame_to_rgb() {
+        let obj = with_pi(
+            dummy_image(
+                1,
+                2,
+                None,
+                3,
+                8,
+                dicom_value!(U8, [76, 85, 255, 29, 255, 107]),
+            ),
+            "YBR_FULL",
+        );
+        let frames = obj.frames().unwrap();
+
+        // not converted by default
+        let frame = frames.decode_frame(0).unwrap();
+        assert_eq!(
+            frame.photometric_interpretation,
+            PhotometricInterpretation::YbrFull
+        );
+        assert_eq!(frame.converted_from, None);
+        assert_eq!(&frame.bytes[..], [76, 85, 255, 29, 255, 107]);
+
+        let options = FrameDecodeOptions::new().with_rgb(true);
+        let frame = frames.decode_frame_with_options(0, &options).unwrap();
+        assert_eq!(
+            frame.photometric_interpretation,
+            PhotometricInterpretation::Rgb
+        );
+        assert_eq!(
+            frame.converted_from,
+            Some(PhotometricInterpretation::YbrFull)
+        );
+        assert_eq!(&frame.bytes[..], [254, 0, 0, 0, 0, 254]);
+    }
+
+    #[test]
+    fn decode_native_ybr_422_frames_to_rgb() {
+        // 2 frames of 2x2 pixels, 2 samples per pixel on average
+        #[rustfmt::skip]
+        let data = [
+            // frame 0
+            0, 255, 128, 128, 128, 128, 128, 128,
+            // frame 1
+            76, 76, 85, 255, 29, 29, 255, 107,
+        ];
+        let obj = with_pi(
+            dummy_image(
+                2,
+                2,
+                Some(2),
+                3,
+                8,
+                PrimitiveValue::U8(data.iter().copied().collect()),
+            ),
+            "YBR_FULL_422",
+        );
+        let frames = obj.frames().unwrap();
+        assert_eq!(frames.info().frame_length(), 8);
+        assert_eq!(frames.info().expected_length(), 16);
+
+        // the stored frame is returned as is by default
+        assert_eq!(&frames.decode_frame(1).unwrap().bytes[..], &data[8..]);
+
+        let options = FrameDecodeOptions::new().with_rgb(true);
+        let frame = frames.decode_frame_with_options(0, &options).unwrap();
+        assert_eq!(
+            frame.converted_from,
+            Some(PhotometricInterpretation::YbrFull422)
+        );
+        #[rustfmt::skip]
+        assert_eq!(
+            &frame.bytes[..],
+            [
+                0, 0, 0, 255, 255, 255,
+                128, 128, 128, 128, 128, 128,
+            ]
+        );
+        let frame = frames.decode_frame_with_options(1, &options).unwrap();
+        assert_eq!(frame.bytes.len(), 2 * 2 * 3);
+        #[rustfmt::skip]
+        assert_eq!(
+            &frame.bytes[..],
+            [
+                254, 0, 0, 254, 0, 0,
+                0, 0, 254, 0, 0, 254,
+            ]
+        );
+    }
+}
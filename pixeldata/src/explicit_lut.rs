@@ -0,0 +1,216 @@
+//! Lookup tables given explicitly in the object,
+//! such as those of the _Modality LUT Sequence_
+//! and of the _VOI LUT Sequence_.
+
+use crate::attribute::PixelRepresentation;
+use crate::{LengthMismatchLutSnafu, Result, UnsupportedOtherSnafu};
+
+/// A lookup table defined by a _LUT Descriptor_ and its _LUT Data_,
+/// mapping input values to output values of 8 to 16 bits.
+///
+/// Modality and VOI lookup tables are obtained via
+/// [`PixelDataInfo::modality_lut`](crate::PixelDataInfo::modality_lut)
+/// and [`PixelDataInfo::voi_lut`](crate::PixelDataInfo::voi_lut).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplicitLut {
+    /// the input value mapped to the first entry
+    first_mapped: i64,
+    /// the number of bits of each entry
+    bits: u16,
+    /// the table entries
+    entries: Vec<u16>,
+    /// the free form description of the table
+    explanation: Option<String>,
+}
+
+impl ExplicitLut {
+    /// Build a lookup table from the raw words of a _LUT Descriptor_
+    /// and the _LUT Data_.
+    ///
+    /// The first value of the descriptor is the number of entries,
+    /// where 0 stands for 65536 entries.
+    /// The first input value mapped
+    /// is interpreted according to the given pixel representation,
+    /// regardless of whether the descriptor was encoded as US or SS.
+    /// Tables of 8-bit entries can be either packed two entries per word,
+    /// or one entry per word.
+    pub fn new(
+        descriptor: [u16; 3],
+        data: Vec<u16>,
+        pixel_representation: PixelRepresentation,
+    ) -> Result<Self> {
+        let (lut, expected) = Self::unpack(descriptor, data, pixel_representation)?;
+        if lut.entries.len() != expected {
+            return LengthMismatchLutSnafu {
+                expected,
+                actual: lut.entries.len(),
+            }
+            .fail()?;
+        }
+        Ok(lut)
+    }
+
+    /// Build a lookup table without checking its number of entries,
+    /// which is returned alongside it.
+    pub(crate) fn unpack(
+        [entries, first_mapped, bits]: [u16; 3],
+        data: Vec<u16>,
+        pixel_representation: PixelRepresentation,
+    ) -> Result<(Self, usize)> {
+        // 0 stands for 2^16 entries
+        let len = if entries == 0 {
+            0x1_0000
+        } else {
+            usize::from(entries)
+        };
+        let first_mapped = match pixel_representation {
+            PixelRepresentation::Signed => i64::from(first_mapped as i16),
+            PixelRepresentation::Unsigned => i64::from(first_mapped),
+        };
+        let entries = match bits {
+            // packed, two entries per word
+            8 if data.len() == len.div_ceil(2) && len > 1 => data
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .take(len)
+                .map(u16::from)
+                .collect(),
+            // one entry per word,
+            // which some writers place in the high byte
+            8 if data.iter().any(|&word| word > 0xFF) => {
+                data.iter().map(|word| word >> 8).collect()
+            }
+            8..=16 => data,
+            _ => {
+                return UnsupportedOtherSnafu {
+                    name: "LUT entry size",
+                    value: bits.to_string(),
+                }
+                .fail()?
+            }
+        };
+        let lut = ExplicitLut {
+            first_mapped,
+            bits,
+            entries,
+            explanation: None,
+        };
+        Ok((lut, len))
+    }
+
+    /// Set the free form description of the table,
+    /// as given by _LUT Explanation_.
+    pub fn with_explanation(mut self, explanation: impl Into<String>) -> Self {
+        self.explanation = Some(explanation.into());
+        self
+    }
+
+    /// The input value mapped to the first entry.
+    #[inline]
+    pub fn first_mapped(&self) -> i64 {
+        self.first_mapped
+    }
+
+    /// The number of bits of each entry.
+    #[inline]
+    pub fn bits(&self) -> u16 {
+        self.bits
+    }
+
+    /// The table entries.
+    #[inline]
+    pub fn entries(&self) -> &[u16] {
+        &self.entries
+    }
+
+    /// The free form description of the table, if any.
+    #[inline]
+    pub fn explanation(&self) -> Option<&str> {
+        self.explanation.as_deref()
+    }
+
+    /// The maximum value an entry can take
+    /// given the number of bits per entry.
+    pub fn max_output(&self) -> u16 {
+        (((1_u32) << self.bits) - 1) as u16
+    }
+
+    /// Map an input value to its table entry.
+    ///
+    /// Values before the first mapped value map to the first entry,
+    /// and values past the end of the table map to the last entry.
+    pub fn apply(&self, value: i64) -> u16 {
+        let last = self.entries.len() as i64 - 1;
+        let index = (value - self.first_mapped).clamp(0, last) as usize;
+        self.entries[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExplicitLut;
+    use crate::{InnerError, PixelRepresentation};
+
+    #[test]
+    fn apply_with_offset_and_clamping() {
+        let lut = ExplicitLut::new(
+            [4, -2_i16 as u16, 12],
+            vec![100, 200, 300, 4095],
+            PixelRepresentation::Signed,
+        )
+        .unwrap()
+        .with_explanation("TEST");
+        assert_eq!(lut.first_mapped(), -2);
+        assert_eq!(lut.bits(), 12);
+        assert_eq!(lut.max_output(), 4095);
+        assert_eq!(lut.explanation(), Some("TEST"));
+
+        assert_eq!(lut.apply(-100), 100);
+        assert_eq!(lut.apply(-2), 100);
+        assert_eq!(lut.apply(-1), 200);
+        assert_eq!(lut.apply(0), 300);
+        assert_eq!(lut.apply(1), 4095);
+        assert_eq!(lut.apply(1000), 4095);
+
+        // the same descriptor word as unsigned
+        let lut = ExplicitLut::new(
+            [4, -2_i16 as u16, 12],
+            vec![100, 200, 300, 4095],
+            PixelRepresentation::Unsigned,
+        )
+        .unwrap();
+        assert_eq!(lut.first_mapped(), 0xFFFE);
+        assert_eq!(lut.apply(0), 100);
+    }
+
+    #[test]
+    fn descriptor_zero_means_65536_entries() {
+        let data: Vec<u16> = (0..=0xFFFF_u32).map(|i| (i >> 4) as u16).collect();
+        let lut = ExplicitLut::new([0, 0, 16], data, PixelRepresentation::Unsigned).unwrap();
+        assert_eq!(lut.entries().len(), 0x1_0000);
+        assert_eq!(lut.apply(0x1234), 0x0123);
+        assert_eq!(lut.apply(0xFFFF), 0x0FFF);
+        assert_eq!(lut.apply(0x1_0000), 0x0FFF);
+
+        // 8-bit entries packed two per word
+        let data: Vec<u16> = (0..0x8000_u32)
+            .map(|i| u16::from_le_bytes([(i * 2) as u8, (i * 2 + 1) as u8]))
+            .collect();
+        let lut = ExplicitLut::new([0, 0, 8], data, PixelRepresentation::Unsigned).unwrap();
+        assert_eq!(lut.entries().len(), 0x1_0000);
+        assert_eq!(lut.apply(0x0102), 0x02);
+        assert_eq!(lut.apply(0x0103), 0x03);
+
+        // too short
+        let err = ExplicitLut::new([0, 0, 16], vec![0; 0x100], PixelRepresentation::Unsigned)
+            .unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::LengthMismatchLut {
+                expected: 0x1_0000,
+                actual: 0x100,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,1348 @@
+//! Frame by frame access to pixel data in its stored form.
+
+use crate::attribute::{PhotometricInterpretation, PlanarConfiguration};
+use crate::color::ybr_frame_to_rgb;
+use crate::info::{PixelDataBytes, PixelDataInfo};
+use crate::normalize::normalize_frame;
+use crate::planar::convert_frame;
+#[cfg(any(feature = "jpeg", feature = "jpegls", feature = "rle"))]
+use crate::DecodeFrameSnafu;
+#[cfg(any(feature = "jpeg", feature = "jpegls"))]
+use crate::DecodedFrameMismatchSnafu;
+use crate::{
+    FrameOutOfRangeSnafu, Result, UnresolvedFrameFragmentsSnafu, UnsupportedOtherSnafu,
+    UnsupportedTransferSyntaxSnafu,
+};
+use dicom_dictionary_std::uids;
+#[cfg(any(feature = "jpeg", feature = "jpegls", feature = "rle"))]
+use snafu::ResultExt;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::ops::Range;
+
+/// A single frame of pixel data,
+/// either native or still encoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame<'a> {
+    /// the index of the frame, starting at 0
+    pub index: u32,
+    /// the bytes of the frame,
+    /// as a concatenation of its fragments in the case of encapsulated pixel data
+    pub bytes: Cow<'a, [u8]>,
+}
+
+/// A single frame of pixel data in native form,
+/// decoded from the stored form if necessary.
+///
+/// See [`Frames::decode_frame`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedFrame<'a> {
+    /// the index of the frame, starting at 0
+    pub index: u32,
+    /// the number of rows of the frame
+    pub rows: u32,
+    /// the number of columns of the frame
+    pub columns: u32,
+    /// the native pixel data samples of the frame,
+    /// in native byte order
+    pub bytes: Cow<'a, [u8]>,
+    /// the photometric interpretation of the decoded samples,
+    /// which may differ from the one declared in the data set
+    pub photometric_interpretation: PhotometricInterpretation,
+    /// the planar configuration of the decoded samples
+    pub planar_configuration: PlanarConfiguration,
+    /// the number of samples per pixel of the decoded samples
+    pub samples_per_pixel: u16,
+    /// the number of bits allocated for each decoded sample
+    pub bits_allocated: u16,
+    /// the photometric interpretation of the samples
+    /// before they were converted into the current one,
+    /// if a color space conversion was applied
+    pub converted_from: Option<PhotometricInterpretation>,
+    /// whether the samples were normalized
+    /// so that only the bits stored remain, starting at the lowest bit,
+    /// with signed samples sign extended to the full sample size
+    pub normalized: bool,
+    /// the maximum absolute error of each decoded sample
+    /// declared by a near-lossless code stream,
+    /// such as the `NEAR` parameter of JPEG-LS
+    pub near_lossless: Option<u16>,
+}
+
+/// Option set for decoding frames
+/// through [`Frames::decode_frame_with_options`].
+#[derive(Debug, Default, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct FrameDecodeOptions {
+    /// Whether to convert frames in the `YBR_FULL` or `YBR_FULL_422`
+    /// color spaces into `RGB`
+    pub rgb: bool,
+    /// The planar configuration to convert frames into,
+    /// or `None` to keep the planar configuration of the decoded samples
+    pub planar_configuration: Option<PlanarConfiguration>,
+    /// Whether to normalize the samples of each frame
+    /// according to _Bits Stored_, _High Bit_, and _Pixel Representation_
+    pub normalize_bits: bool,
+    /// The maximum number of frames held in memory at once
+    /// while decoding frames in parallel,
+    /// or `None` to use twice the number of threads in the thread pool
+    pub max_frames_in_flight: Option<usize>,
+}
+
+impl FrameDecodeOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set whether to convert `YBR_FULL` and `YBR_FULL_422` frames into `RGB`.
+    pub fn with_rgb(mut self, rgb: bool) -> Self {
+        self.rgb = rgb;
+        self
+    }
+
+    /// Set whether to normalize the samples of each frame
+    /// (see [`normalize`](crate::normalize)).
+    pub fn with_normalized_bits(mut self, normalize_bits: bool) -> Self {
+        self.normalize_bits = normalize_bits;
+        self
+    }
+
+    /// Set the planar configuration to convert frames into.
+    pub fn with_planar_configuration(mut self, planar_configuration: PlanarConfiguration) -> Self {
+        self.planar_configuration = Some(planar_configuration);
+        self
+    }
+
+    /// Set the maximum number of frames held in memory at once
+    /// while decoding frames in parallel.
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn with_max_frames_in_flight(mut self, max_frames_in_flight: usize) -> Self {
+        self.max_frames_in_flight = Some(max_frames_in_flight);
+        self
+    }
+}
+
+/// An iterator over the frames of pixel data.
+///
+/// Frames are only sliced out of the pixel data as they are requested,
+/// and skipping frames with [`nth`](Iterator::nth) or [`skip`](Iterator::skip)
+/// does not touch the frames in between.
+///
+/// Obtained via [`PixelDataInfo::into_frames`]
+/// or [`PixelDataAccess::frames`](crate::PixelDataAccess::frames).
+#[derive(Debug, Clone)]
+pub struct Frames<'a> {
+    info: PixelDataInfo<'a>,
+    layout: Layout,
+    /// the index of the next frame to yield
+    next: u32,
+}
+
+/// Where each frame lies in the pixel data.
+#[derive(Debug, Clone)]
+enum Layout {
+    /// native frames of a fixed length in bytes
+    Native { frame_length: usize },
+    /// the range of fragments making up each frame
+    Encapsulated { fragments: Vec<Range<usize>> },
+}
+
+impl<'a> PixelDataInfo<'a> {
+    /// Turn this pixel data into an iterator over its frames.
+    ///
+    /// For encapsulated pixel data,
+    /// fragments are assigned to frames through the basic offset table.
+    /// If the table is empty,
+    /// this is only possible when there is a single frame
+    /// or one fragment per frame.
+    pub fn into_frames(self) -> Result<Frames<'a>> {
+        let layout = match self.data() {
+            PixelDataBytes::Native(_) => {
+                let bits = u64::from(self.rows())
+                    * u64::from(self.columns())
+                    * u64::from(self.samples_per_pixel())
+                    * u64::from(self.bits_allocated());
+                if !bits.is_multiple_of(8) && self.number_of_frames() > 1 {
+                    return UnsupportedOtherSnafu {
+                        name: "frame length in bits",
+                        value: bits.to_string(),
+                    }
+                    .fail()?;
+                }
+                Layout::Native {
+                    frame_length: self.frame_length() as usize,
+                }
+            }
+            PixelDataBytes::Encapsulated {
+                offset_table,
+                fragments,
+            } => Layout::Encapsulated {
+                fragments: frame_fragments(offset_table, fragments, self.number_of_frames())?,
+            },
+        };
+        Ok(Frames {
+            info: self,
+            layout,
+            next: 0,
+        })
+    }
+}
+
+impl<'a> Frames<'a> {
+    /// Retrieve the pixel data properties of the frames.
+    pub fn info(&self) -> &PixelDataInfo<'a> {
+        &self.info
+    }
+
+    /// Retrieve the frame at the given index,
+    /// regardless of the iterator's current position.
+    pub fn get(&self, index: u32) -> Result<Frame<'a>> {
+        if index >= self.info.number_of_frames() {
+            return FrameOutOfRangeSnafu {
+                frame_number: index,
+            }
+            .fail()?;
+        }
+        let bytes = match (&self.layout, self.info.data()) {
+            (Layout::Native { frame_length }, PixelDataBytes::Native(data)) => {
+                let start = frame_length * index as usize;
+                let end = start + frame_length;
+                if end > data.len() {
+                    return FrameOutOfRangeSnafu {
+                        frame_number: index,
+                    }
+                    .fail()?;
+                }
+                match data {
+                    Cow::Borrowed(data) => Cow::Borrowed(&data[start..end]),
+                    Cow::Owned(data) => Cow::Owned(data[start..end].to_vec()),
+                }
+            }
+            (
+                Layout::Encapsulated { fragments: ranges },
+                PixelDataBytes::Encapsulated { fragments, .. },
+            ) => {
+                let fragments = &fragments[ranges[index as usize].clone()];
+                match fragments {
+                    [fragment] => Cow::Borrowed(&fragment[..]),
+                    _ => Cow::Owned(fragments.concat()),
+                }
+            }
+            _ => unreachable!("frame layout does not match pixel data"),
+        };
+        Ok(Frame { index, bytes })
+    }
+}
+
+impl<'a> Frames<'a> {
+    /// Retrieve the frame at the given index in native form,
+    /// decoding it if the pixel data is encapsulated.
+    ///
+    /// Frames of native pixel data are returned as is,
+    /// except for `PALETTE COLOR` images,
+    /// whose frames are always expanded into `RGB`
+    /// through the palette color lookup tables
+    /// (see [`PaletteColorLut`](crate::PaletteColorLut)).
+    /// Decoding is currently supported for
+    /// _Encapsulated Uncompressed Explicit VR Little Endian_,
+    /// _RLE Lossless_ (requires the `rle` feature),
+    /// _JPEG Baseline_, _JPEG Extended_, and _JPEG Lossless_
+    /// (require the `jpeg` feature),
+    /// and _JPEG-LS_ lossless and near-lossless (require the `jpegls` feature).
+    ///
+    /// The photometric interpretation of the decoded frame
+    /// is the one of the decoded samples,
+    /// which is `RGB` for color JPEG images
+    /// even if the data set declares `YBR_FULL_422`.
+    /// Decoded frames of a size or number of samples per pixel
+    /// other than those declared in the data set
+    /// result in an error.
+    ///
+    /// See [`decode_frame_with_options`](Self::decode_frame_with_options)
+    /// for further conversions of the decoded frame.
+    pub fn decode_frame(&self, index: u32) -> Result<DecodedFrame<'a>> {
+        self.decode_frame_with_options(index, &Default::default())
+    }
+
+    /// Retrieve the frame at the given index in native form,
+    /// decoding it if the pixel data is encapsulated,
+    /// and converting it as requested by the given options.
+    ///
+    /// Frames in the `YBR_FULL` or `YBR_FULL_422` color spaces
+    /// can be converted into `RGB`
+    /// (see [`color`](crate::color)),
+    /// upsampling the chroma samples of native `YBR_FULL_422` pixel data.
+    /// The photometric interpretation before the conversion
+    /// is then recorded in [`DecodedFrame::converted_from`].
+    ///
+    /// Before any of these conversions,
+    /// the samples can be normalized
+    /// so that only the bits stored remain,
+    /// starting at the lowest bit and sign extended if signed,
+    /// unpacking 12-bit packed samples to 16 bits
+    /// (see [`DecodedFrame::normalized`]).
+    /// `PALETTE COLOR` frames are not normalized,
+    /// since their lookup tables are always applied to the bits stored.
+    ///
+    /// Frames with more than one sample per pixel
+    /// can also be converted between the interleaved and planar layouts
+    /// (see [`planar`](crate::planar)),
+    /// as reflected in [`DecodedFrame::planar_configuration`].
+    pub fn decode_frame_with_options(
+        &self,
+        index: u32,
+        options: &FrameDecodeOptions,
+    ) -> Result<DecodedFrame<'a>> {
+        self.decode_frame_bytes(self.get(index)?, options)
+    }
+
+    /// Decode the given frame and convert it
+    /// as requested by the given options.
+    fn decode_frame_bytes(
+        &self,
+        frame: Frame<'a>,
+        options: &FrameDecodeOptions,
+    ) -> Result<DecodedFrame<'a>> {
+        let decoded = self.decode_samples(frame)?;
+        let decoded = if options.normalize_bits && self.info.palette_color_lut().is_none() {
+            normalize_frame(&self.info, decoded)?
+        } else {
+            decoded
+        };
+        let decoded = match self.info.palette_color_lut() {
+            Some(lut) => lut.expand_frame(&self.info, decoded)?,
+            None => decoded,
+        };
+        let decoded = if options.rgb
+            && decoded.samples_per_pixel == 3
+            && matches!(
+                decoded.photometric_interpretation,
+                PhotometricInterpretation::YbrFull | PhotometricInterpretation::YbrFull422
+            ) {
+            let pixels = self.info.rows() as usize * self.info.columns() as usize;
+            ybr_frame_to_rgb(decoded, pixels, self.info.bits_stored())?
+        } else {
+            decoded
+        };
+        // chroma subsampled samples are always interleaved
+        let subsampled = self.info.is_native()
+            && decoded.photometric_interpretation == PhotometricInterpretation::YbrFull422;
+        match options.planar_configuration {
+            Some(planar_configuration) if !subsampled => {
+                convert_frame(decoded, planar_configuration)
+            }
+            _ => Ok(decoded),
+        }
+    }
+
+    /// Decode the frame at the given index into native samples,
+    /// without any further conversion.
+    pub(crate) fn decode_frame_samples(&self, index: u32) -> Result<DecodedFrame<'a>> {
+        self.decode_samples(self.get(index)?)
+    }
+
+    /// Decode the given frame into native samples,
+    /// without any further conversion.
+    fn decode_samples(&self, frame: Frame<'a>) -> Result<DecodedFrame<'a>> {
+        let index = frame.index;
+        let info = &self.info;
+        #[cfg_attr(
+            not(any(feature = "jpeg", feature = "jpegls", feature = "rle")),
+            allow(unused_mut)
+        )]
+        let mut decoded = DecodedFrame {
+            index,
+            rows: info.rows(),
+            columns: info.columns(),
+            bytes: frame.bytes,
+            photometric_interpretation: info.photometric_interpretation().clone(),
+            planar_configuration: info.planar_configuration(),
+            samples_per_pixel: info.samples_per_pixel(),
+            bits_allocated: info.bits_allocated(),
+            converted_from: None,
+            normalized: false,
+            near_lossless: None,
+        };
+        if info.is_native() {
+            return Ok(decoded);
+        }
+        match info.transfer_syntax() {
+            uids::ENCAPSULATED_UNCOMPRESSED_EXPLICIT_VR_LITTLE_ENDIAN => {}
+            #[cfg(feature = "rle")]
+            uids::RLE_LOSSLESS => {
+                let mut data = Vec::new();
+                dicom_transfer_syntax_registry::adapters::rle_lossless::decode_rle_frame(
+                    &decoded.bytes,
+                    info.rows() as u16,
+                    info.columns() as u16,
+                    info.samples_per_pixel(),
+                    info.bits_allocated(),
+                    &mut data,
+                )
+                .context(DecodeFrameSnafu {
+                    frame_number: index,
+                })?;
+                decoded.bytes = Cow::Owned(data);
+                decoded.planar_configuration = PlanarConfiguration::Standard;
+            }
+            #[cfg(feature = "jpeg")]
+            uids::JPEG_BASELINE8_BIT | uids::JPEG_EXTENDED12_BIT => {
+                let mut data = Vec::new();
+                let image = dicom_transfer_syntax_registry::adapters::jpeg::decode_jpeg_frame(
+                    &decoded.bytes,
+                    &mut data,
+                )
+                .context(DecodeFrameSnafu {
+                    frame_number: index,
+                })?;
+                self.check_decoded(index, image.height, image.width, image.samples_per_pixel)?;
+                decoded.bytes = Cow::Owned(data);
+                if image.samples_per_pixel == 3 {
+                    decoded.photometric_interpretation = PhotometricInterpretation::Rgb;
+                }
+                decoded.planar_configuration = PlanarConfiguration::Standard;
+            }
+            #[cfg(feature = "jpeg")]
+            uids::JPEG_LOSSLESS | uids::JPEG_LOSSLESS_SV1 => {
+                let mut data = Vec::new();
+                let image =
+                    dicom_transfer_syntax_registry::adapters::jpeg_lossless::decode_jpeg_lossless_frame(
+                        &decoded.bytes,
+                        &mut data,
+                    )
+                    .context(DecodeFrameSnafu {
+                        frame_number: index,
+                    })?;
+                self.check_decoded(index, image.height, image.width, image.samples_per_pixel)?;
+                decoded.bytes = Cow::Owned(data);
+                decoded.planar_configuration = PlanarConfiguration::Standard;
+            }
+            #[cfg(feature = "jpegls")]
+            uids::JPEGLS_LOSSLESS | uids::JPEGLS_NEAR_LOSSLESS => {
+                let mut data = Vec::new();
+                let image = dicom_transfer_syntax_registry::adapters::jpegls::decode_jpegls_frame(
+                    &decoded.bytes,
+                    &mut data,
+                )
+                .context(DecodeFrameSnafu {
+                    frame_number: index,
+                })?;
+                self.check_decoded(index, image.height, image.width, image.samples_per_pixel)?;
+                decoded.bytes = Cow::Owned(data);
+                decoded.planar_configuration = PlanarConfiguration::Standard;
+                if image.near_lossless > 0 {
+                    decoded.near_lossless = Some(image.near_lossless);
+                }
+            }
+            ts => {
+                return UnsupportedTransferSyntaxSnafu { ts }.fail()?;
+            }
+        }
+        Ok(decoded)
+    }
+
+    /// Check the dimensions of a decoded frame
+    /// against those declared in the data set.
+    #[cfg(any(feature = "jpeg", feature = "jpegls"))]
+    fn check_decoded(
+        &self,
+        index: u32,
+        rows: u16,
+        cols: u16,
+        samples_per_pixel: u16,
+    ) -> Result<()> {
+        let info = &self.info;
+        let (rows, cols) = (u32::from(rows), u32::from(cols));
+        if rows != info.rows()
+            || cols != info.columns()
+            || samples_per_pixel != info.samples_per_pixel()
+        {
+            return DecodedFrameMismatchSnafu {
+                frame_number: index,
+                rows,
+                cols,
+                samples_per_pixel,
+                expected_rows: info.rows(),
+                expected_cols: info.columns(),
+                expected_samples_per_pixel: info.samples_per_pixel(),
+            }
+            .fail()?;
+        }
+        Ok(())
+    }
+
+    /// Turn this iterator into one
+    /// which yields each frame decoded to native form.
+    ///
+    /// See [`decode_frame`](Frames::decode_frame).
+    pub fn decoded(self) -> DecodedFrames<'a> {
+        DecodedFrames { frames: self }
+    }
+
+    /// Move on to the next frame,
+    /// returning its index.
+    fn advance(&mut self) -> Option<u32> {
+        if self.next >= self.info.number_of_frames() {
+            return None;
+        }
+        self.next += 1;
+        Some(self.next - 1)
+    }
+
+    /// Skip the given number of frames.
+    fn skip_frames(&mut self, n: usize) {
+        self.next = self
+            .next
+            .saturating_add(u32::try_from(n).unwrap_or(u32::MAX))
+            .min(self.info.number_of_frames());
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<Frame<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.advance().map(|index| self.get(index))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip_frames(n);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.info.number_of_frames().saturating_sub(self.next) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Frames<'_> {}
+
+/// An iterator over the frames of pixel data,
+/// decoded to native form.
+///
+/// Obtained via [`Frames::decoded`].
+#[derive(Debug, Clone)]
+pub struct DecodedFrames<'a> {
+    frames: Frames<'a>,
+}
+
+impl<'a> DecodedFrames<'a> {
+    /// Retrieve the pixel data properties of the frames.
+    pub fn info(&self) -> &PixelDataInfo<'a> {
+        self.frames.info()
+    }
+}
+
+impl<'a> Iterator for DecodedFrames<'a> {
+    type Item = Result<DecodedFrame<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.frames
+            .advance()
+            .map(|index| self.frames.decode_frame(index))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.frames.skip_frames(n);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+}
+
+impl ExactSizeIterator for DecodedFrames<'_> {}
+
+#[cfg(feature = "rayon")]
+impl<'a> Frames<'a> {
+    /// Decode the frames in the given range to native form,
+    /// fanning the decoding work out across the Rayon thread pool.
+    ///
+    /// Frames are sliced out of the pixel data sequentially
+    /// and returned in order.
+    /// At most twice as many frames as there are threads
+    /// are held in memory before they are decoded,
+    /// unless set otherwise through
+    /// [`max_frames_in_flight`](FrameDecodeOptions::max_frames_in_flight).
+    /// The first frame which fails to decode,
+    /// in frame order,
+    /// results in an error carrying its index.
+    ///
+    /// See [`decode_frame`](Frames::decode_frame).
+    pub fn decode_frames_par(&self, range: Range<u32>) -> Result<Vec<DecodedFrame<'a>>> {
+        self.decode_frames_par_with_options(range, &Default::default())
+    }
+
+    /// Decode the frames in the given range to native form in parallel,
+    /// converting them as requested by the given options.
+    ///
+    /// See [`decode_frames_par`](Frames::decode_frames_par)
+    /// and [`decode_frame_with_options`](Frames::decode_frame_with_options).
+    pub fn decode_frames_par_with_options(
+        &self,
+        range: Range<u32>,
+        options: &FrameDecodeOptions,
+    ) -> Result<Vec<DecodedFrame<'a>>> {
+        if range.end > self.info.number_of_frames() {
+            return FrameOutOfRangeSnafu {
+                frame_number: range.end - 1,
+            }
+            .fail()?;
+        }
+        let mut frames = self.clone();
+        frames.next = range.start.min(range.end);
+        ParDecodedFrames::new(frames, range.end, options.clone()).collect()
+    }
+
+    /// Turn this iterator into one
+    /// which yields each frame decoded to native form,
+    /// decoding frames in parallel ahead of the ones consumed.
+    ///
+    /// See [`decode_frames_par`](Frames::decode_frames_par).
+    pub fn par_decoded(self) -> ParDecodedFrames<'a> {
+        self.par_decoded_with_options(Default::default())
+    }
+
+    /// Turn this iterator into one
+    /// which yields each frame decoded to native form in parallel,
+    /// converted as requested by the given options.
+    ///
+    /// See [`decode_frames_par_with_options`](Frames::decode_frames_par_with_options).
+    pub fn par_decoded_with_options(self, options: FrameDecodeOptions) -> ParDecodedFrames<'a> {
+        let end = self.info.number_of_frames();
+        ParDecodedFrames::new(self, end, options)
+    }
+}
+
+/// An iterator over the frames of pixel data,
+/// decoded to native form in parallel batches.
+///
+/// Each batch of frames is sliced out of the pixel data sequentially
+/// and then decoded across the Rayon thread pool,
+/// while the frames are still yielded in order.
+///
+/// Obtained via [`Frames::par_decoded`].
+#[cfg(feature = "rayon")]
+#[derive(Debug)]
+pub struct ParDecodedFrames<'a> {
+    frames: Frames<'a>,
+    /// the index after the last frame to decode
+    end: u32,
+    options: FrameDecodeOptions,
+    /// frames decoded ahead of the ones yielded
+    ready: std::collections::VecDeque<Result<DecodedFrame<'a>>>,
+}
+
+#[cfg(feature = "rayon")]
+impl<'a> ParDecodedFrames<'a> {
+    fn new(frames: Frames<'a>, end: u32, options: FrameDecodeOptions) -> Self {
+        ParDecodedFrames {
+            frames,
+            end,
+            options,
+            ready: Default::default(),
+        }
+    }
+
+    /// Retrieve the pixel data properties of the frames.
+    pub fn info(&self) -> &PixelDataInfo<'a> {
+        self.frames.info()
+    }
+
+    /// Slice out the next batch of frames and decode them in parallel.
+    fn decode_batch(&mut self) {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+        let batch_size = self
+            .options
+            .max_frames_in_flight
+            .unwrap_or_else(|| rayon::current_num_threads() * 2)
+            .max(1);
+        let mut batch = Vec::with_capacity(batch_size);
+        while batch.len() < batch_size && self.frames.next < self.end {
+            match self.frames.advance() {
+                Some(index) => batch.push(self.frames.get(index)),
+                None => break,
+            }
+        }
+
+        let frames = &self.frames;
+        let options = &self.options;
+        let decoded: Vec<_> = batch
+            .into_par_iter()
+            .map(|frame| frames.decode_frame_bytes(frame?, options))
+            .collect();
+        self.ready.extend(decoded);
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a> Iterator for ParDecodedFrames<'a> {
+    type Item = Result<DecodedFrame<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ready.is_empty() {
+            self.decode_batch();
+        }
+        self.ready.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.ready.len() + self.end.saturating_sub(self.frames.next) as usize;
+        (len, Some(len))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl ExactSizeIterator for ParDecodedFrames<'_> {}
+
+/// Determine the range of fragments which make up each frame.
+fn frame_fragments<F>(
+    offset_table: &[u32],
+    fragments: &[F],
+    number_of_frames: u32,
+) -> Result<Vec<Range<usize>>>
+where
+    F: AsRef<[u8]>,
+{
+    let number_of_frames = number_of_frames as usize;
+    let unresolved = || {
+        UnresolvedFrameFragmentsSnafu {
+            number_of_frames: number_of_frames as u32,
+            fragments: fragments.len(),
+        }
+        .fail()
+    };
+
+    if offset_table.is_empty() {
+        return if number_of_frames == 1 {
+            Ok(std::iter::once(0..fragments.len()).collect())
+        } else if number_of_frames == fragments.len() {
+            Ok((0..fragments.len()).map(|i| i..i + 1).collect())
+        } else {
+            unresolved()?
+        };
+    }
+    if offset_table.len() != number_of_frames {
+        return unresolved()?;
+    }
+
+    // offsets are relative to the first byte of the first fragment's item,
+    // each item having an 8 byte header
+    let mut positions = Vec::with_capacity(fragments.len());
+    let mut position = 0u64;
+    for fragment in fragments {
+        positions.push(position);
+        position += 8 + fragment.as_ref().len() as u64;
+    }
+
+    let mut starts = Vec::with_capacity(offset_table.len());
+    for &offset in offset_table {
+        match positions.binary_search(&u64::from(offset)) {
+            Ok(i) => starts.push(i),
+            Err(_) => return unresolved()?,
+        }
+    }
+    let ranges: Vec<_> = starts
+        .iter()
+        .zip(
+            starts
+                .iter()
+                .skip(1)
+                .chain(std::iter::once(&fragments.len())),
+        )
+        .map(|(&start, &end)| start..end)
+        .collect();
+    if ranges.iter().any(|r| r.start >= r.end) {
+        return unresolved()?;
+    }
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::info::tests::dummy_image;
+    #[cfg(any(feature = "jpeg", feature = "jpegls", feature = "rle"))]
+    use crate::PlanarConfiguration;
+    use crate::{InnerError, PixelDataAccess};
+    use dicom_core::value::PixelFragmentSequence;
+    use dicom_core::{DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+
+    #[test]
+    fn native_16bit_frames() {
+        // 10 frames of 2x2 samples, each frame filled with its index
+        let samples: Vec<u16> = (0..10).flat_map(|i| vec![i; 4]).collect();
+        let obj = dummy_image(2, 2, Some(10), 1, 16, PrimitiveValue::U16(samples.into()));
+
+        let frames = obj.frames().unwrap();
+        assert_eq!(frames.len(), 10);
+        for (i, frame) in frames.enumerate() {
+            let frame = frame.unwrap();
+            assert_eq!(frame.index, i as u32);
+            assert_eq!(frame.bytes.len(), 8);
+            assert!(frame
+                .bytes
+                .chunks(2)
+                .all(|b| u16::from_ne_bytes([b[0], b[1]]) == i as u16));
+        }
+
+        // skipping ahead
+        let mut frames = obj.frames().unwrap();
+        let frame = frames.nth(7).unwrap().unwrap();
+        assert_eq!(frame.index, 7);
+        assert_eq!(frames.len(), 2);
+        let indices: Vec<u32> = frames.skip(1).map(|f| f.unwrap().index).collect();
+        assert_eq!(indices, vec![9]);
+    }
+
+    #[test]
+    fn encapsulated_frames_with_offset_table() {
+        let mut obj = dummy_image(1, 1, Some(3), 1, 8, PrimitiveValue::Empty);
+        // frame 0: 2 fragments, frame 1: 1 fragment, frame 2: 1 fragment
+        let fragments = vec![vec![1, 1], vec![2, 2, 2, 2], vec![3, 3], vec![4, 4]];
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new(vec![0, 22, 32], fragments),
+        ));
+
+        let frames: Vec<_> = obj
+            .frames()
+            .unwrap()
+            .map(|f| f.unwrap().bytes.into_owned())
+            .collect();
+        assert_eq!(frames, vec![vec![1, 1, 2, 2, 2, 2], vec![3, 3], vec![4, 4]]);
+
+        let frames = obj.frames().unwrap();
+        assert_eq!(frames.get(2).unwrap().bytes, &[4, 4][..]);
+    }
+
+    #[test]
+    fn encapsulated_frames_without_offset_table() {
+        let mut obj = dummy_image(1, 1, Some(2), 1, 8, PrimitiveValue::Empty);
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(vec![vec![1, 1], vec![2, 2]]),
+        ));
+        let frames: Vec<_> = obj
+            .frames()
+            .unwrap()
+            .map(|f| f.unwrap().bytes.into_owned())
+            .collect();
+        assert_eq!(frames, vec![vec![1, 1], vec![2, 2]]);
+
+        // 3 fragments for 2 frames cannot be resolved without a table
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(vec![vec![1, 1], vec![2, 2], vec![3, 3]]),
+        ));
+        let err = obj.frames().unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::UnresolvedFrameFragments {
+                number_of_frames: 2,
+                fragments: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn frame_out_of_range() {
+        let obj = dummy_image(2, 2, Some(3), 1, 8, PrimitiveValue::U8((0..12).collect()));
+        let mut frames = obj.frames().unwrap();
+        assert!(frames.get(2).is_ok());
+        let err = frames.get(3).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::FrameOutOfRange {
+                frame_number: 3,
+                ..
+            }
+        ));
+        assert!(frames.nth(3).is_none());
+        assert!(frames.next().is_none());
+
+        // truncated pixel data
+        let obj = dummy_image(2, 2, Some(3), 1, 8, PrimitiveValue::U8((0..10).collect()));
+        let mut frames = obj.frames().unwrap();
+        assert!(frames.nth(1).unwrap().is_ok());
+        assert!(frames.next().unwrap().is_err());
+    }
+
+    #[cfg(feature = "rle")]
+    #[test]
+    fn decode_rle_frames() {
+        let mut obj = dummy_image(1, 2, Some(2), 1, 16, PrimitiveValue::Empty);
+        obj.meta_mut()
+            .set_transfer_syntax(&dicom_transfer_syntax_registry::entries::RLE_LOSSLESS);
+
+        // one fragment per frame, 2 segments (MSB, LSB) of 2 pixels each
+        let rle_fragment = |msb: [u8; 2], lsb: [u8; 2]| {
+            let mut data = vec![0u8; 64];
+            data[0] = 2;
+            data[4] = 64;
+            data[8] = 68;
+            data.extend_from_slice(&[0x01, msb[0], msb[1], 0x00]);
+            data.extend_from_slice(&[0x01, lsb[0], lsb[1], 0x00]);
+            data
+        };
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(vec![
+                rle_fragment([0x01, 0x02], [0x10, 0x20]),
+                rle_fragment([0x03, 0x04], [0x30, 0x40]),
+            ]),
+        ));
+
+        let frames: Vec<Vec<u16>> = obj
+            .frames()
+            .unwrap()
+            .decoded()
+            .map(|frame| {
+                frame
+                    .unwrap()
+                    .bytes
+                    .chunks(2)
+                    .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(frames, vec![vec![0x0110, 0x0220], vec![0x0330, 0x0440]]);
+
+        let frame = obj.frames().unwrap().decode_frame(1).unwrap();
+        assert_eq!(frame.index, 1);
+        assert_eq!(frame.planar_configuration, PlanarConfiguration::Standard);
+        assert!(obj.frames().unwrap().decode_frame(2).is_err());
+    }
+
+    /// Encode a 16-bit frame as RLE Lossless,
+    /// with one literal run per row of each segment
+    #[cfg(feature = "rle")]
+    fn rle_frame(cols: usize, samples: &[u16]) -> Vec<u8> {
+        let mut data = vec![0u8; 64];
+        data[0] = 2;
+        for (i, shift) in [8, 0].iter().enumerate() {
+            let offset = data.len() as u32;
+            data[4 + 4 * i..8 + 4 * i].copy_from_slice(&offset.to_le_bytes());
+            for row in samples.chunks(cols) {
+                data.push(row.len() as u8 - 1);
+                data.extend(row.iter().map(|s| (s >> shift) as u8));
+            }
+        }
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        data
+    }
+
+    /// Create a multi-frame RLE Lossless test object,
+    /// each sample depending on its position and frame index
+    #[cfg(feature = "rle")]
+    fn rle_object(rows: u16, cols: u16, frames: u16) -> dicom_object::DefaultDicomObject {
+        let mut obj = dummy_image(
+            rows,
+            cols,
+            Some(i32::from(frames)),
+            1,
+            16,
+            PrimitiveValue::Empty,
+        );
+        obj.meta_mut()
+            .set_transfer_syntax(&dicom_transfer_syntax_registry::entries::RLE_LOSSLESS);
+        let fragments: Vec<_> = (0..frames)
+            .map(|f| {
+                let samples: Vec<u16> = (0..rows * cols)
+                    .map(|i| f.wrapping_mul(7919) ^ i.wrapping_mul(257))
+                    .collect();
+                rle_frame(cols as usize, &samples)
+            })
+            .collect();
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(fragments),
+        ));
+        obj
+    }
+
+    #[cfg(all(feature = "rayon", feature = "rle"))]
+    #[test]
+    fn decode_rle_frames_in_parallel() {
+        use crate::FrameDecodeOptions;
+
+        let obj = rle_object(12, 10, 60);
+        let sequential: Vec<_> = obj
+            .frames()
+            .unwrap()
+            .decoded()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(sequential.len(), 60);
+        assert_eq!(
+            &sequential[3].bytes[..4],
+            &[
+                (3 * 7919_u16).to_ne_bytes(),
+                ((3 * 7919_u16) ^ 257).to_ne_bytes()
+            ]
+            .concat()[..]
+        );
+
+        let frames = obj.frames().unwrap();
+        let parallel = frames.decode_frames_par(0..60).unwrap();
+        assert_eq!(parallel, sequential);
+
+        // a sub-range, with a small number of frames in flight
+        let options = FrameDecodeOptions::new().with_max_frames_in_flight(3);
+        let parallel = frames
+            .decode_frames_par_with_options(10..27, &options)
+            .unwrap();
+        assert_eq!(parallel, &sequential[10..27]);
+        assert!(frames.decode_frames_par(5..5).unwrap().is_empty());
+
+        // the iterator adapter
+        let mut iter = obj.frames().unwrap().par_decoded_with_options(options);
+        assert_eq!(iter.len(), 60);
+        assert_eq!(iter.next().unwrap().unwrap(), sequential[0]);
+        assert_eq!(iter.len(), 59);
+        let rest: Vec<_> = iter.map(|frame| frame.unwrap()).collect();
+        assert_eq!(rest, &sequential[1..]);
+
+        let all: Vec<_> = obj
+            .frames()
+            .unwrap()
+            .par_decoded()
+            .map(|frame| frame.unwrap())
+            .collect();
+        assert_eq!(all, sequential);
+
+        let err = frames.decode_frames_par(0..61).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::FrameOutOfRange {
+                frame_number: 60,
+                ..
+            }
+        ));
+    }
+
+    #[cfg(all(feature = "rayon", feature = "rle"))]
+    #[test]
+    fn parallel_decoding_reports_first_error() {
+        let mut obj = rle_object(4, 4, 20);
+        // corrupt frames #7 and #15
+        let mut fragments = obj
+            .element(tags::PIXEL_DATA)
+            .unwrap()
+            .fragments()
+            .unwrap()
+            .to_vec();
+        fragments[7].truncate(70);
+        fragments[15].truncate(70);
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(fragments),
+        ));
+
+        let frames = obj.frames().unwrap();
+        let err = frames.decode_frames_par(0..20).unwrap_err();
+        assert!(
+            matches!(
+                err.0,
+                InnerError::DecodeFrame {
+                    frame_number: 7,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+        assert!(frames.decode_frames_par(0..7).is_ok());
+
+        // the adapter carries on after an error, like the sequential one
+        let results: Vec<_> = obj.frames().unwrap().par_decoded().collect();
+        let failed: Vec<_> = results
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.is_err())
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(failed, vec![7, 15]);
+    }
+
+    /// Encode a native test image to JPEG baseline,
+    /// returning the encapsulated object along with the original samples
+    #[cfg(feature = "jpeg")]
+    fn jpeg_fixture(samples_per_pixel: u16) -> (dicom_object::DefaultDicomObject, Vec<u8>) {
+        use crate::Transcode;
+
+        // 16x8 smooth gradient, which survives lossy compression well
+        let raster: Vec<u8> = (0..8u8)
+            .flat_map(|y| (0..16u8).map(move |x| x * 12 + y * 4))
+            .flat_map(|v| std::iter::repeat_n(v, samples_per_pixel as usize))
+            .collect();
+        let mut obj = dummy_image(
+            8,
+            16,
+            None,
+            samples_per_pixel,
+            8,
+            PrimitiveValue::U8(raster.iter().copied().collect()),
+        );
+        obj.transcode(&dicom_transfer_syntax_registry::entries::JPEG_BASELINE.erased())
+            .unwrap();
+        (obj, raster)
+    }
+
+    #[cfg(feature = "jpeg")]
+    fn assert_close(decoded: &[u8], expected: &[u8]) {
+        assert_eq!(decoded.len(), expected.len());
+        for (i, (a, b)) in decoded.iter().zip(expected).enumerate() {
+            assert!(
+                (i16::from(*a) - i16::from(*b)).abs() <= 8,
+                "sample #{} differs too much: {} vs {}",
+                i,
+                a,
+                b
+            );
+        }
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn decode_jpeg_baseline_frames() {
+        use crate::PhotometricInterpretation;
+
+        let (obj, raster) = jpeg_fixture(1);
+        let frame = obj.frames().unwrap().decode_frame(0).unwrap();
+        assert_eq!(
+            frame.photometric_interpretation,
+            PhotometricInterpretation::Monochrome2
+        );
+        assert_close(&frame.bytes, &raster);
+
+        let (obj, raster) = jpeg_fixture(3);
+        let frame = obj.frames().unwrap().decode_frame(0).unwrap();
+        assert_eq!(
+            frame.photometric_interpretation,
+            PhotometricInterpretation::Rgb
+        );
+        assert_eq!(frame.planar_configuration, PlanarConfiguration::Standard);
+        assert_close(&frame.bytes, &raster);
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn jpeg_frame_mismatch() {
+        let (mut obj, _) = jpeg_fixture(1);
+        obj.put(DataElement::new(
+            tags::ROWS,
+            VR::US,
+            PrimitiveValue::from(4_u16),
+        ));
+        let err = obj.frames().unwrap().decode_frame(0).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::DecodedFrameMismatch {
+                frame_number: 0,
+                rows: 8,
+                cols: 16,
+                expected_rows: 4,
+                ..
+            }
+        ));
+    }
+
+    /// 8x4 JPEG-LS lossless code stream, 16 bits, monochrome
+    #[cfg(feature = "jpegls")]
+    const JLS_MONO_16BIT: &[u8] = &[
+        255, 216, 255, 247, 0, 11, 16, 0, 4, 0, 8, 1, 1, 17, 0, 255, 218, 0, 8, 1, 1, 0, 0, 0, 0,
+        62, 120, 0, 0, 0, 0, 0, 13, 250, 41, 253, 30, 184, 203, 113, 154, 67, 9, 221, 224, 238,
+        237, 0, 0, 11, 158, 0, 0, 0, 54, 32, 0, 198, 32, 0, 178, 168, 0, 0, 0, 0, 0, 13, 187, 166,
+        156, 176, 0, 0, 0, 0, 0, 31, 203, 128, 0, 170, 5, 86, 240, 34, 97, 251, 142, 122, 56, 67,
+        2, 65, 127, 143, 82, 211, 138, 96, 161, 59, 193, 90, 55, 233, 107, 167, 20, 87, 196, 0, 0,
+        0, 0, 0, 7, 120, 100, 255, 217,
+    ];
+
+    /// Reference raster of [`JLS_MONO_16BIT`]
+    #[cfg(feature = "jpegls")]
+    const RASTER_MONO_16BIT: [u16; 32] = [
+        1000, 42053, 1000, 60623, 1000, 10265, 52318, 28835, 1000, 47405, 20530, 1000, 39100,
+        15617, 57670, 1000, 7312, 1000, 25882, 2399, 41060, 1000, 59630, 1000, 12664, 51325, 27842,
+        1000, 46412, 1000, 1000, 38107,
+    ];
+
+    /// 4x4 JPEG-LS lossless code stream, 8 bits, RGB, sample interleaved
+    #[cfg(feature = "jpegls")]
+    const JLS_RGB_8BIT: &[u8] = &[
+        255, 216, 255, 247, 0, 17, 8, 0, 4, 0, 4, 3, 1, 17, 0, 2, 17, 0, 3, 17, 0, 255, 218, 0, 12,
+        3, 1, 0, 2, 0, 3, 0, 0, 2, 0, 64, 0, 0, 45, 207, 0, 32, 105, 1, 200, 176, 178, 44, 0, 0, 5,
+        252, 0, 0, 144, 2, 33, 23, 57, 32, 17, 9, 0, 0, 1, 127, 70, 0, 0, 41, 140, 13, 82, 130,
+        220, 227, 47, 136, 76, 8, 73, 14, 158, 99, 30, 255, 217,
+    ];
+
+    /// Reference raster of [`JLS_RGB_8BIT`]
+    #[cfg(feature = "jpegls")]
+    const RASTER_RGB_8BIT: [u8; 48] = [
+        0, 200, 30, 16, 191, 30, 32, 182, 30, 48, 173, 30, 64, 164, 30, 80, 155, 30, 96, 146, 30,
+        112, 137, 30, 128, 128, 104, 144, 119, 117, 160, 110, 130, 176, 101, 143, 192, 92, 156,
+        208, 83, 169, 224, 74, 182, 240, 65, 195,
+    ];
+
+    /// Put a single JPEG-LS frame in a test object
+    #[cfg(feature = "jpegls")]
+    fn jpegls_object(
+        rows: u16,
+        cols: u16,
+        samples_per_pixel: u16,
+        bits_allocated: u16,
+        codestream: &[u8],
+    ) -> dicom_object::DefaultDicomObject {
+        let mut obj = dummy_image(
+            rows,
+            cols,
+            Some(1),
+            samples_per_pixel,
+            bits_allocated,
+            PrimitiveValue::Empty,
+        );
+        obj.meta_mut().set_transfer_syntax(
+            &dicom_transfer_syntax_registry::entries::JPEG_LS_LOSSLESS_IMAGE_COMPRESSION,
+        );
+        let mut fragment = codestream.to_vec();
+        if fragment.len() % 2 == 1 {
+            fragment.push(0);
+        }
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(vec![fragment]),
+        ));
+        obj
+    }
+
+    #[cfg(feature = "jpegls")]
+    #[test]
+    fn decode_jpegls_frames() {
+        use crate::PhotometricInterpretation;
+
+        let obj = jpegls_object(4, 8, 1, 16, JLS_MONO_16BIT);
+        let frame = obj.frames().unwrap().decode_frame(0).unwrap();
+        let samples: Vec<u16> = frame
+            .bytes
+            .chunks(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(samples, RASTER_MONO_16BIT);
+        assert_eq!(frame.near_lossless, None);
+
+        let obj = jpegls_object(4, 4, 3, 8, JLS_RGB_8BIT);
+        let frame = obj.frames().unwrap().decode_frame(0).unwrap();
+        assert_eq!(&frame.bytes[..], &RASTER_RGB_8BIT[..]);
+        assert_eq!(
+            frame.photometric_interpretation,
+            PhotometricInterpretation::Rgb
+        );
+        assert_eq!(frame.planar_configuration, PlanarConfiguration::Standard);
+    }
+
+    #[cfg(feature = "jpegls")]
+    #[test]
+    fn jpegls_frame_errors() {
+        // component count mismatch
+        let obj = jpegls_object(4, 4, 1, 8, JLS_RGB_8BIT);
+        let err = obj.frames().unwrap().decode_frame(0).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::DecodedFrameMismatch {
+                samples_per_pixel: 3,
+                expected_samples_per_pixel: 1,
+                ..
+            }
+        ));
+
+        // truncated code stream
+        let obj = jpegls_object(4, 8, 1, 16, &JLS_MONO_16BIT[..40]);
+        let err = obj.frames().unwrap().decode_frame(0).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::DecodeFrame {
+                frame_number: 0,
+                ..
+            }
+        ));
+    }
+
+    /// 6x4 JPEG Lossless (SV1) code stream, 12 bits, monochrome
+    #[cfg(feature = "jpeg")]
+    const JPEG_LOSSLESS_12BIT: &[u8] = &[
+        255, 216, 255, 195, 0, 11, 12, 0, 4, 0, 6, 1, 1, 17, 0, 255, 196, 0, 36, 0, 0, 0, 0, 0, 17,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+        255, 218, 0, 8, 1, 1, 0, 1, 0, 0, 99, 255, 0, 128, 251, 205, 193, 184, 55, 0, 2, 33, 205,
+        193, 184, 55, 0, 2, 39, 205, 193, 184, 55, 0, 2, 45, 215, 112, 87, 60, 148, 115, 255, 0,
+        255, 217,
+    ];
+
+    /// Reference raster of [`JPEG_LOSSLESS_12BIT`]
+    #[cfg(feature = "jpeg")]
+    const RASTER_12BIT: [u16; 24] = [
+        0, 0, 111, 167, 223, 279, 0, 0, 135, 191, 247, 303, 0, 0, 159, 215, 271, 327, 0, 0, 183,
+        1656, 2922, 2130,
+    ];
+
+    /// 6x4 JPEG Lossless (SV1) code stream, 16 bits, monochrome
+    #[cfg(feature = "jpeg")]
+    const JPEG_LOSSLESS_16BIT: &[u8] = &[
+        255, 216, 255, 195, 0, 11, 16, 0, 4, 0, 6, 1, 1, 17, 0, 255, 196, 0, 36, 0, 0, 0, 0, 0, 17,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+        255, 218, 0, 8, 1, 1, 0, 1, 0, 0, 128, 23, 191, 213, 192, 43, 128, 87, 0, 0, 50, 31, 213,
+        192, 43, 128, 87, 0, 0, 50, 127, 213, 192, 43, 128, 87, 0, 0, 50, 223, 221, 87, 202, 231,
+        147, 238, 116, 127, 255, 217,
+    ];
+
+    /// Reference raster of [`JPEG_LOSSLESS_16BIT`]
+    #[cfg(feature = "jpeg")]
+    const RASTER_16BIT: [u16; 24] = [
+        0, 0, 1791, 2687, 3583, 4479, 0, 0, 2175, 3071, 3967, 4863, 0, 0, 2559, 3455, 4351, 5247,
+        0, 0, 2943, 13944, 15210, 38994,
+    ];
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn decode_jpeg_lossless_frames() {
+        for (codestream, raster) in [
+            (JPEG_LOSSLESS_12BIT, RASTER_12BIT),
+            (JPEG_LOSSLESS_16BIT, RASTER_16BIT),
+        ] {
+            let mut obj = dummy_image(4, 6, None, 1, 16, PrimitiveValue::Empty);
+            obj.meta_mut().set_transfer_syntax(
+                &dicom_transfer_syntax_registry::entries::JPEG_LOSSLESS_NON_HIERARCHICAL_FIRST_ORDER_PREDICTION,
+            );
+            let mut fragment = codestream.to_vec();
+            if fragment.len() % 2 == 1 {
+                fragment.push(0);
+            }
+            obj.put(DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PixelFragmentSequence::new_fragments(vec![fragment]),
+            ));
+
+            let frame = obj.frames().unwrap().decode_frame(0).unwrap();
+            let samples: Vec<u16> = frame
+                .bytes
+                .chunks(2)
+                .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+                .collect();
+            assert_eq!(samples, raster);
+        }
+    }
+}
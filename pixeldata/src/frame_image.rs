@@ -0,0 +1,334 @@
+//! Conversion of frames into images of the `image` crate.
+
+use crate::attribute::{PhotometricInterpretation, PlanarConfiguration};
+use crate::frame::{DecodedFrame, FrameDecodeOptions, Frames};
+use crate::planar::convert_frame;
+use crate::{
+    BitDepthOption, ConvertOptions, InvalidBitsAllocatedSnafu, InvalidImageBufferSnafu,
+    ModalityLutOption, Result, UnsupportedPhotometricInterpretationSnafu,
+};
+use image::{DynamicImage, ImageBuffer, Luma, Rgb};
+use snafu::OptionExt;
+
+impl DecodedFrame<'_> {
+    /// Convert the samples of this frame as they are into a dynamic image:
+    /// `MONOCHROME1` and `MONOCHROME2` frames
+    /// into 8-bit or 16-bit grayscale images,
+    /// and `RGB` frames into 8-bit or 16-bit RGB images.
+    ///
+    /// No transformation is applied to the sample values.
+    /// See [`Frames::to_dynamic_image`] for a full conversion pipeline.
+    pub fn to_dynamic_image(&self) -> Result<DynamicImage> {
+        let (cols, rows) = (self.columns, self.rows);
+        match (&self.photometric_interpretation, self.samples_per_pixel) {
+            (
+                PhotometricInterpretation::Monochrome1 | PhotometricInterpretation::Monochrome2,
+                1,
+            ) => match self.bits_allocated {
+                8 => {
+                    let buffer: ImageBuffer<Luma<u8>, Vec<u8>> =
+                        ImageBuffer::from_raw(cols, rows, self.bytes.to_vec())
+                            .context(InvalidImageBufferSnafu)?;
+                    Ok(DynamicImage::ImageLuma8(buffer))
+                }
+                16 => {
+                    let buffer: ImageBuffer<Luma<u16>, Vec<u16>> =
+                        ImageBuffer::from_raw(cols, rows, words(&self.bytes))
+                            .context(InvalidImageBufferSnafu)?;
+                    Ok(DynamicImage::ImageLuma16(buffer))
+                }
+                _ => InvalidBitsAllocatedSnafu.fail()?,
+            },
+            (PhotometricInterpretation::Rgb, 3) => {
+                if self.planar_configuration != PlanarConfiguration::Standard {
+                    return convert_frame(self.clone(), PlanarConfiguration::Standard)?
+                        .to_dynamic_image();
+                }
+                match self.bits_allocated {
+                    8 => {
+                        let buffer: ImageBuffer<Rgb<u8>, Vec<u8>> =
+                            ImageBuffer::from_raw(cols, rows, self.bytes.to_vec())
+                                .context(InvalidImageBufferSnafu)?;
+                        Ok(DynamicImage::ImageRgb8(buffer))
+                    }
+                    16 => {
+                        let buffer: ImageBuffer<Rgb<u16>, Vec<u16>> =
+                            ImageBuffer::from_raw(cols, rows, words(&self.bytes))
+                                .context(InvalidImageBufferSnafu)?;
+                        Ok(DynamicImage::ImageRgb16(buffer))
+                    }
+                    _ => InvalidBitsAllocatedSnafu.fail()?,
+                }
+            }
+            (pi, _) => UnsupportedPhotometricInterpretationSnafu { pi: pi.clone() }.fail()?,
+        }
+    }
+}
+
+impl Frames<'_> {
+    /// Decode the frame at the given index and convert it into a dynamic image,
+    /// applying the transformations described by the given options.
+    ///
+    /// Monochrome frames are normalized and rescaled
+    /// (see [`to_rescaled_f32`](Self::to_rescaled_f32)),
+    /// then go through the VOI LUT function
+    /// into an 8-bit or 16-bit grayscale image,
+    /// with the output of `MONOCHROME1` frames inverted.
+    /// If no window is found in the object
+    /// for the default VOI LUT option,
+    /// a min-max normalization is applied instead.
+    /// Color frames, including `YBR_FULL`, `YBR_FULL_422`, and `PALETTE COLOR`,
+    /// are converted into `RGB` images without any transformation.
+    ///
+    /// The bit depth of the image follows the one of the samples,
+    /// unless another one is requested in the options.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_pixeldata::{ConvertOptions, PixelDataAccess, VoiLutOption, WindowLevel};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let obj = dicom_object::open_file("ct.dcm")?;
+    /// let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Custom(WindowLevel {
+    ///     center: 40.,
+    ///     width: 400.,
+    /// }));
+    /// obj.to_image(0, &options)?.save("ct.png")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_dynamic_image(&self, index: u32, options: &ConvertOptions) -> Result<DynamicImage> {
+        let info = self.info();
+        let pi = info.photometric_interpretation();
+        if info.samples_per_pixel() == 1 && info.palette_color_lut().is_none() {
+            return match pi {
+                PhotometricInterpretation::Monochrome1 | PhotometricInterpretation::Monochrome2 => {
+                    self.monochrome_image(index, options)
+                }
+                pi => UnsupportedPhotometricInterpretationSnafu { pi: pi.clone() }.fail()?,
+            };
+        }
+
+        let decode_options = FrameDecodeOptions::new()
+            .with_rgb(true)
+            .with_planar_configuration(PlanarConfiguration::Standard);
+        let frame = self.decode_frame_with_options(index, &decode_options)?;
+        let image = frame.to_dynamic_image()?;
+        Ok(match (options.bit_depth, image) {
+            (BitDepthOption::Force8Bit, image @ DynamicImage::ImageRgb16(_)) => {
+                DynamicImage::ImageRgb8(image.to_rgb8())
+            }
+            (BitDepthOption::Force16Bit, image @ DynamicImage::ImageRgb8(_)) => {
+                DynamicImage::ImageRgb16(image.to_rgb16())
+            }
+            (_, image) => image,
+        })
+    }
+
+    fn monochrome_image(&self, index: u32, options: &ConvertOptions) -> Result<DynamicImage> {
+        let info = self.info();
+        let y_max = self.output_max(options.bit_depth);
+        let values = self.modality_values(index, &options.modality_lut)?;
+        if options.modality_lut == ModalityLutOption::None {
+            // stored values only, without any transformation
+            let values = values.into_iter().map(|v| v.clamp(0., y_max));
+            return monochrome_buffer(info.columns(), info.rows(), values, y_max);
+        }
+
+        let mut values = self.voi_values(index, values, &options.voi_lut, y_max)?;
+        if *info.photometric_interpretation() == PhotometricInterpretation::Monochrome1 {
+            for v in &mut values {
+                *v = y_max - *v;
+            }
+        }
+        monochrome_buffer(info.columns(), info.rows(), values, y_max)
+    }
+}
+
+/// Build a grayscale image of 8 bits if `y_max` is 255,
+/// 16 bits otherwise,
+/// truncating fractional values.
+fn monochrome_buffer(
+    cols: u32,
+    rows: u32,
+    values: impl IntoIterator<Item = f64>,
+    y_max: f64,
+) -> Result<DynamicImage> {
+    if y_max <= f64::from(u8::MAX) {
+        let data: Vec<u8> = values.into_iter().map(|v| v as u8).collect();
+        let buffer: ImageBuffer<Luma<u8>, Vec<u8>> =
+            ImageBuffer::from_raw(cols, rows, data).context(InvalidImageBufferSnafu)?;
+        Ok(DynamicImage::ImageLuma8(buffer))
+    } else {
+        let data: Vec<u16> = values.into_iter().map(|v| v as u16).collect();
+        let buffer: ImageBuffer<Luma<u16>, Vec<u16>> =
+            ImageBuffer::from_raw(cols, rows, data).context(InvalidImageBufferSnafu)?;
+        Ok(DynamicImage::ImageLuma16(buffer))
+    }
+}
+
+fn words(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::info::tests::dummy_image;
+    use crate::{ConvertOptions, InnerError, PixelDataAccess, VoiLutOption, WindowLevel};
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+    use dicom_object::InMemDicomObject;
+    use image::{DynamicImage, ImageFormat};
+    use std::io::Cursor;
+
+    /// Encode the image to PNG and read it back.
+    fn png_roundtrip(image: &DynamicImage) -> DynamicImage {
+        let mut png = Cursor::new(Vec::new());
+        image.write_to(&mut png, ImageFormat::Png).unwrap();
+        image::load_from_memory_with_format(png.get_ref(), ImageFormat::Png).unwrap()
+    }
+
+    #[test]
+    fn mono16_to_image_with_window() {
+        let obj = dummy_image(
+            2,
+            3,
+            None,
+            1,
+            16,
+            dicom_value!(U16, [0, 500, 1000, 1500, 2000, 4000]),
+        );
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Custom(WindowLevel {
+            center: 1000.,
+            width: 1000.,
+        }));
+
+        let image = png_roundtrip(&obj.to_image(0, &options.clone().force_8bit()).unwrap());
+        let image = image.as_luma8().unwrap();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.as_raw(), &vec![0, 0, 127, 255, 255, 255]);
+        let checksum: u32 = image.as_raw().iter().map(|&v| u32::from(v)).sum();
+        assert_eq!(checksum, 892);
+
+        // 16-bit samples keep their bit depth by default
+        let image = png_roundtrip(&obj.to_image(0, &options).unwrap());
+        let image = image.as_luma16().unwrap();
+        assert_eq!(image.as_raw(), &vec![0, 0, 32800, 65535, 65535, 65535]);
+        let checksum: u32 = image.as_raw().iter().map(|&v| u32::from(v)).sum();
+        assert_eq!(checksum, 229_405);
+    }
+
+    #[test]
+    fn mono16_to_image_with_voi_lut() {
+        let mut obj = dummy_image(
+            2,
+            3,
+            None,
+            1,
+            16,
+            dicom_value!(U16, [9, 10, 11, 12, 13, 4000]),
+        );
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER,
+            VR::DS,
+            PrimitiveValue::from("1000"),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_WIDTH,
+            VR::DS,
+            PrimitiveValue::from("1000"),
+        ));
+        obj.put(DataElement::new(
+            tags::VOILUT_SEQUENCE,
+            VR::SQ,
+            dicom_core::value::DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                DataElement::new(tags::LUT_DESCRIPTOR, VR::US, dicom_value!(U16, [4, 10, 8])),
+                DataElement::new(tags::LUT_EXPLANATION, VR::LO, PrimitiveValue::from("TEST")),
+                DataElement::new(
+                    tags::LUT_DATA,
+                    VR::OW,
+                    dicom_value!(U16, [0, 100, 200, 255]),
+                ),
+            ])]),
+        ));
+        let lut = obj.pixel_data().unwrap().voi_lut().cloned().unwrap();
+        assert_eq!(lut.explanation(), Some("TEST"));
+        assert_eq!(lut.bits(), 8);
+
+        // the window takes precedence by default
+        let image = obj
+            .to_image(0, &ConvertOptions::new().force_8bit())
+            .unwrap();
+        assert_eq!(
+            image.as_luma8().unwrap().as_raw(),
+            &vec![0, 0, 0, 0, 0, 255]
+        );
+
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Lut);
+        let image = obj.to_image(0, &options.clone().force_8bit()).unwrap();
+        assert_eq!(
+            image.as_luma8().unwrap().as_raw(),
+            &vec![0, 0, 100, 200, 255, 255]
+        );
+        // LUT output is scaled to 16 bits
+        let image = obj.to_image(0, &options).unwrap();
+        assert_eq!(
+            image.as_luma16().unwrap().as_raw(),
+            &vec![0, 0, 25700, 51400, 65535, 65535]
+        );
+
+        // without a window, the VOI LUT is applied by default
+        obj.remove_element(tags::WINDOW_CENTER);
+        obj.remove_element(tags::WINDOW_WIDTH);
+        let image = obj
+            .to_image(0, &ConvertOptions::new().force_8bit())
+            .unwrap();
+        assert_eq!(
+            image.as_luma8().unwrap().as_raw(),
+            &vec![0, 0, 100, 200, 255, 255]
+        );
+    }
+
+    #[test]
+    fn rgb_to_image() {
+        let samples = [255, 0, 0, 0, 255, 0, 0, 0, 255, 10, 20, 30];
+        let obj = dummy_image(
+            2,
+            2,
+            None,
+            3,
+            8,
+            PrimitiveValue::U8(samples.iter().copied().collect()),
+        );
+        let image = png_roundtrip(&obj.to_image(0, &ConvertOptions::new()).unwrap());
+        let image = image.as_rgb8().unwrap();
+        assert_eq!(image.dimensions(), (2, 2));
+        assert_eq!(image.as_raw(), &samples.to_vec());
+
+        let image = obj
+            .to_image(0, &ConvertOptions::new().force_16bit())
+            .unwrap();
+        assert_eq!(
+            image.as_rgb16().unwrap().get_pixel(1, 1).0,
+            [2570, 5140, 7710]
+        );
+    }
+
+    #[test]
+    fn to_image_unsupported_photometric_interpretation() {
+        let mut obj = dummy_image(1, 2, None, 1, 8, dicom_value!(U8, [1, 2]));
+        obj.put(DataElement::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            VR::CS,
+            PrimitiveValue::from("HSV"),
+        ));
+        let err = obj.to_image(0, &ConvertOptions::new()).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::UnsupportedPhotometricInterpretation { .. }
+        ));
+    }
+}
@@ -0,0 +1,398 @@
+//! Conversion of frames into multi-dimensional arrays of the `ndarray` crate.
+
+use crate::attribute::{PhotometricInterpretation, PixelRepresentation, PlanarConfiguration};
+use crate::frame::{FrameDecodeOptions, Frames};
+use crate::{
+    ConvertOptions, ElementCountMismatchSnafu, InvalidBitsAllocatedSnafu, InvalidDataTypeSnafu,
+    InvalidShapeSnafu, ModalityLutOption, NotNativePixelDataSnafu, Result, UnalignedPixelDataSnafu,
+    UnsupportedPhotometricInterpretationSnafu, VoiLutOption,
+};
+use ndarray::{Array, Array4, ArrayView, ArrayView4, ShapeBuilder};
+use num_traits::NumCast;
+use snafu::{OptionExt, ResultExt};
+use std::mem::{align_of, size_of};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A type of pixel sample which native pixel data can be viewed as,
+/// without copying.
+///
+/// See [`Frames::to_ndarray_view`].
+/// This trait is sealed and implemented for
+/// `u8`, `i8`, `u16`, `i16`, `u32`, and `i32`.
+pub trait NativeSample: bytemuck::Pod + private::Sealed {
+    /// The _Bits Allocated_ of pixel data with samples of this type.
+    const BITS_ALLOCATED: u16;
+    /// The _Pixel Representation_ of pixel data with samples of this type.
+    const PIXEL_REPRESENTATION: PixelRepresentation;
+}
+
+macro_rules! impl_native_sample {
+    ($($t: ty => $bits: literal, $repr: ident;)*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl NativeSample for $t {
+                const BITS_ALLOCATED: u16 = $bits;
+                const PIXEL_REPRESENTATION: PixelRepresentation = PixelRepresentation::$repr;
+            }
+        )*
+    };
+}
+
+impl_native_sample! {
+    u8 => 8, Unsigned;
+    i8 => 8, Signed;
+    u16 => 16, Unsigned;
+    i16 => 16, Signed;
+    u32 => 32, Unsigned;
+    i32 => 32, Signed;
+}
+
+impl Frames<'_> {
+    /// Decode all frames and convert them
+    /// into a four dimensional array of the given type `T`,
+    /// applying only the modality transformation to monochrome frames.
+    ///
+    /// See [`to_ndarray_with_options`](Self::to_ndarray_with_options).
+    pub fn to_ndarray<T>(&self) -> Result<Array4<T>>
+    where
+        T: NumCast,
+        T: Copy,
+    {
+        self.to_ndarray_with_options(&Default::default())
+    }
+
+    /// Decode all frames and convert them
+    /// into a four dimensional array of the given type `T`.
+    ///
+    /// The shape of the array is `[N, R, C, S]`,
+    /// where `N` is the number of frames,
+    /// `R` is the number of rows,
+    /// `C` is the number of columns,
+    /// and `S` is the number of samples per pixel.
+    ///
+    /// Monochrome frames are normalized and rescaled
+    /// according to the modality LUT option,
+    /// and then go through the VOI LUT function
+    /// only if the VOI LUT option is explicitly set to
+    /// [`First`](VoiLutOption::First),
+    /// [`Custom`](VoiLutOption::Custom),
+    /// or [`Normalize`](VoiLutOption::Normalize).
+    /// The values are not inverted for `MONOCHROME1`.
+    /// Color frames are converted to `RGB` if necessary,
+    /// yielding 3 samples per pixel.
+    ///
+    /// Fails if a value cannot be represented by `T`,
+    /// or if the pixel data does not match
+    /// the number of frames, rows, and columns of the object.
+    pub fn to_ndarray_with_options<T>(&self, options: &ConvertOptions) -> Result<Array4<T>>
+    where
+        T: NumCast,
+        T: Copy,
+    {
+        let info = self.info();
+        if let Some(data) = info.native_data() {
+            let bits_allocated = info.bits_allocated().max(1) as u64;
+            let expected = (info.expected_length() * 8 / bits_allocated) as usize;
+            let actual = (data.len() as u64 * 8 / bits_allocated) as usize;
+            if actual < expected {
+                return ElementCountMismatchSnafu { expected, actual }.fail()?;
+            }
+        }
+
+        let mut samples_per_pixel = info.samples_per_pixel() as usize;
+        let mut data: Vec<T> = Vec::new();
+        for index in 0..info.number_of_frames() {
+            let (values, spp) = self.frame_elements::<T>(index, options)?;
+            samples_per_pixel = spp;
+            data.extend(values);
+        }
+
+        let shape = [
+            info.number_of_frames() as usize,
+            info.rows() as usize,
+            info.columns() as usize,
+            samples_per_pixel,
+        ];
+        let expected = shape.iter().product();
+        if data.len() != expected {
+            return ElementCountMismatchSnafu {
+                expected,
+                actual: data.len(),
+            }
+            .fail()?;
+        }
+        Ok(Array::from_shape_vec(shape, data).context(InvalidShapeSnafu)?)
+    }
+
+    /// View the native pixel data as a four dimensional array
+    /// of stored samples of type `T`, without copying.
+    ///
+    /// The shape of the array is `[N, R, C, S]`,
+    /// as in [`to_ndarray`](Self::to_ndarray).
+    /// Samples are not transformed in any way,
+    /// and planar pixel data is viewed through the array strides.
+    ///
+    /// Fails if the pixel data is encapsulated,
+    /// if `T` does not match _Bits Allocated_ and _Pixel Representation_,
+    /// if the pixel data is too short for the image dimensions,
+    /// or if the pixel data is not aligned for `T` in memory.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_pixeldata::PixelDataAccess;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let obj = dicom_object::open_file("ct.dcm")?;
+    /// let frames = obj.frames()?;
+    /// let view = frames.to_ndarray_view::<i16>()?;
+    /// println!("first voxel: {}", view[[0, 0, 0, 0]]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_ndarray_view<T: NativeSample>(&self) -> Result<ArrayView4<'_, T>> {
+        let info = self.info();
+        let data = info.native_data().context(NotNativePixelDataSnafu)?;
+        if info.bits_allocated() != T::BITS_ALLOCATED
+            || info.pixel_representation() != T::PIXEL_REPRESENTATION
+        {
+            return InvalidDataTypeSnafu.fail()?;
+        }
+        let pi = info.photometric_interpretation();
+        if *pi == PhotometricInterpretation::YbrFull422 && info.samples_per_pixel() == 3 {
+            // subsampled chroma does not fit in an array of 3 samples per pixel
+            return UnsupportedPhotometricInterpretationSnafu { pi: pi.clone() }.fail()?;
+        }
+
+        let (frames, rows, cols, spp) = (
+            info.number_of_frames() as usize,
+            info.rows() as usize,
+            info.columns() as usize,
+            info.samples_per_pixel() as usize,
+        );
+        let expected = frames * rows * cols * spp;
+        let actual = data.len() / size_of::<T>();
+        if actual < expected {
+            return ElementCountMismatchSnafu { expected, actual }.fail()?;
+        }
+        let samples: &[T] = bytemuck::try_cast_slice(&data[..expected * size_of::<T>()])
+            .ok()
+            .context(UnalignedPixelDataSnafu {
+                align: align_of::<T>(),
+            })?;
+
+        let shape = (frames, rows, cols, spp);
+        let view = match info.planar_configuration() {
+            PlanarConfiguration::Standard => ArrayView::from_shape(shape, samples),
+            PlanarConfiguration::PixelFirst => ArrayView::from_shape(
+                shape.strides((rows * cols * spp, cols, 1, rows * cols)),
+                samples,
+            ),
+        };
+        Ok(view.context(InvalidShapeSnafu)?)
+    }
+
+    /// Obtain the elements of the frame at the given index
+    /// and the number of samples per pixel.
+    fn frame_elements<T: NumCast>(
+        &self,
+        index: u32,
+        options: &ConvertOptions,
+    ) -> Result<(Vec<T>, usize)> {
+        let info = self.info();
+        if info.samples_per_pixel() == 1
+            && info.palette_color_lut().is_none()
+            && info.photometric_interpretation().is_monochrome()
+        {
+            let mut values = self.modality_values(index, &options.modality_lut)?;
+            let voi = !matches!(
+                options.voi_lut,
+                VoiLutOption::Default | VoiLutOption::Identity
+            );
+            if voi && options.modality_lut != ModalityLutOption::None {
+                let y_max = self.output_max(options.bit_depth);
+                values = self.voi_values(index, values, &options.voi_lut, y_max)?;
+            }
+            let values = values
+                .into_iter()
+                .map(T::from)
+                .collect::<Option<_>>()
+                .context(InvalidDataTypeSnafu)?;
+            return Ok((values, 1));
+        }
+
+        let decode_options = FrameDecodeOptions::new()
+            .with_rgb(true)
+            .with_normalized_bits(true)
+            .with_planar_configuration(PlanarConfiguration::Standard);
+        let frame = self.decode_frame_with_options(index, &decode_options)?;
+        // converted samples are always unsigned
+        let signed = info.pixel_representation() == PixelRepresentation::Signed
+            && frame.converted_from.is_none();
+        let bytes = &frame.bytes;
+        let values: Option<Vec<T>> = match (frame.bits_allocated, signed) {
+            (8, false) => bytes.iter().map(|&b| T::from(b)).collect(),
+            (8, true) => bytes.iter().map(|&b| T::from(b as i8)).collect(),
+            (16, false) => bytes
+                .chunks_exact(2)
+                .map(|b| T::from(u16::from_ne_bytes([b[0], b[1]])))
+                .collect(),
+            (16, true) => bytes
+                .chunks_exact(2)
+                .map(|b| T::from(i16::from_ne_bytes([b[0], b[1]])))
+                .collect(),
+            (32, false) => bytes
+                .chunks_exact(4)
+                .map(|b| T::from(u32::from_ne_bytes([b[0], b[1], b[2], b[3]])))
+                .collect(),
+            (32, true) => bytes
+                .chunks_exact(4)
+                .map(|b| T::from(i32::from_ne_bytes([b[0], b[1], b[2], b[3]])))
+                .collect(),
+            _ => return InvalidBitsAllocatedSnafu.fail()?,
+        };
+        Ok((
+            values.context(InvalidDataTypeSnafu)?,
+            frame.samples_per_pixel as usize,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::info::tests::dummy_image;
+    use crate::{ConvertOptions, InnerError, ModalityLutOption, PixelDataAccess};
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+
+    /// 3 frames of 2x3 pixels, with stored values `1000 + 100 * frame + pixel`
+    /// and a rescale intercept of -1000.
+    fn mono_frames(number_of_frames: i32) -> dicom_object::DefaultDicomObject {
+        let stored: Vec<u16> = (0..3)
+            .flat_map(|frame| (0..6).map(move |pixel| 1000 + 100 * frame + pixel))
+            .collect();
+        let mut obj = dummy_image(
+            2,
+            3,
+            Some(number_of_frames),
+            1,
+            16,
+            PrimitiveValue::U16(stored.into()),
+        );
+        obj.put(DataElement::new(
+            tags::RESCALE_INTERCEPT,
+            VR::DS,
+            PrimitiveValue::from("-1000"),
+        ));
+        obj
+    }
+
+    #[test]
+    fn mono_multiframe_to_ndarray() {
+        let obj = mono_frames(3);
+        let array = obj.to_ndarray::<f32>().unwrap();
+        assert_eq!(array.shape(), &[3, 2, 3, 1]);
+        assert_eq!(array[[0, 0, 0, 0]], 0.);
+        assert_eq!(array[[1, 0, 2, 0]], 102.);
+        assert_eq!(array[[2, 1, 2, 0]], 205.);
+
+        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+        let array = obj.to_ndarray_with_options::<u16>(&options).unwrap();
+        assert_eq!(array[[2, 1, 0, 0]], 1203);
+
+        // negative values do not fit
+        let mut obj = obj;
+        obj.put(DataElement::new(
+            tags::RESCALE_INTERCEPT,
+            VR::DS,
+            PrimitiveValue::from("-1100"),
+        ));
+        let err = obj.to_ndarray::<u16>().unwrap_err();
+        assert!(matches!(err.0, InnerError::InvalidDataType { .. }));
+    }
+
+    #[test]
+    fn mono_multiframe_ndarray_view() {
+        let obj = mono_frames(3);
+        let frames = obj.frames().unwrap();
+        let view = frames.to_ndarray_view::<u16>().unwrap();
+        assert_eq!(view.shape(), &[3, 2, 3, 1]);
+        assert_eq!(view[[1, 1, 1, 0]], 1104);
+        assert_eq!(view[[2, 0, 1, 0]], 1201);
+
+        let err = frames.to_ndarray_view::<i16>().unwrap_err();
+        assert!(matches!(err.0, InnerError::InvalidDataType { .. }));
+    }
+
+    #[test]
+    fn rgb_to_ndarray() {
+        let interleaved = [255, 0, 0, 0, 255, 0, 0, 0, 255, 10, 20, 30];
+        let obj = dummy_image(
+            2,
+            2,
+            None,
+            3,
+            8,
+            PrimitiveValue::U8(interleaved.iter().copied().collect()),
+        );
+        let array = obj.to_ndarray::<u8>().unwrap();
+        assert_eq!(array.shape(), &[1, 2, 2, 3]);
+        assert_eq!(array[[0, 0, 1, 1]], 255);
+        assert_eq!(array[[0, 1, 1, 0]], 10);
+        assert_eq!(array[[0, 1, 1, 2]], 30);
+
+        // the same samples in planar layout
+        let planar = [255, 0, 0, 10, 0, 255, 0, 20, 0, 0, 255, 30];
+        let mut obj = dummy_image(
+            2,
+            2,
+            None,
+            3,
+            8,
+            PrimitiveValue::U8(planar.iter().copied().collect()),
+        );
+        obj.put(DataElement::new(
+            tags::PLANAR_CONFIGURATION,
+            VR::US,
+            dicom_value!(U16, [1]),
+        ));
+        assert_eq!(obj.to_ndarray::<u8>().unwrap().shape(), &[1, 2, 2, 3]);
+        let frames = obj.frames().unwrap();
+        let view = frames.to_ndarray_view::<u8>().unwrap();
+        assert_eq!(view.shape(), &[1, 2, 2, 3]);
+        for (i, &sample) in interleaved.iter().enumerate() {
+            let (pixel, sample_index) = (i / 3, i % 3);
+            assert_eq!(view[[0, pixel / 2, pixel % 2, sample_index]], sample);
+            assert_eq!(array[[0, pixel / 2, pixel % 2, sample_index]], sample);
+        }
+    }
+
+    #[test]
+    fn ndarray_element_count_mismatch() {
+        // 4 frames declared, 3 frames of pixel data
+        let obj = mono_frames(4);
+        let err = obj.to_ndarray::<f32>().unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::ElementCountMismatch {
+                expected: 24,
+                actual: 18,
+                ..
+            }
+        ));
+
+        let frames = obj.frames().unwrap();
+        let err = frames.to_ndarray_view::<u16>().unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::ElementCountMismatch {
+                expected: 24,
+                actual: 18,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,797 @@
+//! Access to the pixel data of a DICOM object as it is stored,
+//! alongside the image pixel attributes needed to interpret it.
+//!
+//! Unlike [`PixelDecoder`](crate::PixelDecoder),
+//! nothing here decodes or copies the pixel data:
+//! [`PixelDataInfo`] borrows the bytes from the object
+//! and only computes where each frame lies.
+
+use crate::attribute::{self, PhotometricInterpretation, PixelRepresentation, PlanarConfiguration};
+use crate::frame::Frames;
+use crate::overlay::Overlay;
+use crate::{
+    ExplicitLut, FrameOutOfRangeSnafu, GetAttributeSnafu, InvalidPixelDataSnafu,
+    LengthMismatchPixelDataSnafu, LengthMismatchRescaleSnafu, LengthMismatchWindowLevelSnafu,
+    NotNativePixelDataSnafu, PaletteColorLut, Rescale, Result, UnsupportedOtherSnafu,
+    VoiLutFunction, WindowLevel,
+};
+use dicom_core::{DataDictionary, DicomValue};
+use dicom_object::{mem::InMemFragment, FileDicomObject, InMemDicomObject};
+use snafu::{OptionExt, ResultExt};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+/// Option set for retrieving pixel data through [`PixelDataAccess`].
+#[derive(Debug, Default, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct PixelDataOptions {
+    /// What to do when the length of the pixel data
+    /// does not match the image pixel attributes
+    pub length_check: LengthCheckOption,
+}
+
+impl PixelDataOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the pixel data length check option.
+    pub fn with_length_check(mut self, length_check: LengthCheckOption) -> Self {
+        self.length_check = length_check;
+        self
+    }
+}
+
+/// Policy for native pixel data
+/// whose length is inconsistent with the image pixel attributes.
+///
+/// See also [`PixelDataOptions`].
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum LengthCheckOption {
+    /// _Default behavior:_
+    /// log a warning and carry on with the pixel data as is.
+    #[default]
+    Warn,
+    /// Fail with an error.
+    Error,
+    /// Do not check the length of the pixel data.
+    Ignore,
+}
+
+/// A handle to the bytes of a _Pixel Data_ element.
+#[derive(Debug, Clone)]
+pub enum PixelDataBytes<'a> {
+    /// Native pixel data,
+    /// all frames in a single sequence of bytes in native byte order.
+    Native(Cow<'a, [u8]>),
+    /// Encapsulated pixel data,
+    /// as it is found in the pixel data sequence.
+    Encapsulated {
+        /// the basic offset table, possibly empty
+        offset_table: &'a [u32],
+        /// the pixel data fragments
+        fragments: &'a [InMemFragment],
+    },
+}
+
+/// The pixel data of a DICOM object in its stored form,
+/// together with the image pixel attributes describing it.
+///
+/// Obtained via [`PixelDataAccess::pixel_data`].
+#[derive(Debug, Clone)]
+pub struct PixelDataInfo<'a> {
+    /// the number of rows
+    rows: u32,
+    /// the number of columns
+    cols: u32,
+    /// the number of frames
+    number_of_frames: u32,
+    /// the number of bits allocated for each sample
+    bits_allocated: u16,
+    /// the number of bits stored
+    bits_stored: u16,
+    /// the high bit, usually `bits_stored - 1`
+    high_bit: u16,
+    /// the pixel representation: 0 for unsigned, 1 for signed
+    pixel_representation: PixelRepresentation,
+    /// the number of samples per pixel
+    samples_per_pixel: u16,
+    /// the photometric interpretation
+    photometric_interpretation: PhotometricInterpretation,
+    /// the planar configuration: 0 for standard, 1 for channel-contiguous
+    planar_configuration: PlanarConfiguration,
+    /// the UID of the transfer syntax in which the pixel data is encoded
+    transfer_syntax: &'a str,
+    /// the rescale slope, for all frames or for each frame
+    rescale_slope: Vec<f64>,
+    /// the rescale intercept, for all frames or for each frame
+    rescale_intercept: Vec<f64>,
+    /// the lookup table of the _Modality LUT Sequence_
+    modality_lut: Option<ExplicitLut>,
+    /// the window centers, for all frames or for each frame
+    window_center: Option<Vec<f64>>,
+    /// the window widths, for all frames or for each frame
+    window_width: Option<Vec<f64>>,
+    /// the VOI LUT functions, for all frames or for each frame
+    voi_lut_function: Vec<VoiLutFunction>,
+    /// the first lookup table of the _VOI LUT Sequence_
+    voi_lut: Option<ExplicitLut>,
+    /// the palette color lookup tables of a `PALETTE COLOR` image
+    palette_color_lut: Option<PaletteColorLut>,
+    /// the pixel data proper
+    data: PixelDataBytes<'a>,
+}
+
+impl<'a> PixelDataInfo<'a> {
+    /// Retrieves the number of rows of the pixel data.
+    #[inline]
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Retrieves the number of columns of the pixel data.
+    #[inline]
+    pub fn columns(&self) -> u32 {
+        self.cols
+    }
+
+    /// Retrieves the total number of frames.
+    #[inline]
+    pub fn number_of_frames(&self) -> u32 {
+        self.number_of_frames
+    }
+
+    /// Retrieve the number of bits allocated for each sample.
+    #[inline]
+    pub fn bits_allocated(&self) -> u16 {
+        self.bits_allocated
+    }
+
+    /// Retrieve the number of bits effectively used for each sample.
+    #[inline]
+    pub fn bits_stored(&self) -> u16 {
+        self.bits_stored
+    }
+
+    /// Retrieve the high bit index of each sample.
+    #[inline]
+    pub fn high_bit(&self) -> u16 {
+        self.high_bit
+    }
+
+    /// Retrieve the pixel representation.
+    #[inline]
+    pub fn pixel_representation(&self) -> PixelRepresentation {
+        self.pixel_representation
+    }
+
+    /// Retrieves the number of samples per pixel.
+    #[inline]
+    pub fn samples_per_pixel(&self) -> u16 {
+        self.samples_per_pixel
+    }
+
+    /// Retrieves the photometric interpretation.
+    #[inline]
+    pub fn photometric_interpretation(&self) -> &PhotometricInterpretation {
+        &self.photometric_interpretation
+    }
+
+    /// Retrieves the planar configuration of the pixel data.
+    ///
+    /// The value returned is only meaningful for
+    /// images with more than 1 sample per pixel.
+    #[inline]
+    pub fn planar_configuration(&self) -> PlanarConfiguration {
+        self.planar_configuration
+    }
+
+    /// Retrieve the UID of the transfer syntax
+    /// in which the pixel data is encoded.
+    #[inline]
+    pub fn transfer_syntax(&self) -> &'a str {
+        self.transfer_syntax
+    }
+
+    /// Retrieve the rescale function of the frame at the given index,
+    /// as given by the _Rescale Slope_ and _Rescale Intercept_ attributes
+    /// or by the functional groups of an enhanced multi-frame object.
+    ///
+    /// Missing attributes default to a slope of 1 and an intercept of 0.
+    pub fn rescale(&self, index: u32) -> Result<Rescale> {
+        if self.rescale_slope.len() != self.rescale_intercept.len() {
+            return LengthMismatchRescaleSnafu {
+                slope_vm: self.rescale_slope.len() as u32,
+                intercept_vm: self.rescale_intercept.len() as u32,
+            }
+            .fail()?;
+        }
+        let i = if self.rescale_slope.len() > 1 {
+            index as usize
+        } else {
+            0
+        };
+        match (self.rescale_slope.get(i), self.rescale_intercept.get(i)) {
+            (Some(&slope), Some(&intercept)) => Ok(Rescale::new(slope, intercept)),
+            _ => FrameOutOfRangeSnafu {
+                frame_number: index,
+            }
+            .fail()?,
+        }
+    }
+
+    /// Whether the object defines a _Modality LUT Sequence_,
+    /// which takes precedence over the rescale function.
+    #[inline]
+    pub fn has_modality_lut(&self) -> bool {
+        self.modality_lut.is_some()
+    }
+
+    /// Retrieve the lookup table of the _Modality LUT Sequence_,
+    /// which maps stored pixel values to real world values
+    /// in place of the rescale function.
+    #[inline]
+    pub fn modality_lut(&self) -> Option<&ExplicitLut> {
+        self.modality_lut.as_ref()
+    }
+
+    /// Retrieve the window of the frame at the given index,
+    /// as given by the first pair of values in
+    /// _Window Center_ and _Window Width_
+    /// or by the functional groups of an enhanced multi-frame object,
+    /// or `None` if the object does not define a window.
+    pub fn window(&self, index: u32) -> Result<Option<WindowLevel>> {
+        let (centers, widths) = match (&self.window_center, &self.window_width) {
+            (Some(centers), Some(widths)) => (centers, widths),
+            _ => return Ok(None),
+        };
+        if centers.len() != widths.len() {
+            return LengthMismatchWindowLevelSnafu {
+                wc_vm: centers.len() as u32,
+                ww_vm: widths.len() as u32,
+            }
+            .fail()?;
+        }
+        let i = if centers.len() > 1 { index as usize } else { 0 };
+        Ok(centers
+            .get(i)
+            .zip(widths.get(i))
+            .map(|(&center, &width)| WindowLevel { center, width }))
+    }
+
+    /// Retrieve the VOI LUT function of the frame at the given index,
+    /// which is `LINEAR` if _VOI LUT Function_ is absent or not recognized.
+    pub fn voi_lut_function(&self, index: u32) -> VoiLutFunction {
+        let i = if self.voi_lut_function.len() > 1 {
+            index as usize
+        } else {
+            0
+        };
+        self.voi_lut_function.get(i).copied().unwrap_or_default()
+    }
+
+    /// Retrieve the first lookup table of the _VOI LUT Sequence_,
+    /// which maps the output of the modality transformation
+    /// to values meaningful for display.
+    #[inline]
+    pub fn voi_lut(&self) -> Option<&ExplicitLut> {
+        self.voi_lut.as_ref()
+    }
+
+    /// Retrieve the palette color lookup tables,
+    /// which are only present if the photometric interpretation
+    /// is `PALETTE COLOR`.
+    #[inline]
+    pub fn palette_color_lut(&self) -> Option<&PaletteColorLut> {
+        self.palette_color_lut.as_ref()
+    }
+
+    /// Retrieve a handle to the pixel data bytes.
+    #[inline]
+    pub fn data(&self) -> &PixelDataBytes<'a> {
+        &self.data
+    }
+
+    /// Whether the pixel data is in native (uncompressed) form.
+    #[inline]
+    pub fn is_native(&self) -> bool {
+        matches!(self.data, PixelDataBytes::Native(_))
+    }
+
+    /// Retrieve all bytes of native pixel data,
+    /// or `None` if the pixel data is encapsulated.
+    pub fn native_data(&self) -> Option<&[u8]> {
+        match &self.data {
+            PixelDataBytes::Native(data) => Some(data),
+            PixelDataBytes::Encapsulated { .. } => None,
+        }
+    }
+
+    /// Calculate the number of bytes of native pixel data
+    /// expected for all frames,
+    /// according to the image pixel attributes.
+    ///
+    /// This does not include the trailing padding byte
+    /// needed to make the value length even.
+    pub fn expected_length(&self) -> u64 {
+        let bits = self.frame_length_in_bits() * u64::from(self.number_of_frames);
+        bits.div_ceil(8)
+    }
+
+    /// Calculate the number of bytes of a single frame of native pixel data.
+    ///
+    /// Frames of 1-bit pixel data which do not end at a byte boundary
+    /// are rounded up.
+    /// Native `YBR_FULL_422` pixel data has its chroma samples
+    /// subsampled horizontally,
+    /// for an average of two samples per pixel.
+    pub fn frame_length(&self) -> u64 {
+        self.frame_length_in_bits().div_ceil(8)
+    }
+
+    fn frame_length_in_bits(&self) -> u64 {
+        let samples_per_pixel = if self.samples_per_pixel == 3
+            && self.photometric_interpretation == PhotometricInterpretation::YbrFull422
+        {
+            2
+        } else {
+            self.samples_per_pixel
+        };
+        u64::from(self.rows)
+            * u64::from(self.cols)
+            * u64::from(samples_per_pixel)
+            * u64::from(self.bits_allocated)
+    }
+
+    /// Retrieve the bytes of the frame at the given index
+    /// in native pixel data.
+    ///
+    /// In either planar configuration,
+    /// all samples of a frame are contiguous,
+    /// so the slice returned contains
+    /// all color planes of the frame in the case of
+    /// [`PixelFirst`](PlanarConfiguration::PixelFirst).
+    ///
+    /// Fails if the pixel data is encapsulated,
+    /// if the frame is out of range or truncated,
+    /// or if frames do not start at a byte boundary.
+    pub fn frame_bytes(&self, index: u32) -> Result<&[u8]> {
+        let data = self.native_data().context(NotNativePixelDataSnafu)?;
+        let bits = self.frame_length_in_bits();
+        if !bits.is_multiple_of(8) && self.number_of_frames > 1 {
+            return UnsupportedOtherSnafu {
+                name: "frame length in bits",
+                value: bits.to_string(),
+            }
+            .fail()?;
+        }
+        let frame_length = self.frame_length() as usize;
+        let start = frame_length * index as usize;
+        let end = start + frame_length;
+        if index >= self.number_of_frames || end > data.len() {
+            return FrameOutOfRangeSnafu {
+                frame_number: index,
+            }
+            .fail()?;
+        }
+        Ok(&data[start..end])
+    }
+
+    /// Calculate the byte offset of a sample
+    /// relative to the start of its frame,
+    /// taking the planar configuration into account.
+    ///
+    /// Returns `None` if any of the given coordinates is out of range,
+    /// or if samples are smaller than a byte.
+    pub fn sample_offset(&self, row: u32, col: u32, sample: u16) -> Option<usize> {
+        if row >= self.rows
+            || col >= self.cols
+            || sample >= self.samples_per_pixel
+            || !self.bits_allocated.is_multiple_of(8)
+        {
+            return None;
+        }
+        let bytes_per_sample = self.bits_allocated as usize / 8;
+        let pixel = row as usize * self.cols as usize + col as usize;
+        let index = match self.planar_configuration {
+            PlanarConfiguration::Standard => {
+                pixel * self.samples_per_pixel as usize + sample as usize
+            }
+            PlanarConfiguration::PixelFirst => {
+                sample as usize * self.rows as usize * self.cols as usize + pixel
+            }
+        };
+        Some(index * bytes_per_sample)
+    }
+}
+
+/// Extension trait for retrieving the pixel data of a DICOM object
+/// without decoding it.
+///
+/// See also [`PixelDecoder`](crate::PixelDecoder).
+pub trait PixelDataAccess {
+    /// Retrieve the pixel data and image pixel attributes of this object,
+    /// with the default options.
+    fn pixel_data(&self) -> Result<PixelDataInfo<'_>> {
+        self.pixel_data_with_options(&Default::default())
+    }
+
+    /// Retrieve the pixel data and image pixel attributes of this object.
+    fn pixel_data_with_options(&self, options: &PixelDataOptions) -> Result<PixelDataInfo<'_>>;
+
+    /// Iterate over the frames of the pixel data in this object,
+    /// native or encapsulated, without decoding them.
+    ///
+    /// See [`PixelDataInfo::into_frames`].
+    fn frames(&self) -> Result<Frames<'_>> {
+        self.pixel_data()?.into_frames()
+    }
+
+    /// Retrieve the overlay planes of this object,
+    /// in the order of their groups.
+    ///
+    /// The bitmap of each overlay is unpacked from _Overlay Data_,
+    /// or taken from the bit at _Overlay Bit Position_ of each pixel sample
+    /// when the overlay is embedded in the pixel data.
+    fn overlays(&self) -> Result<Vec<Overlay>>;
+
+    /// Decode the frame at the given index
+    /// and convert it into a dynamic image of the `image` crate.
+    ///
+    /// See [`Frames::to_dynamic_image`].
+    #[cfg(feature = "image")]
+    fn to_image(&self, index: u32, options: &crate::ConvertOptions) -> Result<image::DynamicImage> {
+        self.frames()?.to_dynamic_image(index, options)
+    }
+
+    /// Decode all frames
+    /// and convert them into a four dimensional array of the `ndarray` crate,
+    /// applying only the modality transformation to monochrome frames.
+    ///
+    /// See [`Frames::to_ndarray_with_options`].
+    #[cfg(feature = "ndarray")]
+    fn to_ndarray<T>(&self) -> Result<ndarray::Array4<T>>
+    where
+        T: num_traits::NumCast + Copy,
+    {
+        self.frames()?.to_ndarray()
+    }
+
+    /// Decode all frames
+    /// and convert them into a four dimensional array of the `ndarray` crate.
+    ///
+    /// See [`Frames::to_ndarray_with_options`].
+    #[cfg(feature = "ndarray")]
+    fn to_ndarray_with_options<T>(
+        &self,
+        options: &crate::ConvertOptions,
+    ) -> Result<ndarray::Array4<T>>
+    where
+        T: num_traits::NumCast + Copy,
+    {
+        self.frames()?.to_ndarray_with_options(options)
+    }
+}
+
+impl<D> PixelDataAccess for FileDicomObject<InMemDicomObject<D>>
+where
+    D: DataDictionary + Clone,
+{
+    fn overlays(&self) -> Result<Vec<Overlay>> {
+        attribute::overlay_groups(self)
+            .into_iter()
+            .map(|group| {
+                let mut attributes = attribute::overlay(self, group).context(GetAttributeSnafu)?;
+                match attributes.data.take() {
+                    Some(packed) => Overlay::from_packed(group, attributes, &packed),
+                    None => Overlay::from_embedded(group, attributes, &self.frames()?),
+                }
+            })
+            .collect()
+    }
+
+    fn pixel_data_with_options(&self, options: &PixelDataOptions) -> Result<PixelDataInfo<'_>> {
+        let pixel_data = attribute::pixel_data(self).context(GetAttributeSnafu)?;
+        let data = match pixel_data.value() {
+            DicomValue::Primitive(p) => PixelDataBytes::Native(p.to_bytes()),
+            DicomValue::PixelSequence(v) => PixelDataBytes::Encapsulated {
+                offset_table: v.offset_table(),
+                fragments: v.fragments(),
+            },
+            DicomValue::Sequence(..) => InvalidPixelDataSnafu.fail()?,
+        };
+
+        let mut info = PixelDataInfo {
+            rows: attribute::rows(self).context(GetAttributeSnafu)?.into(),
+            cols: attribute::cols(self).context(GetAttributeSnafu)?.into(),
+            number_of_frames: attribute::number_of_frames(self).context(GetAttributeSnafu)?,
+            bits_allocated: attribute::bits_allocated(self).context(GetAttributeSnafu)?,
+            bits_stored: attribute::bits_stored(self).context(GetAttributeSnafu)?,
+            high_bit: attribute::high_bit(self).context(GetAttributeSnafu)?,
+            pixel_representation: attribute::pixel_representation(self)
+                .context(GetAttributeSnafu)?,
+            samples_per_pixel: attribute::samples_per_pixel(self).context(GetAttributeSnafu)?,
+            photometric_interpretation: attribute::photometric_interpretation(self)
+                .context(GetAttributeSnafu)?,
+            planar_configuration: attribute::planar_configuration(self)
+                .context(GetAttributeSnafu)?,
+            transfer_syntax: self.meta().transfer_syntax(),
+            rescale_slope: attribute::rescale_slope(self),
+            rescale_intercept: attribute::rescale_intercept(self),
+            modality_lut: None,
+            window_center: attribute::window_center(self),
+            window_width: attribute::window_width(self),
+            voi_lut_function: attribute::voi_lut_function(self)
+                .context(GetAttributeSnafu)?
+                .unwrap_or_default()
+                .iter()
+                .map(|name| VoiLutFunction::try_from(name.as_str()).unwrap_or_default())
+                .collect(),
+            voi_lut: None,
+            palette_color_lut: None,
+            data,
+        };
+
+        if let Some(lut) = attribute::modality_lut(self).context(GetAttributeSnafu)? {
+            info.modality_lut = Some(explicit_lut(lut, info.pixel_representation)?);
+        }
+        if let Some(lut) = attribute::voi_lut(self).context(GetAttributeSnafu)? {
+            // the output of a modality LUT is always unsigned
+            let pixel_representation = if info.modality_lut.is_some() {
+                PixelRepresentation::Unsigned
+            } else {
+                info.pixel_representation
+            };
+            info.voi_lut = Some(explicit_lut(lut, pixel_representation)?);
+        }
+
+        if info.photometric_interpretation == PhotometricInterpretation::PaletteColor {
+            info.palette_color_lut = Some(PaletteColorLut::new(
+                attribute::palette_color_lut_descriptors(self).context(GetAttributeSnafu)?,
+                attribute::palette_color_lut_data(self).context(GetAttributeSnafu)?,
+                info.pixel_representation,
+            )?);
+        }
+
+        if let Some(data) = info.native_data() {
+            check_length(
+                info.expected_length(),
+                data.len() as u64,
+                options.length_check,
+            )?;
+        }
+
+        Ok(info)
+    }
+}
+
+/// Compare the expected length of native pixel data with the actual one,
+/// accepting one trailing padding byte if the expected length is odd.
+/// Build a lookup table from the attributes of a LUT sequence item.
+fn explicit_lut(
+    lut: attribute::LutAttributes,
+    pixel_representation: PixelRepresentation,
+) -> Result<ExplicitLut> {
+    let out = ExplicitLut::new(lut.descriptor, lut.data, pixel_representation)?;
+    Ok(match lut.explanation {
+        Some(explanation) => out.with_explanation(explanation),
+        None => out,
+    })
+}
+
+fn check_length(expected: u64, actual: u64, option: LengthCheckOption) -> Result<()> {
+    if actual == expected || (expected % 2 == 1 && actual == expected + 1) {
+        return Ok(());
+    }
+    match option {
+        LengthCheckOption::Ignore => Ok(()),
+        LengthCheckOption::Warn => {
+            tracing::warn!(
+                "Pixel data length {} does not match the expected length {}",
+                actual,
+                expected
+            );
+            Ok(())
+        }
+        LengthCheckOption::Error => LengthMismatchPixelDataSnafu { expected, actual }.fail()?,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_object::{DefaultDicomObject, FileMetaTableBuilder};
+
+    pub(crate) fn dummy_image(
+        rows: u16,
+        cols: u16,
+        frames: Option<i32>,
+        samples_per_pixel: u16,
+        bits_allocated: u16,
+        pixel_data: PrimitiveValue,
+    ) -> DefaultDicomObject {
+        let mut obj = FileDicomObject::new_empty_with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)
+                .media_storage_sop_instance_uid("2.25.137038125948464847900039011591283709926")
+                .build()
+                .unwrap(),
+        );
+        let pi = if samples_per_pixel == 3 {
+            "RGB"
+        } else {
+            "MONOCHROME2"
+        };
+        obj.put(DataElement::new(
+            tags::SAMPLES_PER_PIXEL,
+            VR::US,
+            dicom_value!(U16, [samples_per_pixel]),
+        ));
+        obj.put(DataElement::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            VR::CS,
+            PrimitiveValue::from(pi),
+        ));
+        if let Some(frames) = frames {
+            obj.put(DataElement::new(
+                tags::NUMBER_OF_FRAMES,
+                VR::IS,
+                PrimitiveValue::from(frames.to_string()),
+            ));
+        }
+        obj.put(DataElement::new(
+            tags::ROWS,
+            VR::US,
+            dicom_value!(U16, [rows]),
+        ));
+        obj.put(DataElement::new(
+            tags::COLUMNS,
+            VR::US,
+            dicom_value!(U16, [cols]),
+        ));
+        obj.put(DataElement::new(
+            tags::BITS_ALLOCATED,
+            VR::US,
+            dicom_value!(U16, [bits_allocated]),
+        ));
+        obj.put(DataElement::new(
+            tags::BITS_STORED,
+            VR::US,
+            dicom_value!(U16, [bits_allocated]),
+        ));
+        obj.put(DataElement::new(
+            tags::HIGH_BIT,
+            VR::US,
+            dicom_value!(U16, [bits_allocated - 1]),
+        ));
+        obj.put(DataElement::new(
+            tags::PIXEL_REPRESENTATION,
+            VR::US,
+            dicom_value!(U16, [0]),
+        ));
+        let vr = if bits_allocated > 8 { VR::OW } else { VR::OB };
+        obj.put(DataElement::new(tags::PIXEL_DATA, vr, pixel_data));
+        obj
+    }
+
+    #[test]
+    fn native_8bit_single_frame() {
+        // 3x3, odd length, padded to 10 bytes
+        let obj = dummy_image(3, 3, None, 1, 8, PrimitiveValue::U8((0..10).collect()));
+        let info = obj.pixel_data().unwrap();
+        assert_eq!(info.rows(), 3);
+        assert_eq!(info.columns(), 3);
+        assert_eq!(info.number_of_frames(), 1);
+        assert_eq!(info.bits_allocated(), 8);
+        assert_eq!(info.bits_stored(), 8);
+        assert_eq!(info.high_bit(), 7);
+        assert_eq!(info.samples_per_pixel(), 1);
+        assert_eq!(info.pixel_representation(), PixelRepresentation::Unsigned);
+        assert_eq!(
+            info.photometric_interpretation(),
+            &PhotometricInterpretation::Monochrome2
+        );
+        assert_eq!(info.planar_configuration(), PlanarConfiguration::Standard);
+        assert!(info.is_native());
+        assert_eq!(info.expected_length(), 9);
+
+        assert_eq!(info.frame_bytes(0).unwrap(), &[0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(info.frame_bytes(1).is_err());
+        assert_eq!(info.sample_offset(1, 2, 0), Some(5));
+        assert_eq!(info.sample_offset(3, 0, 0), None);
+    }
+
+    #[test]
+    fn native_16bit_multi_frame() {
+        let samples: Vec<u16> = (0..2 * 2 * 3).map(|x| x * 1000).collect();
+        let obj = dummy_image(2, 2, Some(3), 1, 16, PrimitiveValue::U16(samples.into()));
+        let info = obj.pixel_data().unwrap();
+        assert_eq!(info.number_of_frames(), 3);
+        assert_eq!(info.bits_allocated(), 16);
+        assert_eq!(info.frame_length(), 8);
+        assert_eq!(info.expected_length(), 24);
+
+        for frame in 0..3 {
+            let bytes = info.frame_bytes(frame).unwrap();
+            assert_eq!(bytes.len(), 8);
+            let values: Vec<u16> = bytes
+                .chunks(2)
+                .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+                .collect();
+            let base = frame as u16 * 4000;
+            assert_eq!(values, vec![base, base + 1000, base + 2000, base + 3000]);
+        }
+        assert!(info.frame_bytes(3).is_err());
+        assert_eq!(info.sample_offset(1, 0, 0), Some(4));
+    }
+
+    #[test]
+    fn native_rgb_interleaved_and_planar() {
+        // 2 frames of 1x2 RGB pixels
+        let data: Vec<u8> = vec![
+            // frame 0
+            10, 20, 30, 11, 21, 31, //
+            // frame 1
+            40, 50, 60, 41, 51, 61,
+        ];
+        let obj = dummy_image(1, 2, Some(2), 3, 8, PrimitiveValue::U8(data.clone().into()));
+        let info = obj.pixel_data().unwrap();
+        assert_eq!(info.samples_per_pixel(), 3);
+        assert_eq!(
+            info.photometric_interpretation(),
+            &PhotometricInterpretation::Rgb
+        );
+        assert_eq!(info.planar_configuration(), PlanarConfiguration::Standard);
+        assert_eq!(info.frame_bytes(1).unwrap(), &data[6..]);
+        // green sample of the second pixel
+        let frame = info.frame_bytes(0).unwrap();
+        assert_eq!(frame[info.sample_offset(0, 1, 1).unwrap()], 21);
+
+        // same samples, each color plane contiguous
+        let data: Vec<u8> = vec![
+            10, 11, 20, 21, 30, 31, //
+            40, 41, 50, 51, 60, 61,
+        ];
+        let mut obj = dummy_image(1, 2, Some(2), 3, 8, PrimitiveValue::U8(data.into()));
+        obj.put(DataElement::new(
+            tags::PLANAR_CONFIGURATION,
+            VR::US,
+            dicom_value!(U16, [1]),
+        ));
+        let info = obj.pixel_data().unwrap();
+        assert_eq!(info.planar_configuration(), PlanarConfiguration::PixelFirst);
+        let frame = info.frame_bytes(1).unwrap();
+        assert_eq!(frame, &[40, 41, 50, 51, 60, 61]);
+        assert_eq!(frame[info.sample_offset(0, 1, 1).unwrap()], 51);
+        assert_eq!(frame[info.sample_offset(0, 0, 2).unwrap()], 60);
+    }
+
+    #[test]
+    fn length_mismatch_per_options() {
+        // 2x2 8-bit image with one byte missing
+        let obj = dummy_image(2, 2, None, 1, 8, PrimitiveValue::U8([1, 2, 3][..].into()));
+
+        // warn by default
+        let info = obj.pixel_data().unwrap();
+        assert!(info.frame_bytes(0).is_err());
+
+        let options = PixelDataOptions::new().with_length_check(LengthCheckOption::Error);
+        let err = obj.pixel_data_with_options(&options).unwrap_err();
+        assert!(matches!(
+            err.0,
+            crate::InnerError::LengthMismatchPixelData {
+                expected: 4,
+                actual: 3,
+                ..
+            }
+        ));
+
+        // padding is only tolerated for odd lengths
+        let obj = dummy_image(2, 2, None, 1, 8, PrimitiveValue::U8((0..6).collect()));
+        assert!(obj.pixel_data_with_options(&options).is_err());
+    }
+}
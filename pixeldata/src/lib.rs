@@ -129,8 +129,10 @@ use snafu::ensure;
 use snafu::OptionExt;
 use snafu::{Backtrace, ResultExt, Snafu};
 use std::borrow::Cow;
+use std::convert::TryFrom;
 #[cfg(not(feature = "gdcm"))]
 use std::iter::zip;
+use std::ops::Range;
 
 #[cfg(feature = "image")]
 pub use image;
@@ -138,15 +140,40 @@ pub use image;
 pub use ndarray;
 
 mod attribute;
+mod explicit_lut;
+mod frame;
+#[cfg(feature = "image")]
+mod frame_image;
+#[cfg(feature = "ndarray")]
+mod frame_ndarray;
+mod info;
 mod lut;
+mod overlay;
+mod palette;
+mod rescale;
 mod transcode;
+mod voi;
 
+pub mod color;
 pub mod encapsulation;
+pub mod normalize;
+pub mod planar;
 pub(crate) mod transform;
 
 // re-exports
 pub use attribute::{PhotometricInterpretation, PixelRepresentation, PlanarConfiguration};
+pub use explicit_lut::ExplicitLut;
+pub use frame::{DecodedFrame, DecodedFrames, Frame, FrameDecodeOptions, Frames};
+#[cfg(feature = "rayon")]
+pub use frame::ParDecodedFrames;
+#[cfg(feature = "ndarray")]
+pub use frame_ndarray::NativeSample;
+pub use info::{
+    LengthCheckOption, PixelDataAccess, PixelDataBytes, PixelDataInfo, PixelDataOptions,
+};
 pub use lut::{CreateLutError, Lut};
+pub use overlay::{Overlay, OverlayType};
+pub use palette::PaletteColorLut;
 pub use transcode::{Error as TranscodeError, Result as TranscodeResult, Transcode};
 pub use transform::{Rescale, VoiLutFunction, WindowLevel, WindowLevelTransform};
 
@@ -169,6 +196,31 @@ pub enum InnerError {
     #[snafu(display("PixelData attribute is not a primitive value or pixel sequence"))]
     InvalidPixelData { backtrace: Backtrace },
 
+    #[snafu(display(
+        "Pixel data length {} does not match the expected length {}",
+        actual,
+        expected
+    ))]
+    LengthMismatchPixelData {
+        expected: u64,
+        actual: u64,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Could not determine the fragments of each of the {} frames among {} fragments",
+        number_of_frames,
+        fragments
+    ))]
+    UnresolvedFrameFragments {
+        number_of_frames: u32,
+        fragments: usize,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Pixel data is encapsulated, expected native pixel data"))]
+    NotNativePixelData { backtrace: Backtrace },
+
     #[snafu(display("Invalid BitsAllocated, must be 8 or 16"))]
     InvalidBitsAllocated { backtrace: Backtrace },
 
@@ -200,6 +252,19 @@ pub enum InnerError {
     #[snafu(display("Invalid buffer when constructing ImageBuffer"))]
     InvalidImageBuffer { backtrace: Backtrace },
 
+    #[snafu(display("Output buffer has {} elements, expected {}", actual, expected))]
+    BufferLengthMismatch {
+        expected: usize,
+        actual: usize,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("No window center and width defined for frame #{}", frame_number))]
+    MissingWindowLevel {
+        frame_number: u32,
+        backtrace: Backtrace,
+    },
+
     #[cfg(feature = "ndarray")]
     #[snafu(display("Invalid shape for ndarray"))]
     InvalidShape {
@@ -207,6 +272,22 @@ pub enum InnerError {
         backtrace: Backtrace,
     },
 
+    #[cfg(feature = "ndarray")]
+    #[snafu(display(
+        "Pixel data has {} elements, expected {} from the image dimensions",
+        actual,
+        expected
+    ))]
+    ElementCountMismatch {
+        expected: usize,
+        actual: usize,
+        backtrace: Backtrace,
+    },
+
+    #[cfg(feature = "ndarray")]
+    #[snafu(display("Pixel data is not aligned to {} bytes", align))]
+    UnalignedPixelData { align: usize, backtrace: Backtrace },
+
     /// Could not create LUT for target data type
     CreateLut {
         source: lut::CreateLutError,
@@ -222,11 +303,52 @@ pub enum InnerError {
     #[snafu(display("Could not decode pixel data"))]
     DecodePixelData { source: DecodeError },
 
+    #[snafu(display(
+        "Decoded frame #{} is {}x{} with {} samples per pixel, expected {}x{} with {}",
+        frame_number,
+        cols,
+        rows,
+        samples_per_pixel,
+        expected_cols,
+        expected_rows,
+        expected_samples_per_pixel
+    ))]
+    DecodedFrameMismatch {
+        frame_number: u32,
+        rows: u32,
+        cols: u32,
+        samples_per_pixel: u16,
+        expected_rows: u32,
+        expected_cols: u32,
+        expected_samples_per_pixel: u16,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not decode frame #{}", frame_number))]
+    DecodeFrame {
+        frame_number: u32,
+        source: DecodeError,
+    },
+
+    #[snafu(display(
+        "Frame #{} does not have the same layout as the first frame",
+        frame_number
+    ))]
+    FrameLayoutMismatch {
+        frame_number: u32,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Frame #{} is out of range", frame_number))]
     FrameOutOfRange {
         frame_number: u32,
         backtrace: Backtrace,
     },
+    #[snafu(display("Size of frame #{} is too large for this platform", frame_number))]
+    FrameSizeOverflow {
+        frame_number: u32,
+        backtrace: Backtrace,
+    },
     #[snafu(display("Value multiplicity of VOI LUT Function must match the number of frames. Expected `{:?}`, found `{:?}`", nr_frames, vm))]
     LengthMismatchVoiLutFunction {
         vm: u32,
@@ -239,6 +361,36 @@ pub enum InnerError {
         slope_vm: u32,
         backtrace: Backtrace,
     },
+    #[snafu(display(
+        "Palette color lookup table has {} entries, expected {}",
+        actual,
+        expected
+    ))]
+    LengthMismatchPaletteLut {
+        expected: usize,
+        actual: usize,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Lookup table has {} entries, expected {}", actual, expected))]
+    LengthMismatchLut {
+        expected: usize,
+        actual: usize,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Overlay data of group {:04X} has {} bits, expected {}",
+        group,
+        actual,
+        expected
+    ))]
+    LengthMismatchOverlayData {
+        group: u16,
+        expected: usize,
+        actual: usize,
+        backtrace: Backtrace,
+    },
     #[snafu(display("Value multiplicity of Window Center/Width must match. Found `{:?}` (center), `{:?}` (width)", wc_vm, ww_vm))]
     LengthMismatchWindowLevel {
         wc_vm: u32,
@@ -325,7 +477,9 @@ impl ConvertOptions {
 pub enum ModalityLutOption {
     /// _Default behavior:_
     /// rescale the pixel data values
-    /// as described in the decoded pixel data.
+    /// as described in the decoded pixel data,
+    /// through the _Modality LUT Sequence_ if present
+    /// or through the rescale function otherwise.
     #[default]
     Default,
     /// Rescale the pixel data values
@@ -362,6 +516,13 @@ pub enum VoiLutOption {
     First,
     /// Apply a custom window level instead of the one described in the object.
     Custom(WindowLevel),
+    /// Apply the first lookup table of the _VOI LUT Sequence_,
+    /// taking precedence over the window described in the object.
+    /// The first window is applied instead
+    /// if the object does not define a VOI LUT,
+    /// as well as when converting [`DecodedPixelData`],
+    /// which does not retain the lookup tables of the object.
+    Lut,
     /// Perform a min-max normalization instead,
     /// so that the lowest value is 0 and
     /// the highest value is the maximum value of the target type.
@@ -466,21 +627,27 @@ impl DecodedPixelData<'_> {
     /// Retrieve a slice of a frame's raw pixel data samples as bytes,
     /// irrespective of the expected size of each sample.
     pub fn frame_data(&self, frame: u32) -> Result<&[u8]> {
-        let bytes_per_sample = self.bits_allocated as usize / 8;
-        let frame_length = self.rows as usize
-            * self.cols as usize
-            * self.samples_per_pixel as usize
-            * bytes_per_sample;
-        let frame_start = frame_length * frame as usize;
-        let frame_end = frame_start + frame_length;
-        if frame_end > (*self.data).len() {
+        let bytes_per_sample = (self.bits_allocated / 8) as u64;
+        let frame_range = frame_range::<usize>(
+            &[
+                self.rows as u64,
+                self.cols as u64,
+                self.samples_per_pixel as u64,
+                bytes_per_sample,
+            ],
+            frame,
+        )
+        .context(FrameSizeOverflowSnafu {
+            frame_number: frame,
+        })?;
+        if frame_range.end > (*self.data).len() {
             FrameOutOfRangeSnafu {
                 frame_number: frame,
             }
             .fail()?
         }
 
-        Ok(&self.data[frame_start..frame_end])
+        Ok(&self.data[frame_range])
     }
 
     /// Retrieve a copy of a frame's raw pixel data samples
@@ -921,32 +1088,36 @@ impl DecodedPixelData<'_> {
                             (VoiLutOption::Identity, _) => {
                                 Lut::new_rescale(8, false, rescale).context(CreateLutSnafu)?
                             }
-                            (VoiLutOption::Default | VoiLutOption::First, Some(window)) => {
-                                Lut::new_rescale_and_window(
-                                    8,
-                                    signed,
-                                    rescale,
-                                    WindowLevelTransform::new(
-                                        match self.voi_lut_function()? {
-                                            Some(lut) => {
-                                                if lut.len() > 1 {
-                                                    lut[frame as usize]
-                                                } else {
-                                                    lut[0]
-                                                }
+                            (
+                                VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Lut,
+                                Some(window),
+                            ) => Lut::new_rescale_and_window(
+                                8,
+                                signed,
+                                rescale,
+                                WindowLevelTransform::new(
+                                    match self.voi_lut_function()? {
+                                        Some(lut) => {
+                                            if lut.len() > 1 {
+                                                lut[frame as usize]
+                                            } else {
+                                                lut[0]
                                             }
-                                            None => VoiLutFunction::Linear,
-                                        },
-                                        if window.len() > 1 {
-                                            window[frame as usize]
-                                        } else {
-                                            window[0]
-                                        },
-                                    ),
-                                )
-                                .context(CreateLutSnafu)?
-                            }
-                            (VoiLutOption::Default | VoiLutOption::First, None) => {
+                                        }
+                                        None => VoiLutFunction::Linear,
+                                    },
+                                    if window.len() > 1 {
+                                        window[frame as usize]
+                                    } else {
+                                        window[0]
+                                    },
+                                ),
+                            )
+                            .context(CreateLutSnafu)?,
+                            (
+                                VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Lut,
+                                None,
+                            ) => {
                                 tracing::warn!("Could not find window level for object");
                                 Lut::new_rescale_and_normalize(
                                     8,
@@ -1003,12 +1174,19 @@ impl DecodedPixelData<'_> {
                     // convert to image only after shifting values
                     // to an unsigned scale
                     ModalityLutOption::None => {
-                        let frame_length = self.rows as usize
-                            * self.cols as usize
-                            * 2
-                            * self.samples_per_pixel as usize;
-                        let frame_start = frame_length * frame as usize;
-                        let frame_end = frame_start + frame_length;
+                        let frame_range = frame_range::<usize>(
+                            &[
+                                self.rows as u64,
+                                self.cols as u64,
+                                2,
+                                self.samples_per_pixel as u64,
+                            ],
+                            frame,
+                        )
+                        .context(FrameSizeOverflowSnafu {
+                            frame_number: frame,
+                        })?;
+                        let (frame_start, frame_end) = (frame_range.start, frame_range.end);
                         if frame_end > (*self.data).len() {
                             FrameOutOfRangeSnafu {
                                 frame_number: frame,
@@ -1023,7 +1201,7 @@ impl DecodedPixelData<'_> {
                             }
                             // Signed 16-bit representation
                             PixelRepresentation::Signed => {
-                                let mut signed_buffer = vec![0; frame_length / 2];
+                                let mut signed_buffer = vec![0; (frame_end - frame_start) / 2];
                                 NativeEndian::read_i16_into(
                                     &self.data[frame_start..frame_end],
                                     &mut signed_buffer,
@@ -1062,31 +1240,35 @@ impl DecodedPixelData<'_> {
                             (VoiLutOption::Identity, _) => {
                                 Lut::new_rescale(self.bits_stored, signed, rescale)
                             }
-                            (VoiLutOption::Default | VoiLutOption::First, Some(window)) => {
-                                Lut::new_rescale_and_window(
-                                    self.bits_stored,
-                                    signed,
-                                    rescale,
-                                    WindowLevelTransform::new(
-                                        match self.voi_lut_function()? {
-                                            Some(lut) => {
-                                                if lut.len() > 1 {
-                                                    lut[frame as usize]
-                                                } else {
-                                                    lut[0]
-                                                }
+                            (
+                                VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Lut,
+                                Some(window),
+                            ) => Lut::new_rescale_and_window(
+                                self.bits_stored,
+                                signed,
+                                rescale,
+                                WindowLevelTransform::new(
+                                    match self.voi_lut_function()? {
+                                        Some(lut) => {
+                                            if lut.len() > 1 {
+                                                lut[frame as usize]
+                                            } else {
+                                                lut[0]
                                             }
-                                            None => VoiLutFunction::Linear,
-                                        },
-                                        if window.len() > 1 {
-                                            window[frame as usize]
-                                        } else {
-                                            window[0]
-                                        },
-                                    ),
-                                )
-                            }
-                            (VoiLutOption::Default | VoiLutOption::First, None) => {
+                                        }
+                                        None => VoiLutFunction::Linear,
+                                    },
+                                    if window.len() > 1 {
+                                        window[frame as usize]
+                                    } else {
+                                        window[0]
+                                    },
+                                ),
+                            ),
+                            (
+                                VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Lut,
+                                None,
+                            ) => {
                                 tracing::warn!("Could not find window level for object");
 
                                 Lut::new_rescale_and_normalize(
@@ -1341,29 +1523,31 @@ impl DecodedPixelData<'_> {
                             (VoiLutOption::Default | VoiLutOption::Identity, _) => {
                                 Lut::new_rescale(8, signed, rescale)
                             }
-                            (VoiLutOption::First, Some(window)) => Lut::new_rescale_and_window(
-                                8,
-                                signed,
-                                rescale,
-                                WindowLevelTransform::new(
-                                    match self.voi_lut_function()? {
-                                        Some(lut) => {
-                                            if lut.len() > 1 {
-                                                lut[frame as usize]
-                                            } else {
-                                                lut[0]
+                            (VoiLutOption::First | VoiLutOption::Lut, Some(window)) => {
+                                Lut::new_rescale_and_window(
+                                    8,
+                                    signed,
+                                    rescale,
+                                    WindowLevelTransform::new(
+                                        match self.voi_lut_function()? {
+                                            Some(lut) => {
+                                                if lut.len() > 1 {
+                                                    lut[frame as usize]
+                                                } else {
+                                                    lut[0]
+                                                }
                                             }
-                                        }
-                                        None => VoiLutFunction::Linear,
-                                    },
-                                    if window.len() > 1 {
-                                        window[frame as usize]
-                                    } else {
-                                        window[0]
-                                    },
-                                ),
-                            ),
-                            (VoiLutOption::First, None) => {
+                                            None => VoiLutFunction::Linear,
+                                        },
+                                        if window.len() > 1 {
+                                            window[frame as usize]
+                                        } else {
+                                            window[0]
+                                        },
+                                    ),
+                                )
+                            }
+                            (VoiLutOption::First | VoiLutOption::Lut, None) => {
                                 tracing::warn!("Could not find window level for object");
                                 Lut::new_rescale(8, signed, rescale)
                             }
@@ -1443,29 +1627,31 @@ impl DecodedPixelData<'_> {
                             (VoiLutOption::Default | VoiLutOption::Identity, _) => {
                                 Lut::new_rescale(self.bits_stored, signed, rescale)
                             }
-                            (VoiLutOption::First, Some(window)) => Lut::new_rescale_and_window(
-                                self.bits_stored,
-                                signed,
-                                rescale,
-                                WindowLevelTransform::new(
-                                    match self.voi_lut_function()? {
-                                        Some(lut) => {
-                                            if lut.len() > 1 {
-                                                lut[frame as usize]
-                                            } else {
-                                                lut[0]
+                            (VoiLutOption::First | VoiLutOption::Lut, Some(window)) => {
+                                Lut::new_rescale_and_window(
+                                    self.bits_stored,
+                                    signed,
+                                    rescale,
+                                    WindowLevelTransform::new(
+                                        match self.voi_lut_function()? {
+                                            Some(lut) => {
+                                                if lut.len() > 1 {
+                                                    lut[frame as usize]
+                                                } else {
+                                                    lut[0]
+                                                }
                                             }
-                                        }
-                                        None => VoiLutFunction::Linear,
-                                    },
-                                    if window.len() > 1 {
-                                        window[frame as usize]
-                                    } else {
-                                        window[0]
-                                    },
-                                ),
-                            ),
-                            (VoiLutOption::First, None) => {
+                                            None => VoiLutFunction::Linear,
+                                        },
+                                        if window.len() > 1 {
+                                            window[frame as usize]
+                                        } else {
+                                            window[0]
+                                        },
+                                    ),
+                                )
+                            }
+                            (VoiLutOption::First | VoiLutOption::Lut, None) => {
                                 tracing::warn!("Could not find window level for object");
                                 Lut::new_rescale_and_normalize(
                                     self.bits_stored,
@@ -1753,6 +1939,23 @@ impl DecodedPixelData<'_> {
     }
 }
 
+/// Calculate the byte range of the given frame,
+/// where the frame size is the product of the given factors.
+///
+/// Returns `None` if the range cannot be represented in `T`,
+/// which may happen for large frames on 32-bit targets.
+fn frame_range<T>(factors: &[u64], frame: u32) -> Option<Range<T>>
+where
+    T: TryFrom<u64>,
+{
+    let frame_size = factors
+        .iter()
+        .try_fold(1_u64, |acc, &factor| acc.checked_mul(factor))?;
+    let start = frame_size.checked_mul(frame as u64)?;
+    let end = start.checked_add(frame_size)?;
+    Some(T::try_from(start).ok()?..T::try_from(end).ok()?)
+}
+
 fn bytes_to_vec_u16(data: &[u8]) -> Vec<u16> {
     debug_assert!(data.len() % 2 == 0);
     let mut pixel_array: Vec<u16> = vec![0; data.len() / 2];
@@ -1877,17 +2080,30 @@ pub trait PixelDecoder {
         let mut px = self.decode_pixel_data()?;
 
         // calculate frame offset and size
-        let frame_size = ((px.bits_allocated + 7) / 8) as usize
-            * px.samples_per_pixel as usize
-            * px.rows as usize
-            * px.cols as usize;
-        let frame_offset = frame_size * frame as usize;
+        let frame_range = frame_range::<usize>(
+            &[
+                px.bits_allocated.div_ceil(8) as u64,
+                px.samples_per_pixel as u64,
+                px.rows as u64,
+                px.cols as u64,
+            ],
+            frame,
+        )
+        .context(FrameSizeOverflowSnafu {
+            frame_number: frame,
+        })?;
+        if frame_range.end > px.data.len() {
+            FrameOutOfRangeSnafu {
+                frame_number: frame,
+            }
+            .fail()?
+        }
 
         // crop to frame
         match &mut px.data {
-            Cow::Owned(data) => *data = data[frame_offset..frame_offset + frame_size].to_vec(),
+            Cow::Owned(data) => *data = data[frame_range].to_vec(),
             Cow::Borrowed(data) => {
-                *data = &data[frame_offset..frame_offset + frame_size];
+                *data = &data[frame_range];
             }
         }
 
@@ -1929,7 +2145,6 @@ impl ImagingProperties {
         D: Clone + DataDictionary,
     {
         use attribute::*;
-        use std::convert::TryFrom;
 
         let cols = cols(obj).context(GetAttributeSnafu)?;
         let rows = rows(obj).context(GetAttributeSnafu)?;
@@ -2201,13 +2416,26 @@ where
             }
             DicomValue::Primitive(p) => {
                 // Non-encoded, just return the pixel data for a single frame
-                let frame_size = ((bits_allocated + 7) / 8) as usize
-                    * samples_per_pixel as usize
-                    * rows as usize
-                    * cols as usize;
-                let frame_offset = frame_size * frame as usize;
+                let frame_range = frame_range::<usize>(
+                    &[
+                        bits_allocated.div_ceil(8) as u64,
+                        samples_per_pixel as u64,
+                        rows as u64,
+                        cols as u64,
+                    ],
+                    frame,
+                )
+                .context(FrameSizeOverflowSnafu {
+                    frame_number: frame,
+                })?;
                 let data = p.to_bytes();
-                data[frame_offset..frame_offset + frame_size].to_vec()
+                if frame_range.end > data.len() {
+                    FrameOutOfRangeSnafu {
+                        frame_number: frame,
+                    }
+                    .fail()?
+                }
+                data[frame_range].to_vec()
             }
             DicomValue::Sequence(..) => InvalidPixelDataSnafu.fail()?,
         };
@@ -2248,6 +2476,35 @@ mod tests {
         is_send_and_sync::<Error>();
     }
 
+    #[test]
+    fn frame_range_near_overflow() {
+        // 512 x 512 x 3 x 2 bytes per frame
+        let factors = [512, 512, 3, 2];
+        assert_eq!(frame_range::<u32>(&factors, 0), Some(0..1_572_864));
+        assert_eq!(frame_range::<u32>(&factors, 2), Some(3_145_728..4_718_592));
+        // frame #2730 ends right before 4 GiB
+        assert_eq!(
+            frame_range::<u32>(&factors, 2729),
+            Some(4_292_345_856..4_293_918_720)
+        );
+        // frame #2731 ends beyond 4 GiB
+        assert_eq!(frame_range::<u32>(&factors, 2730), None);
+        assert_eq!(
+            frame_range::<u64>(&factors, 2730),
+            Some(4_293_918_720..4_295_491_584)
+        );
+
+        // frame size alone does not fit in 32 bits
+        let factors = [65_535, 65_535, 4, 2];
+        assert_eq!(frame_range::<u32>(&factors, 0), None);
+        assert!(frame_range::<u64>(&factors, 0).is_some());
+
+        // overflows even 64 bits
+        let factors = [65_535, 65_535, 65_535, 65_535, 65_535];
+        assert_eq!(frame_range::<u64>(&factors, 0), None);
+        assert_eq!(frame_range::<u64>(&[u64::MAX / 2, 1], 2), None);
+    }
+
     #[test]
     fn test_to_vec_rgb() {
         let test_file = dicom_test_files::path("pydicom/SC_rgb_16bit.dcm").unwrap();
@@ -0,0 +1,241 @@
+//! Normalization of stored pixel sample values
+//! according to _Bits Allocated_, _Bits Stored_, _High Bit_,
+//! and _Pixel Representation_.
+
+use crate::attribute::PixelRepresentation;
+use crate::frame::DecodedFrame;
+use crate::info::PixelDataInfo;
+use crate::{Result, UnsupportedOtherSnafu};
+use std::borrow::Cow;
+
+/// Unpack 12-bit samples packed two in three bytes
+/// into one 16-bit value per sample.
+///
+/// The first sample takes the first byte as its low 8 bits
+/// and the low nibble of the second byte as its high 4 bits;
+/// the second sample takes the high nibble of the second byte as its low 4 bits
+/// and the third byte as its high 8 bits.
+/// A trailing sample of a single byte and a half is also unpacked.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_pixeldata::normalize::unpack_12bit;
+/// assert_eq!(unpack_12bit(&[0x23, 0x61, 0x45]), vec![0x123, 0x456]);
+/// ```
+pub fn unpack_12bit(bytes: &[u8]) -> Vec<u16> {
+    let count = bytes.len() * 2 / 3;
+    let mut out = Vec::with_capacity(count);
+    for group in bytes.chunks(3) {
+        let b0 = u16::from(group[0]);
+        let b1 = u16::from(group.get(1).copied().unwrap_or(0));
+        out.push(b0 | (b1 & 0x0F) << 8);
+        if let Some(&b2) = group.get(2) {
+            out.push(b1 >> 4 | u16::from(b2) << 4);
+        }
+    }
+    out.truncate(count);
+    out
+}
+
+/// Interpret native pixel data samples as integer values,
+/// keeping only the bits stored
+/// and extending the sign of signed values.
+///
+/// Bits below the low bit of the stored value
+/// (as implied by _High Bit_ and _Bits Stored_)
+/// are shifted out,
+/// and bits above the high bit, such as embedded overlay bits, are cleared.
+/// Samples of 8, 12 (packed), 16, and 32 bits allocated are supported.
+pub(crate) fn stored_values(info: &PixelDataInfo<'_>, bytes: &[u8]) -> Result<Vec<i64>> {
+    let bits_allocated = info.bits_allocated();
+    let raw: Vec<u64> = match bits_allocated {
+        8 => bytes.iter().map(|&b| u64::from(b)).collect(),
+        12 => unpack_12bit(bytes).into_iter().map(u64::from).collect(),
+        16 => bytes
+            .chunks_exact(2)
+            .map(|b| u64::from(u16::from_ne_bytes([b[0], b[1]])))
+            .collect(),
+        32 => bytes
+            .chunks_exact(4)
+            .map(|b| u64::from(u32::from_ne_bytes([b[0], b[1], b[2], b[3]])))
+            .collect(),
+        _ => {
+            return UnsupportedOtherSnafu {
+                name: "BitsAllocated",
+                value: bits_allocated.to_string(),
+            }
+            .fail()?
+        }
+    };
+    let bits_stored = info.bits_stored().clamp(1, bits_allocated);
+    let shift = (info.high_bit() + 1).saturating_sub(bits_stored);
+    let unused_bits = 64 - u32::from(bits_stored);
+    let signed = info.pixel_representation() == PixelRepresentation::Signed;
+
+    Ok(raw
+        .into_iter()
+        .map(|raw| {
+            let value = (raw >> shift) << unused_bits;
+            if signed {
+                (value as i64) >> unused_bits
+            } else {
+                (value >> unused_bits) as i64
+            }
+        })
+        .collect())
+}
+
+/// Normalize the samples of a decoded frame,
+/// as described in [`stored_values`].
+///
+/// The samples keep their size,
+/// except for 12-bit packed samples, which are unpacked to 16 bits.
+/// Signed samples are sign extended to the full sample size,
+/// so that they can be read as `i8`, `i16`, or `i32`.
+pub(crate) fn normalize_frame<'a>(
+    info: &PixelDataInfo<'_>,
+    frame: DecodedFrame<'a>,
+) -> Result<DecodedFrame<'a>> {
+    let values = stored_values(info, &frame.bytes)?;
+    let (bytes, bits_allocated): (Vec<u8>, _) = match info.bits_allocated() {
+        8 => (values.into_iter().map(|v| v as u8).collect(), 8),
+        12 | 16 => (
+            values
+                .into_iter()
+                .flat_map(|v| (v as u16).to_ne_bytes())
+                .collect(),
+            16,
+        ),
+        _ => (
+            values
+                .into_iter()
+                .flat_map(|v| (v as u32).to_ne_bytes())
+                .collect(),
+            32,
+        ),
+    };
+    Ok(DecodedFrame {
+        bytes: Cow::Owned(bytes),
+        bits_allocated,
+        normalized: true,
+        ..frame
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unpack_12bit;
+    use crate::info::tests::dummy_image;
+    use crate::{FrameDecodeOptions, PixelDataAccess};
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+
+    fn set_bits(obj: &mut dicom_object::DefaultDicomObject, bits_stored: u16, high_bit: u16) {
+        obj.put(DataElement::new(
+            tags::BITS_STORED,
+            VR::US,
+            dicom_value!(U16, [bits_stored]),
+        ));
+        obj.put(DataElement::new(
+            tags::HIGH_BIT,
+            VR::US,
+            dicom_value!(U16, [high_bit]),
+        ));
+    }
+
+    fn i16_samples(bytes: &[u8]) -> Vec<i16> {
+        bytes
+            .chunks_exact(2)
+            .map(|b| i16::from_ne_bytes([b[0], b[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn normalize_signed_12_in_16_with_overlay_bits() {
+        // -976, 24, 2047, -2048, with overlay bits in the high nibble
+        let stored: [u16; 4] = [0x0C30 | 0xF000, 0x0018 | 0x1000, 0x07FF | 0x8000, 0x0800];
+        let mut obj = dummy_image(
+            2,
+            2,
+            None,
+            1,
+            16,
+            PrimitiveValue::U16(stored.iter().copied().collect()),
+        );
+        set_bits(&mut obj, 12, 11);
+        obj.put(DataElement::new(
+            tags::PIXEL_REPRESENTATION,
+            VR::US,
+            dicom_value!(U16, [1]),
+        ));
+        let frames = obj.frames().unwrap();
+
+        // not normalized by default
+        let frame = frames.decode_frame(0).unwrap();
+        assert!(!frame.normalized);
+        assert_eq!(i16_samples(&frame.bytes)[0], 0xFC30_u16 as i16);
+
+        let options = FrameDecodeOptions::new().with_normalized_bits(true);
+        let frame = frames.decode_frame_with_options(0, &options).unwrap();
+        assert!(frame.normalized);
+        assert_eq!(frame.bits_allocated, 16);
+        assert_eq!(i16_samples(&frame.bytes), vec![-976, 24, 2047, -2048]);
+    }
+
+    #[test]
+    fn normalize_high_bit_not_at_bits_stored() {
+        // 12 bits stored in the high bits of each sample
+        let stored: [u16; 2] = [0xABC0 | 0x000F, 0x1230];
+        let mut obj = dummy_image(
+            1,
+            2,
+            None,
+            1,
+            16,
+            PrimitiveValue::U16(stored.iter().copied().collect()),
+        );
+        set_bits(&mut obj, 12, 15);
+        let options = FrameDecodeOptions::new().with_normalized_bits(true);
+        let frame = obj
+            .frames()
+            .unwrap()
+            .decode_frame_with_options(0, &options)
+            .unwrap();
+        assert_eq!(i16_samples(&frame.bytes), vec![0x0ABC, 0x0123]);
+    }
+
+    #[test]
+    fn normalize_packed_12bit() {
+        assert_eq!(
+            unpack_12bit(&[0x23, 0x61, 0x45, 0x89, 0xC7, 0xAB]),
+            vec![0x123, 0x456, 0x789, 0xABC]
+        );
+        // odd number of samples
+        assert_eq!(
+            unpack_12bit(&[0x23, 0x61, 0x45, 0xFF, 0x0E]),
+            vec![0x123, 0x456, 0xEFF]
+        );
+
+        // 3x1 pixels, padded to an even length
+        let mut obj = dummy_image(
+            1,
+            3,
+            None,
+            1,
+            12,
+            dicom_value!(U8, [0x23, 0x61, 0x45, 0xFF, 0x0E, 0x00]),
+        );
+        set_bits(&mut obj, 12, 11);
+        let frames = obj.frames().unwrap();
+        assert_eq!(frames.info().frame_length(), 5);
+
+        let options = FrameDecodeOptions::new().with_normalized_bits(true);
+        let frame = frames.decode_frame_with_options(0, &options).unwrap();
+        assert_eq!(frame.bits_allocated, 16);
+        assert_eq!(i16_samples(&frame.bytes), vec![0x123, 0x456, 0xEFF]);
+
+        // rescaling also takes packed samples
+        assert_eq!(frames.to_rescaled_f32(0).unwrap(), vec![291., 1110., 3839.]);
+    }
+}
@@ -0,0 +1,308 @@
+//! Extraction of overlay planes from the repeating groups `60xx`.
+
+use crate::attribute::OverlayAttributes;
+use crate::frame::Frames;
+use crate::{LengthMismatchOverlayDataSnafu, Result, UnsupportedOtherSnafu};
+use std::fmt;
+
+/// The type of content of an overlay plane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverlayType {
+    /// Graphics (`G`)
+    Graphics,
+    /// Region of interest (`R`)
+    Roi,
+    /// Any other value
+    Other(String),
+}
+
+impl From<&str> for OverlayType {
+    fn from(value: &str) -> Self {
+        match value {
+            "G" => OverlayType::Graphics,
+            "R" => OverlayType::Roi,
+            other => OverlayType::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for OverlayType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverlayType::Graphics => f.write_str("G"),
+            OverlayType::Roi => f.write_str("R"),
+            OverlayType::Other(value) => f.write_str(value),
+        }
+    }
+}
+
+/// An overlay plane of a DICOM image,
+/// with its bitmap unpacked to one byte per pixel.
+///
+/// Obtained via [`PixelDataAccess::overlays`](crate::PixelDataAccess::overlays).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overlay {
+    /// the group of the overlay plane, from `0x6000` to `0x601E`
+    pub group: u16,
+    /// the number of rows of the overlay
+    pub rows: u16,
+    /// the number of columns of the overlay
+    pub columns: u16,
+    /// the row and column of the image
+    /// at which the first pixel of the overlay is placed,
+    /// where the first row and column of the image are 1
+    pub origin: [i16; 2],
+    /// the type of overlay content
+    pub overlay_type: OverlayType,
+    /// the user-defined description of the overlay, if any
+    pub description: Option<String>,
+    /// the number of frames of the overlay
+    pub number_of_frames: u32,
+    /// the number of the first image frame to which the overlay applies,
+    /// starting from 1
+    pub image_frame_origin: u16,
+    /// whether the overlay was taken from unused bits of the pixel data
+    /// instead of _Overlay Data_
+    pub embedded: bool,
+    /// the overlay bitmap, frame by frame, in row-major order,
+    /// one byte per pixel,
+    /// with 1 where the overlay is set and 0 elsewhere
+    pub data: Vec<u8>,
+}
+
+impl Overlay {
+    /// Unpack the overlay plane in the given group
+    /// from its _Overlay Data_,
+    /// where the bits of all frames are packed contiguously,
+    /// least significant bit first.
+    pub(crate) fn from_packed(
+        group: u16,
+        attributes: OverlayAttributes,
+        packed: &[u8],
+    ) -> Result<Self> {
+        let expected = attributes.number_of_frames as usize
+            * usize::from(attributes.rows)
+            * usize::from(attributes.columns);
+        if packed.len() * 8 < expected {
+            return LengthMismatchOverlayDataSnafu {
+                group,
+                expected,
+                actual: packed.len() * 8,
+            }
+            .fail()?;
+        }
+        let data = (0..expected)
+            .map(|i| (packed[i / 8] >> (i % 8)) & 1)
+            .collect();
+        Ok(Overlay::new(group, attributes, false, data))
+    }
+
+    /// Extract the overlay plane in the given group
+    /// from the bit at _Overlay Bit Position_ of each pixel sample,
+    /// as done by older files
+    /// in which the overlay is embedded in the pixel data.
+    pub(crate) fn from_embedded(
+        group: u16,
+        attributes: OverlayAttributes,
+        frames: &Frames<'_>,
+    ) -> Result<Self> {
+        let info = frames.info();
+        if attributes.bits_allocated != info.bits_allocated()
+            || !matches!(attributes.bits_allocated, 8 | 16)
+            || attributes.bit_position >= attributes.bits_allocated
+        {
+            return UnsupportedOtherSnafu {
+                name: "embedded overlay bits",
+                value: format!(
+                    "{} allocated at position {}",
+                    attributes.bits_allocated, attributes.bit_position
+                ),
+            }
+            .fail()?;
+        }
+        if info.samples_per_pixel() != 1
+            || u32::from(attributes.rows) != info.rows()
+            || u32::from(attributes.columns) != info.columns()
+        {
+            return UnsupportedOtherSnafu {
+                name: "embedded overlay size",
+                value: format!("{}x{}", attributes.columns, attributes.rows),
+            }
+            .fail()?;
+        }
+
+        let first = u32::from(attributes.image_frame_origin.max(1)) - 1;
+        let position = attributes.bit_position;
+        let mut data = Vec::new();
+        for index in first..first + attributes.number_of_frames {
+            let frame = frames.decode_frame(index)?;
+            if attributes.bits_allocated == 8 {
+                data.extend(frame.bytes.iter().map(|&b| (b >> position) & 1));
+            } else {
+                data.extend(
+                    frame
+                        .bytes
+                        .chunks_exact(2)
+                        .map(|b| ((u16::from_ne_bytes([b[0], b[1]]) >> position) & 1) as u8),
+                );
+            }
+        }
+        Ok(Overlay::new(group, attributes, true, data))
+    }
+
+    fn new(group: u16, attributes: OverlayAttributes, embedded: bool, data: Vec<u8>) -> Self {
+        Overlay {
+            group,
+            rows: attributes.rows,
+            columns: attributes.columns,
+            origin: attributes.origin,
+            overlay_type: OverlayType::from(attributes.overlay_type.as_str()),
+            description: attributes.description,
+            number_of_frames: attributes.number_of_frames,
+            image_frame_origin: attributes.image_frame_origin,
+            embedded,
+            data,
+        }
+    }
+
+    /// Retrieve the bitmap of the overlay frame at the given index,
+    /// starting from 0,
+    /// or `None` if there is no such frame.
+    pub fn frame(&self, index: u32) -> Option<&[u8]> {
+        let length = usize::from(self.rows) * usize::from(self.columns);
+        let start = index as usize * length;
+        self.data.get(start..start + length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OverlayType;
+    use crate::info::tests::dummy_image;
+    use crate::{InnerError, PixelDataAccess};
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, Tag, VR};
+
+    fn put_overlay(
+        obj: &mut dicom_object::DefaultDicomObject,
+        group: u16,
+        rows: u16,
+        columns: u16,
+        data: Option<PrimitiveValue>,
+    ) {
+        obj.put(DataElement::new(
+            Tag(group, 0x0010),
+            VR::US,
+            dicom_value!(U16, [rows]),
+        ));
+        obj.put(DataElement::new(
+            Tag(group, 0x0011),
+            VR::US,
+            dicom_value!(U16, [columns]),
+        ));
+        if let Some(data) = data {
+            obj.put(DataElement::new(Tag(group, 0x3000), VR::OB, data));
+        }
+    }
+
+    #[test]
+    fn unpack_multiframe_overlay() {
+        let mut obj = dummy_image(4, 4, None, 1, 8, PrimitiveValue::U8(vec![0; 16].into()));
+        // 2 frames of 3x3, 18 bits packed contiguously
+        put_overlay(
+            &mut obj,
+            0x6002,
+            3,
+            3,
+            Some(dicom_value!(U8, [0b1000_0001, 0b0111_1110, 0b0000_0011, 0])),
+        );
+        obj.put(DataElement::new(
+            Tag(0x6002, 0x0015),
+            VR::IS,
+            PrimitiveValue::from("2"),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x6002, 0x0040),
+            VR::CS,
+            PrimitiveValue::from("R"),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x6002, 0x0050),
+            VR::SS,
+            dicom_value!(I16, [2, 0]),
+        ));
+
+        let overlays = obj.overlays().unwrap();
+        assert_eq!(overlays.len(), 1);
+        let overlay = &overlays[0];
+        assert_eq!(overlay.group, 0x6002);
+        assert_eq!((overlay.rows, overlay.columns), (3, 3));
+        assert_eq!(overlay.origin, [2, 0]);
+        assert_eq!(overlay.overlay_type, OverlayType::Roi);
+        assert_eq!(overlay.number_of_frames, 2);
+        assert_eq!(overlay.image_frame_origin, 1);
+        assert!(!overlay.embedded);
+        assert_eq!(overlay.frame(0).unwrap(), &[1, 0, 0, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(overlay.frame(1).unwrap(), &[1, 1, 1, 1, 1, 1, 0, 1, 1]);
+        assert_eq!(overlay.frame(2), None);
+
+        // not enough overlay data for 4 frames
+        obj.put(DataElement::new(
+            Tag(0x6002, 0x0015),
+            VR::IS,
+            PrimitiveValue::from("4"),
+        ));
+        let err = obj.overlays().unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::LengthMismatchOverlayData {
+                group: 0x6002,
+                expected: 36,
+                actual: 32,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn extract_embedded_overlay() {
+        // 12 bits stored, overlay in bit 12, garbage in bit 15
+        let samples: [u16; 6] = [0x0FFF, 0x1000, 0x1123, 0x8001, 0x9000, 0x0000];
+        let mut obj = dummy_image(
+            2,
+            3,
+            None,
+            1,
+            16,
+            PrimitiveValue::U16(samples.iter().copied().collect()),
+        );
+        obj.put(DataElement::new(
+            dicom_dictionary_std::tags::BITS_STORED,
+            VR::US,
+            dicom_value!(U16, [12]),
+        ));
+        obj.put(DataElement::new(
+            dicom_dictionary_std::tags::HIGH_BIT,
+            VR::US,
+            dicom_value!(U16, [11]),
+        ));
+        put_overlay(&mut obj, 0x6000, 2, 3, None);
+        obj.put(DataElement::new(
+            Tag(0x6000, 0x0100),
+            VR::US,
+            dicom_value!(U16, [16]),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x6000, 0x0102),
+            VR::US,
+            dicom_value!(U16, [12]),
+        ));
+
+        let overlays = obj.overlays().unwrap();
+        assert_eq!(overlays.len(), 1);
+        let overlay = &overlays[0];
+        assert!(overlay.embedded);
+        assert_eq!(overlay.overlay_type, OverlayType::Graphics);
+        assert_eq!(overlay.origin, [1, 1]);
+        assert_eq!(overlay.data, vec![0, 1, 1, 0, 1, 0]);
+    }
+}
@@ -0,0 +1,275 @@
+//! Expansion of `PALETTE COLOR` pixel data into RGB
+//! through the palette color lookup tables.
+
+use crate::attribute::{PhotometricInterpretation, PixelRepresentation, PlanarConfiguration};
+use crate::explicit_lut::ExplicitLut;
+use crate::frame::DecodedFrame;
+use crate::info::PixelDataInfo;
+use crate::normalize::stored_values;
+use crate::{LengthMismatchPaletteLutSnafu, Result, UnsupportedOtherSnafu};
+use std::borrow::Cow;
+
+/// The red, green, and blue lookup tables of a `PALETTE COLOR` image,
+/// mapping each stored pixel value to a color.
+///
+/// Obtained via [`PixelDataInfo::palette_color_lut`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteColorLut {
+    /// the red, green, and blue tables, in this order
+    channels: [ExplicitLut; 3],
+}
+
+impl PaletteColorLut {
+    /// Build the palette color lookup tables
+    /// from the raw words of the three _Palette Color Lookup Table Descriptors_
+    /// and the three _Palette Color Lookup Table Data_.
+    ///
+    /// The first stored pixel value mapped
+    /// is interpreted according to the pixel representation,
+    /// regardless of whether the descriptor was encoded as US or SS.
+    /// 8-bit tables can be either packed two entries per word,
+    /// or one entry per word.
+    pub(crate) fn new(
+        descriptors: [[u16; 3]; 3],
+        data: [Vec<u16>; 3],
+        pixel_representation: PixelRepresentation,
+    ) -> Result<Self> {
+        let [red, green, blue] = data;
+        Ok(PaletteColorLut {
+            channels: [
+                channel_lut(descriptors[0], red, pixel_representation)?,
+                channel_lut(descriptors[1], green, pixel_representation)?,
+                channel_lut(descriptors[2], blue, pixel_representation)?,
+            ],
+        })
+    }
+
+    /// The number of bits of each output sample:
+    /// 8 if all three tables have 8-bit entries, 16 otherwise.
+    pub fn bits(&self) -> u16 {
+        if self.channels.iter().all(|c| c.bits() == 8) {
+            8
+        } else {
+            16
+        }
+    }
+
+    /// Map a stored pixel value to its red, green, and blue samples,
+    /// of [`bits`](Self::bits) bits each.
+    ///
+    /// Values before the first mapped value
+    /// map to the first entry of each table,
+    /// and values past the end of a table map to its last entry.
+    pub fn get(&self, value: i64) -> [u16; 3] {
+        let bits = self.bits();
+        let [r, g, b] = &self.channels;
+        [
+            channel_get(r, value, bits),
+            channel_get(g, value, bits),
+            channel_get(b, value, bits),
+        ]
+    }
+
+    /// Expand a decoded frame of stored pixel values
+    /// into interleaved RGB samples.
+    pub(crate) fn expand_frame<'a>(
+        &self,
+        info: &PixelDataInfo<'_>,
+        frame: DecodedFrame<'a>,
+    ) -> Result<DecodedFrame<'a>> {
+        let values = stored_values(info, &frame.bytes)?;
+        let bytes = if self.bits() == 8 {
+            values
+                .into_iter()
+                .flat_map(|value| self.get(value).map(|sample| sample as u8))
+                .collect()
+        } else {
+            values
+                .into_iter()
+                .flat_map(|value| self.get(value))
+                .flat_map(u16::to_ne_bytes)
+                .collect()
+        };
+        Ok(DecodedFrame {
+            bytes: Cow::Owned(bytes),
+            converted_from: Some(PhotometricInterpretation::PaletteColor),
+            photometric_interpretation: PhotometricInterpretation::Rgb,
+            planar_configuration: PlanarConfiguration::Standard,
+            samples_per_pixel: 3,
+            bits_allocated: self.bits(),
+            ..frame
+        })
+    }
+}
+
+/// Build the lookup table of a single color channel.
+fn channel_lut(
+    descriptor: [u16; 3],
+    data: Vec<u16>,
+    pixel_representation: PixelRepresentation,
+) -> Result<ExplicitLut> {
+    let (lut, expected) = ExplicitLut::unpack(descriptor, data, pixel_representation)?;
+    if !matches!(lut.bits(), 8 | 16) {
+        return UnsupportedOtherSnafu {
+            name: "palette color LUT entry size",
+            value: lut.bits().to_string(),
+        }
+        .fail()?;
+    }
+    if lut.entries().len() != expected {
+        return LengthMismatchPaletteLutSnafu {
+            expected,
+            actual: lut.entries().len(),
+        }
+        .fail()?;
+    }
+    Ok(lut)
+}
+
+/// Look up the entry of a channel for the given stored value,
+/// scaled to the given number of output bits.
+fn channel_get(lut: &ExplicitLut, value: i64, bits: u16) -> u16 {
+    let entry = lut.apply(value);
+    if bits > lut.bits() {
+        // 8-bit entry to 16-bit output
+        entry * 0x0101
+    } else {
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PaletteColorLut;
+    use crate::info::tests::dummy_image;
+    use crate::{InnerError, PhotometricInterpretation, PixelDataAccess, PixelRepresentation};
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+
+    /// A paletted image of 2x3 pixels
+    /// with 4-entry 16-bit tables starting at stored value 10.
+    fn palette_image() -> dicom_object::DefaultDicomObject {
+        let mut obj = dummy_image(2, 3, None, 1, 8, dicom_value!(U8, [9, 10, 11, 12, 13, 200]));
+        obj.put(DataElement::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            VR::CS,
+            PrimitiveValue::from("PALETTE COLOR"),
+        ));
+        for (descriptor, data, entries) in [
+            (
+                tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+                tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+                [0x0000, 0x1000, 0x2000, 0xFFFF],
+            ),
+            (
+                tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+                tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+                [0x0100, 0x0200, 0x0300, 0x0400],
+            ),
+            (
+                tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+                tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+                [0xFFFF, 0x8000, 0x0001, 0x0000],
+            ),
+        ] {
+            obj.put(DataElement::new(
+                descriptor,
+                VR::US,
+                dicom_value!(U16, [4, 10, 16]),
+            ));
+            obj.put(DataElement::new(
+                data,
+                VR::OW,
+                PrimitiveValue::U16(entries.iter().copied().collect()),
+            ));
+        }
+        obj
+    }
+
+    #[test]
+    fn expand_palette_16bit() {
+        let obj = palette_image();
+        let frames = obj.frames().unwrap();
+        let lut = frames.info().palette_color_lut().unwrap();
+        assert_eq!(lut.bits(), 16);
+
+        let frame = frames.decode_frame(0).unwrap();
+        assert_eq!(
+            frame.photometric_interpretation,
+            PhotometricInterpretation::Rgb
+        );
+        assert_eq!(frame.samples_per_pixel, 3);
+        assert_eq!(frame.bits_allocated, 16);
+
+        let samples: Vec<u16> = frame
+            .bytes
+            .chunks_exact(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        #[rustfmt::skip]
+        assert_eq!(
+            samples,
+            vec![
+                // 9 is before the first mapped value, clamped to the first entry
+                0x0000, 0x0100, 0xFFFF,
+                0x0000, 0x0100, 0xFFFF,
+                0x1000, 0x0200, 0x8000,
+                0x2000, 0x0300, 0x0001,
+                0xFFFF, 0x0400, 0x0000,
+                // 13 and 200 are past the end, clamped to the last entry
+                0xFFFF, 0x0400, 0x0000,
+            ]
+        );
+    }
+
+    #[test]
+    fn palette_descriptor_quirks() {
+        // signed first mapped value encoded as US,
+        // 8-bit entries packed two per word
+        let lut = PaletteColorLut::new(
+            [[3, (-1_i16) as u16, 8]; 3],
+            [
+                vec![0x2010, 0x0030],
+                vec![0x0201, 0x0003],
+                vec![0xFFFE, 0x00FD],
+            ],
+            PixelRepresentation::Signed,
+        )
+        .unwrap();
+        assert_eq!(lut.bits(), 8);
+        assert_eq!(lut.get(-2), [0x10, 0x01, 0xFE]);
+        assert_eq!(lut.get(0), [0x20, 0x02, 0xFF]);
+        assert_eq!(lut.get(1), [0x30, 0x03, 0xFD]);
+
+        // 65536 entries, 8-bit entries in the high byte of each word,
+        // and a 16-bit table turning the output into 16 bits
+        let lut = PaletteColorLut::new(
+            [[0, 0, 8], [0, 0, 8], [2, 0, 16]],
+            [
+                (0..=0xFFFF_u32).map(|i| ((i & 0xFF) << 8) as u16).collect(),
+                vec![0x0100; 0x1_0000],
+                vec![0x1234, 0x5678],
+            ],
+            PixelRepresentation::Unsigned,
+        )
+        .unwrap();
+        assert_eq!(lut.bits(), 16);
+        assert_eq!(lut.get(0x0102), [0x0202, 0x0101, 0x5678]);
+
+        // wrong number of entries
+        let err = PaletteColorLut::new(
+            [[4, 0, 16]; 3],
+            [vec![0; 3], vec![0; 4], vec![0; 4]],
+            PixelRepresentation::Unsigned,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::LengthMismatchPaletteLut {
+                expected: 4,
+                actual: 3,
+                ..
+            }
+        ));
+    }
+}
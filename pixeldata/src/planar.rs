@@ -0,0 +1,377 @@
+//! Conversion of color samples between
+//! the interleaved (`PlanarConfiguration = 0`)
+//! and planar (`PlanarConfiguration = 1`) layouts,
+//! and writing of native pixel data in either layout.
+//!
+//! Both layouts are defined per frame:
+//! each frame of planar pixel data holds
+//! all of its samples of the first color plane,
+//! then all of its samples of the second one, and so on.
+
+use crate::attribute::PlanarConfiguration;
+use crate::frame::DecodedFrame;
+use crate::{
+    FrameLayoutMismatchSnafu, Result, UnsupportedOtherSnafu, UnsupportedSamplesPerPixelSnafu,
+};
+use dicom_core::{DataDictionary, DataElement, PrimitiveValue, VR};
+use dicom_dictionary_std::tags;
+use dicom_encoding::TransferSyntaxIndex;
+use dicom_object::{FileDicomObject, InMemDicomObject};
+use dicom_transfer_syntax_registry::{entries::EXPLICIT_VR_LITTLE_ENDIAN, TransferSyntaxRegistry};
+use std::borrow::Cow;
+
+/// Convert the samples of a single frame in the planar layout
+/// (all samples of each color plane together)
+/// into the interleaved layout (all samples of each pixel together).
+///
+/// A trailing incomplete pixel is ignored.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_pixeldata::planar::interleave;
+/// let planes = [1, 2, 10, 20, 100, 200];
+/// assert_eq!(interleave(&planes, 3), vec![1, 10, 100, 2, 20, 200]);
+/// ```
+pub fn interleave<T: Copy>(planes: &[T], samples_per_pixel: usize) -> Vec<T> {
+    let pixels = planes.len() / samples_per_pixel;
+    (0..pixels)
+        .flat_map(|i| (0..samples_per_pixel).map(move |s| planes[s * pixels + i]))
+        .collect()
+}
+
+/// Convert the samples of a single frame in the interleaved layout
+/// (all samples of each pixel together)
+/// into the planar layout (all samples of each color plane together).
+///
+/// A trailing incomplete pixel is ignored.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_pixeldata::planar::deinterleave;
+/// let pixels = [1, 10, 100, 2, 20, 200];
+/// assert_eq!(deinterleave(&pixels, 3), vec![1, 2, 10, 20, 100, 200]);
+/// ```
+pub fn deinterleave<T: Copy>(pixels: &[T], samples_per_pixel: usize) -> Vec<T> {
+    let pixels = &pixels[..pixels.len() - pixels.len() % samples_per_pixel];
+    (0..samples_per_pixel)
+        .flat_map(|s| pixels.iter().skip(s).step_by(samples_per_pixel).copied())
+        .collect()
+}
+
+/// Convert a decoded frame into the given planar configuration,
+/// leaving frames with a single sample per pixel untouched.
+pub(crate) fn convert_frame(
+    frame: DecodedFrame<'_>,
+    planar_configuration: PlanarConfiguration,
+) -> Result<DecodedFrame<'_>> {
+    if frame.samples_per_pixel == 1 || frame.planar_configuration == planar_configuration {
+        return Ok(frame);
+    }
+    let samples_per_pixel = usize::from(frame.samples_per_pixel);
+    let bytes = match frame.bits_allocated {
+        8 => convert(&frame.bytes, samples_per_pixel, planar_configuration),
+        16 => {
+            let samples: Vec<[u8; 2]> = frame.bytes.chunks_exact(2).map(|b| [b[0], b[1]]).collect();
+            convert(&samples, samples_per_pixel, planar_configuration).concat()
+        }
+        32 => {
+            let samples: Vec<[u8; 4]> = frame
+                .bytes
+                .chunks_exact(4)
+                .map(|b| [b[0], b[1], b[2], b[3]])
+                .collect();
+            convert(&samples, samples_per_pixel, planar_configuration).concat()
+        }
+        bits_allocated => {
+            return UnsupportedOtherSnafu {
+                name: "BitsAllocated",
+                value: bits_allocated.to_string(),
+            }
+            .fail()?
+        }
+    };
+    Ok(DecodedFrame {
+        bytes: Cow::Owned(bytes),
+        planar_configuration,
+        ..frame
+    })
+}
+
+fn convert<T: Copy>(
+    samples: &[T],
+    samples_per_pixel: usize,
+    planar_configuration: PlanarConfiguration,
+) -> Vec<T> {
+    match planar_configuration {
+        PlanarConfiguration::Standard => interleave(samples, samples_per_pixel),
+        PlanarConfiguration::PixelFirst => deinterleave(samples, samples_per_pixel),
+    }
+}
+
+/// Replace the pixel data of the given object
+/// with the given decoded frames as native pixel data,
+/// laid out in the given planar configuration.
+///
+/// _Pixel Data_, _Number of Frames_, _Samples per Pixel_,
+/// _Photometric Interpretation_, _Bits Allocated_,
+/// and _Planar Configuration_ are set according to the frames,
+/// the latter only for images with more than one sample per pixel.
+/// If the object's transfer syntax is encapsulated,
+/// it is changed to _Explicit VR Little Endian_.
+///
+/// All frames must have the same
+/// length, samples per pixel, bits allocated, and photometric interpretation.
+pub fn put_native_frames<D>(
+    obj: &mut FileDicomObject<InMemDicomObject<D>>,
+    frames: &[DecodedFrame<'_>],
+    planar_configuration: PlanarConfiguration,
+) -> Result<()>
+where
+    D: DataDictionary + Clone,
+{
+    let first = match frames.first() {
+        Some(first) => first,
+        None => {
+            return UnsupportedOtherSnafu {
+                name: "number of frames",
+                value: "0",
+            }
+            .fail()?
+        }
+    };
+    if first.samples_per_pixel == 0 {
+        return UnsupportedSamplesPerPixelSnafu { spp: 0_u16 }.fail()?;
+    }
+    let mut data = Vec::with_capacity(first.bytes.len() * frames.len());
+    for (i, frame) in frames.iter().enumerate() {
+        if frame.bytes.len() != first.bytes.len()
+            || frame.samples_per_pixel != first.samples_per_pixel
+            || frame.bits_allocated != first.bits_allocated
+            || frame.photometric_interpretation != first.photometric_interpretation
+        {
+            return FrameLayoutMismatchSnafu {
+                frame_number: i as u32,
+            }
+            .fail()?;
+        }
+        let frame = convert_frame(frame.clone(), planar_configuration)?;
+        data.extend_from_slice(&frame.bytes);
+    }
+
+    let pixel_data = if first.bits_allocated == 8 {
+        DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(data))
+    } else {
+        let words: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        DataElement::new(tags::PIXEL_DATA, VR::OW, PrimitiveValue::U16(words.into()))
+    };
+    obj.put(pixel_data);
+    obj.put(DataElement::new(
+        tags::NUMBER_OF_FRAMES,
+        VR::IS,
+        frames.len().to_string(),
+    ));
+    obj.put(DataElement::new(
+        tags::SAMPLES_PER_PIXEL,
+        VR::US,
+        PrimitiveValue::from(first.samples_per_pixel),
+    ));
+    obj.put(DataElement::new(
+        tags::PHOTOMETRIC_INTERPRETATION,
+        VR::CS,
+        first.photometric_interpretation.as_str(),
+    ));
+    obj.put(DataElement::new(
+        tags::BITS_ALLOCATED,
+        VR::US,
+        PrimitiveValue::from(first.bits_allocated),
+    ));
+    if first.samples_per_pixel > 1 {
+        obj.put(DataElement::new(
+            tags::PLANAR_CONFIGURATION,
+            VR::US,
+            PrimitiveValue::from(planar_configuration as u16),
+        ));
+    } else {
+        obj.remove_element(tags::PLANAR_CONFIGURATION);
+    }
+
+    let is_native = TransferSyntaxRegistry
+        .get(obj.meta().transfer_syntax())
+        .map(|ts| ts.is_codec_free())
+        .unwrap_or(false);
+    if !is_native {
+        obj.meta_mut()
+            .set_transfer_syntax(&EXPLICIT_VR_LITTLE_ENDIAN);
+        obj.remove_element(tags::ENCAPSULATED_PIXEL_DATA_VALUE_TOTAL_LENGTH);
+        obj.remove_element(tags::EXTENDED_OFFSET_TABLE);
+        obj.remove_element(tags::EXTENDED_OFFSET_TABLE_LENGTHS);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::tests::dummy_image;
+    use crate::{FrameDecodeOptions, PixelDataAccess};
+    use dicom_core::dicom_value;
+
+    /// 3 frames of 2x2 RGB pixels, in the planar layout,
+    /// where the decimal digits of each value `fsp`
+    /// tell the frame, sample, and pixel.
+    #[rustfmt::skip]
+    const PLANAR: [u8; 36] = [
+        // frame 0
+        0, 1, 2, 3, 10, 11, 12, 13, 20, 21, 22, 23,
+        // frame 1
+        100, 101, 102, 103, 110, 111, 112, 113, 120, 121, 122, 123,
+        // frame 2
+        200, 201, 202, 203, 210, 211, 212, 213, 220, 221, 222, 223,
+    ];
+
+    /// The same frames in the interleaved layout.
+    #[rustfmt::skip]
+    const INTERLEAVED: [u8; 36] = [
+        // frame 0
+        0, 10, 20, 1, 11, 21, 2, 12, 22, 3, 13, 23,
+        // frame 1
+        100, 110, 120, 101, 111, 121, 102, 112, 122, 103, 113, 123,
+        // frame 2
+        200, 210, 220, 201, 211, 221, 202, 212, 222, 203, 213, 223,
+    ];
+
+    fn planar_image() -> dicom_object::DefaultDicomObject {
+        let mut obj = dummy_image(
+            2,
+            2,
+            Some(3),
+            3,
+            8,
+            PrimitiveValue::U8(PLANAR.iter().copied().collect()),
+        );
+        obj.put(DataElement::new(
+            tags::PLANAR_CONFIGURATION,
+            VR::US,
+            dicom_value!(U16, [1]),
+        ));
+        obj
+    }
+
+    #[test]
+    fn interleave_planar_frames() {
+        let obj = planar_image();
+        let frames = obj.frames().unwrap();
+        assert_eq!(frames.info().frame_length(), 12);
+
+        let options =
+            FrameDecodeOptions::new().with_planar_configuration(PlanarConfiguration::Standard);
+        let mut interleaved = Vec::new();
+        for i in 0..3 {
+            let frame = frames.decode_frame_with_options(i, &options).unwrap();
+            assert_eq!(frame.planar_configuration, PlanarConfiguration::Standard);
+            interleaved.extend_from_slice(&frame.bytes);
+        }
+        assert_eq!(interleaved, INTERLEAVED);
+
+        // frames are returned as stored by default
+        let frame = frames.decode_frame(2).unwrap();
+        assert_eq!(frame.planar_configuration, PlanarConfiguration::PixelFirst);
+        assert_eq!(&frame.bytes[..], &PLANAR[24..]);
+    }
+
+    #[test]
+    fn write_frames_round_trip() {
+        let obj = planar_image();
+        let options =
+            FrameDecodeOptions::new().with_planar_configuration(PlanarConfiguration::Standard);
+        let frames = obj.frames().unwrap();
+        let decoded: Vec<_> = (0..3)
+            .map(|i| frames.decode_frame_with_options(i, &options).unwrap())
+            .collect();
+
+        // write the interleaved frames back as planar
+        let mut out = dummy_image(2, 2, None, 3, 8, PrimitiveValue::Empty);
+        put_native_frames(&mut out, &decoded, PlanarConfiguration::PixelFirst).unwrap();
+        assert_eq!(
+            out.element(tags::PLANAR_CONFIGURATION)
+                .unwrap()
+                .to_int::<u16>()
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            out.element(tags::NUMBER_OF_FRAMES)
+                .unwrap()
+                .to_int::<u32>()
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            &out.element(tags::PIXEL_DATA).unwrap().to_bytes().unwrap()[..],
+            PLANAR
+        );
+
+        // and as interleaved
+        put_native_frames(&mut out, &decoded, PlanarConfiguration::Standard).unwrap();
+        assert_eq!(
+            out.element(tags::PLANAR_CONFIGURATION)
+                .unwrap()
+                .to_int::<u16>()
+                .unwrap(),
+            0
+        );
+        let frames = out.frames().unwrap();
+        for (i, frame) in frames.decoded().enumerate() {
+            assert_eq!(
+                &frame.unwrap().bytes[..],
+                &INTERLEAVED[i * 12..(i + 1) * 12]
+            );
+        }
+
+        // frames of different layouts are rejected
+        let mut mismatched = decoded.clone();
+        mismatched[1].samples_per_pixel = 1;
+        let err =
+            put_native_frames(&mut out, &mismatched, PlanarConfiguration::Standard).unwrap_err();
+        assert!(matches!(
+            err.0,
+            crate::InnerError::FrameLayoutMismatch {
+                frame_number: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn round_trip_16bit_samples() {
+        let pixels: Vec<u16> = (0..3 * 5).map(|i| i * 1000 + 7).collect();
+        let planes = deinterleave(&pixels, 3);
+        assert_eq!(&planes[..5], [7, 3007, 6007, 9007, 12007]);
+        assert_eq!(interleave(&planes, 3), pixels);
+
+        let bytes: Vec<u8> = pixels.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        let frame = DecodedFrame {
+            index: 0,
+            rows: 1,
+            columns: 5,
+            bytes: Cow::Borrowed(&bytes),
+            photometric_interpretation: crate::PhotometricInterpretation::Rgb,
+            planar_configuration: PlanarConfiguration::Standard,
+            samples_per_pixel: 3,
+            bits_allocated: 16,
+            converted_from: None,
+            normalized: false,
+            near_lossless: None,
+        };
+        let planar = convert_frame(frame, PlanarConfiguration::PixelFirst).unwrap();
+        let expected: Vec<u8> = planes.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        assert_eq!(&planar.bytes[..], &expected[..]);
+        let back = convert_frame(planar, PlanarConfiguration::Standard).unwrap();
+        assert_eq!(&back.bytes[..], &bytes[..]);
+    }
+}
@@ -0,0 +1,239 @@
+//! Conversion of stored pixel sample values into real world values
+//! through the modality rescale function or the modality lookup table.
+
+use crate::attribute::PhotometricInterpretation;
+use crate::frame::Frames;
+use crate::normalize::stored_values;
+use crate::{
+    BufferLengthMismatchSnafu, Result, UnsupportedPhotometricInterpretationSnafu,
+    UnsupportedSamplesPerPixelSnafu,
+};
+
+impl Frames<'_> {
+    /// Convert the frame at the given index into real world values,
+    /// such as Hounsfield units in CT,
+    /// decoding it first if necessary.
+    ///
+    /// See [`rescale_frame_into`](Self::rescale_frame_into).
+    pub fn to_rescaled_f32(&self, index: u32) -> Result<Vec<f32>> {
+        let info = self.info();
+        let mut out = vec![0.; info.rows() as usize * info.columns() as usize];
+        self.rescale_frame_into(index, &mut out)?;
+        Ok(out)
+    }
+
+    /// Convert the frame at the given index into real world values,
+    /// decoding it first if necessary,
+    /// and write them into the given buffer,
+    /// which must have one element per pixel.
+    ///
+    /// The stored values are interpreted according to
+    /// _Bits Stored_, _High Bit_, and _Pixel Representation_,
+    /// and then the rescale function of the frame is applied
+    /// (see [`PixelDataInfo::rescale`]).
+    /// If the object has a _Modality LUT Sequence_,
+    /// its lookup table is applied instead
+    /// (see [`PixelDataInfo::modality_lut`]).
+    /// Only images with a single sample per pixel are supported,
+    /// excluding `PALETTE COLOR` images.
+    pub fn rescale_frame_into(&self, index: u32, out: &mut [f32]) -> Result<()> {
+        let info = self.info();
+        if *info.photometric_interpretation() == PhotometricInterpretation::PaletteColor {
+            return UnsupportedPhotometricInterpretationSnafu {
+                pi: info.photometric_interpretation().clone(),
+            }
+            .fail()?;
+        }
+        if info.samples_per_pixel() != 1 {
+            return UnsupportedSamplesPerPixelSnafu {
+                spp: info.samples_per_pixel(),
+            }
+            .fail()?;
+        }
+        let expected = info.rows() as usize * info.columns() as usize;
+        if out.len() != expected {
+            return BufferLengthMismatchSnafu {
+                expected,
+                actual: out.len(),
+            }
+            .fail()?;
+        }
+
+        let frame = self.decode_frame(index)?;
+        let values = stored_values(info, &frame.bytes)?;
+        if let Some(lut) = info.modality_lut() {
+            for (out, value) in out.iter_mut().zip(values) {
+                *out = f32::from(lut.apply(value));
+            }
+            return Ok(());
+        }
+        let rescale = info.rescale(index)?;
+        for (out, value) in out.iter_mut().zip(values) {
+            *out = rescale.apply(value as f64) as f32;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::info::tests::dummy_image;
+    use crate::{InnerError, PixelDataAccess};
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+    use dicom_object::InMemDicomObject;
+
+    /// A signed 12-bit CT image of 2x2 pixels:
+    /// air, water, bone,
+    /// and air again with garbage in the unused high bits.
+    fn ct_image() -> dicom_object::DefaultDicomObject {
+        let stored: [u16; 4] = [
+            (-976_i16 as u16) & 0x0FFF,
+            24,
+            1024,
+            (-976_i16 as u16) & 0x0FFF | 0x5000,
+        ];
+        let mut obj = dummy_image(
+            2,
+            2,
+            None,
+            1,
+            16,
+            PrimitiveValue::U16(stored.iter().copied().collect()),
+        );
+        obj.put(DataElement::new(
+            tags::BITS_STORED,
+            VR::US,
+            dicom_value!(U16, [12]),
+        ));
+        obj.put(DataElement::new(
+            tags::HIGH_BIT,
+            VR::US,
+            dicom_value!(U16, [11]),
+        ));
+        obj.put(DataElement::new(
+            tags::PIXEL_REPRESENTATION,
+            VR::US,
+            dicom_value!(U16, [1]),
+        ));
+        obj.put(DataElement::new(
+            tags::RESCALE_SLOPE,
+            VR::DS,
+            PrimitiveValue::from("1"),
+        ));
+        obj.put(DataElement::new(
+            tags::RESCALE_INTERCEPT,
+            VR::DS,
+            PrimitiveValue::from("-24"),
+        ));
+        obj
+    }
+
+    #[test]
+    fn rescale_signed_ct() {
+        let obj = ct_image();
+        let frames = obj.frames().unwrap();
+        let hu = frames.to_rescaled_f32(0).unwrap();
+        assert_eq!(hu, vec![-1000., 0., 1000., -1000.]);
+
+        // a caller buffer of the wrong size is rejected
+        let mut out = [0.; 3];
+        let err = frames.rescale_frame_into(0, &mut out).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::BufferLengthMismatch {
+                expected: 4,
+                actual: 3,
+                ..
+            }
+        ));
+
+        // missing rescale attributes fall back to the identity
+        let mut obj = ct_image();
+        obj.remove_element(tags::RESCALE_SLOPE);
+        obj.remove_element(tags::RESCALE_INTERCEPT);
+        let values = obj.frames().unwrap().to_rescaled_f32(0).unwrap();
+        assert_eq!(values, vec![-976., 24., 1024., -976.]);
+    }
+
+    #[test]
+    fn rescale_from_functional_groups() {
+        let mut obj = dummy_image(1, 2, Some(2), 1, 16, dicom_value!(U16, [0, 10, 20, 30]));
+        let frame_group = |slope: &str, intercept: &str| {
+            InMemDicomObject::from_element_iter([DataElement::new(
+                tags::PIXEL_VALUE_TRANSFORMATION_SEQUENCE,
+                VR::SQ,
+                dicom_core::value::DataSetSequence::from(vec![
+                    InMemDicomObject::from_element_iter([
+                        DataElement::new(tags::RESCALE_SLOPE, VR::DS, PrimitiveValue::from(slope)),
+                        DataElement::new(
+                            tags::RESCALE_INTERCEPT,
+                            VR::DS,
+                            PrimitiveValue::from(intercept),
+                        ),
+                    ]),
+                ]),
+            )])
+        };
+        obj.put(DataElement::new(
+            tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+            VR::SQ,
+            dicom_core::value::DataSetSequence::from(vec![
+                frame_group("1", "-1024"),
+                frame_group("2", "-1024"),
+            ]),
+        ));
+
+        let frames = obj.frames().unwrap();
+        assert_eq!(frames.to_rescaled_f32(0).unwrap(), vec![-1024., -1014.]);
+        assert_eq!(frames.to_rescaled_f32(1).unwrap(), vec![-984., -964.]);
+    }
+
+    #[test]
+    fn modality_lut_takes_precedence() {
+        let mut obj = ct_image();
+        obj.put(DataElement::new(
+            tags::MODALITY_LUT_SEQUENCE,
+            VR::SQ,
+            dicom_core::value::DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::LUT_DESCRIPTOR,
+                    VR::SS,
+                    dicom_value!(I16, [3, -976, 16]),
+                ),
+                DataElement::new(tags::LUT_EXPLANATION, VR::LO, PrimitiveValue::from("HU")),
+                DataElement::new(tags::LUT_DATA, VR::OW, dicom_value!(U16, [10, 20, 30])),
+                DataElement::new(tags::MODALITY_LUT_TYPE, VR::LO, PrimitiveValue::from("HU")),
+            ])]),
+        ));
+        let frames = obj.frames().unwrap();
+        let info = frames.info();
+        assert!(info.has_modality_lut());
+        let lut = info.modality_lut().unwrap();
+        assert_eq!(lut.first_mapped(), -976);
+        assert_eq!(lut.explanation(), Some("HU"));
+
+        // the rescale function is ignored
+        let values = frames.to_rescaled_f32(0).unwrap();
+        assert_eq!(values, vec![10., 30., 30., 10.]);
+
+        // an inconsistent table is reported
+        obj.put(DataElement::new(
+            tags::MODALITY_LUT_SEQUENCE,
+            VR::SQ,
+            dicom_core::value::DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                DataElement::new(tags::LUT_DESCRIPTOR, VR::US, dicom_value!(U16, [0, 0, 16])),
+                DataElement::new(tags::LUT_DATA, VR::OW, dicom_value!(U16, [10, 20, 30])),
+            ])]),
+        ));
+        let err = obj.frames().unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::LengthMismatchLut {
+                expected: 0x1_0000,
+                actual: 3,
+                ..
+            }
+        ));
+    }
+}
@@ -10,13 +10,13 @@ use dicom_core::{
     ops::ApplyOp, value::PixelFragmentSequence, DataDictionary, DataElement, Length,
     PrimitiveValue, VR,
 };
-use dicom_dictionary_std::tags;
+use dicom_dictionary_std::{tags, uids};
 use dicom_encoding::{adapters::EncodeOptions, Codec, TransferSyntax, TransferSyntaxIndex};
 use dicom_object::{FileDicomObject, InMemDicomObject};
 use dicom_transfer_syntax_registry::{entries::EXPLICIT_VR_LITTLE_ENDIAN, TransferSyntaxRegistry};
 use snafu::{OptionExt, ResultExt, Snafu};
 
-use crate::PixelDecoder;
+use crate::{PixelDataAccess, PixelDecoder};
 
 /// An error occurred during the object transcoding process.
 #[derive(Debug, Snafu)]
@@ -43,6 +43,9 @@ pub(crate) enum InnerError {
 
     /// Unsupported bits per sample ({bits_allocated})
     UnsupportedBitsAllocated { bits_allocated: u16 },
+
+    /// Decoded frame #{frame_number} does not match the layout of the first frame
+    FrameLayoutMismatch { frame_number: u32 },
 }
 
 /// Alias for the result of transcoding a DICOM object.
@@ -101,6 +104,16 @@ pub trait Transcode {
     fn transcode(&mut self, ts: &TransferSyntax) -> Result<()> {
         self.transcode_with_options(ts, EncodeOptions::default())
     }
+
+    /// Decode the receiving object's pixel data, if encapsulated,
+    /// and convert it to _Explicit VR Little Endian_.
+    ///
+    /// By default, this is equivalent to transcoding
+    /// to _Explicit VR Little Endian_.
+    /// Implementations may provide a more efficient way to do this.
+    fn decompress(&mut self) -> Result<()> {
+        self.transcode(&EXPLICIT_VR_LITTLE_ENDIAN.erased())
+    }
 }
 
 impl<D> Transcode for FileDicomObject<InMemDicomObject<D>>
@@ -267,6 +280,144 @@ where
             }
         }
     }
+
+    /// Decode the pixel data frame by frame,
+    /// so that only one decoded frame is held in memory
+    /// besides the native pixel data being built.
+    ///
+    /// The attributes of the image pixel module are updated
+    /// to describe the decoded samples,
+    /// such as the photometric interpretation of JPEG color images
+    /// decoded into `RGB`.
+    /// Other attributes are left as is,
+    /// including those about lossy compression,
+    /// except that _Lossy Image Compression_ is set to `01`
+    /// if missing and the pixel data was decoded from a lossy encoding.
+    fn decompress(&mut self) -> Result<()> {
+        let current_ts_uid = self.meta().transfer_syntax();
+        let current_ts = TransferSyntaxRegistry
+            .get(current_ts_uid)
+            .with_context(|| UnknownSrcTransferSyntaxSnafu {
+                ts: current_ts_uid.to_string(),
+            })?;
+        if current_ts.is_codec_free() {
+            self.meta_mut()
+                .set_transfer_syntax(&EXPLICIT_VR_LITTLE_ENDIAN);
+            return Ok(());
+        }
+        let mut lossy = matches!(
+            current_ts.uid(),
+            uids::JPEG_BASELINE8_BIT | uids::JPEG_EXTENDED12_BIT
+        );
+
+        let frames = self.frames().context(DecodePixelDataSnafu)?;
+        let number_of_frames = frames.info().number_of_frames();
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut words: Vec<u16> = Vec::new();
+        let mut layout = None;
+        for index in 0..number_of_frames {
+            let frame = frames
+                .decode_frame_samples(index)
+                .context(DecodePixelDataSnafu)?;
+            lossy |= frame.near_lossless.is_some();
+            let frame_layout = (
+                frame.photometric_interpretation,
+                frame.planar_configuration,
+                frame.samples_per_pixel,
+                frame.bits_allocated,
+            );
+            match &layout {
+                None => {
+                    let capacity = frame.bytes.len() * number_of_frames as usize;
+                    if frame_layout.3 == 8 {
+                        bytes.reserve_exact(capacity);
+                    } else {
+                        words.reserve_exact(capacity / 2);
+                    }
+                }
+                Some(layout) if *layout != frame_layout => {
+                    return FrameLayoutMismatchSnafu {
+                        frame_number: index,
+                    }
+                    .fail()?;
+                }
+                Some(_) => {}
+            }
+            match frame_layout.3 {
+                8 => bytes.extend_from_slice(&frame.bytes),
+                16 => words.extend(
+                    frame
+                        .bytes
+                        .chunks_exact(2)
+                        .map(|b| u16::from_ne_bytes([b[0], b[1]])),
+                ),
+                bits_allocated => {
+                    return UnsupportedBitsAllocatedSnafu { bits_allocated }.fail()?
+                }
+            }
+            layout = Some(frame_layout);
+        }
+        drop(frames);
+        let (photometric_interpretation, planar_configuration, samples_per_pixel, bits_allocated) =
+            match layout {
+                Some(layout) => layout,
+                // no frames to decode
+                None => return UnsupportedTranscodingSnafu.fail()?,
+            };
+
+        let pixel_data = if bits_allocated == 8 {
+            if bytes.len() % 2 == 1 {
+                bytes.push(0);
+            }
+            DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(bytes))
+        } else {
+            DataElement::new(tags::PIXEL_DATA, VR::OW, PrimitiveValue::U16(words.into()))
+        };
+        self.put(pixel_data);
+        self.put(DataElement::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            VR::CS,
+            photometric_interpretation.as_str(),
+        ));
+        self.put(DataElement::new(
+            tags::SAMPLES_PER_PIXEL,
+            VR::US,
+            PrimitiveValue::from(samples_per_pixel),
+        ));
+        self.put(DataElement::new(
+            tags::BITS_ALLOCATED,
+            VR::US,
+            PrimitiveValue::from(bits_allocated),
+        ));
+        if samples_per_pixel > 1 {
+            self.put(DataElement::new(
+                tags::PLANAR_CONFIGURATION,
+                VR::US,
+                PrimitiveValue::from(planar_configuration as u16),
+            ));
+        } else {
+            self.remove_element(tags::PLANAR_CONFIGURATION);
+        }
+        if lossy
+            && self
+                .element_opt(tags::LOSSY_IMAGE_COMPRESSION)
+                .ok()
+                .flatten()
+                .is_none()
+        {
+            self.put(DataElement::new(
+                tags::LOSSY_IMAGE_COMPRESSION,
+                VR::CS,
+                PrimitiveValue::from("01"),
+            ));
+        }
+        self.remove_element(tags::ENCAPSULATED_PIXEL_DATA_VALUE_TOTAL_LENGTH);
+        self.remove_element(tags::EXTENDED_OFFSET_TABLE);
+        self.remove_element(tags::EXTENDED_OFFSET_TABLE_LENGTHS);
+        self.meta_mut()
+            .set_transfer_syntax(&EXPLICIT_VR_LITTLE_ENDIAN);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -530,4 +681,161 @@ mod tests {
         assert_eq!(fragments[0].len(), 100 * 100 * 3);
         assert_eq!(fragments[1].len(), 100 * 100 * 3);
     }
+
+    /// Encode the planes of a frame as an RLE Lossless fragment,
+    /// with one segment per byte plane, using literal runs only.
+    #[cfg(feature = "rle")]
+    fn rle_fragment(planes: &[&[u8]]) -> Vec<u8> {
+        let mut header = vec![0u8; 64];
+        header[..4].copy_from_slice(&(planes.len() as u32).to_le_bytes());
+        let mut segments = Vec::new();
+        for (i, plane) in planes.iter().enumerate() {
+            let offset = 64 + segments.len() as u32;
+            header[4 + i * 4..8 + i * 4].copy_from_slice(&offset.to_le_bytes());
+            for run in plane.chunks(128) {
+                segments.push(run.len() as u8 - 1);
+                segments.extend_from_slice(run);
+            }
+            if segments.len() % 2 == 1 {
+                segments.push(0);
+            }
+        }
+        header.extend(segments);
+        header
+    }
+
+    #[cfg(feature = "rle")]
+    #[test]
+    fn decompress_rle_to_native() {
+        use crate::info::tests::dummy_image;
+        use dicom_core::dicom_value;
+        use dicom_transfer_syntax_registry::entries::RLE_LOSSLESS;
+
+        // reference: 2 frames of 2x3 RGB pixels
+        let samples: Vec<u8> = (0..36).map(|i| i * 7).collect();
+        let reference = dummy_image(
+            2,
+            3,
+            Some(2),
+            3,
+            8,
+            PrimitiveValue::U8(samples.iter().copied().collect()),
+        );
+
+        let mut obj = reference.clone();
+        obj.meta_mut().set_transfer_syntax(&RLE_LOSSLESS);
+        let fragments: Vec<Vec<u8>> = samples
+            .chunks(18)
+            .map(|frame| {
+                let planes: Vec<Vec<u8>> = (0..3)
+                    .map(|s| frame.iter().skip(s).step_by(3).copied().collect())
+                    .collect();
+                rle_fragment(&[&planes[0], &planes[1], &planes[2]])
+            })
+            .collect();
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(fragments),
+        ));
+        // declared as planar, as required for RLE
+        obj.put(DataElement::new(
+            tags::PLANAR_CONFIGURATION,
+            VR::US,
+            dicom_value!(U16, [1]),
+        ));
+        obj.put(DataElement::new(
+            tags::ENCAPSULATED_PIXEL_DATA_VALUE_TOTAL_LENGTH,
+            VR::UV,
+            PrimitiveValue::from(0_u64),
+        ));
+        obj.put(DataElement::new(
+            tags::LOSSY_IMAGE_COMPRESSION_RATIO,
+            VR::DS,
+            PrimitiveValue::from("1"),
+        ));
+
+        obj.decompress().unwrap();
+
+        assert_eq!(
+            obj.meta().transfer_syntax(),
+            EXPLICIT_VR_LITTLE_ENDIAN.uid()
+        );
+        let pixel_data = obj.element(tags::PIXEL_DATA).unwrap();
+        assert_eq!(pixel_data.vr(), VR::OB);
+        assert_eq!(
+            pixel_data.to_bytes().unwrap(),
+            reference
+                .element(tags::PIXEL_DATA)
+                .unwrap()
+                .to_bytes()
+                .unwrap()
+        );
+        for tag in [
+            tags::ROWS,
+            tags::COLUMNS,
+            tags::NUMBER_OF_FRAMES,
+            tags::SAMPLES_PER_PIXEL,
+            tags::PHOTOMETRIC_INTERPRETATION,
+            tags::BITS_ALLOCATED,
+            tags::PIXEL_REPRESENTATION,
+        ] {
+            assert_eq!(
+                obj.element(tag).unwrap().value(),
+                reference.element(tag).unwrap().value(),
+                "{}",
+                tag
+            );
+        }
+        assert_eq!(
+            obj.element(tags::PLANAR_CONFIGURATION)
+                .unwrap()
+                .to_int::<u16>()
+                .unwrap(),
+            0
+        );
+        assert!(obj
+            .element_opt(tags::ENCAPSULATED_PIXEL_DATA_VALUE_TOTAL_LENGTH)
+            .unwrap()
+            .is_none());
+        // lossy compression attributes are kept, and not added for RLE
+        assert_eq!(
+            obj.element(tags::LOSSY_IMAGE_COMPRESSION_RATIO)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "1"
+        );
+        assert!(obj
+            .element_opt(tags::LOSSY_IMAGE_COMPRESSION)
+            .unwrap()
+            .is_none());
+
+        // 16-bit samples, split into high and low byte segments
+        let words: [u16; 6] = [0x0102, 0xFF00, 0x1234, 0x0000, 0xABCD, 0x00FF];
+        let reference = dummy_image(
+            2,
+            3,
+            None,
+            1,
+            16,
+            PrimitiveValue::U16(words.iter().copied().collect()),
+        );
+        let mut obj = reference.clone();
+        obj.meta_mut().set_transfer_syntax(&RLE_LOSSLESS);
+        let high: Vec<u8> = words.iter().map(|w| (w >> 8) as u8).collect();
+        let low: Vec<u8> = words.iter().map(|w| *w as u8).collect();
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new_fragments(vec![rle_fragment(&[&high, &low])]),
+        ));
+        obj.decompress().unwrap();
+        let pixel_data = obj.element(tags::PIXEL_DATA).unwrap();
+        assert_eq!(pixel_data.vr(), VR::OW);
+        assert_eq!(
+            pixel_data.value(),
+            reference.element(tags::PIXEL_DATA).unwrap().value()
+        );
+    }
 }
@@ -0,0 +1,314 @@
+//! VOI windowing of frames into 8-bit grayscale output.
+
+use crate::attribute::PhotometricInterpretation;
+use crate::frame::Frames;
+#[cfg(any(feature = "image", feature = "ndarray"))]
+use crate::{normalize::stored_values, BitDepthOption, ModalityLutOption, VoiLutOption};
+use crate::{MissingWindowLevelSnafu, Result, WindowLevel, WindowLevelTransform};
+
+impl Frames<'_> {
+    /// Apply the window defined in the object
+    /// to the frame at the given index,
+    /// producing one 8-bit value per pixel.
+    ///
+    /// Fails if the object does not define a window
+    /// (see [`PixelDataInfo::window`](crate::PixelDataInfo::window)).
+    /// See [`window_with`](Self::window_with) for more details.
+    pub fn default_window(&self, index: u32) -> Result<Vec<u8>> {
+        let window_level = self.info().window(index)?.ok_or_else(|| {
+            MissingWindowLevelSnafu {
+                frame_number: index,
+            }
+            .build()
+        })?;
+        self.window(index, window_level.center, window_level.width)
+    }
+
+    /// Apply a window of the given center and width
+    /// to the frame at the given index,
+    /// producing one 8-bit value per pixel.
+    ///
+    /// The VOI LUT function defined in the object is used,
+    /// `LINEAR` by default.
+    /// See [`window_with`](Self::window_with) for more details.
+    pub fn window(&self, index: u32, center: f64, width: f64) -> Result<Vec<u8>> {
+        let voi = WindowLevelTransform::new(
+            self.info().voi_lut_function(index),
+            WindowLevel { center, width },
+        );
+        self.window_with(index, &voi)
+    }
+
+    /// Apply the given VOI transformation
+    /// to the frame at the given index,
+    /// producing one 8-bit value per pixel,
+    /// decoding the frame first if necessary.
+    ///
+    /// The transformation is applied to the real world values of the frame
+    /// (see [`to_rescaled_f32`](Self::to_rescaled_f32)),
+    /// as described in PS3.3 C.11.2.1.2,
+    /// with fractional outputs truncated.
+    /// The output of `MONOCHROME1` images is inverted,
+    /// so that higher values are always brighter.
+    pub fn window_with(&self, index: u32, voi: &WindowLevelTransform) -> Result<Vec<u8>> {
+        let invert =
+            *self.info().photometric_interpretation() == PhotometricInterpretation::Monochrome1;
+        let y_max = f64::from(u8::MAX);
+        Ok(self
+            .to_rescaled_f32(index)?
+            .into_iter()
+            .map(|value| {
+                let y = voi.apply(f64::from(value), y_max) as u8;
+                if invert {
+                    u8::MAX - y
+                } else {
+                    y
+                }
+            })
+            .collect())
+    }
+}
+
+/// Steps of the conversion pipeline shared by the integrations
+/// with the `image` and `ndarray` crates.
+#[cfg(any(feature = "image", feature = "ndarray"))]
+impl Frames<'_> {
+    /// The maximum output value of the VOI transformation
+    /// for the given bit depth option:
+    /// 255 for 8-bit output, 65535 for 16-bit output.
+    pub(crate) fn output_max(&self, bit_depth: BitDepthOption) -> f64 {
+        match (bit_depth, self.info().bits_allocated()) {
+            (BitDepthOption::Force8Bit, _) | (BitDepthOption::Auto, 8) => f64::from(u8::MAX),
+            _ => f64::from(u16::MAX),
+        }
+    }
+
+    /// Obtain the values of the monochrome frame at the given index
+    /// after the modality transformation described by the given option.
+    pub(crate) fn modality_values(
+        &self,
+        index: u32,
+        modality_lut: &ModalityLutOption,
+    ) -> Result<Vec<f64>> {
+        Ok(match modality_lut {
+            ModalityLutOption::Default => self
+                .to_rescaled_f32(index)?
+                .into_iter()
+                .map(f64::from)
+                .collect(),
+            ModalityLutOption::Override(rescale) => {
+                stored_values(self.info(), &self.decode_frame(index)?.bytes)?
+                    .into_iter()
+                    .map(|v| rescale.apply(v as f64))
+                    .collect()
+            }
+            ModalityLutOption::None => {
+                stored_values(self.info(), &self.decode_frame(index)?.bytes)?
+                    .into_iter()
+                    .map(|v| v as f64)
+                    .collect()
+            }
+        })
+    }
+
+    /// Apply the VOI transformation described by the given option
+    /// to the values of the frame at the given index,
+    /// into the range `[0, y_max]`.
+    ///
+    /// The default option applies the first window of the object,
+    /// or the lookup table of the _VOI LUT Sequence_
+    /// if the object does not define a window,
+    /// or normalizes the values to the output range
+    /// if it defines neither.
+    /// The VOI LUT option prefers the lookup table over the window.
+    /// The output of a lookup table is scaled to the output range.
+    /// The output is not inverted for `MONOCHROME1`.
+    pub(crate) fn voi_values(
+        &self,
+        index: u32,
+        values: Vec<f64>,
+        voi_lut: &VoiLutOption,
+        y_max: f64,
+    ) -> Result<Vec<f64>> {
+        let info = self.info();
+        let window = match voi_lut {
+            VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Lut => {
+                info.window(index)?
+            }
+            VoiLutOption::Custom(window) => Some(*window),
+            VoiLutOption::Normalize | VoiLutOption::Identity => None,
+        };
+        let lut = match voi_lut {
+            VoiLutOption::Lut => info.voi_lut(),
+            VoiLutOption::Default | VoiLutOption::First if window.is_none() => info.voi_lut(),
+            _ => None,
+        };
+        if let Some(lut) = lut {
+            let scale = y_max / f64::from(lut.max_output());
+            return Ok(values
+                .into_iter()
+                .map(|v| (f64::from(lut.apply(v.round() as i64)) * scale).min(y_max))
+                .collect());
+        }
+        Ok(match (window, voi_lut) {
+            (Some(window), _) => {
+                let voi = WindowLevelTransform::new(info.voi_lut_function(index), window);
+                values.into_iter().map(|v| voi.apply(v, y_max)).collect()
+            }
+            (None, VoiLutOption::Identity) => {
+                values.into_iter().map(|v| v.clamp(0., y_max)).collect()
+            }
+            (None, _) => {
+                if !matches!(voi_lut, VoiLutOption::Normalize) {
+                    tracing::warn!("Could not find window level for object");
+                }
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let range = max - min;
+                values
+                    .into_iter()
+                    .map(|v| {
+                        if range > 0. {
+                            (v - min) / range * y_max
+                        } else {
+                            0.
+                        }
+                    })
+                    .collect()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::info::tests::dummy_image;
+    use crate::{InnerError, PixelDataAccess};
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+
+    /// Rescaled values of the test image.
+    const VALUES: [i32; 9] = [-5, 0, 1, 64, 128, 192, 255, 256, 1000];
+
+    /// An image of rescaled values [`VALUES`],
+    /// with the window center 128 and width 256,
+    /// followed by a second window which should not be used.
+    fn image(
+        photometric_interpretation: &str,
+        voi_lut_function: Option<&str>,
+    ) -> dicom_object::DefaultDicomObject {
+        let stored: Vec<u16> = VALUES.iter().map(|&v| (v + 1000) as u16).collect();
+        let mut obj = dummy_image(1, 9, None, 1, 16, PrimitiveValue::U16(stored.into()));
+        obj.put(DataElement::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            VR::CS,
+            PrimitiveValue::from(photometric_interpretation),
+        ));
+        obj.put(DataElement::new(
+            tags::RESCALE_INTERCEPT,
+            VR::DS,
+            PrimitiveValue::from("-1000"),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER,
+            VR::DS,
+            dicom_value!(Strs, ["128", "40"]),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_WIDTH,
+            VR::DS,
+            dicom_value!(Strs, ["256", "80"]),
+        ));
+        if let Some(function) = voi_lut_function {
+            obj.put(DataElement::new(
+                tags::VOILUT_FUNCTION,
+                VR::CS,
+                PrimitiveValue::from(function),
+            ));
+        }
+        obj
+    }
+
+    fn inverted(values: &[u8]) -> Vec<u8> {
+        values.iter().map(|v| 255 - v).collect()
+    }
+
+    #[test]
+    fn window_linear() {
+        // x <= c - 0.5 - (w-1)/2 = 0 gives 0,
+        // x > c - 0.5 + (w-1)/2 = 255 gives 255,
+        // and y = ((x - 127.5) / 255 + 0.5) * 255 in between, truncated
+        // (which may fall just short of x)
+        let expected = [0, 0, 0, 64, 128, 192, 255, 255, 255];
+
+        let obj = image("MONOCHROME2", None);
+        let frames = obj.frames().unwrap();
+        assert_eq!(frames.default_window(0).unwrap(), expected);
+        assert_eq!(frames.window(0, 128., 256.).unwrap(), expected);
+
+        let obj = image("MONOCHROME1", Some("LINEAR"));
+        let frames = obj.frames().unwrap();
+        assert_eq!(frames.default_window(0).unwrap(), inverted(&expected));
+
+        // a narrower window saturates more values
+        let obj = image("MONOCHROME2", None);
+        let frames = obj.frames().unwrap();
+        assert_eq!(
+            frames.window(0, 128., 2.).unwrap(),
+            [0, 0, 0, 0, 255, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn window_linear_exact() {
+        // x <= c - w/2 = 0 gives 0,
+        // x > c + w/2 = 256 gives 255,
+        // and y = ((x - 128) / 256 + 0.5) * 255 in between, truncated
+        let expected = [0, 0, 0, 63, 127, 191, 254, 255, 255];
+
+        let obj = image("MONOCHROME2", Some("LINEAR_EXACT"));
+        assert_eq!(obj.frames().unwrap().default_window(0).unwrap(), expected);
+
+        let obj = image("MONOCHROME1", Some("LINEAR_EXACT"));
+        assert_eq!(
+            obj.frames().unwrap().default_window(0).unwrap(),
+            inverted(&expected)
+        );
+    }
+
+    #[test]
+    fn window_sigmoid() {
+        // y = 255 / (1 + exp(-4 * (x - 128) / 256)), truncated
+        let expected: Vec<u8> = VALUES
+            .iter()
+            .map(|&x| (255. / (1. + f64::exp(-4. * (f64::from(x) - 128.) / 256.))) as u8)
+            .collect();
+        assert_eq!(&expected[1..8], [30, 30, 68, 127, 186, 224, 224]);
+
+        let obj = image("MONOCHROME2", Some("SIGMOID"));
+        assert_eq!(obj.frames().unwrap().default_window(0).unwrap(), expected);
+
+        let obj = image("MONOCHROME1", Some("SIGMOID"));
+        assert_eq!(
+            obj.frames().unwrap().default_window(0).unwrap(),
+            inverted(&expected)
+        );
+    }
+
+    #[test]
+    fn window_missing() {
+        let mut obj = image("MONOCHROME2", None);
+        obj.remove_element(tags::WINDOW_WIDTH);
+        let frames = obj.frames().unwrap();
+        let err = frames.default_window(0).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::MissingWindowLevel {
+                frame_number: 0,
+                ..
+            }
+        ));
+        // an explicit window is still possible
+        assert_eq!(frames.window(0, 128., 256.).unwrap()[4], 128);
+    }
+}
@@ -115,3 +115,169 @@ mod jpeg {
         ));
     }
 }
+
+/// A function decoding a fragment into the given buffer,
+/// returning the bits allocated, the samples per pixel,
+/// and the maximum error of each sample.
+#[cfg(any(feature = "jpeg", feature = "jpegls"))]
+type DecodeFn = fn(&[u8], &mut Vec<u8>) -> (u16, u16, u16);
+
+/// Check the decoded samples of the first fragment
+/// of an encapsulated test file against the uncompressed reference file
+/// of the same image.
+///
+/// The maximum error returned by `decode`
+/// must be 0 if the test file is `lossless`.
+#[cfg(any(feature = "jpeg", feature = "jpegls"))]
+fn check_against_reference(
+    test_file: &str,
+    reference_file: &str,
+    decode: DecodeFn,
+    lossless: bool,
+) {
+    use dicom_dictionary_std::tags;
+    use dicom_object::open_file;
+
+    let obj = open_file(dicom_test_files::path(test_file).unwrap()).unwrap();
+    let reference = open_file(dicom_test_files::path(reference_file).unwrap()).unwrap();
+    let attribute = |tag| reference.element(tag).unwrap().to_int::<u16>().unwrap();
+
+    let fragments = obj
+        .element(tags::PIXEL_DATA)
+        .unwrap()
+        .value()
+        .fragments()
+        .expect("test file should have encapsulated pixel data");
+    let mut dst = Vec::new();
+    let (bits_allocated, samples_per_pixel, near) = decode(&fragments[0], &mut dst);
+    assert_eq!(bits_allocated, attribute(tags::BITS_ALLOCATED));
+    assert_eq!(samples_per_pixel, attribute(tags::SAMPLES_PER_PIXEL));
+    assert_eq!(near == 0, lossless, "unexpected error bound {}", near);
+
+    let mask = ((1_u32 << attribute(tags::BITS_STORED)) - 1) as u16;
+    let to_samples = |bytes: &[u8]| -> Vec<u16> {
+        if bits_allocated == 8 {
+            bytes.iter().map(|&b| u16::from(b)).collect()
+        } else {
+            bytes
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]) & mask)
+                .collect()
+        }
+    };
+    let samples_per_pixel = usize::from(samples_per_pixel);
+    let pixels = usize::from(attribute(tags::ROWS)) * usize::from(attribute(tags::COLUMNS));
+    let samples = to_samples(&dst);
+    let mut expected = to_samples(
+        &reference
+            .element(tags::PIXEL_DATA)
+            .unwrap()
+            .to_bytes()
+            .unwrap(),
+    );
+    expected.truncate(pixels * samples_per_pixel);
+    let planar = reference
+        .element_opt(tags::PLANAR_CONFIGURATION)
+        .unwrap()
+        .map(|e| e.to_int::<u16>().unwrap());
+    if planar == Some(1) {
+        // interleave the samples of the reference
+        let planes = expected.clone();
+        for (i, sample) in expected.iter_mut().enumerate() {
+            *sample = planes[(i % samples_per_pixel) * pixels + i / samples_per_pixel];
+        }
+    }
+
+    assert_eq!(samples.len(), expected.len());
+    for (i, (got, expected)) in samples.iter().zip(&expected).enumerate() {
+        assert!(
+            got.abs_diff(*expected) <= near,
+            "sample #{} of {}: {} differs from reference {}",
+            i,
+            test_file,
+            got,
+            expected
+        );
+    }
+}
+
+/// JPEG-LS code streams of the NEMA WG04 test images,
+/// against their uncompressed counterparts.
+#[cfg(feature = "jpegls")]
+mod jpegls {
+    use super::check_against_reference;
+    use dicom_transfer_syntax_registry::adapters::jpegls::decode_jpegls_frame;
+
+    fn decode(data: &[u8], dst: &mut Vec<u8>) -> (u16, u16, u16) {
+        let info = decode_jpegls_frame(data, dst).unwrap();
+        (
+            info.bits_allocated,
+            info.samples_per_pixel,
+            info.near_lossless,
+        )
+    }
+
+    #[test]
+    fn lossless_16bit_monochrome() {
+        check_against_reference("WG04/JLSL/CT1_JLSL", "WG04/REF/CT1_UNC", decode, true);
+    }
+
+    #[test]
+    fn lossless_12bit_monochrome() {
+        check_against_reference("WG04/JLSL/MR1_JLSL", "WG04/REF/MR1_UNC", decode, true);
+    }
+
+    /// Read a test fixture of this crate.
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+        std::fs::read(path).unwrap()
+    }
+
+    /// The test files have no colour JPEG-LS image,
+    /// so the interleaved scans are checked
+    /// against small code streams of a 13x11 RGB image
+    /// made of runs, edges and noise,
+    /// encoded in line and in sample interleaved mode.
+    #[test]
+    fn lossless_8bit_rgb() {
+        let expected = fixture("rgb8_13x11.raw");
+        for name in ["rgb8_13x11_ilv1.jls", "rgb8_13x11_ilv2.jls"] {
+            let mut dst = Vec::new();
+            let info = decode_jpegls_frame(&fixture(name), &mut dst).unwrap();
+            assert_eq!((info.width, info.height), (13, 11), "{}", name);
+            assert_eq!(info.samples_per_pixel, 3, "{}", name);
+            assert_eq!(info.bits_allocated, 8, "{}", name);
+            assert_eq!(info.near_lossless, 0, "{}", name);
+            assert_eq!(dst, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn near_lossless_monochrome() {
+        check_against_reference("WG04/JLSN/CT1_JLSN", "WG04/REF/CT1_UNC", decode, false);
+        check_against_reference("WG04/JLSN/MR1_JLSN", "WG04/REF/MR1_UNC", decode, false);
+    }
+}
+
+/// JPEG Lossless code streams of the NEMA WG04 test images,
+/// against their uncompressed counterparts.
+#[cfg(feature = "jpeg")]
+mod jpeg_lossless {
+    use super::check_against_reference;
+    use dicom_transfer_syntax_registry::adapters::jpeg_lossless::decode_jpeg_lossless_frame;
+
+    fn decode(data: &[u8], dst: &mut Vec<u8>) -> (u16, u16, u16) {
+        let info = decode_jpeg_lossless_frame(data, dst).unwrap();
+        (info.bits_allocated, info.samples_per_pixel, 0)
+    }
+
+    #[test]
+    fn lossless_16bit_monochrome() {
+        check_against_reference("WG04/JPLL/CT1_JPLL", "WG04/REF/CT1_UNC", decode, true);
+    }
+
+    #[test]
+    fn lossless_12bit_monochrome() {
+        check_against_reference("WG04/JPLL/MR1_JPLL", "WG04/REF/MR1_UNC", decode, true);
+    }
+}
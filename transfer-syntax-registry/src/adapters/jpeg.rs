@@ -7,7 +7,7 @@ use dicom_encoding::adapters::{
     PixelDataReader, PixelDataWriter,
 };
 use dicom_encoding::snafu::prelude::*;
-use jpeg_decoder::Decoder;
+use jpeg_decoder::{Decoder, PixelFormat};
 use jpeg_encoder::ColorType;
 use std::borrow::Cow;
 use std::io::Cursor;
@@ -386,6 +386,50 @@ impl PixelDataWriter for JpegAdapter {
     }
 }
 
+/// Basic properties of an image decoded from a JPEG code stream.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct JpegImageInfo {
+    /// the number of columns
+    pub width: u16,
+    /// the number of rows
+    pub height: u16,
+    /// the number of samples per pixel
+    pub samples_per_pixel: u16,
+    /// the number of bits allocated for each decoded sample
+    pub bits_allocated: u16,
+}
+
+/// Decode a single JPEG code stream,
+/// such as one frame of encapsulated pixel data,
+/// appending the decoded samples to `dst`.
+///
+/// Samples are in standard planar configuration
+/// and native byte order.
+/// Images with 3 components are converted to RGB.
+pub fn decode_jpeg_frame(data: &[u8], dst: &mut Vec<u8>) -> DecodeResult<JpegImageInfo> {
+    let mut decoder = Decoder::new(data);
+    let decoded = decoder
+        .decode()
+        .map_err(|e| Box::new(e) as Box<_>)
+        .whatever_context("JPEG decoder failure")?;
+    let info = decoder
+        .info()
+        .whatever_context("Missing JPEG image information")?;
+    let (samples_per_pixel, bits_allocated) = match info.pixel_format {
+        PixelFormat::L8 => (1, 8),
+        PixelFormat::L16 => (1, 16),
+        PixelFormat::RGB24 => (3, 8),
+        PixelFormat::CMYK32 => (4, 8),
+    };
+    dst.extend_from_slice(&decoded);
+    Ok(JpegImageInfo {
+        width: info.width,
+        height: info.height,
+        samples_per_pixel,
+        bits_allocated,
+    })
+}
+
 fn next_even(l: u64) -> u64 {
     (l + 1) & !1
 }
@@ -407,3 +451,64 @@ fn narrow_8bit(frame_data: &[u8], bits_stored: u16) -> EncodeResult<Cow<[u8]>> {
         b => whatever!("Unsupported Bits Stored {}", b),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode a test image with the JPEG baseline encoder
+    fn encode(data: &[u8], width: u16, height: u16, color_type: ColorType) -> Vec<u8> {
+        let mut out = Vec::new();
+        jpeg_encoder::Encoder::new(&mut out, 100)
+            .encode(data, width, height, color_type)
+            .unwrap();
+        out
+    }
+
+    fn assert_close(decoded: &[u8], expected: &[u8]) {
+        assert_eq!(decoded.len(), expected.len());
+        for (i, (a, b)) in decoded.iter().zip(expected).enumerate() {
+            assert!(
+                (i16::from(*a) - i16::from(*b)).abs() <= 4,
+                "sample #{} differs too much: {} vs {}",
+                i,
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn decode_grayscale_frame() {
+        // 16x8 horizontal gradient
+        let raster: Vec<u8> = (0..8).flat_map(|_| (0..16).map(|x| x * 16)).collect();
+        let jpeg = encode(&raster, 16, 8, ColorType::Luma);
+
+        let mut decoded = vec![];
+        let info = decode_jpeg_frame(&jpeg, &mut decoded).unwrap();
+        assert_eq!(
+            info,
+            JpegImageInfo {
+                width: 16,
+                height: 8,
+                samples_per_pixel: 1,
+                bits_allocated: 8,
+            }
+        );
+        assert_close(&decoded, &raster);
+    }
+
+    #[test]
+    fn decode_color_frame() {
+        // 8x8 flat orange
+        let raster: Vec<u8> = (0..64).flat_map(|_| vec![240, 128, 16]).collect();
+        let jpeg = encode(&raster, 8, 8, ColorType::Rgb);
+
+        let mut decoded = vec![];
+        let info = decode_jpeg_frame(&jpeg, &mut decoded).unwrap();
+        assert_eq!(info.samples_per_pixel, 3);
+        assert_close(&decoded, &raster);
+
+        assert!(decode_jpeg_frame(&jpeg[..20], &mut decoded).is_err());
+    }
+}
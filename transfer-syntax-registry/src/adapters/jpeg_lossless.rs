@@ -0,0 +1,745 @@
+//! Support for JPEG Lossless image decoding.
+//!
+//! This is a native implementation of the lossless, non-hierarchical
+//! Huffman coding process of JPEG (ITU-T T.81, Process 14),
+//! as used by the transfer syntaxes
+//! _JPEG Lossless, Non-Hierarchical (Process 14)_
+//! and _JPEG Lossless, Non-Hierarchical, First-Order Prediction_.
+//! All predictors (selection values 1 to 7),
+//! sample precisions from 2 to 16 bits,
+//! any number of components,
+//! point transforms, and restart intervals spanning whole lines
+//! are supported.
+use crate::adapters::jpeg::JpegImageInfo;
+use dicom_encoding::adapters::DecodeResult;
+use dicom_encoding::snafu::prelude::*;
+
+// markers
+const SOF3: u8 = 0xC3;
+const DHT: u8 = 0xC4;
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const SOS: u8 = 0xDA;
+const DRI: u8 = 0xDD;
+
+/// Decode a single JPEG Lossless code stream,
+/// such as one frame of encapsulated pixel data,
+/// appending the decoded samples to `dst`.
+///
+/// Samples are in standard planar configuration
+/// (all samples of a pixel next to each other)
+/// and are not subjected to any color space conversion.
+/// Samples with a precision of up to 8 bits are written as bytes,
+/// whereas higher precisions use 16 bits per sample in native byte order.
+pub fn decode_jpeg_lossless_frame(data: &[u8], dst: &mut Vec<u8>) -> DecodeResult<JpegImageInfo> {
+    ensure_whatever!(
+        data.starts_with(&[0xFF, SOI]),
+        "Missing start of image marker in JPEG code stream"
+    );
+    let mut pos = 2;
+    let mut frame: Option<FrameHeader> = None;
+    let mut tables: [Option<HuffmanTable>; 4] = Default::default();
+    let mut restart_interval = 0;
+    let mut planes: Vec<Option<Vec<u16>>> = Vec::new();
+
+    while let Some((marker, segment_start)) = next_marker(data, pos) {
+        pos = segment_start;
+        match marker {
+            EOI => break,
+            SOF3 => {
+                ensure_whatever!(frame.is_none(), "Multiple JPEG frame headers");
+                let header = FrameHeader::parse(segment(data, pos)?)?;
+                planes = vec![None; header.components.len()];
+                frame = Some(header);
+            }
+            DHT => {
+                let mut segment = segment(data, pos)?;
+                while !segment.is_empty() {
+                    let (id, table, rest) = HuffmanTable::parse(segment)?;
+                    tables[id] = Some(table);
+                    segment = rest;
+                }
+            }
+            DRI => {
+                let segment = segment(data, pos)?;
+                ensure_whatever!(
+                    segment.len() >= 2,
+                    "JPEG restart interval segment is too short"
+                );
+                restart_interval = usize::from(u16::from_be_bytes([segment[0], segment[1]]));
+            }
+            SOS => {
+                let frame = frame
+                    .as_ref()
+                    .whatever_context("JPEG scan before frame header")?;
+                let header_data = segment(data, pos)?;
+                let scan = ScanHeader::parse(header_data, frame)?;
+                let start = pos + 2 + header_data.len();
+                let (segments, end) = entropy_coded_segments(data, start);
+                let scan_tables = scan
+                    .tables
+                    .iter()
+                    .map(|&t| {
+                        tables[t]
+                            .as_ref()
+                            .with_whatever_context(|| format!("Missing Huffman table {}", t))
+                    })
+                    .collect::<DecodeResult<Vec<_>>>()?;
+                let decoded = decode_scan(&segments, frame, &scan, &scan_tables, restart_interval)?;
+                for (&c, plane) in scan.components.iter().zip(decoded) {
+                    planes[c] = Some(plane);
+                }
+                pos = end;
+                continue;
+            }
+            0xC0..=0xCF if marker != 0xC8 && marker != 0xCC => {
+                whatever!(
+                    "Code stream is not JPEG Lossless, Non-Hierarchical (found SOF marker {:02X})",
+                    marker
+                )
+            }
+            _ => {}
+        }
+        // skip to the end of the marker segment
+        pos += segment(data, pos)?.len() + 2;
+    }
+
+    let frame = frame.whatever_context("Missing JPEG frame header")?;
+    let planes: Vec<Vec<u16>> = planes
+        .into_iter()
+        .collect::<Option<_>>()
+        .whatever_context("Not all JPEG image components were decoded")?;
+
+    let bits_allocated = if frame.precision <= 8 { 8 } else { 16 };
+    let pixels = frame.width as usize * frame.height as usize;
+    dst.reserve(pixels * planes.len() * (bits_allocated / 8) as usize);
+    for i in 0..pixels {
+        for plane in &planes {
+            if bits_allocated == 8 {
+                dst.push(plane[i] as u8);
+            } else {
+                dst.extend_from_slice(&plane[i].to_ne_bytes());
+            }
+        }
+    }
+
+    Ok(JpegImageInfo {
+        width: frame.width,
+        height: frame.height,
+        samples_per_pixel: frame.components.len() as u16,
+        bits_allocated,
+    })
+}
+
+/// Find the next marker at or after `pos`,
+/// returning the marker code and the position after it.
+fn next_marker(data: &[u8], mut pos: usize) -> Option<(u8, usize)> {
+    while pos + 1 < data.len() {
+        if data[pos] == 0xFF && data[pos + 1] != 0xFF && data[pos + 1] != 0x00 {
+            return Some((data[pos + 1], pos + 2));
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Obtain the contents of the marker segment starting at `pos`,
+/// excluding its length field.
+fn segment(data: &[u8], pos: usize) -> DecodeResult<&[u8]> {
+    let len = data
+        .get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+        .whatever_context("Unexpected end of JPEG code stream")?;
+    ensure_whatever!(len >= 2, "Invalid JPEG marker segment length {}", len);
+    data.get(pos + 2..pos + len)
+        .whatever_context("Unexpected end of JPEG code stream")
+}
+
+/// Split the entropy coded data starting at `pos`
+/// into the segments between restart markers,
+/// also returning the position of the marker ending the scan.
+fn entropy_coded_segments(data: &[u8], mut pos: usize) -> (Vec<&[u8]>, usize) {
+    let mut segments = Vec::new();
+    let mut start = pos;
+    while pos + 1 < data.len() {
+        if data[pos] != 0xFF {
+            pos += 1;
+            continue;
+        }
+        match data[pos + 1] {
+            // stuffed zero byte or fill byte
+            0x00 | 0xFF => pos += 1,
+            // restart marker
+            0xD0..=0xD7 => {
+                segments.push(&data[start..pos]);
+                pos += 2;
+                start = pos;
+            }
+            _ => {
+                segments.push(&data[start..pos]);
+                return (segments, pos);
+            }
+        }
+    }
+    segments.push(&data[start..]);
+    (segments, data.len())
+}
+
+#[derive(Debug)]
+struct FrameHeader {
+    precision: u8,
+    height: u16,
+    width: u16,
+    /// component identifiers
+    components: Vec<u8>,
+}
+
+impl FrameHeader {
+    fn parse(segment: &[u8]) -> DecodeResult<Self> {
+        ensure_whatever!(segment.len() >= 6, "JPEG frame header is too short");
+        let precision = segment[0];
+        let height = u16::from_be_bytes([segment[1], segment[2]]);
+        let width = u16::from_be_bytes([segment[3], segment[4]]);
+        let count = segment[5] as usize;
+        ensure_whatever!(
+            segment.len() >= 6 + count * 3,
+            "JPEG frame header is too short"
+        );
+        ensure_whatever!(
+            (2..=16).contains(&precision),
+            "Unsupported JPEG Lossless sample precision {}",
+            precision
+        );
+        ensure_whatever!(
+            height > 0 && width > 0 && count > 0,
+            "Invalid JPEG frame dimensions {}x{} with {} components",
+            width,
+            height,
+            count
+        );
+        let components = segment[6..6 + count * 3]
+            .chunks(3)
+            .map(|c| {
+                ensure_whatever!(
+                    c[1] == 0x11,
+                    "JPEG Lossless component subsampling is not supported"
+                );
+                Ok(c[0])
+            })
+            .collect::<DecodeResult<_>>()?;
+        Ok(FrameHeader {
+            precision,
+            height,
+            width,
+            components,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct ScanHeader {
+    /// indices of the frame components in this scan
+    components: Vec<usize>,
+    /// the Huffman table of each component in this scan
+    tables: Vec<usize>,
+    predictor: u8,
+    point_transform: u8,
+}
+
+impl ScanHeader {
+    fn parse(segment: &[u8], frame: &FrameHeader) -> DecodeResult<Self> {
+        let count = *segment
+            .first()
+            .whatever_context("JPEG scan header is too short")? as usize;
+        ensure_whatever!(
+            count > 0 && segment.len() >= 4 + count * 2,
+            "JPEG scan header is too short"
+        );
+        let mut components = Vec::with_capacity(count);
+        let mut tables = Vec::with_capacity(count);
+        for c in segment[1..1 + count * 2].chunks(2) {
+            components.push(
+                frame
+                    .components
+                    .iter()
+                    .position(|&id| id == c[0])
+                    .with_whatever_context(|| format!("Unknown JPEG component {}", c[0]))?,
+            );
+            tables.push(usize::from(c[1] >> 4).min(3));
+        }
+        let predictor = segment[1 + count * 2];
+        let point_transform = segment[3 + count * 2] & 0x0F;
+        ensure_whatever!(
+            (1..=7).contains(&predictor),
+            "Unsupported JPEG Lossless predictor {}",
+            predictor
+        );
+        ensure_whatever!(
+            point_transform < frame.precision,
+            "Invalid JPEG point transform {}",
+            point_transform
+        );
+        Ok(ScanHeader {
+            components,
+            tables,
+            predictor,
+            point_transform,
+        })
+    }
+}
+
+/// A Huffman table for decoding difference magnitude categories.
+#[derive(Debug, Clone)]
+struct HuffmanTable {
+    /// the smallest code of each length
+    min_code: [i32; 17],
+    /// the largest code of each length, or -1 if there is none
+    max_code: [i32; 17],
+    /// the index of the first value of each code length
+    value_offset: [usize; 17],
+    values: Vec<u8>,
+}
+
+impl HuffmanTable {
+    /// Read one table from a DHT marker segment,
+    /// returning its destination identifier and the remaining data.
+    fn parse(segment: &[u8]) -> DecodeResult<(usize, Self, &[u8])> {
+        ensure_whatever!(segment.len() >= 17, "JPEG Huffman table is too short");
+        let id = usize::from(segment[0] & 0x0F);
+        ensure_whatever!(id < 4, "Invalid JPEG Huffman table identifier {}", id);
+        let counts = &segment[1..17];
+        let total: usize = counts.iter().map(|&c| usize::from(c)).sum();
+        let values = segment
+            .get(17..17 + total)
+            .whatever_context("JPEG Huffman table is too short")?
+            .to_vec();
+
+        let mut table = HuffmanTable {
+            min_code: [0; 17],
+            max_code: [-1; 17],
+            value_offset: [0; 17],
+            values,
+        };
+        let mut code = 0;
+        let mut offset = 0;
+        for (len, &count) in (1..=16).zip(counts) {
+            let count = usize::from(count);
+            table.value_offset[len] = offset;
+            table.min_code[len] = code;
+            if count > 0 {
+                code += count as i32;
+                offset += count;
+                table.max_code[len] = code - 1;
+            }
+            code <<= 1;
+        }
+        Ok((id, table, &segment[17 + total..]))
+    }
+}
+
+/// Reads bits from entropy coded data,
+/// removing the zero byte stuffed after each 0xFF byte.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// buffered bits, most significant first
+    acc: u64,
+    bits: u32,
+    /// the number of one bits added past the end of the data
+    padding: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            pos: 0,
+            acc: 0,
+            bits: 0,
+            padding: 0,
+        }
+    }
+
+    fn fill(&mut self) {
+        while self.bits <= 56 {
+            let byte = match self.data.get(self.pos) {
+                Some(&byte) => {
+                    self.pos += if byte == 0xFF { 2 } else { 1 };
+                    byte
+                }
+                None => {
+                    self.padding += 8;
+                    0xFF
+                }
+            };
+            self.acc |= u64::from(byte) << (56 - self.bits);
+            self.bits += 8;
+        }
+    }
+
+    /// Whether more bits were read than available
+    fn overrun(&self) -> bool {
+        self.padding > self.bits
+    }
+
+    fn read_bits(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            return 0;
+        }
+        if self.bits < n {
+            self.fill();
+        }
+        let value = self.acc >> (64 - n);
+        self.acc <<= n;
+        self.bits -= n;
+        value as u32
+    }
+
+    /// Decode a difference magnitude category
+    fn decode(&mut self, table: &HuffmanTable) -> DecodeResult<u8> {
+        let mut code = 0;
+        for len in 1..=16 {
+            code = (code << 1) | self.read_bits(1) as i32;
+            if code <= table.max_code[len] {
+                let index = table.value_offset[len] + (code - table.min_code[len]) as usize;
+                return table
+                    .values
+                    .get(index)
+                    .copied()
+                    .whatever_context("Invalid JPEG Huffman code");
+            }
+        }
+        whatever!("Invalid JPEG Huffman code")
+    }
+
+    /// Decode a difference value
+    fn decode_difference(&mut self, table: &HuffmanTable) -> DecodeResult<i32> {
+        match self.decode(table)? {
+            0 => Ok(0),
+            16 => Ok(32768),
+            ssss @ 1..=15 => {
+                let ssss = u32::from(ssss);
+                let value = self.read_bits(ssss) as i32;
+                if value < 1 << (ssss - 1) {
+                    Ok(value - (1 << ssss) + 1)
+                } else {
+                    Ok(value)
+                }
+            }
+            ssss => whatever!("Invalid JPEG difference category {}", ssss),
+        }
+    }
+}
+
+/// Decode the entropy coded segments of a scan
+/// into one plane of samples for each component in the scan.
+fn decode_scan(
+    segments: &[&[u8]],
+    frame: &FrameHeader,
+    scan: &ScanHeader,
+    tables: &[&HuffmanTable],
+    restart_interval: usize,
+) -> DecodeResult<Vec<Vec<u16>>> {
+    let width = frame.width as usize;
+    let height = frame.height as usize;
+    ensure_whatever!(
+        restart_interval.is_multiple_of(width),
+        "JPEG Lossless restart intervals must span whole lines"
+    );
+    let lines_per_interval = match restart_interval / width {
+        0 => height,
+        lines => lines,
+    };
+    let initial = 1 << (frame.precision - scan.point_transform - 1);
+
+    let mut planes = vec![vec![0u16; width * height]; scan.components.len()];
+    let mut segments = segments.iter();
+    let mut reader = BitReader::new(&[]);
+    for y in 0..height {
+        let first_line = y % lines_per_interval == 0;
+        if first_line {
+            let segment = segments
+                .next()
+                .whatever_context("Missing JPEG restart interval")?;
+            reader = BitReader::new(segment);
+        }
+        for x in 0..width {
+            for (plane, table) in planes.iter_mut().zip(tables) {
+                let difference = reader.decode_difference(table)?;
+                let at = |x: usize, y: usize| i32::from(plane[y * width + x]);
+                let prediction = if first_line {
+                    if x == 0 {
+                        initial
+                    } else {
+                        at(x - 1, y)
+                    }
+                } else if x == 0 {
+                    at(x, y - 1)
+                } else {
+                    let (ra, rb, rc) = (at(x - 1, y), at(x, y - 1), at(x - 1, y - 1));
+                    match scan.predictor {
+                        1 => ra,
+                        2 => rb,
+                        3 => rc,
+                        4 => ra + rb - rc,
+                        5 => ra + ((rb - rc) >> 1),
+                        6 => rb + ((ra - rc) >> 1),
+                        _ => (ra + rb) / 2,
+                    }
+                };
+                plane[y * width + x] = (prediction + difference) as u16;
+            }
+        }
+        ensure_whatever!(!reader.overrun(), "Unexpected end of JPEG scan data");
+    }
+
+    if scan.point_transform > 0 {
+        for sample in planes.iter_mut().flatten() {
+            *sample <<= scan.point_transform;
+        }
+    }
+    Ok(planes)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Writes entropy coded data, stuffing a zero byte after each 0xFF byte.
+    #[derive(Default)]
+    struct BitWriter {
+        out: Vec<u8>,
+        current: u32,
+        bits: u32,
+    }
+
+    impl BitWriter {
+        fn write_bits(&mut self, value: u32, n: u32) {
+            for i in (0..n).rev() {
+                self.current = (self.current << 1) | ((value >> i) & 1);
+                self.bits += 1;
+                if self.bits == 8 {
+                    self.out.push(self.current as u8);
+                    if self.current == 0xFF {
+                        self.out.push(0);
+                    }
+                    self.current = 0;
+                    self.bits = 0;
+                }
+            }
+        }
+
+        /// Pad the last byte with one bits
+        fn flush(&mut self) {
+            if self.bits > 0 {
+                self.write_bits(0xFF, 8 - self.bits);
+            }
+        }
+    }
+
+    /// Encode an image with samples in standard planar configuration
+    /// into a JPEG Lossless code stream,
+    /// using a single Huffman table with 5-bit codes for all categories.
+    pub(crate) fn encode_jpeg_lossless(
+        samples: &[u16],
+        width: u16,
+        height: u16,
+        components: u8,
+        precision: u8,
+        predictor: u8,
+        restart_lines: u16,
+    ) -> Vec<u8> {
+        let count = components as usize;
+        let (w, h) = (width as usize, height as usize);
+        let mut out = vec![0xFF, SOI, 0xFF, SOF3];
+        out.extend_from_slice(&(8 + 3 * u16::from(components)).to_be_bytes());
+        out.push(precision);
+        out.extend_from_slice(&height.to_be_bytes());
+        out.extend_from_slice(&width.to_be_bytes());
+        out.push(components);
+        for id in 1..=components {
+            out.extend_from_slice(&[id, 0x11, 0]);
+        }
+        out.extend_from_slice(&[0xFF, DHT, 0, 36, 0x00]);
+        out.extend_from_slice(&[0, 0, 0, 0, 17, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        out.extend(0..=16);
+        if restart_lines > 0 {
+            out.extend_from_slice(&[0xFF, DRI, 0, 4]);
+            out.extend_from_slice(&(restart_lines * width).to_be_bytes());
+        }
+        out.extend_from_slice(&[0xFF, SOS]);
+        out.extend_from_slice(&(6 + 2 * u16::from(components)).to_be_bytes());
+        out.push(components);
+        for id in 1..=components {
+            out.extend_from_slice(&[id, 0x00]);
+        }
+        out.extend_from_slice(&[predictor, 0, 0]);
+
+        let lines_per_interval = if restart_lines > 0 {
+            restart_lines as usize
+        } else {
+            h
+        };
+        let mut writer = BitWriter::default();
+        for y in 0..h {
+            let first_line = y % lines_per_interval == 0;
+            if first_line && y > 0 {
+                writer.flush();
+                let n = (y / lines_per_interval - 1) % 8;
+                writer.out.extend_from_slice(&[0xFF, 0xD0 + n as u8]);
+            }
+            for x in 0..w {
+                for c in 0..count {
+                    let at = |x: usize, y: usize| i32::from(samples[(y * w + x) * count + c]);
+                    let prediction = if first_line {
+                        if x == 0 {
+                            1 << (precision - 1)
+                        } else {
+                            at(x - 1, y)
+                        }
+                    } else if x == 0 {
+                        at(x, y - 1)
+                    } else {
+                        let (ra, rb, rc) = (at(x - 1, y), at(x, y - 1), at(x - 1, y - 1));
+                        match predictor {
+                            1 => ra,
+                            2 => rb,
+                            3 => rc,
+                            4 => ra + rb - rc,
+                            5 => ra + ((rb - rc) >> 1),
+                            6 => rb + ((ra - rc) >> 1),
+                            _ => (ra + rb) / 2,
+                        }
+                    };
+                    let mut difference = (at(x, y) - prediction) & 0xFFFF;
+                    if difference >= 0x8000 {
+                        difference -= 0x10000;
+                    }
+                    if difference == -0x8000 {
+                        writer.write_bits(16, 5);
+                        continue;
+                    }
+                    let ssss = 32 - difference.unsigned_abs().leading_zeros();
+                    writer.write_bits(ssss, 5);
+                    let bits = if difference < 0 {
+                        difference + (1 << ssss) - 1
+                    } else {
+                        difference
+                    };
+                    writer.write_bits(bits as u32, ssss);
+                }
+            }
+        }
+        writer.flush();
+        out.extend(writer.out);
+        out.extend_from_slice(&[0xFF, EOI]);
+        out
+    }
+
+    /// A test image with smooth areas, flat areas, edges, and noise
+    pub(crate) fn test_image(width: u16, height: u16, components: u8, precision: u8) -> Vec<u16> {
+        let maxval = (1u32 << precision) - 1;
+        let mut seed = 0x2545_F491_u32;
+        let mut samples = Vec::new();
+        for y in 0..u32::from(height) {
+            for x in 0..u32::from(width) {
+                for c in 0..u32::from(components) {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    let value = match (x * 4 / u32::from(width), y % 6) {
+                        (0, _) => c * 3,
+                        (1, _) | (_, 0..=2) => (x * 7 + y * 3 + c * 11) * maxval / 512,
+                        _ => (seed >> 8) % (maxval + 1),
+                    };
+                    samples.push(value.min(maxval) as u16);
+                }
+            }
+        }
+        samples
+    }
+
+    fn decode_samples(data: &[u8]) -> (JpegImageInfo, Vec<u16>) {
+        let mut out = Vec::new();
+        let info = decode_jpeg_lossless_frame(data, &mut out).unwrap();
+        let samples = if info.bits_allocated == 8 {
+            out.into_iter().map(u16::from).collect()
+        } else {
+            out.chunks(2)
+                .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+                .collect()
+        };
+        (info, samples)
+    }
+
+    #[test]
+    fn decode_all_predictors() {
+        for precision in [12, 16] {
+            let image = test_image(27, 13, 1, precision);
+            for predictor in 1..=7 {
+                let data = encode_jpeg_lossless(&image, 27, 13, 1, precision, predictor, 0);
+                let (info, samples) = decode_samples(&data);
+                assert_eq!(
+                    info,
+                    JpegImageInfo {
+                        width: 27,
+                        height: 13,
+                        samples_per_pixel: 1,
+                        bits_allocated: 16,
+                    }
+                );
+                assert_eq!(samples, image, "predictor {}", predictor);
+
+                // compare with an independent implementation
+                let mut decoder = jpeg_decoder::Decoder::new(&data[..]);
+                let reference: Vec<u16> = decoder
+                    .decode()
+                    .unwrap()
+                    .chunks(2)
+                    .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+                    .collect();
+                assert_eq!(reference, image, "predictor {}", predictor);
+            }
+        }
+    }
+
+    #[test]
+    fn decode_other_precisions_and_components() {
+        for (components, precision) in [(3, 8), (3, 16), (1, 2), (1, 7)] {
+            let image = test_image(10, 7, components, precision);
+            let data = encode_jpeg_lossless(&image, 10, 7, components, precision, 1, 0);
+            let (info, samples) = decode_samples(&data);
+            assert_eq!(info.samples_per_pixel, u16::from(components));
+            assert_eq!(info.bits_allocated, if precision <= 8 { 8 } else { 16 });
+            assert_eq!(samples, image);
+        }
+    }
+
+    #[test]
+    fn decode_with_restart_intervals() {
+        let image = test_image(16, 20, 1, 12);
+        for restart_lines in [1, 3] {
+            let data = encode_jpeg_lossless(&image, 16, 20, 1, 12, 6, restart_lines);
+            let (_, samples) = decode_samples(&data);
+            assert_eq!(samples, image);
+        }
+    }
+
+    #[test]
+    fn reject_unsupported_code_streams() {
+        let image = test_image(8, 8, 1, 12);
+        let data = encode_jpeg_lossless(&image, 8, 8, 1, 12, 1, 0);
+        let mut out = Vec::new();
+        assert!(decode_jpeg_lossless_frame(&data[..data.len() - 20], &mut out).is_err());
+
+        // predictor 0 is only for hierarchical mode
+        let sos = data.windows(2).position(|w| w == [0xFF, SOS]).unwrap();
+        let mut no_predictor = data.clone();
+        no_predictor[sos + 7] = 0;
+        let err = decode_jpeg_lossless_frame(&no_predictor, &mut out).unwrap_err();
+        assert!(err.to_string().contains("predictor"));
+
+        // baseline JPEG
+        let mut baseline = data;
+        baseline[3] = 0xC0;
+        assert!(decode_jpeg_lossless_frame(&baseline, &mut out).is_err());
+    }
+}
@@ -0,0 +1,1367 @@
+//! Support for JPEG-LS image decoding.
+//!
+//! This is a native implementation of the JPEG-LS decoding process
+//! (ITU-T T.87 | ISO/IEC 14495-1),
+//! supporting lossless and near-lossless code streams
+//! with a sample precision of 2 to 16 bits,
+//! in all interleave modes.
+//! Mapping tables and restart markers are not supported.
+use std::borrow::Cow;
+
+use dicom_encoding::adapters::{decode_error, DecodeResult, PixelDataObject, PixelDataReader};
+use dicom_encoding::snafu::prelude::*;
+
+/// Pixel data adapter for the JPEG-LS transfer syntaxes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JpegLsAdapter;
+
+/// Pixel data decoder for JPEG-LS Lossless (UID `1.2.840.10008.1.2.4.80`)
+/// and JPEG-LS Lossy (Near-Lossless) (UID `1.2.840.10008.1.2.4.81`)
+impl PixelDataReader for JpegLsAdapter {
+    /// Decode a single frame of the DICOM image from JPEG-LS.
+    fn decode_frame(
+        &self,
+        src: &dyn PixelDataObject,
+        frame: u32,
+        dst: &mut Vec<u8>,
+    ) -> DecodeResult<()> {
+        let cols = src
+            .cols()
+            .context(decode_error::MissingAttributeSnafu { name: "Columns" })?;
+        let rows = src
+            .rows()
+            .context(decode_error::MissingAttributeSnafu { name: "Rows" })?;
+        let samples_per_pixel =
+            src.samples_per_pixel()
+                .context(decode_error::MissingAttributeSnafu {
+                    name: "SamplesPerPixel",
+                })?;
+        let nr_frames = src.number_of_frames().unwrap_or(1);
+        ensure!(frame < nr_frames, decode_error::FrameRangeOutOfBoundsSnafu);
+
+        let raw = src
+            .raw_pixel_data()
+            .whatever_context("Expected to have raw pixel data available")?;
+        let data = if raw.fragments.len() == nr_frames as usize {
+            Cow::Borrowed(&raw.fragments[frame as usize][..])
+        } else if nr_frames == 1 {
+            Cow::Owned(raw.fragments.concat())
+        } else {
+            // gather the fragments of the frame through the basic offset table
+            let start = *raw
+                .offset_table
+                .get(frame as usize)
+                .with_whatever_context(|| format!("Missing offset for frame #{}", frame))?
+                as usize;
+            let end = raw
+                .offset_table
+                .get(frame as usize + 1)
+                .map(|&o| o as usize)
+                .unwrap_or(usize::MAX);
+            let mut offset = 0;
+            let mut data = Vec::new();
+            for fragment in &raw.fragments {
+                if offset >= start && offset < end {
+                    data.extend_from_slice(fragment);
+                }
+                offset += fragment.len() + 8;
+            }
+            Cow::Owned(data)
+        };
+
+        let info = decode_jpegls_frame(&data, dst)?;
+        if info.width != cols || info.height != rows || info.samples_per_pixel != samples_per_pixel
+        {
+            whatever!(
+                "JPEG-LS image is {}x{} with {} components, expected {}x{} with {}",
+                info.width,
+                info.height,
+                info.samples_per_pixel,
+                cols,
+                rows,
+                samples_per_pixel
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Basic properties of an image decoded from a JPEG-LS code stream.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct JpegLsImageInfo {
+    /// the number of columns
+    pub width: u16,
+    /// the number of rows
+    pub height: u16,
+    /// the number of samples per pixel
+    pub samples_per_pixel: u16,
+    /// the sample precision declared in the code stream
+    pub bits_stored: u16,
+    /// the number of bits allocated for each decoded sample (8 or 16)
+    pub bits_allocated: u16,
+    /// the maximum error of each sample (the `NEAR` parameter),
+    /// which is 0 for lossless code streams
+    pub near_lossless: u16,
+}
+
+/// Decode a single JPEG-LS code stream,
+/// such as one frame of encapsulated pixel data,
+/// appending the decoded samples to `dst`.
+///
+/// Samples are in standard planar configuration
+/// (all samples of a pixel next to each other).
+/// Samples with a precision of up to 8 bits are written as bytes,
+/// whereas higher precisions use 16 bits per sample in native byte order.
+pub fn decode_jpegls_frame(data: &[u8], dst: &mut Vec<u8>) -> DecodeResult<JpegLsImageInfo> {
+    ensure_whatever!(
+        data.starts_with(&[0xFF, SOI]),
+        "Missing start of image marker in JPEG-LS code stream"
+    );
+    let mut pos = 2;
+    let mut frame: Option<FrameHeader> = None;
+    let mut preset = PresetParameters::default();
+    let mut planes: Vec<Option<Vec<u16>>> = Vec::new();
+    let mut near_lossless = 0;
+
+    while let Some((marker, segment_start)) = next_marker(data, pos) {
+        pos = segment_start;
+        match marker {
+            EOI => break,
+            SOF55 => {
+                ensure_whatever!(frame.is_none(), "Multiple JPEG-LS frame headers");
+                let header = FrameHeader::parse(segment(data, pos)?)?;
+                planes = vec![None; header.components.len()];
+                frame = Some(header);
+            }
+            LSE => preset.parse(segment(data, pos)?)?,
+            DRI => {
+                let segment = segment(data, pos)?;
+                ensure_whatever!(
+                    segment.len() >= 2 && segment[0] == 0 && segment[1] == 0,
+                    "JPEG-LS restart intervals are not supported"
+                );
+            }
+            SOS => {
+                let frame = frame
+                    .as_ref()
+                    .whatever_context("JPEG-LS scan before frame header")?;
+                let header_data = segment(data, pos)?;
+                let scan = ScanHeader::parse(header_data, frame)?;
+                near_lossless = near_lossless.max(scan.near as u16);
+                let start = pos + 2 + header_data.len();
+                let end = find_marker(data, start);
+                let decoded = decode_scan(&data[start..end], frame, &preset, &scan)?;
+                for (&c, plane) in scan.components.iter().zip(decoded) {
+                    planes[c] = Some(plane);
+                }
+                pos = end;
+                continue;
+            }
+            0xC0..=0xCF if marker != 0xC4 && marker != 0xC8 && marker != 0xCC => {
+                whatever!(
+                    "Code stream is not JPEG-LS (found SOF marker {:02X})",
+                    marker
+                )
+            }
+            _ => {}
+        }
+        // skip to the end of the marker segment
+        pos += segment(data, pos)?.len() + 2;
+    }
+
+    let frame = frame.whatever_context("Missing JPEG-LS frame header")?;
+    let planes: Vec<Vec<u16>> = planes
+        .into_iter()
+        .collect::<Option<_>>()
+        .whatever_context("Not all JPEG-LS image components were decoded")?;
+
+    let bits_allocated = if frame.precision <= 8 { 8 } else { 16 };
+    let pixels = frame.width as usize * frame.height as usize;
+    dst.reserve(pixels * planes.len() * (bits_allocated / 8) as usize);
+    for i in 0..pixels {
+        for plane in &planes {
+            if bits_allocated == 8 {
+                dst.push(plane[i] as u8);
+            } else {
+                dst.extend_from_slice(&plane[i].to_ne_bytes());
+            }
+        }
+    }
+
+    Ok(JpegLsImageInfo {
+        width: frame.width,
+        height: frame.height,
+        samples_per_pixel: frame.components.len() as u16,
+        bits_stored: frame.precision as u16,
+        bits_allocated,
+        near_lossless,
+    })
+}
+
+// markers
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const SOS: u8 = 0xDA;
+const DRI: u8 = 0xDD;
+const SOF55: u8 = 0xF7;
+const LSE: u8 = 0xF8;
+
+/// Find the next marker at or after `pos`,
+/// returning the marker code and the position after it.
+fn next_marker(data: &[u8], mut pos: usize) -> Option<(u8, usize)> {
+    while pos + 1 < data.len() {
+        if data[pos] == 0xFF && data[pos + 1] != 0xFF && data[pos + 1] != 0x00 {
+            return Some((data[pos + 1], pos + 2));
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Find the end of entropy coded data starting at `pos`,
+/// which is where the next marker begins.
+///
+/// In JPEG-LS, a marker is a 0xFF byte
+/// followed by a byte with the most significant bit set.
+fn find_marker(data: &[u8], mut pos: usize) -> usize {
+    while pos + 1 < data.len() {
+        if data[pos] == 0xFF && data[pos + 1] >= 0x80 {
+            return pos;
+        }
+        pos += 1;
+    }
+    data.len()
+}
+
+/// Obtain the contents of the marker segment starting at `pos`,
+/// excluding its length field.
+fn segment(data: &[u8], pos: usize) -> DecodeResult<&[u8]> {
+    let len = data
+        .get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+        .whatever_context("Unexpected end of JPEG-LS code stream")?;
+    ensure_whatever!(len >= 2, "Invalid JPEG-LS marker segment length {}", len);
+    data.get(pos + 2..pos + len)
+        .whatever_context("Unexpected end of JPEG-LS code stream")
+}
+
+fn read_u16(data: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([data[pos], data[pos + 1]])
+}
+
+#[derive(Debug)]
+struct FrameHeader {
+    precision: u8,
+    height: u16,
+    width: u16,
+    /// component identifiers
+    components: Vec<u8>,
+}
+
+impl FrameHeader {
+    fn parse(segment: &[u8]) -> DecodeResult<Self> {
+        ensure_whatever!(segment.len() >= 6, "JPEG-LS frame header is too short");
+        let precision = segment[0];
+        let height = read_u16(segment, 1);
+        let width = read_u16(segment, 3);
+        let count = segment[5] as usize;
+        ensure_whatever!(
+            segment.len() >= 6 + count * 3,
+            "JPEG-LS frame header is too short"
+        );
+        ensure_whatever!(
+            (2..=16).contains(&precision),
+            "Unsupported JPEG-LS sample precision {}",
+            precision
+        );
+        ensure_whatever!(
+            height > 0 && width > 0 && count > 0,
+            "Invalid JPEG-LS frame dimensions {}x{} with {} components",
+            width,
+            height,
+            count
+        );
+        let components = segment[6..6 + count * 3].chunks(3).map(|c| c[0]).collect();
+        Ok(FrameHeader {
+            precision,
+            height,
+            width,
+            components,
+        })
+    }
+}
+
+/// JPEG-LS preset coding parameters,
+/// where 0 means the default value.
+#[derive(Debug, Default)]
+struct PresetParameters {
+    maxval: u16,
+    t1: u16,
+    t2: u16,
+    t3: u16,
+    reset: u16,
+}
+
+impl PresetParameters {
+    /// Read an LSE marker segment
+    fn parse(&mut self, segment: &[u8]) -> DecodeResult<()> {
+        match segment.first() {
+            Some(1) => {
+                ensure_whatever!(segment.len() >= 11, "JPEG-LS preset segment is too short");
+                *self = PresetParameters {
+                    maxval: read_u16(segment, 1),
+                    t1: read_u16(segment, 3),
+                    t2: read_u16(segment, 5),
+                    t3: read_u16(segment, 7),
+                    reset: read_u16(segment, 9),
+                };
+                Ok(())
+            }
+            Some(id) => whatever!("Unsupported JPEG-LS preset parameters type {}", id),
+            None => whatever!("JPEG-LS preset segment is too short"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ScanHeader {
+    /// indices of the frame components in this scan
+    components: Vec<usize>,
+    near: i32,
+    interleave: u8,
+}
+
+impl ScanHeader {
+    fn parse(segment: &[u8], frame: &FrameHeader) -> DecodeResult<Self> {
+        let count = *segment
+            .first()
+            .whatever_context("JPEG-LS scan header is too short")? as usize;
+        ensure_whatever!(
+            count > 0 && segment.len() >= 4 + count * 2,
+            "JPEG-LS scan header is too short"
+        );
+        let components = segment[1..1 + count * 2]
+            .chunks(2)
+            .map(|c| {
+                ensure_whatever!(c[1] == 0, "JPEG-LS mapping tables are not supported");
+                frame
+                    .components
+                    .iter()
+                    .position(|&id| id == c[0])
+                    .with_whatever_context(|| format!("Unknown JPEG-LS component {}", c[0]))
+            })
+            .collect::<DecodeResult<Vec<_>>>()?;
+        let near = segment[1 + count * 2];
+        let interleave = segment[2 + count * 2];
+        let point_transform = segment[3 + count * 2] & 0x0F;
+        ensure_whatever!(
+            interleave <= 2,
+            "Invalid JPEG-LS interleave mode {}",
+            interleave
+        );
+        ensure_whatever!(
+            point_transform == 0,
+            "JPEG-LS point transform is not supported"
+        );
+        Ok(ScanHeader {
+            components,
+            near: near.into(),
+            interleave,
+        })
+    }
+}
+
+/// Coding parameters in effect during a scan.
+#[derive(Debug, Clone)]
+struct CodingParameters {
+    maxval: i32,
+    near: i32,
+    range: i32,
+    qbpp: u32,
+    limit: u32,
+    t1: i32,
+    t2: i32,
+    t3: i32,
+    reset: i32,
+}
+
+// default threshold values for 8-bit samples
+const BASIC_T1: i32 = 3;
+const BASIC_T2: i32 = 7;
+const BASIC_T3: i32 = 21;
+
+/// The number of bits required to represent values up to `n - 1`
+fn ceil_log2(n: i32) -> u32 {
+    let mut bits = 0;
+    while (1 << bits) < n {
+        bits += 1;
+    }
+    bits
+}
+
+impl CodingParameters {
+    fn new(precision: u8, preset: &PresetParameters, near: i32) -> DecodeResult<Self> {
+        let maxval = match preset.maxval {
+            0 => (1 << precision) - 1,
+            maxval => i32::from(maxval),
+        };
+        ensure_whatever!(
+            near <= (maxval / 2).min(255),
+            "Invalid JPEG-LS NEAR parameter {}",
+            near
+        );
+        let range = (maxval + 2 * near) / (2 * near + 1) + 1;
+        let bpp = ceil_log2(maxval + 1).max(2);
+        let limit = 2 * (bpp + bpp.max(8));
+
+        // default thresholds (C.2.4.1.1)
+        let clamp = |i: i32, j: i32| if i > maxval || i < j { j } else { i };
+        let (t1, t2, t3) = if maxval >= 128 {
+            let factor = (maxval.min(4095) + 128) / 256;
+            let t1 = clamp(factor * (BASIC_T1 - 2) + 2 + 3 * near, near + 1);
+            let t2 = clamp(factor * (BASIC_T2 - 3) + 3 + 5 * near, t1);
+            let t3 = clamp(factor * (BASIC_T3 - 4) + 4 + 7 * near, t2);
+            (t1, t2, t3)
+        } else {
+            let factor = 256 / (maxval + 1);
+            let t1 = clamp((BASIC_T1 / factor + 3 * near).max(2), near + 1);
+            let t2 = clamp((BASIC_T2 / factor + 5 * near).max(3), t1);
+            let t3 = clamp((BASIC_T3 / factor + 7 * near).max(4), t2);
+            (t1, t2, t3)
+        };
+        let or_default = |value: u16, default: i32| match value {
+            0 => default,
+            value => i32::from(value),
+        };
+        Ok(CodingParameters {
+            maxval,
+            near,
+            range,
+            qbpp: ceil_log2(range),
+            limit,
+            t1: or_default(preset.t1, t1),
+            t2: or_default(preset.t2, t2),
+            t3: or_default(preset.t3, t3),
+            reset: or_default(preset.reset, 64),
+        })
+    }
+
+    fn quantize_gradient(&self, d: i32) -> i32 {
+        if d <= -self.t3 {
+            -4
+        } else if d <= -self.t2 {
+            -3
+        } else if d <= -self.t1 {
+            -2
+        } else if d < -self.near {
+            -1
+        } else if d <= self.near {
+            0
+        } else if d < self.t1 {
+            1
+        } else if d < self.t2 {
+            2
+        } else if d < self.t3 {
+            3
+        } else {
+            4
+        }
+    }
+
+    /// The signed context number of the local gradients
+    fn context(&self, ra: i32, rb: i32, rc: i32, rd: i32) -> i32 {
+        (self.quantize_gradient(rd - rb) * 9 + self.quantize_gradient(rb - rc)) * 9
+            + self.quantize_gradient(rc - ra)
+    }
+
+    /// Reconstruct a sample from its prediction and quantized error
+    fn reconstruct(&self, prediction: i32, error: i32) -> i32 {
+        let mut value = prediction + error * (2 * self.near + 1);
+        if value < -self.near {
+            value += self.range * (2 * self.near + 1);
+        } else if value > self.maxval + self.near {
+            value -= self.range * (2 * self.near + 1);
+        }
+        value.clamp(0, self.maxval)
+    }
+}
+
+/// The median edge detecting predictor
+fn predict(ra: i32, rb: i32, rc: i32) -> i32 {
+    if rc >= ra.max(rb) {
+        ra.min(rb)
+    } else if rc <= ra.min(rb) {
+        ra.max(rb)
+    } else {
+        ra + rb - rc
+    }
+}
+
+fn sign(n: i32) -> i32 {
+    if n < 0 {
+        -1
+    } else {
+        1
+    }
+}
+
+/// Run length order table
+const J: [u32; 32] = [
+    0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 9, 10, 11, 12, 13,
+    14, 15,
+];
+
+/// Context variables of the regular mode.
+#[derive(Debug, Clone)]
+struct Context {
+    a: i32,
+    b: i32,
+    c: i32,
+    n: i32,
+}
+
+impl Context {
+    fn new(range: i32) -> Self {
+        Context {
+            a: ((range + 32) / 64).max(2),
+            b: 0,
+            c: 0,
+            n: 1,
+        }
+    }
+
+    fn golomb_k(&self) -> u32 {
+        let mut k = 0;
+        while (self.n << k) < self.a {
+            k += 1;
+        }
+        k
+    }
+
+    fn update(&mut self, error: i32, near: i32, reset: i32) {
+        self.a += error.abs();
+        self.b += error * (2 * near + 1);
+        if self.n == reset {
+            self.a >>= 1;
+            self.b >>= 1;
+            self.n >>= 1;
+        }
+        self.n += 1;
+        if self.b + self.n <= 0 {
+            self.b += self.n;
+            if self.b <= -self.n {
+                self.b = -self.n + 1;
+            }
+            if self.c > -128 {
+                self.c -= 1;
+            }
+        } else if self.b > 0 {
+            self.b -= self.n;
+            if self.b > 0 {
+                self.b = 0;
+            }
+            if self.c < 127 {
+                self.c += 1;
+            }
+        }
+    }
+}
+
+/// Context variables of run interruption samples.
+#[derive(Debug, Clone)]
+struct RunContext {
+    a: i32,
+    n: i32,
+    nn: i32,
+    ri_type: i32,
+}
+
+impl RunContext {
+    fn new(range: i32, ri_type: i32) -> Self {
+        RunContext {
+            a: ((range + 32) / 64).max(2),
+            n: 1,
+            nn: 0,
+            ri_type,
+        }
+    }
+
+    fn golomb_k(&self) -> u32 {
+        let temp = self.a + (self.n >> 1) * self.ri_type;
+        let mut k = 0;
+        while (self.n << k) < temp {
+            k += 1;
+        }
+        k
+    }
+
+    /// Recover the error value from the mapped error value
+    fn error_value(&self, mapped: i32, k: u32) -> i32 {
+        let temp = mapped + self.ri_type;
+        let map = temp & 1 == 1;
+        let abs = (temp + (temp & 1)) / 2;
+        if (k != 0 || 2 * self.nn >= self.n) == map {
+            -abs
+        } else {
+            abs
+        }
+    }
+
+    fn update(&mut self, error: i32, mapped: i32, reset: i32) {
+        if error < 0 {
+            self.nn += 1;
+        }
+        self.a += (mapped + 1 - self.ri_type) >> 1;
+        if self.n == reset {
+            self.a >>= 1;
+            self.n >>= 1;
+            self.nn >>= 1;
+        }
+        self.n += 1;
+    }
+}
+
+/// Reads bits from entropy coded data,
+/// removing the zero bit stuffed after each 0xFF byte.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// buffered bits, most significant first
+    acc: u64,
+    bits: u32,
+    last_ff: bool,
+    /// the number of zero bits added past the end of the data
+    padding: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            pos: 0,
+            acc: 0,
+            bits: 0,
+            last_ff: false,
+            padding: 0,
+        }
+    }
+
+    fn fill(&mut self) {
+        while self.bits <= 56 {
+            let (value, n) = match self.data.get(self.pos) {
+                Some(&byte) => {
+                    self.pos += 1;
+                    let n = if self.last_ff { 7 } else { 8 };
+                    self.last_ff = byte == 0xFF;
+                    (u64::from(byte) & ((1 << n) - 1), n)
+                }
+                None => {
+                    self.padding += 8;
+                    (0, 8)
+                }
+            };
+            self.acc |= value << (64 - self.bits - n);
+            self.bits += n;
+        }
+    }
+
+    /// Whether more bits were read than available
+    fn overrun(&self) -> bool {
+        self.padding > self.bits
+    }
+
+    fn read_bits(&mut self, n: u32) -> i32 {
+        if n == 0 {
+            return 0;
+        }
+        if self.bits < n {
+            self.fill();
+        }
+        let value = self.acc >> (64 - n);
+        self.acc <<= n;
+        self.bits -= n;
+        value as i32
+    }
+
+    fn read_bit(&mut self) -> bool {
+        self.read_bits(1) == 1
+    }
+
+    /// Count the zero bits before the next one bit,
+    /// up to `max`.
+    fn read_zeros(&mut self, max: u32) -> Option<u32> {
+        let mut count = 0;
+        loop {
+            if self.bits == 0 {
+                self.fill();
+            }
+            let zeros = self.acc.leading_zeros();
+            if zeros < self.bits {
+                count += zeros;
+                self.acc <<= zeros;
+                self.acc <<= 1;
+                self.bits -= zeros + 1;
+                return Some(count).filter(|&c| c <= max);
+            }
+            count += self.bits;
+            self.acc = 0;
+            self.bits = 0;
+            if count > max {
+                return None;
+            }
+        }
+    }
+}
+
+/// Decoder state for a single scan.
+struct ScanDecoder<'a> {
+    reader: BitReader<'a>,
+    params: CodingParameters,
+    contexts: Vec<Context>,
+    run_contexts: [RunContext; 2],
+    width: usize,
+}
+
+/// Decode the entropy coded data of a scan
+/// into one plane of samples for each component in the scan.
+fn decode_scan(
+    data: &[u8],
+    frame: &FrameHeader,
+    preset: &PresetParameters,
+    scan: &ScanHeader,
+) -> DecodeResult<Vec<Vec<u16>>> {
+    let params = CodingParameters::new(frame.precision, preset, scan.near)?;
+    let width = frame.width as usize;
+    let height = frame.height as usize;
+    let count = scan.components.len();
+    ensure_whatever!(
+        count == 1 || scan.interleave != 0,
+        "JPEG-LS scans without interleaving must have a single component"
+    );
+
+    let mut decoder = ScanDecoder {
+        reader: BitReader::new(data),
+        contexts: vec![Context::new(params.range); 365],
+        run_contexts: [
+            RunContext::new(params.range, 0),
+            RunContext::new(params.range, 1),
+        ],
+        params,
+        width,
+    };
+
+    // lines are padded with one sample on each side
+    let mut previous = vec![vec![0; width + 2]; count];
+    let mut current = vec![vec![0; width + 2]; count];
+    let mut run_index = vec![0; count];
+    let mut planes = vec![Vec::with_capacity(width * height); count];
+
+    for _ in 0..height {
+        for (previous, current) in previous.iter_mut().zip(&mut current) {
+            previous[width + 1] = previous[width];
+            current[0] = previous[1];
+        }
+        if scan.interleave == 2 && count > 1 {
+            decoder.decode_interleaved_line(&previous, &mut current, &mut run_index[0])?;
+        } else {
+            for c in 0..count {
+                decoder.decode_line(&previous[c], &mut current[c], &mut run_index[c])?;
+            }
+        }
+        ensure_whatever!(
+            !decoder.reader.overrun(),
+            "Unexpected end of JPEG-LS scan data"
+        );
+        for (plane, current) in planes.iter_mut().zip(&current) {
+            plane.extend(current[1..=width].iter().map(|&v| v as u16));
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Ok(planes)
+}
+
+impl ScanDecoder<'_> {
+    fn decode_line(
+        &mut self,
+        previous: &[i32],
+        current: &mut [i32],
+        run_index: &mut usize,
+    ) -> DecodeResult<()> {
+        let mut x = 0;
+        while x < self.width {
+            let (ra, rb, rc, rd) = (current[x], previous[x + 1], previous[x], previous[x + 2]);
+            let context = self.params.context(ra, rb, rc, rd);
+            if context != 0 {
+                current[x + 1] = self.decode_regular(context, predict(ra, rb, rc))?;
+                x += 1;
+                continue;
+            }
+
+            // run mode
+            let run = self.decode_run_length(self.width - x, run_index)?;
+            current[x + 1..=x + run].fill(ra);
+            x += run;
+            if x == self.width {
+                break;
+            }
+            let rb = previous[x + 1];
+            current[x + 1] = if (ra - rb).abs() <= self.params.near {
+                let error = self.decode_run_interruption(1, *run_index)?;
+                self.params.reconstruct(ra, error)
+            } else {
+                let error = self.decode_run_interruption(0, *run_index)?;
+                self.params.reconstruct(rb, error * sign(rb - ra))
+            };
+            *run_index = run_index.saturating_sub(1);
+            x += 1;
+        }
+        Ok(())
+    }
+
+    /// Decode a line of all components in sample interleaved mode
+    fn decode_interleaved_line(
+        &mut self,
+        previous: &[Vec<i32>],
+        current: &mut [Vec<i32>],
+        run_index: &mut usize,
+    ) -> DecodeResult<()> {
+        let count = current.len();
+        let mut contexts = vec![0; count];
+        let mut x = 0;
+        while x < self.width {
+            for c in 0..count {
+                let (ra, rb, rc, rd) = (
+                    current[c][x],
+                    previous[c][x + 1],
+                    previous[c][x],
+                    previous[c][x + 2],
+                );
+                contexts[c] = self.params.context(ra, rb, rc, rd);
+            }
+            if contexts.iter().any(|&q| q != 0) {
+                for c in 0..count {
+                    let (ra, rb, rc) = (current[c][x], previous[c][x + 1], previous[c][x]);
+                    current[c][x + 1] = self.decode_regular(contexts[c], predict(ra, rb, rc))?;
+                }
+                x += 1;
+                continue;
+            }
+
+            // run mode
+            let run = self.decode_run_length(self.width - x, run_index)?;
+            for current in current.iter_mut() {
+                let ra = current[x];
+                current[x + 1..=x + run].fill(ra);
+            }
+            x += run;
+            if x == self.width {
+                break;
+            }
+            for c in 0..count {
+                let (ra, rb) = (current[c][x], previous[c][x + 1]);
+                let error = self.decode_run_interruption(0, *run_index)?;
+                current[c][x + 1] = self.params.reconstruct(rb, error * sign(rb - ra));
+            }
+            *run_index = run_index.saturating_sub(1);
+            x += 1;
+        }
+        Ok(())
+    }
+
+    /// Decode a mapped error value with a limited length Golomb code
+    fn decode_value(&mut self, k: u32, limit: u32) -> DecodeResult<i32> {
+        let qbpp = self.params.qbpp;
+        let max = limit
+            .checked_sub(qbpp + 1)
+            .whatever_context("Invalid JPEG-LS code length limit")?;
+        let high = self
+            .reader
+            .read_zeros(max)
+            .whatever_context("Invalid JPEG-LS Golomb code")?;
+        if high == max {
+            Ok(self.reader.read_bits(qbpp) + 1)
+        } else {
+            Ok(((high as i32) << k) + self.reader.read_bits(k))
+        }
+    }
+
+    fn decode_regular(&mut self, context: i32, prediction: i32) -> DecodeResult<i32> {
+        let sign = sign(context);
+        let index = (context * sign) as usize;
+        let k = self.contexts[index].golomb_k();
+        let prediction = (prediction + sign * self.contexts[index].c).clamp(0, self.params.maxval);
+
+        let mapped = self.decode_value(k, self.params.limit)?;
+        let mut error = if mapped % 2 == 0 {
+            mapped / 2
+        } else {
+            -(mapped + 1) / 2
+        };
+        let params = &self.params;
+        let context = &mut self.contexts[index];
+        if k == 0 && params.near == 0 && 2 * context.b + context.n <= 0 {
+            error = -error - 1;
+        }
+        context.update(error, params.near, params.reset);
+        Ok(params.reconstruct(prediction, sign * error))
+    }
+
+    fn decode_run_length(
+        &mut self,
+        remaining: usize,
+        run_index: &mut usize,
+    ) -> DecodeResult<usize> {
+        let mut length = 0;
+        while self.reader.read_bit() {
+            let block = 1 << J[*run_index];
+            let count = block.min(remaining - length);
+            length += count;
+            if count == block {
+                *run_index = (*run_index + 1).min(31);
+            }
+            if length == remaining {
+                return Ok(length);
+            }
+        }
+        length += self.reader.read_bits(J[*run_index]) as usize;
+        ensure_whatever!(length <= remaining, "Invalid JPEG-LS run length");
+        Ok(length)
+    }
+
+    fn decode_run_interruption(&mut self, ri_type: usize, run_index: usize) -> DecodeResult<i32> {
+        let k = self.run_contexts[ri_type].golomb_k();
+        let mapped = self.decode_value(k, self.params.limit - J[run_index] - 1)?;
+        let context = &mut self.run_contexts[ri_type];
+        let error = context.error_value(mapped, k);
+        context.update(error, mapped, self.params.reset);
+        Ok(error)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Writes entropy coded data, stuffing a zero bit after each 0xFF byte.
+    struct BitWriter {
+        out: Vec<u8>,
+        current: u8,
+        bits: u32,
+        capacity: u32,
+    }
+
+    impl BitWriter {
+        fn write_bits(&mut self, value: i32, n: u32) {
+            for i in (0..n).rev() {
+                self.current = (self.current << 1) | (value.checked_shr(i).unwrap_or(0) & 1) as u8;
+                self.bits += 1;
+                if self.bits == self.capacity {
+                    self.out.push(self.current);
+                    self.capacity = if self.current == 0xFF { 7 } else { 8 };
+                    self.current = 0;
+                    self.bits = 0;
+                }
+            }
+        }
+
+        fn finish(mut self) -> Vec<u8> {
+            if self.bits > 0 {
+                self.write_bits(0, self.capacity - self.bits);
+            }
+            if self.out.last() == Some(&0xFF) {
+                self.out.push(0);
+            }
+            self.out
+        }
+    }
+
+    /// A straightforward JPEG-LS encoder mirroring the decoder,
+    /// to produce test code streams.
+    struct ScanEncoder {
+        writer: BitWriter,
+        params: CodingParameters,
+        contexts: Vec<Context>,
+        run_contexts: [RunContext; 2],
+        width: usize,
+    }
+
+    impl ScanEncoder {
+        fn encode_value(&mut self, k: u32, mapped: i32, limit: u32) {
+            let qbpp = self.params.qbpp;
+            let max = limit - qbpp - 1;
+            let high = mapped >> k;
+            if (high as u32) < max {
+                self.writer.write_bits(1, high as u32 + 1);
+                self.writer.write_bits(mapped, k);
+            } else {
+                self.writer.write_bits(1, max + 1);
+                self.writer.write_bits(mapped - 1, qbpp);
+            }
+        }
+
+        /// Quantize and reduce a prediction error
+        fn quantize_error(&self, error: i32) -> i32 {
+            let near = self.params.near;
+            let range = self.params.range;
+            let mut error = if error > 0 {
+                (error + near) / (2 * near + 1)
+            } else {
+                -(near - error) / (2 * near + 1)
+            };
+            if error < 0 {
+                error += range;
+            }
+            if error >= (range + 1) / 2 {
+                error -= range;
+            }
+            error
+        }
+
+        fn encode_regular(&mut self, context: i32, prediction: i32, sample: i32) -> i32 {
+            let sign = sign(context);
+            let index = (context * sign) as usize;
+            let k = self.contexts[index].golomb_k();
+            let prediction =
+                (prediction + sign * self.contexts[index].c).clamp(0, self.params.maxval);
+            let error = self.quantize_error(sign * (sample - prediction));
+            let ctx = &self.contexts[index];
+            let mapped_error = if k == 0 && self.params.near == 0 && 2 * ctx.b + ctx.n <= 0 {
+                -error - 1
+            } else {
+                error
+            };
+            let mapped = if mapped_error >= 0 {
+                2 * mapped_error
+            } else {
+                -2 * mapped_error - 1
+            };
+            self.encode_value(k, mapped, self.params.limit);
+            self.contexts[index].update(error, self.params.near, self.params.reset);
+            self.params.reconstruct(prediction, sign * error)
+        }
+
+        fn encode_run_length(
+            &mut self,
+            mut length: usize,
+            end_of_line: bool,
+            run_index: &mut usize,
+        ) {
+            while length >= 1 << J[*run_index] {
+                self.writer.write_bits(1, 1);
+                length -= 1 << J[*run_index];
+                *run_index = (*run_index + 1).min(31);
+            }
+            if end_of_line {
+                if length > 0 {
+                    self.writer.write_bits(1, 1);
+                }
+            } else {
+                self.writer.write_bits(length as i32, J[*run_index] + 1);
+            }
+        }
+
+        fn encode_run_interruption(&mut self, ri_type: usize, error: i32, run_index: usize) {
+            let context = &self.run_contexts[ri_type];
+            let k = context.golomb_k();
+            let map = (k == 0 && error > 0 && 2 * context.nn < context.n)
+                || (error < 0 && 2 * context.nn >= context.n)
+                || (error < 0 && k != 0);
+            let mapped = 2 * error.abs() - context.ri_type - map as i32;
+            self.encode_value(k, mapped, self.params.limit - J[run_index] - 1);
+            self.run_contexts[ri_type].update(error, mapped, self.params.reset);
+        }
+
+        /// Encode one line of each of the given components
+        fn encode_line(
+            &mut self,
+            samples: &[&[i32]],
+            previous: &[Vec<i32>],
+            current: &mut [Vec<i32>],
+            run_index: &mut usize,
+        ) {
+            let count = samples.len();
+            let near = self.params.near;
+            let mut x = 0;
+            while x < self.width {
+                let contexts: Vec<i32> = (0..count)
+                    .map(|c| {
+                        let (ra, rb, rc, rd) = (
+                            current[c][x],
+                            previous[c][x + 1],
+                            previous[c][x],
+                            previous[c][x + 2],
+                        );
+                        self.params.context(ra, rb, rc, rd)
+                    })
+                    .collect();
+                if contexts.iter().any(|&q| q != 0) {
+                    for c in 0..count {
+                        let (ra, rb, rc) = (current[c][x], previous[c][x + 1], previous[c][x]);
+                        current[c][x + 1] =
+                            self.encode_regular(contexts[c], predict(ra, rb, rc), samples[c][x]);
+                    }
+                    x += 1;
+                    continue;
+                }
+
+                let mut run = 0;
+                while x + run < self.width
+                    && (0..count).all(|c| (samples[c][x + run] - current[c][x]).abs() <= near)
+                {
+                    run += 1;
+                }
+                self.encode_run_length(run, x + run == self.width, run_index);
+                for current in current.iter_mut() {
+                    let ra = current[x];
+                    current[x + 1..=x + run].fill(ra);
+                }
+                x += run;
+                if x == self.width {
+                    break;
+                }
+                for c in 0..count {
+                    let (ra, rb, sample) = (current[c][x], previous[c][x + 1], samples[c][x]);
+                    current[c][x + 1] = if count == 1 && (ra - rb).abs() <= near {
+                        let error = self.quantize_error(sample - ra);
+                        self.encode_run_interruption(1, error, *run_index);
+                        self.params.reconstruct(ra, error)
+                    } else {
+                        let sign = sign(rb - ra);
+                        let error = self.quantize_error((sample - rb) * sign);
+                        self.encode_run_interruption(0, error, *run_index);
+                        self.params.reconstruct(rb, error * sign)
+                    };
+                }
+                *run_index = run_index.saturating_sub(1);
+                x += 1;
+            }
+        }
+    }
+
+    /// Encode an image with samples in standard planar configuration
+    /// into a JPEG-LS code stream.
+    pub(crate) fn encode_jpegls(
+        samples: &[u16],
+        width: u16,
+        height: u16,
+        components: u8,
+        precision: u8,
+        near: u8,
+        interleave: u8,
+    ) -> Vec<u8> {
+        let count = components as usize;
+        let (w, h) = (width as usize, height as usize);
+        let mut out = vec![0xFF, SOI, 0xFF, SOF55];
+        out.extend_from_slice(&(8 + 3 * u16::from(components)).to_be_bytes());
+        out.push(precision);
+        out.extend_from_slice(&height.to_be_bytes());
+        out.extend_from_slice(&width.to_be_bytes());
+        out.push(components);
+        for id in 1..=components {
+            out.extend_from_slice(&[id, 0x11, 0]);
+        }
+
+        let scans: Vec<Vec<usize>> = if interleave == 0 {
+            (0..count).map(|c| vec![c]).collect()
+        } else {
+            vec![(0..count).collect()]
+        };
+        for scan in scans {
+            out.extend_from_slice(&[0xFF, SOS]);
+            out.extend_from_slice(&(6 + 2 * scan.len() as u16).to_be_bytes());
+            out.push(scan.len() as u8);
+            for &c in &scan {
+                out.extend_from_slice(&[c as u8 + 1, 0]);
+            }
+            out.extend_from_slice(&[near, interleave, 0]);
+
+            let params =
+                CodingParameters::new(precision, &PresetParameters::default(), near.into())
+                    .unwrap();
+            let mut encoder = ScanEncoder {
+                writer: BitWriter {
+                    out: Vec::new(),
+                    current: 0,
+                    bits: 0,
+                    capacity: 8,
+                },
+                contexts: vec![Context::new(params.range); 365],
+                run_contexts: [
+                    RunContext::new(params.range, 0),
+                    RunContext::new(params.range, 1),
+                ],
+                params,
+                width: w,
+            };
+            let n = scan.len();
+            let mut previous = vec![vec![0; w + 2]; n];
+            let mut current = vec![vec![0; w + 2]; n];
+            let mut run_index = vec![0; n];
+            for y in 0..h {
+                let lines: Vec<Vec<i32>> = scan
+                    .iter()
+                    .map(|&c| {
+                        (0..w)
+                            .map(|x| i32::from(samples[(y * w + x) * count + c]))
+                            .collect()
+                    })
+                    .collect();
+                for (previous, current) in previous.iter_mut().zip(&mut current) {
+                    previous[w + 1] = previous[w];
+                    current[0] = previous[1];
+                }
+                if interleave == 2 {
+                    let lines: Vec<&[i32]> = lines.iter().map(|l| &l[..]).collect();
+                    encoder.encode_line(&lines, &previous, &mut current, &mut run_index[0]);
+                } else {
+                    for c in 0..n {
+                        encoder.encode_line(
+                            &[&lines[c]],
+                            &previous[c..=c],
+                            &mut current[c..=c],
+                            &mut run_index[c],
+                        );
+                    }
+                }
+                std::mem::swap(&mut previous, &mut current);
+            }
+            out.extend(encoder.writer.finish());
+        }
+        out.extend_from_slice(&[0xFF, EOI]);
+        out
+    }
+
+    /// A test image with smooth areas, flat areas, edges, and noise
+    fn test_image(width: u16, height: u16, components: u8, maxval: u16) -> Vec<u16> {
+        let mut seed = 0x2545_F491_u32;
+        let mut samples = Vec::new();
+        for y in 0..u32::from(height) {
+            for x in 0..u32::from(width) {
+                for c in 0..u32::from(components) {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    let value = if x < u32::from(width) / 4 {
+                        // flat area
+                        c * 20
+                    } else if y % 8 < 4 {
+                        // gradient
+                        (x * 7 + y * 3 + c * 11) * u32::from(maxval) / 1024
+                    } else {
+                        // noise
+                        (seed >> 8) % (u32::from(maxval) + 1)
+                    };
+                    samples.push(value.min(u32::from(maxval)) as u16);
+                }
+            }
+        }
+        samples
+    }
+
+    fn decode_samples(data: &[u8]) -> (JpegLsImageInfo, Vec<u16>) {
+        let mut out = Vec::new();
+        let info = decode_jpegls_frame(data, &mut out).unwrap();
+        let samples = if info.bits_allocated == 8 {
+            out.into_iter().map(u16::from).collect()
+        } else {
+            out.chunks(2)
+                .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+                .collect()
+        };
+        (info, samples)
+    }
+
+    #[test]
+    fn decode_lossless_16bit_monochrome() {
+        let image = test_image(37, 21, 1, 0xFFFF);
+        let data = encode_jpegls(&image, 37, 21, 1, 16, 0, 0);
+        let (info, samples) = decode_samples(&data);
+        assert_eq!(
+            info,
+            JpegLsImageInfo {
+                width: 37,
+                height: 21,
+                samples_per_pixel: 1,
+                bits_stored: 16,
+                bits_allocated: 16,
+                near_lossless: 0,
+            }
+        );
+        assert_eq!(samples, image);
+
+        // 12 bits stored
+        let image = test_image(16, 16, 1, 4095);
+        let data = encode_jpegls(&image, 16, 16, 1, 12, 0, 0);
+        let (info, samples) = decode_samples(&data);
+        assert_eq!(info.bits_stored, 12);
+        assert_eq!(samples, image);
+    }
+
+    #[test]
+    fn decode_lossless_8bit_rgb() {
+        let image = test_image(29, 17, 3, 255);
+        for interleave in 0..=2 {
+            let data = encode_jpegls(&image, 29, 17, 3, 8, 0, interleave);
+            let (info, samples) = decode_samples(&data);
+            assert_eq!(info.samples_per_pixel, 3);
+            assert_eq!(info.bits_allocated, 8);
+            assert_eq!(samples, image, "interleave mode {}", interleave);
+        }
+    }
+
+    #[test]
+    fn decode_near_lossless() {
+        for (components, precision, near) in [(1, 8, 2), (3, 8, 3), (1, 12, 5), (1, 6, 2)] {
+            let maxval = (1 << precision) - 1;
+            let image = test_image(23, 19, components, maxval);
+            let data = encode_jpegls(&image, 23, 19, components, precision, near, 2);
+            let (info, samples) = decode_samples(&data);
+            assert_eq!(info.near_lossless, u16::from(near));
+            assert_eq!(samples.len(), image.len());
+            for (a, b) in samples.iter().zip(&image) {
+                assert!(
+                    (i32::from(*a) - i32::from(*b)).abs() <= i32::from(near),
+                    "{} and {} differ by more than {}",
+                    a,
+                    b,
+                    near
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn default_thresholds() {
+        // values as per T.87 C.2.4.1.1
+        let thresholds = |precision, maxval, near| {
+            let preset = PresetParameters {
+                maxval,
+                ..PresetParameters::default()
+            };
+            let params = CodingParameters::new(precision, &preset, near).unwrap();
+            (params.t1, params.t2, params.t3)
+        };
+        assert_eq!(thresholds(8, 0, 0), (3, 7, 21));
+        assert_eq!(thresholds(8, 0, 3), (12, 22, 42));
+        assert_eq!(thresholds(12, 0, 0), (18, 67, 276));
+        assert_eq!(thresholds(16, 0, 0), (18, 67, 276));
+        // MAXVAL below 128
+        assert_eq!(thresholds(8, 63, 0), (2, 3, 5));
+        assert_eq!(thresholds(8, 63, 2), (6, 11, 19));
+        assert_eq!(thresholds(4, 0, 0), (2, 3, 4));
+    }
+
+    #[test]
+    fn reject_invalid_code_streams() {
+        let image = test_image(16, 16, 1, 255);
+        let data = encode_jpegls(&image, 16, 16, 1, 8, 0, 0);
+        let mut out = Vec::new();
+
+        // truncated scan data
+        assert!(decode_jpegls_frame(&data[..data.len() / 2], &mut out).is_err());
+        // missing SOI
+        assert!(decode_jpegls_frame(&data[2..], &mut out).is_err());
+        // baseline JPEG frame header
+        let mut baseline = data.clone();
+        baseline[3] = 0xC0;
+        assert!(decode_jpegls_frame(&baseline, &mut out).is_err());
+    }
+}
@@ -8,6 +8,9 @@
 //!   and encoding (baseline).
 //!   Requires the `jpeg` feature,
 //!   enabled by default.
+//! - [`jpeg_lossless`](jpeg_lossless) provides a native decoder
+//!   for JPEG Lossless (Process 14) with all predictors.
+//!   Requires the `jpeg` feature.
 //! - [`jpeg2k`](jpeg2k) contains JPEG 2000 support,
 //!   which is currently available through [OpenJPEG].
 //!   The `openjp2` feature provides native JPEG 2000 decoding
@@ -17,6 +20,10 @@
 //!   to statically link to the OpenJPEG reference implementation.
 //!   `openjp2` is enabled by the feature `native`.
 //!   To build on Windows, enable `native_windows` instead.
+//! - [`jpegls`](jpegls) provides native JPEG-LS decoding
+//!   (lossless and near-lossless).
+//!   Requires the `jpegls` feature,
+//!   enabled by `native`.
 //! - [`rle_lossless`](rle_lossless) provides native RLE lossless decoding.
 //!   Requires the `rle` feature,
 //!   enabled by default.
@@ -27,6 +34,10 @@
 pub mod jpeg;
 #[cfg(any(feature = "openjp2", feature = "openjpeg-sys"))]
 pub mod jpeg2k;
+#[cfg(feature = "jpeg")]
+pub mod jpeg_lossless;
+#[cfg(feature = "jpegls")]
+pub mod jpegls;
 #[cfg(feature = "rle")]
 pub mod rle_lossless;
 
@@ -37,12 +48,22 @@ pub mod uncompressed;
 #[cfg(not(feature = "jpeg"))]
 pub mod jpeg {}
 
+/// **Note:** This module is a stub.
+/// Enable the `jpeg` feature to use this module.
+#[cfg(not(feature = "jpeg"))]
+pub mod jpeg_lossless {}
+
 /// **Note:** This module is a stub.
 /// Enable either `openjp2` or `openjpeg-sys` to use this module.
 #[cfg(not(any(feature = "openjp2", feature = "openjpeg-sys")))]
 pub mod jpeg2k {}
 
+/// **Note:** This module is a stub.
+/// Enable the `jpegls` feature to use this module.
+#[cfg(not(feature = "jpegls"))]
+pub mod jpegls {}
+
 /// **Note:** This module is a stub.
 /// Enable the `rle` feature to use this module.
 #[cfg(not(feature = "rle"))]
-pub mod rle {}
+pub mod rle_lossless {}
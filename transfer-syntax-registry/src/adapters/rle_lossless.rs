@@ -39,82 +39,16 @@ impl PixelDataReader for RleLosslessAdapter {
                 name: "BitsAllocated",
             })?;
 
-        if bits_allocated != 8 && bits_allocated != 16 {
-            whatever!("BitsAllocated other than 8 or 16 is not supported");
-        }
         // For RLE the number of fragments = number of frames
         // therefore, we can fetch the fragments one by one
-        let nr_frames =
-            src.number_of_fragments()
-                .whatever_context("Invalid pixel data, no fragments found")? as usize;
-        let bytes_per_sample = (bits_allocated / 8) as usize;
-        let samples_per_pixel = samples_per_pixel as usize;
-        // `stride` is the total number of bytes for each sample plane
-        let stride = bytes_per_sample * cols as usize * rows as usize;
-        let frame_size = stride * samples_per_pixel;
-        // extend `dst` to make room for decoded pixel data
-        let base_offset = dst.len();
-        dst.resize(base_offset + frame_size * nr_frames, 0);
-
-        // RLE encoded data is ordered like this (for 16-bit, 3 sample):
-        //  Segment: 0     | 1     | 2     | 3     | 4     | 5
-        //           R MSB | R LSB | G MSB | G LSB | B MSB | B LSB
-        //  A segment contains only the MSB or LSB parts of all the sample pixels
-
-        // As currently required,
-        // we need to rearrange the pixel data to standard planar configuration.
-        // (and use little endian byte ordering):
-        //    Pixel 1                             | ... Pixel N
-        //    Red         Green       Blue        | ...
-        //    LSB R MSB R LSB G MSB G LSB B MSB B | ...
-
+        let nr_frames = src
+            .number_of_fragments()
+            .whatever_context("Invalid pixel data, no fragments found")?;
         for i in 0..nr_frames {
             let fragment = &src
-                .fragment(i)
+                .fragment(i as usize)
                 .whatever_context("No pixel data found for frame")?;
-            let mut offsets = read_rle_header(fragment);
-            offsets.push(fragment.len() as u32);
-
-            for sample_number in 0..samples_per_pixel {
-                for byte_offset in (0..bytes_per_sample).rev() {
-                    // ii is 1, 0, 3, 2, 5, 4 for the example above
-                    // This is where the segment order correction occurs
-                    let ii = sample_number * bytes_per_sample + byte_offset;
-                    let segment = &fragment[offsets[ii] as usize..offsets[ii + 1] as usize];
-                    let buff = io::Cursor::new(segment);
-                    let (_, decoder) = PackBitsReader::new(buff, segment.len())
-                        .whatever_context("Failed to read RLE segments")?;
-                    let mut decoded_segment = Vec::with_capacity(rows as usize * cols as usize);
-                    decoder
-                        .take(rows as u64 * cols as u64)
-                        .read_to_end(&mut decoded_segment)
-                        .unwrap();
-
-                    // Interleave pixels as described in the example above.
-                    // in 16-bit, this is:
-                    // MSB R channel: 1,  7, 13, ...
-                    // LSB R channel: 0,  6, 12, ...
-                    // MSB G channel: 3,  9, 15, ...
-                    // LSB G channel: 2,  8, 14, ...
-                    // MSB G channel: 5, 11, 17, ...
-                    // LSB G channel: 4, 10, 16, ...
-                    let frame_start = i * frame_size;
-                    let start = frame_start
-                        + if samples_per_pixel == 3 {
-                            sample_number * bytes_per_sample + byte_offset
-                        } else {
-                            sample_number * bytes_per_sample + samples_per_pixel - byte_offset
-                        };
-
-                    let end = (i + 1) * frame_size;
-                    for (decoded_index, dst_index) in (start..end)
-                        .step_by(bytes_per_sample * samples_per_pixel)
-                        .enumerate()
-                    {
-                        dst[base_offset + dst_index] = decoded_segment[decoded_index];
-                    }
-                }
-            }
+            decode_rle_frame(fragment, rows, cols, samples_per_pixel, bits_allocated, dst)?;
         }
         Ok(())
     }
@@ -145,9 +79,6 @@ impl PixelDataReader for RleLosslessAdapter {
                 name: "BitsAllocated",
             })?;
 
-        if bits_allocated != 8 && bits_allocated != 16 {
-            whatever!("BitsAllocated other than 8 or 16 is not supported");
-        }
         // For RLE the number of fragments = number of frames
         // therefore, we can fetch the fragments one by one
         let nr_frames =
@@ -158,77 +89,134 @@ impl PixelDataReader for RleLosslessAdapter {
             decode_error::FrameRangeOutOfBoundsSnafu
         );
 
-        let bytes_per_sample = (bits_allocated / 8) as usize;
-        let samples_per_pixel = samples_per_pixel as usize;
-        // `stride` is the total number of bytes for each sample plane
-        let stride = bytes_per_sample * cols as usize * rows as usize;
-        let frame_size = stride * samples_per_pixel;
-        // extend `dst` to make room for decoded pixel data
-        let base_offset = dst.len();
-        dst.resize(base_offset + frame_size, 0);
-
-        // RLE encoded data is ordered like this (for 16-bit, 3 sample):
-        //  Segment: 0     | 1     | 2     | 3     | 4     | 5
-        //           R MSB | R LSB | G MSB | G LSB | B MSB | B LSB
-        //  A segment contains only the MSB or LSB parts of all the sample pixels
-
-        // As currently required,
-        // we need to rearrange the pixel data to standard planar configuration.
-        // (and use little endian byte ordering):
-        //    Pixel 1                             | ... Pixel N
-        //    Red         Green       Blue        | ...
-        //    LSB R MSB R LSB G MSB G LSB B MSB B | ...
-
         let fragment = &src
             .fragment(frame as usize)
             .whatever_context("No pixel data found for frame")?;
-        let mut offsets = read_rle_header(fragment);
-        offsets.push(fragment.len() as u32);
-
-        for sample_number in 0..samples_per_pixel {
-            for byte_offset in (0..bytes_per_sample).rev() {
-                // ii is 1, 0, 3, 2, 5, 4 for the example above
-                // This is where the segment order correction occurs
-                let ii = sample_number * bytes_per_sample + byte_offset;
-                let segment = &fragment[offsets[ii] as usize..offsets[ii + 1] as usize];
-                let buff = io::Cursor::new(segment);
-                let (_, decoder) = PackBitsReader::new(buff, segment.len())
-                    .map_err(|e| Box::new(e) as Box<_>)
-                    .whatever_context("Failed to read RLE segments")?;
-                let mut decoded_segment = Vec::with_capacity(rows as usize * cols as usize);
-                decoder
-                    .take(rows as u64 * cols as u64)
-                    .read_to_end(&mut decoded_segment)
-                    .unwrap();
-
-                // Interleave pixels as described in the example above.
-                let start = if samples_per_pixel == 3 {
-                    sample_number * bytes_per_sample + byte_offset
-                } else {
-                    sample_number * bytes_per_sample + samples_per_pixel - byte_offset
-                };
-
-                let end = frame_size;
-                for (decoded_index, dst_index) in (start..end)
-                    .step_by(bytes_per_sample * samples_per_pixel)
-                    .enumerate()
-                {
-                    dst[base_offset + dst_index] = decoded_segment[decoded_index];
-                }
-            }
+        decode_rle_frame(fragment, rows, cols, samples_per_pixel, bits_allocated, dst)
+    }
+}
+
+/// Decode a single frame of RLE Lossless pixel data
+/// from the bytes of its fragment,
+/// appending the native pixel data to `dst`.
+///
+/// The output samples are in standard planar configuration
+/// (all samples of a pixel next to each other)
+/// and in the native byte order of the target.
+///
+/// See <https://dicom.nema.org/medical/dicom/2023e/output/chtml/part05/chapter_G.html>
+pub fn decode_rle_frame(
+    fragment: &[u8],
+    rows: u16,
+    cols: u16,
+    samples_per_pixel: u16,
+    bits_allocated: u16,
+    dst: &mut Vec<u8>,
+) -> DecodeResult<()> {
+    if bits_allocated == 0 || !bits_allocated.is_multiple_of(8) || bits_allocated > 32 {
+        whatever!(
+            "BitsAllocated {} is not supported for RLE Lossless",
+            bits_allocated
+        );
+    }
+    let bytes_per_sample = (bits_allocated / 8) as usize;
+    let samples_per_pixel = samples_per_pixel as usize;
+    let pixels = rows as usize * cols as usize;
+    let nr_segments = bytes_per_sample * samples_per_pixel;
+
+    // RLE encoded data is ordered like this (for 16-bit, 3 sample):
+    //  Segment: 0     | 1     | 2     | 3     | 4     | 5
+    //           R MSB | R LSB | G MSB | G LSB | B MSB | B LSB
+    //  A segment contains only the MSB or LSB parts of all the sample pixels
+    let offsets = read_rle_header(fragment)?;
+    if offsets.len() != nr_segments {
+        whatever!(
+            "Expected {} RLE segments, found {}",
+            nr_segments,
+            offsets.len()
+        );
+    }
+
+    // The decoded samples are rearranged to standard planar configuration:
+    //    Pixel 1                             | ... Pixel N
+    //    Red         Green       Blue        | ...
+    //    LSB R MSB R LSB G MSB G LSB B MSB B | ...
+    // (on a little endian target)
+    let base_offset = dst.len();
+    dst.resize(base_offset + pixels * nr_segments, 0);
+    let out = &mut dst[base_offset..];
+
+    for (ii, &start) in offsets.iter().enumerate() {
+        let end = offsets
+            .get(ii + 1)
+            .map(|&end| end as usize)
+            .unwrap_or(fragment.len());
+        let segment = &fragment[start as usize..end];
+        let decoded_segment = decode_segment(segment, pixels)?;
+
+        let sample_number = ii / bytes_per_sample;
+        // segments go from the most to the least significant byte
+        let significance = ii % bytes_per_sample;
+        let byte_offset = if cfg!(target_endian = "little") {
+            bytes_per_sample - 1 - significance
+        } else {
+            significance
+        };
+        let start = sample_number * bytes_per_sample + byte_offset;
+        for (value, dst) in decoded_segment
+            .into_iter()
+            .zip(out[start..].iter_mut().step_by(nr_segments))
+        {
+            *dst = value;
         }
-        Ok(())
     }
+    Ok(())
+}
+
+/// Decode a PackBits encoded segment
+/// into exactly `len` bytes,
+/// ignoring any trailing padding.
+fn decode_segment(segment: &[u8], len: usize) -> DecodeResult<Vec<u8>> {
+    let (decoded_len, mut decoder) = PackBitsReader::new(io::Cursor::new(segment), segment.len())
+        .map_err(|e| Box::new(e) as Box<_>)
+        .whatever_context("Failed to read RLE segment")?;
+    if decoded_len < len {
+        whatever!(
+            "RLE segment is too short: expected {} bytes, decoded {}",
+            len,
+            decoded_len
+        );
+    }
+    let mut decoded = Vec::with_capacity(decoded_len);
+    decoder
+        .read_to_end(&mut decoded)
+        .map_err(|e| Box::new(e) as Box<_>)
+        .whatever_context("Failed to read RLE segment")?;
+    decoded.truncate(len);
+    Ok(decoded)
 }
 
 // TODO(#125) implement `encode`
 
-// Read the RLE header and return the offsets
-fn read_rle_header(fragment: &[u8]) -> Vec<u32> {
+/// Read the RLE header and return the offsets of each segment,
+/// checking that they lie within the fragment.
+fn read_rle_header(fragment: &[u8]) -> DecodeResult<Vec<u32>> {
+    if fragment.len() < 64 {
+        whatever!("RLE fragment is too short for the RLE header");
+    }
     let nr_segments = LittleEndian::read_u32(&fragment[0..4]);
+    if nr_segments == 0 || nr_segments > 15 {
+        whatever!("Invalid number of RLE segments {}", nr_segments);
+    }
     let mut offsets = vec![0; nr_segments as usize];
     LittleEndian::read_u32_into(&fragment[4..4 * (nr_segments + 1) as usize], &mut offsets);
-    offsets
+    if offsets[0] != 64
+        || offsets.windows(2).any(|w| w[0] > w[1])
+        || offsets[offsets.len() - 1] as usize > fragment.len()
+    {
+        whatever!("Invalid RLE segment offsets {:?}", offsets);
+    }
+    Ok(offsets)
 }
 
 /// PackBits Reader from the image-tiff crate
@@ -310,4 +298,77 @@ mod test {
         ];
         assert_eq!(decoded, expected);
     }
+
+    /// Build an RLE fragment from the given PackBits encoded segments
+    fn rle_fragment(segments: &[&[u8]]) -> Vec<u8> {
+        let mut header = vec![0u8; 64];
+        LittleEndian::write_u32(&mut header[0..4], segments.len() as u32);
+        let mut offset = 64;
+        for (i, segment) in segments.iter().enumerate() {
+            LittleEndian::write_u32(&mut header[4 + 4 * i..8 + 4 * i], offset);
+            offset += segment.len() as u32;
+        }
+        segments.iter().fold(header, |mut data, segment| {
+            data.extend_from_slice(segment);
+            data
+        })
+    }
+
+    #[test]
+    fn decode_16bit_monochrome_frame() {
+        // 2x3 image: 0x0102, 0x0102, 0x0102, 0x0304, 0x0506, 0x0708
+        let fragment = rle_fragment(&[
+            // MSB: run of 3 x 0x01, then literal 03 05 07
+            &[0xFE, 0x01, 0x02, 0x03, 0x05, 0x07],
+            // LSB: run of 3 x 0x02, then literal 04 06 08, plus padding
+            &[0xFE, 0x02, 0x02, 0x04, 0x06, 0x08, 0x00],
+        ]);
+        let mut decoded = Vec::new();
+        decode_rle_frame(&fragment, 2, 3, 1, 16, &mut decoded).unwrap();
+
+        let samples: Vec<u16> = decoded
+            .chunks(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(
+            samples,
+            vec![0x0102, 0x0102, 0x0102, 0x0304, 0x0506, 0x0708]
+        );
+    }
+
+    #[test]
+    fn decode_8bit_rgb_frame() {
+        // 2x2 image: red, green, blue, white
+        let fragment = rle_fragment(&[
+            &[0x03, 0xFF, 0x00, 0x00, 0xFF],
+            &[0x03, 0x00, 0xFF, 0x00, 0xFF],
+            &[0x01, 0x00, 0x00, 0xFF, 0xFF],
+        ]);
+        let mut decoded = vec![0xAB];
+        decode_rle_frame(&fragment, 2, 2, 3, 8, &mut decoded).unwrap();
+        assert_eq!(
+            decoded,
+            vec![
+                0xAB, // kept from before
+                0xFF, 0x00, 0x00, //
+                0x00, 0xFF, 0x00, //
+                0x00, 0x00, 0xFF, //
+                0xFF, 0xFF, 0xFF,
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_bad_segments() {
+        // wrong number of segments for 16-bit samples
+        let fragment = rle_fragment(&[&[0xFD, 0x01]]);
+        assert!(decode_rle_frame(&fragment, 2, 2, 1, 16, &mut Vec::new()).is_err());
+
+        // segment shorter than the number of pixels
+        let fragment = rle_fragment(&[&[0xFE, 0x01]]);
+        assert!(decode_rle_frame(&fragment, 2, 2, 1, 8, &mut Vec::new()).is_err());
+
+        // truncated header
+        assert!(decode_rle_frame(&[1, 0, 0, 0], 2, 2, 1, 8, &mut Vec::new()).is_err());
+    }
 }
@@ -27,13 +27,20 @@ use dicom_encoding::transfer_syntax::{AdapterFreeTransferSyntax as Ts, Codec};
 
 use dicom_encoding::transfer_syntax::{NeverAdapter, TransferSyntax};
 
-#[cfg(any(feature = "rle", feature = "openjp2", feature = "openjpeg-sys"))]
+#[cfg(any(
+    feature = "rle",
+    feature = "jpegls",
+    feature = "openjp2",
+    feature = "openjpeg-sys"
+))]
 use dicom_encoding::NeverPixelAdapter;
 
 #[cfg(feature = "jpeg")]
 use crate::adapters::jpeg::JpegAdapter;
 #[cfg(any(feature = "openjp2", feature = "openjpeg-sys"))]
 use crate::adapters::jpeg2k::Jpeg2000Adapter;
+#[cfg(feature = "jpegls")]
+use crate::adapters::jpegls::JpegLsAdapter;
 #[cfg(feature = "rle")]
 use crate::adapters::rle_lossless::RleLosslessAdapter;
 
@@ -260,19 +267,51 @@ pub const JPEG_2000_PART2_MULTI_COMPONENT_IMAGE_COMPRESSION: Ts = create_ts_stub
     "JPEG 2000 Part 2 Multi-component Image Compression",
 );
 
-// --- partially supported transfer syntaxes, pixel data encapsulation not supported ---
+// --- JPEG-LS support ---
+
+/// An alias for a transfer syntax specifier with [`JpegLsAdapter`]
+/// (supports decoding only).
+#[cfg(feature = "jpegls")]
+type JpegLsTs<R = JpegLsAdapter, W = NeverPixelAdapter> = TransferSyntax<NeverAdapter, R, W>;
 
+/// Create a transfer syntax with JPEG-LS encapsulated pixel data
+#[cfg(feature = "jpegls")]
+const fn create_ts_jpegls(uid: &'static str, name: &'static str) -> JpegLsTs {
+    TransferSyntax::new_ele(
+        uid,
+        name,
+        Codec::EncapsulatedPixelData(Some(JpegLsAdapter), None),
+    )
+}
+
+/// **Decoder implementation:** JPEG-LS Lossless Image Compression
+#[cfg(feature = "jpegls")]
+pub const JPEG_LS_LOSSLESS_IMAGE_COMPRESSION: JpegLsTs = create_ts_jpegls(
+    "1.2.840.10008.1.2.4.80",
+    "JPEG-LS Lossless Image Compression",
+);
 /// **Stub descriptor:** JPEG-LS Lossless Image Compression
+#[cfg(not(feature = "jpegls"))]
 pub const JPEG_LS_LOSSLESS_IMAGE_COMPRESSION: Ts = create_ts_stub(
     "1.2.840.10008.1.2.4.80",
     "JPEG-LS Lossless Image Compression",
 );
+
+/// **Decoder implementation:** JPEG-LS Lossy (Near-Lossless) Image Compression
+#[cfg(feature = "jpegls")]
+pub const JPEG_LS_LOSSY_IMAGE_COMPRESSION: JpegLsTs = create_ts_jpegls(
+    "1.2.840.10008.1.2.4.81",
+    "JPEG-LS Lossy (Near-Lossless) Image Compression",
+);
 /// **Stub descriptor:** JPEG-LS Lossy (Near-Lossless) Image Compression
+#[cfg(not(feature = "jpegls"))]
 pub const JPEG_LS_LOSSY_IMAGE_COMPRESSION: Ts = create_ts_stub(
     "1.2.840.10008.1.2.4.81",
     "JPEG-LS Lossy (Near-Lossless) Image Compression",
 );
 
+// --- partially supported transfer syntaxes, pixel data encapsulation not supported ---
+
 /// **Stub descriptor:** JPIP Referenced
 pub const JPIP_REFERENCED: Ts = create_ts_stub("1.2.840.10008.1.2.4.94", "JPIP Referenced");
 
@@ -56,13 +56,15 @@
 //! | JPEG Extended (Process 2 & 4) | Cargo feature `jpeg` | x |
 //! | JPEG Lossless, Non-Hierarchical (Process 14) | Cargo feature `jpeg` | x |
 //! | JPEG Lossless, Non-Hierarchical, First-Order Prediction (Process 14 [Selection Value 1]) | Cargo feature `jpeg` | x |
+//! | JPEG-LS Lossless              | Cargo feature `jpegls` | x |
+//! | JPEG-LS Lossy (Near-Lossless) | Cargo feature `jpegls` | x |
 //! | JPEG 2000 (Lossless Only)     | Cargo feature `openjp2` or `openjpeg-sys` | x |
 //! | JPEG 2000                     | Cargo feature `openjp2` or `openjpeg-sys` | x |
 //! | JPEG 2000 Part 2 Multi-component Image Compression (Lossless Only) | Cargo feature `openjp2` or `openjpeg-sys` | x |
 //! | JPEG 2000 Part 2 Multi-component Image Compression | Cargo feature `openjp2` or `openjpeg-sys` | x |
 //! | RLE Lossless                  | Cargo feature `rle` | x |
 //!
-//! Cargo features behind `native` (`jpeg`, `rle`)
+//! Cargo features behind `native` (`jpeg`, `jpegls`, `rle`)
 //! provide implementations that are written in pure Rust
 //! and are likely available in all supported platforms.
 //! However, a native implementation might not always be available,
@@ -98,7 +100,7 @@ use std::fmt;
 pub use dicom_encoding::TransferSyntax;
 pub mod entries;
 
-mod adapters;
+pub mod adapters;
 
 #[cfg(feature = "inventory-registry")]
 pub use dicom_encoding::inventory;
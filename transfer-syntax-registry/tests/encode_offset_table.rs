@@ -0,0 +1,101 @@
+//! Test for the basic offset table
+//! produced by the default implementation of `PixelDataWriter::encode`.
+
+mod adapters;
+
+use adapters::TestDataObject;
+use dicom_encoding::adapters::{EncodeOptions, EncodeResult, PixelDataObject, PixelDataWriter};
+
+/// A writer producing one fragment per frame,
+/// of a different length for each frame.
+#[derive(Debug)]
+struct VaryingLengthWriter;
+
+impl PixelDataWriter for VaryingLengthWriter {
+    fn encode_frame(
+        &self,
+        _src: &dyn PixelDataObject,
+        frame: u32,
+        _options: EncodeOptions,
+        dst: &mut Vec<u8>,
+    ) -> EncodeResult<Vec<dicom_core::ops::AttributeOp>> {
+        dst.resize(dst.len() + 10 * (frame as usize + 1), frame as u8);
+        Ok(vec![])
+    }
+}
+
+/// A writer producing one fragment per frame,
+/// each of an odd length.
+#[derive(Debug)]
+struct OddLengthWriter;
+
+impl PixelDataWriter for OddLengthWriter {
+    fn encode_frame(
+        &self,
+        _src: &dyn PixelDataObject,
+        frame: u32,
+        _options: EncodeOptions,
+        dst: &mut Vec<u8>,
+    ) -> EncodeResult<Vec<dicom_core::ops::AttributeOp>> {
+        dst.resize(dst.len() + 10 * (frame as usize + 1) + 1, frame as u8);
+        Ok(vec![])
+    }
+}
+
+fn test_object() -> TestDataObject {
+    TestDataObject {
+        ts_uid: "1.2.840.10008.1.2.1".to_string(),
+        rows: 4,
+        columns: 4,
+        bits_allocated: 8,
+        bits_stored: 8,
+        samples_per_pixel: 1,
+        photometric_interpretation: "MONOCHROME2",
+        number_of_frames: 3,
+        flat_pixel_data: Some(vec![0; 48]),
+        pixel_data_sequence: None,
+    }
+}
+
+#[test]
+fn encode_multi_frame_offset_table() {
+    let obj = test_object();
+
+    let mut fragments = Vec::new();
+    let mut offset_table = Vec::new();
+    VaryingLengthWriter
+        .encode(
+            &obj,
+            EncodeOptions::new(),
+            &mut fragments,
+            &mut offset_table,
+        )
+        .unwrap();
+
+    let lengths: Vec<_> = fragments.iter().map(|f| f.len()).collect();
+    assert_eq!(lengths, [10, 20, 30]);
+    // offsets to the first item of each frame,
+    // each item comprising an 8-byte header and the fragment
+    assert_eq!(offset_table, [0, 10 + 8, 10 + 8 + 20 + 8]);
+}
+
+#[test]
+fn encode_odd_length_frames_offset_table() {
+    let obj = test_object();
+
+    let mut fragments = Vec::new();
+    let mut offset_table = Vec::new();
+    OddLengthWriter
+        .encode(
+            &obj,
+            EncodeOptions::new(),
+            &mut fragments,
+            &mut offset_table,
+        )
+        .unwrap();
+
+    let lengths: Vec<_> = fragments.iter().map(|f| f.len()).collect();
+    assert_eq!(lengths, [11, 21, 31]);
+    // each fragment is padded to an even length in its item
+    assert_eq!(offset_table, [0, 12 + 8, 12 + 8 + 22 + 8]);
+}
@@ -0,0 +1,203 @@
+#![warn(missing_docs)]
+//! DICOM XML module
+//!
+//! This library provides serialization of DICOM data
+//! to the Native DICOM Model in XML,
+//! as per the [DICOM standard part 19 section A.1][1].
+//!
+//! [1]: https://dicom.nema.org/medical/dicom/current/output/chtml/part19/chapter_A.html#sect_A.1
+//!
+//! The document is written as the data set is traversed,
+//! so it can be streamed into any [`Write`] implementation
+//! with [`to_writer`],
+//! or collected into a string with [`to_string`].
+//!
+//! # Example
+//!
+//! ```
+//! # use dicom_core::VR;
+//! # use dicom_object::mem::{InMemDicomObject, InMemElement};
+//! # use dicom_dictionary_std::tags;
+//! let obj = InMemDicomObject::from_element_iter([
+//!     InMemElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+//!     InMemElement::new(tags::INSTANCE_NUMBER, VR::IS, "5"),
+//! ]);
+//!
+//! let xml = dicom_xml::to_string(&obj)?;
+//!
+//! assert_eq!(
+//!     xml,
+//!     concat!(
+//!         r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+//!         r#"<NativeDicomModel xmlns="http://dicom.nema.org/PS3.19/models/NativeDICOM" xml:space="preserve">"#,
+//!         r#"<DicomAttribute tag="00100010" vr="PN" keyword="PatientName">"#,
+//!         r#"<PersonName number="1"><Alphabetic>"#,
+//!         r#"<FamilyName>Doe</FamilyName><GivenName>John</GivenName>"#,
+//!         r#"</Alphabetic></PersonName>"#,
+//!         r#"</DicomAttribute>"#,
+//!         r#"<DicomAttribute tag="00200013" vr="IS" keyword="InstanceNumber">"#,
+//!         r#"<Value number="1">5</Value>"#,
+//!         r#"</DicomAttribute>"#,
+//!         r#"</NativeDicomModel>"#,
+//!     ),
+//! );
+//! # Ok::<(), dicom_xml::Error>(())
+//! ```
+
+use std::io::Write;
+
+use dicom_core::dictionary::DataDictionary;
+use dicom_core::Tag;
+use dicom_object::InMemDicomObject;
+use snafu::Snafu;
+
+mod writer;
+
+use crate::writer::XmlWriter;
+
+/// An error which may occur when writing DICOM data to XML.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum Error {
+    /// Could not write the XML document
+    #[snafu(display("Could not write the XML document"))]
+    WriteXml {
+        /// the underlying I/O error
+        source: std::io::Error,
+    },
+    /// Encapsulated pixel data can only be written as a bulk data reference
+    #[snafu(display(
+        "Encapsulated pixel data in {} requires bulk data references to be enabled",
+        tag
+    ))]
+    EncapsulatedPixelData {
+        /// the tag of the pixel data attribute
+        tag: Tag,
+    },
+}
+
+/// Type alias for a result from this crate.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Options for serializing DICOM data to XML.
+///
+/// The default options produce a compact document
+/// with every value written in place.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{PrimitiveValue, VR};
+/// # use dicom_object::mem::{InMemDicomObject, InMemElement};
+/// # use dicom_dictionary_std::tags;
+/// use dicom_xml::SerializeOptions;
+///
+/// let obj = InMemDicomObject::from_element_iter([
+///     InMemElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(vec![0_u8; 4096])),
+/// ]);
+///
+/// let options = SerializeOptions::new()
+///     .indent(true)
+///     .bulk_data("https://example.com/bulk/1.2.3", 1024);
+/// let mut out = Vec::new();
+/// dicom_xml::to_writer_with_options(&mut out, &obj, &options)?;
+///
+/// assert!(String::from_utf8(out)
+///     .unwrap()
+///     .contains(r#"<BulkData uri="https://example.com/bulk/1.2.3/7FE00010"/>"#));
+/// # Ok::<(), dicom_xml::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SerializeOptions {
+    /// Whether to place each XML element in its own line,
+    /// indented by its depth in the document.
+    pub indent: bool,
+    /// The base URI of bulk data references.
+    ///
+    /// When set, binary values larger than the threshold
+    /// and encapsulated pixel data
+    /// are written as `<BulkData>`
+    /// instead of `<InlineBinary>`.
+    /// The URI of each value is the base URI
+    /// followed by the path to the attribute,
+    /// made of the tags of the enclosing sequences,
+    /// the index of the item in each sequence (starting at 0),
+    /// and the attribute's own tag,
+    /// separated by slashes
+    /// (e.g. `{base}/52009229/0/00281052`).
+    pub bulk_data_base_uri: Option<String>,
+    /// The maximum length in bytes of a binary value
+    /// to be written inline when bulk data references are enabled.
+    pub bulk_data_threshold: usize,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        SerializeOptions {
+            indent: false,
+            bulk_data_base_uri: None,
+            bulk_data_threshold: 1024,
+        }
+    }
+}
+
+impl SerializeOptions {
+    /// Create the default set of serialization options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether to indent the XML document.
+    pub fn indent(mut self, value: bool) -> Self {
+        self.indent = value;
+        self
+    }
+
+    /// Write binary values larger than `threshold` bytes
+    /// as references to bulk data under the given base URI.
+    pub fn bulk_data(mut self, base_uri: impl Into<String>, threshold: usize) -> Self {
+        self.bulk_data_base_uri = Some(base_uri.into());
+        self.bulk_data_threshold = threshold;
+        self
+    }
+}
+
+/// Serialize a DICOM data set as a Native DICOM Model XML document
+/// into the given writer.
+///
+/// The writer is not buffered,
+/// consider wrapping it in a [`BufWriter`](std::io::BufWriter).
+pub fn to_writer<W, D>(to: W, obj: &InMemDicomObject<D>) -> Result<()>
+where
+    W: Write,
+    D: DataDictionary + Clone,
+{
+    to_writer_with_options(to, obj, &SerializeOptions::default())
+}
+
+/// Serialize a DICOM data set as a Native DICOM Model XML document
+/// into the given writer,
+/// according to the given options.
+pub fn to_writer_with_options<W, D>(
+    to: W,
+    obj: &InMemDicomObject<D>,
+    options: &SerializeOptions,
+) -> Result<()>
+where
+    W: Write,
+    D: DataDictionary + Clone,
+{
+    XmlWriter::new(to, options).write_document(obj)
+}
+
+/// Serialize a DICOM data set as a Native DICOM Model XML document
+/// into a string.
+pub fn to_string<D>(obj: &InMemDicomObject<D>) -> Result<String>
+where
+    D: DataDictionary + Clone,
+{
+    let mut out = Vec::new();
+    to_writer(&mut out, obj)?;
+    Ok(String::from_utf8(out).expect("XML output should be valid UTF-8"))
+}
@@ -0,0 +1,410 @@
+//! Streaming writer of Native DICOM Model documents.
+
+use std::fmt;
+use std::io::Write;
+
+use base64::Engine;
+use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
+use dicom_core::{header::Header, DicomValue, PrimitiveValue, Tag, VR};
+use dicom_dictionary_std::StandardDataDictionary;
+use dicom_object::mem::InMemElement;
+use dicom_object::InMemDicomObject;
+use snafu::ResultExt;
+
+use crate::{EncapsulatedPixelDataSnafu, Result, SerializeOptions, WriteXmlSnafu};
+
+/// The names of the person name component groups, in order.
+const PN_GROUPS: [&str; 3] = ["Alphabetic", "Ideographic", "Phonetic"];
+
+/// The names of the person name components, in order.
+const PN_COMPONENTS: [&str; 5] = [
+    "FamilyName",
+    "GivenName",
+    "MiddleName",
+    "NamePrefix",
+    "NameSuffix",
+];
+
+/// Format a tag in the Native DICOM Model form `GGGGEEEE`.
+fn tag_key(tag: Tag) -> String {
+    format!("{:04X}{:04X}", tag.group(), tag.element())
+}
+
+/// Whether a primitive value has no content,
+/// in which case no value is written.
+fn is_empty_value(value: &PrimitiveValue) -> bool {
+    match value {
+        PrimitiveValue::Str(_) | PrimitiveValue::Strs(_) => value.to_str().is_empty(),
+        _ => value.multiplicity() == 0,
+    }
+}
+
+/// Text content or attribute value escaped for XML.
+///
+/// Characters which cannot appear in an XML document
+/// are replaced with U+FFFD.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.0;
+        while let Some(i) = rest.find(|c: char| {
+            matches!(c, '&' | '<' | '>' | '"' | '\u{FFFE}' | '\u{FFFF}')
+                || (c.is_control() && !matches!(c, '\t' | '\n' | '\r') && c < '\u{80}')
+        }) {
+            f.write_str(&rest[..i])?;
+            let c = rest[i..].chars().next().unwrap();
+            f.write_str(match c {
+                '&' => "&amp;",
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '"' => "&quot;",
+                _ => "\u{FFFD}",
+            })?;
+            rest = &rest[i + c.len_utf8()..];
+        }
+        f.write_str(rest)
+    }
+}
+
+/// Writes a Native DICOM Model document as the data set is traversed.
+pub(crate) struct XmlWriter<'o, W> {
+    to: W,
+    options: &'o SerializeOptions,
+    depth: usize,
+}
+
+impl<'o, W> XmlWriter<'o, W>
+where
+    W: Write,
+{
+    pub(crate) fn new(to: W, options: &'o SerializeOptions) -> Self {
+        XmlWriter {
+            to,
+            options,
+            depth: 0,
+        }
+    }
+
+    /// Write the full document for the given data set.
+    pub(crate) fn write_document<D>(mut self, obj: &InMemDicomObject<D>) -> Result<()>
+    where
+        D: DataDictionary + Clone,
+    {
+        write!(self.to, r#"<?xml version="1.0" encoding="UTF-8"?>"#).context(WriteXmlSnafu)?;
+        if self.options.indent {
+            writeln!(self.to).context(WriteXmlSnafu)?;
+        }
+        self.open(format_args!(
+            r#"NativeDicomModel xmlns="http://dicom.nema.org/PS3.19/models/NativeDICOM" xml:space="preserve""#
+        ))?;
+        self.write_dataset(obj, "")?;
+        self.close("NativeDicomModel")?;
+        self.to.flush().context(WriteXmlSnafu)
+    }
+
+    /// Start a new line at the current depth, if indenting.
+    fn indent(&mut self) -> Result<()> {
+        if self.options.indent {
+            write!(self.to, "{:1$}", "", self.depth * 2).context(WriteXmlSnafu)?;
+        }
+        Ok(())
+    }
+
+    /// End the current line, if indenting.
+    fn end_line(&mut self) -> Result<()> {
+        if self.options.indent {
+            writeln!(self.to).context(WriteXmlSnafu)?;
+        }
+        Ok(())
+    }
+
+    /// Write a start tag with the given name and attributes.
+    fn open(&mut self, start: fmt::Arguments) -> Result<()> {
+        self.indent()?;
+        write!(self.to, "<{}>", start).context(WriteXmlSnafu)?;
+        self.end_line()?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Write the end tag of the element with the given name.
+    fn close(&mut self, name: &str) -> Result<()> {
+        self.depth -= 1;
+        self.indent()?;
+        write!(self.to, "</{}>", name).context(WriteXmlSnafu)?;
+        self.end_line()
+    }
+
+    /// Write an element without children.
+    fn empty(&mut self, start: fmt::Arguments) -> Result<()> {
+        self.indent()?;
+        write!(self.to, "<{}/>", start).context(WriteXmlSnafu)?;
+        self.end_line()
+    }
+
+    /// Write an element with the given text content.
+    fn text(&mut self, name: &str, attributes: fmt::Arguments, text: &str) -> Result<()> {
+        self.indent()?;
+        write!(
+            self.to,
+            "<{}{}>{}</{}>",
+            name,
+            attributes,
+            Escaped(text),
+            name
+        )
+        .context(WriteXmlSnafu)?;
+        self.end_line()
+    }
+
+    /// Write the attributes of a data set,
+    /// located at the given path prefix in the root data set.
+    fn write_dataset<D>(&mut self, obj: &InMemDicomObject<D>, prefix: &str) -> Result<()>
+    where
+        D: DataDictionary + Clone,
+    {
+        for e in obj {
+            let tag = e.tag();
+            let private_creator = if tag.group() % 2 == 1 && tag.element() >= 0x1000 {
+                obj.get(Tag(tag.group(), tag.element() >> 8))
+                    .and_then(|creator| creator.to_str().ok())
+            } else {
+                None
+            };
+            self.write_attribute(e, private_creator.as_deref(), prefix)?;
+        }
+        Ok(())
+    }
+
+    /// Write a single DICOM attribute.
+    fn write_attribute<D>(
+        &mut self,
+        e: &InMemElement<D>,
+        private_creator: Option<&str>,
+        prefix: &str,
+    ) -> Result<()>
+    where
+        D: DataDictionary + Clone,
+    {
+        let tag = e.tag();
+        let vr = e.vr();
+        let key = tag_key(tag);
+        let keyword = if tag.group() % 2 == 1 {
+            None
+        } else {
+            StandardDataDictionary
+                .by_tag(tag)
+                .map(|entry| entry.alias())
+        };
+        let start = format!(
+            r#"DicomAttribute tag="{}" vr="{}"{}{}"#,
+            key,
+            vr.to_string(),
+            OptionalAttribute("keyword", keyword),
+            OptionalAttribute("privateCreator", private_creator),
+        );
+        let path = format!("{}{}", prefix, key);
+
+        match e.value() {
+            DicomValue::Sequence(seq) if seq.items().is_empty() => {
+                self.empty(format_args!("{}", start))
+            }
+            DicomValue::Sequence(seq) => {
+                self.open(format_args!("{}", start))?;
+                for (i, item) in seq.items().iter().enumerate() {
+                    self.open(format_args!(r#"Item number="{}""#, i + 1))?;
+                    self.write_dataset(item, &format!("{}/{}/", path, i))?;
+                    self.close("Item")?;
+                }
+                self.close("DicomAttribute")
+            }
+            DicomValue::PixelSequence(_) => {
+                let uri = self
+                    .bulk_data_uri(&path)
+                    .ok_or_else(|| EncapsulatedPixelDataSnafu { tag }.build())?;
+                self.open(format_args!("{}", start))?;
+                self.empty(format_args!(r#"BulkData uri="{}""#, Escaped(&uri)))?;
+                self.close("DicomAttribute")
+            }
+            DicomValue::Primitive(v) if is_empty_value(v) => self.empty(format_args!("{}", start)),
+            DicomValue::Primitive(v) => {
+                self.open(format_args!("{}", start))?;
+                self.write_value(vr, v, &path)?;
+                self.close("DicomAttribute")
+            }
+        }
+    }
+
+    /// Write the children of an attribute with a non-empty primitive value.
+    fn write_value(&mut self, vr: VR, value: &PrimitiveValue, path: &str) -> Result<()> {
+        match vr {
+            VR::PN => {
+                for (i, name) in value.to_multi_str().iter().enumerate() {
+                    self.write_person_name(i + 1, name.trim_end_matches([' ', '\0']))?;
+                }
+                Ok(())
+            }
+            VR::AT => match value {
+                PrimitiveValue::Tags(tags) => {
+                    for (i, tag) in tags.iter().enumerate() {
+                        self.text(
+                            "Value",
+                            format_args!(r#" number="{}""#, i + 1),
+                            &tag_key(*tag),
+                        )?;
+                    }
+                    Ok(())
+                }
+                _ => self.write_strings(value, false),
+            },
+            VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN => {
+                let bulk_data_uri = if value.calculate_byte_len() > self.options.bulk_data_threshold
+                {
+                    self.bulk_data_uri(path)
+                } else {
+                    None
+                };
+                match bulk_data_uri {
+                    Some(uri) => self.empty(format_args!(r#"BulkData uri="{}""#, Escaped(&uri))),
+                    None => {
+                        let data =
+                            base64::engine::general_purpose::STANDARD.encode(value.to_bytes());
+                        self.text("InlineBinary", format_args!(""), &data)
+                    }
+                }
+            }
+            VR::LT | VR::ST | VR::UT | VR::UR => self.write_strings(value, false),
+            _ => self.write_strings(value, true),
+        }
+    }
+
+    /// Write each value as a `Value` element,
+    /// removing padding at the end,
+    /// and also at the start if `trim_start` is set.
+    fn write_strings(&mut self, value: &PrimitiveValue, trim_start: bool) -> Result<()> {
+        for (i, v) in value.to_multi_str().iter().enumerate() {
+            let v = v.trim_end_matches([' ', '\0']);
+            let v = if trim_start { v.trim_start() } else { v };
+            if v.is_empty() {
+                self.empty(format_args!(r#"Value number="{}""#, i + 1))?;
+            } else {
+                self.text("Value", format_args!(r#" number="{}""#, i + 1), v)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a person name in its component groups.
+    fn write_person_name(&mut self, number: usize, name: &str) -> Result<()> {
+        if name.is_empty() {
+            return self.empty(format_args!(r#"PersonName number="{}""#, number));
+        }
+        self.open(format_args!(r#"PersonName number="{}""#, number))?;
+        for (group_name, group) in PN_GROUPS.iter().zip(name.split('=')) {
+            if group.is_empty() {
+                continue;
+            }
+            self.open(format_args!("{}", group_name))?;
+            for (component_name, component) in PN_COMPONENTS.iter().zip(group.split('^')) {
+                if !component.is_empty() {
+                    self.text(component_name, format_args!(""), component)?;
+                }
+            }
+            self.close(group_name)?;
+        }
+        self.close("PersonName")
+    }
+
+    /// The bulk data URI of the attribute at the given path,
+    /// if bulk data references are enabled.
+    fn bulk_data_uri(&self, path: &str) -> Option<String> {
+        self.options
+            .bulk_data_base_uri
+            .as_ref()
+            .map(|base| format!("{}/{}", base.trim_end_matches('/'), path))
+    }
+}
+
+/// An XML attribute which is only written if it has a value.
+struct OptionalAttribute<'a>(&'a str, Option<&'a str>);
+
+impl fmt::Display for OptionalAttribute<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(value) => write!(f, r#" {}="{}""#, self.0, Escaped(value)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_dictionary_std::tags;
+
+    #[test]
+    fn escape_text() {
+        assert_eq!(
+            Escaped(r#"A&B <"C"> 'D'"#).to_string(),
+            "A&amp;B &lt;&quot;C&quot;&gt; 'D'"
+        );
+        assert_eq!(
+            Escaped("tab\there\u{1}\u{0}").to_string(),
+            "tab\there\u{FFFD}\u{FFFD}"
+        );
+        assert_eq!(Escaped("山田^太郎").to_string(), "山田^太郎");
+    }
+
+    #[test]
+    fn write_private_attributes() {
+        let obj: InMemDicomObject = InMemDicomObject::from_element_iter([
+            InMemElement::new(Tag(0x0009, 0x0010), VR::LO, "ACME & Co "),
+            InMemElement::new(Tag(0x0009, 0x1002), VR::SH, "X"),
+            InMemElement::new(tags::PATIENT_ID, VR::LO, ""),
+        ]);
+        let mut out = Vec::new();
+        XmlWriter::new(&mut out, &SerializeOptions::default())
+            .write_dataset(&obj, "")
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"<DicomAttribute tag="00090010" vr="LO">"#,
+                r#"<Value number="1">ACME &amp; Co</Value>"#,
+                r#"</DicomAttribute>"#,
+                r#"<DicomAttribute tag="00091002" vr="SH" privateCreator="ACME &amp; Co">"#,
+                r#"<Value number="1">X</Value>"#,
+                r#"</DicomAttribute>"#,
+                r#"<DicomAttribute tag="00100020" vr="LO" keyword="PatientID"/>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn encapsulated_pixel_data_requires_bulk_data() {
+        use dicom_core::value::PixelFragmentSequence;
+
+        let obj: InMemDicomObject = InMemDicomObject::from_element_iter([InMemElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PixelFragmentSequence::new(vec![], vec![vec![0_u8; 4]]),
+        )]);
+        let err = crate::to_string(&obj).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::EncapsulatedPixelData { tag } if tag == tags::PIXEL_DATA
+        ));
+
+        let mut out = Vec::new();
+        crate::to_writer_with_options(
+            &mut out,
+            &obj,
+            &SerializeOptions::new().bulk_data("http://localhost/bulk/", 0),
+        )
+        .unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains(r#"<BulkData uri="http://localhost/bulk/7FE00010"/>"#));
+    }
+}
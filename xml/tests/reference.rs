@@ -0,0 +1,110 @@
+//! Serialization of a nested data set,
+//! checked against a reference Native DICOM Model document.
+use dicom_core::value::DataSetSequence;
+use dicom_core::{dicom_value, Length, PrimitiveValue, Tag, VR};
+use dicom_dictionary_std::tags;
+use dicom_object::mem::{InMemDicomObject, InMemElement};
+use dicom_xml::SerializeOptions;
+
+fn reference(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(path).unwrap()
+}
+
+/// Remove the whitespace around each line of the document,
+/// so that documents are compared regardless of indentation.
+fn normalize(xml: &str) -> String {
+    xml.lines().map(str::trim).collect()
+}
+
+fn referenced_image(instance_uid: &str, extra: InMemElement) -> InMemDicomObject {
+    InMemDicomObject::from_element_iter([
+        InMemElement::new(
+            tags::REFERENCED_SOP_CLASS_UID,
+            VR::UI,
+            "1.2.840.10008.5.1.4.1.1.2\0",
+        ),
+        InMemElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, instance_uid),
+        extra,
+    ])
+}
+
+fn dataset() -> InMemDicomObject {
+    InMemDicomObject::from_element_iter([
+        InMemElement::new(tags::SPECIFIC_CHARACTER_SET, VR::CS, "ISO_IR 192"),
+        InMemElement::new(tags::STUDY_DATE, VR::DA, "20130409"),
+        InMemElement::new(tags::STUDY_TIME, VR::TM, PrimitiveValue::Empty),
+        InMemElement::new(
+            tags::MODALITIES_IN_STUDY,
+            VR::CS,
+            dicom_value!(Strs, ["CT", "PET"]),
+        ),
+        InMemElement::new(tags::STUDY_DESCRIPTION, VR::LO, "Head & Neck <contrast> "),
+        InMemElement::new(
+            tags::REFERENCED_STUDY_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::new(vec![], Length(0)),
+        ),
+        InMemElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::new(
+                vec![
+                    referenced_image(
+                        "1.2.3.4.5.6.7.8.9\0",
+                        InMemElement::new(tags::RESCALE_INTERCEPT, VR::DS, "-1024 "),
+                    ),
+                    referenced_image(
+                        "1.2.3.4.5.6.7.8.10",
+                        InMemElement::new(
+                            tags::ICC_PROFILE,
+                            VR::OB,
+                            PrimitiveValue::from((0..16).collect::<Vec<u8>>()),
+                        ),
+                    ),
+                ],
+                Length::UNDEFINED,
+            ),
+        ),
+        InMemElement::new(
+            tags::PATIENT_NAME,
+            VR::PN,
+            "Yamada^Tarou=山田^太郎=やまだ^たろう",
+        ),
+        InMemElement::new(tags::PATIENT_ID, VR::LO, "12345"),
+        InMemElement::new(
+            tags::OTHER_PATIENT_NAMES,
+            VR::PN,
+            dicom_value!(Strs, ["Smith^Jane^^Dr.", "", "=山田^花子"]),
+        ),
+        InMemElement::new(Tag(0x0019, 0x0010), VR::LO, "ACME 1.1 "),
+        InMemElement::new(Tag(0x0019, 0x1001), VR::DS, dicom_value!(Strs, ["0.5", ""])),
+        InMemElement::new(tags::SLICE_THICKNESS, VR::DS, "2.5"),
+        InMemElement::new(tags::INSTANCE_NUMBER, VR::IS, "5 "),
+        InMemElement::new(
+            tags::DIMENSION_INDEX_POINTER,
+            VR::AT,
+            dicom_value!(Tags, [Tag(0x0020, 0x9056), Tag(0x0020, 0x9057)]),
+        ),
+        InMemElement::new(tags::ROWS, VR::US, PrimitiveValue::from(2_u16)),
+        InMemElement::new(tags::COLUMNS, VR::US, PrimitiveValue::from(2_u16)),
+        InMemElement::new(tags::PIXEL_DATA, VR::OW, dicom_value!(U16, [0, 1, 2, 3])),
+    ])
+}
+
+#[test]
+fn serialize_to_reference_document() {
+    let xml = dicom_xml::to_string(&dataset()).unwrap();
+    pretty_assertions::assert_eq!(xml, normalize(&reference("nested.xml")));
+}
+
+#[test]
+fn serialize_indented_with_bulk_data() {
+    let options = SerializeOptions::new()
+        .indent(true)
+        .bulk_data("https://example.com/instances/1.2.3/bulk/", 8);
+    let mut out = Vec::new();
+    dicom_xml::to_writer_with_options(&mut out, &dataset(), &options).unwrap();
+    let xml = String::from_utf8(out).unwrap();
+    pretty_assertions::assert_eq!(xml, reference("nested_bulk_data.xml"));
+}